@@ -192,6 +192,49 @@ export function FunctionDetail() {
             </CardContent>
           </Card>
 
+          {func.metadata && (func.metadata.owner || func.metadata.repo_url || func.metadata.docs_url || func.metadata.notes) && (
+            <Card>
+              <CardHeader>
+                <CardTitle>Ownership &amp; Notes</CardTitle>
+                <CardDescription>
+                  Operational context for this function
+                </CardDescription>
+              </CardHeader>
+              <CardContent className="space-y-4">
+                <div className="grid grid-cols-2 gap-4">
+                  {func.metadata.owner && (
+                    <div>
+                      <label className="text-sm font-medium text-muted-foreground">Owner</label>
+                      <p className="mt-1 text-sm">{func.metadata.owner}</p>
+                    </div>
+                  )}
+                  {func.metadata.repo_url && (
+                    <div>
+                      <label className="text-sm font-medium text-muted-foreground">Repository</label>
+                      <p className="mt-1 text-sm break-all">
+                        <a href={func.metadata.repo_url} target="_blank" rel="noopener noreferrer" className="text-blue-600 hover:underline">{func.metadata.repo_url}</a>
+                      </p>
+                    </div>
+                  )}
+                  {func.metadata.docs_url && (
+                    <div>
+                      <label className="text-sm font-medium text-muted-foreground">Docs</label>
+                      <p className="mt-1 text-sm break-all">
+                        <a href={func.metadata.docs_url} target="_blank" rel="noopener noreferrer" className="text-blue-600 hover:underline">{func.metadata.docs_url}</a>
+                      </p>
+                    </div>
+                  )}
+                </div>
+                {func.metadata.notes && (
+                  <div>
+                    <label className="text-sm font-medium text-muted-foreground">Notes</label>
+                    <div className="mt-1 text-sm whitespace-pre-wrap rounded border bg-gray-50 p-2">{func.metadata.notes}</div>
+                  </div>
+                )}
+              </CardContent>
+            </Card>
+          )}
+
           <Card>
             <CardHeader>
               <CardTitle>Warm Pool</CardTitle>
@@ -4,18 +4,45 @@ import { Plus, Trash2, Play, ChevronLeft, ChevronRight } from 'lucide-react';
 import { Button } from './ui/button';
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from './ui/card';
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from './ui/table';
+import { Input } from './ui/input';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from './ui/select';
 import { useFunctions, useDeleteFunction } from '../hooks/useFunctions';
 import { formatBytes, formatDate, getStateColor } from '../lib/utils';
 import { useToast } from './ui/use-toast';
 import { useState } from 'react';
+import { AVAILABLE_RUNTIMES, FunctionSortKey, FunctionState } from '../types/api';
+
+const ALL = 'all';
+const SORT_OPTIONS: { value: string; label: string; sortBy: FunctionSortKey; sortOrder: 'asc' | 'desc' }[] = [
+  { value: 'name-asc', label: 'Name (A-Z)', sortBy: 'name', sortOrder: 'asc' },
+  { value: 'name-desc', label: 'Name (Z-A)', sortBy: 'name', sortOrder: 'desc' },
+  { value: 'last_modified-desc', label: 'Recently modified', sortBy: 'last_modified', sortOrder: 'desc' },
+  { value: 'invocation_count-desc', label: 'Most invoked', sortBy: 'invocation_count', sortOrder: 'desc' },
+];
 
 export function FunctionList() {
-  const [currentPage, setCurrentPage] = useState(0);
+  // markers[i] is the marker that loads page i; markers are opaque and only valid for the
+  // filters and sort order they were issued under, so changing either starts over
+  const [markers, setMarkers] = useState<(string | undefined)[]>([undefined]);
+  const currentPage = markers.length - 1;
   const [pageSize] = useState(20);
-  
+  const [namePrefix, setNamePrefix] = useState('');
+  const [runtime, setRuntime] = useState(ALL);
+  const [state, setState] = useState(ALL);
+  const [sort, setSort] = useState(SORT_OPTIONS[0].value);
+  const sortOption = SORT_OPTIONS.find((o) => o.value === sort) ?? SORT_OPTIONS[0];
+
+  const resetPaging = () => setMarkers([undefined]);
+
   const { data: functionsData, isLoading, error } = useFunctions({
-    marker: currentPage > 0 ? (currentPage * pageSize).toString() : undefined,
+    marker: markers[currentPage],
     maxItems: pageSize,
+    namePrefix: namePrefix || undefined,
+    runtime: runtime === ALL ? undefined : runtime,
+    state: state === ALL ? undefined : (state as FunctionState),
+    sortBy: sortOption.sortBy,
+    sortOrder: sortOption.sortOrder,
+    includeWarmState: true,
   });
   const deleteFunction = useDeleteFunction();
   const { toast } = useToast();
@@ -64,13 +91,13 @@ export function FunctionList() {
 
   const handleNextPage = () => {
     if (hasNextPage) {
-      setCurrentPage(prev => prev + 1);
+      setMarkers(prev => [...prev, functionsData?.next_marker]);
     }
   };
 
   const handlePreviousPage = () => {
     if (hasPreviousPage) {
-      setCurrentPage(prev => prev - 1);
+      setMarkers(prev => prev.slice(0, -1));
     }
   };
 
@@ -96,6 +123,47 @@ export function FunctionList() {
         </Button>
       </div>
 
+      <div className="flex flex-wrap items-center gap-2">
+        <Input
+          className="w-64"
+          placeholder="Filter by name prefix"
+          value={namePrefix}
+          onChange={(e) => { setNamePrefix(e.target.value); resetPaging(); }}
+        />
+        <Select value={runtime} onValueChange={(value) => { setRuntime(value); resetPaging(); }}>
+          <SelectTrigger className="w-44">
+            <SelectValue placeholder="Runtime" />
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value={ALL}>All runtimes</SelectItem>
+            {AVAILABLE_RUNTIMES.map((r) => (
+              <SelectItem key={r} value={r}>{r}</SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+        <Select value={state} onValueChange={(value) => { setState(value); resetPaging(); }}>
+          <SelectTrigger className="w-40">
+            <SelectValue placeholder="State" />
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value={ALL}>All states</SelectItem>
+            {(['Pending', 'Active', 'Inactive', 'Failed', 'Archived'] as FunctionState[]).map((s) => (
+              <SelectItem key={s} value={s}>{s}</SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+        <Select value={sort} onValueChange={(value) => { setSort(value); resetPaging(); }}>
+          <SelectTrigger className="w-48">
+            <SelectValue placeholder="Sort by" />
+          </SelectTrigger>
+          <SelectContent>
+            {SORT_OPTIONS.map((o) => (
+              <SelectItem key={o.value} value={o.value}>{o.label}</SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      </div>
+
       {functions.length === 0 ? (
         <Card>
           <CardHeader>
@@ -156,6 +224,18 @@ export function FunctionList() {
                       <span className={`inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium ${getStateColor(func.state)}`}>
                         {func.state}
                       </span>
+                      {(() => {
+                        const warm = functionsData?.warm_state?.[func.function_name];
+                        if (!warm || warm.warm_idle + warm.active + warm.queued === 0) return null;
+                        return (
+                          <span
+                            className="ml-2 inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-green-100 text-green-800"
+                            title={`${warm.warm_idle} warm idle, ${warm.active} active, ${warm.queued} queued`}
+                          >
+                            live
+                          </span>
+                        );
+                      })()}
                     </TableCell>
                     <TableCell>{formatDate(func.last_modified)}</TableCell>
                     <TableCell>{formatBytes(func.code_size)}</TableCell>
@@ -1,12 +1,14 @@
-import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
+import { useQuery, useMutation, useQueryClient, keepPreviousData } from '@tanstack/react-query';
 import { api } from '../lib/api';
-import { CreateFunctionRequest } from '../types/api';
+import { CreateFunctionRequest, ListFunctionsParams } from '../types/api';
 
-export function useFunctions(params?: { marker?: string; maxItems?: number }) {
+export function useFunctions(params?: ListFunctionsParams) {
   return useQuery({
     queryKey: ['functions', params],
     queryFn: () => api.listFunctions(params),
     refetchInterval: 5000, // Refetch every 5 seconds
+    // Keep showing the current page while a new filter or page loads
+    placeholderData: keepPreviousData,
   });
 }
 
@@ -77,6 +79,15 @@ export function useLambdaServiceStats() {
   });
 }
 
+export function useDashboard() {
+  return useQuery({
+    queryKey: ['dashboard'],
+    queryFn: () => api.getDashboard(),
+    refetchInterval: 10000, // Refetch every 10 seconds
+    retry: 3,
+  });
+}
+
 
 export function useUpdateFunctionConfiguration() {
   const queryClient = useQueryClient();
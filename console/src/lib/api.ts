@@ -4,7 +4,23 @@ import {
   ListFunctionsResponse,
   ErrorShape,
   DockerStats,
-  LambdaServiceStats
+  LambdaServiceStats,
+  Dashboard,
+  ListFunctionsParams,
+  SearchResponse,
+  ChangeEntity,
+  ChangeFeedResponse,
+  DoctorReport,
+  FunctionMetadata,
+  ConfigHistoryResponse,
+  Webhook,
+  CreateWebhookRequest,
+  CreateWebhookResponse,
+  WebhookDelivery,
+  EventSourceMapping,
+  CreateEventSourceMappingRequest,
+  FunctionEnvironment,
+  PatchEnvironmentRequest
 } from '../types/api';
 
 // Default to User API on port 8000; override via VITE_API_URL in dev
@@ -42,7 +58,7 @@ async function handleResponse<T>(response: Response): Promise<T> {
 
 export const api = {
   // Function management
-  async listFunctions(params?: { marker?: string; maxItems?: number }): Promise<ListFunctionsResponse> {
+  async listFunctions(params?: ListFunctionsParams): Promise<ListFunctionsResponse> {
     const url = new URL(`${API_BASE_URL}/2015-03-31/functions`);
     if (params?.marker) {
       url.searchParams.set('Marker', params.marker);
@@ -50,6 +66,24 @@ export const api = {
     if (params?.maxItems) {
       url.searchParams.set('MaxItems', params.maxItems.toString());
     }
+    if (params?.namePrefix) {
+      url.searchParams.set('NamePrefix', params.namePrefix);
+    }
+    if (params?.runtime) {
+      url.searchParams.set('Runtime', params.runtime);
+    }
+    if (params?.state) {
+      url.searchParams.set('State', params.state);
+    }
+    if (params?.sortBy) {
+      url.searchParams.set('SortBy', params.sortBy);
+    }
+    if (params?.sortOrder) {
+      url.searchParams.set('SortOrder', params.sortOrder);
+    }
+    if (params?.includeWarmState) {
+      url.searchParams.set('IncludeWarmState', 'true');
+    }
     const response = await fetch(url.toString());
     return handleResponse(response);
   },
@@ -67,6 +101,39 @@ export const api = {
     if (!res.ok) throw new ApiError(`HTTP ${res.status}`, res.status);
   },
 
+  // Webhooks admin
+  async listWebhooks(): Promise<{ webhooks: Webhook[] }> {
+    const res = await fetch(`${API_BASE_URL}/admin/webhooks`);
+    return handleResponse(res);
+  },
+  async createWebhook(data: CreateWebhookRequest): Promise<CreateWebhookResponse> {
+    const res = await fetch(`${API_BASE_URL}/admin/webhooks`, { method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(data) });
+    return handleResponse(res);
+  },
+  async deleteWebhook(id: string): Promise<void> {
+    const res = await fetch(`${API_BASE_URL}/admin/webhooks/${encodeURIComponent(id)}`, { method: 'DELETE' });
+    if (!res.ok) throw new ApiError(`HTTP ${res.status}`, res.status);
+  },
+  async listWebhookDeliveries(id: string, limit = 50): Promise<{ deliveries: WebhookDelivery[] }> {
+    const res = await fetch(`${API_BASE_URL}/admin/webhooks/${encodeURIComponent(id)}/deliveries?limit=${limit}`);
+    return handleResponse(res);
+  },
+
+  // Event source mappings
+  async listEventSourceMappings(functionName?: string): Promise<{ event_source_mappings: EventSourceMapping[] }> {
+    const query = functionName ? `?FunctionName=${encodeURIComponent(functionName)}` : '';
+    const res = await fetch(`${API_BASE_URL}/2015-03-31/event-source-mappings${query}`);
+    return handleResponse(res);
+  },
+  async createEventSourceMapping(data: CreateEventSourceMappingRequest): Promise<EventSourceMapping> {
+    const res = await fetch(`${API_BASE_URL}/2015-03-31/event-source-mappings`, { method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(data) });
+    return handleResponse(res);
+  },
+  async deleteEventSourceMapping(uuid: string): Promise<void> {
+    const res = await fetch(`${API_BASE_URL}/2015-03-31/event-source-mappings/${encodeURIComponent(uuid)}`, { method: 'DELETE' });
+    if (!res.ok) throw new ApiError(`HTTP ${res.status}`, res.status);
+  },
+
   // API Gateway-style proxy invocation
   async invokeViaProxy(
     name: string,
@@ -127,7 +194,7 @@ export const api = {
   },
 
   async getFunction(name: string): Promise<Function> {
-    const response = await fetch(`${API_BASE_URL}/2015-03-31/functions/${encodeURIComponent(name)}`);
+    const response = await fetch(`${API_BASE_URL}/api/functions/${encodeURIComponent(name)}`);
     return handleResponse(response);
   },
 
@@ -159,6 +226,20 @@ export const api = {
     return handleResponse(response);
   },
 
+  async getEnvironment(name: string): Promise<FunctionEnvironment> {
+    const response = await fetch(`${API_BASE_URL}/api/functions/${encodeURIComponent(name)}/environment`);
+    return handleResponse(response);
+  },
+
+  async patchEnvironment(name: string, data: PatchEnvironmentRequest): Promise<FunctionEnvironment> {
+    const response = await fetch(`${API_BASE_URL}/api/functions/${encodeURIComponent(name)}/environment`, {
+      method: 'PATCH',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(data),
+    });
+    return handleResponse(response);
+  },
+
   async deleteFunction(name: string): Promise<void> {
     const response = await fetch(`${API_BASE_URL}/2015-03-31/functions/${encodeURIComponent(name)}`, {
       method: 'DELETE',
@@ -262,6 +343,73 @@ export const api = {
     const response = await fetch(`${API_BASE_URL}/lambda-service-stats`);
     return handleResponse(response);
   },
+
+  // Dashboard summary: counts, 24h invocations, warm pool, deployments and alerts in one call
+  async getDashboard(): Promise<Dashboard> {
+    const response = await fetch(`${API_BASE_URL}/dashboard`);
+    return handleResponse(response);
+  },
+
+  // Owner, links and notes; PUT replaces every field
+  async putFunctionMetadata(name: string, metadata: FunctionMetadata): Promise<FunctionMetadata> {
+    const response = await fetch(`${API_BASE_URL}/functions/${encodeURIComponent(name)}/metadata`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(metadata),
+    });
+    return handleResponse(response);
+  },
+
+  // Configuration revisions, newest first
+  async getConfigHistory(name: string): Promise<ConfigHistoryResponse> {
+    const response = await fetch(`${API_BASE_URL}/functions/${encodeURIComponent(name)}/history`);
+    return handleResponse(response);
+  },
+
+  // Restore a revision's configuration; recorded as a new revision
+  async revertConfiguration(name: string, revision: number): Promise<Function> {
+    const response = await fetch(
+      `${API_BASE_URL}/functions/${encodeURIComponent(name)}/history/${revision}/revert`,
+      { method: 'POST' }
+    );
+    return handleResponse(response);
+  },
+
+  // Search functions, routes, secret names and recent request ids
+  async search(q: string, limit?: number): Promise<SearchResponse> {
+    const url = new URL(`${API_BASE_URL}/search`);
+    url.searchParams.set('q', q);
+    if (limit) {
+      url.searchParams.set('limit', limit.toString());
+    }
+    const response = await fetch(url.toString());
+    return handleResponse(response);
+  },
+
+  // Environment checks; runs a probe container, so allow a few seconds
+  async getDoctorReport(): Promise<DoctorReport> {
+    const response = await fetch(`${API_BASE_URL}/doctor`);
+    return handleResponse(response);
+  },
+
+  // Changes after a cursor; wait is in seconds, for long polling
+  async listChanges(params: { cursor?: string; limit?: number; entities?: ChangeEntity[]; wait?: number } = {}): Promise<ChangeFeedResponse> {
+    const url = new URL(`${API_BASE_URL}/changes`);
+    if (params.cursor) {
+      url.searchParams.set('cursor', params.cursor);
+    }
+    if (params.limit) {
+      url.searchParams.set('limit', params.limit.toString());
+    }
+    if (params.entities?.length) {
+      url.searchParams.set('entities', params.entities.join(','));
+    }
+    if (params.wait) {
+      url.searchParams.set('wait', params.wait.toString());
+    }
+    const response = await fetch(url.toString());
+    return handleResponse(response);
+  },
 };
 
 export { ApiError };
@@ -25,6 +25,8 @@ export function getStateColor(state: string): string {
       return 'text-yellow-600 bg-yellow-50';
     case 'Inactive':
       return 'text-gray-600 bg-gray-50';
+    case 'Archived':
+      return 'text-slate-600 bg-slate-100';
     case 'Failed':
       return 'text-red-600 bg-red-50';
     default:
@@ -17,9 +17,68 @@ export interface Function {
   state: FunctionState;
   state_reason?: string;
   state_reason_code?: string;
+  metadata?: FunctionMetadata; // only returned by GetFunction
 }
 
-export type FunctionState = 'Pending' | 'Active' | 'Inactive' | 'Failed';
+export interface FunctionMetadata {
+  owner?: string | null;
+  repo_url?: string | null;
+  docs_url?: string | null;
+  notes?: string | null; // Markdown
+}
+
+// Configuration history (/api/functions/{name}/history)
+export interface FunctionConfigSnapshot {
+  role: string | null;
+  handler: string;
+  description: string | null;
+  timeout: number;
+  memory_size: number;
+  environment: Record<string, string>;
+}
+
+export type ConfigChangeSource = 'create' | 'update' | 'revert';
+
+export interface ConfigChange {
+  field: string; // e.g. "timeout" or "environment.STAGE"
+  old?: unknown;
+  new?: unknown;
+}
+
+export interface ConfigRevision {
+  revision: number;
+  changed_at: string;
+  changed_by: string | null; // X-Lambdah-Actor of the request
+  source: ConfigChangeSource;
+  reverted_from: number | null;
+  changes: ConfigChange[];
+  configuration: FunctionConfigSnapshot;
+}
+
+export interface ConfigHistoryResponse {
+  revisions: ConfigRevision[]; // newest first
+}
+
+export type FunctionState = 'Pending' | 'Active' | 'Inactive' | 'Failed' | 'Archived';
+
+// Secret references come back by name only, never with their value
+export interface EnvironmentVariable {
+  name: string;
+  value: string | null;
+  secret: string | null;
+  secret_exists: boolean | null;
+}
+
+export interface FunctionEnvironment {
+  variables: EnvironmentVariable[];
+}
+
+// Plain value, secret reference, or null to remove the variable
+export type EnvironmentValue = string | { secret: string } | null;
+
+export interface PatchEnvironmentRequest {
+  variables: Record<string, EnvironmentValue>;
+}
 
 export interface CreateFunctionRequest {
   function_name: string;
@@ -39,12 +98,34 @@ export interface FunctionCode {
   s3_bucket?: string;
   s3_key?: string;
   s3_object_version?: string;
+  workspace_package?: string; // package path inside a zipped npm/pnpm workspace
 }
 
 export interface ListFunctionsResponse {
   functions: Function[];
   next_marker?: string;
   total_count?: number;
+  /** Containers and queued work per function name, when `includeWarmState` was asked for */
+  warm_state?: Record<string, FunctionWarmState>;
+}
+
+export interface FunctionWarmState {
+  warm_idle: number;
+  active: number;
+  queued: number;
+}
+
+export type FunctionSortKey = 'name' | 'last_modified' | 'invocation_count';
+
+export interface ListFunctionsParams {
+  marker?: string;
+  maxItems?: number;
+  namePrefix?: string;
+  runtime?: string;
+  state?: FunctionState;
+  sortBy?: FunctionSortKey;
+  sortOrder?: 'asc' | 'desc';
+  includeWarmState?: boolean;
 }
 
 export interface InvokeRequest {
@@ -251,3 +332,171 @@ export interface LambdaServiceStats {
   max_duration_ms: number;
   min_duration_ms: number;
 }
+
+// Console dashboard (GET /api/dashboard)
+export interface Dashboard {
+  generated_at: string;
+  functions: {
+    total: number;
+    pending: number;
+    active: number;
+    inactive: number;
+    archived: number;
+    failed: number;
+  };
+  invocations: {
+    window_seconds: number;
+    invocations: number;
+    errors: number;
+    error_rate: number;
+    avg_duration_ms: number | null;
+    by_function: {
+      function_name: string;
+      invocations: number;
+      errors: number;
+      error_rate: number;
+    }[];
+  };
+  warm_pool: {
+    total: number;
+    warm_idle: number;
+    active: number;
+    stopped: number;
+    committed_memory_mb: number;
+    host_memory_budget_mb: number | null;
+  };
+  recent_deployments: {
+    function_name: string;
+    version: string;
+    code_sha256: string;
+    kind: 'updated' | 'published';
+    deployed_at: string;
+  }[];
+  alerts: {
+    severity: 'critical' | 'warning';
+    code: string;
+    message: string;
+    function_name: string | null;
+  }[];
+}
+
+// Omnibox search (GET /api/search)
+export type SearchHit =
+  | { kind: 'function'; function_name: string; runtime: string; description: string | null }
+  | { kind: 'route'; route_id: string; path: string; method: string | null; function_name: string }
+  | { kind: 'secret'; name: string }
+  | { kind: 'execution'; request_id: string; function_name: string; status: string; start_time: string };
+
+export interface SearchResponse {
+  query: string;
+  results: SearchHit[];
+  truncated: boolean;
+}
+
+// Environment diagnostics (GET /api/doctor)
+export interface DoctorCheck {
+  name: string;
+  status: 'pass' | 'warn' | 'fail';
+  detail: string;
+  fix?: string;
+}
+
+export interface DoctorReport {
+  checks: DoctorCheck[];
+  checked_at: string;
+}
+
+// Change feed (GET /api/changes)
+export type ChangeEntity = 'function' | 'route' | 'execution';
+
+export interface ChangeEvent {
+  sequence: number;
+  entity: ChangeEntity;
+  entity_id: string;
+  event_name: 'INSERT' | 'MODIFY' | 'REMOVE';
+  occurred_at: string;
+  new_image: Record<string, unknown> | null;
+  old_image: Record<string, unknown> | null;
+}
+
+export interface ChangeFeedResponse {
+  events: ChangeEvent[];
+  next_cursor: string;
+  has_more: boolean;
+}
+
+// Outbound webhooks (/admin/webhooks)
+export type WebhookEventType =
+  | 'function.created'
+  | 'function.updated'
+  | 'function.deleted'
+  | 'version.published'
+  | 'alias.updated'
+  | 'build.failed'
+  | 'alert.fired';
+
+export interface Webhook {
+  webhook_id: string;
+  url: string;
+  /** Empty means every event */
+  events: WebhookEventType[];
+  description: string | null;
+  enabled: boolean;
+  created_at: string;
+}
+
+export interface CreateWebhookRequest {
+  url: string;
+  events?: WebhookEventType[];
+  secret?: string;
+  description?: string;
+  enabled?: boolean;
+}
+
+/** The secret is only ever returned on creation */
+export interface CreateWebhookResponse extends Webhook {
+  secret: string;
+}
+
+export interface WebhookDelivery {
+  delivery_id: string;
+  webhook_id: string;
+  event_id: string;
+  event_type: WebhookEventType;
+  status: 'pending' | 'succeeded' | 'failed';
+  attempts: number;
+  response_status: number | null;
+  error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+// Queue to function delivery (/2015-03-31/event-source-mappings)
+export type FunctionResponseType = 'ReportBatchItemFailures';
+
+export interface S3KeyFilter {
+  prefix?: string;
+  suffix?: string;
+}
+
+export interface EventSourceMapping {
+  uuid: string;
+  event_source_arn: string;
+  function_name: string;
+  batch_size: number;
+  enabled: boolean;
+  function_response_types: FunctionResponseType[];
+  last_modified: string;
+  last_processing_result: string | null;
+  /** Only for S3 sources (`arn:aws:s3:::<bucket>`) */
+  s3_key_filter?: S3KeyFilter;
+}
+
+export interface CreateEventSourceMappingRequest {
+  event_source_arn: string;
+  function_name: string;
+  batch_size?: number;
+  enabled?: boolean;
+  function_response_types?: FunctionResponseType[];
+  s3_key_filter?: S3KeyFilter;
+}
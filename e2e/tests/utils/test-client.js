@@ -60,7 +60,7 @@ class TestClient {
     }
 
     async getFunction(functionName) {
-        const response = await this.client.get(`/2015-03-31/functions/${functionName}`);
+        const response = await this.client.get(`/api/functions/${functionName}`);
         return response.data;
     }
 
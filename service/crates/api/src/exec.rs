@@ -0,0 +1,217 @@
+use crate::handlers::change_actor;
+use crate::AppState;
+use axum::{
+    extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures::StreamExt;
+use lambda_invoker::ExecAttachment;
+use lambda_models::{ErrorShape, ExecAuditEvent, ExecSession};
+use serde::Deserialize;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use tokio::io::AsyncWriteExt;
+use tokio::time::{sleep_until, Instant};
+use tracing::{error, info, instrument, warn};
+
+/// Text frame that resizes the shell's terminal instead of being sent to it
+#[derive(Deserialize)]
+struct ResizeMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    cols: u16,
+    rows: u16,
+}
+
+/// `POST /api/containers/{id}/exec`: open a time-boxed debug shell session into a warm
+/// container. Needs `exec.enabled` and `Authorization: Bearer <exec.admin_token>`.
+#[instrument(skip(state, headers))]
+pub async fn open_exec_session(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<ExecSession>), (StatusCode, Json<ErrorShape>)> {
+    let settings = &state.config.exec;
+    let refuse = |status: StatusCode, message: &str, error_type: &str| {
+        (
+            status,
+            Json(ErrorShape {
+                error_message: message.into(),
+                error_type: error_type.into(),
+                stack_trace: None,
+                build_log: None,
+                references: None,
+            }),
+        )
+    };
+    if !settings.enabled {
+        return Err(refuse(
+            StatusCode::NOT_FOUND,
+            "Exec sessions are not enabled",
+            "ResourceNotFoundException",
+        ));
+    }
+    let Some(token) = settings.admin_token.as_deref().filter(|t| !t.is_empty()) else {
+        return Err(refuse(
+            StatusCode::FORBIDDEN,
+            "Exec sessions need exec.admin_token to be set",
+            "Forbidden",
+        ));
+    };
+    let presented = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !token_matches(presented, token) {
+        warn!(
+            "Refused exec session into {}: bad admin token",
+            container_id
+        );
+        return Err(refuse(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid admin token",
+            "Unauthorized",
+        ));
+    }
+
+    match state
+        .control
+        .open_exec_session(&container_id, change_actor(&headers))
+        .await
+    {
+        Ok(session) => {
+            info!(
+                "Opened exec session {} into container {} of {}",
+                session.session_id, session.container_id, session.function_name
+            );
+            Ok((StatusCode::CREATED, Json(session)))
+        }
+        Err(e) => {
+            error!("Failed to open exec session into {}: {}", container_id, e);
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(e.to_error_shape()),
+            ))
+        }
+    }
+}
+
+/// Compare in constant time, so response timing doesn't reveal how much of the token matched
+fn token_matches(presented: Option<&str>, token: &str) -> bool {
+    presented.is_some_and(|presented| presented.as_bytes().ct_eq(token.as_bytes()).into())
+}
+
+/// `GET /api/exec-sessions/{id}`: WebSocket bridged to the session's shell. Binary and text
+/// frames are the shell's input, except `{"type":"resize","cols":..,"rows":..}`; output
+/// comes back as binary frames.
+pub async fn connect_exec_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    match state.control.take_exec_session(&session_id) {
+        Ok(session) => ws.on_upgrade(move |socket| run_exec_session(state, session, socket)),
+        Err(e) => (
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )
+            .into_response(),
+    }
+}
+
+async fn run_exec_session(state: AppState, session: ExecSession, mut socket: WebSocket) {
+    let remaining = (session.expires_at - chrono::Utc::now())
+        .num_seconds()
+        .max(1) as u64;
+    let attached = state
+        .invoker
+        .exec_attached(&session.container_id, &state.config.exec.shell, remaining)
+        .await;
+    let reason = match attached {
+        Ok(attachment) => bridge(&state, &session, &mut socket, attachment, remaining).await,
+        Err(e) => format!("exec failed: {e}"),
+    };
+    info!("Exec session {} closed: {}", session.session_id, reason);
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: axum::extract::ws::close_code::NORMAL,
+            reason: reason.clone().into(),
+        })))
+        .await;
+    state
+        .control
+        .exec_audit()
+        .record(&session, ExecAuditEvent::Closed { reason });
+}
+
+/// Relay input and output until the shell exits, the client leaves, the session runs out or
+/// sits idle; returns why it ended
+async fn bridge(
+    state: &AppState,
+    session: &ExecSession,
+    socket: &mut WebSocket,
+    attachment: ExecAttachment,
+    remaining_secs: u64,
+) -> String {
+    let ExecAttachment {
+        exec_id,
+        mut output,
+        mut input,
+    } = attachment;
+    let audit = state.control.exec_audit();
+    let deadline = Instant::now() + Duration::from_secs(remaining_secs);
+    let idle_timeout = Duration::from_secs(state.config.exec.idle_timeout_secs.max(1));
+    let mut idle_deadline = Instant::now() + idle_timeout;
+
+    loop {
+        tokio::select! {
+            _ = sleep_until(deadline) => return "session expired".to_string(),
+            _ = sleep_until(idle_deadline) => return "idle timeout".to_string(),
+            chunk = output.next() => match chunk {
+                Some(Ok(bytes)) => {
+                    if socket.send(Message::Binary(bytes.to_vec())).await.is_err() {
+                        return "client disconnected".to_string();
+                    }
+                }
+                Some(Err(e)) => return format!("exec output failed: {e}"),
+                None => return "shell exited".to_string(),
+            },
+            message = socket.recv() => {
+                let data = match message {
+                    Some(Ok(Message::Binary(data))) => data,
+                    Some(Ok(Message::Text(text))) => {
+                        if let Ok(resize) = serde_json::from_str::<ResizeMessage>(&text) {
+                            if resize.kind == "resize" {
+                                let resized = state
+                                    .invoker
+                                    .resize_exec(&exec_id, resize.cols, resize.rows)
+                                    .await;
+                                if let Err(e) = resized {
+                                    warn!("Failed to resize exec {}: {}", exec_id, e);
+                                }
+                                continue;
+                            }
+                        }
+                        text.into_bytes()
+                    }
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        return "client disconnected".to_string();
+                    }
+                };
+                idle_deadline = Instant::now() + idle_timeout;
+                audit.record(
+                    session,
+                    ExecAuditEvent::Input {
+                        data: String::from_utf8_lossy(&data).into_owned(),
+                    },
+                );
+                if let Err(e) = input.write_all(&data).await {
+                    return format!("exec input failed: {e}");
+                }
+            }
+        }
+    }
+}
@@ -2,25 +2,57 @@ use crate::AppState;
 use axum::{
     body::Body,
     body::Bytes,
-    extract::{Path, Query, State},
+    extract::{ConnectInfo, Path, Query, State},
     http::Request,
     http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::IntoResponse,
     response::Json,
+    response::Response,
 };
+use lambda_control::function_urls::{self, url_event, url_response, UrlRequest};
+use lambda_control::{
+    check_response_header, matches_canary, transforms, trusted_headers, BoundedInvoke,
+    ChangeCursor, PeerClient, PendingInvoke, ResponseStream, StreamPart, DEFAULT_CHANGE_LIMIT,
+    FORWARDED_HEADER,
+};
+use lambda_metrics::LogLevelController;
 use lambda_models::{
-    ApiRoute, ConcurrencyConfig, CreateAliasRequest, CreateApiRouteRequest, CreateFunctionRequest,
-    CreateSecretRequest, ErrorShape, FunctionError, InvokeRequest, ListAliasesResponse,
-    ListApiRoutesResponse, ListFunctionsResponse, ListSecretsResponse, ListVersionsResponse,
-    PublishVersionRequest, SecretListItem, UpdateAliasRequest, UpdateFunctionCodeRequest,
-    UpdateFunctionConfigurationRequest,
+    AccessLogEntry, AddPermissionRequest, ApiRoute, AwsEmulation, ChangeEntity, ChangeFeedResponse,
+    ConcurrencyConfig, ConcurrencyUsage, ConfigHistoryResponse, ConsistencyReport, CostEstimate,
+    CostEstimateRequest, CreateAliasRequest, CreateApiRouteRequest,
+    CreateEventSourceMappingRequest, CreateFunctionGroupRequest, CreateFunctionRequest,
+    CreateSecretRequest, CreateTestEventRequest, CreateWebhookRequest, CreateWebhookResponse,
+    Dashboard, DatabaseStatus, DiskStatus, DockerApiStatus, DockerBreakerState, DoctorReport,
+    ErrorShape, ErrorSummary, EventSourceMapping, ExecutionRecord, ExportSecretsRequest,
+    ExportSecretsResponse, FeatureFlagStatus, FunctionAwsEmulation, FunctionEnvironment,
+    FunctionError, FunctionGroup, FunctionGroupMembership, FunctionHooks, FunctionInsights,
+    FunctionLifecycle, FunctionMetadata, FunctionPolicy, FunctionSandbox, FunctionSortKey,
+    FunctionState, FunctionUrlConfig, FunctionUser, GroupMetrics, HedgingConfig,
+    ImportSecretsRequest, ImportSecretsResponse, InstanceHealth, InvocationTrace, InvokeRequest,
+    Job, LambdaError, ListAliasesResponse, ListApiRoutesResponse, ListBuildsResponse,
+    ListEventSourceMappingsResponse, ListFeatureFlagsResponse, ListFunctionGroupsResponse,
+    ListFunctionsQuery, ListFunctionsResponse, ListSchedulesResponse, ListSecretExportsResponse,
+    ListSecretsResponse, ListTagsResponse, ListTestEventsResponse, ListVersionsResponse,
+    ListWebhookDeliveriesResponse, ListWebhooksResponse, LocaleConfig, LogLevelResponse,
+    NamespaceUsage, PatchEnvironmentRequest, PermissionStatement, PrewarmConfig,
+    ProvisionedConcurrencyConfig, PublishVersionRequest, PutProvisionedConcurrencyConfigRequest,
+    ReplicationStatus, ResponseHeadersConfig, Rollout, RouteAccessLog, RouteCanary,
+    RouteInvocation, RouteJwtAuthorizer, RouteLimits, RouteMetrics, RouteMock, RouteRecording,
+    RouteTarget, RouteTransform, RuntimeApiSettings, RuntimeApiStatus, RuntimeManagement,
+    RuntimeManagementConfig, Schedule, ScheduleRequest, SchemaStatus, SearchResponse,
+    SecretListItem, SetFeatureFlagRequest, SetLogLevelRequest, SlowInvokeMode, SortOrder,
+    StopGraceConfig, SyntheticCheck, SyntheticCheckRun, SyntheticCheckStats, SystemInfo,
+    TagResourceRequest, TestEvent, UpdateAliasRequest, UpdateEventSourceMappingRequest,
+    UpdateFunctionCodeRequest, UpdateFunctionConfigurationRequest, UpdateFunctionGroupRequest,
+    ValidateFunctionResponse, WarmupStats, Webhook,
 };
-use std::collections::HashMap;
-use tracing::{error, info, instrument};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::{debug, error, info, instrument, warn};
 
 // Type aliases for complex return types
-type InvokeResponse =
-    Result<(StatusCode, HeaderMap, Json<serde_json::Value>), (StatusCode, Json<ErrorShape>)>;
+type InvokeResponse = Result<(StatusCode, HeaderMap, Response), (StatusCode, Json<ErrorShape>)>;
 
 #[instrument(skip(state, payload))]
 pub async fn create_function(
@@ -48,15 +80,32 @@ pub async fn create_function(
     }
 }
 
+#[instrument(skip(state, payload))]
+pub async fn validate_function(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateFunctionRequest>,
+) -> Result<Json<ValidateFunctionResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.validate_function(&payload).await {
+        Ok(problems) => Ok(Json(ValidateFunctionResponse {
+            valid: problems.is_empty(),
+            problems,
+        })),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
 #[instrument(skip(state))]
 pub async fn get_function(
     State(state): State<AppState>,
     Path(name): Path<String>,
-) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
+) -> Result<Json<lambda_models::GetFunctionResponse>, (StatusCode, Json<ErrorShape>)> {
     info!("Getting function: {}", name);
 
-    match state.control.get_function(&name).await {
-        Ok(function) => Ok(Json(function)),
+    match state.control.get_function_response(&name).await {
+        Ok(response) => Ok(Json(response)),
         Err(e) => {
             error!("Failed to get function {}: {}", name, e);
             let error_shape = e.to_error_shape();
@@ -68,6 +117,67 @@ pub async fn get_function(
     }
 }
 
+/// `GET /api/functions/{name}`: the stored function with every lambda-at-home setting
+#[instrument(skip(state))]
+pub async fn describe_function(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.describe_function(&name).await {
+        Ok(function) => Ok(Json(function)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/functions/{name}/code`: the function's ZIP, linked from GetFunction's `Code`
+#[instrument(skip(state))]
+pub async fn get_function_code(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<(HeaderMap, Vec<u8>), (StatusCode, Json<ErrorShape>)> {
+    match state.control.function_code(&name).await {
+        Ok(zip) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                axum::http::header::CONTENT_TYPE,
+                HeaderValue::from_static("application/zip"),
+            );
+            let disposition = format!("attachment; filename=\"{name}.zip\"");
+            if let Ok(value) = HeaderValue::from_str(&disposition) {
+                headers.insert(axum::http::header::CONTENT_DISPOSITION, value);
+            }
+            Ok((headers, zip))
+        }
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `POST /api/functions/batch`: delete or reconfigure many functions with a result per function
+#[instrument(skip(state, headers, payload))]
+pub async fn batch_functions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<lambda_models::BatchFunctionsRequest>,
+) -> Result<Json<lambda_models::BatchFunctionsResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state
+        .control
+        .batch_functions(payload, change_actor(&headers))
+        .await
+    {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
 #[instrument(skip(state))]
 pub async fn delete_function(
     State(state): State<AppState>,
@@ -96,10 +206,11 @@ pub async fn list_functions(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Json<ListFunctionsResponse>, (StatusCode, Json<ErrorShape>)> {
-    let marker = params.get("Marker");
-    let max_items = params.get("MaxItems").and_then(|s| s.parse::<u32>().ok());
-
-    match state.control.list_functions(marker, max_items).await {
+    let result = match list_functions_query(&params) {
+        Ok(query) => state.control.list_functions(&query).await,
+        Err(e) => Err(e),
+    };
+    match result {
         Ok(response) => Ok(Json(response)),
         Err(e) => {
             error!("Failed to list functions: {}", e);
@@ -112,6 +223,56 @@ pub async fn list_functions(
     }
 }
 
+/// Read ListFunctions query parameters: `Marker`, `MaxItems`, `NamePrefix`, `Runtime`,
+/// `State`, `Tag` (`key:value` pairs separated by commas, all of which a function must
+/// carry), `Group` (only the functions of that group), `SortBy` (name, last_modified, invocation_count), `SortOrder` (asc, desc) and
+/// `IncludeWarmState` (true to add each function's warm state)
+fn list_functions_query(
+    params: &HashMap<String, String>,
+) -> Result<ListFunctionsQuery, LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    let non_empty = |key: &str| params.get(key).filter(|v| !v.is_empty()).cloned();
+    Ok(ListFunctionsQuery {
+        marker: non_empty("Marker"),
+        max_items: non_empty("MaxItems")
+            .map(|v| {
+                v.parse::<u32>()
+                    .map_err(|_| invalid(format!("Invalid MaxItems: {v}")))
+            })
+            .transpose()?,
+        name_prefix: non_empty("NamePrefix"),
+        runtime: non_empty("Runtime"),
+        state: non_empty("State")
+            .map(|v| v.parse::<FunctionState>().map_err(invalid))
+            .transpose()?,
+        group: non_empty("Group"),
+        tags: non_empty("Tag")
+            .map(|v| {
+                v.split(',')
+                    .map(|pair| match pair.split_once(':') {
+                        Some((key, value)) if !key.is_empty() => {
+                            Ok((key.to_string(), value.to_string()))
+                        }
+                        _ => Err(invalid(format!("Invalid Tag {pair}: expected key:value"))),
+                    })
+                    .collect::<Result<BTreeMap<_, _>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default(),
+        sort_by: non_empty("SortBy")
+            .map(|v| v.parse::<FunctionSortKey>().map_err(invalid))
+            .transpose()?
+            .unwrap_or_default(),
+        order: non_empty("SortOrder")
+            .map(|v| v.parse::<SortOrder>().map_err(invalid))
+            .transpose()?
+            .unwrap_or_default(),
+        include_warm_state: params
+            .get("IncludeWarmState")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true")),
+    })
+}
+
 #[instrument(skip(state, payload))]
 pub async fn update_function_code(
     State(state): State<AppState>,
@@ -133,17 +294,37 @@ pub async fn update_function_code(
     }
 }
 
-#[instrument(skip(state, payload))]
+/// GetFunctionConfiguration, polled by AWS SDK waiters for `State` and `LastUpdateStatus`
+#[instrument(skip(state))]
+pub async fn get_function_configuration(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_function(&name).await {
+        Ok(function) => Ok(Json(function)),
+        Err(e) => {
+            error!("Failed to get function configuration for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, headers, payload))]
 pub async fn update_function_configuration(
     State(state): State<AppState>,
     Path(name): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateFunctionConfigurationRequest>,
 ) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
     info!("Updating function configuration: {}", name);
 
     match state
         .control
-        .update_function_configuration(&name, payload)
+        .update_function_configuration_by(&name, payload, change_actor(&headers))
         .await
     {
         Ok(function) => Ok(Json(function)),
@@ -286,71 +467,136 @@ pub async fn delete_alias(
     }
 }
 
-// -------- Secrets admin --------
+// -------- Schedules --------
+#[instrument(skip(state, payload))]
+pub async fn create_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<ScheduleRequest>,
+) -> Result<(StatusCode, Json<Schedule>), (StatusCode, Json<ErrorShape>)> {
+    info!(
+        "Scheduling function {} at {}",
+        name, payload.schedule_expression
+    );
+
+    match state.control.create_schedule(&name, payload).await {
+        Ok(schedule) => Ok((StatusCode::CREATED, Json(schedule))),
+        Err(e) => {
+            error!("Failed to create schedule for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
 #[instrument(skip(state))]
-pub async fn list_secrets(
+pub async fn list_schedules(
     State(state): State<AppState>,
-) -> Result<Json<ListSecretsResponse>, (StatusCode, Json<ErrorShape>)> {
-    match state.control.list_secrets().await {
-        Ok(items) => {
-            let secrets = items
-                .into_iter()
-                .map(|(name, created_at)| SecretListItem { name, created_at })
-                .collect();
-            Ok(Json(ListSecretsResponse { secrets }))
+    Path(name): Path<String>,
+) -> Result<Json<ListSchedulesResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.list_schedules(&name).await {
+        Ok(schedules) => Ok(Json(ListSchedulesResponse { schedules })),
+        Err(e) => {
+            error!("Failed to list schedules for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_schedule(
+    State(state): State<AppState>,
+    Path((name, schedule_id)): Path<(String, String)>,
+) -> Result<Json<Schedule>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_schedule(&name, &schedule_id).await {
+        Ok(schedule) => Ok(Json(schedule)),
+        Err(e) => {
+            error!("Failed to get schedule {} for {}: {}", schedule_id, name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
         }
-        Err(e) => Err((
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(e.to_error_shape()),
-        )),
     }
 }
 
 #[instrument(skip(state, payload))]
-pub async fn create_secret(
+pub async fn update_schedule(
     State(state): State<AppState>,
-    Json(payload): Json<CreateSecretRequest>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    Path((name, schedule_id)): Path<(String, String)>,
+    Json(payload): Json<ScheduleRequest>,
+) -> Result<Json<Schedule>, (StatusCode, Json<ErrorShape>)> {
+    info!("Updating schedule {} for function: {}", schedule_id, name);
+
     match state
         .control
-        .create_secret(&payload.name, &payload.value)
+        .update_schedule(&name, &schedule_id, payload)
         .await
     {
-        Ok(()) => Ok(StatusCode::CREATED),
-        Err(e) => Err((
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(e.to_error_shape()),
-        )),
+        Ok(schedule) => Ok(Json(schedule)),
+        Err(e) => {
+            error!(
+                "Failed to update schedule {} for {}: {}",
+                schedule_id, name, e
+            );
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
     }
 }
 
 #[instrument(skip(state))]
-pub async fn delete_secret(
+pub async fn delete_schedule(
     State(state): State<AppState>,
-    Path(name): Path<String>,
+    Path((name, schedule_id)): Path<(String, String)>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
-    match state.control.delete_secret(&name).await {
+    info!("Deleting schedule {} for function: {}", schedule_id, name);
+
+    match state.control.delete_schedule(&name, &schedule_id).await {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => Err((
-            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(e.to_error_shape()),
-        )),
+        Err(e) => {
+            error!(
+                "Failed to delete schedule {} for {}: {}",
+                schedule_id, name, e
+            );
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
     }
 }
 
+// -------- Function URLs --------
 #[instrument(skip(state))]
-pub async fn list_aliases(
+pub async fn create_function_url_config(
     State(state): State<AppState>,
     Path(name): Path<String>,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<ListAliasesResponse>, (StatusCode, Json<ErrorShape>)> {
-    let marker = params.get("Marker");
-    let max_items = params.get("MaxItems").and_then(|s| s.parse::<u32>().ok());
+) -> Result<(StatusCode, Json<FunctionUrlConfig>), (StatusCode, Json<ErrorShape>)> {
+    info!("Creating function URL for function: {}", name);
+    let qualifier = params.get("Qualifier").map(String::as_str);
 
-    match state.control.list_aliases(&name, marker, max_items).await {
-        Ok(response) => Ok(Json(response)),
+    match state
+        .control
+        .create_function_url_config(&name, qualifier)
+        .await
+    {
+        Ok(config) => Ok((StatusCode::CREATED, Json(config))),
         Err(e) => {
-            error!("Failed to list aliases for {}: {}", name, e);
+            error!("Failed to create function URL for {}: {}", name, e);
             let error_shape = e.to_error_shape();
             Err((
                 StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
@@ -361,17 +607,21 @@ pub async fn list_aliases(
 }
 
 #[instrument(skip(state))]
-pub async fn put_concurrency(
+pub async fn get_function_url_config(
     State(state): State<AppState>,
     Path(name): Path<String>,
-    Json(payload): Json<ConcurrencyConfig>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
-    info!("Setting concurrency for function: {}", name);
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<FunctionUrlConfig>, (StatusCode, Json<ErrorShape>)> {
+    let qualifier = params.get("Qualifier").map(String::as_str);
 
-    match state.control.put_concurrency(&name, payload).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
+    match state
+        .control
+        .get_function_url_config(&name, qualifier)
+        .await
+    {
+        Ok(config) => Ok(Json(config)),
         Err(e) => {
-            error!("Failed to set concurrency for {}: {}", name, e);
+            error!("Failed to get function URL for {}: {}", name, e);
             let error_shape = e.to_error_shape();
             Err((
                 StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
@@ -382,16 +632,22 @@ pub async fn put_concurrency(
 }
 
 #[instrument(skip(state))]
-pub async fn get_concurrency(
+pub async fn delete_function_url_config(
     State(state): State<AppState>,
     Path(name): Path<String>,
-) -> Result<Json<ConcurrencyConfig>, (StatusCode, Json<ErrorShape>)> {
-    info!("Getting concurrency for function: {}", name);
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting function URL for function: {}", name);
+    let qualifier = params.get("Qualifier").map(String::as_str);
 
-    match state.control.get_concurrency(&name).await {
-        Ok(config) => Ok(Json(config)),
+    match state
+        .control
+        .delete_function_url_config(&name, qualifier)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(e) => {
-            error!("Failed to get concurrency for {}: {}", name, e);
+            error!("Failed to delete function URL for {}: {}", name, e);
             let error_shape = e.to_error_shape();
             Err((
                 StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
@@ -401,17 +657,22 @@ pub async fn get_concurrency(
     }
 }
 
-#[instrument(skip(state))]
-pub async fn delete_concurrency(
+// -------- Function policies --------
+#[instrument(skip(state, payload))]
+pub async fn add_permission(
     State(state): State<AppState>,
     Path(name): Path<String>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
-    info!("Deleting concurrency for function: {}", name);
+    Json(payload): Json<AddPermissionRequest>,
+) -> Result<(StatusCode, Json<PermissionStatement>), (StatusCode, Json<ErrorShape>)> {
+    info!(
+        "Adding policy statement {} to function: {}",
+        payload.statement_id, name
+    );
 
-    match state.control.delete_concurrency(&name).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
+    match state.control.add_permission(&name, payload).await {
+        Ok(statement) => Ok((StatusCode::CREATED, Json(statement))),
         Err(e) => {
-            error!("Failed to delete concurrency for {}: {}", name, e);
+            error!("Failed to add policy statement to {}: {}", name, e);
             let error_shape = e.to_error_shape();
             Err((
                 StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
@@ -421,138 +682,3102 @@ pub async fn delete_concurrency(
     }
 }
 
-#[instrument(skip(state, headers, body))]
-pub async fn invoke_function(
+#[instrument(skip(state))]
+pub async fn get_policy(
     State(state): State<AppState>,
     Path(name): Path<String>,
-    headers: HeaderMap,
-    body: Bytes,
-) -> InvokeResponse {
-    // Parse invocation type from headers
-    let invocation_type = headers
-        .get("X-Amz-Invocation-Type")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(lambda_models::InvocationType::RequestResponse);
+) -> Result<Json<FunctionPolicy>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_policy(&name).await {
+        Ok(policy) => Ok(Json(policy)),
+        Err(e) => {
+            error!("Failed to get policy of {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
 
-    let log_type = headers
-        .get("X-Amz-Log-Type")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.parse().ok());
+#[instrument(skip(state))]
+pub async fn remove_permission(
+    State(state): State<AppState>,
+    Path((name, statement_id)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!(
+        "Removing policy statement {} from function: {}",
+        statement_id, name
+    );
 
-    // Parse payload
-    let payload = if body.is_empty() {
-        None
-    } else {
-        match serde_json::from_slice(&body) {
-            Ok(p) => Some(p),
-            Err(_) => {
-                // If not valid JSON, treat as string
-                Some(serde_json::Value::String(
-                    String::from_utf8_lossy(&body).to_string(),
-                ))
-            }
+    match state.control.remove_permission(&name, &statement_id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!(
+                "Failed to remove policy statement {} from {}: {}",
+                statement_id, name, e
+            );
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
         }
-    };
+    }
+}
 
-    let request = InvokeRequest {
-        function_name: name.clone(),
-        invocation_type,
-        log_type,
-        client_context: None,
-        payload,
-        qualifier: None,
+/// `POST /2017-03-31/tags/{arn}`: TagResource
+#[instrument(skip(state, payload))]
+pub async fn tag_resource(
+    State(state): State<AppState>,
+    Path(arn): Path<String>,
+    Json(payload): Json<TagResourceRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    match state.control.tag_resource(&arn, payload.tags).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `DELETE /2017-03-31/tags/{arn}?tagKeys=a&tagKeys=b`: UntagResource
+#[instrument(skip(state))]
+pub async fn untag_resource(
+    State(state): State<AppState>,
+    Path(arn): Path<String>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let keys: Vec<String> = params
+        .into_iter()
+        .filter(|(param, _)| param == "tagKeys")
+        .map(|(_, key)| key)
+        .collect();
+    match state.control.untag_resource(&arn, &keys).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /2017-03-31/tags/{arn}`: ListTags
+#[instrument(skip(state))]
+pub async fn list_tags(
+    State(state): State<AppState>,
+    Path(arn): Path<String>,
+) -> Result<Json<ListTagsResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.list_tags(&arn).await {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+// -------- Function groups --------
+fn group_error(e: LambdaError) -> (StatusCode, Json<ErrorShape>) {
+    (
+        StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(e.to_error_shape()),
+    )
+}
+
+/// `GET /api/groups`: every group with its functions, and the ungrouped functions
+#[instrument(skip(state))]
+pub async fn list_groups(
+    State(state): State<AppState>,
+) -> Result<Json<ListFunctionGroupsResponse>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .list_groups()
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `POST /api/groups`
+#[instrument(skip(state, payload))]
+pub async fn create_group(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateFunctionGroupRequest>,
+) -> Result<(StatusCode, Json<FunctionGroup>), (StatusCode, Json<ErrorShape>)> {
+    match state.control.create_group(payload).await {
+        Ok(group) => Ok((StatusCode::CREATED, Json(group))),
+        Err(e) => Err(group_error(e)),
+    }
+}
+
+/// `GET /api/groups/{group}`
+#[instrument(skip(state))]
+pub async fn get_group(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+) -> Result<Json<FunctionGroup>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .get_group(&group)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `PUT /api/groups/{group}`
+#[instrument(skip(state, payload))]
+pub async fn update_group(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+    Json(payload): Json<UpdateFunctionGroupRequest>,
+) -> Result<Json<FunctionGroup>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .update_group(&group, payload)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `DELETE /api/groups/{group}`: the group's functions stay, ungrouped
+#[instrument(skip(state))]
+pub async fn delete_group(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    match state.control.delete_group(&group).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(group_error(e)),
+    }
+}
+
+/// `GET /api/groups/{group}/metrics?window=24h`: executions of the group's functions
+#[instrument(skip(state))]
+pub async fn get_group_metrics(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<GroupMetrics>, (StatusCode, Json<ErrorShape>)> {
+    let window = params.get("window").map(String::as_str).unwrap_or("24h");
+    let result = match lambda_control::parse_window(window) {
+        Ok(window) => state.control.group_metrics(&group, window).await,
+        Err(e) => Err(e),
+    };
+    result.map(Json).map_err(group_error)
+}
+
+/// `POST /api/groups/{group}/drain`: stop the containers of every function of the group
+#[instrument(skip(state))]
+pub async fn drain_group(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+) -> Result<Json<lambda_models::BatchFunctionsResponse>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .drain_group(&group)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `POST /api/groups/{group}/disable`: refuse invocations of the group's functions
+#[instrument(skip(state))]
+pub async fn disable_group(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+) -> Result<Json<FunctionGroup>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .set_group_disabled(&group, true)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `POST /api/groups/{group}/enable`
+#[instrument(skip(state))]
+pub async fn enable_group(
+    State(state): State<AppState>,
+    Path(group): Path<String>,
+) -> Result<Json<FunctionGroup>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .set_group_disabled(&group, false)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `PUT /api/functions/{name}/group`: move the function into a group
+#[instrument(skip(state, payload))]
+pub async fn put_function_group(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<FunctionGroupMembership>,
+) -> Result<Json<FunctionGroupMembership>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .set_function_group(&name, &payload.group)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `GET /api/functions/{name}/group`: `null` when the function is in no group
+#[instrument(skip(state))]
+pub async fn get_function_group(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Option<FunctionGroupMembership>>, (StatusCode, Json<ErrorShape>)> {
+    state
+        .control
+        .get_function_group(&name)
+        .await
+        .map(Json)
+        .map_err(group_error)
+}
+
+/// `DELETE /api/functions/{name}/group`
+#[instrument(skip(state))]
+pub async fn delete_function_group(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    match state.control.remove_function_group(&name).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(group_error(e)),
+    }
+}
+
+// -------- Secrets admin --------
+#[instrument(skip(state))]
+pub async fn list_secrets(
+    State(state): State<AppState>,
+) -> Result<Json<ListSecretsResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.list_secrets().await {
+        Ok(items) => {
+            let secrets = items
+                .into_iter()
+                .map(|(name, created_at)| SecretListItem { name, created_at })
+                .collect();
+            Ok(Json(ListSecretsResponse { secrets }))
+        }
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_secret(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateSecretRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    match state
+        .control
+        .create_secret(&payload.name, &payload.value)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::CREATED),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn import_secrets(
+    State(state): State<AppState>,
+    Json(payload): Json<ImportSecretsRequest>,
+) -> Result<Json<ImportSecretsResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.import_secrets(payload).await {
+        Ok(imported) => Ok(Json(imported)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, headers, payload))]
+pub async fn export_secrets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<ExportSecretsRequest>,
+) -> Result<Json<ExportSecretsResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state
+        .control
+        .export_secrets(payload, change_actor(&headers))
+        .await
+    {
+        Ok(exported) => Ok(Json(exported)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_secret_exports(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ListSecretExportsResponse>, (StatusCode, Json<ErrorShape>)> {
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse::<u32>().ok())
+        .unwrap_or(lambda_control::secrets::DEFAULT_EXPORT_LIST_LIMIT)
+        .clamp(1, lambda_control::secrets::MAX_EXPORT_AUDIT_ENTRIES as u32);
+    match state.control.list_secret_exports(limit).await {
+        Ok(exports) => Ok(Json(ListSecretExportsResponse { exports })),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_secret(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    match state.control.delete_secret(&name).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+// -------- Webhooks admin --------
+fn parse_webhook_id(id: &str) -> Result<uuid::Uuid, (StatusCode, Json<ErrorShape>)> {
+    uuid::Uuid::parse_str(id).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorShape {
+                error_message: "Invalid webhook id".into(),
+                error_type: "BadRequest".into(),
+                stack_trace: None,
+                build_log: None,
+                references: None,
+            }),
+        )
+    })
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreateWebhookResponse>), (StatusCode, Json<ErrorShape>)> {
+    match state.control.create_webhook(payload).await {
+        Ok(created) => Ok((StatusCode::CREATED, Json(created))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+) -> Result<Json<ListWebhooksResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.list_webhooks().await {
+        Ok(webhooks) => Ok(Json(webhooks)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Webhook>, (StatusCode, Json<ErrorShape>)> {
+    let webhook_id = parse_webhook_id(&id)?;
+    match state.control.get_webhook(webhook_id).await {
+        Ok(webhook) => Ok(Json(webhook)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let webhook_id = parse_webhook_id(&id)?;
+    match state.control.delete_webhook(webhook_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ListWebhookDeliveriesResponse>, (StatusCode, Json<ErrorShape>)> {
+    let webhook_id = parse_webhook_id(&id)?;
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse::<u32>().ok())
+        .unwrap_or(lambda_control::DEFAULT_DELIVERY_LIST_LIMIT)
+        .clamp(1, lambda_control::DELIVERY_LOG_LIMIT as u32);
+    match state
+        .control
+        .list_webhook_deliveries(webhook_id, limit)
+        .await
+    {
+        Ok(deliveries) => Ok(Json(deliveries)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+fn parse_mapping_uuid(uuid: &str) -> Result<uuid::Uuid, (StatusCode, Json<ErrorShape>)> {
+    uuid::Uuid::parse_str(uuid).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorShape {
+                error_message: "Invalid event source mapping UUID".into(),
+                error_type: "BadRequest".into(),
+                stack_trace: None,
+                build_log: None,
+                references: None,
+            }),
+        )
+    })
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_event_source_mapping(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateEventSourceMappingRequest>,
+) -> Result<(StatusCode, Json<EventSourceMapping>), (StatusCode, Json<ErrorShape>)> {
+    match state.control.create_event_source_mapping(payload).await {
+        Ok(mapping) => Ok((StatusCode::ACCEPTED, Json(mapping))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_event_source_mappings(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ListEventSourceMappingsResponse>, (StatusCode, Json<ErrorShape>)> {
+    let function_name = params.get("FunctionName").map(String::as_str);
+    match state
+        .control
+        .list_event_source_mappings(function_name)
+        .await
+    {
+        Ok(mappings) => Ok(Json(mappings)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_event_source_mapping(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Result<Json<EventSourceMapping>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_mapping_uuid(&uuid)?;
+    match state.control.get_event_source_mapping(uuid).await {
+        Ok(mapping) => Ok(Json(mapping)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn update_event_source_mapping(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(payload): Json<UpdateEventSourceMappingRequest>,
+) -> Result<Json<EventSourceMapping>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_mapping_uuid(&uuid)?;
+    match state
+        .control
+        .update_event_source_mapping(uuid, payload)
+        .await
+    {
+        Ok(mapping) => Ok(Json(mapping)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_event_source_mapping(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_mapping_uuid(&uuid)?;
+    match state.control.delete_event_source_mapping(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `POST /api/s3/notifications`: bucket notifications from a MinIO webhook target
+#[instrument(skip(state, headers, payload))]
+pub async fn receive_s3_notification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorShape>)> {
+    let settings = &state.config.s3_notifications;
+    if !settings.enabled {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorShape {
+                error_message: "S3 notifications are not enabled".into(),
+                error_type: "ResourceNotFoundException".into(),
+                stack_trace: None,
+                build_log: None,
+                references: None,
+            }),
+        ));
+    }
+    if let Some(token) = settings.auth_token.as_deref().filter(|t| !t.is_empty()) {
+        let presented = headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.strip_prefix("Bearer ").unwrap_or(v));
+        if presented != Some(token) {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorShape {
+                    error_message: "Missing or invalid S3 notification token".into(),
+                    error_type: "Unauthorized".into(),
+                    stack_trace: None,
+                    build_log: None,
+                    references: None,
+                }),
+            ));
+        }
+    }
+    match lambda_control::dispatch_notification(&state.control, &payload).await {
+        Ok(dispatched) => Ok(Json(serde_json::json!({ "dispatched": dispatched }))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+fn log_level_controller() -> Result<&'static LogLevelController, (StatusCode, Json<ErrorShape>)> {
+    LogLevelController::global().ok_or_else(|| {
+        let e = LambdaError::InternalError {
+            reason: "Log level control is not enabled".to_string(),
+        };
+        (StatusCode::SERVICE_UNAVAILABLE, Json(e.to_error_shape()))
+    })
+}
+
+fn log_level_response(controller: &LogLevelController) -> LogLevelResponse {
+    LogLevelResponse {
+        filter: controller.current_filter(),
+        default_filter: controller.default_filter().to_string(),
+    }
+}
+
+#[instrument(skip(_state))]
+pub async fn get_log_level(
+    State(_state): State<AppState>,
+) -> Result<Json<LogLevelResponse>, (StatusCode, Json<ErrorShape>)> {
+    let controller = log_level_controller()?;
+    Ok(Json(log_level_response(controller)))
+}
+
+#[instrument(skip(_state))]
+pub async fn put_log_level(
+    State(_state): State<AppState>,
+    Json(payload): Json<SetLogLevelRequest>,
+) -> Result<Json<LogLevelResponse>, (StatusCode, Json<ErrorShape>)> {
+    let controller = log_level_controller()?;
+    let result = match payload.revert_after_secs {
+        Some(secs) => controller.set_filter_for(&payload.filter, Duration::from_secs(secs)),
+        None => controller.set_filter(&payload.filter),
+    };
+    match result {
+        Ok(()) => Ok(Json(log_level_response(controller))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(_state))]
+pub async fn reset_log_level(
+    State(_state): State<AppState>,
+) -> Result<Json<LogLevelResponse>, (StatusCode, Json<ErrorShape>)> {
+    let controller = log_level_controller()?;
+    match controller.reset() {
+        Ok(()) => Ok(Json(log_level_response(controller))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// How function containers created now reach the runtime API
+#[instrument(skip(state))]
+pub async fn get_runtime_api(
+    State(state): State<AppState>,
+) -> Result<Json<RuntimeApiStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.runtime_api_status().await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// Advertise another runtime API address to containers created from now on
+#[instrument(skip(state))]
+pub async fn put_runtime_api(
+    State(state): State<AppState>,
+    Json(payload): Json<RuntimeApiSettings>,
+) -> Result<Json<RuntimeApiStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.set_runtime_api(Some(payload)).await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// Go back to the runtime API settings of the config file
+#[instrument(skip(state))]
+pub async fn reset_runtime_api(
+    State(state): State<AppState>,
+) -> Result<Json<RuntimeApiStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.set_runtime_api(None).await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_feature_flags(
+    State(state): State<AppState>,
+) -> Result<Json<ListFeatureFlagsResponse>, (StatusCode, Json<ErrorShape>)> {
+    Ok(Json(state.control.list_feature_flags()))
+}
+
+#[instrument(skip(state))]
+pub async fn put_feature_flag(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<SetFeatureFlagRequest>,
+) -> Result<Json<FeatureFlagStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.set_feature_flag(&name, payload.enabled) {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn reset_feature_flag(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FeatureFlagStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.reset_feature_flag(&name) {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_aliases(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ListAliasesResponse>, (StatusCode, Json<ErrorShape>)> {
+    let marker = params.get("Marker");
+    let max_items = params.get("MaxItems").and_then(|s| s.parse::<u32>().ok());
+
+    match state.control.list_aliases(&name, marker, max_items).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to list aliases for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_concurrency(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<ConcurrencyConfig>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting concurrency for function: {}", name);
+
+    match state.control.put_concurrency(&name, payload).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to set concurrency for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// `Qualifier` query parameter of the provisioned concurrency endpoints, which AWS requires
+fn provisioned_qualifier(params: &HashMap<String, String>) -> Result<String, LambdaError> {
+    params
+        .get("Qualifier")
+        .filter(|q| !q.is_empty())
+        .cloned()
+        .ok_or_else(|| LambdaError::InvalidRequest {
+            reason: "Qualifier is required: a published version or an alias".to_string(),
+        })
+}
+
+/// `PUT /2019-09-30/functions/{name}/provisioned-concurrency?Qualifier=live`:
+/// PutProvisionedConcurrencyConfig
+#[instrument(skip(state, payload))]
+pub async fn put_provisioned_concurrency(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    Json(payload): Json<PutProvisionedConcurrencyConfigRequest>,
+) -> Result<(StatusCode, Json<ProvisionedConcurrencyConfig>), (StatusCode, Json<ErrorShape>)> {
+    let result = match provisioned_qualifier(&params) {
+        Ok(qualifier) => {
+            state
+                .control
+                .put_provisioned_concurrency(&name, &qualifier, payload)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(config) => Ok((StatusCode::ACCEPTED, Json(config))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /2019-09-30/functions/{name}/provisioned-concurrency?Qualifier=live`:
+/// GetProvisionedConcurrencyConfig
+#[instrument(skip(state))]
+pub async fn get_provisioned_concurrency(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ProvisionedConcurrencyConfig>, (StatusCode, Json<ErrorShape>)> {
+    let result = match provisioned_qualifier(&params) {
+        Ok(qualifier) => {
+            state
+                .control
+                .get_provisioned_concurrency(&name, &qualifier)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `DELETE /2019-09-30/functions/{name}/provisioned-concurrency?Qualifier=live`:
+/// DeleteProvisionedConcurrencyConfig
+#[instrument(skip(state))]
+pub async fn delete_provisioned_concurrency(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let result = match provisioned_qualifier(&params) {
+        Ok(qualifier) => {
+            state
+                .control
+                .delete_provisioned_concurrency(&name, &qualifier)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_concurrency(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ConcurrencyConfig>, (StatusCode, Json<ErrorShape>)> {
+    info!("Getting concurrency for function: {}", name);
+
+    match state.control.get_concurrency(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get concurrency for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_concurrency_usage(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ConcurrencyUsage>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.concurrency_usage(&name).await {
+        Ok(usage) => Ok(Json(usage)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_concurrency(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting concurrency for function: {}", name);
+
+    match state.control.delete_concurrency(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete concurrency for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_hedging(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<HedgingConfig>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting hedging for function: {}", name);
+
+    match state.control.put_hedging(&name, payload).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to set hedging for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_hedging(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<HedgingConfig>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_hedging(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get hedging for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_hedging(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting hedging for function: {}", name);
+
+    match state.control.delete_hedging(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete hedging for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// `PUT /api/functions/{name}/lifecycle`: idle timeout and warm container bounds
+#[instrument(skip(state))]
+pub async fn put_lifecycle(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<FunctionLifecycle>,
+) -> Result<Json<FunctionLifecycle>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting warm pool lifecycle for function: {}", name);
+
+    match state.control.put_lifecycle(&name, payload).await {
+        Ok(lifecycle) => Ok(Json(lifecycle)),
+        Err(e) => {
+            error!("Failed to set warm pool lifecycle for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_lifecycle(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionLifecycle>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_lifecycle(&name).await {
+        Ok(lifecycle) => Ok(Json(lifecycle)),
+        Err(e) => {
+            error!("Failed to get warm pool lifecycle for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_lifecycle(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting warm pool lifecycle for function: {}", name);
+
+    match state.control.delete_lifecycle(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete warm pool lifecycle for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_stop_grace(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<StopGraceConfig>,
+) -> Result<Json<StopGraceConfig>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting stop grace period for function: {}", name);
+
+    match state.control.put_stop_grace(&name, payload).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to set stop grace period for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_stop_grace(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<StopGraceConfig>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_stop_grace(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get stop grace period for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_stop_grace(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting stop grace period for function: {}", name);
+
+    match state.control.delete_stop_grace(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete stop grace period for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_sandbox(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<FunctionSandbox>,
+) -> Result<Json<FunctionSandbox>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting sandbox profile for function: {}", name);
+
+    match state.control.put_sandbox(&name, payload).await {
+        Ok(sandbox) => Ok(Json(sandbox)),
+        Err(e) => {
+            error!("Failed to set sandbox profile for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_sandbox(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionSandbox>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_sandbox(&name).await {
+        Ok(sandbox) => Ok(Json(sandbox)),
+        Err(e) => {
+            error!("Failed to get sandbox profile for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_sandbox(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting sandbox profile for function: {}", name);
+
+    match state.control.delete_sandbox(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete sandbox profile for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_user(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<FunctionUser>,
+) -> Result<Json<FunctionUser>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting container user for function: {}", name);
+
+    match state.control.put_user(&name, payload).await {
+        Ok(user) => Ok(Json(user)),
+        Err(e) => {
+            error!("Failed to set container user for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_user(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionUser>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_user(&name).await {
+        Ok(user) => Ok(Json(user)),
+        Err(e) => {
+            error!("Failed to get container user for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_user(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting container user for function: {}", name);
+
+    match state.control.delete_user(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete container user for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_aws_emulation(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<AwsEmulation>,
+) -> Result<Json<FunctionAwsEmulation>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting AWS emulation for function: {}", name);
+
+    match state.control.put_aws_emulation(&name, payload).await {
+        Ok(emulation) => Ok(Json(emulation)),
+        Err(e) => {
+            error!("Failed to set AWS emulation for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_aws_emulation(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionAwsEmulation>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_aws_emulation(&name).await {
+        Ok(emulation) => Ok(Json(emulation)),
+        Err(e) => {
+            error!("Failed to get AWS emulation for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_aws_emulation(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting AWS emulation for function: {}", name);
+
+    match state.control.delete_aws_emulation(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete AWS emulation for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_hooks(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<FunctionHooks>,
+) -> Result<Json<FunctionHooks>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting invocation hooks for function: {}", name);
+
+    match state.control.put_hooks(&name, payload).await {
+        Ok(hooks) => Ok(Json(hooks)),
+        Err(e) => {
+            error!("Failed to set invocation hooks for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_hooks(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionHooks>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_hooks(&name).await {
+        Ok(hooks) => Ok(Json(hooks)),
+        Err(e) => {
+            error!("Failed to get invocation hooks for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_hooks(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting invocation hooks for function: {}", name);
+
+    match state.control.delete_hooks(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete invocation hooks for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_locale(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<LocaleConfig>,
+) -> Result<Json<LocaleConfig>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting timezone and locale for function: {}", name);
+
+    match state.control.put_locale(&name, payload).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to set timezone and locale for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_locale(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<LocaleConfig>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_locale(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get timezone and locale for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_locale(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting timezone and locale for function: {}", name);
+
+    match state.control.delete_locale(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete timezone and locale for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_runtime_management(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<RuntimeManagementConfig>,
+) -> Result<Json<RuntimeManagement>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting runtime management for function: {}", name);
+
+    match state.control.put_runtime_management(&name, payload).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to set runtime management for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_runtime_management(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<RuntimeManagement>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_runtime_management(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get runtime management for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_environment(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionEnvironment>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_environment(&name).await {
+        Ok(environment) => Ok(Json(environment)),
+        Err(e) => {
+            error!("Failed to get environment of {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, headers, payload))]
+pub async fn patch_environment(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<PatchEnvironmentRequest>,
+) -> Result<Json<FunctionEnvironment>, (StatusCode, Json<ErrorShape>)> {
+    info!("Updating environment of function: {}", name);
+
+    match state
+        .control
+        .patch_environment(&name, payload, change_actor(&headers))
+        .await
+    {
+        Ok(environment) => Ok(Json(environment)),
+        Err(e) => {
+            error!("Failed to update environment of {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_prewarm(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<PrewarmConfig>,
+) -> Result<Json<PrewarmConfig>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting prewarm settings for function: {}", name);
+
+    match state.control.put_prewarm(&name, payload).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to set prewarm settings for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_prewarm(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<PrewarmConfig>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_prewarm(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get prewarm settings for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_prewarm(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting prewarm settings for function: {}", name);
+
+    match state.control.delete_prewarm(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete prewarm settings for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// `GET /api/functions/{name}/rollout`: progress of replacing the function's containers
+/// after its last memory or environment change
+#[instrument(skip(state))]
+pub async fn get_rollout(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Rollout>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_rollout(&name).await {
+        Ok(rollout) => Ok(Json(rollout)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn put_response_headers(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<ResponseHeadersConfig>,
+) -> Result<Json<ResponseHeadersConfig>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting response headers for function: {}", name);
+
+    match state.control.put_response_headers(&name, payload).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to set response headers for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_response_headers(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ResponseHeadersConfig>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_response_headers(&name).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            error!("Failed to get response headers for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_response_headers(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting response headers for function: {}", name);
+
+    match state.control.delete_response_headers(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete response headers for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_metadata(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<FunctionMetadata>,
+) -> Result<Json<FunctionMetadata>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting metadata for function: {}", name);
+
+    match state.control.put_metadata(&name, payload).await {
+        Ok(metadata) => Ok(Json(metadata)),
+        Err(e) => {
+            error!("Failed to set metadata for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_metadata(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FunctionMetadata>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_metadata(&name).await {
+        Ok(metadata) => Ok(Json(metadata)),
+        Err(e) => {
+            error!("Failed to get metadata for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list_test_events(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ListTestEventsResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.list_test_events(&name).await {
+        Ok(events) => Ok(Json(events)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_test_event(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<CreateTestEventRequest>,
+) -> Result<(StatusCode, Json<TestEvent>), (StatusCode, Json<ErrorShape>)> {
+    info!("Saving test event {} for function: {}", payload.name, name);
+
+    match state.control.create_test_event(&name, payload).await {
+        Ok(event) => Ok((StatusCode::CREATED, Json(event))),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_test_event(
+    State(state): State<AppState>,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<Json<TestEvent>, (StatusCode, Json<ErrorShape>)> {
+    let event_id = parse_test_event_id(&id)?;
+    match state.control.get_test_event(&name, event_id).await {
+        Ok(event) => Ok(Json(event)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_test_event(
+    State(state): State<AppState>,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let event_id = parse_test_event_id(&id)?;
+    match state.control.delete_test_event(&name, event_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+fn parse_test_event_id(id: &str) -> Result<uuid::Uuid, (StatusCode, Json<ErrorShape>)> {
+    uuid::Uuid::parse_str(id).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorShape {
+                error_message: "Invalid test event id".into(),
+                error_type: "BadRequest".into(),
+                stack_trace: None,
+                build_log: None,
+                references: None,
+            }),
+        )
+    })
+}
+
+/// Who made a configuration change, as recorded in the function's history
+pub(crate) fn change_actor(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("X-Lambdah-Actor")
+        .and_then(|h| h.to_str().ok())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+#[instrument(skip(state))]
+pub async fn get_config_history(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ConfigHistoryResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.config_history(&name).await {
+        Ok(history) => Ok(Json(history)),
+        Err(e) => {
+            error!("Failed to get configuration history for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, headers))]
+pub async fn revert_function_configuration(
+    State(state): State<AppState>,
+    Path((name, revision)): Path<(String, u64)>,
+    headers: HeaderMap,
+) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
+    info!(
+        "Reverting configuration of {} to revision {}",
+        name, revision
+    );
+
+    match state
+        .control
+        .revert_function_configuration(&name, revision, change_actor(&headers))
+        .await
+    {
+        Ok(function) => Ok(Json(function)),
+        Err(e) => {
+            error!("Failed to revert configuration of {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_synthetic_check(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<SyntheticCheck>,
+) -> Result<Json<SyntheticCheck>, (StatusCode, Json<ErrorShape>)> {
+    info!("Setting synthetic check for function: {}", name);
+
+    match state.control.put_synthetic_check(&name, payload).await {
+        Ok(check) => Ok(Json(check)),
+        Err(e) => {
+            error!("Failed to set synthetic check for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_synthetic_check(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<SyntheticCheck>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_synthetic_check(&name).await {
+        Ok(check) => Ok(Json(check)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_synthetic_check(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting synthetic check for function: {}", name);
+
+    match state.control.delete_synthetic_check(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete synthetic check for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// `POST /api/functions/{name}/synthetic-check/run`: run the check now instead of waiting
+/// for its interval
+#[instrument(skip(state))]
+pub async fn run_synthetic_check(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<SyntheticCheckRun>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.run_synthetic_check(&name).await {
+        Ok(run) => Ok(Json(run)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_synthetic_check_stats(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<SyntheticCheckStats>, (StatusCode, Json<ErrorShape>)> {
+    let window = params.get("window").map(String::as_str).unwrap_or("24h");
+    let result = match lambda_control::parse_window(window) {
+        Ok(window) => state.control.synthetic_check_stats(&name, window).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(stats) => Ok(Json(stats)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `POST /api/functions/{name}/archive`: move the function to cold storage now
+#[instrument(skip(state))]
+pub async fn archive_function(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
+    match state
+        .control
+        .archive_function(&name, "The function was archived on request.")
+        .await
+    {
+        Ok(function) => Ok(Json(function)),
+        Err(e) => {
+            error!("Failed to archive function {}: {}", name, e);
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(e.to_error_shape()),
+            ))
+        }
+    }
+}
+
+/// `POST /api/functions/{name}/unarchive`: rebuild an archived function's image
+#[instrument(skip(state))]
+pub async fn unarchive_function(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<lambda_models::Function>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.unarchive_function(&name).await {
+        Ok(function) => Ok(Json(function)),
+        Err(e) => {
+            error!("Failed to unarchive function {}: {}", name, e);
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(e.to_error_shape()),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_metadata(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    info!("Deleting metadata for function: {}", name);
+
+    match state.control.delete_metadata(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete metadata for {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// Invoke on behalf of the operator. Function policies only govern invokes from containers
+/// through the runtime API, so none is checked here
+#[instrument(skip(state, headers, body))]
+pub async fn invoke_function(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> InvokeResponse {
+    let started = std::time::Instant::now();
+    let qualifier = params.get("Qualifier").cloned();
+    let response = dispatch_invoke(&state, &name, qualifier, &headers, body).await;
+    if state.control.access_log().enabled() {
+        let (status, request_id) = match &response {
+            Ok((status, response_headers, _)) => (
+                *status,
+                response_headers
+                    .get("X-Amz-Request-Id")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string),
+            ),
+            Err((status, _)) => (*status, None),
+        };
+        state.control.access_log().record(AccessLogEntry {
+            timestamp: chrono::Utc::now(),
+            method: "POST".to_string(),
+            path: format!("/2015-03-31/functions/{name}/invocations"),
+            function_name: name,
+            route_id: None,
+            status: status.as_u16(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            source_ip: source_ip(&headers, connect_info.as_ref()),
+            request_id,
+        });
+    }
+    response
+}
+
+/// Client address for the access log: the first `X-Forwarded-For` hop when behind a proxy,
+/// else the peer address
+fn source_ip(
+    headers: &HeaderMap,
+    connect_info: Option<&ConnectInfo<SocketAddr>>,
+) -> Option<String> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+        .or_else(|| connect_info.map(|ConnectInfo(addr)| addr.ip().to_string()))
+}
+
+async fn dispatch_invoke(
+    state: &AppState,
+    name: &str,
+    qualifier: Option<String>,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> InvokeResponse {
+    // Parse invocation type from headers
+    let invocation_type = headers
+        .get("X-Amz-Invocation-Type")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(lambda_models::InvocationType::RequestResponse);
+
+    let log_type = headers
+        .get("X-Amz-Log-Type")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse().ok());
+
+    // Opt-in to automatic retry when the container fails mid-invoke
+    let idempotent = headers
+        .get("X-Lambdah-Idempotent")
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|s| s.eq_ignore_ascii_case("true"));
+
+    // Record the runtime API exchange, read back at /api/executions/{id}/trace
+    let trace = headers
+        .get("X-Lambdah-Trace")
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|s| s.eq_ignore_ascii_case("true"));
+
+    // Event invocations are queued and retried in the background; their result is kept for polling
+    let is_event = invocation_type == lambda_models::InvocationType::Event;
+
+    // Return a job to poll instead of waiting for the result
+    let as_job = headers
+        .get("X-Lambdah-Job")
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|s| s.eq_ignore_ascii_case("true"));
+
+    // Run synchronous invokes on the replication peer while this instance is unhealthy,
+    // unless the peer already forwarded this one. Published versions are not replicated
+    if !as_job && !is_event && qualifier.is_none() && !headers.contains_key(FORWARDED_HEADER) {
+        if let Some(peer) = state.control.failover_peer() {
+            if let Some(response) = forward_invoke(state, &peer, name, headers, &body).await {
+                return response;
+            }
+        }
+    }
+
+    let request = InvokeRequest {
+        function_name: name.to_string(),
+        invocation_type,
+        log_type,
+        client_context: None,
+        payload: invoke_payload(&body),
+        qualifier,
+        idempotent,
+        trace,
+    };
+
+    if as_job || is_event {
+        let job = if is_event {
+            state.control.enqueue_event(request).await
+        } else {
+            state.control.start_job(request).await
+        };
+        return match job {
+            Ok(job) => {
+                let mut response_headers = HeaderMap::new();
+                if let Ok(header_value) = HeaderValue::from_str(&job.job_id) {
+                    response_headers.insert("X-Amz-Request-Id", header_value);
+                }
+                if is_event {
+                    return Ok((
+                        StatusCode::ACCEPTED,
+                        response_headers,
+                        Json(serde_json::Value::Null).into_response(),
+                    ));
+                }
+                if let Ok(header_value) =
+                    HeaderValue::from_str(&format!("/api/jobs/{}", job.job_id))
+                {
+                    response_headers.insert("Location", header_value);
+                }
+                let body = serde_json::to_value(&job).unwrap_or(serde_json::Value::Null);
+                Ok((
+                    StatusCode::ACCEPTED,
+                    response_headers,
+                    Json(body).into_response(),
+                ))
+            }
+            Err(e) => {
+                error!("Failed to start job for function {}: {}", name, e);
+                let error_shape = e.to_error_shape();
+                Err((
+                    StatusCode::from_u16(e.http_status())
+                        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                    Json(error_shape),
+                ))
+            }
+        };
+    }
+
+    let result = match sync_invoke(state, request, state.config.sync_invoke.invoke).await {
+        SyncInvoke::Finished(result) => result,
+        SyncInvoke::Detached {
+            request_id,
+            waited_secs,
+        } => {
+            let (status, response_headers, body) = result_pending(request_id, waited_secs);
+            return Ok((status, response_headers, body.into_response()));
+        }
+        // Not a mode of the invoke API, which cannot answer before the result
+        SyncInvoke::Streaming(mut pending) => pending.result().await,
+    };
+    match result {
+        Ok(response) => {
+            let mut response_headers = invoke_response_headers(&response);
+
+            let mut payload = response.payload.unwrap_or(serde_json::Value::Null);
+            let mut custom_headers = response.headers;
+            // Successful results get the configured default headers and may carry their own
+            if response.function_error.is_none() {
+                let (body, headers) = state.control.shape_invoke_response(name, payload).await;
+                payload = body;
+                for (key, value) in headers {
+                    custom_headers.entry(key).or_insert(value);
+                }
+            }
+
+            // Add custom headers
+            for (key, value) in custom_headers {
+                if let Ok(header_name) = HeaderName::from_bytes(key.as_bytes()) {
+                    if let Ok(header_value) = HeaderValue::from_str(&value) {
+                        response_headers.insert(header_name, header_value);
+                    }
+                }
+            }
+
+            let status_code = StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::OK);
+            let body = invoke_body(&response_headers, payload);
+
+            Ok((status_code, response_headers, body))
+        }
+        Err(e) => {
+            error!("Failed to invoke function {}: {}", name, e);
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// Invoke payload from a request body; a body that is not JSON is passed as a string
+fn invoke_payload(body: &[u8]) -> Option<serde_json::Value> {
+    if body.is_empty() {
+        return None;
+    }
+    Some(
+        serde_json::from_slice(body).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(body).to_string())
+        }),
+    )
+}
+
+/// `X-Amz-*` headers describing how an invocation went
+fn invoke_response_headers(response: &lambda_models::InvokeResponse) -> HeaderMap {
+    let mut response_headers = HeaderMap::new();
+
+    if let Some(executed_version) = &response.executed_version {
+        if let Ok(header_value) = HeaderValue::from_str(executed_version) {
+            response_headers.insert("X-Amz-Executed-Version", header_value);
+        }
+    }
+
+    if let Some(log_result) = &response.log_result {
+        if let Ok(header_value) = HeaderValue::from_str(log_result) {
+            response_headers.insert("X-Amz-Log-Result", header_value);
+        }
+    }
+
+    if let Some(function_error) = &response.function_error {
+        let error_str = match function_error {
+            FunctionError::Handled => "Handled",
+            FunctionError::Unhandled => "Unhandled",
+        };
+        if let Ok(header_value) = HeaderValue::from_str(error_str) {
+            response_headers.insert("X-Amz-Function-Error", header_value);
+        }
+    }
+
+    if let Some(duration_ms) = &response.duration_ms {
+        if let Ok(header_value) = HeaderValue::from_str(&duration_ms.to_string()) {
+            response_headers.insert("X-Amz-Duration", header_value);
+        }
+    }
+    response_headers
+}
+
+/// `POST /2021-11-15/functions/{name}/response-streaming-invocations`: invoke and send the
+/// response on in chunks as the function streams it. A function that posts its response
+/// whole is answered as by the invoke API
+#[instrument(skip(state, headers, body))]
+pub async fn invoke_with_response_stream(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let started = std::time::Instant::now();
+    let request = InvokeRequest {
+        function_name: name.clone(),
+        invocation_type: lambda_models::InvocationType::RequestResponse,
+        log_type: headers
+            .get("X-Amz-Log-Type")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.parse().ok()),
+        client_context: None,
+        payload: invoke_payload(&body),
+        qualifier: params.get("Qualifier").cloned(),
+        idempotent: false,
+        trace: headers
+            .get("X-Lambdah-Trace")
+            .and_then(|h| h.to_str().ok())
+            .is_some_and(|s| s.eq_ignore_ascii_case("true")),
+    };
+    let stream = state.control.invoke_with_response_stream(request);
+    let request_id = stream.request_id.clone();
+    let response = response_stream(stream).await;
+    if state.control.access_log().enabled() {
+        state.control.access_log().record(AccessLogEntry {
+            timestamp: chrono::Utc::now(),
+            method: "POST".to_string(),
+            path: format!("/2021-11-15/functions/{name}/response-streaming-invocations"),
+            function_name: name,
+            route_id: None,
+            status: response.status().as_u16(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            source_ip: source_ip(&headers, connect_info.as_ref()),
+            request_id: Some(request_id),
+        });
+    }
+    response
+}
+
+/// Answer with the chunks of `stream` as they arrive. The body is cut short when the
+/// function fails after it started streaming, since the status was already sent
+async fn response_stream(stream: ResponseStream) -> Response {
+    let ResponseStream {
+        request_id,
+        mut parts,
+        mut result,
+    } = stream;
+    // Wait for the runtime to start streaming, or for a response it posted whole
+    let content_type = tokio::select! {
+        biased;
+        Some(StreamPart::Start { content_type }) = parts.recv() => content_type,
+        result = &mut result => {
+            let result = result.unwrap_or_else(|e| {
+                Err(LambdaError::InternalError {
+                    reason: format!("Invocation {request_id} ended without a result: {e}"),
+                })
+            });
+            return match result {
+                Ok(response) => {
+                    let mut headers = invoke_response_headers(&response);
+                    for (key, value) in &response.headers {
+                        if let (Ok(name), Ok(value)) = (
+                            HeaderName::from_bytes(key.as_bytes()),
+                            HeaderValue::from_str(value),
+                        ) {
+                            headers.insert(name, value);
+                        }
+                    }
+                    let status = StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::OK);
+                    let payload = response.payload.unwrap_or(serde_json::Value::Null);
+                    (status, headers, Json(payload)).into_response()
+                }
+                Err(e) => (
+                    StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                    Json(e.to_error_shape()),
+                )
+                    .into_response(),
+            };
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Ok(header_value) = HeaderValue::from_str(&request_id) {
+        headers.insert("X-Amz-Request-Id", header_value);
+    }
+    let content_type = content_type
+        .and_then(|v| HeaderValue::from_str(&v).ok())
+        .unwrap_or_else(|| HeaderValue::from_static("application/octet-stream"));
+    headers.insert(axum::http::header::CONTENT_TYPE, content_type);
+    let body = futures::stream::unfold(Some((parts, result)), |streaming| async move {
+        let (mut parts, result) = streaming?;
+        while let Some(part) = parts.recv().await {
+            if let StreamPart::Chunk(chunk) = part {
+                return Some((Ok(Bytes::from(chunk)), Some((parts, result))));
+            }
+        }
+        match result.await {
+            Ok(Ok(response)) if response.function_error.is_none() => None,
+            _ => Some((
+                Err(std::io::Error::other(
+                    "The function failed while streaming its response",
+                )),
+                None,
+            )),
+        }
+    });
+    (StatusCode::OK, headers, Body::from_stream(body)).into_response()
+}
+
+/// Send an invoke to the replication peer and relay its answer; `None` when the peer could
+/// not be reached, to run it locally after all
+async fn forward_invoke(
+    state: &AppState,
+    peer: &PeerClient,
+    name: &str,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Option<InvokeResponse> {
+    let request_headers = headers
+        .iter()
+        .filter(|(k, _)| forwarded_header(k.as_str()) || k.as_str() == "content-type")
+        .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+        .collect();
+    let response = match peer
+        .forward_invoke(name, request_headers, body.to_vec())
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Failed to forward invoke of {} to the peer: {}", name, e);
+            state.control.record_forwarded_invoke(name, true);
+            return None;
+        }
+    };
+    state.control.record_forwarded_invoke(name, false);
+
+    let mut response_headers = HeaderMap::new();
+    for (key, value) in &response.headers {
+        // The peer's response headers, such as a function's defaults, come along too
+        if !forwarded_header(key) && check_response_header(key, value).is_err() {
+            continue;
+        }
+        if let (Ok(k), Ok(v)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            response_headers.insert(k, v);
+        }
+    }
+    response_headers.insert(FORWARDED_HEADER, HeaderValue::from_static("true"));
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::BAD_GATEWAY);
+    let payload = if response.body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(&response.body).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(&response.body).to_string())
+        })
+    };
+    if status.is_success() {
+        let body = invoke_body(&response_headers, payload);
+        Some(Ok((status, response_headers, body)))
+    } else {
+        // Errors keep the peer's error body
+        let error_shape = serde_json::from_value(payload.clone()).unwrap_or(ErrorShape {
+            error_message: payload.to_string(),
+            error_type: "ServiceException".to_string(),
+            stack_trace: None,
+            build_log: None,
+            references: None,
+        });
+        Some(Err((status, Json(error_shape))))
+    }
+}
+
+/// Response body of an invoke: JSON, except a string result sent with a non-JSON
+/// `Content-Type`, which goes out as is so e.g. `text/html` results are not quoted
+fn invoke_body(headers: &HeaderMap, payload: serde_json::Value) -> Response {
+    let raw = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| !v.to_ascii_lowercase().contains("json"));
+    match payload {
+        serde_json::Value::String(text) if raw => text.into_response(),
+        payload => Json(payload).into_response(),
+    }
+}
+
+/// Headers passed through in both directions when forwarding an invoke
+fn forwarded_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("x-amz-") || name.starts_with("x-lambdah-")
+}
+
+/// `GET /api/executions/{id}/result`: outcome of an Event invocation
+#[instrument(skip(state))]
+pub async fn get_execution_result(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+) -> Result<Json<Job>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_invocation_result(&request_id).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+/// `GET /api/jobs/{id}`: status of a job, with the result once it has finished
+#[instrument(skip(state))]
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<Job>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_job(&job_id).await {
+        Ok(job) => Ok(Json(job)),
+        Err(e) => {
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(_state))]
+pub async fn health_check(State(_state): State<AppState>) -> Result<&'static str, StatusCode> {
+    Ok("OK")
+}
+
+/// Ready unless the circuit breaker has suspended Docker calls, which answers 503
+pub async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<DockerApiStatus>) {
+    let status = state.control.docker_api_status();
+    let code = if status.state == DockerBreakerState::Open {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (code, Json(status))
+}
+
+#[instrument(skip(state))]
+pub async fn metrics(State(state): State<AppState>) -> Result<String, StatusCode> {
+    let warmup = state
+        .metrics
+        .render_warmup_metrics(&state.control.warmup_stats());
+    let disk = state
+        .metrics
+        .render_disk_metrics(&state.control.disk_status());
+    let replication = state
+        .metrics
+        .render_replication_metrics(&state.control.replication_status());
+    let docker = state
+        .metrics
+        .render_docker_metrics(&state.control.docker_api_status());
+    let concurrency = state
+        .metrics
+        .render_concurrency_metrics(&state.control.concurrency_overview());
+    match state.metrics.get_prometheus_metrics().await {
+        Ok(mut metrics) => {
+            match warmup {
+                Ok(warmup) => metrics.push_str(&warmup),
+                Err(e) => error!("Failed to render warmup metrics: {}", e),
+            }
+            match disk {
+                Ok(disk) => metrics.push_str(&disk),
+                Err(e) => error!("Failed to render disk metrics: {}", e),
+            }
+            match replication {
+                Ok(replication) => metrics.push_str(&replication),
+                Err(e) => error!("Failed to render replication metrics: {}", e),
+            }
+            match docker {
+                Ok(docker) => metrics.push_str(&docker),
+                Err(e) => error!("Failed to render Docker metrics: {}", e),
+            }
+            match concurrency {
+                Ok(concurrency) => metrics.push_str(&concurrency),
+                Err(e) => error!("Failed to render concurrency metrics: {}", e),
+            }
+            Ok(metrics)
+        }
+        Err(e) => {
+            error!("Failed to get metrics: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_execution(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+) -> Result<Json<ExecutionRecord>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_execution(&request_id).await {
+        Ok(record) => Ok(Json(record)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/executions/{id}/trace`: runtime API exchange of an invocation run with
+/// `X-Lambdah-Trace: true`, as a JSON download
+#[instrument(skip(state))]
+pub async fn get_execution_trace(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+) -> Result<(HeaderMap, Json<InvocationTrace>), (StatusCode, Json<ErrorShape>)> {
+    match state.control.get_execution_trace(&request_id).await {
+        Ok(trace) => {
+            let mut headers = HeaderMap::new();
+            let disposition = format!("attachment; filename=\"trace-{request_id}.json\"");
+            if let Ok(value) = HeaderValue::from_str(&disposition) {
+                headers.insert(axum::http::header::CONTENT_DISPOSITION, value);
+            }
+            Ok((headers, Json(trace)))
+        }
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/namespaces/{ns}/usage`: what the namespace uses of each quota
+#[instrument(skip(state))]
+pub async fn get_namespace_usage(
+    State(state): State<AppState>,
+    Path(namespace): Path<String>,
+) -> Result<Json<NamespaceUsage>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.namespace_usage(&namespace).await {
+        Ok(usage) => Ok(Json(usage)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_error_summary(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ErrorSummary>, (StatusCode, Json<ErrorShape>)> {
+    let window = params.get("window").map(String::as_str).unwrap_or("24h");
+    let result = match lambda_control::parse_window(window) {
+        Ok(window) => state.control.error_summary(&name, window).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(summary) => Ok(Json(summary)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/functions/{name}/insights?window=24h`: whether the function is getting worse
+#[instrument(skip(state))]
+pub async fn get_function_insights(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<FunctionInsights>, (StatusCode, Json<ErrorShape>)> {
+    let window = params.get("window").map(String::as_str).unwrap_or("24h");
+    let result = match lambda_control::parse_window(window) {
+        Ok(window) => state.control.function_insights(&name, window).await,
+        Err(e) => Err(e),
     };
+    match result {
+        Ok(insights) => Ok(Json(insights)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-    match state.control.invoke_function(request).await {
-        Ok(response) => {
-            let mut response_headers = HeaderMap::new();
+/// `POST /api/cost-estimate`: what the recorded executions would cost on AWS Lambda
+#[instrument(skip(state))]
+pub async fn estimate_cost(
+    State(state): State<AppState>,
+    Json(request): Json<CostEstimateRequest>,
+) -> Result<Json<CostEstimate>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.cost_estimate(request).await {
+        Ok(estimate) => Ok(Json(estimate)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-            if let Some(executed_version) = &response.executed_version {
-                if let Ok(header_value) = HeaderValue::from_str(executed_version) {
-                    response_headers.insert("X-Amz-Executed-Version", header_value);
-                }
-            }
+/// Environment diagnostics; runs a short-lived probe container, so it takes a few seconds
+#[instrument(skip(state))]
+pub async fn get_doctor_report(State(state): State<AppState>) -> Json<DoctorReport> {
+    Json(state.control.doctor().await)
+}
 
-            if let Some(log_result) = &response.log_result {
-                if let Ok(header_value) = HeaderValue::from_str(log_result) {
-                    response_headers.insert("X-Amz-Log-Result", header_value);
-                }
-            }
+#[instrument(skip(state))]
+pub async fn get_dashboard(
+    State(state): State<AppState>,
+) -> Result<Json<Dashboard>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.dashboard().await {
+        Ok(dashboard) => Ok(Json(dashboard)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-            if let Some(function_error) = &response.function_error {
-                let error_str = match function_error {
-                    FunctionError::Handled => "Handled",
-                    FunctionError::Unhandled => "Unhandled",
-                };
-                if let Ok(header_value) = HeaderValue::from_str(error_str) {
-                    response_headers.insert("X-Amz-Function-Error", header_value);
-                }
-            }
+/// `GET /api/search?q=...&limit=...`
+#[instrument(skip(state))]
+pub async fn search(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<SearchResponse>, (StatusCode, Json<ErrorShape>)> {
+    let query = params.get("q").map(String::as_str).unwrap_or_default();
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(lambda_control::DEFAULT_SEARCH_LIMIT);
+    match state.control.search(query, limit).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/changes?cursor=...&limit=...&entities=function,route&wait=...`
+#[instrument(skip(state))]
+pub async fn list_changes(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ChangeFeedResponse>, (StatusCode, Json<ErrorShape>)> {
+    let result = match change_feed_query(&params) {
+        Ok((cursor, limit, entities, wait)) => {
+            state
+                .control
+                .change_feed()
+                .read(cursor, limit, &entities, wait)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /admin/consistency`: routes and mappings that name a function which no longer exists
+#[instrument(skip(state))]
+pub async fn get_consistency_report(
+    State(state): State<AppState>,
+) -> Result<Json<ConsistencyReport>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.consistency_report().await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn get_replication_status(State(state): State<AppState>) -> Json<ReplicationStatus> {
+    Json(state.control.replication_status())
+}
+
+/// `POST /admin/replication/health`: a peer reports its health and gets this instance's
+#[instrument(skip(state))]
+pub async fn exchange_replication_health(
+    State(state): State<AppState>,
+    Json(health): Json<InstanceHealth>,
+) -> Json<InstanceHealth> {
+    Json(state.control.receive_peer_health(health).await)
+}
+
+/// `POST /admin/replication/sync?force=true`: run a sync round now; `force` overwrites
+/// conflicting copies on the peer
+#[instrument(skip(state))]
+pub async fn sync_replication(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ReplicationStatus>, (StatusCode, Json<ErrorShape>)> {
+    let force = params.get("force").is_some_and(|f| f == "true");
+    match state.control.replicate(force).await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+type ChangeFeedQuery = (ChangeCursor, u32, Vec<ChangeEntity>, Duration);
+
+/// Read change feed query parameters: `cursor` (a sequence or `latest`, oldest retained event
+/// when absent), `limit`, `entities` (comma separated) and `wait` in seconds
+fn change_feed_query(params: &HashMap<String, String>) -> Result<ChangeFeedQuery, LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    let cursor = ChangeCursor::parse(params.get("cursor").map(String::as_str))?;
+    let limit = match params.get("limit").filter(|v| !v.is_empty()) {
+        Some(v) => v
+            .parse::<u32>()
+            .map_err(|_| invalid(format!("Invalid limit: {v}")))?,
+        None => DEFAULT_CHANGE_LIMIT,
+    };
+    let entities = params
+        .get("entities")
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .map(|e| e.parse::<ChangeEntity>().map_err(invalid))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let wait = match params.get("wait").filter(|v| !v.is_empty()) {
+        Some(v) => Duration::from_secs(
+            v.parse::<u64>()
+                .map_err(|_| invalid(format!("Invalid wait: {v}")))?,
+        ),
+        None => Duration::ZERO,
+    };
+    Ok((cursor, limit, entities, wait))
+}
+
+#[instrument(skip(state))]
+pub async fn get_warmup_stats(State(state): State<AppState>) -> Json<WarmupStats> {
+    Json(state.control.warmup_stats())
+}
+
+#[instrument(skip(state))]
+pub async fn get_disk_stats(State(state): State<AppState>) -> Json<DiskStatus> {
+    Json(state.control.disk_status())
+}
+
+/// `GET /api/system/database`: database size and rows per table
+#[instrument(skip(state))]
+pub async fn get_database_status(
+    State(state): State<AppState>,
+) -> Result<Json<DatabaseStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.database_status().await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/system/schema`: schema version of the database and pending migrations
+#[instrument(skip(state))]
+pub async fn get_schema_status(
+    State(state): State<AppState>,
+) -> Result<Json<SchemaStatus>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.schema_status().await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// `GET /api/system/info`: version, features, runtimes, limits and container backend
+#[instrument(skip(state))]
+pub async fn get_system_info(State(state): State<AppState>) -> Json<SystemInfo> {
+    Json(state.control.system_info().await)
+}
+
+/// `GET /api/builds`: image builds running and queued
+#[instrument(skip(state))]
+pub async fn list_builds(State(state): State<AppState>) -> Json<ListBuildsResponse> {
+    Json(state.control.list_builds())
+}
+
+/// `DELETE /api/builds/{id}`: cancel a queued or running image build
+#[instrument(skip(state))]
+pub async fn cancel_build(
+    State(state): State<AppState>,
+    Path(build_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    match state.control.cancel_build(&build_id) {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let error_shape = e.to_error_shape();
+            Err((
+                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(error_shape),
+            ))
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn warm_pool_summary(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<lambda_control::warm_pool::WarmPoolSummary>, (StatusCode, Json<ErrorShape>)> {
+    let summary = state.control.warm_pool().summary_for_function(&name).await;
+    Ok(Json(summary))
+}
+
+// -------- API Gateway routes admin --------
+#[instrument(skip(state))]
+pub async fn list_api_routes(
+    State(state): State<AppState>,
+) -> Result<Json<ListApiRoutesResponse>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.list_api_routes().await {
+        Ok(resp) => Ok(Json(resp)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+/// OpenAPI document of every configured route, with the schemas functions bundle
+#[instrument(skip(state))]
+pub async fn gateway_openapi(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.gateway_openapi().await {
+        Ok(document) => Ok(Json(document)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_api_route(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateApiRouteRequest>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    match state.control.create_api_route(payload).await {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_api_route(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteCanary>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.put_api_route_canary(uuid, payload).await {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_api_route_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_canary(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_access_log(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteAccessLog>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state
+        .control
+        .set_api_route_access_log(uuid, payload.enabled)
+        .await
+    {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_transform(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteTransform>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.put_api_route_transform(uuid, payload).await {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn delete_api_route_transform(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_transform(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-            if let Some(duration_ms) = &response.duration_ms {
-                if let Ok(header_value) = HeaderValue::from_str(&duration_ms.to_string()) {
-                    response_headers.insert("X-Amz-Duration", header_value);
-                }
-            }
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_invocation(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteInvocation>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.put_api_route_invocation(uuid, payload).await {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-            // Add custom headers
-            for (key, value) in response.headers {
-                if let Ok(header_name) = HeaderName::from_bytes(key.as_bytes()) {
-                    if let Ok(header_value) = HeaderValue::from_str(&value) {
-                        response_headers.insert(header_name, header_value);
-                    }
-                }
-            }
+#[instrument(skip(state))]
+pub async fn delete_api_route_invocation(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_invocation(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-            let status_code = StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::OK);
-            let payload = response.payload.unwrap_or(serde_json::Value::Null);
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_jwt_authorizer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteJwtAuthorizer>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state
+        .control
+        .put_api_route_jwt_authorizer(uuid, payload)
+        .await
+    {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
 
-            Ok((status_code, response_headers, Json(payload)))
-        }
-        Err(e) => {
-            error!("Failed to invoke function {}: {}", name, e);
-            let error_shape = e.to_error_shape();
-            Err((
-                StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-                Json(error_shape),
-            ))
-        }
+#[instrument(skip(state))]
+pub async fn delete_api_route_jwt_authorizer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_jwt_authorizer(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
     }
 }
 
-#[instrument(skip(_state))]
-pub async fn health_check(State(_state): State<AppState>) -> Result<&'static str, StatusCode> {
-    Ok("OK")
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_mock(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteMock>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.put_api_route_mock(uuid, payload).await {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
 }
 
 #[instrument(skip(state))]
-pub async fn metrics(State(state): State<AppState>) -> Result<String, StatusCode> {
-    match state.metrics.get_prometheus_metrics().await {
-        Ok(metrics) => Ok(metrics),
-        Err(e) => {
-            error!("Failed to get metrics: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+pub async fn delete_api_route_mock(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_mock(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
     }
 }
 
-#[instrument(skip(state))]
-pub async fn warm_pool_summary(
+#[instrument(skip(state, payload))]
+pub async fn put_api_route_recording(
     State(state): State<AppState>,
-    Path(name): Path<String>,
-) -> Result<Json<lambda_control::warm_pool::WarmPoolSummary>, (StatusCode, Json<ErrorShape>)> {
-    let summary = state.control.warm_pool().summary_for_function(&name).await;
-    Ok(Json(summary))
+    Path(id): Path<String>,
+    Json(payload): Json<RouteRecording>,
+) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.put_api_route_recording(uuid, payload).await {
+        Ok(route) => Ok(Json(route)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
 }
 
-// -------- API Gateway routes admin --------
 #[instrument(skip(state))]
-pub async fn list_api_routes(
+pub async fn delete_api_route_recording(
     State(state): State<AppState>,
-) -> Result<Json<ListApiRoutesResponse>, (StatusCode, Json<ErrorShape>)> {
-    match state.control.list_api_routes().await {
-        Ok(resp) => Ok(Json(resp)),
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_recording(uuid).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err((
             StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
             Json(e.to_error_shape()),
@@ -561,11 +3786,13 @@ pub async fn list_api_routes(
 }
 
 #[instrument(skip(state, payload))]
-pub async fn create_api_route(
+pub async fn put_api_route_limits(
     State(state): State<AppState>,
-    Json(payload): Json<CreateApiRouteRequest>,
+    Path(id): Path<String>,
+    Json(payload): Json<RouteLimits>,
 ) -> Result<Json<ApiRoute>, (StatusCode, Json<ErrorShape>)> {
-    match state.control.create_api_route(payload).await {
+    let uuid = parse_route_id(&id)?;
+    match state.control.put_api_route_limits(uuid, payload).await {
         Ok(route) => Ok(Json(route)),
         Err(e) => Err((
             StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
@@ -575,24 +3802,12 @@ pub async fn create_api_route(
 }
 
 #[instrument(skip(state))]
-pub async fn delete_api_route(
+pub async fn delete_api_route_limits(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorShape>)> {
-    let uuid = match uuid::Uuid::parse_str(&id) {
-        Ok(u) => u,
-        Err(_) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorShape {
-                    error_message: "Invalid route id".into(),
-                    error_type: "BadRequest".into(),
-                    stack_trace: None,
-                }),
-            ))
-        }
-    };
-    match state.control.delete_api_route(uuid).await {
+    let uuid = parse_route_id(&id)?;
+    match state.control.delete_api_route_limits(uuid).await {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err((
             StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
@@ -601,6 +3816,177 @@ pub async fn delete_api_route(
     }
 }
 
+#[instrument(skip(state))]
+pub async fn get_api_route_metrics(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<RouteMetrics>, (StatusCode, Json<ErrorShape>)> {
+    let uuid = parse_route_id(&id)?;
+    match state.control.api_route_metrics(uuid).await {
+        Ok(metrics) => Ok(Json(metrics)),
+        Err(e) => Err((
+            StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(e.to_error_shape()),
+        )),
+    }
+}
+
+fn parse_route_id(id: &str) -> Result<uuid::Uuid, (StatusCode, Json<ErrorShape>)> {
+    uuid::Uuid::parse_str(id).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorShape {
+                error_message: "Invalid route id".into(),
+                error_type: "BadRequest".into(),
+                stack_trace: None,
+                build_log: None,
+                references: None,
+            }),
+        )
+    })
+}
+
+/// Requests to a function URL, `/lambda-url/{url_id}/...`: the function gets the request as
+/// a payload format 2.0 event, and its result is read back the same way
+#[instrument(skip(state, req))]
+pub async fn function_url_proxy(State(state): State<AppState>, req: Request<Body>) -> Response {
+    let started = std::time::Instant::now();
+    let uri = req.uri().clone();
+    let below_prefix = uri
+        .path()
+        .strip_prefix(function_urls::URL_PATH_PREFIX)
+        .unwrap_or_default();
+    let (url_id, path) = match below_prefix.find('/') {
+        Some(slash) => (&below_prefix[..slash], &below_prefix[slash..]),
+        None => (below_prefix, "/"),
+    };
+    let (function, qualifier) = match state.control.resolve_function_url(url_id).await {
+        Ok(target) => target,
+        Err(e) => return gateway_response(Err(e)),
+    };
+    let source_ip = source_ip(
+        req.headers(),
+        req.extensions().get::<ConnectInfo<SocketAddr>>(),
+    );
+    let method = req.method().to_string();
+    let headers: Vec<(String, String)> = req
+        .headers()
+        .iter()
+        .filter_map(|(k, v)| v.to_str().ok().map(|s| (k.to_string(), s.to_string())))
+        .collect();
+    let Ok(body) = axum::body::to_bytes(req.into_body(), 1024 * 1024).await else {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Body::from("Request body too large"),
+        )
+            .into_response();
+    };
+    let event = url_event(&UrlRequest {
+        url_id,
+        method: &method,
+        path,
+        raw_query: uri.query().unwrap_or_default(),
+        headers: &headers,
+        body: &body,
+        source_ip: source_ip.as_deref(),
+        request_id: &uuid::Uuid::new_v4().to_string(),
+        account_id: &state.control.identity().account_id,
+        time: chrono::Utc::now(),
+    });
+
+    let request = lambda_models::InvokeRequest {
+        function_name: function.function_name.clone(),
+        invocation_type: lambda_models::InvocationType::RequestResponse,
+        log_type: None,
+        client_context: None,
+        payload: Some(event),
+        qualifier,
+        idempotent: false,
+        trace: false,
+    };
+    let (request_id, response) =
+        match sync_invoke(&state, request, state.config.sync_invoke.gateway).await {
+            SyncInvoke::Finished(result) => (
+                result
+                    .as_ref()
+                    .ok()
+                    .and_then(|resp| resp.headers.get("X-Amz-Request-Id").cloned()),
+                function_url_response(result),
+            ),
+            SyncInvoke::Detached {
+                request_id,
+                waited_secs,
+            } => (
+                Some(request_id.clone()),
+                result_pending(request_id, waited_secs).into_response(),
+            ),
+            SyncInvoke::Streaming(pending) => (
+                Some(pending.request_id.clone()),
+                keep_alive_response(
+                    pending,
+                    Duration::from_secs(state.config.sync_invoke.heartbeat_secs),
+                    (),
+                ),
+            ),
+        };
+    state.control.access_log().record(AccessLogEntry {
+        timestamp: chrono::Utc::now(),
+        method,
+        path: uri.path().to_string(),
+        function_name: function.function_name,
+        route_id: None,
+        status: response.status().as_u16(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        source_ip,
+        request_id,
+    });
+    response
+}
+
+/// Map an invoke result to a function URL's response. A function error answers 502 with
+/// the error the function reported.
+fn function_url_response(result: Result<lambda_models::InvokeResponse, LambdaError>) -> Response {
+    let resp = match result {
+        Ok(resp) => resp,
+        Err(e) => return gateway_response(Err(e)),
+    };
+    let mut headers = HeaderMap::new();
+    if let Some(request_id) = resp
+        .headers
+        .get("X-Amz-Request-Id")
+        .and_then(|id| HeaderValue::from_str(id).ok())
+    {
+        headers.insert("X-Amz-Request-Id", request_id);
+    }
+    if resp.function_error.is_some() {
+        return (
+            StatusCode::BAD_GATEWAY,
+            headers,
+            Json(resp.payload.unwrap_or(serde_json::Value::Null)),
+        )
+            .into_response();
+    }
+    let url = match url_response(resp.payload.as_ref()) {
+        Ok(url) => url,
+        Err(e) => return gateway_response(Err(e)),
+    };
+    for (name, value) in &url.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+    for cookie in &url.cookies {
+        if let Ok(value) = HeaderValue::from_str(cookie) {
+            headers.append(axum::http::header::SET_COOKIE, value);
+        }
+    }
+    let status = StatusCode::from_u16(url.status).unwrap_or(StatusCode::OK);
+    (status, headers, Body::from(url.body)).into_response()
+}
+
 // API Gateway-style proxy: path name equals function name
 // Captures any unmatched path and invokes a function named by the first segment.
 #[instrument(skip(state, req))]
@@ -608,8 +3994,17 @@ pub async fn api_gateway_proxy(
     State(state): State<AppState>,
     req: Request<Body>,
 ) -> impl IntoResponse {
+    let started = std::time::Instant::now();
     let uri = req.uri().clone();
     let path = uri.path().to_string();
+    let source_ip = source_ip(
+        req.headers(),
+        req.extensions().get::<ConnectInfo<SocketAddr>>(),
+    );
+    let peer_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
 
     // Skip API routes that should be handled by explicit routes
     if path.starts_with("/api/")
@@ -623,15 +4018,14 @@ pub async fn api_gateway_proxy(
     let mut segs = path.trim_start_matches('/').split('/');
     // First try to resolve via configured API routes (longest prefix, optional method)
     let method_str = req.method().to_string();
-    let resolved = state
+    let route = state
         .control
-        .resolve_api_route(&method_str, &path)
+        .match_api_route(&method_str, &path)
         .await
         .unwrap_or_default();
-    let mut from_mapping = false;
-    let func_name = if let Some(f) = resolved {
-        from_mapping = true;
-        f
+    let from_mapping = route.is_some();
+    let func_name = if let Some(route) = &route {
+        route.function_name.clone()
     } else {
         match segs.next() {
             Some(s) if !s.is_empty() => s.to_string(),
@@ -639,6 +4033,74 @@ pub async fn api_gateway_proxy(
         }
     };
 
+    // A route with a JWT authorizer only lets through requests bearing a valid token
+    let mut jwt = None;
+    if let Some((route, authorizer)) = route
+        .as_ref()
+        .and_then(|r| r.jwt_authorizer.as_ref().map(|a| (r, a)))
+    {
+        let authorization = req
+            .headers()
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        match state
+            .control
+            .authorize_route_request(authorizer, authorization)
+            .await
+        {
+            Ok(context) => jwt = Some(context),
+            Err(reason) => {
+                debug!("Rejected request to route {}: {}", route.route_id, reason);
+                if route.access_log {
+                    state.control.access_log().record(AccessLogEntry {
+                        timestamp: chrono::Utc::now(),
+                        method: method_str,
+                        path,
+                        function_name: func_name,
+                        route_id: Some(route.route_id),
+                        status: StatusCode::UNAUTHORIZED.as_u16(),
+                        latency_ms: started.elapsed().as_millis() as u64,
+                        source_ip,
+                        request_id: None,
+                    });
+                }
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [
+                        (axum::http::header::CONTENT_TYPE, "application/json"),
+                        (axum::http::header::WWW_AUTHENTICATE, "Bearer"),
+                    ],
+                    Body::from(r#"{"message":"Unauthorized"}"#),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    // A mocked route answers with its static response instead of invoking the function
+    if let Some(route) = &route {
+        if let Some(mock) = route.mock.as_ref().filter(|m| m.enabled) {
+            if mock.latency_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(mock.latency_ms)).await;
+            }
+            let response = mock_response(mock);
+            if route.access_log {
+                state.control.access_log().record(AccessLogEntry {
+                    timestamp: chrono::Utc::now(),
+                    method: method_str,
+                    path,
+                    function_name: func_name,
+                    route_id: Some(route.route_id),
+                    status: response.status().as_u16(),
+                    latency_ms: started.elapsed().as_millis() as u64,
+                    source_ip,
+                    request_id: None,
+                });
+            }
+            return response;
+        }
+    }
+
     // If no mapping was found and we're about to treat the first path segment as a function name,
     // verify that the function actually exists. If not, return a clean 404 instead of invoking.
     if !from_mapping && state.control.get_function(&func_name).await.is_err() {
@@ -665,7 +4127,7 @@ pub async fn api_gateway_proxy(
         .unwrap_or_else(|_| Bytes::new());
     let body_str = String::from_utf8_lossy(&whole_body).to_string();
 
-    let event = serde_json::json!({
+    let mut event = serde_json::json!({
         "resource": path,
         "path": path,
         "httpMethod": method,
@@ -673,21 +4135,321 @@ pub async fn api_gateway_proxy(
         "queryStringParameters": query_map,
         "pathParameters": serde_json::Value::Null,
         "stageVariables": serde_json::Value::Null,
-        "requestContext": { "path": path },
+        "requestContext": { "path": path, "accountId": state.control.identity().account_id },
         "body": if body_str.is_empty() { serde_json::Value::Null } else { serde_json::Value::String(body_str.clone()) },
         "isBase64Encoded": false
     });
+    // Identity asserted by an authenticating proxy in front of the gateway
+    if let Some(authorizer) =
+        trusted_headers::authorizer(state.control.trusted_headers(), &headers_map, peer_ip)
+    {
+        event["requestContext"]["authorizer"] = authorizer;
+    }
+    if let Some(jwt) = jwt {
+        if !event["requestContext"]["authorizer"].is_object() {
+            event["requestContext"]["authorizer"] = serde_json::json!({});
+        }
+        event["requestContext"]["authorizer"]["jwt"] = jwt;
+    }
+
+    // A recording route saves a sample of its requests as test events of its function
+    if let Some(route) = route
+        .as_ref()
+        .filter(|r| r.recording.as_ref().is_some_and(|rec| rec.enabled))
+    {
+        let control = state.control.clone();
+        let (route, event) = (route.clone(), event.clone());
+        tokio::spawn(async move {
+            if let Err(e) = control.capture_route_event(&route, &event).await {
+                warn!(
+                    "Failed to record request of route {}: {}",
+                    route.route_id, e
+                );
+            }
+        });
+    }
+
+    // A route's canary serves the requests matching its condition
+    let (target, func_name, qualifier) = match route.as_ref().and_then(|r| r.canary.as_ref()) {
+        Some(canary) if matches_canary(&canary.condition, &headers_map) => (
+            RouteTarget::Canary,
+            canary.function_name.clone(),
+            canary.qualifier.clone(),
+        ),
+        _ => (RouteTarget::Primary, func_name, None),
+    };
+
+    // A route's request template builds the event the function gets
+    let transform = route.as_ref().and_then(|r| r.transform.as_ref());
+    let event = match transform.and_then(|t| t.request.as_ref()) {
+        Some(template) => transforms::render(template, &transforms::request_context(&event)),
+        None => event,
+    };
+
+    // A route can have its function invoked in the background, or let the request choose
+    let invocation_type = match route.as_ref().and_then(|r| r.invocation.as_ref()) {
+        Some(invocation) => invocation
+            .invocation_type_for(headers_map.get("x-amz-invocation-type").map(String::as_str)),
+        None => lambda_models::InvocationType::RequestResponse,
+    };
 
     let request = lambda_models::InvokeRequest {
         function_name: func_name.clone(),
-        invocation_type: lambda_models::InvocationType::RequestResponse,
+        invocation_type: invocation_type.clone(),
         log_type: None,
         client_context: None,
         payload: Some(event),
-        qualifier: None,
+        qualifier,
+        idempotent: false,
+        trace: false,
+    };
+
+    // Event invocations answer 202 with the request id as soon as they are queued
+    if invocation_type == lambda_models::InvocationType::Event {
+        let (request_id, response) = match state.control.enqueue_event(request).await {
+            Ok(job) => {
+                let mut response_headers = HeaderMap::new();
+                if let Ok(header_value) = HeaderValue::from_str(&job.job_id) {
+                    response_headers.insert("X-Amz-Request-Id", header_value);
+                }
+                (
+                    Some(job.job_id),
+                    (StatusCode::ACCEPTED, response_headers, Body::empty()).into_response(),
+                )
+            }
+            Err(e) => (None, gateway_response(Err(e))),
+        };
+        if let Some(route) = &route {
+            state.control.record_route_request(
+                route.route_id,
+                target,
+                request_id.is_none(),
+                started.elapsed().as_millis() as u64,
+            );
+        }
+        if route.as_ref().is_none_or(|r| r.access_log) {
+            state.control.access_log().record(AccessLogEntry {
+                timestamp: chrono::Utc::now(),
+                method,
+                path,
+                function_name: func_name,
+                route_id: route.as_ref().map(|r| r.route_id),
+                status: response.status().as_u16(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                source_ip,
+                request_id,
+            });
+        }
+        return response;
+    }
+
+    // A route with limits forwards only as many requests at once as they allow
+    let admission = match &route {
+        Some(route) => state.control.admit_route_request(route).await.map(Some),
+        None => Ok(None),
+    };
+    let (result, permit) = match admission {
+        Ok(permit) => (
+            sync_invoke(&state, request, state.config.sync_invoke.gateway).await,
+            permit,
+        ),
+        Err(e) => (SyncInvoke::Finished(Err(e)), None),
+    };
+    let shed = matches!(
+        result,
+        SyncInvoke::Finished(Err(LambdaError::RouteSaturated { .. }))
+    );
+    if let Some(route) = route.as_ref().filter(|_| !shed) {
+        let failed = match &result {
+            SyncInvoke::Finished(Ok(resp)) => resp.function_error.is_some(),
+            // Not known yet when the response starts
+            SyncInvoke::Streaming(_) => false,
+            _ => true,
+        };
+        state.control.record_route_request(
+            route.route_id,
+            target,
+            failed,
+            started.elapsed().as_millis() as u64,
+        );
+    }
+
+    let (request_id, response) = match result {
+        SyncInvoke::Finished(mut result) => {
+            // A route's response template reshapes what the function returned, unless it failed
+            if let (Some(template), Ok(resp)) =
+                (transform.and_then(|t| t.response.as_ref()), result.as_mut())
+            {
+                if resp.function_error.is_none() {
+                    let context = transforms::response_context(resp.payload.as_ref());
+                    resp.payload = Some(transforms::render(template, &context));
+                }
+            }
+            (
+                result
+                    .as_ref()
+                    .ok()
+                    .and_then(|resp| resp.headers.get("X-Amz-Request-Id").cloned()),
+                gateway_response(result),
+            )
+        }
+        SyncInvoke::Detached {
+            request_id,
+            waited_secs,
+        } => (
+            Some(request_id.clone()),
+            result_pending(request_id, waited_secs).into_response(),
+        ),
+        SyncInvoke::Streaming(pending) => (
+            Some(pending.request_id.clone()),
+            keep_alive_response(
+                pending,
+                Duration::from_secs(state.config.sync_invoke.heartbeat_secs),
+                permit,
+            ),
+        ),
+    };
+    if route.as_ref().is_none_or(|r| r.access_log) {
+        state.control.access_log().record(AccessLogEntry {
+            timestamp: chrono::Utc::now(),
+            method,
+            path,
+            function_name: func_name,
+            route_id: route.as_ref().map(|r| r.route_id),
+            status: response.status().as_u16(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            source_ip,
+            request_id,
+        });
+    }
+    response
+}
+
+/// How a synchronous invoke ended for its caller under `[sync_invoke]`
+enum SyncInvoke {
+    Finished(Result<lambda_models::InvokeResponse, LambdaError>),
+    /// The caller stopped waiting; the result will be stored under the request id
+    Detached {
+        request_id: String,
+        waited_secs: u64,
+    },
+    /// Still running, its response to be streamed with heartbeats
+    Streaming(PendingInvoke),
+}
+
+/// Invoke `request`, holding the caller no longer than `[sync_invoke]` allows, then going on
+/// as `mode` says
+async fn sync_invoke(state: &AppState, request: InvokeRequest, mode: SlowInvokeMode) -> SyncInvoke {
+    let Some(max_wait_secs) = state.config.sync_invoke.max_wait_secs else {
+        return SyncInvoke::Finished(state.control.invoke_function(request).await);
+    };
+    let wait = Duration::from_secs(max_wait_secs);
+    let mut pending = match state.control.invoke_function_within(request, wait).await {
+        BoundedInvoke::Finished(result) => return SyncInvoke::Finished(result),
+        BoundedInvoke::Pending(pending) => pending,
+    };
+    match mode {
+        SlowInvokeMode::Wait => SyncInvoke::Finished(pending.result().await),
+        SlowInvokeMode::KeepAlive => SyncInvoke::Streaming(pending),
+        SlowInvokeMode::GatewayTimeout => {
+            let request_id = pending.request_id.clone();
+            match state.control.detach_invoke(pending).await {
+                Ok(Some(result)) => SyncInvoke::Finished(result),
+                Ok(None) => SyncInvoke::Detached {
+                    request_id,
+                    waited_secs: max_wait_secs,
+                },
+                Err(e) => SyncInvoke::Finished(Err(e)),
+            }
+        }
+    }
+}
+
+/// 504 for a caller that stopped waiting, with where the result will be
+fn result_pending(
+    request_id: String,
+    waited_secs: u64,
+) -> (StatusCode, HeaderMap, Json<ErrorShape>) {
+    let mut headers = HeaderMap::new();
+    if let Ok(header_value) = HeaderValue::from_str(&request_id) {
+        headers.insert("X-Amz-Request-Id", header_value);
+    }
+    if let Ok(header_value) = HeaderValue::from_str(&format!("/api/executions/{request_id}/result"))
+    {
+        headers.insert("Location", header_value);
+    }
+    let e = LambdaError::ResultPending {
+        request_id,
+        waited_secs,
+    };
+    (
+        StatusCode::GATEWAY_TIMEOUT,
+        headers,
+        Json(e.to_error_shape()),
+    )
+}
+
+/// 200 before the result is known: a space every `heartbeat` keeps proxies from timing out
+/// until the body of the function's response follows. Its status and headers come too late
+/// to be sent. `guard` is held until the invocation finished
+fn keep_alive_response(
+    pending: PendingInvoke,
+    heartbeat: Duration,
+    guard: impl Send + 'static,
+) -> Response {
+    let mut headers = HeaderMap::new();
+    if let Ok(header_value) = HeaderValue::from_str(&pending.request_id) {
+        headers.insert("X-Amz-Request-Id", header_value);
+    }
+    headers.insert("x-lambdah-keep-alive", HeaderValue::from_static("true"));
+    let body = futures::stream::unfold(Some((pending, guard)), move |waiting| async move {
+        let (mut pending, guard) = waiting?;
+        let chunk = match tokio::time::timeout(heartbeat, pending.result()).await {
+            Ok(result) => axum::body::to_bytes(gateway_response(result).into_body(), usize::MAX)
+                .await
+                .unwrap_or_default(),
+            Err(_elapsed) => {
+                return Some((
+                    Ok::<_, std::convert::Infallible>(Bytes::from_static(b" ")),
+                    Some((pending, guard)),
+                ))
+            }
+        };
+        Some((Ok(chunk), None))
+    });
+    (StatusCode::OK, headers, Body::from_stream(body)).into_response()
+}
+
+/// Build the response of a mocked route, marked with `X-Lambdah-Mock: true`
+fn mock_response(mock: &RouteMock) -> Response {
+    let mut headers = HeaderMap::new();
+    let body = match &mock.body {
+        None => Body::empty(),
+        Some(serde_json::Value::String(s)) => Body::from(s.clone()),
+        Some(value) => {
+            headers.insert(
+                axum::http::header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            Body::from(value.to_string())
+        }
     };
+    for (k, v) in &mock.headers {
+        if let (Ok(name), Ok(val)) = (
+            HeaderName::from_bytes(k.as_bytes()),
+            HeaderValue::from_str(v),
+        ) {
+            headers.insert(name, val);
+        }
+    }
+    headers.insert("x-lambdah-mock", HeaderValue::from_static("true"));
+    let status = StatusCode::from_u16(mock.status).unwrap_or(StatusCode::OK);
+    (status, headers, body).into_response()
+}
 
-    match state.control.invoke_function(request).await {
+/// Map an invoke result to the gateway's HTTP response
+fn gateway_response(result: Result<lambda_models::InvokeResponse, LambdaError>) -> Response {
+    match result {
         Ok(resp) => {
             // If the function returned an API Gateway proxy result (statusCode/body/headers), map it.
             if let Some(payload) = &resp.payload {
@@ -780,7 +4542,17 @@ pub async fn api_gateway_proxy(
         Err(e) => {
             let status =
                 StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-            (status, Json(e.to_error_shape())).into_response()
+            let mut headers = HeaderMap::new();
+            if let LambdaError::RouteSaturated {
+                retry_after_secs, ..
+            } = &e
+            {
+                headers.insert(
+                    axum::http::header::RETRY_AFTER,
+                    HeaderValue::from(*retry_after_secs),
+                );
+            }
+            (status, headers, Json(e.to_error_shape())).into_response()
         }
     }
 }
@@ -814,6 +4586,8 @@ pub async fn get_lambda_service_stats(State(state): State<AppState>) -> impl Int
                 error_message: e.to_string(),
                 error_type: "InternalError".to_string(),
                 stack_trace: None,
+                build_log: None,
+                references: None,
             };
             (StatusCode::INTERNAL_SERVER_ERROR, Json(error_shape)).into_response()
         }
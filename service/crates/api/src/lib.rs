@@ -1,8 +1,10 @@
+pub mod exec;
 pub mod handlers;
 pub mod middleware;
 pub mod routes;
 pub mod state;
 
+pub use exec::*;
 pub use handlers::*;
 pub use middleware::*;
 pub use routes::*;
@@ -18,6 +20,7 @@ use lambda_metrics::MetricsService;
 use lambda_models::Config;
 use lambda_packaging::PackagingService;
 use rust_embed::RustEmbed;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
@@ -103,7 +106,12 @@ pub async fn start_server(
     let listener = tokio::net::TcpListener::bind(format!("{bind}:{port}")).await?;
     info!("User API server listening on {}:{}", bind, port);
 
-    axum::serve(listener, app).await?;
+    // Peer addresses are needed for the access log
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
@@ -1,8 +1,9 @@
-use crate::{handlers::warm_pool_summary, handlers::*, AppState};
+use crate::{exec::*, handlers::warm_pool_summary, handlers::*, AppState};
 use axum::{
-    routing::{delete, get, post, put},
+    routing::{any, delete, get, patch, post, put},
     Router,
 };
+use lambda_models::FeatureFlag;
 
 pub fn create_router() -> Router<AppState> {
     Router::new()
@@ -11,11 +12,16 @@ pub fn create_router() -> Router<AppState> {
         .route("/2015-03-31/functions/:name", get(get_function))
         .route("/2015-03-31/functions/:name", delete(delete_function))
         .route("/2015-03-31/functions", get(list_functions))
+        .route("/api/validate/function", post(validate_function))
         // Function code and configuration
         .route(
             "/2015-03-31/functions/:name/code",
             put(update_function_code),
         )
+        .route(
+            "/2015-03-31/functions/:name/configuration",
+            get(get_function_configuration),
+        )
         .route(
             "/2015-03-31/functions/:name/configuration",
             put(update_function_configuration),
@@ -38,6 +44,64 @@ pub fn create_router() -> Router<AppState> {
             delete(delete_alias),
         )
         .route("/2015-03-31/functions/:name/aliases", get(list_aliases))
+        // Schedules: cron and rate rules that invoke the function
+        .route(
+            "/2015-03-31/functions/:name/schedules",
+            post(create_schedule),
+        )
+        .route("/2015-03-31/functions/:name/schedules", get(list_schedules))
+        .route(
+            "/2015-03-31/functions/:name/schedules/:schedule_id",
+            get(get_schedule),
+        )
+        .route(
+            "/2015-03-31/functions/:name/schedules/:schedule_id",
+            put(update_schedule),
+        )
+        .route(
+            "/2015-03-31/functions/:name/schedules/:schedule_id",
+            delete(delete_schedule),
+        )
+        // Function URLs
+        .route(
+            "/2021-10-31/functions/:name/url",
+            post(create_function_url_config),
+        )
+        .route(
+            "/2021-10-31/functions/:name/url",
+            get(get_function_url_config),
+        )
+        .route(
+            "/2021-10-31/functions/:name/url",
+            delete(delete_function_url_config),
+        )
+        .route("/lambda-url/:url_id", any(function_url_proxy))
+        .route("/lambda-url/:url_id/", any(function_url_proxy))
+        .route("/lambda-url/:url_id/*path", any(function_url_proxy))
+        // Policies: which functions may invoke through the runtime API
+        .route("/2015-03-31/functions/:name/policy", post(add_permission))
+        .route("/2015-03-31/functions/:name/policy", get(get_policy))
+        .route(
+            "/2015-03-31/functions/:name/policy/:statement_id",
+            delete(remove_permission),
+        )
+        // Tags
+        .route("/2017-03-31/tags/:arn", post(tag_resource))
+        .route("/2017-03-31/tags/:arn", get(list_tags))
+        .route("/2017-03-31/tags/:arn", delete(untag_resource))
+        // Function groups
+        .route("/api/groups", get(list_groups))
+        .route("/api/groups", post(create_group))
+        .route("/api/groups/:group", get(get_group))
+        .route("/api/groups/:group", put(update_group))
+        .route("/api/groups/:group", delete(delete_group))
+        .route("/api/groups/:group/metrics", get(get_group_metrics))
+        .route("/api/groups/:group/drain", post(drain_group))
+        .route("/api/groups/:group/disable", post(disable_group))
+        .route("/api/groups/:group/enable", post(enable_group))
+        .route("/api/functions/:name/group", put(put_function_group))
+        .route("/api/functions/:name/group", get(get_function_group))
+        .route("/api/functions/:name/group", delete(delete_function_group))
         // Concurrency
         .route(
             "/2015-03-31/functions/:name/concurrency",
@@ -51,31 +115,308 @@ pub fn create_router() -> Router<AppState> {
             "/2015-03-31/functions/:name/concurrency",
             delete(delete_concurrency),
         )
+        .route(
+            "/api/functions/:name/concurrency/usage",
+            get(get_concurrency_usage),
+        )
+        .route(
+            "/2019-09-30/functions/:name/provisioned-concurrency",
+            put(put_provisioned_concurrency),
+        )
+        .route(
+            "/2019-09-30/functions/:name/provisioned-concurrency",
+            get(get_provisioned_concurrency),
+        )
+        .route(
+            "/2019-09-30/functions/:name/provisioned-concurrency",
+            delete(delete_provisioned_concurrency),
+        )
+        // The stored function with every setting, and its code
+        .route("/api/functions/:name", get(describe_function))
+        .route("/api/functions/:name/code", get(get_function_code))
+        // Deleting or reconfiguring many functions at once
+        .route("/api/functions/batch", post(batch_functions))
+        // Hedged invokes
+        .route("/api/functions/:name/hedging", put(put_hedging))
+        .route("/api/functions/:name/hedging", get(get_hedging))
+        .route("/api/functions/:name/hedging", delete(delete_hedging))
+        .route("/api/functions/:name/stop-grace", put(put_stop_grace))
+        .route("/api/functions/:name/stop-grace", get(get_stop_grace))
+        .route("/api/functions/:name/stop-grace", delete(delete_stop_grace))
+        .route("/api/functions/:name/lifecycle", put(put_lifecycle))
+        .route("/api/functions/:name/lifecycle", get(get_lifecycle))
+        .route("/api/functions/:name/lifecycle", delete(delete_lifecycle))
+        .route("/api/functions/:name/sandbox", put(put_sandbox))
+        .route("/api/functions/:name/sandbox", get(get_sandbox))
+        .route("/api/functions/:name/sandbox", delete(delete_sandbox))
+        .route("/api/functions/:name/user", put(put_user))
+        .route("/api/functions/:name/user", get(get_user))
+        .route("/api/functions/:name/user", delete(delete_user))
+        .route("/api/functions/:name/aws-emulation", put(put_aws_emulation))
+        .route("/api/functions/:name/aws-emulation", get(get_aws_emulation))
+        .route(
+            "/api/functions/:name/aws-emulation",
+            delete(delete_aws_emulation),
+        )
+        .route("/api/functions/:name/hooks", put(put_hooks))
+        .route("/api/functions/:name/hooks", get(get_hooks))
+        .route("/api/functions/:name/hooks", delete(delete_hooks))
+        .route("/api/functions/:name/locale", put(put_locale))
+        .route("/api/functions/:name/locale", get(get_locale))
+        .route("/api/functions/:name/locale", delete(delete_locale))
+        .route(
+            "/api/functions/:name/runtime-management",
+            put(put_runtime_management),
+        )
+        .route(
+            "/api/functions/:name/runtime-management",
+            get(get_runtime_management),
+        )
+        .route("/api/functions/:name/environment", get(get_environment))
+        .route("/api/functions/:name/environment", patch(patch_environment))
+        .route("/api/functions/:name/prewarm", put(put_prewarm))
+        .route("/api/functions/:name/prewarm", get(get_prewarm))
+        .route("/api/functions/:name/prewarm", delete(delete_prewarm))
+        .route("/api/functions/:name/rollout", get(get_rollout))
+        .route(
+            "/api/functions/:name/response-headers",
+            put(put_response_headers),
+        )
+        .route(
+            "/api/functions/:name/response-headers",
+            get(get_response_headers),
+        )
+        .route(
+            "/api/functions/:name/response-headers",
+            delete(delete_response_headers),
+        )
+        .route("/api/functions/:name/metadata", put(put_metadata))
+        .route("/api/functions/:name/metadata", get(get_metadata))
+        .route("/api/functions/:name/metadata", delete(delete_metadata))
+        .route("/api/functions/:name/test-events", get(list_test_events))
+        .route("/api/functions/:name/test-events", post(create_test_event))
+        .route("/api/functions/:name/test-events/:id", get(get_test_event))
+        .route(
+            "/api/functions/:name/test-events/:id",
+            delete(delete_test_event),
+        )
+        .route("/api/functions/:name/history", get(get_config_history))
+        .route(
+            "/api/functions/:name/history/:revision/revert",
+            post(revert_function_configuration),
+        )
+        .route("/api/functions/:name/archive", post(archive_function))
+        .route("/api/functions/:name/unarchive", post(unarchive_function))
+        // Event source mappings
+        .route(
+            "/2015-03-31/event-source-mappings",
+            post(create_event_source_mapping),
+        )
+        .route(
+            "/2015-03-31/event-source-mappings",
+            get(list_event_source_mappings),
+        )
+        .route(
+            "/2015-03-31/event-source-mappings/:uuid",
+            get(get_event_source_mapping),
+        )
+        .route(
+            "/2015-03-31/event-source-mappings/:uuid",
+            put(update_event_source_mapping),
+        )
+        .route(
+            "/2015-03-31/event-source-mappings/:uuid",
+            delete(delete_event_source_mapping),
+        )
+        .route("/api/s3/notifications", post(receive_s3_notification))
         // Invocation
         .route(
             "/2015-03-31/functions/:name/invocations",
-            post(|state, path, headers, body| async move {
-                invoke_function(state, path, headers, body).await
-            }),
+            post(
+                |state, path, query, connect_info, headers, body| async move {
+                    invoke_function(state, path, query, connect_info, headers, body).await
+                },
+            ),
         )
+        .route(
+            "/2021-11-15/functions/:name/response-streaming-invocations",
+            post(invoke_with_response_stream),
+        )
+        .route("/api/jobs/:id", get(get_job))
+        .route("/api/executions/:id", get(get_execution))
+        .route("/api/executions/:id/result", get(get_execution_result))
+        .route("/api/executions/:id/trace", get(get_execution_trace))
         // Health and metrics
         .route("/healthz", get(health_check))
+        .route("/readyz", get(readiness_check))
         .route("/metrics", get(metrics))
         .route("/docker-stats", get(get_docker_stats))
         .route("/lambda-service-stats", get(get_lambda_service_stats))
+        .route("/api/dashboard", get(get_dashboard))
+        .route("/api/doctor", get(get_doctor_report))
+        .route("/api/search", get(search))
+        .route("/api/changes", get(list_changes))
+        .route("/api/stats/warmup", get(get_warmup_stats))
+        .route("/api/stats/disk", get(get_disk_stats))
+        .route("/api/system/database", get(get_database_status))
+        .route("/api/system/schema", get(get_schema_status))
+        .route("/api/system/info", get(get_system_info))
+        .route("/api/namespaces/:ns/usage", get(get_namespace_usage))
+        .route("/api/builds", get(list_builds))
+        .route("/api/builds/:id", delete(cancel_build))
+        .route(
+            "/api/functions/:name/errors/summary",
+            get(get_error_summary),
+        )
+        .route("/api/functions/:name/insights", get(get_function_insights))
+        .route("/api/cost-estimate", post(estimate_cost))
         // Warm pool admin
         .route("/admin/warm-pool/:name", get(warm_pool_summary))
+        // Debug shells into warm containers
+        .route("/api/containers/:id/exec", post(open_exec_session))
+        .route("/api/exec-sessions/:id", get(connect_exec_session))
         // API Gateway routes admin
         .route("/admin/api-gateway/routes", get(list_api_routes))
         .route("/admin/api-gateway/routes", post(create_api_route))
         .route("/admin/api-gateway/routes/:id", delete(delete_api_route))
+        .route("/api/gateway/openapi.json", get(gateway_openapi))
+        .route(
+            "/admin/api-gateway/routes/:id/canary",
+            put(put_api_route_canary),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/canary",
+            delete(delete_api_route_canary),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/access-log",
+            put(put_api_route_access_log),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/mock",
+            put(put_api_route_mock),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/mock",
+            delete(delete_api_route_mock),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/invocation",
+            put(put_api_route_invocation),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/invocation",
+            delete(delete_api_route_invocation),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/authorizer",
+            put(put_api_route_jwt_authorizer),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/authorizer",
+            delete(delete_api_route_jwt_authorizer),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/transform",
+            put(put_api_route_transform),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/transform",
+            delete(delete_api_route_transform),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/recording",
+            put(put_api_route_recording),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/recording",
+            delete(delete_api_route_recording),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/limits",
+            put(put_api_route_limits),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/limits",
+            delete(delete_api_route_limits),
+        )
+        .route(
+            "/admin/api-gateway/routes/:id/metrics",
+            get(get_api_route_metrics),
+        )
         // Secrets admin
         .route("/admin/secrets", get(list_secrets))
         .route("/admin/secrets", post(create_secret))
+        .route("/admin/secrets/import", post(import_secrets))
+        .route("/admin/secrets/export", post(export_secrets))
+        .route("/admin/secrets/exports", get(list_secret_exports))
         .route("/admin/secrets/:name", delete(delete_secret))
+        // Webhooks admin
+        .route("/admin/webhooks", get(list_webhooks))
+        .route("/admin/webhooks", post(create_webhook))
+        .route("/admin/webhooks/:id", get(get_webhook))
+        .route("/admin/webhooks/:id", delete(delete_webhook))
+        .route(
+            "/admin/webhooks/:id/deliveries",
+            get(list_webhook_deliveries),
+        )
+        // Replication to a peer instance
+        .route("/admin/replication/status", get(get_replication_status))
+        .route("/admin/replication/sync", post(sync_replication))
+        .route(
+            "/admin/replication/health",
+            post(exchange_replication_health),
+        )
+        // Function references that outlived their function
+        .route("/admin/consistency", get(get_consistency_report))
+        // Logging admin
+        .route("/admin/log-level", get(get_log_level))
+        .route("/admin/log-level", put(put_log_level))
+        .route("/admin/log-level", delete(reset_log_level))
+        // Runtime API address given to new containers
+        .route("/admin/runtime-api", get(get_runtime_api))
+        .route("/admin/runtime-api", put(put_runtime_api))
+        .route("/admin/runtime-api", delete(reset_runtime_api))
+        // Feature flags
+        .route("/admin/features", get(list_feature_flags))
+        .route("/admin/features/:name", put(put_feature_flag))
+        .route("/admin/features/:name", delete(reset_feature_flag))
         .fallback(|state, req| async move { api_gateway_proxy(state, req).await })
 }
 
+/// Routes of the synthetic checks subsystem, registered only when its flag is on at startup
+pub fn synthetic_check_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/functions/:name/synthetic-check",
+            put(put_synthetic_check),
+        )
+        .route(
+            "/api/functions/:name/synthetic-check",
+            get(get_synthetic_check),
+        )
+        .route(
+            "/api/functions/:name/synthetic-check",
+            delete(delete_synthetic_check),
+        )
+        .route(
+            "/api/functions/:name/synthetic-check/run",
+            post(run_synthetic_check),
+        )
+        .route(
+            "/api/functions/:name/synthetic-check/stats",
+            get(get_synthetic_check_stats),
+        )
+}
+
 pub fn build_router(state: AppState) -> Router {
-    create_router().with_state(state)
+    let mut router = create_router();
+    if state
+        .control
+        .features()
+        .is_enabled(FeatureFlag::SyntheticChecks)
+    {
+        router = router.merge(synthetic_check_routes());
+    }
+    router.with_state(state)
 }
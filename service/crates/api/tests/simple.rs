@@ -9,7 +9,10 @@ use std::sync::Arc;
 use tower::util::ServiceExt;
 
 async fn create_test_app_state() -> AppState {
-    let config = Config::default();
+    app_state_with(Config::default()).await
+}
+
+async fn app_state_with(config: Config) -> AppState {
     AppState {
         config: config.clone(),
         control: Arc::new(
@@ -50,3 +53,161 @@ async fn metrics_endpoint_works() {
         .unwrap();
     assert_eq!(res.status(), StatusCode::OK);
 }
+
+#[tokio::test]
+async fn ready_endpoint_reports_the_docker_breaker() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .oneshot(Request::get("/readyz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status["state"], "closed");
+}
+
+#[tokio::test]
+async fn concurrency_usage_of_an_unknown_function_is_not_found() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .oneshot(
+            Request::get("/api/functions/missing/concurrency/usage")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn code_of_an_unknown_function_is_not_found() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .oneshot(
+            Request::get("/api/functions/missing/code")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn batch_without_a_selection_is_refused() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .oneshot(
+            Request::post("/api/functions/batch")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"selector":{"names":[]},"operation":{"type":"delete"}}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn tags_of_an_unknown_function_are_not_found() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::get(
+                "/2017-03-31/tags/arn%3Aaws%3Alambda%3Aus-east-1%3A000000000000%3Afunction%3Amissing",
+            )
+            .body(Body::empty())
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = app
+        .oneshot(
+            Request::get("/2017-03-31/tags/missing")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn unknown_group_is_not_found() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .oneshot(
+            Request::get("/api/groups/missing")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn provisioned_concurrency_needs_a_qualifier() {
+    let state = create_test_app_state().await;
+    let app = create_router().with_state(state);
+
+    let res = app
+        .oneshot(
+            Request::get("/2019-09-30/functions/orders/provisioned-concurrency")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn exec_sessions_need_the_exact_admin_token() {
+    let mut config = Config::default();
+    config.exec.enabled = true;
+    config.exec.admin_token = Some("t0k3n".into());
+    let app = create_router().with_state(app_state_with(config).await);
+
+    for (authorization, status) in [
+        (None, StatusCode::UNAUTHORIZED),
+        (Some("Bearer t0k3"), StatusCode::UNAUTHORIZED),
+        (Some("Bearer t0k3x"), StatusCode::UNAUTHORIZED),
+        (Some("Bearer t0k3n!"), StatusCode::UNAUTHORIZED),
+        (Some("t0k3n"), StatusCode::UNAUTHORIZED),
+        // Past the token check, the container is unknown
+        (Some("Bearer t0k3n"), StatusCode::NOT_FOUND),
+    ] {
+        let mut request = Request::post("/api/containers/missing/exec");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        let res = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), status, "{authorization:?}");
+    }
+}
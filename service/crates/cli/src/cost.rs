@@ -0,0 +1,111 @@
+use crate::output::{emit, print_table, OutputFormat};
+use clap::ValueEnum;
+use lambda_models::{Architecture, AwsPricingConfig, CostEstimate, CostEstimateRequest};
+use reqwest::Client;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Arch {
+    #[value(name = "x86_64")]
+    X86_64,
+    Arm64,
+}
+
+/// Price the executions the server recorded as if they had run on AWS Lambda
+pub async fn cost(
+    client: &Client,
+    endpoint: &str,
+    function_name: Option<String>,
+    window: String,
+    architecture: Arch,
+    pricing: Option<PathBuf>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pricing = match pricing {
+        Some(file) => {
+            let text =
+                std::fs::read_to_string(&file).map_err(|e| format!("{}: {e}", file.display()))?;
+            Some(
+                toml::from_str::<AwsPricingConfig>(&text)
+                    .map_err(|e| format!("{}: {e}", file.display()))?,
+            )
+        }
+        None => None,
+    };
+    let request = CostEstimateRequest {
+        function_name,
+        window: Some(window),
+        architecture: match architecture {
+            Arch::X86_64 => Architecture::X86_64,
+            Arch::Arm64 => Architecture::Arm64,
+        },
+        pricing,
+    };
+    let response = client
+        .post(format!("{endpoint}/api/cost-estimate"))
+        .json(&request)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(response.text().await?.into());
+    }
+    let estimate: CostEstimate = response.json().await?;
+
+    emit(output, &estimate, |estimate| {
+        let rows: Vec<Vec<String>> = estimate
+            .functions
+            .iter()
+            .map(|f| {
+                let (p50, p99) = f
+                    .duration
+                    .as_ref()
+                    .map(|d| (format!("{} ms", d.p50_ms), format!("{} ms", d.p99_ms)))
+                    .unwrap_or_else(|| ("-".to_string(), "-".to_string()));
+                vec![
+                    f.function_name.clone(),
+                    f.invocations.to_string(),
+                    p50,
+                    p99,
+                    format!("{} MB", f.memory_size_mb),
+                    f.max_memory_used_mb
+                        .map(|mb| format!("{mb} MB"))
+                        .unwrap_or_else(|| "-".to_string()),
+                    format!("{:.1}", f.gb_seconds),
+                    format!("${:.4}", f.cost),
+                    format!("${:.2}", f.monthly_cost),
+                ]
+            })
+            .collect();
+        print_table(
+            &[
+                "NAME",
+                "INVOCATIONS",
+                "P50",
+                "P99",
+                "MEMORY",
+                "MAX USED",
+                "GB-S",
+                "COST",
+                "MONTHLY",
+            ],
+            &rows,
+        );
+        println!();
+        println!(
+            "Over {}h on {}: {} invocations, {:.1} GB-s, ${:.4}",
+            estimate.window_seconds / 3600,
+            match estimate.architecture {
+                Architecture::X86_64 => "x86_64",
+                Architecture::Arm64 => "arm64",
+            },
+            estimate.invocations,
+            estimate.gb_seconds,
+            estimate.cost
+        );
+        println!(
+            "Per month: ${:.2}, or ${:.2} after the free tier",
+            estimate.monthly_cost_before_free_tier, estimate.monthly_cost
+        );
+    })?;
+    Ok(())
+}
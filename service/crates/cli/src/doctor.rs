@@ -0,0 +1,63 @@
+use crate::output::{emit, OutputFormat};
+use lambda_models::{CheckStatus, DoctorCheck, DoctorReport};
+use reqwest::Client;
+
+/// Check the API answers, then print the server's environment checks with their fixes.
+/// Fails when any check fails.
+pub async fn doctor(
+    client: &Client,
+    endpoint: &str,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = match client.get(format!("{endpoint}/healthz")).send().await {
+        Ok(response) if response.status().is_success() => {
+            let api = DoctorCheck::pass("api", format!("{endpoint} is reachable"));
+            // Probing containers takes a few seconds
+            let response = client.get(format!("{endpoint}/api/doctor")).send().await?;
+            if !response.status().is_success() {
+                return Err(response.text().await?.into());
+            }
+            let mut report: DoctorReport = response.json().await?;
+            report.checks.insert(0, api);
+            report
+        }
+        outcome => {
+            let detail = match outcome {
+                Ok(response) => format!("{endpoint}/healthz answered {}", response.status()),
+                Err(e) => format!("Cannot reach {endpoint}: {e}"),
+            };
+            DoctorReport {
+                checks: vec![DoctorCheck::fail(
+                    "api",
+                    detail,
+                    "Start the server, and point --endpoint or the profile at its user API port",
+                )],
+                checked_at: chrono::Utc::now(),
+            }
+        }
+    };
+
+    emit(output, &report, |report| {
+        for check in &report.checks {
+            let icon = match check.status {
+                CheckStatus::Pass => "✅",
+                CheckStatus::Warn => "⚠️ ",
+                CheckStatus::Fail => "❌",
+            };
+            println!("{icon} {:<13} {}", check.name, check.detail);
+            if let Some(fix) = &check.fix {
+                println!("   {:<13} → {fix}", "");
+            }
+        }
+    })?;
+
+    let failed = report
+        .checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(format!("{failed} check(s) failed").into());
+    }
+    Ok(())
+}
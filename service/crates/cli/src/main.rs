@@ -1,10 +1,21 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use lambda_models::{CreateFunctionRequest, FunctionCode};
+use output::{emit, print_table, OutputFormat};
 use reqwest::Client;
+use serde::Serialize;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use tracing::{error, info};
 
+mod cost;
+mod doctor;
+mod output;
+mod pipe;
+mod profiles;
+mod repl;
+mod secrets;
+
 #[derive(Parser)]
 #[command(name = "lambda-cli")]
 #[command(about = "CLI tool for Lambda@Home")]
@@ -12,8 +23,18 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    #[arg(long, default_value = "http://localhost:8000")]
-    endpoint: String,
+    /// Server URL; overrides the profile's endpoint (default http://localhost:8000). Further
+    /// comma-separated URLs are tried in order when it does not answer.
+    #[arg(long, global = true, value_delimiter = ',')]
+    endpoint: Vec<String>,
+
+    /// Profile from ~/.config/lambda-at-home/config.toml
+    #[arg(long, global = true, env = "LAMBDAH_PROFILE")]
+    profile: Option<String>,
+
+    /// Output format: table for people, json or yaml for scripts
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -28,6 +49,10 @@ enum Commands {
         handler: String,
         /// ZIP file path
         zip_file: PathBuf,
+        /// Deploy only this package of the zipped npm/pnpm workspace, with the workspace
+        /// packages it depends on
+        #[arg(long)]
+        workspace_package: Option<String>,
         /// Function description
         #[arg(long)]
         description: Option<String>,
@@ -37,6 +62,9 @@ enum Commands {
         /// Timeout in seconds
         #[arg(long, default_value = "3")]
         timeout: u64,
+        /// Publish version 1 along with the function
+        #[arg(long)]
+        publish: bool,
     },
     /// List functions
     List,
@@ -61,14 +89,80 @@ enum Commands {
         #[arg(long, default_value = "RequestResponse")]
         invocation_type: String,
     },
+    /// Invoke a function once per line of NDJSON events on stdin, writing results as NDJSON
+    /// whatever the output format
+    Pipe {
+        /// Function name
+        name: String,
+        /// Invocations in flight at once; results keep input order
+        #[arg(long, default_value = "4")]
+        parallelism: usize,
+    },
+    /// Interactive prompt that invokes a function with each entered JSON payload
+    Repl {
+        /// Function name, optionally with a qualifier (name:alias)
+        name: String,
+    },
+    /// Import secrets from a dotenv or JSON file, or export them
+    Secrets {
+        #[command(subcommand)]
+        command: secrets::SecretsCommand,
+    },
+    /// Estimate what the recorded executions would cost on AWS Lambda
+    Cost {
+        /// Only this function
+        #[arg(long)]
+        function: Option<String>,
+        /// Executions to price, e.g. 24h or 30d
+        #[arg(long, default_value = "30d")]
+        window: String,
+        /// Architecture to price GB-seconds at
+        #[arg(long, value_enum, default_value_t = cost::Arch::X86_64)]
+        architecture: cost::Arch,
+        /// TOML file of prices to use instead of the server's `[aws_pricing]`
+        #[arg(long)]
+        pricing: Option<PathBuf>,
+    },
+    /// Diagnose the server's environment: Docker, container networking and disk space
+    Doctor,
+    /// List profiles from ~/.config/lambda-at-home/config.toml
+    Profiles,
+    /// Print a shell completion script, e.g. `lambda-cli completions bash > /etc/bash_completion.d/lambda-cli`
+    Completions {
+        /// bash, zsh, fish, elvish or powershell
+        shell: clap_complete::Shell,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt::init();
+    // Logs go to stderr so they never mix with json or yaml output
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
 
     let cli = Cli::parse();
-    let client = Client::new();
+    match cli.command {
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "lambda-cli",
+                &mut std::io::stdout(),
+            );
+            return Ok(());
+        }
+        Commands::Profiles => return profiles::list_profiles(cli.output),
+        _ => {}
+    }
+
+    let connection = profiles::resolve(
+        &profiles::load_config()?,
+        cli.profile.as_deref(),
+        cli.endpoint.clone(),
+    )?;
+    let client = connection.client()?;
+    let endpoint = connection.select_endpoint(&client).await;
 
     match cli.command {
         Commands::Create {
@@ -76,40 +170,85 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             runtime,
             handler,
             zip_file,
+            workspace_package,
             description,
             memory,
             timeout,
+            publish,
         } => {
             create_function(
                 &client,
-                &cli.endpoint,
+                &endpoint,
                 CreateFunctionParams {
                     name,
                     runtime,
                     handler,
                     zip_file,
+                    workspace_package,
                     description,
                     memory,
                     timeout,
+                    publish,
                 },
+                cli.output,
             )
             .await?;
         }
         Commands::List => {
-            list_functions(&client, &cli.endpoint).await?;
+            list_functions(&client, &endpoint, cli.output).await?;
         }
         Commands::Get { name } => {
-            get_function(&client, &cli.endpoint, name).await?;
+            get_function(&client, &endpoint, name, cli.output).await?;
         }
         Commands::Delete { name } => {
-            delete_function(&client, &cli.endpoint, name).await?;
+            delete_function(&client, &endpoint, name, cli.output).await?;
         }
         Commands::Invoke {
             name,
             payload,
             invocation_type,
         } => {
-            invoke_function(&client, &cli.endpoint, name, payload, invocation_type).await?;
+            invoke_function(
+                &client,
+                &endpoint,
+                name,
+                payload,
+                invocation_type,
+                cli.output,
+            )
+            .await?;
+        }
+        Commands::Pipe { name, parallelism } => {
+            pipe::pipe(&client, &endpoint, &name, parallelism).await?;
+        }
+        Commands::Repl { name } => {
+            repl::repl(&client, &endpoint, &name).await?;
+        }
+        Commands::Secrets { command } => {
+            secrets::secrets(&client, &endpoint, command, cli.output).await?;
+        }
+        Commands::Cost {
+            function,
+            window,
+            architecture,
+            pricing,
+        } => {
+            cost::cost(
+                &client,
+                &endpoint,
+                function,
+                window,
+                architecture,
+                pricing,
+                cli.output,
+            )
+            .await?;
+        }
+        Commands::Doctor => {
+            doctor::doctor(&client, &endpoint, cli.output).await?;
+        }
+        Commands::Completions { .. } | Commands::Profiles => {
+            unreachable!("handled before connecting")
         }
     }
 
@@ -122,15 +261,18 @@ struct CreateFunctionParams {
     runtime: String,
     handler: String,
     zip_file: PathBuf,
+    workspace_package: Option<String>,
     description: Option<String>,
     memory: u64,
     timeout: u64,
+    publish: bool,
 }
 
 async fn create_function(
     client: &Client,
     endpoint: &str,
     params: CreateFunctionParams,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Creating function: {}", params.name);
 
@@ -148,12 +290,16 @@ async fn create_function(
             s3_bucket: None,
             s3_key: None,
             s3_object_version: None,
+            workspace_package: params.workspace_package,
+            image_uri: None,
         },
         description: params.description,
         timeout: Some(params.timeout * 1000), // Convert to milliseconds
         memory_size: Some(params.memory),
         environment: None,
-        publish: Some(false),
+        publish: Some(params.publish),
+        package_type: None,
+        tags: None,
     };
 
     let response = client
@@ -164,12 +310,17 @@ async fn create_function(
 
     if response.status().is_success() {
         let function: lambda_models::Function = response.json().await?;
-        println!("✅ Function created successfully:");
-        println!("   Name: {}", function.function_name);
-        println!("   Runtime: {}", function.runtime);
-        println!("   Handler: {}", function.handler);
-        println!("   Memory: {} MB", function.memory_size);
-        println!("   Timeout: {} ms", function.timeout);
+        emit(output, &function, |function| {
+            println!("✅ Function created successfully:");
+            println!("   Name: {}", function.function_name);
+            println!("   Runtime: {}", function.runtime);
+            println!("   Handler: {}", function.handler);
+            println!("   Memory: {} MB", function.memory_size);
+            println!("   Timeout: {} ms", function.timeout);
+            if params.publish {
+                println!("   Published version: {}", function.version);
+            }
+        })?;
     } else {
         let error_text = response.text().await?;
         error!("Failed to create function: {}", error_text);
@@ -179,7 +330,11 @@ async fn create_function(
     Ok(())
 }
 
-async fn list_functions(client: &Client, endpoint: &str) -> Result<(), Box<dyn std::error::Error>> {
+async fn list_functions(
+    client: &Client,
+    endpoint: &str,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("Listing functions");
 
     let response = client
@@ -189,13 +344,22 @@ async fn list_functions(client: &Client, endpoint: &str) -> Result<(), Box<dyn s
 
     if response.status().is_success() {
         let list_response: lambda_models::ListFunctionsResponse = response.json().await?;
-        println!("📋 Functions:");
-        for function in list_response.functions {
-            println!(
-                "   • {} ({}) - {} MB, {} ms",
-                function.function_name, function.runtime, function.memory_size, function.timeout
-            );
-        }
+        emit(output, &list_response, |list| {
+            let rows: Vec<Vec<String>> = list
+                .functions
+                .iter()
+                .map(|f| {
+                    vec![
+                        f.function_name.clone(),
+                        f.runtime.clone(),
+                        format!("{} MB", f.memory_size),
+                        format!("{} ms", f.timeout),
+                        format!("{:?}", f.state),
+                    ]
+                })
+                .collect();
+            print_table(&["NAME", "RUNTIME", "MEMORY", "TIMEOUT", "STATE"], &rows);
+        })?;
     } else {
         let error_text = response.text().await?;
         error!("Failed to list functions: {}", error_text);
@@ -209,27 +373,37 @@ async fn get_function(
     client: &Client,
     endpoint: &str,
     name: String,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Getting function: {}", name);
 
     let response = client
-        .get(format!("{endpoint}/2015-03-31/functions/{name}"))
+        .get(format!("{endpoint}/api/functions/{name}"))
         .send()
         .await?;
 
     if response.status().is_success() {
         let function: lambda_models::Function = response.json().await?;
-        println!("📋 Function details:");
-        println!("   Name: {}", function.function_name);
-        println!("   Runtime: {}", function.runtime);
-        println!("   Handler: {}", function.handler);
-        println!("   Memory: {} MB", function.memory_size);
-        println!("   Timeout: {} ms", function.timeout);
-        println!("   State: {:?}", function.state);
-        println!("   Last Modified: {}", function.last_modified);
-        if let Some(desc) = function.description {
-            println!("   Description: {desc}");
-        }
+        emit(output, &function, |function| {
+            println!("📋 Function details:");
+            println!("   Name: {}", function.function_name);
+            println!("   Runtime: {}", function.runtime);
+            println!("   Handler: {}", function.handler);
+            println!("   Memory: {} MB", function.memory_size);
+            println!("   Timeout: {} ms", function.timeout);
+            println!("   State: {:?}", function.state);
+            if let Some(reason) = &function.state_reason {
+                println!("   State Reason: {reason}");
+            }
+            println!("   Last Update: {:?}", function.last_update_status);
+            if let Some(reason) = &function.last_update_status_reason {
+                println!("   Last Update Reason: {reason}");
+            }
+            println!("   Last Modified: {}", function.last_modified);
+            if let Some(desc) = &function.description {
+                println!("   Description: {desc}");
+            }
+        })?;
     } else {
         let error_text = response.text().await?;
         error!("Failed to get function: {}", error_text);
@@ -239,10 +413,18 @@ async fn get_function(
     Ok(())
 }
 
+/// `--output json|yaml` result of `delete`
+#[derive(Debug, Serialize)]
+struct DeleteOutput {
+    function_name: String,
+    deleted: bool,
+}
+
 async fn delete_function(
     client: &Client,
     endpoint: &str,
     name: String,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Deleting function: {}", name);
 
@@ -252,7 +434,16 @@ async fn delete_function(
         .await?;
 
     if response.status().is_success() {
-        println!("✅ Function deleted successfully: {name}");
+        let deleted = DeleteOutput {
+            function_name: name,
+            deleted: true,
+        };
+        emit(output, &deleted, |deleted| {
+            println!(
+                "✅ Function deleted successfully: {}",
+                deleted.function_name
+            );
+        })?;
     } else {
         let error_text = response.text().await?;
         error!("Failed to delete function: {}", error_text);
@@ -262,12 +453,23 @@ async fn delete_function(
     Ok(())
 }
 
+/// `--output json|yaml` result of `invoke`
+#[derive(Debug, Serialize)]
+struct InvokeOutput {
+    status: u16,
+    /// `x-amz-*` response headers, such as `x-amz-function-error` and `x-amz-executed-version`
+    headers: BTreeMap<String, String>,
+    /// The function's response, as a string when it is not JSON
+    payload: serde_json::Value,
+}
+
 async fn invoke_function(
     client: &Client,
     endpoint: &str,
     name: String,
     payload: Option<String>,
     invocation_type: String,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Invoking function: {}", name);
 
@@ -286,18 +488,29 @@ async fn invoke_function(
 
     let response = request.send().await?;
 
-    println!("📤 Invocation response:");
-    println!("   Status: {}", response.status());
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter(|(key, _)| key.as_str().starts_with("x-amz-"))
+        .map(|(key, value)| (key.to_string(), value.to_str().unwrap_or("").to_string()))
+        .collect();
+    let response_text = response.text().await?;
+    let result = InvokeOutput {
+        status,
+        headers,
+        payload: serde_json::from_str(&response_text)
+            .unwrap_or(serde_json::Value::String(response_text.clone())),
+    };
 
-    // Print headers
-    for (key, value) in response.headers() {
-        if key.as_str().starts_with("x-amz-") {
-            println!("   {}: {}", key, value.to_str().unwrap_or(""));
+    emit(output, &result, |result| {
+        println!("📤 Invocation response:");
+        println!("   Status: {}", result.status);
+        for (key, value) in &result.headers {
+            println!("   {key}: {value}");
         }
-    }
-
-    let response_text = response.text().await?;
-    println!("   Body: {response_text}");
+        println!("   Body: {response_text}");
+    })?;
 
     Ok(())
 }
@@ -0,0 +1,49 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// How command results are printed. `json` and `yaml` print the API response (or a small
+/// documented object for commands without one) so scripts can rely on the field names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Table,
+    Json,
+    Yaml,
+}
+
+/// Print `value` as JSON or YAML, or hand it to `table` for human-readable output
+pub fn emit<T: Serialize>(
+    format: OutputFormat,
+    value: &T,
+    table: impl FnOnce(&T),
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Table => table(value),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+    }
+    Ok(())
+}
+
+/// Print rows under a header, each column padded to its widest cell
+pub fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(header.to_vec());
+    for row in rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
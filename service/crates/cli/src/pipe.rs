@@ -0,0 +1,128 @@
+use futures::future;
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// One output line of `lambda-cli pipe`, written in input order
+#[derive(Debug, Serialize)]
+struct PipeResult {
+    /// 1-based line number of the event in the input
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    /// `X-Amz-Function-Error` of the invocation, when the handler failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<Value>,
+    /// Why the event could not be invoked (bad JSON, connection failure)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl PipeResult {
+    fn failed(line: usize, error: String) -> Self {
+        Self {
+            line,
+            status: None,
+            function_error: None,
+            payload: None,
+            error: Some(error),
+        }
+    }
+
+    fn succeeded(&self) -> bool {
+        self.error.is_none()
+            && self.function_error.is_none()
+            && self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+}
+
+/// Invoke `name` once per newline-delimited JSON event on stdin, with up to `parallelism`
+/// invocations in flight, and write one result per event to stdout. Blank lines are skipped.
+/// Fails after all events are processed if any of them did.
+pub async fn pipe(
+    client: &Client,
+    endpoint: &str,
+    name: &str,
+    parallelism: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!("{endpoint}/2015-03-31/functions/{name}/invocations");
+    let lines = BufReader::new(tokio::io::stdin()).lines();
+    // Events are read as they arrive, so long or endless inputs stream through
+    let events = stream::unfold(Some((lines, 0)), |state| async move {
+        let (mut lines, number) = state?;
+        let number = number + 1;
+        match lines.next_line().await {
+            Ok(Some(line)) => Some(((number, Ok(line)), Some((lines, number)))),
+            Ok(None) => None,
+            Err(e) => Some(((number, Err(e)), None)),
+        }
+    })
+    .filter(|(_, line)| future::ready(line.as_ref().map_or(true, |l| !l.trim().is_empty())));
+
+    let url = url.as_str();
+    let results = events
+        .map(|(line, event)| async move {
+            match event {
+                Ok(event) => invoke_line(client, url, line, event).await,
+                Err(e) => PipeResult::failed(line, format!("failed to read stdin: {e}")),
+            }
+        })
+        .buffered(parallelism.max(1));
+    let mut results = std::pin::pin!(results);
+    let mut stdout = tokio::io::stdout();
+    let (mut total, mut failed) = (0, 0);
+    while let Some(result) = results.next().await {
+        total += 1;
+        if !result.succeeded() {
+            failed += 1;
+        }
+        let mut out = serde_json::to_vec(&result)?;
+        out.push(b'\n');
+        stdout.write_all(&out).await?;
+    }
+    stdout.flush().await?;
+
+    if failed > 0 {
+        return Err(format!("{failed} of {total} event(s) failed").into());
+    }
+    Ok(())
+}
+
+async fn invoke_line(client: &Client, url: &str, line: usize, event: String) -> PipeResult {
+    let payload: Value = match serde_json::from_str(&event) {
+        Ok(payload) => payload,
+        Err(e) => return PipeResult::failed(line, format!("invalid JSON: {e}")),
+    };
+    let response = match client
+        .post(url)
+        .header("X-Amz-Invocation-Type", "RequestResponse")
+        .json(&payload)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return PipeResult::failed(line, e.to_string()),
+    };
+
+    let status = response.status().as_u16();
+    let function_error = response
+        .headers()
+        .get("x-amz-function-error")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    match response.text().await {
+        Ok(body) => PipeResult {
+            line,
+            status: Some(status),
+            function_error,
+            // Responses that are not JSON are passed through as strings
+            payload: Some(serde_json::from_str(&body).unwrap_or(Value::String(body))),
+            error: None,
+        },
+        Err(e) => PipeResult::failed(line, e.to_string()),
+    }
+}
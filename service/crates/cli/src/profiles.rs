@@ -0,0 +1,192 @@
+use crate::output::{emit, print_table, OutputFormat};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:8000";
+
+/// How long an endpoint gets to answer before the next fallback is tried
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `~/.config/lambda-at-home/config.toml`:
+///
+/// ```toml
+/// default_profile = "nas"
+///
+/// [profiles.nas]
+/// endpoint = "http://nas.local:8000"
+///
+/// [profiles.cloud]
+/// endpoint = "https://lambda.example.com"
+/// fallback_endpoints = ["https://replica.example.com"]
+/// auth_token = "..."
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct CliConfig {
+    /// Profile used when neither `--profile` nor `LAMBDAH_PROFILE` is given
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Profile {
+    pub endpoint: Option<String>,
+    /// Tried in order when `endpoint` does not answer, e.g. a replication peer
+    #[serde(default)]
+    pub fallback_endpoints: Vec<String>,
+    /// Sent as `Authorization: Bearer <token>`, e.g. for a reverse proxy in front of the server
+    pub auth_token: Option<String>,
+}
+
+/// Where the CLI sends requests, after applying flags and the selected profile
+#[derive(Debug)]
+pub struct Connection {
+    pub endpoint: String,
+    pub fallback_endpoints: Vec<String>,
+    pub auth_token: Option<String>,
+}
+
+impl Connection {
+    pub fn client(&self) -> Result<Client, Box<dyn std::error::Error>> {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &self.auth_token {
+            let mut value = HeaderValue::from_str(&format!("Bearer {token}"))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(Client::builder().default_headers(headers).build()?)
+    }
+
+    /// The first endpoint whose `/healthz` answers, falling back to the primary endpoint when
+    /// none does so that errors name it
+    pub async fn select_endpoint(&self, client: &Client) -> String {
+        if self.fallback_endpoints.is_empty() {
+            return self.endpoint.clone();
+        }
+        for endpoint in std::iter::once(&self.endpoint).chain(&self.fallback_endpoints) {
+            let healthy = client
+                .get(format!("{endpoint}/healthz"))
+                .timeout(HEALTH_CHECK_TIMEOUT)
+                .send()
+                .await
+                .is_ok_and(|r| r.status().is_success());
+            if healthy {
+                if endpoint != &self.endpoint {
+                    warn!("{} is unreachable, using {}", self.endpoint, endpoint);
+                }
+                return endpoint.clone();
+            }
+        }
+        self.endpoint.clone()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/lambda-at-home/config.toml`, falling back to `~/.config`
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_dir.join("lambda-at-home").join("config.toml"))
+}
+
+/// The config file, or an empty config when there is none
+pub fn load_config() -> Result<CliConfig, Box<dyn std::error::Error>> {
+    let Some(path) = config_path().filter(|p| p.exists()) else {
+        return Ok(CliConfig::default());
+    };
+    let contents = std::fs::read_to_string(&path)?;
+    toml::from_str(&contents).map_err(|e| format!("invalid {}: {e}", path.display()).into())
+}
+
+/// Resolve the endpoint: `--endpoint` wins over the profile, which wins over the default.
+/// Its first URL is the primary endpoint and the others are fallbacks, replacing the
+/// profile's. A named profile must exist; without one the config's `default_profile`
+/// applies, if any.
+pub fn resolve(
+    config: &CliConfig,
+    profile: Option<&str>,
+    endpoints: Vec<String>,
+) -> Result<Connection, Box<dyn std::error::Error>> {
+    let selected = match profile.or(config.default_profile.as_deref()) {
+        Some(name) => Some(config.profiles.get(name).ok_or_else(|| {
+            format!(
+                "profile {name} not found in {}",
+                config_path().map_or("the CLI config".into(), |p| p.display().to_string())
+            )
+        })?),
+        None => None,
+    };
+    let profile = selected.cloned().unwrap_or_default();
+    let mut endpoints = endpoints.into_iter().filter(|e| !e.is_empty());
+    let (endpoint, fallback_endpoints) = match endpoints.next() {
+        Some(endpoint) => (endpoint, endpoints.collect()),
+        None => (
+            profile
+                .endpoint
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            profile.fallback_endpoints,
+        ),
+    };
+    Ok(Connection {
+        endpoint,
+        fallback_endpoints,
+        auth_token: profile.auth_token.filter(|t| !t.is_empty()),
+    })
+}
+
+/// `--output json|yaml` entry of `profiles`; tokens are never printed
+#[derive(Debug, Serialize)]
+struct ProfileSummary {
+    name: String,
+    endpoint: String,
+    fallback_endpoints: Vec<String>,
+    auth_token: bool,
+    default: bool,
+}
+
+pub fn list_profiles(output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config()?;
+    let profiles: Vec<ProfileSummary> = config
+        .profiles
+        .iter()
+        .map(|(name, profile)| ProfileSummary {
+            name: name.clone(),
+            endpoint: profile
+                .endpoint
+                .clone()
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            fallback_endpoints: profile.fallback_endpoints.clone(),
+            auth_token: profile.auth_token.as_ref().is_some_and(|t| !t.is_empty()),
+            default: config.default_profile.as_ref() == Some(name),
+        })
+        .collect();
+    emit(output, &profiles, |profiles| {
+        let rows: Vec<Vec<String>> = profiles
+            .iter()
+            .map(|p| {
+                vec![
+                    p.name.clone(),
+                    p.endpoint.clone(),
+                    if p.fallback_endpoints.is_empty() {
+                        "-".to_string()
+                    } else {
+                        p.fallback_endpoints.join(", ")
+                    },
+                    if p.auth_token { "token" } else { "-" }.to_string(),
+                    if p.default { "*" } else { "" }.to_string(),
+                ]
+            })
+            .collect();
+        print_table(&["NAME", "ENDPOINT", "FALLBACKS", "AUTH", "DEFAULT"], &rows);
+    })
+}
@@ -0,0 +1,226 @@
+use base64::Engine;
+use reqwest::Client;
+use serde_json::Value;
+use std::io::Write;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const HISTORY_FILE: &str = ".lambda-cli_history";
+const MAX_HISTORY: usize = 1000;
+
+const HELP: &str = "\
+Enter a JSON payload to invoke the function; it may span several lines.
+  :use <name>[:<qualifier>]  switch the target function
+  :qualifier [<qualifier>]   set or clear the qualifier (version or alias)
+  :history                   list previous payloads
+  !<n> / !!                  invoke history entry n / the last payload again
+  :help                      show this help
+  :quit                      leave (or Ctrl-D)";
+
+struct Target {
+    name: String,
+    qualifier: Option<String>,
+}
+
+impl Target {
+    fn parse(target: &str) -> Self {
+        match target.split_once(':') {
+            Some((name, qualifier)) if !qualifier.is_empty() => Self {
+                name: name.to_string(),
+                qualifier: Some(qualifier.to_string()),
+            },
+            _ => Self {
+                name: target.trim_end_matches(':').to_string(),
+                qualifier: None,
+            },
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match &self.qualifier {
+            Some(qualifier) => format!("{}:{}> ", self.name, qualifier),
+            None => format!("{}> ", self.name),
+        }
+    }
+}
+
+/// Payloads entered in earlier sessions, one JSON document per line of `~/.lambda-cli_history`
+struct History {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+}
+
+impl History {
+    fn load() -> Self {
+        let path = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        let entries = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|s| s.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    fn push(&mut self, payload: &Value) {
+        let entry = payload.to_string();
+        if self.entries.last() == Some(&entry) {
+            return;
+        }
+        self.entries.push(entry);
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.drain(..self.entries.len() - MAX_HISTORY);
+        }
+        if let Some(path) = &self.path {
+            // History is a convenience; an unwritable home directory is not worth failing over
+            let _ = std::fs::write(path, self.entries.join("\n") + "\n");
+        }
+    }
+
+    /// `!!` is the last entry, `!n` the 1-based entry `n`
+    fn recall(&self, reference: &str) -> Option<&str> {
+        let index = match reference {
+            "!" => self.entries.len().checked_sub(1)?,
+            n => n.parse::<usize>().ok()?.checked_sub(1)?,
+        };
+        self.entries.get(index).map(String::as_str)
+    }
+}
+
+/// Interactive prompt that invokes `target` (`name` or `name:qualifier`) with each entered
+/// payload and prints the response with its log tail
+pub async fn repl(
+    client: &Client,
+    endpoint: &str,
+    target: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut target = Target::parse(target);
+    let mut history = History::load();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut buffer = String::new();
+    println!("Invoking {}. Type :help for commands.", target.name);
+
+    loop {
+        if buffer.is_empty() {
+            print!("{}", target.prompt());
+        } else {
+            print!("...> ");
+        }
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            return Ok(());
+        };
+
+        if buffer.is_empty() {
+            let input = line.trim();
+            if input.is_empty() {
+                continue;
+            }
+            if let Some(command) = input.strip_prefix(':') {
+                let (command, arg) = command
+                    .split_once(char::is_whitespace)
+                    .map_or((command, ""), |(c, a)| (c, a.trim()));
+                match command {
+                    "q" | "quit" | "exit" => return Ok(()),
+                    "help" | "h" => println!("{HELP}"),
+                    "use" if !arg.is_empty() => target = Target::parse(arg),
+                    "qualifier" => {
+                        target.qualifier = Some(arg.to_string()).filter(|q| !q.is_empty())
+                    }
+                    "history" => {
+                        for (i, entry) in history.entries.iter().enumerate() {
+                            println!("{:>4}  {}", i + 1, entry);
+                        }
+                    }
+                    _ => println!("Unknown command :{command}. Type :help for commands."),
+                }
+                continue;
+            }
+            if let Some(reference) = input.strip_prefix('!') {
+                match history.recall(reference).map(serde_json::from_str::<Value>) {
+                    Some(Ok(payload)) => {
+                        println!("{payload}");
+                        invoke(client, endpoint, &target, &payload).await;
+                        history.push(&payload);
+                    }
+                    _ => println!("No history entry {input}"),
+                }
+                continue;
+            }
+        }
+
+        buffer.push_str(&line);
+        buffer.push('\n');
+        match serde_json::from_str::<Value>(&buffer) {
+            Ok(payload) => {
+                buffer.clear();
+                invoke(client, endpoint, &target, &payload).await;
+                history.push(&payload);
+            }
+            // Keep reading until the document is complete
+            Err(e) if e.is_eof() => {}
+            Err(e) => {
+                buffer.clear();
+                println!("Invalid JSON: {e}");
+            }
+        }
+    }
+}
+
+async fn invoke(client: &Client, endpoint: &str, target: &Target, payload: &Value) {
+    let mut request = client
+        .post(format!(
+            "{endpoint}/2015-03-31/functions/{}/invocations",
+            target.name
+        ))
+        .header("X-Amz-Invocation-Type", "RequestResponse")
+        .header("X-Amz-Log-Type", "Tail")
+        .json(payload);
+    if let Some(qualifier) = &target.qualifier {
+        request = request.query(&[("Qualifier", qualifier)]);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("❌ {e}");
+            return;
+        }
+    };
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let mut summary = format!("Status: {}", response.status());
+    if let Some(duration) = header("x-amz-duration") {
+        summary.push_str(&format!(", {duration} ms"));
+    }
+    if let Some(function_error) = header("x-amz-function-error") {
+        summary.push_str(&format!(", function error: {function_error}"));
+    }
+    let log_tail = header("x-amz-log-result")
+        .and_then(|tail| base64::engine::general_purpose::STANDARD.decode(tail).ok())
+        .map(|tail| String::from_utf8_lossy(&tail).into_owned());
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            println!("❌ {e}");
+            return;
+        }
+    };
+    println!("{summary}");
+    match serde_json::from_str::<Value>(&body) {
+        Ok(value) => println!("{}", serde_json::to_string_pretty(&value).unwrap_or(body)),
+        Err(_) => println!("{body}"),
+    }
+    if let Some(tail) = log_tail.filter(|t| !t.trim().is_empty()) {
+        println!("--- log tail ---");
+        println!("{}", tail.trim_end());
+    }
+}
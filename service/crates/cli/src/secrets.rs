@@ -0,0 +1,122 @@
+use crate::output::{emit, print_table, OutputFormat};
+use clap::{Subcommand, ValueEnum};
+use lambda_models::{
+    ExportSecretsRequest, ExportSecretsResponse, ImportSecretsRequest, ImportSecretsResponse,
+    SecretConflictMode, SecretsFileFormat, EXPORT_VALUES_CONFIRMATION,
+};
+use reqwest::Client;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum SecretsCommand {
+    /// Create secrets from a dotenv (`NAME=value` lines) or JSON (`{ "NAME": "value" }`) file
+    Import {
+        /// File to read, e.g. .env
+        file: PathBuf,
+        /// What to do with secrets that already exist
+        #[arg(long, value_enum, default_value_t = Conflict::Fail)]
+        on_conflict: Conflict,
+        /// Format of the file; by default JSON when it starts with `{`, else dotenv
+        #[arg(long, value_enum)]
+        format: Option<FileFormat>,
+    },
+    /// Print the secrets as a dotenv file: names only, or with their values
+    Export {
+        /// Include the values; the export is recorded in the server's audit log
+        #[arg(long, requires = "confirm")]
+        include_values: bool,
+        /// Confirm that the values may leave the server
+        #[arg(long)]
+        confirm: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Conflict {
+    /// Keep the existing secret
+    Skip,
+    /// Replace the existing secret's value
+    Overwrite,
+    /// Import nothing when any secret exists
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FileFormat {
+    Dotenv,
+    Json,
+}
+
+pub async fn secrets(
+    client: &Client,
+    endpoint: &str,
+    command: SecretsCommand,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        SecretsCommand::Import {
+            file,
+            on_conflict,
+            format,
+        } => {
+            let request = ImportSecretsRequest {
+                content: std::fs::read_to_string(&file)
+                    .map_err(|e| format!("{}: {e}", file.display()))?,
+                format: format.map(|format| match format {
+                    FileFormat::Dotenv => SecretsFileFormat::Dotenv,
+                    FileFormat::Json => SecretsFileFormat::Json,
+                }),
+                on_conflict: match on_conflict {
+                    Conflict::Skip => SecretConflictMode::Skip,
+                    Conflict::Overwrite => SecretConflictMode::Overwrite,
+                    Conflict::Fail => SecretConflictMode::Fail,
+                },
+            };
+            let response = client
+                .post(format!("{endpoint}/api/admin/secrets/import"))
+                .json(&request)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(response.text().await?.into());
+            }
+            let imported: ImportSecretsResponse = response.json().await?;
+            emit(output, &imported, |imported| {
+                let mut rows = Vec::new();
+                for (names, outcome) in [
+                    (&imported.created, "created"),
+                    (&imported.overwritten, "overwritten"),
+                    (&imported.skipped, "skipped"),
+                ] {
+                    rows.extend(names.iter().map(|n| vec![n.clone(), outcome.to_string()]));
+                }
+                print_table(&["NAME", "RESULT"], &rows);
+            })?;
+        }
+        SecretsCommand::Export {
+            include_values,
+            confirm,
+        } => {
+            let request = ExportSecretsRequest {
+                include_values,
+                confirm: confirm.then(|| EXPORT_VALUES_CONFIRMATION.to_string()),
+            };
+            let mut export = client
+                .post(format!("{endpoint}/api/admin/secrets/export"))
+                .json(&request);
+            // Names who exported in the audit log
+            if let Ok(user) = std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+                export = export.header("X-Lambdah-Actor", user);
+            }
+            let response = export.send().await?;
+            if !response.status().is_success() {
+                return Err(response.text().await?.into());
+            }
+            let exported: ExportSecretsResponse = response.json().await?;
+            emit(output, &exported, |exported| {
+                print!("{}", exported.to_dotenv())
+            })?;
+        }
+    }
+    Ok(())
+}
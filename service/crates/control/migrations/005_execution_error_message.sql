@@ -0,0 +1,3 @@
+-- Keep the error message of failed executions for error grouping
+ALTER TABLE executions ADD COLUMN error_message TEXT;
+CREATE INDEX IF NOT EXISTS idx_executions_function_status ON executions (function_id, status, start_time);
@@ -0,0 +1,2 @@
+-- Full error payload (errorMessage, errorType, stackTrace) posted by the runtime
+ALTER TABLE executions ADD COLUMN error_payload TEXT;
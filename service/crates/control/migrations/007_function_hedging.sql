@@ -0,0 +1,8 @@
+-- Per-function hedged invoke settings
+CREATE TABLE IF NOT EXISTS function_hedging (
+    function_id TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    percentile REAL NULL,
+    min_delay_ms INTEGER NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Owner, links and notes shown alongside a function
+CREATE TABLE IF NOT EXISTS function_metadata (
+    function_id TEXT PRIMARY KEY,
+    owner TEXT NULL,
+    repo_url TEXT NULL,
+    docs_url TEXT NULL,
+    notes TEXT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,41 @@
+-- Local SQS-compatible queues
+CREATE TABLE IF NOT EXISTS queues (
+    name TEXT PRIMARY KEY,
+    visibility_timeout_secs INTEGER NOT NULL,
+    delay_seconds INTEGER NOT NULL DEFAULT 0,
+    redrive_policy TEXT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Times are epoch milliseconds so visibility checks compare numerically
+CREATE TABLE IF NOT EXISTS queue_messages (
+    message_id TEXT PRIMARY KEY,
+    queue_name TEXT NOT NULL,
+    body TEXT NOT NULL,
+    md5_of_body TEXT NOT NULL,
+    sent_at_ms INTEGER NOT NULL,
+    visible_at_ms INTEGER NOT NULL,
+    receive_count INTEGER NOT NULL DEFAULT 0,
+    first_received_at_ms INTEGER NULL,
+    receipt_handle TEXT NULL,
+    FOREIGN KEY (queue_name) REFERENCES queues (name) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_queue_messages_visible
+    ON queue_messages (queue_name, visible_at_ms, sent_at_ms);
+CREATE INDEX IF NOT EXISTS idx_queue_messages_receipt
+    ON queue_messages (receipt_handle);
+
+-- Queue to function batch delivery
+CREATE TABLE IF NOT EXISTS event_source_mappings (
+    uuid TEXT PRIMARY KEY,
+    queue_name TEXT NOT NULL,
+    function_id TEXT NOT NULL,
+    batch_size INTEGER NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    report_batch_item_failures INTEGER NOT NULL DEFAULT 0,
+    last_processing_result TEXT NULL,
+    last_modified TEXT NOT NULL,
+    FOREIGN KEY (queue_name) REFERENCES queues (name) ON DELETE CASCADE,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
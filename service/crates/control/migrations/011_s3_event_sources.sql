@@ -0,0 +1,32 @@
+-- Event source mappings for buckets in an external S3-compatible store. SQLite cannot
+-- relax a NOT NULL column in place, so the table is rebuilt with a nullable queue_name.
+CREATE TABLE event_source_mappings_new (
+    uuid TEXT PRIMARY KEY,
+    queue_name TEXT NULL,
+    bucket TEXT NULL,
+    key_prefix TEXT NULL,
+    key_suffix TEXT NULL,
+    function_id TEXT NOT NULL,
+    batch_size INTEGER NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    report_batch_item_failures INTEGER NOT NULL DEFAULT 0,
+    last_processing_result TEXT NULL,
+    last_modified TEXT NOT NULL,
+    CHECK ((queue_name IS NULL) <> (bucket IS NULL)),
+    FOREIGN KEY (queue_name) REFERENCES queues (name) ON DELETE CASCADE,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+
+INSERT INTO event_source_mappings_new (
+    uuid, queue_name, function_id, batch_size, enabled, report_batch_item_failures,
+    last_processing_result, last_modified
+)
+SELECT uuid, queue_name, function_id, batch_size, enabled, report_batch_item_failures,
+       last_processing_result, last_modified
+FROM event_source_mappings;
+
+DROP TABLE event_source_mappings;
+ALTER TABLE event_source_mappings_new RENAME TO event_source_mappings;
+
+CREATE INDEX IF NOT EXISTS idx_event_source_mappings_bucket
+    ON event_source_mappings (bucket);
@@ -0,0 +1,290 @@
+-- Change feed: every insert, update and delete of functions, routes and executions,
+-- recorded by triggers in the same transaction as the write, so the feed order is the
+-- commit order. Images are JSON objects of the row; execution error payloads are left out.
+-- UUID columns are bound as 16-byte blobs, so they are spelled out as text.
+CREATE TABLE IF NOT EXISTS change_events (
+    sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    event_name TEXT NOT NULL,
+    occurred_at TEXT NOT NULL,
+    new_image TEXT NULL,
+    old_image TEXT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_change_events_occurred_at ON change_events (occurred_at);
+CREATE INDEX IF NOT EXISTS idx_change_events_entity ON change_events (entity, sequence);
+
+CREATE TRIGGER IF NOT EXISTS change_feed_functions_insert
+AFTER INSERT ON functions
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'function',
+        NEW.function_name,
+        'INSERT',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        json_object(
+            'function_id', CASE WHEN typeof(NEW.function_id) = 'blob' THEN lower(substr(hex(NEW.function_id), 1, 8) || '-' || substr(hex(NEW.function_id), 9, 4) || '-' || substr(hex(NEW.function_id), 13, 4) || '-' || substr(hex(NEW.function_id), 17, 4) || '-' || substr(hex(NEW.function_id), 21)) ELSE NEW.function_id END,
+            'function_name', NEW.function_name,
+            'runtime', NEW.runtime,
+            'role', NEW.role,
+            'handler', NEW.handler,
+            'code_sha256', NEW.code_sha256,
+            'description', NEW.description,
+            'timeout', NEW.timeout,
+            'memory_size', NEW.memory_size,
+            'environment', CASE WHEN json_valid(NEW.environment) THEN json(NEW.environment) ELSE NEW.environment END,
+            'last_modified', NEW.last_modified,
+            'code_size', NEW.code_size,
+            'version', NEW.version,
+            'state', NEW.state,
+            'state_reason', NEW.state_reason,
+            'state_reason_code', NEW.state_reason_code
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_functions_update
+AFTER UPDATE ON functions
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'function',
+        NEW.function_name,
+        'MODIFY',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        json_object(
+            'function_id', CASE WHEN typeof(NEW.function_id) = 'blob' THEN lower(substr(hex(NEW.function_id), 1, 8) || '-' || substr(hex(NEW.function_id), 9, 4) || '-' || substr(hex(NEW.function_id), 13, 4) || '-' || substr(hex(NEW.function_id), 17, 4) || '-' || substr(hex(NEW.function_id), 21)) ELSE NEW.function_id END,
+            'function_name', NEW.function_name,
+            'runtime', NEW.runtime,
+            'role', NEW.role,
+            'handler', NEW.handler,
+            'code_sha256', NEW.code_sha256,
+            'description', NEW.description,
+            'timeout', NEW.timeout,
+            'memory_size', NEW.memory_size,
+            'environment', CASE WHEN json_valid(NEW.environment) THEN json(NEW.environment) ELSE NEW.environment END,
+            'last_modified', NEW.last_modified,
+            'code_size', NEW.code_size,
+            'version', NEW.version,
+            'state', NEW.state,
+            'state_reason', NEW.state_reason,
+            'state_reason_code', NEW.state_reason_code
+        ),
+        json_object(
+            'function_id', CASE WHEN typeof(OLD.function_id) = 'blob' THEN lower(substr(hex(OLD.function_id), 1, 8) || '-' || substr(hex(OLD.function_id), 9, 4) || '-' || substr(hex(OLD.function_id), 13, 4) || '-' || substr(hex(OLD.function_id), 17, 4) || '-' || substr(hex(OLD.function_id), 21)) ELSE OLD.function_id END,
+            'function_name', OLD.function_name,
+            'runtime', OLD.runtime,
+            'role', OLD.role,
+            'handler', OLD.handler,
+            'code_sha256', OLD.code_sha256,
+            'description', OLD.description,
+            'timeout', OLD.timeout,
+            'memory_size', OLD.memory_size,
+            'environment', CASE WHEN json_valid(OLD.environment) THEN json(OLD.environment) ELSE OLD.environment END,
+            'last_modified', OLD.last_modified,
+            'code_size', OLD.code_size,
+            'version', OLD.version,
+            'state', OLD.state,
+            'state_reason', OLD.state_reason,
+            'state_reason_code', OLD.state_reason_code
+        )
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_functions_delete
+AFTER DELETE ON functions
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'function',
+        OLD.function_name,
+        'REMOVE',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        NULL,
+        json_object(
+            'function_id', CASE WHEN typeof(OLD.function_id) = 'blob' THEN lower(substr(hex(OLD.function_id), 1, 8) || '-' || substr(hex(OLD.function_id), 9, 4) || '-' || substr(hex(OLD.function_id), 13, 4) || '-' || substr(hex(OLD.function_id), 17, 4) || '-' || substr(hex(OLD.function_id), 21)) ELSE OLD.function_id END,
+            'function_name', OLD.function_name,
+            'runtime', OLD.runtime,
+            'role', OLD.role,
+            'handler', OLD.handler,
+            'code_sha256', OLD.code_sha256,
+            'description', OLD.description,
+            'timeout', OLD.timeout,
+            'memory_size', OLD.memory_size,
+            'environment', CASE WHEN json_valid(OLD.environment) THEN json(OLD.environment) ELSE OLD.environment END,
+            'last_modified', OLD.last_modified,
+            'code_size', OLD.code_size,
+            'version', OLD.version,
+            'state', OLD.state,
+            'state_reason', OLD.state_reason,
+            'state_reason_code', OLD.state_reason_code
+        )
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_api_routes_insert
+AFTER INSERT ON api_routes
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'route',
+        CASE WHEN typeof(NEW.route_id) = 'blob' THEN lower(substr(hex(NEW.route_id), 1, 8) || '-' || substr(hex(NEW.route_id), 9, 4) || '-' || substr(hex(NEW.route_id), 13, 4) || '-' || substr(hex(NEW.route_id), 17, 4) || '-' || substr(hex(NEW.route_id), 21)) ELSE NEW.route_id END,
+        'INSERT',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        json_object(
+            'route_id', CASE WHEN typeof(NEW.route_id) = 'blob' THEN lower(substr(hex(NEW.route_id), 1, 8) || '-' || substr(hex(NEW.route_id), 9, 4) || '-' || substr(hex(NEW.route_id), 13, 4) || '-' || substr(hex(NEW.route_id), 17, 4) || '-' || substr(hex(NEW.route_id), 21)) ELSE NEW.route_id END,
+            'path', NEW.path,
+            'method', NEW.method,
+            'function_name', NEW.function_name,
+            'created_at', NEW.created_at
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_api_routes_update
+AFTER UPDATE ON api_routes
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'route',
+        CASE WHEN typeof(NEW.route_id) = 'blob' THEN lower(substr(hex(NEW.route_id), 1, 8) || '-' || substr(hex(NEW.route_id), 9, 4) || '-' || substr(hex(NEW.route_id), 13, 4) || '-' || substr(hex(NEW.route_id), 17, 4) || '-' || substr(hex(NEW.route_id), 21)) ELSE NEW.route_id END,
+        'MODIFY',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        json_object(
+            'route_id', CASE WHEN typeof(NEW.route_id) = 'blob' THEN lower(substr(hex(NEW.route_id), 1, 8) || '-' || substr(hex(NEW.route_id), 9, 4) || '-' || substr(hex(NEW.route_id), 13, 4) || '-' || substr(hex(NEW.route_id), 17, 4) || '-' || substr(hex(NEW.route_id), 21)) ELSE NEW.route_id END,
+            'path', NEW.path,
+            'method', NEW.method,
+            'function_name', NEW.function_name,
+            'created_at', NEW.created_at
+        ),
+        json_object(
+            'route_id', CASE WHEN typeof(OLD.route_id) = 'blob' THEN lower(substr(hex(OLD.route_id), 1, 8) || '-' || substr(hex(OLD.route_id), 9, 4) || '-' || substr(hex(OLD.route_id), 13, 4) || '-' || substr(hex(OLD.route_id), 17, 4) || '-' || substr(hex(OLD.route_id), 21)) ELSE OLD.route_id END,
+            'path', OLD.path,
+            'method', OLD.method,
+            'function_name', OLD.function_name,
+            'created_at', OLD.created_at
+        )
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_api_routes_delete
+AFTER DELETE ON api_routes
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'route',
+        CASE WHEN typeof(OLD.route_id) = 'blob' THEN lower(substr(hex(OLD.route_id), 1, 8) || '-' || substr(hex(OLD.route_id), 9, 4) || '-' || substr(hex(OLD.route_id), 13, 4) || '-' || substr(hex(OLD.route_id), 17, 4) || '-' || substr(hex(OLD.route_id), 21)) ELSE OLD.route_id END,
+        'REMOVE',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        NULL,
+        json_object(
+            'route_id', CASE WHEN typeof(OLD.route_id) = 'blob' THEN lower(substr(hex(OLD.route_id), 1, 8) || '-' || substr(hex(OLD.route_id), 9, 4) || '-' || substr(hex(OLD.route_id), 13, 4) || '-' || substr(hex(OLD.route_id), 17, 4) || '-' || substr(hex(OLD.route_id), 21)) ELSE OLD.route_id END,
+            'path', OLD.path,
+            'method', OLD.method,
+            'function_name', OLD.function_name,
+            'created_at', OLD.created_at
+        )
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_executions_insert
+AFTER INSERT ON executions
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'execution',
+        NEW.aws_request_id,
+        'INSERT',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        json_object(
+            'execution_id', CASE WHEN typeof(NEW.execution_id) = 'blob' THEN lower(substr(hex(NEW.execution_id), 1, 8) || '-' || substr(hex(NEW.execution_id), 9, 4) || '-' || substr(hex(NEW.execution_id), 13, 4) || '-' || substr(hex(NEW.execution_id), 17, 4) || '-' || substr(hex(NEW.execution_id), 21)) ELSE NEW.execution_id END,
+            'function_id', CASE WHEN typeof(NEW.function_id) = 'blob' THEN lower(substr(hex(NEW.function_id), 1, 8) || '-' || substr(hex(NEW.function_id), 9, 4) || '-' || substr(hex(NEW.function_id), 13, 4) || '-' || substr(hex(NEW.function_id), 17, 4) || '-' || substr(hex(NEW.function_id), 21)) ELSE NEW.function_id END,
+            'function_version', NEW.function_version,
+            'aws_request_id', NEW.aws_request_id,
+            'container_id', NEW.container_id,
+            'start_time', NEW.start_time,
+            'end_time', NEW.end_time,
+            'duration_ms', NEW.duration_ms,
+            'billed_ms', NEW.billed_ms,
+            'memory_used_mb', NEW.memory_used_mb,
+            'error_type', NEW.error_type,
+            'error_message', NEW.error_message,
+            'status', NEW.status
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_executions_update
+AFTER UPDATE ON executions
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'execution',
+        NEW.aws_request_id,
+        'MODIFY',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        json_object(
+            'execution_id', CASE WHEN typeof(NEW.execution_id) = 'blob' THEN lower(substr(hex(NEW.execution_id), 1, 8) || '-' || substr(hex(NEW.execution_id), 9, 4) || '-' || substr(hex(NEW.execution_id), 13, 4) || '-' || substr(hex(NEW.execution_id), 17, 4) || '-' || substr(hex(NEW.execution_id), 21)) ELSE NEW.execution_id END,
+            'function_id', CASE WHEN typeof(NEW.function_id) = 'blob' THEN lower(substr(hex(NEW.function_id), 1, 8) || '-' || substr(hex(NEW.function_id), 9, 4) || '-' || substr(hex(NEW.function_id), 13, 4) || '-' || substr(hex(NEW.function_id), 17, 4) || '-' || substr(hex(NEW.function_id), 21)) ELSE NEW.function_id END,
+            'function_version', NEW.function_version,
+            'aws_request_id', NEW.aws_request_id,
+            'container_id', NEW.container_id,
+            'start_time', NEW.start_time,
+            'end_time', NEW.end_time,
+            'duration_ms', NEW.duration_ms,
+            'billed_ms', NEW.billed_ms,
+            'memory_used_mb', NEW.memory_used_mb,
+            'error_type', NEW.error_type,
+            'error_message', NEW.error_message,
+            'status', NEW.status
+        ),
+        json_object(
+            'execution_id', CASE WHEN typeof(OLD.execution_id) = 'blob' THEN lower(substr(hex(OLD.execution_id), 1, 8) || '-' || substr(hex(OLD.execution_id), 9, 4) || '-' || substr(hex(OLD.execution_id), 13, 4) || '-' || substr(hex(OLD.execution_id), 17, 4) || '-' || substr(hex(OLD.execution_id), 21)) ELSE OLD.execution_id END,
+            'function_id', CASE WHEN typeof(OLD.function_id) = 'blob' THEN lower(substr(hex(OLD.function_id), 1, 8) || '-' || substr(hex(OLD.function_id), 9, 4) || '-' || substr(hex(OLD.function_id), 13, 4) || '-' || substr(hex(OLD.function_id), 17, 4) || '-' || substr(hex(OLD.function_id), 21)) ELSE OLD.function_id END,
+            'function_version', OLD.function_version,
+            'aws_request_id', OLD.aws_request_id,
+            'container_id', OLD.container_id,
+            'start_time', OLD.start_time,
+            'end_time', OLD.end_time,
+            'duration_ms', OLD.duration_ms,
+            'billed_ms', OLD.billed_ms,
+            'memory_used_mb', OLD.memory_used_mb,
+            'error_type', OLD.error_type,
+            'error_message', OLD.error_message,
+            'status', OLD.status
+        )
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS change_feed_executions_delete
+AFTER DELETE ON executions
+BEGIN
+    INSERT INTO change_events (entity, entity_id, event_name, occurred_at, new_image, old_image)
+    VALUES (
+        'execution',
+        OLD.aws_request_id,
+        'REMOVE',
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+        NULL,
+        json_object(
+            'execution_id', CASE WHEN typeof(OLD.execution_id) = 'blob' THEN lower(substr(hex(OLD.execution_id), 1, 8) || '-' || substr(hex(OLD.execution_id), 9, 4) || '-' || substr(hex(OLD.execution_id), 13, 4) || '-' || substr(hex(OLD.execution_id), 17, 4) || '-' || substr(hex(OLD.execution_id), 21)) ELSE OLD.execution_id END,
+            'function_id', CASE WHEN typeof(OLD.function_id) = 'blob' THEN lower(substr(hex(OLD.function_id), 1, 8) || '-' || substr(hex(OLD.function_id), 9, 4) || '-' || substr(hex(OLD.function_id), 13, 4) || '-' || substr(hex(OLD.function_id), 17, 4) || '-' || substr(hex(OLD.function_id), 21)) ELSE OLD.function_id END,
+            'function_version', OLD.function_version,
+            'aws_request_id', OLD.aws_request_id,
+            'container_id', OLD.container_id,
+            'start_time', OLD.start_time,
+            'end_time', OLD.end_time,
+            'duration_ms', OLD.duration_ms,
+            'billed_ms', OLD.billed_ms,
+            'memory_used_mb', OLD.memory_used_mb,
+            'error_type', OLD.error_type,
+            'error_message', OLD.error_message,
+            'status', OLD.status
+        )
+    );
+END;
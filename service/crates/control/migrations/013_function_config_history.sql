@@ -0,0 +1,14 @@
+-- Revisions of each function's configuration (role, handler, description, timeout,
+-- memory and environment), with what changed, when and by whom
+CREATE TABLE IF NOT EXISTS function_config_revisions (
+    function_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    changed_at TEXT NOT NULL,
+    changed_by TEXT NULL,
+    source TEXT NOT NULL,
+    reverted_from INTEGER NULL,
+    changes TEXT NOT NULL,
+    configuration TEXT NOT NULL,
+    PRIMARY KEY (function_id, revision),
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
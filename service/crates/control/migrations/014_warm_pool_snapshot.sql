@@ -0,0 +1,15 @@
+-- Warm pool membership, rewritten on every change while warm_pool.persist is on, so the
+-- next start can adopt containers left running instead of cold-starting them
+CREATE TABLE IF NOT EXISTS warm_pool_snapshot (
+    container_id TEXT PRIMARY KEY,
+    instance_id TEXT NOT NULL,
+    function_id TEXT NOT NULL,
+    function_name TEXT NOT NULL,
+    runtime TEXT NOT NULL,
+    version TEXT NOT NULL,
+    env_hash TEXT NOT NULL,
+    image_ref TEXT NOT NULL,
+    memory_mb INTEGER NOT NULL,
+    state TEXT NOT NULL,
+    saved_at TEXT NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Per-function grace period between SIGTERM and SIGKILL when a container is stopped
+CREATE TABLE IF NOT EXISTS function_stop_grace (
+    function_id TEXT PRIMARY KEY,
+    grace_period_seconds INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,17 @@
+-- Invocations started in job mode; results are polled and kept until expires_at.
+-- No foreign key so a result stays readable after its function is deleted.
+CREATE TABLE IF NOT EXISTS invocation_jobs (
+    job_id TEXT PRIMARY KEY,
+    function_name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    completed_at TEXT,
+    expires_at TEXT,
+    executed_version TEXT,
+    function_error TEXT,
+    payload TEXT,
+    error_message TEXT,
+    duration_ms INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_invocation_jobs_expires_at ON invocation_jobs (expires_at);
@@ -0,0 +1,2 @@
+-- Results over jobs.max_result_bytes are dropped and flagged instead of stored
+ALTER TABLE invocation_jobs ADD COLUMN payload_truncated INTEGER NOT NULL DEFAULT 0;
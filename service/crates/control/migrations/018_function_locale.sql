@@ -0,0 +1,8 @@
+-- Per-function timezone and locale, injected as TZ, LANG and LC_ALL
+CREATE TABLE IF NOT EXISTS function_locale (
+    function_id TEXT PRIMARY KEY,
+    timezone TEXT,
+    locale TEXT,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
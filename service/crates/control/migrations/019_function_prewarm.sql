@@ -0,0 +1,8 @@
+-- Per-function prewarm invocation for containers started ahead of traffic
+CREATE TABLE IF NOT EXISTS function_prewarm (
+    function_id TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL,
+    timeout_ms INTEGER,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- Optional canary target of a route, as JSON ({ function_name, qualifier, match })
+ALTER TABLE api_routes ADD COLUMN canary TEXT NULL;
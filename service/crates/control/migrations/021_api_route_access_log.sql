@@ -0,0 +1,2 @@
+-- Requests through a route are written to the access log unless it is turned off
+ALTER TABLE api_routes ADD COLUMN access_log INTEGER NOT NULL DEFAULT 1;
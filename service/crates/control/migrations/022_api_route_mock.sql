@@ -0,0 +1,2 @@
+-- Optional mock response of a route, as JSON ({ enabled, status, headers, body, latency_ms })
+ALTER TABLE api_routes ADD COLUMN mock TEXT NULL;
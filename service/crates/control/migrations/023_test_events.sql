@@ -0,0 +1,13 @@
+-- Saved payloads to invoke a function with, created by hand or recorded from route traffic
+CREATE TABLE IF NOT EXISTS test_events (
+    event_id TEXT PRIMARY KEY,
+    function_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    source TEXT NOT NULL,
+    route_id TEXT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_test_events_function ON test_events (function_id, created_at);
@@ -0,0 +1,2 @@
+-- Optional traffic recording of a route, as JSON ({ enabled, per_minute, redact_*, max_events })
+ALTER TABLE api_routes ADD COLUMN recording TEXT NULL;
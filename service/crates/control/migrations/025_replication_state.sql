@@ -0,0 +1,11 @@
+-- What the last replication round pushed to the peer, to notice changes made on the peer.
+-- local_id is the function name or the local route id; peer_id the route id on the peer.
+CREATE TABLE IF NOT EXISTS replication_state (
+    kind TEXT NOT NULL,
+    local_id TEXT NOT NULL,
+    label TEXT NOT NULL,
+    peer_id TEXT NULL,
+    fingerprint TEXT NOT NULL,
+    synced_at TEXT NOT NULL,
+    PRIMARY KEY (kind, local_id)
+);
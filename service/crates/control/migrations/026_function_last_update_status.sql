@@ -0,0 +1,5 @@
+-- Outcome of the last create or image build, polled by AWS SDK waiters.
+-- Stored as JSON like functions.state.
+ALTER TABLE functions ADD COLUMN last_update_status TEXT NOT NULL DEFAULT '"Successful"';
+ALTER TABLE functions ADD COLUMN last_update_status_reason TEXT NULL;
+ALTER TABLE functions ADD COLUMN last_update_status_reason_code TEXT NULL;
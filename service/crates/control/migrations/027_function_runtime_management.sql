@@ -0,0 +1,11 @@
+-- Runtime version pinning, and the base image digest each function's last build used.
+-- runtime_version is the pinned digest when update_runtime_on is Manual.
+CREATE TABLE IF NOT EXISTS function_runtime_management (
+    function_id TEXT PRIMARY KEY,
+    update_runtime_on TEXT NOT NULL DEFAULT 'Auto',
+    runtime_version TEXT NULL,
+    last_build_runtime_version TEXT NULL,
+    last_build_at TEXT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Image ID each function's code built to, so versions record the image they run and
+-- rebuilds of the same code can be checked against it.
+CREATE TABLE IF NOT EXISTS function_image_digests (
+    function_id TEXT NOT NULL,
+    code_sha256 TEXT NOT NULL,
+    image_digest TEXT NOT NULL,
+    built_at TEXT NOT NULL,
+    PRIMARY KEY (function_id, code_sha256),
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+ALTER TABLE versions ADD COLUMN image_digest TEXT NULL;
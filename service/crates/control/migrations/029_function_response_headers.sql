@@ -0,0 +1,9 @@
+-- Default headers for a function's direct invoke responses, and whether its results may
+-- carry their own in an envelope. headers is a JSON object of lowercase names to values.
+CREATE TABLE IF NOT EXISTS function_response_headers (
+    function_id TEXT PRIMARY KEY,
+    headers TEXT NOT NULL,
+    envelope INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+-- Runtime API exchange of invocations run with X-Lambdah-Trace: true, as a JSON document
+CREATE TABLE IF NOT EXISTS execution_traces (
+    execution_id TEXT PRIMARY KEY,
+    trace TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
@@ -0,0 +1,21 @@
+-- Scheduled invocations whose results are checked, one per function, and their runs.
+-- config is a JSON SyntheticCheck.
+CREATE TABLE IF NOT EXISTS function_synthetic_checks (
+    function_id TEXT PRIMARY KEY,
+    config TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS synthetic_check_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    function_id TEXT NOT NULL,
+    ran_at TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    failure TEXT,
+    request_id TEXT,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_synthetic_check_runs_function ON synthetic_check_runs (function_id, ran_at);
@@ -0,0 +1,2 @@
+-- Optional admission limits of a route, as JSON ({ max_in_flight, max_queued, queue_timeout_ms, retry_after_secs })
+ALTER TABLE api_routes ADD COLUMN limits TEXT NULL;
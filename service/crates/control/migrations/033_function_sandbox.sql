@@ -0,0 +1,7 @@
+-- Per-function sandbox profile, one of the [sandbox] profiles of the config
+CREATE TABLE IF NOT EXISTS function_sandbox (
+    function_id TEXT PRIMARY KEY,
+    profile TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- Per-function user and group the containers run as
+CREATE TABLE IF NOT EXISTS function_user (
+    function_id TEXT PRIMARY KEY,
+    uid INTEGER NOT NULL,
+    gid INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
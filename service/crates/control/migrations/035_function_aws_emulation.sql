@@ -0,0 +1,7 @@
+-- Per-function fake AWS credentials and emulator endpoints, as JSON
+CREATE TABLE IF NOT EXISTS function_aws_emulation (
+    function_id TEXT PRIMARY KEY,
+    settings TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
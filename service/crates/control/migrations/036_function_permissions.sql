@@ -0,0 +1,10 @@
+-- Statements of function policies: which functions may invoke a function through the runtime API
+CREATE TABLE IF NOT EXISTS function_permissions (
+    function_id TEXT NOT NULL,
+    statement_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    principal TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (function_id, statement_id),
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
@@ -0,0 +1,7 @@
+-- Per-function pre- and post-invoke hooks, as JSON
+CREATE TABLE IF NOT EXISTS function_hooks (
+    function_id TEXT PRIMARY KEY,
+    hooks TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
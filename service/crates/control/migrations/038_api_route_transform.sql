@@ -0,0 +1,2 @@
+-- Optional request/response mapping templates of a route, as JSON ({ request, response })
+ALTER TABLE api_routes ADD COLUMN transform TEXT NULL;
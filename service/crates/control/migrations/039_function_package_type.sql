@@ -0,0 +1,4 @@
+-- How a function's code is deployed, stored as JSON like functions.state, and the
+-- pre-built image run by `Image` functions
+ALTER TABLE functions ADD COLUMN package_type TEXT NOT NULL DEFAULT '"Zip"';
+ALTER TABLE functions ADD COLUMN image_uri TEXT NULL;
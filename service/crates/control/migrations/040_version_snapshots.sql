@@ -0,0 +1,6 @@
+-- Configuration a version was published with, as JSON, and the image of `Image` functions
+ALTER TABLE versions ADD COLUMN config TEXT NULL;
+ALTER TABLE versions ADD COLUMN image_uri TEXT NULL;
+
+-- The unpublished function is $LATEST, apart from its published versions
+UPDATE functions SET version = '$LATEST' WHERE version = '1';
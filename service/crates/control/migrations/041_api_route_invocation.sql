@@ -0,0 +1,2 @@
+-- How the gateway invokes a route's function, as JSON ({ invocation_type, allow_override })
+ALTER TABLE api_routes ADD COLUMN invocation TEXT NULL;
@@ -0,0 +1,13 @@
+-- Event invocations waiting for an attempt. A row is removed once its result is final
+-- and stored with the job of the same id; claimed_at marks an attempt under way.
+CREATE TABLE IF NOT EXISTS async_invocations (
+    request_id TEXT PRIMARY KEY,
+    function_name TEXT NOT NULL,
+    request TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    enqueued_at TEXT NOT NULL,
+    next_attempt_at TEXT NOT NULL,
+    claimed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_async_invocations_next_attempt_at ON async_invocations (next_attempt_at);
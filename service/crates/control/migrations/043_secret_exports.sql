@@ -0,0 +1,7 @@
+-- Audit log of secret exports that included values
+CREATE TABLE IF NOT EXISTS secret_exports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    exported_at TEXT NOT NULL,
+    actor TEXT,
+    names TEXT NOT NULL
+);
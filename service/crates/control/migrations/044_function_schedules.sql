@@ -0,0 +1,18 @@
+-- Cron and rate rules invoking a function asynchronously. next_run_at is NULL while a rule
+-- is disabled or once its cron expression has no later match.
+CREATE TABLE IF NOT EXISTS function_schedules (
+    schedule_id TEXT PRIMARY KEY,
+    function_id TEXT NOT NULL,
+    schedule_expression TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    qualifier TEXT,
+    description TEXT,
+    enabled INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    last_run_at TEXT,
+    last_job_id TEXT,
+    next_run_at TEXT,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_function_schedules_next_run_at ON function_schedules (next_run_at);
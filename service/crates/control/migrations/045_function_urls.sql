@@ -0,0 +1,10 @@
+-- Function URLs: one per function and qualifier ('' for $LATEST), reached at
+-- /lambda-url/{url_id}/
+CREATE TABLE IF NOT EXISTS function_urls (
+    url_id TEXT PRIMARY KEY,
+    function_id TEXT NOT NULL,
+    qualifier TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (function_id, qualifier),
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
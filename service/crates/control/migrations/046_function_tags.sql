@@ -0,0 +1,10 @@
+-- Function tags: key/value labels set with TagResource or at creation
+CREATE TABLE IF NOT EXISTS function_tags (
+    function_id TEXT NOT NULL,
+    tag_key TEXT NOT NULL,
+    tag_value TEXT NOT NULL,
+    PRIMARY KEY (function_id, tag_key),
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_function_tags_key_value ON function_tags (tag_key, tag_value);
@@ -0,0 +1,17 @@
+-- Function groups: folders functions are organized in, each function in at most one
+CREATE TABLE IF NOT EXISTS function_groups (
+    name TEXT PRIMARY KEY,
+    description TEXT,
+    disabled INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS function_group_members (
+    function_id TEXT PRIMARY KEY,
+    group_name TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE,
+    FOREIGN KEY (group_name) REFERENCES function_groups (name) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_function_group_members_group ON function_group_members (group_name);
@@ -0,0 +1,9 @@
+-- Provisioned concurrency: containers kept warm for a published version or an alias
+CREATE TABLE IF NOT EXISTS provisioned_concurrency (
+    function_id TEXT NOT NULL,
+    qualifier TEXT NOT NULL,
+    requested INTEGER NOT NULL,
+    last_modified TEXT NOT NULL,
+    PRIMARY KEY (function_id, qualifier),
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
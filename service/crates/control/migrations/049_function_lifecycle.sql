@@ -0,0 +1,9 @@
+-- Per-function warm pool lifecycle: idle timeout and warm container bounds
+CREATE TABLE IF NOT EXISTS function_lifecycle (
+    function_id TEXT PRIMARY KEY,
+    idle_timeout_secs INTEGER,
+    max_warm_containers INTEGER,
+    min_warm_containers INTEGER,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (function_id) REFERENCES functions (function_id) ON DELETE CASCADE
+);
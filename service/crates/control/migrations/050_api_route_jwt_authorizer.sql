@@ -0,0 +1,2 @@
+-- JWT authorizer of a route, as JSON ({ issuer, audience, jwks_url })
+ALTER TABLE api_routes ADD COLUMN jwt_authorizer TEXT NULL;
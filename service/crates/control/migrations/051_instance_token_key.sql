@@ -0,0 +1,6 @@
+-- Key the tokens of function containers are derived from; kept so containers adopted
+-- after a restart still prove who they are
+CREATE TABLE IF NOT EXISTS instance_token_key (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    key TEXT NOT NULL
+);
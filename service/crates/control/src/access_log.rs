@@ -0,0 +1,144 @@
+use lambda_models::{AccessLogConfig, AccessLogEntry};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use tracing::{info, warn};
+
+/// Appends lines to a file, moving it to `<path>.1` (and older ones up to `<path>.<max_files>`)
+/// once it grows past `max_bytes`
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, max_files: u32) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Writes access log entries to the configured file from a background thread, or to the
+/// server log when no file is set
+#[derive(Clone)]
+pub struct AccessLog {
+    enabled: bool,
+    file: Option<mpsc::Sender<AccessLogEntry>>,
+}
+
+impl AccessLog {
+    pub fn new(config: &AccessLogConfig) -> Self {
+        let file = match &config.file {
+            Some(path) if config.enabled => {
+                let max_bytes = config.max_file_mb * 1024 * 1024;
+                match RotatingFile::open(path, max_bytes, config.max_files) {
+                    Ok(file) => Some(spawn_writer(file)),
+                    Err(e) => {
+                        warn!(
+                            "Failed to open access log {}: {}; logging to the server log",
+                            path, e
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        Self {
+            enabled: config.enabled,
+            file,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn record(&self, entry: AccessLogEntry) {
+        if !self.enabled {
+            return;
+        }
+        match &self.file {
+            Some(sender) => {
+                let _ = sender.send(entry);
+            }
+            None => info!(
+                target: "access_log",
+                method = %entry.method,
+                path = %entry.path,
+                function_name = %entry.function_name,
+                route_id = ?entry.route_id,
+                status = entry.status,
+                latency_ms = entry.latency_ms,
+                source_ip = entry.source_ip.as_deref().unwrap_or("-"),
+                request_id = entry.request_id.as_deref().unwrap_or("-"),
+                "access"
+            ),
+        }
+    }
+}
+
+fn spawn_writer(mut file: RotatingFile) -> mpsc::Sender<AccessLogEntry> {
+    let (tx, rx) = mpsc::channel::<AccessLogEntry>();
+    std::thread::spawn(move || {
+        for entry in rx {
+            let line = serde_json::to_string(&entry).unwrap_or_default();
+            if let Err(e) = file.write_line(&line) {
+                warn!("Failed to write access log entry: {}", e);
+            }
+        }
+    });
+    tx
+}
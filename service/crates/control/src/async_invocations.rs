@@ -0,0 +1,207 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use lambda_models::{AsyncInvokeConfig, InvokeRequest, InvokeResponse, LambdaError};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::info;
+
+/// Longest the worker sleeps with nothing due; new events wake it at once
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Event invocations queued in `async_invocations` until an attempt at them succeeds or
+/// they run out of retries. The queue survives restarts, and attempts a restart cut short
+/// are made again
+#[derive(Clone)]
+pub struct AsyncInvocations {
+    pool: SqlitePool,
+    wake: Arc<Notify>,
+}
+
+/// An Event invocation claimed for an attempt
+#[derive(Debug, Clone)]
+pub struct QueuedEvent {
+    pub request_id: String,
+    pub request: InvokeRequest,
+    /// Attempts counted against `max_retry_attempts` so far; throttled ones are not
+    pub attempts: u32,
+    pub enqueued_at: DateTime<Utc>,
+}
+
+impl AsyncInvocations {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Queue `request` for its first attempt, under `request_id`
+    pub async fn enqueue(
+        &self,
+        request_id: &str,
+        request: &InvokeRequest,
+    ) -> Result<(), LambdaError> {
+        let now = Utc::now();
+        let body = serde_json::to_string(request).map_err(|e| LambdaError::InternalError {
+            reason: format!("Failed to encode event: {e}"),
+        })?;
+        sqlx::query(
+            "INSERT INTO async_invocations (request_id, function_name, request, attempts, enqueued_at, next_attempt_at) \
+             VALUES (?, ?, ?, 0, ?, ?)",
+        )
+        .bind(request_id)
+        .bind(&request.function_name)
+        .bind(body)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// Claim the event due longest ago, if any is due
+    pub async fn claim_next(&self) -> Result<Option<QueuedEvent>, LambdaError> {
+        let now = Utc::now();
+        let row = sqlx::query(
+            "UPDATE async_invocations SET claimed_at = ? WHERE request_id = (\
+               SELECT request_id FROM async_invocations \
+               WHERE claimed_at IS NULL AND next_attempt_at <= ? \
+               ORDER BY next_attempt_at LIMIT 1) \
+             RETURNING *",
+        )
+        .bind(now)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        row.map(|row| row_to_event(&row)).transpose()
+    }
+
+    /// Put a claimed event back, to be attempted again after `delay`
+    pub async fn retry(
+        &self,
+        request_id: &str,
+        attempts: u32,
+        delay: Duration,
+    ) -> Result<(), LambdaError> {
+        let next_attempt_at = Utc::now() + ChronoDuration::from_std(delay).unwrap_or_default();
+        sqlx::query(
+            "UPDATE async_invocations SET attempts = ?, next_attempt_at = ?, claimed_at = NULL \
+             WHERE request_id = ?",
+        )
+        .bind(attempts as i64)
+        .bind(next_attempt_at)
+        .bind(request_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Drop an event whose result is final
+    pub async fn remove(&self, request_id: &str) -> Result<(), LambdaError> {
+        sqlx::query("DELETE FROM async_invocations WHERE request_id = ?")
+            .bind(request_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Events a previous process claimed never finished their attempt; make them due again
+    pub async fn release_claimed(&self) -> Result<u64, LambdaError> {
+        let released = sqlx::query(
+            "UPDATE async_invocations SET claimed_at = NULL WHERE claimed_at IS NOT NULL",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .rows_affected();
+        if released > 0 {
+            info!("Requeued {} interrupted Event invocation(s)", released);
+        }
+        Ok(released)
+    }
+
+    /// Events waiting for an attempt or under way
+    pub async fn depth(&self) -> Result<u64, LambdaError> {
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM async_invocations")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(row.get::<i64, _>("count") as u64)
+    }
+
+    /// Wait until an event is queued, or at most `timeout`
+    pub async fn wait(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.wake.notified()).await;
+    }
+}
+
+/// Whether an attempt that ended with `result` is worth repeating: function errors,
+/// throttles and server-side failures are; requests that can never succeed are not
+pub fn is_retryable(result: &Result<InvokeResponse, LambdaError>) -> bool {
+    match result {
+        Ok(response) => response.function_error.is_some(),
+        Err(e) => e.http_status() == 429 || e.http_status() >= 500,
+    }
+}
+
+/// Wait before the retry following `attempts` counted attempts, doubling from the
+/// configured delay
+pub fn retry_delay(config: &AsyncInvokeConfig, attempts: u32) -> Duration {
+    let factor = 1u64 << attempts.saturating_sub(1).min(16);
+    Duration::from_millis(config.retry_delay_ms.saturating_mul(factor))
+}
+
+/// When to attempt `event` again after an attempt at `now` ended with `result`: the
+/// attempts counted by then and the wait, or `None` once the result is final. Throttled
+/// attempts are repeated without counting against `max_retry_attempts`, and nothing is
+/// attempted past `max_event_age_secs`
+pub fn next_attempt(
+    config: &AsyncInvokeConfig,
+    event: &QueuedEvent,
+    result: &Result<InvokeResponse, LambdaError>,
+    now: DateTime<Utc>,
+) -> Option<(u32, Duration)> {
+    if !is_retryable(result) {
+        return None;
+    }
+    let throttled = matches!(result, Err(e) if e.http_status() == 429);
+    let attempts = if throttled {
+        event.attempts
+    } else {
+        if event.attempts >= config.max_retry_attempts {
+            return None;
+        }
+        event.attempts + 1
+    };
+    let delay = retry_delay(config, attempts.max(1));
+    let due = now + ChronoDuration::from_std(delay).unwrap_or_default();
+    if due > expires_at(config, event) {
+        return None;
+    }
+    Some((attempts, delay))
+}
+
+/// When `event` is too old to be attempted
+pub fn expires_at(config: &AsyncInvokeConfig, event: &QueuedEvent) -> DateTime<Utc> {
+    event.enqueued_at + ChronoDuration::seconds(config.max_event_age_secs as i64)
+}
+
+fn row_to_event(row: &sqlx::sqlite::SqliteRow) -> Result<QueuedEvent, LambdaError> {
+    let request = serde_json::from_str(&row.get::<String, _>("request")).map_err(|e| {
+        LambdaError::InternalError {
+            reason: format!("Corrupt queued event: {e}"),
+        }
+    })?;
+    Ok(QueuedEvent {
+        request_id: row.get("request_id"),
+        request,
+        attempts: row.get::<i64, _>("attempts") as u32,
+        enqueued_at: row.get("enqueued_at"),
+    })
+}
@@ -1,26 +1,55 @@
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 use tracing::{error, info, instrument, warn};
 
-use crate::{registry::ControlPlane, warm_pool::InstanceState};
+use crate::{
+    instance_tokens::INSTANCE_TOKEN_ENV, queues::FnKey, registry::ControlPlane,
+    warm_pool::InstanceState,
+};
+use lambda_models::{BuildPriority, LambdaError};
+
+/// How often provisioned concurrency configs and min_warm_containers are re-read and their
+/// containers topped up
+const KEEP_WARM_TICK: Duration = Duration::from_secs(5);
 
 pub struct Autoscaler {
     control: Arc<ControlPlane>,
+    wake: Arc<Notify>,
 }
 
 impl Autoscaler {
     pub fn new(control: Arc<ControlPlane>) -> Self {
-        Self { control }
+        Self {
+            control,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Reconcile as soon as `wake` is notified instead of waiting for the next tick
+    pub fn with_wake(mut self, wake: Arc<Notify>) -> Self {
+        self.wake = wake;
+        self
     }
 
     #[instrument(skip(self))]
     pub async fn start(self) {
         let tick = Duration::from_millis(250);
+        let mut kept_warm_at: Option<Instant> = None;
         loop {
+            if kept_warm_at.is_none_or(|at| at.elapsed() >= KEEP_WARM_TICK) {
+                if let Err(e) = self.reconcile_kept_warm().await {
+                    warn!("autoscaler keep-warm error: {}", e);
+                }
+                kept_warm_at = Some(Instant::now());
+            }
             if let Err(e) = self.reconcile_once().await {
                 warn!("autoscaler reconcile error: {}", e);
             }
-            tokio::time::sleep(tick).await;
+            tokio::select! {
+                _ = tokio::time::sleep(tick) => {}
+                _ = self.wake.notified() => {}
+            }
         }
     }
 
@@ -36,62 +65,154 @@ impl Autoscaler {
                 .warm_pool()
                 .count_state(&key, InstanceState::WarmIdle)
                 .await;
+            // Containers running their prewarm invocation take work once it succeeds
+            let prewarming = self
+                .control
+                .warm_pool()
+                .count_state(&key, InstanceState::Initializing)
+                .await;
             let stopped = self
                 .control
                 .warm_pool()
                 .count_state(&key, InstanceState::Stopped)
                 .await;
-            let (to_restart, to_create) = plan_scale(qsize, idle, stopped);
+            let (to_restart, to_create) = plan_scale(qsize, idle + prewarming, stopped);
             if to_restart == 0 && to_create == 0 {
                 continue;
             }
+            self.scale_up(&key, to_restart, to_create).await?;
+        }
+        Ok(())
+    }
 
-            // Restart stopped ones first
-            let stopped_ids = self.control.warm_pool().list_stopped(&key).await;
-            for cid in stopped_ids.into_iter().take(to_restart) {
-                info!(
-                    "autoscaler: restarting stopped container {} for {}",
-                    cid, key.function_name
+    /// Keep the containers of every provisioned version and alias, and the min_warm_containers
+    /// of functions, running and exempt them from idle cleanup
+    async fn reconcile_kept_warm(&self) -> anyhow::Result<()> {
+        let mut targets = self.control.provisioned_targets().await?;
+        for (key, min_warm) in self.control.min_warm_targets().await? {
+            let target = targets.entry(key).or_insert(0);
+            *target = (*target).max(min_warm);
+        }
+        self.control.warm_pool().set_provisioned(targets.clone());
+        for (key, provisioned) in targets {
+            let running = self.control.warm_pool().count_running(&key).await;
+            let stopped = self
+                .control
+                .warm_pool()
+                .count_state(&key, InstanceState::Stopped)
+                .await;
+            let (to_restart, to_create) = plan_scale(provisioned, running, stopped);
+            if to_restart == 0 && to_create == 0 {
+                continue;
+            }
+            info!(
+                "autoscaler: keeping {} more containers warm for {}",
+                to_restart + to_create,
+                key.function_name
+            );
+            if let Err(e) = self.scale_up(&key, to_restart, to_create).await {
+                warn!(
+                    "autoscaler: failed to provision {}: {}",
+                    key.function_name, e
                 );
-                if let Err(e) = self.control.invoker().start_container(&cid).await {
-                    error!("start failed: {}", e);
-                    continue;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restart `to_restart` stopped containers of `key` and create `to_create` new ones, as
+    /// far as the function's max_warm_containers allows
+    async fn scale_up(
+        &self,
+        key: &FnKey,
+        mut to_restart: usize,
+        mut to_create: usize,
+    ) -> anyhow::Result<()> {
+        let function = self.control.function_for_key(key).await?;
+        if let Some(room) = self
+            .control
+            .warm_pool()
+            .warm_room(function.function_id)
+            .await
+        {
+            to_restart = to_restart.min(room);
+            to_create = to_create.min(room - to_restart);
+            if to_restart + to_create == 0 {
+                return Ok(());
+            }
+        }
+
+        // Restart stopped ones first
+        let stopped_ids = self.control.warm_pool().list_stopped(key).await;
+        for cid in stopped_ids.into_iter().take(to_restart) {
+            info!(
+                "autoscaler: restarting stopped container {} for {}",
+                cid, key.function_name
+            );
+            let _placement = match self.control.reserve_memory(key, function.memory_size).await {
+                Ok(guard) => guard,
+                Err(e) => {
+                    warn!("autoscaler: not restarting {}: {}", cid, e);
+                    break;
                 }
-                let _ = self
-                    .control
-                    .warm_pool()
-                    .set_state_by_container_id(&cid, InstanceState::WarmIdle)
-                    .await;
+            };
+            if let Err(e) = self.control.invoker().start_container(&cid).await {
+                error!("start failed: {}", e);
+                continue;
             }
+            let _ = self
+                .control
+                .warm_pool()
+                .set_state_by_container_id(&cid, InstanceState::WarmIdle)
+                .await;
+        }
 
-            // Create new containers as needed
-            for _ in 0..to_create {
-                if let Err(e) = self.create_one(&key).await {
-                    error!("create failed: {}", e);
+        // Create new containers as needed
+        for _ in 0..to_create {
+            if let Err(e) = self.create_one(key).await {
+                error!("create failed: {}", e);
+                if matches!(
+                    e.downcast_ref::<LambdaError>(),
+                    Some(LambdaError::HostMemoryExhausted { .. })
+                ) {
+                    break;
                 }
             }
         }
         Ok(())
     }
 
-    async fn create_one(&self, key: &crate::queues::FnKey) -> anyhow::Result<()> {
-        let function = self.control.get_function(&key.function_name).await?;
-        let image_ref = format!(
-            "lambda-home/{}:{}",
-            function.function_name, function.code_sha256
-        );
-        let config = self.control.config();
-        let mut packaging = lambda_packaging::PackagingService::new(config.clone());
-        packaging.build_image(&function, &image_ref, config.server.port_runtime_api).await?;
+    async fn create_one(&self, key: &FnKey) -> anyhow::Result<()> {
+        let function = self.control.function_for_key(key).await?;
+        let image_ref = function.image_ref();
+        self.control
+            .build_function_image(&function, &image_ref, BuildPriority::Normal)
+            .await?;
 
         let instance_id = uuid::Uuid::new_v4().to_string();
         let mut env_vars = function.environment.clone();
         env_vars.insert("LAMBDAH_INSTANCE_ID".to_string(), instance_id.clone());
+        env_vars.insert(
+            INSTANCE_TOKEN_ENV.to_string(),
+            self.control.instance_token(&instance_id),
+        );
+        let _placement = self
+            .control
+            .reserve_memory(key, function.memory_size)
+            .await?;
         let container_id = self
             .control
-            .invoker()
-            .create_container(&function, &image_ref, env_vars)
+            .create_function_container(&function, &image_ref, env_vars)
             .await?;
+        // Hold the prewarm event before the container starts polling
+        let prewarming = match self.control.prewarm_timeout(&function).await {
+            Some(timeout) => {
+                self.control
+                    .prewarm_container(&function, &container_id, &instance_id, timeout);
+                true
+            }
+            None => false,
+        };
         self.control
             .invoker()
             .start_container(&container_id)
@@ -102,9 +223,14 @@ impl Autoscaler {
             instance_id: instance_id.clone(),
             function_id: function.function_id,
             image_ref: image_ref.clone(),
+            memory_mb: function.memory_size,
             created_at: std::time::Instant::now(),
             last_used: std::time::Instant::now(),
-            state: InstanceState::WarmIdle,
+            state: if prewarming {
+                InstanceState::Initializing
+            } else {
+                InstanceState::WarmIdle
+            },
         };
         self.control
             .warm_pool()
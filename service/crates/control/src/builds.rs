@@ -0,0 +1,257 @@
+use chrono::{DateTime, Utc};
+use lambda_models::{Build, BuildFailureKind, BuildPriority, BuildStatus, Function, LambdaError};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+struct Entry {
+    build: Build,
+    seq: u64,
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Default)]
+struct State {
+    entries: Vec<Entry>,
+    next_seq: u64,
+}
+
+impl State {
+    /// The queued build to start next: urgent ones first, then the oldest
+    fn next_queued(&self) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter(|e| e.build.status == BuildStatus::Queued)
+            .min_by_key(|e| (std::cmp::Reverse(e.build.priority), e.seq))
+    }
+
+    fn running(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.build.status == BuildStatus::Running)
+            .count()
+    }
+}
+
+/// Image builds waiting for or holding one of `max_concurrent` build slots. Cancelling a
+/// build drops its future, which stops `docker build` and removes its build context.
+#[derive(Clone)]
+pub struct BuildQueue {
+    max_concurrent: usize,
+    state: Arc<Mutex<State>>,
+    changed: Arc<Notify>,
+}
+
+/// Removes a build from the queue however its future ends, freeing its slot
+struct EntryGuard<'a> {
+    queue: &'a BuildQueue,
+    build_id: String,
+}
+
+impl Drop for EntryGuard<'_> {
+    fn drop(&mut self) {
+        self.queue
+            .state
+            .lock()
+            .unwrap()
+            .entries
+            .retain(|e| e.build.build_id != self.build_id);
+        self.queue.changed.notify_waiters();
+    }
+}
+
+impl BuildQueue {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            state: Arc::new(Mutex::new(State::default())),
+            changed: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Run `build` once a slot is free and no build ahead of it is queued
+    pub async fn run<T>(
+        &self,
+        function_name: &str,
+        image_ref: &str,
+        priority: BuildPriority,
+        build: impl Future<Output = Result<T, LambdaError>>,
+    ) -> Result<T, LambdaError> {
+        let build_id = uuid::Uuid::new_v4().to_string();
+        let (cancel, mut cancelled) = oneshot::channel();
+        {
+            let mut state = self.state.lock().unwrap();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.entries.push(Entry {
+                build: Build {
+                    build_id: build_id.clone(),
+                    function_name: function_name.to_string(),
+                    image_ref: image_ref.to_string(),
+                    priority,
+                    status: BuildStatus::Queued,
+                    queued_at: Utc::now(),
+                    started_at: None,
+                },
+                seq,
+                cancel: Some(cancel),
+            });
+        }
+        let _entry = EntryGuard {
+            queue: self,
+            build_id: build_id.clone(),
+        };
+        let cancelled_err = || LambdaError::BuildCancelled {
+            build_id: build_id.clone(),
+        };
+
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.try_start(&build_id) {
+                break;
+            }
+            tokio::select! {
+                _ = &mut notified => {}
+                _ = &mut cancelled => return Err(cancelled_err()),
+            }
+        }
+
+        tokio::select! {
+            result = build => result,
+            _ = &mut cancelled => Err(cancelled_err()),
+        }
+    }
+
+    fn try_start(&self, build_id: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.running() >= self.max_concurrent
+            || state.next_queued().map(|e| e.build.build_id.as_str()) != Some(build_id)
+        {
+            return false;
+        }
+        let entry = state
+            .entries
+            .iter_mut()
+            .find(|e| e.build.build_id == build_id)
+            .expect("queued build is in the queue");
+        entry.build.status = BuildStatus::Running;
+        entry.build.started_at = Some(Utc::now());
+        true
+    }
+
+    /// Stop a queued or running build; whoever waits on it gets `BuildCancelled`
+    pub fn cancel(&self, build_id: &str) -> Result<Build, LambdaError> {
+        let mut state = self.state.lock().unwrap();
+        let entry = state
+            .entries
+            .iter_mut()
+            .find(|e| e.build.build_id == build_id)
+            .ok_or_else(|| LambdaError::BuildNotFound {
+                build_id: build_id.to_string(),
+            })?;
+        if let Some(cancel) = entry.cancel.take() {
+            let _ = cancel.send(());
+        }
+        Ok(entry.build.clone())
+    }
+
+    /// Running builds, then queued ones in the order they will start
+    pub fn list(&self) -> Vec<Build> {
+        let state = self.state.lock().unwrap();
+        let mut entries: Vec<&Entry> = state.entries.iter().collect();
+        entries.sort_by_key(|e| {
+            (
+                e.build.status == BuildStatus::Queued,
+                std::cmp::Reverse(e.build.priority),
+                e.seq,
+            )
+        });
+        entries.into_iter().map(|e| e.build.clone()).collect()
+    }
+}
+
+struct FailedBuild {
+    function_name: String,
+    /// `last_modified` of the function the build was for
+    function_modified: DateTime<Utc>,
+    failed_at: Instant,
+    kind: BuildFailureKind,
+    summary: String,
+    build_log: String,
+}
+
+/// Image builds that failed in a way building again will not fix, by image reference, so
+/// invocations get the failure at once instead of waiting on the same failing build. An
+/// entry lapses after the backoff, or once the function's code or configuration changed.
+#[derive(Clone)]
+pub struct FailedBuilds {
+    backoff: Duration,
+    failures: Arc<Mutex<HashMap<String, FailedBuild>>>,
+}
+
+impl FailedBuilds {
+    pub fn new(backoff: Duration) -> Self {
+        Self {
+            backoff,
+            failures: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The failure of the last build of `image_ref` for `function`, while it still stands
+    pub fn known_failure(&self, function: &Function, image_ref: &str) -> Option<LambdaError> {
+        let mut failures = self.failures.lock().unwrap();
+        let failure = failures.get(image_ref)?;
+        if failure.failed_at.elapsed() >= self.backoff
+            || failure.function_modified != function.last_modified
+        {
+            failures.remove(image_ref);
+            return None;
+        }
+        Some(LambdaError::ImageBuildFailed {
+            function_name: failure.function_name.clone(),
+            kind: failure.kind,
+            summary: failure.summary.clone(),
+            build_log: failure.build_log.clone(),
+        })
+    }
+
+    /// Remember how a build of `image_ref` ended; only failures that are not transient stick
+    pub fn record(&self, function: &Function, image_ref: &str, outcome: Result<(), &LambdaError>) {
+        let mut failures = self.failures.lock().unwrap();
+        match outcome {
+            Err(LambdaError::ImageBuildFailed {
+                kind,
+                summary,
+                build_log,
+                ..
+            }) if !kind.is_transient() && !self.backoff.is_zero() => {
+                failures.insert(
+                    image_ref.to_string(),
+                    FailedBuild {
+                        function_name: function.function_name.clone(),
+                        function_modified: function.last_modified,
+                        failed_at: Instant::now(),
+                        kind: *kind,
+                        summary: summary.clone(),
+                        build_log: build_log.clone(),
+                    },
+                );
+            }
+            _ => {
+                failures.remove(image_ref);
+            }
+        }
+    }
+
+    /// Forget the failed builds of a function, e.g. when its base image changed
+    pub fn forget_function(&self, function_name: &str) {
+        self.failures
+            .lock()
+            .unwrap()
+            .retain(|_, f| f.function_name != function_name);
+    }
+}
@@ -0,0 +1,102 @@
+use dashmap::DashMap;
+use lambda_models::{CanaryMatch, RouteMetrics, RouteSaturation, RouteTarget, RouteTargetStats};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Whether a request with `headers` (lowercase names) goes to the route's canary
+pub fn matches_canary(condition: &CanaryMatch, headers: &HashMap<String, String>) -> bool {
+    match condition {
+        CanaryMatch::Header { name, value } => headers
+            .iter()
+            .any(|(k, v)| k.eq_ignore_ascii_case(name) && v == value),
+        CanaryMatch::Cookie { name, value } => headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case("cookie"))
+            .flat_map(|(_, v)| v.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .any(|(k, v)| k == name && v == value),
+        CanaryMatch::Percentage { percent } => rand::random::<f64>() * 100.0 < *percent,
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    total_latency_ms: AtomicU64,
+    max_latency_ms: AtomicU64,
+}
+
+impl Counters {
+    fn record(&self, failed: bool, latency_ms: u64) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_latency_ms
+            .fetch_add(latency_ms, Ordering::Relaxed);
+        self.max_latency_ms.fetch_max(latency_ms, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> RouteTargetStats {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let errors = self.errors.load(Ordering::Relaxed);
+        let (error_rate, avg_latency_ms) = if requests == 0 {
+            (0.0, 0.0)
+        } else {
+            (
+                errors as f64 / requests as f64,
+                self.total_latency_ms.load(Ordering::Relaxed) as f64 / requests as f64,
+            )
+        };
+        RouteTargetStats {
+            requests,
+            errors,
+            error_rate,
+            avg_latency_ms,
+            max_latency_ms: self.max_latency_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Gateway requests per route and target, to compare a canary against the primary function
+#[derive(Clone, Default)]
+pub struct RouteMetricsTracker {
+    per_target: Arc<DashMap<(Uuid, RouteTarget), Counters>>,
+}
+
+impl RouteMetricsTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, route_id: Uuid, target: RouteTarget, failed: bool, latency_ms: u64) {
+        self.per_target
+            .entry((route_id, target))
+            .or_default()
+            .record(failed, latency_ms);
+    }
+
+    pub fn snapshot(&self, route_id: Uuid) -> RouteMetrics {
+        let stats = |target| {
+            self.per_target
+                .get(&(route_id, target))
+                .map(|c| c.snapshot())
+                .unwrap_or_default()
+        };
+        RouteMetrics {
+            route_id,
+            primary: stats(RouteTarget::Primary),
+            canary: stats(RouteTarget::Canary),
+            saturation: RouteSaturation::default(),
+        }
+    }
+
+    /// Drop the counters of a deleted route
+    pub fn remove_route(&self, route_id: Uuid) {
+        self.per_target.remove(&(route_id, RouteTarget::Primary));
+        self.per_target.remove(&(route_id, RouteTarget::Canary));
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_models::{ChangeEntity, ChangeEvent, ChangeFeedConfig, ChangeFeedResponse, LambdaError};
+use sqlx::{Row, SqlitePool};
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub const DEFAULT_CHANGE_LIMIT: u32 = 100;
+pub const MAX_CHANGE_LIMIT: u32 = 1000;
+/// Longest a read may wait for new events, as with long-polled queue receives
+pub const MAX_CHANGE_WAIT: Duration = Duration::from_secs(20);
+/// Triggers cannot wake readers, so waiting readers re-check at this interval
+const CHANGE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const CHANGE_PRUNE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Where a read starts. Cursors are the sequence of the last event seen, so they stay valid
+/// until that event is trimmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeCursor {
+    /// Oldest retained event
+    TrimHorizon,
+    /// Only events recorded after the read
+    Latest,
+    After(i64),
+}
+
+impl ChangeCursor {
+    pub fn parse(cursor: Option<&str>) -> Result<Self, LambdaError> {
+        match cursor.map(str::trim).filter(|c| !c.is_empty()) {
+            None => Ok(Self::TrimHorizon),
+            Some("latest") => Ok(Self::Latest),
+            Some(c) => c
+                .parse::<i64>()
+                .ok()
+                .filter(|sequence| *sequence >= 0)
+                .map(Self::After)
+                .ok_or_else(|| LambdaError::InvalidRequest {
+                    reason: format!("Invalid change feed cursor: {c}"),
+                }),
+        }
+    }
+}
+
+/// Reads the `change_events` table that triggers on functions, routes and executions fill
+#[derive(Clone)]
+pub struct ChangeFeed {
+    pool: SqlitePool,
+    config: ChangeFeedConfig,
+}
+
+impl ChangeFeed {
+    pub fn new(pool: SqlitePool, config: ChangeFeedConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Events after `cursor`, oldest first, optionally only for some entities. With a
+    /// `wait`, an empty read blocks until an event arrives or the wait runs out.
+    pub async fn read(
+        &self,
+        cursor: ChangeCursor,
+        limit: u32,
+        entities: &[ChangeEntity],
+        wait: Duration,
+    ) -> Result<ChangeFeedResponse, LambdaError> {
+        let limit = limit.clamp(1, MAX_CHANGE_LIMIT);
+        let after = match cursor {
+            ChangeCursor::TrimHorizon => self.first_available().await? - 1,
+            ChangeCursor::Latest => self.last_sequence().await?,
+            ChangeCursor::After(sequence) => {
+                self.check_not_trimmed(sequence).await?;
+                sequence
+            }
+        };
+        let deadline = tokio::time::Instant::now() + wait.min(MAX_CHANGE_WAIT);
+
+        loop {
+            let mut events = self.events_after(after, limit + 1, entities).await?;
+            let has_more = events.len() > limit as usize;
+            events.truncate(limit as usize);
+            let now = tokio::time::Instant::now();
+            if !events.is_empty() || now >= deadline {
+                let next = events.last().map_or(after, |e| e.sequence);
+                return Ok(ChangeFeedResponse {
+                    events,
+                    next_cursor: next.to_string(),
+                    has_more,
+                });
+            }
+            tokio::time::sleep((deadline - now).min(CHANGE_POLL_INTERVAL)).await;
+        }
+    }
+
+    async fn events_after(
+        &self,
+        after: i64,
+        limit: u32,
+        entities: &[ChangeEntity],
+    ) -> Result<Vec<ChangeEvent>, LambdaError> {
+        let entities = if entities.is_empty() {
+            &ChangeEntity::ALL[..]
+        } else {
+            entities
+        };
+        let placeholders = vec!["?"; entities.len()].join(", ");
+        let sql = format!(
+            "SELECT * FROM change_events WHERE sequence > ? AND entity IN ({placeholders}) ORDER BY sequence LIMIT ?"
+        );
+        let mut query = sqlx::query(&sql).bind(after);
+        for entity in entities {
+            query = query.bind(entity.as_str());
+        }
+        let rows = query
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        rows.iter().map(row_to_change_event).collect()
+    }
+
+    async fn last_sequence(&self) -> Result<i64, LambdaError> {
+        // sqlite_sequence keeps the highest sequence ever handed out, even once trimmed
+        let last: Option<i64> =
+            sqlx::query_scalar("SELECT seq FROM sqlite_sequence WHERE name = 'change_events'")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(last.unwrap_or(0))
+    }
+
+    /// Sequence of the oldest retained event, or of the next one when none are retained
+    async fn first_available(&self) -> Result<i64, LambdaError> {
+        let oldest: Option<i64> = sqlx::query_scalar("SELECT MIN(sequence) FROM change_events")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        match oldest {
+            Some(oldest) => Ok(oldest),
+            None => Ok(self.last_sequence().await? + 1),
+        }
+    }
+
+    /// A cursor expires once events right after it have been trimmed
+    async fn check_not_trimmed(&self, after: i64) -> Result<(), LambdaError> {
+        if after + 1 < self.first_available().await? {
+            return Err(LambdaError::ExpiredCursor {
+                cursor: after.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Trim events older than the retention window
+    pub async fn prune(&self) -> Result<u64, LambdaError> {
+        let cutoff = Utc::now() - ChronoDuration::hours(self.config.retention_hours as i64);
+        let removed = sqlx::query("DELETE FROM change_events WHERE occurred_at < ?")
+            .bind(cutoff.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .rows_affected();
+        if removed > 0 {
+            info!("Trimmed {} change feed event(s)", removed);
+        }
+        Ok(removed)
+    }
+
+    pub async fn start_pruning(self) {
+        loop {
+            if let Err(e) = self.prune().await {
+                warn!("change feed prune error: {}", e);
+            }
+            tokio::time::sleep(CHANGE_PRUNE_INTERVAL).await;
+        }
+    }
+}
+
+fn parse_image(image: Option<String>) -> Option<serde_json::Value> {
+    image.map(|i| serde_json::from_str(&i).unwrap_or(serde_json::Value::String(i)))
+}
+
+fn row_to_change_event(row: &sqlx::sqlite::SqliteRow) -> Result<ChangeEvent, LambdaError> {
+    let invalid = |e: String| LambdaError::InternalError {
+        reason: format!("Corrupt change event: {e}"),
+    };
+    Ok(ChangeEvent {
+        sequence: row.get("sequence"),
+        entity: row.get::<String, _>("entity").parse().map_err(invalid)?,
+        entity_id: row.get("entity_id"),
+        event_name: row
+            .get::<String, _>("event_name")
+            .parse()
+            .map_err(invalid)?,
+        occurred_at: row.get("occurred_at"),
+        new_image: parse_image(row.get("new_image")),
+        old_image: parse_image(row.get("old_image")),
+    })
+}
@@ -1,4 +1,4 @@
-use lambda_models::{Function, LambdaError};
+use lambda_models::{ConcurrencyOverview, ConcurrencyUsage, Function, LambdaError};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::sync::Semaphore;
@@ -24,7 +24,10 @@ impl Concurrency {
             "Acquired concurrency token, {} remaining",
             self.sem.available_permits()
         );
-        Ok(TokenGuard { _permit: permit })
+        Ok(TokenGuard {
+            _permit: permit,
+            _load: None,
+        })
     }
 
     /// Get the number of available permits
@@ -37,6 +40,7 @@ impl Concurrency {
 /// The token is automatically released when this guard is dropped
 pub struct TokenGuard {
     _permit: tokio::sync::OwnedSemaphorePermit, // keeps the token until drop
+    _load: Option<LoadGuard>,                   // counts the invocation as in flight
 }
 
 impl Drop for TokenGuard {
@@ -45,11 +49,78 @@ impl Drop for TokenGuard {
     }
 }
 
+/// Invocations of a function holding or waiting for a token
+#[derive(Default)]
+struct Load {
+    function_name: String,
+    in_flight: u32,
+    waiting: u32,
+}
+
+type Loads = Arc<Mutex<HashMap<uuid::Uuid, Load>>>;
+
+/// Counts an invocation as waiting until [`LoadGuard::start`], then as in flight, until
+/// dropped
+struct LoadGuard {
+    loads: Loads,
+    function_id: uuid::Uuid,
+    running: bool,
+}
+
+impl LoadGuard {
+    fn new(loads: &Loads, function: &Function) -> Self {
+        let mut map = loads.lock().unwrap();
+        let load = map.entry(function.function_id).or_default();
+        load.function_name = function.function_name.clone();
+        load.waiting += 1;
+        Self {
+            loads: loads.clone(),
+            function_id: function.function_id,
+            running: false,
+        }
+    }
+
+    fn start(&mut self) {
+        let mut map = self.loads.lock().unwrap();
+        if let Some(load) = map.get_mut(&self.function_id) {
+            load.waiting -= 1;
+            load.in_flight += 1;
+        }
+        self.running = true;
+    }
+}
+
+impl Drop for LoadGuard {
+    fn drop(&mut self) {
+        let mut map = self.loads.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(load) = map.get_mut(&self.function_id) {
+            if self.running {
+                load.in_flight -= 1;
+            } else {
+                load.waiting -= 1;
+            }
+            if load.in_flight == 0 && load.waiting == 0 {
+                map.remove(&self.function_id);
+            }
+        }
+    }
+}
+
+/// A function's reserved concurrency
+#[derive(Clone)]
+struct Reserved {
+    function_name: String,
+    limit: u32,
+    concurrency: Concurrency,
+}
+
 /// Concurrency manager that provides per-function or global concurrency control
 #[derive(Clone)]
 pub struct ConcurrencyManager {
     global: Concurrency,
-    per_function: Arc<Mutex<HashMap<uuid::Uuid, Concurrency>>>, // reserved limits per function
+    global_limit: usize,
+    per_function: Arc<Mutex<HashMap<uuid::Uuid, Reserved>>>, // reserved limits per function
+    loads: Loads,
 }
 
 impl Default for ConcurrencyManager {
@@ -60,83 +131,169 @@ impl Default for ConcurrencyManager {
 
 impl ConcurrencyManager {
     pub fn new() -> Self {
-        Self {
-            global: Concurrency::new(256), // Default global limit
-            per_function: Arc::new(Mutex::new(HashMap::new())),
-        }
+        Self::with_max_concurrency(256) // Default global limit
     }
 
     pub fn with_max_concurrency(limit: usize) -> Self {
         Self {
             global: Concurrency::new(limit),
+            global_limit: limit,
             per_function: Arc::new(Mutex::new(HashMap::new())),
+            loads: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    fn reserved(&self, function: &Function) -> Option<Concurrency> {
+        self.per_function
+            .lock()
+            .unwrap()
+            .get(&function.function_id)
+            .map(|reserved| reserved.concurrency.clone())
+    }
+
     /// Acquire a concurrency token for a function
     /// Currently uses global concurrency, but can be extended for per-function limits
     pub async fn acquire_token(&self, function: &Function) -> Result<TokenGuard, LambdaError> {
         // If a per-function limit exists, use it; otherwise use global
-        let per = {
-            self.per_function
-                .lock()
-                .unwrap()
-                .get(&function.function_id)
-                .cloned()
-        };
-        if let Some(c) = per {
-            return c.acquire().await.map_err(|e| LambdaError::InternalError {
-                reason: format!("Failed to acquire concurrency token: {e}"),
-            });
-        }
-        self.global
+        let concurrency = self
+            .reserved(function)
+            .unwrap_or_else(|| self.global.clone());
+        let mut load = LoadGuard::new(&self.loads, function);
+        let mut guard = concurrency
             .acquire()
             .await
             .map_err(|e| LambdaError::InternalError {
                 reason: format!("Failed to acquire concurrency token: {e}"),
-            })
+            })?;
+        load.start();
+        guard._load = Some(load);
+        Ok(guard)
     }
 
     /// Try to acquire a concurrency token without blocking
     /// Returns an error if no tokens are available
     pub fn try_acquire_token(&self, function: &Function) -> Result<TokenGuard, LambdaError> {
-        let per = {
-            self.per_function
-                .lock()
-                .unwrap()
-                .get(&function.function_id)
-                .cloned()
-        };
-        if let Some(c) = per {
-            return c
+        let concurrency = self
+            .reserved(function)
+            .unwrap_or_else(|| self.global.clone());
+        let permit =
+            concurrency
                 .sem
-                .clone()
                 .try_acquire_owned()
-                .map(|permit| TokenGuard { _permit: permit })
                 .map_err(|_| LambdaError::InternalError {
                     reason: "No concurrency tokens available".to_string(),
-                });
-        }
-        self.global
-            .sem
-            .clone()
-            .try_acquire_owned()
-            .map(|permit| TokenGuard { _permit: permit })
-            .map_err(|_| LambdaError::InternalError {
-                reason: "No concurrency tokens available".to_string(),
-            })
+                })?;
+        let mut load = LoadGuard::new(&self.loads, function);
+        load.start();
+        Ok(TokenGuard {
+            _permit: permit,
+            _load: Some(load),
+        })
     }
 
     /// Set or clear a per-function reserved concurrency limit
-    pub fn set_reserved_limit(&self, function_id: uuid::Uuid, limit: Option<u32>) {
+    pub fn set_reserved_limit(&self, function: &Function, limit: Option<u32>) {
         let mut map = self.per_function.lock().unwrap();
         match limit {
             Some(n) => {
-                map.insert(function_id, Concurrency::new(n as usize));
+                map.insert(
+                    function.function_id,
+                    Reserved {
+                        function_name: function.function_name.clone(),
+                        limit: n,
+                        concurrency: Concurrency::new(n as usize),
+                    },
+                );
             }
             None => {
-                map.remove(&function_id);
+                map.remove(&function.function_id);
             }
         }
     }
+
+    /// Invocations of `function` running and waiting now, against its limit
+    pub fn usage(&self, function: &Function) -> ConcurrencyUsage {
+        let (in_flight, waiting) = self
+            .loads
+            .lock()
+            .unwrap()
+            .get(&function.function_id)
+            .map_or((0, 0), |load| (load.in_flight, load.waiting));
+        let reserved = self
+            .per_function
+            .lock()
+            .unwrap()
+            .get(&function.function_id)
+            .map(|reserved| reserved.limit);
+        self.usage_of(function.function_name.clone(), in_flight, waiting, reserved)
+    }
+
+    /// Usage of every function with invocations running or waiting, or with reserved
+    /// concurrency, by name
+    pub fn overview(&self) -> ConcurrencyOverview {
+        let mut functions: HashMap<uuid::Uuid, (String, u32, u32, Option<u32>)> = self
+            .per_function
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, reserved)| {
+                (
+                    *id,
+                    (reserved.function_name.clone(), 0, 0, Some(reserved.limit)),
+                )
+            })
+            .collect();
+        for (id, load) in self.loads.lock().unwrap().iter() {
+            let entry = functions
+                .entry(*id)
+                .or_insert_with(|| (String::new(), 0, 0, None));
+            entry.0 = load.function_name.clone();
+            entry.1 = load.in_flight;
+            entry.2 = load.waiting;
+        }
+        let mut functions: Vec<ConcurrencyUsage> = functions
+            .into_values()
+            .map(|(name, in_flight, waiting, reserved)| {
+                self.usage_of(name, in_flight, waiting, reserved)
+            })
+            .collect();
+        functions.sort_by(|a, b| a.function_name.cmp(&b.function_name));
+        ConcurrencyOverview {
+            unreserved_concurrency_limit: self.global_limit as u32,
+            unreserved_in_flight: self.unreserved_in_flight(),
+            functions,
+        }
+    }
+
+    fn unreserved_in_flight(&self) -> u32 {
+        self.global_limit
+            .saturating_sub(self.global.available_permits()) as u32
+    }
+
+    fn usage_of(
+        &self,
+        function_name: String,
+        in_flight: u32,
+        waiting: u32,
+        reserved: Option<u32>,
+    ) -> ConcurrencyUsage {
+        let unreserved_in_flight = self.unreserved_in_flight();
+        let (used, limit) = match reserved {
+            Some(limit) => (in_flight, limit),
+            None => (unreserved_in_flight, self.global_limit as u32),
+        };
+        ConcurrencyUsage {
+            function_name,
+            in_flight,
+            waiting,
+            reserved_concurrent_executions: reserved,
+            unreserved_concurrency_limit: self.global_limit as u32,
+            unreserved_in_flight,
+            utilization: if limit == 0 {
+                1.0
+            } else {
+                used as f64 / limit as f64
+            },
+        }
+    }
 }
@@ -0,0 +1,112 @@
+use chrono::Utc;
+use lambda_models::{
+    ConfigChange, ConfigChangeSource, ConfigRevision, FunctionConfigSnapshot, LambdaError,
+};
+use serde_json::{json, Value};
+use sqlx::{Row, Sqlite};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// Settings that differ between two configurations, in a fixed order with environment
+/// variables last and sorted by name. Without `old`, every setting counts as added.
+pub fn diff_configs(
+    old: Option<&FunctionConfigSnapshot>,
+    new: &FunctionConfigSnapshot,
+) -> Vec<ConfigChange> {
+    let fields = |c: &FunctionConfigSnapshot| {
+        [
+            ("role", json!(c.role)),
+            ("handler", json!(c.handler)),
+            ("description", json!(c.description)),
+            ("timeout", json!(c.timeout)),
+            ("memory_size", json!(c.memory_size)),
+        ]
+    };
+    let present = |v: Value| Some(v).filter(|v| !v.is_null());
+
+    let mut changes = Vec::new();
+    let old_fields = old.map(fields);
+    for (i, (field, new_value)) in fields(new).into_iter().enumerate() {
+        let old_value = old_fields.as_ref().map_or(Value::Null, |f| f[i].1.clone());
+        if old_value != new_value {
+            changes.push(ConfigChange {
+                field: field.to_string(),
+                old: present(old_value),
+                new: present(new_value),
+            });
+        }
+    }
+
+    let old_env = old.map(|o| &o.environment);
+    let names: BTreeSet<&String> = new
+        .environment
+        .keys()
+        .chain(old_env.into_iter().flat_map(|env| env.keys()))
+        .collect();
+    for name in names {
+        let old_value = old_env.and_then(|env| env.get(name));
+        let new_value = new.environment.get(name);
+        if old_value != new_value {
+            changes.push(ConfigChange {
+                field: format!("environment.{name}"),
+                old: old_value.map(|v| json!(v)),
+                new: new_value.map(|v| json!(v)),
+            });
+        }
+    }
+    changes
+}
+
+/// Append the next revision for a function. Updates that change nothing are not recorded;
+/// returns the revision number when one was written.
+pub async fn insert_revision<'c>(
+    executor: impl sqlx::Executor<'c, Database = Sqlite>,
+    function_id: Uuid,
+    before: Option<&FunctionConfigSnapshot>,
+    after: &FunctionConfigSnapshot,
+    source: ConfigChangeSource,
+    reverted_from: Option<u64>,
+    changed_by: Option<&str>,
+) -> Result<Option<u64>, LambdaError> {
+    let changes = diff_configs(before, after);
+    if changes.is_empty() && source != ConfigChangeSource::Create {
+        return Ok(None);
+    }
+    let revision: i64 = sqlx::query_scalar(
+        r#"INSERT INTO function_config_revisions
+               (function_id, revision, changed_at, changed_by, source, reverted_from, changes, configuration)
+           SELECT ?, COALESCE(MAX(revision), 0) + 1, ?, ?, ?, ?, ?, ?
+           FROM function_config_revisions WHERE function_id = ?
+           RETURNING revision"#,
+    )
+    .bind(function_id)
+    .bind(Utc::now())
+    .bind(changed_by)
+    .bind(source.as_str())
+    .bind(reverted_from.map(|r| r as i64))
+    .bind(serde_json::to_string(&changes).unwrap_or_default())
+    .bind(serde_json::to_string(after).unwrap_or_default())
+    .bind(function_id)
+    .fetch_one(executor)
+    .await
+    .map_err(LambdaError::SqlxError)?;
+    Ok(Some(revision as u64))
+}
+
+pub fn row_to_config_revision(
+    row: &sqlx::sqlite::SqliteRow,
+) -> Result<ConfigRevision, LambdaError> {
+    let invalid = |e: String| LambdaError::InternalError {
+        reason: format!("Corrupt config revision: {e}"),
+    };
+    Ok(ConfigRevision {
+        revision: row.get::<i64, _>("revision") as u64,
+        changed_at: row.get("changed_at"),
+        changed_by: row.get("changed_by"),
+        source: row.get::<String, _>("source").parse().map_err(invalid)?,
+        reverted_from: row.get::<Option<i64>, _>("reverted_from").map(|r| r as u64),
+        changes: serde_json::from_str(row.get("changes")).map_err(|e| invalid(e.to_string()))?,
+        configuration: serde_json::from_str(row.get("configuration"))
+            .map_err(|e| invalid(e.to_string()))?,
+    })
+}
@@ -1,15 +1,19 @@
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 use tokio::time::interval;
 
+use crate::pending::Pending;
+use crate::queues::{FnKey, Queues};
 use crate::warm_pool::{InstanceState, WarmPool};
 use lambda_invoker::{ContainerEvent, ContainerEventSender};
-use tracing::{error, info, instrument, warn};
+use tracing::{debug, error, info, instrument, warn};
 
 pub struct ContainerMonitor {
     warm_pool: Arc<WarmPool>,
     event_receiver: mpsc::UnboundedReceiver<ContainerEvent>,
+    pending: Option<Pending>,
+    replacement: Option<(Queues, Arc<Notify>)>,
 }
 
 impl ContainerMonitor {
@@ -19,11 +23,37 @@ impl ContainerMonitor {
         let monitor = Self {
             warm_pool,
             event_receiver: receiver,
+            pending: None,
+            replacement: None,
         };
 
         (monitor, sender)
     }
 
+    /// Fail in-flight invocations when the container running them dies
+    pub fn with_pending(mut self, pending: Pending) -> Self {
+        self.pending = Some(pending);
+        self
+    }
+
+    /// Wake the autoscaler when a lost container's function still has queued work
+    pub fn with_replacement(mut self, queues: Queues, wake: Arc<Notify>) -> Self {
+        self.replacement = Some((queues, wake));
+        self
+    }
+
+    fn fail_in_flight(&self, container_id: &str, reason: &str) {
+        if let Some(pending) = &self.pending {
+            let failed = pending.fail_container(container_id, reason);
+            if failed > 0 {
+                warn!(
+                    "Failed {} in-flight invocation(s) on container {}: {}",
+                    failed, container_id, reason
+                );
+            }
+        }
+    }
+
     #[instrument(skip(self))]
     pub async fn start(mut self) {
         info!("Starting container state monitor");
@@ -64,24 +94,22 @@ impl ContainerMonitor {
                     "Container died: {} (exit code: {:?})",
                     container_id, exit_code
                 );
-
-                // Remove from warm pool since container is dead
-                if let Err(e) = self.warm_pool.remove_container_by_id(&container_id).await {
-                    warn!(
-                        "Failed to remove dead container {} from warm pool: {}",
-                        container_id, e
-                    );
-                }
+                let reason = match exit_code {
+                    Some(code) => format!("Container exited mid-invocation (exit code {code})"),
+                    None => "Container exited mid-invocation".to_string(),
+                };
+                self.container_exited(&container_id, &reason).await;
             }
 
             ContainerEvent::Stop { container_id } => {
                 info!("Container stopped: {}", container_id);
 
                 // Mark as stopped in warm pool
-                if !self
-                    .warm_pool
-                    .set_state_by_container_id(&container_id, InstanceState::Stopped)
-                    .await
+                if self.warm_pool.find_container(&container_id).await.is_some()
+                    && !self
+                        .warm_pool
+                        .set_state_by_container_id(&container_id, InstanceState::Stopped)
+                        .await
                 {
                     warn!("Failed to mark container {} as stopped", container_id);
                 }
@@ -89,36 +117,36 @@ impl ContainerMonitor {
 
             ContainerEvent::Kill { container_id } => {
                 info!("Container killed: {}", container_id);
-
-                // Remove from warm pool since container was killed
-                if let Err(e) = self.warm_pool.remove_container_by_id(&container_id).await {
-                    warn!(
-                        "Failed to remove killed container {} from warm pool: {}",
-                        container_id, e
-                    );
-                }
+                self.container_exited(&container_id, "Container was killed mid-invocation")
+                    .await;
             }
 
             ContainerEvent::Remove { container_id } => {
                 info!("Container removed: {}", container_id);
 
                 // Remove from warm pool since container no longer exists
-                if let Err(e) = self.warm_pool.remove_container_by_id(&container_id).await {
-                    warn!(
-                        "Failed to remove deleted container {} from warm pool: {}",
-                        container_id, e
-                    );
-                }
+                let key = self
+                    .warm_pool
+                    .find_container(&container_id)
+                    .await
+                    .map(|(key, _)| key);
+                self.container_lost(
+                    &container_id,
+                    key.as_ref(),
+                    "Container was removed mid-invocation",
+                )
+                .await;
             }
 
             ContainerEvent::Start { container_id } => {
                 info!("Container started: {}", container_id);
 
                 // Mark as warm idle when container starts
-                if !self
-                    .warm_pool
-                    .set_state_by_container_id(&container_id, InstanceState::WarmIdle)
-                    .await
+                if self.warm_pool.find_container(&container_id).await.is_some()
+                    && !self
+                        .warm_pool
+                        .set_state_by_container_id(&container_id, InstanceState::WarmIdle)
+                        .await
                 {
                     warn!("Failed to mark container {} as warm idle", container_id);
                 }
@@ -133,6 +161,49 @@ impl ContainerMonitor {
         Ok(())
     }
 
+    /// Die and kill events. Containers we are stopping ourselves (idle watchdog, memory
+    /// placement) stay in the pool as stopped; any other exit loses the container.
+    async fn container_exited(&self, container_id: &str, reason: &str) {
+        match self.warm_pool.find_container(container_id).await {
+            Some((_, InstanceState::Stopping | InstanceState::Stopped)) => {
+                self.warm_pool
+                    .set_state_by_container_id(container_id, InstanceState::Stopped)
+                    .await;
+            }
+            found => {
+                let key = found.map(|(key, _)| key);
+                self.container_lost(container_id, key.as_ref(), reason)
+                    .await;
+            }
+        }
+    }
+
+    async fn container_lost(&self, container_id: &str, key: Option<&FnKey>, reason: &str) {
+        if key.is_some() {
+            if let Err(e) = self.warm_pool.remove_container_by_id(container_id).await {
+                warn!(
+                    "Failed to remove container {} from warm pool: {}",
+                    container_id, e
+                );
+            }
+        } else {
+            debug!("Ignoring container {} outside the warm pool", container_id);
+        }
+
+        // Removed first so a retry cannot be routed back to this container
+        self.fail_in_flight(container_id, reason);
+
+        if let (Some(key), Some((queues, wake))) = (key, &self.replacement) {
+            if queues.queue_size(key) > 0 {
+                info!(
+                    "Replacing lost container {} for {} with work queued",
+                    container_id, key.function_name
+                );
+                wake.notify_one();
+            }
+        }
+    }
+
     async fn sync_container_states(
         warm_pool: &WarmPool,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -0,0 +1,111 @@
+use crate::insights::percentile;
+use lambda_models::{
+    Architecture, AwsPricingConfig, CostEstimate, DurationDistribution, FunctionCostEstimate,
+};
+
+/// Executions priced when a request names no window
+pub const DEFAULT_COST_WINDOW: &str = "30d";
+/// Hours in the month AWS prices are quoted for
+pub const HOURS_PER_MONTH: f64 = 730.0;
+
+/// Finished executions of one function within the window
+#[derive(Debug, Clone, Default)]
+pub struct RecordedUsage {
+    pub function_name: String,
+    pub memory_size_mb: u64,
+    pub durations_ms: Vec<u64>,
+    pub max_memory_used_mb: Option<u64>,
+}
+
+/// USD per GB-second on `architecture`
+pub fn gb_second_price(pricing: &AwsPricingConfig, architecture: Architecture) -> f64 {
+    match architecture {
+        Architecture::X86_64 => pricing.x86_64_gb_second_price,
+        Architecture::Arm64 => pricing.arm64_gb_second_price,
+    }
+}
+
+/// Price one function's executions. AWS bills each invocation's duration rounded up to the
+/// millisecond, at least 1 ms, times the configured memory.
+pub fn estimate_function(
+    mut usage: RecordedUsage,
+    pricing: &AwsPricingConfig,
+    architecture: Architecture,
+    window_secs: u64,
+) -> FunctionCostEstimate {
+    usage.durations_ms.sort_unstable();
+    let invocations = usage.durations_ms.len() as u64;
+    let billed_ms: u64 = usage.durations_ms.iter().map(|d| (*d).max(1)).sum();
+    let gb_seconds = billed_ms as f64 / 1000.0 * usage.memory_size_mb as f64 / 1024.0;
+    let request_cost = invocations as f64 * pricing.request_price_per_million / 1_000_000.0;
+    let compute_cost = gb_seconds * gb_second_price(pricing, architecture);
+    let cost = request_cost + compute_cost;
+    FunctionCostEstimate {
+        duration: distribution(&usage.durations_ms),
+        function_name: usage.function_name,
+        memory_size_mb: usage.memory_size_mb,
+        invocations,
+        max_memory_used_mb: usage.max_memory_used_mb,
+        gb_seconds,
+        request_cost,
+        compute_cost,
+        cost,
+        monthly_cost: cost * months_factor(window_secs),
+    }
+}
+
+/// Add up the functions' estimates and take the monthly free tier off the account's total
+pub fn estimate(
+    usage: Vec<RecordedUsage>,
+    pricing: AwsPricingConfig,
+    architecture: Architecture,
+    window_secs: u64,
+) -> CostEstimate {
+    let mut functions: Vec<FunctionCostEstimate> = usage
+        .into_iter()
+        .map(|usage| estimate_function(usage, &pricing, architecture, window_secs))
+        .collect();
+    functions.sort_by(|a, b| {
+        b.cost
+            .total_cmp(&a.cost)
+            .then_with(|| a.function_name.cmp(&b.function_name))
+    });
+
+    let invocations: u64 = functions.iter().map(|f| f.invocations).sum();
+    let gb_seconds: f64 = functions.iter().map(|f| f.gb_seconds).sum();
+    let cost: f64 = functions.iter().map(|f| f.cost).sum();
+    let factor = months_factor(window_secs);
+    let monthly_requests = invocations as f64 * factor;
+    let monthly_gb_seconds = gb_seconds * factor;
+    let billed_requests = (monthly_requests - pricing.free_requests_per_month as f64).max(0.0);
+    let billed_gb_seconds = (monthly_gb_seconds - pricing.free_gb_seconds_per_month).max(0.0);
+    CostEstimate {
+        generated_at: chrono::Utc::now(),
+        window_seconds: window_secs,
+        architecture,
+        functions,
+        invocations,
+        gb_seconds,
+        cost,
+        monthly_cost_before_free_tier: cost * factor,
+        monthly_cost: billed_requests * pricing.request_price_per_million / 1_000_000.0
+            + billed_gb_seconds * gb_second_price(&pricing, architecture),
+        pricing,
+    }
+}
+
+/// How many windows fit in a month
+fn months_factor(window_secs: u64) -> f64 {
+    HOURS_PER_MONTH * 3600.0 / window_secs.max(1) as f64
+}
+
+fn distribution(sorted_ms: &[u64]) -> Option<DurationDistribution> {
+    let max_ms = *sorted_ms.last()?;
+    Some(DurationDistribution {
+        p50_ms: percentile(sorted_ms, 50.0)?,
+        p90_ms: percentile(sorted_ms, 90.0)?,
+        p99_ms: percentile(sorted_ms, 99.0)?,
+        max_ms,
+        mean_ms: sorted_ms.iter().sum::<u64>() as f64 / sorted_ms.len() as f64,
+    })
+}
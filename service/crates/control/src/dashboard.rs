@@ -0,0 +1,200 @@
+use crate::queues::FnKey;
+use crate::warm_pool::{InstanceState, WarmContainer};
+use lambda_models::{
+    AlertSeverity, DashboardAlert, DiskPressure, DiskStatus, FunctionInvocationTotals,
+    FunctionState, FunctionStateCounts, InvocationTotals, WarmPoolOccupancy,
+};
+use std::time::Duration;
+
+/// Window the dashboard invocation totals cover
+pub const DASHBOARD_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+pub const RECENT_DEPLOYMENTS_LIMIT: i64 = 10;
+/// A function is flagged once this share of its invocations fail...
+pub const HIGH_ERROR_RATE: f64 = 0.5;
+/// ...provided it was invoked at least this often in the window
+pub const HIGH_ERROR_RATE_MIN_INVOCATIONS: u64 = 10;
+/// Share of the host memory budget above which placement is about to start refusing
+pub const HOST_MEMORY_ALERT_RATIO: f64 = 0.9;
+
+pub fn error_rate(errors: u64, invocations: u64) -> f64 {
+    if invocations == 0 {
+        0.0
+    } else {
+        errors as f64 / invocations as f64
+    }
+}
+
+pub fn count_states<'a>(
+    states: impl IntoIterator<Item = &'a FunctionState>,
+) -> FunctionStateCounts {
+    let mut counts = FunctionStateCounts::default();
+    for state in states {
+        counts.total += 1;
+        match state {
+            FunctionState::Pending => counts.pending += 1,
+            FunctionState::Active => counts.active += 1,
+            FunctionState::Inactive => counts.inactive += 1,
+            FunctionState::Failed => counts.failed += 1,
+            FunctionState::Archived => counts.archived += 1,
+        }
+    }
+    counts
+}
+
+/// Sum per-function totals and order them busiest first
+pub fn invocation_totals(
+    window: Duration,
+    mut by_function: Vec<FunctionInvocationTotals>,
+    avg_duration_ms: Option<f64>,
+) -> InvocationTotals {
+    by_function.sort_by(|a, b| {
+        b.invocations
+            .cmp(&a.invocations)
+            .then_with(|| a.function_name.cmp(&b.function_name))
+    });
+    let invocations = by_function.iter().map(|f| f.invocations).sum();
+    let errors = by_function.iter().map(|f| f.errors).sum();
+    InvocationTotals {
+        window_seconds: window.as_secs(),
+        invocations,
+        errors,
+        error_rate: error_rate(errors, invocations),
+        avg_duration_ms,
+        by_function,
+    }
+}
+
+pub fn occupancy(
+    containers: &[(FnKey, Vec<WarmContainer>)],
+    host_memory_budget_mb: Option<u64>,
+) -> WarmPoolOccupancy {
+    let mut occupancy = WarmPoolOccupancy {
+        host_memory_budget_mb,
+        ..Default::default()
+    };
+    for container in containers.iter().flat_map(|(_, list)| list) {
+        match container.state {
+            InstanceState::WarmIdle => occupancy.warm_idle += 1,
+            InstanceState::Active => occupancy.active += 1,
+            InstanceState::Stopped => occupancy.stopped += 1,
+            _ => {}
+        }
+        if !container.state.releases_memory() {
+            occupancy.committed_memory_mb += container.memory_mb;
+        }
+    }
+    occupancy.total = occupancy.warm_idle + occupancy.active + occupancy.stopped;
+    occupancy
+}
+
+fn alert(
+    severity: AlertSeverity,
+    code: &str,
+    message: String,
+    function_name: Option<&str>,
+) -> DashboardAlert {
+    DashboardAlert {
+        severity,
+        code: code.to_string(),
+        message,
+        function_name: function_name.map(str::to_string),
+    }
+}
+
+/// Derive alerts from the figures already gathered for the dashboard.
+/// `failed_functions` holds the name and state reason of every function in the Failed state,
+/// `failing_checks` the name and failure of every function whose synthetic check last failed.
+pub fn build_alerts(
+    failed_functions: &[(String, Option<String>)],
+    failing_checks: &[(String, Option<String>)],
+    invocations: &InvocationTotals,
+    warm_pool: &WarmPoolOccupancy,
+    disk: &DiskStatus,
+) -> Vec<DashboardAlert> {
+    let mut alerts = Vec::new();
+
+    for (name, reason) in failed_functions {
+        let message = match reason {
+            Some(reason) => format!("Function {name} is in the Failed state: {reason}"),
+            None => format!("Function {name} is in the Failed state"),
+        };
+        alerts.push(alert(
+            AlertSeverity::Critical,
+            "FunctionFailed",
+            message,
+            Some(name),
+        ));
+    }
+
+    for (name, failure) in failing_checks {
+        let message = match failure {
+            Some(failure) => format!("Synthetic check of {name} is failing: {failure}"),
+            None => format!("Synthetic check of {name} is failing"),
+        };
+        alerts.push(alert(
+            AlertSeverity::Critical,
+            "SyntheticCheckFailing",
+            message,
+            Some(name),
+        ));
+    }
+
+    match disk.pressure {
+        DiskPressure::Critical => alerts.push(alert(
+            AlertSeverity::Critical,
+            "DiskPressure",
+            format!(
+                "Free disk is below {} MB; new builds are refused",
+                disk.min_free_mb
+            ),
+            None,
+        )),
+        DiskPressure::Low => alerts.push(alert(
+            AlertSeverity::Warning,
+            "DiskPressure",
+            format!(
+                "Free disk is below {} MB; build artifacts are being collected",
+                disk.gc_below_free_mb
+            ),
+            None,
+        )),
+        DiskPressure::Ok => {}
+    }
+
+    if let Some(budget_mb) = warm_pool.host_memory_budget_mb.filter(|b| *b > 0) {
+        if warm_pool.committed_memory_mb as f64 >= budget_mb as f64 * HOST_MEMORY_ALERT_RATIO {
+            alerts.push(alert(
+                AlertSeverity::Warning,
+                "HostMemoryNearlyFull",
+                format!(
+                    "Containers hold {} of {} MB of the host memory budget",
+                    warm_pool.committed_memory_mb, budget_mb
+                ),
+                None,
+            ));
+        }
+    }
+
+    for function in &invocations.by_function {
+        if function.invocations >= HIGH_ERROR_RATE_MIN_INVOCATIONS
+            && function.error_rate >= HIGH_ERROR_RATE
+        {
+            alerts.push(alert(
+                AlertSeverity::Warning,
+                "HighErrorRate",
+                format!(
+                    "{} of {} invocations of {} failed in the last {}h",
+                    function.errors,
+                    function.invocations,
+                    function.function_name,
+                    invocations.window_seconds / 3600
+                ),
+                Some(&function.function_name),
+            ));
+        }
+    }
+
+    // Stable sort keeps the grouping above within each severity
+    alerts.sort_by_key(|a| a.severity);
+    alerts
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, Utc};
+use lambda_models::{DiskConfig, DiskLocationStatus, DiskPressure, DiskStatus, LambdaError};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+const MB: u64 = 1024 * 1024;
+
+/// Free and total bytes of the filesystem holding `path`
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // statvfs field widths differ between platforms
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL-terminated and stat is a valid out-pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some((stat.f_bavail as u64 * block, stat.f_blocks as u64 * block))
+}
+
+#[cfg(not(unix))]
+pub fn disk_space(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+pub fn classify(free_bytes: u64, config: &DiskConfig) -> DiskPressure {
+    if free_bytes < config.min_free_mb * MB {
+        DiskPressure::Critical
+    } else if free_bytes < config.gc_below_free_mb * MB {
+        DiskPressure::Low
+    } else {
+        DiskPressure::Ok
+    }
+}
+
+fn worst(a: DiskPressure, b: DiskPressure) -> DiskPressure {
+    match (a, b) {
+        (DiskPressure::Critical, _) | (_, DiskPressure::Critical) => DiskPressure::Critical,
+        (DiskPressure::Low, _) | (_, DiskPressure::Low) => DiskPressure::Low,
+        _ => DiskPressure::Ok,
+    }
+}
+
+pub fn measure(path: &str, config: &DiskConfig) -> Option<DiskLocationStatus> {
+    let (free_bytes, total_bytes) = disk_space(Path::new(path))?;
+    Some(DiskLocationStatus {
+        path: path.to_string(),
+        free_bytes,
+        total_bytes,
+        pressure: classify(free_bytes, config),
+    })
+}
+
+/// Latest disk measurements for the data dir and the Docker root
+#[derive(Clone)]
+pub struct DiskMonitor {
+    config: DiskConfig,
+    data_dir: String,
+    docker_root: Arc<RwLock<Option<String>>>,
+    last_gc_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    status: Arc<RwLock<Option<DiskStatus>>>,
+    alerted: Arc<RwLock<DiskPressure>>,
+}
+
+impl DiskMonitor {
+    pub fn new(config: DiskConfig, data_dir: String) -> Self {
+        Self {
+            config,
+            data_dir,
+            docker_root: Arc::new(RwLock::new(None)),
+            last_gc_at: Arc::new(RwLock::new(None)),
+            status: Arc::new(RwLock::new(None)),
+            alerted: Arc::new(RwLock::new(DiskPressure::Ok)),
+        }
+    }
+
+    pub fn config(&self) -> &DiskConfig {
+        &self.config
+    }
+
+    /// Docker root as reported by the daemon; ignored when it is not a local path
+    pub fn set_docker_root(&self, path: Option<String>) {
+        let local = path.filter(|p| Path::new(p).exists());
+        if let Ok(mut root) = self.docker_root.write() {
+            *root = local;
+        }
+    }
+
+    pub fn record_gc(&self) {
+        if let Ok(mut last) = self.last_gc_at.write() {
+            *last = Some(Utc::now());
+        }
+    }
+
+    /// Record `pressure` as the level last alerted on; true when it is worse than the
+    /// previous one, so each escalation is reported once
+    pub fn escalated(&self, pressure: DiskPressure) -> bool {
+        let Ok(mut alerted) = self.alerted.write() else {
+            return false;
+        };
+        let worse = worst(*alerted, pressure) != *alerted;
+        *alerted = pressure;
+        worse
+    }
+
+    /// Measure now and remember the result
+    pub fn refresh(&self) -> DiskStatus {
+        let data_dir = measure(&self.data_dir, &self.config);
+        let docker_root = self
+            .docker_root
+            .read()
+            .ok()
+            .and_then(|root| root.clone())
+            .and_then(|root| measure(&root, &self.config));
+        let pressure = [&data_dir, &docker_root]
+            .into_iter()
+            .flatten()
+            .fold(DiskPressure::Ok, |acc, loc| worst(acc, loc.pressure));
+        let status = DiskStatus {
+            pressure,
+            data_dir,
+            docker_root,
+            gc_below_free_mb: self.config.gc_below_free_mb,
+            min_free_mb: self.config.min_free_mb,
+            checked_at: Utc::now(),
+            last_gc_at: self.last_gc_at.read().ok().and_then(|last| *last),
+        };
+        if let Ok(mut current) = self.status.write() {
+            *current = Some(status.clone());
+        }
+        status
+    }
+
+    /// Last measurement, taking one if none exists yet
+    pub fn status(&self) -> DiskStatus {
+        match self.status.read().ok().and_then(|s| s.clone()) {
+            Some(status) => status,
+            None => self.refresh(),
+        }
+    }
+
+    /// Fail fast before a build instead of running out of space halfway through it
+    pub fn check_build_capacity(&self) -> Result<(), LambdaError> {
+        let status = self.refresh();
+        for location in [status.data_dir, status.docker_root].into_iter().flatten() {
+            if location.pressure == DiskPressure::Critical {
+                return Err(LambdaError::InsufficientDiskSpace {
+                    path: location.path,
+                    free_mb: location.free_bytes / MB,
+                    required_mb: self.config.min_free_mb,
+                });
+            }
+        }
+        Ok(())
+    }
+}
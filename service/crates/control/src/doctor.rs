@@ -0,0 +1,287 @@
+use lambda_invoker::{SandboxProfiles, SandboxSupport};
+use lambda_models::{
+    DiskPressure, DiskStatus, DockerVersion, DoctorCheck, LambdaError, ServerConfig,
+};
+
+/// Small image with `wget`, used to test what a function container can reach
+pub const PROBE_IMAGE: &str = "busybox:1.36";
+pub const PROBE_TIMEOUT_MS: u64 = 15_000;
+/// First Docker release that understands `host-gateway` in extra hosts
+pub const MIN_DOCKER_VERSION: (u64, u64) = (20, 10);
+
+/// Major and minor of a Docker version such as `24.0.7` or `20.10.21+dfsg1`
+pub fn parse_docker_version(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts
+        .next()?
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .ok()?;
+    Some((major, minor))
+}
+
+pub fn check_docker(version: Result<&DockerVersion, &LambdaError>) -> DoctorCheck {
+    let version = match version {
+        Ok(version) => version,
+        Err(e) => {
+            return DoctorCheck::fail(
+                "docker",
+                format!("Cannot reach the Docker daemon: {e}"),
+                "Start Docker, and give the server access to its socket (add the user to the \
+                 docker group) or point [docker] host or DOCKER_HOST at it",
+            )
+        }
+    };
+    let detail = format!(
+        "Docker {} (API {}, {}/{})",
+        version.version, version.api_version, version.os, version.arch
+    );
+    match parse_docker_version(&version.version) {
+        Some(found) if found >= MIN_DOCKER_VERSION => DoctorCheck::pass("docker", detail),
+        Some(_) => DoctorCheck::fail(
+            "docker",
+            detail,
+            format!(
+                "Upgrade to Docker {}.{} or newer; older releases cannot map host.docker.internal",
+                MIN_DOCKER_VERSION.0, MIN_DOCKER_VERSION.1
+            ),
+        ),
+        None => DoctorCheck::warn(
+            "docker",
+            detail,
+            format!(
+                "Could not read the version; Docker {}.{} or newer is required",
+                MIN_DOCKER_VERSION.0, MIN_DOCKER_VERSION.1
+            ),
+        ),
+    }
+}
+
+/// Whether `bind` only accepts connections from the host itself
+pub fn is_loopback_bind(bind: &str) -> bool {
+    bind == "localhost"
+        || bind
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Fetch the runtime API health check the way a function's runtime reaches it, at the
+/// `host:port` containers are given
+pub fn probe_command(runtime_api: &str) -> Vec<String> {
+    vec![
+        "wget".into(),
+        "-q".into(),
+        "-T".into(),
+        "5".into(),
+        "-O".into(),
+        "-".into(),
+        format!("http://{runtime_api}/runtime/healthz"),
+    ]
+}
+
+/// Checks for when the probe never ran: the image could not be pulled or Docker is down
+pub fn probe_not_run(reason: &str, fix: &str) -> Vec<DoctorCheck> {
+    vec![
+        DoctorCheck::warn("host_gateway", format!("Not checked: {reason}"), fix),
+        DoctorCheck::warn("runtime_api", format!("Not checked: {reason}"), fix),
+    ]
+}
+
+/// Read the probe container's outcome as `host_gateway` and `runtime_api` checks
+pub fn check_probe(
+    outcome: Result<(i64, String), LambdaError>,
+    server: &ServerConfig,
+) -> Vec<DoctorCheck> {
+    let port = server.port_runtime_api;
+    let upgrade = format!(
+        "Upgrade to Docker {}.{} or newer, which maps host-gateway to the host",
+        MIN_DOCKER_VERSION.0, MIN_DOCKER_VERSION.1
+    );
+    let (exit_code, output) = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) if e.to_string().contains("host-gateway") => {
+            return vec![
+                DoctorCheck::fail(
+                    "host_gateway",
+                    format!("Docker rejected the host-gateway mapping: {e}"),
+                    upgrade,
+                ),
+                DoctorCheck::warn(
+                    "runtime_api",
+                    "Not checked: the probe container could not be created",
+                    "Fix host_gateway first",
+                ),
+            ]
+        }
+        Err(e) => {
+            return probe_not_run(
+                &format!("the probe container failed: {e}"),
+                &format!(
+                    "Check that Docker can run containers: docker run --rm {PROBE_IMAGE} true"
+                ),
+            )
+        }
+    };
+    if exit_code == 0 {
+        return vec![
+            DoctorCheck::pass(
+                "host_gateway",
+                "host.docker.internal resolves inside containers",
+            ),
+            DoctorCheck::pass(
+                "runtime_api",
+                format!("Containers reach the runtime API at host.docker.internal:{port}"),
+            ),
+        ];
+    }
+
+    let last_line = output.lines().last().unwrap_or_default().trim().to_string();
+    if output.contains("bad address") {
+        return vec![
+            DoctorCheck::fail(
+                "host_gateway",
+                "host.docker.internal does not resolve inside containers",
+                upgrade,
+            ),
+            DoctorCheck::warn(
+                "runtime_api",
+                "Not checked: containers cannot resolve the host",
+                "Fix host_gateway first",
+            ),
+        ];
+    }
+    let fix = if is_loopback_bind(&server.bind) {
+        format!(
+            "server.bind is {}, which containers cannot reach; set it to 0.0.0.0",
+            server.bind
+        )
+    } else if cfg!(windows) {
+        format!(
+            "Allow port {port} through Windows Defender Firewall, e.g. New-NetFirewallRule \
+             -DisplayName lambda-at-home -Direction Inbound -Protocol TCP -LocalPort {port} \
+             -Action Allow"
+        )
+    } else {
+        format!(
+            "Allow port {port} from the Docker bridge through the host firewall, e.g. \
+             ufw allow in on docker0 to any port {port}"
+        )
+    };
+    vec![
+        DoctorCheck::pass(
+            "host_gateway",
+            "host.docker.internal resolves inside containers",
+        ),
+        DoctorCheck::fail(
+            "runtime_api",
+            format!("Containers cannot reach the runtime API on port {port}: {last_line}"),
+            fix,
+        ),
+    ]
+}
+
+/// Whether the Docker host can apply every sandbox profile; `None` when Docker is unreachable
+pub fn check_sandbox(
+    support: Option<Result<&SandboxSupport, &LambdaError>>,
+    profiles: &SandboxProfiles,
+) -> DoctorCheck {
+    let support = match support {
+        None => {
+            return DoctorCheck::warn(
+                "sandbox",
+                "Not checked, Docker is unreachable",
+                "Fix the docker check first",
+            )
+        }
+        Some(Err(e)) => {
+            return DoctorCheck::warn(
+                "sandbox",
+                format!("Cannot read the security options of the Docker host: {e}"),
+                "Check that docker info works for the server's user",
+            )
+        }
+        Some(Ok(support)) => support,
+    };
+    let available = |yes: bool| if yes { "available" } else { "unavailable" };
+    let detail = format!(
+        "seccomp {}, AppArmor {}",
+        available(support.seccomp),
+        available(support.apparmor)
+    );
+    let names = profiles.names();
+    let unusable: Vec<&String> = names
+        .iter()
+        .filter(|name| {
+            profiles
+                .get(name)
+                .is_some_and(|profile| support.unsupported(profile).is_some())
+        })
+        .collect();
+    if unusable.is_empty() {
+        return DoctorCheck::pass(
+            "sandbox",
+            format!("{detail}; profiles: {}", names.join(", ")),
+        );
+    }
+    let detail = format!(
+        "{detail}; profiles that cannot be applied: {}",
+        unusable
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if unusable
+        .iter()
+        .any(|name| name.as_str() == profiles.default_profile())
+    {
+        DoctorCheck::fail(
+            "sandbox",
+            detail,
+            "Containers of functions without a profile cannot start; enable seccomp or AppArmor \
+             on the Docker host or choose another sandbox.default_profile",
+        )
+    } else {
+        DoctorCheck::warn(
+            "sandbox",
+            detail,
+            "Enable seccomp or AppArmor on the Docker host, or keep functions off these profiles",
+        )
+    }
+}
+
+pub fn check_disk(status: &DiskStatus) -> DoctorCheck {
+    let locations: Vec<String> = [&status.data_dir, &status.docker_root]
+        .into_iter()
+        .flatten()
+        .map(|l| format!("{} MB free in {}", l.free_bytes / (1024 * 1024), l.path))
+        .collect();
+    let detail = if locations.is_empty() {
+        "Free space could not be measured".to_string()
+    } else {
+        locations.join(", ")
+    };
+    match status.pressure {
+        DiskPressure::Ok => DoctorCheck::pass("disk", detail),
+        DiskPressure::Low => DoctorCheck::warn(
+            "disk",
+            detail,
+            format!(
+                "Below {} MB free, unused images and artifacts are being collected; free space \
+                 or remove unused images with docker image prune",
+                status.gc_below_free_mb
+            ),
+        ),
+        DiskPressure::Critical => DoctorCheck::fail(
+            "disk",
+            detail,
+            format!(
+                "Below {} MB free, builds are refused; free space on these volumes",
+                status.min_free_mb
+            ),
+        ),
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use lambda_models::{ErrorGroup, LambdaError};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Request ids kept per error group
+pub const MAX_SAMPLE_REQUEST_IDS: usize = 5;
+/// Longest window [`parse_window`] accepts
+pub const MAX_WINDOW: Duration = Duration::from_secs(366 * 24 * 60 * 60);
+
+/// A failed execution as read from the executions table
+#[derive(Debug, Clone)]
+pub struct FailedExecution {
+    pub request_id: String,
+    pub error_type: String,
+    pub error_message: Option<String>,
+    pub start_time: DateTime<Utc>,
+}
+
+/// Parse a window such as `30m`, `24h`, `7d` or `3600s` (bare numbers are seconds)
+pub fn parse_window(window: &str) -> Result<Duration, LambdaError> {
+    let window = window.trim();
+    let invalid = || LambdaError::InvalidRequest {
+        reason: format!("Invalid window '{window}', expected e.g. 30m, 24h or 7d"),
+    };
+    let (value, unit_secs) = match window.char_indices().last() {
+        Some((idx, 's')) => (&window[..idx], 1),
+        Some((idx, 'm')) => (&window[..idx], 60),
+        Some((idx, 'h')) => (&window[..idx], 3600),
+        Some((idx, 'd')) => (&window[..idx], 86400),
+        Some(_) => (window, 1),
+        None => return Err(invalid()),
+    };
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    if value == 0 {
+        return Err(invalid());
+    }
+    let parsed = Duration::from_secs(value.saturating_mul(unit_secs));
+    if parsed > MAX_WINDOW {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!(
+                "Window '{window}' is longer than {} days",
+                MAX_WINDOW.as_secs() / 86400
+            ),
+        });
+    }
+    Ok(parsed)
+}
+
+/// When a window ending at `now` starts
+pub fn window_start(now: DateTime<Utc>, window: Duration) -> Result<DateTime<Utc>, LambdaError> {
+    chrono::Duration::from_std(window)
+        .ok()
+        .and_then(|span| now.checked_sub_signed(span))
+        .ok_or_else(|| LambdaError::InvalidRequest {
+            reason: format!("Window of {}s reaches too far back", window.as_secs()),
+        })
+}
+
+/// Strip the variable parts of an error message (numbers, ids, quoted values)
+/// so that occurrences of the same failure group together
+pub fn normalize_message(message: &str) -> String {
+    let mut normalized = String::with_capacity(message.len());
+    let mut in_number = false;
+    for c in message.chars() {
+        if c.is_ascii_hexdigit() && (c.is_ascii_digit() || in_number) {
+            if !in_number {
+                normalized.push('#');
+                in_number = true;
+            }
+        } else {
+            in_number = false;
+            normalized.push(c);
+        }
+    }
+    normalized.trim().chars().take(200).collect()
+}
+
+pub fn fingerprint(error_type: &str, error_message: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(error_type.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(normalize_message(error_message.unwrap_or_default()).as_bytes());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// Group failures by fingerprint, most frequent first
+pub fn group_errors(failures: Vec<FailedExecution>) -> Vec<ErrorGroup> {
+    let mut groups: HashMap<String, ErrorGroup> = HashMap::new();
+    for failure in failures {
+        let fp = fingerprint(&failure.error_type, failure.error_message.as_deref());
+        let group = groups.entry(fp.clone()).or_insert_with(|| ErrorGroup {
+            fingerprint: fp,
+            error_type: failure.error_type.clone(),
+            error_message: failure.error_message.clone(),
+            count: 0,
+            first_seen: failure.start_time,
+            last_seen: failure.start_time,
+            sample_request_ids: Vec::new(),
+        });
+        group.count += 1;
+        if failure.start_time < group.first_seen {
+            group.first_seen = failure.start_time;
+        }
+        if failure.start_time >= group.last_seen {
+            group.last_seen = failure.start_time;
+            group.error_message = failure.error_message.clone();
+        }
+        group.sample_request_ids.push(failure.request_id);
+    }
+
+    let mut groups: Vec<ErrorGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        // Samples were pushed in query order (newest first); keep the most recent few
+        group.sample_request_ids.truncate(MAX_SAMPLE_REQUEST_IDS);
+    }
+    groups.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| b.last_seen.cmp(&a.last_seen))
+    });
+    groups
+}
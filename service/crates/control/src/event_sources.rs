@@ -0,0 +1,258 @@
+use crate::registry::ControlPlane;
+use dashmap::DashSet;
+use lambda_models::{
+    queue_name_from_arn, EventSourceMapping, FunctionResponseType, IdentityConfig, InvocationType,
+    InvokeRequest, LambdaError, ReceivedMessage, SqsEvent, SqsEventRecord,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+pub const DEFAULT_BATCH_SIZE: u32 = 10;
+pub const MAX_BATCH_SIZE: u32 = 10;
+/// How often idle mappings look for new messages
+pub const EVENT_SOURCE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn validate_batch_size(batch_size: u32) -> Result<(), LambdaError> {
+    if !(1..=MAX_BATCH_SIZE).contains(&batch_size) {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!("batch_size must be between 1 and {MAX_BATCH_SIZE}"),
+        });
+    }
+    Ok(())
+}
+
+pub fn sqs_event(
+    identity: &IdentityConfig,
+    queue_name: &str,
+    messages: &[ReceivedMessage],
+) -> SqsEvent {
+    SqsEvent {
+        records: messages
+            .iter()
+            .map(|m| SqsEventRecord {
+                message_id: m.message_id.clone(),
+                receipt_handle: m.receipt_handle.clone(),
+                body: m.body.clone(),
+                attributes: HashMap::from([
+                    (
+                        "ApproximateReceiveCount".to_string(),
+                        m.receive_count.to_string(),
+                    ),
+                    (
+                        "SentTimestamp".to_string(),
+                        m.sent_at.timestamp_millis().to_string(),
+                    ),
+                    ("SenderId".to_string(), identity.account_id.clone()),
+                    (
+                        "ApproximateFirstReceiveTimestamp".to_string(),
+                        m.first_received_at.timestamp_millis().to_string(),
+                    ),
+                ]),
+                message_attributes: HashMap::new(),
+                md5_of_body: m.md5_of_body.clone(),
+                event_source: "aws:sqs".to_string(),
+                event_source_arn: identity.queue_arn(queue_name),
+                aws_region: identity.region.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Message ids a function reported in `batchItemFailures`. An empty or missing list means
+/// the whole batch succeeded; None means the response was malformed (an entry without a
+/// known `itemIdentifier`), which, as in Lambda, fails the whole batch.
+pub fn reported_failures(
+    payload: Option<&serde_json::Value>,
+    message_ids: &[String],
+) -> Option<HashSet<String>> {
+    let failures = match payload.and_then(|p| p.get("batchItemFailures")) {
+        None | Some(serde_json::Value::Null) => return Some(HashSet::new()),
+        Some(serde_json::Value::Array(items)) => items,
+        Some(_) => return None,
+    };
+    failures
+        .iter()
+        .map(|item| {
+            item.get("itemIdentifier")
+                .and_then(|id| id.as_str())
+                .filter(|id| message_ids.iter().any(|m| m == id))
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// What happened to a batch: which messages to delete and the mapping's processing result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOutcome {
+    pub delete: Vec<String>,
+    pub result: String,
+    /// Stop draining until the next poll, so a failing function is not hammered
+    pub back_off: bool,
+}
+
+/// Decide a batch's fate from the invoke result. Failed messages are left in the queue to
+/// reappear after their visibility timeout; the queue's redrive policy bounds the retries.
+pub fn batch_outcome(
+    mapping: &EventSourceMapping,
+    messages: &[ReceivedMessage],
+    invoke: &Result<lambda_models::InvokeResponse, LambdaError>,
+) -> BatchOutcome {
+    let all = || messages.iter().map(|m| m.receipt_handle.clone()).collect();
+    let failed = |result: String| BatchOutcome {
+        delete: Vec::new(),
+        result,
+        back_off: true,
+    };
+    let response = match invoke {
+        Err(e) => return failed(format!("Error: {e}")),
+        Ok(response) if response.function_error.is_some() => {
+            let error_type = response
+                .payload
+                .as_ref()
+                .and_then(|p| p.get("errorType"))
+                .and_then(|t| t.as_str())
+                .unwrap_or("Unhandled");
+            return failed(format!("FunctionError: {error_type}"));
+        }
+        Ok(response) => response,
+    };
+
+    if !mapping
+        .function_response_types
+        .contains(&FunctionResponseType::ReportBatchItemFailures)
+    {
+        return BatchOutcome {
+            delete: all(),
+            result: "OK".to_string(),
+            back_off: false,
+        };
+    }
+    let ids: Vec<String> = messages.iter().map(|m| m.message_id.clone()).collect();
+    match reported_failures(response.payload.as_ref(), &ids) {
+        None => failed("InvalidBatchItemFailures".to_string()),
+        Some(failures) if failures.is_empty() => BatchOutcome {
+            delete: all(),
+            result: "OK".to_string(),
+            back_off: false,
+        },
+        Some(failures) => BatchOutcome {
+            delete: messages
+                .iter()
+                .filter(|m| !failures.contains(&m.message_id))
+                .map(|m| m.receipt_handle.clone())
+                .collect(),
+            result: format!(
+                "PartialBatchFailure: {} of {} failed",
+                failures.len(),
+                messages.len()
+            ),
+            back_off: false,
+        },
+    }
+}
+
+/// Feeds queue messages to mapped functions. Each mapping drains its queue one batch at a
+/// time until it is empty, then waits for the next poll.
+pub struct EventSourcePoller {
+    control: Arc<ControlPlane>,
+    draining: Arc<DashSet<Uuid>>,
+}
+
+impl EventSourcePoller {
+    pub fn new(control: Arc<ControlPlane>) -> Self {
+        Self {
+            control,
+            draining: Arc::new(DashSet::new()),
+        }
+    }
+
+    pub async fn start(self) {
+        loop {
+            if let Err(e) = self.poll_once().await {
+                warn!("event source poll error: {}", e);
+            }
+            tokio::time::sleep(EVENT_SOURCE_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn poll_once(&self) -> Result<(), LambdaError> {
+        let mappings = self.control.list_event_source_mappings(None).await?;
+        for mapping in mappings.event_source_mappings {
+            // Bucket sources are pushed by notifications rather than polled
+            if !mapping.enabled
+                || queue_name_from_arn(&mapping.event_source_arn).is_none()
+                || !self.draining.insert(mapping.uuid)
+            {
+                continue;
+            }
+            let control = self.control.clone();
+            let draining = self.draining.clone();
+            tokio::spawn(async move {
+                let uuid = mapping.uuid;
+                if let Err(e) = drain(&control, mapping).await {
+                    debug!("event source mapping {} stopped draining: {}", uuid, e);
+                }
+                draining.remove(&uuid);
+            });
+        }
+        Ok(())
+    }
+}
+
+async fn drain(control: &ControlPlane, mapping: EventSourceMapping) -> Result<(), LambdaError> {
+    let Some(queue_name) = queue_name_from_arn(&mapping.event_source_arn) else {
+        return Ok(());
+    };
+    let queue = control.sqs().get_queue(queue_name).await?;
+    let function = control.get_function(&mapping.function_name).await?;
+    // Keep the batch hidden for at least as long as the function may run
+    let visibility = queue.visibility_timeout_secs.max(function.timeout as u32);
+
+    loop {
+        let messages = control
+            .sqs()
+            .receive_messages(
+                queue_name,
+                mapping.batch_size,
+                Some(visibility),
+                Duration::ZERO,
+            )
+            .await?;
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let payload = serde_json::to_value(sqs_event(control.identity(), queue_name, &messages))
+            .map_err(|e| LambdaError::InternalError {
+                reason: format!("Failed to build SQS event: {e}"),
+            })?;
+        let invoke = control
+            .invoke_function(InvokeRequest {
+                function_name: mapping.function_name.clone(),
+                invocation_type: InvocationType::RequestResponse,
+                log_type: None,
+                client_context: None,
+                payload: Some(payload),
+                qualifier: None,
+                idempotent: false,
+                trace: false,
+            })
+            .await;
+        let outcome = batch_outcome(&mapping, &messages, &invoke);
+        for receipt_handle in &outcome.delete {
+            control
+                .sqs()
+                .delete_message(queue_name, receipt_handle)
+                .await?;
+        }
+        control
+            .record_event_source_result(mapping.uuid, &outcome.result)
+            .await?;
+        if outcome.back_off {
+            return Ok(());
+        }
+    }
+}
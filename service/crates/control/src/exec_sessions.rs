@@ -0,0 +1,126 @@
+use crate::access_log::RotatingFile;
+use chrono::Utc;
+use dashmap::DashMap;
+use lambda_models::{ExecAuditEntry, ExecAuditEvent, ExecConfig, ExecSession};
+use std::sync::{mpsc, Arc};
+use tracing::{info, warn};
+
+const AUDIT_FILE_MAX_BYTES: u64 = 100 * 1024 * 1024;
+const AUDIT_FILE_MAX_FILES: u32 = 5;
+
+/// Exec sessions opened but not yet connected to. Each one is handed out once, to the first
+/// WebSocket that connects with its id before it expires.
+#[derive(Clone)]
+pub struct ExecSessions {
+    pending: Arc<DashMap<String, ExecSession>>,
+    audit: ExecAudit,
+}
+
+impl ExecSessions {
+    pub fn new(config: &ExecConfig) -> Self {
+        Self {
+            pending: Arc::new(DashMap::new()),
+            audit: ExecAudit::new(config),
+        }
+    }
+
+    pub fn insert(&self, session: ExecSession) {
+        self.audit.record(&session, ExecAuditEvent::Opened);
+        self.pending.insert(session.session_id.clone(), session);
+    }
+
+    /// Take the session to connect to it; `None` when it does not exist, was already
+    /// connected to, or expired. Expired sessions are dropped on the way.
+    pub fn take(&self, session_id: &str) -> Option<ExecSession> {
+        let now = Utc::now();
+        let expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|entry| entry.expires_at <= now)
+            .map(|entry| entry.key().clone())
+            .collect();
+        for id in expired {
+            if let Some((_, session)) = self.pending.remove(&id) {
+                self.audit.record(
+                    &session,
+                    ExecAuditEvent::Closed {
+                        reason: "expired before connecting".to_string(),
+                    },
+                );
+            }
+        }
+        let (_, session) = self.pending.remove(session_id)?;
+        self.audit.record(&session, ExecAuditEvent::Connected);
+        Some(session)
+    }
+
+    pub fn audit(&self) -> &ExecAudit {
+        &self.audit
+    }
+}
+
+/// Writes exec audit entries to the configured file from a background thread, or to the
+/// server log when no file is set
+#[derive(Clone)]
+pub struct ExecAudit {
+    file: Option<mpsc::Sender<ExecAuditEntry>>,
+}
+
+impl ExecAudit {
+    pub fn new(config: &ExecConfig) -> Self {
+        let file = match &config.audit_file {
+            Some(path) if config.enabled => {
+                match RotatingFile::open(path, AUDIT_FILE_MAX_BYTES, AUDIT_FILE_MAX_FILES) {
+                    Ok(file) => Some(spawn_writer(file)),
+                    Err(e) => {
+                        warn!(
+                            "Failed to open exec audit log {}: {}; logging to the server log",
+                            path, e
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        Self { file }
+    }
+
+    pub fn record(&self, session: &ExecSession, event: ExecAuditEvent) {
+        let entry = ExecAuditEntry {
+            timestamp: Utc::now(),
+            session_id: session.session_id.clone(),
+            container_id: session.container_id.clone(),
+            function_name: session.function_name.clone(),
+            actor: session.opened_by.clone(),
+            event,
+        };
+        match &self.file {
+            Some(sender) => {
+                let _ = sender.send(entry);
+            }
+            None => info!(
+                target: "exec_audit",
+                session_id = %entry.session_id,
+                container_id = %entry.container_id,
+                function_name = %entry.function_name,
+                actor = entry.actor.as_deref().unwrap_or("-"),
+                event = %serde_json::to_string(&entry.event).unwrap_or_default(),
+                "exec"
+            ),
+        }
+    }
+}
+
+fn spawn_writer(mut file: RotatingFile) -> mpsc::Sender<ExecAuditEntry> {
+    let (tx, rx) = mpsc::channel::<ExecAuditEntry>();
+    std::thread::spawn(move || {
+        for entry in rx {
+            let line = serde_json::to_string(&entry).unwrap_or_default();
+            if let Err(e) = file.write_line(&line) {
+                warn!("Failed to write exec audit entry: {}", e);
+            }
+        }
+    });
+    tx
+}
@@ -77,15 +77,19 @@ impl ExecutionTracker {
         });
     }
 
-    /// Record failed execution completion (deferred async)
+    /// Record failed execution completion (deferred async).
+    /// `error_payload` is the full error document (errorMessage, errorType, stackTrace).
     pub fn record_execution_failure(
         &self,
         execution_id: String,
         error_type: String,
+        error_message: Option<String>,
+        error_payload: Option<serde_json::Value>,
         end_time: chrono::DateTime<chrono::Utc>,
     ) {
         let pool = self.pool.clone();
         let execution_id_clone = execution_id.clone();
+        let error_payload = error_payload.map(|p| p.to_string());
 
         tokio::spawn(async move {
             // Get the start time to calculate duration
@@ -103,10 +107,12 @@ impl ExecutionTracker {
             };
 
             let _ = sqlx::query(
-                "UPDATE executions SET end_time = ?, status = 'Failed', error_type = ?, duration_ms = ? WHERE execution_id = ?"
+                "UPDATE executions SET end_time = ?, status = 'Failed', error_type = ?, error_message = ?, error_payload = ?, duration_ms = ? WHERE execution_id = ?"
             )
             .bind(end_time)
             .bind(&error_type)
+            .bind(&error_message)
+            .bind(&error_payload)
             .bind(duration_ms)
             .bind(&execution_id_clone)
             .execute(&*pool)
@@ -118,9 +124,21 @@ impl ExecutionTracker {
     pub fn record_execution_timeout(
         &self,
         execution_id: String,
+        timeout_secs: u64,
         end_time: chrono::DateTime<chrono::Utc>,
     ) {
-        self.record_execution_failure(execution_id, "TaskTimedOut".to_string(), end_time);
+        let message = format!("Task timed out after {timeout_secs} seconds");
+        let payload = serde_json::json!({
+            "errorMessage": message,
+            "errorType": "TaskTimedOut"
+        });
+        self.record_execution_failure(
+            execution_id,
+            "TaskTimedOut".to_string(),
+            Some(message),
+            Some(payload),
+            end_time,
+        );
     }
 
     /// Record init error execution (deferred async)
@@ -129,6 +147,16 @@ impl ExecutionTracker {
         execution_id: String,
         end_time: chrono::DateTime<chrono::Utc>,
     ) {
-        self.record_execution_failure(execution_id, "InitError".to_string(), end_time);
+        let payload = serde_json::json!({
+            "errorMessage": "Runtime channel closed",
+            "errorType": "InitError"
+        });
+        self.record_execution_failure(
+            execution_id,
+            "InitError".to_string(),
+            Some("Runtime channel closed".to_string()),
+            Some(payload),
+            end_time,
+        );
     }
 }
@@ -0,0 +1,97 @@
+use lambda_models::{FeatureFlag, FeatureFlagStatus, LambdaError};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Feature flags as configured under `[features]`, with the overrides the admin API set
+/// on runtime-safe flags since startup
+#[derive(Clone, Default)]
+pub struct FeatureFlags {
+    configured: BTreeMap<FeatureFlag, bool>,
+    overrides: Arc<RwLock<BTreeMap<FeatureFlag, bool>>>,
+}
+
+impl FeatureFlags {
+    pub fn new(configured: &BTreeMap<FeatureFlag, bool>) -> Self {
+        Self {
+            configured: configured.clone(),
+            overrides: Arc::default(),
+        }
+    }
+
+    /// Value under `[features]`, or the flag's default
+    pub fn configured(&self, flag: FeatureFlag) -> bool {
+        self.configured
+            .get(&flag)
+            .copied()
+            .unwrap_or_else(|| flag.default_enabled())
+    }
+
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        match self.overrides.read().unwrap().get(&flag) {
+            Some(enabled) => *enabled,
+            None => self.configured(flag),
+        }
+    }
+
+    /// Refuse a request to a subsystem whose flag is off
+    pub fn require(&self, flag: FeatureFlag) -> Result<(), LambdaError> {
+        if self.is_enabled(flag) {
+            Ok(())
+        } else {
+            Err(LambdaError::FeatureDisabled {
+                name: flag.name().to_string(),
+            })
+        }
+    }
+
+    /// Turn a runtime-safe flag on or off until reset or restart
+    pub fn set(&self, flag: FeatureFlag, enabled: bool) -> Result<FeatureFlagStatus, LambdaError> {
+        if !flag.runtime_safe() {
+            return Err(LambdaError::FeatureFlagRequiresRestart {
+                name: flag.name().to_string(),
+            });
+        }
+        let mut overrides = self.overrides.write().unwrap();
+        if enabled == self.configured(flag) {
+            overrides.remove(&flag);
+        } else {
+            overrides.insert(flag, enabled);
+        }
+        drop(overrides);
+        Ok(self.status(flag))
+    }
+
+    /// Drop the admin API's override, back to the configured value
+    pub fn reset(&self, flag: FeatureFlag) -> FeatureFlagStatus {
+        self.overrides.write().unwrap().remove(&flag);
+        self.status(flag)
+    }
+
+    pub fn status(&self, flag: FeatureFlag) -> FeatureFlagStatus {
+        let configured = self.configured(flag);
+        let enabled = self.is_enabled(flag);
+        FeatureFlagStatus {
+            name: flag,
+            description: flag.description().to_string(),
+            enabled,
+            configured,
+            overridden: enabled != configured,
+            runtime_safe: flag.runtime_safe(),
+        }
+    }
+
+    /// Every known flag, in declaration order
+    pub fn list(&self) -> Vec<FeatureFlagStatus> {
+        FeatureFlag::ALL
+            .iter()
+            .map(|flag| self.status(*flag))
+            .collect()
+    }
+}
+
+/// The flag named `name`, as the admin API's path carries it
+pub fn parse_feature_flag(name: &str) -> Result<FeatureFlag, LambdaError> {
+    name.parse().map_err(|_| LambdaError::FeatureFlagNotFound {
+        name: name.to_string(),
+    })
+}
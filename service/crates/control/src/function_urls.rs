@@ -0,0 +1,238 @@
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use lambda_models::{LambdaError, ServerConfig};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// Function URLs are served below this path, at `/lambda-url/{url_id}/`
+pub const URL_PATH_PREFIX: &str = "/lambda-url/";
+/// `routeKey` and `stage` of every function URL event
+const DEFAULT_ROUTE: &str = "$default";
+
+/// A new URL id: 32 lowercase letters and digits, like the subdomain of an AWS function URL
+pub fn new_url_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+pub fn url_path(url_id: &str) -> String {
+    format!("{URL_PATH_PREFIX}{url_id}/")
+}
+
+/// Base URL of the user API, e.g. `http://127.0.0.1:8000`: `server.public_url` when set.
+/// Otherwise the bind address, where a server listening on every interface is reached
+/// locally through localhost
+pub fn user_api_url(server: &ServerConfig) -> String {
+    if let Some(public_url) = &server.public_url {
+        return public_url.trim_end_matches('/').to_string();
+    }
+    let bind = server.bind.trim_start_matches('[').trim_end_matches(']');
+    let host = match bind.parse::<IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => "localhost".to_string(),
+        Ok(IpAddr::V6(ip)) => format!("[{ip}]"),
+        _ => bind.to_string(),
+    };
+    format!("http://{host}:{}", server.port_user_api)
+}
+
+/// An HTTP request to a function URL
+pub struct UrlRequest<'a> {
+    pub url_id: &'a str,
+    pub method: &'a str,
+    /// Path below the URL, starting with `/`
+    pub path: &'a str,
+    pub raw_query: &'a str,
+    /// In the order received; names may repeat
+    pub headers: &'a [(String, String)],
+    pub body: &'a [u8],
+    pub source_ip: Option<&'a str>,
+    pub request_id: &'a str,
+    pub account_id: &'a str,
+    pub time: DateTime<Utc>,
+}
+
+/// The request as an HTTP API payload format 2.0 event: header names lowercased and repeated
+/// headers and query parameters joined with commas, cookies in `cookies`, and a body that is
+/// not UTF-8 base64-encoded
+pub fn url_event(request: &UrlRequest) -> Value {
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    let mut cookies = Vec::new();
+    for (name, value) in request.headers {
+        let name = name.to_ascii_lowercase();
+        if name == "cookie" {
+            cookies.extend(
+                value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string),
+            );
+            continue;
+        }
+        headers
+            .entry(name)
+            .and_modify(|joined| {
+                joined.push(',');
+                joined.push_str(value);
+            })
+            .or_insert_with(|| value.clone());
+    }
+
+    let mut query: BTreeMap<String, String> = BTreeMap::new();
+    for (name, value) in form_urlencoded::parse(request.raw_query.as_bytes()) {
+        query
+            .entry(name.into_owned())
+            .and_modify(|joined| {
+                joined.push(',');
+                joined.push_str(&value);
+            })
+            .or_insert_with(|| value.into_owned());
+    }
+
+    let (body, is_base64_encoded) = match std::str::from_utf8(request.body) {
+        _ if request.body.is_empty() => (Value::Null, false),
+        Ok(text) => (Value::String(text.to_string()), false),
+        Err(_) => (
+            Value::String(base64::engine::general_purpose::STANDARD.encode(request.body)),
+            true,
+        ),
+    };
+
+    let domain_name = headers.get("host").cloned().unwrap_or_default();
+    let mut event = json!({
+        "version": "2.0",
+        "routeKey": DEFAULT_ROUTE,
+        "rawPath": request.path,
+        "rawQueryString": request.raw_query,
+        "headers": headers,
+        "queryStringParameters": if query.is_empty() { Value::Null } else { json!(query) },
+        "requestContext": {
+            "accountId": request.account_id,
+            "apiId": request.url_id,
+            "domainName": domain_name,
+            "domainPrefix": request.url_id,
+            "http": {
+                "method": request.method,
+                "path": request.path,
+                "protocol": "HTTP/1.1",
+                "sourceIp": request.source_ip.unwrap_or_default(),
+                "userAgent": headers.get("user-agent").cloned().unwrap_or_default(),
+            },
+            "requestId": request.request_id,
+            "routeKey": DEFAULT_ROUTE,
+            "stage": DEFAULT_ROUTE,
+            "time": request.time.format("%d/%b/%Y:%H:%M:%S %z").to_string(),
+            "timeEpoch": request.time.timestamp_millis(),
+        },
+        "body": body,
+        "pathParameters": Value::Null,
+        "isBase64Encoded": is_base64_encoded,
+        "stageVariables": Value::Null,
+    });
+    if !cookies.is_empty() {
+        event["cookies"] = json!(cookies);
+    }
+    event
+}
+
+/// HTTP response a function URL sends for a function's result
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Each sent as a `Set-Cookie` header
+    pub cookies: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+/// Read a result in payload format 2.0. An object with a `statusCode` names the status,
+/// `headers`, `cookies` and `body`, base64-encoded when `isBase64Encoded` is set. Any other
+/// result is sent with status 200 as `application/json`: a string as it is, anything else as
+/// JSON.
+pub fn url_response(payload: Option<&Value>) -> Result<UrlResponse, LambdaError> {
+    let Some(payload) = payload else {
+        return Ok(UrlResponse {
+            status: 200,
+            headers: Vec::new(),
+            cookies: Vec::new(),
+            body: Vec::new(),
+        });
+    };
+    let structured = payload
+        .as_object()
+        .filter(|object| object.contains_key("statusCode"));
+    let Some(object) = structured else {
+        let body = match payload {
+            Value::String(text) => text.clone().into_bytes(),
+            other => other.to_string().into_bytes(),
+        };
+        return Ok(UrlResponse {
+            status: 200,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            cookies: Vec::new(),
+            body,
+        });
+    };
+
+    let status = object
+        .get("statusCode")
+        .and_then(Value::as_u64)
+        .filter(|status| (100..=599).contains(status))
+        .ok_or_else(|| malformed("statusCode must be a number from 100 to 599"))?
+        as u16;
+    let headers = match object.get("headers") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Object(headers)) => headers.iter().map(header_value).collect(),
+        Some(_) => return Err(malformed("headers must be an object")),
+    };
+    let cookies = match object.get("cookies") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(cookies)) => cookies
+            .iter()
+            .map(|cookie| {
+                cookie
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| malformed("cookies must be strings"))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(malformed("cookies must be an array")),
+    };
+    let body = match object.get("body") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(body)) if is_base64_encoded(object) => {
+            base64::engine::general_purpose::STANDARD
+                .decode(body)
+                .map_err(|e| malformed(&format!("body is not valid base64: {e}")))?
+        }
+        Some(Value::String(body)) => body.clone().into_bytes(),
+        Some(body) => body.to_string().into_bytes(),
+    };
+    Ok(UrlResponse {
+        status,
+        headers,
+        cookies,
+        body,
+    })
+}
+
+fn header_value((name, value): (&String, &Value)) -> (String, String) {
+    let value = match value {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    };
+    (name.clone(), value)
+}
+
+fn is_base64_encoded(object: &Map<String, Value>) -> bool {
+    object
+        .get("isBase64Encoded")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn malformed(reason: &str) -> LambdaError {
+    LambdaError::InvalidFunctionUrlResponse {
+        reason: reason.to_string(),
+    }
+}
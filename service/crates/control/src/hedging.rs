@@ -0,0 +1,76 @@
+use dashmap::DashMap;
+use lambda_models::{HedgingConfig, LambdaError};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DEFAULT_HEDGE_PERCENTILE: f64 = 99.0;
+/// Hedging stays off until a function has this many successful samples
+pub const MIN_LATENCY_SAMPLES: usize = 20;
+const LATENCY_WINDOW: usize = 200;
+
+/// Rolling window of recent successful invoke durations per function
+#[derive(Clone, Default)]
+pub struct LatencyTracker {
+    per_function: Arc<DashMap<String, VecDeque<u64>>>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, function_name: &str, duration_ms: u64) {
+        let mut samples = self
+            .per_function
+            .entry(function_name.to_string())
+            .or_default();
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(duration_ms);
+    }
+
+    pub fn remove_function(&self, function_name: &str) {
+        self.per_function.remove(function_name);
+    }
+
+    /// Nearest-rank percentile of the window, or None while there are too few samples
+    pub fn percentile(&self, function_name: &str, percentile: f64) -> Option<u64> {
+        let samples = self.per_function.get(function_name)?;
+        if samples.len() < MIN_LATENCY_SAMPLES {
+            return None;
+        }
+        let mut sorted: Vec<u64> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+    }
+}
+
+pub fn validate_hedging_config(config: &HedgingConfig) -> Result<(), LambdaError> {
+    if let Some(percentile) = config.percentile {
+        if !(percentile > 0.0 && percentile <= 100.0) {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!("percentile must be in (0, 100], got {percentile}"),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// How long to wait on the first dispatch before sending a duplicate
+pub fn hedge_delay(
+    config: &HedgingConfig,
+    latencies: &LatencyTracker,
+    function_name: &str,
+) -> Option<Duration> {
+    if !config.enabled {
+        return None;
+    }
+    let percentile = config.percentile.unwrap_or(DEFAULT_HEDGE_PERCENTILE);
+    let delay_ms = latencies
+        .percentile(function_name, percentile)?
+        .max(config.min_delay_ms.unwrap_or(0));
+    Some(Duration::from_millis(delay_ms))
+}
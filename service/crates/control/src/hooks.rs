@@ -0,0 +1,85 @@
+//! Pre- and post-invoke hooks. The registry runs function hooks itself; this module calls
+//! webhook hooks and reads what hooks answer.
+
+use lambda_models::{
+    HookEvent, HookFailurePolicy, InvocationHook, LambdaError, PreInvokeHookResult,
+};
+use std::time::Duration;
+use tracing::warn;
+
+/// Header naming the hook phase on webhook calls
+pub const HOOK_HEADER: &str = "X-Lambdah-Hook";
+
+#[derive(Clone, Default)]
+pub struct HookCaller {
+    client: reqwest::Client,
+}
+
+impl HookCaller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// POST `event` to `url`; the JSON it answers with, if any, unless it timed out or
+    /// answered with something other than a 2xx
+    pub async fn call_webhook(
+        &self,
+        url: &str,
+        event: &HookEvent,
+        timeout: Duration,
+    ) -> Result<Option<serde_json::Value>, String> {
+        let response = self
+            .client
+            .post(url)
+            .timeout(timeout)
+            .header(HOOK_HEADER, event.hook.as_str())
+            .json(event)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("{url} responded with HTTP {}", response.status()));
+        }
+        let body = response.bytes().await.map_err(|e| e.to_string())?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+        serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(|e| format!("{url} answered with invalid JSON: {e}"))
+    }
+}
+
+/// Read what a pre-invoke hook answered; nothing at all lets the invocation through
+pub fn pre_invoke_result(answer: Option<serde_json::Value>) -> Result<PreInvokeHookResult, String> {
+    match answer {
+        None | Some(serde_json::Value::Null) => Ok(PreInvokeHookResult::default()),
+        Some(answer @ serde_json::Value::Object(_)) => serde_json::from_value(answer)
+            .map_err(|e| format!("unexpected pre-invoke hook answer: {e}")),
+        Some(_) => Err("pre-invoke hooks must answer with a JSON object".to_string()),
+    }
+}
+
+/// Apply `hook`'s failure policy to a failed call
+pub fn hook_failure(
+    hook: &InvocationHook,
+    event: &HookEvent,
+    reason: String,
+) -> Result<(), LambdaError> {
+    match hook.on_failure {
+        HookFailurePolicy::Fail => Err(LambdaError::HookFailed {
+            function_name: event.function_name.clone(),
+            hook: event.hook.as_str().to_string(),
+            reason,
+        }),
+        HookFailurePolicy::Continue => {
+            warn!(
+                "The {} hook of {} failed, continuing without it: {}",
+                event.hook.as_str(),
+                event.function_name,
+                reason
+            );
+            Ok(())
+        }
+    }
+}
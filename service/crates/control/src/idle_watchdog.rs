@@ -74,22 +74,26 @@ impl IdleWatchdog {
                 .warm_pool
                 .set_state_by_container_id(&container_id, crate::warm_pool::InstanceState::Stopping)
                 .await;
-            match self.invoker.stop_container(&container_id).await {
-                Ok(_) => {
-                    // Mark as fully stopped now
-                    let _ = self
-                        .warm_pool
-                        .set_state_by_container_id(
-                            &container_id,
-                            crate::warm_pool::InstanceState::Stopped,
-                        )
-                        .await;
-                    info!("Soft-idle container stopped: {}", container_id);
+            // Stopping waits out the function's grace period, so don't hold up the cycle
+            let warm_pool = self.warm_pool.clone();
+            let invoker = self.invoker.clone();
+            tokio::spawn(async move {
+                match invoker.stop_container(&container_id).await {
+                    Ok(_) => {
+                        // Mark as fully stopped now
+                        let _ = warm_pool
+                            .set_state_by_container_id(
+                                &container_id,
+                                crate::warm_pool::InstanceState::Stopped,
+                            )
+                            .await;
+                        info!("Soft-idle container stopped: {}", container_id);
+                    }
+                    Err(e) => {
+                        error!("Failed to stop soft-idle container {}: {}", container_id, e);
+                    }
                 }
-                Err(e) => {
-                    error!("Failed to stop soft-idle container {}: {}", container_id, e);
-                }
-            }
+            });
         }
 
         Ok(())
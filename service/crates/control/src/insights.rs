@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use lambda_models::{LambdaError, MetricTrend, TrendDirection};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest window insights cover; the throttle log keeps no more than this
+pub const MAX_INSIGHTS_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Throttles kept per function however many arrive within the window
+const MAX_THROTTLES_KEPT: usize = 10_000;
+/// A trend moves once the current figure differs from the previous by this share
+const TREND_THRESHOLD: f64 = 0.1;
+
+/// When invocations of each function were refused for lack of concurrency or memory, since
+/// server start
+#[derive(Clone, Default)]
+pub struct ThrottleTracker {
+    per_function: Arc<DashMap<String, VecDeque<DateTime<Utc>>>>,
+}
+
+impl ThrottleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, function_name: &str, at: DateTime<Utc>) {
+        let mut throttles = self
+            .per_function
+            .entry(function_name.to_string())
+            .or_default();
+        let horizon = at - chrono::Duration::from_std(MAX_INSIGHTS_WINDOW).unwrap_or_default();
+        while throttles
+            .front()
+            .is_some_and(|t| *t < horizon || throttles.len() >= MAX_THROTTLES_KEPT)
+        {
+            throttles.pop_front();
+        }
+        throttles.push_back(at);
+    }
+
+    pub fn count_since(&self, function_name: &str, since: DateTime<Utc>) -> u64 {
+        self.per_function
+            .get(function_name)
+            .map(|throttles| throttles.iter().filter(|t| **t >= since).count() as u64)
+            .unwrap_or(0)
+    }
+
+    pub fn remove_function(&self, function_name: &str) {
+        self.per_function.remove(function_name);
+    }
+}
+
+/// Compare a figure of the window with the window before it. A figure going from zero to
+/// anything is up; figures missing from either window leave the trend unknown
+pub fn trend(current: Option<f64>, previous: Option<f64>) -> MetricTrend {
+    let direction = match (current, previous) {
+        (Some(current), Some(previous)) => {
+            let change = current - previous;
+            if change.abs() <= previous.abs() * TREND_THRESHOLD {
+                TrendDirection::Flat
+            } else if change > 0.0 {
+                TrendDirection::Up
+            } else {
+                TrendDirection::Down
+            }
+        }
+        _ => TrendDirection::Unknown,
+    };
+    MetricTrend {
+        current,
+        previous,
+        direction,
+    }
+}
+
+/// Nearest-rank percentile of `sorted`, which must be in ascending order
+pub fn percentile(sorted: &[u64], percentile: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+}
+
+/// Invocations of a function within one window
+#[derive(Debug, Default)]
+pub struct WindowFigures {
+    pub invocations: u64,
+    pub errors: u64,
+    durations_ms: Vec<u64>,
+}
+
+impl WindowFigures {
+    pub fn record(&mut self, failed: bool, duration_ms: Option<u64>) {
+        self.invocations += 1;
+        if failed {
+            self.errors += 1;
+        }
+        self.durations_ms.extend(duration_ms);
+    }
+
+    /// None without invocations, so an idle window does not read as a recovery
+    pub fn error_rate(&self) -> Option<f64> {
+        (self.invocations > 0).then(|| self.errors as f64 / self.invocations as f64)
+    }
+
+    pub fn p95_duration_ms(&mut self) -> Option<f64> {
+        self.durations_ms.sort_unstable();
+        percentile(&self.durations_ms, 95.0).map(|p| p as f64)
+    }
+}
+
+/// Whether an invocation was refused for lack of concurrency or memory
+pub fn is_throttle(error: &LambdaError) -> bool {
+    matches!(
+        error,
+        LambdaError::ConcurrencyLimitExceeded { .. }
+            | LambdaError::GlobalConcurrencyLimitExceeded
+            | LambdaError::HostMemoryExhausted { .. }
+    )
+}
@@ -0,0 +1,54 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use lambda_models::LambdaError;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+
+/// Environment variable a function container finds its token in
+pub const INSTANCE_TOKEN_ENV: &str = "LAMBDAH_INSTANCE_TOKEN";
+
+/// Tokens proving which container a runtime API call comes from. A container's token is
+/// an HMAC of its instance id, which shows up in traces, under a key only the control plane
+/// knows, so the instance id alone is not enough to act as that container.
+#[derive(Clone)]
+pub struct InstanceTokens {
+    key: Vec<u8>,
+}
+
+impl InstanceTokens {
+    /// Load the key from the database, generating it on first start
+    pub async fn load(pool: &SqlitePool) -> Result<Self, LambdaError> {
+        sqlx::query("INSERT OR IGNORE INTO instance_token_key(id, key) VALUES (1, ?)")
+            .bind(crate::webhooks::generate_secret())
+            .execute(pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        let key: String = sqlx::query_scalar("SELECT key FROM instance_token_key WHERE id = 1")
+            .fetch_one(pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(Self {
+            key: key.into_bytes(),
+        })
+    }
+
+    fn mac(&self, instance_id: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        mac.update(instance_id.as_bytes());
+        mac
+    }
+
+    /// Token of the container running `instance_id`
+    pub fn token(&self, instance_id: &str) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(self.mac(instance_id).finalize().into_bytes())
+    }
+
+    /// Whether `token` is the one of `instance_id`, compared in constant time
+    pub fn verify(&self, instance_id: &str, token: &str) -> bool {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token)
+            .is_ok_and(|token| self.mac(instance_id).verify_slice(&token).is_ok())
+    }
+}
@@ -0,0 +1,38 @@
+use lambda_models::{ApiRoute, EventSourceMapping, FunctionReference, ReferenceKind};
+use std::collections::HashSet;
+
+/// The functions a route sends requests to: its own and its canary's
+pub fn route_references(route: &ApiRoute) -> Vec<FunctionReference> {
+    let reference = |kind, function_name: &str| FunctionReference {
+        kind,
+        id: route.route_id.to_string(),
+        resource: route.path.clone(),
+        function_name: function_name.to_string(),
+    };
+    let mut references = vec![reference(ReferenceKind::ApiRoute, &route.function_name)];
+    if let Some(canary) = &route.canary {
+        references.push(reference(ReferenceKind::RouteCanary, &canary.function_name));
+    }
+    references
+}
+
+pub fn mapping_reference(mapping: &EventSourceMapping) -> FunctionReference {
+    FunctionReference {
+        kind: ReferenceKind::EventSourceMapping,
+        id: mapping.uuid.to_string(),
+        resource: mapping.event_source_arn.clone(),
+        function_name: mapping.function_name.clone(),
+    }
+}
+
+/// References of `routes` to functions missing from `functions`
+pub fn dangling_route_references(
+    routes: &[ApiRoute],
+    functions: &HashSet<String>,
+) -> Vec<FunctionReference> {
+    routes
+        .iter()
+        .flat_map(route_references)
+        .filter(|r| !functions.contains(&r.function_name))
+        .collect()
+}
@@ -0,0 +1,115 @@
+use chrono::Utc;
+use dashmap::DashMap;
+use lambda_models::{InvocationTrace, LambdaError, RuntimeTraceCall, RuntimeTraceEvent};
+use sqlx::{Row, SqlitePool};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Longest body kept per trace event; the rest is cut off
+pub const MAX_TRACE_BODY_BYTES: usize = 256 * 1024;
+
+/// Runtime API exchanges of traced invocations. Events are collected in memory while the
+/// invocation runs and stored with its execution record once it finished.
+#[derive(Clone)]
+pub struct InvocationTraces {
+    pool: SqlitePool,
+    active: Arc<DashMap<String, InvocationTrace>>,
+}
+
+impl InvocationTraces {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            active: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Start collecting the runtime API calls made for `request_id`
+    pub fn begin(&self, request_id: &str, function_name: &str) {
+        self.active.insert(
+            request_id.to_string(),
+            InvocationTrace {
+                request_id: request_id.to_string(),
+                function_name: function_name.to_string(),
+                started_at: Utc::now(),
+                completed_at: None,
+                events: Vec::new(),
+            },
+        );
+    }
+
+    pub fn is_tracing(&self, request_id: &str) -> bool {
+        self.active.contains_key(request_id)
+    }
+
+    /// Add a runtime API call to the trace of `request_id`; untraced requests are ignored
+    pub fn record(
+        &self,
+        request_id: &str,
+        instance_id: Option<&str>,
+        call: RuntimeTraceCall,
+        headers: BTreeMap<String, String>,
+        body: &[u8],
+    ) {
+        let Some(mut trace) = self.active.get_mut(request_id) else {
+            return;
+        };
+        let body_truncated = body.len() > MAX_TRACE_BODY_BYTES;
+        let body = String::from_utf8_lossy(&body[..body.len().min(MAX_TRACE_BODY_BYTES)]);
+        trace.events.push(RuntimeTraceEvent {
+            at: Utc::now(),
+            instance_id: instance_id.map(str::to_string),
+            call,
+            headers,
+            body: body.into_owned(),
+            body_truncated,
+        });
+    }
+
+    /// Stop collecting for `request_id` and store what was collected
+    pub async fn finish(&self, request_id: &str) -> Result<(), LambdaError> {
+        let Some((_, mut trace)) = self.active.remove(request_id) else {
+            return Ok(());
+        };
+        let completed_at = Utc::now();
+        trace.completed_at = Some(completed_at);
+        let document = serde_json::to_string(&trace).map_err(|e| LambdaError::InternalError {
+            reason: format!("Failed to serialize trace: {e}"),
+        })?;
+        sqlx::query(
+            "INSERT INTO execution_traces (execution_id, trace, created_at) VALUES (?, ?, ?)
+             ON CONFLICT(execution_id) DO UPDATE SET trace = excluded.trace, created_at = excluded.created_at",
+        )
+        .bind(request_id)
+        .bind(document)
+        .bind(completed_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    pub async fn get(&self, request_id: &str) -> Result<InvocationTrace, LambdaError> {
+        let row = sqlx::query("SELECT trace FROM execution_traces WHERE execution_id = ?")
+            .bind(request_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .ok_or_else(|| LambdaError::TraceNotFound {
+                request_id: request_id.to_string(),
+            })?;
+        serde_json::from_str(row.get::<&str, _>("trace")).map_err(|e| LambdaError::InternalError {
+            reason: format!("Stored trace is not valid: {e}"),
+        })
+    }
+
+    pub async fn exists(&self, request_id: &str) -> Result<bool, LambdaError> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM execution_traces WHERE execution_id = ?")
+                .bind(request_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(count > 0)
+    }
+}
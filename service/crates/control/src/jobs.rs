@@ -0,0 +1,199 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_models::{FunctionError, InvokeResponse, Job, JobStatus, JobsConfig, LambdaError};
+use sqlx::{Row, SqlitePool};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const JOB_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Invocations started in job mode, kept in `invocation_jobs` so callers can poll for the
+/// result instead of holding an HTTP request open
+#[derive(Clone)]
+pub struct JobStore {
+    pool: SqlitePool,
+    config: JobsConfig,
+}
+
+impl JobStore {
+    pub fn new(pool: SqlitePool, config: JobsConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Record a running job for `function_name`
+    pub async fn create(&self, function_name: &str) -> Result<Job, LambdaError> {
+        self.create_with_id(&Uuid::new_v4().to_string(), function_name)
+            .await
+    }
+
+    /// Record a running job under an id chosen by the caller, such as the request id of an
+    /// invocation already under way
+    pub async fn create_with_id(
+        &self,
+        job_id: &str,
+        function_name: &str,
+    ) -> Result<Job, LambdaError> {
+        let created_at = Utc::now();
+        sqlx::query(
+            "INSERT INTO invocation_jobs (job_id, function_name, status, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(job_id)
+        .bind(function_name)
+        .bind(JobStatus::Running.as_str())
+        .bind(created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.get(job_id).await
+    }
+
+    /// Store the outcome of the invocation and start the result's TTL
+    pub async fn complete(
+        &self,
+        job_id: &str,
+        result: Result<InvokeResponse, LambdaError>,
+    ) -> Result<(), LambdaError> {
+        let completed_at = Utc::now();
+        let expires_at = completed_at + ChronoDuration::seconds(self.config.result_ttl_secs as i64);
+        let (status, version, function_error, mut payload, error_message, duration_ms) =
+            match result {
+                Ok(response) => (
+                    if response.function_error.is_some() {
+                        JobStatus::Failed
+                    } else {
+                        JobStatus::Succeeded
+                    },
+                    response.executed_version,
+                    response.function_error.map(|e| match e {
+                        FunctionError::Handled => "Handled",
+                        FunctionError::Unhandled => "Unhandled",
+                    }),
+                    response.payload.map(|p| p.to_string()),
+                    None,
+                    response.duration_ms.map(|d| d as i64),
+                ),
+                Err(e) => (
+                    JobStatus::Failed,
+                    None,
+                    None,
+                    None,
+                    Some(e.to_string()),
+                    None,
+                ),
+            };
+        let payload_truncated = payload
+            .as_ref()
+            .is_some_and(|p| p.len() as u64 > self.config.max_result_bytes);
+        if payload_truncated {
+            payload = None;
+        }
+        sqlx::query(
+            "UPDATE invocation_jobs SET status = ?, completed_at = ?, expires_at = ?, executed_version = ?, \
+             function_error = ?, payload = ?, payload_truncated = ?, error_message = ?, duration_ms = ? \
+             WHERE job_id = ?",
+        )
+        .bind(status.as_str())
+        .bind(completed_at)
+        .bind(expires_at)
+        .bind(version)
+        .bind(function_error)
+        .bind(payload)
+        .bind(payload_truncated)
+        .bind(error_message)
+        .bind(duration_ms)
+        .bind(job_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// A job, until its result expires
+    pub async fn get(&self, job_id: &str) -> Result<Job, LambdaError> {
+        let row = sqlx::query(
+            "SELECT * FROM invocation_jobs WHERE job_id = ? AND (expires_at IS NULL OR expires_at > ?)",
+        )
+        .bind(job_id)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::JobNotFound {
+            job_id: job_id.to_string(),
+        })?;
+        row_to_job(&row)
+    }
+
+    /// Jobs still running belonged to a previous process; nothing will finish them now.
+    /// Queued Event invocations are left running, as the queue picks them up again
+    pub async fn fail_interrupted(&self) -> Result<u64, LambdaError> {
+        let now = Utc::now();
+        let failed = sqlx::query(
+            "UPDATE invocation_jobs SET status = ?, completed_at = ?, expires_at = ?, error_message = ? \
+             WHERE status = ? AND job_id NOT IN (SELECT request_id FROM async_invocations)",
+        )
+        .bind(JobStatus::Failed.as_str())
+        .bind(now)
+        .bind(now + ChronoDuration::seconds(self.config.result_ttl_secs as i64))
+        .bind("Server restarted before the job finished")
+        .bind(JobStatus::Running.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .rows_affected();
+        if failed > 0 {
+            warn!("Marked {} interrupted job(s) as failed", failed);
+        }
+        Ok(failed)
+    }
+
+    /// Drop jobs whose result has expired
+    pub async fn prune(&self) -> Result<u64, LambdaError> {
+        let removed = sqlx::query("DELETE FROM invocation_jobs WHERE expires_at <= ?")
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .rows_affected();
+        if removed > 0 {
+            info!("Removed {} expired job(s)", removed);
+        }
+        Ok(removed)
+    }
+
+    pub async fn start_pruning(self) {
+        loop {
+            if let Err(e) = self.prune().await {
+                warn!("job prune error: {}", e);
+            }
+            tokio::time::sleep(JOB_PRUNE_INTERVAL).await;
+        }
+    }
+}
+
+fn row_to_job(row: &sqlx::sqlite::SqliteRow) -> Result<Job, LambdaError> {
+    let invalid = |e: String| LambdaError::InternalError {
+        reason: format!("Corrupt job: {e}"),
+    };
+    let function_error = match row.get::<Option<String>, _>("function_error").as_deref() {
+        None => None,
+        Some("Handled") => Some(FunctionError::Handled),
+        Some(_) => Some(FunctionError::Unhandled),
+    };
+    Ok(Job {
+        job_id: row.get("job_id"),
+        function_name: row.get("function_name"),
+        status: row.get::<String, _>("status").parse().map_err(invalid)?,
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+        expires_at: row.get("expires_at"),
+        executed_version: row.get("executed_version"),
+        function_error,
+        payload: row
+            .get::<Option<String>, _>("payload")
+            .map(|p| serde_json::from_str(&p).unwrap_or(serde_json::Value::String(p))),
+        payload_truncated: row.get("payload_truncated"),
+        error_message: row.get("error_message"),
+        duration_ms: row.get::<Option<i64>, _>("duration_ms").map(|d| d as u64),
+    })
+}
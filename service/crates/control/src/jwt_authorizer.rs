@@ -0,0 +1,319 @@
+use base64::Engine;
+use dashmap::DashMap;
+use lambda_models::RouteJwtAuthorizer;
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// How long a fetched key set is used before it is fetched again
+const JWKS_TTL: Duration = Duration::from_secs(300);
+/// Least time between fetches of a key set for tokens signed with a key it lacks
+const JWKS_MIN_REFRESH: Duration = Duration::from_secs(30);
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Clock skew tolerated on `exp` and `nbf`
+const LEEWAY_SECS: i64 = 60;
+
+/// A public key of a JSON Web Key Set; RSA keys have `n` and `e`, EC keys `crv`, `x` and `y`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+    #[serde(default)]
+    pub alg: Option<String>,
+    #[serde(default)]
+    pub n: Option<String>,
+    #[serde(default)]
+    pub e: Option<String>,
+    #[serde(default)]
+    pub crv: Option<String>,
+    #[serde(default)]
+    pub x: Option<String>,
+    #[serde(default)]
+    pub y: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+struct CachedKeys {
+    keys: Arc<Vec<Jwk>>,
+    fetched_at: Instant,
+}
+
+/// Key sets of the JWT authorizers of routes, by URL, fetched when first needed and again
+/// once stale or when a token names a key they lack
+#[derive(Clone)]
+pub struct JwksCache {
+    client: reqwest::Client,
+    sets: Arc<DashMap<String, CachedKeys>>,
+}
+
+impl Default for JwksCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JwksCache {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(JWKS_FETCH_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            sets: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Check the bearer token in `authorization` against the route's authorizer: the
+    /// `requestContext.authorizer.jwt` of its events when valid, why it is not otherwise
+    pub async fn authorize(
+        &self,
+        authorizer: &RouteJwtAuthorizer,
+        authorization: Option<&str>,
+    ) -> Result<Value, String> {
+        let token = bearer_token(authorization).ok_or("no bearer token")?;
+        let kid = token_header(token)?.kid;
+        let mut keys = self.keys(&authorizer.jwks_url, false).await?;
+        if !has_key(&keys, kid.as_deref()) {
+            keys = self.keys(&authorizer.jwks_url, true).await?;
+        }
+        let claims = verify_token(token, &keys, authorizer, chrono::Utc::now().timestamp())?;
+        Ok(jwt_context(&claims))
+    }
+
+    /// Keys published at `url`; `refresh` fetches them again unless that just happened
+    async fn keys(&self, url: &str, refresh: bool) -> Result<Arc<Vec<Jwk>>, String> {
+        if let Some(cached) = self.sets.get(url) {
+            let age = cached.fetched_at.elapsed();
+            let fresh = if refresh {
+                age < JWKS_MIN_REFRESH
+            } else {
+                age < JWKS_TTL
+            };
+            if fresh {
+                return Ok(cached.keys.clone());
+            }
+        }
+        debug!("Fetching JWKS from {}", url);
+        let fetched = async {
+            self.client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<JwkSet>()
+                .await
+        }
+        .await;
+        match fetched {
+            Ok(set) => {
+                let keys = Arc::new(set.keys);
+                self.sets.insert(
+                    url.to_string(),
+                    CachedKeys {
+                        keys: keys.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(keys)
+            }
+            Err(e) => {
+                warn!("Failed to fetch JWKS from {}: {}", url, e);
+                // Keys fetched before still serve while the issuer is unreachable
+                match self.sets.get(url) {
+                    Some(cached) => Ok(cached.keys.clone()),
+                    None => Err(format!("signing keys unavailable: {e}")),
+                }
+            }
+        }
+    }
+}
+
+/// The token of an `Authorization` header, with or without its `Bearer` scheme
+pub fn bearer_token(authorization: Option<&str>) -> Option<&str> {
+    let value = authorization?.trim();
+    let token = match value.split_once(' ') {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => token.trim(),
+        _ if value.eq_ignore_ascii_case("bearer") => "",
+        _ => value,
+    };
+    (!token.is_empty()).then_some(token)
+}
+
+fn has_key(keys: &[Jwk], kid: Option<&str>) -> bool {
+    match kid {
+        Some(kid) => keys.iter().any(|k| k.kid.as_deref() == Some(kid)),
+        None => !keys.is_empty(),
+    }
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(segment.trim_end_matches('='))
+        .map_err(|_| "malformed token".to_string())
+}
+
+fn token_header(token: &str) -> Result<JwtHeader, String> {
+    let header = token.split('.').next().unwrap_or_default();
+    serde_json::from_slice(&decode_segment(header)?).map_err(|_| "malformed token".to_string())
+}
+
+/// Verify the signature of `token` with one of `keys` and check its issuer, audience and
+/// validity period at `now` (seconds since the epoch). Returns its claims.
+pub fn verify_token(
+    token: &str,
+    keys: &[Jwk],
+    authorizer: &RouteJwtAuthorizer,
+    now: i64,
+) -> Result<Map<String, Value>, String> {
+    let mut segments = token.split('.');
+    let (Some(header), Some(payload), Some(signature), None) = (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) else {
+        return Err("malformed token".to_string());
+    };
+    let parsed_header = token_header(token)?;
+    let signature = decode_segment(signature)?;
+    let signed = &token[..header.len() + 1 + payload.len()];
+
+    let candidates: Vec<&Jwk> = keys
+        .iter()
+        .filter(|k| parsed_header.kid.is_none() || k.kid == parsed_header.kid)
+        .filter(|k| k.alg.as_deref().is_none_or(|alg| alg == parsed_header.alg))
+        .collect();
+    if candidates.is_empty() {
+        return Err("unknown signing key".to_string());
+    }
+    let verified = candidates.iter().any(|key| {
+        verify_signature(&parsed_header.alg, key, signed.as_bytes(), &signature).unwrap_or(false)
+    });
+    if !verified {
+        return Err("invalid signature".to_string());
+    }
+
+    let claims: Map<String, Value> =
+        serde_json::from_slice(&decode_segment(payload)?).map_err(|_| "malformed claims")?;
+    if claims.get("iss").and_then(Value::as_str) != Some(authorizer.issuer.as_str()) {
+        return Err("issuer mismatch".to_string());
+    }
+    let audiences: Vec<&str> = match claims.get("aud").or_else(|| claims.get("client_id")) {
+        Some(Value::String(aud)) => vec![aud.as_str()],
+        Some(Value::Array(auds)) => auds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !audiences
+        .iter()
+        .any(|aud| authorizer.audience.iter().any(|a| a == aud))
+    {
+        return Err("audience mismatch".to_string());
+    }
+    let exp = claims
+        .get("exp")
+        .and_then(Value::as_i64)
+        .ok_or("token has no expiry")?;
+    if now > exp + LEEWAY_SECS {
+        return Err("token expired".to_string());
+    }
+    if claims
+        .get("nbf")
+        .and_then(Value::as_i64)
+        .is_some_and(|nbf| now + LEEWAY_SECS < nbf)
+    {
+        return Err("token not yet valid".to_string());
+    }
+    Ok(claims)
+}
+
+/// Whether `signature` over `message` is valid for `key` under JWS algorithm `alg`; `None`
+/// when the algorithm or key is not supported
+fn verify_signature(alg: &str, key: &Jwk, message: &[u8], signature: &[u8]) -> Option<bool> {
+    let decode = |field: &Option<String>| decode_segment(field.as_deref()?).ok();
+    match (alg, key.kty.as_str()) {
+        ("RS256" | "RS384" | "RS512", "RSA") => {
+            let params = match alg {
+                "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                _ => &signature::RSA_PKCS1_2048_8192_SHA512,
+            };
+            let components = RsaPublicKeyComponents {
+                n: decode(&key.n)?,
+                e: decode(&key.e)?,
+            };
+            Some(components.verify(params, message, signature).is_ok())
+        }
+        ("ES256" | "ES384", "EC") => {
+            let (params, crv) = match alg {
+                "ES256" => (&signature::ECDSA_P256_SHA256_FIXED, "P-256"),
+                _ => (&signature::ECDSA_P384_SHA384_FIXED, "P-384"),
+            };
+            if key.crv.as_deref() != Some(crv) {
+                return None;
+            }
+            // Uncompressed point: 0x04, then x and y
+            let mut point = vec![0x04];
+            point.extend(decode(&key.x)?);
+            point.extend(decode(&key.y)?);
+            Some(
+                UnparsedPublicKey::new(params, point)
+                    .verify(message, signature)
+                    .is_ok(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Claims and scopes as HTTP API's JWT authorizer passes them: claim values as strings,
+/// arrays as `[a b]`, and scopes from `scope` or `scp` (`null` without either)
+pub fn jwt_context(claims: &Map<String, Value>) -> Value {
+    let as_text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(|item| item
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or(item.to_string()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        other => other.to_string(),
+    };
+    let claims_text: Map<String, Value> = claims
+        .iter()
+        .map(|(name, value)| (name.clone(), Value::String(as_text(value))))
+        .collect();
+    let scopes: Option<Vec<String>> = match (claims.get("scope"), claims.get("scp")) {
+        (Some(Value::String(scope)), _) => {
+            Some(scope.split_whitespace().map(str::to_string).collect())
+        }
+        (_, Some(Value::Array(scp))) => Some(
+            scp.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        ),
+        (_, Some(Value::String(scp))) => Some(scp.split_whitespace().map(str::to_string).collect()),
+        _ => None,
+    };
+    json!({ "claims": claims_text, "scopes": scopes })
+}
@@ -1,27 +1,110 @@
+pub mod access_log;
+pub mod async_invocations;
 pub mod autoscaler;
+pub mod builds;
 pub mod cache;
+pub mod canary;
+pub mod change_feed;
 pub mod concurrency;
+pub mod config_history;
 pub mod container_monitor;
+pub mod cost;
+pub mod dashboard;
+pub mod disk;
+pub mod doctor;
+pub mod error_summary;
+pub mod event_sources;
+pub mod exec_sessions;
 pub mod execution_tracker;
+pub mod features;
+pub mod function_urls;
+pub mod hedging;
+pub mod hooks;
 pub mod idle_watchdog;
+pub mod insights;
+pub mod instance_tokens;
+pub mod integrity;
+pub mod invocation_traces;
+pub mod jobs;
+pub mod jwt_authorizer;
+pub mod listing;
+pub mod maintenance;
 pub mod migrations;
+pub mod openapi;
 pub mod pending;
+pub mod placement;
+pub mod prewarm;
 pub mod queues;
+pub mod quotas;
+pub mod recording;
 pub mod registry;
+pub mod replication;
+pub mod response_headers;
+pub mod rollouts;
+pub mod route_limits;
+pub mod s3_notifications;
 pub mod scheduler;
+pub mod schedules;
+pub mod search;
+pub mod secrets;
+pub mod sqs;
+pub mod static_functions;
+pub mod sync_invoke;
+pub mod synthetics;
+pub mod system_info;
+pub mod transforms;
+pub mod trusted_headers;
+pub mod upgrade;
+pub mod validation;
+pub mod versions;
 pub mod warm_pool;
+pub mod warm_pool_snapshot;
+pub mod warmup_stats;
+pub mod webhooks;
 pub mod work_item;
 
+pub use access_log::*;
 pub use autoscaler::*;
+pub use builds::*;
 pub use cache::*;
+pub use canary::*;
+pub use change_feed::*;
 pub use concurrency::*;
+pub use config_history::*;
 pub use container_monitor::*;
+pub use dashboard::*;
+pub use disk::*;
+pub use doctor::*;
+pub use error_summary::*;
+pub use event_sources::*;
+pub use exec_sessions::*;
 pub use execution_tracker::*;
+pub use hedging::*;
 pub use idle_watchdog::*;
+pub use invocation_traces::*;
+pub use listing::*;
+pub use maintenance::*;
 pub use migrations::*;
 pub use pending::*;
+pub use placement::*;
 pub use queues::*;
+pub use quotas::*;
+pub use recording::*;
 pub use registry::*;
+pub use replication::*;
+pub use response_headers::*;
+pub use rollouts::*;
+pub use route_limits::*;
+pub use s3_notifications::*;
 pub use scheduler::*;
+pub use search::*;
+pub use sqs::*;
+pub use static_functions::*;
+pub use sync_invoke::*;
+pub use synthetics::*;
+pub use validation::*;
 pub use warm_pool::*;
+pub use warm_pool_snapshot::*;
+pub use warmup_stats::*;
+pub use webhooks::*;
 pub use work_item::*;
@@ -0,0 +1,130 @@
+use base64::Engine;
+use lambda_models::{FunctionSortKey, LambdaError, SortOrder};
+
+pub const DEFAULT_LIST_MAX_ITEMS: u32 = 50;
+pub const MAX_LIST_MAX_ITEMS: u32 = 1000;
+
+/// Position after the last function of a page: its sort value and name.
+/// The sort key and order are carried along so a marker cannot be replayed against a
+/// differently ordered listing, where it would silently skip or repeat functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListMarker {
+    pub sort_by: FunctionSortKey,
+    pub order: SortOrder,
+    pub sort_value: String,
+    pub function_name: String,
+}
+
+fn sort_key_name(sort_by: FunctionSortKey) -> &'static str {
+    match sort_by {
+        FunctionSortKey::Name => "name",
+        FunctionSortKey::LastModified => "last_modified",
+        FunctionSortKey::InvocationCount => "invocation_count",
+    }
+}
+
+fn order_name(order: SortOrder) -> &'static str {
+    match order {
+        SortOrder::Asc => "asc",
+        SortOrder::Desc => "desc",
+    }
+}
+
+impl ListMarker {
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}\n{}\n{}\n{}",
+            sort_key_name(self.sort_by),
+            order_name(self.order),
+            self.sort_value,
+            self.function_name
+        );
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode a marker and check it belongs to a listing with the same ordering
+    pub fn decode(
+        marker: &str,
+        sort_by: FunctionSortKey,
+        order: SortOrder,
+    ) -> Result<Self, LambdaError> {
+        let invalid = |reason: &str| LambdaError::InvalidRequest {
+            reason: format!("Invalid marker: {reason}"),
+        };
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(marker)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| invalid("not produced by ListFunctions"))?;
+        let mut parts = raw.splitn(4, '\n');
+        let (Some(key), Some(dir), Some(sort_value), Some(function_name)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("not produced by ListFunctions"));
+        };
+        if key != sort_key_name(sort_by) || dir != order_name(order) {
+            return Err(invalid("it was issued for a different sort order"));
+        }
+        Ok(Self {
+            sort_by,
+            order,
+            sort_value: sort_value.to_string(),
+            function_name: function_name.to_string(),
+        })
+    }
+}
+
+/// SQL expression ListFunctions orders by (`f` is functions, `ec` the execution counts)
+pub fn sort_expression(sort_by: FunctionSortKey) -> &'static str {
+    match sort_by {
+        FunctionSortKey::Name => "f.function_name",
+        FunctionSortKey::LastModified => "f.last_modified",
+        FunctionSortKey::InvocationCount => "COALESCE(ec.invocation_count, 0)",
+    }
+}
+
+/// ORDER BY clause; the name tie-breaker keeps pages stable when sort values repeat
+pub fn order_by_clause(sort_by: FunctionSortKey, order: SortOrder) -> String {
+    let dir = match order {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+    match sort_by {
+        FunctionSortKey::Name => format!("f.function_name {dir}"),
+        _ => format!("{} {dir}, f.function_name ASC", sort_expression(sort_by)),
+    }
+}
+
+/// WHERE fragment selecting rows after the marker, with the marker's sort value bound
+/// as `?7` and its function name as `?8`
+pub fn after_marker_clause(sort_by: FunctionSortKey, order: SortOrder) -> String {
+    let cmp = match order {
+        SortOrder::Asc => ">",
+        SortOrder::Desc => "<",
+    };
+    match sort_by {
+        FunctionSortKey::Name => format!("f.function_name {cmp} ?8"),
+        _ => {
+            let expr = sort_expression(sort_by);
+            format!("({expr} {cmp} ?7 OR ({expr} = ?7 AND f.function_name > ?8))")
+        }
+    }
+}
+
+/// WHERE fragment keeping functions (`f`) that carry every tag of the JSON object bound as
+/// `param`; an empty object keeps them all
+pub fn tags_clause(param: &str) -> String {
+    format!(
+        "NOT EXISTS (SELECT 1 FROM json_each({param}) t WHERE NOT EXISTS (
+             SELECT 1 FROM function_tags ft WHERE ft.function_id = f.function_id
+               AND ft.tag_key = t.key AND ft.tag_value = t.value))"
+    )
+}
+
+/// WHERE fragment keeping functions (`f`) in the group bound as `param`; NULL keeps them all
+pub fn group_clause(param: &str) -> String {
+    format!(
+        "({param} IS NULL OR f.function_id IN
+             (SELECT function_id FROM function_group_members WHERE group_name = {param}))"
+    )
+}
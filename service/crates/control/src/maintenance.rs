@@ -0,0 +1,184 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use lambda_models::{DatabaseStatus, LambdaError, MaintenanceConfig, TableStatus};
+use sqlx::{Row, SqlitePool};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Executions deleted per statement, so pruning a large backlog does not hold the write
+/// lock for long
+const PRUNE_BATCH: i64 = 1000;
+
+#[derive(Default)]
+struct MaintenanceRuns {
+    last_pruned_at: Option<DateTime<Utc>>,
+    last_pruned_executions: u64,
+    last_vacuum_at: Option<DateTime<Utc>>,
+}
+
+/// Prunes old executions and compacts the database file, so it does not keep growing
+#[derive(Clone)]
+pub struct DatabaseMaintenance {
+    pool: SqlitePool,
+    config: MaintenanceConfig,
+    runs: Arc<Mutex<MaintenanceRuns>>,
+}
+
+impl DatabaseMaintenance {
+    pub fn new(pool: SqlitePool, config: MaintenanceConfig) -> Self {
+        Self {
+            pool,
+            config,
+            runs: Arc::new(Mutex::new(MaintenanceRuns::default())),
+        }
+    }
+
+    /// Delete executions past `execution_retention_days`, then the oldest ones beyond
+    /// `max_executions`
+    pub async fn prune_executions(&self) -> Result<u64, LambdaError> {
+        let mut removed = 0;
+        if self.config.execution_retention_days > 0 {
+            let cutoff =
+                Utc::now() - ChronoDuration::days(self.config.execution_retention_days as i64);
+            loop {
+                let deleted = sqlx::query(
+                    "DELETE FROM executions WHERE execution_id IN
+                     (SELECT execution_id FROM executions WHERE start_time < ? LIMIT ?)",
+                )
+                .bind(cutoff)
+                .bind(PRUNE_BATCH)
+                .execute(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?
+                .rows_affected();
+                removed += deleted;
+                if deleted < PRUNE_BATCH as u64 {
+                    break;
+                }
+            }
+        }
+        if self.config.max_executions > 0 {
+            loop {
+                let total: i64 = sqlx::query("SELECT COUNT(*) AS total FROM executions")
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(LambdaError::SqlxError)?
+                    .get("total");
+                let excess = total - self.config.max_executions as i64;
+                if excess <= 0 {
+                    break;
+                }
+                removed += sqlx::query(
+                    "DELETE FROM executions WHERE execution_id IN
+                     (SELECT execution_id FROM executions ORDER BY start_time ASC LIMIT ?)",
+                )
+                .bind(excess.min(PRUNE_BATCH))
+                .execute(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?
+                .rows_affected();
+            }
+        }
+
+        let mut runs = self.runs.lock().unwrap();
+        runs.last_pruned_at = Some(Utc::now());
+        runs.last_pruned_executions = removed;
+        if removed > 0 {
+            info!("Pruned {} execution(s)", removed);
+        }
+        Ok(removed)
+    }
+
+    /// Give free pages back to the file system and refresh the statistics the query
+    /// planner picks indexes by
+    pub async fn vacuum(&self) -> Result<(), LambdaError> {
+        sqlx::query("VACUUM")
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        sqlx::query("ANALYZE")
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        self.runs.lock().unwrap().last_vacuum_at = Some(Utc::now());
+        info!("Vacuumed and analyzed the database");
+        Ok(())
+    }
+
+    pub async fn status(&self) -> Result<DatabaseStatus, LambdaError> {
+        let page_size = self.pragma("page_size").await?;
+        let page_count = self.pragma("page_count").await?;
+        let freelist_count = self.pragma("freelist_count").await?;
+
+        let names: Vec<String> = sqlx::query(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .iter()
+        .map(|row| row.get("name"))
+        .collect();
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let rows: i64 = sqlx::query(&format!(
+                "SELECT COUNT(*) AS rows FROM \"{}\"",
+                name.replace('"', "\"\"")
+            ))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .get("rows");
+            tables.push(TableStatus {
+                name,
+                rows: rows as u64,
+            });
+        }
+        tables.sort_by(|a, b| b.rows.cmp(&a.rows).then_with(|| a.name.cmp(&b.name)));
+
+        let runs = self.runs.lock().unwrap();
+        Ok(DatabaseStatus {
+            size_bytes: page_size * page_count,
+            free_bytes: page_size * freelist_count,
+            tables,
+            last_pruned_at: runs.last_pruned_at,
+            last_pruned_executions: runs.last_pruned_executions,
+            last_vacuum_at: runs.last_vacuum_at,
+        })
+    }
+
+    async fn pragma(&self, name: &str) -> Result<u64, LambdaError> {
+        let value: i64 = sqlx::query(&format!("PRAGMA {name}"))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .get(0);
+        Ok(value.max(0) as u64)
+    }
+
+    /// Prune every `prune_interval_secs`, and vacuum every `vacuum_interval_hours` from
+    /// the first one after start
+    pub async fn run(self) {
+        let prune_interval = Duration::from_secs(self.config.prune_interval_secs.max(60));
+        let vacuum_interval = (self.config.vacuum_interval_hours > 0)
+            .then(|| Duration::from_secs(self.config.vacuum_interval_hours * 3600));
+        let mut next_vacuum = vacuum_interval.map(|interval| Instant::now() + interval);
+        loop {
+            if let Err(e) = self.prune_executions().await {
+                warn!("execution prune error: {}", e);
+            }
+            if let (Some(due), Some(interval)) = (next_vacuum, vacuum_interval) {
+                if Instant::now() >= due {
+                    if let Err(e) = self.vacuum().await {
+                        warn!("database vacuum error: {}", e);
+                    }
+                    next_vacuum = Some(Instant::now() + interval);
+                }
+            }
+            tokio::time::sleep(prune_interval).await;
+        }
+    }
+}
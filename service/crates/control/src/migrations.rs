@@ -1,3 +1,4 @@
+use lambda_models::{SchemaMigration, SchemaStatus};
 use sqlx::{Pool, Sqlite};
 use tracing::info;
 
@@ -7,9 +8,177 @@ const MIGRATION_002_API_ROUTES: &str = include_str!("../migrations/002_api_route
 const MIGRATION_003_SECRETS: &str = include_str!("../migrations/003_secrets.sql");
 const MIGRATION_004_FUNCTION_CONCURRENCY: &str =
     include_str!("../migrations/004_function_concurrency.sql");
+const MIGRATION_005_EXECUTION_ERROR_MESSAGE: &str =
+    include_str!("../migrations/005_execution_error_message.sql");
+const MIGRATION_006_EXECUTION_ERROR_PAYLOAD: &str =
+    include_str!("../migrations/006_execution_error_payload.sql");
+const MIGRATION_007_FUNCTION_HEDGING: &str = include_str!("../migrations/007_function_hedging.sql");
+const MIGRATION_008_FUNCTION_METADATA: &str =
+    include_str!("../migrations/008_function_metadata.sql");
+const MIGRATION_009_WEBHOOKS: &str = include_str!("../migrations/009_webhooks.sql");
+const MIGRATION_010_QUEUES: &str = include_str!("../migrations/010_queues.sql");
+const MIGRATION_011_S3_EVENT_SOURCES: &str = include_str!("../migrations/011_s3_event_sources.sql");
+const MIGRATION_012_CHANGE_FEED: &str = include_str!("../migrations/012_change_feed.sql");
+const MIGRATION_013_FUNCTION_CONFIG_HISTORY: &str =
+    include_str!("../migrations/013_function_config_history.sql");
+const MIGRATION_014_WARM_POOL_SNAPSHOT: &str =
+    include_str!("../migrations/014_warm_pool_snapshot.sql");
+const MIGRATION_015_FUNCTION_STOP_GRACE: &str =
+    include_str!("../migrations/015_function_stop_grace.sql");
+const MIGRATION_016_INVOCATION_JOBS: &str = include_str!("../migrations/016_invocation_jobs.sql");
+const MIGRATION_017_JOB_PAYLOAD_TRUNCATED: &str =
+    include_str!("../migrations/017_job_payload_truncated.sql");
+const MIGRATION_018_FUNCTION_LOCALE: &str = include_str!("../migrations/018_function_locale.sql");
+const MIGRATION_019_FUNCTION_PREWARM: &str = include_str!("../migrations/019_function_prewarm.sql");
+const MIGRATION_020_API_ROUTE_CANARY: &str = include_str!("../migrations/020_api_route_canary.sql");
+const MIGRATION_021_API_ROUTE_ACCESS_LOG: &str =
+    include_str!("../migrations/021_api_route_access_log.sql");
+const MIGRATION_022_API_ROUTE_MOCK: &str = include_str!("../migrations/022_api_route_mock.sql");
+const MIGRATION_023_TEST_EVENTS: &str = include_str!("../migrations/023_test_events.sql");
+const MIGRATION_024_API_ROUTE_RECORDING: &str =
+    include_str!("../migrations/024_api_route_recording.sql");
+const MIGRATION_025_REPLICATION_STATE: &str =
+    include_str!("../migrations/025_replication_state.sql");
+const MIGRATION_026_FUNCTION_LAST_UPDATE_STATUS: &str =
+    include_str!("../migrations/026_function_last_update_status.sql");
+const MIGRATION_027_FUNCTION_RUNTIME_MANAGEMENT: &str =
+    include_str!("../migrations/027_function_runtime_management.sql");
+const MIGRATION_028_IMAGE_DIGESTS: &str = include_str!("../migrations/028_image_digests.sql");
+const MIGRATION_029_FUNCTION_RESPONSE_HEADERS: &str =
+    include_str!("../migrations/029_function_response_headers.sql");
+const MIGRATION_030_EXECUTION_TRACES: &str = include_str!("../migrations/030_execution_traces.sql");
+const MIGRATION_031_SYNTHETIC_CHECKS: &str = include_str!("../migrations/031_synthetic_checks.sql");
+const MIGRATION_032_API_ROUTE_LIMITS: &str = include_str!("../migrations/032_api_route_limits.sql");
+const MIGRATION_033_FUNCTION_SANDBOX: &str = include_str!("../migrations/033_function_sandbox.sql");
+const MIGRATION_034_FUNCTION_USER: &str = include_str!("../migrations/034_function_user.sql");
+const MIGRATION_035_FUNCTION_AWS_EMULATION: &str =
+    include_str!("../migrations/035_function_aws_emulation.sql");
+const MIGRATION_036_FUNCTION_PERMISSIONS: &str =
+    include_str!("../migrations/036_function_permissions.sql");
+const MIGRATION_037_FUNCTION_HOOKS: &str = include_str!("../migrations/037_function_hooks.sql");
+const MIGRATION_038_API_ROUTE_TRANSFORM: &str =
+    include_str!("../migrations/038_api_route_transform.sql");
+const MIGRATION_039_FUNCTION_PACKAGE_TYPE: &str =
+    include_str!("../migrations/039_function_package_type.sql");
+const MIGRATION_040_VERSION_SNAPSHOTS: &str =
+    include_str!("../migrations/040_version_snapshots.sql");
+const MIGRATION_041_API_ROUTE_INVOCATION: &str =
+    include_str!("../migrations/041_api_route_invocation.sql");
+const MIGRATION_042_ASYNC_INVOCATIONS: &str =
+    include_str!("../migrations/042_async_invocations.sql");
+const MIGRATION_043_SECRET_EXPORTS: &str = include_str!("../migrations/043_secret_exports.sql");
+const MIGRATION_044_FUNCTION_SCHEDULES: &str =
+    include_str!("../migrations/044_function_schedules.sql");
+const MIGRATION_045_FUNCTION_URLS: &str = include_str!("../migrations/045_function_urls.sql");
+const MIGRATION_046_FUNCTION_TAGS: &str = include_str!("../migrations/046_function_tags.sql");
+const MIGRATION_047_FUNCTION_GROUPS: &str = include_str!("../migrations/047_function_groups.sql");
+const MIGRATION_048_PROVISIONED_CONCURRENCY: &str =
+    include_str!("../migrations/048_provisioned_concurrency.sql");
+const MIGRATION_049_FUNCTION_LIFECYCLE: &str =
+    include_str!("../migrations/049_function_lifecycle.sql");
+const MIGRATION_050_API_ROUTE_JWT_AUTHORIZER: &str =
+    include_str!("../migrations/050_api_route_jwt_authorizer.sql");
+const MIGRATION_051_INSTANCE_TOKEN_KEY: &str =
+    include_str!("../migrations/051_instance_token_key.sql");
+
+/// Version and name of every embedded migration, oldest first
+pub const MIGRATIONS: &[(u32, &str)] = &[
+    (1, "Initial schema"),
+    (2, "API Routes"),
+    (3, "Secrets"),
+    (4, "Function Concurrency"),
+    (5, "Execution error messages"),
+    (6, "Execution error payloads"),
+    (7, "Function hedging"),
+    (8, "Function metadata"),
+    (9, "Webhooks"),
+    (10, "Queues"),
+    (11, "S3 event sources"),
+    (12, "Change feed"),
+    (13, "Function config history"),
+    (14, "Warm pool snapshot"),
+    (15, "Function stop grace period"),
+    (16, "Invocation jobs"),
+    (17, "Oversized job results"),
+    (18, "Function timezone and locale"),
+    (19, "Function prewarm invocations"),
+    (20, "Route canaries"),
+    (21, "Per-route access logging"),
+    (22, "Route mock responses"),
+    (23, "Test events"),
+    (24, "Route traffic recording"),
+    (25, "Replication state"),
+    (26, "Function last update status"),
+    (27, "Function runtime management"),
+    (28, "Image digests"),
+    (29, "Function response headers"),
+    (30, "Execution traces"),
+    (31, "Synthetic checks"),
+    (32, "Route admission limits"),
+    (33, "Function sandbox profiles"),
+    (34, "Function container users"),
+    (35, "Function AWS emulation"),
+    (36, "Function permissions"),
+    (37, "Function hooks"),
+    (38, "Route mapping templates"),
+    (39, "Function package type"),
+    (40, "Version snapshots"),
+    (41, "Route invocation type"),
+    (42, "Async invocation queue"),
+    (43, "Secret export audit log"),
+    (44, "Function schedules"),
+    (45, "Function URLs"),
+    (46, "Function tags"),
+    (47, "Function groups"),
+    (48, "Provisioned concurrency"),
+    (49, "Function warm pool lifecycle"),
+    (50, "Route JWT authorizer"),
+    (51, "Instance token key"),
+];
+
+/// Schema version a database is at once every embedded migration ran. Recorded in the
+/// database's `user_version`, so a binary can tell a schema newer than it understands
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].0;
+
+/// Schema version recorded in the database; 0 when none was
+pub async fn schema_version(pool: &Pool<Sqlite>) -> Result<u32, sqlx::Error> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    Ok(version.max(0) as u32)
+}
+
+/// Schema version of the database and the migrations [`run_migrations`] would run
+pub async fn schema_status(pool: &Pool<Sqlite>) -> Result<SchemaStatus, sqlx::Error> {
+    let current_version = schema_version(pool).await?;
+    Ok(SchemaStatus {
+        current_version,
+        latest_version: SCHEMA_VERSION,
+        pending: MIGRATIONS
+            .iter()
+            .filter(|(version, _)| *version > current_version)
+            .map(|(version, name)| SchemaMigration {
+                version: *version,
+                name: name.to_string(),
+            })
+            .collect(),
+    })
+}
 
 /// Run all embedded migrations
 pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    // An older binary would work against tables it does not know about
+    let current = schema_version(pool).await?;
+    if current > SCHEMA_VERSION {
+        return Err(sqlx::Error::Configuration(
+            format!(
+                "the database schema is at version {current}, newer than version \
+                 {SCHEMA_VERSION} this binary understands; run a newer lambda-at-home-server"
+            )
+            .into(),
+        ));
+    }
+
     info!("Running database migrations...");
 
     // Migration 001: Initial schema
@@ -30,6 +199,330 @@ pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
         .execute(pool)
         .await?;
 
+    // Migration 005: Execution error messages
+    if !column_exists(pool, "executions", "error_message").await? {
+        info!("Running migration 005: Execution error messages");
+        sqlx::query(MIGRATION_005_EXECUTION_ERROR_MESSAGE)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 006: Execution error payloads
+    if !column_exists(pool, "executions", "error_payload").await? {
+        info!("Running migration 006: Execution error payloads");
+        sqlx::query(MIGRATION_006_EXECUTION_ERROR_PAYLOAD)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 007: Function hedging
+    info!("Running migration 007: Function hedging");
+    sqlx::query(MIGRATION_007_FUNCTION_HEDGING)
+        .execute(pool)
+        .await?;
+
+    // Migration 008: Function metadata
+    info!("Running migration 008: Function metadata");
+    sqlx::query(MIGRATION_008_FUNCTION_METADATA)
+        .execute(pool)
+        .await?;
+
+    // Migration 009: Webhooks and their delivery log
+    info!("Running migration 009: Webhooks");
+    sqlx::query(MIGRATION_009_WEBHOOKS).execute(pool).await?;
+
+    // Migration 010: Local queues and event source mappings
+    info!("Running migration 010: Queues");
+    sqlx::query(MIGRATION_010_QUEUES).execute(pool).await?;
+
+    // Migration 011: Bucket notification event sources
+    if !column_exists(pool, "event_source_mappings", "bucket").await? {
+        info!("Running migration 011: S3 event sources");
+        sqlx::query(MIGRATION_011_S3_EVENT_SOURCES)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 012: Change feed table and triggers
+    info!("Running migration 012: Change feed");
+    sqlx::query(MIGRATION_012_CHANGE_FEED).execute(pool).await?;
+
+    // Migration 013: Function configuration history
+    info!("Running migration 013: Function config history");
+    sqlx::query(MIGRATION_013_FUNCTION_CONFIG_HISTORY)
+        .execute(pool)
+        .await?;
+
+    // Migration 014: Warm pool snapshot
+    info!("Running migration 014: Warm pool snapshot");
+    sqlx::query(MIGRATION_014_WARM_POOL_SNAPSHOT)
+        .execute(pool)
+        .await?;
+
+    // Migration 015: Function stop grace period
+    info!("Running migration 015: Function stop grace period");
+    sqlx::query(MIGRATION_015_FUNCTION_STOP_GRACE)
+        .execute(pool)
+        .await?;
+
+    // Migration 016: Invocation jobs
+    info!("Running migration 016: Invocation jobs");
+    sqlx::query(MIGRATION_016_INVOCATION_JOBS)
+        .execute(pool)
+        .await?;
+
+    // Migration 017: Oversized job results
+    if !column_exists(pool, "invocation_jobs", "payload_truncated").await? {
+        info!("Running migration 017: Oversized job results");
+        sqlx::query(MIGRATION_017_JOB_PAYLOAD_TRUNCATED)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 018: Function timezone and locale
+    info!("Running migration 018: Function timezone and locale");
+    sqlx::query(MIGRATION_018_FUNCTION_LOCALE)
+        .execute(pool)
+        .await?;
+
+    // Migration 019: Function prewarm invocations
+    info!("Running migration 019: Function prewarm invocations");
+    sqlx::query(MIGRATION_019_FUNCTION_PREWARM)
+        .execute(pool)
+        .await?;
+
+    // Migration 020: Route canaries
+    if !column_exists(pool, "api_routes", "canary").await? {
+        info!("Running migration 020: Route canaries");
+        sqlx::query(MIGRATION_020_API_ROUTE_CANARY)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 021: Per-route access logging
+    if !column_exists(pool, "api_routes", "access_log").await? {
+        info!("Running migration 021: Per-route access logging");
+        sqlx::query(MIGRATION_021_API_ROUTE_ACCESS_LOG)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 022: Route mock responses
+    if !column_exists(pool, "api_routes", "mock").await? {
+        info!("Running migration 022: Route mock responses");
+        sqlx::query(MIGRATION_022_API_ROUTE_MOCK)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 023: Saved test events
+    info!("Running migration 023: Test events");
+    sqlx::query(MIGRATION_023_TEST_EVENTS).execute(pool).await?;
+
+    // Migration 024: Route traffic recording
+    if !column_exists(pool, "api_routes", "recording").await? {
+        info!("Running migration 024: Route traffic recording");
+        sqlx::query(MIGRATION_024_API_ROUTE_RECORDING)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 025: Replication state
+    info!("Running migration 025: Replication state");
+    sqlx::query(MIGRATION_025_REPLICATION_STATE)
+        .execute(pool)
+        .await?;
+
+    // Migration 026: Function last update status
+    if !column_exists(pool, "functions", "last_update_status").await? {
+        info!("Running migration 026: Function last update status");
+        sqlx::query(MIGRATION_026_FUNCTION_LAST_UPDATE_STATUS)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 027: Function runtime management
+    info!("Running migration 027: Function runtime management");
+    sqlx::query(MIGRATION_027_FUNCTION_RUNTIME_MANAGEMENT)
+        .execute(pool)
+        .await?;
+
+    // Migration 028: Image digests
+    if !column_exists(pool, "versions", "image_digest").await? {
+        info!("Running migration 028: Image digests");
+        sqlx::query(MIGRATION_028_IMAGE_DIGESTS)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 029: Function response headers
+    info!("Running migration 029: Function response headers");
+    sqlx::query(MIGRATION_029_FUNCTION_RESPONSE_HEADERS)
+        .execute(pool)
+        .await?;
+
+    // Migration 030: Execution traces
+    info!("Running migration 030: Execution traces");
+    sqlx::query(MIGRATION_030_EXECUTION_TRACES)
+        .execute(pool)
+        .await?;
+
+    // Migration 031: Synthetic checks
+    info!("Running migration 031: Synthetic checks");
+    sqlx::query(MIGRATION_031_SYNTHETIC_CHECKS)
+        .execute(pool)
+        .await?;
+
+    // Migration 032: Route admission limits
+    if !column_exists(pool, "api_routes", "limits").await? {
+        info!("Running migration 032: Route admission limits");
+        sqlx::query(MIGRATION_032_API_ROUTE_LIMITS)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 033: Function sandbox profiles
+    info!("Running migration 033: Function sandbox profiles");
+    sqlx::query(MIGRATION_033_FUNCTION_SANDBOX)
+        .execute(pool)
+        .await?;
+
+    // Migration 034: Function container users
+    info!("Running migration 034: Function container users");
+    sqlx::query(MIGRATION_034_FUNCTION_USER)
+        .execute(pool)
+        .await?;
+
+    // Migration 035: Function AWS emulation
+    info!("Running migration 035: Function AWS emulation");
+    sqlx::query(MIGRATION_035_FUNCTION_AWS_EMULATION)
+        .execute(pool)
+        .await?;
+
+    // Migration 036: Function permissions
+    info!("Running migration 036: Function permissions");
+    sqlx::query(MIGRATION_036_FUNCTION_PERMISSIONS)
+        .execute(pool)
+        .await?;
+
+    // Migration 037: Function hooks
+    info!("Running migration 037: Function hooks");
+    sqlx::query(MIGRATION_037_FUNCTION_HOOKS)
+        .execute(pool)
+        .await?;
+
+    // Migration 038: Route mapping templates
+    if !column_exists(pool, "api_routes", "transform").await? {
+        info!("Running migration 038: Route mapping templates");
+        sqlx::query(MIGRATION_038_API_ROUTE_TRANSFORM)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 039: Function package type
+    if !column_exists(pool, "functions", "package_type").await? {
+        info!("Running migration 039: Function package type");
+        sqlx::query(MIGRATION_039_FUNCTION_PACKAGE_TYPE)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 040: Version snapshots
+    if !column_exists(pool, "versions", "config").await? {
+        info!("Running migration 040: Version snapshots");
+        sqlx::query(MIGRATION_040_VERSION_SNAPSHOTS)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 041: Route invocation type
+    if !column_exists(pool, "api_routes", "invocation").await? {
+        info!("Running migration 041: Route invocation type");
+        sqlx::query(MIGRATION_041_API_ROUTE_INVOCATION)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 042: Async invocation queue
+    info!("Running migration 042: Async invocation queue");
+    sqlx::query(MIGRATION_042_ASYNC_INVOCATIONS)
+        .execute(pool)
+        .await?;
+
+    // Migration 043: Secret export audit log
+    info!("Running migration 043: Secret export audit log");
+    sqlx::query(MIGRATION_043_SECRET_EXPORTS)
+        .execute(pool)
+        .await?;
+
+    // Migration 044: Function schedules
+    info!("Running migration 044: Function schedules");
+    sqlx::query(MIGRATION_044_FUNCTION_SCHEDULES)
+        .execute(pool)
+        .await?;
+
+    // Migration 045: Function URLs
+    info!("Running migration 045: Function URLs");
+    sqlx::query(MIGRATION_045_FUNCTION_URLS)
+        .execute(pool)
+        .await?;
+
+    // Migration 046: Function tags
+    info!("Running migration 046: Function tags");
+    sqlx::query(MIGRATION_046_FUNCTION_TAGS)
+        .execute(pool)
+        .await?;
+
+    // Migration 047: Function groups
+    info!("Running migration 047: Function groups");
+    sqlx::query(MIGRATION_047_FUNCTION_GROUPS)
+        .execute(pool)
+        .await?;
+
+    // Migration 048: Provisioned concurrency
+    info!("Running migration 048: Provisioned concurrency");
+    sqlx::query(MIGRATION_048_PROVISIONED_CONCURRENCY)
+        .execute(pool)
+        .await?;
+
+    // Migration 049: Function warm pool lifecycle
+    info!("Running migration 049: Function warm pool lifecycle");
+    sqlx::query(MIGRATION_049_FUNCTION_LIFECYCLE)
+        .execute(pool)
+        .await?;
+
+    // Migration 050: Route JWT authorizer
+    if !column_exists(pool, "api_routes", "jwt_authorizer").await? {
+        info!("Running migration 050: Route JWT authorizer");
+        sqlx::query(MIGRATION_050_API_ROUTE_JWT_AUTHORIZER)
+            .execute(pool)
+            .await?;
+    }
+
+    // Migration 051: Instance token key
+    info!("Running migration 051: Instance token key");
+    sqlx::query(MIGRATION_051_INSTANCE_TOKEN_KEY)
+        .execute(pool)
+        .await?;
+
+    sqlx::query(&format!("PRAGMA user_version = {SCHEMA_VERSION}"))
+        .execute(pool)
+        .await?;
+
     info!("All migrations completed successfully");
     Ok(())
 }
+
+/// ALTER TABLE migrations are not idempotent, so they only run when the column is missing
+async fn column_exists(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    column: &str,
+) -> Result<bool, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
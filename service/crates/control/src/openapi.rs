@@ -0,0 +1,168 @@
+//! OpenAPI document of the API gateway, put together from the configured routes and the
+//! schema documents functions bundle with their code.
+//!
+//! A function documents itself with an `openapi.json` at the root of its package: an OpenAPI
+//! 3 fragment whose `paths` are relative to the route the function is mounted at (`/` is the
+//! route's own path), plus any `components` its operations refer to.
+
+use lambda_models::ApiRoute;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Schema document a function may bundle with its code
+pub const OPENAPI_FILE: &str = "openapi.json";
+
+/// Methods a route without one answers, as documented
+const ANY_METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+const OPERATION_KEYS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Build the gateway's OpenAPI 3 document from `routes` and the schema documents of their
+/// functions by name. Operations of a function without one just name it. `warnings`, and
+/// problems found here such as a component defined twice with different contents, are
+/// listed under `x-lambdah-warnings`
+pub fn gateway_document(
+    routes: &[ApiRoute],
+    documents: &HashMap<String, Value>,
+    mut warnings: Vec<String>,
+    version: &str,
+) -> Value {
+    let mut paths = Map::new();
+    let mut components: Map<String, Value> = Map::new();
+    let mut merged_components = Vec::new();
+
+    for route in routes {
+        let method = route.method.as_deref().map(str::to_ascii_lowercase);
+        let document = documents.get(&route.function_name);
+        let mut documented = false;
+        if let Some(fragment_paths) = document
+            .and_then(|d| d.get("paths"))
+            .and_then(Value::as_object)
+        {
+            for (relative, item) in fragment_paths {
+                let Some(item) = item.as_object() else {
+                    warnings.push(format!(
+                        "{}: path {relative} is not an object",
+                        route.function_name
+                    ));
+                    continue;
+                };
+                let full = join_path(&route.path, relative);
+                for (key, operation) in item {
+                    let is_operation = OPERATION_KEYS.contains(&key.as_str());
+                    if is_operation && method.as_deref().is_some_and(|m| m != key) {
+                        continue;
+                    }
+                    let mut operation = operation.clone();
+                    if is_operation {
+                        tag_operation(&mut operation, &route.function_name);
+                        documented = true;
+                    }
+                    let entry = paths
+                        .entry(full.clone())
+                        .or_insert_with(|| Value::Object(Map::new()));
+                    if let Some(entry) = entry.as_object_mut() {
+                        if entry.contains_key(key) {
+                            warnings.push(format!("{key} {full} is documented twice"));
+                        } else {
+                            entry.insert(key.clone(), operation);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(fragment_components) = document
+            .filter(|_| !merged_components.contains(&route.function_name))
+            .and_then(|d| d.get("components"))
+            .and_then(Value::as_object)
+        {
+            merged_components.push(route.function_name.clone());
+            for (kind, entries) in fragment_components {
+                let Some(entries) = entries.as_object() else {
+                    continue;
+                };
+                let target = components
+                    .entry(kind.clone())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                let Some(target) = target.as_object_mut() else {
+                    continue;
+                };
+                for (name, value) in entries {
+                    match target.get(name) {
+                        Some(existing) if existing != value => warnings.push(format!(
+                            "{}: components.{kind}.{name} differs from an earlier definition, which is kept",
+                            route.function_name
+                        )),
+                        Some(_) => {}
+                        None => {
+                            target.insert(name.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        if !documented {
+            let entry = paths
+                .entry(route.path.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            let methods: Vec<&str> = match &method {
+                Some(method) => vec![method.as_str()],
+                None => ANY_METHODS.to_vec(),
+            };
+            if let Some(entry) = entry.as_object_mut() {
+                for method in methods {
+                    entry
+                        .entry(method.to_string())
+                        .or_insert_with(|| undocumented_operation(&route.function_name));
+                }
+            }
+        }
+    }
+
+    let mut document = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Lambda@Home API gateway",
+            "version": version,
+        },
+        "paths": paths,
+    });
+    if !components.is_empty() {
+        document["components"] = Value::Object(components);
+    }
+    if !warnings.is_empty() {
+        document["x-lambdah-warnings"] = json!(warnings);
+    }
+    document
+}
+
+/// `relative` under the route path `base`; `/` is the route itself
+fn join_path(base: &str, relative: &str) -> String {
+    let relative = relative.trim_start_matches('/');
+    if relative.is_empty() {
+        return base.to_string();
+    }
+    format!("{}/{relative}", base.trim_end_matches('/'))
+}
+
+/// Group an operation under its function unless it names its own tags
+fn tag_operation(operation: &mut Value, function_name: &str) {
+    if let Some(operation) = operation.as_object_mut() {
+        operation
+            .entry("tags")
+            .or_insert_with(|| json!([function_name]));
+    }
+}
+
+fn undocumented_operation(function_name: &str) -> Value {
+    json!({
+        "summary": format!("Invoke {function_name}"),
+        "tags": [function_name],
+        "responses": {
+            "default": { "description": format!("Response of {function_name}") },
+        },
+    })
+}
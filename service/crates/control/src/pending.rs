@@ -1,8 +1,28 @@
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use std::sync::Arc;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{info, warn};
 
+/// errorType reported when an invocation is lost to a container failure
+pub const INFRASTRUCTURE_ERROR_TYPE: &str = "Runtime.ContainerFailure";
+/// errorType reported when an invocation outruns the function's timeout
+pub const TIMED_OUT_ERROR_TYPE: &str = "TaskTimedOut";
+
+/// Chunks a streamed response may get ahead of the caller reading them before the
+/// runtime's upload waits
+const STREAM_BUFFER: usize = 16;
+
+/// What a runtime streaming its response sends to the caller, in order
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamPart {
+    /// The runtime started streaming, with the content type it declared
+    Start {
+        content_type: Option<String>,
+    },
+    Chunk(Vec<u8>),
+}
+
 #[derive(Clone, Debug)]
 pub struct InvocationResult {
     pub ok: bool,
@@ -10,6 +30,10 @@ pub struct InvocationResult {
     pub log_tail_b64: Option<String>,
     pub executed_version: Option<String>,
     pub function_error: Option<String>, // "Handled" | "Unhandled"
+    /// Set when the container failed underneath the invocation (not a function code error)
+    pub infrastructure_failure: bool,
+    /// Set when the invocation ran past its deadline and its container was killed
+    pub timed_out: bool,
 }
 
 impl InvocationResult {
@@ -20,6 +44,8 @@ impl InvocationResult {
             log_tail_b64: None,
             executed_version: None,
             function_error: None,
+            infrastructure_failure: false,
+            timed_out: false,
         }
     }
 
@@ -30,6 +56,42 @@ impl InvocationResult {
             log_tail_b64: None,
             executed_version: None,
             function_error: Some(kind.to_string()),
+            infrastructure_failure: false,
+            timed_out: false,
+        }
+    }
+
+    /// The container running the invocation went away before producing a result
+    pub fn infrastructure(reason: &str) -> Self {
+        let payload = serde_json::json!({
+            "errorMessage": reason,
+            "errorType": INFRASTRUCTURE_ERROR_TYPE
+        });
+        Self {
+            ok: false,
+            payload: serde_json::to_vec(&payload).unwrap_or_default(),
+            log_tail_b64: None,
+            executed_version: None,
+            function_error: Some("Unhandled".to_string()),
+            infrastructure_failure: true,
+            timed_out: false,
+        }
+    }
+
+    /// The invocation outran the function's timeout of `timeout_ms`
+    pub fn timed_out(timeout_ms: u64) -> Self {
+        let payload = serde_json::json!({
+            "errorMessage": format!("Task timed out after {} seconds", timeout_ms / 1000),
+            "errorType": TIMED_OUT_ERROR_TYPE
+        });
+        Self {
+            ok: false,
+            payload: serde_json::to_vec(&payload).unwrap_or_default(),
+            log_tail_b64: None,
+            executed_version: None,
+            function_error: Some("Unhandled".to_string()),
+            infrastructure_failure: false,
+            timed_out: true,
         }
     }
 }
@@ -37,6 +99,14 @@ impl InvocationResult {
 #[derive(Clone)]
 pub struct Pending {
     inner: Arc<DashMap<String, oneshot::Sender<InvocationResult>>>,
+    // req_id -> container_id of the container that picked the request up
+    assignments: Arc<DashMap<String, String>>,
+    // req_id -> caller reading the response as the runtime streams it
+    streams: Arc<DashMap<String, mpsc::Sender<StreamPart>>>,
+    // req_id -> (deadline_ms, timeout_ms) of a request a runtime picked up
+    deadlines: Arc<DashMap<String, (i64, u64)>>,
+    // req_id -> when it timed out, for requests whose late results are refused
+    discarded: Arc<DashMap<String, i64>>,
 }
 
 impl Default for Pending {
@@ -49,6 +119,10 @@ impl Pending {
     pub fn new() -> Self {
         Self {
             inner: Arc::new(DashMap::new()),
+            assignments: Arc::new(DashMap::new()),
+            streams: Arc::new(DashMap::new()),
+            deadlines: Arc::new(DashMap::new()),
+            discarded: Arc::new(DashMap::new()),
         }
     }
 
@@ -61,10 +135,39 @@ impl Pending {
         rx
     }
 
+    /// Have the response of a request streamed to the returned receiver as the runtime posts
+    /// it. The stream ends when the request completes; its result still comes through
+    /// [`Self::register`]'s receiver
+    pub fn register_stream(&self, req_id: String) -> mpsc::Receiver<StreamPart> {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        self.streams.insert(req_id, tx);
+        rx
+    }
+
+    /// Where a runtime streaming the response of `req_id` sends it; `None` when the caller
+    /// did not ask for a stream or the request already completed
+    pub fn stream(&self, req_id: &str) -> Option<mpsc::Sender<StreamPart>> {
+        self.streams.get(req_id).map(|tx| tx.clone())
+    }
+
+    /// Stop streaming the response of `req_id`, closing the caller's receiver
+    pub fn end_stream(&self, req_id: &str) {
+        self.streams.remove(req_id);
+    }
+
+    /// Whether the caller of `req_id` reads its response as a stream
+    pub fn is_streaming(&self, req_id: &str) -> bool {
+        self.streams.contains_key(req_id)
+    }
+
     /// Complete a pending invocation with a result
     /// Returns true if the request was found and completed, false if not found (late/duplicate)
     pub fn complete(&self, req_id: &str, res: InvocationResult) -> bool {
-        if let Some((_, tx)) = self.inner.remove(req_id) {
+        let waiter = self.inner.remove(req_id);
+        self.assignments.remove(req_id);
+        self.streams.remove(req_id);
+        self.deadlines.remove(req_id);
+        if let Some((_, tx)) = waiter {
             let _ = tx.send(res);
             info!("Completed pending invocation: {}", req_id);
             true
@@ -79,4 +182,117 @@ impl Pending {
     pub fn fail_if_waiting(&self, req_id: &str, kind: &str, body: Vec<u8>) -> bool {
         self.complete(req_id, InvocationResult::err(kind, body))
     }
+
+    /// Drop a waiter without delivering a result; a late response is then treated as a duplicate
+    pub fn cancel(&self, req_id: &str) -> bool {
+        let cancelled = self.inner.remove(req_id).is_some();
+        self.assignments.remove(req_id);
+        self.streams.remove(req_id);
+        self.deadlines.remove(req_id);
+        cancelled
+    }
+
+    /// Fail a request that ran out of time with `res` and discard it: the result its runtime
+    /// posts later is refused, and a runtime not yet holding it never gets it. Its deadline
+    /// and container are kept until [`Self::clear_deadline`], so the container running it is
+    /// still dealt with when the caller gave up first. Returns false, discarding nothing,
+    /// when the request had already completed or been discarded
+    pub fn discard(&self, req_id: &str, res: InvocationResult, now_ms: i64) -> bool {
+        // Recorded before the waiter goes so a result racing in is refused, not taken as late
+        match self.discarded.entry(req_id.to_string()) {
+            Entry::Occupied(_) => return false,
+            Entry::Vacant(entry) => {
+                entry.insert(now_ms);
+            }
+        }
+        let Some((_, tx)) = self.inner.remove(req_id) else {
+            self.discarded.remove(req_id);
+            warn!("Attempted to discard unknown request: {}", req_id);
+            return false;
+        };
+        self.streams.remove(req_id);
+        if !self.deadlines.contains_key(req_id) {
+            // No runtime picked it up, so no container is left to deal with
+            self.assignments.remove(req_id);
+        }
+        let _ = tx.send(res);
+        info!("Discarded pending invocation: {}", req_id);
+        true
+    }
+
+    /// Whether `req_id` timed out and was discarded
+    pub fn is_discarded(&self, req_id: &str) -> bool {
+        self.discarded.contains_key(req_id)
+    }
+
+    /// Forget requests discarded before `before_ms`; results still posted for them are
+    /// then refused as unknown
+    pub fn forget_discarded(&self, before_ms: i64) {
+        self.discarded
+            .retain(|_, discarded_at| *discarded_at >= before_ms);
+    }
+
+    /// Remember which container is running a request so its death can fail the waiter
+    pub fn assign(&self, req_id: &str, container_id: String) {
+        // Holding the waiter's entry keeps a concurrent complete from clearing assignments
+        // before this one is recorded, which would leave it behind
+        if let Some(_waiter) = self.inner.get(req_id) {
+            self.assignments.insert(req_id.to_string(), container_id);
+        }
+    }
+
+    /// Record when a request a runtime picked up must have finished by, so an overrun can
+    /// be cut short
+    pub fn set_deadline(&self, req_id: &str, deadline_ms: i64, timeout_ms: u64) {
+        if let Some(_waiter) = self.inner.get(req_id) {
+            self.deadlines
+                .insert(req_id.to_string(), (deadline_ms, timeout_ms));
+        }
+    }
+
+    /// Stop tracking the deadline of `req_id` and the container running it, once the
+    /// overrun was dealt with
+    pub fn clear_deadline(&self, req_id: &str) {
+        self.deadlines.remove(req_id);
+        self.assignments.remove(req_id);
+    }
+
+    /// Requests past their deadline at `now_ms`: each with the function's timeout and the
+    /// container running it, when known
+    pub fn overdue(&self, now_ms: i64) -> Vec<(String, u64, Option<String>)> {
+        self.deadlines
+            .iter()
+            .filter(|entry| entry.value().0 <= now_ms)
+            .map(|entry| {
+                let req_id = entry.key().clone();
+                let container_id = self.assignments.get(&req_id).map(|c| c.value().clone());
+                (req_id, entry.value().1, container_id)
+            })
+            .collect()
+    }
+
+    /// Requests still waiting for a result
+    pub fn waiting(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Requests recorded as running on a container
+    pub fn assigned(&self) -> usize {
+        self.assignments.len()
+    }
+
+    /// Fail every request still running on `container_id` with an infrastructure error.
+    /// Returns the number of waiters that were notified.
+    pub fn fail_container(&self, container_id: &str, reason: &str) -> usize {
+        let req_ids: Vec<String> = self
+            .assignments
+            .iter()
+            .filter(|entry| entry.value() == container_id)
+            .map(|entry| entry.key().clone())
+            .collect();
+        req_ids
+            .iter()
+            .filter(|req_id| self.complete(req_id, InvocationResult::infrastructure(reason)))
+            .count()
+    }
 }
@@ -0,0 +1,58 @@
+use crate::queues::FnKey;
+use crate::warm_pool::WarmContainer;
+use std::collections::HashMap;
+
+/// Outcome of checking a new container against the host memory budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// Fits as is
+    Fits,
+    /// Fits once these idle containers are stopped
+    Evict(Vec<String>),
+    /// Does not fit even after evicting every idle container
+    Refuse { available_mb: u64 },
+}
+
+/// Decide whether `requested_mb` fits under `budget_mb`.
+/// Eviction prefers idle containers of the most over-provisioned functions (most idle
+/// containers), then the least recently used.
+pub fn plan_placement(
+    budget_mb: Option<u64>,
+    committed_mb: u64,
+    requested_mb: u64,
+    idle: &[(FnKey, WarmContainer)],
+) -> Placement {
+    let Some(budget_mb) = budget_mb else {
+        return Placement::Fits;
+    };
+    let available_mb = budget_mb.saturating_sub(committed_mb);
+    if requested_mb <= available_mb {
+        return Placement::Fits;
+    }
+
+    let mut idle_per_key: HashMap<&FnKey, usize> = HashMap::new();
+    for (key, _) in idle {
+        *idle_per_key.entry(key).or_default() += 1;
+    }
+    let mut candidates: Vec<&(FnKey, WarmContainer)> = idle.iter().collect();
+    candidates.sort_by(|(a_key, a), (b_key, b)| {
+        idle_per_key[b_key]
+            .cmp(&idle_per_key[a_key])
+            .then(a.last_used.cmp(&b.last_used))
+    });
+
+    let mut freed_mb = 0;
+    let mut evict = Vec::new();
+    for (_, container) in candidates {
+        if requested_mb <= available_mb + freed_mb {
+            break;
+        }
+        freed_mb += container.memory_mb;
+        evict.push(container.container_id.clone());
+    }
+    if requested_mb <= available_mb + freed_mb {
+        Placement::Evict(evict)
+    } else {
+        Placement::Refuse { available_mb }
+    }
+}
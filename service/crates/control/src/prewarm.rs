@@ -0,0 +1,76 @@
+use crate::work_item::WorkItem;
+use dashmap::DashMap;
+use lambda_models::LambdaError;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// `source` of the prewarm event, for handlers to tell it apart from real traffic
+pub const PREWARM_EVENT_SOURCE: &str = "lambdah.prewarm";
+
+pub fn prewarm_event() -> serde_json::Value {
+    serde_json::json!({
+        "source": PREWARM_EVENT_SOURCE,
+        "detail-type": "Prewarm",
+    })
+}
+
+struct Slot {
+    /// Taken by the instance's first poll
+    item: Option<WorkItem>,
+    /// `Some(succeeded)` once the prewarm invocation finished
+    outcome: watch::Receiver<Option<bool>>,
+}
+
+/// Prewarm invocations, each reserved for the container instance it was created for. The
+/// instance gets its prewarm event on its first poll, and later polls only reach the shared
+/// queue once the prewarm succeeded.
+#[derive(Clone, Default)]
+pub struct PrewarmRequests {
+    slots: Arc<DashMap<String, Slot>>,
+}
+
+impl PrewarmRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve `item` for `instance_id`; report the outcome with [`Self::finish`]
+    pub fn hold(&self, instance_id: &str, item: WorkItem) -> watch::Sender<Option<bool>> {
+        let (outcome_tx, outcome) = watch::channel(None);
+        self.slots.insert(
+            instance_id.to_string(),
+            Slot {
+                item: Some(item),
+                outcome,
+            },
+        );
+        outcome_tx
+    }
+
+    /// The prewarm invocation for a poll from `instance_id`, or `None` when the instance
+    /// may take queued work. Waits while its prewarm invocation is still running.
+    pub async fn next_for(&self, instance_id: &str) -> Result<Option<WorkItem>, LambdaError> {
+        let mut outcome = match self.slots.get_mut(instance_id) {
+            None => return Ok(None),
+            Some(mut slot) => match slot.item.take() {
+                Some(item) => return Ok(Some(item)),
+                None => slot.outcome.clone(),
+            },
+        };
+        match outcome.wait_for(Option::is_some).await.map(|o| *o) {
+            Ok(Some(true)) => Ok(None),
+            _ => Err(LambdaError::ContainerInitError {
+                reason: format!("Prewarm invocation failed for instance {instance_id}"),
+            }),
+        }
+    }
+
+    pub fn finish(&self, instance_id: &str, outcome: watch::Sender<Option<bool>>, succeeded: bool) {
+        self.slots.remove(instance_id);
+        let _ = outcome.send(Some(succeeded));
+    }
+
+    pub fn is_held(&self, instance_id: &str) -> bool {
+        self.slots.contains_key(instance_id)
+    }
+}
@@ -3,8 +3,8 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::Notify;
 
-use crate::work_item::WorkItem;
-use lambda_models::LambdaError;
+use crate::work_item::{FunctionMeta, WorkItem};
+use lambda_models::{Function, LambdaError};
 use tracing::{debug, info};
 
 use sha2::{Digest, Sha256};
@@ -19,19 +19,27 @@ pub struct FnKey {
 
 impl FnKey {
     pub fn from_work_item(w: &WorkItem) -> Self {
+        Self::from_meta(&w.function)
+    }
+
+    /// The key invocations of `function` are queued under
+    pub fn for_function(function: &Function) -> Self {
+        Self::from_meta(&FunctionMeta::from(function.clone()))
+    }
+
+    fn from_meta(function: &FunctionMeta) -> Self {
         // Stable hash of environment: serialize Option<HashMap<..>> deterministically
         let env_value =
-            serde_json::to_value(&w.function.environment).unwrap_or(serde_json::Value::Null);
+            serde_json::to_value(&function.environment).unwrap_or(serde_json::Value::Null);
         let stable_bytes = serde_json::to_vec(&env_value).unwrap_or_default();
         let mut hasher = Sha256::new();
         hasher.update(&stable_bytes);
         let env_hash = format!("{:x}", hasher.finalize());
 
         Self {
-            function_name: w.function.function_name.clone(),
-            runtime: w.function.runtime.clone(),
-            version: w
-                .function
+            function_name: function.function_name.clone(),
+            runtime: function.runtime.clone(),
+            version: function
                 .version
                 .clone()
                 .unwrap_or_else(|| "LATEST".to_string()),
@@ -194,4 +202,15 @@ impl Queues {
             None
         }
     }
+
+    /// Drop a still-queued work item (e.g. the losing copy of a hedged invoke)
+    pub fn remove(&self, key: &FnKey, request_id: &str) -> bool {
+        if let Some(mut entry) = self.inner.get_mut(key) {
+            let before = entry.queue.len();
+            entry.queue.retain(|w| w.request_id != request_id);
+            entry.queue.len() < before
+        } else {
+            false
+        }
+    }
 }
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use lambda_models::{LambdaError, DEFAULT_NAMESPACE};
+
+/// Refuse adding `requested` to `used` when that goes over `limit`
+pub fn check_quota(
+    quota: &str,
+    used: u64,
+    requested: u64,
+    limit: Option<u64>,
+) -> Result<(), LambdaError> {
+    match limit {
+        Some(limit) if used.saturating_add(requested) > limit => Err(LambdaError::QuotaExceeded {
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            quota: quota.to_string(),
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Midnight UTC of the day `now` is in; daily quotas count from there
+pub fn start_of_day(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|midnight| midnight.and_utc())
+        .unwrap_or(now)
+}
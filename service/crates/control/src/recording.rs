@@ -0,0 +1,94 @@
+use chrono::Utc;
+use dashmap::DashMap;
+use lambda_models::RouteRecording;
+use serde_json::Value;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Replacement for redacted values in recorded events
+pub const REDACTED: &str = "[REDACTED]";
+pub const MAX_RECORDING_PER_MINUTE: u32 = 600;
+pub const MAX_RECORDED_EVENTS: u32 = 1000;
+pub const MAX_TEST_EVENT_NAME_LEN: usize = 128;
+
+/// Limits how many requests of each route are recorded per clock minute
+#[derive(Clone, Default)]
+pub struct RecordingSampler {
+    // route -> (minute, requests recorded in it)
+    windows: Arc<DashMap<Uuid, (i64, u32)>>,
+}
+
+impl RecordingSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether one more request of the route may be recorded this minute
+    pub fn try_acquire(&self, route_id: Uuid, per_minute: u32) -> bool {
+        let minute = Utc::now().timestamp().div_euclid(60);
+        let mut window = self.windows.entry(route_id).or_insert((minute, 0));
+        if window.0 != minute {
+            *window = (minute, 0);
+        }
+        if window.1 >= per_minute {
+            return false;
+        }
+        window.1 += 1;
+        true
+    }
+
+    pub fn remove_route(&self, route_id: Uuid) {
+        self.windows.remove(&route_id);
+    }
+}
+
+/// Apply the recording's redaction rules to an API Gateway proxy event
+pub fn redact_event(recording: &RouteRecording, event: &mut Value) {
+    if let Some(headers) = event.get_mut("headers").and_then(Value::as_object_mut) {
+        for (name, value) in headers.iter_mut() {
+            if recording
+                .redact_headers
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(name))
+            {
+                *value = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    if let Some(query) = event
+        .get_mut("queryStringParameters")
+        .and_then(Value::as_object_mut)
+    {
+        for (name, value) in query.iter_mut() {
+            if recording.redact_query.contains(name) {
+                *value = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    if recording.redact_body_fields.is_empty() {
+        return;
+    }
+    // Only JSON bodies can be redacted field by field
+    if let Some(Value::String(body)) = event.get_mut("body") {
+        if let Ok(mut parsed) = serde_json::from_str::<Value>(body) {
+            redact_fields(&mut parsed, &recording.redact_body_fields);
+            *body = parsed.to_string();
+        }
+    }
+}
+
+fn redact_fields(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if fields.contains(key) {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(v, fields);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| redact_fields(v, fields)),
+        _ => {}
+    }
+}
@@ -1,33 +1,141 @@
+use crate::access_log::AccessLog;
+use crate::async_invocations::{self, AsyncInvocations, QueuedEvent};
 use crate::autoscaler::Autoscaler;
+use crate::builds::{BuildQueue, FailedBuilds};
 use crate::cache::FunctionCache;
+use crate::canary::RouteMetricsTracker;
+use crate::change_feed::ChangeFeed;
 use crate::concurrency::ConcurrencyManager;
+use crate::config_history::{insert_revision, row_to_config_revision};
 use crate::container_monitor::ContainerMonitor;
+use crate::cost::{self, RecordedUsage, DEFAULT_COST_WINDOW};
+use crate::dashboard::{
+    build_alerts, count_states, invocation_totals, occupancy, DASHBOARD_WINDOW,
+    RECENT_DEPLOYMENTS_LIMIT,
+};
+use crate::disk::DiskMonitor;
+use crate::doctor::{self, probe_command, PROBE_IMAGE, PROBE_TIMEOUT_MS};
+use crate::error_summary::{group_errors, FailedExecution};
+use crate::event_sources::{validate_batch_size, EventSourcePoller, DEFAULT_BATCH_SIZE};
+use crate::exec_sessions::{ExecAudit, ExecSessions};
 use crate::execution_tracker::ExecutionTracker;
+use crate::features::{parse_feature_flag, FeatureFlags};
+use crate::function_urls;
+use crate::hedging::{hedge_delay, validate_hedging_config, LatencyTracker};
+use crate::hooks::{self, HookCaller};
+use crate::insights::{self, ThrottleTracker, WindowFigures, MAX_INSIGHTS_WINDOW};
+use crate::instance_tokens::{InstanceTokens, INSTANCE_TOKEN_ENV};
+use crate::integrity::{dangling_route_references, mapping_reference, route_references};
+use crate::invocation_traces::InvocationTraces;
+use crate::jobs::JobStore;
+use crate::jwt_authorizer::JwksCache;
+use crate::listing::{
+    after_marker_clause, group_clause, order_by_clause, sort_expression, tags_clause, ListMarker,
+    DEFAULT_LIST_MAX_ITEMS, MAX_LIST_MAX_ITEMS,
+};
+use crate::maintenance::DatabaseMaintenance;
 use crate::migrations;
-use crate::pending::Pending;
+use crate::openapi;
+use crate::pending::{InvocationResult, Pending};
+use crate::placement::{plan_placement, Placement};
+use crate::prewarm::{prewarm_event, PrewarmRequests};
 use crate::queues::Queues;
+use crate::quotas::{check_quota, start_of_day};
+use crate::recording::{
+    redact_event, RecordingSampler, MAX_RECORDED_EVENTS, MAX_RECORDING_PER_MINUTE,
+    MAX_TEST_EVENT_NAME_LEN,
+};
+use crate::replication::{
+    function_fingerprint, plan_sync, route_fingerprint, route_label, PeerClient, Replicator,
+    SyncAction, SyncedItem,
+};
+use crate::response_headers::shape_response;
+use crate::rollouts::{changed_container_settings, RolloutTask, Rollouts};
+use crate::route_limits::{validate_route_limits, RouteGates, RoutePermit};
+use crate::s3_notifications::{normalize_key_filter, validate_s3_source};
 use crate::scheduler::{run_dispatcher, Scheduler};
-use crate::warm_pool::WarmPool;
+use crate::schedules::{ScheduleExpression, SCHEDULE_TICK};
+use crate::search::{
+    like_pattern, match_position_order, rank_hits, MAX_SEARCH_LIMIT, RECENT_EXECUTIONS_WINDOW,
+};
+use crate::secrets;
+use crate::sqs::QueueService;
+use crate::static_functions::{
+    code_sha256, load_static_code, static_config_update, static_create_request,
+    StaticFunctionOutcome, STATIC_FUNCTION_ACTOR,
+};
+use crate::sync_invoke::{run_bounded, BoundedInvoke, PendingInvoke, ResponseStream};
+use crate::synthetics::{
+    check_due, evaluate_run, summarize_runs, validate_synthetic_check,
+    CHECK_TICK as SYNTHETIC_CHECK_TICK, RUN_RETENTION,
+};
+use crate::system_info;
+use crate::transforms::{self, validate_template};
+use crate::validation;
+use crate::versions::{alias_version, validate_routing, Qualifier};
+use crate::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use crate::warm_pool_snapshot::{restore_action, RestoreAction, WarmPoolSnapshot};
+use crate::warmup_stats::{StartKind, WarmupTracker};
+use crate::webhooks::{generate_secret, row_to_delivery, validate_webhook_secret, WebhookNotifier};
 use base64;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use lambda_models::{
-    Alias, ApiRoute, CacheStats, CacheTypeStats, ConcurrencyConfig, CreateAliasRequest,
-    CreateApiRouteRequest, CreateFunctionRequest, DockerStats, Function, FunctionError,
-    FunctionState, InitError, InvokeRequest, InvokeResponse, LambdaError, ListAliasesResponse,
-    ListApiRoutesResponse, ListFunctionsResponse, ListVersionsResponse, PublishVersionRequest,
-    RoutingConfig, RuntimeError, RuntimeInvocation, RuntimeResponse, UpdateAliasRequest,
-    UpdateFunctionCodeRequest, UpdateFunctionConfigurationRequest, Version,
+    bucket_name_from_arn, function_name_from_arn, queue_name_from_arn, AddPermissionRequest, Alias,
+    ApiRoute, AwsEmulation, BackendInfo, BatchFunctionsRequest, BatchFunctionsResponse,
+    BatchItemResult, BatchOperation, Build, BuildPriority, CacheStats, CacheTypeStats, CanaryMatch,
+    ConcurrencyConfig, ConcurrencyOverview, ConcurrencyUsage, ConfigChangeSource,
+    ConfigHistoryResponse, ConsistencyReport, CostEstimate, CostEstimateRequest,
+    CreateAliasRequest, CreateApiRouteRequest, CreateEventSourceMappingRequest,
+    CreateFunctionGroupRequest, CreateFunctionRequest, CreateTestEventRequest,
+    CreateWebhookRequest, CreateWebhookResponse, Dashboard, DatabaseStatus, Deployment,
+    DeploymentKind, DiskPressure, DiskStatus, DockerApiStatus, DockerBreakerState, DockerStats,
+    DoctorReport, EnvironmentValue, EnvironmentVariable, ErrorSummary, EventSourceMapping,
+    ExecSession, ExecutionErrorDetail, ExecutionRecord, ExportSecretsRequest,
+    ExportSecretsResponse, ExportedSecret, FeatureFlag, FeatureFlagStatus, Function,
+    FunctionAwsEmulation, FunctionCode, FunctionCodeLocation, FunctionConcurrency,
+    FunctionConfigSnapshot, FunctionConfiguration, FunctionEnvironment, FunctionError,
+    FunctionGroup, FunctionGroupMembership, FunctionHooks, FunctionInsights,
+    FunctionInvocationTotals, FunctionLifecycle, FunctionMetadata, FunctionPolicy,
+    FunctionReference, FunctionResponseType, FunctionSandbox, FunctionSelector, FunctionSortKey,
+    FunctionState, FunctionUrlConfig, FunctionUser, FunctionWarmState, GetFunctionResponse,
+    GroupFunctionMetrics, GroupMetrics, HedgingConfig, HookEvent, HookInvocationResult, HookPhase,
+    HookTarget, IdentityConfig, ImportSecretsRequest, ImportSecretsResponse, InitError,
+    InstanceHealth, InvocationHook, InvocationTrace, InvokeRequest, InvokeResponse, Job,
+    LambdaError, LastUpdateStatus, ListAliasesResponse, ListApiRoutesResponse, ListBuildsResponse,
+    ListEventSourceMappingsResponse, ListFeatureFlagsResponse, ListFunctionGroupsResponse,
+    ListFunctionsQuery, ListFunctionsResponse, ListTagsResponse, ListTestEventsResponse,
+    ListVersionsResponse, ListWebhookDeliveriesResponse, ListWebhooksResponse, LocaleConfig,
+    NamespaceUsage, PackageType, PatchEnvironmentRequest, PermissionStatement, PrewarmConfig,
+    ProvisionedConcurrencyConfig, ProvisionedConcurrencyStatus, PublishVersionRequest,
+    PutProvisionedConcurrencyConfigRequest, QuotaUsage, ReferenceKind, ReplicatedKind,
+    ReplicationItem, ReplicationState, ReplicationStatus, ResponseHeadersConfig, Rollout,
+    RolloutStatus, RouteCanary, RouteInvocation, RouteJwtAuthorizer, RouteLimits, RouteMetrics,
+    RouteMock, RouteRecording, RouteSaturation, RouteTarget, RouteTransform, RoutingConfig,
+    RuntimeApiSettings, RuntimeApiStatus, RuntimeError, RuntimeInfo, RuntimeInvocation,
+    RuntimeManagement, RuntimeManagementConfig, RuntimeResponse, S3KeyFilter, Schedule,
+    ScheduleRequest, SchemaStatus, SearchHit, SearchResponse, SecretConflictMode,
+    SecretExportAuditEntry, StaticFunctionConfig, StopGraceConfig, SyntheticCheck,
+    SyntheticCheckRun, SyntheticCheckStats, SystemInfo, SystemLimits, TestEvent, TestEventSource,
+    TimeoutAction, UpdateAliasRequest, UpdateEventSourceMappingRequest, UpdateFunctionCodeRequest,
+    UpdateFunctionConfigurationRequest, UpdateFunctionGroupRequest, UpdateRuntimeOn,
+    ValidationProblem, Version, WarmupStats, Webhook, WebhookEventType, ANY_FUNCTION_PRINCIPAL,
+    DEFAULT_NAMESPACE, EXPORT_VALUES_CONFIRMATION, INVOKE_FUNCTION_ACTION, LATEST_VERSION,
+    SECRET_REF_PREFIX,
 };
 use sqlx::{Row, SqlitePool};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use uuid::Uuid;
 
 use crate::work_item::WorkItem;
 // No need for FnKey import - using function names directly
-use std::sync::Arc;
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::sync::Mutex;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
+
+/// How long the results of timed out invocations are refused as discarded, rather than as
+/// results of unknown invocations
+const DISCARDED_RETENTION_MS: i64 = 60 * 60 * 1000;
 
 pub struct ControlPlane {
     pool: SqlitePool,
@@ -38,7 +146,35 @@ pub struct ControlPlane {
     config: lambda_models::Config,
     cache: Arc<FunctionCache>,
     execution_tracker: ExecutionTracker,
+    warmup_tracker: WarmupTracker,
+    latency_tracker: LatencyTracker,
+    // Serializes memory-budget checks with the container starts they admit
+    placement_lock: Arc<tokio::sync::Mutex<()>>,
+    disk_monitor: DiskMonitor,
     functions_being_deleted: Arc<Mutex<HashSet<String>>>,
+    webhooks: WebhookNotifier,
+    sqs: QueueService,
+    change_feed: ChangeFeed,
+    warm_pool_snapshot: WarmPoolSnapshot,
+    jobs: JobStore,
+    async_invocations: AsyncInvocations,
+    maintenance: DatabaseMaintenance,
+    prewarm: PrewarmRequests,
+    route_metrics: RouteMetricsTracker,
+    route_gates: RouteGates,
+    access_log: AccessLog,
+    exec_sessions: ExecSessions,
+    invocation_traces: InvocationTraces,
+    recording_sampler: RecordingSampler,
+    replicator: Replicator,
+    builds: BuildQueue,
+    failed_builds: FailedBuilds,
+    rollouts: Rollouts,
+    hooks: HookCaller,
+    throttles: ThrottleTracker,
+    features: FeatureFlags,
+    jwks: JwksCache,
+    instance_tokens: InstanceTokens,
 }
 
 impl ControlPlane {
@@ -47,6 +183,15 @@ impl ControlPlane {
         invoker: Arc<lambda_invoker::Invoker>,
         config: lambda_models::Config,
     ) -> Result<Self, LambdaError> {
+        config.identity.validate()?;
+        config.sync_invoke.validate()?;
+        config.aws_emulation.validate()?;
+        config.aws_pricing.validate()?;
+        config.docker.runtime_api_settings().validate()?;
+        config.docker.retry.validate()?;
+        config.async_invoke.validate()?;
+        config.trusted_headers.validate()?;
+
         // Run embedded migrations
         migrations::run_migrations(&pool)
             .await
@@ -54,6 +199,7 @@ impl ControlPlane {
                 reason: e.to_string(),
             })?;
 
+        let instance_tokens = InstanceTokens::load(&pool).await?;
         let (scheduler, rx) = Scheduler::new();
         let warm_pool = Arc::new(WarmPool::new());
         let concurrency_manager = Arc::new(ConcurrencyManager::new());
@@ -70,6 +216,38 @@ impl ControlPlane {
 
         // Spawn autoscaler loop
         let execution_tracker = ExecutionTracker::new(Arc::new(pool.clone()));
+        let warmup_tracker = WarmupTracker::new();
+        let latency_tracker = LatencyTracker::new();
+        let placement_lock = Arc::new(tokio::sync::Mutex::new(()));
+        let disk_monitor = DiskMonitor::new(config.disk.clone(), config.data.dir.clone());
+        let webhooks = WebhookNotifier::new(pool.clone(), config.webhooks.clone());
+        let sqs = QueueService::new(pool.clone());
+        let change_feed = ChangeFeed::new(pool.clone(), config.change_feed.clone());
+        let warm_pool_snapshot = WarmPoolSnapshot::new(pool.clone());
+        let jobs = JobStore::new(pool.clone(), config.jobs.clone());
+        let async_invocations = AsyncInvocations::new(pool.clone());
+        let maintenance = DatabaseMaintenance::new(pool.clone(), config.maintenance.clone());
+        let features = FeatureFlags::new(&config.features);
+        let jwks = JwksCache::new();
+        let prewarm = PrewarmRequests::new();
+        let route_metrics = RouteMetricsTracker::new();
+        let route_gates = RouteGates::new();
+        let access_log = AccessLog::new(&config.access_log);
+        let exec_sessions = ExecSessions::new(&config.exec);
+        let invocation_traces = InvocationTraces::new(pool.clone());
+        let recording_sampler = RecordingSampler::new();
+        let replicator = Replicator::new(&config.replication);
+        let builds = BuildQueue::new(config.builds.max_concurrent);
+        let failed_builds = FailedBuilds::new(std::time::Duration::from_secs(
+            config.builds.failed_build_backoff_secs,
+        ));
+        let (rollouts, rollout_tasks) = Rollouts::new();
+        if let Err(e) = jobs.fail_interrupted().await {
+            warn!("Failed to fail interrupted jobs: {}", e);
+        }
+        if let Err(e) = async_invocations.release_claimed().await {
+            warn!("Failed to requeue interrupted Event invocations: {}", e);
+        }
         let control_ref = Arc::new(Self {
             pool: pool.clone(),
             scheduler: Arc::new(scheduler.clone()),
@@ -79,13 +257,140 @@ impl ControlPlane {
             config: config.clone(),
             cache: cache.clone(),
             execution_tracker: execution_tracker.clone(),
+            warmup_tracker: warmup_tracker.clone(),
+            latency_tracker: latency_tracker.clone(),
+            placement_lock: placement_lock.clone(),
+            disk_monitor: disk_monitor.clone(),
             functions_being_deleted: Arc::new(Mutex::new(HashSet::new())),
+            webhooks: webhooks.clone(),
+            sqs: sqs.clone(),
+            change_feed: change_feed.clone(),
+            warm_pool_snapshot: warm_pool_snapshot.clone(),
+            jobs: jobs.clone(),
+            async_invocations: async_invocations.clone(),
+            maintenance: maintenance.clone(),
+            prewarm: prewarm.clone(),
+            route_metrics: route_metrics.clone(),
+            route_gates: route_gates.clone(),
+            access_log: access_log.clone(),
+            exec_sessions: exec_sessions.clone(),
+            invocation_traces: invocation_traces.clone(),
+            recording_sampler: recording_sampler.clone(),
+            replicator: replicator.clone(),
+            builds: builds.clone(),
+            failed_builds: failed_builds.clone(),
+            rollouts: rollouts.clone(),
+            hooks: HookCaller::new(),
+            throttles: ThrottleTracker::new(),
+            features: features.clone(),
+            jwks: jwks.clone(),
+            instance_tokens: instance_tokens.clone(),
         });
-        let autoscaler = Autoscaler::new(control_ref.clone());
+        let scale_wake = Arc::new(tokio::sync::Notify::new());
+        let autoscaler = Autoscaler::new(control_ref.clone()).with_wake(scale_wake.clone());
         tokio::spawn(async move {
             autoscaler.start().await;
         });
 
+        // Hold invocations to their function's timeout
+        let deadline_control = control_ref.clone();
+        tokio::spawn(async move {
+            deadline_control.run_deadline_enforcer().await;
+        });
+
+        // Feed queue messages to mapped functions
+        let poller = EventSourcePoller::new(control_ref.clone());
+        tokio::spawn(async move {
+            poller.start().await;
+        });
+
+        // Trim change feed events past their retention
+        let change_feed_pruner = change_feed.clone();
+        tokio::spawn(async move {
+            change_feed_pruner.start_pruning().await;
+        });
+
+        // Drop job results past their TTL
+        let job_pruner = jobs.clone();
+        tokio::spawn(async move {
+            job_pruner.start_pruning().await;
+        });
+
+        // Attempt queued Event invocations, retrying failed ones
+        let async_control = control_ref.clone();
+        tokio::spawn(async move {
+            async_control.run_async_invocations().await;
+        });
+
+        // Prune old executions and compact the database file
+        let database_maintenance = maintenance.clone();
+        tokio::spawn(async move {
+            database_maintenance.run().await;
+        });
+
+        // Move functions idle for long enough to cold storage
+        if config.archive.idle_days.is_some() {
+            let archive_control = control_ref.clone();
+            tokio::spawn(async move {
+                archive_control.run_archiver().await;
+            });
+        }
+
+        // Report routes and mappings naming functions that no longer exist
+        if config.integrity.check_interval_secs > 0 {
+            let integrity_control = control_ref.clone();
+            tokio::spawn(async move {
+                integrity_control.run_consistency_checks().await;
+            });
+        }
+
+        // Replace warm containers after configuration changes
+        let rollout_control = control_ref.clone();
+        tokio::spawn(async move {
+            rollout_control.run_rollouts(rollout_tasks).await;
+        });
+
+        // Invoke functions with synthetic checks as the checks come due
+        if features.is_enabled(FeatureFlag::SyntheticChecks) {
+            let synthetics_control = control_ref.clone();
+            tokio::spawn(async move {
+                synthetics_control.run_synthetic_checks().await;
+            });
+        }
+
+        // Queue an Event invocation whenever a function's schedule comes due
+        let schedules_control = control_ref.clone();
+        tokio::spawn(async move {
+            schedules_control.run_schedules().await;
+        });
+
+        // Watch free disk and collect build garbage when it runs low
+        let disk_control = control_ref.clone();
+        tokio::spawn(async move {
+            disk_control.run_disk_monitor().await;
+        });
+
+        // Mirror functions and routes to the replication peer
+        if replicator.peer().is_some() {
+            let replication_control = control_ref.clone();
+            tokio::spawn(async move {
+                replication_control.run_replication().await;
+            });
+            // Exchange health with the peer, for invoke failover
+            let health_control = control_ref.clone();
+            tokio::spawn(async move {
+                health_control.run_health_exchange().await;
+            });
+        }
+
+        // Create or update the functions declared in the config file
+        if !config.functions.is_empty() {
+            let static_control = control_ref.clone();
+            tokio::spawn(async move {
+                static_control.apply_static_functions().await;
+            });
+        }
+
         // Start cache cleanup task
         let cache_cleanup = cache.clone();
         tokio::spawn(async move {
@@ -101,6 +406,9 @@ impl ControlPlane {
 
         // Start container monitor for bidirectional state sync
         let (container_monitor, event_sender) = ContainerMonitor::new(warm_pool.clone());
+        let container_monitor = container_monitor
+            .with_pending(scheduler.pending())
+            .with_replacement(scheduler.queues(), scale_wake);
 
         // Start Docker events monitoring
         if let Err(e) = invoker.start_events_monitor_with_sender(event_sender).await {
@@ -112,6 +420,25 @@ impl ControlPlane {
             container_monitor.start().await;
         });
 
+        // Adopt containers a previous run left running (or remove them when persistence
+        // is off), now that Docker events keep the pool in sync
+        if let Err(e) = control_ref.restore_warm_pool().await {
+            warn!("Failed to restore warm pool snapshot: {}", e);
+        }
+        if let Err(e) = control_ref.restore_reserved_concurrency().await {
+            warn!("Failed to restore reserved concurrency: {}", e);
+        }
+        if let Err(e) = control_ref.restore_lifecycles().await {
+            warn!("Failed to restore function lifecycles: {}", e);
+        }
+        if config.warm_pool.persist {
+            let snapshot = warm_pool_snapshot.clone();
+            let snapshot_pool = warm_pool.clone();
+            tokio::spawn(async move {
+                snapshot.run(snapshot_pool).await;
+            });
+        }
+
         Ok(Self {
             pool,
             scheduler: Arc::new(scheduler),
@@ -121,7 +448,34 @@ impl ControlPlane {
             config,
             cache,
             execution_tracker,
+            warmup_tracker,
+            latency_tracker,
+            placement_lock,
+            disk_monitor,
             functions_being_deleted: Arc::new(Mutex::new(HashSet::new())),
+            webhooks,
+            sqs,
+            change_feed,
+            warm_pool_snapshot,
+            jobs,
+            async_invocations,
+            maintenance,
+            prewarm,
+            route_metrics,
+            route_gates,
+            access_log,
+            exec_sessions,
+            invocation_traces,
+            recording_sampler,
+            replicator,
+            builds,
+            failed_builds,
+            rollouts,
+            hooks: HookCaller::new(),
+            throttles: ThrottleTracker::new(),
+            features,
+            jwks,
+            instance_tokens,
         })
     }
 
@@ -132,18 +486,37 @@ impl ControlPlane {
     pub fn pending(&self) -> Pending {
         self.scheduler.pending()
     }
+    pub fn warmup_stats(&self) -> WarmupStats {
+        self.warmup_tracker.snapshot()
+    }
+    pub fn features(&self) -> FeatureFlags {
+        self.features.clone()
+    }
     pub fn invoker(&self) -> Arc<lambda_invoker::Invoker> {
         self.invoker.clone()
     }
     pub fn config(&self) -> lambda_models::Config {
         self.config.clone()
     }
+    /// Token the container running `instance_id` proves itself with
+    pub fn instance_token(&self, instance_id: &str) -> String {
+        self.instance_tokens.token(instance_id)
+    }
+    pub fn identity(&self) -> &IdentityConfig {
+        &self.config.identity
+    }
+    pub fn trusted_headers(&self) -> &lambda_models::TrustedHeadersConfig {
+        &self.config.trusted_headers
+    }
 
     // Function deletion state management
     pub fn mark_function_for_deletion(&self, function_name: &str) {
         if let Ok(mut set) = self.functions_being_deleted.lock() {
             set.insert(function_name.to_string());
-            info!("Marked function {} for deletion - new invocations will be rejected", function_name);
+            info!(
+                "Marked function {} for deletion - new invocations will be rejected",
+                function_name
+            );
         }
     }
 
@@ -161,6 +534,318 @@ impl ControlPlane {
             false
         }
     }
+    /// Write the warm pool snapshot now, e.g. right before a planned shutdown
+    pub async fn save_warm_pool_snapshot(&self) -> Result<usize, LambdaError> {
+        self.warm_pool_snapshot.save(&self.warm_pool).await
+    }
+
+    /// Apply the reserved concurrency stored for each function to the limiter
+    async fn restore_reserved_concurrency(&self) -> Result<(), LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT functions.*, function_concurrency.reserved_concurrent_executions AS reserved
+               FROM functions
+               JOIN function_concurrency ON function_concurrency.function_id = functions.function_id
+               WHERE function_concurrency.reserved_concurrent_executions IS NOT NULL"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        for row in &rows {
+            let function = self.row_to_function(row)?;
+            let reserved: i64 = row.try_get("reserved").map_err(LambdaError::SqlxError)?;
+            self.concurrency_manager
+                .set_reserved_limit(&function, Some(reserved as u32));
+        }
+        Ok(())
+    }
+
+    /// Apply the lifecycle settings stored for each function to the warm pool
+    async fn restore_lifecycles(&self) -> Result<(), LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT functions.*, function_lifecycle.idle_timeout_secs,
+                      function_lifecycle.max_warm_containers, function_lifecycle.min_warm_containers
+               FROM functions
+               JOIN function_lifecycle ON function_lifecycle.function_id = functions.function_id"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        for row in &rows {
+            let function = self.row_to_function(row)?;
+            self.warm_pool
+                .set_lifecycle(function.function_id, Some(row_to_lifecycle(row)));
+        }
+        Ok(())
+    }
+
+    /// Reconcile the last warm pool snapshot with Docker: adopt containers that still
+    /// serve their function and remove the rest. With `warm_pool.persist` off, every
+    /// container in the snapshot is removed. Returns how many containers were adopted.
+    pub async fn restore_warm_pool(&self) -> Result<usize, LambdaError> {
+        let entries = self.warm_pool_snapshot.load().await?;
+        if entries.is_empty() {
+            return Ok(0);
+        }
+        let (mut adopted, mut removed, mut forgotten) = (0, 0, 0);
+        for entry in entries {
+            let action = if self.config.warm_pool.persist {
+                let running = self.invoker.container_running(&entry.container_id).await?;
+                // Containers of a version are checked against that version
+                let current = match self.get_function(&entry.key.function_name).await {
+                    Ok(f) if f.function_id == entry.function_id => self
+                        .function_at_version(f, &entry.key.version)
+                        .await
+                        .ok()
+                        .map(|f| {
+                            (
+                                f.image_ref(),
+                                crate::queues::FnKey::for_function(&f).env_hash,
+                            )
+                        }),
+                    _ => None,
+                };
+                restore_action(
+                    &entry,
+                    running,
+                    current.as_ref().map(|(i, e)| (i.as_str(), e.as_str())),
+                )
+            } else {
+                RestoreAction::Remove
+            };
+            match action {
+                RestoreAction::Adopt(state) => {
+                    let (key, container) = entry.into_warm_container(state);
+                    self.warm_pool.add_warm_container(key, container).await;
+                    adopted += 1;
+                }
+                RestoreAction::Remove => {
+                    let _ = self.invoker.remove_container(&entry.container_id).await;
+                    removed += 1;
+                }
+                RestoreAction::Forget => forgotten += 1,
+            }
+        }
+        self.warm_pool_snapshot.save(&self.warm_pool).await?;
+        info!(
+            "Warm pool restored: {} adopted, {} removed, {} gone",
+            adopted, removed, forgotten
+        );
+        Ok(adopted)
+    }
+
+    /// Ordered feed of function, route and execution changes
+    pub fn change_feed(&self) -> ChangeFeed {
+        self.change_feed.clone()
+    }
+
+    /// Start `request` as a job: the function is checked now, the invocation runs in the
+    /// background under the job id as its request id, and its result is read back with
+    /// [`Self::get_job`]
+    pub async fn start_job(self: &Arc<Self>, request: InvokeRequest) -> Result<Job, LambdaError> {
+        self.get_function(&request.function_name).await?;
+        if self.is_function_being_deleted(&request.function_name) {
+            return Err(LambdaError::FunctionNotFound {
+                function_name: request.function_name.clone(),
+            });
+        }
+        let job = self.jobs.create(&request.function_name).await?;
+        let control = self.clone();
+        let job_id = job.job_id.clone();
+        tokio::spawn(async move {
+            let result = control
+                .invoke_with_request_id(job_id.clone(), request)
+                .await;
+            if let Err(e) = control.jobs.complete(&job_id, result).await {
+                error!("Failed to store result of job {}: {}", job_id, e);
+            }
+        });
+        Ok(job)
+    }
+
+    /// Queue `request` as an Event invocation: the function is checked now, and the
+    /// invocation is attempted in the background, retried as `async_invoke` allows. Its
+    /// final result is read back with [`Self::get_invocation_result`]
+    pub async fn enqueue_event(&self, request: InvokeRequest) -> Result<Job, LambdaError> {
+        self.get_function(&request.function_name).await?;
+        if self.is_function_being_deleted(&request.function_name) {
+            return Err(LambdaError::FunctionNotFound {
+                function_name: request.function_name.clone(),
+            });
+        }
+        let job = self.jobs.create(&request.function_name).await?;
+        self.async_invocations
+            .enqueue(&job.job_id, &request)
+            .await?;
+        Ok(job)
+    }
+
+    /// Event invocations waiting for an attempt or under way
+    pub async fn async_queue_depth(&self) -> Result<u64, LambdaError> {
+        self.async_invocations.depth().await
+    }
+
+    /// Claim queued Event invocations as they come due and attempt up to
+    /// `async_invoke.max_concurrent` of them at a time
+    async fn run_async_invocations(self: Arc<Self>) {
+        let slots = Arc::new(tokio::sync::Semaphore::new(
+            self.config.async_invoke.max_concurrent,
+        ));
+        loop {
+            let Ok(slot) = slots.clone().acquire_owned().await else {
+                return;
+            };
+            match self.async_invocations.claim_next().await {
+                Ok(Some(event)) => {
+                    let control = self.clone();
+                    tokio::spawn(async move {
+                        control.attempt_event(event).await;
+                        drop(slot);
+                    });
+                }
+                Ok(None) => {
+                    drop(slot);
+                    self.async_invocations
+                        .wait(async_invocations::POLL_INTERVAL)
+                        .await;
+                }
+                Err(e) => {
+                    drop(slot);
+                    warn!("Failed to claim a queued Event invocation: {}", e);
+                    tokio::time::sleep(async_invocations::POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Make one attempt at `event`, then queue it for a retry or store its final result.
+    /// Retries run under request ids of their own, as each attempt is an execution
+    async fn attempt_event(&self, event: QueuedEvent) {
+        let config = &self.config.async_invoke;
+        let now = Utc::now();
+        let result = if now > async_invocations::expires_at(config, &event) {
+            Err(LambdaError::EventAgeExceeded {
+                max_event_age_secs: config.max_event_age_secs,
+            })
+        } else {
+            let request_id = if event.attempts == 0 {
+                event.request_id.clone()
+            } else {
+                Uuid::new_v4().to_string()
+            };
+            self.invoke_with_request_id(request_id, event.request.clone())
+                .await
+        };
+
+        if let Some((attempts, delay)) =
+            async_invocations::next_attempt(config, &event, &result, Utc::now())
+        {
+            info!(
+                "Retrying Event invocation {} of {} in {:?}",
+                event.request_id, event.request.function_name, delay
+            );
+            if let Err(e) = self
+                .async_invocations
+                .retry(&event.request_id, attempts, delay)
+                .await
+            {
+                error!(
+                    "Failed to requeue Event invocation {}: {}",
+                    event.request_id, e
+                );
+            }
+            return;
+        }
+        if let Err(e) = self.jobs.complete(&event.request_id, result).await {
+            error!(
+                "Failed to store result of Event invocation {}: {}",
+                event.request_id, e
+            );
+        }
+        if let Err(e) = self.async_invocations.remove(&event.request_id).await {
+            error!(
+                "Failed to dequeue Event invocation {}: {}",
+                event.request_id, e
+            );
+        }
+    }
+
+    /// Run a synchronous `request`, waiting at most `wait` for its result. An invocation
+    /// still running then goes on in the background, see [`PendingInvoke`]
+    pub async fn invoke_function_within(
+        self: &Arc<Self>,
+        request: InvokeRequest,
+        wait: std::time::Duration,
+    ) -> BoundedInvoke {
+        let control = self.clone();
+        let request_id = Uuid::new_v4().to_string();
+        let function_name = request.function_name.clone();
+        let invocation_id = request_id.clone();
+        run_bounded(
+            self.jobs.clone(),
+            request_id,
+            function_name,
+            wait,
+            async move { control.invoke_with_request_id(invocation_id, request).await },
+        )
+        .await
+    }
+
+    /// Run a synchronous `request` whose response the function may stream back in chunks,
+    /// see [`ResponseStream`]
+    pub fn invoke_with_response_stream(
+        self: &Arc<Self>,
+        mut request: InvokeRequest,
+    ) -> ResponseStream {
+        let request_id = Uuid::new_v4().to_string();
+        let pending = self.scheduler.pending();
+        let parts = pending.register_stream(request_id.clone());
+        // Chunks the caller already read cannot be taken back, so the invoke never re-runs
+        request.idempotent = false;
+        let control = self.clone();
+        let invocation_id = request_id.clone();
+        let result = tokio::spawn(async move {
+            let result = control
+                .invoke_with_request_id(invocation_id.clone(), request)
+                .await;
+            // An invoke refused before it reached a runtime leaves the stream registered
+            pending.end_stream(&invocation_id);
+            result
+        });
+        ResponseStream {
+            request_id,
+            parts,
+            result,
+        }
+    }
+
+    /// Give up waiting for `pending`: its result is stored for
+    /// [`Self::get_invocation_result`] once it finishes. The result itself when it already did
+    pub async fn detach_invoke(
+        &self,
+        pending: PendingInvoke,
+    ) -> Result<Option<Result<InvokeResponse, LambdaError>>, LambdaError> {
+        pending.detach(&self.jobs).await
+    }
+
+    pub async fn get_job(&self, job_id: &str) -> Result<Job, LambdaError> {
+        self.jobs.get(job_id).await
+    }
+
+    /// Stored outcome of an Event invocation, by request id
+    pub async fn get_invocation_result(&self, request_id: &str) -> Result<Job, LambdaError> {
+        self.jobs.get(request_id).await.map_err(|e| match e {
+            LambdaError::JobNotFound { job_id } => {
+                LambdaError::ExecutionNotFound { request_id: job_id }
+            }
+            e => e,
+        })
+    }
+
+    /// Embedded SQS-compatible queues
+    pub fn sqs(&self) -> QueueService {
+        self.sqs.clone()
+    }
+
     pub fn queues(&self) -> Queues {
         self.scheduler.queues()
     }
@@ -175,19 +860,24 @@ impl ControlPlane {
                 function_name: req.function_name,
             });
         }
+        let canary = match req.canary {
+            Some(canary) => Some(self.validate_route_canary(canary).await?),
+            None => None,
+        };
         let route_id = Uuid::new_v4();
         let created_at = chrono::Utc::now();
         let path = normalize_path(&req.path);
         let method = req.method.as_ref().map(|m| m.to_uppercase());
 
         sqlx::query(
-            "INSERT INTO api_routes (route_id, path, method, function_name, created_at) VALUES (?, ?, ?, ?, ?)"
+            "INSERT INTO api_routes (route_id, path, method, function_name, created_at, canary) VALUES (?, ?, ?, ?, ?, ?)"
         )
         .bind(route_id)
         .bind(&path)
         .bind(&method)
         .bind(&req.function_name)
         .bind(created_at)
+        .bind(canary.as_ref().map(|c| serde_json::to_string(c).unwrap_or_default()))
         .execute(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
@@ -198,29 +888,88 @@ impl ControlPlane {
             method,
             function_name: req.function_name,
             created_at,
+            canary,
+            access_log: true,
+            mock: None,
+            recording: None,
+            limits: None,
+            transform: None,
+            invocation: None,
+            jwt_authorizer: None,
         })
     }
 
+    /// OpenAPI document of the API gateway's routes, with the schema documents their
+    /// functions bundle; see [`crate::openapi`]
+    #[instrument(skip(self))]
+    pub async fn gateway_openapi(&self) -> Result<serde_json::Value, LambdaError> {
+        self.features.require(FeatureFlag::GatewayOpenapi)?;
+        let routes = self.list_api_routes().await?.routes;
+        let names: BTreeSet<&str> = routes.iter().map(|r| r.function_name.as_str()).collect();
+        let mut documents = HashMap::new();
+        let mut warnings = Vec::new();
+        for name in names {
+            match self.function_openapi(name).await {
+                Ok(Some(document)) => {
+                    documents.insert(name.to_string(), document);
+                }
+                Ok(None) => {}
+                Err(e) => warnings.push(format!("{name}: {e}")),
+            }
+        }
+        Ok(openapi::gateway_document(
+            &routes,
+            &documents,
+            warnings,
+            env!("CARGO_PKG_VERSION"),
+        ))
+    }
+
+    /// The schema document bundled with a function's code, if it has one
+    async fn function_openapi(&self, name: &str) -> Result<Option<serde_json::Value>, LambdaError> {
+        let function = self.get_function(name).await?;
+        if function.code_sha256.is_empty() || function.package_type == PackageType::Image {
+            return Ok(None);
+        }
+        let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+        let zip_data = packaging_service.load_zip(&function.code_sha256)?;
+        let zip_info = packaging_service.process_zip(&zip_data).await?;
+        if !zip_info.contains_file(openapi::OPENAPI_FILE) {
+            return Ok(None);
+        }
+        let content = zip_info.read_file(openapi::OPENAPI_FILE)?;
+        match serde_json::from_slice(&content) {
+            Ok(document @ serde_json::Value::Object(_)) => Ok(Some(document)),
+            Ok(_) => Err(LambdaError::InvalidRequest {
+                reason: format!("{} is not a JSON object", openapi::OPENAPI_FILE),
+            }),
+            Err(e) => Err(LambdaError::InvalidRequest {
+                reason: format!("{} is not valid JSON: {e}", openapi::OPENAPI_FILE),
+            }),
+        }
+    }
+
     pub async fn list_api_routes(&self) -> Result<ListApiRoutesResponse, LambdaError> {
         let rows = sqlx::query("SELECT * FROM api_routes ORDER BY path")
             .fetch_all(&self.pool)
             .await
             .map_err(LambdaError::SqlxError)?;
-        let mut routes = Vec::with_capacity(rows.len());
-        for row in rows.iter() {
-            routes.push(ApiRoute {
-                route_id: row.try_get("route_id").map_err(LambdaError::SqlxError)?,
-                path: row.try_get("path").map_err(LambdaError::SqlxError)?,
-                method: row.try_get("method").map_err(LambdaError::SqlxError)?,
-                function_name: row
-                    .try_get("function_name")
-                    .map_err(LambdaError::SqlxError)?,
-                created_at: row.try_get("created_at").map_err(LambdaError::SqlxError)?,
-            });
-        }
+        let routes = rows.iter().map(row_to_api_route).collect();
         Ok(ListApiRoutesResponse { routes })
     }
 
+    pub async fn get_api_route(&self, route_id: Uuid) -> Result<ApiRoute, LambdaError> {
+        sqlx::query("SELECT * FROM api_routes WHERE route_id = ?")
+            .bind(route_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .map(|row| row_to_api_route(&row))
+            .ok_or_else(|| LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            })
+    }
+
     pub async fn delete_api_route(&self, route_id: Uuid) -> Result<(), LambdaError> {
         let result = sqlx::query("DELETE FROM api_routes WHERE route_id = ?")
             .bind(route_id)
@@ -232,755 +981,5815 @@ impl ControlPlane {
                 reason: "Route not found".to_string(),
             });
         }
+        self.route_metrics.remove_route(route_id);
+        self.route_gates.remove_route(route_id);
+        self.recording_sampler.remove_route(route_id);
         Ok(())
     }
 
-    pub async fn resolve_api_route(
+    /// Send the requests matching `canary` to a secondary target. Replacing a canary keeps
+    /// the route's metrics, so reset them by deleting the canary first if needed.
+    pub async fn put_api_route_canary(
         &self,
-        method: &str,
-        path: &str,
-    ) -> Result<Option<String>, LambdaError> {
-        let norm_path = normalize_path(path);
-        let upper_m = method.to_uppercase();
-        let rows = sqlx::query("SELECT path, method, function_name FROM api_routes")
-            .fetch_all(&self.pool)
+        route_id: Uuid,
+        canary: RouteCanary,
+    ) -> Result<ApiRoute, LambdaError> {
+        let canary = self.validate_route_canary(canary).await?;
+        let result = sqlx::query("UPDATE api_routes SET canary = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&canary).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
             .await
             .map_err(LambdaError::SqlxError)?;
-        let mut best: Option<(usize, String)> = None;
-        for row in rows.iter() {
-            let r_path: String = row.try_get("path").map_err(LambdaError::SqlxError)?;
-            let r_method: Option<String> = row.try_get("method").map_err(LambdaError::SqlxError)?;
-            let fname: String = row
-                .try_get("function_name")
-                .map_err(LambdaError::SqlxError)?;
-            if !norm_path.starts_with(&r_path) {
-                continue;
-            }
-            if let Some(m) = r_method.as_ref() {
-                if m.to_uppercase() != upper_m {
-                    continue;
-                }
-            }
-            let plen = r_path.len();
-            if best.as_ref().map(|(l, _)| *l).unwrap_or(0) < plen {
-                best = Some((plen, fname));
-            }
-        }
-        Ok(best.map(|(_, f)| f))
-    }
-
-    #[instrument(skip(self, request))]
-    pub async fn create_function(
-        &self,
-        request: CreateFunctionRequest,
-    ) -> Result<Function, LambdaError> {
-        let function_id = Uuid::new_v4();
-        let now = Utc::now();
-
-        // Validate function name
-        if !self.is_valid_function_name(&request.function_name) {
-            return Err(LambdaError::InvalidFunctionName {
-                function_name: request.function_name,
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
             });
         }
+        self.get_api_route(route_id).await
+    }
 
-        // Check if function already exists
-        if self.function_exists(&request.function_name).await? {
-            return Err(LambdaError::FunctionAlreadyExists {
-                function_name: request.function_name,
+    /// Send all of the route's traffic to its primary function again
+    pub async fn delete_api_route_canary(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET canary = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
             });
         }
+        self.route_metrics.remove_route(route_id);
+        Ok(())
+    }
 
-        // Validate runtime
-        if !self.is_valid_runtime(&request.runtime) {
-            return Err(LambdaError::InvalidRuntime {
-                runtime: request.runtime,
+    /// Turn access logging of the route's requests on or off
+    pub async fn set_api_route_access_log(
+        &self,
+        route_id: Uuid,
+        enabled: bool,
+    ) -> Result<ApiRoute, LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET access_log = ? WHERE route_id = ?")
+            .bind(enabled)
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    /// Answer the route's requests with `mock` instead of invoking its function while the
+    /// mock is enabled
+    pub async fn put_api_route_mock(
+        &self,
+        route_id: Uuid,
+        mock: RouteMock,
+    ) -> Result<ApiRoute, LambdaError> {
+        validate_route_mock(&mock)?;
+        let result = sqlx::query("UPDATE api_routes SET mock = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&mock).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    pub async fn delete_api_route_mock(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET mock = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Map the route's requests and responses with templates
+    pub async fn put_api_route_transform(
+        &self,
+        route_id: Uuid,
+        transform: RouteTransform,
+    ) -> Result<ApiRoute, LambdaError> {
+        validate_route_transform(&transform)?;
+        let result = sqlx::query("UPDATE api_routes SET transform = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&transform).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    pub async fn delete_api_route_transform(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET transform = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Invoke the route's function as `invocation` says instead of waiting for it
+    pub async fn put_api_route_invocation(
+        &self,
+        route_id: Uuid,
+        invocation: RouteInvocation,
+    ) -> Result<ApiRoute, LambdaError> {
+        if invocation.invocation_type == lambda_models::InvocationType::DryRun {
+            return Err(LambdaError::InvalidRequest {
+                reason: "A route's invocation_type must be RequestResponse or Event".to_string(),
+            });
+        }
+        let result = sqlx::query("UPDATE api_routes SET invocation = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&invocation).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    /// Wait for the route's function again on every request
+    pub async fn delete_api_route_invocation(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET invocation = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Require a valid bearer token on the route's requests
+    pub async fn put_api_route_jwt_authorizer(
+        &self,
+        route_id: Uuid,
+        authorizer: RouteJwtAuthorizer,
+    ) -> Result<ApiRoute, LambdaError> {
+        validation::validate_jwt_authorizer(&authorizer)?;
+        let result = sqlx::query("UPDATE api_routes SET jwt_authorizer = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&authorizer).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    /// Let requests through the route without a token again
+    pub async fn delete_api_route_jwt_authorizer(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET jwt_authorizer = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check a request's `Authorization` header against the route's JWT authorizer: the
+    /// `requestContext.authorizer.jwt` of its event, or why the request is refused
+    pub async fn authorize_route_request(
+        &self,
+        authorizer: &RouteJwtAuthorizer,
+        authorization: Option<&str>,
+    ) -> Result<serde_json::Value, String> {
+        self.jwks.authorize(authorizer, authorization).await
+    }
+
+    /// Save samples of the route's requests as test events of its function while the
+    /// recording is enabled
+    pub async fn put_api_route_recording(
+        &self,
+        route_id: Uuid,
+        recording: RouteRecording,
+    ) -> Result<ApiRoute, LambdaError> {
+        validate_route_recording(&recording)?;
+        let result = sqlx::query("UPDATE api_routes SET recording = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&recording).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    /// Stop recording the route's requests; events recorded so far are kept
+    pub async fn delete_api_route_recording(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET recording = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.recording_sampler.remove_route(route_id);
+        Ok(())
+    }
+
+    /// Cap the requests the route forwards at once and queues, shedding the rest with 503
+    pub async fn put_api_route_limits(
+        &self,
+        route_id: Uuid,
+        limits: RouteLimits,
+    ) -> Result<ApiRoute, LambdaError> {
+        validate_route_limits(&limits)?;
+        let result = sqlx::query("UPDATE api_routes SET limits = ? WHERE route_id = ?")
+            .bind(serde_json::to_string(&limits).unwrap_or_default())
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
+            });
+        }
+        self.get_api_route(route_id).await
+    }
+
+    /// Forward all of the route's requests again, bounded only by function concurrency
+    pub async fn delete_api_route_limits(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("UPDATE api_routes SET limits = NULL WHERE route_id = ?")
+            .bind(route_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Route not found".to_string(),
             });
         }
+        Ok(())
+    }
+
+    /// Take a slot of `route` for one request, waiting in its queue if needed. Fails with
+    /// `RouteSaturated` when the request is shed.
+    pub async fn admit_route_request(&self, route: &ApiRoute) -> Result<RoutePermit, LambdaError> {
+        self.route_gates.admit(route).await
+    }
 
-        // Process ZIP file if provided
-        let (code_sha256, code_size, state) = if let Some(zip_file_base64) = &request.code.zip_file
+    /// Save a gateway `event` of the route as a redacted test event of its function, unless
+    /// the route isn't recording or already recorded `per_minute` requests this minute
+    pub async fn capture_route_event(
+        &self,
+        route: &ApiRoute,
+        event: &serde_json::Value,
+    ) -> Result<Option<TestEvent>, LambdaError> {
+        let Some(recording) = route.recording.as_ref().filter(|r| r.enabled) else {
+            return Ok(None);
+        };
+        if !self
+            .recording_sampler
+            .try_acquire(route.route_id, recording.per_minute)
         {
-            let zip_data =
-                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, zip_file_base64)
-                    .map_err(|e| LambdaError::InvalidRequest {
-                        reason: format!("Invalid base64 ZIP data: {e}"),
-                    })?;
+            return Ok(None);
+        }
+        let function = self.get_function(&route.function_name).await?;
+        let mut payload = event.clone();
+        redact_event(recording, &mut payload);
+        let created_at = Utc::now();
+        let name = format!(
+            "{} {} {}",
+            payload["httpMethod"].as_str().unwrap_or("ANY"),
+            payload["path"].as_str().unwrap_or(&route.path),
+            created_at.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        // Recorded as the function receives it, from the redacted request
+        if let Some(template) = route.transform.as_ref().and_then(|t| t.request.as_ref()) {
+            payload = transforms::render(template, &transforms::request_context(&payload));
+        }
+        let test_event = TestEvent {
+            event_id: Uuid::new_v4(),
+            function_name: function.function_name.clone(),
+            name,
+            payload,
+            source: TestEventSource::Recorded,
+            route_id: Some(route.route_id),
+            created_at,
+        };
+        self.insert_test_event(function.function_id, &test_event)
+            .await?;
+
+        // Keep only the newest recorded events of the function
+        sqlx::query(
+            r#"DELETE FROM test_events WHERE function_id = ?1 AND source = 'recorded'
+               AND event_id NOT IN (
+                   SELECT event_id FROM test_events WHERE function_id = ?1 AND source = 'recorded'
+                   ORDER BY created_at DESC LIMIT ?2
+               )"#,
+        )
+        .bind(function.function_id)
+        .bind(recording.max_events as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(Some(test_event))
+    }
+
+    pub fn access_log(&self) -> &AccessLog {
+        &self.access_log
+    }
+
+    /// Open a debug shell session into a running container of the warm pool. The shell
+    /// starts when a client connects to the session, which it must do before `expires_at`.
+    #[instrument(skip(self))]
+    pub async fn open_exec_session(
+        &self,
+        container_id: &str,
+        opened_by: Option<&str>,
+    ) -> Result<ExecSession, LambdaError> {
+        if !self.config.exec.enabled {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Exec sessions are not enabled".to_string(),
+            });
+        }
+        let (key, state) = self
+            .warm_pool
+            .find_container(container_id)
+            .await
+            .ok_or_else(|| LambdaError::ContainerNotFound {
+                container_id: container_id.to_string(),
+            })?;
+        if !matches!(state, InstanceState::WarmIdle | InstanceState::Active) {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!("Container {container_id} is {}, not warm", state.as_str()),
+            });
+        }
+        let session_id = Uuid::new_v4().to_string();
+        let opened_at = Utc::now();
+        let max_secs = self.config.exec.max_session_secs.max(1);
+        let session = ExecSession {
+            websocket_path: format!("/api/exec-sessions/{session_id}"),
+            session_id,
+            container_id: container_id.to_string(),
+            function_name: key.function_name,
+            opened_by: opened_by.map(str::to_string),
+            opened_at,
+            expires_at: opened_at + chrono::Duration::seconds(max_secs as i64),
+        };
+        self.exec_sessions.insert(session.clone());
+        Ok(session)
+    }
+
+    /// Claim an open exec session to connect to it; each session can be claimed once
+    pub fn take_exec_session(&self, session_id: &str) -> Result<ExecSession, LambdaError> {
+        self.exec_sessions
+            .take(session_id)
+            .ok_or_else(|| LambdaError::ExecSessionNotFound {
+                session_id: session_id.to_string(),
+            })
+    }
+
+    pub fn exec_audit(&self) -> &ExecAudit {
+        self.exec_sessions.audit()
+    }
+
+    /// Runtime API exchanges being recorded for invocations run with tracing on
+    pub fn invocation_traces(&self) -> &InvocationTraces {
+        &self.invocation_traces
+    }
+
+    /// Recorded runtime API exchange of a traced execution, by request id
+    #[instrument(skip(self))]
+    pub async fn get_execution_trace(
+        &self,
+        request_id: &str,
+    ) -> Result<InvocationTrace, LambdaError> {
+        self.invocation_traces.get(request_id).await
+    }
+
+    /// What `namespace` uses of each quota, next to its limits
+    #[instrument(skip(self))]
+    pub async fn namespace_usage(&self, namespace: &str) -> Result<NamespaceUsage, LambdaError> {
+        if namespace != DEFAULT_NAMESPACE {
+            return Err(LambdaError::NamespaceNotFound {
+                namespace: namespace.to_string(),
+            });
+        }
+        let quotas = &self.config.quotas;
+        let day_started_at = start_of_day(Utc::now());
+        Ok(NamespaceUsage {
+            namespace: namespace.to_string(),
+            functions: QuotaUsage {
+                used: self.count_functions().await?,
+                limit: quotas.max_functions,
+            },
+            warm_memory_mb: QuotaUsage {
+                used: self.warm_pool.committed_memory_mb().await,
+                limit: quotas.max_warm_memory_mb,
+            },
+            daily_invocations: QuotaUsage {
+                used: self.invocations_since(day_started_at).await?,
+                limit: quotas.max_daily_invocations,
+            },
+            storage_bytes: QuotaUsage {
+                used: self.stored_code_bytes(None).await?,
+                limit: quotas.max_storage_bytes,
+            },
+            day_started_at,
+        })
+    }
+
+    async fn count_functions(&self) -> Result<u64, LambdaError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM functions")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(count as u64)
+    }
+
+    async fn invocations_since(&self, since: DateTime<Utc>) -> Result<u64, LambdaError> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM executions WHERE start_time >= ?")
+                .bind(since)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(count as u64)
+    }
+
+    /// Bytes of the deployment packages functions and versions refer to, each package counted
+    /// once, leaving out `except_sha256`
+    async fn stored_code_bytes(&self, except_sha256: Option<&str>) -> Result<u64, LambdaError> {
+        let bytes: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(code_size), 0) FROM (
+                 SELECT code_sha256, MAX(code_size) AS code_size FROM (
+                     SELECT code_sha256, code_size FROM functions
+                     UNION ALL SELECT code_sha256, code_size FROM versions
+                 )
+                 WHERE code_sha256 != '' AND code_sha256 != COALESCE(?, '')
+                 GROUP BY code_sha256
+             )",
+        )
+        .bind(except_sha256)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(bytes as u64)
+    }
+
+    pub async fn api_route_metrics(&self, route_id: Uuid) -> Result<RouteMetrics, LambdaError> {
+        let route = self.get_api_route(route_id).await?;
+        let mut metrics = self.route_metrics.snapshot(route_id);
+        metrics.saturation = RouteSaturation {
+            max_in_flight: route.limits.as_ref().map(|l| l.max_in_flight),
+            max_queued: route.limits.as_ref().map(|l| l.max_queued),
+            ..self.route_gates.saturation(route_id)
+        };
+        Ok(metrics)
+    }
+
+    pub fn record_route_request(
+        &self,
+        route_id: Uuid,
+        target: RouteTarget,
+        failed: bool,
+        latency_ms: u64,
+    ) {
+        self.route_metrics
+            .record(route_id, target, failed, latency_ms);
+    }
+
+    async fn validate_route_canary(&self, canary: RouteCanary) -> Result<RouteCanary, LambdaError> {
+        if !self.function_exists(&canary.function_name).await? {
+            return Err(LambdaError::FunctionNotFound {
+                function_name: canary.function_name,
+            });
+        }
+        let qualifier = canary
+            .qualifier
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty());
+        let condition = match canary.condition {
+            CanaryMatch::Header { name, value } => CanaryMatch::Header {
+                name: canary_match_name(&name)?.to_ascii_lowercase(),
+                value,
+            },
+            CanaryMatch::Cookie { name, value } => CanaryMatch::Cookie {
+                name: canary_match_name(&name)?,
+                value,
+            },
+            CanaryMatch::Percentage { percent } => {
+                if !(0.0..=100.0).contains(&percent) {
+                    return Err(LambdaError::InvalidRequest {
+                        reason: format!("Canary percent must be between 0 and 100, got {percent}"),
+                    });
+                }
+                CanaryMatch::Percentage { percent }
+            }
+        };
+        Ok(RouteCanary {
+            function_name: canary.function_name,
+            qualifier,
+            condition,
+        })
+    }
+
+    /// The route serving `method` and `path`: the longest matching path prefix, optionally
+    /// restricted to a method
+    pub async fn match_api_route(
+        &self,
+        method: &str,
+        path: &str,
+    ) -> Result<Option<ApiRoute>, LambdaError> {
+        let norm_path = normalize_path(path);
+        let upper_m = method.to_uppercase();
+        let rows = sqlx::query("SELECT * FROM api_routes")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        let mut best: Option<ApiRoute> = None;
+        for route in rows.iter().map(row_to_api_route) {
+            if !norm_path.starts_with(&route.path) {
+                continue;
+            }
+            if let Some(m) = route.method.as_ref() {
+                if m.to_uppercase() != upper_m {
+                    continue;
+                }
+            }
+            if best.as_ref().map(|b| b.path.len()).unwrap_or(0) < route.path.len() {
+                best = Some(route);
+            }
+        }
+        Ok(best)
+    }
+
+    pub async fn resolve_api_route(
+        &self,
+        method: &str,
+        path: &str,
+    ) -> Result<Option<String>, LambdaError> {
+        Ok(self
+            .match_api_route(method, path)
+            .await?
+            .map(|route| route.function_name))
+    }
+
+    #[instrument(skip(self, request))]
+    pub async fn create_function(
+        &self,
+        request: CreateFunctionRequest,
+    ) -> Result<Function, LambdaError> {
+        let function_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        // Validate function name
+        if !self.is_valid_function_name(&request.function_name) {
+            return Err(LambdaError::InvalidFunctionName {
+                function_name: request.function_name,
+            });
+        }
+
+        // Check if function already exists
+        if self.function_exists(&request.function_name).await? {
+            return Err(LambdaError::FunctionAlreadyExists {
+                function_name: request.function_name,
+            });
+        }
+
+        // Validate runtime
+        if !self.is_valid_runtime(&request.runtime) {
+            return Err(LambdaError::InvalidRuntime {
+                runtime: request.runtime,
+            });
+        }
+
+        check_quota(
+            "max_functions",
+            self.count_functions().await?,
+            1,
+            self.config.quotas.max_functions,
+        )?;
+
+        if let Some(problem) = validation::check_package(&request) {
+            return Err(LambdaError::InvalidRequest {
+                reason: problem.message,
+            });
+        }
+        let tags = request.tags.unwrap_or_default();
+        validation::check_tags(tags.iter(), tags.len())
+            .map_err(|reason| LambdaError::InvalidRequest { reason })?;
+        let package_type = request.package_type.unwrap_or_default();
+
+        // Image functions run their image as it is; ZIPs are processed if provided
+        let (code_sha256, code_size, state) = if let Some(image_uri) = &request.code.image_uri {
+            // Stands in for the code hash: it changes with the image the function runs
+            (code_sha256(image_uri.as_bytes()), 0, FunctionState::Active)
+        } else if let Some(zip_file_base64) = &request.code.zip_file {
+            let zip_data =
+                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, zip_file_base64)
+                    .map_err(|e| LambdaError::InvalidRequest {
+                        reason: format!("Invalid base64 ZIP data: {e}"),
+                    })?;
+            let zip_data = match &request.code.workspace_package {
+                Some(package) => lambda_packaging::bundle_workspace(&zip_data, package)?,
+                None => zip_data,
+            };
+
+            // Process the ZIP file
+            let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+            let zip_info = packaging_service.process_zip(&zip_data).await?;
+
+            // Fail fast if the handler does not resolve instead of failing on first invoke
+            lambda_packaging::verify_handler(&request.runtime, &request.handler, &zip_info)?;
+            lambda_packaging::check_build_context(&zip_info, self.max_build_context_bytes())?;
+            if self.config.quotas.max_storage_bytes.is_some() {
+                check_quota(
+                    "max_storage_bytes",
+                    self.stored_code_bytes(Some(&zip_info.sha256)).await?,
+                    zip_info.total_size,
+                    self.config.quotas.max_storage_bytes,
+                )?;
+            }
+
+            // Store the ZIP file
+            self.disk_monitor.check_build_capacity()?;
+            packaging_service.store_zip(&zip_info)?;
+
+            (zip_info.sha256, zip_info.total_size, FunctionState::Active)
+        } else {
+            ("".to_string(), 0, FunctionState::Pending)
+        };
+        // With warm-up on the image is built right away, and the function stays Pending
+        // until that build settles
+        let building = state == FunctionState::Active && self.config.warmup.enabled;
+
+        // Create function record
+        let function = Function {
+            function_id,
+            function_name: request.function_name.clone(),
+            runtime: request.runtime,
+            role: request.role,
+            handler: request.handler,
+            code_sha256,
+            description: request.description,
+            timeout: request.timeout.unwrap_or(3), // seconds, not milliseconds
+            memory_size: request.memory_size.unwrap_or(512),
+            environment: request.environment.unwrap_or_default(),
+            last_modified: now,
+            code_size,
+            version: LATEST_VERSION.to_string(),
+            state: if building {
+                FunctionState::Pending
+            } else {
+                state
+            },
+            state_reason: building.then(|| "The function is being created.".to_string()),
+            state_reason_code: building.then(|| "Creating".to_string()),
+            last_update_status: if building {
+                LastUpdateStatus::InProgress
+            } else {
+                LastUpdateStatus::Successful
+            },
+            last_update_status_reason: None,
+            last_update_status_reason_code: None,
+            metadata: None,
+            function_arn: None,
+            package_type,
+            image_uri: request.code.image_uri,
+        };
+
+        // The function and, with `publish`, its first version are stored together
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        sqlx::query(
+            r#"
+            INSERT INTO functions (
+                function_id, function_name, runtime, role, handler, code_sha256,
+                description, timeout, memory_size, environment, last_modified,
+                code_size, version, state, state_reason, state_reason_code,
+                last_update_status, package_type, image_uri
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(function.function_id)
+        .bind(&function.function_name)
+        .bind(&function.runtime)
+        .bind(&function.role)
+        .bind(&function.handler)
+        .bind(&function.code_sha256)
+        .bind(&function.description)
+        .bind(function.timeout as i64)
+        .bind(function.memory_size as i64)
+        .bind(serde_json::to_string(&function.environment).unwrap_or_default())
+        .bind(function.last_modified)
+        .bind(function.code_size as i64)
+        .bind(&function.version)
+        .bind(serde_json::to_string(&function.state).unwrap_or_default())
+        .bind(&function.state_reason)
+        .bind(&function.state_reason_code)
+        .bind(serde_json::to_string(&function.last_update_status).unwrap_or_default())
+        .bind(serde_json::to_string(&function.package_type).unwrap_or_default())
+        .bind(&function.image_uri)
+        .execute(&mut *tx)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        for (key, value) in &tags {
+            sqlx::query(
+                "INSERT INTO function_tags (function_id, tag_key, tag_value) VALUES (?, ?, ?)",
+            )
+            .bind(function.function_id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        }
+        insert_revision(
+            &mut *tx,
+            function.function_id,
+            None,
+            &FunctionConfigSnapshot::from(&function),
+            ConfigChangeSource::Create,
+            None,
+            None,
+        )
+        .await?;
+        let published = if request.publish == Some(true) {
+            Some(insert_next_version(&mut tx, &function, function.description.clone()).await?)
+        } else {
+            None
+        };
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+
+        info!(
+            "Created function: {} with code SHA256: {}",
+            function.function_name, function.code_sha256
+        );
+        self.webhooks.emit(
+            WebhookEventType::FunctionCreated,
+            Some(&function.function_name),
+            serde_json::json!({
+                "runtime": function.runtime,
+                "code_sha256": function.code_sha256,
+                "state": function.state,
+            }),
+        );
+        if let Some(version) = &published {
+            self.webhooks.emit(
+                WebhookEventType::VersionPublished,
+                Some(&function.function_name),
+                serde_json::json!({
+                    "version": version.version,
+                    "code_sha256": version.code_sha256,
+                }),
+            );
+        }
+
+        // Warm up container for faster cold starts (if enabled)
+        if building {
+            if let Err(e) = self.warm_up_function(&function).await {
+                // Log warning but don't fail function creation
+                tracing::warn!(
+                    "Failed to warm up container for function {}: {}",
+                    function.function_name,
+                    e
+                );
+                // The build itself records its outcome; this settles a warm-up that timed
+                // out before the build finished
+                let current = self.get_function(&function.function_name).await?;
+                if current.last_update_status == LastUpdateStatus::InProgress {
+                    self.record_build_outcome(&current, Err(&e)).await?;
+                }
+            }
+            return self.get_function(&function.function_name).await;
+        }
+
+        Ok(function)
+    }
+
+    /// Run CreateFunction validation without creating anything (dry run)
+    #[instrument(skip(self, request))]
+    pub async fn validate_function(
+        &self,
+        request: &CreateFunctionRequest,
+    ) -> Result<Vec<ValidationProblem>, LambdaError> {
+        let (mut problems, zip_info) = validation::validate_create_function_request(request).await;
+        if let Some(zip_info) = &zip_info {
+            if let Err(e) =
+                lambda_packaging::check_build_context(zip_info, self.max_build_context_bytes())
+            {
+                problems.push(ValidationProblem {
+                    field: "code.zip_file".to_string(),
+                    code: "BuildContextTooLarge".to_string(),
+                    message: e.to_string(),
+                });
+            }
+        }
+
+        if self.is_valid_function_name(&request.function_name)
+            && self.function_exists(&request.function_name).await?
+        {
+            problems.push(ValidationProblem {
+                field: "function_name".to_string(),
+                code: "FunctionAlreadyExists".to_string(),
+                message: format!("Function already exists: {}", request.function_name),
+            });
+        }
+
+        Ok(problems)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_function(&self, name: &str) -> Result<Function, LambdaError> {
+        // Try cache first
+        if let Some(cached_function) = self.cache.get_function(name) {
+            return Ok((*cached_function).clone());
+        }
+
+        // Cache miss - fetch from database
+        let row = sqlx::query("SELECT * FROM functions WHERE function_name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .ok_or_else(|| LambdaError::FunctionNotFound {
+                function_name: name.to_string(),
+            })?;
+
+        let function = self.row_to_function(&row)?;
+
+        // Cache the result
+        self.cache.set_function(name.to_string(), function.clone());
+
+        Ok(function)
+    }
+
+    #[instrument(skip(self))]
+    /// Delete a function. Routes, canaries and event source mappings that name it block
+    /// the delete, or with `integrity.cascade_function_deletes` go with it.
+    pub async fn delete_function(&self, name: &str) -> Result<(), LambdaError> {
+        let references = self.function_references(name).await?;
+        if references.is_empty() {
+            return self.remove_function(name).await;
+        }
+        if !self.config.integrity.cascade_function_deletes {
+            // A missing function is reported as such, whatever still names it
+            self.get_function(name).await?;
+            return Err(LambdaError::FunctionInUse {
+                function_name: name.to_string(),
+                references,
+            });
+        }
+        self.remove_function(name).await?;
+        // Mappings are removed with the function by their foreign key
+        let mut deleted_routes = HashSet::new();
+        for reference in references {
+            let Ok(route_id) = Uuid::parse_str(&reference.id) else {
+                continue;
+            };
+            let removed = match reference.kind {
+                ReferenceKind::ApiRoute => {
+                    deleted_routes.insert(route_id);
+                    self.delete_api_route(route_id).await
+                }
+                // The canary went with its route when both named the function
+                ReferenceKind::RouteCanary if deleted_routes.contains(&route_id) => continue,
+                ReferenceKind::RouteCanary => self.delete_api_route_canary(route_id).await,
+                ReferenceKind::EventSourceMapping => continue,
+            };
+            match removed {
+                Ok(()) => info!("Removed {} with function {}", reference, name),
+                Err(e) => warn!("Failed to remove {} of function {}: {}", reference, name, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Routes, canaries and event source mappings that name a function
+    pub async fn function_references(
+        &self,
+        name: &str,
+    ) -> Result<Vec<FunctionReference>, LambdaError> {
+        let mut references: Vec<FunctionReference> = self
+            .list_api_routes()
+            .await?
+            .routes
+            .iter()
+            .flat_map(route_references)
+            .filter(|r| r.function_name == name)
+            .collect();
+        references.extend(
+            self.list_event_source_mappings(Some(name))
+                .await?
+                .event_source_mappings
+                .iter()
+                .map(mapping_reference),
+        );
+        Ok(references)
+    }
+
+    /// Routes and canaries naming functions that do not exist, and mappings left without
+    /// their function (reported with its id as `function_name`)
+    pub async fn consistency_report(&self) -> Result<ConsistencyReport, LambdaError> {
+        let functions: HashSet<String> = sqlx::query_scalar("SELECT function_name FROM functions")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .into_iter()
+            .collect();
+        let routes = self.list_api_routes().await?.routes;
+        let mut dangling = dangling_route_references(&routes, &functions);
+
+        let rows = sqlx::query(
+            r#"SELECT m.*, m.function_id AS function_name FROM event_source_mappings m
+               LEFT JOIN functions f ON f.function_id = m.function_id
+               WHERE f.function_id IS NULL"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        dangling.extend(rows.iter().map(|row| {
+            mapping_reference(&row_to_event_source_mapping(&self.config.identity, row))
+        }));
+        Ok(ConsistencyReport {
+            checked_at: Utc::now(),
+            dangling,
+        })
+    }
+
+    async fn run_consistency_checks(&self) {
+        let interval = std::time::Duration::from_secs(self.config.integrity.check_interval_secs);
+        loop {
+            tokio::time::sleep(interval).await;
+            match self.consistency_report().await {
+                Ok(report) => {
+                    for reference in report.dangling {
+                        warn!(
+                            "{} names function {}, which does not exist",
+                            reference, reference.function_name
+                        );
+                    }
+                }
+                Err(e) => error!("Failed to check route and mapping references: {}", e),
+            }
+        }
+    }
+
+    /// Delete a function and everything keyed by its id, leaving what names it alone
+    async fn remove_function(&self, name: &str) -> Result<(), LambdaError> {
+        // Mark function for deletion immediately to reject new invocations
+        self.mark_function_for_deletion(name);
+
+        // Get function first to get function_id for cache invalidation and image cleanup
+        let function = self.get_function(name).await.ok();
+
+        let result = sqlx::query("DELETE FROM functions WHERE function_name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::FunctionNotFound {
+                function_name: name.to_string(),
+            });
+        }
+
+        // Clean up containers and Docker image if function exists
+        if let Some(func) = &function {
+            self.warm_pool.set_lifecycle(func.function_id, None);
+            self.remove_containers(func).await;
+            self.remove_image(&func.function_name, &func.code_sha256)
+                .await;
+        }
+
+        // Note: Pending requests will naturally fail when containers are terminated
+        // TODO: Implement proper pending request cleanup by function ID
+
+        // Invalidate cache
+        self.cache.invalidate_function(name);
+        if let Some(func) = function {
+            self.cache
+                .invalidate_concurrency(&func.function_id.to_string());
+            self.cache
+                .invalidate_env_vars(&func.function_id.to_string());
+        }
+        self.warmup_tracker.remove_function(name);
+        self.throttles.remove_function(name);
+        self.rollouts.remove(name);
+        self.latency_tracker.remove_function(name);
+
+        // Unmark function for deletion
+        self.unmark_function_for_deletion(name);
+
+        info!("Deleted function: {}", name);
+        self.webhooks.emit(
+            WebhookEventType::FunctionDeleted,
+            Some(name),
+            serde_json::json!({}),
+        );
+        Ok(())
+    }
+
+    /// Stop and remove the containers of a function, so its next invocation starts cold
+    #[instrument(skip(self))]
+    pub async fn drain_function(&self, name: &str) -> Result<(), LambdaError> {
+        let function = self.get_function(name).await?;
+        self.remove_containers(&function).await;
+        Ok(())
+    }
+
+    /// Stop and remove all containers of a function (best effort)
+    async fn remove_containers(&self, func: &Function) {
+        let container_ids = self.warm_pool.drain_by_function_id(func.function_id).await;
+        for container_id in container_ids {
+            // Try to stop the container first (best effort)
+            if let Err(e) = self.invoker.stop_container(&container_id).await {
+                debug!(
+                    "Failed to stop container {} (may already be stopped): {}",
+                    container_id, e
+                );
+            }
+
+            // Remove the container
+            if let Err(e) = self.invoker.remove_container(&container_id).await {
+                debug!(
+                    "Failed to remove container {} (may already be removed): {}",
+                    container_id, e
+                );
+            } else {
+                info!("Removed container: {}", container_id);
+            }
+        }
+    }
+
+    /// Remove the Docker image built from a function's code (ignore errors if it doesn't exist)
+    async fn remove_image(&self, function_name: &str, code_sha256: &str) {
+        let image_ref = format!("lambda-home/{function_name}:{code_sha256}");
+        if let Err(e) = self.invoker.remove_image(&image_ref, false).await {
+            info!(
+                "Failed to remove Docker image {} (may not exist): {}",
+                image_ref, e
+            );
+        } else {
+            info!("Removed Docker image: {}", image_ref);
+        }
+    }
+
+    /// Move a function to cold storage: its containers are drained and the images of its
+    /// code and published versions removed, while the code itself is kept. Invoking or
+    /// unarchiving it rebuilds the image.
+    #[instrument(skip(self))]
+    pub async fn archive_function(
+        &self,
+        name: &str,
+        reason: &str,
+    ) -> Result<Function, LambdaError> {
+        let function = self.get_function(name).await?;
+        if function.state == FunctionState::Archived {
+            return Ok(function);
+        }
+        if function.state == FunctionState::Pending {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!("Function {name} is still being created"),
+            });
+        }
+
+        self.remove_containers(&function).await;
+        let mut code_sha256s: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT code_sha256 FROM versions WHERE function_id = ?")
+                .bind(function.function_id)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        code_sha256s.push(function.code_sha256.clone());
+        code_sha256s.sort();
+        code_sha256s.dedup();
+        for code_sha256 in code_sha256s.iter().filter(|sha| !sha.is_empty()) {
+            self.remove_image(name, code_sha256).await;
+        }
+
+        sqlx::query(
+            "UPDATE functions SET state = ?, state_reason = ?, state_reason_code = ? \
+             WHERE function_id = ?",
+        )
+        .bind(serde_json::to_string(&FunctionState::Archived).unwrap_or_default())
+        .bind(reason)
+        .bind("Archived")
+        .bind(function.function_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.cache.invalidate_function(name);
+        info!("Archived function {}: {}", name, reason);
+        self.webhooks.emit(
+            WebhookEventType::FunctionUpdated,
+            Some(name),
+            serde_json::json!({ "change": "archived" }),
+        );
+        self.get_function(name).await
+    }
+
+    /// Bring an archived function back by rebuilding its image; other functions are
+    /// returned as they are
+    #[instrument(skip(self))]
+    pub async fn unarchive_function(&self, name: &str) -> Result<Function, LambdaError> {
+        let function = self.get_function(name).await?;
+        if function.state != FunctionState::Archived {
+            return Ok(function);
+        }
+        let image_ref = function.image_ref();
+        // A successful build activates the function again
+        self.build_function_image(&function, &image_ref, BuildPriority::Normal)
+            .await?;
+        self.cache.invalidate_function(name);
+        info!("Unarchived function {}", name);
+        self.get_function(name).await
+    }
+
+    /// Archive functions that have not been invoked (or changed) for `archive.idle_days`,
+    /// returning their names
+    #[instrument(skip(self))]
+    pub async fn archive_idle_functions(&self) -> Result<Vec<String>, LambdaError> {
+        let Some(idle_days) = self.config.archive.idle_days else {
+            return Ok(Vec::new());
+        };
+        let cutoff = Utc::now() - chrono::Duration::days(idle_days as i64);
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT f.function_name
+             FROM functions f
+             LEFT JOIN (SELECT function_id, MAX(start_time) AS last_invoked
+                        FROM executions GROUP BY function_id) e
+               ON e.function_id = f.function_id
+             WHERE f.state IN (?, ?, ?)
+               AND COALESCE(e.last_invoked, f.last_modified) <= ?",
+        )
+        .bind(serde_json::to_string(&FunctionState::Active).unwrap_or_default())
+        .bind(serde_json::to_string(&FunctionState::Inactive).unwrap_or_default())
+        .bind(serde_json::to_string(&FunctionState::Failed).unwrap_or_default())
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let reason = format!("The function was not invoked for {idle_days} days.");
+        let mut archived = Vec::new();
+        for name in names {
+            match self.archive_function(&name, &reason).await {
+                Ok(_) => archived.push(name),
+                Err(e) => warn!("Failed to archive idle function {}: {}", name, e),
+            }
+        }
+        Ok(archived)
+    }
+
+    async fn run_archiver(&self) {
+        let interval =
+            std::time::Duration::from_secs(self.config.archive.check_interval_secs.max(60));
+        loop {
+            tokio::time::sleep(interval).await;
+            if let Err(e) = self.archive_idle_functions().await {
+                error!("Failed to archive idle functions: {}", e);
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn cleanup_orphaned_images(&self) -> Result<usize, LambdaError> {
+        // Get all current function image references (every function, not just the first page,
+        // and the code of their published versions)
+        let functions: Vec<(String, String)> = sqlx::query_as(
+            "SELECT function_name, code_sha256 FROM functions
+             UNION
+             SELECT f.function_name, v.code_sha256
+             FROM versions v JOIN functions f ON f.function_id = v.function_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let active_image_refs: std::collections::HashSet<String> = functions
+            .into_iter()
+            .map(|(function_name, code_sha256)| {
+                format!("lambda-home/{function_name}:{code_sha256}")
+            })
+            .collect();
+
+        // Get all Lambda@Home Docker images
+        let all_lambda_images =
+            self.invoker
+                .list_lambda_images()
+                .await
+                .map_err(|e| LambdaError::InternalError {
+                    reason: format!("Failed to list Docker images: {e}"),
+                })?;
+
+        // Find orphaned images (images not referenced by any function)
+        let orphaned_images: Vec<String> = all_lambda_images
+            .into_iter()
+            .filter(|image_ref| !active_image_refs.contains(image_ref))
+            .collect();
+
+        let mut removed_count = 0;
+        for image_ref in orphaned_images {
+            if let Err(e) = self.invoker.remove_image(&image_ref, false).await {
+                info!("Failed to remove orphaned image {}: {}", image_ref, e);
+            } else {
+                info!("Removed orphaned Docker image: {}", image_ref);
+                removed_count += 1;
+            }
+        }
+
+        info!("Cleaned up {} orphaned Docker images", removed_count);
+        Ok(removed_count)
+    }
+
+    /// Delete stored ZIPs that no function or published version references
+    #[instrument(skip(self))]
+    pub async fn cleanup_unreferenced_artifacts(&self) -> Result<usize, LambdaError> {
+        let keep: HashSet<String> = sqlx::query_scalar(
+            "SELECT code_sha256 FROM functions UNION SELECT code_sha256 FROM versions",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .into_iter()
+        .collect();
+
+        let mut packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+        let (removed, _) = packaging_service.remove_unreferenced_zips(&keep)?;
+        Ok(removed)
+    }
+
+    /// Refuse a build up front when the data dir or Docker root is critically low on space
+    pub fn check_build_capacity(&self) -> Result<(), LambdaError> {
+        self.disk_monitor.check_build_capacity()
+    }
+
+    /// Latest disk pressure measurement for the data dir and Docker root
+    pub fn disk_status(&self) -> DiskStatus {
+        self.disk_monitor.status()
+    }
+
+    /// Retries of container calls and the circuit breaker around them
+    pub fn docker_api_status(&self) -> DockerApiStatus {
+        self.invoker.docker_api_status()
+    }
+
+    /// Schema version of the database and migrations not yet run against it
+    pub async fn schema_status(&self) -> Result<SchemaStatus, LambdaError> {
+        migrations::schema_status(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)
+    }
+
+    /// Size of the database, rows per table, and when it was last pruned and vacuumed
+    pub async fn database_status(&self) -> Result<DatabaseStatus, LambdaError> {
+        self.maintenance.status().await
+    }
+
+    /// Every feature flag with its configured and current value
+    pub fn list_feature_flags(&self) -> ListFeatureFlagsResponse {
+        ListFeatureFlagsResponse {
+            flags: self.features.list(),
+        }
+    }
+
+    /// Turn a runtime-safe feature flag on or off until reset or restart
+    pub fn set_feature_flag(
+        &self,
+        name: &str,
+        enabled: bool,
+    ) -> Result<FeatureFlagStatus, LambdaError> {
+        let status = self.features.set(parse_feature_flag(name)?, enabled)?;
+        info!("Feature flag {} set to {}", name, enabled);
+        Ok(status)
+    }
+
+    /// Put a feature flag back to its configured value
+    pub fn reset_feature_flag(&self, name: &str) -> Result<FeatureFlagStatus, LambdaError> {
+        let status = self.features.reset(parse_feature_flag(name)?);
+        info!("Feature flag {} reset to {}", name, status.enabled);
+        Ok(status)
+    }
+
+    /// Version, features, runtimes, limits and container backend of this server
+    pub async fn system_info(&self) -> SystemInfo {
+        let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+        let mut runtimes = Vec::with_capacity(lambda_packaging::SUPPORTED_RUNTIMES.len());
+        for runtime in lambda_packaging::SUPPORTED_RUNTIMES {
+            let Some(base_image) = lambda_packaging::base_image(runtime) else {
+                continue;
+            };
+            let base_image_digest = packaging_service
+                .base_image_digest(base_image)
+                .await
+                .unwrap_or(None);
+            runtimes.push(RuntimeInfo {
+                runtime: runtime.to_string(),
+                base_image: base_image.to_string(),
+                base_image_digest,
+            });
+        }
+        let (docker, error) = match self.invoker.docker_version().await {
+            Ok(version) => (Some(version), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let sandbox = self.invoker.sandbox_profiles();
+        SystemInfo {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            commit: system_info::BUILD_COMMIT.map(str::to_string),
+            schema_version: migrations::SCHEMA_VERSION,
+            features: system_info::enabled_features(&self.config, &self.features),
+            runtimes,
+            limits: SystemLimits {
+                max_global_concurrency: self.config.limits.max_global_concurrency,
+                host_memory_budget_mb: self.config.limits.host_memory_budget_mb,
+                max_request_body_size_mb: self.config.server.max_request_body_size_mb,
+                max_code_size_bytes: validation::MAX_ZIP_SIZE_BYTES,
+                max_build_context_bytes: self.max_build_context_bytes(),
+                min_timeout_secs: validation::MIN_TIMEOUT_SECS,
+                max_timeout_secs: validation::MAX_TIMEOUT_SECS,
+                min_memory_mb: validation::MIN_MEMORY_MB,
+                max_memory_mb: validation::MAX_MEMORY_MB,
+                quotas: self.config.quotas.clone(),
+            },
+            backend: BackendInfo {
+                kind: "docker".to_string(),
+                host: self.config.docker.host.clone(),
+                docker,
+                error,
+                sandbox_profiles: sandbox.names(),
+                default_sandbox_profile: sandbox.default_profile().to_string(),
+            },
+        }
+    }
+
+    /// Measure free disk and, under pressure, remove orphaned images, dangling layers
+    /// and unreferenced artifacts before measuring again
+    pub async fn check_disk_pressure(&self) -> DiskStatus {
+        let status = self.disk_monitor.refresh();
+        if self.disk_monitor.escalated(status.pressure) {
+            self.webhooks.emit(
+                WebhookEventType::AlertFired,
+                None,
+                serde_json::json!({ "alert": "disk_pressure", "disk": status }),
+            );
+        }
+        if status.pressure == DiskPressure::Ok {
+            return status;
+        }
+
+        warn!(
+            "Disk pressure {:?}, collecting unused images and build artifacts",
+            status.pressure
+        );
+        if let Err(e) = self.cleanup_orphaned_images().await {
+            warn!("Orphaned image cleanup failed: {}", e);
+        }
+        match self.invoker.prune_dangling_images().await {
+            Ok(bytes) => info!("Pruned dangling images, reclaimed {} bytes", bytes),
+            Err(e) => warn!("Dangling image prune failed: {}", e),
+        }
+        if let Err(e) = self.cleanup_unreferenced_artifacts().await {
+            warn!("Artifact cleanup failed: {}", e);
+        }
+        self.disk_monitor.record_gc();
+        self.disk_monitor.refresh()
+    }
+
+    pub fn replication_status(&self) -> ReplicationStatus {
+        self.replicator.status()
+    }
+
+    /// Bring a function declared in the config file in line with its declaration. Its code
+    /// and runtime cannot be changed in place, so a change to either recreates the function.
+    #[instrument(skip(self, spec), fields(function_name = %spec.name))]
+    pub async fn apply_static_function(
+        &self,
+        spec: &StaticFunctionConfig,
+    ) -> Result<StaticFunctionOutcome, LambdaError> {
+        let zip_data = load_static_code(spec)?;
+        let existing = match self.get_function(&spec.name).await {
+            Ok(function) => Some(function),
+            Err(LambdaError::FunctionNotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+        match existing {
+            None => {
+                self.create_function(static_create_request(spec, &zip_data))
+                    .await?;
+                Ok(StaticFunctionOutcome::Created)
+            }
+            Some(function)
+                if function.runtime != spec.runtime
+                    || function.code_sha256 != code_sha256(&zip_data) =>
+            {
+                // Routes name the function, so they keep pointing at the new one
+                self.remove_function(&spec.name).await?;
+                self.create_function(static_create_request(spec, &zip_data))
+                    .await?;
+                Ok(StaticFunctionOutcome::Recreated)
+            }
+            Some(function) => match static_config_update(spec, &function) {
+                Some(request) => {
+                    self.update_function_configuration_by(
+                        &spec.name,
+                        request,
+                        Some(STATIC_FUNCTION_ACTOR),
+                    )
+                    .await?;
+                    Ok(StaticFunctionOutcome::Updated)
+                }
+                None => Ok(StaticFunctionOutcome::Unchanged),
+            },
+        }
+    }
+
+    async fn apply_static_functions(&self) {
+        for spec in &self.config.functions {
+            match self.apply_static_function(spec).await {
+                Ok(StaticFunctionOutcome::Unchanged) => {
+                    debug!("Function {} matches the config file", spec.name)
+                }
+                Ok(outcome) => info!("Function {} from the config file: {:?}", spec.name, outcome),
+                Err(e) => error!(
+                    "Failed to apply function {} from the config file: {}",
+                    spec.name, e
+                ),
+            }
+        }
+    }
+
+    /// Whether this instance can run invocations: Docker answers and the disk is not
+    /// critically low
+    pub async fn check_instance_health(&self) -> InstanceHealth {
+        let docker = self.invoker.docker_version().await;
+        let docker_breaker = self.invoker.docker_api_status().state;
+        let disk_pressure = self.disk_status().pressure;
+        let reason = match &docker {
+            Err(e) => Some(format!("Docker is unreachable: {e}")),
+            Ok(_) if docker_breaker == DockerBreakerState::Open => {
+                Some("Docker calls are suspended after repeated failures".to_string())
+            }
+            Ok(_) if disk_pressure == DiskPressure::Critical => {
+                Some("disk space is critically low".to_string())
+            }
+            Ok(_) => None,
+        };
+        InstanceHealth {
+            healthy: reason.is_none(),
+            docker: docker.is_ok(),
+            docker_breaker,
+            disk_pressure,
+            reason,
+            checked_at: Utc::now(),
+        }
+    }
+
+    /// Record the health the peer reported and answer with this instance's
+    pub async fn receive_peer_health(&self, health: InstanceHealth) -> InstanceHealth {
+        self.replicator.record_peer_health(health);
+        match self.replicator.local_health() {
+            Some(local) => local,
+            None => self.check_instance_health().await,
+        }
+    }
+
+    /// The replication peer to forward an invoke to, while this instance is unhealthy and
+    /// failover is enabled
+    pub fn failover_peer(&self) -> Option<PeerClient> {
+        self.replicator.failover_peer().cloned()
+    }
+
+    pub fn record_forwarded_invoke(&self, function_name: &str, failed: bool) {
+        self.replicator.record_forward(function_name, failed);
+    }
+
+    async fn run_health_exchange(&self) {
+        let Some(peer) = self.replicator.peer().cloned() else {
+            return;
+        };
+        let interval =
+            std::time::Duration::from_secs(self.config.replication.health_interval_secs.max(1));
+        loop {
+            let local = self.check_instance_health().await;
+            self.replicator.set_local_health(local.clone());
+            match peer.exchange_health(&local).await {
+                Ok(Some(health)) => self.replicator.record_peer_health(health),
+                Ok(None) => debug!("Replication peer does not exchange health"),
+                Err(e) => {
+                    debug!("Health exchange with the replication peer failed: {}", e);
+                    self.replicator.record_peer_health(InstanceHealth {
+                        healthy: false,
+                        docker: false,
+                        docker_breaker: DockerBreakerState::Closed,
+                        disk_pressure: DiskPressure::Ok,
+                        reason: Some(format!("unreachable: {e}")),
+                        checked_at: Utc::now(),
+                    });
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Bring the replication peer in line with the local functions, their code and routes.
+    /// Items changed on the peer since the last round are reported as conflicts and left
+    /// alone unless `force`.
+    #[instrument(skip(self))]
+    pub async fn replicate(&self, force: bool) -> Result<ReplicationStatus, LambdaError> {
+        let Some(peer) = self.replicator.peer() else {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Replication is not enabled".to_string(),
+            });
+        };
+        let _round = self.replicator.lock_round().await;
+        let started = Utc::now();
+        let result = match self.replicate_functions(peer, force).await {
+            Ok(mut items) if self.config.replication.routes => {
+                self.replicate_routes(peer, force).await.map(|routes| {
+                    items.extend(routes);
+                    items
+                })
+            }
+            other => other,
+        };
+        Ok(self.replicator.finish_round(started, result))
+    }
+
+    async fn run_replication(&self) {
+        let interval = std::time::Duration::from_secs(self.config.replication.interval_secs.max(1));
+        loop {
+            tokio::time::sleep(interval).await;
+            match self.replicate(false).await {
+                Ok(status) => {
+                    if let Some(e) = status.last_error {
+                        warn!("Replication round failed: {}", e);
+                    }
+                }
+                Err(e) => warn!("Replication round failed: {}", e),
+            }
+        }
+    }
+
+    async fn replicate_functions(
+        &self,
+        peer: &PeerClient,
+        force: bool,
+    ) -> Result<Vec<ReplicationItem>, LambdaError> {
+        let rows = sqlx::query("SELECT * FROM functions")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        let mut local = BTreeMap::new();
+        for row in &rows {
+            let function = self.row_to_function(row)?;
+            local.insert(function.function_name.clone(), function);
+        }
+        let synced = self
+            .load_replication_state(ReplicatedKind::Function)
+            .await?;
+        let names: BTreeSet<String> = local.keys().chain(synced.keys()).cloned().collect();
+
+        let mut items = Vec::new();
+        for name in names {
+            let previous = synced.get(&name);
+            let item = self
+                .replicate_function(peer, &name, local.get(&name), previous, force)
+                .await
+                .unwrap_or_else(|e| {
+                    Some(ReplicationItem {
+                        kind: ReplicatedKind::Function,
+                        id: name.clone(),
+                        state: ReplicationState::Failed,
+                        message: Some(e.to_string()),
+                        synced_at: previous.map(|p| p.synced_at),
+                    })
+                });
+            items.extend(item);
+        }
+        Ok(items)
+    }
+
+    async fn replicate_function(
+        &self,
+        peer: &PeerClient,
+        name: &str,
+        local: Option<&Function>,
+        previous: Option<&SyncedItem>,
+        force: bool,
+    ) -> Result<Option<ReplicationItem>, LambdaError> {
+        let remote = peer.get_function(name).await?;
+        let fingerprint = local.map(function_fingerprint);
+        let action = plan_sync(
+            fingerprint.as_deref(),
+            remote.as_ref().map(function_fingerprint).as_deref(),
+            previous.map(|p| p.fingerprint.as_str()),
+            force,
+        );
+        let item = |state, message, synced_at| {
+            Some(ReplicationItem {
+                kind: ReplicatedKind::Function,
+                id: name.to_string(),
+                state,
+                message,
+                synced_at,
+            })
+        };
+        let previous_sync = previous.map(|p| p.synced_at);
+        let (local, fingerprint) = match (action, local.zip(fingerprint)) {
+            (SyncAction::InSync, _) => {
+                return Ok(item(ReplicationState::InSync, None, previous_sync))
+            }
+            (SyncAction::Conflict(reason), _) => {
+                return Ok(item(
+                    ReplicationState::Conflict,
+                    Some(reason),
+                    previous_sync,
+                ))
+            }
+            (SyncAction::Delete, _) => {
+                peer.delete_function(name).await?;
+                self.forget_replication_state(ReplicatedKind::Function, name)
+                    .await?;
+                return Ok(None);
+            }
+            (SyncAction::Create, Some((local, fingerprint))) => {
+                peer.create_function(&self.replica_create_request(local)?)
+                    .await?;
+                (local, fingerprint)
+            }
+            (SyncAction::Update, Some((local, fingerprint))) => {
+                self.update_replica(peer, local, remote.as_ref()).await?;
+                (local, fingerprint)
+            }
+            (SyncAction::Adopt, Some(local)) => local,
+            // Create, Update and Adopt always have a local definition
+            _ => {
+                self.forget_replication_state(ReplicatedKind::Function, name)
+                    .await?;
+                return Ok(None);
+            }
+        };
+        let synced_at = self
+            .save_replication_state(ReplicatedKind::Function, name, name, None, &fingerprint)
+            .await?;
+        debug!("Replicated function {} to the peer", local.function_name);
+        Ok(item(ReplicationState::InSync, None, Some(synced_at)))
+    }
+
+    /// Make the peer's copy of a function match `local`
+    async fn update_replica(
+        &self,
+        peer: &PeerClient,
+        local: &Function,
+        remote: Option<&Function>,
+    ) -> Result<(), LambdaError> {
+        let name = local.function_name.as_str();
+        match remote {
+            Some(remote)
+                if remote.runtime == local.runtime
+                    && (remote.code_sha256 == local.code_sha256
+                        || local.code_sha256.is_empty()) =>
+            {
+                let request = UpdateFunctionConfigurationRequest {
+                    role: local.role.clone(),
+                    handler: Some(local.handler.clone()),
+                    description: local.description.clone(),
+                    timeout: Some(local.timeout),
+                    memory_size: Some(local.memory_size),
+                    environment: Some(local.environment.clone()),
+                };
+                peer.update_function_configuration(name, &request).await
+            }
+            // The runtime cannot be changed in place, and UpdateFunctionCode keeps the old code
+            Some(_) => {
+                peer.delete_function(name).await?;
+                peer.create_function(&self.replica_create_request(local)?)
+                    .await
+            }
+            None => {
+                peer.create_function(&self.replica_create_request(local)?)
+                    .await
+            }
+        }
+    }
+
+    fn replica_create_request(
+        &self,
+        local: &Function,
+    ) -> Result<CreateFunctionRequest, LambdaError> {
+        let zip_file = if local.code_sha256.is_empty() || local.package_type == PackageType::Image {
+            None
+        } else {
+            Some(self.code_base64(local)?)
+        };
+        Ok(CreateFunctionRequest {
+            function_name: local.function_name.clone(),
+            runtime: local.runtime.clone(),
+            role: local.role.clone(),
+            handler: local.handler.clone(),
+            code: FunctionCode {
+                zip_file,
+                s3_bucket: None,
+                s3_key: None,
+                s3_object_version: None,
+                workspace_package: None,
+                image_uri: local.image_uri.clone(),
+            },
+            description: local.description.clone(),
+            timeout: Some(local.timeout),
+            memory_size: Some(local.memory_size),
+            environment: Some(local.environment.clone()),
+            publish: None,
+            package_type: Some(local.package_type),
+            tags: None,
+        })
+    }
+
+    fn code_base64(&self, function: &Function) -> Result<String, LambdaError> {
+        let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+        let zip_data = packaging_service.load_zip(&function.code_sha256)?;
+        Ok(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            zip_data,
+        ))
+    }
+
+    async fn replicate_routes(
+        &self,
+        peer: &PeerClient,
+        force: bool,
+    ) -> Result<Vec<ReplicationItem>, LambdaError> {
+        let local: BTreeMap<String, ApiRoute> = self
+            .list_api_routes()
+            .await?
+            .routes
+            .into_iter()
+            .map(|route| (route.route_id.to_string(), route))
+            .collect();
+        let remote = peer.list_routes().await?;
+        let synced = self.load_replication_state(ReplicatedKind::Route).await?;
+        let ids: BTreeSet<String> = local.keys().chain(synced.keys()).cloned().collect();
+
+        let mut items = Vec::new();
+        for id in ids {
+            let route = local.get(&id);
+            let previous = synced.get(&id);
+            // The peer's copy is the route pushed last round, else one with the same method
+            // and path
+            let peer_route = match previous.and_then(|p| p.peer_id.as_deref()) {
+                Some(peer_id) => remote.iter().find(|r| r.route_id.to_string() == peer_id),
+                None => route.and_then(|local| {
+                    remote
+                        .iter()
+                        .find(|r| r.path == local.path && r.method == local.method)
+                }),
+            };
+            let item = self
+                .replicate_route(peer, &id, route, peer_route, previous, force)
+                .await
+                .unwrap_or_else(|e| {
+                    Some(ReplicationItem {
+                        kind: ReplicatedKind::Route,
+                        id: route
+                            .map(route_label)
+                            .or_else(|| previous.map(|p| p.label.clone()))
+                            .unwrap_or_else(|| id.clone()),
+                        state: ReplicationState::Failed,
+                        message: Some(e.to_string()),
+                        synced_at: previous.map(|p| p.synced_at),
+                    })
+                });
+            items.extend(item);
+        }
+        Ok(items)
+    }
+
+    async fn replicate_route(
+        &self,
+        peer: &PeerClient,
+        id: &str,
+        route: Option<&ApiRoute>,
+        peer_route: Option<&ApiRoute>,
+        previous: Option<&SyncedItem>,
+        force: bool,
+    ) -> Result<Option<ReplicationItem>, LambdaError> {
+        let action = plan_sync(
+            route.map(route_fingerprint).as_deref(),
+            peer_route.map(route_fingerprint).as_deref(),
+            previous.map(|p| p.fingerprint.as_str()),
+            force,
+        );
+        let label = route
+            .map(route_label)
+            .or_else(|| previous.map(|p| p.label.clone()))
+            .unwrap_or_else(|| id.to_string());
+        let item = |state, message, synced_at| {
+            Some(ReplicationItem {
+                kind: ReplicatedKind::Route,
+                id: label.clone(),
+                state,
+                message,
+                synced_at,
+            })
+        };
+        let previous_sync = previous.map(|p| p.synced_at);
+        let peer_id = match (action, route) {
+            (SyncAction::InSync, _) => {
+                return Ok(item(ReplicationState::InSync, None, previous_sync))
+            }
+            (SyncAction::Conflict(reason), _) => {
+                return Ok(item(
+                    ReplicationState::Conflict,
+                    Some(reason),
+                    previous_sync,
+                ))
+            }
+            (SyncAction::Delete, _) => {
+                if let Some(peer_route) = peer_route {
+                    peer.delete_route(peer_route.route_id).await?;
+                }
+                self.forget_replication_state(ReplicatedKind::Route, id)
+                    .await?;
+                return Ok(None);
+            }
+            (SyncAction::Adopt, Some(_)) => peer_route.map(|r| r.route_id),
+            (SyncAction::Create | SyncAction::Update, Some(route)) => {
+                // Routes cannot be edited, so an update replaces the peer's route
+                if let Some(peer_route) = peer_route {
+                    peer.delete_route(peer_route.route_id).await?;
+                }
+                let request = CreateApiRouteRequest {
+                    path: route.path.clone(),
+                    method: route.method.clone(),
+                    function_name: route.function_name.clone(),
+                    canary: None,
+                };
+                Some(peer.create_route(&request).await?.route_id)
+            }
+            _ => {
+                self.forget_replication_state(ReplicatedKind::Route, id)
+                    .await?;
+                return Ok(None);
+            }
+        };
+        let fingerprint = route.map(route_fingerprint).unwrap_or_default();
+        let synced_at = self
+            .save_replication_state(
+                ReplicatedKind::Route,
+                id,
+                &label,
+                peer_id.map(|p| p.to_string()).as_deref(),
+                &fingerprint,
+            )
+            .await?;
+        Ok(item(ReplicationState::InSync, None, Some(synced_at)))
+    }
+
+    async fn load_replication_state(
+        &self,
+        kind: ReplicatedKind,
+    ) -> Result<HashMap<String, SyncedItem>, LambdaError> {
+        let rows = sqlx::query(
+            "SELECT local_id, label, peer_id, fingerprint, synced_at FROM replication_state WHERE kind = ?",
+        )
+        .bind(kind.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let item = SyncedItem {
+                    label: row.get("label"),
+                    peer_id: row.get("peer_id"),
+                    fingerprint: row.get("fingerprint"),
+                    synced_at: row.get("synced_at"),
+                };
+                (row.get("local_id"), item)
+            })
+            .collect())
+    }
+
+    async fn save_replication_state(
+        &self,
+        kind: ReplicatedKind,
+        local_id: &str,
+        label: &str,
+        peer_id: Option<&str>,
+        fingerprint: &str,
+    ) -> Result<DateTime<Utc>, LambdaError> {
+        let synced_at = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO replication_state(kind, local_id, label, peer_id, fingerprint, synced_at)
+               VALUES(?, ?, ?, ?, ?, ?)
+               ON CONFLICT(kind, local_id) DO UPDATE SET
+                 label = excluded.label,
+                 peer_id = excluded.peer_id,
+                 fingerprint = excluded.fingerprint,
+                 synced_at = excluded.synced_at"#,
+        )
+        .bind(kind.as_str())
+        .bind(local_id)
+        .bind(label)
+        .bind(peer_id)
+        .bind(fingerprint)
+        .bind(synced_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(synced_at)
+    }
+
+    async fn forget_replication_state(
+        &self,
+        kind: ReplicatedKind,
+        local_id: &str,
+    ) -> Result<(), LambdaError> {
+        sqlx::query("DELETE FROM replication_state WHERE kind = ? AND local_id = ?")
+            .bind(kind.as_str())
+            .bind(local_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    async fn run_disk_monitor(&self) {
+        match self.invoker.docker_root_dir().await {
+            Ok(root) => self.disk_monitor.set_docker_root(root),
+            Err(e) => debug!("Docker root unavailable for disk monitoring: {}", e),
+        }
+        let interval = std::time::Duration::from_millis(
+            self.disk_monitor.config().check_interval_ms.max(1000),
+        );
+        loop {
+            self.check_disk_pressure().await;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_functions(
+        &self,
+        query: &ListFunctionsQuery,
+    ) -> Result<ListFunctionsResponse, LambdaError> {
+        let limit = query
+            .max_items
+            .unwrap_or(DEFAULT_LIST_MAX_ITEMS)
+            .clamp(1, MAX_LIST_MAX_ITEMS) as i64;
+        let marker = query
+            .marker
+            .as_deref()
+            .map(|m| ListMarker::decode(m, query.sort_by, query.order))
+            .transpose()?;
+        let state = query
+            .state
+            .as_ref()
+            .map(|state| serde_json::to_string(state).unwrap_or_default());
+
+        // Execution counts are only joined in when they decide the order
+        let from = match query.sort_by {
+            FunctionSortKey::InvocationCount => {
+                "functions f
+                 LEFT JOIN (SELECT function_id, COUNT(*) AS invocation_count
+                            FROM executions GROUP BY function_id) ec
+                   ON ec.function_id = f.function_id"
+            }
+            _ => "functions f",
+        };
+        let tags = serde_json::to_string(&query.tags).unwrap_or_default();
+        let filters = format!(
+            "(?1 IS NULL OR instr(f.function_name, ?1) = 1)
+             AND (?2 IS NULL OR f.runtime = ?2)
+             AND (?3 IS NULL OR f.state = ?3)
+             AND {}
+             AND {}",
+            tags_clause("?4"),
+            group_clause("?5")
+        );
+
+        let total_count_row = sqlx::query(&format!(
+            "SELECT COUNT(*) as count FROM functions f WHERE {filters}"
+        ))
+        .bind(&query.name_prefix)
+        .bind(&query.runtime)
+        .bind(&state)
+        .bind(&tags)
+        .bind(&query.group)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let total_count: i64 = total_count_row.get("count");
+
+        let sql = format!(
+            "SELECT f.*, {} AS sort_value FROM {from} WHERE {filters}{} ORDER BY {} LIMIT ?6",
+            sort_expression(query.sort_by),
+            match marker {
+                Some(_) => format!(" AND {}", after_marker_clause(query.sort_by, query.order)),
+                None => String::new(),
+            },
+            order_by_clause(query.sort_by, query.order),
+        );
+        // One extra row tells whether another page follows
+        let mut select = sqlx::query(&sql)
+            .bind(&query.name_prefix)
+            .bind(&query.runtime)
+            .bind(&state)
+            .bind(&tags)
+            .bind(&query.group)
+            .bind(limit + 1);
+        if let Some(marker) = &marker {
+            select = match query.sort_by {
+                FunctionSortKey::InvocationCount => {
+                    select.bind(marker.sort_value.parse::<i64>().map_err(|_| {
+                        LambdaError::InvalidRequest {
+                            reason: "Invalid marker: not produced by ListFunctions".to_string(),
+                        }
+                    })?)
+                }
+                _ => select.bind(marker.sort_value.clone()),
+            }
+            .bind(marker.function_name.clone());
+        }
+        let mut rows = select
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+
+        let next_marker = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| {
+                let sort_value = match query.sort_by {
+                    FunctionSortKey::InvocationCount => row.get::<i64, _>("sort_value").to_string(),
+                    _ => row.get::<String, _>("sort_value"),
+                };
+                ListMarker {
+                    sort_by: query.sort_by,
+                    order: query.order,
+                    sort_value,
+                    function_name: row.get("function_name"),
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+
+        let functions = rows
+            .iter()
+            .map(|row| self.row_to_function(row))
+            .collect::<Result<Vec<Function>, LambdaError>>()?;
+
+        let warm_state = if query.include_warm_state {
+            Some(self.warm_states_of(&functions).await)
+        } else {
+            None
+        };
+
+        Ok(ListFunctionsResponse {
+            functions,
+            next_marker,
+            total_count: Some(total_count as u32),
+            warm_state,
+        })
+    }
+
+    /// Warm state of each of `functions`, from one pass over the warm pool and the queues
+    async fn warm_states_of(&self, functions: &[Function]) -> BTreeMap<String, FunctionWarmState> {
+        let mut states = self.warm_pool.warm_states().await;
+        for (key, queued) in self.scheduler.queues().snapshot_sizes() {
+            states.entry(key.function_name).or_default().queued += queued as u32;
+        }
+        functions
+            .iter()
+            .map(|f| {
+                let state = states.get(&f.function_name).copied().unwrap_or_default();
+                (f.function_name.clone(), state)
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self, _request))]
+    pub async fn update_function_code(
+        &self,
+        name: &str,
+        _request: UpdateFunctionCodeRequest,
+    ) -> Result<Function, LambdaError> {
+        // TODO: Implement code update logic
+        let mut function = self.get_function(name).await?;
+        function.last_modified = Utc::now();
+
+        sqlx::query("UPDATE functions SET last_modified = ? WHERE function_name = ?")
+            .bind(function.last_modified)
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+
+        // Invalidate cache since function was updated
+        self.cache.invalidate_function(name);
+        self.cache
+            .invalidate_env_vars(&function.function_id.to_string());
+        self.webhooks.emit(
+            WebhookEventType::FunctionUpdated,
+            Some(name),
+            serde_json::json!({ "change": "code", "code_sha256": function.code_sha256 }),
+        );
+
+        Ok(function)
+    }
+
+    #[instrument(skip(self, request))]
+    pub async fn update_function_configuration(
+        &self,
+        name: &str,
+        request: UpdateFunctionConfigurationRequest,
+    ) -> Result<Function, LambdaError> {
+        self.update_function_configuration_by(name, request, None)
+            .await
+    }
+
+    /// UpdateFunctionConfiguration, attributing the change in the function's history
+    #[instrument(skip(self, request))]
+    pub async fn update_function_configuration_by(
+        &self,
+        name: &str,
+        request: UpdateFunctionConfigurationRequest,
+        changed_by: Option<&str>,
+    ) -> Result<Function, LambdaError> {
+        let before = self.get_function(name).await?;
+        let mut function = before.clone();
+
+        if let Some(role) = request.role {
+            function.role = Some(role);
+        }
+        if let Some(handler) = request.handler {
+            function.handler = handler;
+        }
+        if let Some(description) = request.description {
+            function.description = Some(description);
+        }
+        if let Some(timeout) = request.timeout {
+            function.timeout = timeout;
+        }
+        if let Some(memory_size) = request.memory_size {
+            function.memory_size = memory_size;
+        }
+        if let Some(environment) = request.environment {
+            function.environment = environment;
+        }
+
+        self.store_configuration(
+            before,
+            function,
+            ConfigChangeSource::Update,
+            None,
+            changed_by,
+        )
+        .await
+    }
+
+    /// Configuration revisions of a function, newest first
+    #[instrument(skip(self))]
+    pub async fn config_history(&self, name: &str) -> Result<ConfigHistoryResponse, LambdaError> {
+        let function = self.get_function(name).await?;
+        let rows = sqlx::query(
+            "SELECT * FROM function_config_revisions WHERE function_id = ? ORDER BY revision DESC",
+        )
+        .bind(function.function_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let revisions = rows
+            .iter()
+            .map(row_to_config_revision)
+            .collect::<Result<_, _>>()?;
+        Ok(ConfigHistoryResponse { revisions })
+    }
+
+    /// Restore the configuration recorded in `revision`. The restore is itself recorded
+    /// as a new revision, so it can be reverted in turn.
+    #[instrument(skip(self))]
+    pub async fn revert_function_configuration(
+        &self,
+        name: &str,
+        revision: u64,
+        changed_by: Option<&str>,
+    ) -> Result<Function, LambdaError> {
+        let before = self.get_function(name).await?;
+        let row = sqlx::query(
+            "SELECT * FROM function_config_revisions WHERE function_id = ? AND revision = ?",
+        )
+        .bind(before.function_id)
+        .bind(revision as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::ConfigRevisionNotFound {
+            function_name: name.to_string(),
+            revision,
+        })?;
+        let target = row_to_config_revision(&row)?.configuration;
+
+        let mut function = before.clone();
+        function.role = target.role;
+        function.handler = target.handler;
+        function.description = target.description;
+        function.timeout = target.timeout;
+        function.memory_size = target.memory_size;
+        function.environment = target.environment;
+
+        self.store_configuration(
+            before,
+            function,
+            ConfigChangeSource::Revert,
+            Some(revision),
+            changed_by,
+        )
+        .await
+    }
+
+    /// Persist a configuration change together with its history revision, then make
+    /// running containers pick it up.
+    async fn store_configuration(
+        &self,
+        before: Function,
+        mut function: Function,
+        source: ConfigChangeSource,
+        reverted_from: Option<u64>,
+        changed_by: Option<&str>,
+    ) -> Result<Function, LambdaError> {
+        let name = before.function_name.as_str();
+        if function.handler != before.handler
+            && !function.code_sha256.is_empty()
+            && function.package_type == PackageType::Zip
+        {
+            let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+            let zip_data = packaging_service.load_zip(&function.code_sha256)?;
+            let zip_info = packaging_service.process_zip(&zip_data).await?;
+            lambda_packaging::verify_handler(&function.runtime, &function.handler, &zip_info)?;
+        }
+        let changed = changed_container_settings(&before, &function);
+
+        function.last_modified = Utc::now();
+
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        sqlx::query(
+            r#"
+            UPDATE functions SET 
+                role = ?, handler = ?, description = ?, timeout = ?, 
+                memory_size = ?, environment = ?, last_modified = ?
+            WHERE function_name = ?
+            "#,
+        )
+        .bind(&function.role)
+        .bind(&function.handler)
+        .bind(&function.description)
+        .bind(function.timeout as i64)
+        .bind(function.memory_size as i64)
+        .bind(serde_json::to_string(&function.environment).unwrap_or_default())
+        .bind(function.last_modified)
+        .bind(name)
+        .execute(&mut *tx)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        insert_revision(
+            &mut *tx,
+            function.function_id,
+            Some(&FunctionConfigSnapshot::from(&before)),
+            &FunctionConfigSnapshot::from(&function),
+            source,
+            reverted_from,
+            changed_by,
+        )
+        .await?;
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+
+        // Invalidate cache since function configuration was updated
+        self.cache.invalidate_function(name);
+        self.cache
+            .invalidate_env_vars(&function.function_id.to_string());
+        // Containers keep the memory and environment they were started with
+        if !changed.is_empty() {
+            self.start_rollout(&before, &function, changed).await;
+        }
+        self.webhooks.emit(
+            WebhookEventType::FunctionUpdated,
+            Some(name),
+            serde_json::json!({ "change": "configuration" }),
+        );
+
+        Ok(function)
+    }
+
+    /// Replace the function's warm containers, which run with the settings of `before`.
+    /// Stopped ones are removed right away, since restarting them would bring the previous
+    /// settings back; running ones are replaced in the background.
+    async fn start_rollout(&self, before: &Function, function: &Function, changed: Vec<String>) {
+        let (stopped, running): (Vec<_>, Vec<_>) = self
+            .warm_pool
+            .containers_of_function(function.function_id)
+            .await
+            .into_iter()
+            .partition(|c| c.state.releases_memory());
+        for container in &stopped {
+            self.retire_container(&container.container_id).await;
+        }
+        info!(
+            "Rolling {} containers of {} onto changed {}",
+            running.len(),
+            function.function_name,
+            changed.join(", ")
+        );
+        let rollout = Rollout {
+            function_name: function.function_name.clone(),
+            status: RolloutStatus::InProgress,
+            changed,
+            total: running.len(),
+            replaced: 0,
+            removed_stopped: stopped.len(),
+            started_at: Utc::now(),
+            finished_at: None,
+            error: None,
+        };
+        // Invocations already running keep the deadline they started with
+        let drain_timeout =
+            std::time::Duration::from_secs(before.timeout.max(function.timeout) + 5);
+        self.rollouts
+            .begin(rollout, function.clone(), running, drain_timeout);
+    }
+
+    /// Progress of the latest rollout of a function's containers
+    pub async fn get_rollout(&self, name: &str) -> Result<Rollout, LambdaError> {
+        self.get_function(name).await?;
+        self.rollouts
+            .get(name)
+            .ok_or_else(|| LambdaError::RolloutNotFound {
+                function_name: name.to_string(),
+            })
+    }
+
+    async fn run_rollouts(
+        self: &Arc<Self>,
+        mut tasks: tokio::sync::mpsc::UnboundedReceiver<RolloutTask>,
+    ) {
+        while let Some(task) = tasks.recv().await {
+            let control = self.clone();
+            tokio::spawn(async move { control.run_rollout(task).await });
+        }
+    }
+
+    /// Replace the task's containers `rollout.batch_size` at a time, stopping early when a
+    /// newer rollout of the function began
+    async fn run_rollout(&self, task: RolloutTask) {
+        let name = task.function.function_name.clone();
+        let fn_key = crate::queues::FnKey::for_function(&task.function);
+        for batch in task
+            .containers
+            .chunks(self.config.rollout.batch_size.max(1))
+        {
+            if !self.rollouts.is_current(&name, task.generation) {
+                return;
+            }
+            let results = futures::future::join_all(batch.iter().map(|container| {
+                self.replace_container(&task.function, &fn_key, container, task.drain_timeout)
+            }))
+            .await;
+            for result in results {
+                if let Err(e) = result {
+                    warn!("Rollout of {} failed: {}", name, e);
+                    self.rollouts.update(&name, task.generation, |rollout| {
+                        rollout.status = RolloutStatus::Failed;
+                        rollout.error = Some(e.to_string());
+                        rollout.finished_at = Some(Utc::now());
+                    });
+                    return;
+                }
+                self.rollouts
+                    .update(&name, task.generation, |rollout| rollout.replaced += 1);
+            }
+        }
+        let finished = self.rollouts.update(&name, task.generation, |rollout| {
+            rollout.status = RolloutStatus::Completed;
+            rollout.finished_at = Some(Utc::now());
+        });
+        if finished {
+            info!("Rollout of {} completed", name);
+        }
+    }
+
+    /// Start a container with the function's new settings, then drain `old` once it has
+    /// finished its invocation. The replacement starts first so the function keeps its
+    /// capacity, unless that would take it past its reserved concurrency or the memory
+    /// budget; then `old` is drained first.
+    async fn replace_container(
+        &self,
+        function: &Function,
+        fn_key: &crate::queues::FnKey,
+        old: &WarmContainer,
+        drain_timeout: std::time::Duration,
+    ) -> Result<(), LambdaError> {
+        let reserved = self
+            .get_concurrency(&function.function_name)
+            .await?
+            .reserved_concurrent_executions;
+        let running = self
+            .warm_pool
+            .containers_of_function(function.function_id)
+            .await
+            .iter()
+            .filter(|c| !c.state.releases_memory())
+            .count();
+        let mut replaced = false;
+        if reserved.is_none_or(|reserved| running < reserved as usize) {
+            match self.create_and_start_container(function, fn_key).await {
+                Ok(_) => replaced = true,
+                Err(
+                    LambdaError::HostMemoryExhausted { .. } | LambdaError::QuotaExceeded { .. },
+                ) => {
+                    debug!(
+                        "No room to start a replacement for {} first",
+                        old.container_id
+                    )
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.drain_container(&old.container_id, drain_timeout).await;
+        if !replaced {
+            self.create_and_start_container(function, fn_key).await?;
+        }
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for the container to finish its invocation, then retire it
+    async fn drain_container(&self, container_id: &str, timeout: std::time::Duration) {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !self.warm_pool.mark_stopping_unless_busy(container_id).await {
+            if tokio::time::Instant::now() >= deadline {
+                warn!(
+                    "Container {} still busy after {:?}, draining it anyway",
+                    container_id, timeout
+                );
+                let _ = self
+                    .warm_pool
+                    .set_state_by_container_id(container_id, InstanceState::Stopping)
+                    .await;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        self.retire_container(container_id).await;
+    }
+
+    /// Stop a container within its grace period and remove it from Docker and the pool
+    async fn retire_container(&self, container_id: &str) {
+        if let Err(e) = self.invoker.stop_container(container_id).await {
+            debug!(
+                "Failed to stop container {} (may already be stopped): {}",
+                container_id, e
+            );
+        }
+        let _ = self.warm_pool.remove_container_by_id(container_id).await;
+        if let Err(e) = self.invoker.remove_container(container_id).await {
+            debug!(
+                "Failed to remove container {} (may already be removed): {}",
+                container_id, e
+            );
+        } else {
+            info!("Retired container: {}", container_id);
+        }
+    }
+
+    #[instrument(skip(self, request))]
+    pub async fn publish_version(
+        &self,
+        name: &str,
+        request: PublishVersionRequest,
+    ) -> Result<Version, LambdaError> {
+        let function = self.get_function(name).await?;
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        let version = insert_next_version(&mut tx, &function, request.description).await?;
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+
+        self.webhooks.emit(
+            WebhookEventType::VersionPublished,
+            Some(name),
+            serde_json::json!({ "version": version.version, "code_sha256": version.code_sha256 }),
+        );
+        Ok(version)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_versions(
+        &self,
+        name: &str,
+        marker: Option<&String>,
+        max_items: Option<u32>,
+    ) -> Result<ListVersionsResponse, LambdaError> {
+        let function = self.get_function(name).await?;
+        let limit = max_items.unwrap_or(50).min(1000) as i64;
+        let offset = marker.and_then(|m| m.parse::<i64>().ok()).unwrap_or(0);
+
+        let rows = sqlx::query(
+            "SELECT * FROM versions WHERE function_id = ? \
+             ORDER BY CAST(version AS INTEGER) LIMIT ? OFFSET ?",
+        )
+        .bind(function.function_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let versions: Result<Vec<Version>, LambdaError> =
+            rows.iter().map(|row| self.row_to_version(row)).collect();
+
+        let next_marker = if rows.len() as i64 == limit {
+            Some((offset + limit).to_string())
+        } else {
+            None
+        };
+
+        Ok(ListVersionsResponse {
+            versions: versions?,
+            next_marker,
+        })
+    }
+
+    /// The function as `qualifier` names it: as it is now for `$LATEST` or no qualifier, as
+    /// published for a version number, and through an alias the version it routes to
+    pub async fn resolve_qualifier(
+        &self,
+        function: Function,
+        qualifier: Option<&str>,
+    ) -> Result<Function, LambdaError> {
+        match Qualifier::parse(qualifier) {
+            Qualifier::Latest => Ok(function),
+            Qualifier::Version(version) => self.function_at_version(function, version).await,
+            Qualifier::Alias(alias) => {
+                let alias = self.get_alias(&function.function_name, alias).await?;
+                let version = alias_version(&alias, rand::random::<f64>()).to_string();
+                self.function_at_version(function, &version).await
+            }
+        }
+    }
+
+    /// The function as `version` (a number or `$LATEST`) published it
+    async fn function_at_version(
+        &self,
+        function: Function,
+        version: &str,
+    ) -> Result<Function, LambdaError> {
+        if version == LATEST_VERSION {
+            return Ok(function);
+        }
+        let row = sqlx::query("SELECT * FROM versions WHERE function_id = ? AND version = ?")
+            .bind(function.function_id)
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .ok_or_else(|| LambdaError::FunctionNotFound {
+                function_name: format!("{}:{}", function.function_name, version),
+            })?;
+        Ok(function.at_version(&self.row_to_version(&row)?))
+    }
+
+    /// Refuse an alias whose routing is invalid or names versions that were never published
+    async fn check_alias_versions(
+        &self,
+        function: &Function,
+        function_version: &str,
+        routing: Option<&RoutingConfig>,
+    ) -> Result<(), LambdaError> {
+        validate_routing(function_version, routing)?;
+        let additional = routing
+            .into_iter()
+            .flat_map(|routing| routing.additional_version_weights.keys());
+        for version in std::iter::once(function_version).chain(additional.map(String::as_str)) {
+            self.function_at_version(function.clone(), version).await?;
+        }
+        Ok(())
+    }
+
+    /// The function a warm pool key's containers run, at the key's version
+    pub async fn function_for_key(
+        &self,
+        key: &crate::queues::FnKey,
+    ) -> Result<Function, LambdaError> {
+        let function = self.get_function(&key.function_name).await?;
+        self.function_at_version(function, &key.version).await
+    }
+
+    #[instrument(skip(self, request))]
+    pub async fn create_alias(
+        &self,
+        name: &str,
+        request: CreateAliasRequest,
+    ) -> Result<Alias, LambdaError> {
+        let function = self.get_function(name).await?;
+        self.check_alias_versions(
+            &function,
+            &request.function_version,
+            request.routing_config.as_ref(),
+        )
+        .await?;
+        let alias_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let alias = Alias {
+            alias_id,
+            function_id: function.function_id,
+            name: request.name,
+            function_version: request.function_version,
+            description: request.description,
+            routing_config: request.routing_config,
+            revision_id: Uuid::new_v4().to_string(),
+            last_modified: now,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO aliases (
+                alias_id, function_id, name, function_version, description,
+                routing_config, revision_id, last_modified
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(alias.alias_id)
+        .bind(alias.function_id)
+        .bind(&alias.name)
+        .bind(&alias.function_version)
+        .bind(&alias.description)
+        .bind(serde_json::to_string(&alias.routing_config).unwrap_or_default())
+        .bind(&alias.revision_id)
+        .bind(alias.last_modified)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        self.emit_alias_updated(name, &alias);
+        Ok(alias)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_alias(&self, name: &str, alias: &str) -> Result<Alias, LambdaError> {
+        let function = self.get_function(name).await?;
+
+        let row = sqlx::query("SELECT * FROM aliases WHERE function_id = ? AND name = ?")
+            .bind(function.function_id)
+            .bind(alias)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .ok_or_else(|| LambdaError::FunctionNotFound {
+                function_name: format!("{name}/{alias}"),
+            })?;
+
+        self.row_to_alias(&row)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn update_alias(
+        &self,
+        name: &str,
+        alias: &str,
+        request: UpdateAliasRequest,
+    ) -> Result<Alias, LambdaError> {
+        let mut alias_obj = self.get_alias(name, alias).await?;
+
+        if let Some(function_version) = request.function_version {
+            alias_obj.function_version = function_version;
+        }
+        if let Some(description) = request.description {
+            alias_obj.description = Some(description);
+        }
+        if let Some(routing_config) = request.routing_config {
+            alias_obj.routing_config = Some(routing_config);
+        }
+        let function = self.get_function(name).await?;
+        self.check_alias_versions(
+            &function,
+            &alias_obj.function_version,
+            alias_obj.routing_config.as_ref(),
+        )
+        .await?;
+
+        alias_obj.revision_id = Uuid::new_v4().to_string();
+        alias_obj.last_modified = Utc::now();
+
+        sqlx::query(
+            r#"
+            UPDATE aliases SET 
+                function_version = ?, description = ?, routing_config = ?,
+                revision_id = ?, last_modified = ?
+            WHERE function_id = ? AND name = ?
+            "#,
+        )
+        .bind(&alias_obj.function_version)
+        .bind(&alias_obj.description)
+        .bind(serde_json::to_string(&alias_obj.routing_config).unwrap_or_default())
+        .bind(&alias_obj.revision_id)
+        .bind(alias_obj.last_modified)
+        .bind(alias_obj.function_id)
+        .bind(alias)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        self.emit_alias_updated(name, &alias_obj);
+        Ok(alias_obj)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_alias(&self, name: &str, alias: &str) -> Result<(), LambdaError> {
+        let function = self.get_function(name).await?;
+
+        let result = sqlx::query("DELETE FROM aliases WHERE function_id = ? AND name = ?")
+            .bind(function.function_id)
+            .bind(alias)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::FunctionNotFound {
+                function_name: format!("{name}/{alias}"),
+            });
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_aliases(
+        &self,
+        name: &str,
+        marker: Option<&String>,
+        max_items: Option<u32>,
+    ) -> Result<ListAliasesResponse, LambdaError> {
+        let function = self.get_function(name).await?;
+        let limit = max_items.unwrap_or(50).min(1000) as i64;
+        let offset = marker.and_then(|m| m.parse::<i64>().ok()).unwrap_or(0);
+
+        let rows = sqlx::query(
+            "SELECT * FROM aliases WHERE function_id = ? ORDER BY name LIMIT ? OFFSET ?",
+        )
+        .bind(function.function_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let aliases: Result<Vec<Alias>, LambdaError> =
+            rows.iter().map(|row| self.row_to_alias(row)).collect();
+
+        let next_marker = if rows.len() as i64 == limit {
+            Some((offset + limit).to_string())
+        } else {
+            None
+        };
+
+        Ok(ListAliasesResponse {
+            aliases: aliases?,
+            next_marker,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn put_concurrency(
+        &self,
+        name: &str,
+        config: ConcurrencyConfig,
+    ) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let now = chrono::Utc::now();
+        let reserved = config.reserved_concurrent_executions.map(|v| v as i64);
+        sqlx::query(
+            r#"INSERT INTO function_concurrency(function_id, reserved_concurrent_executions, updated_at)
+               VALUES(?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET reserved_concurrent_executions = excluded.reserved_concurrent_executions, updated_at = excluded.updated_at"#
+        )
+        .bind(func.function_id)
+        .bind(reserved)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        // Update in-memory limiter
+        self.concurrency_manager
+            .set_reserved_limit(&func, config.reserved_concurrent_executions);
+
+        // Invalidate cache since concurrency config was updated
+        self.cache
+            .invalidate_concurrency(&func.function_id.to_string());
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_concurrency(&self, name: &str) -> Result<ConcurrencyConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+
+        // Try cache first
+        if let Some(cached_config) = self.cache.get_concurrency(&func.function_id.to_string()) {
+            return Ok(cached_config);
+        }
+
+        // Cache miss - fetch from database
+        let reserved: Option<i64> = sqlx::query_scalar(
+            "SELECT reserved_concurrent_executions FROM function_concurrency WHERE function_id = ?",
+        )
+        .bind(func.function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .flatten();
+
+        let config = ConcurrencyConfig {
+            reserved_concurrent_executions: reserved.map(|v| v as u32),
+        };
+
+        // Cache the result
+        self.cache
+            .set_concurrency(func.function_id.to_string(), config.clone());
+
+        Ok(config)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn put_hedging(&self, name: &str, config: HedgingConfig) -> Result<(), LambdaError> {
+        validate_hedging_config(&config)?;
+        let func = self.get_function(name).await?;
+        let now = chrono::Utc::now();
+        sqlx::query(
+            r#"INSERT INTO function_hedging(function_id, enabled, percentile, min_delay_ms, updated_at)
+               VALUES(?, ?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET enabled = excluded.enabled, percentile = excluded.percentile, min_delay_ms = excluded.min_delay_ms, updated_at = excluded.updated_at"#
+        )
+        .bind(func.function_id)
+        .bind(config.enabled)
+        .bind(config.percentile)
+        .bind(config.min_delay_ms.map(|v| v as i64))
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Hedging settings for a function; disabled when none were stored
+    #[instrument(skip(self))]
+    pub async fn get_hedging(&self, name: &str) -> Result<HedgingConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        let row = sqlx::query(
+            "SELECT enabled, percentile, min_delay_ms FROM function_hedging WHERE function_id = ?",
+        )
+        .bind(func.function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        Ok(match row {
+            Some(row) => HedgingConfig {
+                enabled: row.get("enabled"),
+                percentile: row.get("percentile"),
+                min_delay_ms: row.get::<Option<i64>, _>("min_delay_ms").map(|v| v as u64),
+            },
+            None => HedgingConfig {
+                enabled: false,
+                percentile: None,
+                min_delay_ms: None,
+            },
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_hedging(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_hedging WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Set the grace period for containers started from now on
+    #[instrument(skip(self))]
+    pub async fn put_stop_grace(
+        &self,
+        name: &str,
+        config: StopGraceConfig,
+    ) -> Result<StopGraceConfig, LambdaError> {
+        if config.grace_period_seconds > validation::MAX_STOP_GRACE_SECS {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "grace_period_seconds must be at most {}, got {}",
+                    validation::MAX_STOP_GRACE_SECS,
+                    config.grace_period_seconds
+                ),
+            });
+        }
+        let func = self.get_function(name).await?;
+        sqlx::query(
+            r#"INSERT INTO function_stop_grace(function_id, grace_period_seconds, updated_at)
+               VALUES(?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET grace_period_seconds = excluded.grace_period_seconds, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(config.grace_period_seconds as i64)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(config)
+    }
+
+    /// Stop grace period for a function; the configured default when none was stored
+    #[instrument(skip(self))]
+    pub async fn get_stop_grace(&self, name: &str) -> Result<StopGraceConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        let stored = self.load_stop_grace(func.function_id).await?;
+        Ok(StopGraceConfig {
+            grace_period_seconds: stored.unwrap_or(self.config.defaults.stop_grace_seconds),
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_stop_grace(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_stop_grace WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    async fn load_stop_grace(&self, function_id: Uuid) -> Result<Option<u64>, LambdaError> {
+        let grace: Option<i64> = sqlx::query_scalar(
+            "SELECT grace_period_seconds FROM function_stop_grace WHERE function_id = ?",
+        )
+        .bind(function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(grace.map(|v| v as u64))
+    }
+
+    /// Set how long the function's containers stay warm and how many it keeps; the idle
+    /// watchdog and the autoscaler apply it from their next pass
+    #[instrument(skip(self))]
+    pub async fn put_lifecycle(
+        &self,
+        name: &str,
+        lifecycle: FunctionLifecycle,
+    ) -> Result<FunctionLifecycle, LambdaError> {
+        validation::validate_lifecycle(&lifecycle)?;
+        let func = self.get_function(name).await?;
+        sqlx::query(
+            r#"INSERT INTO function_lifecycle(function_id, idle_timeout_secs, max_warm_containers, min_warm_containers, updated_at)
+               VALUES(?, ?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET idle_timeout_secs = excluded.idle_timeout_secs, max_warm_containers = excluded.max_warm_containers, min_warm_containers = excluded.min_warm_containers, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(lifecycle.idle_timeout_secs.map(|v| v as i64))
+        .bind(lifecycle.max_warm_containers.map(|v| v as i64))
+        .bind(lifecycle.min_warm_containers.map(|v| v as i64))
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.warm_pool
+            .set_lifecycle(func.function_id, Some(lifecycle.clone()));
+        Ok(lifecycle)
+    }
+
+    /// Lifecycle settings of a function; all unset when it follows the defaults
+    #[instrument(skip(self))]
+    pub async fn get_lifecycle(&self, name: &str) -> Result<FunctionLifecycle, LambdaError> {
+        let func = self.get_function(name).await?;
+        let row = sqlx::query("SELECT * FROM function_lifecycle WHERE function_id = ?")
+            .bind(func.function_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(row.map(|row| row_to_lifecycle(&row)).unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_lifecycle(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_lifecycle WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        self.warm_pool.set_lifecycle(func.function_id, None);
+        Ok(())
+    }
+
+    /// Containers of `$LATEST` to keep running per key, for functions with min_warm_containers
+    pub async fn min_warm_targets(
+        &self,
+    ) -> Result<HashMap<crate::queues::FnKey, usize>, LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT functions.*, function_lifecycle.min_warm_containers AS min_warm
+               FROM functions
+               JOIN function_lifecycle ON function_lifecycle.function_id = functions.function_id
+               WHERE function_lifecycle.min_warm_containers > 0"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let mut targets = HashMap::new();
+        for row in &rows {
+            let function = self.row_to_function(row)?;
+            let min_warm: i64 = row.try_get("min_warm").map_err(LambdaError::SqlxError)?;
+            targets.insert(
+                crate::queues::FnKey::for_function(&function),
+                min_warm as usize,
+            );
+        }
+        Ok(targets)
+    }
+
+    /// Run the function's containers under a sandbox profile; warm containers are replaced
+    /// so the next invocation runs under it
+    #[instrument(skip(self))]
+    pub async fn put_sandbox(
+        &self,
+        name: &str,
+        sandbox: FunctionSandbox,
+    ) -> Result<FunctionSandbox, LambdaError> {
+        let func = self.get_function(name).await?;
+        self.check_sandbox_profile(&sandbox.profile).await?;
+        sqlx::query(
+            r#"INSERT INTO function_sandbox(function_id, profile, updated_at)
+               VALUES(?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET profile = excluded.profile, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(&sandbox.profile)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.drain_function_containers(func.function_id).await;
+        Ok(sandbox)
+    }
+
+    /// Sandbox profile of a function; `sandbox.default_profile` when none was stored
+    #[instrument(skip(self))]
+    pub async fn get_sandbox(&self, name: &str) -> Result<FunctionSandbox, LambdaError> {
+        let func = self.get_function(name).await?;
+        let stored = self.load_sandbox(func.function_id).await?;
+        Ok(FunctionSandbox {
+            profile: stored.unwrap_or_else(|| self.config.sandbox.default_profile.clone()),
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_sandbox(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let removed = sqlx::query("DELETE FROM function_sandbox WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .rows_affected();
+        if removed > 0 {
+            self.drain_function_containers(func.function_id).await;
+        }
+        Ok(())
+    }
+
+    /// A profile must exist and be one the Docker host can apply
+    async fn check_sandbox_profile(&self, name: &str) -> Result<(), LambdaError> {
+        let profiles = self.invoker.sandbox_profiles();
+        let profile = profiles
+            .get(name)
+            .ok_or_else(|| LambdaError::InvalidRequest {
+                reason: format!(
+                    "Unknown sandbox profile {name}, expected one of: {}",
+                    profiles.names().join(", ")
+                ),
+            })?;
+        if let Some(reason) = self.invoker.sandbox_support().await?.unsupported(profile) {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!("Sandbox profile {name} cannot be applied: {reason}"),
+            });
+        }
+        Ok(())
+    }
+
+    async fn load_sandbox(&self, function_id: Uuid) -> Result<Option<String>, LambdaError> {
+        sqlx::query_scalar("SELECT profile FROM function_sandbox WHERE function_id = ?")
+            .bind(function_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)
+    }
+
+    /// Run the function's containers as another user; warm containers are replaced so the
+    /// next invocation runs as it. Root is only allowed on a rootless Docker host, where it
+    /// is the daemon's own unprivileged user
+    #[instrument(skip(self))]
+    pub async fn put_user(
+        &self,
+        name: &str,
+        user: FunctionUser,
+    ) -> Result<FunctionUser, LambdaError> {
+        let func = self.get_function(name).await?;
+        if user.is_root() && !self.invoker.sandbox_support().await?.rootless {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "Containers cannot run as {} unless the Docker daemon is rootless",
+                    user.docker_user()
+                ),
+            });
+        }
+        sqlx::query(
+            r#"INSERT INTO function_user(function_id, uid, gid, updated_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET uid = excluded.uid, gid = excluded.gid, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(user.uid as i64)
+        .bind(user.gid as i64)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.drain_function_containers(func.function_id).await;
+        Ok(user)
+    }
+
+    /// User a function's containers run as; `1000:1000` when none was stored
+    #[instrument(skip(self))]
+    pub async fn get_user(&self, name: &str) -> Result<FunctionUser, LambdaError> {
+        let func = self.get_function(name).await?;
+        Ok(self.load_user(func.function_id).await?.unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_user(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let removed = sqlx::query("DELETE FROM function_user WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .rows_affected();
+        if removed > 0 {
+            self.drain_function_containers(func.function_id).await;
+        }
+        Ok(())
+    }
+
+    async fn load_user(&self, function_id: Uuid) -> Result<Option<FunctionUser>, LambdaError> {
+        let row: Option<(i64, i64)> =
+            sqlx::query_as("SELECT uid, gid FROM function_user WHERE function_id = ?")
+                .bind(function_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(row.map(|(uid, gid)| FunctionUser {
+            uid: uid as u32,
+            gid: gid as u32,
+        }))
+    }
+
+    /// Give the function its own fake credentials and emulator endpoints instead of
+    /// `[aws_emulation]`'s; warm containers are replaced so the next invocation gets them
+    #[instrument(skip(self, settings))]
+    pub async fn put_aws_emulation(
+        &self,
+        name: &str,
+        settings: AwsEmulation,
+    ) -> Result<FunctionAwsEmulation, LambdaError> {
+        settings.validate()?;
+        let func = self.get_function(name).await?;
+        sqlx::query(
+            r#"INSERT INTO function_aws_emulation(function_id, settings, updated_at)
+               VALUES(?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET settings = excluded.settings, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(serde_json::to_string(&settings).unwrap_or_default())
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.drain_function_containers(func.function_id).await;
+        Ok(self.aws_emulation_status(&func.function_name, Some(settings)))
+    }
+
+    /// AWS emulation of a function, with the environment its containers get
+    #[instrument(skip(self))]
+    pub async fn get_aws_emulation(&self, name: &str) -> Result<FunctionAwsEmulation, LambdaError> {
+        let func = self.get_function(name).await?;
+        let stored = self.load_aws_emulation(func.function_id).await?;
+        Ok(self.aws_emulation_status(&func.function_name, stored))
+    }
+
+    /// Go back to `[aws_emulation]`'s settings
+    #[instrument(skip(self))]
+    pub async fn delete_aws_emulation(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let removed = sqlx::query("DELETE FROM function_aws_emulation WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .rows_affected();
+        if removed > 0 {
+            self.drain_function_containers(func.function_id).await;
+        }
+        Ok(())
+    }
+
+    fn aws_emulation_status(
+        &self,
+        function_name: &str,
+        stored: Option<AwsEmulation>,
+    ) -> FunctionAwsEmulation {
+        let overridden = stored.is_some();
+        let settings = stored.unwrap_or_else(|| self.config.aws_emulation.settings());
+        let environment = settings.environment(function_name, &self.config.identity);
+        FunctionAwsEmulation {
+            overridden,
+            settings,
+            environment,
+        }
+    }
+
+    async fn load_aws_emulation(
+        &self,
+        function_id: Uuid,
+    ) -> Result<Option<AwsEmulation>, LambdaError> {
+        let settings: Option<String> =
+            sqlx::query_scalar("SELECT settings FROM function_aws_emulation WHERE function_id = ?")
+                .bind(function_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(settings.and_then(|s| serde_json::from_str(&s).ok()))
+    }
+
+    /// Set the hooks run around every invocation of the function
+    #[instrument(skip(self, hooks))]
+    pub async fn put_hooks(
+        &self,
+        name: &str,
+        hooks: FunctionHooks,
+    ) -> Result<FunctionHooks, LambdaError> {
+        let func = self.get_function(name).await?;
+        validation::validate_function_hooks(&func.function_name, &hooks)?;
+        for hook in [&hooks.pre_invoke, &hooks.post_invoke]
+            .into_iter()
+            .flatten()
+        {
+            if let HookTarget::Function { function_name } = &hook.target {
+                if let Err(LambdaError::FunctionNotFound { .. }) =
+                    self.get_function(function_name).await
+                {
+                    return Err(LambdaError::InvalidRequest {
+                        reason: format!("Hook function {function_name} does not exist"),
+                    });
+                }
+            }
+        }
+        sqlx::query(
+            r#"INSERT INTO function_hooks(function_id, hooks, updated_at)
+               VALUES(?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET hooks = excluded.hooks, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(serde_json::to_string(&hooks).unwrap_or_default())
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(hooks)
+    }
+
+    /// Hooks of a function; none when it has not set any
+    #[instrument(skip(self))]
+    pub async fn get_hooks(&self, name: &str) -> Result<FunctionHooks, LambdaError> {
+        let func = self.get_function(name).await?;
+        Ok(self.load_hooks(func.function_id).await?.unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_hooks(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_hooks WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    async fn load_hooks(&self, function_id: Uuid) -> Result<Option<FunctionHooks>, LambdaError> {
+        let hooks: Option<String> =
+            sqlx::query_scalar("SELECT hooks FROM function_hooks WHERE function_id = ?")
+                .bind(function_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(hooks.and_then(|h| serde_json::from_str(&h).ok()))
+    }
+
+    /// AddPermission: let another function, or every function, invoke this one through the
+    /// runtime API
+    #[instrument(skip(self, request))]
+    pub async fn add_permission(
+        &self,
+        name: &str,
+        request: AddPermissionRequest,
+    ) -> Result<PermissionStatement, LambdaError> {
+        let request = validation::normalize_add_permission(request, &self.config.identity)?;
+        let func = self.get_function(name).await?;
+        let statement = PermissionStatement {
+            statement_id: request.statement_id,
+            action: request.action,
+            principal: request.principal,
+            created_at: chrono::Utc::now(),
+        };
+        let inserted = sqlx::query(
+            r#"INSERT INTO function_permissions(function_id, statement_id, action, principal, created_at)
+               VALUES(?, ?, ?, ?, ?)
+               ON CONFLICT(function_id, statement_id) DO NOTHING"#,
+        )
+        .bind(func.function_id)
+        .bind(&statement.statement_id)
+        .bind(&statement.action)
+        .bind(&statement.principal)
+        .bind(statement.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(LambdaError::PermissionAlreadyExists {
+                function_name: func.function_name,
+                statement_id: statement.statement_id,
+            });
+        }
+        Ok(statement)
+    }
+
+    /// GetPolicy: statements of a function's policy, oldest first
+    #[instrument(skip(self))]
+    pub async fn get_policy(&self, name: &str) -> Result<FunctionPolicy, LambdaError> {
+        let func = self.get_function(name).await?;
+        let rows: Vec<(String, String, String, DateTime<Utc>)> = sqlx::query_as(
+            r#"SELECT statement_id, action, principal, created_at FROM function_permissions
+               WHERE function_id = ? ORDER BY created_at, statement_id"#,
+        )
+        .bind(func.function_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(FunctionPolicy {
+            function_name: func.function_name,
+            statements: rows
+                .into_iter()
+                .map(
+                    |(statement_id, action, principal, created_at)| PermissionStatement {
+                        statement_id,
+                        action,
+                        principal,
+                        created_at,
+                    },
+                )
+                .collect(),
+        })
+    }
+
+    /// RemovePermission: drop a statement from a function's policy
+    #[instrument(skip(self))]
+    pub async fn remove_permission(
+        &self,
+        name: &str,
+        statement_id: &str,
+    ) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let removed = sqlx::query(
+            "DELETE FROM function_permissions WHERE function_id = ? AND statement_id = ?",
+        )
+        .bind(func.function_id)
+        .bind(statement_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .rows_affected();
+        if removed == 0 {
+            return Err(LambdaError::PermissionNotFound {
+                function_name: func.function_name,
+                statement_id: statement_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that the function running in `instance_id` may invoke `target`, and name it. Only
+    /// a container the warm pool knows identifies its function, by its instance id and the
+    /// token it was started with, and the target's policy must grant it, by name or to every
+    /// function
+    #[instrument(skip(self, instance_token))]
+    pub async fn authorize_function_invoke(
+        &self,
+        instance_id: Option<&str>,
+        instance_token: Option<&str>,
+        target: &str,
+    ) -> Result<String, LambdaError> {
+        let key = match (instance_id, instance_token) {
+            (Some(instance_id), Some(token)) if self.instance_tokens.verify(instance_id, token) => {
+                self.warm_pool.instance_key(instance_id).await
+            }
+            _ => None,
+        };
+        let caller = key
+            .ok_or_else(|| LambdaError::AccessDenied {
+                reason: "Invokes through the runtime API must come from a function container, identified by X-LambdaH-Instance-Id and X-LambdaH-Instance-Token".to_string(),
+            })?
+            .function_name;
+        let func = self.get_function(target).await?;
+        let granted: Option<String> = sqlx::query_scalar(
+            r#"SELECT statement_id FROM function_permissions
+               WHERE function_id = ? AND action = ? AND principal IN (?, ?)
+               LIMIT 1"#,
+        )
+        .bind(func.function_id)
+        .bind(INVOKE_FUNCTION_ACTION)
+        .bind(&caller)
+        .bind(ANY_FUNCTION_PRINCIPAL)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        if granted.is_none() {
+            return Err(LambdaError::AccessDenied {
+                reason: format!(
+                    "Function {caller} is not allowed to invoke {}",
+                    func.function_name
+                ),
+            });
+        }
+        Ok(caller)
+    }
+
+    /// Set the timezone and locale; warm containers are replaced so the change applies to
+    /// the next invocation
+    #[instrument(skip(self))]
+    pub async fn put_locale(
+        &self,
+        name: &str,
+        config: LocaleConfig,
+    ) -> Result<LocaleConfig, LambdaError> {
+        let config = validation::normalize_locale_config(config)?;
+        let func = self.get_function(name).await?;
+        sqlx::query(
+            r#"INSERT INTO function_locale(function_id, timezone, locale, updated_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET timezone = excluded.timezone, locale = excluded.locale, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(&config.timezone)
+        .bind(&config.locale)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.drain_function_containers(func.function_id).await;
+        Ok(config)
+    }
+
+    /// Timezone and locale for a function; both unset (UTC, image default) when none was stored
+    #[instrument(skip(self))]
+    pub async fn get_locale(&self, name: &str) -> Result<LocaleConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        Ok(self
+            .load_locale(func.function_id)
+            .await?
+            .unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_locale(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let removed = sqlx::query("DELETE FROM function_locale WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .rows_affected();
+        if removed > 0 {
+            self.drain_function_containers(func.function_id).await;
+        }
+        Ok(())
+    }
+
+    async fn load_locale(&self, function_id: Uuid) -> Result<Option<LocaleConfig>, LambdaError> {
+        let row = sqlx::query("SELECT timezone, locale FROM function_locale WHERE function_id = ?")
+            .bind(function_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(row.map(|row| LocaleConfig {
+            timezone: row.get("timezone"),
+            locale: row.get("locale"),
+        }))
+    }
+
+    /// Keep builds of a function on its runtime's base image tag, or pin them to a digest
+    #[instrument(skip(self))]
+    pub async fn put_runtime_management(
+        &self,
+        name: &str,
+        config: RuntimeManagementConfig,
+    ) -> Result<RuntimeManagement, LambdaError> {
+        let current = self.get_runtime_management(name).await?;
+        let runtime_version = match config.update_runtime_on {
+            UpdateRuntimeOn::Auto if config.runtime_version.is_some() => {
+                return Err(LambdaError::InvalidRequest {
+                    reason: "runtime_version can only be set with update_runtime_on Manual"
+                        .to_string(),
+                });
+            }
+            UpdateRuntimeOn::Auto => None,
+            UpdateRuntimeOn::Manual => {
+                let version = config
+                    .runtime_version
+                    .or_else(|| current.last_build_runtime_version.clone())
+                    .ok_or_else(|| LambdaError::InvalidRequest {
+                        reason: format!(
+                            "No build of {name} recorded its base image digest yet; \
+                             pass runtime_version to pin one"
+                        ),
+                    })?;
+                validation::validate_runtime_version(&version)?;
+                Some(version)
+            }
+        };
+        let func = self.get_function(name).await?;
+        sqlx::query(
+            r#"INSERT INTO function_runtime_management(function_id, update_runtime_on, runtime_version, updated_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET update_runtime_on = excluded.update_runtime_on, runtime_version = excluded.runtime_version, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(match config.update_runtime_on {
+            UpdateRuntimeOn::Auto => "Auto",
+            UpdateRuntimeOn::Manual => "Manual",
+        })
+        .bind(&runtime_version)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        // Another base image may build where the last one failed
+        self.failed_builds.forget_function(name);
+        Ok(RuntimeManagement {
+            update_runtime_on: config.update_runtime_on,
+            runtime_version,
+            ..current
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_runtime_management(
+        &self,
+        name: &str,
+    ) -> Result<RuntimeManagement, LambdaError> {
+        let func = self.get_function(name).await?;
+        self.load_runtime_management(&func).await
+    }
+
+    async fn load_runtime_management(
+        &self,
+        function: &Function,
+    ) -> Result<RuntimeManagement, LambdaError> {
+        let base_image = lambda_packaging::base_image(&function.runtime).ok_or_else(|| {
+            LambdaError::InvalidRuntime {
+                runtime: function.runtime.clone(),
+            }
+        })?;
+        let row = sqlx::query(
+            "SELECT update_runtime_on, runtime_version, last_build_runtime_version, \
+             last_build_at FROM function_runtime_management WHERE function_id = ?",
+        )
+        .bind(function.function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let mode = row
+            .as_ref()
+            .map(|r| r.get::<String, _>("update_runtime_on"));
+        Ok(RuntimeManagement {
+            update_runtime_on: match mode.as_deref() {
+                Some("Manual") => UpdateRuntimeOn::Manual,
+                _ => UpdateRuntimeOn::Auto,
+            },
+            runtime_version: row.as_ref().and_then(|r| r.get("runtime_version")),
+            base_image: base_image.to_string(),
+            last_build_runtime_version: row
+                .as_ref()
+                .and_then(|r| r.get("last_build_runtime_version")),
+            last_build_at: row.as_ref().and_then(|r| r.get("last_build_at")),
+        })
+    }
+
+    /// Remember the base image digest a build of `function` ran on
+    async fn record_build_runtime_version(
+        &self,
+        function: &Function,
+        runtime_version: &str,
+    ) -> Result<(), LambdaError> {
+        let now = chrono::Utc::now();
+        sqlx::query(
+            r#"INSERT INTO function_runtime_management(function_id, last_build_runtime_version, last_build_at, updated_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET last_build_runtime_version = excluded.last_build_runtime_version, last_build_at = excluded.last_build_at"#,
+        )
+        .bind(function.function_id)
+        .bind(runtime_version)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Record the image ID `function`'s current code built to, and give it to the versions of
+    /// that code published before the build. A rebuild that comes out different means the
+    /// build is not reproducible; it is logged, and versions keep the digest they have.
+    pub async fn record_image_digest(
+        &self,
+        function: &Function,
+        image_digest: &str,
+    ) -> Result<(), LambdaError> {
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        let previous: Option<String> = sqlx::query_scalar(
+            "SELECT image_digest FROM function_image_digests WHERE function_id = ? AND code_sha256 = ?",
+        )
+        .bind(function.function_id)
+        .bind(&function.code_sha256)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        if let Some(previous) = previous.filter(|p| p != image_digest) {
+            warn!(
+                "Image of function {} code {} rebuilt as {}, previously {}",
+                function.function_name, function.code_sha256, image_digest, previous
+            );
+        }
+        sqlx::query(
+            r#"INSERT INTO function_image_digests(function_id, code_sha256, image_digest, built_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id, code_sha256) DO UPDATE SET image_digest = excluded.image_digest, built_at = excluded.built_at"#,
+        )
+        .bind(function.function_id)
+        .bind(&function.code_sha256)
+        .bind(image_digest)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        sqlx::query(
+            "UPDATE versions SET image_digest = ? \
+             WHERE function_id = ? AND code_sha256 = ? AND image_digest IS NULL",
+        )
+        .bind(image_digest)
+        .bind(function.function_id)
+        .bind(&function.code_sha256)
+        .execute(&mut *tx)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Set the headers added to the function's successful direct invoke responses
+    #[instrument(skip(self))]
+    pub async fn put_response_headers(
+        &self,
+        name: &str,
+        config: ResponseHeadersConfig,
+    ) -> Result<ResponseHeadersConfig, LambdaError> {
+        let config = validation::normalize_response_headers_config(config)?;
+        let func = self.get_function(name).await?;
+        let headers =
+            serde_json::to_string(&config.headers).map_err(|e| LambdaError::InternalError {
+                reason: format!("Failed to serialize response headers: {e}"),
+            })?;
+        sqlx::query(
+            r#"INSERT INTO function_response_headers(function_id, headers, envelope, updated_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET headers = excluded.headers, envelope = excluded.envelope, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(headers)
+        .bind(config.envelope)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(config)
+    }
+
+    /// Response headers of a function; none and no envelope unless set
+    #[instrument(skip(self))]
+    pub async fn get_response_headers(
+        &self,
+        name: &str,
+    ) -> Result<ResponseHeadersConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        Ok(self
+            .load_response_headers(func.function_id)
+            .await?
+            .unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_response_headers(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_response_headers WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    async fn load_response_headers(
+        &self,
+        function_id: Uuid,
+    ) -> Result<Option<ResponseHeadersConfig>, LambdaError> {
+        let row = sqlx::query(
+            "SELECT headers, envelope FROM function_response_headers WHERE function_id = ?",
+        )
+        .bind(function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(row.map(|row| ResponseHeadersConfig {
+            headers: serde_json::from_str(&row.get::<String, _>("headers")).unwrap_or_default(),
+            envelope: row.get("envelope"),
+        }))
+    }
+
+    /// Body and headers of a successful direct invoke of `function_name`: the configured
+    /// default headers, and the result's own when the function returns an envelope
+    pub async fn shape_invoke_response(
+        &self,
+        function_name: &str,
+        payload: serde_json::Value,
+    ) -> (serde_json::Value, HashMap<String, String>) {
+        let global_headers = &self.config.invoke_response.default_headers;
+        let config = match self.get_function(function_name).await {
+            Ok(func) => self.load_response_headers(func.function_id).await,
+            Err(e) => Err(e),
+        };
+        let config = match config {
+            Ok(config) => config.unwrap_or_default(),
+            Err(e) => {
+                warn!(
+                    "Failed to load response headers for {}: {}",
+                    function_name, e
+                );
+                ResponseHeadersConfig::default()
+            }
+        };
+        shape_response(global_headers, &config, payload)
+    }
+
+    /// Invoke the function on a schedule and check its result; replaces an existing check
+    #[instrument(skip(self, check))]
+    pub async fn put_synthetic_check(
+        &self,
+        name: &str,
+        check: SyntheticCheck,
+    ) -> Result<SyntheticCheck, LambdaError> {
+        validate_synthetic_check(&check)?;
+        let func = self.get_function(name).await?;
+        let config = serde_json::to_string(&check).map_err(|e| LambdaError::InternalError {
+            reason: format!("Failed to serialize synthetic check: {e}"),
+        })?;
+        sqlx::query(
+            r#"INSERT INTO function_synthetic_checks(function_id, config, updated_at)
+               VALUES(?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET config = excluded.config, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(config)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(check)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_synthetic_check(&self, name: &str) -> Result<SyntheticCheck, LambdaError> {
+        let func = self.get_function(name).await?;
+        self.load_synthetic_check(func.function_id)
+            .await?
+            .ok_or_else(|| LambdaError::SyntheticCheckNotFound {
+                function_name: name.to_string(),
+            })
+    }
+
+    /// Remove the function's synthetic check along with its recorded runs
+    #[instrument(skip(self))]
+    pub async fn delete_synthetic_check(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        sqlx::query("DELETE FROM function_synthetic_checks WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        sqlx::query("DELETE FROM synthetic_check_runs WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    async fn load_synthetic_check(
+        &self,
+        function_id: Uuid,
+    ) -> Result<Option<SyntheticCheck>, LambdaError> {
+        let config: Option<String> = sqlx::query_scalar(
+            "SELECT config FROM function_synthetic_checks WHERE function_id = ?",
+        )
+        .bind(function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        config
+            .map(|config| {
+                serde_json::from_str(&config).map_err(|e| LambdaError::InternalError {
+                    reason: format!("Invalid stored synthetic check: {e}"),
+                })
+            })
+            .transpose()
+    }
+
+    /// Invoke the function with its synthetic check's payload and record whether the result
+    /// passed. A failure after a passing run, or as the first run, fires an alert webhook.
+    #[instrument(skip(self))]
+    pub async fn run_synthetic_check(&self, name: &str) -> Result<SyntheticCheckRun, LambdaError> {
+        let func = self.get_function(name).await?;
+        let check = self
+            .load_synthetic_check(func.function_id)
+            .await?
+            .ok_or_else(|| LambdaError::SyntheticCheckNotFound {
+                function_name: name.to_string(),
+            })?;
+
+        let request_id = Uuid::new_v4().to_string();
+        let started = std::time::Instant::now();
+        let outcome = self
+            .invoke_with_request_id(
+                request_id.clone(),
+                InvokeRequest {
+                    function_name: name.to_string(),
+                    invocation_type: lambda_models::InvocationType::RequestResponse,
+                    log_type: None,
+                    client_context: None,
+                    payload: Some(check.payload.clone()),
+                    qualifier: None,
+                    idempotent: false,
+                    trace: false,
+                },
+            )
+            .await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let failure = evaluate_run(&check, outcome.as_ref(), latency_ms);
+        let run = SyntheticCheckRun {
+            ran_at: Utc::now(),
+            success: failure.is_none(),
+            latency_ms,
+            failure,
+            request_id: outcome.is_ok().then_some(request_id),
+        };
+
+        let previous_success: Option<bool> = sqlx::query_scalar(
+            "SELECT success FROM synthetic_check_runs WHERE function_id = ? ORDER BY id DESC LIMIT 1",
+        )
+        .bind(func.function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        sqlx::query(
+            r#"INSERT INTO synthetic_check_runs(function_id, ran_at, success, latency_ms, failure, request_id)
+               VALUES(?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(func.function_id)
+        .bind(run.ran_at)
+        .bind(run.success)
+        .bind(run.latency_ms as i64)
+        .bind(&run.failure)
+        .bind(&run.request_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let retention = chrono::Duration::from_std(RUN_RETENTION).unwrap_or_default();
+        sqlx::query("DELETE FROM synthetic_check_runs WHERE function_id = ? AND ran_at < ?")
+            .bind(func.function_id)
+            .bind(run.ran_at - retention)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+
+        if !run.success && previous_success != Some(false) {
+            warn!(
+                "Synthetic check of {} failed: {}",
+                name,
+                run.failure.as_deref().unwrap_or_default()
+            );
+            self.webhooks.emit(
+                WebhookEventType::AlertFired,
+                Some(name),
+                serde_json::json!({ "alert": "synthetic_check_failed", "run": run }),
+            );
+        }
+        Ok(run)
+    }
+
+    /// Uptime, SLO and latency of the function's synthetic check runs within `window`
+    #[instrument(skip(self))]
+    pub async fn synthetic_check_stats(
+        &self,
+        name: &str,
+        window: std::time::Duration,
+    ) -> Result<SyntheticCheckStats, LambdaError> {
+        let func = self.get_function(name).await?;
+        let check = self
+            .load_synthetic_check(func.function_id)
+            .await?
+            .ok_or_else(|| LambdaError::SyntheticCheckNotFound {
+                function_name: name.to_string(),
+            })?;
+        if window > RUN_RETENTION {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "Synthetic check stats cover at most {}d",
+                    RUN_RETENTION.as_secs() / 86400
+                ),
+            });
+        }
+        let since = crate::window_start(Utc::now(), window)?;
+        let rows = sqlx::query(
+            r#"SELECT ran_at, success, latency_ms, failure, request_id
+               FROM synthetic_check_runs
+               WHERE function_id = ? AND ran_at >= ?
+               ORDER BY id DESC"#,
+        )
+        .bind(func.function_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let runs = rows
+            .iter()
+            .map(|row| SyntheticCheckRun {
+                ran_at: row.get("ran_at"),
+                success: row.get("success"),
+                latency_ms: row.get::<i64, _>("latency_ms") as u64,
+                failure: row.get("failure"),
+                request_id: row.get("request_id"),
+            })
+            .collect();
+        Ok(summarize_runs(name, window, check.slo_target, runs))
+    }
+
+    /// Functions whose enabled synthetic check failed its latest run, with the failure
+    async fn failing_synthetic_checks(&self) -> Result<Vec<(String, Option<String>)>, LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT f.function_name, c.config, r.failure
+               FROM synthetic_check_runs r
+               JOIN function_synthetic_checks c ON c.function_id = r.function_id
+               JOIN functions f ON f.function_id = r.function_id
+               WHERE r.success = 0
+                 AND r.id = (SELECT MAX(id) FROM synthetic_check_runs WHERE function_id = r.function_id)
+               ORDER BY f.function_name"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(rows
+            .iter()
+            .filter(|row| {
+                serde_json::from_str::<SyntheticCheck>(&row.get::<String, _>("config"))
+                    .is_ok_and(|check| check.enabled)
+            })
+            .map(|row| (row.get("function_name"), row.get("failure")))
+            .collect())
+    }
+
+    /// Names of functions whose enabled synthetic check has not run for its interval
+    async fn due_synthetic_checks(&self) -> Result<Vec<String>, LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT f.function_name, c.config,
+                      (SELECT ran_at FROM synthetic_check_runs r
+                       WHERE r.function_id = c.function_id ORDER BY id DESC LIMIT 1) AS last_run
+               FROM function_synthetic_checks c
+               JOIN functions f ON f.function_id = c.function_id"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let now = Utc::now();
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let name: String = row.get("function_name");
+                let check: SyntheticCheck =
+                    serde_json::from_str(&row.get::<String, _>("config")).ok()?;
+                let last_run: Option<DateTime<Utc>> = row.get("last_run");
+                (check.enabled && check_due(&check, last_run, now)).then_some(name)
+            })
+            .collect())
+    }
+
+    /// Run synthetic checks as they come due; a check still running is not started again
+    async fn run_synthetic_checks(self: Arc<Self>) {
+        let in_flight: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
+        loop {
+            tokio::time::sleep(SYNTHETIC_CHECK_TICK).await;
+            let due = match self.due_synthetic_checks().await {
+                Ok(due) => due,
+                Err(e) => {
+                    error!("Failed to load synthetic checks: {}", e);
+                    continue;
+                }
+            };
+            for name in due {
+                if !in_flight.lock().unwrap().insert(name.clone()) {
+                    continue;
+                }
+                let control = self.clone();
+                let in_flight = in_flight.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = control.run_synthetic_check(&name).await {
+                        warn!("Failed to run synthetic check of {}: {}", name, e);
+                    }
+                    in_flight.lock().unwrap().remove(&name);
+                });
+            }
+        }
+    }
+
+    /// Invoke the function asynchronously with `request.payload` whenever
+    /// `request.schedule_expression` comes due
+    #[instrument(skip(self, request))]
+    pub async fn create_schedule(
+        &self,
+        name: &str,
+        request: ScheduleRequest,
+    ) -> Result<Schedule, LambdaError> {
+        let expression = ScheduleExpression::parse(&request.schedule_expression)?;
+        let func = self.get_function(name).await?;
+        self.resolve_qualifier(func.clone(), request.qualifier.as_deref())
+            .await?;
+        let now = Utc::now();
+        let schedule = Schedule {
+            schedule_id: Uuid::new_v4().to_string(),
+            function_name: func.function_name,
+            next_run_at: request
+                .enabled
+                .then(|| expression.next_run(now, now))
+                .flatten(),
+            schedule_expression: request.schedule_expression,
+            payload: request.payload,
+            qualifier: request.qualifier,
+            description: request.description,
+            enabled: request.enabled,
+            created_at: now,
+            last_run_at: None,
+            last_job_id: None,
+        };
+        sqlx::query(
+            r#"INSERT INTO function_schedules
+               (schedule_id, function_id, schedule_expression, payload, qualifier, description, enabled, created_at, next_run_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(&schedule.schedule_id)
+        .bind(func.function_id)
+        .bind(&schedule.schedule_expression)
+        .bind(schedule.payload.to_string())
+        .bind(&schedule.qualifier)
+        .bind(&schedule.description)
+        .bind(schedule.enabled)
+        .bind(schedule.created_at)
+        .bind(schedule.next_run_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        info!(
+            "Scheduled function {} at {}",
+            schedule.function_name, schedule.schedule_expression
+        );
+        Ok(schedule)
+    }
+
+    /// The function's schedules, oldest first
+    #[instrument(skip(self))]
+    pub async fn list_schedules(&self, name: &str) -> Result<Vec<Schedule>, LambdaError> {
+        let func = self.get_function(name).await?;
+        let rows = sqlx::query(
+            r#"SELECT s.*, f.function_name FROM function_schedules s
+               JOIN functions f ON f.function_id = s.function_id
+               WHERE s.function_id = ? ORDER BY s.created_at, s.schedule_id"#,
+        )
+        .bind(func.function_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(rows.iter().map(row_to_schedule).collect())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_schedule(
+        &self,
+        name: &str,
+        schedule_id: &str,
+    ) -> Result<Schedule, LambdaError> {
+        let func = self.get_function(name).await?;
+        let row = sqlx::query(
+            r#"SELECT s.*, f.function_name FROM function_schedules s
+               JOIN functions f ON f.function_id = s.function_id
+               WHERE s.function_id = ? AND s.schedule_id = ?"#,
+        )
+        .bind(func.function_id)
+        .bind(schedule_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::ScheduleNotFound {
+            function_name: name.to_string(),
+            schedule_id: schedule_id.to_string(),
+        })?;
+        Ok(row_to_schedule(&row))
+    }
+
+    /// Replace the schedule's expression, payload and target. The next run is worked out
+    /// again from its latest run, or from its creation when it has not run yet.
+    #[instrument(skip(self, request))]
+    pub async fn update_schedule(
+        &self,
+        name: &str,
+        schedule_id: &str,
+        request: ScheduleRequest,
+    ) -> Result<Schedule, LambdaError> {
+        let expression = ScheduleExpression::parse(&request.schedule_expression)?;
+        let existing = self.get_schedule(name, schedule_id).await?;
+        let func = self.get_function(name).await?;
+        self.resolve_qualifier(func, request.qualifier.as_deref())
+            .await?;
+        let now = Utc::now();
+        let anchor = existing.last_run_at.unwrap_or(existing.created_at);
+        let schedule = Schedule {
+            next_run_at: request
+                .enabled
+                .then(|| expression.next_run(anchor, now))
+                .flatten(),
+            schedule_expression: request.schedule_expression,
+            payload: request.payload,
+            qualifier: request.qualifier,
+            description: request.description,
+            enabled: request.enabled,
+            ..existing
+        };
+        sqlx::query(
+            r#"UPDATE function_schedules
+               SET schedule_expression = ?, payload = ?, qualifier = ?, description = ?, enabled = ?, next_run_at = ?
+               WHERE schedule_id = ?"#,
+        )
+        .bind(&schedule.schedule_expression)
+        .bind(schedule.payload.to_string())
+        .bind(&schedule.qualifier)
+        .bind(&schedule.description)
+        .bind(schedule.enabled)
+        .bind(schedule.next_run_at)
+        .bind(schedule_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(schedule)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_schedule(&self, name: &str, schedule_id: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let result =
+            sqlx::query("DELETE FROM function_schedules WHERE function_id = ? AND schedule_id = ?")
+                .bind(func.function_id)
+                .bind(schedule_id)
+                .execute(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::ScheduleNotFound {
+                function_name: name.to_string(),
+                schedule_id: schedule_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Queue an Event invocation for each enabled schedule due at `now`, and move it on to
+    /// its next run. A schedule that fell behind, e.g. while the server was down, runs once
+    /// and not once per missed run. Returns the schedules that ran.
+    pub async fn run_due_schedules(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Schedule>, LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT s.*, f.function_name FROM function_schedules s
+               JOIN functions f ON f.function_id = s.function_id
+               WHERE s.enabled = 1 AND s.next_run_at <= ?
+               ORDER BY s.next_run_at"#,
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let mut ran = Vec::new();
+        for row in rows {
+            let mut schedule = row_to_schedule(&row);
+            let due: String = row.get("next_run_at");
+            let Some(due_at) = schedule.next_run_at else {
+                continue;
+            };
+            let next_run_at = match ScheduleExpression::parse(&schedule.schedule_expression) {
+                Ok(expression) => expression.next_run(due_at, now),
+                Err(e) => {
+                    warn!("Schedule {} cannot run: {}", schedule.schedule_id, e);
+                    None
+                }
+            };
+            // Claim the run, so a run is never queued twice
+            let claimed = sqlx::query(
+                r#"UPDATE function_schedules SET last_run_at = ?, next_run_at = ?
+                   WHERE schedule_id = ? AND next_run_at = ?"#,
+            )
+            .bind(now)
+            .bind(next_run_at)
+            .bind(&schedule.schedule_id)
+            .bind(due)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+            if claimed.rows_affected() == 0 {
+                continue;
+            }
+            schedule.last_run_at = Some(now);
+            schedule.next_run_at = next_run_at;
+            let request = InvokeRequest {
+                function_name: schedule.function_name.clone(),
+                invocation_type: lambda_models::InvocationType::Event,
+                log_type: None,
+                client_context: None,
+                payload: Some(schedule.payload.clone()),
+                qualifier: schedule.qualifier.clone(),
+                idempotent: false,
+                trace: false,
+            };
+            match self.enqueue_event(request).await {
+                Ok(job) => {
+                    sqlx::query(
+                        "UPDATE function_schedules SET last_job_id = ? WHERE schedule_id = ?",
+                    )
+                    .bind(&job.job_id)
+                    .bind(&schedule.schedule_id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(LambdaError::SqlxError)?;
+                    schedule.last_job_id = Some(job.job_id);
+                }
+                Err(e) => warn!(
+                    "Failed to queue scheduled invocation of {}: {}",
+                    schedule.function_name, e
+                ),
+            }
+            ran.push(schedule);
+        }
+        Ok(ran)
+    }
+
+    /// Run schedules as they come due. Schedules live in the database, so they carry on
+    /// after a restart from where they were.
+    async fn run_schedules(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(SCHEDULE_TICK).await;
+            if let Err(e) = self.run_due_schedules(Utc::now()).await {
+                error!("Failed to run due schedules: {}", e);
+            }
+        }
+    }
+
+    /// Give the function, or the version or alias `qualifier` names, a URL of its own that
+    /// invokes it with HTTP requests in payload format 2.0
+    #[instrument(skip(self))]
+    pub async fn create_function_url_config(
+        &self,
+        name: &str,
+        qualifier: Option<&str>,
+    ) -> Result<FunctionUrlConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        self.resolve_qualifier(func.clone(), qualifier).await?;
+        if self.load_function_url(&func, qualifier).await?.is_some() {
+            return Err(LambdaError::FunctionUrlConfigAlreadyExists {
+                function_name: name.to_string(),
+            });
+        }
+        let url_id = function_urls::new_url_id();
+        let created_at = Utc::now();
+        sqlx::query(
+            "INSERT INTO function_urls (url_id, function_id, qualifier, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&url_id)
+        .bind(func.function_id)
+        .bind(qualifier.unwrap_or_default())
+        .bind(created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        info!("Created function URL {} for {}", url_id, name);
+        Ok(self.function_url_config(&func.function_name, qualifier, url_id, created_at))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_function_url_config(
+        &self,
+        name: &str,
+        qualifier: Option<&str>,
+    ) -> Result<FunctionUrlConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        self.load_function_url(&func, qualifier)
+            .await?
+            .ok_or_else(|| LambdaError::FunctionUrlConfigNotFound {
+                function_name: name.to_string(),
+            })
+    }
+
+    /// Remove the function URL; requests to it answer 404 from now on
+    #[instrument(skip(self))]
+    pub async fn delete_function_url_config(
+        &self,
+        name: &str,
+        qualifier: Option<&str>,
+    ) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let result =
+            sqlx::query("DELETE FROM function_urls WHERE function_id = ? AND qualifier = ?")
+                .bind(func.function_id)
+                .bind(qualifier.unwrap_or_default())
+                .execute(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::FunctionUrlConfigNotFound {
+                function_name: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The function a URL id belongs to, and the qualifier the URL invokes
+    pub async fn resolve_function_url(
+        &self,
+        url_id: &str,
+    ) -> Result<(Function, Option<String>), LambdaError> {
+        let row = sqlx::query(
+            r#"SELECT f.function_name, u.qualifier FROM function_urls u
+               JOIN functions f ON f.function_id = u.function_id
+               WHERE u.url_id = ?"#,
+        )
+        .bind(url_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::FunctionUrlNotFound {
+            url_id: url_id.to_string(),
+        })?;
+        let func = self.get_function(row.get("function_name")).await?;
+        let qualifier: String = row.get("qualifier");
+        Ok((func, Some(qualifier).filter(|q| !q.is_empty())))
+    }
+
+    async fn load_function_url(
+        &self,
+        func: &Function,
+        qualifier: Option<&str>,
+    ) -> Result<Option<FunctionUrlConfig>, LambdaError> {
+        let row = sqlx::query(
+            "SELECT url_id, created_at FROM function_urls WHERE function_id = ? AND qualifier = ?",
+        )
+        .bind(func.function_id)
+        .bind(qualifier.unwrap_or_default())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(row.map(|row| {
+            self.function_url_config(
+                &func.function_name,
+                qualifier,
+                row.get("url_id"),
+                row.get("created_at"),
+            )
+        }))
+    }
+
+    fn function_url_config(
+        &self,
+        function_name: &str,
+        qualifier: Option<&str>,
+        url_id: String,
+        creation_time: DateTime<Utc>,
+    ) -> FunctionUrlConfig {
+        FunctionUrlConfig {
+            function_url: format!(
+                "{}{}",
+                self.user_api_url(),
+                function_urls::url_path(&url_id)
+            ),
+            url_id,
+            function_name: function_name.to_string(),
+            function_arn: self.config.identity.function_arn(function_name),
+            qualifier: qualifier.map(str::to_string),
+            creation_time,
+        }
+    }
+
+    /// Base URL of the user API; see [`function_urls::user_api_url`]
+    fn user_api_url(&self) -> String {
+        function_urls::user_api_url(&self.config.server)
+    }
+
+    /// Enable or disable the prewarm invocation for containers started from now on
+    #[instrument(skip(self))]
+    pub async fn put_prewarm(
+        &self,
+        name: &str,
+        config: PrewarmConfig,
+    ) -> Result<PrewarmConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        let max_ms = validation::MAX_TIMEOUT_SECS * 1000;
+        if let Some(timeout_ms) = config.timeout_ms {
+            if timeout_ms == 0 || timeout_ms > max_ms {
+                return Err(LambdaError::InvalidRequest {
+                    reason: format!("timeout_ms must be between 1 and {max_ms}, got {timeout_ms}"),
+                });
+            }
+        }
+        sqlx::query(
+            r#"INSERT INTO function_prewarm(function_id, enabled, timeout_ms, updated_at)
+               VALUES(?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET enabled = excluded.enabled, timeout_ms = excluded.timeout_ms, updated_at = excluded.updated_at"#,
+        )
+        .bind(func.function_id)
+        .bind(config.enabled)
+        .bind(config.timeout_ms.map(|t| t as i64))
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(config)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_prewarm(&self, name: &str) -> Result<PrewarmConfig, LambdaError> {
+        let func = self.get_function(name).await?;
+        Ok(self
+            .load_prewarm(func.function_id)
+            .await?
+            .unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_prewarm(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_prewarm WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    async fn load_prewarm(&self, function_id: Uuid) -> Result<Option<PrewarmConfig>, LambdaError> {
+        let row =
+            sqlx::query("SELECT enabled, timeout_ms FROM function_prewarm WHERE function_id = ?")
+                .bind(function_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(row.map(|row| PrewarmConfig {
+            enabled: row.get("enabled"),
+            timeout_ms: row.get::<Option<i64>, _>("timeout_ms").map(|t| t as u64),
+        }))
+    }
+
+    /// How long a new container of `function` gets for its prewarm invocation, or `None`
+    /// when prewarming is off and the container can take traffic right away
+    pub async fn prewarm_timeout(&self, function: &Function) -> Option<std::time::Duration> {
+        match self.load_prewarm(function.function_id).await {
+            Ok(Some(config)) if config.enabled => Some(std::time::Duration::from_millis(
+                config.timeout_ms.unwrap_or(function.timeout * 1000),
+            )),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(
+                    "Failed to load prewarm settings for {}: {}",
+                    function.function_name, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Reserve the prewarm event for `instance_id` and wait for its handler in the
+    /// background. Call before starting the container so its first poll gets the event. The
+    /// container becomes WarmIdle if the handler succeeds in time and is removed otherwise;
+    /// the task returns whether it succeeded.
+    pub fn prewarm_container(
+        self: &Arc<Self>,
+        function: &Function,
+        container_id: &str,
+        instance_id: &str,
+        timeout: std::time::Duration,
+    ) -> tokio::task::JoinHandle<bool> {
+        let request_id = Uuid::new_v4().to_string();
+        let work_item = WorkItem {
+            request_id: request_id.clone(),
+            function: function.clone().into(),
+            payload: serde_json::to_vec(&prewarm_event()).unwrap_or_default(),
+            deadline_ms: Utc::now().timestamp_millis() + timeout.as_millis() as i64,
+            log_type: None,
+            client_context: None,
+            cognito_identity: None,
+        };
+        let pending = self.scheduler.pending();
+        let result = pending.register(request_id.clone());
+        let outcome = self.prewarm.hold(instance_id, work_item);
+
+        let control = self.clone();
+        let function_name = function.function_name.clone();
+        let container_id = container_id.to_string();
+        let instance_id = instance_id.to_string();
+        tokio::spawn(async move {
+            control
+                .finish_prewarm(
+                    &function_name,
+                    &container_id,
+                    &instance_id,
+                    &request_id,
+                    result,
+                    outcome,
+                    timeout,
+                )
+                .await
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn finish_prewarm(
+        &self,
+        function_name: &str,
+        container_id: &str,
+        instance_id: &str,
+        request_id: &str,
+        result: tokio::sync::oneshot::Receiver<crate::pending::InvocationResult>,
+        outcome: tokio::sync::watch::Sender<Option<bool>>,
+        timeout: std::time::Duration,
+    ) -> bool {
+        let pending = self.scheduler.pending();
+        let succeeded = match tokio::time::timeout(timeout, result).await {
+            Ok(Ok(result)) if result.ok => true,
+            Ok(Ok(result)) => {
+                warn!(
+                    "Prewarm invocation failed in container {} of {}: {}",
+                    container_id,
+                    function_name,
+                    String::from_utf8_lossy(&result.payload)
+                );
+                false
+            }
+            Ok(Err(_)) => false,
+            Err(_) => {
+                pending.cancel(request_id);
+                warn!(
+                    "Prewarm invocation timed out after {}ms in container {} of {}",
+                    timeout.as_millis(),
+                    container_id,
+                    function_name
+                );
+                false
+            }
+        };
+        self.prewarm.finish(instance_id, outcome, succeeded);
 
-            // Process the ZIP file
-            let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
-            let zip_info = packaging_service.process_zip(&zip_data).await?;
+        if succeeded {
+            info!("Container {} of {} prewarmed", container_id, function_name);
+            let _ = self
+                .warm_pool
+                .set_state_by_container_id(container_id, InstanceState::WarmIdle)
+                .await;
+        } else {
+            let _ = self.warm_pool.remove_container_by_id(container_id).await;
+            if let Err(e) = self.invoker.remove_container(container_id).await {
+                warn!("Failed to remove container {}: {}", container_id, e);
+            }
+        }
+        succeeded
+    }
 
-            // Store the ZIP file
-            packaging_service.store_zip(&zip_info)?;
+    async fn drain_function_containers(&self, function_id: Uuid) {
+        for id in self.warm_pool.drain_by_function_id(function_id).await {
+            let _ = self.invoker.remove_container(&id).await;
+        }
+    }
 
-            (zip_info.sha256, zip_info.total_size, FunctionState::Active)
-        } else {
-            ("".to_string(), 0, FunctionState::Pending)
+    /// Create a container for a function with its stop grace period, timezone, locale and
+    /// AWS emulation applied
+    pub async fn create_function_container(
+        &self,
+        function: &Function,
+        image_ref: &str,
+        mut env_vars: HashMap<String, String>,
+    ) -> Result<String, LambdaError> {
+        match self.load_locale(function.function_id).await {
+            Ok(Some(locale)) => {
+                if let Some(tz) = locale.timezone {
+                    env_vars.insert("TZ".to_string(), tz);
+                }
+                if let Some(locale) = locale.locale {
+                    env_vars.insert("LANG".to_string(), locale.clone());
+                    env_vars.insert("LC_ALL".to_string(), locale);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!(
+                "Failed to load timezone and locale for {}: {}",
+                function.function_name, e
+            ),
+        }
+        match self.load_aws_emulation(function.function_id).await {
+            Ok(stored) => {
+                let status = self.aws_emulation_status(&function.function_name, stored);
+                // The function's own environment wins, except for the reserved region
+                for (key, value) in status.environment {
+                    if key == "AWS_REGION" || key == "AWS_DEFAULT_REGION" {
+                        env_vars.insert(key, value);
+                    } else {
+                        env_vars.entry(key).or_insert(value);
+                    }
+                }
+            }
+            Err(e) => warn!(
+                "Failed to load AWS emulation for {}: {}",
+                function.function_name, e
+            ),
+        }
+        let stop_grace = match self.load_stop_grace(function.function_id).await {
+            Ok(grace) => grace,
+            Err(e) => {
+                warn!(
+                    "Failed to load stop grace period for {}: {}",
+                    function.function_name, e
+                );
+                None
+            }
         };
-
-        // Create function record
-        let function = Function {
-            function_id,
-            function_name: request.function_name.clone(),
-            runtime: request.runtime,
-            role: request.role,
-            handler: request.handler,
-            code_sha256,
-            description: request.description,
-            timeout: request.timeout.unwrap_or(3), // seconds, not milliseconds
-            memory_size: request.memory_size.unwrap_or(512),
-            environment: request.environment.unwrap_or_default(),
-            last_modified: now,
-            code_size,
-            version: "1".to_string(),
-            state,
-            state_reason: None,
-            state_reason_code: None,
+        let sandbox = match self.load_sandbox(function.function_id).await {
+            Ok(profile) => profile,
+            Err(e) => {
+                warn!(
+                    "Failed to load sandbox profile for {}: {}",
+                    function.function_name, e
+                );
+                None
+            }
+        };
+        let sandbox = sandbox.unwrap_or_else(|| self.config.sandbox.default_profile.clone());
+        let user = match self.load_user(function.function_id).await {
+            Ok(user) => user.unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to load user for {}: {}", function.function_name, e);
+                FunctionUser::default()
+            }
         };
+        self.invoker
+            .create_container(function, image_ref, env_vars, stop_grace, &sandbox, &user)
+            .await
+    }
 
+    /// Store owner, links and notes for a function, replacing any previous metadata
+    #[instrument(skip(self, metadata))]
+    pub async fn put_metadata(
+        &self,
+        name: &str,
+        metadata: FunctionMetadata,
+    ) -> Result<FunctionMetadata, LambdaError> {
+        let metadata = validation::normalize_function_metadata(metadata)?;
+        let func = self.get_function(name).await?;
         sqlx::query(
-            r#"
-            INSERT INTO functions (
-                function_id, function_name, runtime, role, handler, code_sha256,
-                description, timeout, memory_size, environment, last_modified,
-                code_size, version, state, state_reason, state_reason_code
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
+            r#"INSERT INTO function_metadata(function_id, owner, repo_url, docs_url, notes, updated_at)
+               VALUES(?, ?, ?, ?, ?, ?)
+               ON CONFLICT(function_id) DO UPDATE SET owner = excluded.owner, repo_url = excluded.repo_url, docs_url = excluded.docs_url, notes = excluded.notes, updated_at = excluded.updated_at"#,
         )
-        .bind(function.function_id)
-        .bind(&function.function_name)
-        .bind(&function.runtime)
-        .bind(&function.role)
-        .bind(&function.handler)
-        .bind(&function.code_sha256)
-        .bind(&function.description)
-        .bind(function.timeout as i64)
-        .bind(function.memory_size as i64)
-        .bind(serde_json::to_string(&function.environment).unwrap_or_default())
-        .bind(function.last_modified)
-        .bind(function.code_size as i64)
-        .bind(&function.version)
-        .bind(serde_json::to_string(&function.state).unwrap_or_default())
-        .bind(&function.state_reason)
-        .bind(&function.state_reason_code)
+        .bind(func.function_id)
+        .bind(&metadata.owner)
+        .bind(&metadata.repo_url)
+        .bind(&metadata.docs_url)
+        .bind(&metadata.notes)
+        .bind(chrono::Utc::now())
         .execute(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
+        Ok(metadata)
+    }
 
-        info!(
-            "Created function: {} with code SHA256: {}",
-            function.function_name, function.code_sha256
-        );
+    /// Metadata for a function; all fields empty when none was stored
+    #[instrument(skip(self))]
+    pub async fn get_metadata(&self, name: &str) -> Result<FunctionMetadata, LambdaError> {
+        let func = self.get_function(name).await?;
+        self.load_metadata(func.function_id).await
+    }
 
-        // Warm up container for faster cold starts (if enabled)
-        if function.state == FunctionState::Active && self.config.warmup.enabled {
-            if let Err(e) = self.warm_up_function(&function).await {
-                // Log warning but don't fail function creation
-                tracing::warn!(
-                    "Failed to warm up container for function {}: {}",
-                    function.function_name,
-                    e
-                );
-            }
-        }
+    async fn load_metadata(&self, function_id: Uuid) -> Result<FunctionMetadata, LambdaError> {
+        let row = sqlx::query(
+            "SELECT owner, repo_url, docs_url, notes FROM function_metadata WHERE function_id = ?",
+        )
+        .bind(function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
 
-        Ok(function)
+        Ok(match row {
+            Some(row) => FunctionMetadata {
+                owner: row.get("owner"),
+                repo_url: row.get("repo_url"),
+                docs_url: row.get("docs_url"),
+                notes: row.get("notes"),
+            },
+            None => FunctionMetadata::default(),
+        })
     }
 
     #[instrument(skip(self))]
-    pub async fn get_function(&self, name: &str) -> Result<Function, LambdaError> {
-        // Try cache first
-        if let Some(cached_function) = self.cache.get_function(name) {
-            return Ok((*cached_function).clone());
-        }
-
-        // Cache miss - fetch from database
-        let row = sqlx::query("SELECT * FROM functions WHERE function_name = ?")
-            .bind(name)
-            .fetch_optional(&self.pool)
+    pub async fn delete_metadata(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_metadata WHERE function_id = ?")
+            .bind(func.function_id)
+            .execute(&self.pool)
             .await
-            .map_err(LambdaError::SqlxError)?
-            .ok_or_else(|| LambdaError::FunctionNotFound {
-                function_name: name.to_string(),
-            })?;
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
 
-        let function = self.row_to_function(&row)?;
+    #[instrument(skip(self))]
+    pub async fn list_test_events(
+        &self,
+        name: &str,
+    ) -> Result<ListTestEventsResponse, LambdaError> {
+        let func = self.get_function(name).await?;
+        let rows = sqlx::query(
+            "SELECT event_id, name, payload, source, route_id, created_at FROM test_events WHERE function_id = ? ORDER BY created_at",
+        )
+        .bind(func.function_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let test_events = rows
+            .iter()
+            .map(|row| row_to_test_event(row, &func.function_name))
+            .collect();
+        Ok(ListTestEventsResponse { test_events })
+    }
 
-        // Cache the result
-        self.cache.set_function(name.to_string(), function.clone());
+    #[instrument(skip(self))]
+    pub async fn get_test_event(
+        &self,
+        name: &str,
+        event_id: Uuid,
+    ) -> Result<TestEvent, LambdaError> {
+        let func = self.get_function(name).await?;
+        let row = sqlx::query(
+            "SELECT event_id, name, payload, source, route_id, created_at FROM test_events WHERE function_id = ? AND event_id = ?",
+        )
+        .bind(func.function_id)
+        .bind(event_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::TestEventNotFound {
+            event_id: event_id.to_string(),
+        })?;
+        Ok(row_to_test_event(&row, &func.function_name))
+    }
 
-        Ok(function)
+    /// Save a test event, e.g. one exported from another function or server
+    #[instrument(skip(self, request))]
+    pub async fn create_test_event(
+        &self,
+        name: &str,
+        request: CreateTestEventRequest,
+    ) -> Result<TestEvent, LambdaError> {
+        let func = self.get_function(name).await?;
+        let event_name = request.name.trim().to_string();
+        if event_name.is_empty() || event_name.len() > MAX_TEST_EVENT_NAME_LEN {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "Test event name must be 1 to {MAX_TEST_EVENT_NAME_LEN} characters"
+                ),
+            });
+        }
+        let test_event = TestEvent {
+            event_id: Uuid::new_v4(),
+            function_name: func.function_name.clone(),
+            name: event_name,
+            payload: request.payload,
+            source: TestEventSource::Manual,
+            route_id: None,
+            created_at: Utc::now(),
+        };
+        self.insert_test_event(func.function_id, &test_event)
+            .await?;
+        Ok(test_event)
     }
 
     #[instrument(skip(self))]
-    pub async fn delete_function(&self, name: &str) -> Result<(), LambdaError> {
-        // Mark function for deletion immediately to reject new invocations
-        self.mark_function_for_deletion(name);
-        
-        // Get function first to get function_id for cache invalidation and image cleanup
-        let function = self.get_function(name).await.ok();
-
-        let result = sqlx::query("DELETE FROM functions WHERE function_name = ?")
-            .bind(name)
+    pub async fn delete_test_event(&self, name: &str, event_id: Uuid) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let result = sqlx::query("DELETE FROM test_events WHERE function_id = ? AND event_id = ?")
+            .bind(func.function_id)
+            .bind(event_id)
             .execute(&self.pool)
             .await
             .map_err(LambdaError::SqlxError)?;
-
         if result.rows_affected() == 0 {
-            return Err(LambdaError::FunctionNotFound {
-                function_name: name.to_string(),
+            return Err(LambdaError::TestEventNotFound {
+                event_id: event_id.to_string(),
             });
         }
+        Ok(())
+    }
 
-        // Clean up containers and Docker image if function exists
-        if let Some(func) = &function {
-            // First, stop and remove all containers for this function
-            let container_ids = self.warm_pool.drain_by_function_id(func.function_id).await;
-            for container_id in container_ids {
-                // Try to stop the container first (best effort)
-                if let Err(e) = self.invoker.stop_container(&container_id).await {
-                    debug!("Failed to stop container {} (may already be stopped): {}", container_id, e);
-                }
-                
-                // Remove the container
-                if let Err(e) = self.invoker.remove_container(&container_id).await {
-                    debug!("Failed to remove container {} (may already be removed): {}", container_id, e);
-                } else {
-                    info!("Removed container: {}", container_id);
-                }
-            }
-
-            // Then remove the Docker image
-            let image_ref = format!("lambda-home/{}:{}", func.function_name, func.code_sha256);
-
-            // Try to remove the Docker image (ignore errors if image doesn't exist)
-            if let Err(e) = self.invoker.remove_image(&image_ref, false).await {
-                info!(
-                    "Failed to remove Docker image {} (may not exist): {}",
-                    image_ref, e
-                );
-            } else {
-                info!("Removed Docker image: {}", image_ref);
-            }
-        }
-
-        // Note: Pending requests will naturally fail when containers are terminated
-        // TODO: Implement proper pending request cleanup by function ID
-
-        // Invalidate cache
-        self.cache.invalidate_function(name);
-        if let Some(func) = function {
-            self.cache
-                .invalidate_concurrency(&func.function_id.to_string());
-            self.cache
-                .invalidate_env_vars(&func.function_id.to_string());
-        }
-
-        // Unmark function for deletion
-        self.unmark_function_for_deletion(name);
-
-        info!("Deleted function: {}", name);
+    async fn insert_test_event(
+        &self,
+        function_id: Uuid,
+        event: &TestEvent,
+    ) -> Result<(), LambdaError> {
+        sqlx::query(
+            "INSERT INTO test_events(event_id, function_id, name, payload, source, route_id, created_at) VALUES(?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(event.event_id)
+        .bind(function_id)
+        .bind(&event.name)
+        .bind(event.payload.to_string())
+        .bind(event.source.as_str())
+        .bind(event.route_id)
+        .bind(event.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
         Ok(())
     }
 
+    /// GetFunction: the function together with its metadata
     #[instrument(skip(self))]
-    pub async fn cleanup_orphaned_images(&self) -> Result<usize, LambdaError> {
-        // Get all current function image references
-        let functions = self.list_functions(None, None).await?;
-        let active_image_refs: std::collections::HashSet<String> = functions
-            .functions
-            .into_iter()
-            .map(|func| format!("lambda-home/{}:{}", func.function_name, func.code_sha256))
-            .collect();
-
-        // Get all Lambda@Home Docker images
-        let all_lambda_images =
-            self.invoker
-                .list_lambda_images()
-                .await
-                .map_err(|e| LambdaError::InternalError {
-                    reason: format!("Failed to list Docker images: {e}"),
-                })?;
-
-        // Find orphaned images (images not referenced by any function)
-        let orphaned_images: Vec<String> = all_lambda_images
-            .into_iter()
-            .filter(|image_ref| !active_image_refs.contains(image_ref))
-            .collect();
+    pub async fn describe_function(&self, name: &str) -> Result<Function, LambdaError> {
+        let mut function = self.get_function(name).await?;
+        function.metadata = Some(self.load_metadata(function.function_id).await?);
+        function.function_arn = Some(self.config.identity.function_arn(&function.function_name));
+        Ok(function)
+    }
 
-        let mut removed_count = 0;
-        for image_ref in orphaned_images {
-            if let Err(e) = self.invoker.remove_image(&image_ref, false).await {
-                info!("Failed to remove orphaned image {}: {}", image_ref, e);
-            } else {
-                info!("Removed orphaned Docker image: {}", image_ref);
-                removed_count += 1;
-            }
+    /// GetFunction in AWS's shape, with the ZIP downloadable from the user API
+    #[instrument(skip(self))]
+    pub async fn get_function_response(
+        &self,
+        name: &str,
+    ) -> Result<GetFunctionResponse, LambdaError> {
+        let function = self.get_function(name).await?;
+        let reserved = self
+            .get_concurrency(name)
+            .await?
+            .reserved_concurrent_executions;
+        let code = match function.package_type {
+            PackageType::Image => FunctionCodeLocation {
+                repository_type: "ECR".to_string(),
+                location: None,
+                image_uri: function.image_uri.clone(),
+            },
+            PackageType::Zip => FunctionCodeLocation {
+                repository_type: "S3".to_string(),
+                location: (!function.code_sha256.is_empty()).then(|| {
+                    format!(
+                        "{}/api/functions/{}/code",
+                        self.user_api_url(),
+                        function.function_name
+                    )
+                }),
+                image_uri: None,
+            },
+        };
+        Ok(GetFunctionResponse {
+            configuration: FunctionConfiguration::new(
+                &function,
+                self.config.identity.function_arn(&function.function_name),
+            ),
+            code,
+            tags: self.function_tags(function.function_id).await?,
+            concurrency: reserved.map(|reserved_concurrent_executions| FunctionConcurrency {
+                reserved_concurrent_executions,
+            }),
+        })
+    }
+
+    /// The ZIP a function was created or last updated with
+    #[instrument(skip(self))]
+    pub async fn function_code(&self, name: &str) -> Result<Vec<u8>, LambdaError> {
+        let function = self.get_function(name).await?;
+        if function.package_type == PackageType::Image || function.code_sha256.is_empty() {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!("Function {name} has no ZIP code"),
+            });
         }
+        let packaging_service = lambda_packaging::PackagingService::new(self.config.clone());
+        packaging_service.load_zip(&function.code_sha256)
+    }
 
-        info!("Cleaned up {} orphaned Docker images", removed_count);
-        Ok(removed_count)
+    /// Tags of a function, by key
+    async fn function_tags(
+        &self,
+        function_id: Uuid,
+    ) -> Result<BTreeMap<String, String>, LambdaError> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT tag_key, tag_value FROM function_tags WHERE function_id = ?")
+                .bind(function_id)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        Ok(rows.into_iter().collect())
     }
 
+    /// Tags of a function, by key
     #[instrument(skip(self))]
-    pub async fn list_functions(
+    pub async fn list_function_tags(
         &self,
-        marker: Option<&String>,
-        max_items: Option<u32>,
-    ) -> Result<ListFunctionsResponse, LambdaError> {
-        let limit = max_items.unwrap_or(50).min(1000) as i64;
-        let offset = marker.and_then(|m| m.parse::<i64>().ok()).unwrap_or(0);
-
-        // Get total count
-        let total_count_row = sqlx::query("SELECT COUNT(*) as count FROM functions")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(LambdaError::SqlxError)?;
-        let total_count: i64 = total_count_row.get("count");
+        name: &str,
+    ) -> Result<BTreeMap<String, String>, LambdaError> {
+        let function = self.get_function(name).await?;
+        self.function_tags(function.function_id).await
+    }
 
-        let rows = sqlx::query("SELECT * FROM functions ORDER BY function_name LIMIT ? OFFSET ?")
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
+    /// Add tags to a function, overwriting the values of keys it already has
+    #[instrument(skip(self))]
+    pub async fn tag_function(
+        &self,
+        name: &str,
+        tags: BTreeMap<String, String>,
+    ) -> Result<(), LambdaError> {
+        let function = self.get_function(name).await?;
+        let existing = self.function_tags(function.function_id).await?;
+        let total = existing
+            .keys()
+            .chain(tags.keys())
+            .collect::<BTreeSet<_>>()
+            .len();
+        validation::check_tags(tags.iter(), total)
+            .map_err(|reason| LambdaError::InvalidRequest { reason })?;
+
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        for (key, value) in &tags {
+            sqlx::query(
+                r#"INSERT INTO function_tags (function_id, tag_key, tag_value) VALUES (?, ?, ?)
+                   ON CONFLICT(function_id, tag_key) DO UPDATE SET tag_value = excluded.tag_value"#,
+            )
+            .bind(function.function_id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
             .await
             .map_err(LambdaError::SqlxError)?;
+        }
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
 
-        let functions: Result<Vec<Function>, LambdaError> =
-            rows.iter().map(|row| self.row_to_function(row)).collect();
-
-        let next_marker = if rows.len() as i64 == limit {
-            Some((offset + limit).to_string())
-        } else {
-            None
-        };
-
-        Ok(ListFunctionsResponse {
-            functions: functions?,
-            next_marker,
-            total_count: Some(total_count as u32),
-        })
+    /// Remove tags from a function; keys it does not have are ignored
+    #[instrument(skip(self))]
+    pub async fn untag_function(&self, name: &str, keys: &[String]) -> Result<(), LambdaError> {
+        let function = self.get_function(name).await?;
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        for key in keys {
+            sqlx::query("DELETE FROM function_tags WHERE function_id = ? AND tag_key = ?")
+                .bind(function.function_id)
+                .bind(key)
+                .execute(&mut *tx)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        }
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+        Ok(())
     }
 
-    #[instrument(skip(self, _request))]
-    pub async fn update_function_code(
+    /// TagResource; only functions can be tagged
+    pub async fn tag_resource(
         &self,
-        name: &str,
-        _request: UpdateFunctionCodeRequest,
-    ) -> Result<Function, LambdaError> {
-        // TODO: Implement code update logic
-        let mut function = self.get_function(name).await?;
-        function.last_modified = Utc::now();
-
-        sqlx::query("UPDATE functions SET last_modified = ? WHERE function_name = ?")
-            .bind(function.last_modified)
-            .bind(name)
-            .execute(&self.pool)
-            .await
-            .map_err(LambdaError::SqlxError)?;
+        arn: &str,
+        tags: BTreeMap<String, String>,
+    ) -> Result<(), LambdaError> {
+        self.tag_function(tagged_function(arn)?, tags).await
+    }
 
-        // Invalidate cache since function was updated
-        self.cache.invalidate_function(name);
-        self.cache
-            .invalidate_env_vars(&function.function_id.to_string());
+    /// UntagResource
+    pub async fn untag_resource(&self, arn: &str, keys: &[String]) -> Result<(), LambdaError> {
+        self.untag_function(tagged_function(arn)?, keys).await
+    }
 
-        Ok(function)
+    /// ListTags
+    pub async fn list_tags(&self, arn: &str) -> Result<ListTagsResponse, LambdaError> {
+        Ok(ListTagsResponse {
+            tags: self.list_function_tags(tagged_function(arn)?).await?,
+        })
     }
 
+    // ---------------- Function groups ----------------
+
     #[instrument(skip(self, request))]
-    pub async fn update_function_configuration(
+    pub async fn create_group(
         &self,
-        name: &str,
-        request: UpdateFunctionConfigurationRequest,
-    ) -> Result<Function, LambdaError> {
-        let mut function = self.get_function(name).await?;
-        let env_will_change = request.environment.is_some();
-
-        if let Some(role) = request.role {
-            function.role = Some(role);
-        }
-        if let Some(handler) = request.handler {
-            function.handler = handler;
-        }
-        if let Some(description) = request.description {
-            function.description = Some(description);
-        }
-        if let Some(timeout) = request.timeout {
-            function.timeout = timeout;
-        }
-        if let Some(memory_size) = request.memory_size {
-            function.memory_size = memory_size;
-        }
-        if let Some(environment) = request.environment {
-            function.environment = environment;
+        request: CreateFunctionGroupRequest,
+    ) -> Result<FunctionGroup, LambdaError> {
+        let description = request.description.filter(|d| !d.is_empty());
+        validation::validate_group(&request.name, description.as_deref())?;
+        let now = Utc::now();
+        let inserted = sqlx::query(
+            "INSERT INTO function_groups (name, description, disabled, created_at, updated_at)
+             VALUES (?, ?, 0, ?, ?) ON CONFLICT(name) DO NOTHING",
+        )
+        .bind(&request.name)
+        .bind(&description)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        if inserted.rows_affected() == 0 {
+            return Err(LambdaError::GroupAlreadyExists {
+                group: request.name,
+            });
         }
+        info!("Created function group {}", request.name);
+        self.get_group(&request.name).await
+    }
 
-        function.last_modified = Utc::now();
+    /// Every group with its functions, and the functions in none
+    #[instrument(skip(self))]
+    pub async fn list_groups(&self) -> Result<ListFunctionGroupsResponse, LambdaError> {
+        let rows = sqlx::query("SELECT * FROM function_groups ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        let members: Vec<(String, String)> = sqlx::query_as(
+            "SELECT m.group_name, f.function_name FROM function_group_members m
+             JOIN functions f ON f.function_id = m.function_id
+             ORDER BY f.function_name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let mut by_group: HashMap<String, Vec<String>> = HashMap::new();
+        for (group, function_name) in members {
+            by_group.entry(group).or_default().push(function_name);
+        }
+        let groups = rows
+            .iter()
+            .map(|row| {
+                let name: String = row.get("name");
+                let functions = by_group.remove(&name).unwrap_or_default();
+                row_to_group(row, functions)
+            })
+            .collect();
+        let ungrouped: Vec<String> = sqlx::query_scalar(
+            "SELECT function_name FROM functions
+             WHERE function_id NOT IN (SELECT function_id FROM function_group_members)
+             ORDER BY function_name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(ListFunctionGroupsResponse { groups, ungrouped })
+    }
 
-        sqlx::query(
-            r#"
-            UPDATE functions SET 
-                role = ?, handler = ?, description = ?, timeout = ?, 
-                memory_size = ?, environment = ?, last_modified = ?
-            WHERE function_name = ?
-            "#,
+    #[instrument(skip(self))]
+    pub async fn get_group(&self, name: &str) -> Result<FunctionGroup, LambdaError> {
+        let row = sqlx::query("SELECT * FROM function_groups WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .ok_or_else(|| LambdaError::GroupNotFound {
+                group: name.to_string(),
+            })?;
+        let functions: Vec<String> = sqlx::query_scalar(
+            "SELECT f.function_name FROM function_group_members m
+             JOIN functions f ON f.function_id = m.function_id
+             WHERE m.group_name = ? ORDER BY f.function_name",
         )
-        .bind(&function.role)
-        .bind(&function.handler)
-        .bind(&function.description)
-        .bind(function.timeout as i64)
-        .bind(function.memory_size as i64)
-        .bind(serde_json::to_string(&function.environment).unwrap_or_default())
-        .bind(function.last_modified)
         .bind(name)
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
+        Ok(row_to_group(&row, functions))
+    }
 
-        // If env is updated, drain existing warm containers so new env applies immediately
-        if env_will_change {
-            let ids = self
-                .warm_pool
-                .drain_by_function_id(function.function_id)
-                .await;
-            for id in ids {
-                let _ = self.invoker.remove_container(&id).await;
-            }
+    async fn require_group(&self, name: &str) -> Result<(), LambdaError> {
+        let exists: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM function_groups WHERE name = ?")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        match exists {
+            Some(_) => Ok(()),
+            None => Err(LambdaError::GroupNotFound {
+                group: name.to_string(),
+            }),
         }
-
-        // Invalidate cache since function configuration was updated
-        self.cache.invalidate_function(name);
-        self.cache
-            .invalidate_env_vars(&function.function_id.to_string());
-
-        Ok(function)
     }
 
     #[instrument(skip(self, request))]
-    pub async fn publish_version(
+    pub async fn update_group(
         &self,
         name: &str,
-        request: PublishVersionRequest,
-    ) -> Result<Version, LambdaError> {
-        let function = self.get_function(name).await?;
-        let version_id = Uuid::new_v4();
-        let now = Utc::now();
-
-        let version = Version {
-            version_id,
-            function_id: function.function_id,
-            version: "2".to_string(), // TODO: Implement proper versioning
-            description: request.description,
-            code_sha256: function.code_sha256,
-            last_modified: now,
-            code_size: function.code_size,
-        };
-
-        sqlx::query(
-            r#"
-            INSERT INTO versions (
-                version_id, function_id, version, description, code_sha256,
-                last_modified, code_size
-            ) VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
+        request: UpdateFunctionGroupRequest,
+    ) -> Result<FunctionGroup, LambdaError> {
+        let description = request.description.filter(|d| !d.is_empty());
+        validation::validate_group(name, description.as_deref())?;
+        let updated = sqlx::query(
+            "UPDATE function_groups SET description = ?, updated_at = ? WHERE name = ?",
         )
-        .bind(version.version_id)
-        .bind(version.function_id)
-        .bind(&version.version)
-        .bind(&version.description)
-        .bind(&version.code_sha256)
-        .bind(version.last_modified)
-        .bind(version.code_size as i64)
+        .bind(&description)
+        .bind(Utc::now())
+        .bind(name)
         .execute(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
+        if updated.rows_affected() == 0 {
+            return Err(LambdaError::GroupNotFound {
+                group: name.to_string(),
+            });
+        }
+        self.get_group(name).await
+    }
 
-        Ok(version)
+    /// Delete a group; its functions stay, ungrouped
+    #[instrument(skip(self))]
+    pub async fn delete_group(&self, name: &str) -> Result<(), LambdaError> {
+        let deleted = sqlx::query("DELETE FROM function_groups WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if deleted.rows_affected() == 0 {
+            return Err(LambdaError::GroupNotFound {
+                group: name.to_string(),
+            });
+        }
+        info!("Deleted function group {}", name);
+        Ok(())
     }
 
+    /// Refuse or allow invocations of every function of a group
     #[instrument(skip(self))]
-    pub async fn list_versions(
+    pub async fn set_group_disabled(
         &self,
         name: &str,
-        marker: Option<&String>,
-        max_items: Option<u32>,
-    ) -> Result<ListVersionsResponse, LambdaError> {
-        let function = self.get_function(name).await?;
-        let limit = max_items.unwrap_or(50).min(1000) as i64;
-        let offset = marker.and_then(|m| m.parse::<i64>().ok()).unwrap_or(0);
-
-        let rows = sqlx::query(
-            "SELECT * FROM versions WHERE function_id = ? ORDER BY version LIMIT ? OFFSET ?",
-        )
-        .bind(function.function_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(LambdaError::SqlxError)?;
-
-        let versions: Result<Vec<Version>, LambdaError> =
-            rows.iter().map(|row| self.row_to_version(row)).collect();
+        disabled: bool,
+    ) -> Result<FunctionGroup, LambdaError> {
+        let updated =
+            sqlx::query("UPDATE function_groups SET disabled = ?, updated_at = ? WHERE name = ?")
+                .bind(disabled)
+                .bind(Utc::now())
+                .bind(name)
+                .execute(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        if updated.rows_affected() == 0 {
+            return Err(LambdaError::GroupNotFound {
+                group: name.to_string(),
+            });
+        }
+        info!(
+            "{} function group {}",
+            if disabled { "Disabled" } else { "Enabled" },
+            name
+        );
+        self.get_group(name).await
+    }
 
-        let next_marker = if rows.len() as i64 == limit {
-            Some((offset + limit).to_string())
-        } else {
-            None
+    /// Stop and remove the containers of every function of a group
+    #[instrument(skip(self))]
+    pub async fn drain_group(&self, name: &str) -> Result<BatchFunctionsResponse, LambdaError> {
+        let request = BatchFunctionsRequest {
+            selector: FunctionSelector {
+                group: Some(name.to_string()),
+                ..Default::default()
+            },
+            operation: BatchOperation::Drain,
         };
+        self.batch_functions(request, None).await
+    }
 
-        Ok(ListVersionsResponse {
-            versions: versions?,
-            next_marker,
-        })
+    /// Group the invocations of a function are refused by, if it is disabled
+    async fn disabled_group_of(&self, function_id: Uuid) -> Result<Option<String>, LambdaError> {
+        sqlx::query_scalar(
+            "SELECT g.name FROM function_group_members m
+             JOIN function_groups g ON g.name = m.group_name
+             WHERE m.function_id = ? AND g.disabled = 1",
+        )
+        .bind(function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)
     }
 
-    #[instrument(skip(self, request))]
-    pub async fn create_alias(
+    /// Move a function into a group, out of the one it was in
+    #[instrument(skip(self))]
+    pub async fn set_function_group(
         &self,
         name: &str,
-        request: CreateAliasRequest,
-    ) -> Result<Alias, LambdaError> {
-        let function = self.get_function(name).await?;
-        let alias_id = Uuid::new_v4();
-        let now = Utc::now();
-
-        let alias = Alias {
-            alias_id,
-            function_id: function.function_id,
-            name: request.name,
-            function_version: request.function_version,
-            description: request.description,
-            routing_config: request.routing_config,
-            revision_id: Uuid::new_v4().to_string(),
-            last_modified: now,
-        };
-
+        group: &str,
+    ) -> Result<FunctionGroupMembership, LambdaError> {
+        let function = self.get_function(name).await?;
+        self.require_group(group).await?;
         sqlx::query(
-            r#"
-            INSERT INTO aliases (
-                alias_id, function_id, name, function_version, description,
-                routing_config, revision_id, last_modified
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
+            "INSERT INTO function_group_members (function_id, group_name) VALUES (?, ?)
+             ON CONFLICT(function_id) DO UPDATE SET group_name = excluded.group_name",
         )
-        .bind(alias.alias_id)
-        .bind(alias.function_id)
-        .bind(&alias.name)
-        .bind(&alias.function_version)
-        .bind(&alias.description)
-        .bind(serde_json::to_string(&alias.routing_config).unwrap_or_default())
-        .bind(&alias.revision_id)
-        .bind(alias.last_modified)
+        .bind(function.function_id)
+        .bind(group)
         .execute(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
-
-        Ok(alias)
+        Ok(FunctionGroupMembership {
+            group: group.to_string(),
+        })
     }
 
+    /// Group of a function; None when it is in none
     #[instrument(skip(self))]
-    pub async fn get_alias(&self, name: &str, alias: &str) -> Result<Alias, LambdaError> {
+    pub async fn get_function_group(
+        &self,
+        name: &str,
+    ) -> Result<Option<FunctionGroupMembership>, LambdaError> {
         let function = self.get_function(name).await?;
+        let group: Option<String> = sqlx::query_scalar(
+            "SELECT group_name FROM function_group_members WHERE function_id = ?",
+        )
+        .bind(function.function_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(group.map(|group| FunctionGroupMembership { group }))
+    }
 
-        let row = sqlx::query("SELECT * FROM aliases WHERE function_id = ? AND name = ?")
+    /// Take a function out of its group
+    #[instrument(skip(self))]
+    pub async fn remove_function_group(&self, name: &str) -> Result<(), LambdaError> {
+        let function = self.get_function(name).await?;
+        sqlx::query("DELETE FROM function_group_members WHERE function_id = ?")
             .bind(function.function_id)
-            .bind(alias)
-            .fetch_optional(&self.pool)
+            .execute(&self.pool)
             .await
-            .map_err(LambdaError::SqlxError)?
-            .ok_or_else(|| LambdaError::FunctionNotFound {
-                function_name: format!("{name}/{alias}"),
-            })?;
-
-        self.row_to_alias(&row)
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
     }
 
+    /// Executions of a group's functions in the last `window`, in total and per function
     #[instrument(skip(self))]
-    pub async fn update_alias(
+    pub async fn group_metrics(
         &self,
         name: &str,
-        alias: &str,
-        request: UpdateAliasRequest,
-    ) -> Result<Alias, LambdaError> {
-        let mut alias_obj = self.get_alias(name, alias).await?;
-
-        if let Some(function_version) = request.function_version {
-            alias_obj.function_version = function_version;
-        }
-        if let Some(description) = request.description {
-            alias_obj.description = Some(description);
-        }
-        if let Some(routing_config) = request.routing_config {
-            alias_obj.routing_config = Some(routing_config);
+        window: std::time::Duration,
+    ) -> Result<GroupMetrics, LambdaError> {
+        if window > MAX_INSIGHTS_WINDOW {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "Group metrics cover at most {}h",
+                    MAX_INSIGHTS_WINDOW.as_secs() / 3600
+                ),
+            });
         }
+        self.require_group(name).await?;
+        let span = chrono::Duration::from_std(window).map_err(|e| LambdaError::InvalidRequest {
+            reason: format!("Invalid window: {e}"),
+        })?;
+        let since = Utc::now() - span;
 
-        alias_obj.revision_id = Uuid::new_v4().to_string();
-        alias_obj.last_modified = Utc::now();
-
-        sqlx::query(
-            r#"
-            UPDATE aliases SET 
-                function_version = ?, description = ?, routing_config = ?,
-                revision_id = ?, last_modified = ?
-            WHERE function_id = ? AND name = ?
-            "#,
+        let rows = sqlx::query(
+            "SELECT f.function_name,
+                    COUNT(e.execution_id) AS invocations,
+                    COALESCE(SUM(e.status = 'Failed'), 0) AS errors,
+                    AVG(e.duration_ms) AS avg_duration_ms,
+                    COUNT(e.duration_ms) AS timed
+             FROM function_group_members m
+             JOIN functions f ON f.function_id = m.function_id
+             LEFT JOIN executions e ON e.function_id = f.function_id AND e.start_time >= ?
+             WHERE m.group_name = ?
+             GROUP BY f.function_name
+             ORDER BY invocations DESC, f.function_name",
         )
-        .bind(&alias_obj.function_version)
-        .bind(&alias_obj.description)
-        .bind(serde_json::to_string(&alias_obj.routing_config).unwrap_or_default())
-        .bind(&alias_obj.revision_id)
-        .bind(alias_obj.last_modified)
-        .bind(alias_obj.function_id)
-        .bind(alias)
-        .execute(&self.pool)
+        .bind(since)
+        .bind(name)
+        .fetch_all(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
 
-        Ok(alias_obj)
+        let mut functions = Vec::with_capacity(rows.len());
+        let (mut invocations, mut errors, mut timed, mut total_duration) = (0, 0, 0, 0.0);
+        for row in &rows {
+            let metrics = GroupFunctionMetrics {
+                function_name: row.get("function_name"),
+                invocations: row.get::<i64, _>("invocations") as u64,
+                errors: row.get::<i64, _>("errors") as u64,
+                avg_duration_ms: row.get("avg_duration_ms"),
+            };
+            let function_timed = row.get::<i64, _>("timed") as u64;
+            invocations += metrics.invocations;
+            errors += metrics.errors;
+            timed += function_timed;
+            total_duration += metrics.avg_duration_ms.unwrap_or(0.0) * function_timed as f64;
+            functions.push(metrics);
+        }
+        Ok(GroupMetrics {
+            group: name.to_string(),
+            since,
+            invocations,
+            errors,
+            error_rate: if invocations == 0 {
+                0.0
+            } else {
+                errors as f64 / invocations as f64
+            },
+            avg_duration_ms: (timed > 0).then(|| total_duration / timed as f64),
+            functions,
+        })
     }
 
+    /// Invocations of a function running and waiting now, and how much of the concurrency
+    /// limit they count toward is in use
     #[instrument(skip(self))]
-    pub async fn delete_alias(&self, name: &str, alias: &str) -> Result<(), LambdaError> {
-        let function = self.get_function(name).await?;
+    pub async fn concurrency_usage(&self, name: &str) -> Result<ConcurrencyUsage, LambdaError> {
+        let func = self.get_function(name).await?;
+        Ok(self.concurrency_manager.usage(&func))
+    }
 
-        let result = sqlx::query("DELETE FROM aliases WHERE function_id = ? AND name = ?")
-            .bind(function.function_id)
-            .bind(alias)
+    /// Concurrency in use by every function running or with reserved concurrency
+    pub fn concurrency_overview(&self) -> ConcurrencyOverview {
+        self.concurrency_manager.overview()
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_concurrency(&self, name: &str) -> Result<(), LambdaError> {
+        let func = self.get_function(name).await?;
+        let _ = sqlx::query("DELETE FROM function_concurrency WHERE function_id = ?")
+            .bind(func.function_id)
             .execute(&self.pool)
             .await
             .map_err(LambdaError::SqlxError)?;
+        self.concurrency_manager.set_reserved_limit(&func, None);
 
-        if result.rows_affected() == 0 {
-            return Err(LambdaError::FunctionNotFound {
-                function_name: format!("{name}/{alias}"),
-            });
-        }
+        // Invalidate cache since concurrency config was deleted
+        self.cache
+            .invalidate_concurrency(&func.function_id.to_string());
 
         Ok(())
     }
 
-    #[instrument(skip(self))]
-    pub async fn list_aliases(
+    /// The version provisioned concurrency for `qualifier` keeps warm: the version itself, or
+    /// the one an alias points at. `$LATEST` cannot have provisioned concurrency.
+    async fn provisioned_function(
         &self,
+        function: Function,
+        qualifier: &str,
+    ) -> Result<Function, LambdaError> {
+        match Qualifier::parse(Some(qualifier)) {
+            Qualifier::Latest => Err(LambdaError::InvalidRequest {
+                reason:
+                    "Provisioned concurrency needs a published version or an alias, not $LATEST"
+                        .to_string(),
+            }),
+            Qualifier::Version(version) => self.function_at_version(function, version).await,
+            Qualifier::Alias(alias) => {
+                let alias = self.get_alias(&function.function_name, alias).await?;
+                self.function_at_version(function, &alias.function_version)
+                    .await
+            }
+        }
+    }
+
+    /// Keep `executions` containers warm for a version or alias. The image is built right away
+    /// rather than on the first invocation, and the autoscaler starts the containers.
+    #[instrument(skip(self, request))]
+    pub async fn put_provisioned_concurrency(
+        self: &Arc<Self>,
         name: &str,
-        marker: Option<&String>,
-        max_items: Option<u32>,
-    ) -> Result<ListAliasesResponse, LambdaError> {
+        qualifier: &str,
+        request: PutProvisionedConcurrencyConfigRequest,
+    ) -> Result<ProvisionedConcurrencyConfig, LambdaError> {
+        let requested = request.provisioned_concurrent_executions;
+        if requested == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "ProvisionedConcurrentExecutions must be at least 1".to_string(),
+            });
+        }
         let function = self.get_function(name).await?;
-        let limit = max_items.unwrap_or(50).min(1000) as i64;
-        let offset = marker.and_then(|m| m.parse::<i64>().ok()).unwrap_or(0);
-
-        let rows = sqlx::query(
-            "SELECT * FROM aliases WHERE function_id = ? ORDER BY name LIMIT ? OFFSET ?",
+        let target = self
+            .provisioned_function(function.clone(), qualifier)
+            .await?;
+        let reserved = self
+            .get_concurrency(name)
+            .await?
+            .reserved_concurrent_executions;
+        if let Some(reserved) = reserved.filter(|reserved| requested > *reserved) {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "ProvisionedConcurrentExecutions {requested} exceeds the function's reserved concurrency of {reserved}"
+                ),
+            });
+        }
+        sqlx::query(
+            "INSERT INTO provisioned_concurrency (function_id, qualifier, requested, last_modified)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT(function_id, qualifier) DO UPDATE SET
+                 requested = excluded.requested, last_modified = excluded.last_modified",
         )
         .bind(function.function_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
+        .bind(qualifier)
+        .bind(requested as i64)
+        .bind(Utc::now())
+        .execute(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
+        info!(
+            "Provisioned {} containers for {}:{}",
+            requested, name, qualifier
+        );
 
-        let aliases: Result<Vec<Alias>, LambdaError> =
-            rows.iter().map(|row| self.row_to_alias(row)).collect();
+        let control = self.clone();
+        tokio::spawn(async move {
+            let image_ref = target.image_ref();
+            if let Err(e) = control
+                .build_function_image(&target, &image_ref, BuildPriority::Normal)
+                .await
+            {
+                warn!(
+                    "Failed to build the provisioned image of {}: {}",
+                    target.function_name, e
+                );
+            }
+        });
+        self.get_provisioned_concurrency(name, qualifier).await
+    }
 
-        let next_marker = if rows.len() as i64 == limit {
-            Some((offset + limit).to_string())
+    /// The provisioned concurrency of a version or alias and how many of its containers run
+    #[instrument(skip(self))]
+    pub async fn get_provisioned_concurrency(
+        &self,
+        name: &str,
+        qualifier: &str,
+    ) -> Result<ProvisionedConcurrencyConfig, LambdaError> {
+        let function = self.get_function(name).await?;
+        let row = sqlx::query(
+            "SELECT requested, last_modified FROM provisioned_concurrency
+             WHERE function_id = ? AND qualifier = ?",
+        )
+        .bind(function.function_id)
+        .bind(qualifier)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::ProvisionedConcurrencyConfigNotFound {
+            function_name: name.to_string(),
+            qualifier: qualifier.to_string(),
+        })?;
+        let requested = row.get::<i64, _>("requested") as u32;
+        let target = self.provisioned_function(function, qualifier).await?;
+        let key = crate::queues::FnKey::for_function(&target);
+        let allocated = self.warm_pool.count_running(&key).await as u32;
+        let available = (self
+            .warm_pool
+            .count_state(&key, crate::warm_pool::InstanceState::WarmIdle)
+            .await
+            + self
+                .warm_pool
+                .count_state(&key, crate::warm_pool::InstanceState::Active)
+                .await) as u32;
+        let failure = self
+            .failed_builds
+            .known_failure(&target, &target.image_ref());
+        let status = if failure.is_some() {
+            ProvisionedConcurrencyStatus::Failed
+        } else if available >= requested {
+            ProvisionedConcurrencyStatus::Ready
         } else {
-            None
+            ProvisionedConcurrencyStatus::InProgress
         };
-
-        Ok(ListAliasesResponse {
-            aliases: aliases?,
-            next_marker,
+        Ok(ProvisionedConcurrencyConfig {
+            requested_provisioned_concurrent_executions: requested,
+            allocated_provisioned_concurrent_executions: allocated.min(requested),
+            available_provisioned_concurrent_executions: available.min(requested),
+            status,
+            status_reason: failure.map(|e| e.to_string()),
+            last_modified: row.get("last_modified"),
         })
     }
 
+    /// Stop keeping containers warm for a version or alias; the idle watchdog reclaims them
     #[instrument(skip(self))]
-    pub async fn put_concurrency(
+    pub async fn delete_provisioned_concurrency(
         &self,
         name: &str,
-        config: ConcurrencyConfig,
+        qualifier: &str,
     ) -> Result<(), LambdaError> {
-        let func = self.get_function(name).await?;
-        let now = chrono::Utc::now();
-        let reserved = config.reserved_concurrent_executions.map(|v| v as i64);
-        sqlx::query(
-            r#"INSERT INTO function_concurrency(function_id, reserved_concurrent_executions, updated_at)
-               VALUES(?, ?, ?)
-               ON CONFLICT(function_id) DO UPDATE SET reserved_concurrent_executions = excluded.reserved_concurrent_executions, updated_at = excluded.updated_at"#
+        let function = self.get_function(name).await?;
+        let deleted = sqlx::query(
+            "DELETE FROM provisioned_concurrency WHERE function_id = ? AND qualifier = ?",
         )
-        .bind(func.function_id)
-        .bind(reserved)
-        .bind(now)
+        .bind(function.function_id)
+        .bind(qualifier)
         .execute(&self.pool)
         .await
         .map_err(LambdaError::SqlxError)?;
-        // Update in-memory limiter
-        self.concurrency_manager
-            .set_reserved_limit(func.function_id, config.reserved_concurrent_executions);
-
-        // Invalidate cache since concurrency config was updated
-        self.cache
-            .invalidate_concurrency(&func.function_id.to_string());
-
+        if deleted.rows_affected() == 0 {
+            return Err(LambdaError::ProvisionedConcurrencyConfigNotFound {
+                function_name: name.to_string(),
+                qualifier: qualifier.to_string(),
+            });
+        }
+        info!("Removed provisioned concurrency of {}:{}", name, qualifier);
         Ok(())
     }
 
-    #[instrument(skip(self))]
-    pub async fn get_concurrency(&self, name: &str) -> Result<ConcurrencyConfig, LambdaError> {
-        let func = self.get_function(name).await?;
-
-        // Try cache first
-        if let Some(cached_config) = self.cache.get_concurrency(&func.function_id.to_string()) {
-            return Ok(cached_config);
-        }
-
-        // Cache miss - fetch from database
-        let reserved: Option<i64> = sqlx::query_scalar(
-            "SELECT reserved_concurrent_executions FROM function_concurrency WHERE function_id = ?",
+    /// Containers to keep warm per key, for every provisioned version and alias. Configs
+    /// whose alias or version no longer resolves are skipped.
+    pub async fn provisioned_targets(
+        &self,
+    ) -> Result<HashMap<crate::queues::FnKey, usize>, LambdaError> {
+        let rows: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT f.function_name, p.qualifier, p.requested FROM provisioned_concurrency p
+             JOIN functions f ON f.function_id = p.function_id",
         )
-        .bind(func.function_id)
-        .fetch_optional(&self.pool)
+        .fetch_all(&self.pool)
         .await
-        .map_err(LambdaError::SqlxError)?
-        .flatten();
+        .map_err(LambdaError::SqlxError)?;
+        let mut targets = HashMap::new();
+        for (name, qualifier, requested) in rows {
+            let function = self.get_function(&name).await?;
+            match self.provisioned_function(function, &qualifier).await {
+                Ok(target) => {
+                    *targets
+                        .entry(crate::queues::FnKey::for_function(&target))
+                        .or_insert(0) += requested as usize;
+                }
+                Err(e) => debug!(
+                    "Skipping provisioned concurrency of {}:{}: {}",
+                    name, qualifier, e
+                ),
+            }
+        }
+        Ok(targets)
+    }
 
-        let config = ConcurrencyConfig {
-            reserved_concurrent_executions: reserved.map(|v| v as u32),
+    pub async fn invoke_function(
+        &self,
+        request: InvokeRequest,
+    ) -> Result<InvokeResponse, LambdaError> {
+        self.invoke_with_request_id(Uuid::new_v4().to_string(), request)
+            .await
+    }
+
+    /// Run `request` under `req_id` between the function's pre- and post-invoke hooks
+    async fn invoke_with_request_id(
+        &self,
+        req_id: String,
+        mut request: InvokeRequest,
+    ) -> Result<InvokeResponse, LambdaError> {
+        let hooks = self.invocation_hooks(&request.function_name).await;
+        if let Some(hook) = &hooks.pre_invoke {
+            if let Some(payload) = self.run_pre_invoke_hook(hook, &req_id, &request).await? {
+                request.payload = Some(payload);
+            }
+        }
+        let Some(hook) = &hooks.post_invoke else {
+            return self.invoke_traced(req_id, request).await;
         };
 
-        // Cache the result
-        self.cache
-            .set_concurrency(func.function_id.to_string(), config.clone());
+        let function_name = request.function_name.clone();
+        let payload = request.payload.clone();
+        let response = self.invoke_traced(req_id.clone(), request).await?;
+        let event = HookEvent {
+            hook: HookPhase::PostInvoke,
+            function_name,
+            request_id: req_id,
+            payload,
+            response: Some(HookInvocationResult {
+                payload: response.payload.clone(),
+                function_error: response.function_error.clone(),
+                duration_ms: response.duration_ms,
+            }),
+        };
+        if let Err(reason) = self.call_hook(hook, &event).await {
+            hooks::hook_failure(hook, &event, reason)?;
+        }
+        Ok(response)
+    }
 
-        Ok(config)
+    /// Hooks of the function invoked; none if they cannot be loaded, or the function does
+    /// not exist, which the invocation itself reports
+    async fn invocation_hooks(&self, function_name: &str) -> FunctionHooks {
+        let Ok(function) = self.get_function(function_name).await else {
+            return FunctionHooks::default();
+        };
+        match self.load_hooks(function.function_id).await {
+            Ok(hooks) => hooks.unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to load hooks of {}: {}", function_name, e);
+                FunctionHooks::default()
+            }
+        }
     }
 
-    #[instrument(skip(self))]
-    pub async fn delete_concurrency(&self, name: &str) -> Result<(), LambdaError> {
-        let func = self.get_function(name).await?;
-        let _ = sqlx::query("DELETE FROM function_concurrency WHERE function_id = ?")
-            .bind(func.function_id)
-            .execute(&self.pool)
+    /// The payload the pre-invoke hook replaces the request's with, if any
+    async fn run_pre_invoke_hook(
+        &self,
+        hook: &InvocationHook,
+        req_id: &str,
+        request: &InvokeRequest,
+    ) -> Result<Option<serde_json::Value>, LambdaError> {
+        let event = HookEvent {
+            hook: HookPhase::PreInvoke,
+            function_name: request.function_name.clone(),
+            request_id: req_id.to_string(),
+            payload: request.payload.clone(),
+            response: None,
+        };
+        match self
+            .call_hook(hook, &event)
             .await
-            .map_err(LambdaError::SqlxError)?;
-        self.concurrency_manager
-            .set_reserved_limit(func.function_id, None);
+            .and_then(hooks::pre_invoke_result)
+        {
+            Ok(result) => match result.reject {
+                Some(reason) => Err(LambdaError::InvocationRejected {
+                    function_name: event.function_name,
+                    reason,
+                }),
+                None => Ok(result.payload),
+            },
+            Err(reason) => hooks::hook_failure(hook, &event, reason).map(|()| None),
+        }
+    }
 
-        // Invalidate cache since concurrency config was deleted
-        self.cache
-            .invalidate_concurrency(&func.function_id.to_string());
+    /// Call `hook` with `event` within its timeout; what it answered, or why it failed
+    async fn call_hook(
+        &self,
+        hook: &InvocationHook,
+        event: &HookEvent,
+    ) -> Result<Option<serde_json::Value>, String> {
+        let timeout = std::time::Duration::from_millis(hook.timeout_ms);
+        let function_name = match &hook.target {
+            HookTarget::Webhook { url } => {
+                return self.hooks.call_webhook(url, event, timeout).await;
+            }
+            HookTarget::Function { function_name } => function_name,
+        };
+        let request = InvokeRequest {
+            function_name: function_name.clone(),
+            invocation_type: lambda_models::InvocationType::RequestResponse,
+            log_type: None,
+            client_context: None,
+            payload: Some(serde_json::to_value(event).unwrap_or_default()),
+            qualifier: None,
+            idempotent: false,
+            trace: false,
+        };
+        // Hook functions run without hooks of their own
+        let invocation = self.run_invocation(Uuid::new_v4().to_string(), request);
+        match tokio::time::timeout(timeout, invocation).await {
+            Err(_elapsed) => Err(format!(
+                "{function_name} timed out after {} ms",
+                hook.timeout_ms
+            )),
+            Ok(Err(e)) => Err(e.to_string()),
+            Ok(Ok(response)) if response.function_error.is_some() => Err(format!(
+                "{function_name} failed: {}",
+                response.payload.unwrap_or_default()
+            )),
+            Ok(Ok(response)) => Ok(response.payload),
+        }
+    }
 
-        Ok(())
+    /// Run `request` under `req_id`, storing the runtime API exchange once it finished when
+    /// the request asked for a trace, and noting throttles for the function's insights
+    async fn invoke_traced(
+        &self,
+        req_id: String,
+        request: InvokeRequest,
+    ) -> Result<InvokeResponse, LambdaError> {
+        let function_name = request.function_name.clone();
+        let result = if request.trace {
+            self.invocation_traces.begin(&req_id, &function_name);
+            let result = self.run_invocation(req_id.clone(), request).await;
+            if let Err(e) = self.invocation_traces.finish(&req_id).await {
+                warn!("Failed to store trace of invocation {}: {}", req_id, e);
+            }
+            result
+        } else {
+            self.run_invocation(req_id, request).await
+        };
+        if result.as_ref().is_err_and(insights::is_throttle) {
+            self.throttles.record(&function_name, Utc::now());
+        }
+        result
     }
 
     #[instrument(skip(self, request))]
-    pub async fn invoke_function(
+    async fn run_invocation(
         &self,
+        req_id: String,
         request: InvokeRequest,
     ) -> Result<InvokeResponse, LambdaError> {
         // 1) Lookup function meta from Registry. If not found → 404.
         let function = self.get_function(&request.function_name).await?;
+        // The version the qualifier names runs in containers of its own
+        let function = self
+            .resolve_qualifier(function, request.qualifier.as_deref())
+            .await?;
 
         // 1.5) Check if function is being deleted - reject new invocations
         if self.is_function_being_deleted(&request.function_name) {
@@ -988,13 +6797,26 @@ impl ControlPlane {
                 function_name: request.function_name.clone(),
             });
         }
+        if let Some(group) = self.disabled_group_of(function.function_id).await? {
+            return Err(LambdaError::FunctionDisabled {
+                function_name: request.function_name.clone(),
+                group,
+            });
+        }
+
+        if self.config.quotas.max_daily_invocations.is_some() {
+            let today = self.invocations_since(start_of_day(Utc::now())).await?;
+            check_quota(
+                "max_daily_invocations",
+                today,
+                1,
+                self.config.quotas.max_daily_invocations,
+            )?;
+        }
 
         // 2) Acquire concurrency token (RAII guard ensures release on any exit)
         let _token_guard = self.concurrency_manager.acquire_token(&function).await?;
 
-        // 3) Create request ID: req_id = Uuid::new_v4().to_string()
-        let req_id = uuid::Uuid::new_v4().to_string();
-
         // 3.5) Record execution start in database (deferred async)
         let start_time = chrono::Utc::now();
         self.execution_tracker.record_execution_start(
@@ -1004,223 +6826,1174 @@ impl ControlPlane {
             start_time,
         );
 
-        // 4) Register pending waiter: let rx = pending.register(req_id.clone())
-        let rx = self.scheduler.pending().register(req_id.clone());
-
-        // 5) Build WorkItem
+        // 4) Build WorkItem
         let work_item =
             WorkItem::from_invoke_request(req_id.clone(), function.clone(), request.clone());
-
-        // 6) Ensure at least one warm container exists for this function-key (fn+rt+ver+env)
-        // Important: do NOT consume availability here. Just check count to avoid
-        // toggling a container to unavailable inadvertently.
         let fn_key = crate::queues::FnKey::from_work_item(&work_item);
-        if self.warm_pool.container_count(&fn_key).await == 0 {
+
+        // Retries after a container failure share the function's timeout budget, plus time
+        // for a container to start; the deadline enforcer holds the function to its timeout
+        // once a runtime picked the invocation up
+        let deadline = tokio::time::Instant::now()
+            + tokio::time::Duration::from_secs(function.timeout)
+            + tokio::time::Duration::from_millis(self.config.defaults.init_buffer_ms);
+        let max_retries = if request.idempotent {
+            self.config.retry.max_retries
+        } else {
+            0
+        };
+        let mut retries = 0;
+        // A duplicate would stream its response to no one
+        let hedge_after = if self.scheduler.pending().is_streaming(&req_id) {
+            None
+        } else {
+            self.hedge_delay(&function).await
+        };
+
+        loop {
+            // 5) Register pending waiter: let rx = pending.register(req_id.clone())
+            let rx = self.scheduler.pending().register(req_id.clone());
+
+            // 6) Ensure at least one warm container exists for this function-key (fn+rt+ver+env)
+            let start_kind = self.ensure_container(&function, &fn_key).await?;
+            self.warmup_tracker
+                .record(&function.function_name, start_kind);
+
+            // 7) Enqueue: scheduler.enqueue(work_item).await
+            self.scheduler
+                .enqueue(work_item.clone())
+                .await
+                .map_err(|e| LambdaError::InternalError {
+                    reason: format!("Failed to enqueue work item: {e}"),
+                })?;
+
+            // 8) Wait for result until the function's configured timeout
+            let outcome = match hedge_after {
+                Some(delay) => {
+                    self.await_hedged(&fn_key, &work_item, rx, delay, deadline)
+                        .await
+                }
+                None => tokio::time::timeout_at(deadline, rx).await,
+            };
+
+            // Container-level failures are safe to re-run on another container if the caller allows it
+            let infrastructure_failure = match &outcome {
+                Ok(Ok(result)) => result.infrastructure_failure,
+                Ok(Err(_canceled)) => true,
+                Err(_elapsed) => false,
+            };
+            if infrastructure_failure && retries < max_retries {
+                retries += 1;
+                warn!(
+                    "Invocation {} lost to a container failure, retrying ({}/{})",
+                    req_id, retries, max_retries
+                );
+                continue;
+            }
+
+            let mut headers = std::collections::HashMap::new();
+            headers.insert("X-Amz-Request-Id".to_string(), req_id.clone());
+            if retries > 0 {
+                headers.insert("X-Lambdah-Retry-Count".to_string(), retries.to_string());
+            }
+
+            return match outcome {
+                Ok(Ok(InvocationResult {
+                    timed_out: true, ..
+                }))
+                | Err(_) => {
+                    // Timeout: mark timeout and return 200 with X-Amz-Function-Error: Unhandled
+                    let timeout_json = serde_json::json!({
+                        "errorMessage": format!("Task timed out after {} seconds", function.timeout),
+                        "errorType": crate::pending::TIMED_OUT_ERROR_TYPE
+                    });
+                    let timeout_body = serde_json::to_vec(&timeout_json).unwrap_or_default();
+                    // Discarded so a container yet to pick it up never runs it; one already
+                    // running it is dealt with by the deadline enforcer
+                    let _ = self.scheduler.pending().discard(
+                        &req_id,
+                        InvocationResult::err("Unhandled", timeout_body),
+                        Utc::now().timestamp_millis(),
+                    );
+
+                    // Record timeout in execution record (deferred async)
+                    let end_time = chrono::Utc::now();
+                    let duration_ms = (end_time - start_time).num_milliseconds() as u64;
+                    self.execution_tracker.record_execution_timeout(
+                        req_id.clone(),
+                        function.timeout,
+                        end_time,
+                    );
+
+                    Ok(InvokeResponse {
+                        status_code: 200,
+                        payload: Some(timeout_json),
+                        executed_version: Some(function.version.clone()),
+                        function_error: Some(FunctionError::Unhandled),
+                        log_result: None,
+                        headers,
+                        duration_ms: Some(duration_ms),
+                    })
+                }
+                Ok(Ok(result)) if result.infrastructure_failure => {
+                    // Container died mid-invoke: not a function code error
+                    let end_time = chrono::Utc::now();
+                    let duration_ms = (end_time - start_time).num_milliseconds() as u64;
+                    let payload: serde_json::Value =
+                        serde_json::from_slice(&result.payload).unwrap_or(serde_json::Value::Null);
+                    let message = payload
+                        .get("errorMessage")
+                        .and_then(|m| m.as_str())
+                        .map(|m| m.to_string());
+                    self.execution_tracker.record_execution_failure(
+                        req_id.clone(),
+                        crate::pending::INFRASTRUCTURE_ERROR_TYPE.to_string(),
+                        message,
+                        Some(payload.clone()),
+                        end_time,
+                    );
+
+                    Ok(InvokeResponse {
+                        status_code: 200,
+                        payload: Some(payload),
+                        executed_version: Some(function.version.clone()),
+                        function_error: Some(FunctionError::Unhandled),
+                        log_result: None,
+                        headers,
+                        duration_ms: Some(duration_ms),
+                    })
+                }
+                Ok(Ok(result)) => {
+                    // Calculate duration
+                    let end_time = chrono::Utc::now();
+                    let duration_ms = (end_time - start_time).num_milliseconds() as u64;
+
+                    // Success: build Lambda response
+                    if result.ok {
+                        self.latency_tracker
+                            .record(&function.function_name, duration_ms);
+                        Ok(InvokeResponse {
+                            status_code: 200,
+                            payload: Some(
+                                serde_json::from_slice(&result.payload)
+                                    .unwrap_or(serde_json::Value::Null),
+                            ),
+                            executed_version: result
+                                .executed_version
+                                .or_else(|| Some(function.version.clone())),
+                            function_error: None,
+                            log_result: result.log_tail_b64,
+                            headers,
+                            duration_ms: Some(duration_ms),
+                        })
+                    } else {
+                        // Function error: return 200 with X-Amz-Function-Error header
+                        Ok(InvokeResponse {
+                            status_code: 200,
+                            payload: Some(
+                                serde_json::from_slice(&result.payload)
+                                    .unwrap_or(serde_json::Value::Null),
+                            ),
+                            executed_version: result
+                                .executed_version
+                                .or_else(|| Some(function.version.clone())),
+                            function_error: result.function_error.as_ref().map(|fe| {
+                                match fe.as_str() {
+                                    "Handled" => FunctionError::Handled,
+                                    _ => FunctionError::Unhandled,
+                                }
+                            }),
+                            log_result: result.log_tail_b64,
+                            headers,
+                            duration_ms: Some(duration_ms),
+                        })
+                    }
+                }
+                Ok(Err(_canceled)) => {
+                    // Runtime channel dropped → 200 with X-Amz-Function-Error: InitError
+                    error!("Runtime channel closed for invocation: {}", req_id);
+
+                    // Record init error in execution record (deferred async)
+                    let end_time = chrono::Utc::now();
+                    let duration_ms = (end_time - start_time).num_milliseconds() as u64;
+                    self.execution_tracker
+                        .record_execution_init_error(req_id.clone(), end_time);
+
+                    Ok(InvokeResponse {
+                        status_code: 200,
+                        payload: Some(serde_json::json!({
+                            "errorMessage": "Runtime channel closed",
+                            "errorType": "InitError"
+                        })),
+                        executed_version: Some(function.version.clone()),
+                        function_error: Some(FunctionError::Unhandled),
+                        log_result: None,
+                        headers,
+                        duration_ms: Some(duration_ms),
+                    })
+                }
+            };
+        }
+        // Token guard automatically releases concurrency token when dropped
+    }
+
+    async fn hedge_delay(&self, function: &Function) -> Option<std::time::Duration> {
+        let config = match self.get_hedging(&function.function_name).await {
+            Ok(config) => config,
+            Err(e) => {
+                warn!(
+                    "Failed to load hedging config for {}: {}",
+                    function.function_name, e
+                );
+                return None;
+            }
+        };
+        hedge_delay(&config, &self.latency_tracker, &function.function_name)
+    }
+
+    /// Wait for `rx`; if it is still outstanding after `delay` and an idle warm container
+    /// exists, dispatch a duplicate and take whichever copy finishes first.
+    async fn await_hedged(
+        &self,
+        fn_key: &crate::queues::FnKey,
+        work_item: &WorkItem,
+        mut rx: tokio::sync::oneshot::Receiver<InvocationResult>,
+        delay: std::time::Duration,
+        deadline: tokio::time::Instant,
+    ) -> Result<
+        Result<InvocationResult, tokio::sync::oneshot::error::RecvError>,
+        tokio::time::error::Elapsed,
+    > {
+        let hedge_at = tokio::time::Instant::now() + delay;
+        if hedge_at >= deadline {
+            return tokio::time::timeout_at(deadline, rx).await;
+        }
+        if let Ok(result) = tokio::time::timeout_at(hedge_at, &mut rx).await {
+            return Ok(result);
+        }
+
+        // Never cold start for a duplicate: only hedge onto an idle warm container
+        if !self.warm_pool.has_available(fn_key).await {
+            return tokio::time::timeout_at(deadline, rx).await;
+        }
+
+        let hedge_id = uuid::Uuid::new_v4().to_string();
+        let mut hedge_item = work_item.clone();
+        hedge_item.request_id = hedge_id.clone();
+        // The copy gets no execution record of its own: the caller's request id carries the
+        // outcome of whichever copy wins
+        let hedge_rx = self.scheduler.pending().register(hedge_id.clone());
+        if let Err(e) = self.scheduler.enqueue(hedge_item).await {
+            warn!(
+                "Failed to enqueue hedge for {}: {}",
+                work_item.request_id, e
+            );
+            self.cancel_hedge_copy(fn_key, &hedge_id);
+            return tokio::time::timeout_at(deadline, rx).await;
+        }
+        info!(
+            "Hedged invocation {} with {} after {:?}",
+            work_item.request_id, hedge_id, delay
+        );
+
+        let outcome = tokio::time::timeout_at(deadline, async {
+            tokio::select! {
+                result = &mut rx => (result, false),
+                result = hedge_rx => (result, true),
+            }
+        })
+        .await;
+        match outcome {
+            Ok((result, hedge_won)) => {
+                let loser = if hedge_won {
+                    &work_item.request_id
+                } else {
+                    &hedge_id
+                };
+                self.cancel_hedge_copy(fn_key, loser);
+                if let (true, Ok(result)) = (hedge_won, &result) {
+                    self.record_hedge_outcome(&work_item.request_id, result);
+                }
+                Ok(result)
+            }
+            Err(elapsed) => {
+                // The caller's timeout handling covers the original request
+                self.cancel_hedge_copy(fn_key, &hedge_id);
+                Err(elapsed)
+            }
+        }
+    }
+
+    /// Stop waiting on the losing copy of a hedged invoke and drop it if no container claimed
+    /// it. A result the loser posts later finds no waiter and is not recorded
+    fn cancel_hedge_copy(&self, fn_key: &crate::queues::FnKey, request_id: &str) {
+        self.scheduler.pending().cancel(request_id);
+        self.scheduler.queues().remove(fn_key, request_id);
+    }
+
+    /// Record the result of the hedge copy that won as the execution of the caller's request.
+    /// Timeouts and container failures are recorded by the caller as for any invoke
+    fn record_hedge_outcome(&self, request_id: &str, result: &InvocationResult) {
+        if result.timed_out || result.infrastructure_failure {
+            return;
+        }
+        let end_time = chrono::Utc::now();
+        if result.ok {
+            self.execution_tracker
+                .record_execution_success(request_id.to_string(), end_time);
+            return;
+        }
+        let payload =
+            serde_json::from_slice::<serde_json::Value>(&result.payload).unwrap_or_default();
+        let error_type = payload
+            .get("errorType")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)
+            .or_else(|| result.function_error.clone())
+            .unwrap_or_else(|| "Unhandled".to_string());
+        let error_message = payload
+            .get("errorMessage")
+            .and_then(|m| m.as_str())
+            .map(str::to_string);
+        self.execution_tracker.record_execution_failure(
+            request_id.to_string(),
+            error_type,
+            error_message,
+            Some(payload),
+            end_time,
+        );
+    }
+
+    /// Make sure a container can pick up work for `fn_key`, starting or creating one if needed.
+    /// Important: do NOT consume availability here. Just check count to avoid
+    /// toggling a container to unavailable inadvertently.
+    async fn ensure_container(
+        &self,
+        function: &Function,
+        fn_key: &crate::queues::FnKey,
+    ) -> Result<StartKind, LambdaError> {
+        if self.warm_pool.container_count(fn_key).await == 0 {
             info!(
                 "No container present, creating new container for function: {}",
                 function.function_name
             );
+            let container_id = self.create_and_start_container(function, fn_key).await?;
+            info!(
+                "Created and started new container: {} for function: {}",
+                container_id, function.function_name
+            );
+            Ok(StartKind::Cold)
+        } else if !self.warm_pool.has_available(fn_key).await {
+            let busy = self
+                .warm_pool
+                .count_state(fn_key, InstanceState::Active)
+                .await;
+            if busy > 0 && self.warm_pool.warm_room(function.function_id).await == Some(0) {
+                // At max_warm_containers: queue behind the busy ones instead
+                info!(
+                    "Warm container maximum reached, queueing {} behind busy containers",
+                    function.function_name
+                );
+                return Ok(StartKind::Warm);
+            }
+            match self.scale_up(function, fn_key).await {
+                Err(
+                    LambdaError::HostMemoryExhausted { .. } | LambdaError::QuotaExceeded { .. },
+                ) if busy > 0 => {
+                    // No room for another container: queue behind the busy ones instead
+                    warn!(
+                        "Memory budget reached, queueing {} behind busy containers",
+                        function.function_name
+                    );
+                    Ok(StartKind::Warm)
+                }
+                other => other,
+            }
+        } else {
+            Ok(StartKind::Warm)
+        }
+    }
 
-            // Build image reference
-            let image_ref = format!(
-                "lambda-home/{}:{}",
-                function.function_name, function.code_sha256
+    async fn scale_up(
+        &self,
+        function: &Function,
+        fn_key: &crate::queues::FnKey,
+    ) -> Result<StartKind, LambdaError> {
+        // Prefer restarting a stopped container for this key
+        if let Some(stopped_id) = self.warm_pool.get_one_stopped(fn_key).await {
+            info!(
+                "Re-starting stopped container {} for function: {}",
+                stopped_id, function.function_name
+            );
+            let _placement = self.reserve_memory(fn_key, function.memory_size).await?;
+            self.invoker.start_container(&stopped_id).await?;
+            let _ = self
+                .warm_pool
+                .set_state_by_container_id(&stopped_id, InstanceState::WarmIdle)
+                .await;
+            Ok(StartKind::Restarted)
+        } else {
+            // All existing containers are busy; scale up by creating a new one
+            info!(
+                "All containers busy for {}. Scaling up by 1.",
+                function.function_name
+            );
+            let container_id = self.create_and_start_container(function, fn_key).await?;
+            info!(
+                "Scaled up with new container: {} for function: {}",
+                container_id, function.function_name
             );
+            Ok(StartKind::Cold)
+        }
+    }
 
-            // Build Docker image first
+    /// Make room for a container of `memory_mb` under the host memory budget, stopping idle
+    /// containers of other functions if needed. Hold the returned guard until the new
+    /// container is running and registered so concurrent placements see its memory.
+    pub async fn reserve_memory(
+        &self,
+        fn_key: &crate::queues::FnKey,
+        memory_mb: u64,
+    ) -> Result<tokio::sync::OwnedMutexGuard<()>, LambdaError> {
+        let guard = self.placement_lock.clone().lock_owned().await;
+        // The warm memory quota is a budget of its own; whichever is lower applies
+        let quota_mb = self.config.quotas.max_warm_memory_mb;
+        let budget_mb = match (self.config.limits.host_memory_budget_mb, quota_mb) {
+            (Some(host_mb), Some(quota_mb)) => Some(host_mb.min(quota_mb)),
+            (host_mb, quota_mb) => host_mb.or(quota_mb),
+        };
+        if budget_mb.is_none() {
+            return Ok(guard);
+        }
+
+        let committed_mb = self.warm_pool.committed_memory_mb().await;
+        let idle = self.warm_pool.idle_containers_except(fn_key).await;
+        match plan_placement(budget_mb, committed_mb, memory_mb, &idle) {
+            Placement::Fits => Ok(guard),
+            Placement::Evict(container_ids) => {
+                for container_id in container_ids {
+                    info!(
+                        "Stopping idle container {} to free memory for {}",
+                        container_id, fn_key.function_name
+                    );
+                    // Marked first so the container monitor treats the exit as ours
+                    let _ = self
+                        .warm_pool
+                        .set_state_by_container_id(&container_id, InstanceState::Stopping)
+                        .await;
+                    self.invoker.stop_container(&container_id).await?;
+                    let _ = self
+                        .warm_pool
+                        .set_state_by_container_id(&container_id, InstanceState::Stopped)
+                        .await;
+                }
+                Ok(guard)
+            }
+            Placement::Refuse { .. } if quota_mb == budget_mb => Err(LambdaError::QuotaExceeded {
+                namespace: DEFAULT_NAMESPACE.to_string(),
+                quota: "max_warm_memory_mb".to_string(),
+                limit: quota_mb.unwrap_or_default(),
+            }),
+            Placement::Refuse { available_mb } => Err(LambdaError::HostMemoryExhausted {
+                requested_mb: memory_mb,
+                available_mb,
+            }),
+        }
+    }
+
+    /// Build a function's image when there is disk to spare, notifying webhooks of failures.
+    /// The build waits in the build queue, behind builds of higher `priority`. A build that
+    /// failed recently for a reason another attempt will not fix fails again without running.
+    pub async fn build_function_image(
+        &self,
+        function: &Function,
+        image_ref: &str,
+        priority: BuildPriority,
+    ) -> Result<(), LambdaError> {
+        if let Some(failure) = self.failed_builds.known_failure(function, image_ref) {
+            debug!(
+                "Not rebuilding {} of function {}: its last build failed",
+                image_ref, function.function_name
+            );
+            return Err(failure);
+        }
+        let build = async {
+            self.disk_monitor.check_build_capacity()?;
             let mut packaging_service =
                 lambda_packaging::PackagingService::new(self.config.clone());
-            packaging_service.build_image(&function, &image_ref, self.config.server.port_runtime_api).await?;
+            if function.package_type == PackageType::Image {
+                // Image functions bring their own image, pulled instead of built
+                self.invoker.ensure_image(image_ref).await?;
+            } else {
+                // Pinned functions only build on their digest; the others build on the digest
+                // their tag currently resolves to, and record which digest that was
+                let runtime = self.load_runtime_management(function).await?;
+                let pinned = match runtime.update_runtime_on {
+                    UpdateRuntimeOn::Manual => runtime.runtime_version.clone(),
+                    // Not pulled yet: the build pulls the tag, resolved below
+                    UpdateRuntimeOn::Auto => packaging_service
+                        .base_image_digest(&runtime.base_image)
+                        .await
+                        .unwrap_or(None),
+                };
+                let base_image =
+                    lambda_packaging::pinned_base_image(&runtime.base_image, pinned.as_deref());
+                packaging_service
+                    .build_image(
+                        function,
+                        image_ref,
+                        self.config.server.port_runtime_api,
+                        &base_image,
+                    )
+                    .await?;
+                let built_on = match pinned {
+                    Some(digest) => Some(digest),
+                    None => packaging_service
+                        .base_image_digest(&base_image)
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("Failed to resolve digest of {}: {}", base_image, e);
+                            None
+                        }),
+                };
+                let changed =
+                    built_on.filter(|d| runtime.last_build_runtime_version.as_ref() != Some(d));
+                if let Some(digest) = changed {
+                    if let Err(e) = self.record_build_runtime_version(function, &digest).await {
+                        tracing::warn!(
+                            "Failed to record base image digest of function {}: {}",
+                            function.function_name,
+                            e
+                        );
+                    }
+                }
+            }
+            let recorded = match packaging_service.image_digest(image_ref).await {
+                Ok(image_digest) => self.record_image_digest(function, &image_digest).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = recorded {
+                tracing::warn!(
+                    "Failed to record image digest of function {}: {}",
+                    function.function_name,
+                    e
+                );
+            }
+            Ok::<(), LambdaError>(())
+        };
+        let result = self
+            .builds
+            .run(&function.function_name, image_ref, priority, build)
+            .await;
+        if !matches!(result, Err(LambdaError::BuildCancelled { .. })) {
+            self.failed_builds
+                .record(function, image_ref, result.as_ref().map(|_| ()));
+        }
+        if let Some(e) = result
+            .as_ref()
+            .err()
+            .filter(|e| !matches!(e, LambdaError::BuildCancelled { .. }))
+        {
+            self.webhooks.emit(
+                WebhookEventType::BuildFailed,
+                Some(&function.function_name),
+                serde_json::json!({ "image": image_ref, "error": e.to_string() }),
+            );
+        }
+        if let Err(e) = self
+            .record_build_outcome(function, result.as_ref().map(|_| ()))
+            .await
+        {
+            tracing::warn!(
+                "Failed to record build outcome of function {}: {}",
+                function.function_name,
+                e
+            );
+        }
+        result
+    }
 
-            // Create container: generate instance id and inject as env
-            let instance_id = uuid::Uuid::new_v4().to_string();
-            let mut env_vars = self.resolve_env_vars(&function).await?;
-            env_vars.insert("LAMBDAH_INSTANCE_ID".to_string(), instance_id.clone());
-            let container_id = self
-                .invoker
-                .create_container(&function, &image_ref, env_vars)
-                .await?;
-            self.invoker.start_container(&container_id).await?;
+    /// Reflect a build in the function's `State` and `LastUpdateStatus`, which AWS SDK
+    /// waiters poll. A successful build activates a Pending or Failed function; a failed one
+    /// fails a function that never got going, and only marks the update of an Active one.
+    pub async fn record_build_outcome(
+        &self,
+        function: &Function,
+        outcome: Result<(), &LambdaError>,
+    ) -> Result<(), LambdaError> {
+        let settled = matches!(
+            function.state,
+            FunctionState::Active | FunctionState::Inactive
+        );
+        if outcome.is_ok() && settled && function.last_update_status == LastUpdateStatus::Successful
+        {
+            return Ok(());
+        }
+        // A failed image build explains itself with the tail of its output
+        let reason = outcome.err().map(|e| match e {
+            LambdaError::ImageBuildFailed { build_log, .. } => format!("{e}\n{build_log}"),
+            e => e.to_string(),
+        });
+        let (state, state_reason) = match &reason {
+            _ if settled => (function.state.clone(), None),
+            Some(reason) => (FunctionState::Failed, Some(reason.clone())),
+            None => (FunctionState::Active, None),
+        };
+        let last_update_status = if reason.is_some() {
+            LastUpdateStatus::Failed
+        } else {
+            LastUpdateStatus::Successful
+        };
+        let reason_code = |reason: &Option<String>| reason.as_ref().map(|_| "ImageBuildFailed");
+        sqlx::query(
+            "UPDATE functions SET state = ?, state_reason = ?, state_reason_code = ?, \
+             last_update_status = ?, last_update_status_reason = ?, \
+             last_update_status_reason_code = ? WHERE function_id = ?",
+        )
+        .bind(serde_json::to_string(&state).unwrap_or_default())
+        .bind(&state_reason)
+        .bind(reason_code(&state_reason))
+        .bind(serde_json::to_string(&last_update_status).unwrap_or_default())
+        .bind(&reason)
+        .bind(reason_code(&reason))
+        .bind(function.function_id)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        self.cache.invalidate_function(&function.function_name);
+        Ok(())
+    }
 
-            // Add to warm pool
-            let warm_container = crate::warm_pool::WarmContainer {
-                container_id: container_id.clone(),
-                instance_id: instance_id.clone(),
-                function_id: function.function_id,
-                image_ref: image_ref.clone(),
-                created_at: std::time::Instant::now(),
-                last_used: std::time::Instant::now(),
-                state: crate::warm_pool::InstanceState::WarmIdle, // Ready for work
-            };
-            self.warm_pool
-                .add_warm_container(fn_key.clone(), warm_container)
-                .await;
+    /// Image builds running and waiting for a slot
+    pub fn list_builds(&self) -> ListBuildsResponse {
+        ListBuildsResponse {
+            builds: self.builds.list(),
+        }
+    }
+
+    /// Cancel a queued or running build. A running `docker build` is stopped, and its
+    /// build context and untagged images are removed.
+    #[instrument(skip(self))]
+    pub fn cancel_build(&self, build_id: &str) -> Result<Build, LambdaError> {
+        let build = self.builds.cancel(build_id)?;
+        info!(
+            "Cancelled {:?} build {} of function {}",
+            build.status, build.build_id, build.function_name
+        );
+        Ok(build)
+    }
+
+    /// Build the image, start a fresh container and register it in the warm pool
+    async fn create_and_start_container(
+        &self,
+        function: &Function,
+        fn_key: &crate::queues::FnKey,
+    ) -> Result<String, LambdaError> {
+        // Build image reference
+        let image_ref = function.image_ref();
+
+        // Build Docker image first; an invocation is waiting on it
+        self.build_function_image(function, &image_ref, BuildPriority::Urgent)
+            .await?;
+
+        // Create container: generate instance id and inject as env
+        let instance_id = uuid::Uuid::new_v4().to_string();
+        let mut env_vars = self.resolve_env_vars(function).await?;
+        env_vars.insert("LAMBDAH_INSTANCE_ID".to_string(), instance_id.clone());
+        env_vars.insert(
+            INSTANCE_TOKEN_ENV.to_string(),
+            self.instance_tokens.token(&instance_id),
+        );
+        let _placement = self.reserve_memory(fn_key, function.memory_size).await?;
+        let container_id = self
+            .create_function_container(function, &image_ref, env_vars)
+            .await?;
+        self.invoker.start_container(&container_id).await?;
+
+        // Add to warm pool
+        let warm_container = crate::warm_pool::WarmContainer {
+            container_id: container_id.clone(),
+            instance_id,
+            function_id: function.function_id,
+            image_ref,
+            memory_mb: function.memory_size,
+            created_at: std::time::Instant::now(),
+            last_used: std::time::Instant::now(),
+            state: crate::warm_pool::InstanceState::WarmIdle, // Ready for work
+        };
+        self.warm_pool
+            .add_warm_container(fn_key.clone(), warm_container)
+            .await;
+        Ok(container_id)
+    }
+
+    /// Look up an execution by request id, including the captured error payload
+    #[instrument(skip(self))]
+    pub async fn get_execution(&self, request_id: &str) -> Result<ExecutionRecord, LambdaError> {
+        let row = sqlx::query(
+            "SELECT e.*, f.function_name
+             FROM executions e LEFT JOIN functions f ON f.function_id = e.function_id
+             WHERE e.aws_request_id = ? OR e.execution_id = ?
+             LIMIT 1",
+        )
+        .bind(request_id)
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::ExecutionNotFound {
+            request_id: request_id.to_string(),
+        })?;
+
+        let error = row
+            .get::<Option<String>, _>("error_type")
+            .map(|error_type| {
+                let payload: Option<serde_json::Value> = row
+                    .get::<Option<String>, _>("error_payload")
+                    .and_then(|p| serde_json::from_str(&p).ok());
+                let stack_trace = payload
+                    .as_ref()
+                    .and_then(|p| p.get("stackTrace"))
+                    .and_then(|t| serde_json::from_value::<Vec<String>>(t.clone()).ok());
+                ExecutionErrorDetail {
+                    error_type,
+                    error_message: row.get("error_message"),
+                    stack_trace,
+                    payload,
+                }
+            });
+
+        let execution_id: String = row.get("execution_id");
+        Ok(ExecutionRecord {
+            traced: self.invocation_traces.exists(&execution_id).await?,
+            execution_id,
+            aws_request_id: row.get("aws_request_id"),
+            function_name: row.get("function_name"),
+            function_version: row.get("function_version"),
+            container_id: row.get("container_id"),
+            status: row.get("status"),
+            start_time: row.get("start_time"),
+            end_time: row.get("end_time"),
+            duration_ms: row.get("duration_ms"),
+            error,
+        })
+    }
+
+    /// Failed executions of a function within `window`, grouped by error fingerprint
+    #[instrument(skip(self))]
+    pub async fn error_summary(
+        &self,
+        name: &str,
+        window: std::time::Duration,
+    ) -> Result<ErrorSummary, LambdaError> {
+        let function = self.get_function(name).await?;
+        let since = crate::window_start(Utc::now(), window)?;
+
+        let rows = sqlx::query(
+            "SELECT aws_request_id, error_type, error_message, start_time
+             FROM executions
+             WHERE function_id = ? AND status = 'Failed' AND start_time >= ?
+             ORDER BY start_time DESC",
+        )
+        .bind(function.function_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let failures: Vec<FailedExecution> = rows
+            .iter()
+            .map(|row| FailedExecution {
+                request_id: row.get("aws_request_id"),
+                error_type: row
+                    .get::<Option<String>, _>("error_type")
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                error_message: row.get("error_message"),
+                start_time: row.get("start_time"),
+            })
+            .collect();
+
+        Ok(ErrorSummary {
+            function_name: function.function_name,
+            window_seconds: window.as_secs(),
+            total_errors: failures.len() as u64,
+            groups: group_errors(failures),
+        })
+    }
+
+    /// Health of a function over the last `window`, each figure compared with the window
+    /// before it
+    #[instrument(skip(self))]
+    pub async fn function_insights(
+        &self,
+        name: &str,
+        window: std::time::Duration,
+    ) -> Result<FunctionInsights, LambdaError> {
+        self.features.require(FeatureFlag::FunctionInsights)?;
+        if window > MAX_INSIGHTS_WINDOW {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "Insights cover at most {}h",
+                    MAX_INSIGHTS_WINDOW.as_secs() / 3600
+                ),
+            });
+        }
+        let function = self.get_function(name).await?;
+        let now = Utc::now();
+        let span = chrono::Duration::from_std(window).map_err(|e| LambdaError::InvalidRequest {
+            reason: format!("Invalid window: {e}"),
+        })?;
+        let since = now - span;
 
-            info!(
-                "Created and started new container: {} for function: {}",
-                container_id, function.function_name
-            );
-        } else if !self.warm_pool.has_available(&fn_key).await {
-            // Prefer restarting a stopped container for this key
-            if let Some(stopped_id) = self.warm_pool.get_one_stopped(&fn_key).await {
-                info!(
-                    "Re-starting stopped container {} for function: {}",
-                    stopped_id, function.function_name
-                );
-                self.invoker.start_container(&stopped_id).await?;
-                let _ = self
-                    .warm_pool
-                    .set_state_by_container_id(
-                        &stopped_id,
-                        crate::warm_pool::InstanceState::WarmIdle,
-                    )
-                    .await;
+        let rows = sqlx::query(
+            "SELECT status, duration_ms, start_time FROM executions
+             WHERE function_id = ? AND start_time >= ?",
+        )
+        .bind(function.function_id)
+        .bind(since - span)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let mut current = WindowFigures::default();
+        let mut previous = WindowFigures::default();
+        for row in &rows {
+            let figures = if row.get::<DateTime<Utc>, _>("start_time") >= since {
+                &mut current
             } else {
-                // All existing containers are busy; scale up by creating a new one
-                info!(
-                    "All containers busy for {}. Scaling up by 1.",
-                    function.function_name
-                );
-                let image_ref = format!(
-                    "lambda-home/{}:{}",
-                    function.function_name, function.code_sha256
-                );
-                let mut packaging_service =
-                    lambda_packaging::PackagingService::new(self.config.clone());
-                packaging_service.build_image(&function, &image_ref, self.config.server.port_runtime_api).await?;
-
-                let instance_id = uuid::Uuid::new_v4().to_string();
-                let mut env_vars = self.resolve_env_vars(&function).await?;
-                env_vars.insert("LAMBDAH_INSTANCE_ID".to_string(), instance_id.clone());
-                let container_id = self
-                    .invoker
-                    .create_container(&function, &image_ref, env_vars)
-                    .await?;
-                self.invoker.start_container(&container_id).await?;
-
-                let warm_container = crate::warm_pool::WarmContainer {
-                    container_id: container_id.clone(),
-                    instance_id: instance_id.clone(),
-                    function_id: function.function_id,
-                    image_ref: image_ref.clone(),
-                    created_at: std::time::Instant::now(),
-                    last_used: std::time::Instant::now(),
-                    state: crate::warm_pool::InstanceState::WarmIdle,
-                };
-                self.warm_pool
-                    .add_warm_container(fn_key, warm_container)
-                    .await;
-                info!(
-                    "Scaled up with new container: {} for function: {}",
-                    container_id, function.function_name
-                );
-            }
+                &mut previous
+            };
+            figures.record(
+                row.get::<String, _>("status") == "Failed",
+                row.get::<Option<i64>, _>("duration_ms")
+                    .map(|d| d.max(0) as u64),
+            );
         }
 
-        // 7) Enqueue: scheduler.enqueue(work_item).await
-        self.scheduler
-            .enqueue(work_item)
+        let changes = sqlx::query(
+            "SELECT COUNT(*) AS changes, MAX(changed_at) AS last_change
+             FROM function_config_revisions WHERE function_id = ? AND changed_at >= ?",
+        )
+        .bind(function.function_id)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let starts = self.warmup_tracker.function_counts(name);
+        let containers: Vec<_> = self
+            .warm_pool
+            .list_all_containers()
             .await
-            .map_err(|e| LambdaError::InternalError {
-                reason: format!("Failed to enqueue work item: {e}"),
-            })?;
+            .into_iter()
+            .filter(|(key, _)| key.function_name == name)
+            .collect();
 
-        // 8) Wait for result with function's configured timeout
-        let total = tokio::time::Duration::from_secs(function.timeout);
-        match tokio::time::timeout(total, rx).await {
-            Ok(Ok(result)) => {
-                // Calculate duration
-                let end_time = chrono::Utc::now();
-                let duration_ms = (end_time - start_time).num_milliseconds() as u64;
-                
-                // Success: build Lambda response
-                if result.ok {
-                    Ok(InvokeResponse {
-                        status_code: 200,
-                        payload: Some(
-                            serde_json::from_slice(&result.payload)
-                                .unwrap_or(serde_json::Value::Null),
-                        ),
-                        executed_version: result.executed_version,
-                        function_error: None,
-                        log_result: result.log_tail_b64,
-                        headers: std::collections::HashMap::new(),
-                        duration_ms: Some(duration_ms),
-                    })
-                } else {
-                    // Function error: return 200 with X-Amz-Function-Error header
-                    Ok(InvokeResponse {
-                        status_code: 200,
-                        payload: Some(
-                            serde_json::from_slice(&result.payload)
-                                .unwrap_or(serde_json::Value::Null),
-                        ),
-                        executed_version: result.executed_version,
-                        function_error: result.function_error.as_ref().map(|fe| {
-                            match fe.as_str() {
-                                "Handled" => FunctionError::Handled,
-                                _ => FunctionError::Unhandled,
-                            }
-                        }),
-                        log_result: result.log_tail_b64,
-                        headers: std::collections::HashMap::new(),
-                        duration_ms: Some(duration_ms),
-                    })
+        Ok(FunctionInsights {
+            function_name: function.function_name,
+            generated_at: now,
+            window_seconds: window.as_secs(),
+            invocations: current.invocations,
+            errors: current.errors,
+            error_rate: insights::trend(current.error_rate(), previous.error_rate()),
+            p95_duration_ms: insights::trend(current.p95_duration_ms(), previous.p95_duration_ms()),
+            cold_start_ratio: (starts.total() > 0)
+                .then(|| starts.cold_starts as f64 / starts.total() as f64),
+            starts,
+            throttles: self.throttles.count_since(name, since),
+            config_changes: changes.get::<i64, _>("changes") as u64,
+            last_config_change: changes.get("last_change"),
+            warm_pool: occupancy(&containers, self.config.limits.host_memory_budget_mb),
+        })
+    }
+
+    /// What the executions recorded within the request's window would have cost on AWS
+    /// Lambda, to help decide which functions to move to or from the cloud
+    #[instrument(skip(self))]
+    pub async fn cost_estimate(
+        &self,
+        request: CostEstimateRequest,
+    ) -> Result<CostEstimate, LambdaError> {
+        let window = crate::parse_window(request.window.as_deref().unwrap_or(DEFAULT_COST_WINDOW))?;
+        let pricing = request
+            .pricing
+            .unwrap_or_else(|| self.config.aws_pricing.clone());
+        pricing.validate()?;
+        let since = crate::window_start(Utc::now(), window)?;
+
+        let mut usage: HashMap<uuid::Uuid, RecordedUsage> = HashMap::new();
+        match &request.function_name {
+            Some(name) => {
+                let function = self.get_function(name).await?;
+                usage.insert(
+                    function.function_id,
+                    RecordedUsage {
+                        function_name: function.function_name,
+                        memory_size_mb: function.memory_size,
+                        ..Default::default()
+                    },
+                );
+            }
+            None => {
+                let rows =
+                    sqlx::query("SELECT function_id, function_name, memory_size FROM functions")
+                        .fetch_all(&self.pool)
+                        .await
+                        .map_err(LambdaError::SqlxError)?;
+                for row in rows {
+                    usage.insert(
+                        row.get("function_id"),
+                        RecordedUsage {
+                            function_name: row.get("function_name"),
+                            memory_size_mb: row.get::<i64, _>("memory_size").max(0) as u64,
+                            ..Default::default()
+                        },
+                    );
                 }
             }
-            Ok(Err(_canceled)) => {
-                // Runtime channel dropped → 200 with X-Amz-Function-Error: InitError
-                error!("Runtime channel closed for invocation: {}", req_id);
-
-                // Record init error in execution record (deferred async)
-                let end_time = chrono::Utc::now();
-                let duration_ms = (end_time - start_time).num_milliseconds() as u64;
-                self.execution_tracker
-                    .record_execution_init_error(req_id.clone(), end_time);
-
-                Ok(InvokeResponse {
-                    status_code: 200,
-                    payload: Some(serde_json::json!({
-                        "errorMessage": "Runtime channel closed",
-                        "errorType": "InitError"
-                    })),
-                    executed_version: Some("1".to_string()),
-                    function_error: Some(FunctionError::Unhandled),
-                    log_result: None,
-                    headers: std::collections::HashMap::new(),
-                    duration_ms: Some(duration_ms),
-                })
+        }
+
+        let rows = sqlx::query(
+            "SELECT function_id, duration_ms, memory_used_mb FROM executions
+             WHERE start_time >= ? AND duration_ms IS NOT NULL",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        for row in rows {
+            let Some(function) = usage.get_mut(&row.get::<uuid::Uuid, _>("function_id")) else {
+                continue;
+            };
+            function
+                .durations_ms
+                .push(row.get::<i64, _>("duration_ms").max(0) as u64);
+            if let Some(used) = row.get::<Option<i64>, _>("memory_used_mb") {
+                let used = used.max(0) as u64;
+                function.max_memory_used_mb = function.max_memory_used_mb.max(Some(used));
             }
-            Err(_elapsed) => {
-                // Timeout: mark timeout and return 200 with X-Amz-Function-Error: Unhandled
-                let timeout_json = serde_json::json!({
-                    "errorMessage": format!("Task timed out after {} seconds", function.timeout),
-                    "errorType": "TaskTimedOut"
-                });
-                let timeout_body = serde_json::to_vec(&timeout_json).unwrap_or_default();
-                let _ =
-                    self.scheduler
-                        .pending()
-                        .fail_if_waiting(&req_id, "Unhandled", timeout_body);
-
-                // Record timeout in execution record (deferred async)
-                let end_time = chrono::Utc::now();
-                let duration_ms = (end_time - start_time).num_milliseconds() as u64;
-                self.execution_tracker
-                    .record_execution_timeout(req_id.clone(), end_time);
-
-                Ok(InvokeResponse {
-                    status_code: 200,
-                    payload: Some(timeout_json),
-                    executed_version: Some("1".to_string()),
-                    function_error: Some(FunctionError::Unhandled),
-                    log_result: None,
-                    headers: std::collections::HashMap::new(),
-                    duration_ms: Some(duration_ms),
-                })
+        }
+
+        Ok(cost::estimate(
+            usage.into_values().collect(),
+            pricing,
+            request.architecture,
+            window.as_secs(),
+        ))
+    }
+
+    /// Console summary: function states, recent invocations, warm pool occupancy,
+    /// recent deployments and alerts, gathered in one pass
+    #[instrument(skip(self))]
+    pub async fn dashboard(&self) -> Result<Dashboard, LambdaError> {
+        let function_rows = sqlx::query("SELECT function_name, state, state_reason FROM functions")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        let mut states = Vec::with_capacity(function_rows.len());
+        let mut failed_functions = Vec::new();
+        for row in &function_rows {
+            let state: FunctionState = serde_json::from_str(row.get::<String, _>("state").as_str())
+                .unwrap_or(FunctionState::Pending);
+            if state == FunctionState::Failed {
+                failed_functions.push((
+                    row.get::<String, _>("function_name"),
+                    row.get::<Option<String>, _>("state_reason"),
+                ));
             }
+            states.push(state);
         }
-        // Token guard automatically releases concurrency token when dropped
+
+        let since = Utc::now()
+            - chrono::Duration::from_std(DASHBOARD_WINDOW).unwrap_or(chrono::Duration::zero());
+        let invocation_rows = sqlx::query(
+            "SELECT f.function_name,
+                    COUNT(*) AS invocations,
+                    SUM(CASE WHEN e.status = 'Failed' THEN 1 ELSE 0 END) AS errors,
+                    SUM(e.duration_ms) AS total_duration_ms,
+                    COUNT(e.duration_ms) AS timed
+             FROM executions e
+             JOIN functions f ON f.function_id = e.function_id
+             WHERE e.start_time >= ?
+             GROUP BY f.function_name",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let mut total_duration_ms = 0i64;
+        let mut timed = 0i64;
+        let by_function = invocation_rows
+            .iter()
+            .map(|row| {
+                total_duration_ms += row.get::<Option<i64>, _>("total_duration_ms").unwrap_or(0);
+                timed += row.get::<i64, _>("timed");
+                let invocations = row.get::<i64, _>("invocations") as u64;
+                let errors = row.get::<i64, _>("errors") as u64;
+                FunctionInvocationTotals {
+                    function_name: row.get("function_name"),
+                    invocations,
+                    errors,
+                    error_rate: crate::dashboard::error_rate(errors, invocations),
+                }
+            })
+            .collect();
+        let avg_duration_ms = (timed > 0).then(|| total_duration_ms as f64 / timed as f64);
+        let invocations = invocation_totals(DASHBOARD_WINDOW, by_function, avg_duration_ms);
+
+        let deployment_rows = sqlx::query(
+            "SELECT function_name, '$LATEST' AS version, code_sha256, last_modified, 'updated' AS kind
+             FROM functions
+             UNION ALL
+             SELECT f.function_name, v.version, v.code_sha256, v.last_modified, 'published' AS kind
+             FROM versions v
+             JOIN functions f ON f.function_id = v.function_id
+             ORDER BY last_modified DESC
+             LIMIT ?",
+        )
+        .bind(RECENT_DEPLOYMENTS_LIMIT)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let recent_deployments = deployment_rows
+            .iter()
+            .map(|row| Deployment {
+                function_name: row.get("function_name"),
+                version: row.get("version"),
+                code_sha256: row.get("code_sha256"),
+                kind: if row.get::<String, _>("kind") == "published" {
+                    DeploymentKind::Published
+                } else {
+                    DeploymentKind::Updated
+                },
+                deployed_at: row.get("last_modified"),
+            })
+            .collect();
+
+        let warm_pool = occupancy(
+            &self.warm_pool.list_all_containers().await,
+            self.config.limits.host_memory_budget_mb,
+        );
+        let failing_checks = self.failing_synthetic_checks().await?;
+        let alerts = build_alerts(
+            &failed_functions,
+            &failing_checks,
+            &invocations,
+            &warm_pool,
+            &self.disk_monitor.status(),
+        );
+
+        Ok(Dashboard {
+            generated_at: Utc::now(),
+            functions: count_states(&states),
+            invocations,
+            warm_pool,
+            recent_deployments,
+            alerts,
+        })
+    }
+
+    /// Search function names and descriptions, route paths, secret names and recent
+    /// execution request ids for the console omnibox
+    #[instrument(skip(self))]
+    pub async fn search(&self, query: &str, limit: usize) -> Result<SearchResponse, LambdaError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(LambdaError::InvalidRequest {
+                reason: "Search query must not be empty".to_string(),
+            });
+        }
+        let limit = limit.clamp(1, MAX_SEARCH_LIMIT);
+        // One more row per kind than requested tells whether results were cut off
+        let per_kind = limit as i64 + 1;
+        let pattern = like_pattern(query);
+        let mut hits = Vec::new();
+
+        let rows = sqlx::query(&format!(
+            "SELECT function_name, runtime, description FROM functions
+             WHERE function_name LIKE ?2 ESCAPE '\\' OR description LIKE ?2 ESCAPE '\\'
+             ORDER BY {} LIMIT ?3",
+            match_position_order("function_name")
+        ))
+        .bind(query)
+        .bind(&pattern)
+        .bind(per_kind)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        hits.extend(rows.iter().map(|row| SearchHit::Function {
+            function_name: row.get("function_name"),
+            runtime: row.get("runtime"),
+            description: row.get("description"),
+        }));
+
+        let rows = sqlx::query(&format!(
+            "SELECT route_id, path, method, function_name FROM api_routes
+             WHERE path LIKE ?2 ESCAPE '\\'
+             ORDER BY {} LIMIT ?3",
+            match_position_order("path")
+        ))
+        .bind(query)
+        .bind(&pattern)
+        .bind(per_kind)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        for row in &rows {
+            hits.push(SearchHit::Route {
+                route_id: row.try_get("route_id").map_err(LambdaError::SqlxError)?,
+                path: row.get("path"),
+                method: row.get("method"),
+                function_name: row.get("function_name"),
+            });
+        }
+
+        let rows = sqlx::query(&format!(
+            "SELECT name FROM secrets WHERE name LIKE ?2 ESCAPE '\\' ORDER BY {} LIMIT ?3",
+            match_position_order("name")
+        ))
+        .bind(query)
+        .bind(&pattern)
+        .bind(per_kind)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        hits.extend(rows.iter().map(|row| SearchHit::Secret {
+            name: row.get("name"),
+        }));
+
+        let since = Utc::now()
+            - chrono::Duration::from_std(RECENT_EXECUTIONS_WINDOW)
+                .unwrap_or(chrono::Duration::zero());
+        let rows = sqlx::query(&format!(
+            "SELECT e.aws_request_id, e.status, e.start_time, f.function_name
+             FROM executions e
+             JOIN functions f ON f.function_id = e.function_id
+             WHERE e.aws_request_id LIKE ?2 ESCAPE '\\' AND e.start_time >= ?4
+             ORDER BY {}, e.start_time DESC LIMIT ?3",
+            match_position_order("e.aws_request_id")
+        ))
+        .bind(query)
+        .bind(&pattern)
+        .bind(per_kind)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        for row in &rows {
+            hits.push(SearchHit::Execution {
+                request_id: row.get("aws_request_id"),
+                function_name: row.get("function_name"),
+                status: row.get("status"),
+                start_time: row.try_get("start_time").map_err(LambdaError::SqlxError)?,
+            });
+        }
+
+        let (results, truncated) = rank_hits(hits, query, limit);
+        Ok(SearchResponse {
+            query: query.to_string(),
+            results,
+            truncated,
+        })
     }
 
     #[instrument(skip(self))]
@@ -1246,7 +8019,7 @@ impl ControlPlane {
             version: version.unwrap_or("LATEST").to_string(),
             env_hash: env_hash.unwrap_or("").to_string(),
         };
-        let work_item = self.scheduler.queues().pop_or_wait(&key).await?;
+        let work_item = self.next_work_item(&key).await?;
 
         // Active marking handled by runtime API using instance header
 
@@ -1256,22 +8029,131 @@ impl ControlPlane {
         );
 
         // 3) Return JSON in AWS Lambda Runtime API format
-        Ok(RuntimeInvocation {
-            aws_request_id: Uuid::parse_str(&work_item.request_id).map_err(|_| {
-                LambdaError::InvalidRequest {
-                    reason: "Invalid request ID".to_string(),
+        self.dispatch(work_item)
+    }
+
+    /// The next queued work item of `key`, passing over invocations that timed out before a
+    /// runtime picked them up
+    async fn next_work_item(&self, key: &crate::queues::FnKey) -> Result<WorkItem, LambdaError> {
+        loop {
+            let work_item = self.scheduler.queues().pop_or_wait(key).await?;
+            if !self.scheduler.pending().is_discarded(&work_item.request_id) {
+                return Ok(work_item);
+            }
+            debug!(
+                "Dropping invocation {}, which timed out in the queue",
+                work_item.request_id
+            );
+        }
+    }
+
+    /// Hand a queued work item to a runtime. Its deadline runs from now, as a function's
+    /// timeout does on AWS, and the deadline enforcer discards it on overrun.
+    fn dispatch(&self, mut work_item: WorkItem) -> Result<RuntimeInvocation, LambdaError> {
+        let timeout_ms = work_item.function.timeout_ms as u64;
+        work_item.deadline_ms = Utc::now().timestamp_millis() + timeout_ms as i64;
+        self.scheduler.pending().set_deadline(
+            &work_item.request_id,
+            work_item.deadline_ms,
+            timeout_ms,
+        );
+        runtime_invocation(&self.config.identity, work_item)
+    }
+
+    /// Fail invocations running past their deadline as timed out and discard them, then deal
+    /// with the containers running them as `defaults.on_timeout` says: by default they are
+    /// killed so they take no further work in a state the function did not finish
+    async fn run_deadline_enforcer(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(250));
+        loop {
+            interval.tick().await;
+            let pending = self.scheduler.pending();
+            let now_ms = Utc::now().timestamp_millis();
+            pending.forget_discarded(now_ms - DISCARDED_RETENTION_MS);
+            for (req_id, timeout_ms, container_id) in pending.overdue(now_ms) {
+                // A caller that gave up first already discarded it, leaving the container
+                let timed_out =
+                    pending.discard(&req_id, InvocationResult::timed_out(timeout_ms), now_ms)
+                        || pending.is_discarded(&req_id);
+                pending.clear_deadline(&req_id);
+                if !timed_out {
+                    continue;
+                }
+                let Some(container_id) = container_id else {
+                    continue;
+                };
+                let action = self.config.defaults.on_timeout;
+                if action == TimeoutAction::Keep {
+                    warn!(
+                        "Invocation {} ran past its {} ms timeout, leaving container {} running",
+                        req_id, timeout_ms, container_id
+                    );
+                    continue;
+                }
+                warn!(
+                    "Invocation {} ran past its {} ms timeout, killing container {}",
+                    req_id, timeout_ms, container_id
+                );
+                let key = self
+                    .warm_pool
+                    .find_container(&container_id)
+                    .await
+                    .map(|(key, _)| key);
+                let _ = self.warm_pool.remove_container_by_id(&container_id).await;
+                if let Err(e) = self.invoker.remove_container(&container_id).await {
+                    warn!("Failed to kill container {}: {}", container_id, e);
+                }
+                if let (TimeoutAction::Restart, Some(key)) = (action, key) {
+                    let control = self.clone();
+                    tokio::spawn(async move {
+                        control.replace_timed_out_container(&key).await;
+                    });
                 }
-            })?,
-            deadline_ms: work_item.deadline_ms,
-            invoked_function_arn: format!(
-                "arn:aws:lambda:us-east-1:123456789012:function:{}",
-                work_item.function.function_name
+            }
+        }
+    }
+
+    /// Start a container in place of one killed for running past an invocation's timeout
+    async fn replace_timed_out_container(&self, key: &crate::queues::FnKey) {
+        let started = match self.function_for_key(key).await {
+            Ok(function) => self.create_and_start_container(&function, key).await,
+            Err(e) => Err(e),
+        };
+        match started {
+            Ok(container_id) => info!(
+                "Started container {} for {} after a timeout",
+                container_id, key.function_name
             ),
-            trace_id: None,
-            client_context: work_item.client_context.clone(),
-            cognito_identity: work_item.cognito_identity.clone(),
-            payload: serde_json::from_slice(&work_item.payload).unwrap_or(serde_json::Value::Null),
-        })
+            Err(e) => warn!(
+                "Failed to replace timed out container of {}: {}",
+                key.function_name, e
+            ),
+        }
+    }
+
+    /// Like [`Self::get_next_invocation`], but a container still owing its prewarm
+    /// invocation gets that first and no queued work until it succeeded
+    pub async fn get_next_invocation_for_instance(
+        &self,
+        instance_id: Option<&str>,
+        function_name: &str,
+        runtime: &str,
+        version: Option<&str>,
+        env_hash: Option<&str>,
+    ) -> Result<RuntimeInvocation, LambdaError> {
+        if let Some(instance_id) = instance_id {
+            if let Some(work_item) = self.prewarm.next_for(instance_id).await? {
+                debug!("Sending prewarm invocation to instance {}", instance_id);
+                return runtime_invocation(&self.config.identity, work_item);
+            }
+            // Containers of a published version take that version's work only
+            if let Some(key) = self.warm_pool.instance_key(instance_id).await {
+                let work_item = self.next_work_item(&key).await?;
+                return self.dispatch(work_item);
+            }
+        }
+        self.get_next_invocation(function_name, runtime, version, env_hash)
+            .await
     }
 
     #[instrument(skip(self))]
@@ -1285,6 +8167,7 @@ impl ControlPlane {
             "Processing response from container for request: {}",
             request_id
         );
+        self.refuse_discarded(&request_id)?;
 
         // Success: POST /2018-06-01/runtime/invocation/{requestId}/response
         // Build InvocationResult::ok(payload)
@@ -1335,6 +8218,7 @@ impl ControlPlane {
             "Processing error from container for request: {}",
             request_id
         );
+        self.refuse_discarded(&request_id)?;
 
         // Error: POST /2018-06-01/runtime/invocation/{requestId}/error
         // Build InvocationResult::err(kind, payload) where kind from header X-Amz-Function-Error or default "Unhandled"
@@ -1370,6 +8254,8 @@ impl ControlPlane {
             self.execution_tracker.record_execution_failure(
                 request_id.clone(),
                 error.error_type.clone(),
+                Some(error.error_message.clone()),
+                Some(error_payload),
                 end_time,
             );
 
@@ -1386,9 +8272,40 @@ impl ControlPlane {
         }
     }
 
+    /// Refuse the result of a request that timed out; it goes nowhere, and must not mark
+    /// some other container idle the way an unknown request's result does
+    fn refuse_discarded(&self, request_id: &str) -> Result<(), LambdaError> {
+        if !self.scheduler.pending().is_discarded(request_id) {
+            return Ok(());
+        }
+        info!("Discarding result of timed out invocation {}", request_id);
+        Err(LambdaError::InvalidRequest {
+            reason: "Invocation not found".to_string(),
+        })
+    }
+
+    /// A runtime failed to load its handler and is about to exit, so its container no longer
+    /// counts as idle capacity; invocations it held fail once the container is gone
     #[instrument(skip(self))]
-    pub async fn post_init_error(&self, error: InitError) -> Result<(), LambdaError> {
-        // TODO: Implement posting init error from containers
+    pub async fn post_init_error(
+        &self,
+        instance_id: Option<&str>,
+        error: InitError,
+    ) -> Result<(), LambdaError> {
+        let stopping = match instance_id {
+            Some(instance_id) => self.warm_pool.mark_stopping_by_instance(instance_id).await,
+            None => None,
+        };
+        match stopping {
+            Some((key, container_id)) => error!(
+                "Container {} of {} failed to initialize: {}: {}",
+                container_id, key.function_name, error.error_type, error.error_message
+            ),
+            None => error!(
+                "Runtime failed to initialize: {}: {}",
+                error.error_type, error.error_message
+            ),
+        }
         Ok(())
     }
 
@@ -1406,19 +8323,33 @@ impl ControlPlane {
         self.warm_pool.mark_idle_by_instance(instance_id).await
     }
 
+    /// A runtime shutting down on SIGTERM: it has finished its invocation and takes no more
+    pub async fn unregister_instance(
+        &self,
+        instance_id: &str,
+    ) -> Option<(crate::queues::FnKey, String)> {
+        let unregistered = self.warm_pool.mark_stopping_by_instance(instance_id).await;
+        if let Some((key, container_id)) = &unregistered {
+            info!(
+                "Instance {} ({}) of {} unregistered for shutdown",
+                instance_id, container_id, key.function_name
+            );
+        }
+        unregistered
+    }
+
     // Helper methods
 
+    fn max_build_context_bytes(&self) -> u64 {
+        self.config.builds.max_context_mb * 1024 * 1024
+    }
+
     fn is_valid_function_name(&self, name: &str) -> bool {
-        // AWS Lambda function name validation rules
-        !name.is_empty()
-            && name.len() <= 64
-            && name
-                .chars()
-                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        validation::is_valid_function_name(name)
     }
 
     fn is_valid_runtime(&self, runtime: &str) -> bool {
-        matches!(runtime, "nodejs18.x" | "nodejs22.x" | "nodejs24.x" | "python3.11" | "rust")
+        validation::is_valid_runtime(runtime)
     }
 
     async fn function_exists(&self, name: &str) -> Result<bool, LambdaError> {
@@ -1446,6 +8377,18 @@ impl ControlPlane {
                 .as_str(),
         )
         .unwrap_or(FunctionState::Pending);
+        let last_update_status: LastUpdateStatus = serde_json::from_str(
+            row.try_get::<String, _>("last_update_status")
+                .map_err(LambdaError::SqlxError)?
+                .as_str(),
+        )
+        .unwrap_or_default();
+        let package_type: PackageType = serde_json::from_str(
+            row.try_get::<String, _>("package_type")
+                .map_err(LambdaError::SqlxError)?
+                .as_str(),
+        )
+        .unwrap_or_default();
 
         Ok(Function {
             function_id: row.try_get("function_id").map_err(LambdaError::SqlxError)?,
@@ -1478,6 +8421,17 @@ impl ControlPlane {
             state_reason_code: row
                 .try_get("state_reason_code")
                 .map_err(LambdaError::SqlxError)?,
+            last_update_status,
+            last_update_status_reason: row
+                .try_get("last_update_status_reason")
+                .map_err(LambdaError::SqlxError)?,
+            last_update_status_reason_code: row
+                .try_get("last_update_status_reason_code")
+                .map_err(LambdaError::SqlxError)?,
+            metadata: None,
+            function_arn: None,
+            package_type,
+            image_uri: row.try_get("image_uri").map_err(LambdaError::SqlxError)?,
         })
     }
 
@@ -1494,6 +8448,14 @@ impl ControlPlane {
             code_size: row
                 .try_get::<i64, _>("code_size")
                 .map_err(LambdaError::SqlxError)? as u64,
+            image_digest: row
+                .try_get("image_digest")
+                .map_err(LambdaError::SqlxError)?,
+            config: row
+                .try_get::<Option<String>, _>("config")
+                .map_err(LambdaError::SqlxError)?
+                .and_then(|config| serde_json::from_str(&config).ok()),
+            image_uri: row.try_get("image_uri").map_err(LambdaError::SqlxError)?,
         })
     }
 
@@ -1522,6 +8484,247 @@ impl ControlPlane {
     }
 }
 
+/// A work item in the shape of the Lambda Runtime API's next-invocation response
+fn runtime_invocation(
+    identity: &IdentityConfig,
+    work_item: WorkItem,
+) -> Result<RuntimeInvocation, LambdaError> {
+    Ok(RuntimeInvocation {
+        aws_request_id: Uuid::parse_str(&work_item.request_id).map_err(|_| {
+            LambdaError::InvalidRequest {
+                reason: "Invalid request ID".to_string(),
+            }
+        })?,
+        deadline_ms: work_item.deadline_ms,
+        invoked_function_arn: identity.function_arn(&work_item.function.function_name),
+        trace_id: None,
+        client_context: work_item.client_context,
+        cognito_identity: work_item.cognito_identity,
+        payload: serde_json::from_slice(&work_item.payload).unwrap_or(serde_json::Value::Null),
+    })
+}
+
+fn row_to_event_source_mapping(
+    identity: &IdentityConfig,
+    row: &sqlx::sqlite::SqliteRow,
+) -> EventSourceMapping {
+    let function_response_types = if row.get::<bool, _>("report_batch_item_failures") {
+        vec![FunctionResponseType::ReportBatchItemFailures]
+    } else {
+        Vec::new()
+    };
+    let (event_source_arn, s3_key_filter) = match row.get::<Option<String>, _>("bucket") {
+        Some(bucket) => (
+            identity.bucket_arn(&bucket),
+            normalize_key_filter(Some(S3KeyFilter {
+                prefix: row.get("key_prefix"),
+                suffix: row.get("key_suffix"),
+            })),
+        ),
+        None => (
+            identity.queue_arn(&row.get::<String, _>("queue_name")),
+            None,
+        ),
+    };
+    EventSourceMapping {
+        uuid: row.get("uuid"),
+        event_source_arn,
+        function_name: row.get("function_name"),
+        batch_size: row.get::<i64, _>("batch_size") as u32,
+        enabled: row.get("enabled"),
+        function_response_types,
+        last_modified: row.get("last_modified"),
+        last_processing_result: row.get("last_processing_result"),
+        s3_key_filter,
+    }
+}
+
+fn row_to_webhook(row: &sqlx::sqlite::SqliteRow) -> Webhook {
+    Webhook {
+        webhook_id: row.get("webhook_id"),
+        url: row.get("url"),
+        events: serde_json::from_str(&row.get::<String, _>("events")).unwrap_or_default(),
+        description: row.get("description"),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn row_to_api_route(row: &sqlx::sqlite::SqliteRow) -> ApiRoute {
+    ApiRoute {
+        route_id: row.get("route_id"),
+        path: row.get("path"),
+        method: row.get("method"),
+        function_name: row.get("function_name"),
+        created_at: row.get("created_at"),
+        canary: row
+            .get::<Option<String>, _>("canary")
+            .and_then(|c| serde_json::from_str(&c).ok()),
+        access_log: row.get("access_log"),
+        mock: row
+            .get::<Option<String>, _>("mock")
+            .and_then(|m| serde_json::from_str(&m).ok()),
+        recording: row
+            .get::<Option<String>, _>("recording")
+            .and_then(|r| serde_json::from_str(&r).ok()),
+        limits: row
+            .get::<Option<String>, _>("limits")
+            .and_then(|l| serde_json::from_str(&l).ok()),
+        transform: row
+            .get::<Option<String>, _>("transform")
+            .and_then(|t| serde_json::from_str(&t).ok()),
+        invocation: row
+            .get::<Option<String>, _>("invocation")
+            .and_then(|i| serde_json::from_str(&i).ok()),
+        jwt_authorizer: row
+            .get::<Option<String>, _>("jwt_authorizer")
+            .and_then(|a| serde_json::from_str(&a).ok()),
+    }
+}
+
+fn row_to_schedule(row: &sqlx::sqlite::SqliteRow) -> Schedule {
+    Schedule {
+        schedule_id: row.get("schedule_id"),
+        function_name: row.get("function_name"),
+        schedule_expression: row.get("schedule_expression"),
+        payload: serde_json::from_str(&row.get::<String, _>("payload"))
+            .unwrap_or(serde_json::Value::Null),
+        qualifier: row.get("qualifier"),
+        description: row.get("description"),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+        last_run_at: row.get("last_run_at"),
+        last_job_id: row.get("last_job_id"),
+        next_run_at: row.get("next_run_at"),
+    }
+}
+
+fn row_to_test_event(row: &sqlx::sqlite::SqliteRow, function_name: &str) -> TestEvent {
+    TestEvent {
+        event_id: row.get("event_id"),
+        function_name: function_name.to_string(),
+        name: row.get("name"),
+        payload: serde_json::from_str(&row.get::<String, _>("payload"))
+            .unwrap_or(serde_json::Value::Null),
+        source: match row.get::<String, _>("source").as_str() {
+            "recorded" => TestEventSource::Recorded,
+            _ => TestEventSource::Manual,
+        },
+        route_id: row.get("route_id"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn canary_match_name(name: &str) -> Result<String, LambdaError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(LambdaError::InvalidRequest {
+            reason: "Canary header and cookie matches need a name".to_string(),
+        });
+    }
+    Ok(name.to_string())
+}
+
+fn validate_route_recording(recording: &RouteRecording) -> Result<(), LambdaError> {
+    let invalid = |reason: String| Err(LambdaError::InvalidRequest { reason });
+    if !(1..=MAX_RECORDING_PER_MINUTE).contains(&recording.per_minute) {
+        return invalid(format!(
+            "Recording per_minute must be between 1 and {MAX_RECORDING_PER_MINUTE}"
+        ));
+    }
+    if !(1..=MAX_RECORDED_EVENTS).contains(&recording.max_events) {
+        return invalid(format!(
+            "Recording max_events must be between 1 and {MAX_RECORDED_EVENTS}"
+        ));
+    }
+    let names = recording
+        .redact_headers
+        .iter()
+        .chain(&recording.redact_query)
+        .chain(&recording.redact_body_fields);
+    if names.into_iter().any(|n| n.trim().is_empty()) {
+        return invalid("Redaction rules must not contain empty names".to_string());
+    }
+    Ok(())
+}
+
+fn validate_route_transform(transform: &RouteTransform) -> Result<(), LambdaError> {
+    if transform.request.is_none() && transform.response.is_none() {
+        return Err(LambdaError::InvalidRequest {
+            reason: "A transform needs a request or a response template".to_string(),
+        });
+    }
+    let templates = [
+        ("request", &transform.request),
+        ("response", &transform.response),
+    ];
+    for (name, template) in templates {
+        if let Some(template) = template {
+            validate_template(template).map_err(|e| LambdaError::InvalidRequest {
+                reason: format!("Invalid {name} template: {e}"),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_route_mock(mock: &RouteMock) -> Result<(), LambdaError> {
+    let invalid = |reason: String| Err(LambdaError::InvalidRequest { reason });
+    if !(100..=599).contains(&mock.status) {
+        return invalid(format!(
+            "Mock status must be between 100 and 599, got {}",
+            mock.status
+        ));
+    }
+    let max_latency_ms = validation::MAX_TIMEOUT_SECS * 1000;
+    if mock.latency_ms > max_latency_ms {
+        return invalid(format!(
+            "Mock latency_ms must be at most {max_latency_ms}, got {}",
+            mock.latency_ms
+        ));
+    }
+    for (name, value) in &mock.headers {
+        let valid_name =
+            !name.is_empty() && name.bytes().all(|b| b.is_ascii_graphic() && b != b':');
+        if !valid_name || value.bytes().any(|b| b.is_ascii_control() && b != b'\t') {
+            return invalid(format!("Invalid mock header: {name}"));
+        }
+    }
+    Ok(())
+}
+
+fn row_to_lifecycle(row: &sqlx::sqlite::SqliteRow) -> FunctionLifecycle {
+    FunctionLifecycle {
+        idle_timeout_secs: row
+            .get::<Option<i64>, _>("idle_timeout_secs")
+            .map(|v| v as u64),
+        max_warm_containers: row
+            .get::<Option<i64>, _>("max_warm_containers")
+            .map(|v| v as u32),
+        min_warm_containers: row
+            .get::<Option<i64>, _>("min_warm_containers")
+            .map(|v| v as u32),
+    }
+}
+
+fn row_to_group(row: &sqlx::sqlite::SqliteRow, functions: Vec<String>) -> FunctionGroup {
+    FunctionGroup {
+        name: row.get("name"),
+        description: row.get("description"),
+        disabled: row.get("disabled"),
+        functions,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Function a tagging call's resource ARN names
+fn tagged_function(arn: &str) -> Result<&str, LambdaError> {
+    function_name_from_arn(arn).ok_or_else(|| LambdaError::InvalidRequest {
+        reason: format!("{arn} is not a function ARN"),
+    })
+}
+
 fn normalize_path(p: &str) -> String {
     let mut s = if p.starts_with('/') {
         p.to_string()
@@ -1536,7 +8739,7 @@ fn normalize_path(p: &str) -> String {
 
 impl ControlPlane {
     // Resolve environment variables, replacing secret references with actual values.
-    // Secret reference format: "SECRET_REF:<name>"
+    // Secret reference format: "SECRET_REF:<name>" (`SECRET_REF_PREFIX`)
     pub async fn resolve_env_vars(
         &self,
         function: &Function,
@@ -1550,7 +8753,7 @@ impl ControlPlane {
         let mut out = function.environment.clone();
         // Only fetch secrets when needed to avoid extra queries
         for (_k, v) in out.clone().iter() {
-            if let Some(name) = v.strip_prefix("SECRET_REF:") {
+            if let Some(name) = v.strip_prefix(SECRET_REF_PREFIX) {
                 // Lookup secret value
                 if let Some(val) = self.get_secret_value(name).await? {
                     out.insert(_k.clone(), val);
@@ -1567,6 +8770,211 @@ impl ControlPlane {
         Ok(out)
     }
 
+    /// Environment of a function for the console's editor: plain values as they are, secret
+    /// references by name only
+    #[instrument(skip(self))]
+    pub async fn get_environment(&self, name: &str) -> Result<FunctionEnvironment, LambdaError> {
+        let function = self.get_function(name).await?;
+        self.describe_environment(&function.environment).await
+    }
+
+    /// Add, change or remove some environment variables and keep the rest. This is an
+    /// UpdateFunctionConfiguration underneath, so it lands in the config history and
+    /// containers running on the old environment are replaced.
+    #[instrument(skip(self, request))]
+    pub async fn patch_environment(
+        &self,
+        name: &str,
+        request: PatchEnvironmentRequest,
+        changed_by: Option<&str>,
+    ) -> Result<FunctionEnvironment, LambdaError> {
+        let function = self.get_function(name).await?;
+        let mut environment = function.environment;
+        let mut set = Vec::new();
+        for (key, value) in &request.variables {
+            let value = match value {
+                None => {
+                    environment.remove(key);
+                    continue;
+                }
+                Some(EnvironmentValue::Plain(value)) => value.clone(),
+                Some(EnvironmentValue::Secret { secret }) => {
+                    if !self.secret_exists(secret).await? {
+                        return Err(LambdaError::InvalidRequest {
+                            reason: format!("Secret {secret} does not exist"),
+                        });
+                    }
+                    format!("{SECRET_REF_PREFIX}{secret}")
+                }
+            };
+            environment.insert(key.clone(), value);
+            set.push(key.as_str());
+        }
+        validation::check_environment_update(&environment, &set)?;
+
+        let request = UpdateFunctionConfigurationRequest {
+            role: None,
+            handler: None,
+            description: None,
+            timeout: None,
+            memory_size: None,
+            environment: Some(environment),
+        };
+        let function = self
+            .update_function_configuration_by(name, request, changed_by)
+            .await?;
+        self.describe_environment(&function.environment).await
+    }
+
+    /// Apply one operation to every function a selector picks. Each function is changed on
+    /// its own, as the single-function endpoint would, and reported in the results.
+    #[instrument(skip(self, request))]
+    pub async fn batch_functions(
+        &self,
+        request: BatchFunctionsRequest,
+        changed_by: Option<&str>,
+    ) -> Result<BatchFunctionsResponse, LambdaError> {
+        if let BatchOperation::UpdateConfiguration {
+            memory_size,
+            timeout,
+        } = &request.operation
+        {
+            validation::check_batch_configuration(*memory_size, *timeout)?;
+        }
+        let names = self.select_functions(&request.selector).await?;
+
+        let mut results = Vec::with_capacity(names.len());
+        for name in names {
+            let outcome = match &request.operation {
+                BatchOperation::Delete => self.delete_function(&name).await.map(|_| 204),
+                BatchOperation::PatchEnvironment { variables } => {
+                    let patch = PatchEnvironmentRequest {
+                        variables: variables.clone(),
+                    };
+                    self.patch_environment(&name, patch, changed_by)
+                        .await
+                        .map(|_| 200)
+                }
+                BatchOperation::Tag { tags } => {
+                    self.tag_function(&name, tags.clone()).await.map(|_| 200)
+                }
+                BatchOperation::Drain => self.drain_function(&name).await.map(|_| 200),
+                BatchOperation::UpdateConfiguration {
+                    memory_size,
+                    timeout,
+                } => {
+                    let update = UpdateFunctionConfigurationRequest {
+                        role: None,
+                        handler: None,
+                        description: None,
+                        timeout: *timeout,
+                        memory_size: *memory_size,
+                        environment: None,
+                    };
+                    self.update_function_configuration_by(&name, update, changed_by)
+                        .await
+                        .map(|_| 200)
+                }
+            };
+            results.push(match outcome {
+                Ok(status) => BatchItemResult {
+                    function_name: name,
+                    status,
+                    error: None,
+                },
+                Err(e) => {
+                    warn!("Batch operation failed for function {}: {}", name, e);
+                    BatchItemResult {
+                        function_name: name,
+                        status: e.http_status(),
+                        error: Some(e.to_error_shape()),
+                    }
+                }
+            });
+        }
+
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        Ok(BatchFunctionsResponse {
+            succeeded: results.len() - failed,
+            failed,
+            results,
+        })
+    }
+
+    /// Names a selector picks, sorted. Named functions that do not exist are kept so the
+    /// batch reports them as not found.
+    async fn select_functions(
+        &self,
+        selector: &FunctionSelector,
+    ) -> Result<Vec<String>, LambdaError> {
+        let prefix = selector.name_prefix.as_deref().filter(|p| !p.is_empty());
+        if selector.names.is_empty()
+            && prefix.is_none()
+            && selector.tags.is_empty()
+            && selector.group.is_none()
+        {
+            return Err(LambdaError::InvalidRequest {
+                reason: "selector needs names, a non-empty name_prefix, tags or a group"
+                    .to_string(),
+            });
+        }
+        if let Some(group) = &selector.group {
+            self.require_group(group).await?;
+        }
+        let mut names: BTreeSet<String> = selector.names.iter().cloned().collect();
+        if prefix.is_some() || !selector.tags.is_empty() || selector.group.is_some() {
+            let matching: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT function_name FROM functions f
+                 WHERE (?1 IS NULL OR instr(f.function_name, ?1) = 1) AND {} AND {}",
+                tags_clause("?2"),
+                group_clause("?3")
+            ))
+            .bind(prefix)
+            .bind(serde_json::to_string(&selector.tags).unwrap_or_default())
+            .bind(&selector.group)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+            names.extend(matching);
+        }
+        Ok(names.into_iter().collect())
+    }
+
+    async fn describe_environment(
+        &self,
+        environment: &HashMap<String, String>,
+    ) -> Result<FunctionEnvironment, LambdaError> {
+        let mut variables = Vec::with_capacity(environment.len());
+        for (name, value) in environment {
+            let variable = match value.strip_prefix(SECRET_REF_PREFIX) {
+                Some(secret) => EnvironmentVariable {
+                    name: name.clone(),
+                    value: None,
+                    secret: Some(secret.to_string()),
+                    secret_exists: Some(self.secret_exists(secret).await?),
+                },
+                None => EnvironmentVariable {
+                    name: name.clone(),
+                    value: Some(value.clone()),
+                    secret: None,
+                    secret_exists: None,
+                },
+            };
+            variables.push(variable);
+        }
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(FunctionEnvironment { variables })
+    }
+
+    async fn secret_exists(&self, name: &str) -> Result<bool, LambdaError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM secrets WHERE name = ?")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(count > 0)
+    }
+
     // Secrets management helpers
     pub async fn create_secret(&self, name: &str, value: &str) -> Result<(), LambdaError> {
         let now = chrono::Utc::now();
@@ -1645,9 +9053,118 @@ impl ControlPlane {
         Ok(None)
     }
 
+    /// Create the secrets of a dotenv or JSON file in one transaction. Existing secrets are
+    /// kept, replaced, or fail the whole import, as `on_conflict` says.
+    #[instrument(skip(self, request))]
+    pub async fn import_secrets(
+        &self,
+        request: ImportSecretsRequest,
+    ) -> Result<ImportSecretsResponse, LambdaError> {
+        let imported = secrets::parse_secrets_file(&request.content, request.format)?;
+        let existing: BTreeSet<String> = self
+            .list_secrets()
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| imported.contains_key(name))
+            .collect();
+        if request.on_conflict == SecretConflictMode::Fail && !existing.is_empty() {
+            return Err(LambdaError::SecretsAlreadyExist {
+                names: existing.into_iter().collect(),
+            });
+        }
+
+        let now = Utc::now();
+        let mut response = ImportSecretsResponse::default();
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        for (name, value) in &imported {
+            if existing.contains(name) {
+                if request.on_conflict == SecretConflictMode::Skip {
+                    response.skipped.push(name.clone());
+                    continue;
+                }
+                response.overwritten.push(name.clone());
+            } else {
+                response.created.push(name.clone());
+            }
+            let encoded = base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                value.as_bytes(),
+            );
+            sqlx::query("INSERT OR REPLACE INTO secrets(name, value, created_at) VALUES(?, ?, ?)")
+                .bind(name)
+                .bind(encoded)
+                .bind(now)
+                .execute(&mut *tx)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        }
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+
+        for name in &response.overwritten {
+            self.cache.invalidate_secret(name);
+            self.drain_functions_referencing_secret(name).await?;
+        }
+        info!(
+            "Imported secrets: {} created, {} overwritten, {} skipped",
+            response.created.len(),
+            response.overwritten.len(),
+            response.skipped.len()
+        );
+        Ok(response)
+    }
+
+    /// Every secret's name, and with `include_values` and the confirmation its value. Exports
+    /// of values are recorded in the audit log under `actor`.
+    #[instrument(skip(self, request))]
+    pub async fn export_secrets(
+        &self,
+        request: ExportSecretsRequest,
+        actor: Option<&str>,
+    ) -> Result<ExportSecretsResponse, LambdaError> {
+        if request.include_values && request.confirm.as_deref() != Some(EXPORT_VALUES_CONFIRMATION)
+        {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!(
+                    "Exporting secret values needs confirm set to \"{EXPORT_VALUES_CONFIRMATION}\""
+                ),
+            });
+        }
+        let mut exported = Vec::new();
+        for (name, created_at) in self.list_secrets().await? {
+            let value = match request.include_values {
+                true => self.get_secret_value(&name).await?,
+                false => None,
+            };
+            exported.push(ExportedSecret {
+                name,
+                created_at,
+                value,
+            });
+        }
+        if request.include_values {
+            let names: Vec<String> = exported.iter().map(|s| s.name.clone()).collect();
+            secrets::record_export(&self.pool, actor, &names).await?;
+            warn!(
+                "Exported the values of {} secret(s) for {}",
+                names.len(),
+                actor.unwrap_or("an unnamed actor")
+            );
+        }
+        Ok(ExportSecretsResponse { secrets: exported })
+    }
+
+    /// Exports that included secret values, newest first
+    pub async fn list_secret_exports(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<SecretExportAuditEntry>, LambdaError> {
+        secrets::list_exports(&self.pool, limit as i64).await
+    }
+
     async fn drain_functions_referencing_secret(&self, name: &str) -> Result<(), LambdaError> {
         // Find functions whose environment JSON contains this secret reference
-        let pattern = format!("%SECRET_REF:{name}%");
+        let pattern = format!("%{SECRET_REF_PREFIX}{name}%");
         let rows = sqlx::query("SELECT function_id FROM functions WHERE environment LIKE ?")
             .bind(&pattern)
             .fetch_all(&self.pool)
@@ -1663,6 +9180,408 @@ impl ControlPlane {
         Ok(())
     }
 
+    /// Register a webhook target; the returned secret signs every delivery to it
+    #[instrument(skip(self, request))]
+    pub async fn create_webhook(
+        &self,
+        request: CreateWebhookRequest,
+    ) -> Result<CreateWebhookResponse, LambdaError> {
+        let url = request.url.trim().to_string();
+        validation::validate_http_url("url", &url)?;
+        let secret = match request.secret {
+            Some(secret) => {
+                validate_webhook_secret(&secret)?;
+                secret
+            }
+            None => generate_secret(),
+        };
+        let mut events = request.events;
+        events.sort_by_key(|e| e.as_str());
+        events.dedup();
+
+        let webhook = Webhook {
+            webhook_id: Uuid::new_v4(),
+            url,
+            events,
+            description: request.description.filter(|d| !d.trim().is_empty()),
+            enabled: request.enabled.unwrap_or(true),
+            created_at: Utc::now(),
+        };
+        sqlx::query(
+            "INSERT INTO webhooks(webhook_id, url, secret, events, description, enabled, created_at) VALUES(?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(webhook.webhook_id)
+        .bind(&webhook.url)
+        .bind(&secret)
+        .bind(serde_json::to_string(&webhook.events).unwrap_or_default())
+        .bind(&webhook.description)
+        .bind(webhook.enabled)
+        .bind(webhook.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        info!("Created webhook {} for {}", webhook.webhook_id, webhook.url);
+        Ok(CreateWebhookResponse { webhook, secret })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_webhooks(&self) -> Result<ListWebhooksResponse, LambdaError> {
+        let rows = sqlx::query(
+            "SELECT webhook_id, url, events, description, enabled, created_at FROM webhooks ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let webhooks = rows.iter().map(row_to_webhook).collect();
+        Ok(ListWebhooksResponse { webhooks })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_webhook(&self, webhook_id: Uuid) -> Result<Webhook, LambdaError> {
+        let row = sqlx::query(
+            "SELECT webhook_id, url, events, description, enabled, created_at FROM webhooks WHERE webhook_id = ?",
+        )
+        .bind(webhook_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::WebhookNotFound {
+            webhook_id: webhook_id.to_string(),
+        })?;
+        Ok(row_to_webhook(&row))
+    }
+
+    /// Remove a webhook along with its delivery log
+    #[instrument(skip(self))]
+    pub async fn delete_webhook(&self, webhook_id: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE webhook_id = ?")
+            .bind(webhook_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::WebhookNotFound {
+                webhook_id: webhook_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Most recent deliveries to a webhook, newest first
+    #[instrument(skip(self))]
+    pub async fn list_webhook_deliveries(
+        &self,
+        webhook_id: Uuid,
+        limit: u32,
+    ) -> Result<ListWebhookDeliveriesResponse, LambdaError> {
+        self.get_webhook(webhook_id).await?;
+        let rows = sqlx::query(
+            "SELECT * FROM webhook_deliveries WHERE webhook_id = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(webhook_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        let deliveries = rows
+            .iter()
+            .map(row_to_delivery)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ListWebhookDeliveriesResponse { deliveries })
+    }
+
+    fn emit_alias_updated(&self, function_name: &str, alias: &Alias) {
+        self.webhooks.emit(
+            WebhookEventType::AliasUpdated,
+            Some(function_name),
+            serde_json::json!({
+                "alias": alias.name,
+                "function_version": alias.function_version,
+                "routing_config": alias.routing_config,
+            }),
+        );
+    }
+
+    /// Map a local queue to a function so its messages are delivered in batches
+    #[instrument(skip(self, request))]
+    pub async fn create_event_source_mapping(
+        &self,
+        request: CreateEventSourceMappingRequest,
+    ) -> Result<EventSourceMapping, LambdaError> {
+        let mut function_response_types = request.function_response_types;
+        function_response_types.dedup();
+        let (queue_name, bucket, batch_size, s3_key_filter) =
+            if let Some(queue_name) = queue_name_from_arn(&request.event_source_arn) {
+                if request.s3_key_filter.is_some() {
+                    return Err(LambdaError::InvalidRequest {
+                        reason: "s3_key_filter only applies to S3 event sources".to_string(),
+                    });
+                }
+                let queue = self.sqs.get_queue(queue_name).await?;
+                let batch_size = request.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+                validate_batch_size(batch_size)?;
+                (Some(queue.name), None, batch_size, None)
+            } else if let Some(bucket) = bucket_name_from_arn(&request.event_source_arn) {
+                validate_s3_source(bucket, request.batch_size, &function_response_types)?;
+                let filter = normalize_key_filter(request.s3_key_filter);
+                (None, Some(bucket.to_string()), 1, filter)
+            } else {
+                return Err(LambdaError::InvalidRequest {
+                    reason: format!(
+                        "event_source_arn must be a local queue or S3 bucket ARN: {}",
+                        request.event_source_arn
+                    ),
+                });
+            };
+        let function = self.get_function(&request.function_name).await?;
+
+        let mapping = EventSourceMapping {
+            uuid: Uuid::new_v4(),
+            event_source_arn: match (&queue_name, &bucket) {
+                (Some(queue_name), _) => self.config.identity.queue_arn(queue_name),
+                (None, Some(bucket)) => self.config.identity.bucket_arn(bucket),
+                (None, None) => unreachable!("every source has a queue or a bucket"),
+            },
+            function_name: function.function_name,
+            batch_size,
+            enabled: request.enabled.unwrap_or(true),
+            function_response_types,
+            last_modified: Utc::now(),
+            last_processing_result: None,
+            s3_key_filter,
+        };
+        let filter = mapping.s3_key_filter.clone().unwrap_or_default();
+        sqlx::query(
+            r#"INSERT INTO event_source_mappings(uuid, queue_name, bucket, key_prefix, key_suffix, function_id, batch_size, enabled, report_batch_item_failures, last_modified)
+               VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(mapping.uuid)
+        .bind(&queue_name)
+        .bind(&bucket)
+        .bind(&filter.prefix)
+        .bind(&filter.suffix)
+        .bind(function.function_id)
+        .bind(mapping.batch_size as i64)
+        .bind(mapping.enabled)
+        .bind(!mapping.function_response_types.is_empty())
+        .bind(mapping.last_modified)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        info!(
+            "Mapped {} to function {}",
+            mapping.event_source_arn, mapping.function_name
+        );
+        Ok(mapping)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_event_source_mapping(
+        &self,
+        uuid: Uuid,
+    ) -> Result<EventSourceMapping, LambdaError> {
+        let row = sqlx::query(
+            r#"SELECT m.*, f.function_name FROM event_source_mappings m
+               JOIN functions f ON f.function_id = m.function_id WHERE m.uuid = ?"#,
+        )
+        .bind(uuid)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .ok_or_else(|| LambdaError::EventSourceMappingNotFound {
+            uuid: uuid.to_string(),
+        })?;
+        Ok(row_to_event_source_mapping(&self.config.identity, &row))
+    }
+
+    /// All mappings, or only those of one function
+    #[instrument(skip(self))]
+    pub async fn list_event_source_mappings(
+        &self,
+        function_name: Option<&str>,
+    ) -> Result<ListEventSourceMappingsResponse, LambdaError> {
+        let rows = sqlx::query(
+            r#"SELECT m.*, f.function_name FROM event_source_mappings m
+               JOIN functions f ON f.function_id = m.function_id
+               WHERE ?1 IS NULL OR f.function_name = ?1
+               ORDER BY m.last_modified"#,
+        )
+        .bind(function_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(ListEventSourceMappingsResponse {
+            event_source_mappings: rows
+                .iter()
+                .map(|row| row_to_event_source_mapping(&self.config.identity, row))
+                .collect(),
+        })
+    }
+
+    #[instrument(skip(self, request))]
+    pub async fn update_event_source_mapping(
+        &self,
+        uuid: Uuid,
+        request: UpdateEventSourceMappingRequest,
+    ) -> Result<EventSourceMapping, LambdaError> {
+        let mut mapping = self.get_event_source_mapping(uuid).await?;
+        let bucket = bucket_name_from_arn(&mapping.event_source_arn).map(str::to_string);
+        if let Some(batch_size) = request.batch_size {
+            validate_batch_size(batch_size)?;
+            mapping.batch_size = batch_size;
+        }
+        if let Some(enabled) = request.enabled {
+            mapping.enabled = enabled;
+        }
+        if let Some(mut types) = request.function_response_types {
+            types.dedup();
+            mapping.function_response_types = types;
+        }
+        match &bucket {
+            Some(bucket) => {
+                validate_s3_source(
+                    bucket,
+                    Some(mapping.batch_size),
+                    &mapping.function_response_types,
+                )?;
+                if request.s3_key_filter.is_some() {
+                    mapping.s3_key_filter = normalize_key_filter(request.s3_key_filter);
+                }
+            }
+            None if request.s3_key_filter.is_some() => {
+                return Err(LambdaError::InvalidRequest {
+                    reason: "s3_key_filter only applies to S3 event sources".to_string(),
+                });
+            }
+            None => {}
+        }
+        mapping.last_modified = Utc::now();
+
+        let filter = mapping.s3_key_filter.clone().unwrap_or_default();
+        sqlx::query(
+            "UPDATE event_source_mappings SET batch_size = ?, enabled = ?, report_batch_item_failures = ?, key_prefix = ?, key_suffix = ?, last_modified = ? WHERE uuid = ?",
+        )
+        .bind(mapping.batch_size as i64)
+        .bind(mapping.enabled)
+        .bind(!mapping.function_response_types.is_empty())
+        .bind(&filter.prefix)
+        .bind(&filter.suffix)
+        .bind(mapping.last_modified)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(mapping)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_event_source_mapping(&self, uuid: Uuid) -> Result<(), LambdaError> {
+        let result = sqlx::query("DELETE FROM event_source_mappings WHERE uuid = ?")
+            .bind(uuid)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::EventSourceMappingNotFound {
+                uuid: uuid.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn record_event_source_result(
+        &self,
+        uuid: Uuid,
+        result: &str,
+    ) -> Result<(), LambdaError> {
+        sqlx::query("UPDATE event_source_mappings SET last_processing_result = ? WHERE uuid = ?")
+            .bind(result)
+            .bind(uuid)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// How function containers created now reach the runtime API
+    #[instrument(skip(self))]
+    pub async fn runtime_api_status(&self) -> Result<RuntimeApiStatus, LambdaError> {
+        let (settings, overridden) = self.invoker.runtime_api_settings();
+        let address = self.invoker.runtime_api_address().await?;
+        Ok(RuntimeApiStatus {
+            settings,
+            overridden,
+            advertised: address.advertised,
+            extra_hosts: address.extra_hosts.unwrap_or_default(),
+        })
+    }
+
+    /// Change how containers created from now on reach the runtime API, without a restart;
+    /// None goes back to `[docker]` in the config file
+    #[instrument(skip(self))]
+    pub async fn set_runtime_api(
+        &self,
+        settings: Option<RuntimeApiSettings>,
+    ) -> Result<RuntimeApiStatus, LambdaError> {
+        self.invoker.set_runtime_api(settings).await?;
+        self.runtime_api_status().await
+    }
+
+    /// Diagnose the environment: the Docker daemon, what function containers can reach
+    /// (by running a probe container) and free disk space
+    #[instrument(skip(self))]
+    pub async fn doctor(&self) -> DoctorReport {
+        let version = self.invoker.docker_version().await;
+        let mut checks = vec![doctor::check_docker(version.as_ref())];
+        if version.is_err() {
+            checks.extend(doctor::probe_not_run(
+                "Docker is unreachable",
+                "Fix the docker check first",
+            ));
+        } else if let Err(e) = self.invoker.ensure_image(PROBE_IMAGE).await {
+            checks.extend(doctor::probe_not_run(
+                &format!("the probe image could not be pulled: {e}"),
+                &format!(
+                    "Pull it by hand (docker pull {PROBE_IMAGE}) or let the Docker daemon reach \
+                     the registry"
+                ),
+            ));
+        } else {
+            match self.invoker.runtime_api_address().await {
+                Ok(address) => {
+                    let outcome = self
+                        .invoker
+                        .run_probe(
+                            PROBE_IMAGE,
+                            probe_command(&address.advertised),
+                            PROBE_TIMEOUT_MS,
+                        )
+                        .await;
+                    checks.extend(doctor::check_probe(outcome, &self.config.server));
+                }
+                Err(e) => checks.extend(doctor::probe_not_run(
+                    &e.to_string(),
+                    "Create the network, or set another through PUT /api/admin/runtime-api",
+                )),
+            }
+        }
+        let sandbox = match &version {
+            Ok(_) => Some(self.invoker.sandbox_support().await),
+            Err(_) => None,
+        };
+        checks.push(doctor::check_sandbox(
+            sandbox.as_ref().map(|support| support.as_ref()),
+            self.invoker.sandbox_profiles(),
+        ));
+        checks.push(doctor::check_disk(&self.disk_status()));
+        DoctorReport {
+            checks,
+            checked_at: Utc::now(),
+        }
+    }
+
     /// Get comprehensive Docker and cache statistics
     #[instrument(skip(self))]
     pub async fn get_docker_stats(&self) -> Result<DockerStats, LambdaError> {
@@ -1852,40 +9771,37 @@ impl ControlPlane {
         // Apply timeout to the entire warm-up process
         let warmup_future = async {
             // Build image reference
-            let image_ref = format!(
-                "lambda-home/{}:{}",
-                function.function_name, function.code_sha256
-            );
+            let image_ref = function.image_ref();
 
             // Build Docker image first
-            let mut packaging_service =
-                lambda_packaging::PackagingService::new(self.config.clone());
-            packaging_service.build_image(function, &image_ref, self.config.server.port_runtime_api).await?;
+            self.build_function_image(function, &image_ref, BuildPriority::Normal)
+                .await?;
 
             // Create container: generate instance id and inject as env
             let instance_id = uuid::Uuid::new_v4().to_string();
             let mut env_vars = self.resolve_env_vars(function).await?;
             env_vars.insert("LAMBDAH_INSTANCE_ID".to_string(), instance_id.clone());
+            env_vars.insert(
+                INSTANCE_TOKEN_ENV.to_string(),
+                self.instance_tokens.token(&instance_id),
+            );
+
+            let fn_key = crate::queues::FnKey::for_function(function);
+            let _placement = self.reserve_memory(&fn_key, function.memory_size).await?;
 
             let container_id = self
-                .invoker
-                .create_container(function, &image_ref, env_vars)
+                .create_function_container(function, &image_ref, env_vars)
                 .await?;
             self.invoker.start_container(&container_id).await?;
 
             // Add to warm pool
-            let fn_key = crate::queues::FnKey {
-                function_name: function.function_name.clone(),
-                runtime: function.runtime.clone(),
-                version: "1".to_string(), // Default version for new functions
-                env_hash: self.compute_env_hash(&function.environment).await?,
-            };
 
             let warm_container = crate::warm_pool::WarmContainer {
                 container_id: container_id.clone(),
                 instance_id: instance_id.clone(),
                 function_id: function.function_id,
                 image_ref: image_ref.clone(),
+                memory_mb: function.memory_size,
                 created_at: std::time::Instant::now(),
                 last_used: std::time::Instant::now(),
                 state: crate::warm_pool::InstanceState::WarmIdle, // Ready for work
@@ -1918,28 +9834,64 @@ impl ControlPlane {
             }
         }
     }
+}
 
-    /// Compute environment hash for function key
-    async fn compute_env_hash(
-        &self,
-        environment: &std::collections::HashMap<String, String>,
-    ) -> Result<String, LambdaError> {
-        // Create a deterministic hash of the environment variables
-        let mut env_string = String::new();
-        let mut keys: Vec<_> = environment.keys().collect();
-        keys.sort(); // Ensure deterministic ordering
-
-        for key in keys {
-            if let Some(value) = environment.get(key) {
-                env_string.push_str(&format!("{key}={value};"));
-            }
-        }
-
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+/// Publish the function's current code and configuration as its next version: 1, then one
+/// past the highest version published so far
+async fn insert_next_version(
+    conn: &mut sqlx::SqliteConnection,
+    function: &Function,
+    description: Option<String>,
+) -> Result<Version, LambdaError> {
+    let latest: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(CAST(version AS INTEGER)), 0) FROM versions WHERE function_id = ?",
+    )
+    .bind(function.function_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(LambdaError::SqlxError)?;
+    // Unknown until the code is built; recording the build fills it in then
+    let image_digest: Option<String> = sqlx::query_scalar(
+        "SELECT image_digest FROM function_image_digests WHERE function_id = ? AND code_sha256 = ?",
+    )
+    .bind(function.function_id)
+    .bind(&function.code_sha256)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(LambdaError::SqlxError)?;
+    let version = Version {
+        version_id: Uuid::new_v4(),
+        function_id: function.function_id,
+        version: (latest + 1).to_string(),
+        description,
+        code_sha256: function.code_sha256.clone(),
+        last_modified: Utc::now(),
+        code_size: function.code_size,
+        image_digest,
+        config: Some(FunctionConfigSnapshot::from(function)),
+        image_uri: function.image_uri.clone(),
+    };
 
-        let mut hasher = DefaultHasher::new();
-        env_string.hash(&mut hasher);
-        Ok(format!("{:x}", hasher.finish()))
-    }
+    sqlx::query(
+        r#"
+        INSERT INTO versions (
+            version_id, function_id, version, description, code_sha256,
+            last_modified, code_size, image_digest, config, image_uri
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(version.version_id)
+    .bind(version.function_id)
+    .bind(&version.version)
+    .bind(&version.description)
+    .bind(&version.code_sha256)
+    .bind(version.last_modified)
+    .bind(version.code_size as i64)
+    .bind(&version.image_digest)
+    .bind(serde_json::to_string(&version.config).unwrap_or_default())
+    .bind(&version.image_uri)
+    .execute(&mut *conn)
+    .await
+    .map_err(LambdaError::SqlxError)?;
+    Ok(version)
 }
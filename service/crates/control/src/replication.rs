@@ -0,0 +1,437 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use lambda_models::{
+    ApiRoute, CreateApiRouteRequest, CreateFunctionRequest, ForwardedInvocations, Function,
+    InstanceHealth, LambdaError, ListApiRoutesResponse, ReplicationConfig, ReplicationItem,
+    ReplicationStatus, UpdateFunctionConfigurationRequest,
+};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Sync step for one function or route, decided from fingerprints of its local definition,
+/// of the peer's copy and of what the last round pushed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncAction {
+    InSync,
+    /// The peer has no copy yet
+    Create,
+    /// Replace the peer's copy with the local definition
+    Update,
+    /// The peer already has the local definition; track it from now on
+    Adopt,
+    /// Gone locally; remove the peer's copy
+    Delete,
+    /// Nothing left to replicate; stop tracking it
+    Forget,
+    Conflict(String),
+}
+
+const CHANGED_ON_PEER: &str = "changed on the peer since the last sync";
+
+/// Pick the sync step. A peer copy that no longer matches what the last round pushed was
+/// changed on the peer, which is a conflict unless `force` lets the local side win.
+pub fn plan_sync(
+    local: Option<&str>,
+    peer: Option<&str>,
+    synced: Option<&str>,
+    force: bool,
+) -> SyncAction {
+    let changed_on_peer = || SyncAction::Conflict(CHANGED_ON_PEER.to_string());
+    match (local, peer, synced) {
+        (None, None, _) | (None, Some(_), None) => SyncAction::Forget,
+        (None, Some(peer), Some(synced)) => {
+            if peer == synced || force {
+                SyncAction::Delete
+            } else {
+                changed_on_peer()
+            }
+        }
+        (Some(_), None, None) => SyncAction::Create,
+        (Some(_), None, Some(_)) => {
+            if force {
+                SyncAction::Create
+            } else {
+                SyncAction::Conflict("deleted on the peer".into())
+            }
+        }
+        (Some(local), Some(peer), synced) if local == peer => {
+            if synced == Some(peer) {
+                SyncAction::InSync
+            } else {
+                SyncAction::Adopt
+            }
+        }
+        (Some(_), Some(peer), synced) => {
+            if force || synced == Some(peer) {
+                SyncAction::Update
+            } else if synced.is_none() {
+                SyncAction::Conflict("exists on the peer with a different definition".into())
+            } else {
+                changed_on_peer()
+            }
+        }
+    }
+}
+
+/// Set on invokes forwarded to the peer, which runs them itself instead of forwarding again
+pub const FORWARDED_HEADER: &str = "X-Lambdah-Forwarded";
+
+/// Forward invokes when this instance is unhealthy and the peer recently reported itself
+/// healthy; a peer report older than `stale_after` no longer counts
+pub fn should_forward(
+    local: Option<&InstanceHealth>,
+    peer: Option<&InstanceHealth>,
+    now: DateTime<Utc>,
+    stale_after: chrono::Duration,
+) -> bool {
+    local.is_some_and(|local| !local.healthy)
+        && peer.is_some_and(|peer| peer.healthy && now - peer.checked_at <= stale_after)
+}
+
+/// Digest of everything replication copies for a function
+pub fn function_fingerprint(function: &Function) -> String {
+    let environment: BTreeMap<_, _> = function.environment.iter().collect();
+    let definition = serde_json::json!({
+        "runtime": function.runtime,
+        "role": function.role,
+        "handler": function.handler,
+        "code_sha256": function.code_sha256,
+        "description": function.description,
+        "timeout": function.timeout,
+        "memory_size": function.memory_size,
+        "environment": environment,
+    });
+    let mut hasher = Sha256::new();
+    hasher.update(definition.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Routes cannot be edited, so their method, path and function identify them
+pub fn route_fingerprint(route: &ApiRoute) -> String {
+    format!("{} -> {}", route_label(route), route.function_name)
+}
+
+/// How a route is shown in the replication status
+pub fn route_label(route: &ApiRoute) -> String {
+    format!(
+        "{} {}",
+        route.method.as_deref().unwrap_or("ANY"),
+        route.path
+    )
+}
+
+/// What the last round pushed for an item
+#[derive(Debug, Clone)]
+pub struct SyncedItem {
+    pub label: String,
+    /// Id of the copy on the peer, for routes
+    pub peer_id: Option<String>,
+    pub fingerprint: String,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// What the peer answered to a forwarded invoke
+#[derive(Debug, Clone)]
+pub struct ForwardedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Management API of the replication peer
+#[derive(Clone)]
+pub struct PeerClient {
+    base_url: String,
+    auth_token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl PeerClient {
+    pub fn new(peer_url: &str, config: &ReplicationConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+        Self {
+            base_url: peer_url.trim_end_matches('/').to_string(),
+            auth_token: config.auth_token.clone(),
+            client,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{}", self.base_url, path));
+        match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Send a request; a 404 answers `None`
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Option<T>, LambdaError> {
+        let peer_error = |reason: String| LambdaError::PeerError { reason };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| peer_error(e.to_string()))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(peer_error(format!("{status}: {}", body.trim())));
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| peer_error(e.to_string()))?;
+        // Deletes answer 204 without a body
+        let body: &[u8] = if body.is_empty() { b"null" } else { &body };
+        serde_json::from_slice(body)
+            .map(Some)
+            .map_err(|e| peer_error(format!("unexpected response: {e}")))
+    }
+
+    pub async fn get_function(&self, name: &str) -> Result<Option<Function>, LambdaError> {
+        let path = format!("/api/functions/{name}");
+        self.send(self.request(reqwest::Method::GET, &path)).await
+    }
+
+    pub async fn create_function(
+        &self,
+        request: &CreateFunctionRequest,
+    ) -> Result<(), LambdaError> {
+        let builder = self
+            .request(reqwest::Method::POST, "/2015-03-31/functions")
+            .json(request);
+        self.send::<serde_json::Value>(builder).await?;
+        Ok(())
+    }
+
+    pub async fn update_function_configuration(
+        &self,
+        name: &str,
+        request: &UpdateFunctionConfigurationRequest,
+    ) -> Result<(), LambdaError> {
+        let path = format!("/2015-03-31/functions/{name}/configuration");
+        let builder = self.request(reqwest::Method::PUT, &path).json(request);
+        self.send::<serde_json::Value>(builder).await?;
+        Ok(())
+    }
+
+    pub async fn delete_function(&self, name: &str) -> Result<(), LambdaError> {
+        let path = format!("/api/functions/{name}");
+        self.send::<serde_json::Value>(self.request(reqwest::Method::DELETE, &path))
+            .await?;
+        Ok(())
+    }
+
+    /// Send this instance's health and get the peer's in return
+    pub async fn exchange_health(
+        &self,
+        health: &InstanceHealth,
+    ) -> Result<Option<InstanceHealth>, LambdaError> {
+        let builder = self
+            .request(reqwest::Method::POST, "/admin/replication/health")
+            .json(health);
+        self.send(builder).await
+    }
+
+    /// Pass an invoke through to the peer as-is, whatever status it answers with
+    pub async fn forward_invoke(
+        &self,
+        name: &str,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    ) -> Result<ForwardedResponse, LambdaError> {
+        let path = format!("/2015-03-31/functions/{name}/invocations");
+        let mut builder = self
+            .request(reqwest::Method::POST, &path)
+            .header(FORWARDED_HEADER, "true")
+            .body(body);
+        for (name, value) in headers {
+            builder = builder.header(name, value);
+        }
+        let peer_error = |e: reqwest::Error| LambdaError::PeerError {
+            reason: e.to_string(),
+        };
+        let response = builder.send().await.map_err(peer_error)?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.bytes().await.map_err(peer_error)?.to_vec();
+        Ok(ForwardedResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    pub async fn list_routes(&self) -> Result<Vec<ApiRoute>, LambdaError> {
+        let builder = self.request(reqwest::Method::GET, "/admin/api-gateway/routes");
+        Ok(self
+            .send::<ListApiRoutesResponse>(builder)
+            .await?
+            .map(|r| r.routes)
+            .unwrap_or_default())
+    }
+
+    pub async fn create_route(
+        &self,
+        request: &CreateApiRouteRequest,
+    ) -> Result<ApiRoute, LambdaError> {
+        let builder = self
+            .request(reqwest::Method::POST, "/admin/api-gateway/routes")
+            .json(request);
+        self.send(builder)
+            .await?
+            .ok_or_else(|| LambdaError::PeerError {
+                reason: "route endpoint not found".to_string(),
+            })
+    }
+
+    pub async fn delete_route(&self, route_id: Uuid) -> Result<(), LambdaError> {
+        let path = format!("/admin/api-gateway/routes/{route_id}");
+        self.send::<serde_json::Value>(self.request(reqwest::Method::DELETE, &path))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Peer connection and latest status of replication, shared by the sync loop and the API
+#[derive(Clone)]
+pub struct Replicator {
+    peer: Option<PeerClient>,
+    status: Arc<RwLock<ReplicationStatus>>,
+    /// Keeps a manual sync from running alongside the periodic one
+    round: Arc<tokio::sync::Mutex<()>>,
+    /// Peer health reports older than this are ignored for failover
+    stale_after: chrono::Duration,
+    forwarded: Arc<DashMap<String, ForwardedInvocations>>,
+}
+
+impl Replicator {
+    pub fn new(config: &ReplicationConfig) -> Self {
+        let peer_url = config
+            .peer_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|u| !u.is_empty());
+        if config.enabled && peer_url.is_none() {
+            warn!("Replication is enabled without a peer_url; nothing will be replicated");
+        }
+        let peer = peer_url
+            .filter(|_| config.enabled)
+            .map(|url| PeerClient::new(url, config));
+        let status = ReplicationStatus {
+            enabled: peer.is_some(),
+            peer_url: peer_url.map(str::to_string),
+            failover: peer.is_some() && config.failover,
+            ..Default::default()
+        };
+        // Three missed reports mark the peer's health as unknown
+        let stale_after =
+            chrono::Duration::seconds(config.health_interval_secs.max(1).saturating_mul(3) as i64);
+        Self {
+            peer,
+            status: Arc::new(RwLock::new(status)),
+            round: Arc::new(tokio::sync::Mutex::new(())),
+            stale_after,
+            forwarded: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn peer(&self) -> Option<&PeerClient> {
+        self.peer.as_ref()
+    }
+
+    pub async fn lock_round(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.round.lock().await
+    }
+
+    pub fn status(&self) -> ReplicationStatus {
+        let mut status = self.status.read().unwrap().clone();
+        status.forwarded = self.forwarded.iter().map(|f| f.value().clone()).collect();
+        status
+            .forwarded
+            .sort_by(|a, b| a.function_name.cmp(&b.function_name));
+        status
+    }
+
+    pub fn local_health(&self) -> Option<InstanceHealth> {
+        self.status.read().unwrap().local_health.clone()
+    }
+
+    pub fn set_local_health(&self, health: InstanceHealth) {
+        self.status.write().unwrap().local_health = Some(health);
+    }
+
+    /// Remember the peer's health as of now
+    pub fn record_peer_health(&self, health: InstanceHealth) {
+        self.status.write().unwrap().peer_health = Some(InstanceHealth {
+            checked_at: Utc::now(),
+            ..health
+        });
+    }
+
+    /// The peer to send invokes to, when failover is on and should happen now
+    pub fn failover_peer(&self) -> Option<&PeerClient> {
+        let status = self.status.read().unwrap();
+        let forward = status.failover
+            && should_forward(
+                status.local_health.as_ref(),
+                status.peer_health.as_ref(),
+                Utc::now(),
+                self.stale_after,
+            );
+        self.peer.as_ref().filter(|_| forward)
+    }
+
+    /// Count an invoke sent to the peer; `failed` when the peer could not be reached
+    pub fn record_forward(&self, function_name: &str, failed: bool) {
+        let mut entry = self
+            .forwarded
+            .entry(function_name.to_string())
+            .or_insert_with(|| ForwardedInvocations {
+                function_name: function_name.to_string(),
+                ..Default::default()
+            });
+        if failed {
+            entry.failed += 1;
+        } else {
+            entry.forwarded += 1;
+        }
+    }
+
+    /// Record the outcome of a round that started at `started`
+    pub fn finish_round(
+        &self,
+        started: DateTime<Utc>,
+        result: Result<Vec<ReplicationItem>, LambdaError>,
+    ) -> ReplicationStatus {
+        let mut status = self.status.write().unwrap();
+        status.last_sync_at = Some(started);
+        match result {
+            Ok(items) => {
+                status.items = items;
+                status.last_error = None;
+            }
+            Err(e) => status.last_error = Some(e.to_string()),
+        }
+        status.clone()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::validation::check_response_header;
+use lambda_models::ResponseHeadersConfig;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Headers and body of a successful direct invoke response. Headers come from
+/// `global_headers`, then the function's `config`, then the result's envelope when the
+/// function opted into one; later ones win. Names are lowercase.
+pub fn shape_response(
+    global_headers: &HashMap<String, String>,
+    config: &ResponseHeadersConfig,
+    payload: serde_json::Value,
+) -> (serde_json::Value, HashMap<String, String>) {
+    let mut headers: HashMap<String, String> = global_headers
+        .iter()
+        .chain(&config.headers)
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.clone()))
+        .collect();
+    if !config.envelope {
+        return (payload, headers);
+    }
+    match unwrap_envelope(payload) {
+        Ok((body, envelope_headers)) => {
+            for (name, value) in envelope_headers {
+                match check_response_header(&name, &value) {
+                    Ok(()) => {
+                        headers.insert(name.to_ascii_lowercase(), value);
+                    }
+                    Err(reason) => warn!("Dropping response header from envelope: {}", reason),
+                }
+            }
+            (body, headers)
+        }
+        Err(payload) => (payload, headers),
+    }
+}
+
+/// `body` and `headers` of an envelope, or the payload back when it is not one. An envelope
+/// is an object with exactly these two keys; header values that are not strings are sent as
+/// JSON.
+fn unwrap_envelope(
+    payload: serde_json::Value,
+) -> Result<(serde_json::Value, HashMap<String, String>), serde_json::Value> {
+    let mut object = match payload {
+        serde_json::Value::Object(object)
+            if object.len() == 2
+                && object.contains_key("body")
+                && object.get("headers").is_some_and(|h| h.is_object()) =>
+        {
+            object
+        }
+        other => return Err(other),
+    };
+    let body = object.remove("body").unwrap_or_default();
+    let headers = match object.remove("headers") {
+        Some(serde_json::Value::Object(headers)) => headers
+            .into_iter()
+            .map(|(name, value)| match value {
+                serde_json::Value::String(value) => (name, value),
+                other => (name, other.to_string()),
+            })
+            .collect(),
+        _ => HashMap::new(),
+    };
+    Ok((body, headers))
+}
@@ -0,0 +1,109 @@
+use crate::warm_pool::WarmContainer;
+use dashmap::DashMap;
+use lambda_models::{Function, Rollout, RolloutStatus};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::info;
+
+/// Settings containers are started with, so that changing them needs new containers. The
+/// timeout is not among them: each invocation carries its own deadline.
+pub fn changed_container_settings(before: &Function, after: &Function) -> Vec<String> {
+    let mut changed = Vec::new();
+    if before.memory_size != after.memory_size {
+        changed.push("memory_size".to_string());
+    }
+    if before.environment != after.environment {
+        changed.push("environment".to_string());
+    }
+    changed
+}
+
+/// A rollout waiting for the control plane to carry it out
+pub struct RolloutTask {
+    /// The function with its new settings
+    pub function: Function,
+    pub generation: u64,
+    /// Running containers with the previous settings
+    pub containers: Vec<WarmContainer>,
+    /// How long to wait for a container's invocation before draining it anyway
+    pub drain_timeout: Duration,
+}
+
+/// The latest rollout of each function. A rollout is identified by its generation, so a
+/// rollout that a later one superseded notices and stops.
+#[derive(Clone)]
+pub struct Rollouts {
+    latest: Arc<DashMap<String, (u64, Rollout)>>,
+    next_generation: Arc<AtomicU64>,
+    tasks: mpsc::UnboundedSender<RolloutTask>,
+}
+
+impl Rollouts {
+    /// The tracker and the tasks it hands out as rollouts begin
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<RolloutTask>) {
+        let (tasks, receiver) = mpsc::unbounded_channel();
+        let rollouts = Self {
+            latest: Arc::new(DashMap::new()),
+            next_generation: Arc::new(AtomicU64::new(0)),
+            tasks,
+        };
+        (rollouts, receiver)
+    }
+
+    /// Record `rollout` as its function's latest and queue the replacement of `containers`.
+    /// A rollout still in progress stops at its next step.
+    pub fn begin(
+        &self,
+        rollout: Rollout,
+        function: Function,
+        containers: Vec<WarmContainer>,
+        drain_timeout: Duration,
+    ) {
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        let name = rollout.function_name.clone();
+        if let Some((_, previous)) = self.latest.insert(name.clone(), (generation, rollout)) {
+            if previous.status == RolloutStatus::InProgress {
+                info!("Rollout of {} superseded by a newer one", name);
+            }
+        }
+        let _ = self.tasks.send(RolloutTask {
+            function,
+            generation,
+            containers,
+            drain_timeout,
+        });
+    }
+
+    /// Change the rollout of `generation`; false once a newer rollout replaced it
+    pub fn update(
+        &self,
+        function_name: &str,
+        generation: u64,
+        f: impl FnOnce(&mut Rollout),
+    ) -> bool {
+        match self.latest.get_mut(function_name) {
+            Some(mut entry) if entry.0 == generation => {
+                f(&mut entry.1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the rollout of `generation` is still its function's latest
+    pub fn is_current(&self, function_name: &str, generation: u64) -> bool {
+        self.latest
+            .get(function_name)
+            .is_some_and(|entry| entry.0 == generation)
+    }
+
+    pub fn get(&self, function_name: &str) -> Option<Rollout> {
+        self.latest.get(function_name).map(|entry| entry.1.clone())
+    }
+
+    pub fn remove(&self, function_name: &str) {
+        self.latest.remove(function_name);
+    }
+}
@@ -0,0 +1,149 @@
+use dashmap::DashMap;
+use lambda_models::{ApiRoute, LambdaError, RouteLimits, RouteSaturation};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+/// Longest a queued request may wait for a slot
+pub const MAX_QUEUE_TIMEOUT_MS: u64 = 60_000;
+
+pub fn validate_route_limits(limits: &RouteLimits) -> Result<(), LambdaError> {
+    if limits.max_in_flight == 0 {
+        return Err(LambdaError::InvalidRequest {
+            reason: "max_in_flight must be at least 1".to_string(),
+        });
+    }
+    if limits.queue_timeout_ms > MAX_QUEUE_TIMEOUT_MS {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!("queue_timeout_ms must be at most {MAX_QUEUE_TIMEOUT_MS}"),
+        });
+    }
+    Ok(())
+}
+
+/// Slots and counters of one route under one set of limits
+struct RouteGate {
+    limits: Option<RouteLimits>,
+    slots: Option<Arc<Semaphore>>,
+    in_flight: AtomicU32,
+    queued: AtomicU32,
+    admitted: AtomicU64,
+    queued_total: AtomicU64,
+    shed: AtomicU64,
+}
+
+impl RouteGate {
+    fn new(limits: Option<RouteLimits>) -> Self {
+        Self {
+            slots: limits
+                .as_ref()
+                .map(|l| Arc::new(Semaphore::new(l.max_in_flight as usize))),
+            limits,
+            in_flight: AtomicU32::new(0),
+            queued: AtomicU32::new(0),
+            admitted: AtomicU64::new(0),
+            queued_total: AtomicU64::new(0),
+            shed: AtomicU64::new(0),
+        }
+    }
+
+    fn shed(&self, route: &ApiRoute, limits: &RouteLimits) -> LambdaError {
+        self.shed.fetch_add(1, Ordering::Relaxed);
+        LambdaError::RouteSaturated {
+            path: route.path.clone(),
+            retry_after_secs: limits.retry_after_secs,
+        }
+    }
+}
+
+/// Held while an admitted request is forwarded; frees its slot when dropped
+pub struct RoutePermit {
+    gate: Arc<RouteGate>,
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for RoutePermit {
+    fn drop(&mut self) {
+        self.gate.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Per-route admission of gateway requests: up to `max_in_flight` are forwarded at once,
+/// up to `max_queued` more wait for a slot, and the rest are shed
+#[derive(Clone, Default)]
+pub struct RouteGates {
+    gates: Arc<DashMap<Uuid, Arc<RouteGate>>>,
+}
+
+impl RouteGates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The route's gate, replaced with fresh counters when its limits changed. Requests
+    /// admitted before the change keep their slot in the old gate until they finish.
+    fn gate(&self, route_id: Uuid, limits: Option<&RouteLimits>) -> Arc<RouteGate> {
+        let mut gate = self
+            .gates
+            .entry(route_id)
+            .or_insert_with(|| Arc::new(RouteGate::new(limits.cloned())));
+        if gate.limits.as_ref() != limits {
+            *gate = Arc::new(RouteGate::new(limits.cloned()));
+        }
+        gate.clone()
+    }
+
+    /// Wait for a slot of `route`, or fail with `RouteSaturated` when its queue is full or
+    /// the wait times out
+    pub async fn admit(&self, route: &ApiRoute) -> Result<RoutePermit, LambdaError> {
+        let gate = self.gate(route.route_id, route.limits.as_ref());
+        let slot = match (&gate.limits, &gate.slots) {
+            (Some(limits), Some(slots)) => match slots.clone().try_acquire_owned() {
+                Ok(slot) => Some(slot),
+                Err(_) => {
+                    if gate.queued.fetch_add(1, Ordering::Relaxed) >= limits.max_queued {
+                        gate.queued.fetch_sub(1, Ordering::Relaxed);
+                        return Err(gate.shed(route, limits));
+                    }
+                    gate.queued_total.fetch_add(1, Ordering::Relaxed);
+                    let waited = tokio::time::timeout(
+                        Duration::from_millis(limits.queue_timeout_ms),
+                        slots.clone().acquire_owned(),
+                    )
+                    .await;
+                    gate.queued.fetch_sub(1, Ordering::Relaxed);
+                    match waited {
+                        Ok(Ok(slot)) => Some(slot),
+                        _ => return Err(gate.shed(route, limits)),
+                    }
+                }
+            },
+            _ => None,
+        };
+        gate.admitted.fetch_add(1, Ordering::Relaxed);
+        gate.in_flight.fetch_add(1, Ordering::Relaxed);
+        Ok(RoutePermit { gate, _slot: slot })
+    }
+
+    pub fn saturation(&self, route_id: Uuid) -> RouteSaturation {
+        let Some(gate) = self.gates.get(&route_id).map(|g| g.clone()) else {
+            return RouteSaturation::default();
+        };
+        RouteSaturation {
+            in_flight: gate.in_flight.load(Ordering::Relaxed),
+            queued: gate.queued.load(Ordering::Relaxed),
+            max_in_flight: gate.limits.as_ref().map(|l| l.max_in_flight),
+            max_queued: gate.limits.as_ref().map(|l| l.max_queued),
+            admitted: gate.admitted.load(Ordering::Relaxed),
+            queued_total: gate.queued_total.load(Ordering::Relaxed),
+            shed: gate.shed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Drop the gate of a deleted route
+    pub fn remove_route(&self, route_id: Uuid) {
+        self.gates.remove(&route_id);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::registry::ControlPlane;
+use lambda_models::{
+    FunctionResponseType, IdentityConfig, InvocationType, InvokeRequest, LambdaError, S3KeyFilter,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// `eventVersion` given to records that arrive without one
+pub const S3_EVENT_VERSION: &str = "2.1";
+pub const MIN_BUCKET_NAME_LEN: usize = 3;
+pub const MAX_BUCKET_NAME_LEN: usize = 63;
+
+/// 3-63 lowercase letters, digits, dots and hyphens, starting and ending with a letter or digit
+pub fn is_valid_bucket_name(name: &str) -> bool {
+    let edge = |c: Option<char>| c.is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    (MIN_BUCKET_NAME_LEN..=MAX_BUCKET_NAME_LEN).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+        && edge(name.chars().next())
+        && edge(name.chars().last())
+}
+
+/// Object keys in notifications are URL-encoded, with `+` for spaces
+pub fn decode_key(key: &str) -> String {
+    percent_encoding::percent_decode_str(&key.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// S3 sources deliver one record per invoke and have no batch item failures to report
+pub fn validate_s3_source(
+    bucket: &str,
+    batch_size: Option<u32>,
+    function_response_types: &[FunctionResponseType],
+) -> Result<(), LambdaError> {
+    if !is_valid_bucket_name(bucket) {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!("Invalid bucket name: {bucket}"),
+        });
+    }
+    if batch_size.is_some_and(|size| size != 1) {
+        return Err(LambdaError::InvalidRequest {
+            reason: "S3 event sources deliver one record at a time; batch_size must be 1"
+                .to_string(),
+        });
+    }
+    if !function_response_types.is_empty() {
+        return Err(LambdaError::InvalidRequest {
+            reason: "function_response_types only applies to queue event sources".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Drop empty prefix and suffix; a filter with neither matches everything
+pub fn normalize_key_filter(filter: Option<S3KeyFilter>) -> Option<S3KeyFilter> {
+    let filter = filter?;
+    let non_empty = |v: Option<String>| v.filter(|v| !v.is_empty());
+    let filter = S3KeyFilter {
+        prefix: non_empty(filter.prefix),
+        suffix: non_empty(filter.suffix),
+    };
+    (filter.prefix.is_some() || filter.suffix.is_some()).then_some(filter)
+}
+
+pub fn key_matches(filter: Option<&S3KeyFilter>, key: &str) -> bool {
+    let Some(filter) = filter else {
+        return true;
+    };
+    filter.prefix.as_deref().is_none_or(|p| key.starts_with(p))
+        && filter.suffix.as_deref().is_none_or(|s| key.ends_with(s))
+}
+
+/// One object event from a notification
+#[derive(Debug, Clone, PartialEq)]
+pub struct S3Notice {
+    pub bucket: String,
+    /// Decoded object key, as filters are written
+    pub key: String,
+    /// The record as Lambda's S3 event carries it
+    pub record: Value,
+}
+
+/// Split a MinIO notification (`{ EventName, Key, Records }`, each record shaped like an S3
+/// event record) into per-object notices. Records without a bucket or key are skipped.
+pub fn parse_notification(
+    identity: &IdentityConfig,
+    payload: &Value,
+) -> Result<Vec<S3Notice>, LambdaError> {
+    let records = payload
+        .get("Records")
+        .and_then(Value::as_array)
+        .ok_or_else(|| LambdaError::InvalidRequest {
+            reason: "S3 notification has no Records".to_string(),
+        })?;
+
+    let mut notices = Vec::new();
+    for record in records {
+        let bucket = record.pointer("/s3/bucket/name").and_then(Value::as_str);
+        let key = record.pointer("/s3/object/key").and_then(Value::as_str);
+        let (Some(bucket), Some(key)) = (bucket, key) else {
+            warn!("Skipping S3 notification record without bucket or key");
+            continue;
+        };
+        notices.push(S3Notice {
+            bucket: bucket.to_string(),
+            key: decode_key(key),
+            record: lambda_record(identity, record, bucket),
+        });
+    }
+    Ok(notices)
+}
+
+/// MinIO names events `s3:ObjectCreated:Put` and tags them `minio:s3`; Lambda receives
+/// `ObjectCreated:Put` from `aws:s3`
+fn lambda_record(identity: &IdentityConfig, record: &Value, bucket: &str) -> Value {
+    let mut record = record.clone();
+    let Some(fields) = record.as_object_mut() else {
+        return record;
+    };
+    if let Some(name) = fields.get("eventName").and_then(Value::as_str) {
+        let name = name.strip_prefix("s3:").unwrap_or(name).to_string();
+        fields.insert("eventName".into(), Value::String(name));
+    }
+    fields.insert("eventSource".into(), json!("aws:s3"));
+    fields
+        .entry("eventVersion")
+        .or_insert_with(|| json!(S3_EVENT_VERSION));
+    if fields
+        .get("awsRegion")
+        .and_then(Value::as_str)
+        .is_none_or(str::is_empty)
+    {
+        fields.insert("awsRegion".into(), json!(identity.region));
+    }
+    if let Some(bucket_fields) = fields
+        .get_mut("s3")
+        .and_then(|s3| s3.get_mut("bucket"))
+        .and_then(Value::as_object_mut)
+    {
+        bucket_fields
+            .entry("arn")
+            .or_insert_with(|| json!(identity.bucket_arn(bucket)));
+    }
+    record
+}
+
+/// Invoke every enabled mapping whose bucket and key filter match, one record per invoke.
+/// Invocations run in the background; returns how many were started.
+pub async fn dispatch_notification(
+    control: &Arc<ControlPlane>,
+    payload: &Value,
+) -> Result<usize, LambdaError> {
+    let notices = parse_notification(control.identity(), payload)?;
+    if notices.is_empty() {
+        return Ok(0);
+    }
+    let mappings = control
+        .list_event_source_mappings(None)
+        .await?
+        .event_source_mappings;
+
+    let mut dispatched = 0;
+    for notice in notices {
+        let arn = control.identity().bucket_arn(&notice.bucket);
+        for mapping in mappings.iter().filter(|m| {
+            m.enabled
+                && m.event_source_arn == arn
+                && key_matches(m.s3_key_filter.as_ref(), &notice.key)
+        }) {
+            let control = control.clone();
+            let mapping_uuid = mapping.uuid;
+            let function_name = mapping.function_name.clone();
+            let event = json!({ "Records": [notice.record.clone()] });
+            tokio::spawn(async move {
+                let result = match control
+                    .invoke_function(InvokeRequest {
+                        function_name,
+                        invocation_type: InvocationType::RequestResponse,
+                        log_type: None,
+                        client_context: None,
+                        payload: Some(event),
+                        qualifier: None,
+                        idempotent: false,
+                        trace: false,
+                    })
+                    .await
+                {
+                    Ok(response) if response.function_error.is_some() => {
+                        "FunctionError".to_string()
+                    }
+                    Ok(_) => "OK".to_string(),
+                    Err(e) => format!("Error: {e}"),
+                };
+                if let Err(e) = control
+                    .record_event_source_result(mapping_uuid, &result)
+                    .await
+                {
+                    debug!("could not record S3 event source result: {}", e);
+                }
+            });
+            dispatched += 1;
+        }
+    }
+    Ok(dispatched)
+}
@@ -0,0 +1,255 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, TimeZone, Timelike, Utc};
+use lambda_models::LambdaError;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// How often the control plane looks for schedules that are due
+pub const SCHEDULE_TICK: Duration = Duration::from_secs(1);
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+const MIN_YEAR: u32 = 1970;
+const MAX_YEAR: u32 = 2199;
+
+/// When a schedule invokes its function, parsed from an EventBridge schedule expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleExpression {
+    /// `rate(value unit)`
+    Rate(ChronoDuration),
+    /// `cron(minutes hours day-of-month month day-of-week year)`
+    Cron(CronExpression),
+}
+
+/// Values each field of a cron expression matches. Exactly one of the day fields is `?`,
+/// which leaves it `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpression {
+    minutes: BTreeSet<u32>,
+    hours: BTreeSet<u32>,
+    days_of_month: Option<BTreeSet<u32>>,
+    months: BTreeSet<u32>,
+    /// 1 is Sunday, as in EventBridge
+    days_of_week: Option<BTreeSet<u32>>,
+    years: BTreeSet<u32>,
+}
+
+impl ScheduleExpression {
+    pub fn parse(expression: &str) -> Result<Self, LambdaError> {
+        let expression = expression.trim();
+        if let Some(body) = inner(expression, "rate") {
+            parse_rate(body).map(Self::Rate)
+        } else if let Some(body) = inner(expression, "cron") {
+            CronExpression::parse(body).map(Self::Cron)
+        } else {
+            Err(invalid(format!(
+                "Schedule expression '{expression}' must be cron(...) or rate(...)"
+            )))
+        }
+    }
+
+    /// First run after `after`. A rate runs every interval from `anchor`, its creation or
+    /// latest run, and a cron expression at its next matching minute. Runs missed while the
+    /// server was down are not made up: the next one is always later than `after`.
+    pub fn next_run(&self, anchor: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Rate(rate) => {
+                let mut next = anchor + *rate;
+                if next <= after {
+                    let missed = (after - anchor).num_seconds() / rate.num_seconds();
+                    next = anchor + *rate * (missed as i32 + 1);
+                }
+                Some(next)
+            }
+            Self::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+fn inner<'a>(expression: &'a str, kind: &str) -> Option<&'a str> {
+    expression
+        .strip_prefix(kind)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+fn parse_rate(body: &str) -> Result<ChronoDuration, LambdaError> {
+    let malformed = || {
+        invalid(format!(
+            "rate({body}) must be rate(value unit) with a positive value and a unit of minutes, hours or days"
+        ))
+    };
+    let mut parts = body.split_whitespace();
+    let (Some(value), Some(unit), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(malformed());
+    };
+    let value: i64 = value
+        .parse()
+        .ok()
+        .filter(|v| *v > 0)
+        .ok_or_else(malformed)?;
+    let unit_secs = match unit {
+        "minute" | "minutes" => 60,
+        "hour" | "hours" => 3600,
+        "day" | "days" => 86400,
+        _ => return Err(malformed()),
+    };
+    value
+        .checked_mul(unit_secs)
+        .filter(|secs| *secs <= 365 * 86400)
+        .map(ChronoDuration::seconds)
+        .ok_or_else(|| invalid(format!("rate({body}) must be at most a year")))
+}
+
+impl CronExpression {
+    /// The six space-separated fields inside `cron(...)`
+    pub fn parse(body: &str) -> Result<Self, LambdaError> {
+        let fields: Vec<&str> = body.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week, years] = fields[..] else {
+            return Err(invalid(format!(
+                "cron({body}) must have six fields: minutes hours day-of-month month day-of-week year"
+            )));
+        };
+        let cron = Self {
+            minutes: parse_field("minutes", minutes, 0, 59, &[])?,
+            hours: parse_field("hours", hours, 0, 23, &[])?,
+            days_of_month: parse_day_field("day-of-month", days_of_month, 1, 31, &[])?,
+            months: parse_field("month", months, 1, 12, &MONTH_NAMES)?,
+            days_of_week: parse_day_field("day-of-week", days_of_week, 1, 7, &DAY_NAMES)?,
+            years: parse_field("year", years, MIN_YEAR, MAX_YEAR, &[])?,
+        };
+        if cron.days_of_month.is_some() == cron.days_of_week.is_some() {
+            return Err(invalid(format!(
+                "cron({body}) must use ? in exactly one of day-of-month and day-of-week"
+            )));
+        }
+        Ok(cron)
+    }
+
+    /// The first minute after `after` that every field matches
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let mut date = start.date_naive();
+        loop {
+            if date.year() as u32 > MAX_YEAR {
+                return None;
+            }
+            if !self.years.contains(&(date.year() as u32)) {
+                date = NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)?;
+                continue;
+            }
+            if !self.months.contains(&date.month()) {
+                date = first_of_next_month(date)?;
+                continue;
+            }
+            if self.day_matches(date) {
+                let (from_hour, from_minute) = if date == start.date_naive() {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+                for &hour in self.hours.range(from_hour..) {
+                    let from = if hour == from_hour { from_minute } else { 0 };
+                    if let Some(&minute) = self.minutes.range(from..).next() {
+                        let time = date.and_hms_opt(hour, minute, 0)?;
+                        return Some(Utc.from_utc_datetime(&time));
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        match (&self.days_of_month, &self.days_of_week) {
+            (Some(days), _) => days.contains(&date.day()),
+            (_, Some(days)) => days.contains(&date.weekday().number_from_sunday()),
+            (None, None) => false,
+        }
+    }
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+}
+
+/// A day field, which may be `?` to leave the day to the other one
+fn parse_day_field(
+    field: &str,
+    text: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<Option<BTreeSet<u32>>, LambdaError> {
+    if text == "?" {
+        return Ok(None);
+    }
+    parse_field(field, text, min, max, names).map(Some)
+}
+
+/// Comma-separated values, `a-b` ranges and `/step` increments of `*`, a value or a range.
+/// `names` spell the values from `min` on.
+fn parse_field(
+    field: &str,
+    text: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<BTreeSet<u32>, LambdaError> {
+    let malformed = |reason: &str| invalid(format!("Cron {field} '{text}': {reason}"));
+    let value = |part: &str| -> Result<u32, LambdaError> {
+        if let Some(i) = names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(part))
+        {
+            return Ok(min + i as u32);
+        }
+        if part.contains(['L', 'W', '#']) {
+            return Err(malformed("L, W and # are not supported"));
+        }
+        part.parse::<u32>()
+            .ok()
+            .filter(|v| (min..=max).contains(v))
+            .ok_or_else(|| malformed(&format!("{part} is not between {min} and {max}")))
+    };
+    let mut values = BTreeSet::new();
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step =
+                    step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(|| {
+                        malformed(&format!("step {step} must be a positive number"))
+                    })?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((from, to)) => (value(from)?, value(to)?),
+                // `5/15` runs from 5 to the end of the field
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let single = value(range)?;
+                    (single, single)
+                }
+            },
+        };
+        if from > to {
+            return Err(malformed(&format!("range {range} runs backwards")));
+        }
+        values.extend((from..=to).step_by(step as usize));
+    }
+    Ok(values)
+}
+
+fn invalid(reason: String) -> LambdaError {
+    LambdaError::InvalidRequest { reason }
+}
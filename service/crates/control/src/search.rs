@@ -0,0 +1,84 @@
+use lambda_models::SearchHit;
+use std::time::Duration;
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+pub const MAX_SEARCH_LIMIT: usize = 100;
+/// Executions older than this are not searched
+pub const RECENT_EXECUTIONS_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// `%query%` for `LIKE ... ESCAPE '\'`, with LIKE wildcards in the query taken literally
+pub fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// ORDER BY fragment putting rows where `column` contains `?1` earliest first, shortest
+/// first, so exact and prefix matches survive the per-kind row limit
+pub fn match_position_order(column: &str) -> String {
+    format!(
+        "CASE instr(lower({column}), lower(?1)) WHEN 0 THEN 1000000 \
+         ELSE instr(lower({column}), lower(?1)) END, length({column})"
+    )
+}
+
+/// 0 for an exact match, 1 for a prefix, 2 for a substring; ASCII case-insensitive
+/// like SQLite's LIKE
+pub fn match_rank(text: &str, query: &str) -> Option<u8> {
+    let text = text.to_ascii_lowercase();
+    let query = query.to_ascii_lowercase();
+    if text == query {
+        Some(0)
+    } else if text.starts_with(&query) {
+        Some(1)
+    } else if text.contains(&query) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn hit_rank(hit: &SearchHit, query: &str) -> u8 {
+    let rank = match hit {
+        SearchHit::Function {
+            function_name,
+            description,
+            ..
+        } => match_rank(function_name, query).or_else(|| {
+            // A description match ranks below any name match
+            description
+                .as_deref()
+                .and_then(|d| match_rank(d, query))
+                .map(|_| 3)
+        }),
+        SearchHit::Route { path, .. } => match_rank(path, query),
+        SearchHit::Secret { name } => match_rank(name, query),
+        SearchHit::Execution { request_id, .. } => match_rank(request_id, query),
+    };
+    rank.unwrap_or(u8::MAX)
+}
+
+fn kind_order(hit: &SearchHit) -> u8 {
+    match hit {
+        SearchHit::Function { .. } => 0,
+        SearchHit::Route { .. } => 1,
+        SearchHit::Secret { .. } => 2,
+        SearchHit::Execution { .. } => 3,
+    }
+}
+
+/// Order candidates best match first (kinds in a fixed order within a rank, candidates
+/// otherwise in the order given) and keep `limit`; returns whether any were dropped
+pub fn rank_hits(mut hits: Vec<SearchHit>, query: &str, limit: usize) -> (Vec<SearchHit>, bool) {
+    hits.sort_by_key(|hit| (hit_rank(hit, query), kind_order(hit)));
+    let truncated = hits.len() > limit;
+    hits.truncate(limit);
+    (hits, truncated)
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use lambda_models::{LambdaError, SecretExportAuditEntry, SecretsFileFormat};
+use sqlx::{Row, SqlitePool};
+use std::collections::{BTreeMap, HashMap};
+
+/// Value exports kept in the audit log
+pub const MAX_EXPORT_AUDIT_ENTRIES: i64 = 1000;
+pub const DEFAULT_EXPORT_LIST_LIMIT: u32 = 50;
+
+/// Name and value of each secret in an import file, in name order. The format is JSON when
+/// the content starts with `{` unless `format` says otherwise.
+pub fn parse_secrets_file(
+    content: &str,
+    format: Option<SecretsFileFormat>,
+) -> Result<BTreeMap<String, String>, LambdaError> {
+    let format = format.unwrap_or(if content.trim_start().starts_with('{') {
+        SecretsFileFormat::Json
+    } else {
+        SecretsFileFormat::Dotenv
+    });
+    let secrets = match format {
+        SecretsFileFormat::Dotenv => parse_dotenv(content)?,
+        SecretsFileFormat::Json => parse_json(content)?,
+    };
+    for name in secrets.keys() {
+        validate_secret_name(name)?;
+    }
+    Ok(secrets)
+}
+
+/// Secret names are what a dotenv file or a shell accepts: `[A-Za-z_][A-Za-z0-9_]*`
+pub fn validate_secret_name(name: &str) -> Result<(), LambdaError> {
+    let mut chars = name.chars();
+    let well_formed = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if well_formed {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "Secret name '{name}' must match [A-Za-z_][A-Za-z0-9_]*"
+        )))
+    }
+}
+
+/// `KEY=value` lines, optionally prefixed with `export`. Blank lines and `#` comments are
+/// skipped. Double-quoted values may span lines and take `\n`, `\"` and `\\` escapes,
+/// single-quoted ones are taken as they are, and unquoted ones end at ` #`.
+pub fn parse_dotenv(content: &str) -> Result<BTreeMap<String, String>, LambdaError> {
+    let mut secrets = BTreeMap::new();
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    let mut lines = content.lines().enumerate().map(|(i, line)| (i + 1, line));
+    while let Some((line_no, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((name, value)) = line.split_once('=') else {
+            return Err(invalid(format!("Line {line_no}: expected NAME=value")));
+        };
+        let name = name.trim().to_string();
+        let value = value.trim();
+        let unclosed = || {
+            invalid(format!(
+                "Line {line_no}: the value of {name} has no closing quote"
+            ))
+        };
+        let after_quote = |trailing: &str| {
+            let trailing = trailing.trim();
+            if trailing.is_empty() || trailing.starts_with('#') {
+                Ok(())
+            } else {
+                Err(invalid(format!(
+                    "Line {line_no}: unexpected {trailing} after the value of {name}"
+                )))
+            }
+        };
+        let value = if let Some(rest) = value.strip_prefix('"') {
+            let mut quoted = rest.to_string();
+            let end = loop {
+                if let Some(end) = closing_double_quote(&quoted) {
+                    break end;
+                }
+                let (_, next) = lines.next().ok_or_else(unclosed)?;
+                quoted.push('\n');
+                quoted.push_str(next);
+            };
+            after_quote(&quoted[end + 1..])?;
+            unescape(&quoted[..end])
+        } else if let Some(rest) = value.strip_prefix('\'') {
+            let end = rest.find('\'').ok_or_else(unclosed)?;
+            after_quote(&rest[end + 1..])?;
+            rest[..end].to_string()
+        } else {
+            value
+                .split_once(" #")
+                .map_or(value, |(value, _)| value)
+                .trim_end()
+                .to_string()
+        };
+        if let Some(first) = first_seen.insert(name.clone(), line_no) {
+            return Err(invalid(format!(
+                "{name} is set twice, on lines {first} and {line_no}"
+            )));
+        }
+        secrets.insert(name, value);
+    }
+    Ok(secrets)
+}
+
+/// Offset of the first unescaped `"` in `quoted`, what follows an opening `"`
+fn closing_double_quote(quoted: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in quoted.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// A JSON object of names to string values
+pub fn parse_json(content: &str) -> Result<BTreeMap<String, String>, LambdaError> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| {
+            invalid(format!(
+                "Expected a JSON object of secret names to values: {e}"
+            ))
+        })?;
+    object
+        .into_iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(value) => Ok((name, value)),
+            _ => Err(invalid(format!("The value of {name} must be a string"))),
+        })
+        .collect()
+}
+
+/// Record an export that included the values of `names`
+pub async fn record_export(
+    pool: &SqlitePool,
+    actor: Option<&str>,
+    names: &[String],
+) -> Result<SecretExportAuditEntry, LambdaError> {
+    let entry = SecretExportAuditEntry {
+        exported_at: Utc::now(),
+        actor: actor.map(str::to_string),
+        names: names.to_vec(),
+    };
+    sqlx::query("INSERT INTO secret_exports (exported_at, actor, names) VALUES (?, ?, ?)")
+        .bind(entry.exported_at)
+        .bind(&entry.actor)
+        .bind(serde_json::to_string(&entry.names).unwrap_or_default())
+        .execute(pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+    sqlx::query(
+        "DELETE FROM secret_exports WHERE id NOT IN \
+         (SELECT id FROM secret_exports ORDER BY id DESC LIMIT ?)",
+    )
+    .bind(MAX_EXPORT_AUDIT_ENTRIES)
+    .execute(pool)
+    .await
+    .map_err(LambdaError::SqlxError)?;
+    Ok(entry)
+}
+
+/// Exports that included values, newest first
+pub async fn list_exports(
+    pool: &SqlitePool,
+    limit: i64,
+) -> Result<Vec<SecretExportAuditEntry>, LambdaError> {
+    let rows = sqlx::query(
+        "SELECT exported_at, actor, names FROM secret_exports ORDER BY id DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(LambdaError::SqlxError)?;
+    Ok(rows
+        .iter()
+        .map(|row| SecretExportAuditEntry {
+            exported_at: row.get::<DateTime<Utc>, _>("exported_at"),
+            actor: row.get("actor"),
+            names: serde_json::from_str(&row.get::<String, _>("names")).unwrap_or_default(),
+        })
+        .collect())
+}
+
+fn invalid(reason: String) -> LambdaError {
+    LambdaError::InvalidRequest { reason }
+}
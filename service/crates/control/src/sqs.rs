@@ -0,0 +1,457 @@
+use chrono::{DateTime, TimeZone, Utc};
+use dashmap::DashMap;
+use lambda_models::{
+    queue_name_from_arn, CreateQueueOptions, LambdaError, Queue, QueueCounts, ReceivedMessage,
+    RedrivePolicy,
+};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub const DEFAULT_VISIBILITY_TIMEOUT_SECS: u32 = 30;
+/// SQS limits, enforced so code tested locally keeps working against the real service
+pub const MAX_VISIBILITY_TIMEOUT_SECS: u32 = 12 * 60 * 60;
+pub const MAX_DELAY_SECONDS: u32 = 900;
+pub const MAX_WAIT_TIME_SECONDS: u32 = 20;
+pub const MAX_RECEIVE_BATCH: u32 = 10;
+pub const MAX_MESSAGE_BYTES: usize = 256 * 1024;
+pub const MAX_QUEUE_NAME_LEN: usize = 80;
+
+/// 1-80 alphanumerics, hyphens and underscores, as SQS requires for standard queues
+pub fn is_valid_queue_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_QUEUE_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn md5_hex(body: &str) -> String {
+    format!("{:x}", md5::compute(body.as_bytes()))
+}
+
+fn invalid(reason: String) -> LambdaError {
+    LambdaError::InvalidRequest { reason }
+}
+
+fn from_millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+fn row_to_queue(row: &sqlx::sqlite::SqliteRow) -> Queue {
+    Queue {
+        name: row.get("name"),
+        visibility_timeout_secs: row.get::<i64, _>("visibility_timeout_secs") as u32,
+        delay_seconds: row.get::<i64, _>("delay_seconds") as u32,
+        redrive_policy: row
+            .get::<Option<String>, _>("redrive_policy")
+            .and_then(|p| serde_json::from_str(&p).ok()),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Embedded SQS-style queues stored in SQLite: at-least-once delivery, visibility
+/// timeouts, delays, long polling and redrive to a dead-letter queue
+#[derive(Clone)]
+pub struct QueueService {
+    pool: SqlitePool,
+    /// Wakes long-polling receivers when a message is sent to their queue
+    arrivals: Arc<DashMap<String, Arc<Notify>>>,
+}
+
+impl QueueService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            arrivals: Arc::new(DashMap::new()),
+        }
+    }
+
+    fn arrivals(&self, queue_name: &str) -> Arc<Notify> {
+        self.arrivals
+            .entry(queue_name.to_string())
+            .or_insert_with(|| Arc::new(Notify::new()))
+            .clone()
+    }
+
+    async fn validate_redrive(
+        &self,
+        queue_name: &str,
+        policy: &RedrivePolicy,
+    ) -> Result<(), LambdaError> {
+        let target = queue_name_from_arn(&policy.dead_letter_target_arn).ok_or_else(|| {
+            invalid(format!(
+                "deadLetterTargetArn is not a queue ARN: {}",
+                policy.dead_letter_target_arn
+            ))
+        })?;
+        if target == queue_name {
+            return Err(invalid(
+                "A queue cannot be its own dead-letter queue".into(),
+            ));
+        }
+        if !(1..=1000).contains(&policy.max_receive_count) {
+            return Err(invalid("maxReceiveCount must be between 1 and 1000".into()));
+        }
+        self.get_queue(target).await?;
+        Ok(())
+    }
+
+    /// Create a queue; creating one that already exists returns it unchanged
+    pub async fn create_queue(
+        &self,
+        name: &str,
+        options: CreateQueueOptions,
+    ) -> Result<Queue, LambdaError> {
+        if !is_valid_queue_name(name) {
+            return Err(invalid(format!(
+                "Queue names are 1-{MAX_QUEUE_NAME_LEN} alphanumerics, hyphens or underscores: {name}"
+            )));
+        }
+        if let Ok(existing) = self.get_queue(name).await {
+            return Ok(existing);
+        }
+        let visibility_timeout_secs = options
+            .visibility_timeout_secs
+            .unwrap_or(DEFAULT_VISIBILITY_TIMEOUT_SECS);
+        if visibility_timeout_secs > MAX_VISIBILITY_TIMEOUT_SECS {
+            return Err(invalid(format!(
+                "VisibilityTimeout must be at most {MAX_VISIBILITY_TIMEOUT_SECS} seconds"
+            )));
+        }
+        let delay_seconds = options.delay_seconds.unwrap_or(0);
+        if delay_seconds > MAX_DELAY_SECONDS {
+            return Err(invalid(format!(
+                "DelaySeconds must be at most {MAX_DELAY_SECONDS}"
+            )));
+        }
+        if let Some(policy) = &options.redrive_policy {
+            self.validate_redrive(name, policy).await?;
+        }
+
+        let queue = Queue {
+            name: name.to_string(),
+            visibility_timeout_secs,
+            delay_seconds,
+            redrive_policy: options.redrive_policy,
+            created_at: Utc::now(),
+        };
+        sqlx::query(
+            "INSERT INTO queues(name, visibility_timeout_secs, delay_seconds, redrive_policy, created_at) VALUES(?, ?, ?, ?, ?)",
+        )
+        .bind(&queue.name)
+        .bind(queue.visibility_timeout_secs as i64)
+        .bind(queue.delay_seconds as i64)
+        .bind(
+            queue
+                .redrive_policy
+                .as_ref()
+                .map(|p| serde_json::to_string(p).unwrap_or_default()),
+        )
+        .bind(queue.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        info!("Created queue {}", queue.name);
+        Ok(queue)
+    }
+
+    pub async fn get_queue(&self, name: &str) -> Result<Queue, LambdaError> {
+        let row = sqlx::query("SELECT * FROM queues WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?
+            .ok_or_else(|| LambdaError::QueueNotFound {
+                queue_name: name.to_string(),
+            })?;
+        Ok(row_to_queue(&row))
+    }
+
+    pub async fn list_queues(&self, prefix: Option<&str>) -> Result<Vec<Queue>, LambdaError> {
+        let rows = sqlx::query(
+            "SELECT * FROM queues WHERE substr(name, 1, length(?1)) = ?1 ORDER BY name",
+        )
+        .bind(prefix.unwrap_or_default())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(rows.iter().map(row_to_queue).collect())
+    }
+
+    /// Delete a queue with its messages and event source mappings
+    pub async fn delete_queue(&self, name: &str) -> Result<(), LambdaError> {
+        let result = sqlx::query("DELETE FROM queues WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        if result.rows_affected() == 0 {
+            return Err(LambdaError::QueueNotFound {
+                queue_name: name.to_string(),
+            });
+        }
+        self.arrivals.remove(name);
+        info!("Deleted queue {}", name);
+        Ok(())
+    }
+
+    /// Drop every message in the queue
+    pub async fn purge_queue(&self, name: &str) -> Result<(), LambdaError> {
+        self.get_queue(name).await?;
+        sqlx::query("DELETE FROM queue_messages WHERE queue_name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Enqueue a message; returns its id and the MD5 of the body
+    pub async fn send_message(
+        &self,
+        queue_name: &str,
+        body: &str,
+        delay_seconds: Option<u32>,
+    ) -> Result<(String, String), LambdaError> {
+        let queue = self.get_queue(queue_name).await?;
+        if body.is_empty() || body.len() > MAX_MESSAGE_BYTES {
+            return Err(invalid(format!(
+                "Message body must be 1 to {MAX_MESSAGE_BYTES} bytes"
+            )));
+        }
+        let delay = delay_seconds.unwrap_or(queue.delay_seconds);
+        if delay > MAX_DELAY_SECONDS {
+            return Err(invalid(format!(
+                "DelaySeconds must be at most {MAX_DELAY_SECONDS}"
+            )));
+        }
+
+        let message_id = Uuid::new_v4().to_string();
+        let md5_of_body = md5_hex(body);
+        let now_ms = Utc::now().timestamp_millis();
+        sqlx::query(
+            r#"INSERT INTO queue_messages(message_id, queue_name, body, md5_of_body, sent_at_ms, visible_at_ms)
+               VALUES(?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(&message_id)
+        .bind(queue_name)
+        .bind(body)
+        .bind(&md5_of_body)
+        .bind(now_ms)
+        .bind(now_ms + delay as i64 * 1000)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        self.arrivals(queue_name).notify_waiters();
+        Ok((message_id, md5_of_body))
+    }
+
+    /// Receive up to `max_messages` visible messages, hiding them for `visibility_timeout`
+    /// (the queue default when None). Waits up to `wait` for a message when none is ready.
+    pub async fn receive_messages(
+        &self,
+        queue_name: &str,
+        max_messages: u32,
+        visibility_timeout: Option<u32>,
+        wait: Duration,
+    ) -> Result<Vec<ReceivedMessage>, LambdaError> {
+        let queue = self.get_queue(queue_name).await?;
+        if !(1..=MAX_RECEIVE_BATCH).contains(&max_messages) {
+            return Err(invalid(format!(
+                "MaxNumberOfMessages must be between 1 and {MAX_RECEIVE_BATCH}"
+            )));
+        }
+        let visibility = visibility_timeout.unwrap_or(queue.visibility_timeout_secs);
+        if visibility > MAX_VISIBILITY_TIMEOUT_SECS {
+            return Err(invalid(format!(
+                "VisibilityTimeout must be at most {MAX_VISIBILITY_TIMEOUT_SECS} seconds"
+            )));
+        }
+        let wait = wait.min(Duration::from_secs(MAX_WAIT_TIME_SECONDS as u64));
+        let deadline = tokio::time::Instant::now() + wait;
+        let arrivals = self.arrivals(queue_name);
+
+        loop {
+            // Register for wakeups before looking, so a send in between is not missed
+            let notified = arrivals.notified();
+            if let Some(policy) = &queue.redrive_policy {
+                self.redrive(queue_name, policy).await?;
+            }
+            let messages = self
+                .take_visible(queue_name, max_messages, visibility)
+                .await?;
+            let now = tokio::time::Instant::now();
+            if !messages.is_empty() || now >= deadline {
+                return Ok(messages);
+            }
+            // Delayed and timed-out messages become visible without a send, so re-check
+            // at least once a second
+            let nap = (deadline - now).min(Duration::from_secs(1));
+            tokio::select! {
+                _ = notified => {}
+                _ = tokio::time::sleep(nap) => {}
+            }
+        }
+    }
+
+    /// Claim visible messages, oldest first, in a single statement so concurrent receivers
+    /// never get the same message
+    async fn take_visible(
+        &self,
+        queue_name: &str,
+        max_messages: u32,
+        visibility_secs: u32,
+    ) -> Result<Vec<ReceivedMessage>, LambdaError> {
+        let now_ms = Utc::now().timestamp_millis();
+        let rows = sqlx::query(
+            r#"UPDATE queue_messages
+               SET visible_at_ms = ?1 + ?2,
+                   receive_count = receive_count + 1,
+                   first_received_at_ms = COALESCE(first_received_at_ms, ?1),
+                   receipt_handle = lower(hex(randomblob(16))) || '-' || message_id
+               WHERE message_id IN (
+                   SELECT message_id FROM queue_messages
+                   WHERE queue_name = ?3 AND visible_at_ms <= ?1
+                   ORDER BY sent_at_ms LIMIT ?4
+               )
+               RETURNING message_id, receipt_handle, body, md5_of_body, sent_at_ms,
+                         receive_count, first_received_at_ms"#,
+        )
+        .bind(now_ms)
+        .bind(visibility_secs as i64 * 1000)
+        .bind(queue_name)
+        .bind(max_messages as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let mut messages: Vec<ReceivedMessage> = rows
+            .iter()
+            .map(|row| ReceivedMessage {
+                message_id: row.get("message_id"),
+                receipt_handle: row.get("receipt_handle"),
+                body: row.get("body"),
+                md5_of_body: row.get("md5_of_body"),
+                sent_at: from_millis(row.get("sent_at_ms")),
+                receive_count: row.get::<i64, _>("receive_count") as u32,
+                first_received_at: from_millis(row.get("first_received_at_ms")),
+            })
+            .collect();
+        // RETURNING order is unspecified
+        messages.sort_by_key(|m| m.sent_at);
+        Ok(messages)
+    }
+
+    /// Move visible messages that reached the policy's receive count to the dead-letter queue
+    async fn redrive(&self, queue_name: &str, policy: &RedrivePolicy) -> Result<(), LambdaError> {
+        let Some(target) = queue_name_from_arn(&policy.dead_letter_target_arn) else {
+            return Ok(());
+        };
+        if self.get_queue(target).await.is_err() {
+            warn!(
+                "Dead-letter queue {} of {} no longer exists; not redriving",
+                target, queue_name
+            );
+            return Ok(());
+        }
+        let now_ms = Utc::now().timestamp_millis();
+        let moved = sqlx::query(
+            r#"UPDATE queue_messages
+               SET queue_name = ?1, visible_at_ms = ?2, receive_count = 0,
+                   first_received_at_ms = NULL, receipt_handle = NULL
+               WHERE queue_name = ?3 AND visible_at_ms <= ?2 AND receive_count >= ?4"#,
+        )
+        .bind(target)
+        .bind(now_ms)
+        .bind(queue_name)
+        .bind(policy.max_receive_count as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?
+        .rows_affected();
+        if moved > 0 {
+            info!(
+                "Moved {} message(s) from {} to dead-letter queue {}",
+                moved, queue_name, target
+            );
+            self.arrivals(target).notify_waiters();
+        }
+        Ok(())
+    }
+
+    /// Delete a received message. Like SQS, a handle from an earlier receive or of a
+    /// message that is already gone is not an error.
+    pub async fn delete_message(
+        &self,
+        queue_name: &str,
+        receipt_handle: &str,
+    ) -> Result<(), LambdaError> {
+        self.get_queue(queue_name).await?;
+        sqlx::query("DELETE FROM queue_messages WHERE queue_name = ? AND receipt_handle = ?")
+            .bind(queue_name)
+            .bind(receipt_handle)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+
+    /// Make a received message visible again after `visibility_timeout` seconds
+    pub async fn change_message_visibility(
+        &self,
+        queue_name: &str,
+        receipt_handle: &str,
+        visibility_timeout: u32,
+    ) -> Result<(), LambdaError> {
+        self.get_queue(queue_name).await?;
+        if visibility_timeout > MAX_VISIBILITY_TIMEOUT_SECS {
+            return Err(invalid(format!(
+                "VisibilityTimeout must be at most {MAX_VISIBILITY_TIMEOUT_SECS} seconds"
+            )));
+        }
+        let updated = sqlx::query(
+            "UPDATE queue_messages SET visible_at_ms = ? WHERE queue_name = ? AND receipt_handle = ?",
+        )
+        .bind(Utc::now().timestamp_millis() + visibility_timeout as i64 * 1000)
+        .bind(queue_name)
+        .bind(receipt_handle)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        if updated.rows_affected() == 0 {
+            return Err(invalid(format!(
+                "Receipt handle is invalid or expired: {receipt_handle}"
+            )));
+        }
+        if visibility_timeout == 0 {
+            self.arrivals(queue_name).notify_waiters();
+        }
+        Ok(())
+    }
+
+    pub async fn queue_counts(&self, queue_name: &str) -> Result<QueueCounts, LambdaError> {
+        self.get_queue(queue_name).await?;
+        let row = sqlx::query(
+            r#"SELECT
+                   COALESCE(SUM(visible_at_ms <= ?1), 0) AS visible,
+                   COALESCE(SUM(visible_at_ms > ?1 AND receive_count > 0), 0) AS in_flight,
+                   COALESCE(SUM(visible_at_ms > ?1 AND receive_count = 0), 0) AS delayed
+               FROM queue_messages WHERE queue_name = ?2"#,
+        )
+        .bind(Utc::now().timestamp_millis())
+        .bind(queue_name)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(QueueCounts {
+            visible: row.get::<i64, _>("visible") as u64,
+            in_flight: row.get::<i64, _>("in_flight") as u64,
+            delayed: row.get::<i64, _>("delayed") as u64,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+use lambda_models::{
+    CreateFunctionRequest, Function, FunctionCode, LambdaError, StaticFunctionConfig,
+    UpdateFunctionConfigurationRequest,
+};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Recorded as the author of configuration changes made to match the config file
+pub const STATIC_FUNCTION_ACTOR: &str = "config file";
+
+/// What startup did to bring a declared function in line with the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaticFunctionOutcome {
+    Created,
+    /// Deleted and created again because its runtime or code changed
+    Recreated,
+    /// Configuration updated in place
+    Updated,
+    Unchanged,
+}
+
+/// Zip of a declared function's code: a `.zip` file as-is, or the files of a directory,
+/// narrowed down to `workspace_package` when it is set
+pub fn load_static_code(spec: &StaticFunctionConfig) -> Result<Vec<u8>, LambdaError> {
+    let path = Path::new(&spec.code);
+    let metadata = std::fs::metadata(path).map_err(|e| LambdaError::InvalidRequest {
+        reason: format!("Code of function {} at {}: {e}", spec.name, spec.code),
+    })?;
+    if metadata.is_dir() {
+        return match &spec.workspace_package {
+            Some(package) => lambda_packaging::bundle_workspace_directory(path, package),
+            None => lambda_packaging::zip_directory(path),
+        };
+    }
+    let zip_data = std::fs::read(path).map_err(|e| LambdaError::InvalidRequest {
+        reason: format!("Code of function {} at {}: {e}", spec.name, spec.code),
+    })?;
+    match &spec.workspace_package {
+        Some(package) => lambda_packaging::bundle_workspace(&zip_data, package),
+        None => Ok(zip_data),
+    }
+}
+
+pub fn code_sha256(zip_data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(zip_data);
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn static_create_request(
+    spec: &StaticFunctionConfig,
+    zip_data: &[u8],
+) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        function_name: spec.name.clone(),
+        runtime: spec.runtime.clone(),
+        role: spec.role.clone(),
+        handler: spec.handler.clone(),
+        code: FunctionCode {
+            zip_file: Some(base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                zip_data,
+            )),
+            s3_bucket: None,
+            s3_key: None,
+            s3_object_version: None,
+            workspace_package: None,
+            image_uri: None,
+        },
+        description: spec.description.clone(),
+        timeout: spec.timeout,
+        memory_size: spec.memory_size,
+        environment: spec.environment.clone(),
+        publish: None,
+        package_type: None,
+        tags: None,
+    }
+}
+
+/// Configuration update bringing `function` in line with `spec`, or `None` when it already
+/// matches. Settings the config file leaves out are not touched.
+pub fn static_config_update(
+    spec: &StaticFunctionConfig,
+    function: &Function,
+) -> Option<UpdateFunctionConfigurationRequest> {
+    fn changed<T: PartialEq + Clone>(wanted: Option<&T>, current: &T) -> Option<T> {
+        wanted.filter(|w| *w != current).cloned()
+    }
+    let request = UpdateFunctionConfigurationRequest {
+        role: spec
+            .role
+            .clone()
+            .filter(|r| function.role.as_ref() != Some(r)),
+        handler: changed(Some(&spec.handler), &function.handler),
+        description: spec
+            .description
+            .clone()
+            .filter(|d| function.description.as_ref() != Some(d)),
+        timeout: changed(spec.timeout.as_ref(), &function.timeout),
+        memory_size: changed(spec.memory_size.as_ref(), &function.memory_size),
+        environment: changed(spec.environment.as_ref(), &function.environment),
+    };
+    let unchanged = request.role.is_none()
+        && request.handler.is_none()
+        && request.description.is_none()
+        && request.timeout.is_none()
+        && request.memory_size.is_none()
+        && request.environment.is_none();
+    (!unchanged).then_some(request)
+}
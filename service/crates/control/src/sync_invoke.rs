@@ -0,0 +1,106 @@
+use crate::jobs::JobStore;
+use crate::pending::StreamPart;
+use lambda_models::{InvokeResponse, LambdaError};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tracing::error;
+
+type InvokeResult = Result<InvokeResponse, LambdaError>;
+
+/// A synchronous invoke after its caller waited a limited time for it
+pub enum BoundedInvoke {
+    Finished(InvokeResult),
+    Pending(PendingInvoke),
+}
+
+/// A synchronous invocation still running once its caller's wait ran out. Its result comes
+/// through [`Self::result`] while anyone awaits it; once detached it is stored as a job under
+/// the request id instead, read back at `/api/executions/{id}/result`
+pub struct PendingInvoke {
+    pub request_id: String,
+    function_name: String,
+    result: oneshot::Receiver<InvokeResult>,
+    /// Taken by the invocation to hand its result over, or by [`Self::detach`]
+    sender: Arc<Mutex<Option<oneshot::Sender<InvokeResult>>>>,
+}
+
+impl PendingInvoke {
+    /// Wait for the invocation to finish; cancelling this keeps the result for the next call
+    pub async fn result(&mut self) -> InvokeResult {
+        (&mut self.result)
+            .await
+            .unwrap_or_else(|_| Err(no_result(&self.request_id)))
+    }
+
+    /// Stop waiting and have the invocation store its result as a job. The result when it
+    /// arrived in the meantime
+    pub async fn detach(mut self, jobs: &JobStore) -> Result<Option<InvokeResult>, LambdaError> {
+        // Holding the sender's lock keeps the invocation from finishing halfway through
+        let mut sender = self.sender.lock().await;
+        if let Ok(result) = self.result.try_recv() {
+            return Ok(Some(result));
+        }
+        jobs.create_with_id(&self.request_id, &self.function_name)
+            .await?;
+        sender.take();
+        Ok(None)
+    }
+}
+
+/// A synchronous invoke whose function may stream its response. A runtime that streams
+/// sends [`StreamPart::Start`] and then the chunks through `parts`, which closes once the
+/// invocation finished; one that posts its response whole only produces `result`
+pub struct ResponseStream {
+    pub request_id: String,
+    pub parts: mpsc::Receiver<StreamPart>,
+    pub result: JoinHandle<InvokeResult>,
+}
+
+/// Run `invocation` in the background and wait for it at most `wait`
+pub async fn run_bounded(
+    jobs: JobStore,
+    request_id: String,
+    function_name: String,
+    wait: Duration,
+    invocation: impl Future<Output = InvokeResult> + Send + 'static,
+) -> BoundedInvoke {
+    let (tx, mut rx) = oneshot::channel();
+    let sender = Arc::new(Mutex::new(Some(tx)));
+    let shared = sender.clone();
+    let job_id = request_id.clone();
+    tokio::spawn(async move {
+        let result = invocation.await;
+        let result = match shared.lock().await.take() {
+            Some(tx) => match tx.send(result) {
+                Ok(()) => return,
+                // The caller went away while it still waited
+                Err(result) => result,
+            },
+            None => result,
+        };
+        if let Err(e) = jobs.complete(&job_id, result).await {
+            error!("Failed to store result of invocation {}: {}", job_id, e);
+        }
+    });
+    match tokio::time::timeout(wait, &mut rx).await {
+        Ok(result) => {
+            BoundedInvoke::Finished(result.unwrap_or_else(|_| Err(no_result(&request_id))))
+        }
+        Err(_elapsed) => BoundedInvoke::Pending(PendingInvoke {
+            request_id,
+            function_name,
+            result: rx,
+            sender,
+        }),
+    }
+}
+
+/// The invocation's task went away, which only a panic does
+fn no_result(request_id: &str) -> LambdaError {
+    LambdaError::InternalError {
+        reason: format!("Invocation {request_id} ended without a result"),
+    }
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use lambda_models::{
+    InvokeResponse, LambdaError, SyntheticCheck, SyntheticCheckRun, SyntheticCheckStats,
+};
+use std::time::Duration;
+
+/// Checks run at most this often
+pub const MIN_CHECK_INTERVAL_SECS: u64 = 10;
+/// Checks run at least this often
+pub const MAX_CHECK_INTERVAL_SECS: u64 = 7 * 86400;
+/// How often the control plane looks for checks that are due
+pub const CHECK_TICK: Duration = Duration::from_secs(5);
+/// Runs older than this are dropped, which also bounds the stats window
+pub const RUN_RETENTION: Duration = Duration::from_secs(30 * 86400);
+/// Runs listed with the stats
+pub const RECENT_RUNS_LIMIT: usize = 20;
+
+pub fn validate_synthetic_check(check: &SyntheticCheck) -> Result<(), LambdaError> {
+    if !(MIN_CHECK_INTERVAL_SECS..=MAX_CHECK_INTERVAL_SECS).contains(&check.interval_secs) {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!(
+                "interval_secs must be between {MIN_CHECK_INTERVAL_SECS} and {MAX_CHECK_INTERVAL_SECS}"
+            ),
+        });
+    }
+    if !(check.slo_target > 0.0 && check.slo_target <= 100.0) {
+        return Err(LambdaError::InvalidRequest {
+            reason: "slo_target must be a percentage above 0 and at most 100".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Whether a check last run at `last_run` is due again at `now`. A check stored with an
+/// interval too long to add to a date never comes due
+pub fn check_due(
+    check: &SyntheticCheck,
+    last_run: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some(last_run) = last_run else {
+        return true;
+    };
+    i64::try_from(check.interval_secs)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .and_then(|interval| last_run.checked_add_signed(interval))
+        .is_some_and(|next_run| next_run <= now)
+}
+
+/// Whether `actual` contains `expected`: objects match when each key of `expected` matches
+/// the same key of `actual`, anything else must be equal
+pub fn matches_expectation(expected: &serde_json::Value, actual: &serde_json::Value) -> bool {
+    match (expected, actual) {
+        (serde_json::Value::Object(expected), serde_json::Value::Object(actual)) => {
+            expected.iter().all(|(key, value)| {
+                actual
+                    .get(key)
+                    .is_some_and(|actual| matches_expectation(value, actual))
+            })
+        }
+        _ => expected == actual,
+    }
+}
+
+/// Why a run of `check` failed, or `None` when it passed
+pub fn evaluate_run(
+    check: &SyntheticCheck,
+    outcome: Result<&InvokeResponse, &LambdaError>,
+    latency_ms: u64,
+) -> Option<String> {
+    let response = match outcome {
+        Ok(response) => response,
+        Err(e) => return Some(format!("Invoke failed: {e}")),
+    };
+    let payload = response.payload.clone().unwrap_or(serde_json::Value::Null);
+    if response.function_error.is_some() {
+        let message = payload
+            .get("errorMessage")
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| payload.to_string());
+        return Some(format!("Function error: {message}"));
+    }
+    if let Some(max_latency_ms) = check.max_latency_ms {
+        if latency_ms > max_latency_ms {
+            return Some(format!(
+                "Took {latency_ms} ms, more than the allowed {max_latency_ms} ms"
+            ));
+        }
+    }
+    match &check.expect {
+        Some(expected) if !matches_expectation(expected, &payload) => Some(format!(
+            "Result {payload} does not match the expected {expected}"
+        )),
+        _ => None,
+    }
+}
+
+/// Uptime, SLO and latency figures of the runs in a window; `runs` is newest first
+pub fn summarize_runs(
+    function_name: &str,
+    window: Duration,
+    slo_target: f64,
+    runs: Vec<SyntheticCheckRun>,
+) -> SyntheticCheckStats {
+    let total = runs.len() as u64;
+    let successes = runs.iter().filter(|r| r.success).count() as u64;
+    let uptime_percent = (total > 0).then(|| successes as f64 * 100.0 / total as f64);
+    let allowed_failures = total as f64 * (100.0 - slo_target) / 100.0;
+
+    let mut latencies: Vec<u64> = runs.iter().map(|r| r.latency_ms).collect();
+    latencies.sort_unstable();
+    let percentile = |p: f64| -> Option<u64> {
+        if latencies.is_empty() {
+            return None;
+        }
+        let rank = ((p / 100.0) * latencies.len() as f64).ceil() as usize;
+        latencies.get(rank.clamp(1, latencies.len()) - 1).copied()
+    };
+
+    SyntheticCheckStats {
+        function_name: function_name.to_string(),
+        window_seconds: window.as_secs(),
+        runs: total,
+        successes,
+        uptime_percent,
+        slo_target,
+        slo_met: uptime_percent.map(|uptime| uptime >= slo_target),
+        error_budget_remaining: (total > 0).then(|| allowed_failures - (total - successes) as f64),
+        p50_latency_ms: percentile(50.0),
+        p95_latency_ms: percentile(95.0),
+        recent_runs: runs.into_iter().take(RECENT_RUNS_LIMIT).collect(),
+    }
+}
@@ -0,0 +1,28 @@
+use crate::features::FeatureFlags;
+use lambda_models::{Config, FeatureFlag};
+use std::collections::BTreeMap;
+
+/// Commit the server was built from, recorded by builds that set `LAMBDAH_GIT_COMMIT`
+pub const BUILD_COMMIT: Option<&str> = option_env!("LAMBDAH_GIT_COMMIT");
+
+/// Optional subsystems that configuration turns on or off, and feature flags as they are
+/// now, by name
+pub fn enabled_features(config: &Config, flags: &FeatureFlags) -> BTreeMap<String, bool> {
+    let flags = FeatureFlag::ALL
+        .iter()
+        .map(|flag| (flag.name(), flags.is_enabled(*flag)));
+    [
+        ("access_log", config.access_log.enabled),
+        ("archive", config.archive.idle_days.is_some()),
+        ("aws_emulation", config.aws_emulation.enabled),
+        ("exec", config.exec.enabled),
+        ("replication", config.replication.enabled),
+        ("s3_notifications", config.s3_notifications.enabled),
+        ("warm_pool_persist", config.warm_pool.persist),
+        ("warmup", config.warmup.enabled),
+    ]
+    .into_iter()
+    .chain(flags)
+    .map(|(name, enabled)| (name.to_string(), enabled))
+    .collect()
+}
@@ -0,0 +1,156 @@
+//! Mapping templates of API routes, so clients with a fixed request or response format can
+//! call functions written for a cleaner event shape.
+//!
+//! A template is a JSON document. Its strings may refer to the context with `{{ path }}`,
+//! where `path` is a dot-separated list of object keys and array indexes, e.g.
+//! `{{ body.items.0.sku }}` or `{{ headers.x-user-id }}`. A string that is nothing but one
+//! reference becomes the referenced value, JSON type and all (`null` when it is missing);
+//! references inside longer strings are interpolated as text. A reference may end with a
+//! filter: `| json` turns the value into JSON text, `| parse` reads JSON text into a value.
+
+use serde_json::{json, Map, Value};
+
+/// Filters a reference may end with
+const FILTERS: &[&str] = &["json", "parse"];
+
+enum Part<'a> {
+    Text(&'a str),
+    Reference {
+        path: Vec<&'a str>,
+        filter: Option<&'a str>,
+    },
+}
+
+/// Check that every string of `template` parses
+pub fn validate_template(template: &Value) -> Result<(), String> {
+    match template {
+        Value::String(s) => parse(s).map(|_| ()),
+        Value::Array(items) => items.iter().try_for_each(validate_template),
+        Value::Object(fields) => fields.iter().try_for_each(|(key, value)| {
+            validate_template(value).map_err(|e| format!("{key}: {e}"))
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Render `template` against `context`. Templates are validated when they are set, so a
+/// string that does not parse is kept as it is
+pub fn render(template: &Value, context: &Value) -> Value {
+    match template {
+        Value::String(s) => render_string(s, context),
+        Value::Array(items) => Value::Array(items.iter().map(|i| render(i, context)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), render(value, context)))
+                .collect::<Map<_, _>>(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Context of request templates, from the proxy event the gateway built. `body` is the
+/// request body read as JSON when it is JSON, and as text otherwise
+pub fn request_context(event: &Value) -> Value {
+    let raw_body = event.get("body").cloned().unwrap_or(Value::Null);
+    let body = raw_body
+        .as_str()
+        .and_then(|b| serde_json::from_str(b).ok())
+        .unwrap_or_else(|| raw_body.clone());
+    json!({
+        "method": event.get("httpMethod"),
+        "path": event.get("path"),
+        "headers": event.get("headers"),
+        "query": event.get("queryStringParameters"),
+        "body": body,
+        "rawBody": raw_body,
+        "event": event,
+    })
+}
+
+/// Context of response templates: `payload` is what the function returned
+pub fn response_context(payload: Option<&Value>) -> Value {
+    json!({ "payload": payload })
+}
+
+fn render_string(s: &str, context: &Value) -> Value {
+    let Ok(parts) = parse(s) else {
+        return Value::String(s.to_string());
+    };
+    if let [Part::Reference { path, filter }] = parts.as_slice() {
+        return resolve(path, *filter, context);
+    }
+    let mut out = String::new();
+    for part in &parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Reference { path, filter } => match resolve(path, *filter, context) {
+                Value::Null => {}
+                Value::String(value) => out.push_str(&value),
+                value => out.push_str(&value.to_string()),
+            },
+        }
+    }
+    Value::String(out)
+}
+
+fn resolve(path: &[&str], filter: Option<&str>, context: &Value) -> Value {
+    let mut value = context;
+    for segment in path {
+        let next = match value {
+            Value::Object(fields) => fields.get(*segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(next) => value = next,
+            None => return Value::Null,
+        }
+    }
+    match filter {
+        Some("json") => Value::String(value.to_string()),
+        Some("parse") => value
+            .as_str()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or(Value::Null),
+        _ => value.clone(),
+    }
+}
+
+fn parse(s: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| format!("unclosed {{{{ in {s:?}"))?;
+        parts.push(parse_reference(after[..end].trim())?);
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+fn parse_reference(expression: &str) -> Result<Part<'_>, String> {
+    let (path, filter) = match expression.split_once('|') {
+        Some((path, filter)) => (path.trim(), Some(filter.trim())),
+        None => (expression, None),
+    };
+    if let Some(filter) = filter.filter(|f| !FILTERS.contains(f)) {
+        return Err(format!(
+            "unknown filter {filter:?} (one of {})",
+            FILTERS.join(", ")
+        ));
+    }
+    let path: Vec<&str> = path.split('.').collect();
+    if path.iter().any(|segment| segment.is_empty()) {
+        return Err(format!("invalid reference {{{{ {expression} }}}}"));
+    }
+    Ok(Part::Reference { path, filter })
+}
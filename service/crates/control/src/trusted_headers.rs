@@ -0,0 +1,46 @@
+use lambda_models::TrustedHeadersConfig;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// The caller identity an authenticating proxy asserted, as the `requestContext.authorizer`
+/// of a gateway event: `principalId` and `user` from the user header, `groups` split on
+/// commas and `email` when present. `None` when trusted headers are off, the request did not
+/// come from a trusted proxy, or it carries no user header.
+pub fn authorizer(
+    config: &TrustedHeadersConfig,
+    headers: &HashMap<String, String>,
+    peer: Option<IpAddr>,
+) -> Option<Value> {
+    if !config.enabled || !config.trusts(peer) {
+        return None;
+    }
+    let header = |name: &str| {
+        headers
+            .get(&name.to_ascii_lowercase())
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let user = header(&config.user_header)?;
+    let groups: Vec<&str> = config
+        .groups_header
+        .as_deref()
+        .and_then(header)
+        .map(|groups| {
+            groups
+                .split(',')
+                .map(str::trim)
+                .filter(|g| !g.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut authorizer = json!({
+        "principalId": user,
+        "user": user,
+        "groups": groups,
+    });
+    if let Some(email) = config.email_header.as_deref().and_then(header) {
+        authorizer["email"] = json!(email);
+    }
+    Some(authorizer)
+}
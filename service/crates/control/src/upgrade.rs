@@ -0,0 +1,252 @@
+use crate::migrations;
+use lambda_models::{LambdaError, UpgradeConfig};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// The latest release as the feed describes it
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+fn upgrade_error(reason: impl Into<String>) -> LambdaError {
+    LambdaError::UpgradeFailed {
+        reason: reason.into(),
+    }
+}
+
+/// `major.minor.patch` of a version or tag such as `v0.4.0`; pre-release and build suffixes
+/// are ignored
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// Whether `tag` names a later release than `current`
+pub fn is_newer(current: &str, tag: &str) -> bool {
+    matches!((parse_version(current), parse_version(tag)), (Some(c), Some(t)) if t > c)
+}
+
+/// Platform suffix of the release binaries built for this host, e.g. `linux-x86_64`
+pub fn host_platform() -> Option<String> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "macos",
+        _ => return None,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x86_64",
+        "aarch64" => "arm64",
+        _ => return None,
+    };
+    Some(format!("{os}-{arch}"))
+}
+
+/// The server binary of `release` for `platform` and its `.sha256` file
+pub fn release_assets<'a>(
+    release: &'a Release,
+    platform: &str,
+) -> Result<(&'a ReleaseAsset, &'a ReleaseAsset), LambdaError> {
+    let name = format!("lambda-at-home-server-{}-{platform}", release.tag_name);
+    let find = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| upgrade_error(format!("release {} has no {name}", release.tag_name)))
+    };
+    Ok((find(&name)?, find(&format!("{name}.sha256"))?))
+}
+
+/// The digest in a `sha256sum` line, `<hex digest>  <file name>`
+pub fn parse_checksum(text: &str) -> Option<String> {
+    let digest = text.split_whitespace().next()?.to_ascii_lowercase();
+    (digest.len() == 64 && digest.bytes().all(|b| b.is_ascii_hexdigit())).then_some(digest)
+}
+
+pub fn verify_checksum(bytes: &[u8], expected: &str) -> Result<(), LambdaError> {
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if actual != expected {
+        return Err(upgrade_error(format!(
+            "checksum mismatch: expected {expected}, downloaded file has {actual}"
+        )));
+    }
+    Ok(())
+}
+
+/// Fetches releases from the configured feed
+pub struct ReleaseFeed {
+    client: reqwest::Client,
+    feed_url: String,
+}
+
+impl ReleaseFeed {
+    pub fn new(config: &UpgradeConfig) -> Result<Self, LambdaError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            // GitHub's API refuses requests without a user agent
+            .user_agent(concat!("lambda-at-home-server/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| upgrade_error(e.to_string()))?;
+        Ok(Self {
+            client,
+            feed_url: config.feed_url.clone(),
+        })
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, LambdaError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| upgrade_error(format!("{url}: {e}")))?;
+        if !response.status().is_success() {
+            return Err(upgrade_error(format!("{url}: HTTP {}", response.status())));
+        }
+        Ok(response)
+    }
+
+    pub async fn latest_release(&self) -> Result<Release, LambdaError> {
+        self.get(&self.feed_url)
+            .await?
+            .json()
+            .await
+            .map_err(|e| upgrade_error(format!("invalid release feed: {e}")))
+    }
+
+    /// The server binary of `release` for `platform`, checked against its published digest
+    pub async fn download(
+        &self,
+        release: &Release,
+        platform: &str,
+    ) -> Result<Vec<u8>, LambdaError> {
+        let (binary, checksum) = release_assets(release, platform)?;
+        let checksum_text = self
+            .get(&checksum.browser_download_url)
+            .await?
+            .text()
+            .await
+            .map_err(|e| upgrade_error(e.to_string()))?;
+        let expected = parse_checksum(&checksum_text)
+            .ok_or_else(|| upgrade_error(format!("{} holds no SHA-256 digest", checksum.name)))?;
+        info!("Downloading {}", binary.browser_download_url);
+        let bytes = self
+            .get(&binary.browser_download_url)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| upgrade_error(e.to_string()))?;
+        verify_checksum(&bytes, &expected)?;
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Where the binary an upgrade replaced is kept
+pub fn previous_binary_path(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".previous");
+    exe.with_file_name(name)
+}
+
+/// Swap `bytes` in as the binary at `exe`, keeping the current one at
+/// [`previous_binary_path`]. The new file is written next to `exe` first, so the swap
+/// is two renames on the same filesystem.
+pub fn install_binary(exe: &Path, bytes: &[u8]) -> Result<PathBuf, LambdaError> {
+    let io_error = |what: &str, e: std::io::Error| upgrade_error(format!("{what}: {e}"));
+    let mut staged_name = exe.file_name().unwrap_or_default().to_os_string();
+    staged_name.push(".new");
+    let staged = exe.with_file_name(staged_name);
+    std::fs::write(&staged, bytes).map_err(|e| io_error("writing new binary", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| io_error("making new binary executable", e))?;
+    }
+    let previous = previous_binary_path(exe);
+    std::fs::rename(exe, &previous).map_err(|e| io_error("keeping current binary", e))?;
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::rename(&previous, exe);
+        return Err(io_error("installing new binary", e));
+    }
+    Ok(previous)
+}
+
+/// Put the binary kept by [`install_binary`] back in place
+pub fn restore_binary(exe: &Path) -> Result<(), LambdaError> {
+    std::fs::rename(previous_binary_path(exe), exe)
+        .map_err(|e| upgrade_error(format!("restoring previous binary: {e}")))
+}
+
+/// File of a `sqlite:` or `sqlite://` URL; None for in-memory databases
+pub fn sqlite_path(db_url: &str) -> Option<PathBuf> {
+    let path = db_url
+        .strip_prefix("sqlite://")
+        .or_else(|| db_url.strip_prefix("sqlite:"))?;
+    let path = path.split('?').next().unwrap_or_default();
+    (!path.is_empty() && !path.starts_with(":memory:")).then(|| PathBuf::from(path))
+}
+
+/// Back up the database, then run the migrations. When they fail the database is restored
+/// from the backup, so the previous binary can still open it. Returns the backup, None for
+/// in-memory databases.
+pub async fn migrate_with_backup(db_url: &str) -> Result<Option<PathBuf>, LambdaError> {
+    let pool = SqlitePool::connect(db_url)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+    let Some(db_path) = sqlite_path(db_url) else {
+        migrations::run_migrations(&pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        return Ok(None);
+    };
+
+    let backup = db_path.with_extension(format!(
+        "pre-migration-{}.db",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    // VACUUM INTO writes a consistent copy, including what is still in the WAL
+    sqlx::query("VACUUM INTO ?")
+        .bind(backup.to_string_lossy().to_string())
+        .execute(&pool)
+        .await
+        .map_err(|e| upgrade_error(format!("backing up {}: {e}", db_path.display())))?;
+    info!("Backed up {} to {}", db_path.display(), backup.display());
+
+    let Err(e) = migrations::run_migrations(&pool).await else {
+        pool.close().await;
+        return Ok(Some(backup));
+    };
+    pool.close().await;
+    warn!("Migration failed, restoring {}: {}", backup.display(), e);
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.clone().into_os_string();
+        sidecar.push(suffix);
+        let _ = std::fs::remove_file(sidecar);
+    }
+    std::fs::copy(&backup, &db_path).map_err(|copy_error| {
+        upgrade_error(format!(
+            "migration failed ({e}) and restoring {} failed: {copy_error}",
+            backup.display()
+        ))
+    })?;
+    Err(upgrade_error(format!(
+        "migration failed and the database was restored: {e}"
+    )))
+}
@@ -0,0 +1,723 @@
+use lambda_models::{
+    AddPermissionRequest, CreateFunctionRequest, FunctionHooks, FunctionLifecycle,
+    FunctionMetadata, HookTarget, IdentityConfig, LambdaError, LocaleConfig, PackageType,
+    ResponseHeadersConfig, RouteJwtAuthorizer, ValidationProblem, ANY_FUNCTION_PRINCIPAL,
+    INVOKE_FUNCTION_ACTION, MAX_HOOK_TIMEOUT_MS,
+};
+use lambda_packaging::{bundle_workspace, verify_handler, ZipHandler, ZipInfo, SUPPORTED_RUNTIMES};
+
+/// AWS caps the serialized size of a function's environment at 4 KB
+pub const MAX_ENVIRONMENT_BYTES: usize = 4096;
+pub const MAX_ZIP_SIZE_BYTES: u64 = 50 * 1024 * 1024;
+pub const MIN_TIMEOUT_SECS: u64 = 1;
+pub const MAX_TIMEOUT_SECS: u64 = 900;
+pub const MAX_STOP_GRACE_SECS: u64 = 900;
+pub const MAX_IDLE_TIMEOUT_SECS: u64 = 86_400;
+pub const MAX_MIN_WARM_CONTAINERS: u32 = 100;
+pub const MIN_MEMORY_MB: u64 = 128;
+pub const MAX_MEMORY_MB: u64 = 10240;
+pub const MAX_METADATA_OWNER_LEN: usize = 256;
+pub const MAX_METADATA_URL_LEN: usize = 2048;
+pub const MAX_METADATA_NOTES_BYTES: usize = 64 * 1024;
+pub const MAX_STATEMENT_ID_LEN: usize = 100;
+pub const MAX_IMAGE_URI_LEN: usize = 512;
+/// AWS's limits on the tags of a resource
+pub const MAX_TAGS: usize = 50;
+pub const MAX_TAG_KEY_LEN: usize = 128;
+pub const MAX_TAG_VALUE_LEN: usize = 256;
+pub const MAX_GROUP_DESCRIPTION_LEN: usize = 256;
+
+/// Environment keys that the runtime sets itself and functions may not override
+const RESERVED_ENV_KEYS: &[&str] = &[
+    "_HANDLER",
+    "AWS_REGION",
+    "AWS_DEFAULT_REGION",
+    "AWS_EXECUTION_ENV",
+    "AWS_LAMBDA_FUNCTION_NAME",
+    "AWS_LAMBDA_FUNCTION_HANDLER",
+    "AWS_LAMBDA_FUNCTION_MEMORY_SIZE",
+    "AWS_LAMBDA_FUNCTION_VERSION",
+    "AWS_LAMBDA_LOG_GROUP_NAME",
+    "AWS_LAMBDA_LOG_STREAM_NAME",
+    "AWS_LAMBDA_RUNTIME_API",
+    "LAMBDA_TASK_ROOT",
+    "LAMBDA_RUNTIME_DIR",
+];
+
+/// Response headers that describe the message framing or connection, which the server sets
+const RESERVED_RESPONSE_HEADERS: &[&str] = &[
+    "connection",
+    "content-length",
+    "keep-alive",
+    "transfer-encoding",
+    "upgrade",
+];
+
+fn problem(field: impl Into<String>, code: &str, message: impl Into<String>) -> ValidationProblem {
+    ValidationProblem {
+        field: field.into(),
+        code: code.to_string(),
+        message: message.into(),
+    }
+}
+
+pub fn is_valid_function_name(name: &str) -> bool {
+    // AWS Lambda function name validation rules
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn is_valid_runtime(runtime: &str) -> bool {
+    SUPPORTED_RUNTIMES.contains(&runtime)
+}
+
+/// A Docker image reference: `name[:tag]` or `name@sha256:...`, optionally behind a registry
+pub fn is_valid_image_uri(image_uri: &str) -> bool {
+    !image_uri.is_empty()
+        && image_uri.len() <= MAX_IMAGE_URI_LEN
+        && !image_uri.starts_with(['-', ':', '@', '/'])
+        && image_uri
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:/@".contains(c))
+}
+
+/// Check that the code matches the package type: a ZIP for `Zip`, only an image URI for
+/// `Image`
+pub fn check_package(request: &CreateFunctionRequest) -> Option<ValidationProblem> {
+    let code = &request.code;
+    match request.package_type.unwrap_or_default() {
+        PackageType::Zip => code.image_uri.is_some().then(|| {
+            problem(
+                "code.image_uri",
+                "InvalidPackageType",
+                "code.image_uri requires package_type Image",
+            )
+        }),
+        PackageType::Image => match &code.image_uri {
+            None => Some(problem(
+                "code.image_uri",
+                "InvalidImageUri",
+                "package_type Image requires code.image_uri",
+            )),
+            Some(image_uri) if !is_valid_image_uri(image_uri) => Some(problem(
+                "code.image_uri",
+                "InvalidImageUri",
+                format!("Invalid image URI: {image_uri}"),
+            )),
+            Some(_)
+                if code.zip_file.is_some()
+                    || code.s3_bucket.is_some()
+                    || code.workspace_package.is_some() =>
+            {
+                Some(problem(
+                    "code",
+                    "InvalidPackageType",
+                    "package_type Image takes code.image_uri only",
+                ))
+            }
+            Some(_) => None,
+        },
+    }
+}
+
+/// Interpreted runtimes expect `module.function`; compiled runtimes only need a non-empty handler
+pub fn is_valid_handler(runtime: &str, handler: &str) -> bool {
+    if handler.is_empty() || handler.len() > 128 || handler.chars().any(char::is_whitespace) {
+        return false;
+    }
+    match runtime {
+        "nodejs18.x" | "nodejs22.x" | "nodejs24.x" | "python3.11" => handler
+            .rsplit_once('.')
+            .is_some_and(|(module, function)| !module.is_empty() && !function.is_empty()),
+        _ => true,
+    }
+}
+
+/// Check everything in a CreateFunction request that does not depend on stored state.
+/// Returns the decoded ZIP (if any) alongside the problems so callers can reuse it.
+pub async fn validate_create_function_request(
+    request: &CreateFunctionRequest,
+) -> (Vec<ValidationProblem>, Option<ZipInfo>) {
+    let mut problems = Vec::new();
+
+    if !is_valid_function_name(&request.function_name) {
+        problems.push(problem(
+            "function_name",
+            "InvalidFunctionName",
+            "Function name must be 1-64 characters of letters, numbers, hyphens or underscores",
+        ));
+    }
+
+    let runtime_ok = is_valid_runtime(&request.runtime);
+    if !runtime_ok {
+        problems.push(problem(
+            "runtime",
+            "InvalidRuntime",
+            format!("Unsupported runtime: {}", request.runtime),
+        ));
+    }
+
+    // Images start their own handler
+    let image = request.package_type == Some(PackageType::Image);
+    let handler_ok = image || is_valid_handler(&request.runtime, &request.handler);
+    if !handler_ok {
+        problems.push(problem(
+            "handler",
+            "InvalidHandler",
+            format!(
+                "Handler '{}' is not valid for runtime {} (expected module.function)",
+                request.handler, request.runtime
+            ),
+        ));
+    }
+
+    if let Some(timeout) = request.timeout {
+        if !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&timeout) {
+            problems.push(problem(
+                "timeout",
+                "InvalidTimeout",
+                format!(
+                    "Timeout must be between {MIN_TIMEOUT_SECS} and {MAX_TIMEOUT_SECS} seconds"
+                ),
+            ));
+        }
+    }
+
+    if let Some(memory_size) = request.memory_size {
+        if !(MIN_MEMORY_MB..=MAX_MEMORY_MB).contains(&memory_size) {
+            problems.push(problem(
+                "memory_size",
+                "InvalidMemorySize",
+                format!("Memory size must be between {MIN_MEMORY_MB} and {MAX_MEMORY_MB} MB"),
+            ));
+        }
+    }
+
+    if let Some(environment) = &request.environment {
+        validate_environment(environment, &mut problems);
+    }
+
+    if let Some(tags) = &request.tags {
+        if let Err(message) = check_tags(tags.iter(), tags.len()) {
+            problems.push(problem("tags", "InvalidTags", message));
+        }
+    }
+
+    problems.extend(check_package(request));
+
+    let zip_data = match &request.code.zip_file {
+        Some(zip_file_base64) => {
+            match base64::Engine::decode(
+                &base64::engine::general_purpose::STANDARD,
+                zip_file_base64,
+            ) {
+                Ok(zip_data) => Some(zip_data),
+                Err(e) => {
+                    problems.push(problem(
+                        "code.zip_file",
+                        "InvalidZipFile",
+                        format!("Invalid base64 ZIP data: {e}"),
+                    ));
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let zip_data = match (zip_data, &request.code.workspace_package) {
+        (Some(zip_data), Some(package)) => match bundle_workspace(&zip_data, package) {
+            Ok(bundle) => Some(bundle),
+            Err(e) => {
+                problems.push(problem(
+                    "code.workspace_package",
+                    "InvalidWorkspacePackage",
+                    e.to_string(),
+                ));
+                None
+            }
+        },
+        (zip_data, _) => zip_data,
+    };
+
+    let zip_info = match zip_data {
+        Some(zip_data) => match ZipHandler::new(MAX_ZIP_SIZE_BYTES)
+            .process_zip(&zip_data)
+            .await
+        {
+            Ok(zip_info) => Some(zip_info),
+            Err(e) => {
+                let code = match e {
+                    LambdaError::CodeTooLarge { .. } => "CodeTooLarge",
+                    _ => "InvalidZipFile",
+                };
+                problems.push(problem("code.zip_file", code, e.to_string()));
+                None
+            }
+        },
+        None => None,
+    };
+
+    if let (Some(zip_info), true, true) = (&zip_info, runtime_ok, handler_ok) {
+        if let Some(p) = check_handler_file(&request.runtime, &request.handler, zip_info) {
+            problems.push(p);
+        }
+    }
+
+    (problems, zip_info)
+}
+
+/// Verify that the handler resolves to a function defined in the bundle
+pub fn check_handler_file(
+    runtime: &str,
+    handler: &str,
+    zip_info: &ZipInfo,
+) -> Option<ValidationProblem> {
+    match verify_handler(runtime, handler, zip_info) {
+        Ok(()) => None,
+        Err(e) => Some(problem("handler", "HandlerNotFound", e.to_string())),
+    }
+}
+
+fn validate_environment(
+    environment: &std::collections::HashMap<String, String>,
+    problems: &mut Vec<ValidationProblem>,
+) {
+    let mut keys: Vec<&String> = environment.keys().collect();
+    keys.sort();
+    for key in keys {
+        let field = format!("environment.{key}");
+        let mut chars = key.chars();
+        let well_formed = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !well_formed {
+            problems.push(problem(
+                field,
+                "InvalidEnvironmentVariableName",
+                format!("Environment variable name '{key}' must match [a-zA-Z][a-zA-Z0-9_]*"),
+            ));
+        } else if RESERVED_ENV_KEYS.contains(&key.as_str()) {
+            problems.push(problem(
+                field,
+                "ReservedEnvironmentVariable",
+                format!("Environment variable '{key}' is reserved by the runtime"),
+            ));
+        }
+    }
+
+    let size = serde_json::to_string(environment)
+        .map(|s| s.len())
+        .unwrap_or_default();
+    if size > MAX_ENVIRONMENT_BYTES {
+        problems.push(problem(
+            "environment",
+            "EnvironmentTooLarge",
+            format!("Environment variables total {size} bytes, limit is {MAX_ENVIRONMENT_BYTES}"),
+        ));
+    }
+}
+
+/// Check the names of the variables in `keys`, and the size of the whole `environment` they
+/// are part of
+pub fn check_environment_update(
+    environment: &std::collections::HashMap<String, String>,
+    keys: &[&str],
+) -> Result<(), LambdaError> {
+    let mut problems = Vec::new();
+    validate_environment(environment, &mut problems);
+    let reasons: Vec<String> = problems
+        .into_iter()
+        .filter(|p| match p.field.strip_prefix("environment.") {
+            Some(key) => keys.contains(&key),
+            None => true,
+        })
+        .map(|p| p.message)
+        .collect();
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    Err(LambdaError::InvalidRequest {
+        reason: reasons.join("; "),
+    })
+}
+
+/// A batch configuration update sets memory and/or timeout, each within the limits a
+/// single function accepts
+pub fn check_batch_configuration(
+    memory_size: Option<u64>,
+    timeout: Option<u64>,
+) -> Result<(), LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    if memory_size.is_none() && timeout.is_none() {
+        return Err(invalid(
+            "update_configuration needs memory_size or timeout".to_string(),
+        ));
+    }
+    if timeout.is_some_and(|t| !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&t)) {
+        return Err(invalid(format!(
+            "Timeout must be between {MIN_TIMEOUT_SECS} and {MAX_TIMEOUT_SECS} seconds"
+        )));
+    }
+    if memory_size.is_some_and(|m| !(MIN_MEMORY_MB..=MAX_MEMORY_MB).contains(&m)) {
+        return Err(invalid(format!(
+            "Memory size must be between {MIN_MEMORY_MB} and {MAX_MEMORY_MB} MB"
+        )));
+    }
+    Ok(())
+}
+
+/// Why tags are refused: a function would end up with more than `MAX_TAGS` of them
+/// (`total`), a key is empty, too long or under the reserved `aws:` prefix, or a value is
+/// too long
+pub fn check_tags<'a>(
+    tags: impl IntoIterator<Item = (&'a String, &'a String)>,
+    total: usize,
+) -> Result<(), String> {
+    if total > MAX_TAGS {
+        return Err(format!("A function can have at most {MAX_TAGS} tags"));
+    }
+    for (key, value) in tags {
+        if key.is_empty() || key.chars().count() > MAX_TAG_KEY_LEN {
+            return Err(format!(
+                "Tag keys must be 1-{MAX_TAG_KEY_LEN} characters, got '{key}'"
+            ));
+        }
+        if key.to_ascii_lowercase().starts_with("aws:") {
+            return Err(format!("Tag key '{key}' uses the reserved aws: prefix"));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(format!(
+                "The value of tag '{key}' is longer than {MAX_TAG_VALUE_LEN} characters"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Group names follow the rules of function names
+pub fn validate_group(name: &str, description: Option<&str>) -> Result<(), LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    if !is_valid_function_name(name) {
+        return Err(invalid(format!(
+            "Invalid group name {name}: 1-64 letters, numbers, hyphens or underscores"
+        )));
+    }
+    if description.is_some_and(|d| d.chars().count() > MAX_GROUP_DESCRIPTION_LEN) {
+        return Err(invalid(format!(
+            "Group descriptions are at most {MAX_GROUP_DESCRIPTION_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Idle timeouts of 1s to a day, and warm container counts where the minimum fits under
+/// the maximum
+pub fn validate_lifecycle(lifecycle: &FunctionLifecycle) -> Result<(), LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    if let Some(timeout) = lifecycle.idle_timeout_secs {
+        if !(1..=MAX_IDLE_TIMEOUT_SECS).contains(&timeout) {
+            return Err(invalid(format!(
+                "idle_timeout_secs must be between 1 and {MAX_IDLE_TIMEOUT_SECS}, got {timeout}"
+            )));
+        }
+    }
+    if lifecycle.max_warm_containers == Some(0) {
+        return Err(invalid(
+            "max_warm_containers must be at least 1".to_string(),
+        ));
+    }
+    if let Some(min) = lifecycle.min_warm_containers {
+        if min > MAX_MIN_WARM_CONTAINERS {
+            return Err(invalid(format!(
+                "min_warm_containers must be at most {MAX_MIN_WARM_CONTAINERS}, got {min}"
+            )));
+        }
+        if let Some(max) = lifecycle.max_warm_containers.filter(|max| min > *max) {
+            return Err(invalid(format!(
+                "min_warm_containers {min} exceeds max_warm_containers {max}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// An issuer, at least one audience and an http(s) JWKS URL
+pub fn validate_jwt_authorizer(authorizer: &RouteJwtAuthorizer) -> Result<(), LambdaError> {
+    let invalid = |reason: &str| LambdaError::InvalidRequest {
+        reason: reason.to_string(),
+    };
+    if authorizer.issuer.trim().is_empty() {
+        return Err(invalid("issuer must not be empty"));
+    }
+    if authorizer.audience.is_empty() || authorizer.audience.iter().any(|a| a.is_empty()) {
+        return Err(invalid("audience must list at least one non-empty value"));
+    }
+    validate_http_url("jwks_url", &authorizer.jwks_url)
+}
+
+/// Links must be http(s) and at most `MAX_METADATA_URL_LEN` characters
+pub fn validate_http_url(field: &str, url: &str) -> Result<(), LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    if url.len() > MAX_METADATA_URL_LEN {
+        return Err(invalid(format!(
+            "{field} must be at most {MAX_METADATA_URL_LEN} characters"
+        )));
+    }
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(invalid(format!(
+            "{field} must be an http(s) URL, got {url}"
+        )));
+    }
+    Ok(())
+}
+
+/// Pinned runtime versions are image digests: `sha256:` and 64 lowercase hex digits
+pub fn validate_runtime_version(version: &str) -> Result<(), LambdaError> {
+    let valid = version.strip_prefix("sha256:").is_some_and(|hex| {
+        hex.len() == 64
+            && hex
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    });
+    if !valid {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!(
+                "runtime_version must be an image digest (sha256:<64 hex>), got {version}"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Trim the settings, drop empty ones and check the timezone against the tz database and
+/// the locale against the POSIX `language[_TERRITORY][.codeset][@modifier]` form
+pub fn normalize_locale_config(config: LocaleConfig) -> Result<LocaleConfig, LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let timezone = clean(config.timezone);
+    if let Some(tz) = &timezone {
+        if tz.parse::<chrono_tz::Tz>().is_err() {
+            return Err(invalid(format!("Unknown timezone: {tz}")));
+        }
+    }
+    let locale = clean(config.locale);
+    if let Some(locale) = &locale {
+        if !is_valid_locale(locale) {
+            return Err(invalid(format!(
+                "Invalid locale: {locale} (expected e.g. en_US.UTF-8, C.UTF-8 or POSIX)"
+            )));
+        }
+    }
+    Ok(LocaleConfig { timezone, locale })
+}
+
+/// Check an AddPermission request and reduce its principal to a function name or `*`; a
+/// function ARN must be one of this server's, given its identity
+pub fn normalize_add_permission(
+    request: AddPermissionRequest,
+    identity: &IdentityConfig,
+) -> Result<AddPermissionRequest, LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+
+    let statement_id = request.statement_id.trim().to_string();
+    if statement_id.is_empty()
+        || statement_id.len() > MAX_STATEMENT_ID_LEN
+        || !statement_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(invalid(format!(
+            "Invalid statement id: {statement_id} (1 to {MAX_STATEMENT_ID_LEN} letters, digits, hyphens or underscores)"
+        )));
+    }
+    if request.action != INVOKE_FUNCTION_ACTION {
+        return Err(invalid(format!(
+            "Unsupported action: {} (only {INVOKE_FUNCTION_ACTION})",
+            request.action
+        )));
+    }
+
+    let principal = request.principal.trim();
+    let principal = if principal == ANY_FUNCTION_PRINCIPAL {
+        principal
+    } else if principal.starts_with("arn:") {
+        principal
+            .strip_prefix(identity.function_arn("").as_str())
+            .ok_or_else(|| {
+                invalid(format!(
+                    "Principal {principal} is not the ARN of a function of this server"
+                ))
+            })?
+    } else {
+        principal
+    };
+    if principal != ANY_FUNCTION_PRINCIPAL && !is_valid_function_name(principal) {
+        return Err(invalid(format!("Invalid principal: {principal}")));
+    }
+    Ok(AddPermissionRequest {
+        statement_id,
+        action: request.action,
+        principal: principal.to_string(),
+    })
+}
+
+/// Check the hooks of `function_name`: timeouts in range, http(s) webhooks, and no function
+/// hooking itself
+pub fn validate_function_hooks(
+    function_name: &str,
+    hooks: &FunctionHooks,
+) -> Result<(), LambdaError> {
+    let invalid = |reason: String| Err(LambdaError::InvalidRequest { reason });
+    let phases = [
+        ("pre_invoke", &hooks.pre_invoke),
+        ("post_invoke", &hooks.post_invoke),
+    ];
+    for (phase, hook) in phases {
+        let Some(hook) = hook else {
+            continue;
+        };
+        if hook.timeout_ms == 0 || hook.timeout_ms > MAX_HOOK_TIMEOUT_MS {
+            return invalid(format!(
+                "{phase}.timeout_ms must be between 1 and {MAX_HOOK_TIMEOUT_MS}"
+            ));
+        }
+        match &hook.target {
+            HookTarget::Function {
+                function_name: target,
+            } => {
+                if !is_valid_function_name(target) {
+                    return invalid(format!("{phase}: invalid function name: {target}"));
+                }
+                if target == function_name {
+                    return invalid(format!("{phase}: a function cannot be its own hook"));
+                }
+            }
+            HookTarget::Webhook { url } => validate_http_url(&format!("{phase}.url"), url)?,
+        }
+    }
+    Ok(())
+}
+
+/// Lowercase the header names and check that each header may be added to invoke responses
+pub fn normalize_response_headers_config(
+    config: ResponseHeadersConfig,
+) -> Result<ResponseHeadersConfig, LambdaError> {
+    let mut headers = std::collections::HashMap::new();
+    for (name, value) in config.headers {
+        let name = name.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+        check_response_header(&name, &value)
+            .map_err(|reason| LambdaError::InvalidRequest { reason })?;
+        if headers.insert(name.clone(), value).is_some() {
+            return Err(LambdaError::InvalidRequest {
+                reason: format!("Header {name} is set more than once"),
+            });
+        }
+    }
+    Ok(ResponseHeadersConfig {
+        headers,
+        envelope: config.envelope,
+    })
+}
+
+/// Why `name: value` may not be added to an invoke response. `X-Amz-*` and `X-Lambdah-*`
+/// headers belong to the invoke API, and framing headers to the server.
+pub fn check_response_header(name: &str, value: &str) -> Result<(), String> {
+    let lower = name.to_ascii_lowercase();
+    let is_token_char = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if name.is_empty() || !name.chars().all(is_token_char) {
+        return Err(format!("Invalid header name: {name:?}"));
+    }
+    if lower.starts_with("x-amz-")
+        || lower.starts_with("x-lambdah-")
+        || RESERVED_RESPONSE_HEADERS.contains(&lower.as_str())
+    {
+        return Err(format!("Header {name} is set by the server"));
+    }
+    if !value.chars().all(|c| c == '\t' || (' '..='~').contains(&c)) {
+        return Err(format!("Invalid value for header {name}"));
+    }
+    Ok(())
+}
+
+fn is_valid_locale(locale: &str) -> bool {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let (name, codeset) = match rest.split_once('.') {
+        Some((name, codeset)) => (name, Some(codeset)),
+        None => (rest, None),
+    };
+    let (language, territory) = match name.split_once('_') {
+        Some((language, territory)) => (language, Some(territory)),
+        None => (name, None),
+    };
+    let language_ok = matches!(language, "C" | "POSIX")
+        || ((2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase()));
+    let territory_ok =
+        territory.is_none_or(|t| t.len() == 2 && t.chars().all(|c| c.is_ascii_uppercase()));
+    let codeset_ok = codeset
+        .is_none_or(|c| !c.is_empty() && c.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    let modifier_ok =
+        modifier.is_none_or(|m| !m.is_empty() && m.chars().all(|c| c.is_ascii_alphanumeric()));
+    language_ok && territory_ok && codeset_ok && modifier_ok
+}
+
+/// Trim metadata fields, drop empty ones and check lengths and link schemes
+pub fn normalize_function_metadata(
+    metadata: FunctionMetadata,
+) -> Result<FunctionMetadata, LambdaError> {
+    let invalid = |reason: String| LambdaError::InvalidRequest { reason };
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let owner = clean(metadata.owner);
+    if owner
+        .as_ref()
+        .is_some_and(|o| o.chars().count() > MAX_METADATA_OWNER_LEN)
+    {
+        return Err(invalid(format!(
+            "owner must be at most {MAX_METADATA_OWNER_LEN} characters"
+        )));
+    }
+
+    let mut links = [
+        ("repo_url", clean(metadata.repo_url)),
+        ("docs_url", clean(metadata.docs_url)),
+    ];
+    for (field, url) in &links {
+        if let Some(url) = url {
+            validate_http_url(field, url)?;
+        }
+    }
+
+    // Notes keep their whitespace; Markdown depends on it
+    let notes = metadata.notes.filter(|n| !n.trim().is_empty());
+    if notes
+        .as_ref()
+        .is_some_and(|n| n.len() > MAX_METADATA_NOTES_BYTES)
+    {
+        return Err(invalid(format!(
+            "notes must be at most {MAX_METADATA_NOTES_BYTES} bytes"
+        )));
+    }
+
+    Ok(FunctionMetadata {
+        owner,
+        repo_url: links[0].1.take(),
+        docs_url: links[1].1.take(),
+        notes,
+    })
+}
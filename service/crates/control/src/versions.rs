@@ -0,0 +1,71 @@
+use lambda_models::{Alias, LambdaError, RoutingConfig, LATEST_VERSION};
+
+/// What an invocation's qualifier names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Qualifier<'a> {
+    Latest,
+    Version(&'a str),
+    Alias(&'a str),
+}
+
+impl<'a> Qualifier<'a> {
+    /// `$LATEST` or no qualifier, a published version number, or else an alias name
+    pub fn parse(qualifier: Option<&'a str>) -> Self {
+        match qualifier.map(str::trim) {
+            None | Some("") | Some(LATEST_VERSION) => Qualifier::Latest,
+            Some(q) if q.bytes().all(|b| b.is_ascii_digit()) => Qualifier::Version(q),
+            Some(q) => Qualifier::Alias(q),
+        }
+    }
+}
+
+/// Version an invocation through `alias` runs: one of its additional versions for the share
+/// of `roll` (in `0.0..1.0`) their weights cover, else the alias's own version
+pub fn alias_version(alias: &Alias, roll: f64) -> &str {
+    let mut weights: Vec<(&String, &f64)> = alias
+        .routing_config
+        .iter()
+        .flat_map(|routing| routing.additional_version_weights.iter())
+        .collect();
+    weights.sort_by(|a, b| a.0.cmp(b.0));
+    let mut covered = 0.0;
+    for (version, weight) in weights {
+        covered += weight.clamp(0.0, 1.0);
+        if roll < covered {
+            return version;
+        }
+    }
+    &alias.function_version
+}
+
+/// Check an alias's routing before it is stored: at most one additional version, apart from
+/// the alias's own, weighted within `0.0..=1.0`. Traffic is only shifted between published
+/// versions.
+pub fn validate_routing(
+    function_version: &str,
+    routing: Option<&RoutingConfig>,
+) -> Result<(), LambdaError> {
+    let invalid = |reason: String| Err(LambdaError::InvalidRequest { reason });
+    let Some(routing) = routing else {
+        return Ok(());
+    };
+    if routing.additional_version_weights.len() > 1 {
+        return invalid("routing_config can name only one additional version".to_string());
+    }
+    for (version, weight) in &routing.additional_version_weights {
+        if !(0.0..=1.0).contains(weight) {
+            return invalid(format!(
+                "Weight of version {version} must be between 0.0 and 1.0"
+            ));
+        }
+        if version == function_version {
+            return invalid(format!("Version {version} is already the alias's version"));
+        }
+        if version == LATEST_VERSION || function_version == LATEST_VERSION {
+            return invalid(format!(
+                "Traffic cannot be shifted to or from {LATEST_VERSION}"
+            ));
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use crate::queues::FnKey;
 use dashmap::DashMap;
-use lambda_models::LambdaError;
+use lambda_models::{FunctionLifecycle, FunctionWarmState, LambdaError};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 use tracing::{error, info, instrument};
 use uuid::Uuid;
 
@@ -12,6 +14,8 @@ pub struct WarmContainer {
     pub instance_id: String,
     pub function_id: Uuid,
     pub image_ref: String,
+    /// Memory the container was started with, counted against the host budget
+    pub memory_mb: u64,
     pub created_at: Instant,
     pub last_used: Instant,
     pub state: InstanceState,
@@ -31,11 +35,74 @@ pub enum InstanceState {
     Failed,
 }
 
+impl InstanceState {
+    /// States that count toward a key's provisioned containers
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self,
+            InstanceState::Provisioning
+                | InstanceState::Initializing
+                | InstanceState::WarmIdle
+                | InstanceState::Active
+        )
+    }
+
+    /// States in which the container process is not running
+    pub fn releases_memory(&self) -> bool {
+        matches!(
+            self,
+            InstanceState::Stopped | InstanceState::Terminated | InstanceState::Failed
+        )
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstanceState::Init => "Init",
+            InstanceState::Provisioning => "Provisioning",
+            InstanceState::Initializing => "Initializing",
+            InstanceState::WarmIdle => "WarmIdle",
+            InstanceState::Active => "Active",
+            InstanceState::Draining => "Draining",
+            InstanceState::Stopping => "Stopping",
+            InstanceState::Stopped => "Stopped",
+            InstanceState::Terminated => "Terminated",
+            InstanceState::Failed => "Failed",
+        }
+    }
+}
+
+impl std::str::FromStr for InstanceState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Init" => Ok(InstanceState::Init),
+            "Provisioning" => Ok(InstanceState::Provisioning),
+            "Initializing" => Ok(InstanceState::Initializing),
+            "WarmIdle" => Ok(InstanceState::WarmIdle),
+            "Active" => Ok(InstanceState::Active),
+            "Draining" => Ok(InstanceState::Draining),
+            "Stopping" => Ok(InstanceState::Stopping),
+            "Stopped" => Ok(InstanceState::Stopped),
+            "Terminated" => Ok(InstanceState::Terminated),
+            "Failed" => Ok(InstanceState::Failed),
+            other => Err(format!("Unknown instance state: {other}")),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WarmPool {
     // Key by FnKey for proper isolation (function+runtime+version+env)
     // Using DashMap for lock-free concurrent access instead of Mutex<HashMap>
     containers: Arc<DashMap<FnKey, Vec<WarmContainer>>>,
+    // Signalled on every membership or state change, for the snapshot writer
+    changed: Arc<Notify>,
+    // Containers per key kept warm for provisioned concurrency or a function's
+    // min_warm_containers, exempt from idle cleanup
+    provisioned: Arc<DashMap<FnKey, usize>>,
+    // Idle timeout and warm container bounds of the functions that set them
+    lifecycles: Arc<DashMap<Uuid, FunctionLifecycle>>,
 }
 
 impl Default for WarmPool {
@@ -48,9 +115,113 @@ impl WarmPool {
     pub fn new() -> Self {
         Self {
             containers: Arc::new(DashMap::new()),
+            changed: Arc::new(Notify::new()),
+            provisioned: Arc::new(DashMap::new()),
+            lifecycles: Arc::new(DashMap::new()),
         }
     }
 
+    /// Apply a function's lifecycle settings to its containers; `None` restores the defaults
+    pub fn set_lifecycle(&self, function_id: Uuid, lifecycle: Option<FunctionLifecycle>) {
+        match lifecycle {
+            Some(lifecycle) => {
+                self.lifecycles.insert(function_id, lifecycle);
+            }
+            None => {
+                self.lifecycles.remove(&function_id);
+            }
+        }
+    }
+
+    /// Lifecycle settings of a function, all unset when it has none
+    pub fn lifecycle(&self, function_id: Uuid) -> FunctionLifecycle {
+        self.lifecycles
+            .get(&function_id)
+            .map(|lifecycle| lifecycle.clone())
+            .unwrap_or_default()
+    }
+
+    /// Soft and hard idle timeouts of a function's containers: its `idle_timeout_secs` stops
+    /// them, and removal waits for the later of that and `hard_idle`
+    fn idle_timeouts(
+        &self,
+        function_id: Uuid,
+        soft_idle: Duration,
+        hard_idle: Duration,
+    ) -> (Duration, Duration) {
+        match self
+            .lifecycles
+            .get(&function_id)
+            .and_then(|lifecycle| lifecycle.idle_timeout_secs)
+        {
+            Some(secs) => {
+                let soft = Duration::from_secs(secs);
+                (soft, soft.max(hard_idle))
+            }
+            None => (soft_idle, hard_idle),
+        }
+    }
+
+    /// How many more containers the function may start under its `max_warm_containers`;
+    /// `None` when it sets no maximum
+    pub async fn warm_room(&self, function_id: Uuid) -> Option<usize> {
+        let max = self.lifecycles.get(&function_id)?.max_warm_containers? as usize;
+        Some(max.saturating_sub(self.count_running_of_function(function_id).await))
+    }
+
+    /// Count containers of a function, across its keys, that are starting, idle or busy
+    pub async fn count_running_of_function(&self, function_id: Uuid) -> usize {
+        self.containers
+            .iter()
+            .map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .filter(|c| c.function_id == function_id && c.state.is_running())
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Replace the provisioned container count of every key; keys left out have none
+    pub fn set_provisioned(&self, floors: HashMap<FnKey, usize>) {
+        self.provisioned.retain(|key, _| floors.contains_key(key));
+        for (key, count) in floors {
+            self.provisioned.insert(key, count);
+        }
+    }
+
+    /// Containers kept for provisioned concurrency: the most recently used running ones of
+    /// each key, up to its provisioned count
+    fn provisioned_ids(&self) -> HashSet<String> {
+        let mut ids = HashSet::new();
+        for floor in self.provisioned.iter() {
+            let Some(list) = self.containers.get(floor.key()) else {
+                continue;
+            };
+            let mut running: Vec<&WarmContainer> =
+                list.iter().filter(|c| c.state.is_running()).collect();
+            running.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+            ids.extend(
+                running
+                    .into_iter()
+                    .take(*floor.value())
+                    .map(|c| c.container_id.clone()),
+            );
+        }
+        ids
+    }
+
+    fn touch(&self) {
+        self.changed.notify_one();
+    }
+
+    /// Wait for the next change to membership or state. Changes made while nobody waits
+    /// are coalesced into one wakeup.
+    pub async fn changed(&self) {
+        self.changed.notified().await;
+    }
+
     #[instrument(skip(self))]
     pub async fn get_warm_container(&self, key: &FnKey) -> Option<WarmContainer> {
         if let Some(mut container_list) = self.containers.get_mut(key) {
@@ -64,6 +235,7 @@ impl WarmPool {
                         "Reusing warm container: {} for function: {}",
                         container.container_id, key.function_name
                     );
+                    self.touch();
                     return Some(container.clone());
                 }
             }
@@ -79,6 +251,7 @@ impl WarmPool {
                 if c.state == InstanceState::WarmIdle {
                     c.state = InstanceState::Active;
                     c.last_used = Instant::now();
+                    self.touch();
                     return Some(c.container_id.clone());
                 }
             }
@@ -93,6 +266,7 @@ impl WarmPool {
                 if c.state == InstanceState::Active {
                     c.state = InstanceState::WarmIdle;
                     c.last_used = Instant::now();
+                    self.touch();
                     return Some(c.container_id.clone());
                 }
             }
@@ -114,6 +288,7 @@ impl WarmPool {
                 if let Some(c_mut) = list.iter_mut().find(|c| c.state == InstanceState::Active) {
                     c_mut.state = InstanceState::WarmIdle;
                     c_mut.last_used = Instant::now();
+                    self.touch();
                     return Some((key, c_mut.container_id.clone()));
                 }
             }
@@ -129,6 +304,7 @@ impl WarmPool {
             .entry(key.clone())
             .or_default()
             .push(container);
+        self.touch();
 
         info!(
             "Added warm container: {} for function: {}",
@@ -147,6 +323,7 @@ impl WarmPool {
                 if container.container_id == container_id {
                     container.state = InstanceState::WarmIdle;
                     container.last_used = Instant::now();
+                    self.touch();
                     info!("Returned container to warm pool: {}", container_id);
                     return Ok(());
                 }
@@ -172,9 +349,11 @@ impl WarmPool {
             let now_empty = container_list.is_empty();
             drop(container_list);
 
+            // A container added meanwhile keeps the key
             if now_empty {
-                self.containers.remove(key);
+                self.containers.remove_if(key, |_, list| list.is_empty());
             }
+            self.touch();
 
             info!("Removed container from warm pool: {}", container_id);
             Ok(())
@@ -195,12 +374,18 @@ impl WarmPool {
         let now = Instant::now();
         let mut to_remove = Vec::new();
         let mut to_stop = Vec::new();
+        let provisioned = self.provisioned_ids();
 
         // First pass: identify containers to remove (without holding lock during removal)
         for entry in self.containers.iter() {
             let key = entry.key().clone();
             for container in entry.value().iter() {
+                if provisioned.contains(&container.container_id) {
+                    continue;
+                }
                 let idle_time = now.duration_since(container.last_used);
+                let (soft_idle, hard_idle) =
+                    self.idle_timeouts(container.function_id, soft_idle, hard_idle);
 
                 if idle_time >= hard_idle {
                     to_remove.push((key.clone(), container.container_id.clone()));
@@ -250,21 +435,65 @@ impl WarmPool {
         }
     }
 
-    /// List containers that are soft idle (idle beyond soft_idle and currently WarmIdle)
+    /// List containers that are soft idle (idle beyond soft_idle, or their function's
+    /// idle_timeout_secs, and currently WarmIdle), and the least recently used idle
+    /// containers of functions running more than their max_warm_containers
     pub async fn list_soft_idle_containers(&self, soft_idle: Duration) -> Vec<String> {
         let now = Instant::now();
         let mut to_stop = Vec::new();
+        let provisioned = self.provisioned_ids();
+        let maxima: Vec<(Uuid, usize)> = self
+            .lifecycles
+            .iter()
+            .filter_map(|l| Some((*l.key(), l.max_warm_containers? as usize)))
+            .collect();
+        let mut over_max: HashMap<Uuid, usize> = HashMap::new();
+        for (function_id, max) in maxima {
+            let running = self.count_running_of_function(function_id).await;
+            if running > max {
+                over_max.insert(function_id, running - max);
+            }
+        }
+        let mut idle_over_max: Vec<(Instant, Uuid, String)> = Vec::new();
         for entry in self.containers.iter() {
             for c in entry.value().iter() {
                 let idle_time = now.duration_since(c.last_used);
-                if idle_time >= soft_idle && c.state == InstanceState::WarmIdle {
+                let (soft_idle, _) = self.idle_timeouts(c.function_id, soft_idle, soft_idle);
+                if over_max.contains_key(&c.function_id)
+                    && c.state == InstanceState::WarmIdle
+                    && !provisioned.contains(&c.container_id)
+                {
+                    idle_over_max.push((c.last_used, c.function_id, c.container_id.clone()));
+                }
+                if idle_time >= soft_idle
+                    && c.state == InstanceState::WarmIdle
+                    && !provisioned.contains(&c.container_id)
+                {
                     to_stop.push(c.container_id.clone());
                 }
             }
         }
+        idle_over_max.sort();
+        for (_, function_id, container_id) in idle_over_max {
+            let Some(excess) = over_max.get_mut(&function_id).filter(|excess| **excess > 0) else {
+                continue;
+            };
+            *excess -= 1;
+            if !to_stop.contains(&container_id) {
+                to_stop.push(container_id);
+            }
+        }
         to_stop
     }
 
+    /// Count containers of a key that are starting, idle or busy
+    pub async fn count_running(&self, key: &FnKey) -> usize {
+        self.containers
+            .get(key)
+            .map(|list| list.iter().filter(|c| c.state.is_running()).count())
+            .unwrap_or(0)
+    }
+
     /// Count containers in a specific state for a key
     pub async fn count_state(&self, key: &FnKey, state: InstanceState) -> usize {
         self.containers
@@ -303,6 +532,7 @@ impl WarmPool {
             if let Some(mut list) = self.containers.get_mut(&key) {
                 if let Some(c_mut) = list.iter_mut().find(|c| c.container_id == container_id) {
                     c_mut.state = state;
+                    self.touch();
                     return true;
                 }
             }
@@ -310,6 +540,17 @@ impl WarmPool {
         false
     }
 
+    /// Find which key a container belongs to and its current state
+    pub async fn find_container(&self, container_id: &str) -> Option<(FnKey, InstanceState)> {
+        self.containers.iter().find_map(|entry| {
+            entry
+                .value()
+                .iter()
+                .find(|c| c.container_id == container_id)
+                .map(|c| (entry.key().clone(), c.state))
+        })
+    }
+
     /// Get one stopped container for a given key if present (does not change state)
     pub async fn get_one_stopped(&self, key: &FnKey) -> Option<String> {
         if let Some(list) = self.containers.get(key) {
@@ -331,6 +572,7 @@ impl WarmPool {
             }
         }
         self.containers.clear();
+        self.touch();
         ids
     }
 
@@ -363,9 +605,12 @@ impl WarmPool {
 
             if now_empty {
                 // Remove key after dropping the guard
-                self.containers.remove(&key);
+                self.containers.remove_if(&key, |_, list| list.is_empty());
             }
         }
+        if !removed.is_empty() {
+            self.touch();
+        }
         removed
     }
 
@@ -381,8 +626,12 @@ impl WarmPool {
         for key in keys {
             if let Some(mut list) = self.containers.get_mut(&key) {
                 if let Some(c_mut) = list.iter_mut().find(|c| c.instance_id == instance_id) {
-                    c_mut.state = InstanceState::Active;
+                    // A prewarming container only leaves Initializing once its prewarm succeeded
+                    if c_mut.state != InstanceState::Initializing {
+                        c_mut.state = InstanceState::Active;
+                    }
                     c_mut.last_used = Instant::now();
+                    self.touch();
                     return Some((key, c_mut.container_id.clone()));
                 }
             }
@@ -402,8 +651,11 @@ impl WarmPool {
         for key in keys {
             if let Some(mut list) = self.containers.get_mut(&key) {
                 if let Some(c_mut) = list.iter_mut().find(|c| c.instance_id == instance_id) {
-                    c_mut.state = InstanceState::WarmIdle;
+                    if c_mut.state != InstanceState::Initializing {
+                        c_mut.state = InstanceState::WarmIdle;
+                    }
                     c_mut.last_used = Instant::now();
+                    self.touch();
                     return Some((key, c_mut.container_id.clone()));
                 }
             }
@@ -411,6 +663,71 @@ impl WarmPool {
         None
     }
 
+    /// Mark a specific instance by its instance_id as Stopping, so it gets no more work
+    pub async fn mark_stopping_by_instance(&self, instance_id: &str) -> Option<(FnKey, String)> {
+        // Collect keys first to avoid nested locking
+        let keys: Vec<FnKey> = self
+            .containers
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        for key in keys {
+            if let Some(mut list) = self.containers.get_mut(&key) {
+                if let Some(c_mut) = list.iter_mut().find(|c| c.instance_id == instance_id) {
+                    c_mut.state = InstanceState::Stopping;
+                    self.touch();
+                    return Some((key, c_mut.container_id.clone()));
+                }
+            }
+        }
+        None
+    }
+
+    /// Key of the container an instance runs in
+    pub async fn instance_key(&self, instance_id: &str) -> Option<FnKey> {
+        self.containers
+            .iter()
+            .find(|entry| entry.value().iter().any(|c| c.instance_id == instance_id))
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Containers of a function under any key
+    pub async fn containers_of_function(&self, function_id: Uuid) -> Vec<WarmContainer> {
+        self.containers
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .filter(|c| c.function_id == function_id)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Mark a container Stopping so it gets no more work, unless it is running an
+    /// invocation or its prewarm. True when marked or no longer in the pool.
+    pub async fn mark_stopping_unless_busy(&self, container_id: &str) -> bool {
+        for mut entry in self.containers.iter_mut() {
+            if let Some(c) = entry
+                .value_mut()
+                .iter_mut()
+                .find(|c| c.container_id == container_id)
+            {
+                if matches!(c.state, InstanceState::Active | InstanceState::Initializing) {
+                    return false;
+                }
+                c.state = InstanceState::Stopping;
+                drop(entry);
+                self.touch();
+                return true;
+            }
+        }
+        true
+    }
+
     /// Remove container by container_id across all keys
     pub async fn remove_container_by_id(&self, container_id: &str) -> Result<(), LambdaError> {
         // Collect keys first to avoid nested locking during mutation
@@ -431,8 +748,9 @@ impl WarmPool {
                     drop(list); // Release the lock
 
                     if now_empty {
-                        self.containers.remove(&key);
+                        self.containers.remove_if(&key, |_, list| list.is_empty());
                     }
+                    self.touch();
 
                     info!("Removed container from warm pool: {}", container_id);
                     return Ok(());
@@ -454,6 +772,58 @@ impl WarmPool {
             .collect()
     }
 
+    /// Memory committed by containers that are running (stopped containers hold none)
+    pub async fn committed_memory_mb(&self) -> u64 {
+        self.containers
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .filter(|c| !c.state.releases_memory())
+                    .map(|c| c.memory_mb)
+                    .collect::<Vec<_>>()
+            })
+            .sum()
+    }
+
+    /// Idle containers that could be stopped to free memory, excluding `keep` and the
+    /// provisioned ones
+    pub async fn idle_containers_except(&self, keep: &FnKey) -> Vec<(FnKey, WarmContainer)> {
+        let provisioned = self.provisioned_ids();
+        self.containers
+            .iter()
+            .filter(|entry| entry.key() != keep)
+            .flat_map(|entry| {
+                let key = entry.key().clone();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|c| {
+                        c.state == InstanceState::WarmIdle && !provisioned.contains(&c.container_id)
+                    })
+                    .map(|c| (key.clone(), c.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Idle and active containers of every function by name, in one pass over the pool
+    pub async fn warm_states(&self) -> HashMap<String, FunctionWarmState> {
+        let mut states: HashMap<String, FunctionWarmState> = HashMap::new();
+        for entry in self.containers.iter() {
+            let state = states.entry(entry.key().function_name.clone()).or_default();
+            for c in entry.value().iter() {
+                match c.state {
+                    InstanceState::WarmIdle => state.warm_idle += 1,
+                    InstanceState::Active => state.active += 1,
+                    _ => {}
+                }
+            }
+        }
+        states
+    }
+
     /// Build a summary for a given function name across all keys (versions/envs).
     pub async fn summary_for_function(&self, function_name: &str) -> WarmPoolSummary {
         let now = Instant::now();
@@ -0,0 +1,178 @@
+use crate::queues::FnKey;
+use crate::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use chrono::Utc;
+use lambda_models::LambdaError;
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Changes arriving within this window are written as one snapshot
+pub const SNAPSHOT_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// A warm container as recorded by the last snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotEntry {
+    pub key: FnKey,
+    pub container_id: String,
+    pub instance_id: String,
+    pub function_id: Uuid,
+    pub image_ref: String,
+    pub memory_mb: u64,
+    pub state: InstanceState,
+}
+
+impl SnapshotEntry {
+    /// Back into the pool. Idle time restarts from now, since the previous run's clock is gone.
+    pub fn into_warm_container(self, state: InstanceState) -> (FnKey, WarmContainer) {
+        let now = Instant::now();
+        (
+            self.key,
+            WarmContainer {
+                container_id: self.container_id,
+                instance_id: self.instance_id,
+                function_id: self.function_id,
+                image_ref: self.image_ref,
+                memory_mb: self.memory_mb,
+                created_at: now,
+                last_used: now,
+                state,
+            },
+        )
+    }
+}
+
+/// What to do with a snapshotted container on startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreAction {
+    /// Manage it again, in this state
+    Adopt(InstanceState),
+    /// It can no longer serve its function; remove the container
+    Remove,
+    /// Docker no longer has it; drop the entry
+    Forget,
+}
+
+/// Reconcile a snapshot entry with Docker (`running` is `None` when the container is gone)
+/// and with the function as it is now: its current image and environment hash, or `None`
+/// when it was deleted. In-flight invocations died with the previous run, so running
+/// containers come back idle.
+pub fn restore_action(
+    entry: &SnapshotEntry,
+    running: Option<bool>,
+    current: Option<(&str, &str)>,
+) -> RestoreAction {
+    let Some(running) = running else {
+        return RestoreAction::Forget;
+    };
+    if matches!(
+        entry.state,
+        InstanceState::Terminated | InstanceState::Failed
+    ) {
+        return RestoreAction::Remove;
+    }
+    match current {
+        Some((image_ref, env_hash))
+            if image_ref == entry.image_ref && env_hash == entry.key.env_hash =>
+        {
+            RestoreAction::Adopt(if running {
+                InstanceState::WarmIdle
+            } else {
+                InstanceState::Stopped
+            })
+        }
+        _ => RestoreAction::Remove,
+    }
+}
+
+/// Warm pool membership persisted to SQLite, so a restarted server can adopt the
+/// containers the previous run left behind
+#[derive(Clone)]
+pub struct WarmPoolSnapshot {
+    pool: SqlitePool,
+}
+
+impl WarmPoolSnapshot {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Replace the stored snapshot with the pool's current membership
+    pub async fn save(&self, warm_pool: &WarmPool) -> Result<usize, LambdaError> {
+        let containers = warm_pool.list_all_containers().await;
+        let saved_at = Utc::now();
+        let mut tx = self.pool.begin().await.map_err(LambdaError::SqlxError)?;
+        sqlx::query("DELETE FROM warm_pool_snapshot")
+            .execute(&mut *tx)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        let mut count = 0;
+        for (key, list) in &containers {
+            for c in list {
+                sqlx::query(
+                    r#"INSERT INTO warm_pool_snapshot
+                           (container_id, instance_id, function_id, function_name, runtime,
+                            version, env_hash, image_ref, memory_mb, state, saved_at)
+                       VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+                )
+                .bind(&c.container_id)
+                .bind(&c.instance_id)
+                .bind(c.function_id)
+                .bind(&key.function_name)
+                .bind(&key.runtime)
+                .bind(&key.version)
+                .bind(&key.env_hash)
+                .bind(&c.image_ref)
+                .bind(c.memory_mb as i64)
+                .bind(c.state.as_str())
+                .bind(saved_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+                count += 1;
+            }
+        }
+        tx.commit().await.map_err(LambdaError::SqlxError)?;
+        Ok(count)
+    }
+
+    pub async fn load(&self) -> Result<Vec<SnapshotEntry>, LambdaError> {
+        let rows = sqlx::query("SELECT * FROM warm_pool_snapshot ORDER BY function_name")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+        rows.iter()
+            .map(|row| {
+                Ok(SnapshotEntry {
+                    key: FnKey {
+                        function_name: row.get("function_name"),
+                        runtime: row.get("runtime"),
+                        version: row.get("version"),
+                        env_hash: row.get("env_hash"),
+                    },
+                    container_id: row.get("container_id"),
+                    instance_id: row.get("instance_id"),
+                    function_id: row.get("function_id"),
+                    image_ref: row.get("image_ref"),
+                    memory_mb: row.get::<i64, _>("memory_mb") as u64,
+                    state: row
+                        .get::<String, _>("state")
+                        .parse()
+                        .map_err(|reason| LambdaError::InternalError { reason })?,
+                })
+            })
+            .collect()
+    }
+
+    /// Save after every membership or state change, coalescing bursts of changes
+    pub async fn run(self, warm_pool: Arc<WarmPool>) {
+        loop {
+            warm_pool.changed().await;
+            tokio::time::sleep(SNAPSHOT_DEBOUNCE).await;
+            if let Err(e) = self.save(&warm_pool).await {
+                warn!("Failed to save warm pool snapshot: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use dashmap::DashMap;
+use lambda_models::{FunctionWarmupStats, WarmupCounts, WarmupStats};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Where an invocation was placed when it was dispatched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartKind {
+    Warm,
+    Restarted,
+    Cold,
+}
+
+#[derive(Default)]
+struct Counters {
+    warm: AtomicU64,
+    restarted: AtomicU64,
+    cold: AtomicU64,
+}
+
+impl Counters {
+    fn record(&self, kind: StartKind) {
+        let counter = match kind {
+            StartKind::Warm => &self.warm,
+            StartKind::Restarted => &self.restarted,
+            StartKind::Cold => &self.cold,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> WarmupCounts {
+        WarmupCounts::new(
+            self.warm.load(Ordering::Relaxed),
+            self.restarted.load(Ordering::Relaxed),
+            self.cold.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Tracks execution environment reuse (warm hits vs container starts), per function and globally
+#[derive(Clone, Default)]
+pub struct WarmupTracker {
+    global: Arc<Counters>,
+    per_function: Arc<DashMap<String, Counters>>,
+}
+
+impl WarmupTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, function_name: &str, kind: StartKind) {
+        self.global.record(kind);
+        self.per_function
+            .entry(function_name.to_string())
+            .or_default()
+            .record(kind);
+    }
+
+    /// Drop counters for a deleted function (global totals are kept)
+    pub fn remove_function(&self, function_name: &str) {
+        self.per_function.remove(function_name);
+    }
+
+    /// Counts of one function, all zero when it was never invoked
+    pub fn function_counts(&self, function_name: &str) -> WarmupCounts {
+        self.per_function
+            .get(function_name)
+            .map(|counters| counters.snapshot())
+            .unwrap_or_default()
+    }
+
+    pub fn snapshot(&self) -> WarmupStats {
+        let mut functions: Vec<FunctionWarmupStats> = self
+            .per_function
+            .iter()
+            .map(|entry| FunctionWarmupStats {
+                function_name: entry.key().clone(),
+                counts: entry.value().snapshot(),
+            })
+            .collect();
+        functions.sort_by(|a, b| a.function_name.cmp(&b.function_name));
+
+        WarmupStats {
+            global: self.global.snapshot(),
+            functions,
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use lambda_models::{
+    DeliveryStatus, LambdaError, WebhookDelivery, WebhookEvent, WebhookEventType, WebhooksConfig,
+};
+use rand::RngCore;
+use sha2::Sha256;
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+pub const EVENT_HEADER: &str = "X-Lambdah-Event";
+pub const DELIVERY_HEADER: &str = "X-Lambdah-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Lambdah-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Lambdah-Signature";
+/// Retries never wait longer than this between attempts
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Deliveries kept per webhook; older ones are pruned as new ones finish
+pub const DELIVERY_LOG_LIMIT: i64 = 500;
+pub const DEFAULT_DELIVERY_LIST_LIMIT: u32 = 50;
+pub const MAX_WEBHOOK_SECRET_LEN: usize = 256;
+
+/// `sha256=<hex HMAC-SHA256 of "{timestamp}.{body}">`. Receivers recompute it with the
+/// webhook secret and should reject stale timestamps to stop replays.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 32 random bytes, hex encoded
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Wait before attempt `attempt + 1`, doubling from the initial backoff
+pub fn retry_backoff(config: &WebhooksConfig, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(config.initial_backoff_ms.saturating_mul(factor)).min(MAX_BACKOFF)
+}
+
+/// Server errors, throttling and timeouts may clear up; other 4xx answers will not
+pub fn is_retryable_status(status: u16) -> bool {
+    status >= 500 || status == 429 || status == 408
+}
+
+/// Whether a webhook subscribed to `events` receives `event_type`
+pub fn subscribes_to(events: &[WebhookEventType], event_type: WebhookEventType) -> bool {
+    events.is_empty() || events.contains(&event_type)
+}
+
+pub fn validate_webhook_secret(secret: &str) -> Result<(), LambdaError> {
+    if secret.is_empty() || secret.len() > MAX_WEBHOOK_SECRET_LEN {
+        return Err(LambdaError::InvalidRequest {
+            reason: format!("secret must be 1 to {MAX_WEBHOOK_SECRET_LEN} bytes"),
+        });
+    }
+    Ok(())
+}
+
+pub fn row_to_delivery(row: &sqlx::sqlite::SqliteRow) -> Result<WebhookDelivery, LambdaError> {
+    let parse_err = |reason: String| LambdaError::InternalError { reason };
+    Ok(WebhookDelivery {
+        delivery_id: row.get("delivery_id"),
+        webhook_id: row.get("webhook_id"),
+        event_id: row.get("event_id"),
+        event_type: row
+            .get::<String, _>("event_type")
+            .parse()
+            .map_err(parse_err)?,
+        status: row.get::<String, _>("status").parse().map_err(parse_err)?,
+        attempts: row.get::<i64, _>("attempts") as u32,
+        response_status: row
+            .get::<Option<i64>, _>("response_status")
+            .map(|s| s as u16),
+        error: row.get("error"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+struct Target {
+    webhook_id: Uuid,
+    url: String,
+    secret: String,
+}
+
+/// Sends lifecycle events to subscribed webhooks in the background, retrying with
+/// backoff and recording every attempt in the delivery log
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    pool: SqlitePool,
+    client: reqwest::Client,
+    config: Arc<WebhooksConfig>,
+}
+
+impl WebhookNotifier {
+    pub fn new(pool: SqlitePool, config: WebhooksConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+        Self {
+            pool,
+            client,
+            config: Arc::new(config),
+        }
+    }
+
+    /// Queue `event_type` for every enabled webhook subscribed to it; never blocks or
+    /// fails the caller
+    pub fn emit(
+        &self,
+        event_type: WebhookEventType,
+        function_name: Option<&str>,
+        detail: serde_json::Value,
+    ) {
+        let event = WebhookEvent {
+            event_id: Uuid::new_v4(),
+            event_type,
+            occurred_at: Utc::now(),
+            function_name: function_name.map(str::to_string),
+            detail,
+        };
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = notifier.dispatch(event).await {
+                warn!("Failed to dispatch webhook event: {}", e);
+            }
+        });
+    }
+
+    async fn dispatch(&self, event: WebhookEvent) -> Result<(), LambdaError> {
+        let rows =
+            sqlx::query("SELECT webhook_id, url, secret, events FROM webhooks WHERE enabled = 1")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(LambdaError::SqlxError)?;
+        let body = serde_json::to_vec(&event).map_err(|e| LambdaError::InternalError {
+            reason: format!("Failed to serialize webhook event: {e}"),
+        })?;
+
+        for row in rows {
+            let events: Vec<WebhookEventType> =
+                serde_json::from_str(&row.get::<String, _>("events")).unwrap_or_default();
+            if !subscribes_to(&events, event.event_type) {
+                continue;
+            }
+            let target = Target {
+                webhook_id: row.get("webhook_id"),
+                url: row.get("url"),
+                secret: row.get("secret"),
+            };
+            let notifier = self.clone();
+            let event = event.clone();
+            let body = body.clone();
+            // Targets are independent; a slow one must not hold up the others
+            tokio::spawn(async move {
+                if let Err(e) = notifier.deliver(&target, &event, &body).await {
+                    warn!("Failed to record webhook delivery to {}: {}", target.url, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    async fn deliver(
+        &self,
+        target: &Target,
+        event: &WebhookEvent,
+        body: &[u8],
+    ) -> Result<(), LambdaError> {
+        let delivery_id = Uuid::new_v4();
+        let now = Utc::now();
+        sqlx::query(
+            r#"INSERT INTO webhook_deliveries(delivery_id, webhook_id, event_id, event_type, status, attempts, created_at, updated_at)
+               VALUES(?, ?, ?, ?, 'pending', 0, ?, ?)"#,
+        )
+        .bind(delivery_id)
+        .bind(target.webhook_id)
+        .bind(event.event_id)
+        .bind(event.event_type.as_str())
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+
+        let max_attempts = self.config.max_attempts.max(1);
+        for attempt in 1..=max_attempts {
+            let (response_status, error) = self.attempt(target, event, delivery_id, body).await;
+            let retryable = match response_status {
+                Some(status) => is_retryable_status(status),
+                None => true,
+            };
+            let status = if error.is_none() {
+                DeliveryStatus::Succeeded
+            } else if retryable && attempt < max_attempts {
+                DeliveryStatus::Pending
+            } else {
+                DeliveryStatus::Failed
+            };
+
+            sqlx::query(
+                "UPDATE webhook_deliveries SET status = ?, attempts = ?, response_status = ?, error = ?, updated_at = ? WHERE delivery_id = ?",
+            )
+            .bind(status.as_str())
+            .bind(attempt as i64)
+            .bind(response_status.map(|s| s as i64))
+            .bind(&error)
+            .bind(Utc::now())
+            .bind(delivery_id)
+            .execute(&self.pool)
+            .await
+            .map_err(LambdaError::SqlxError)?;
+
+            if status != DeliveryStatus::Pending {
+                debug!(
+                    "Webhook delivery {} to {} finished as {:?} after {} attempt(s)",
+                    delivery_id, target.url, status, attempt
+                );
+                break;
+            }
+            tokio::time::sleep(retry_backoff(&self.config, attempt)).await;
+        }
+
+        self.prune_deliveries(target.webhook_id).await
+    }
+
+    /// One POST; the response status (if any) and an error unless it was a 2xx
+    async fn attempt(
+        &self,
+        target: &Target,
+        event: &WebhookEvent,
+        delivery_id: Uuid,
+        body: &[u8],
+    ) -> (Option<u16>, Option<String>) {
+        let timestamp = Utc::now().timestamp();
+        let result = self
+            .client
+            .post(&target.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.event_type.as_str())
+            .header(DELIVERY_HEADER, delivery_id.to_string())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                sign_payload(&target.secret, timestamp, body),
+            )
+            .body(body.to_vec())
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => {
+                (Some(response.status().as_u16()), None)
+            }
+            Ok(response) => (
+                Some(response.status().as_u16()),
+                Some(format!("target responded with HTTP {}", response.status())),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        }
+    }
+
+    async fn prune_deliveries(&self, webhook_id: Uuid) -> Result<(), LambdaError> {
+        sqlx::query(
+            r#"DELETE FROM webhook_deliveries WHERE webhook_id = ?1 AND delivery_id NOT IN (
+                   SELECT delivery_id FROM webhook_deliveries WHERE webhook_id = ?1
+                   ORDER BY created_at DESC LIMIT ?2
+               )"#,
+        )
+        .bind(webhook_id)
+        .bind(DELIVERY_LOG_LIMIT)
+        .execute(&self.pool)
+        .await
+        .map_err(LambdaError::SqlxError)?;
+        Ok(())
+    }
+}
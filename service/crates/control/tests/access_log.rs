@@ -0,0 +1,140 @@
+use crate::common::{control_plane, create_request, temp_dir};
+use lambda_control::access_log::{AccessLog, RotatingFile};
+use lambda_models::{AccessLogConfig, AccessLogEntry, CreateApiRouteRequest};
+use std::path::Path;
+use std::time::Duration;
+
+fn lines(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn entry(path: &str) -> AccessLogEntry {
+    AccessLogEntry {
+        timestamp: chrono::Utc::now(),
+        method: "GET".into(),
+        path: path.into(),
+        function_name: "orders".into(),
+        route_id: None,
+        status: 200,
+        latency_ms: 12,
+        source_ip: Some("10.0.0.7".into()),
+        request_id: Some("req-1".into()),
+    }
+}
+
+#[test]
+fn rotation_keeps_the_newest_files() {
+    let dir = temp_dir();
+    let path = dir.path().join("access.log");
+    let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+    for line in ["one", "two", "three", "four"] {
+        file.write_line(line).unwrap();
+    }
+
+    assert_eq!(lines(&path), vec!["four"]);
+    assert_eq!(lines(&dir.path().join("access.log.1")), vec!["three"]);
+    assert_eq!(lines(&dir.path().join("access.log.2")), vec!["one", "two"]);
+    assert!(!dir.path().join("access.log.3").exists());
+}
+
+#[test]
+fn lines_share_a_file_until_it_is_full() {
+    let dir = temp_dir();
+    let path = dir.path().join("access.log");
+    let mut file = RotatingFile::open(&path, 1024, 2).unwrap();
+    file.write_line("one").unwrap();
+    file.write_line("two").unwrap();
+    drop(file);
+
+    // Reopening appends to the existing file
+    let mut file = RotatingFile::open(&path, 1024, 2).unwrap();
+    file.write_line("three").unwrap();
+    assert_eq!(lines(&path), vec!["one", "two", "three"]);
+    assert!(!dir.path().join("access.log.1").exists());
+}
+
+#[tokio::test]
+async fn entries_are_written_as_json_lines() {
+    let dir = temp_dir();
+    let path = dir.path().join("logs").join("access.log");
+    let log = AccessLog::new(&AccessLogConfig {
+        enabled: true,
+        file: Some(path.to_string_lossy().into_owned()),
+        ..AccessLogConfig::default()
+    });
+    log.record(entry("/orders/1"));
+    log.record(entry("/orders/2"));
+
+    let mut written = Vec::new();
+    for _ in 0..50 {
+        written = lines(&path);
+        if written.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let parsed: Vec<AccessLogEntry> = written
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        parsed,
+        vec![
+            entry_at(&parsed[0], "/orders/1"),
+            entry_at(&parsed[1], "/orders/2")
+        ]
+    );
+}
+
+fn entry_at(written: &AccessLogEntry, path: &str) -> AccessLogEntry {
+    AccessLogEntry {
+        timestamp: written.timestamp,
+        ..entry(path)
+    }
+}
+
+#[test]
+fn disabled_log_writes_nothing() {
+    let dir = temp_dir();
+    let path = dir.path().join("access.log");
+    let log = AccessLog::new(&AccessLogConfig {
+        enabled: false,
+        file: Some(path.to_string_lossy().into_owned()),
+        ..AccessLogConfig::default()
+    });
+    assert!(!log.enabled());
+    log.record(entry("/orders/1"));
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn routes_can_opt_out_of_access_logging() {
+    let cp = control_plane().await;
+    cp.create_function(create_request("orders")).await.unwrap();
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/orders".into(),
+            method: None,
+            function_name: "orders".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+    assert!(route.access_log);
+
+    let route = cp
+        .set_api_route_access_log(route.route_id, false)
+        .await
+        .unwrap();
+    assert!(!route.access_log);
+    let matched = cp.match_api_route("GET", "/orders").await.unwrap().unwrap();
+    assert!(!matched.access_log);
+    assert!(cp
+        .set_api_route_access_log(uuid::Uuid::new_v4(), true)
+        .await
+        .is_err());
+}
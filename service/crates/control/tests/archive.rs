@@ -0,0 +1,94 @@
+use crate::common::{control_plane_with, create_request, temp_dir, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{CreateFunctionRequest, FunctionCode, FunctionState, ListFunctionsQuery};
+use lambda_testsupport::helpers::{b64, zip_dir};
+use std::path::Path;
+
+async fn control_plane(dir: &Path, idle_days: Option<u64>) -> ControlPlane {
+    let mut config = test_config();
+    config.data.dir = dir.to_string_lossy().into_owned();
+    config.archive.idle_days = idle_days;
+    control_plane_with(config).await
+}
+
+async fn create_function(cp: &ControlPlane, dir: &Path, name: &str, with_code: bool) {
+    let zip_file = with_code.then(|| {
+        let src = dir.join(format!("src-{name}"));
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("index.js"), "exports.handler = async () => 1;").unwrap();
+        b64(zip_dir(&src).unwrap())
+    });
+    cp.create_function(CreateFunctionRequest {
+        code: FunctionCode {
+            zip_file,
+            ..Default::default()
+        },
+        ..create_request(name)
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn archived_functions_keep_their_code() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path(), None).await;
+    create_function(&cp, dir.path(), "report", true).await;
+    let before = cp.get_function("report").await.unwrap();
+
+    let archived = cp
+        .archive_function("report", "The function was archived on request.")
+        .await
+        .unwrap();
+    assert_eq!(archived.state, FunctionState::Archived);
+    assert_eq!(archived.state_reason_code.as_deref(), Some("Archived"));
+    assert_eq!(archived.code_sha256, before.code_sha256);
+
+    // Archiving again leaves it as it is
+    let again = cp.archive_function("report", "other").await.unwrap();
+    assert_eq!(again.state_reason, archived.state_reason);
+
+    let listed = cp
+        .list_functions(&ListFunctionsQuery {
+            state: Some(FunctionState::Archived),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(listed.functions.len(), 1);
+    assert_eq!(cp.dashboard().await.unwrap().functions.archived, 1);
+}
+
+#[tokio::test]
+async fn functions_still_being_created_are_not_archived() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path(), None).await;
+    create_function(&cp, dir.path(), "pending", false).await;
+
+    let err = cp.archive_function("pending", "idle").await.unwrap_err();
+    assert_eq!(err.http_status(), 400, "{err}");
+}
+
+#[tokio::test]
+async fn idle_functions_are_archived_only_when_configured() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path(), None).await;
+    create_function(&cp, dir.path(), "report", true).await;
+    assert!(cp.archive_idle_functions().await.unwrap().is_empty());
+
+    let dir = temp_dir();
+    let cp = control_plane(dir.path(), Some(0)).await;
+    create_function(&cp, dir.path(), "report", true).await;
+    create_function(&cp, dir.path(), "pending", false).await;
+    assert_eq!(cp.archive_idle_functions().await.unwrap(), vec!["report"]);
+    let function = cp.get_function("report").await.unwrap();
+    assert_eq!(function.state, FunctionState::Archived);
+    assert_eq!(
+        function.state_reason.as_deref(),
+        Some("The function was not invoked for 0 days.")
+    );
+
+    // Unarchiving only rebuilds archived functions
+    let pending = cp.unarchive_function("pending").await.unwrap();
+    assert_eq!(pending.state, FunctionState::Pending);
+}
@@ -0,0 +1,252 @@
+use crate::common::{control_plane_and_pool, test_config};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::async_invocations::{next_attempt, retry_delay, AsyncInvocations, QueuedEvent};
+use lambda_control::jobs::JobStore;
+use lambda_control::migrations::run_migrations;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    AsyncInvokeConfig, FunctionError, InvocationType, InvokeRequest, InvokeResponse, JobStatus,
+    JobsConfig, LambdaError,
+};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn request(function_name: &str) -> InvokeRequest {
+    InvokeRequest {
+        function_name: function_name.into(),
+        invocation_type: InvocationType::Event,
+        log_type: None,
+        client_context: None,
+        payload: Some(serde_json::json!({ "order": 7 })),
+        qualifier: None,
+        idempotent: false,
+        trace: false,
+    }
+}
+
+fn event(attempts: u32, age_secs: i64) -> QueuedEvent {
+    QueuedEvent {
+        request_id: "req-1".into(),
+        request: request("orders"),
+        attempts,
+        enqueued_at: Utc::now() - ChronoDuration::seconds(age_secs),
+    }
+}
+
+fn response(function_error: Option<FunctionError>) -> Result<InvokeResponse, LambdaError> {
+    Ok(InvokeResponse {
+        status_code: 200,
+        payload: None,
+        executed_version: Some("$LATEST".into()),
+        function_error,
+        log_result: None,
+        headers: HashMap::new(),
+        duration_ms: Some(5),
+    })
+}
+
+fn throttled() -> Result<InvokeResponse, LambdaError> {
+    Err(LambdaError::GlobalConcurrencyLimitExceeded)
+}
+
+async fn queue() -> (SqlitePool, AsyncInvocations) {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    (pool.clone(), AsyncInvocations::new(pool))
+}
+
+#[test]
+fn function_errors_are_retried_up_to_the_limit() {
+    let config = AsyncInvokeConfig::default();
+    let failed = response(Some(FunctionError::Unhandled));
+    assert_eq!(
+        next_attempt(&config, &event(0, 0), &failed, Utc::now()),
+        Some((1, Duration::from_secs(60)))
+    );
+    assert_eq!(
+        next_attempt(&config, &event(1, 0), &failed, Utc::now()),
+        Some((2, Duration::from_secs(120)))
+    );
+    assert_eq!(
+        next_attempt(&config, &event(2, 0), &failed, Utc::now()),
+        None
+    );
+
+    let no_retries = AsyncInvokeConfig {
+        max_retry_attempts: 0,
+        ..AsyncInvokeConfig::default()
+    };
+    assert_eq!(
+        next_attempt(&no_retries, &event(0, 0), &failed, Utc::now()),
+        None
+    );
+    assert_eq!(retry_delay(&config, 3), Duration::from_secs(240));
+}
+
+#[test]
+fn only_failures_that_may_pass_are_retried() {
+    let config = AsyncInvokeConfig::default();
+    assert_eq!(
+        next_attempt(&config, &event(0, 0), &response(None), Utc::now()),
+        None
+    );
+    let missing = Err(LambdaError::FunctionNotFound {
+        function_name: "orders".into(),
+    });
+    assert_eq!(
+        next_attempt(&config, &event(0, 0), &missing, Utc::now()),
+        None
+    );
+    let failed = Err(LambdaError::InternalError {
+        reason: "container died".into(),
+    });
+    assert_eq!(
+        next_attempt(&config, &event(0, 0), &failed, Utc::now()),
+        Some((1, Duration::from_secs(60)))
+    );
+
+    // Throttles do not use up retries, but stop when the event gets too old
+    assert_eq!(
+        next_attempt(&config, &event(2, 0), &throttled(), Utc::now()),
+        Some((2, Duration::from_secs(120)))
+    );
+    assert_eq!(
+        next_attempt(&config, &event(2, 21500), &throttled(), Utc::now()),
+        None
+    );
+}
+
+#[test]
+fn settings_stay_within_what_aws_allows() {
+    assert!(AsyncInvokeConfig::default().validate().is_ok());
+    for config in [
+        AsyncInvokeConfig {
+            max_retry_attempts: 3,
+            ..AsyncInvokeConfig::default()
+        },
+        AsyncInvokeConfig {
+            max_event_age_secs: 30,
+            ..AsyncInvokeConfig::default()
+        },
+        AsyncInvokeConfig {
+            max_event_age_secs: 21601,
+            ..AsyncInvokeConfig::default()
+        },
+        AsyncInvokeConfig {
+            max_concurrent: 0,
+            ..AsyncInvokeConfig::default()
+        },
+    ] {
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("async_invoke"), "{err}");
+    }
+}
+
+#[tokio::test]
+async fn events_are_claimed_once_and_come_back_for_retries() {
+    let (_, queue) = queue().await;
+    queue.enqueue("req-1", &request("orders")).await.unwrap();
+    assert_eq!(queue.depth().await.unwrap(), 1);
+
+    let claimed = queue.claim_next().await.unwrap().unwrap();
+    assert_eq!(claimed.request_id, "req-1");
+    assert_eq!(claimed.attempts, 0);
+    assert_eq!(claimed.request.payload, request("orders").payload);
+    assert!(queue.claim_next().await.unwrap().is_none());
+
+    queue
+        .retry("req-1", 1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(queue.claim_next().await.unwrap().is_none());
+    queue.retry("req-1", 1, Duration::ZERO).await.unwrap();
+    let claimed = queue.claim_next().await.unwrap().unwrap();
+    assert_eq!(claimed.attempts, 1);
+
+    queue.remove("req-1").await.unwrap();
+    assert_eq!(queue.depth().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn queued_events_survive_a_restart() {
+    let (pool, queue) = queue().await;
+    let jobs = JobStore::new(pool.clone(), JobsConfig::default());
+    let queued = jobs.create("orders").await.unwrap();
+    queue
+        .enqueue(&queued.job_id, &request("orders"))
+        .await
+        .unwrap();
+    queue.claim_next().await.unwrap().unwrap();
+    let running = jobs.create("orders").await.unwrap();
+
+    // What a new process does on start
+    assert_eq!(jobs.fail_interrupted().await.unwrap(), 1);
+    assert_eq!(queue.release_claimed().await.unwrap(), 1);
+
+    assert_eq!(
+        jobs.get(&queued.job_id).await.unwrap().status,
+        JobStatus::Running
+    );
+    assert_eq!(
+        jobs.get(&running.job_id).await.unwrap().status,
+        JobStatus::Failed
+    );
+    let claimed = queue.claim_next().await.unwrap().unwrap();
+    assert_eq!(claimed.request_id, queued.job_id);
+}
+
+async fn finished_job(cp: &ControlPlane, request_id: &str) -> lambda_models::Job {
+    for _ in 0..100 {
+        let job = cp.get_invocation_result(request_id).await.unwrap();
+        if job.status != JobStatus::Running {
+            return job;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("Event invocation {request_id} never finished");
+}
+
+#[tokio::test]
+async fn the_worker_stores_final_results() {
+    let (cp, pool) = control_plane_and_pool(test_config()).await;
+    let err = cp.enqueue_event(request("missing")).await.unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    // The function is gone by the time the event is attempted
+    let jobs = JobStore::new(pool.clone(), JobsConfig::default());
+    let gone = jobs.create("gone").await.unwrap();
+    AsyncInvocations::new(pool.clone())
+        .enqueue(&gone.job_id, &request("gone"))
+        .await
+        .unwrap();
+    let job = finished_job(&cp, &gone.job_id).await;
+    assert_eq!(job.status, JobStatus::Failed);
+    assert!(
+        job.error_message.unwrap().contains("gone"),
+        "a missing function is not retried"
+    );
+
+    // Events older than max_event_age_secs are dropped without an attempt
+    let stale = jobs.create("orders").await.unwrap();
+    sqlx::query(
+        "INSERT INTO async_invocations (request_id, function_name, request, attempts, enqueued_at, next_attempt_at) \
+         VALUES (?, 'orders', ?, 0, ?, ?)",
+    )
+    .bind(&stale.job_id)
+    .bind(serde_json::to_string(&request("orders")).unwrap())
+    .bind(Utc::now() - ChronoDuration::hours(7))
+    .bind(Utc::now())
+    .execute(&pool)
+    .await
+    .unwrap();
+    let job = finished_job(&cp, &stale.job_id).await;
+    assert_eq!(job.status, JobStatus::Failed);
+    assert!(
+        job.error_message
+            .unwrap()
+            .contains("not processed within 21600"),
+        "stale events expire"
+    );
+    assert_eq!(cp.async_queue_depth().await.unwrap(), 0);
+}
@@ -0,0 +1,162 @@
+use crate::common::{control_plane_with, create_function, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{AwsEmulation, AwsEmulationConfig, Config, IdentityConfig};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+async fn control_plane_emulating(aws_emulation: AwsEmulationConfig) -> ControlPlane {
+    let mut config = test_config();
+    config.aws_emulation = aws_emulation;
+    control_plane_with(config).await
+}
+
+#[tokio::test]
+async fn functions_follow_the_config_until_they_have_their_own_settings() {
+    let cp = control_plane_emulating(AwsEmulationConfig {
+        enabled: true,
+        endpoint_url: Some("http://host.docker.internal:4566".into()),
+        ..Default::default()
+    })
+    .await;
+    create_function(&cp, "orders").await;
+
+    let inherited = cp.get_aws_emulation("orders").await.unwrap();
+    assert!(!inherited.overridden);
+    assert_eq!(inherited.environment["AWS_ACCESS_KEY_ID"], "test");
+    assert_eq!(inherited.environment["AWS_REGION"], "local");
+    assert_eq!(
+        inherited.environment["AWS_ENDPOINT_URL"],
+        "http://host.docker.internal:4566"
+    );
+
+    let own = cp
+        .put_aws_emulation(
+            "orders",
+            AwsEmulation {
+                access_key_id: "{function_name}-key".into(),
+                service_endpoints: BTreeMap::from([(
+                    "s3".to_string(),
+                    "http://host.docker.internal:9000".to_string(),
+                )]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(own.overridden);
+    assert_eq!(own.environment["AWS_ACCESS_KEY_ID"], "orders-key");
+    assert_eq!(
+        own.environment["AWS_ENDPOINT_URL_S3"],
+        "http://host.docker.internal:9000"
+    );
+    assert!(!own.environment.contains_key("AWS_ENDPOINT_URL"));
+    assert_eq!(cp.get_aws_emulation("orders").await.unwrap(), own);
+
+    // A function can opt out of the config's settings
+    cp.put_aws_emulation(
+        "orders",
+        AwsEmulation {
+            enabled: false,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert!(cp
+        .get_aws_emulation("orders")
+        .await
+        .unwrap()
+        .environment
+        .is_empty());
+
+    cp.delete_aws_emulation("orders").await.unwrap();
+    assert_eq!(cp.get_aws_emulation("orders").await.unwrap(), inherited);
+    assert_eq!(
+        cp.get_aws_emulation("missing")
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn emulation_is_off_by_default() {
+    let cp = control_plane_emulating(AwsEmulationConfig::default()).await;
+    create_function(&cp, "orders").await;
+    let emulation = cp.get_aws_emulation("orders").await.unwrap();
+    assert!(!emulation.settings.enabled);
+    assert!(emulation.environment.is_empty());
+}
+
+#[tokio::test]
+async fn invalid_settings_are_refused() {
+    let cp = control_plane_emulating(AwsEmulationConfig::default()).await;
+    create_function(&cp, "orders").await;
+
+    for settings in [
+        AwsEmulation {
+            access_key_id: String::new(),
+            ..Default::default()
+        },
+        AwsEmulation {
+            secret_access_key: "{function}".into(),
+            ..Default::default()
+        },
+        AwsEmulation {
+            session_token: Some("{region".into()),
+            ..Default::default()
+        },
+        AwsEmulation {
+            endpoint_url: Some("localhost:4566".into()),
+            ..Default::default()
+        },
+        AwsEmulation {
+            service_endpoints: BTreeMap::from([(
+                "S3".to_string(),
+                "http://host.docker.internal:9000".to_string(),
+            )]),
+            ..Default::default()
+        },
+    ] {
+        let err = cp.put_aws_emulation("orders", settings).await.unwrap_err();
+        assert_eq!(err.http_status(), 400, "{err}");
+    }
+    assert!(!cp.get_aws_emulation("orders").await.unwrap().overridden);
+
+    // The config section is checked at startup
+    let mut config = Config::default();
+    config.aws_emulation.endpoint_url = Some("ftp://emulator".into());
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    assert!(ControlPlane::new(pool, invoker, config).await.is_err());
+}
+
+#[test]
+fn placeholders_are_filled_from_the_identity() {
+    let identity = IdentityConfig {
+        region: "eu-south-1".into(),
+        account_id: "123456789012".into(),
+        ..Default::default()
+    };
+    let settings = AwsEmulation {
+        secret_access_key: "{account_id}/{function_name}".into(),
+        endpoint_url: Some("http://{region}.emulator:4566".into()),
+        service_endpoints: BTreeMap::from([(
+            "secrets-manager".to_string(),
+            "http://secrets:4566".to_string(),
+        )]),
+        ..Default::default()
+    };
+    settings.validate().unwrap();
+    let env = settings.environment("orders", &identity);
+    assert_eq!(env["AWS_SECRET_ACCESS_KEY"], "123456789012/orders");
+    assert_eq!(env["AWS_ENDPOINT_URL"], "http://eu-south-1.emulator:4566");
+    assert_eq!(env["AWS_DEFAULT_REGION"], "eu-south-1");
+    assert_eq!(
+        env["AWS_ENDPOINT_URL_SECRETS_MANAGER"],
+        "http://secrets:4566"
+    );
+    assert!(!env.contains_key("AWS_SESSION_TOKEN"));
+}
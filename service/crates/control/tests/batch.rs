@@ -0,0 +1,132 @@
+use crate::common::{control_plane, create_function_with_env};
+use lambda_models::{
+    BatchFunctionsRequest, BatchOperation, EnvironmentValue, FunctionSelector, LambdaError,
+};
+use std::collections::HashMap;
+
+fn selector(names: &[&str], name_prefix: Option<&str>) -> FunctionSelector {
+    FunctionSelector {
+        names: names.iter().map(|n| n.to_string()).collect(),
+        name_prefix: name_prefix.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn configuration_is_updated_per_function_with_missing_ones_reported() {
+    let cp = control_plane().await;
+    for name in ["orders-api", "orders-worker", "billing"] {
+        create_function_with_env(&cp, name, &[("STAGE", "dev")]).await;
+    }
+
+    let response = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: selector(&["ghost"], Some("orders-")),
+                operation: BatchOperation::UpdateConfiguration {
+                    memory_size: Some(1024),
+                    timeout: None,
+                },
+            },
+            Some("ops"),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!((response.succeeded, response.failed), (2, 1));
+    let statuses: Vec<(&str, u16)> = response
+        .results
+        .iter()
+        .map(|r| (r.function_name.as_str(), r.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [("ghost", 404), ("orders-api", 200), ("orders-worker", 200)]
+    );
+    for name in ["orders-api", "orders-worker"] {
+        let function = cp.get_function(name).await.unwrap();
+        assert_eq!(function.memory_size, 1024);
+        assert_eq!(function.environment["STAGE"], "dev");
+    }
+    assert_ne!(cp.get_function("billing").await.unwrap().memory_size, 1024);
+}
+
+#[tokio::test]
+async fn environment_is_patched_and_functions_deleted() {
+    let cp = control_plane().await;
+    for name in ["a", "b", "c"] {
+        create_function_with_env(&cp, name, &[("STAGE", "dev")]).await;
+    }
+
+    let response = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: selector(&["a", "b"], None),
+                operation: BatchOperation::PatchEnvironment {
+                    variables: HashMap::from([
+                        (
+                            "LOG_LEVEL".into(),
+                            Some(EnvironmentValue::Plain("debug".into())),
+                        ),
+                        ("STAGE".into(), None),
+                    ]),
+                },
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.failed, 0);
+    let environment = cp.get_function("b").await.unwrap().environment;
+    assert_eq!(
+        environment,
+        HashMap::from([("LOG_LEVEL".to_string(), "debug".to_string())])
+    );
+
+    let response = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: selector(&["a", "c"], None),
+                operation: BatchOperation::Delete,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.succeeded, 2);
+    assert!(response.results.iter().all(|r| r.status == 204));
+    assert!(cp.get_function("a").await.is_err());
+    assert!(cp.get_function("b").await.is_ok());
+}
+
+#[tokio::test]
+async fn batches_without_a_selection_or_valid_settings_are_refused() {
+    let cp = control_plane().await;
+    create_function_with_env(&cp, "orders", &[("STAGE", "dev")]).await;
+
+    let empty = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: selector(&[], Some("")),
+                operation: BatchOperation::Delete,
+            },
+            None,
+        )
+        .await;
+    assert!(matches!(empty, Err(LambdaError::InvalidRequest { .. })));
+
+    let too_much = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: selector(&["orders"], None),
+                operation: BatchOperation::UpdateConfiguration {
+                    memory_size: None,
+                    timeout: Some(901),
+                },
+            },
+            None,
+        )
+        .await;
+    assert!(matches!(too_much, Err(LambdaError::InvalidRequest { .. })));
+    assert_eq!(cp.get_function("orders").await.unwrap().timeout, 3);
+}
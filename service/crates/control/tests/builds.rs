@@ -0,0 +1,224 @@
+use lambda_control::builds::{BuildQueue, FailedBuilds};
+use lambda_models::{
+    Build, BuildFailureKind, BuildPriority, BuildStatus, Function, FunctionState, LambdaError,
+    LastUpdateStatus, PackageType,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Wait until the queue holds builds in `statuses`, in list order
+async fn wait_for(queue: &BuildQueue, statuses: &[(&str, BuildStatus)]) -> Vec<Build> {
+    for _ in 0..200 {
+        let builds = queue.list();
+        let listed: Vec<_> = builds
+            .iter()
+            .map(|b| (b.function_name.as_str(), b.status))
+            .collect();
+        if listed == statuses {
+            return builds;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("builds never became {statuses:?}: {:?}", queue.list());
+}
+
+fn spawn_build(
+    queue: &BuildQueue,
+    name: &'static str,
+    priority: BuildPriority,
+    started: Arc<Mutex<Vec<&'static str>>>,
+    release: oneshot::Receiver<()>,
+) -> tokio::task::JoinHandle<Result<(), LambdaError>> {
+    let queue = queue.clone();
+    tokio::spawn(async move {
+        queue
+            .run(
+                name,
+                &format!("lambda-home/{name}:abc"),
+                priority,
+                async move {
+                    started.lock().unwrap().push(name);
+                    let _ = release.await;
+                    Ok(())
+                },
+            )
+            .await
+    })
+}
+
+#[tokio::test]
+async fn urgent_builds_jump_the_queue() {
+    let queue = BuildQueue::new(1);
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let (release_a, a) = oneshot::channel();
+    let (release_b, b) = oneshot::channel();
+    let (release_c, c) = oneshot::channel();
+
+    let first = spawn_build(&queue, "a", BuildPriority::Normal, started.clone(), a);
+    wait_for(&queue, &[("a", BuildStatus::Running)]).await;
+    let normal = spawn_build(&queue, "b", BuildPriority::Normal, started.clone(), b);
+    wait_for(
+        &queue,
+        &[("a", BuildStatus::Running), ("b", BuildStatus::Queued)],
+    )
+    .await;
+    let urgent = spawn_build(&queue, "c", BuildPriority::Urgent, started.clone(), c);
+    wait_for(
+        &queue,
+        &[
+            ("a", BuildStatus::Running),
+            ("c", BuildStatus::Queued),
+            ("b", BuildStatus::Queued),
+        ],
+    )
+    .await;
+
+    release_a.send(()).unwrap();
+    first.await.unwrap().unwrap();
+    wait_for(
+        &queue,
+        &[("c", BuildStatus::Running), ("b", BuildStatus::Queued)],
+    )
+    .await;
+    release_c.send(()).unwrap();
+    urgent.await.unwrap().unwrap();
+    release_b.send(()).unwrap();
+    normal.await.unwrap().unwrap();
+
+    assert_eq!(*started.lock().unwrap(), ["a", "c", "b"]);
+    assert!(queue.list().is_empty());
+}
+
+#[tokio::test]
+async fn queued_and_running_builds_can_be_cancelled() {
+    let queue = BuildQueue::new(1);
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let (_release_a, a) = oneshot::channel();
+    let (_release_b, b) = oneshot::channel();
+
+    let running = spawn_build(&queue, "a", BuildPriority::Normal, started.clone(), a);
+    wait_for(&queue, &[("a", BuildStatus::Running)]).await;
+    let queued = spawn_build(&queue, "b", BuildPriority::Normal, started.clone(), b);
+    let builds = wait_for(
+        &queue,
+        &[("a", BuildStatus::Running), ("b", BuildStatus::Queued)],
+    )
+    .await;
+
+    queue.cancel(&builds[1].build_id).unwrap();
+    assert!(matches!(
+        queued.await.unwrap(),
+        Err(LambdaError::BuildCancelled { .. })
+    ));
+    let cancelled = queue.cancel(&builds[0].build_id).unwrap();
+    assert_eq!(cancelled.status, BuildStatus::Running);
+    assert!(matches!(
+        running.await.unwrap(),
+        Err(LambdaError::BuildCancelled { .. })
+    ));
+
+    assert_eq!(*started.lock().unwrap(), ["a"]);
+    assert!(queue.list().is_empty());
+    let err = queue.cancel(&builds[0].build_id).unwrap_err();
+    assert_eq!(err.http_status(), 404);
+}
+
+fn function() -> Function {
+    Function {
+        function_id: uuid::Uuid::new_v4(),
+        function_name: "orders".into(),
+        runtime: "nodejs22.x".into(),
+        role: None,
+        handler: "index.handler".into(),
+        code_sha256: "sha".into(),
+        description: None,
+        timeout: 3,
+        memory_size: 128,
+        environment: Default::default(),
+        last_modified: chrono::Utc::now(),
+        code_size: 0,
+        version: "1".into(),
+        state: FunctionState::Active,
+        state_reason: None,
+        state_reason_code: None,
+        last_update_status: LastUpdateStatus::Successful,
+        last_update_status_reason: None,
+        last_update_status_reason_code: None,
+        metadata: None,
+        function_arn: None,
+        package_type: PackageType::Zip,
+        image_uri: None,
+    }
+}
+
+fn build_failure(kind: BuildFailureKind) -> LambdaError {
+    LambdaError::ImageBuildFailed {
+        function_name: "orders".into(),
+        kind,
+        summary: "npm ERR! 404 Not Found".into(),
+        build_log: "npm ERR! 404 Not Found".into(),
+    }
+}
+
+#[test]
+fn failed_builds_are_not_retried_until_the_function_changes() {
+    let failed = FailedBuilds::new(Duration::from_secs(300));
+    let mut function = function();
+    let image = "lambda-home/orders:sha";
+    failed.record(
+        &function,
+        image,
+        Err(&build_failure(BuildFailureKind::StepFailed)),
+    );
+
+    let err = failed.known_failure(&function, image).unwrap();
+    assert_eq!(err.http_status(), 502);
+    assert_eq!(
+        err.to_error_shape().build_log.as_deref(),
+        Some("npm ERR! 404 Not Found")
+    );
+    assert!(failed
+        .known_failure(&function, "lambda-home/orders:other")
+        .is_none());
+
+    function.last_modified += chrono::Duration::seconds(1);
+    assert!(failed.known_failure(&function, image).is_none());
+}
+
+#[test]
+fn transient_failures_and_successes_are_not_remembered() {
+    let failed = FailedBuilds::new(Duration::from_secs(300));
+    let function = function();
+    let image = "lambda-home/orders:sha";
+    failed.record(
+        &function,
+        image,
+        Err(&build_failure(BuildFailureKind::DaemonUnavailable)),
+    );
+    assert!(failed.known_failure(&function, image).is_none());
+
+    failed.record(
+        &function,
+        image,
+        Err(&build_failure(BuildFailureKind::BaseImagePull)),
+    );
+    failed.forget_function("orders");
+    assert!(failed.known_failure(&function, image).is_none());
+
+    failed.record(
+        &function,
+        image,
+        Err(&build_failure(BuildFailureKind::BaseImagePull)),
+    );
+    failed.record(&function, image, Ok(()));
+    assert!(failed.known_failure(&function, image).is_none());
+
+    let disabled = FailedBuilds::new(Duration::ZERO);
+    disabled.record(
+        &function,
+        image,
+        Err(&build_failure(BuildFailureKind::StepFailed)),
+    );
+    assert!(disabled.known_failure(&function, image).is_none());
+}
@@ -0,0 +1,190 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::canary::{matches_canary, RouteMetricsTracker};
+use lambda_models::{CanaryMatch, CreateApiRouteRequest, LambdaError, RouteCanary, RouteTarget};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn canary(function_name: &str, condition: CanaryMatch) -> RouteCanary {
+    RouteCanary {
+        function_name: function_name.into(),
+        qualifier: None,
+        condition,
+    }
+}
+
+#[test]
+fn header_and_cookie_conditions_match_exact_values() {
+    let header = CanaryMatch::Header {
+        name: "x-canary".into(),
+        value: "1".into(),
+    };
+    assert!(matches_canary(&header, &headers(&[("x-canary", "1")])));
+    assert!(matches_canary(&header, &headers(&[("X-Canary", "1")])));
+    assert!(!matches_canary(&header, &headers(&[("x-canary", "2")])));
+    assert!(!matches_canary(&header, &headers(&[])));
+
+    let cookie = CanaryMatch::Cookie {
+        name: "variant".into(),
+        value: "b".into(),
+    };
+    assert!(matches_canary(
+        &cookie,
+        &headers(&[("cookie", "session=abc; variant=b")])
+    ));
+    assert!(!matches_canary(
+        &cookie,
+        &headers(&[("cookie", "session=abc; variant=a")])
+    ));
+    assert!(!matches_canary(&cookie, &headers(&[("variant", "b")])));
+}
+
+#[test]
+fn percentage_bounds_route_all_or_nothing() {
+    let none = CanaryMatch::Percentage { percent: 0.0 };
+    let all = CanaryMatch::Percentage { percent: 100.0 };
+    for _ in 0..100 {
+        assert!(!matches_canary(&none, &headers(&[])));
+        assert!(matches_canary(&all, &headers(&[])));
+    }
+}
+
+#[test]
+fn metrics_are_split_per_target() {
+    let tracker = RouteMetricsTracker::new();
+    let route_id = Uuid::new_v4();
+    tracker.record(route_id, RouteTarget::Primary, false, 10);
+    tracker.record(route_id, RouteTarget::Primary, false, 30);
+    tracker.record(route_id, RouteTarget::Canary, true, 50);
+    tracker.record(route_id, RouteTarget::Canary, false, 70);
+
+    let metrics = tracker.snapshot(route_id);
+    assert_eq!(metrics.primary.requests, 2);
+    assert_eq!(metrics.primary.errors, 0);
+    assert_eq!(metrics.primary.avg_latency_ms, 20.0);
+    assert_eq!(metrics.canary.requests, 2);
+    assert_eq!(metrics.canary.error_rate, 0.5);
+    assert_eq!(metrics.canary.max_latency_ms, 70);
+
+    tracker.remove_route(route_id);
+    assert_eq!(tracker.snapshot(route_id).canary.requests, 0);
+}
+
+#[tokio::test]
+async fn canary_is_stored_with_the_route() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    create_function(&cp, "orders-next").await;
+
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/orders".into(),
+            method: None,
+            function_name: "orders".into(),
+            canary: Some(canary(
+                "orders-next",
+                CanaryMatch::Header {
+                    name: " X-Canary ".into(),
+                    value: "1".into(),
+                },
+            )),
+        })
+        .await
+        .unwrap();
+    let expected = canary(
+        "orders-next",
+        CanaryMatch::Header {
+            name: "x-canary".into(),
+            value: "1".into(),
+        },
+    );
+    assert_eq!(route.canary.as_ref(), Some(&expected));
+
+    let matched = cp
+        .match_api_route("GET", "/orders/42")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(matched.route_id, route.route_id);
+    assert_eq!(matched.canary, Some(expected));
+
+    let updated = cp
+        .put_api_route_canary(
+            route.route_id,
+            canary("orders-next", CanaryMatch::Percentage { percent: 5.0 }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        updated.canary.unwrap().condition,
+        CanaryMatch::Percentage { percent: 5.0 }
+    );
+
+    cp.record_route_request(route.route_id, RouteTarget::Canary, true, 12);
+    let metrics = cp.api_route_metrics(route.route_id).await.unwrap();
+    assert_eq!(metrics.canary.errors, 1);
+
+    cp.delete_api_route_canary(route.route_id).await.unwrap();
+    assert!(cp
+        .get_api_route(route.route_id)
+        .await
+        .unwrap()
+        .canary
+        .is_none());
+    let metrics = cp.api_route_metrics(route.route_id).await.unwrap();
+    assert_eq!(metrics.canary.requests, 0);
+}
+
+#[tokio::test]
+async fn invalid_canaries_are_rejected() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/orders".into(),
+            method: None,
+            function_name: "orders".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+
+    let err = cp
+        .put_api_route_canary(
+            route.route_id,
+            canary("missing", CanaryMatch::Percentage { percent: 5.0 }),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, LambdaError::FunctionNotFound { .. }));
+
+    for condition in [
+        CanaryMatch::Percentage { percent: 120.0 },
+        CanaryMatch::Percentage { percent: -1.0 },
+        CanaryMatch::Cookie {
+            name: " ".into(),
+            value: "b".into(),
+        },
+    ] {
+        let err = cp
+            .put_api_route_canary(route.route_id, canary("orders", condition))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LambdaError::InvalidRequest { .. }));
+    }
+
+    let err = cp
+        .put_api_route_canary(
+            Uuid::new_v4(),
+            canary("orders", CanaryMatch::Percentage { percent: 5.0 }),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, LambdaError::InvalidRequest { .. }));
+}
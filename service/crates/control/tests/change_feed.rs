@@ -0,0 +1,181 @@
+use crate::common::{control_plane, control_plane_and_pool, create_function_with_env, test_config};
+use lambda_control::change_feed::{ChangeCursor, ChangeFeed};
+use lambda_models::{
+    ChangeEntity, ChangeEventName, ChangeFeedConfig, CreateApiRouteRequest,
+    UpdateFunctionConfigurationRequest,
+};
+use std::time::Duration;
+
+#[test]
+fn cursors_parse() {
+    assert_eq!(
+        ChangeCursor::parse(None).unwrap(),
+        ChangeCursor::TrimHorizon
+    );
+    assert_eq!(
+        ChangeCursor::parse(Some("latest")).unwrap(),
+        ChangeCursor::Latest
+    );
+    assert_eq!(
+        ChangeCursor::parse(Some("42")).unwrap(),
+        ChangeCursor::After(42)
+    );
+    assert_eq!(
+        ChangeCursor::parse(Some("-1")).unwrap_err().http_status(),
+        400
+    );
+}
+
+#[tokio::test]
+async fn records_row_changes_in_commit_order() {
+    let cp = control_plane().await;
+    let feed = cp.change_feed();
+    create_function_with_env(&cp, "orders", &[("STAGE", "dev")]).await;
+    cp.update_function_configuration(
+        "orders",
+        UpdateFunctionConfigurationRequest {
+            role: None,
+            handler: None,
+            description: Some("Order intake".into()),
+            timeout: None,
+            memory_size: None,
+            environment: None,
+        },
+    )
+    .await
+    .unwrap();
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/orders".into(),
+            method: Some("POST".into()),
+            function_name: "orders".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+
+    let page = feed
+        .read(ChangeCursor::TrimHorizon, 100, &[], Duration::ZERO)
+        .await
+        .unwrap();
+    assert!(!page.has_more);
+    let inserted = &page.events[0];
+    assert_eq!(inserted.entity, ChangeEntity::Function);
+    assert_eq!(inserted.entity_id, "orders");
+    assert_eq!(inserted.event_name, ChangeEventName::Insert);
+    assert!(inserted.old_image.is_none());
+    // The environment column is stored as JSON and kept structured in the image
+    assert_eq!(
+        inserted.new_image.as_ref().unwrap()["environment"]["STAGE"],
+        "dev"
+    );
+    assert!(page
+        .events
+        .windows(2)
+        .all(|pair| pair[0].sequence < pair[1].sequence));
+
+    let modified = page
+        .events
+        .iter()
+        .find(|e| {
+            e.event_name == ChangeEventName::Modify
+                && e.new_image.as_ref().unwrap()["description"] == "Order intake"
+        })
+        .expect("configuration update is recorded");
+    assert!(modified.old_image.as_ref().unwrap()["description"].is_null());
+
+    let last = page.events.last().unwrap();
+    assert_eq!(last.entity, ChangeEntity::Route);
+    assert_eq!(last.entity_id, route.route_id.to_string());
+    assert_eq!(page.next_cursor, last.sequence.to_string());
+
+    // Paging and entity filters
+    let first = feed
+        .read(ChangeCursor::TrimHorizon, 1, &[], Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(first.events.len(), 1);
+    assert!(first.has_more);
+    let routes_only = feed
+        .read(
+            ChangeCursor::TrimHorizon,
+            100,
+            &[ChangeEntity::Route],
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+    assert_eq!(routes_only.events.len(), 1);
+
+    cp.delete_api_route(route.route_id).await.unwrap();
+    cp.delete_function("orders").await.unwrap();
+    let after = feed
+        .read(
+            ChangeCursor::parse(Some(&page.next_cursor)).unwrap(),
+            100,
+            &[ChangeEntity::Function],
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+    let removed = after.events.last().unwrap();
+    assert_eq!(removed.event_name, ChangeEventName::Remove);
+    assert!(removed.new_image.is_none());
+    assert_eq!(
+        removed.old_image.as_ref().unwrap()["function_name"],
+        "orders"
+    );
+}
+
+#[tokio::test]
+async fn waiting_reads_return_new_events() {
+    let cp = control_plane().await;
+    let feed = cp.change_feed();
+    create_function_with_env(&cp, "before", &[("STAGE", "dev")]).await;
+
+    let latest = feed
+        .read(ChangeCursor::Latest, 100, &[], Duration::ZERO)
+        .await
+        .unwrap();
+    assert!(latest.events.is_empty());
+    let cursor = ChangeCursor::parse(Some(&latest.next_cursor)).unwrap();
+
+    let reader = feed.clone();
+    let waiting = tokio::spawn(async move {
+        reader
+            .read(cursor, 100, &[], Duration::from_secs(10))
+            .await
+            .unwrap()
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    create_function_with_env(&cp, "after", &[("STAGE", "dev")]).await;
+
+    let page = tokio::time::timeout(Duration::from_secs(3), waiting)
+        .await
+        .expect("waiting read should return once a change is recorded")
+        .unwrap();
+    assert_eq!(page.events[0].entity_id, "after");
+}
+
+#[tokio::test]
+async fn cursors_expire_once_their_events_are_trimmed() {
+    let (cp, pool) = control_plane_and_pool(test_config()).await;
+    create_function_with_env(&cp, "old", &[("STAGE", "dev")]).await;
+
+    let feed = ChangeFeed::new(pool, ChangeFeedConfig { retention_hours: 0 });
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    assert!(feed.prune().await.unwrap() > 0);
+
+    let err = feed
+        .read(ChangeCursor::After(0), 100, &[], Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 410);
+    // Starting over from the trim horizon still works
+    let page = feed
+        .read(ChangeCursor::TrimHorizon, 100, &[], Duration::ZERO)
+        .await
+        .unwrap();
+    assert!(page.events.is_empty());
+    assert_ne!(page.next_cursor, "0");
+}
@@ -1,6 +1,11 @@
 use lambda_control::queues::FnKey;
+use lambda_control::registry::ControlPlane;
 use lambda_control::work_item::{FunctionMeta, WorkItem};
+use lambda_models::{Config, CreateFunctionRequest, Function};
+use sqlx::SqlitePool;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
 
 #[allow(dead_code)]
 pub fn now_ms() -> i64 {
@@ -48,3 +53,73 @@ pub fn fn_key_from_meta() -> FnKey {
     let w = wi("test-key");
     FnKey::from_work_item(&w)
 }
+
+/// Scratch directory, removed with everything in it when dropped
+#[allow(dead_code)]
+pub fn temp_dir() -> TempDir {
+    tempfile::Builder::new().prefix("lh-").tempdir().unwrap()
+}
+
+/// Config for a control plane that starts no containers on its own
+#[allow(dead_code)]
+pub fn test_config() -> Config {
+    let mut config = Config::default();
+    config.warmup.enabled = false;
+    config
+}
+
+#[allow(dead_code)]
+pub async fn control_plane() -> ControlPlane {
+    control_plane_with(test_config()).await
+}
+
+#[allow(dead_code)]
+pub async fn control_plane_with(config: Config) -> ControlPlane {
+    control_plane_and_pool(config).await.0
+}
+
+/// Control plane on an in-memory database, which the test may also query
+#[allow(dead_code)]
+pub async fn control_plane_and_pool(config: Config) -> (ControlPlane, SqlitePool) {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    let cp = ControlPlane::new(pool.clone(), invoker, config)
+        .await
+        .unwrap();
+    (cp, pool)
+}
+
+/// A Node function without code; override fields with `..create_request(name)`
+#[allow(dead_code)]
+pub fn create_request(name: &str) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        function_name: name.into(),
+        runtime: "nodejs22.x".into(),
+        handler: "index.handler".into(),
+        ..Default::default()
+    }
+}
+
+#[allow(dead_code)]
+pub async fn create_function(cp: &ControlPlane, name: &str) -> Function {
+    cp.create_function(create_request(name)).await.unwrap()
+}
+
+/// Like [`create_function`], with `env` as the function's environment
+#[allow(dead_code)]
+pub async fn create_function_with_env(
+    cp: &ControlPlane,
+    name: &str,
+    env: &[(&str, &str)],
+) -> Function {
+    cp.create_function(CreateFunctionRequest {
+        environment: Some(
+            env.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        ),
+        ..create_request(name)
+    })
+    .await
+    .unwrap()
+}
@@ -1,4 +1,11 @@
-use lambda_control::concurrency::Concurrency;
+use crate::common::create_request;
+use lambda_control::concurrency::{Concurrency, ConcurrencyManager};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    ConcurrencyConfig, Config, Function, FunctionState, LastUpdateStatus, PackageType,
+};
+use sqlx::SqlitePool;
+use std::sync::Arc;
 use tokio::time::{timeout, Duration};
 
 #[tokio::test]
@@ -27,3 +34,143 @@ async fn raii_permit_released_on_drop() {
         .unwrap();
     drop(guard);
 }
+
+fn function(name: &str) -> Function {
+    Function {
+        function_id: uuid::Uuid::new_v4(),
+        function_name: name.into(),
+        runtime: "nodejs22.x".into(),
+        role: None,
+        handler: "index.handler".into(),
+        code_sha256: "sha".into(),
+        description: None,
+        timeout: 3,
+        memory_size: 128,
+        environment: Default::default(),
+        last_modified: chrono::Utc::now(),
+        code_size: 0,
+        version: "1".into(),
+        state: FunctionState::Active,
+        state_reason: None,
+        state_reason_code: None,
+        last_update_status: LastUpdateStatus::Successful,
+        last_update_status_reason: None,
+        last_update_status_reason_code: None,
+        metadata: None,
+        function_arn: None,
+        package_type: PackageType::Zip,
+        image_uri: None,
+    }
+}
+
+#[tokio::test]
+async fn usage_counts_running_and_waiting_invocations() {
+    let manager = ConcurrencyManager::with_max_concurrency(10);
+    let orders = function("orders");
+    manager.set_reserved_limit(&orders, Some(2));
+
+    let first = manager.acquire_token(&orders).await.unwrap();
+    let _second = manager.try_acquire_token(&orders).unwrap();
+    let waiter = {
+        let manager = manager.clone();
+        let orders = orders.clone();
+        tokio::spawn(async move { manager.acquire_token(&orders).await.map(|_| ()) })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let usage = manager.usage(&orders);
+    assert_eq!(usage.in_flight, 2);
+    assert_eq!(usage.waiting, 1);
+    assert_eq!(usage.reserved_concurrent_executions, Some(2));
+    assert_eq!(usage.utilization, 1.0);
+    // Reserved invocations do not take from the shared pool
+    assert_eq!(usage.unreserved_in_flight, 0);
+
+    drop(first);
+    timeout(Duration::from_secs(1), waiter)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let usage = manager.usage(&orders);
+    assert_eq!((usage.in_flight, usage.waiting), (1, 0));
+    assert_eq!(usage.utilization, 0.5);
+}
+
+#[tokio::test]
+async fn unreserved_functions_share_the_global_limit() {
+    let manager = ConcurrencyManager::with_max_concurrency(4);
+    let (orders, users) = (function("orders"), function("users"));
+    let _a = manager.acquire_token(&orders).await.unwrap();
+    let _b = manager.acquire_token(&users).await.unwrap();
+
+    let usage = manager.usage(&orders);
+    assert_eq!(usage.in_flight, 1);
+    assert_eq!(usage.reserved_concurrent_executions, None);
+    assert_eq!(usage.unreserved_concurrency_limit, 4);
+    assert_eq!(usage.unreserved_in_flight, 2);
+    assert_eq!(usage.utilization, 0.5);
+}
+
+#[tokio::test]
+async fn overview_lists_running_and_reserved_functions() {
+    let manager = ConcurrencyManager::with_max_concurrency(4);
+    let (orders, users, idle) = (function("orders"), function("users"), function("idle"));
+    manager.set_reserved_limit(&orders, Some(5));
+    let guard = manager.acquire_token(&users).await.unwrap();
+    drop(manager.acquire_token(&idle).await.unwrap());
+
+    let overview = manager.overview();
+    let names: Vec<_> = overview
+        .functions
+        .iter()
+        .map(|usage| usage.function_name.as_str())
+        .collect();
+    assert_eq!(names, ["orders", "users"]);
+    assert_eq!(overview.unreserved_in_flight, 1);
+
+    drop(guard);
+    manager.set_reserved_limit(&orders, None);
+    assert!(manager.overview().functions.is_empty());
+}
+
+#[tokio::test]
+async fn reserved_concurrency_is_restored_at_startup() {
+    let mut config = Config::default();
+    config.warmup.enabled = false;
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    let cp = ControlPlane::new(pool.clone(), invoker.clone(), config.clone())
+        .await
+        .unwrap();
+    cp.create_function(create_request("orders")).await.unwrap();
+    cp.put_concurrency(
+        "orders",
+        ConcurrencyConfig {
+            reserved_concurrent_executions: Some(3),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        cp.concurrency_usage("orders")
+            .await
+            .unwrap()
+            .reserved_concurrent_executions,
+        Some(3)
+    );
+
+    let restarted = ControlPlane::new(pool, invoker, config).await.unwrap();
+    let usage = restarted.concurrency_usage("orders").await.unwrap();
+    assert_eq!(usage.reserved_concurrent_executions, Some(3));
+    assert_eq!(usage.in_flight, 0);
+    assert_eq!(usage.utilization, 0.0);
+    assert_eq!(
+        restarted
+            .concurrency_usage("missing")
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+}
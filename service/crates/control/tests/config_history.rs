@@ -0,0 +1,144 @@
+use crate::common::{control_plane, control_plane_and_pool, create_function_with_env, test_config};
+use lambda_control::config_history::diff_configs;
+use lambda_models::{
+    ConfigChangeSource, FunctionConfigSnapshot, UpdateFunctionConfigurationRequest,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn update(
+    timeout: Option<u64>,
+    environment: Option<HashMap<String, String>>,
+) -> UpdateFunctionConfigurationRequest {
+    UpdateFunctionConfigurationRequest {
+        role: None,
+        handler: None,
+        description: None,
+        timeout,
+        memory_size: None,
+        environment,
+    }
+}
+
+fn snapshot(timeout: u64, environment: &[(&str, &str)]) -> FunctionConfigSnapshot {
+    FunctionConfigSnapshot {
+        role: None,
+        handler: "index.handler".into(),
+        description: None,
+        timeout,
+        memory_size: 512,
+        environment: environment
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn diffs_list_settings_then_sorted_environment_variables() {
+    let old = snapshot(3, &[("STAGE", "dev"), ("API_KEY", "a"), ("DEBUG", "1")]);
+    let new = snapshot(30, &[("STAGE", "prod"), ("API_KEY", "a"), ("REGION", "eu")]);
+    let changes = diff_configs(Some(&old), &new);
+    let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        [
+            "timeout",
+            "environment.DEBUG",
+            "environment.REGION",
+            "environment.STAGE"
+        ]
+    );
+    assert_eq!(changes[0].old, Some(json!(3)));
+    assert_eq!(changes[0].new, Some(json!(30)));
+    assert_eq!(changes[1].new, None);
+    assert_eq!(changes[2].old, None);
+
+    assert!(diff_configs(Some(&old), &old).is_empty());
+    // A first revision lists every setting that has a value
+    let created = diff_configs(None, &old);
+    assert!(created.iter().all(|c| c.old.is_none()));
+    assert!(!created.iter().any(|c| c.field == "role"));
+}
+
+#[tokio::test]
+async fn records_create_and_updates_with_actor() {
+    let cp = control_plane().await;
+    create_function_with_env(&cp, "orders", &[("STAGE", "dev")]).await;
+    cp.update_function_configuration_by("orders", update(Some(10), None), Some("alice"))
+        .await
+        .unwrap();
+    // Updates that change nothing are not recorded
+    cp.update_function_configuration("orders", update(Some(10), None))
+        .await
+        .unwrap();
+
+    let history = cp.config_history("orders").await.unwrap();
+    assert_eq!(history.revisions.len(), 2);
+    let latest = &history.revisions[0];
+    assert_eq!(latest.revision, 2);
+    assert_eq!(latest.source, ConfigChangeSource::Update);
+    assert_eq!(latest.changed_by.as_deref(), Some("alice"));
+    assert_eq!(latest.changes.len(), 1);
+    assert_eq!(latest.changes[0].field, "timeout");
+    assert_eq!(latest.configuration.timeout, 10);
+
+    let created = &history.revisions[1];
+    assert_eq!(created.revision, 1);
+    assert_eq!(created.source, ConfigChangeSource::Create);
+    assert_eq!(created.changed_by, None);
+    assert_eq!(created.configuration.environment["STAGE"], "dev");
+}
+
+#[tokio::test]
+async fn reverting_restores_a_revision_as_a_new_one() {
+    let cp = control_plane().await;
+    create_function_with_env(&cp, "orders", &[("STAGE", "dev")]).await;
+    cp.update_function_configuration(
+        "orders",
+        update(
+            Some(30),
+            Some(HashMap::from([("STAGE".into(), "prod".into())])),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let function = cp
+        .revert_function_configuration("orders", 1, Some("bob"))
+        .await
+        .unwrap();
+    assert_eq!(function.environment["STAGE"], "dev");
+    assert_eq!(cp.get_function("orders").await.unwrap().timeout, 3);
+
+    let history = cp.config_history("orders").await.unwrap();
+    let revert = &history.revisions[0];
+    assert_eq!(revert.revision, 3);
+    assert_eq!(revert.source, ConfigChangeSource::Revert);
+    assert_eq!(revert.reverted_from, Some(1));
+    assert_eq!(revert.changed_by.as_deref(), Some("bob"));
+    assert_eq!(revert.configuration, history.revisions[2].configuration);
+
+    let missing = cp
+        .revert_function_configuration("orders", 9, None)
+        .await
+        .unwrap_err();
+    assert_eq!(missing.http_status(), 404);
+}
+
+#[tokio::test]
+async fn history_is_removed_with_the_function() {
+    let (cp, pool) = control_plane_and_pool(test_config()).await;
+    create_function_with_env(&cp, "orders", &[("STAGE", "dev")]).await;
+    cp.delete_function("orders").await.unwrap();
+
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM function_config_revisions")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+    assert_eq!(
+        cp.config_history("orders").await.unwrap_err().http_status(),
+        404
+    );
+}
@@ -1,9 +1,12 @@
 use lambda_control::container_monitor::ContainerMonitor;
-use lambda_control::queues::FnKey;
+use lambda_control::pending::Pending;
+use lambda_control::queues::{FnKey, Queues};
 use lambda_control::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use lambda_control::work_item::{FunctionMeta, WorkItem};
 use lambda_invoker::ContainerEvent;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 use uuid::Uuid;
 
 #[tokio::test]
@@ -24,6 +27,7 @@ async fn test_container_monitor_handles_die_event() {
         instance_id: "test-instance-123".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: InstanceState::WarmIdle,
@@ -49,6 +53,27 @@ async fn test_container_monitor_handles_die_event() {
     assert_eq!(warm_pool.container_count(&fn_key).await, 0);
 }
 
+#[tokio::test]
+async fn test_container_monitor_fails_in_flight_requests_on_die() {
+    let warm_pool = Arc::new(WarmPool::new());
+    let pending = Pending::new();
+    let (monitor, _sender) = ContainerMonitor::new(warm_pool.clone());
+    let monitor = monitor.with_pending(pending.clone());
+
+    let rx = pending.register("req-1".to_string());
+    pending.assign("req-1", "test-container-456".to_string());
+
+    let die_event = ContainerEvent::Die {
+        container_id: "test-container-456".to_string(),
+        exit_code: Some(137),
+    };
+    let _ = monitor.handle_container_event(die_event).await;
+
+    let result = rx.await.unwrap();
+    assert!(result.infrastructure_failure);
+    assert_eq!(result.function_error.as_deref(), Some("Unhandled"));
+}
+
 #[tokio::test]
 async fn test_container_monitor_handles_stop_event() {
     let warm_pool = Arc::new(WarmPool::new());
@@ -67,6 +92,7 @@ async fn test_container_monitor_handles_stop_event() {
         instance_id: "test-instance-456".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: InstanceState::WarmIdle,
@@ -110,6 +136,7 @@ async fn test_container_monitor_handles_kill_event() {
         instance_id: "test-instance-789".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: InstanceState::Active,
@@ -152,6 +179,7 @@ async fn test_container_monitor_handles_remove_event() {
         instance_id: "test-instance-remove".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: InstanceState::Stopped,
@@ -194,6 +222,7 @@ async fn test_container_monitor_handles_start_event() {
         instance_id: "test-instance-start".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: InstanceState::Stopped,
@@ -254,6 +283,7 @@ async fn test_container_monitor_multiple_containers() {
             instance_id: format!("test-instance-{i}"),
             function_id: Uuid::new_v4(),
             image_ref: "test-image:latest".to_string(),
+            memory_mb: 128,
             created_at: Instant::now(),
             last_used: Instant::now(),
             state: InstanceState::WarmIdle,
@@ -302,6 +332,7 @@ async fn test_container_monitor_state_transitions() {
         instance_id: "test-instance-state".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: InstanceState::WarmIdle,
@@ -346,3 +377,121 @@ async fn test_container_monitor_state_transitions() {
     // Verify container was removed
     assert_eq!(warm_pool.container_count(&fn_key).await, 0);
 }
+
+fn pooled(container_id: &str, state: InstanceState) -> WarmContainer {
+    WarmContainer {
+        container_id: container_id.to_string(),
+        instance_id: format!("inst-{container_id}"),
+        function_id: Uuid::new_v4(),
+        image_ref: "test-image:latest".to_string(),
+        memory_mb: 128,
+        created_at: Instant::now(),
+        last_used: Instant::now(),
+        state,
+    }
+}
+
+#[tokio::test]
+async fn test_container_monitor_keeps_idle_stopped_containers() {
+    let warm_pool = Arc::new(WarmPool::new());
+    let pending = Pending::new();
+    let (monitor, _sender) = ContainerMonitor::new(warm_pool.clone());
+    let monitor = monitor.with_pending(pending.clone());
+    let fn_key = FnKey {
+        function_name: "test-function".to_string(),
+        runtime: "nodejs22.x".to_string(),
+        version: "1".to_string(),
+        env_hash: "test-env".to_string(),
+    };
+    warm_pool
+        .add_warm_container(fn_key.clone(), pooled("idle", InstanceState::Stopping))
+        .await;
+
+    // `docker stop` from the idle watchdog emits kill, die, then stop
+    for event in [
+        ContainerEvent::Kill {
+            container_id: "idle".to_string(),
+        },
+        ContainerEvent::Die {
+            container_id: "idle".to_string(),
+            exit_code: Some(143),
+        },
+        ContainerEvent::Stop {
+            container_id: "idle".to_string(),
+        },
+    ] {
+        monitor.handle_container_event(event).await.unwrap();
+    }
+
+    assert_eq!(warm_pool.container_count(&fn_key).await, 1);
+    assert_eq!(
+        warm_pool.find_container("idle").await,
+        Some((fn_key, InstanceState::Stopped))
+    );
+}
+
+#[tokio::test]
+async fn test_container_monitor_replaces_lost_container_with_queued_work() {
+    let warm_pool = Arc::new(WarmPool::new());
+    let pending = Pending::new();
+    let queues = Queues::new();
+    let wake = Arc::new(Notify::new());
+    let (monitor, _sender) = ContainerMonitor::new(warm_pool.clone());
+    let monitor = monitor
+        .with_pending(pending.clone())
+        .with_replacement(queues.clone(), wake.clone());
+
+    let item = WorkItem {
+        request_id: "queued".to_string(),
+        function: FunctionMeta {
+            function_name: "test-function".into(),
+            runtime: "nodejs22.x".into(),
+            version: None,
+            environment: None,
+            timeout_ms: 3000,
+        },
+        payload: b"{}".to_vec(),
+        deadline_ms: i64::MAX,
+        log_type: None,
+        client_context: None,
+        cognito_identity: None,
+    };
+    let fn_key = FnKey::from_work_item(&item);
+    warm_pool
+        .add_warm_container(fn_key.clone(), pooled("busy", InstanceState::Active))
+        .await;
+    warm_pool
+        .add_warm_container(fn_key.clone(), pooled("idle", InstanceState::WarmIdle))
+        .await;
+    let rx = pending.register("in-flight".to_string());
+    pending.assign("in-flight", "busy".to_string());
+
+    // Nothing queued: the pool shrinks without waking the autoscaler
+    monitor
+        .handle_container_event(ContainerEvent::Remove {
+            container_id: "idle".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), wake.notified())
+            .await
+            .is_err()
+    );
+
+    queues.push(item).unwrap();
+    monitor
+        .handle_container_event(ContainerEvent::Die {
+            container_id: "busy".to_string(),
+            exit_code: Some(137),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(warm_pool.container_count(&fn_key).await, 0);
+    let result = rx.await.unwrap();
+    assert!(result.infrastructure_failure);
+    tokio::time::timeout(Duration::from_millis(50), wake.notified())
+        .await
+        .expect("autoscaler should be woken");
+}
@@ -0,0 +1,214 @@
+use crate::common::{control_plane_and_pool, create_request, test_config};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::cost::{estimate, estimate_function, RecordedUsage};
+use lambda_models::{Architecture, AwsPricingConfig, CostEstimateRequest, CreateFunctionRequest};
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-9
+}
+
+fn usage(name: &str, memory_size_mb: u64, durations_ms: Vec<u64>) -> RecordedUsage {
+    RecordedUsage {
+        function_name: name.into(),
+        memory_size_mb,
+        durations_ms,
+        max_memory_used_mb: None,
+    }
+}
+
+#[test]
+fn executions_are_billed_per_millisecond_of_configured_memory() {
+    let pricing = AwsPricingConfig::default();
+    // 1000 ms at 1024 MB is one GB-second; a 0 ms execution is billed 1 ms
+    let durations = vec![1000, 250, 0, 4000];
+    let one_day = 24 * 3600;
+    let x86 = estimate_function(
+        usage("orders", 1024, durations.clone()),
+        &pricing,
+        Architecture::X86_64,
+        one_day,
+    );
+    assert_eq!(x86.invocations, 4);
+    assert!(close(x86.gb_seconds, 5.251));
+    assert!(close(x86.request_cost, 4.0 * 0.20 / 1_000_000.0));
+    assert!(close(x86.compute_cost, 5.251 * 0.0000166667));
+    assert!(close(x86.cost, x86.request_cost + x86.compute_cost));
+    // 730 hours in a month
+    assert!(close(x86.monthly_cost, x86.cost * 730.0 / 24.0));
+
+    let duration = x86.duration.unwrap();
+    assert_eq!(duration.p50_ms, 250);
+    assert_eq!(duration.p99_ms, 4000);
+    assert_eq!(duration.max_ms, 4000);
+    assert!(close(duration.mean_ms, 1312.5));
+
+    let arm = estimate_function(
+        usage("orders", 1024, durations),
+        &pricing,
+        Architecture::Arm64,
+        one_day,
+    );
+    assert!(close(arm.compute_cost, 5.251 * 0.0000133334));
+
+    let idle = estimate_function(
+        usage("idle", 128, Vec::new()),
+        &pricing,
+        Architecture::X86_64,
+        one_day,
+    );
+    assert_eq!(idle.duration, None);
+    assert_eq!(idle.cost, 0.0);
+}
+
+#[test]
+fn the_free_tier_comes_off_the_monthly_total() {
+    let pricing = AwsPricingConfig {
+        request_price_per_million: 1.0,
+        x86_64_gb_second_price: 0.001,
+        arm64_gb_second_price: 0.001,
+        free_requests_per_month: 1_000,
+        free_gb_seconds_per_month: 100.0,
+    };
+    // A whole month of two busy functions: 1100 requests and 300 GB-s in all
+    let month = 730 * 3600;
+    let result = estimate(
+        vec![
+            usage("small", 1024, vec![100; 1000]),
+            usage("big", 2048, vec![1000; 100]),
+            usage("idle", 128, Vec::new()),
+        ],
+        pricing,
+        Architecture::X86_64,
+        month,
+    );
+    let names: Vec<_> = result
+        .functions
+        .iter()
+        .map(|f| f.function_name.as_str())
+        .collect();
+    assert_eq!(names, ["big", "small", "idle"]);
+    assert_eq!(result.invocations, 1100);
+    assert!(close(result.gb_seconds, 300.0));
+    assert!(close(result.cost, 1100.0 / 1_000_000.0 + 0.3));
+    assert!(close(result.monthly_cost_before_free_tier, result.cost));
+    // 100 requests and 200 GB-s are left after the free tier
+    assert!(close(result.monthly_cost, 100.0 / 1_000_000.0 + 0.2));
+}
+
+#[tokio::test]
+async fn recorded_executions_within_the_window_are_priced() {
+    let (cp, pool) = control_plane_and_pool(test_config()).await;
+    let mut functions = Vec::new();
+    for (name, memory_size) in [("orders", 1024), ("billing", 256)] {
+        functions.push(
+            cp.create_function(CreateFunctionRequest {
+                memory_size: Some(memory_size),
+                ..create_request(name)
+            })
+            .await
+            .unwrap(),
+        );
+    }
+
+    // The pending execution has not finished and the old one is outside the window
+    let rows = [
+        ("o1", 0, Some(1000), Some(90), 10),
+        ("o2", 0, Some(3000), Some(300), 20),
+        ("pending", 0, None, None, 1),
+        ("old", 0, Some(9000), Some(900), 60 * 24 * 2),
+        ("b1", 1, Some(40), None, 30),
+    ];
+    for (id, function, duration_ms, memory_used_mb, minutes_ago) in rows {
+        sqlx::query(
+            "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, duration_ms, memory_used_mb, status)
+             VALUES (?, ?, '$LATEST', ?, ?, ?, ?, 'Success')",
+        )
+        .bind(id)
+        .bind(functions[function].function_id)
+        .bind(id)
+        .bind(Utc::now() - ChronoDuration::minutes(minutes_ago))
+        .bind(duration_ms)
+        .bind(memory_used_mb)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let result = cp
+        .cost_estimate(CostEstimateRequest {
+            window: Some("24h".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.window_seconds, 24 * 3600);
+    assert_eq!(result.pricing, AwsPricingConfig::default());
+    assert_eq!(result.invocations, 3);
+    let orders = &result.functions[0];
+    assert_eq!(orders.function_name, "orders");
+    assert_eq!(orders.memory_size_mb, 1024);
+    assert_eq!(orders.invocations, 2);
+    assert!(close(orders.gb_seconds, 4.0));
+    assert_eq!(orders.max_memory_used_mb, Some(300));
+    let billing = &result.functions[1];
+    assert_eq!(billing.function_name, "billing");
+    assert!(close(billing.gb_seconds, 0.04 / 4.0));
+    assert_eq!(billing.max_memory_used_mb, None);
+    // Well within the free tier
+    assert_eq!(result.monthly_cost, 0.0);
+
+    let custom = AwsPricingConfig {
+        free_requests_per_month: 0,
+        free_gb_seconds_per_month: 0.0,
+        ..Default::default()
+    };
+    let result = cp
+        .cost_estimate(CostEstimateRequest {
+            function_name: Some("orders".into()),
+            window: Some("30d".into()),
+            architecture: Architecture::Arm64,
+            pricing: Some(custom.clone()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.functions.len(), 1);
+    assert_eq!(result.invocations, 3);
+    assert_eq!(result.pricing, custom);
+    assert!(close(
+        result.monthly_cost,
+        result.monthly_cost_before_free_tier
+    ));
+
+    let negative = AwsPricingConfig {
+        request_price_per_million: -1.0,
+        ..Default::default()
+    };
+    for request in [
+        CostEstimateRequest {
+            window: Some("soon".into()),
+            ..Default::default()
+        },
+        // Far longer than anything recorded, and than now can be taken back by
+        CostEstimateRequest {
+            window: Some("100000000d".into()),
+            ..Default::default()
+        },
+        CostEstimateRequest {
+            pricing: Some(negative),
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(
+            cp.cost_estimate(request).await.unwrap_err().http_status(),
+            400
+        );
+    }
+    let err = cp
+        .cost_estimate(CostEstimateRequest {
+            function_name: Some("missing".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+}
@@ -0,0 +1,211 @@
+use crate::common::{control_plane_and_pool, create_request};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::dashboard::{build_alerts, count_states, invocation_totals, occupancy};
+use lambda_control::queues::FnKey;
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_models::{
+    AlertSeverity, Config, DeploymentKind, DiskPressure, DiskStatus, FunctionInvocationTotals,
+    FunctionState, PublishVersionRequest,
+};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+fn totals(name: &str, invocations: u64, errors: u64) -> FunctionInvocationTotals {
+    FunctionInvocationTotals {
+        function_name: name.to_string(),
+        invocations,
+        errors,
+        error_rate: errors as f64 / invocations as f64,
+    }
+}
+
+fn disk(pressure: DiskPressure) -> DiskStatus {
+    DiskStatus {
+        pressure,
+        data_dir: None,
+        docker_root: None,
+        gc_below_free_mb: 5120,
+        min_free_mb: 1024,
+        checked_at: Utc::now(),
+        last_gc_at: None,
+    }
+}
+
+fn container(id: &str, memory_mb: u64, state: InstanceState) -> WarmContainer {
+    WarmContainer {
+        container_id: id.to_string(),
+        instance_id: format!("inst-{id}"),
+        function_id: Uuid::new_v4(),
+        image_ref: "img".to_string(),
+        memory_mb,
+        created_at: Instant::now(),
+        last_used: Instant::now(),
+        state,
+    }
+}
+
+#[test]
+fn counts_function_states() {
+    let counts = count_states(&[
+        FunctionState::Active,
+        FunctionState::Active,
+        FunctionState::Failed,
+        FunctionState::Pending,
+    ]);
+    assert_eq!(counts.total, 4);
+    assert_eq!(counts.active, 2);
+    assert_eq!(counts.failed, 1);
+    assert_eq!(counts.pending, 1);
+    assert_eq!(counts.inactive, 0);
+}
+
+#[test]
+fn totals_are_summed_and_sorted_busiest_first() {
+    let totals = invocation_totals(
+        Duration::from_secs(86400),
+        vec![totals("quiet", 2, 0), totals("busy", 8, 2)],
+        Some(12.5),
+    );
+    assert_eq!(totals.window_seconds, 86400);
+    assert_eq!(totals.invocations, 10);
+    assert_eq!(totals.errors, 2);
+    assert!((totals.error_rate - 0.2).abs() < f64::EPSILON);
+    assert_eq!(totals.by_function[0].function_name, "busy");
+}
+
+#[test]
+fn occupancy_counts_states_and_running_memory() {
+    let key = FnKey {
+        function_name: "hello".to_string(),
+        runtime: "nodejs22.x".to_string(),
+        version: "LATEST".to_string(),
+        env_hash: String::new(),
+    };
+    let occupancy = occupancy(
+        &[(
+            key,
+            vec![
+                container("a", 128, InstanceState::WarmIdle),
+                container("b", 256, InstanceState::Active),
+                container("c", 512, InstanceState::Stopped),
+            ],
+        )],
+        Some(1024),
+    );
+    assert_eq!(occupancy.total, 3);
+    assert_eq!(occupancy.warm_idle, 1);
+    assert_eq!(occupancy.active, 1);
+    assert_eq!(occupancy.stopped, 1);
+    assert_eq!(occupancy.committed_memory_mb, 384);
+    assert_eq!(occupancy.host_memory_budget_mb, Some(1024));
+}
+
+#[test]
+fn alerts_cover_failures_error_rates_disk_and_memory() {
+    let invocations = invocation_totals(
+        Duration::from_secs(86400),
+        vec![
+            totals("flaky", 20, 15),
+            totals("rare", 4, 4),
+            totals("fine", 50, 1),
+        ],
+        None,
+    );
+    let mut warm_pool = occupancy(&[], Some(1000));
+    warm_pool.committed_memory_mb = 950;
+
+    let alerts = build_alerts(
+        &[("broken".to_string(), Some("image build failed".to_string()))],
+        &[("hook".to_string(), Some("Function error: boom".to_string()))],
+        &invocations,
+        &warm_pool,
+        &disk(DiskPressure::Low),
+    );
+    let codes: Vec<&str> = alerts.iter().map(|a| a.code.as_str()).collect();
+    assert_eq!(
+        codes,
+        vec![
+            "FunctionFailed",
+            "SyntheticCheckFailing",
+            "DiskPressure",
+            "HostMemoryNearlyFull",
+            "HighErrorRate"
+        ]
+    );
+    assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+    assert!(alerts[0].message.contains("image build failed"));
+    assert_eq!(alerts[1].function_name.as_deref(), Some("hook"));
+    assert!(alerts[1].message.contains("boom"));
+    assert_eq!(alerts[4].function_name.as_deref(), Some("flaky"));
+
+    let alerts = build_alerts(
+        &[],
+        &[],
+        &invocation_totals(Duration::from_secs(86400), Vec::new(), None),
+        &occupancy(&[], None),
+        &disk(DiskPressure::Ok),
+    );
+    assert!(alerts.is_empty());
+}
+
+#[tokio::test]
+async fn dashboard_summarizes_functions_invocations_and_deployments() {
+    let config = Config::default();
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    let mut functions = Vec::new();
+    for name in ["alpha", "beta"] {
+        functions.push(cp.create_function(create_request(name)).await.unwrap());
+    }
+    cp.publish_version(
+        "alpha",
+        PublishVersionRequest {
+            description: None,
+            revision_id: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let rows = [
+        ("a1", 0, "Success", Some(10), 5),
+        ("a2", 0, "Failed", Some(30), 10),
+        ("b1", 1, "Success", None, 15),
+        ("old", 1, "Failed", Some(99), 60 * 48),
+    ];
+    for (id, function, status, duration_ms, minutes_ago) in rows {
+        sqlx::query(
+            "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, duration_ms, status)
+             VALUES (?, ?, '1', ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(functions[function].function_id)
+        .bind(id)
+        .bind(Utc::now() - ChronoDuration::minutes(minutes_ago))
+        .bind(duration_ms)
+        .bind(status)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let dashboard = cp.dashboard().await.unwrap();
+    assert_eq!(dashboard.functions.total, 2);
+    assert_eq!(dashboard.invocations.invocations, 3);
+    assert_eq!(dashboard.invocations.errors, 1);
+    assert_eq!(dashboard.invocations.avg_duration_ms, Some(20.0));
+    assert_eq!(dashboard.invocations.by_function[0].function_name, "alpha");
+    assert_eq!(dashboard.invocations.by_function[0].invocations, 2);
+    assert_eq!(dashboard.warm_pool.total, 0);
+
+    assert_eq!(dashboard.recent_deployments.len(), 3);
+    assert_eq!(
+        dashboard.recent_deployments[0].kind,
+        DeploymentKind::Published
+    );
+    assert_eq!(dashboard.recent_deployments[0].function_name, "alpha");
+    assert!(dashboard
+        .recent_deployments
+        .windows(2)
+        .all(|w| w[0].deployed_at >= w[1].deployed_at));
+}
@@ -0,0 +1,61 @@
+use lambda_control::disk::{classify, DiskMonitor};
+use lambda_models::{DiskConfig, DiskPressure};
+
+const MB: u64 = 1024 * 1024;
+
+fn config(gc_below_free_mb: u64, min_free_mb: u64) -> DiskConfig {
+    DiskConfig {
+        gc_below_free_mb,
+        min_free_mb,
+        check_interval_ms: 1000,
+    }
+}
+
+#[test]
+fn classify_uses_both_thresholds() {
+    let config = config(100, 10);
+    assert_eq!(classify(200 * MB, &config), DiskPressure::Ok);
+    assert_eq!(classify(100 * MB, &config), DiskPressure::Ok);
+    assert_eq!(classify(50 * MB, &config), DiskPressure::Low);
+    assert_eq!(classify(5 * MB, &config), DiskPressure::Critical);
+}
+
+#[cfg(unix)]
+#[test]
+fn monitor_measures_data_dir() {
+    let dir = std::env::temp_dir();
+    let monitor = DiskMonitor::new(config(0, 0), dir.to_string_lossy().to_string());
+    let status = monitor.refresh();
+    let data_dir = status.data_dir.expect("data dir should be measurable");
+    assert!(data_dir.total_bytes >= data_dir.free_bytes);
+    assert_eq!(status.pressure, DiskPressure::Ok);
+    assert!(monitor.check_build_capacity().is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn builds_are_refused_below_min_free() {
+    let dir = std::env::temp_dir();
+    let monitor = DiskMonitor::new(
+        config(u64::MAX / MB, u64::MAX / MB),
+        dir.to_string_lossy().to_string(),
+    );
+    let err = monitor.check_build_capacity().unwrap_err();
+    assert_eq!(err.http_status(), 507);
+    assert_eq!(monitor.status().pressure, DiskPressure::Critical);
+}
+
+#[test]
+fn docker_root_outside_this_host_is_ignored() {
+    let monitor = DiskMonitor::new(config(0, 0), ".".to_string());
+    monitor.set_docker_root(Some("/definitely/not/a/local/docker/root".to_string()));
+    assert!(monitor.refresh().docker_root.is_none());
+}
+
+#[test]
+fn gc_time_is_reported() {
+    let monitor = DiskMonitor::new(config(0, 0), ".".to_string());
+    assert!(monitor.refresh().last_gc_at.is_none());
+    monitor.record_gc();
+    assert!(monitor.refresh().last_gc_at.is_some());
+}
@@ -0,0 +1,146 @@
+use chrono::Utc;
+use lambda_control::doctor::{
+    check_disk, check_docker, check_probe, check_sandbox, is_loopback_bind, parse_docker_version,
+};
+use lambda_invoker::{SandboxProfiles, SandboxSupport};
+use lambda_models::{
+    CheckStatus, Config, DiskLocationStatus, DiskPressure, DiskStatus, DockerVersion, LambdaError,
+    SandboxConfig,
+};
+
+fn docker_version(version: &str) -> DockerVersion {
+    DockerVersion {
+        version: version.into(),
+        api_version: "1.43".into(),
+        min_api_version: "1.12".into(),
+        git_commit: String::new(),
+        go_version: String::new(),
+        os: "linux".into(),
+        arch: "amd64".into(),
+        kernel_version: String::new(),
+        experimental: false,
+        build_time: String::new(),
+    }
+}
+
+#[test]
+fn docker_versions_are_checked_for_host_gateway_support() {
+    assert_eq!(parse_docker_version("24.0.7"), Some((24, 0)));
+    assert_eq!(parse_docker_version("20.10.21+dfsg1"), Some((20, 10)));
+    assert_eq!(parse_docker_version("dev"), None);
+
+    assert_eq!(
+        check_docker(Ok(&docker_version("24.0.7"))).status,
+        CheckStatus::Pass
+    );
+    assert_eq!(
+        check_docker(Ok(&docker_version("19.03.15"))).status,
+        CheckStatus::Fail
+    );
+    let unreachable = check_docker(Err(&LambdaError::DockerError {
+        message: "socket not found".into(),
+    }));
+    assert_eq!(unreachable.status, CheckStatus::Fail);
+    assert!(unreachable.fix.unwrap().contains("DOCKER_HOST"));
+}
+
+#[test]
+fn probe_outcomes_point_at_the_broken_hop() {
+    let mut server = Config::default().server;
+    server.bind = "0.0.0.0".into();
+
+    let ok = check_probe(Ok((0, "ok".into())), &server);
+    assert!(ok.iter().all(|c| c.status == CheckStatus::Pass));
+
+    let unresolved = check_probe(
+        Ok((1, "wget: bad address 'host.docker.internal:9001'".into())),
+        &server,
+    );
+    assert_eq!(unresolved[0].name, "host_gateway");
+    assert_eq!(unresolved[0].status, CheckStatus::Fail);
+    assert_eq!(unresolved[1].status, CheckStatus::Warn);
+
+    let refused = check_probe(
+        Ok((1, "wget: can't connect to remote host".into())),
+        &server,
+    );
+    assert_eq!(refused[0].status, CheckStatus::Pass);
+    assert_eq!(refused[1].status, CheckStatus::Fail);
+    assert!(refused[1].fix.as_ref().unwrap().contains("firewall"));
+
+    server.bind = "127.0.0.1".into();
+    let loopback = check_probe(
+        Ok((1, "wget: can't connect to remote host".into())),
+        &server,
+    );
+    assert!(loopback[1].fix.as_ref().unwrap().contains("server.bind"));
+    assert!(is_loopback_bind("localhost"));
+    assert!(!is_loopback_bind("0.0.0.0"));
+
+    let rejected = check_probe(
+        Err(LambdaError::DockerError {
+            message: "invalid IP address in add-host: \"host-gateway\"".into(),
+        }),
+        &server,
+    );
+    assert_eq!(rejected[0].status, CheckStatus::Fail);
+}
+
+#[test]
+fn disk_pressure_maps_to_check_status() {
+    let status = |pressure| DiskStatus {
+        pressure,
+        data_dir: Some(DiskLocationStatus {
+            path: "/var/lib/lambdah".into(),
+            free_bytes: 512 * 1024 * 1024,
+            total_bytes: 10 * 1024 * 1024 * 1024,
+            pressure,
+        }),
+        docker_root: None,
+        gc_below_free_mb: 2048,
+        min_free_mb: 512,
+        checked_at: Utc::now(),
+        last_gc_at: None,
+    };
+    let ok = check_disk(&status(DiskPressure::Ok));
+    assert_eq!(ok.status, CheckStatus::Pass);
+    assert_eq!(ok.detail, "512 MB free in /var/lib/lambdah");
+    assert_eq!(
+        check_disk(&status(DiskPressure::Low)).status,
+        CheckStatus::Warn
+    );
+    assert_eq!(
+        check_disk(&status(DiskPressure::Critical)).status,
+        CheckStatus::Fail
+    );
+}
+
+#[test]
+fn sandbox_profiles_the_host_cannot_apply_are_reported() {
+    let full = SandboxSupport {
+        seccomp: true,
+        apparmor: true,
+        rootless: false,
+    };
+    let no_seccomp = SandboxSupport::default();
+    let profiles = SandboxProfiles::load(&SandboxConfig::default()).unwrap();
+    assert_eq!(
+        check_sandbox(Some(Ok(&full)), &profiles).status,
+        CheckStatus::Pass
+    );
+    // Only functions that chose strict are affected
+    let check = check_sandbox(Some(Ok(&no_seccomp)), &profiles);
+    assert_eq!(check.status, CheckStatus::Warn);
+    assert!(check.detail.contains("strict"));
+
+    let strict_default = SandboxProfiles::load(&SandboxConfig {
+        default_profile: "strict".into(),
+        ..SandboxConfig::default()
+    })
+    .unwrap();
+    assert_eq!(
+        check_sandbox(Some(Ok(&no_seccomp)), &strict_default).status,
+        CheckStatus::Fail
+    );
+    assert_eq!(check_sandbox(None, &profiles).status, CheckStatus::Warn);
+}
@@ -0,0 +1,141 @@
+use crate::common::control_plane;
+use lambda_models::{
+    CreateFunctionRequest, EnvironmentValue, EnvironmentVariable, PatchEnvironmentRequest,
+};
+use std::collections::HashMap;
+
+fn create_request(name: &str, environment: &[(&str, &str)]) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        environment: Some(
+            environment
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ),
+        ..crate::common::create_request(name)
+    }
+}
+
+fn patch(variables: &[(&str, Option<EnvironmentValue>)]) -> PatchEnvironmentRequest {
+    PatchEnvironmentRequest {
+        variables: variables
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<HashMap<_, _>>(),
+    }
+}
+
+fn plain(name: &str, value: &str) -> EnvironmentVariable {
+    EnvironmentVariable {
+        name: name.into(),
+        value: Some(value.into()),
+        secret: None,
+        secret_exists: None,
+    }
+}
+
+#[tokio::test]
+async fn secret_references_are_listed_by_name_only() {
+    let cp = control_plane().await;
+    cp.create_secret("DB_PASS", "s3cr3t").await.unwrap();
+    cp.create_function(create_request(
+        "env",
+        &[
+            ("STAGE", "dev"),
+            ("DB_PASSWORD", "SECRET_REF:DB_PASS"),
+            ("API_KEY", "SECRET_REF:GONE"),
+        ],
+    ))
+    .await
+    .unwrap();
+
+    let environment = cp.get_environment("env").await.unwrap();
+    assert_eq!(
+        environment.variables,
+        [
+            EnvironmentVariable {
+                name: "API_KEY".into(),
+                value: None,
+                secret: Some("GONE".into()),
+                secret_exists: Some(false),
+            },
+            EnvironmentVariable {
+                name: "DB_PASSWORD".into(),
+                value: None,
+                secret: Some("DB_PASS".into()),
+                secret_exists: Some(true),
+            },
+            plain("STAGE", "dev"),
+        ]
+    );
+    let body = serde_json::to_string(&environment).unwrap();
+    assert!(!body.contains("s3cr3t"));
+}
+
+#[tokio::test]
+async fn patches_only_touch_the_named_variables() {
+    let cp = control_plane().await;
+    cp.create_secret("TOKEN", "t0k3n").await.unwrap();
+    cp.create_function(create_request("env", &[("STAGE", "dev"), ("OLD", "x")]))
+        .await
+        .unwrap();
+
+    let environment = cp
+        .patch_environment(
+            "env",
+            patch(&[
+                ("OLD", None),
+                ("REGION_NAME", Some(EnvironmentValue::Plain("eu".into()))),
+                (
+                    "TOKEN",
+                    Some(EnvironmentValue::Secret {
+                        secret: "TOKEN".into(),
+                    }),
+                ),
+            ]),
+            Some("console"),
+        )
+        .await
+        .unwrap();
+    let names: Vec<_> = environment
+        .variables
+        .iter()
+        .map(|v| v.name.as_str())
+        .collect();
+    assert_eq!(names, ["REGION_NAME", "STAGE", "TOKEN"]);
+    assert_eq!(environment.variables[1], plain("STAGE", "dev"));
+    assert_eq!(environment.variables[2].secret.as_deref(), Some("TOKEN"));
+
+    let function = cp.get_function("env").await.unwrap();
+    assert_eq!(function.environment["TOKEN"], "SECRET_REF:TOKEN");
+    let history = cp.config_history("env").await.unwrap();
+    assert_eq!(history.revisions[0].changed_by.as_deref(), Some("console"));
+}
+
+#[tokio::test]
+async fn invalid_patches_change_nothing() {
+    let cp = control_plane().await;
+    cp.create_function(create_request("env", &[("STAGE", "dev")]))
+        .await
+        .unwrap();
+
+    for (key, value) in [
+        ("1BAD", EnvironmentValue::Plain("x".into())),
+        ("AWS_REGION", EnvironmentValue::Plain("x".into())),
+        ("BIG", EnvironmentValue::Plain("x".repeat(5000))),
+        (
+            "TOKEN",
+            EnvironmentValue::Secret {
+                secret: "MISSING".into(),
+            },
+        ),
+    ] {
+        let err = cp
+            .patch_environment("env", patch(&[(key, Some(value))]), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 400, "{key}: {err}");
+    }
+    let environment = cp.get_environment("env").await.unwrap();
+    assert_eq!(environment.variables, [plain("STAGE", "dev")]);
+}
@@ -0,0 +1,113 @@
+use crate::common::{control_plane_and_pool, create_request};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::error_summary::{
+    fingerprint, group_errors, parse_window, window_start, FailedExecution, MAX_WINDOW,
+};
+use lambda_models::Config;
+use std::time::Duration;
+
+fn failure(id: &str, error_type: &str, message: &str, minutes_ago: i64) -> FailedExecution {
+    FailedExecution {
+        request_id: id.to_string(),
+        error_type: error_type.to_string(),
+        error_message: Some(message.to_string()),
+        start_time: Utc::now() - ChronoDuration::minutes(minutes_ago),
+    }
+}
+
+#[test]
+fn parses_windows() {
+    assert_eq!(parse_window("24h").unwrap(), Duration::from_secs(86400));
+    assert_eq!(parse_window("30m").unwrap(), Duration::from_secs(1800));
+    assert_eq!(parse_window("7d").unwrap(), Duration::from_secs(604800));
+    assert_eq!(parse_window("90").unwrap(), Duration::from_secs(90));
+    assert!(parse_window("").is_err());
+    assert!(parse_window("0h").is_err());
+    assert!(parse_window("soon").is_err());
+    // Windows too long to subtract from now are refused rather than overflowing
+    assert_eq!(parse_window("366d").unwrap(), MAX_WINDOW);
+    assert!(parse_window("367d").is_err());
+    assert!(parse_window("100000000d").is_err());
+    assert!(parse_window("18446744073709551615").is_err());
+}
+
+#[test]
+fn window_start_refuses_spans_it_cannot_subtract() {
+    let now = Utc::now();
+    assert_eq!(
+        window_start(now, Duration::from_secs(3600)).unwrap(),
+        now - ChronoDuration::hours(1)
+    );
+    assert!(window_start(now, Duration::from_secs(u64::MAX)).is_err());
+}
+
+#[test]
+fn groups_by_type_and_normalized_message() {
+    assert_eq!(
+        fingerprint("TypeError", Some("bad item 12")),
+        fingerprint("TypeError", Some("bad item 345"))
+    );
+    assert_ne!(
+        fingerprint("TypeError", Some("bad item 12")),
+        fingerprint("RangeError", Some("bad item 12"))
+    );
+
+    let groups = group_errors(vec![
+        failure("r1", "TypeError", "bad item 1", 1),
+        failure("r2", "TypeError", "bad item 2", 5),
+        failure("r3", "TaskTimedOut", "Task timed out after 3 seconds", 10),
+        failure("r4", "TypeError", "bad item 3", 20),
+    ]);
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].error_type, "TypeError");
+    assert_eq!(groups[0].count, 3);
+    assert_eq!(groups[0].sample_request_ids, vec!["r1", "r2", "r4"]);
+    assert_eq!(groups[0].error_message.as_deref(), Some("bad item 1"));
+    assert!(groups[0].first_seen < groups[0].last_seen);
+    assert_eq!(groups[1].count, 1);
+}
+
+#[tokio::test]
+async fn summarizes_failed_executions_in_window() {
+    let config = Config::default();
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    let function = cp.create_function(create_request("errs")).await.unwrap();
+
+    let rows = [
+        ("a", "Failed", Some("Error"), Some("boom 1"), 5),
+        ("b", "Failed", Some("Error"), Some("boom 2"), 10),
+        ("c", "Success", None, None, 10),
+        ("d", "Failed", Some("Error"), Some("boom 3"), 60 * 48),
+    ];
+    for (id, status, error_type, error_message, minutes_ago) in rows {
+        sqlx::query(
+            "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, status, error_type, error_message)
+             VALUES (?, ?, '1', ?, ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(function.function_id)
+        .bind(id)
+        .bind(Utc::now() - ChronoDuration::minutes(minutes_ago))
+        .bind(status)
+        .bind(error_type)
+        .bind(error_message)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let summary = cp
+        .error_summary("errs", Duration::from_secs(86400))
+        .await
+        .unwrap();
+    assert_eq!(summary.total_errors, 2);
+    assert_eq!(summary.groups.len(), 1);
+    assert_eq!(summary.groups[0].sample_request_ids, vec!["a", "b"]);
+
+    assert!(cp
+        .error_summary("missing", Duration::from_secs(60))
+        .await
+        .is_err());
+}
@@ -0,0 +1,126 @@
+use crate::common::{control_plane_with, temp_dir, test_config};
+use lambda_control::queues::FnKey;
+use lambda_control::registry::ControlPlane;
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_models::{Config, ExecAuditEntry, ExecAuditEvent};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+async fn control_plane(audit_file: Option<&Path>) -> ControlPlane {
+    let mut config = test_config();
+    config.exec.enabled = audit_file.is_some();
+    config.exec.admin_token = Some("t0k3n".into());
+    config.exec.audit_file = audit_file.map(|p| p.to_string_lossy().into_owned());
+    control_plane_with(config).await
+}
+
+async fn add_container(cp: &ControlPlane, container_id: &str, state: InstanceState) {
+    let key = FnKey {
+        function_name: "orders".to_string(),
+        runtime: "nodejs22.x".to_string(),
+        version: "LATEST".to_string(),
+        env_hash: "abc".to_string(),
+    };
+    cp.warm_pool()
+        .add_warm_container(
+            key,
+            WarmContainer {
+                container_id: container_id.to_string(),
+                instance_id: format!("inst-{container_id}"),
+                function_id: uuid::Uuid::new_v4(),
+                image_ref: "lambda-home/orders:sha".to_string(),
+                memory_mb: 128,
+                created_at: Instant::now(),
+                last_used: Instant::now(),
+                state,
+            },
+        )
+        .await;
+}
+
+async fn audit_entries(path: &Path, count: usize) -> Vec<ExecAuditEntry> {
+    let mut written = Vec::new();
+    for _ in 0..50 {
+        written = std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        if written.len() >= count {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    written
+}
+
+#[tokio::test]
+async fn exec_sessions_are_off_by_default() {
+    let cp = control_plane(None).await;
+    add_container(&cp, "c1", InstanceState::WarmIdle).await;
+
+    let err = cp.open_exec_session("c1", None).await.unwrap_err();
+    assert_eq!(err.http_status(), 400, "{err}");
+}
+
+#[tokio::test]
+async fn only_warm_containers_can_be_opened() {
+    let dir = temp_dir();
+    let cp = control_plane(Some(&dir.path().join("exec.log"))).await;
+    add_container(&cp, "stopped", InstanceState::Stopped).await;
+
+    let err = cp.open_exec_session("missing", None).await.unwrap_err();
+    assert_eq!(err.http_status(), 404, "{err}");
+    let err = cp.open_exec_session("stopped", None).await.unwrap_err();
+    assert_eq!(err.http_status(), 400, "{err}");
+}
+
+#[tokio::test]
+async fn sessions_are_connected_to_once_and_audited() {
+    let dir = temp_dir();
+    let path = dir.path().join("exec.log");
+    let cp = control_plane(Some(&path)).await;
+    add_container(&cp, "c1", InstanceState::Active).await;
+
+    let session = cp.open_exec_session("c1", Some("alice")).await.unwrap();
+    assert_eq!(session.function_name, "orders");
+    assert_eq!(session.opened_by.as_deref(), Some("alice"));
+    assert_eq!(
+        session.websocket_path,
+        format!("/api/exec-sessions/{}", session.session_id)
+    );
+    assert_eq!(
+        (session.expires_at - session.opened_at).num_seconds(),
+        Config::default().exec.max_session_secs as i64
+    );
+
+    assert_eq!(cp.take_exec_session(&session.session_id).unwrap(), session);
+    let err = cp.take_exec_session(&session.session_id).unwrap_err();
+    assert_eq!(err.http_status(), 404, "{err}");
+
+    cp.exec_audit().record(
+        &session,
+        ExecAuditEvent::Input {
+            data: "ls\r".into(),
+        },
+    );
+    let events: Vec<_> = audit_entries(&path, 3)
+        .await
+        .into_iter()
+        .map(|entry| {
+            assert_eq!(entry.session_id, session.session_id);
+            assert_eq!(entry.actor.as_deref(), Some("alice"));
+            entry.event
+        })
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            ExecAuditEvent::Opened,
+            ExecAuditEvent::Connected,
+            ExecAuditEvent::Input {
+                data: "ls\r".into()
+            },
+        ]
+    );
+}
@@ -0,0 +1,47 @@
+use crate::common::{control_plane_and_pool, create_request};
+use lambda_control::execution_tracker::ExecutionTracker;
+use lambda_models::{Config, LambdaError};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn failed_execution_keeps_error_payload() {
+    let config = Config::default();
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    let function = cp.create_function(create_request("fails")).await.unwrap();
+
+    let tracker = ExecutionTracker::new(Arc::new(pool));
+    let req_id = "req-err-1".to_string();
+    tracker.record_execution_start(
+        req_id.clone(),
+        &function,
+        req_id.clone(),
+        chrono::Utc::now(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    tracker.record_execution_failure(
+        req_id.clone(),
+        "TypeError".into(),
+        Some("x is undefined".into()),
+        Some(serde_json::json!({
+            "errorMessage": "x is undefined",
+            "errorType": "TypeError",
+            "stackTrace": ["at handler (index.js:3:9)", "at run (bootstrap.js:80:5)"]
+        })),
+        chrono::Utc::now(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let record = cp.get_execution(&req_id).await.unwrap();
+    assert_eq!(record.status, "Failed");
+    assert_eq!(record.function_name.as_deref(), Some("fails"));
+    let error = record.error.unwrap();
+    assert_eq!(error.error_type, "TypeError");
+    assert_eq!(error.error_message.as_deref(), Some("x is undefined"));
+    assert_eq!(error.stack_trace.unwrap().len(), 2);
+    assert_eq!(error.payload.unwrap()["errorType"], "TypeError");
+
+    let err = cp.get_execution("nope").await.unwrap_err();
+    assert!(matches!(err, LambdaError::ExecutionNotFound { .. }));
+}
@@ -0,0 +1,105 @@
+use crate::common::{control_plane_with, test_config};
+use lambda_control::features::{parse_feature_flag, FeatureFlags};
+use lambda_models::{Config, FeatureFlag};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+fn config_with(features: &[(FeatureFlag, bool)]) -> Config {
+    let mut config = test_config();
+    config.features = features.iter().copied().collect();
+    config
+}
+
+#[test]
+fn flags_follow_the_configuration_and_overrides() {
+    let flags = FeatureFlags::new(&BTreeMap::from([(FeatureFlag::FunctionInsights, false)]));
+    assert!(flags.is_enabled(FeatureFlag::GatewayOpenapi));
+    assert!(!flags.is_enabled(FeatureFlag::FunctionInsights));
+
+    let status = flags.set(FeatureFlag::FunctionInsights, true).unwrap();
+    assert!(status.enabled);
+    assert!(!status.configured);
+    assert!(status.overridden);
+    // Clones share overrides, as the router and background tasks hold their own
+    assert!(flags.clone().is_enabled(FeatureFlag::FunctionInsights));
+
+    // Setting the configured value is the same as a reset
+    let status = flags.set(FeatureFlag::FunctionInsights, false).unwrap();
+    assert!(!status.overridden);
+
+    flags.set(FeatureFlag::GatewayOpenapi, false).unwrap();
+    let status = flags.reset(FeatureFlag::GatewayOpenapi);
+    assert!(status.enabled);
+    assert!(!status.overridden);
+}
+
+#[test]
+fn flags_are_named_in_snake_case() {
+    for flag in FeatureFlag::ALL {
+        assert_eq!(parse_feature_flag(flag.name()).unwrap(), *flag);
+        assert_eq!(
+            serde_json::to_value(flag).unwrap(),
+            serde_json::json!(flag.name())
+        );
+    }
+    let err = parse_feature_flag("time_travel").unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    let features: BTreeMap<FeatureFlag, bool> =
+        serde_json::from_value(serde_json::json!({ "function_insights": false })).unwrap();
+    assert_eq!(features.get(&FeatureFlag::FunctionInsights), Some(&false));
+    assert!(serde_json::from_value::<BTreeMap<FeatureFlag, bool>>(
+        serde_json::json!({ "time_travel": true })
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn runtime_safe_flags_toggle_through_the_control_plane() {
+    let cp = control_plane_with(config_with(&[])).await;
+    let flags = cp.list_feature_flags().flags;
+    assert_eq!(flags.len(), FeatureFlag::ALL.len());
+    assert!(flags.iter().all(|flag| flag.enabled && !flag.overridden));
+
+    let status = cp.set_feature_flag("function_insights", false).unwrap();
+    assert!(!status.enabled);
+    assert!(status.overridden);
+    let err = cp
+        .function_insights("orders", Duration::from_secs(3600))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+    assert!(
+        err.to_string().contains("function_insights is disabled"),
+        "{err}"
+    );
+    assert_eq!(
+        cp.system_info().await.features.get("function_insights"),
+        Some(&false)
+    );
+
+    let status = cp.reset_feature_flag("function_insights").unwrap();
+    assert!(status.enabled);
+
+    let err = cp.set_feature_flag("time_travel", true).unwrap_err();
+    assert_eq!(err.http_status(), 404);
+}
+
+#[tokio::test]
+async fn startup_flags_only_change_on_restart() {
+    let cp = control_plane_with(config_with(&[(FeatureFlag::SyntheticChecks, false)])).await;
+    let err = cp.set_feature_flag("synthetic_checks", true).unwrap_err();
+    assert_eq!(err.http_status(), 409);
+    assert!(!cp.features().is_enabled(FeatureFlag::SyntheticChecks));
+    assert!(!cp.list_feature_flags().flags[0].runtime_safe);
+}
+
+#[tokio::test]
+async fn configured_off_flags_refuse_their_requests() {
+    let cp = control_plane_with(config_with(&[(FeatureFlag::GatewayOpenapi, false)])).await;
+    let err = cp.gateway_openapi().await.unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    cp.set_feature_flag("gateway_openapi", true).unwrap();
+    assert!(cp.gateway_openapi().await.is_ok());
+}
@@ -0,0 +1,232 @@
+use crate::common::{control_plane, create_function};
+use chrono::{TimeZone, Utc};
+use lambda_control::function_urls::{
+    url_event, url_response, user_api_url, UrlRequest, UrlResponse,
+};
+use lambda_models::Config;
+use serde_json::json;
+
+fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn requests_become_payload_format_2_events() {
+    let headers = headers(&[
+        ("Host", "localhost:8000"),
+        ("User-Agent", "curl/8.0"),
+        ("Accept", "text/html"),
+        ("Accept", "application/json"),
+        ("Cookie", "session=abc; theme=dark"),
+    ]);
+    let event = url_event(&UrlRequest {
+        url_id: "a1b2",
+        method: "POST",
+        path: "/orders/7",
+        raw_query: "tag=a&tag=b&q=hello%20world",
+        headers: &headers,
+        body: br#"{"qty":2}"#,
+        source_ip: Some("10.0.0.9"),
+        request_id: "req-1",
+        account_id: "000000000000",
+        time: Utc.with_ymd_and_hms(2026, 3, 12, 19, 3, 58).unwrap(),
+    });
+    assert_eq!(event["version"], "2.0");
+    assert_eq!(event["routeKey"], "$default");
+    assert_eq!(event["rawPath"], "/orders/7");
+    assert_eq!(event["rawQueryString"], "tag=a&tag=b&q=hello%20world");
+    assert_eq!(
+        event["queryStringParameters"],
+        json!({ "tag": "a,b", "q": "hello world" })
+    );
+    assert_eq!(event["headers"]["accept"], "text/html,application/json");
+    assert!(event["headers"].get("cookie").is_none());
+    assert_eq!(event["cookies"], json!(["session=abc", "theme=dark"]));
+    assert_eq!(event["body"], r#"{"qty":2}"#);
+    assert_eq!(event["isBase64Encoded"], false);
+    let context = &event["requestContext"];
+    assert_eq!(context["domainName"], "localhost:8000");
+    assert_eq!(context["domainPrefix"], "a1b2");
+    assert_eq!(
+        context["http"],
+        json!({
+            "method": "POST",
+            "path": "/orders/7",
+            "protocol": "HTTP/1.1",
+            "sourceIp": "10.0.0.9",
+            "userAgent": "curl/8.0",
+        })
+    );
+    assert_eq!(context["time"], "12/Mar/2026:19:03:58 +0000");
+    assert_eq!(context["timeEpoch"], 1773342238000i64);
+}
+
+#[test]
+fn binary_and_empty_bodies() {
+    let request = |body: &'static [u8]| UrlRequest {
+        url_id: "a1b2",
+        method: "PUT",
+        path: "/",
+        raw_query: "",
+        headers: &[],
+        body,
+        source_ip: None,
+        request_id: "req-1",
+        account_id: "000000000000",
+        time: Utc::now(),
+    };
+    let event = url_event(&request(&[0xff, 0x00, 0x10]));
+    assert_eq!(event["body"], "/wAQ");
+    assert_eq!(event["isBase64Encoded"], true);
+    let event = url_event(&request(b""));
+    assert!(event["body"].is_null());
+    assert!(event["queryStringParameters"].is_null());
+    assert!(event.get("cookies").is_none());
+}
+
+#[test]
+fn results_map_back_to_http_responses() {
+    let response = url_response(Some(&json!({
+        "statusCode": 201,
+        "headers": { "Content-Type": "text/plain", "X-Count": 3 },
+        "cookies": ["a=1; Path=/", "b=2"],
+        "body": "created",
+    })))
+    .unwrap();
+    assert_eq!(
+        response,
+        UrlResponse {
+            status: 201,
+            headers: headers(&[("Content-Type", "text/plain"), ("X-Count", "3")]),
+            cookies: vec!["a=1; Path=/".into(), "b=2".into()],
+            body: b"created".to_vec(),
+        }
+    );
+
+    let binary = url_response(Some(&json!({
+        "statusCode": 200,
+        "body": "/wAQ",
+        "isBase64Encoded": true,
+    })))
+    .unwrap();
+    assert_eq!(binary.body, vec![0xff, 0x00, 0x10]);
+
+    // Without a statusCode the whole result is the JSON body
+    let inferred = url_response(Some(&json!({ "message": "hi" }))).unwrap();
+    assert_eq!(inferred.status, 200);
+    assert_eq!(
+        inferred.headers,
+        headers(&[("content-type", "application/json")])
+    );
+    assert_eq!(inferred.body, br#"{"message":"hi"}"#.to_vec());
+    assert_eq!(
+        url_response(Some(&json!("Hello (world)"))).unwrap().body,
+        b"Hello (world)".to_vec()
+    );
+
+    for (result, expected) in [
+        (json!({ "statusCode": "ok" }), "statusCode must be a number"),
+        (
+            json!({ "statusCode": 200, "headers": [] }),
+            "headers must be an object",
+        ),
+        (
+            json!({ "statusCode": 200, "body": "%%", "isBase64Encoded": true }),
+            "body is not valid base64",
+        ),
+    ] {
+        let err = url_response(Some(&result)).unwrap_err();
+        assert_eq!(err.http_status(), 502);
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[tokio::test]
+async fn each_function_and_qualifier_gets_one_stable_url() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+
+    let err = cp
+        .get_function_url_config("orders", None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    let config = cp.create_function_url_config("orders", None).await.unwrap();
+    assert_eq!(config.function_name, "orders");
+    assert_eq!(config.url_id.len(), 32);
+    assert_eq!(
+        config.function_url,
+        format!("http://127.0.0.1:8000/lambda-url/{}/", config.url_id)
+    );
+    assert_eq!(
+        cp.get_function_url_config("orders", None).await.unwrap(),
+        config
+    );
+    let err = cp
+        .create_function_url_config("orders", None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 409);
+
+    // The URL leads back to the function
+    let (function, qualifier) = cp.resolve_function_url(&config.url_id).await.unwrap();
+    assert_eq!(function.function_name, "orders");
+    assert_eq!(qualifier, None);
+    assert_eq!(
+        cp.resolve_function_url("nope")
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+
+    // A qualifier must name a version or alias
+    let err = cp
+        .create_function_url_config("orders", Some("live"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    cp.delete_function_url_config("orders", None).await.unwrap();
+    assert_eq!(
+        cp.resolve_function_url(&config.url_id)
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+    let recreated = cp.create_function_url_config("orders", None).await.unwrap();
+    assert_ne!(recreated.url_id, config.url_id);
+
+    // The URL goes with its function
+    cp.delete_function("orders").await.unwrap();
+    assert!(cp.resolve_function_url(&recreated.url_id).await.is_err());
+}
+
+#[test]
+fn user_api_urls_follow_the_bind_address_unless_public_url_is_set() {
+    let mut server = Config::default().server;
+    let url = |bind: &str, public_url: Option<&str>| {
+        let mut server = server.clone();
+        server.bind = bind.to_string();
+        server.public_url = public_url.map(str::to_string);
+        user_api_url(&server)
+    };
+    assert_eq!(url("127.0.0.1", None), "http://127.0.0.1:8000");
+    assert_eq!(url("0.0.0.0", None), "http://localhost:8000");
+    assert_eq!(url("::", None), "http://localhost:8000");
+    assert_eq!(url("::1", None), "http://[::1]:8000");
+    assert_eq!(url("[::1]", None), "http://[::1]:8000");
+    assert_eq!(url("lambda.lan", None), "http://lambda.lan:8000");
+    assert_eq!(
+        url("0.0.0.0", Some("https://lambda.example.com/")),
+        "https://lambda.example.com"
+    );
+
+    server.port_user_api = 9000;
+    assert_eq!(user_api_url(&server), "http://127.0.0.1:9000");
+}
@@ -0,0 +1,41 @@
+use crate::common::{control_plane, create_function};
+use lambda_models::FunctionUser;
+
+#[tokio::test]
+async fn containers_run_as_the_stored_user() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    assert_eq!(
+        cp.get_user("orders").await.unwrap(),
+        FunctionUser::default()
+    );
+
+    let user = FunctionUser {
+        uid: 1001,
+        gid: 2000,
+    };
+    cp.put_user("orders", user.clone()).await.unwrap();
+    assert_eq!(cp.get_user("orders").await.unwrap(), user);
+
+    cp.delete_user("orders").await.unwrap();
+    assert_eq!(
+        cp.get_user("orders").await.unwrap().docker_user(),
+        "1000:1000"
+    );
+    assert_eq!(cp.get_user("missing").await.unwrap_err().http_status(), 404);
+}
+
+#[test]
+fn root_and_the_root_group_count_as_root() {
+    assert!(!FunctionUser::default().is_root());
+    assert!(FunctionUser { uid: 0, gid: 1000 }.is_root());
+    assert!(FunctionUser { uid: 1000, gid: 0 }.is_root());
+    assert_eq!(
+        FunctionUser {
+            uid: 65534,
+            gid: 65534
+        }
+        .docker_user(),
+        "65534:65534"
+    );
+}
@@ -0,0 +1,92 @@
+use crate::common::{control_plane_with, create_request, temp_dir, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{ConcurrencyConfig, Config, CreateFunctionRequest, FunctionCode};
+use lambda_testsupport::helpers::{b64, zip_dir};
+use std::path::Path;
+
+async fn control_plane(dir: &Path) -> ControlPlane {
+    let mut config = test_config();
+    config.data.dir = dir.to_string_lossy().into_owned();
+    control_plane_with(config).await
+}
+
+async fn create_function(cp: &ControlPlane, name: &str, zip_file: Option<String>) {
+    cp.create_function(CreateFunctionRequest {
+        code: FunctionCode {
+            zip_file,
+            ..Default::default()
+        },
+        description: Some("orders API".into()),
+        timeout: Some(10),
+        memory_size: Some(256),
+        ..create_request(name)
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn response_has_the_aws_sections() {
+    let dir = temp_dir();
+    let src = dir.path().join("src");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(src.join("index.js"), "exports.handler = async () => 1;").unwrap();
+    let zip = zip_dir(&src).unwrap();
+    let cp = control_plane(dir.path()).await;
+    create_function(&cp, "orders", Some(b64(zip.clone()))).await;
+
+    let response = serde_json::to_value(cp.get_function_response("orders").await.unwrap()).unwrap();
+    let configuration = &response["Configuration"];
+    assert_eq!(configuration["FunctionName"], "orders");
+    assert_eq!(
+        configuration["FunctionArn"],
+        Config::default().identity.function_arn("orders")
+    );
+    assert_eq!(configuration["Runtime"], "nodejs22.x");
+    assert_eq!(configuration["Handler"], "index.handler");
+    assert_eq!(configuration["Description"], "orders API");
+    assert_eq!(configuration["Timeout"], 10);
+    assert_eq!(configuration["MemorySize"], 256);
+    assert_eq!(configuration["PackageType"], "Zip");
+    assert!(configuration["CodeSha256"]
+        .as_str()
+        .is_some_and(|s| !s.is_empty()));
+    assert_eq!(response["Code"]["RepositoryType"], "S3");
+    assert_eq!(
+        response["Code"]["Location"],
+        "http://127.0.0.1:8000/api/functions/orders/code"
+    );
+    assert_eq!(response["Tags"], serde_json::json!({}));
+    assert!(response.get("Concurrency").is_none());
+
+    assert_eq!(cp.function_code("orders").await.unwrap(), zip);
+}
+
+#[tokio::test]
+async fn concurrency_appears_once_reserved() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path()).await;
+    create_function(&cp, "orders", None).await;
+    cp.put_concurrency(
+        "orders",
+        ConcurrencyConfig {
+            reserved_concurrent_executions: Some(5),
+        },
+    )
+    .await
+    .unwrap();
+
+    let response = serde_json::to_value(cp.get_function_response("orders").await.unwrap()).unwrap();
+    assert_eq!(response["Concurrency"]["ReservedConcurrentExecutions"], 5);
+    // Without code there is nothing to download
+    assert!(response["Code"].get("Location").is_none());
+    assert!(cp.function_code("orders").await.is_err());
+}
+
+#[tokio::test]
+async fn unknown_function_is_not_found() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path()).await;
+    let error = cp.get_function_response("missing").await.unwrap_err();
+    assert_eq!(error.http_status(), 404);
+}
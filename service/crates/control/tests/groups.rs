@@ -0,0 +1,204 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    BatchFunctionsRequest, BatchOperation, CreateFunctionGroupRequest, FunctionSelector,
+    InvocationType, InvokeRequest, LambdaError, ListFunctionsQuery, UpdateFunctionGroupRequest,
+};
+use std::time::Duration;
+
+async fn create_group(cp: &ControlPlane, name: &str) {
+    cp.create_group(CreateFunctionGroupRequest {
+        name: name.into(),
+        description: Some(format!("{name} team")),
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn groups_form_a_tree_with_the_ungrouped_functions_apart() {
+    let cp = control_plane().await;
+    for name in ["orders-api", "orders-worker", "billing"] {
+        create_function(&cp, name).await;
+    }
+    create_group(&cp, "orders").await;
+    create_group(&cp, "empty").await;
+    cp.set_function_group("orders-worker", "orders")
+        .await
+        .unwrap();
+    cp.set_function_group("orders-api", "orders").await.unwrap();
+
+    let tree = cp.list_groups().await.unwrap();
+    let groups: Vec<(&str, Vec<String>)> = tree
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.functions.clone()))
+        .collect();
+    assert_eq!(
+        groups,
+        [
+            ("empty", vec![]),
+            (
+                "orders",
+                vec!["orders-api".to_string(), "orders-worker".to_string()]
+            ),
+        ]
+    );
+    assert_eq!(tree.ungrouped, ["billing"]);
+    assert_eq!(
+        cp.get_function_group("orders-api")
+            .await
+            .unwrap()
+            .map(|m| m.group),
+        Some("orders".to_string())
+    );
+    assert!(cp.get_function_group("billing").await.unwrap().is_none());
+
+    let listed = cp
+        .list_functions(&ListFunctionsQuery {
+            group: Some("orders".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let names: Vec<&str> = listed
+        .functions
+        .iter()
+        .map(|f| f.function_name.as_str())
+        .collect();
+    assert_eq!(names, ["orders-api", "orders-worker"]);
+
+    let updated = cp
+        .update_group(
+            "orders",
+            UpdateFunctionGroupRequest {
+                description: Some("checkout".into()),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.description.as_deref(), Some("checkout"));
+
+    // Deleting a group leaves its functions in place, ungrouped
+    cp.delete_group("orders").await.unwrap();
+    assert!(cp.get_function("orders-api").await.is_ok());
+    assert_eq!(
+        cp.list_groups().await.unwrap().ungrouped,
+        ["billing", "orders-api", "orders-worker"]
+    );
+}
+
+#[tokio::test]
+async fn duplicate_and_unknown_groups_are_refused() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    create_group(&cp, "team").await;
+
+    let duplicate = cp
+        .create_group(CreateFunctionGroupRequest {
+            name: "team".into(),
+            description: None,
+        })
+        .await;
+    assert!(matches!(
+        duplicate,
+        Err(LambdaError::GroupAlreadyExists { .. })
+    ));
+    let invalid = cp
+        .create_group(CreateFunctionGroupRequest {
+            name: "no spaces".into(),
+            description: None,
+        })
+        .await;
+    assert!(matches!(invalid, Err(LambdaError::InvalidRequest { .. })));
+
+    let unknown = cp.set_function_group("orders", "ghost").await.unwrap_err();
+    assert_eq!(unknown.http_status(), 404);
+    assert_eq!(cp.get_group("ghost").await.unwrap_err().http_status(), 404);
+    assert_eq!(
+        cp.delete_group("ghost").await.unwrap_err().http_status(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn functions_of_a_disabled_group_are_not_invoked() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    create_group(&cp, "team").await;
+    cp.set_function_group("orders", "team").await.unwrap();
+
+    assert!(cp.set_group_disabled("team", true).await.unwrap().disabled);
+    let error = cp
+        .invoke_function(InvokeRequest {
+            function_name: "orders".into(),
+            invocation_type: InvocationType::RequestResponse,
+            log_type: None,
+            client_context: None,
+            payload: None,
+            qualifier: None,
+            idempotent: false,
+            trace: false,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        LambdaError::FunctionDisabled { ref group, .. } if group == "team"
+    ));
+    assert_eq!(error.http_status(), 409);
+
+    assert!(!cp.set_group_disabled("team", false).await.unwrap().disabled);
+}
+
+#[tokio::test]
+async fn group_operations_and_metrics_cover_its_functions() {
+    let cp = control_plane().await;
+    for name in ["a", "b", "c"] {
+        create_function(&cp, name).await;
+    }
+    create_group(&cp, "team").await;
+    cp.set_function_group("a", "team").await.unwrap();
+    cp.set_function_group("b", "team").await.unwrap();
+
+    let drained = cp.drain_group("team").await.unwrap();
+    let names: Vec<&str> = drained
+        .results
+        .iter()
+        .map(|r| r.function_name.as_str())
+        .collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(drained.failed, 0);
+
+    let response = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: FunctionSelector {
+                    group: Some("team".into()),
+                    ..Default::default()
+                },
+                operation: BatchOperation::UpdateConfiguration {
+                    memory_size: Some(1024),
+                    timeout: None,
+                },
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.succeeded, 2);
+    assert_ne!(cp.get_function("c").await.unwrap().memory_size, 1024);
+
+    let metrics = cp
+        .group_metrics("team", Duration::from_secs(3600))
+        .await
+        .unwrap();
+    assert_eq!((metrics.invocations, metrics.errors), (0, 0));
+    assert_eq!(metrics.error_rate, 0.0);
+    assert_eq!(metrics.avg_duration_ms, None);
+    assert_eq!(metrics.functions.len(), 2);
+    assert!(cp
+        .group_metrics("team", Duration::from_secs(90 * 86400))
+        .await
+        .is_err());
+}
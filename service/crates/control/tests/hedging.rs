@@ -0,0 +1,104 @@
+use crate::common::{control_plane_with, create_request};
+use lambda_control::hedging::{hedge_delay, LatencyTracker, MIN_LATENCY_SAMPLES};
+use lambda_control::queues::Queues;
+use lambda_control::work_item::{FunctionMeta, WorkItem};
+use lambda_models::{Config, HedgingConfig, LambdaError};
+use std::time::Duration;
+
+fn enabled(percentile: Option<f64>, min_delay_ms: Option<u64>) -> HedgingConfig {
+    HedgingConfig {
+        enabled: true,
+        percentile,
+        min_delay_ms,
+    }
+}
+
+#[test]
+fn no_hedge_until_enough_samples() {
+    let latencies = LatencyTracker::new();
+    for _ in 0..MIN_LATENCY_SAMPLES - 1 {
+        latencies.record("fn", 10);
+    }
+    assert_eq!(hedge_delay(&enabled(None, None), &latencies, "fn"), None);
+
+    latencies.record("fn", 10);
+    assert_eq!(
+        hedge_delay(&enabled(None, None), &latencies, "fn"),
+        Some(Duration::from_millis(10))
+    );
+}
+
+#[test]
+fn hedge_delay_follows_percentile_and_floor() {
+    let latencies = LatencyTracker::new();
+    for ms in 1..=100 {
+        latencies.record("fn", ms);
+    }
+    assert_eq!(latencies.percentile("fn", 99.0), Some(99));
+    assert_eq!(latencies.percentile("fn", 50.0), Some(50));
+    assert_eq!(
+        hedge_delay(&enabled(Some(50.0), Some(80)), &latencies, "fn"),
+        Some(Duration::from_millis(80))
+    );
+
+    let disabled = HedgingConfig {
+        enabled: false,
+        percentile: None,
+        min_delay_ms: None,
+    };
+    assert_eq!(hedge_delay(&disabled, &latencies, "fn"), None);
+}
+
+#[test]
+fn queued_copy_can_be_removed() {
+    let queues = Queues::new();
+    let item = |id: &str| WorkItem {
+        request_id: id.to_string(),
+        function: FunctionMeta {
+            function_name: "fn".into(),
+            runtime: "nodejs22.x".into(),
+            version: None,
+            environment: None,
+            timeout_ms: 3000,
+        },
+        payload: Vec::new(),
+        deadline_ms: 0,
+        log_type: None,
+        client_context: None,
+        cognito_identity: None,
+    };
+    queues.push(item("a")).unwrap();
+    queues.push(item("b")).unwrap();
+    let key = lambda_control::queues::FnKey::from_work_item(&item("a"));
+
+    assert!(queues.remove(&key, "a"));
+    assert!(!queues.remove(&key, "a"));
+    assert_eq!(queues.queue_size(&key), 1);
+}
+
+#[tokio::test]
+async fn hedging_config_round_trips() {
+    let config = Config::default();
+    let cp = control_plane_with(config).await;
+
+    cp.create_function(create_request("reader")).await.unwrap();
+
+    assert!(!cp.get_hedging("reader").await.unwrap().enabled);
+
+    cp.put_hedging("reader", enabled(Some(95.0), Some(25)))
+        .await
+        .unwrap();
+    assert_eq!(
+        cp.get_hedging("reader").await.unwrap(),
+        enabled(Some(95.0), Some(25))
+    );
+
+    let err = cp
+        .put_hedging("reader", enabled(Some(150.0), None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, LambdaError::InvalidRequest { .. }));
+
+    cp.delete_hedging("reader").await.unwrap();
+    assert!(!cp.get_hedging("reader").await.unwrap().enabled);
+}
@@ -0,0 +1,89 @@
+use crate::common::{control_plane, create_function};
+use lambda_models::{FunctionHooks, HookFailurePolicy, HookTarget, InvocationHook};
+
+fn hook(target: HookTarget) -> InvocationHook {
+    InvocationHook {
+        target,
+        timeout_ms: 1_000,
+        on_failure: HookFailurePolicy::Fail,
+    }
+}
+
+fn function(name: &str) -> HookTarget {
+    HookTarget::Function {
+        function_name: name.into(),
+    }
+}
+
+fn pre_invoke(target: HookTarget) -> FunctionHooks {
+    FunctionHooks {
+        pre_invoke: Some(hook(target)),
+        post_invoke: None,
+    }
+}
+
+#[tokio::test]
+async fn hooks_are_stored_per_function() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    create_function(&cp, "auth").await;
+    assert!(cp.get_hooks("orders").await.unwrap().is_empty());
+
+    let hooks = FunctionHooks {
+        pre_invoke: Some(hook(function("auth"))),
+        post_invoke: Some(InvocationHook {
+            on_failure: HookFailurePolicy::Continue,
+            ..hook(HookTarget::Webhook {
+                url: "https://audit.example.com/hook".into(),
+            })
+        }),
+    };
+    cp.put_hooks("orders", hooks.clone()).await.unwrap();
+    assert_eq!(cp.get_hooks("orders").await.unwrap(), hooks);
+    assert!(cp.get_hooks("auth").await.unwrap().is_empty());
+
+    cp.delete_hooks("orders").await.unwrap();
+    assert!(cp.get_hooks("orders").await.unwrap().is_empty());
+    assert_eq!(
+        cp.get_hooks("missing").await.unwrap_err().http_status(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn invalid_hooks_are_refused() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+
+    let slow = FunctionHooks {
+        pre_invoke: Some(InvocationHook {
+            timeout_ms: 120_000,
+            ..hook(function("orders-auth"))
+        }),
+        post_invoke: None,
+    };
+    for hooks in [
+        pre_invoke(function("orders")),
+        pre_invoke(function("missing")),
+        pre_invoke(function("not a name")),
+        pre_invoke(HookTarget::Webhook {
+            url: "ftp://example.com".into(),
+        }),
+        slow,
+    ] {
+        let err = cp.put_hooks("orders", hooks).await.unwrap_err();
+        assert_eq!(err.http_status(), 400, "{err}");
+    }
+    assert!(cp.get_hooks("orders").await.unwrap().is_empty());
+}
+
+#[test]
+fn hooks_fail_invocations_by_default() {
+    let hooks: FunctionHooks = serde_json::from_value(serde_json::json!({
+        "pre_invoke": { "target": { "type": "webhook", "url": "http://localhost:9000/auth" } }
+    }))
+    .unwrap();
+    let hook = hooks.pre_invoke.unwrap();
+    assert_eq!(hook.timeout_ms, 3_000);
+    assert_eq!(hook.on_failure, HookFailurePolicy::Fail);
+}
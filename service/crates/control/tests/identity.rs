@@ -0,0 +1,110 @@
+use crate::common::{control_plane_with, create_request, test_config};
+use chrono::Utc;
+use lambda_control::event_sources::sqs_event;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    Config, CreateEventSourceMappingRequest, CreateQueueOptions, IdentityConfig, ReceivedMessage,
+};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+fn identity() -> IdentityConfig {
+    IdentityConfig {
+        partition: "aws-cn".into(),
+        region: "cn-north-1".into(),
+        account_id: "123456789012".into(),
+    }
+}
+
+#[test]
+fn arns_use_the_configured_identity() {
+    let identity = identity();
+    assert!(identity.validate().is_ok());
+    assert_eq!(
+        identity.function_arn("hello"),
+        "arn:aws-cn:lambda:cn-north-1:123456789012:function:hello"
+    );
+    assert_eq!(
+        identity.queue_arn("jobs"),
+        "arn:aws-cn:sqs:cn-north-1:123456789012:jobs"
+    );
+    assert_eq!(identity.bucket_arn("photos"), "arn:aws-cn:s3:::photos");
+
+    let message = ReceivedMessage {
+        message_id: "a".into(),
+        receipt_handle: "rh-a".into(),
+        body: "{}".into(),
+        md5_of_body: String::new(),
+        receive_count: 1,
+        sent_at: Utc::now(),
+        first_received_at: Utc::now(),
+    };
+    let event = serde_json::to_value(sqs_event(&identity, "jobs", &[message])).unwrap();
+    let record = &event["Records"][0];
+    assert_eq!(record["awsRegion"], "cn-north-1");
+    assert_eq!(record["attributes"]["SenderId"], "123456789012");
+    assert_eq!(
+        record["eventSourceARN"],
+        "arn:aws-cn:sqs:cn-north-1:123456789012:jobs"
+    );
+}
+
+#[test]
+fn identities_are_validated() {
+    assert!(IdentityConfig::default().validate().is_ok());
+    let mut short_account = identity();
+    short_account.account_id = "1234".into();
+    assert_eq!(short_account.validate().unwrap_err().http_status(), 400);
+    let mut bad_region = identity();
+    bad_region.region = "us:east".into();
+    assert!(bad_region.validate().is_err());
+    let mut no_partition = identity();
+    no_partition.partition = String::new();
+    assert!(no_partition.validate().is_err());
+}
+
+#[tokio::test]
+async fn functions_and_mappings_report_arns_under_the_identity() {
+    let mut config = test_config();
+    config.identity = identity();
+    let cp = control_plane_with(config).await;
+    cp.create_function(create_request("worker")).await.unwrap();
+
+    let function = cp.describe_function("worker").await.unwrap();
+    assert_eq!(
+        function.function_arn.as_deref(),
+        Some("arn:aws-cn:lambda:cn-north-1:123456789012:function:worker")
+    );
+
+    cp.sqs()
+        .create_queue("jobs", CreateQueueOptions::default())
+        .await
+        .unwrap();
+    // ARNs of local queues are accepted whatever identity they were written with
+    let mapping = cp
+        .create_event_source_mapping(CreateEventSourceMappingRequest {
+            event_source_arn: "arn:aws:sqs:local:000000000000:jobs".into(),
+            function_name: "worker".into(),
+            batch_size: None,
+            enabled: Some(false),
+            function_response_types: Vec::new(),
+            s3_key_filter: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        mapping.event_source_arn,
+        "arn:aws-cn:sqs:cn-north-1:123456789012:jobs"
+    );
+    let stored = cp.get_event_source_mapping(mapping.uuid).await.unwrap();
+    assert_eq!(stored.event_source_arn, mapping.event_source_arn);
+}
+
+#[tokio::test]
+async fn an_invalid_identity_is_rejected_at_startup() {
+    let mut config = Config::default();
+    config.identity.account_id = "not-an-account".into();
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    assert!(ControlPlane::new(pool, invoker, config).await.is_err());
+}
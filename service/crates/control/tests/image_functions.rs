@@ -0,0 +1,107 @@
+use crate::common::{control_plane, create_request};
+use lambda_control::validation::is_valid_image_uri;
+use lambda_models::{CreateFunctionRequest, FunctionCode, FunctionState, PackageType};
+
+fn image_request(
+    name: &str,
+    package_type: Option<PackageType>,
+    image_uri: Option<&str>,
+) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        code: FunctionCode {
+            image_uri: image_uri.map(Into::into),
+            ..Default::default()
+        },
+        package_type,
+        ..create_request(name)
+    }
+}
+
+#[test]
+fn image_uris_are_docker_references() {
+    assert!(is_valid_image_uri("orders"));
+    assert!(is_valid_image_uri(
+        "registry.example.com:5000/team/orders:1.4"
+    ));
+    assert!(is_valid_image_uri(
+        "orders@sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+    ));
+    assert!(!is_valid_image_uri(""));
+    assert!(!is_valid_image_uri("-orders"));
+    assert!(!is_valid_image_uri("orders latest"));
+    assert!(!is_valid_image_uri(&"a".repeat(600)));
+}
+
+#[tokio::test]
+async fn image_functions_run_their_image() {
+    let cp = control_plane().await;
+    let image = "registry.example.com/orders:1.4";
+    let created = cp
+        .create_function(image_request(
+            "orders",
+            Some(PackageType::Image),
+            Some(image),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(created.package_type, PackageType::Image);
+    assert_eq!(created.state, FunctionState::Active);
+    assert_eq!(created.code_size, 0);
+    assert_eq!(created.code_sha256.len(), 64);
+
+    let function = cp.get_function("orders").await.unwrap();
+    assert_eq!(function.package_type, PackageType::Image);
+    assert_eq!(function.image_uri.as_deref(), Some(image));
+    assert_eq!(function.image_ref(), image);
+
+    // ZIP functions keep running the image built from their code
+    let zip = cp
+        .create_function(image_request("billing", None, None))
+        .await
+        .unwrap();
+    assert_eq!(zip.package_type, PackageType::Zip);
+    assert_eq!(zip.image_ref(), "lambda-home/billing:");
+}
+
+#[tokio::test]
+async fn the_code_must_match_the_package_type() {
+    let cp = control_plane().await;
+
+    let err = cp
+        .create_function(image_request("orders", Some(PackageType::Image), None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    assert!(err.to_string().contains("requires code.image_uri"), "{err}");
+
+    let err = cp
+        .create_function(image_request("orders", None, Some("orders:1")))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("requires package_type Image"),
+        "{err}"
+    );
+
+    let mut request = image_request("orders", Some(PackageType::Image), Some("orders:1"));
+    request.code.zip_file = Some("UEsFBgAAAAAAAAAAAAAAAAAAAAAAAA==".into());
+    let err = cp.create_function(request).await.unwrap_err();
+    assert!(err.to_string().contains("image_uri only"), "{err}");
+
+    let problems = cp
+        .validate_function(&image_request(
+            "orders",
+            Some(PackageType::Image),
+            Some("orders latest"),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].field, "code.image_uri");
+    assert_eq!(problems[0].code, "InvalidImageUri");
+
+    // Images start their own handler
+    let mut request = image_request("orders", Some(PackageType::Image), Some("orders:1"));
+    request.handler = String::new();
+    assert!(cp.validate_function(&request).await.unwrap().is_empty());
+}
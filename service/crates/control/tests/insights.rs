@@ -0,0 +1,109 @@
+use crate::common::{control_plane_and_pool, create_request, test_config};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::insights::{percentile, trend, ThrottleTracker};
+use lambda_models::TrendDirection;
+use std::time::Duration;
+
+#[test]
+fn trends_compare_with_the_previous_window() {
+    assert_eq!(trend(Some(0.5), Some(0.2)).direction, TrendDirection::Up);
+    assert_eq!(
+        trend(Some(100.0), Some(200.0)).direction,
+        TrendDirection::Down
+    );
+    assert_eq!(
+        trend(Some(105.0), Some(100.0)).direction,
+        TrendDirection::Flat
+    );
+    assert_eq!(trend(Some(0.0), Some(0.0)).direction, TrendDirection::Flat);
+    assert_eq!(trend(Some(0.1), Some(0.0)).direction, TrendDirection::Up);
+    assert_eq!(trend(Some(0.1), None).direction, TrendDirection::Unknown);
+
+    assert_eq!(percentile(&[], 95.0), None);
+    assert_eq!(percentile(&[7], 95.0), Some(7));
+    let durations: Vec<u64> = (1..=100).collect();
+    assert_eq!(percentile(&durations, 95.0), Some(95));
+}
+
+#[test]
+fn throttles_are_counted_within_the_window() {
+    let throttles = ThrottleTracker::new();
+    let now = Utc::now();
+    throttles.record("orders", now - ChronoDuration::hours(30));
+    throttles.record("orders", now - ChronoDuration::minutes(5));
+    throttles.record("orders", now);
+    assert_eq!(
+        throttles.count_since("orders", now - ChronoDuration::hours(24)),
+        2
+    );
+    assert_eq!(
+        throttles.count_since("billing", now - ChronoDuration::hours(24)),
+        0
+    );
+    throttles.remove_function("orders");
+    assert_eq!(
+        throttles.count_since("orders", now - ChronoDuration::days(7)),
+        0
+    );
+}
+
+#[tokio::test]
+async fn insights_compare_the_window_with_the_one_before() {
+    let (cp, pool) = control_plane_and_pool(test_config()).await;
+    let function = cp.create_function(create_request("orders")).await.unwrap();
+
+    // The last hour fails half the time and got slower; the hour before never failed
+    let rows = [
+        ("c1", "Success", 100, 10),
+        ("c2", "Failed", 400, 20),
+        ("p1", "Success", 100, 70),
+        ("p2", "Success", 120, 80),
+        ("old", "Failed", 900, 200),
+    ];
+    for (id, status, duration_ms, minutes_ago) in rows {
+        sqlx::query(
+            "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, duration_ms, status)
+             VALUES (?, ?, '$LATEST', ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(function.function_id)
+        .bind(id)
+        .bind(Utc::now() - ChronoDuration::minutes(minutes_ago))
+        .bind(duration_ms)
+        .bind(status)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let insights = cp
+        .function_insights("orders", Duration::from_secs(3600))
+        .await
+        .unwrap();
+    assert_eq!(insights.window_seconds, 3600);
+    assert_eq!(insights.invocations, 2);
+    assert_eq!(insights.errors, 1);
+    assert_eq!(insights.error_rate.current, Some(0.5));
+    assert_eq!(insights.error_rate.previous, Some(0.0));
+    assert_eq!(insights.error_rate.direction, TrendDirection::Up);
+    assert_eq!(insights.p95_duration_ms.current, Some(400.0));
+    assert_eq!(insights.p95_duration_ms.previous, Some(120.0));
+    assert_eq!(insights.p95_duration_ms.direction, TrendDirection::Up);
+    // Creating the function is its first configuration revision
+    assert_eq!(insights.config_changes, 1);
+    assert!(insights.last_config_change.is_some());
+    assert_eq!(insights.cold_start_ratio, None);
+    assert_eq!(insights.throttles, 0);
+    assert_eq!(insights.warm_pool.total, 0);
+
+    let err = cp
+        .function_insights("orders", Duration::from_secs(8 * 24 * 3600))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    let err = cp
+        .function_insights("missing", Duration::from_secs(3600))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+}
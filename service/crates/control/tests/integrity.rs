@@ -0,0 +1,143 @@
+use crate::common::{control_plane_with, create_function, test_config};
+use lambda_control::integrity::dangling_route_references;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    ApiRoute, CanaryMatch, CreateApiRouteRequest, CreateEventSourceMappingRequest,
+    CreateQueueOptions, LambdaError, ReferenceKind, RouteCanary,
+};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+async fn control_plane(cascade: bool) -> ControlPlane {
+    let mut config = test_config();
+    config.integrity.cascade_function_deletes = cascade;
+    control_plane_with(config).await
+}
+
+async fn create_route(cp: &ControlPlane, path: &str, function_name: &str) -> ApiRoute {
+    cp.create_api_route(CreateApiRouteRequest {
+        path: path.into(),
+        method: None,
+        function_name: function_name.into(),
+        canary: None,
+    })
+    .await
+    .unwrap()
+}
+
+fn canary(function_name: &str) -> RouteCanary {
+    RouteCanary {
+        function_name: function_name.into(),
+        qualifier: None,
+        condition: CanaryMatch::Percentage { percent: 10.0 },
+    }
+}
+
+#[tokio::test]
+async fn functions_still_referenced_are_not_deleted() {
+    let cp = control_plane(false).await;
+    create_function(&cp, "orders").await;
+    create_function(&cp, "orders-v2").await;
+    let route = create_route(&cp, "/orders", "orders").await;
+    cp.put_api_route_canary(route.route_id, canary("orders-v2"))
+        .await
+        .unwrap();
+    cp.sqs()
+        .create_queue("jobs", CreateQueueOptions::default())
+        .await
+        .unwrap();
+    cp.create_event_source_mapping(CreateEventSourceMappingRequest {
+        event_source_arn: cp.identity().queue_arn("jobs"),
+        function_name: "orders".into(),
+        batch_size: None,
+        enabled: Some(false),
+        function_response_types: Vec::new(),
+        s3_key_filter: None,
+    })
+    .await
+    .unwrap();
+
+    let err = cp.delete_function("orders").await.unwrap_err();
+    assert_eq!(err.http_status(), 409);
+    let LambdaError::FunctionInUse { references, .. } = &err else {
+        panic!("unexpected error {err}");
+    };
+    let kinds: Vec<_> = references.iter().map(|r| r.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![ReferenceKind::ApiRoute, ReferenceKind::EventSourceMapping]
+    );
+    assert_eq!(references[0].id, route.route_id.to_string());
+    let shape = err.to_error_shape();
+    assert_eq!(shape.error_type, "ResourceConflictException");
+    assert_eq!(shape.references.as_ref().unwrap().len(), 2);
+
+    let err = cp.delete_function("orders-v2").await.unwrap_err();
+    let LambdaError::FunctionInUse { references, .. } = err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(references[0].kind, ReferenceKind::RouteCanary);
+    assert_eq!(references[0].resource, "/orders");
+    assert!(cp.get_function("orders").await.is_ok());
+    assert!(cp.get_function("orders-v2").await.is_ok());
+
+    // Once nothing names it the function goes
+    cp.delete_api_route_canary(route.route_id).await.unwrap();
+    cp.delete_function("orders-v2").await.unwrap();
+    // A missing function is still a 404
+    let err = cp.delete_function("orders-v2").await.unwrap_err();
+    assert_eq!(err.http_status(), 404);
+}
+
+#[tokio::test]
+async fn cascading_deletes_remove_routes_and_clear_canaries() {
+    let cp = control_plane(true).await;
+    create_function(&cp, "orders").await;
+    create_function(&cp, "orders-v2").await;
+    let primary = create_route(&cp, "/orders", "orders").await;
+    let secondary = create_route(&cp, "/orders/v2", "orders-v2").await;
+    cp.put_api_route_canary(secondary.route_id, canary("orders"))
+        .await
+        .unwrap();
+
+    cp.delete_function("orders").await.unwrap();
+    assert!(cp.get_api_route(primary.route_id).await.is_err());
+    let kept = cp.get_api_route(secondary.route_id).await.unwrap();
+    assert_eq!(kept.function_name, "orders-v2");
+    assert!(kept.canary.is_none());
+    assert!(cp.consistency_report().await.unwrap().dangling.is_empty());
+}
+
+#[test]
+fn routes_to_missing_functions_are_dangling() {
+    let route = |function_name: &str, canary_function: Option<&str>| ApiRoute {
+        route_id: Uuid::new_v4(),
+        path: format!("/{function_name}"),
+        method: None,
+        function_name: function_name.into(),
+        created_at: chrono::Utc::now(),
+        canary: canary_function.map(canary),
+        access_log: true,
+        mock: None,
+        recording: None,
+        limits: None,
+        transform: None,
+        invocation: None,
+        jwt_authorizer: None,
+    };
+    let routes = vec![
+        route("orders", Some("gone")),
+        route("removed", None),
+        route("users", None),
+    ];
+    let functions: HashSet<String> = ["orders", "users"].map(String::from).into();
+
+    let dangling = dangling_route_references(&routes, &functions);
+    assert_eq!(dangling.len(), 2);
+    assert_eq!(dangling[0].kind, ReferenceKind::RouteCanary);
+    assert_eq!(dangling[0].function_name, "gone");
+    assert_eq!(dangling[0].resource, "/orders");
+    assert_eq!(dangling[1].kind, ReferenceKind::ApiRoute);
+    assert_eq!(dangling[1].function_name, "removed");
+    assert_eq!(dangling[1].to_string(), "route /removed");
+}
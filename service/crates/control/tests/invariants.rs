@@ -0,0 +1,389 @@
+//! Property tests for the invariants the scheduler relies on under races: queued work is popped
+//! exactly once without lost wakeups, a pending request completes at most once and leaves
+//! nothing behind, and warm pool membership survives concurrent changes. Operations run on
+//! real threads in proptest-chosen mixes; DashMap and tokio's `Notify` are not
+//! loom-instrumented, so their interleavings cannot be explored exhaustively.
+
+use lambda_control::pending::{InvocationResult, Pending};
+use lambda_control::queues::{FnKey, Queues};
+use lambda_control::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use lambda_control::work_item::{FunctionMeta, WorkItem};
+use proptest::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Long enough that only a lost wakeup or a deadlock runs into it
+const STUCK: Duration = Duration::from_secs(10);
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+fn meta(function_name: &str) -> FunctionMeta {
+    FunctionMeta {
+        function_name: function_name.into(),
+        runtime: "nodejs22.x".into(),
+        version: None,
+        environment: None,
+        timeout_ms: 1000,
+    }
+}
+
+fn work_item(function_name: &str, request_id: String) -> WorkItem {
+    WorkItem {
+        request_id,
+        function: meta(function_name),
+        payload: b"{}".to_vec(),
+        deadline_ms: 0,
+        log_type: None,
+        client_context: None,
+        cognito_identity: None,
+    }
+}
+
+fn key(function_name: &str) -> FnKey {
+    FnKey::from_work_item(&work_item(function_name, String::new()))
+}
+
+fn container(container_id: &str, function_id: Uuid) -> WarmContainer {
+    WarmContainer {
+        container_id: container_id.into(),
+        instance_id: format!("inst-{container_id}"),
+        function_id,
+        image_ref: "lambda-home/fn:abc".into(),
+        memory_mb: 128,
+        created_at: Instant::now(),
+        last_used: Instant::now(),
+        state: InstanceState::WarmIdle,
+    }
+}
+
+/// Consumers of one function's queue and the work pushed to it
+#[derive(Clone, Debug)]
+struct QueueLoad {
+    consumers: usize,
+    abandoned_polls: usize,
+    /// Yields before each push, to spread pushes among the pops
+    push_delays: Vec<u8>,
+}
+
+fn queue_load() -> impl Strategy<Value = QueueLoad> {
+    (1..6usize, 0..4usize, prop::collection::vec(0..4u8, 0..24)).prop_map(
+        |(consumers, abandoned_polls, push_delays)| QueueLoad {
+            consumers,
+            abandoned_polls,
+            push_delays,
+        },
+    )
+}
+
+/// One step racing for a pending request
+#[derive(Clone, Copy, Debug)]
+enum PendingOp {
+    Complete,
+    TimeOut,
+    Cancel,
+    Assign(u8),
+    FailContainer(u8),
+}
+
+fn pending_op() -> impl Strategy<Value = PendingOp> {
+    prop_oneof![
+        Just(PendingOp::Complete),
+        Just(PendingOp::TimeOut),
+        Just(PendingOp::Cancel),
+        (0..3u8).prop_map(PendingOp::Assign),
+        (0..3u8).prop_map(PendingOp::FailContainer),
+    ]
+}
+
+#[derive(Clone, Debug)]
+enum PoolOp {
+    Add { function: u8 },
+    Remove { pick: usize },
+    MarkActive { pick: usize },
+    MarkIdle { pick: usize },
+    Drain { function: u8 },
+}
+
+fn pool_op() -> impl Strategy<Value = PoolOp> {
+    prop_oneof![
+        3 => (0..2u8).prop_map(|function| PoolOp::Add { function }),
+        1 => any::<usize>().prop_map(|pick| PoolOp::Remove { pick }),
+        1 => any::<usize>().prop_map(|pick| PoolOp::MarkActive { pick }),
+        1 => any::<usize>().prop_map(|pick| PoolOp::MarkIdle { pick }),
+        1 => (0..2u8).prop_map(|function| PoolOp::Drain { function }),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn queued_work_is_popped_exactly_once(loads in prop::collection::vec(queue_load(), 1..4)) {
+        let rt = runtime();
+        rt.block_on(async move {
+            let queues = Queues::new();
+            let mut consumers = Vec::new();
+            let mut producers = Vec::new();
+            let mut pushed = HashSet::new();
+            for (f, load) in loads.iter().enumerate() {
+                let function = format!("fn-{f}");
+                // Polls whose runtime went away still sit in the queue's waiter list
+                for _ in 0..load.abandoned_polls {
+                    let queues = queues.clone();
+                    let key = key(&function);
+                    let poll = tokio::spawn(async move { queues.pop_or_wait(&key).await });
+                    tokio::task::yield_now().await;
+                    poll.abort();
+                }
+                for _ in 0..load.consumers {
+                    let queues = queues.clone();
+                    let key = key(&function);
+                    consumers.push(tokio::spawn(async move {
+                        let mut popped = Vec::new();
+                        loop {
+                            let item = queues.pop_or_wait(&key).await.unwrap();
+                            if item.request_id.starts_with("stop-") {
+                                return popped;
+                            }
+                            popped.push(item.request_id);
+                        }
+                    }));
+                }
+                let requests: Vec<String> = (0..load.push_delays.len())
+                    .map(|n| format!("{function}-{n}"))
+                    .collect();
+                pushed.extend(requests.iter().cloned());
+                let queues = queues.clone();
+                let delays = load.push_delays.clone();
+                producers.push(tokio::spawn(async move {
+                    for (request_id, delay) in requests.into_iter().zip(delays) {
+                        for _ in 0..delay {
+                            tokio::task::yield_now().await;
+                        }
+                        queues.push(work_item(&function, request_id)).unwrap();
+                    }
+                }));
+            }
+            for producer in producers {
+                producer.await.unwrap();
+            }
+            // Queues are FIFO, so every consumer stops only after all real work was taken
+            for (f, load) in loads.iter().enumerate() {
+                for n in 0..load.consumers {
+                    queues
+                        .push(work_item(&format!("fn-{f}"), format!("stop-{n}")))
+                        .unwrap();
+                }
+            }
+
+            let mut popped = Vec::new();
+            for consumer in consumers {
+                let items = tokio::time::timeout(STUCK, consumer)
+                    .await
+                    .expect("a consumer missed its wakeup")
+                    .unwrap();
+                popped.extend(items);
+            }
+            let distinct: HashSet<String> = popped.iter().cloned().collect();
+            assert_eq!(distinct.len(), popped.len(), "work was popped twice");
+            assert_eq!(distinct, pushed, "work was lost");
+            assert_eq!(queues.total_queued(), 0);
+        });
+    }
+
+    #[test]
+    fn pending_requests_complete_at_most_once(
+        requests in prop::collection::vec(prop::collection::vec(pending_op(), 1..6), 1..24)
+    ) {
+        let rt = runtime();
+        rt.block_on(async move {
+            let pending = Pending::new();
+            let mut receivers = Vec::new();
+            let mut tasks = Vec::new();
+            for (n, ops) in requests.iter().enumerate() {
+                let request_id = format!("req-{n}");
+                receivers.push(pending.register(request_id.clone()));
+                for op in ops.clone() {
+                    let pending = pending.clone();
+                    let request_id = request_id.clone();
+                    tasks.push(tokio::spawn(async move {
+                        tokio::task::yield_now().await;
+                        match op {
+                            PendingOp::Complete => (
+                                pending.complete(&request_id, InvocationResult::ok(b"ok".to_vec()))
+                                    as usize,
+                                0,
+                            ),
+                            PendingOp::TimeOut => (
+                                pending.fail_if_waiting(&request_id, "Unhandled", Vec::new())
+                                    as usize,
+                                0,
+                            ),
+                            PendingOp::Cancel => (0, pending.cancel(&request_id) as usize),
+                            PendingOp::Assign(c) => {
+                                pending.assign(&request_id, format!("container-{c}"));
+                                (0, 0)
+                            }
+                            PendingOp::FailContainer(c) => (
+                                pending.fail_container(&format!("container-{c}"), "exited"),
+                                0,
+                            ),
+                        }
+                    }));
+                }
+            }
+            let (mut delivered, mut cancelled) = (0, 0);
+            for task in tasks {
+                let (d, c) = tokio::time::timeout(STUCK, task).await.unwrap().unwrap();
+                delivered += d;
+                cancelled += c;
+            }
+            // Requests nobody finished are dropped, as a caller giving up does
+            for n in 0..requests.len() {
+                cancelled += pending.cancel(&format!("req-{n}")) as usize;
+            }
+
+            assert_eq!(delivered + cancelled, requests.len(), "a request finished twice");
+            let received = receivers
+                .into_iter()
+                .filter_map(|mut rx| rx.try_recv().ok())
+                .count();
+            assert_eq!(received, delivered);
+            assert_eq!(pending.waiting(), 0, "a waiter was left behind");
+            assert_eq!(pending.assigned(), 0, "an assignment was left behind");
+        });
+    }
+
+    #[test]
+    fn warm_pool_matches_a_model(ops in prop::collection::vec(pool_op(), 1..40)) {
+        let rt = runtime();
+        rt.block_on(async move {
+            let pool = WarmPool::new();
+            let functions = [Uuid::new_v4(), Uuid::new_v4()];
+            // container id -> (function, state), in insertion order
+            let mut model: Vec<(String, u8, InstanceState)> = Vec::new();
+            for (n, op) in ops.into_iter().enumerate() {
+                match op {
+                    PoolOp::Add { function } => {
+                        let id = format!("c{n}");
+                        pool.add_warm_container(
+                            key(&format!("fn-{function}")),
+                            container(&id, functions[function as usize]),
+                        )
+                        .await;
+                        model.push((id, function, InstanceState::WarmIdle));
+                    }
+                    PoolOp::Remove { pick } if !model.is_empty() => {
+                        let (id, _, _) = model.remove(pick % model.len());
+                        pool.remove_container_by_id(&id).await.unwrap();
+                    }
+                    PoolOp::MarkActive { pick } if !model.is_empty() => {
+                        let i = pick % model.len();
+                        pool.mark_active_by_instance(&format!("inst-{}", model[i].0))
+                            .await
+                            .unwrap();
+                        model[i].2 = InstanceState::Active;
+                    }
+                    PoolOp::MarkIdle { pick } if !model.is_empty() => {
+                        let i = pick % model.len();
+                        pool.mark_idle_by_instance(&format!("inst-{}", model[i].0))
+                            .await
+                            .unwrap();
+                        model[i].2 = InstanceState::WarmIdle;
+                    }
+                    PoolOp::Drain { function } => {
+                        let mut drained =
+                            pool.drain_by_function_id(functions[function as usize]).await;
+                        let mut expected: Vec<String> = model
+                            .iter()
+                            .filter(|(_, f, _)| *f == function)
+                            .map(|(id, _, _)| id.clone())
+                            .collect();
+                        drained.sort();
+                        expected.sort();
+                        assert_eq!(drained, expected);
+                        model.retain(|(_, f, _)| *f != function);
+                    }
+                    _ => {}
+                }
+
+                assert_eq!(pool.total_container_count().await, model.len());
+                for function in 0..2u8 {
+                    let key = key(&format!("fn-{function}"));
+                    let count = |state: InstanceState| {
+                        model
+                            .iter()
+                            .filter(|(_, f, s)| *f == function && *s == state)
+                            .count()
+                    };
+                    assert_eq!(
+                        pool.count_state(&key, InstanceState::WarmIdle).await,
+                        count(InstanceState::WarmIdle)
+                    );
+                    assert_eq!(
+                        pool.count_state(&key, InstanceState::Active).await,
+                        count(InstanceState::Active)
+                    );
+                    assert_eq!(
+                        pool.has_available(&key).await,
+                        count(InstanceState::WarmIdle) > 0
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn containers_added_during_removals_are_kept(
+        adds in 1..16usize,
+        removals in 1..16usize,
+    ) {
+        let rt = runtime();
+        rt.block_on(async move {
+            let pool = WarmPool::new();
+            let key = key("fn");
+            let function_id = Uuid::new_v4();
+            for n in 0..removals {
+                pool.add_warm_container(key.clone(), container(&format!("old-{n}"), function_id))
+                    .await;
+            }
+            let mut tasks = Vec::new();
+            for n in 0..removals.max(adds) {
+                if n < removals {
+                    let pool = pool.clone();
+                    tasks.push(tokio::spawn(async move {
+                        pool.remove_container_by_id(&format!("old-{n}")).await.unwrap();
+                    }));
+                }
+                if n < adds {
+                    let pool = pool.clone();
+                    let key = key.clone();
+                    tasks.push(tokio::spawn(async move {
+                        pool.add_warm_container(key, container(&format!("new-{n}"), function_id))
+                            .await;
+                    }));
+                }
+            }
+            for task in tasks {
+                task.await.unwrap();
+            }
+            // Emptying the key's list must not drop containers added to it meanwhile
+            assert_eq!(pool.container_count(&key).await, adds);
+            let ids: HashMap<String, InstanceState> = pool
+                .list_all_containers()
+                .await
+                .into_iter()
+                .flat_map(|(_, containers)| containers)
+                .map(|c| (c.container_id, c.state))
+                .collect();
+            assert!((0..adds).all(|n| ids.contains_key(&format!("new-{n}"))));
+        });
+    }
+}
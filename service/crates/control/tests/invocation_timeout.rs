@@ -0,0 +1,118 @@
+use crate::common::control_plane;
+use lambda_control::pending::InvocationResult;
+use lambda_control::queues::FnKey;
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_control::work_item::{FunctionMeta, WorkItem};
+use lambda_models::RuntimeResponse;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+use uuid::Uuid;
+
+fn work_item(request_id: Uuid) -> WorkItem {
+    WorkItem {
+        request_id: request_id.to_string(),
+        function: FunctionMeta {
+            function_name: "orders".into(),
+            runtime: "nodejs22.x".into(),
+            version: None,
+            environment: None,
+            timeout_ms: 3000,
+        },
+        payload: b"{}".to_vec(),
+        deadline_ms: 0,
+        log_type: None,
+        client_context: None,
+        cognito_identity: None,
+    }
+}
+
+#[tokio::test]
+async fn invocations_that_timed_out_in_the_queue_are_never_dispatched() {
+    let cp = control_plane().await;
+    let (late, fresh) = (Uuid::new_v4(), Uuid::new_v4());
+    let _late_rx = cp.pending().register(late.to_string());
+    let _fresh_rx = cp.pending().register(fresh.to_string());
+    let key = FnKey::from_work_item(&work_item(late));
+    cp.queues().push(work_item(late)).unwrap();
+    cp.queues().push(work_item(fresh)).unwrap();
+
+    assert!(cp.pending().discard(
+        &late.to_string(),
+        InvocationResult::timed_out(3000),
+        chrono::Utc::now().timestamp_millis(),
+    ));
+
+    let invocation = timeout(
+        Duration::from_secs(1),
+        cp.get_next_invocation(&key.function_name, &key.runtime, None, Some(&key.env_hash)),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(invocation.aws_request_id, fresh);
+    assert_eq!(cp.queues().queue_size(&key), 0);
+}
+
+#[tokio::test]
+async fn results_of_discarded_invocations_are_refused() {
+    let cp = control_plane().await;
+    let request_id = Uuid::new_v4();
+    let _rx = cp.pending().register(request_id.to_string());
+    assert!(cp.pending().discard(
+        &request_id.to_string(),
+        InvocationResult::timed_out(3000),
+        chrono::Utc::now().timestamp_millis(),
+    ));
+
+    let late = cp
+        .post_response(
+            RuntimeResponse {
+                aws_request_id: request_id,
+                payload: serde_json::json!("late"),
+            },
+            None,
+        )
+        .await;
+    assert!(late.is_err());
+}
+
+#[tokio::test]
+async fn containers_of_invocations_their_caller_gave_up_on_are_killed() {
+    let cp = control_plane().await;
+    let request_id = Uuid::new_v4().to_string();
+    let key = FnKey::from_work_item(&work_item(Uuid::new_v4()));
+    cp.warm_pool()
+        .add_warm_container(
+            key.clone(),
+            WarmContainer {
+                container_id: "c1".to_string(),
+                instance_id: "inst-1".to_string(),
+                function_id: Uuid::new_v4(),
+                image_ref: "lambda-home/orders:sha".to_string(),
+                memory_mb: 128,
+                created_at: Instant::now(),
+                last_used: Instant::now(),
+                state: InstanceState::Active,
+            },
+        )
+        .await;
+    let _rx = cp.pending().register(request_id.clone());
+    cp.pending().assign(&request_id, "c1".to_string());
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    cp.pending().set_deadline(&request_id, now_ms + 100, 3000);
+
+    // The caller times out before the deadline enforcer gets to it
+    assert!(cp
+        .pending()
+        .discard(&request_id, InvocationResult::timed_out(3000), now_ms));
+    assert!(cp.warm_pool().find_container("c1").await.is_some());
+
+    timeout(Duration::from_secs(2), async {
+        while cp.warm_pool().find_container("c1").await.is_some() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(cp.pending().assigned(), 0);
+}
@@ -0,0 +1,95 @@
+use crate::common::control_plane_with;
+use lambda_control::invocation_traces::MAX_TRACE_BODY_BYTES;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{Config, RuntimeTraceCall};
+use std::collections::BTreeMap;
+
+async fn control_plane() -> ControlPlane {
+    control_plane_with(Config::default()).await
+}
+
+fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn traced_invocations_keep_their_runtime_api_calls() {
+    let cp = control_plane().await;
+    let traces = cp.invocation_traces();
+    traces.begin("req-1", "orders");
+    assert!(traces.is_tracing("req-1"));
+
+    let polled_at = chrono::Utc::now();
+    traces.record(
+        "req-1",
+        Some("inst-1"),
+        RuntimeTraceCall::Next {
+            polled_at,
+            waited_ms: 12,
+        },
+        headers(&[("lambda-runtime-aws-request-id", "req-1")]),
+        br#"{"order":1}"#,
+    );
+    traces.record(
+        "req-1",
+        Some("inst-1"),
+        RuntimeTraceCall::Error,
+        headers(&[("lambda-runtime-function-error-type", "Runtime.Crash")]),
+        b"not json",
+    );
+    // Calls for requests that are not traced are dropped
+    traces.record(
+        "req-2",
+        None,
+        RuntimeTraceCall::Response,
+        BTreeMap::new(),
+        b"{}",
+    );
+    traces.finish("req-1").await.unwrap();
+    assert!(!traces.is_tracing("req-1"));
+
+    let trace = cp.get_execution_trace("req-1").await.unwrap();
+    assert_eq!(trace.function_name, "orders");
+    assert!(trace.completed_at.is_some());
+    assert_eq!(trace.events.len(), 2);
+    assert_eq!(
+        trace.events[0].call,
+        RuntimeTraceCall::Next {
+            polled_at,
+            waited_ms: 12
+        }
+    );
+    assert_eq!(trace.events[0].body, r#"{"order":1}"#);
+    assert_eq!(trace.events[0].instance_id.as_deref(), Some("inst-1"));
+    assert_eq!(trace.events[1].call, RuntimeTraceCall::Error);
+    assert_eq!(
+        trace.events[1].headers,
+        headers(&[("lambda-runtime-function-error-type", "Runtime.Crash")])
+    );
+    assert_eq!(trace.events[1].body, "not json");
+
+    let err = cp.get_execution_trace("req-2").await.unwrap_err();
+    assert_eq!(err.http_status(), 404, "{err}");
+}
+
+#[tokio::test]
+async fn large_bodies_are_cut_off() {
+    let cp = control_plane().await;
+    let traces = cp.invocation_traces();
+    traces.begin("req-1", "orders");
+    traces.record(
+        "req-1",
+        None,
+        RuntimeTraceCall::Response,
+        BTreeMap::new(),
+        &vec![b'x'; MAX_TRACE_BODY_BYTES + 10],
+    );
+    traces.finish("req-1").await.unwrap();
+
+    let trace = cp.get_execution_trace("req-1").await.unwrap();
+    assert_eq!(trace.events[0].body.len(), MAX_TRACE_BODY_BYTES);
+    assert!(trace.events[0].body_truncated);
+}
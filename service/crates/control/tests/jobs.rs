@@ -0,0 +1,186 @@
+use crate::common::test_config;
+use lambda_control::jobs::JobStore;
+use lambda_control::migrations::run_migrations;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    FunctionError, InvocationType, InvokeRequest, InvokeResponse, JobStatus, JobsConfig,
+    LambdaError,
+};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+async fn job_store(result_ttl_secs: u64) -> JobStore {
+    job_store_with(JobsConfig {
+        result_ttl_secs,
+        ..JobsConfig::default()
+    })
+    .await
+}
+
+async fn job_store_with(config: JobsConfig) -> JobStore {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    JobStore::new(pool, config)
+}
+
+fn response(payload: serde_json::Value, function_error: Option<FunctionError>) -> InvokeResponse {
+    InvokeResponse {
+        status_code: 200,
+        payload: Some(payload),
+        executed_version: Some("$LATEST".into()),
+        function_error,
+        log_result: None,
+        headers: HashMap::new(),
+        duration_ms: Some(42),
+    }
+}
+
+#[tokio::test]
+async fn job_runs_until_completed_with_result() {
+    let jobs = job_store(3600).await;
+    let job = jobs.create("report").await.unwrap();
+    assert_eq!(job.status, JobStatus::Running);
+    assert!(job.completed_at.is_none() && job.expires_at.is_none());
+
+    jobs.complete(
+        &job.job_id,
+        Ok(response(serde_json::json!({ "rows": 3 }), None)),
+    )
+    .await
+    .unwrap();
+    let done = jobs.get(&job.job_id).await.unwrap();
+    assert_eq!(done.status, JobStatus::Succeeded);
+    assert_eq!(done.payload, Some(serde_json::json!({ "rows": 3 })));
+    assert_eq!(done.executed_version.as_deref(), Some("$LATEST"));
+    assert_eq!(done.duration_ms, Some(42));
+    assert!(done.expires_at.unwrap() > done.completed_at.unwrap());
+}
+
+#[tokio::test]
+async fn handler_and_invoke_errors_fail_the_job() {
+    let jobs = job_store(3600).await;
+
+    let handled = jobs.create("report").await.unwrap();
+    jobs.complete(
+        &handled.job_id,
+        Ok(response(
+            serde_json::json!({ "errorMessage": "boom" }),
+            Some(FunctionError::Handled),
+        )),
+    )
+    .await
+    .unwrap();
+    let handled = jobs.get(&handled.job_id).await.unwrap();
+    assert_eq!(handled.status, JobStatus::Failed);
+    assert_eq!(handled.function_error, Some(FunctionError::Handled));
+
+    let timed_out = jobs.create("report").await.unwrap();
+    jobs.complete(
+        &timed_out.job_id,
+        Err(LambdaError::ContainerTimeout { timeout_ms: 900 }),
+    )
+    .await
+    .unwrap();
+    let timed_out = jobs.get(&timed_out.job_id).await.unwrap();
+    assert_eq!(timed_out.status, JobStatus::Failed);
+    assert!(timed_out.payload.is_none());
+    assert!(timed_out.error_message.unwrap().contains("900ms"));
+}
+
+#[tokio::test]
+async fn expired_results_are_gone_and_pruned() {
+    let jobs = job_store(0).await;
+    let job = jobs.create("report").await.unwrap();
+    jobs.complete(&job.job_id, Ok(response(serde_json::json!(1), None)))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        jobs.get(&job.job_id).await,
+        Err(LambdaError::JobNotFound { .. })
+    ));
+    assert_eq!(jobs.prune().await.unwrap(), 1);
+
+    // Running jobs have no expiry yet
+    let running = jobs.create("report").await.unwrap();
+    assert_eq!(jobs.prune().await.unwrap(), 0);
+    assert_eq!(
+        jobs.get(&running.job_id).await.unwrap().status,
+        JobStatus::Running
+    );
+}
+
+#[tokio::test]
+async fn running_jobs_fail_after_restart() {
+    let jobs = job_store(3600).await;
+    let job = jobs.create("report").await.unwrap();
+
+    assert_eq!(jobs.fail_interrupted().await.unwrap(), 1);
+    let job = jobs.get(&job.job_id).await.unwrap();
+    assert_eq!(job.status, JobStatus::Failed);
+    assert_eq!(
+        job.error_message.as_deref(),
+        Some("Server restarted before the job finished")
+    );
+}
+
+#[tokio::test]
+async fn starting_a_job_for_a_missing_function_fails_up_front() {
+    let config = test_config();
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    let cp = Arc::new(ControlPlane::new(pool, invoker, config).await.unwrap());
+
+    let err = cp
+        .start_job(InvokeRequest {
+            function_name: "missing".into(),
+            invocation_type: InvocationType::RequestResponse,
+            log_type: None,
+            client_context: None,
+            payload: None,
+            qualifier: None,
+            idempotent: false,
+            trace: false,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, LambdaError::FunctionNotFound { .. }));
+    assert!(matches!(
+        cp.get_job("nope").await,
+        Err(LambdaError::JobNotFound { .. })
+    ));
+    assert!(matches!(
+        cp.get_invocation_result("nope").await,
+        Err(LambdaError::ExecutionNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn oversized_results_are_dropped() {
+    let jobs = job_store_with(JobsConfig {
+        result_ttl_secs: 3600,
+        max_result_bytes: 16,
+    })
+    .await;
+
+    let small = jobs.create("report").await.unwrap();
+    jobs.complete(&small.job_id, Ok(response(serde_json::json!("ok"), None)))
+        .await
+        .unwrap();
+    let small = jobs.get(&small.job_id).await.unwrap();
+    assert_eq!(small.payload, Some(serde_json::json!("ok")));
+    assert!(!small.payload_truncated);
+
+    let large = jobs.create("report").await.unwrap();
+    jobs.complete(
+        &large.job_id,
+        Ok(response(serde_json::json!("x".repeat(64)), None)),
+    )
+    .await
+    .unwrap();
+    let large = jobs.get(&large.job_id).await.unwrap();
+    assert_eq!(large.status, JobStatus::Succeeded);
+    assert!(large.payload.is_none());
+    assert!(large.payload_truncated);
+}
@@ -0,0 +1,134 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use lambda_control::jwt_authorizer::{bearer_token, jwt_context, verify_token, Jwk};
+use lambda_models::RouteJwtAuthorizer;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Value};
+
+const NOW: i64 = 1_700_000_000;
+
+struct Issuer {
+    key: EcdsaKeyPair,
+    rng: SystemRandom,
+}
+
+impl Issuer {
+    fn new() -> Self {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+            .unwrap();
+        Self { key, rng }
+    }
+
+    fn jwk(&self, kid: &str) -> Jwk {
+        // Uncompressed point: 0x04, then 32 bytes each of x and y
+        let point = self.key.public_key().as_ref();
+        serde_json::from_value(json!({
+            "kty": "EC",
+            "kid": kid,
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+        }))
+        .unwrap()
+    }
+
+    fn sign(&self, kid: &str, claims: Value) -> String {
+        let header = json!({ "alg": "ES256", "typ": "JWT", "kid": kid });
+        let signed = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = self.key.sign(&self.rng, signed.as_bytes()).unwrap();
+        format!("{signed}.{}", URL_SAFE_NO_PAD.encode(signature.as_ref()))
+    }
+}
+
+fn authorizer() -> RouteJwtAuthorizer {
+    RouteJwtAuthorizer {
+        issuer: "https://auth.example.com".into(),
+        audience: vec!["orders-api".into()],
+        jwks_url: "https://auth.example.com/.well-known/jwks.json".into(),
+    }
+}
+
+fn claims() -> Value {
+    json!({
+        "iss": "https://auth.example.com",
+        "aud": "orders-api",
+        "sub": "user-1",
+        "exp": NOW + 3600,
+        "scope": "orders:read orders:write",
+    })
+}
+
+#[test]
+fn valid_tokens_yield_their_claims() {
+    let issuer = Issuer::new();
+    let token = issuer.sign("k1", claims());
+
+    let claims = verify_token(&token, &[issuer.jwk("k1")], &authorizer(), NOW).unwrap();
+    assert_eq!(claims["sub"], "user-1");
+
+    let context = jwt_context(&claims);
+    assert_eq!(context["claims"]["sub"], "user-1");
+    assert_eq!(context["claims"]["exp"], (NOW + 3600).to_string());
+    assert_eq!(context["scopes"], json!(["orders:read", "orders:write"]));
+}
+
+#[test]
+fn invalid_tokens_are_refused() {
+    let issuer = Issuer::new();
+    let keys = [issuer.jwk("k1")];
+    let with = |change: Value| {
+        let mut claims = claims();
+        for (name, value) in change.as_object().unwrap() {
+            claims[name] = value.clone();
+        }
+        issuer.sign("k1", claims)
+    };
+
+    let expired = with(json!({ "exp": NOW - 120 }));
+    assert_eq!(
+        verify_token(&expired, &keys, &authorizer(), NOW).unwrap_err(),
+        "token expired"
+    );
+    let audience = with(json!({ "aud": ["billing-api"] }));
+    assert_eq!(
+        verify_token(&audience, &keys, &authorizer(), NOW).unwrap_err(),
+        "audience mismatch"
+    );
+    let foreign = with(json!({ "iss": "https://evil.example.com" }));
+    assert_eq!(
+        verify_token(&foreign, &keys, &authorizer(), NOW).unwrap_err(),
+        "issuer mismatch"
+    );
+
+    // Signed by another key under the same key id
+    let forged = Issuer::new().sign("k1", claims());
+    assert_eq!(
+        verify_token(&forged, &keys, &authorizer(), NOW).unwrap_err(),
+        "invalid signature"
+    );
+    let unknown = issuer.sign("k2", claims());
+    assert_eq!(
+        verify_token(&unknown, &keys, &authorizer(), NOW).unwrap_err(),
+        "unknown signing key"
+    );
+    assert!(verify_token("not-a-token", &keys, &authorizer(), NOW).is_err());
+}
+
+#[test]
+fn bearer_tokens_are_taken_from_the_authorization_header() {
+    assert_eq!(
+        bearer_token(Some("Bearer abc.def.ghi")),
+        Some("abc.def.ghi")
+    );
+    assert_eq!(bearer_token(Some("bearer  abc")), Some("abc"));
+    assert_eq!(bearer_token(Some("abc")), Some("abc"));
+    assert_eq!(bearer_token(Some("Bearer ")), None);
+    assert_eq!(bearer_token(None), None);
+}
@@ -0,0 +1,66 @@
+use crate::common::{control_plane, create_request};
+use lambda_models::{FunctionState, LambdaError, LastUpdateStatus};
+
+fn build_error() -> LambdaError {
+    LambdaError::InternalError {
+        reason: "docker build failed".into(),
+    }
+}
+
+#[tokio::test]
+async fn build_outcome_settles_a_pending_function() {
+    let cp = control_plane().await;
+    let function = cp.create_function(create_request("pending")).await.unwrap();
+    assert_eq!(function.state, FunctionState::Pending);
+    assert_eq!(function.last_update_status, LastUpdateStatus::Successful);
+
+    cp.record_build_outcome(&function, Err(&build_error()))
+        .await
+        .unwrap();
+    let failed = cp.get_function("pending").await.unwrap();
+    assert_eq!(failed.state, FunctionState::Failed);
+    assert_eq!(
+        failed.state_reason_code.as_deref(),
+        Some("ImageBuildFailed")
+    );
+    assert_eq!(failed.last_update_status, LastUpdateStatus::Failed);
+    assert!(failed
+        .last_update_status_reason
+        .as_deref()
+        .unwrap()
+        .contains("docker build failed"));
+
+    cp.record_build_outcome(&failed, Ok(())).await.unwrap();
+    let active = cp.get_function("pending").await.unwrap();
+    assert_eq!(active.state, FunctionState::Active);
+    assert_eq!(active.state_reason, None);
+    assert_eq!(active.last_update_status, LastUpdateStatus::Successful);
+    assert_eq!(active.last_update_status_reason_code, None);
+}
+
+#[tokio::test]
+async fn failed_rebuild_keeps_an_active_function_active() {
+    let cp = control_plane().await;
+    let function = cp.create_function(create_request("active")).await.unwrap();
+    cp.record_build_outcome(&function, Ok(())).await.unwrap();
+    let active = cp.get_function("active").await.unwrap();
+
+    cp.record_build_outcome(&active, Err(&build_error()))
+        .await
+        .unwrap();
+    let function = cp.get_function("active").await.unwrap();
+    assert_eq!(function.state, FunctionState::Active);
+    assert_eq!(function.state_reason, None);
+    assert_eq!(function.last_update_status, LastUpdateStatus::Failed);
+    assert_eq!(
+        function.last_update_status_reason_code.as_deref(),
+        Some("ImageBuildFailed")
+    );
+
+    // Listing reads the same columns
+    let listed = cp.list_functions(&Default::default()).await.unwrap();
+    assert_eq!(
+        listed.functions[0].last_update_status,
+        LastUpdateStatus::Failed
+    );
+}
@@ -1,8 +1,86 @@
+mod access_log;
+mod archive;
+mod async_invocations;
+mod autoscaler_tests;
+mod aws_emulation;
+mod batch;
+mod builds;
+mod canary;
+mod change_feed;
 mod common;
 mod concurrency;
+mod config_history;
+mod container_monitor_tests;
+mod cost_estimate;
+mod dashboard;
+mod disk;
+mod doctor;
+mod environment;
+mod error_summary;
+mod exec_sessions;
+mod executions;
+mod features;
 mod fnkey;
+mod function_urls;
+mod function_user;
+mod get_function;
+mod groups;
+mod hedging;
+mod hooks;
+mod identity;
+mod image_functions;
+mod insights;
+mod integrity;
+mod invariants;
+mod invocation_timeout;
+mod invocation_traces;
+mod jobs;
+mod jwt_authorizer;
+mod last_update_status;
+mod lifecycle;
+mod listing;
+mod locale;
+mod maintenance;
+mod metadata;
+mod openapi;
 mod pending;
+mod permissions;
+mod placement;
+mod prewarm;
+mod provisioned_concurrency;
 mod queues;
+mod quotas;
+mod recording;
 mod registry_tests;
+mod replication;
+mod response_headers;
+mod rollouts;
+mod route_invocation;
+mod route_limits;
+mod route_mock;
+mod runtime_api;
+mod runtime_management;
+mod s3_notifications;
+mod sandbox;
 mod scheduler;
+mod schedules;
+mod schema;
+mod search;
+mod secrets;
+mod secrets_import;
+mod sqs_queues;
+mod static_functions;
+mod stop_grace;
+mod sync_invoke;
+mod synthetic_checks;
+mod system_info;
+mod tags;
+mod transforms;
+mod trusted_headers;
+mod upgrade;
+mod validation;
+mod versions;
 mod warm_pool;
+mod warm_pool_snapshot;
+mod warmup_stats;
+mod webhooks;
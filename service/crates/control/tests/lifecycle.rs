@@ -0,0 +1,67 @@
+use crate::common::{control_plane, create_function};
+use lambda_models::{FunctionLifecycle, LambdaError};
+
+#[tokio::test]
+async fn lifecycle_is_stored_and_applied_to_the_warm_pool() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    let function_id = cp.get_function("orders").await.unwrap().function_id;
+    assert_eq!(
+        cp.get_lifecycle("orders").await.unwrap(),
+        FunctionLifecycle::default()
+    );
+
+    let lifecycle = FunctionLifecycle {
+        idle_timeout_secs: Some(600),
+        max_warm_containers: Some(4),
+        min_warm_containers: Some(2),
+    };
+    cp.put_lifecycle("orders", lifecycle.clone()).await.unwrap();
+    assert_eq!(cp.get_lifecycle("orders").await.unwrap(), lifecycle);
+    assert_eq!(cp.warm_pool().lifecycle(function_id), lifecycle);
+    assert_eq!(cp.warm_pool().warm_room(function_id).await, Some(4));
+
+    // min_warm_containers keeps containers of $LATEST running
+    let targets = cp.min_warm_targets().await.unwrap();
+    let (key, count) = targets.into_iter().next().unwrap();
+    assert_eq!(key.function_name, "orders");
+    assert_eq!(count, 2);
+
+    cp.delete_lifecycle("orders").await.unwrap();
+    assert_eq!(
+        cp.get_lifecycle("orders").await.unwrap(),
+        FunctionLifecycle::default()
+    );
+    assert_eq!(cp.warm_pool().warm_room(function_id).await, None);
+    assert!(cp.min_warm_targets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn invalid_lifecycles_are_refused() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+
+    for lifecycle in [
+        FunctionLifecycle {
+            idle_timeout_secs: Some(0),
+            ..Default::default()
+        },
+        FunctionLifecycle {
+            max_warm_containers: Some(0),
+            ..Default::default()
+        },
+        FunctionLifecycle {
+            max_warm_containers: Some(1),
+            min_warm_containers: Some(2),
+            ..Default::default()
+        },
+    ] {
+        let result = cp.put_lifecycle("orders", lifecycle).await;
+        assert!(matches!(result, Err(LambdaError::InvalidRequest { .. })));
+    }
+    let missing = cp
+        .put_lifecycle("missing", FunctionLifecycle::default())
+        .await
+        .unwrap_err();
+    assert_eq!(missing.http_status(), 404);
+}
@@ -0,0 +1,296 @@
+use crate::common::control_plane_and_pool;
+use chrono::Utc;
+use lambda_control::listing::ListMarker;
+use lambda_control::queues::FnKey;
+use lambda_control::registry::ControlPlane;
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_control::work_item::{FunctionMeta, WorkItem};
+use lambda_models::{
+    Config, CreateFunctionRequest, FunctionSortKey, FunctionState, FunctionWarmState,
+    ListFunctionsQuery, SortOrder,
+};
+use std::time::Instant;
+
+async fn control_plane_with(functions: &[(&str, &str, usize)]) -> ControlPlane {
+    let config = Config::default();
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    for (name, runtime, invocations) in functions {
+        let function = cp
+            .create_function(CreateFunctionRequest {
+                function_name: name.to_string(),
+                runtime: runtime.to_string(),
+                handler: "index.handler".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        for i in 0..*invocations {
+            sqlx::query(
+                "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, status)
+                 VALUES (?, ?, '1', ?, ?, 'Success')",
+            )
+            .bind(format!("{name}-{i}"))
+            .bind(function.function_id)
+            .bind(format!("{name}-{i}"))
+            .bind(Utc::now())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    }
+    cp
+}
+
+/// Follow next_marker until the listing is exhausted
+async fn collect_pages(cp: &ControlPlane, mut query: ListFunctionsQuery) -> Vec<String> {
+    let mut names = Vec::new();
+    loop {
+        let page = cp.list_functions(&query).await.unwrap();
+        assert!(page.functions.len() <= query.max_items.unwrap() as usize);
+        names.extend(page.functions.into_iter().map(|f| f.function_name));
+        match page.next_marker {
+            Some(marker) => query.marker = Some(marker),
+            None => return names,
+        }
+    }
+}
+
+#[test]
+fn marker_round_trips_and_is_tied_to_its_ordering() {
+    let marker = ListMarker {
+        sort_by: FunctionSortKey::InvocationCount,
+        order: SortOrder::Desc,
+        sort_value: "42".to_string(),
+        function_name: "fn-a".to_string(),
+    };
+    let encoded = marker.encode();
+    assert_eq!(
+        ListMarker::decode(&encoded, FunctionSortKey::InvocationCount, SortOrder::Desc).unwrap(),
+        marker
+    );
+    assert!(ListMarker::decode(&encoded, FunctionSortKey::Name, SortOrder::Desc).is_err());
+    assert!(
+        ListMarker::decode(&encoded, FunctionSortKey::InvocationCount, SortOrder::Asc).is_err()
+    );
+    assert!(ListMarker::decode("20", FunctionSortKey::Name, SortOrder::Asc).is_err());
+}
+
+#[tokio::test]
+async fn pages_through_every_function_once_per_ordering() {
+    let cp = control_plane_with(&[
+        ("echo", "nodejs22.x", 3),
+        ("alpha", "python3.11", 1),
+        ("delta", "nodejs22.x", 3),
+        ("charlie", "nodejs22.x", 0),
+        ("bravo", "python3.11", 5),
+    ])
+    .await;
+
+    let by_name = collect_pages(
+        &cp,
+        ListFunctionsQuery {
+            max_items: Some(2),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(by_name, vec!["alpha", "bravo", "charlie", "delta", "echo"]);
+
+    let by_name_desc = collect_pages(
+        &cp,
+        ListFunctionsQuery {
+            max_items: Some(2),
+            order: SortOrder::Desc,
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(
+        by_name_desc,
+        vec!["echo", "delta", "charlie", "bravo", "alpha"]
+    );
+
+    // Ties (delta and echo both have 3) fall back to the name
+    let busiest = collect_pages(
+        &cp,
+        ListFunctionsQuery {
+            max_items: Some(2),
+            sort_by: FunctionSortKey::InvocationCount,
+            order: SortOrder::Desc,
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(busiest, vec!["bravo", "delta", "echo", "alpha", "charlie"]);
+
+    let oldest_first = collect_pages(
+        &cp,
+        ListFunctionsQuery {
+            max_items: Some(3),
+            sort_by: FunctionSortKey::LastModified,
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(
+        oldest_first,
+        vec!["echo", "alpha", "delta", "charlie", "bravo"]
+    );
+}
+
+#[tokio::test]
+async fn filters_by_prefix_runtime_and_state() {
+    let cp = control_plane_with(&[
+        ("api-users", "nodejs22.x", 0),
+        ("api-orders", "python3.11", 0),
+        ("worker", "nodejs22.x", 0),
+    ])
+    .await;
+
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            name_prefix: Some("api-".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, Some(2));
+    assert!(page.next_marker.is_none());
+
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            name_prefix: Some("api-".to_string()),
+            runtime: Some("nodejs22.x".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let names: Vec<_> = page
+        .functions
+        .iter()
+        .map(|f| f.function_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["api-users"]);
+
+    // Prefix matching is literal, not a LIKE pattern
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            name_prefix: Some("API".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, Some(0));
+
+    let state = cp.get_function("worker").await.unwrap().state;
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            state: Some(state.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, Some(3));
+    let other = if state == FunctionState::Failed {
+        FunctionState::Active
+    } else {
+        FunctionState::Failed
+    };
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            state: Some(other),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, Some(0));
+}
+
+#[tokio::test]
+async fn rejects_markers_from_another_ordering() {
+    let cp = control_plane_with(&[("a", "nodejs22.x", 0), ("b", "nodejs22.x", 0)]).await;
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            max_items: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let err = cp
+        .list_functions(&ListFunctionsQuery {
+            marker: page.next_marker,
+            max_items: Some(1),
+            sort_by: FunctionSortKey::LastModified,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+}
+
+#[tokio::test]
+async fn reports_warm_state_only_when_asked() {
+    let cp = control_plane_with(&[("orders", "nodejs22.x", 0), ("users", "nodejs22.x", 0)]).await;
+    let page = cp
+        .list_functions(&ListFunctionsQuery::default())
+        .await
+        .unwrap();
+    assert!(page.warm_state.is_none());
+
+    let orders = cp.get_function("orders").await.unwrap();
+    let key = FnKey::for_function(&orders);
+    for (container_id, state) in [
+        ("idle-1", InstanceState::WarmIdle),
+        ("idle-2", InstanceState::WarmIdle),
+        ("busy", InstanceState::Active),
+        ("stopped", InstanceState::Stopped),
+    ] {
+        cp.warm_pool()
+            .add_warm_container(
+                key.clone(),
+                WarmContainer {
+                    container_id: container_id.into(),
+                    instance_id: format!("inst-{container_id}"),
+                    function_id: orders.function_id,
+                    image_ref: "lambda-home/orders:abc".into(),
+                    memory_mb: 128,
+                    created_at: Instant::now(),
+                    last_used: Instant::now(),
+                    state,
+                },
+            )
+            .await;
+    }
+    cp.queues()
+        .push(WorkItem {
+            request_id: "queued".into(),
+            function: FunctionMeta::from(orders.clone()),
+            payload: b"{}".to_vec(),
+            deadline_ms: i64::MAX,
+            log_type: None,
+            client_context: None,
+            cognito_identity: None,
+        })
+        .unwrap();
+
+    let page = cp
+        .list_functions(&ListFunctionsQuery {
+            include_warm_state: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let warm_state = page.warm_state.unwrap();
+    assert_eq!(
+        warm_state["orders"],
+        FunctionWarmState {
+            warm_idle: 2,
+            active: 1,
+            queued: 1,
+        }
+    );
+    // Listed functions without containers or work are reported idle
+    assert_eq!(warm_state["users"], FunctionWarmState::default());
+    assert_eq!(warm_state.len(), 2);
+}
@@ -0,0 +1,91 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::validation::normalize_locale_config;
+use lambda_models::{LambdaError, LocaleConfig};
+
+fn locale(timezone: Option<&str>, locale: Option<&str>) -> LocaleConfig {
+    LocaleConfig {
+        timezone: timezone.map(str::to_string),
+        locale: locale.map(str::to_string),
+    }
+}
+
+#[test]
+fn timezones_are_checked_against_the_tz_database() {
+    assert_eq!(
+        normalize_locale_config(locale(Some(" Europe/Rome "), Some(""))).unwrap(),
+        locale(Some("Europe/Rome"), None)
+    );
+    assert!(normalize_locale_config(locale(Some("America/Argentina/Buenos_Aires"), None)).is_ok());
+    assert!(normalize_locale_config(locale(Some("UTC"), None)).is_ok());
+    for tz in ["Mars/Olympus_Mons", "europe/rome", "+02:00"] {
+        assert!(
+            normalize_locale_config(locale(Some(tz), None)).is_err(),
+            "{tz} accepted"
+        );
+    }
+}
+
+#[test]
+fn locales_must_be_posix_names() {
+    for ok in [
+        "C",
+        "POSIX",
+        "C.UTF-8",
+        "en_US.UTF-8",
+        "de_DE",
+        "sr_RS@latin",
+        "ast_ES.utf8",
+    ] {
+        assert!(
+            normalize_locale_config(locale(None, Some(ok))).is_ok(),
+            "{ok} rejected"
+        );
+    }
+    for bad in [
+        "english",
+        "en-US",
+        "en_us.UTF-8",
+        "en_US.",
+        "en_US.UTF-8@",
+        "en_US;rm",
+    ] {
+        assert!(
+            normalize_locale_config(locale(None, Some(bad))).is_err(),
+            "{bad} accepted"
+        );
+    }
+}
+
+#[tokio::test]
+async fn locale_is_stored_per_function() {
+    let cp = control_plane().await;
+    create_function(&cp, "cron").await;
+    assert_eq!(
+        cp.get_locale("cron").await.unwrap(),
+        LocaleConfig::default()
+    );
+
+    let stored = cp
+        .put_locale("cron", locale(Some("Asia/Tokyo"), Some("ja_JP.UTF-8")))
+        .await
+        .unwrap();
+    assert_eq!(stored, locale(Some("Asia/Tokyo"), Some("ja_JP.UTF-8")));
+    assert_eq!(cp.get_locale("cron").await.unwrap(), stored);
+
+    let err = cp
+        .put_locale("cron", locale(Some("Asia/Atlantis"), None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    assert_eq!(cp.get_locale("cron").await.unwrap(), stored);
+
+    cp.delete_locale("cron").await.unwrap();
+    assert_eq!(
+        cp.get_locale("cron").await.unwrap(),
+        LocaleConfig::default()
+    );
+    assert!(matches!(
+        cp.get_locale("missing").await,
+        Err(LambdaError::FunctionNotFound { .. })
+    ));
+}
@@ -0,0 +1,96 @@
+use crate::common::{control_plane_and_pool, create_request, test_config};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::maintenance::DatabaseMaintenance;
+use lambda_control::registry::ControlPlane;
+use lambda_models::MaintenanceConfig;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+async fn control_plane_with_executions(ages_in_days: &[i64]) -> (ControlPlane, SqlitePool) {
+    let mut config = test_config();
+    // Pruning is left to the tests
+    config.maintenance.execution_retention_days = 0;
+    let (cp, pool) = control_plane_and_pool(config).await;
+    let function = cp.create_function(create_request("orders")).await.unwrap();
+    for days_ago in ages_in_days {
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, status)
+             VALUES (?, ?, '$LATEST', ?, ?, 'Success')",
+        )
+        .bind(&id)
+        .bind(function.function_id)
+        .bind(&id)
+        .bind(Utc::now() - ChronoDuration::days(*days_ago) - ChronoDuration::minutes(1))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    (cp, pool)
+}
+
+async fn execution_ages(pool: &SqlitePool) -> Vec<i64> {
+    sqlx::query("SELECT start_time FROM executions ORDER BY start_time DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| (Utc::now() - row.get::<chrono::DateTime<Utc>, _>("start_time")).num_days())
+        .collect()
+}
+
+#[tokio::test]
+async fn executions_are_pruned_by_age_then_by_count() {
+    let (_cp, pool) = control_plane_with_executions(&[0, 1, 2, 10, 40, 90]).await;
+
+    let maintenance = DatabaseMaintenance::new(
+        pool.clone(),
+        MaintenanceConfig {
+            execution_retention_days: 30,
+            max_executions: 0,
+            ..Default::default()
+        },
+    );
+    assert_eq!(maintenance.prune_executions().await.unwrap(), 2);
+    assert_eq!(execution_ages(&pool).await, [0, 1, 2, 10]);
+
+    let maintenance = DatabaseMaintenance::new(
+        pool.clone(),
+        MaintenanceConfig {
+            execution_retention_days: 0,
+            max_executions: 3,
+            ..Default::default()
+        },
+    );
+    assert_eq!(maintenance.prune_executions().await.unwrap(), 1);
+    assert_eq!(execution_ages(&pool).await, [0, 1, 2]);
+
+    let status = maintenance.status().await.unwrap();
+    assert_eq!(status.last_pruned_executions, 1);
+    assert!(status.last_pruned_at.is_some());
+    assert!(status.last_vacuum_at.is_none());
+}
+
+#[tokio::test]
+async fn database_status_counts_rows_per_table() {
+    let (cp, pool) = control_plane_with_executions(&[0, 1]).await;
+
+    let status = cp.database_status().await.unwrap();
+    assert!(status.size_bytes > 0);
+    assert!(status.free_bytes <= status.size_bytes);
+    let rows = |name: &str| {
+        status
+            .tables
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.rows)
+    };
+    assert_eq!(rows("executions"), Some(2));
+    assert_eq!(rows("functions"), Some(1));
+    assert_eq!(rows("api_routes"), Some(0));
+    assert!(status.tables.windows(2).all(|w| w[0].rows >= w[1].rows));
+
+    let maintenance = DatabaseMaintenance::new(pool, MaintenanceConfig::default());
+    maintenance.vacuum().await.unwrap();
+    assert!(maintenance.status().await.unwrap().last_vacuum_at.is_some());
+}
@@ -0,0 +1,106 @@
+use crate::common::{control_plane_and_pool, create_request};
+use lambda_control::validation::normalize_function_metadata;
+use lambda_models::{Config, FunctionMetadata};
+
+#[test]
+fn normalizes_and_validates_metadata() {
+    let metadata = normalize_function_metadata(FunctionMetadata {
+        owner: Some("  payments-team ".to_string()),
+        repo_url: Some("https://github.com/acme/payments".to_string()),
+        docs_url: Some("   ".to_string()),
+        notes: Some("## Runbook\n\n- restart it\n".to_string()),
+    })
+    .unwrap();
+    assert_eq!(metadata.owner.as_deref(), Some("payments-team"));
+    assert_eq!(metadata.docs_url, None);
+    assert_eq!(
+        metadata.notes.as_deref(),
+        Some("## Runbook\n\n- restart it\n")
+    );
+
+    let bad_link = normalize_function_metadata(FunctionMetadata {
+        docs_url: Some("javascript:alert(1)".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(bad_link.unwrap_err().http_status(), 400);
+
+    let long_owner = normalize_function_metadata(FunctionMetadata {
+        owner: Some("x".repeat(257)),
+        ..Default::default()
+    });
+    assert!(long_owner.is_err());
+}
+
+#[tokio::test]
+async fn metadata_is_stored_returned_by_describe_and_cleared() {
+    let config = Config::default();
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    let function = cp
+        .create_function(create_request("documented"))
+        .await
+        .unwrap();
+    assert_eq!(function.metadata, None);
+    assert_eq!(
+        cp.get_metadata("documented").await.unwrap(),
+        FunctionMetadata::default()
+    );
+
+    let stored = cp
+        .put_metadata(
+            "documented",
+            FunctionMetadata {
+                owner: Some("platform".into()),
+                repo_url: Some("https://git.example.com/platform/documented".into()),
+                docs_url: None,
+                notes: Some("Paged via **#platform-oncall**".into()),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(cp.get_metadata("documented").await.unwrap(), stored);
+    assert_eq!(
+        cp.describe_function("documented").await.unwrap().metadata,
+        Some(stored.clone())
+    );
+    // Internal lookups stay lean
+    assert_eq!(cp.get_function("documented").await.unwrap().metadata, None);
+
+    // PUT replaces every field
+    let replaced = cp
+        .put_metadata(
+            "documented",
+            FunctionMetadata {
+                owner: Some("billing".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(replaced.repo_url, None);
+    assert_eq!(cp.get_metadata("documented").await.unwrap(), replaced);
+
+    cp.delete_metadata("documented").await.unwrap();
+    assert_eq!(
+        cp.get_metadata("documented").await.unwrap(),
+        FunctionMetadata::default()
+    );
+
+    assert!(cp
+        .put_metadata("missing", FunctionMetadata::default())
+        .await
+        .is_err());
+
+    // Metadata goes away with its function
+    cp.put_metadata("documented", replaced).await.unwrap();
+    sqlx::query("DELETE FROM functions WHERE function_id = ?")
+        .bind(function.function_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM function_metadata")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
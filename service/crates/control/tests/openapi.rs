@@ -0,0 +1,142 @@
+use crate::common::{control_plane_with, create_request, temp_dir, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{CreateApiRouteRequest, CreateFunctionRequest, FunctionCode};
+use lambda_testsupport::helpers::{b64, zip_dir};
+use serde_json::json;
+use std::path::Path;
+
+async fn control_plane(dir: &Path) -> ControlPlane {
+    let mut config = test_config();
+    config.data.dir = dir.to_string_lossy().into_owned();
+    control_plane_with(config).await
+}
+
+async fn create_function(
+    cp: &ControlPlane,
+    dir: &Path,
+    name: &str,
+    document: Option<serde_json::Value>,
+) {
+    let src = dir.join(format!("src-{name}"));
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(src.join("index.js"), "exports.handler = async () => 1;").unwrap();
+    if let Some(document) = document {
+        std::fs::write(src.join("openapi.json"), document.to_string()).unwrap();
+    }
+    cp.create_function(CreateFunctionRequest {
+        code: FunctionCode {
+            zip_file: Some(b64(zip_dir(&src).unwrap())),
+            ..Default::default()
+        },
+        ..create_request(name)
+    })
+    .await
+    .unwrap();
+}
+
+async fn route(cp: &ControlPlane, path: &str, method: Option<&str>, function_name: &str) {
+    cp.create_api_route(CreateApiRouteRequest {
+        path: path.into(),
+        method: method.map(str::to_string),
+        function_name: function_name.into(),
+        canary: None,
+    })
+    .await
+    .unwrap();
+}
+
+fn pet_schema(name_type: &str) -> serde_json::Value {
+    json!({ "type": "object", "properties": { "name": { "type": name_type } } })
+}
+
+#[tokio::test]
+async fn bundled_fragments_are_mounted_under_their_routes() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path()).await;
+    create_function(
+        &cp,
+        dir.path(),
+        "pets",
+        Some(json!({
+            "paths": {
+                "/": {
+                    "get": { "summary": "List pets", "responses": { "200": { "description": "ok" } } },
+                    "post": { "summary": "Add a pet", "responses": { "201": { "description": "created" } } },
+                },
+                "/{id}": {
+                    "get": { "summary": "Get a pet", "tags": ["catalog"], "responses": { "200": { "description": "ok" } } },
+                },
+            },
+            "components": { "schemas": { "Pet": pet_schema("string") } },
+        })),
+    )
+    .await;
+    create_function(&cp, dir.path(), "health", None).await;
+    route(&cp, "/pets", Some("GET"), "pets").await;
+    route(&cp, "/health", None, "health").await;
+
+    let document = cp.gateway_openapi().await.unwrap();
+    assert_eq!(document["openapi"], "3.0.3");
+    let paths = &document["paths"];
+    assert_eq!(paths["/pets"]["get"]["summary"], "List pets");
+    assert_eq!(paths["/pets"]["get"]["tags"], json!(["pets"]));
+    // The route answers GET only
+    assert!(paths["/pets"].get("post").is_none());
+    assert_eq!(paths["/pets/{id}"]["get"]["tags"], json!(["catalog"]));
+    assert_eq!(
+        document["components"]["schemas"]["Pet"],
+        pet_schema("string")
+    );
+
+    // Functions without a document still show up, under every method their route answers
+    for method in ["get", "post", "put", "patch", "delete"] {
+        assert_eq!(paths["/health"][method]["summary"], "Invoke health");
+    }
+    assert!(document.get("x-lambdah-warnings").is_none());
+}
+
+#[tokio::test]
+async fn conflicts_and_unreadable_documents_are_warnings() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path()).await;
+    create_function(
+        &cp,
+        dir.path(),
+        "pets",
+        Some(json!({ "components": { "schemas": { "Pet": pet_schema("string") } } })),
+    )
+    .await;
+    create_function(
+        &cp,
+        dir.path(),
+        "legacy-pets",
+        Some(json!({ "components": { "schemas": { "Pet": pet_schema("integer") } } })),
+    )
+    .await;
+    create_function(&cp, dir.path(), "broken", Some(json!("not a document"))).await;
+    route(&cp, "/pets", None, "pets").await;
+    route(&cp, "/v1/pets", None, "legacy-pets").await;
+    route(&cp, "/broken", None, "broken").await;
+
+    let document = cp.gateway_openapi().await.unwrap();
+    assert_eq!(
+        document["components"]["schemas"]["Pet"],
+        pet_schema("string")
+    );
+    // The unreadable document leaves its route undocumented rather than failing the rest
+    assert_eq!(
+        document["paths"]["/broken"]["get"]["summary"],
+        "Invoke broken"
+    );
+    let warnings: Vec<&str> = document["x-lambdah-warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|w| w.as_str())
+        .collect();
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert!(warnings.iter().any(|w| w.starts_with("broken:")));
+    assert!(warnings
+        .iter()
+        .any(|w| w.contains("legacy-pets: components.schemas.Pet")));
+}
@@ -1,4 +1,4 @@
-use lambda_control::pending::{InvocationResult, Pending};
+use lambda_control::pending::{InvocationResult, Pending, StreamPart};
 use tokio::time::{timeout, Duration};
 
 #[tokio::test]
@@ -19,3 +19,159 @@ async fn pending_delivers_once() {
     // Late/duplicate completes should return false (no waiter)
     assert!(!p.complete(&id, InvocationResult::ok(b"late".to_vec())));
 }
+
+#[tokio::test]
+async fn fail_container_only_fails_requests_on_that_container() {
+    let p = Pending::new();
+    let rx_a = p.register("req-a".to_string());
+    let rx_b = p.register("req-b".to_string());
+    p.assign("req-a", "container-1".to_string());
+    p.assign("req-b", "container-2".to_string());
+
+    assert_eq!(p.fail_container("container-1", "Container exited"), 1);
+
+    let res = timeout(Duration::from_millis(200), rx_a)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!res.ok);
+    assert!(res.infrastructure_failure);
+    let body: serde_json::Value = serde_json::from_slice(&res.payload).unwrap();
+    assert_eq!(body["errorMessage"], "Container exited");
+
+    // The other request is still waiting and completes normally
+    assert!(p.complete("req-b", InvocationResult::ok(b"ok".to_vec())));
+    let res = timeout(Duration::from_millis(200), rx_b)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res.ok);
+    assert!(!res.infrastructure_failure);
+
+    // Completed requests are no longer tracked against their container
+    assert_eq!(p.fail_container("container-2", "Container exited"), 0);
+}
+
+#[tokio::test]
+async fn a_streamed_response_ends_when_the_request_completes() {
+    let p = Pending::new();
+    let id = "req-stream".to_string();
+    let rx = p.register(id.clone());
+    let mut parts = p.register_stream(id.clone());
+    assert!(p.is_streaming(&id));
+    assert!(p.stream("req-other").is_none());
+
+    let stream = p.stream(&id).unwrap();
+    stream
+        .send(StreamPart::Start {
+            content_type: Some("text/plain".into()),
+        })
+        .await
+        .unwrap();
+    stream
+        .send(StreamPart::Chunk(b"hel".to_vec()))
+        .await
+        .unwrap();
+    drop(stream);
+    assert!(p.complete(&id, InvocationResult::ok(Vec::new())));
+    assert!(!p.is_streaming(&id));
+
+    let mut received = Vec::new();
+    while let Some(part) = timeout(Duration::from_millis(200), parts.recv())
+        .await
+        .unwrap()
+    {
+        received.push(part);
+    }
+    assert_eq!(
+        received,
+        [
+            StreamPart::Start {
+                content_type: Some("text/plain".into())
+            },
+            StreamPart::Chunk(b"hel".to_vec()),
+        ]
+    );
+    assert!(
+        timeout(Duration::from_millis(200), rx)
+            .await
+            .unwrap()
+            .unwrap()
+            .ok
+    );
+
+    // Ending a stream nobody completed closes it all the same
+    let mut parts = p.register_stream("req-refused".to_string());
+    p.end_stream("req-refused");
+    assert_eq!(parts.recv().await, None);
+}
+
+#[tokio::test]
+async fn requests_past_their_deadline_are_overdue_until_completed() {
+    let p = Pending::new();
+    let rx = p.register("req-slow".to_string());
+    let _rx_fast = p.register("req-fast".to_string());
+    p.assign("req-slow", "container-1".to_string());
+    p.set_deadline("req-slow", 1_000, 3_000);
+    p.set_deadline("req-fast", 5_000, 3_000);
+    // Requests no one waits for get no deadline
+    p.set_deadline("req-gone", 1_000, 3_000);
+
+    assert!(p.overdue(999).is_empty());
+    assert_eq!(
+        p.overdue(1_000),
+        [(
+            "req-slow".to_string(),
+            3_000,
+            Some("container-1".to_string())
+        )]
+    );
+
+    assert!(p.complete("req-slow", InvocationResult::timed_out(3_000)));
+    let res = timeout(Duration::from_millis(200), rx)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res.timed_out);
+    assert!(!res.infrastructure_failure);
+    let body: serde_json::Value = serde_json::from_slice(&res.payload).unwrap();
+    assert_eq!(body["errorType"], "TaskTimedOut");
+    assert_eq!(body["errorMessage"], "Task timed out after 3 seconds");
+    assert!(p.overdue(10_000).iter().all(|(id, ..)| id == "req-fast"));
+}
+
+#[tokio::test]
+async fn discarded_requests_refuse_late_results() {
+    let p = Pending::new();
+    let rx = p.register("req-slow".to_string());
+    p.assign("req-slow", "container-1".to_string());
+    p.set_deadline("req-slow", 1_000, 3_000);
+
+    assert!(p.discard("req-slow", InvocationResult::timed_out(3_000), 1_000));
+    let res = timeout(Duration::from_millis(200), rx)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res.timed_out);
+    assert!(p.is_discarded("req-slow"));
+    // The container running it stays known until the overrun was dealt with
+    assert_eq!(p.overdue(10_000).len(), 1);
+    assert_eq!(p.assigned(), 1);
+    assert!(!p.discard("req-slow", InvocationResult::timed_out(3_000), 2_000));
+    p.clear_deadline("req-slow");
+    assert!(p.overdue(10_000).is_empty());
+    assert_eq!(p.assigned(), 0);
+    // The runtime's late result finds no waiter
+    assert!(!p.complete("req-slow", InvocationResult::ok(b"late".to_vec())));
+
+    // Nothing is discarded for a request that already completed
+    let _rx = p.register("req-done".to_string());
+    assert!(p.complete("req-done", InvocationResult::ok(b"ok".to_vec())));
+    assert!(!p.discard("req-done", InvocationResult::timed_out(3_000), 1_000));
+    assert!(!p.is_discarded("req-done"));
+
+    p.forget_discarded(1_000);
+    assert!(p.is_discarded("req-slow"));
+    p.forget_discarded(1_001);
+    assert!(!p.is_discarded("req-slow"));
+}
@@ -0,0 +1,161 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::queues::FnKey;
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_models::AddPermissionRequest;
+use std::time::Instant;
+
+fn grant(statement_id: &str, principal: &str) -> AddPermissionRequest {
+    AddPermissionRequest {
+        statement_id: statement_id.into(),
+        action: "lambda:InvokeFunction".into(),
+        principal: principal.into(),
+    }
+}
+
+#[tokio::test]
+async fn statements_are_added_listed_and_removed() {
+    let cp = control_plane().await;
+    create_function(&cp, "payments").await;
+    assert!(cp
+        .get_policy("payments")
+        .await
+        .unwrap()
+        .statements
+        .is_empty());
+
+    let by_name = cp
+        .add_permission("payments", grant("checkout", "checkout"))
+        .await
+        .unwrap();
+    assert_eq!(by_name.principal, "checkout");
+    // ARNs of this server's functions come down to the function name
+    let by_arn = cp
+        .add_permission(
+            "payments",
+            grant(
+                "refunds",
+                "arn:aws:lambda:local:000000000000:function:refunds",
+            ),
+        )
+        .await
+        .unwrap();
+    assert_eq!(by_arn.principal, "refunds");
+
+    let err = cp
+        .add_permission("payments", grant("checkout", "orders"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 409);
+
+    let policy = cp.get_policy("payments").await.unwrap();
+    assert_eq!(policy.function_name, "payments");
+    let principals: Vec<_> = policy
+        .statements
+        .iter()
+        .map(|s| s.principal.as_str())
+        .collect();
+    assert_eq!(principals, ["checkout", "refunds"]);
+
+    cp.remove_permission("payments", "checkout").await.unwrap();
+    assert_eq!(
+        cp.remove_permission("payments", "checkout")
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+    assert_eq!(cp.get_policy("payments").await.unwrap().statements.len(), 1);
+    assert_eq!(
+        cp.get_policy("missing").await.unwrap_err().http_status(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn invalid_statements_are_refused() {
+    let cp = control_plane().await;
+    create_function(&cp, "payments").await;
+
+    for request in [
+        grant("", "checkout"),
+        grant("has space", "checkout"),
+        grant("checkout", "not a function"),
+        grant(
+            "checkout",
+            "arn:aws:lambda:us-east-1:123456789012:function:checkout",
+        ),
+        AddPermissionRequest {
+            action: "lambda:GetFunction".into(),
+            ..grant("checkout", "checkout")
+        },
+    ] {
+        let err = cp.add_permission("payments", request).await.unwrap_err();
+        assert_eq!(err.http_status(), 400, "{err}");
+    }
+    assert!(cp
+        .get_policy("payments")
+        .await
+        .unwrap()
+        .statements
+        .is_empty());
+}
+
+#[tokio::test]
+async fn invokes_from_unknown_instances_are_denied() {
+    let cp = control_plane().await;
+    create_function(&cp, "payments").await;
+    cp.add_permission("payments", grant("everyone", "*"))
+        .await
+        .unwrap();
+
+    let token = cp.instance_token("inst-unknown");
+    for instance_id in [None, Some("inst-unknown")] {
+        let err = cp
+            .authorize_function_invoke(instance_id, Some(&token), "payments")
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 403);
+        assert_eq!(err.error_type(), "AccessDeniedException");
+    }
+}
+
+#[tokio::test]
+async fn invokes_need_the_token_of_the_calling_instance() {
+    let cp = control_plane().await;
+    let checkout = create_function(&cp, "checkout").await;
+    create_function(&cp, "payments").await;
+    cp.add_permission("payments", grant("checkout", "checkout"))
+        .await
+        .unwrap();
+    cp.warm_pool()
+        .add_warm_container(
+            FnKey::for_function(&checkout),
+            WarmContainer {
+                container_id: "c1".to_string(),
+                instance_id: "inst-1".to_string(),
+                function_id: checkout.function_id,
+                image_ref: checkout.image_ref(),
+                memory_mb: 128,
+                created_at: Instant::now(),
+                last_used: Instant::now(),
+                state: InstanceState::Active,
+            },
+        )
+        .await;
+
+    let token = cp.instance_token("inst-1");
+    assert_eq!(
+        cp.authorize_function_invoke(Some("inst-1"), Some(&token), "payments")
+            .await
+            .unwrap(),
+        "checkout"
+    );
+    let other = cp.instance_token("inst-2");
+    for forged in [None, Some("made-up"), Some(other.as_str())] {
+        let err = cp
+            .authorize_function_invoke(Some("inst-1"), forged, "payments")
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 403);
+    }
+}
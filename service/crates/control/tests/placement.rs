@@ -0,0 +1,113 @@
+use lambda_control::placement::{plan_placement, Placement};
+use lambda_control::queues::FnKey;
+use lambda_control::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+fn key(name: &str) -> FnKey {
+    FnKey {
+        function_name: name.to_string(),
+        runtime: "nodejs22.x".to_string(),
+        version: "LATEST".to_string(),
+        env_hash: String::new(),
+    }
+}
+
+fn container(id: &str, memory_mb: u64, idle_for: Duration, state: InstanceState) -> WarmContainer {
+    WarmContainer {
+        container_id: id.to_string(),
+        instance_id: format!("inst-{id}"),
+        function_id: Uuid::new_v4(),
+        image_ref: "img".to_string(),
+        memory_mb,
+        created_at: Instant::now(),
+        last_used: Instant::now() - idle_for,
+        state,
+    }
+}
+
+#[test]
+fn unlimited_budget_always_fits() {
+    assert_eq!(plan_placement(None, 100_000, 512, &[]), Placement::Fits);
+}
+
+#[test]
+fn fits_within_remaining_budget() {
+    assert_eq!(plan_placement(Some(1024), 512, 512, &[]), Placement::Fits);
+}
+
+#[test]
+fn refuses_when_idle_containers_cannot_cover_it() {
+    let idle = vec![(
+        key("a"),
+        container("a1", 128, Duration::from_secs(5), InstanceState::WarmIdle),
+    )];
+    assert_eq!(
+        plan_placement(Some(1024), 1000, 512, &idle),
+        Placement::Refuse { available_mb: 24 }
+    );
+}
+
+#[test]
+fn evicts_from_most_over_provisioned_function_first() {
+    let idle = vec![
+        (
+            key("lean"),
+            container(
+                "lean1",
+                256,
+                Duration::from_secs(60),
+                InstanceState::WarmIdle,
+            ),
+        ),
+        (
+            key("fat"),
+            container("fat1", 256, Duration::from_secs(1), InstanceState::WarmIdle),
+        ),
+        (
+            key("fat"),
+            container(
+                "fat2",
+                256,
+                Duration::from_secs(30),
+                InstanceState::WarmIdle,
+            ),
+        ),
+    ];
+    // 1024 budget, 1024 committed: need 256 back for a 256 MB container
+    assert_eq!(
+        plan_placement(Some(1024), 1024, 256, &idle),
+        Placement::Evict(vec!["fat2".to_string()])
+    );
+    assert_eq!(
+        plan_placement(Some(1024), 1024, 512, &idle),
+        Placement::Evict(vec!["fat2".to_string(), "fat1".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn stopped_containers_do_not_count_against_budget() {
+    let pool = WarmPool::new();
+    pool.add_warm_container(
+        key("a"),
+        container("a1", 512, Duration::ZERO, InstanceState::Active),
+    )
+    .await;
+    pool.add_warm_container(
+        key("a"),
+        container("a2", 512, Duration::ZERO, InstanceState::Stopped),
+    )
+    .await;
+    pool.add_warm_container(
+        key("b"),
+        container("b1", 256, Duration::ZERO, InstanceState::WarmIdle),
+    )
+    .await;
+
+    assert_eq!(pool.committed_memory_mb().await, 768);
+
+    let idle = pool.idle_containers_except(&key("a")).await;
+    assert_eq!(idle.len(), 1);
+    assert_eq!(idle[0].1.container_id, "b1");
+    assert!(pool.idle_containers_except(&key("b")).await.is_empty());
+}
@@ -0,0 +1,170 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::prewarm::{prewarm_event, PrewarmRequests, PREWARM_EVENT_SOURCE};
+use lambda_control::work_item::{FunctionMeta, WorkItem};
+use lambda_models::{LambdaError, PrewarmConfig, RuntimeResponse};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn prewarm_item(id: &str) -> WorkItem {
+    WorkItem {
+        request_id: id.to_string(),
+        function: FunctionMeta {
+            function_name: "hello".into(),
+            runtime: "nodejs22.x".into(),
+            version: None,
+            environment: None,
+            timeout_ms: 1000,
+        },
+        payload: serde_json::to_vec(&prewarm_event()).unwrap(),
+        deadline_ms: 0,
+        log_type: None,
+        client_context: None,
+        cognito_identity: None,
+    }
+}
+
+#[tokio::test]
+async fn instance_takes_queued_work_after_prewarm_succeeds() {
+    let requests = PrewarmRequests::new();
+    assert!(requests.next_for("other").await.unwrap().is_none());
+
+    let outcome = requests.hold("i-1", prewarm_item("warm-1"));
+    let item = requests.next_for("i-1").await.unwrap().unwrap();
+    assert_eq!(item.request_id, "warm-1");
+
+    // Later polls wait for the prewarm invocation to finish
+    let waiting = {
+        let requests = requests.clone();
+        tokio::spawn(async move { requests.next_for("i-1").await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!waiting.is_finished());
+
+    requests.finish("i-1", outcome, true);
+    let next = timeout(Duration::from_secs(1), waiting)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(next.unwrap().is_none());
+    assert!(!requests.is_held("i-1"));
+}
+
+#[tokio::test]
+async fn failed_prewarm_keeps_instance_away_from_queued_work() {
+    let requests = PrewarmRequests::new();
+    let outcome = requests.hold("i-1", prewarm_item("warm-1"));
+    requests.next_for("i-1").await.unwrap().unwrap();
+
+    let waiting = {
+        let requests = requests.clone();
+        tokio::spawn(async move { requests.next_for("i-1").await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    requests.finish("i-1", outcome, false);
+    let next = timeout(Duration::from_secs(1), waiting)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(next, Err(LambdaError::ContainerInitError { .. })));
+}
+
+#[tokio::test]
+async fn prewarm_settings_round_trip() {
+    let cp = control_plane().await;
+    create_function(&cp, "warm").await;
+
+    assert_eq!(
+        cp.get_prewarm("warm").await.unwrap(),
+        PrewarmConfig::default()
+    );
+    let function = cp.get_function("warm").await.unwrap();
+    assert!(cp.prewarm_timeout(&function).await.is_none());
+
+    for timeout_ms in [0, 901_000] {
+        let err = cp
+            .put_prewarm(
+                "warm",
+                PrewarmConfig {
+                    enabled: true,
+                    timeout_ms: Some(timeout_ms),
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LambdaError::InvalidRequest { .. }));
+    }
+
+    cp.put_prewarm(
+        "warm",
+        PrewarmConfig {
+            enabled: true,
+            timeout_ms: None,
+        },
+    )
+    .await
+    .unwrap();
+    // Without a timeout the prewarm invocation gets the function timeout
+    assert_eq!(
+        cp.prewarm_timeout(&function).await,
+        Some(Duration::from_secs(function.timeout))
+    );
+
+    cp.delete_prewarm("warm").await.unwrap();
+    assert_eq!(
+        cp.get_prewarm("warm").await.unwrap(),
+        PrewarmConfig::default()
+    );
+    assert!(matches!(
+        cp.get_prewarm("missing").await,
+        Err(LambdaError::FunctionNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn new_instance_gets_the_prewarm_event_first() {
+    let cp = Arc::new(control_plane().await);
+    create_function(&cp, "warm").await;
+    cp.put_prewarm(
+        "warm",
+        PrewarmConfig {
+            enabled: true,
+            timeout_ms: Some(5_000),
+        },
+    )
+    .await
+    .unwrap();
+    let function = cp.get_function("warm").await.unwrap();
+    let prewarm_timeout = cp.prewarm_timeout(&function).await.unwrap();
+    assert_eq!(prewarm_timeout, Duration::from_secs(5));
+
+    let prewarm = cp.prewarm_container(&function, "container-1", "i-1", prewarm_timeout);
+    let invocation = timeout(
+        Duration::from_secs(1),
+        cp.get_next_invocation_for_instance(
+            Some("i-1"),
+            "warm",
+            &function.runtime,
+            Some(&function.version),
+            None,
+        ),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(invocation.payload["source"], PREWARM_EVENT_SOURCE);
+
+    cp.post_response(
+        RuntimeResponse {
+            aws_request_id: invocation.aws_request_id,
+            payload: serde_json::json!(null),
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(timeout(Duration::from_secs(1), prewarm)
+        .await
+        .unwrap()
+        .unwrap());
+}
@@ -0,0 +1,128 @@
+use crate::common::create_request;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    ConcurrencyConfig, CreateAliasRequest, CreateFunctionRequest, LambdaError,
+    ProvisionedConcurrencyStatus, PutProvisionedConcurrencyConfigRequest,
+};
+use std::sync::Arc;
+
+async fn control_plane() -> Arc<ControlPlane> {
+    Arc::new(crate::common::control_plane().await)
+}
+
+async fn create_published_function(cp: &ControlPlane, name: &str) {
+    cp.create_function(CreateFunctionRequest {
+        publish: Some(true),
+        ..create_request(name)
+    })
+    .await
+    .unwrap();
+    cp.create_alias(
+        name,
+        CreateAliasRequest {
+            name: "live".into(),
+            function_version: "1".into(),
+            description: None,
+            routing_config: None,
+        },
+    )
+    .await
+    .unwrap();
+}
+
+fn executions(count: u32) -> PutProvisionedConcurrencyConfigRequest {
+    PutProvisionedConcurrencyConfigRequest {
+        provisioned_concurrent_executions: count,
+    }
+}
+
+#[tokio::test]
+async fn aliases_and_versions_are_provisioned_until_deleted() {
+    let cp = control_plane().await;
+    create_published_function(&cp, "orders").await;
+
+    let config = cp
+        .put_provisioned_concurrency("orders", "live", executions(2))
+        .await
+        .unwrap();
+    assert_eq!(config.requested_provisioned_concurrent_executions, 2);
+    assert_eq!(config.available_provisioned_concurrent_executions, 0);
+    assert_ne!(config.status, ProvisionedConcurrencyStatus::Ready);
+    cp.put_provisioned_concurrency("orders", "1", executions(1))
+        .await
+        .unwrap();
+
+    // Both configs keep version 1 warm
+    let targets = cp.provisioned_targets().await.unwrap();
+    assert_eq!(targets.len(), 1);
+    let (key, count) = targets.into_iter().next().unwrap();
+    assert_eq!(
+        (key.function_name.as_str(), key.version.as_str()),
+        ("orders", "1")
+    );
+    assert_eq!(count, 3);
+
+    let json = serde_json::to_value(
+        cp.get_provisioned_concurrency("orders", "live")
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(json["RequestedProvisionedConcurrentExecutions"], 2);
+    assert!(json["LastModified"].is_string());
+
+    cp.delete_provisioned_concurrency("orders", "live")
+        .await
+        .unwrap();
+    let missing = cp
+        .get_provisioned_concurrency("orders", "live")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        missing,
+        LambdaError::ProvisionedConcurrencyConfigNotFound { .. }
+    ));
+    assert_eq!(missing.http_status(), 404);
+    assert_eq!(
+        cp.provisioned_targets()
+            .await
+            .unwrap()
+            .values()
+            .sum::<usize>(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn invalid_configs_are_refused() {
+    let cp = control_plane().await;
+    create_published_function(&cp, "orders").await;
+
+    for qualifier in ["$LATEST", "7", "ghost"] {
+        assert!(cp
+            .put_provisioned_concurrency("orders", qualifier, executions(1))
+            .await
+            .is_err());
+    }
+    let zero = cp
+        .put_provisioned_concurrency("orders", "live", executions(0))
+        .await;
+    assert!(matches!(zero, Err(LambdaError::InvalidRequest { .. })));
+
+    cp.put_concurrency(
+        "orders",
+        ConcurrencyConfig {
+            reserved_concurrent_executions: Some(2),
+        },
+    )
+    .await
+    .unwrap();
+    let over_reserved = cp
+        .put_provisioned_concurrency("orders", "live", executions(3))
+        .await;
+    assert!(matches!(
+        over_reserved,
+        Err(LambdaError::InvalidRequest { .. })
+    ));
+    assert!(cp.provisioned_targets().await.unwrap().is_empty());
+}
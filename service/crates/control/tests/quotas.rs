@@ -0,0 +1,126 @@
+use crate::common::{control_plane_with, temp_dir, test_config};
+use chrono::{TimeZone, Utc};
+use lambda_control::quotas::{check_quota, start_of_day};
+use lambda_models::{Config, CreateFunctionRequest, FunctionCode, QuotaUsage};
+use lambda_testsupport::helpers::{b64, zip_dir};
+use std::path::Path;
+
+fn create_request(name: &str, zip_file: Option<String>) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        code: FunctionCode {
+            zip_file,
+            ..Default::default()
+        },
+        ..crate::common::create_request(name)
+    }
+}
+
+fn zip_base64(dir: &Path, source: &str) -> String {
+    let src = dir.join(format!("src-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(src.join("index.js"), source).unwrap();
+    b64(zip_dir(&src).unwrap())
+}
+
+#[test]
+fn quotas_allow_up_to_their_limit() {
+    assert!(check_quota("max_functions", 1, 1, Some(2)).is_ok());
+    assert!(check_quota("max_functions", 5, 1, None).is_ok());
+    let err = check_quota("max_functions", 2, 1, Some(2)).unwrap_err();
+    assert_eq!(err.http_status(), 429);
+    assert_eq!(err.error_type(), "QuotaExceededException");
+    assert_eq!(
+        err.to_string(),
+        "Quota max_functions of namespace default exceeded: limit is 2"
+    );
+}
+
+#[test]
+fn days_start_at_midnight_utc() {
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 17, 30, 5).unwrap();
+    assert_eq!(
+        start_of_day(now),
+        Utc.with_ymd_and_hms(2026, 3, 4, 0, 0, 0).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn function_count_is_limited() {
+    let mut config = Config::default();
+    config.quotas.max_functions = Some(1);
+    let cp = control_plane_with(config).await;
+
+    cp.create_function(create_request("first", None))
+        .await
+        .unwrap();
+    let err = cp
+        .create_function(create_request("second", None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 429, "{err}");
+    assert!(cp.get_function("second").await.is_err());
+}
+
+#[tokio::test]
+async fn storage_is_limited() {
+    let dir = temp_dir();
+    let mut config = test_config();
+    config.data.dir = dir.path().to_string_lossy().into_owned();
+    config.quotas.max_storage_bytes = Some(100);
+    let cp = control_plane_with(config).await;
+
+    let small = "exports.handler = async () => 1;";
+    cp.create_function(create_request("small", Some(zip_base64(dir.path(), small))))
+        .await
+        .unwrap();
+    let large = format!("exports.handler = async () => '{}';", "x".repeat(200));
+    let err = cp
+        .create_function(create_request(
+            "large",
+            Some(zip_base64(dir.path(), &large)),
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 429, "{err}");
+
+    let usage = cp.namespace_usage("default").await.unwrap();
+    assert_eq!(
+        usage.storage_bytes,
+        QuotaUsage {
+            used: small.len() as u64,
+            limit: Some(100)
+        }
+    );
+}
+
+#[tokio::test]
+async fn usage_is_reported_for_the_default_namespace() {
+    let mut config = Config::default();
+    config.quotas.max_daily_invocations = Some(1000);
+    let cp = control_plane_with(config).await;
+    cp.create_function(create_request("hello", None))
+        .await
+        .unwrap();
+
+    let usage = cp.namespace_usage("default").await.unwrap();
+    assert_eq!(usage.namespace, "default");
+    assert_eq!(
+        usage.functions,
+        QuotaUsage {
+            used: 1,
+            limit: None
+        }
+    );
+    assert_eq!(
+        usage.daily_invocations,
+        QuotaUsage {
+            used: 0,
+            limit: Some(1000)
+        }
+    );
+    assert_eq!(usage.warm_memory_mb.used, 0);
+    assert_eq!(usage.day_started_at, start_of_day(Utc::now()));
+
+    let err = cp.namespace_usage("team-a").await.unwrap_err();
+    assert_eq!(err.http_status(), 404, "{err}");
+}
@@ -0,0 +1,222 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::recording::{redact_event, RecordingSampler, REDACTED};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    ApiRoute, CreateApiRouteRequest, CreateTestEventRequest, LambdaError, RouteRecording,
+    TestEventSource,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+async fn create_route(cp: &ControlPlane) -> ApiRoute {
+    create_function(cp, "orders").await;
+    cp.create_api_route(CreateApiRouteRequest {
+        path: "/orders".into(),
+        method: None,
+        function_name: "orders".into(),
+        canary: None,
+    })
+    .await
+    .unwrap()
+}
+
+fn recording() -> RouteRecording {
+    serde_json::from_value(json!({ "redact_body_fields": ["card"] })).unwrap()
+}
+
+fn gateway_event() -> serde_json::Value {
+    json!({
+        "path": "/orders/7",
+        "httpMethod": "POST",
+        "headers": { "Authorization": "Bearer secret", "accept": "*/*" },
+        "queryStringParameters": { "token": "abc", "page": "2" },
+        "body": r#"{"items":[{"card":"4111"}],"card":"4111","total":3}"#,
+    })
+}
+
+#[test]
+fn sampler_limits_each_route_per_minute() {
+    let sampler = RecordingSampler::new();
+    let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+    let granted = (0..5).filter(|_| sampler.try_acquire(a, 2)).count();
+    // 2, unless the clock minute turned over during the loop
+    assert!((2..=4).contains(&granted));
+    assert!(sampler.try_acquire(b, 2));
+
+    sampler.remove_route(a);
+    assert!(sampler.try_acquire(a, 2));
+}
+
+#[test]
+fn redaction_covers_headers_query_and_json_body() {
+    let mut rules = recording();
+    rules.redact_query = vec!["token".into()];
+    let mut event = gateway_event();
+    redact_event(&rules, &mut event);
+
+    assert_eq!(event["headers"]["Authorization"], REDACTED);
+    assert_eq!(event["headers"]["accept"], "*/*");
+    assert_eq!(event["queryStringParameters"]["token"], REDACTED);
+    assert_eq!(event["queryStringParameters"]["page"], "2");
+    let body: serde_json::Value = serde_json::from_str(event["body"].as_str().unwrap()).unwrap();
+    assert_eq!(body["card"], REDACTED);
+    assert_eq!(body["items"][0]["card"], REDACTED);
+    assert_eq!(body["total"], 3);
+
+    // Bodies that aren't JSON are kept as they are
+    let mut event = json!({ "body": "card=4111" });
+    redact_event(&rules, &mut event);
+    assert_eq!(event["body"], "card=4111");
+}
+
+#[tokio::test]
+async fn recording_route_saves_redacted_test_events() {
+    let cp = control_plane().await;
+    let route = create_route(&cp).await;
+
+    // Not recording yet
+    assert!(cp
+        .capture_route_event(&route, &gateway_event())
+        .await
+        .unwrap()
+        .is_none());
+
+    let mut rules = recording();
+    rules.per_minute = 600;
+    rules.max_events = 2;
+    let route = cp
+        .put_api_route_recording(route.route_id, rules)
+        .await
+        .unwrap();
+    let recording = route.recording.clone().unwrap();
+    assert!(recording.enabled);
+    assert_eq!(recording.redact_headers, vec!["authorization", "cookie"]);
+
+    for _ in 0..3 {
+        let saved = cp
+            .capture_route_event(&route, &gateway_event())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.source, TestEventSource::Recorded);
+        assert_eq!(saved.route_id, Some(route.route_id));
+        assert!(saved.name.starts_with("POST /orders/7 "));
+        assert_eq!(saved.payload["headers"]["Authorization"], REDACTED);
+    }
+    // Only the newest `max_events` recorded events are kept
+    let events = cp.list_test_events("orders").await.unwrap().test_events;
+    assert_eq!(events.len(), 2);
+
+    cp.delete_api_route_recording(route.route_id).await.unwrap();
+    let route = cp.get_api_route(route.route_id).await.unwrap();
+    assert!(route.recording.is_none());
+    assert!(cp
+        .capture_route_event(&route, &gateway_event())
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        cp.list_test_events("orders")
+            .await
+            .unwrap()
+            .test_events
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn invalid_recordings_are_rejected() {
+    let cp = control_plane().await;
+    let route = create_route(&cp).await;
+    let invalid = [
+        RouteRecording {
+            per_minute: 0,
+            ..recording()
+        },
+        RouteRecording {
+            max_events: 1001,
+            ..recording()
+        },
+        RouteRecording {
+            redact_query: vec![" ".into()],
+            ..recording()
+        },
+    ];
+    for rules in invalid {
+        let err = cp
+            .put_api_route_recording(route.route_id, rules)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LambdaError::InvalidRequest { .. }));
+    }
+    assert!(cp
+        .put_api_route_recording(Uuid::new_v4(), recording())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_events_round_trip() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    create_function(&cp, "orders-v2").await;
+
+    let saved = cp
+        .create_test_event(
+            "orders",
+            CreateTestEventRequest {
+                name: " checkout ".into(),
+                payload: json!({ "total": 3 }),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(saved.name, "checkout");
+    assert_eq!(saved.source, TestEventSource::Manual);
+
+    // Export from one function and import into another
+    let exported = cp.get_test_event("orders", saved.event_id).await.unwrap();
+    let imported = cp
+        .create_test_event(
+            "orders-v2",
+            CreateTestEventRequest {
+                name: exported.name,
+                payload: exported.payload,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(imported.function_name, "orders-v2");
+    assert_eq!(imported.payload, json!({ "total": 3 }));
+
+    assert!(matches!(
+        cp.get_test_event("orders-v2", saved.event_id).await,
+        Err(LambdaError::TestEventNotFound { .. })
+    ));
+    assert!(matches!(
+        cp.create_test_event(
+            "orders",
+            CreateTestEventRequest {
+                name: "".into(),
+                payload: json!(null),
+            },
+        )
+        .await,
+        Err(LambdaError::InvalidRequest { .. })
+    ));
+
+    cp.delete_test_event("orders", saved.event_id)
+        .await
+        .unwrap();
+    assert!(cp
+        .list_test_events("orders")
+        .await
+        .unwrap()
+        .test_events
+        .is_empty());
+    assert!(matches!(
+        cp.delete_test_event("orders", saved.event_id).await,
+        Err(LambdaError::TestEventNotFound { .. })
+    ));
+}
@@ -59,7 +59,7 @@ fn test_deletion_state_thread_safety() {
     for i in 0..10 {
         let functions_being_deleted = functions_being_deleted.clone();
         let handle = thread::spawn(move || {
-            let function_name = format!("function-{}", i);
+            let function_name = format!("function-{i}");
             mark_function_for_deletion(&functions_being_deleted, &function_name);
             assert!(is_function_being_deleted(&functions_being_deleted, &function_name));
             unmark_function_for_deletion(&functions_being_deleted, &function_name);
@@ -75,7 +75,7 @@ fn test_deletion_state_thread_safety() {
     
     // All functions should be unmarked
     for i in 0..10 {
-        assert!(!is_function_being_deleted(&functions_being_deleted, &format!("function-{}", i)));
+        assert!(!is_function_being_deleted(&functions_being_deleted, &format!("function-{i}")));
     }
 }
 
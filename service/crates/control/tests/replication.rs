@@ -0,0 +1,281 @@
+use crate::common::{control_plane_with, create_function};
+use lambda_control::registry::ControlPlane;
+use lambda_control::replication::{
+    function_fingerprint, plan_sync, should_forward, Replicator, SyncAction,
+};
+use lambda_models::{
+    Config, CreateApiRouteRequest, DiskPressure, DockerBreakerState, InstanceHealth, LambdaError,
+    ReplicatedKind, ReplicationConfig, ReplicationState,
+};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+async fn control_plane(config: Config) -> ControlPlane {
+    let mut config = config;
+    config.warmup.enabled = false;
+    control_plane_with(config).await
+}
+
+#[test]
+fn plans_sync_from_fingerprints() {
+    let conflict = |action| matches!(action, SyncAction::Conflict(_));
+    // New locally
+    assert_eq!(plan_sync(Some("a"), None, None, false), SyncAction::Create);
+    assert_eq!(
+        plan_sync(Some("a"), Some("a"), None, false),
+        SyncAction::Adopt
+    );
+    assert!(conflict(plan_sync(Some("a"), Some("b"), None, false)));
+    assert_eq!(
+        plan_sync(Some("a"), Some("b"), None, true),
+        SyncAction::Update
+    );
+    // Previously synced
+    assert_eq!(
+        plan_sync(Some("a"), Some("a"), Some("a"), false),
+        SyncAction::InSync
+    );
+    assert_eq!(
+        plan_sync(Some("b"), Some("a"), Some("a"), false),
+        SyncAction::Update
+    );
+    assert!(conflict(plan_sync(Some("b"), Some("c"), Some("a"), false)));
+    assert_eq!(
+        plan_sync(Some("b"), Some("c"), Some("a"), true),
+        SyncAction::Update
+    );
+    assert!(conflict(plan_sync(Some("a"), None, Some("a"), false)));
+    assert_eq!(
+        plan_sync(Some("a"), None, Some("a"), true),
+        SyncAction::Create
+    );
+    // Deleted locally
+    assert_eq!(
+        plan_sync(None, Some("a"), Some("a"), false),
+        SyncAction::Delete
+    );
+    assert!(conflict(plan_sync(None, Some("b"), Some("a"), false)));
+    assert_eq!(plan_sync(None, None, Some("a"), false), SyncAction::Forget);
+}
+
+#[tokio::test]
+async fn fingerprint_covers_code_and_configuration() {
+    let cp = control_plane(Config::default()).await;
+    create_function(&cp, "orders").await;
+    let function = cp.get_function("orders").await.unwrap();
+
+    let mut same = function.clone();
+    same.last_modified = chrono::Utc::now();
+    assert_eq!(function_fingerprint(&function), function_fingerprint(&same));
+
+    let mut new_code = function.clone();
+    new_code.code_sha256 = "abc".into();
+    assert_ne!(
+        function_fingerprint(&function),
+        function_fingerprint(&new_code)
+    );
+    let mut new_env = function.clone();
+    new_env.environment.insert("STAGE".into(), "prod".into());
+    assert_ne!(
+        function_fingerprint(&function),
+        function_fingerprint(&new_env)
+    );
+}
+
+#[tokio::test]
+async fn sync_requires_replication_to_be_enabled() {
+    let cp = control_plane(Config::default()).await;
+    assert!(!cp.replication_status().enabled);
+    assert!(matches!(
+        cp.replicate(false).await,
+        Err(LambdaError::InvalidRequest { .. })
+    ));
+}
+
+/// A peer that has no functions and no routes, accepts every create and logs the
+/// request lines it receives
+async fn spawn_empty_peer() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read headers, then as much body as Content-Length announces
+            let text = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                let Some(end) = text.find("\r\n\r\n") else {
+                    if n == 0 {
+                        break text;
+                    }
+                    continue;
+                };
+                let length = text
+                    .lines()
+                    .find_map(|l| {
+                        l.to_ascii_lowercase()
+                            .strip_prefix("content-length: ")
+                            .map(str::to_string)
+                    })
+                    .and_then(|l| l.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                if n == 0 || request.len() >= end + 4 + length {
+                    break text;
+                }
+            };
+            let line = text.lines().next().unwrap_or_default().to_string();
+            let (status, body) = match line.split(' ').take(2).collect::<Vec<_>>()[..] {
+                ["GET", "/admin/api-gateway/routes"] => (200, r#"{"routes":[]}"#.to_string()),
+                ["POST", "/admin/api-gateway/routes"] => {
+                    let body = &text[text.find("\r\n\r\n").unwrap() + 4..];
+                    let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                    let route = serde_json::json!({
+                        "route_id": uuid::Uuid::new_v4(),
+                        "path": request["path"],
+                        "method": request["method"],
+                        "function_name": request["function_name"],
+                        "created_at": chrono::Utc::now(),
+                    });
+                    (200, route.to_string())
+                }
+                ["POST", _] => (201, "{}".to_string()),
+                _ => (404, "{}".to_string()),
+            };
+            log.lock().unwrap().push(line);
+            let response = format!(
+                "HTTP/1.1 {status} Status\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (url, received)
+}
+
+#[tokio::test]
+async fn pushes_new_items_and_flags_copies_missing_on_the_peer() {
+    let (peer_url, received) = spawn_empty_peer().await;
+    let mut config = Config::default();
+    config.replication.enabled = true;
+    config.replication.peer_url = Some(peer_url.clone());
+    // The background loop waits an interval before its first round
+    config.replication.interval_secs = 3600;
+    let cp = control_plane(config).await;
+    create_function(&cp, "orders").await;
+    cp.create_api_route(CreateApiRouteRequest {
+        path: "/orders".into(),
+        method: Some("GET".into()),
+        function_name: "orders".into(),
+        canary: None,
+    })
+    .await
+    .unwrap();
+
+    let status = cp.replicate(false).await.unwrap();
+    assert!(status.enabled);
+    assert_eq!(status.peer_url.as_deref(), Some(peer_url.as_str()));
+    assert!(status.last_error.is_none(), "{:?}", status.last_error);
+    let states: Vec<_> = status
+        .items
+        .iter()
+        .map(|i| (i.kind, i.id.as_str(), i.state))
+        .collect();
+    assert!(states.contains(&(ReplicatedKind::Function, "orders", ReplicationState::InSync)));
+    assert!(states.contains(&(
+        ReplicatedKind::Route,
+        "GET /orders",
+        ReplicationState::InSync
+    )));
+    let requests = received.lock().unwrap().clone();
+    assert!(requests
+        .iter()
+        .any(|r| r.starts_with("POST /2015-03-31/functions ")));
+    assert!(requests
+        .iter()
+        .any(|r| r.starts_with("POST /admin/api-gateway/routes ")));
+
+    // The peer still reports nothing, as if its copies had been deleted there
+    let status = cp.replicate(false).await.unwrap();
+    assert!(status
+        .items
+        .iter()
+        .all(|i| i.state == ReplicationState::Conflict && i.synced_at.is_some()));
+    assert_eq!(cp.replication_status().items.len(), 2);
+}
+
+fn health(healthy: bool) -> InstanceHealth {
+    InstanceHealth {
+        healthy,
+        docker: healthy,
+        docker_breaker: DockerBreakerState::Closed,
+        disk_pressure: DiskPressure::Ok,
+        reason: None,
+        checked_at: chrono::Utc::now(),
+    }
+}
+
+#[test]
+fn forwards_only_from_an_unhealthy_instance_to_a_fresh_healthy_peer() {
+    let now = chrono::Utc::now();
+    let stale_after = chrono::Duration::seconds(30);
+    let (up, down) = (health(true), health(false));
+    assert!(should_forward(Some(&down), Some(&up), now, stale_after));
+    assert!(!should_forward(Some(&up), Some(&up), now, stale_after));
+    assert!(!should_forward(Some(&down), Some(&down), now, stale_after));
+    assert!(!should_forward(Some(&down), None, now, stale_after));
+    assert!(!should_forward(None, Some(&up), now, stale_after));
+
+    let old = InstanceHealth {
+        checked_at: now - chrono::Duration::seconds(31),
+        ..up
+    };
+    assert!(!should_forward(Some(&down), Some(&old), now, stale_after));
+}
+
+#[test]
+fn failover_peer_follows_health_reports() {
+    let config = ReplicationConfig {
+        enabled: true,
+        peer_url: Some("http://127.0.0.1:1".into()),
+        failover: true,
+        ..Default::default()
+    };
+    let replicator = Replicator::new(&config);
+    assert!(replicator.failover_peer().is_none());
+
+    replicator.set_local_health(health(false));
+    replicator.record_peer_health(InstanceHealth {
+        // Reports count from when they arrive, not from the peer's clock
+        checked_at: chrono::Utc::now() - chrono::Duration::days(1),
+        ..health(true)
+    });
+    assert!(replicator.failover_peer().is_some());
+
+    replicator.set_local_health(health(true));
+    assert!(replicator.failover_peer().is_none());
+
+    replicator.record_forward("orders", false);
+    replicator.record_forward("orders", true);
+    let status = replicator.status();
+    assert!(status.failover);
+    assert_eq!(status.forwarded.len(), 1);
+    assert_eq!(
+        (status.forwarded[0].forwarded, status.forwarded[0].failed),
+        (1, 1)
+    );
+
+    // Failover stays off unless configured
+    let replicator = Replicator::new(&ReplicationConfig {
+        failover: false,
+        ..config
+    });
+    replicator.set_local_health(health(false));
+    replicator.record_peer_health(health(true));
+    assert!(replicator.failover_peer().is_none());
+}
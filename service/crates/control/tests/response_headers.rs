@@ -0,0 +1,141 @@
+use crate::common::{control_plane_with, create_function};
+use lambda_control::response_headers::shape_response;
+use lambda_models::{Config, ResponseHeadersConfig};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn function_headers_override_global_ones() {
+    let config = ResponseHeadersConfig {
+        headers: headers(&[("cache-control", "max-age=60")]),
+        envelope: false,
+    };
+    let global = headers(&[("Cache-Control", "no-store"), ("X-Frame-Options", "DENY")]);
+
+    let (body, shaped) = shape_response(&global, &config, json!({"ok": true}));
+    assert_eq!(body, json!({"ok": true}));
+    assert_eq!(
+        shaped,
+        headers(&[("cache-control", "max-age=60"), ("x-frame-options", "DENY")])
+    );
+}
+
+#[test]
+fn envelopes_are_unwrapped_only_when_enabled() {
+    let envelope = json!({
+        "headers": {"Content-Type": "text/html", "X-Amz-Request-Id": "spoofed", "X-Count": 3},
+        "body": "<p>hi</p>",
+    });
+    let mut config = ResponseHeadersConfig {
+        headers: headers(&[("content-type", "application/json")]),
+        envelope: false,
+    };
+
+    let (body, _) = shape_response(&HashMap::new(), &config, envelope.clone());
+    assert_eq!(body, envelope);
+
+    config.envelope = true;
+    let (body, shaped) = shape_response(&HashMap::new(), &config, envelope);
+    assert_eq!(body, json!("<p>hi</p>"));
+    assert_eq!(
+        shaped,
+        headers(&[("content-type", "text/html"), ("x-count", "3")])
+    );
+
+    // Anything else passes through as the result
+    let result = json!({"headers": {}, "body": 1, "statusCode": 200});
+    let (body, _) = shape_response(&HashMap::new(), &config, result.clone());
+    assert_eq!(body, result);
+}
+
+#[tokio::test]
+async fn response_headers_are_stored_lowercase() {
+    let cp = control_plane_with(Config::default()).await;
+    create_function(&cp, "hello").await;
+    assert_eq!(
+        cp.get_response_headers("hello").await.unwrap(),
+        ResponseHeadersConfig::default()
+    );
+
+    let stored = cp
+        .put_response_headers(
+            "hello",
+            ResponseHeadersConfig {
+                headers: headers(&[("Content-Type", "text/plain; charset=utf-8")]),
+                envelope: true,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        stored.headers,
+        headers(&[("content-type", "text/plain; charset=utf-8")])
+    );
+    assert_eq!(cp.get_response_headers("hello").await.unwrap(), stored);
+
+    cp.delete_response_headers("hello").await.unwrap();
+    assert_eq!(
+        cp.get_response_headers("hello").await.unwrap(),
+        ResponseHeadersConfig::default()
+    );
+}
+
+#[tokio::test]
+async fn server_headers_are_rejected() {
+    let cp = control_plane_with(Config::default()).await;
+    create_function(&cp, "hello").await;
+
+    for (name, value) in [
+        ("X-Amz-Function-Error", "Handled"),
+        ("x-lambdah-retry-count", "1"),
+        ("Content-Length", "5"),
+        ("Bad Header", "x"),
+        ("X-Note", "line\nbreak"),
+    ] {
+        let err = cp
+            .put_response_headers(
+                "hello",
+                ResponseHeadersConfig {
+                    headers: headers(&[(name, value)]),
+                    envelope: false,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 400, "{name}: {err}");
+    }
+}
+
+#[tokio::test]
+async fn shaping_applies_global_and_function_headers() {
+    let mut config = Config::default();
+    config.invoke_response.default_headers = headers(&[("Cache-Control", "no-store")]);
+    let cp = control_plane_with(config).await;
+    create_function(&cp, "hello").await;
+    cp.put_response_headers(
+        "hello",
+        ResponseHeadersConfig {
+            headers: headers(&[("content-type", "text/plain")]),
+            envelope: false,
+        },
+    )
+    .await
+    .unwrap();
+
+    let (body, shaped) = cp.shape_invoke_response("hello", json!("hi")).await;
+    assert_eq!(body, json!("hi"));
+    assert_eq!(
+        shaped,
+        headers(&[
+            ("cache-control", "no-store"),
+            ("content-type", "text/plain")
+        ])
+    );
+}
@@ -0,0 +1,170 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::queues::FnKey;
+use lambda_control::rollouts::{changed_container_settings, Rollouts};
+use lambda_control::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use lambda_models::{Rollout, RolloutStatus, UpdateFunctionConfigurationRequest};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+fn update() -> UpdateFunctionConfigurationRequest {
+    UpdateFunctionConfigurationRequest {
+        role: None,
+        handler: None,
+        description: None,
+        timeout: None,
+        memory_size: None,
+        environment: None,
+    }
+}
+
+fn rollout(function_name: &str) -> Rollout {
+    Rollout {
+        function_name: function_name.into(),
+        status: RolloutStatus::InProgress,
+        changed: vec!["memory_size".into()],
+        total: 2,
+        replaced: 0,
+        removed_stopped: 0,
+        started_at: chrono::Utc::now(),
+        finished_at: None,
+        error: None,
+    }
+}
+
+fn container(container_id: &str, state: InstanceState) -> WarmContainer {
+    WarmContainer {
+        container_id: container_id.into(),
+        instance_id: format!("inst-{container_id}"),
+        function_id: Uuid::new_v4(),
+        image_ref: "lambda-home/orders:abc".into(),
+        memory_mb: 128,
+        created_at: Instant::now(),
+        last_used: Instant::now(),
+        state,
+    }
+}
+
+#[tokio::test]
+async fn memory_and_environment_changes_need_new_containers() {
+    let cp = control_plane().await;
+    let before = create_function(&cp, "orders").await;
+
+    let mut after = before.clone();
+    after.timeout += 10;
+    after.description = Some("Order intake".into());
+    assert!(changed_container_settings(&before, &after).is_empty());
+    after.memory_size *= 2;
+    after
+        .environment
+        .insert("STAGE".to_string(), "prod".to_string());
+    assert_eq!(
+        changed_container_settings(&before, &after),
+        vec!["memory_size", "environment"]
+    );
+
+    // Replacements are queued under the key invocations of the new settings use
+    assert_eq!(
+        FnKey::for_function(&before).version,
+        FnKey::for_function(&after).version
+    );
+    assert_ne!(
+        FnKey::for_function(&before).env_hash,
+        FnKey::for_function(&after).env_hash
+    );
+}
+
+#[tokio::test]
+async fn a_newer_rollout_supersedes_one_in_progress() {
+    let cp = control_plane().await;
+    let function = create_function(&cp, "orders").await;
+    let (rollouts, mut tasks) = Rollouts::new();
+
+    rollouts.begin(
+        rollout("orders"),
+        function.clone(),
+        vec![container("c1", InstanceState::WarmIdle)],
+        Duration::from_secs(5),
+    );
+    let first = tasks.recv().await.unwrap();
+    assert_eq!(first.containers.len(), 1);
+    assert!(rollouts.update("orders", first.generation, |r| r.replaced += 1));
+
+    rollouts.begin(
+        rollout("orders"),
+        function,
+        Vec::new(),
+        Duration::from_secs(5),
+    );
+    let second = tasks.recv().await.unwrap();
+    assert!(!rollouts.is_current("orders", first.generation));
+    assert!(rollouts.is_current("orders", second.generation));
+    // The superseded rollout no longer changes what is reported
+    assert!(!rollouts.update("orders", first.generation, |r| r.replaced += 1));
+    assert_eq!(rollouts.get("orders").unwrap().replaced, 0);
+}
+
+#[tokio::test]
+async fn busy_containers_are_not_drained() {
+    let pool = WarmPool::new();
+    let key = FnKey {
+        function_name: "orders".into(),
+        runtime: "nodejs22.x".into(),
+        version: "LATEST".into(),
+        env_hash: "h".into(),
+    };
+    pool.add_warm_container(key.clone(), container("busy", InstanceState::Active))
+        .await;
+    pool.add_warm_container(key.clone(), container("idle", InstanceState::WarmIdle))
+        .await;
+
+    assert!(!pool.mark_stopping_unless_busy("busy").await);
+    assert!(pool.mark_stopping_unless_busy("idle").await);
+    assert_eq!(pool.count_state(&key, InstanceState::Stopping).await, 1);
+    assert_eq!(pool.count_state(&key, InstanceState::Active).await, 1);
+    // Containers already gone count as drained
+    assert!(pool.mark_stopping_unless_busy("missing").await);
+}
+
+#[tokio::test]
+async fn configuration_changes_start_a_rollout() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    assert_eq!(
+        cp.get_rollout("orders").await.unwrap_err().http_status(),
+        404
+    );
+    assert_eq!(
+        cp.get_rollout("missing").await.unwrap_err().http_status(),
+        404
+    );
+
+    // A new timeout needs no new containers
+    let mut request = update();
+    request.timeout = Some(30);
+    cp.update_function_configuration("orders", request)
+        .await
+        .unwrap();
+    assert!(cp.get_rollout("orders").await.is_err());
+
+    let mut request = update();
+    request.memory_size = Some(1024);
+    cp.update_function_configuration("orders", request)
+        .await
+        .unwrap();
+    let mut rollout = cp.get_rollout("orders").await.unwrap();
+    for _ in 0..50 {
+        if rollout.status != RolloutStatus::InProgress {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        rollout = cp.get_rollout("orders").await.unwrap();
+    }
+    assert_eq!(rollout.status, RolloutStatus::Completed);
+    assert_eq!(rollout.changed, vec!["memory_size"]);
+    assert_eq!(rollout.total, 0);
+    assert!(rollout.finished_at.is_some());
+
+    cp.delete_function("orders").await.unwrap();
+    create_function(&cp, "orders").await;
+    assert!(cp.get_rollout("orders").await.is_err());
+}
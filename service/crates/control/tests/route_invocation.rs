@@ -0,0 +1,86 @@
+use crate::common::{control_plane, create_function};
+use lambda_models::{CreateApiRouteRequest, InvocationType, RouteInvocation};
+use uuid::Uuid;
+
+fn invocation(invocation_type: InvocationType, allow_override: bool) -> RouteInvocation {
+    RouteInvocation {
+        invocation_type,
+        allow_override,
+    }
+}
+
+#[test]
+fn requests_choose_the_type_only_when_the_route_allows_it() {
+    let fixed = invocation(InvocationType::Event, false);
+    assert_eq!(fixed.invocation_type_for(None), InvocationType::Event);
+    assert_eq!(
+        fixed.invocation_type_for(Some("RequestResponse")),
+        InvocationType::Event
+    );
+
+    let open = invocation(InvocationType::RequestResponse, true);
+    assert_eq!(
+        open.invocation_type_for(None),
+        InvocationType::RequestResponse
+    );
+    assert_eq!(
+        open.invocation_type_for(Some("Event")),
+        InvocationType::Event
+    );
+    // Dry runs and unknown types keep the route's type
+    assert_eq!(
+        open.invocation_type_for(Some("DryRun")),
+        InvocationType::RequestResponse
+    );
+    assert_eq!(
+        open.invocation_type_for(Some("event")),
+        InvocationType::RequestResponse
+    );
+}
+
+#[tokio::test]
+async fn routes_store_how_their_function_is_invoked() {
+    let cp = control_plane().await;
+    create_function(&cp, "webhooks").await;
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/hooks".into(),
+            method: Some("POST".into()),
+            function_name: "webhooks".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(route.invocation, None);
+
+    let updated = cp
+        .put_api_route_invocation(route.route_id, invocation(InvocationType::Event, true))
+        .await
+        .unwrap();
+    assert_eq!(
+        updated.invocation,
+        Some(invocation(InvocationType::Event, true))
+    );
+    let matched = cp.match_api_route("POST", "/hooks").await.unwrap().unwrap();
+    assert_eq!(matched.invocation, updated.invocation);
+
+    let err = cp
+        .put_api_route_invocation(route.route_id, invocation(InvocationType::DryRun, false))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+
+    cp.delete_api_route_invocation(route.route_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        cp.get_api_route(route.route_id).await.unwrap().invocation,
+        None
+    );
+
+    let err = cp
+        .put_api_route_invocation(Uuid::new_v4(), invocation(InvocationType::Event, false))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Route not found"), "{err}");
+}
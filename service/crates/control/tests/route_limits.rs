@@ -0,0 +1,140 @@
+use crate::common::{control_plane_with, create_request};
+use lambda_control::route_limits::RouteGates;
+use lambda_models::{ApiRoute, Config, CreateApiRouteRequest, LambdaError, RouteLimits};
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn route(limits: Option<RouteLimits>) -> ApiRoute {
+    ApiRoute {
+        route_id: Uuid::new_v4(),
+        path: "/orders".into(),
+        method: None,
+        function_name: "orders".into(),
+        created_at: chrono::Utc::now(),
+        canary: None,
+        access_log: true,
+        mock: None,
+        recording: None,
+        limits,
+        transform: None,
+        invocation: None,
+        jwt_authorizer: None,
+    }
+}
+
+fn limits(value: serde_json::Value) -> RouteLimits {
+    serde_json::from_value(value).unwrap()
+}
+
+#[tokio::test]
+async fn requests_over_the_limit_without_a_queue_are_shed() {
+    let gates = RouteGates::new();
+    let route = route(Some(limits(
+        json!({ "max_in_flight": 1, "retry_after_secs": 5 }),
+    )));
+
+    let first = gates.admit(&route).await.unwrap();
+    let err = gates.admit(&route).await.err().unwrap();
+    assert!(matches!(
+        err,
+        LambdaError::RouteSaturated {
+            retry_after_secs: 5,
+            ..
+        }
+    ));
+    assert_eq!(err.http_status(), 503);
+
+    drop(first);
+    let saturation = gates.saturation(route.route_id);
+    assert_eq!(saturation.in_flight, 0);
+    assert_eq!(saturation.admitted, 1);
+    assert_eq!(saturation.shed, 1);
+    assert!(gates.admit(&route).await.is_ok());
+}
+
+#[tokio::test]
+async fn queued_requests_wait_for_a_slot_until_their_timeout() {
+    let gates = RouteGates::new();
+    let route = route(Some(limits(json!({
+        "max_in_flight": 1,
+        "max_queued": 1,
+        "queue_timeout_ms": 200,
+    }))));
+
+    let first = gates.admit(&route).await.unwrap();
+    let (queued_gates, queued_route) = (gates.clone(), route.clone());
+    let queued = tokio::spawn(async move { queued_gates.admit(&queued_route).await.is_ok() });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(gates.saturation(route.route_id).queued, 1);
+    // The queue is full, so a third request is shed right away
+    assert!(gates.admit(&route).await.is_err());
+    drop(first);
+    assert!(queued.await.unwrap());
+
+    let _held = gates.admit(&route).await.unwrap();
+    assert!(
+        gates.admit(&route).await.is_err(),
+        "waited past the timeout"
+    );
+    let saturation = gates.saturation(route.route_id);
+    assert_eq!(saturation.queued_total, 2);
+    assert_eq!(saturation.shed, 2);
+    assert_eq!(saturation.in_flight, 1);
+}
+
+#[tokio::test]
+async fn routes_without_limits_are_only_counted() {
+    let gates = RouteGates::new();
+    let route = route(None);
+    let permits: Vec<_> = futures::future::join_all((0..10).map(|_| gates.admit(&route))).await;
+    assert!(permits.iter().all(Result::is_ok));
+    let saturation = gates.saturation(route.route_id);
+    assert_eq!(saturation.in_flight, 10);
+    assert_eq!(saturation.max_in_flight, None);
+}
+
+#[tokio::test]
+async fn limits_are_stored_on_the_route() {
+    let config = Config::default();
+    let cp = control_plane_with(config).await;
+    cp.create_function(create_request("orders")).await.unwrap();
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/orders".into(),
+            method: None,
+            function_name: "orders".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+
+    let err = cp
+        .put_api_route_limits(route.route_id, limits(json!({ "max_in_flight": 0 })))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+
+    let updated = cp
+        .put_api_route_limits(
+            route.route_id,
+            limits(json!({ "max_in_flight": 2, "max_queued": 4 })),
+        )
+        .await
+        .unwrap();
+    let stored = updated.limits.unwrap();
+    assert_eq!(stored.queue_timeout_ms, 1000);
+    assert_eq!(stored.retry_after_secs, 1);
+
+    let metrics = cp.api_route_metrics(route.route_id).await.unwrap();
+    assert_eq!(metrics.saturation.max_in_flight, Some(2));
+    assert_eq!(metrics.saturation.max_queued, Some(4));
+
+    cp.delete_api_route_limits(route.route_id).await.unwrap();
+    assert!(cp
+        .get_api_route(route.route_id)
+        .await
+        .unwrap()
+        .limits
+        .is_none());
+}
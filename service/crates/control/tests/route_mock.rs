@@ -0,0 +1,99 @@
+use crate::common::{control_plane, create_function};
+use lambda_models::{CreateApiRouteRequest, LambdaError, RouteMock};
+use std::collections::HashMap;
+
+fn mock() -> RouteMock {
+    serde_json::from_value(serde_json::json!({
+        "headers": { "x-source": "fixture" },
+        "body": { "items": [] },
+        "latency_ms": 50
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn mock_round_trip() {
+    let cp = control_plane().await;
+    create_function(&cp, "items").await;
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/items".into(),
+            method: Some("GET".into()),
+            function_name: "items".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+    assert!(route.mock.is_none());
+
+    let updated = cp.put_api_route_mock(route.route_id, mock()).await.unwrap();
+    let stored = updated.mock.unwrap();
+    assert!(stored.enabled);
+    assert_eq!(stored.status, 200);
+    assert_eq!(stored, mock());
+
+    // The matched route carries the mock so the gateway can skip the invoke
+    let matched = cp
+        .match_api_route("GET", "/items/1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(matched.mock, Some(mock()));
+
+    let paused = RouteMock {
+        enabled: false,
+        ..mock()
+    };
+    let updated = cp.put_api_route_mock(route.route_id, paused).await.unwrap();
+    assert!(!updated.mock.unwrap().enabled);
+
+    cp.delete_api_route_mock(route.route_id).await.unwrap();
+    assert!(cp
+        .get_api_route(route.route_id)
+        .await
+        .unwrap()
+        .mock
+        .is_none());
+}
+
+#[tokio::test]
+async fn invalid_mocks_are_rejected() {
+    let cp = control_plane().await;
+    create_function(&cp, "items").await;
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/items".into(),
+            method: None,
+            function_name: "items".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+
+    let invalid = [
+        RouteMock {
+            status: 99,
+            ..mock()
+        },
+        RouteMock {
+            latency_ms: 900_001,
+            ..mock()
+        },
+        RouteMock {
+            headers: HashMap::from([("bad header".to_string(), "x".to_string())]),
+            ..mock()
+        },
+        RouteMock {
+            headers: HashMap::from([("x-ok".to_string(), "a\r\nb".to_string())]),
+            ..mock()
+        },
+    ];
+    for m in invalid {
+        let err = cp.put_api_route_mock(route.route_id, m).await.unwrap_err();
+        assert!(matches!(err, LambdaError::InvalidRequest { .. }));
+    }
+
+    let missing = uuid::Uuid::new_v4();
+    assert!(cp.put_api_route_mock(missing, mock()).await.is_err());
+    assert!(cp.delete_api_route_mock(missing).await.is_err());
+}
@@ -0,0 +1,74 @@
+use crate::common::{control_plane_with, test_config};
+use lambda_control::doctor::probe_command;
+use lambda_control::registry::ControlPlane;
+use lambda_models::{Config, RuntimeApiSettings};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn the_runtime_api_address_changes_without_a_restart() {
+    let mut config = test_config();
+    config.docker.runtime_api_port = Some(19001);
+    let cp = control_plane_with(config).await;
+
+    let status = cp.runtime_api_status().await.unwrap();
+    assert!(!status.overridden);
+    assert_eq!(status.settings.port, Some(19001));
+    assert_eq!(status.advertised, "host.docker.internal:19001");
+
+    let status = cp
+        .set_runtime_api(Some(RuntimeApiSettings {
+            network: Some("host".into()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert!(status.overridden);
+    assert_eq!(status.advertised, "127.0.0.1:8001");
+    assert!(status.extra_hosts.is_empty());
+    assert_eq!(cp.runtime_api_status().await.unwrap(), status);
+
+    // Invalid settings and networks that cannot be inspected leave the current ones
+    for settings in [
+        RuntimeApiSettings {
+            host: Some("10.0.0.5:9001".into()),
+            ..Default::default()
+        },
+        RuntimeApiSettings {
+            port: Some(0),
+            ..Default::default()
+        },
+        RuntimeApiSettings {
+            network: Some("no-such-network".into()),
+            ..Default::default()
+        },
+    ] {
+        let err = cp.set_runtime_api(Some(settings)).await.unwrap_err();
+        assert_eq!(err.http_status(), 400);
+    }
+    assert_eq!(
+        cp.runtime_api_status().await.unwrap().advertised,
+        "127.0.0.1:8001"
+    );
+
+    let status = cp.set_runtime_api(None).await.unwrap();
+    assert!(!status.overridden);
+    assert_eq!(status.advertised, "host.docker.internal:19001");
+}
+
+#[tokio::test]
+async fn invalid_runtime_api_settings_are_refused_at_startup() {
+    let mut config = Config::default();
+    config.docker.runtime_api_host = Some(" ".into());
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    assert!(ControlPlane::new(pool, invoker, config).await.is_err());
+}
+
+#[test]
+fn the_doctor_probes_the_advertised_address() {
+    assert_eq!(
+        probe_command("10.0.0.5:19001").last().unwrap(),
+        "http://10.0.0.5:19001/runtime/healthz"
+    );
+}
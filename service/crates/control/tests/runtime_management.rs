@@ -0,0 +1,75 @@
+use lambda_control::registry::ControlPlane;
+use lambda_models::{CreateFunctionRequest, RuntimeManagementConfig, UpdateRuntimeOn};
+
+async fn control_plane() -> ControlPlane {
+    let cp = crate::common::control_plane().await;
+    cp.create_function(CreateFunctionRequest {
+        function_name: "pinned".into(),
+        runtime: "python3.11".into(),
+        handler: "app.handler".into(),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    cp
+}
+
+fn manual(runtime_version: Option<&str>) -> RuntimeManagementConfig {
+    RuntimeManagementConfig {
+        update_runtime_on: UpdateRuntimeOn::Manual,
+        runtime_version: runtime_version.map(String::from),
+    }
+}
+
+#[tokio::test]
+async fn functions_follow_the_base_image_tag_by_default() {
+    let cp = control_plane().await;
+    let runtime = cp.get_runtime_management("pinned").await.unwrap();
+    assert_eq!(runtime.update_runtime_on, UpdateRuntimeOn::Auto);
+    assert_eq!(runtime.base_image, "python:3.11-alpine");
+    assert_eq!(runtime.runtime_version, None);
+    assert_eq!(runtime.last_build_runtime_version, None);
+}
+
+#[tokio::test]
+async fn manual_mode_pins_a_digest() {
+    let cp = control_plane().await;
+    let digest = format!("sha256:{}", "0f".repeat(32));
+
+    // Nothing was built yet, so there is no digest to pin implicitly
+    let err = cp
+        .put_runtime_management("pinned", manual(None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    let err = cp
+        .put_runtime_management("pinned", manual(Some("latest")))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("image digest"), "{err}");
+
+    let pinned = cp
+        .put_runtime_management("pinned", manual(Some(&digest)))
+        .await
+        .unwrap();
+    assert_eq!(pinned.runtime_version.as_deref(), Some(digest.as_str()));
+    assert_eq!(cp.get_runtime_management("pinned").await.unwrap(), pinned);
+
+    let err = cp
+        .put_runtime_management(
+            "pinned",
+            RuntimeManagementConfig {
+                update_runtime_on: UpdateRuntimeOn::Auto,
+                runtime_version: Some(digest),
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    let auto = cp
+        .put_runtime_management("pinned", RuntimeManagementConfig::default())
+        .await
+        .unwrap();
+    assert_eq!(auto.update_runtime_on, UpdateRuntimeOn::Auto);
+    assert_eq!(auto.runtime_version, None);
+}
@@ -0,0 +1,219 @@
+use crate::common::{create_request, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_control::s3_notifications::{
+    decode_key, dispatch_notification, is_valid_bucket_name, key_matches, parse_notification,
+};
+use lambda_models::{
+    bucket_name_from_arn, CreateEventSourceMappingRequest, CreateQueueOptions,
+    FunctionResponseType, IdentityConfig, S3KeyFilter, UpdateEventSourceMappingRequest,
+};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// A notification as a MinIO webhook target posts it
+fn minio_notification(bucket: &str, key: &str) -> serde_json::Value {
+    json!({
+        "EventName": "s3:ObjectCreated:Put",
+        "Key": format!("{bucket}/{key}"),
+        "Records": [{
+            "eventVersion": "2.0",
+            "eventSource": "minio:s3",
+            "awsRegion": "",
+            "eventTime": "2026-10-17T09:00:00.000Z",
+            "eventName": "s3:ObjectCreated:Put",
+            "userIdentity": { "principalId": "minioadmin" },
+            "s3": {
+                "s3SchemaVersion": "1.0",
+                "configurationId": "Config",
+                "bucket": {
+                    "name": bucket,
+                    "ownerIdentity": { "principalId": "minioadmin" },
+                    "arn": format!("arn:aws:s3:::{bucket}")
+                },
+                "object": {
+                    "key": key,
+                    "size": 42,
+                    "eTag": "0123456789abcdef",
+                    "contentType": "image/png",
+                    "sequencer": "17A0B0C0D0E0F000"
+                }
+            }
+        }]
+    })
+}
+
+fn bucket_arn(bucket: &str) -> String {
+    IdentityConfig::default().bucket_arn(bucket)
+}
+
+#[test]
+fn bucket_arns_and_names() {
+    assert_eq!(bucket_arn("photos"), "arn:aws:s3:::photos");
+    assert_eq!(bucket_name_from_arn("arn:aws:s3:::photos"), Some("photos"));
+    assert_eq!(bucket_name_from_arn("arn:aws:s3:::photos/raw"), None);
+    assert_eq!(
+        bucket_name_from_arn("arn:aws-us-gov:s3:::photos"),
+        Some("photos")
+    );
+    assert_eq!(
+        bucket_name_from_arn("arn:aws:sqs:local:000000000000:q"),
+        None
+    );
+
+    assert!(is_valid_bucket_name("my.photos-2026"));
+    assert!(!is_valid_bucket_name("Photos"));
+    assert!(!is_valid_bucket_name("ab"));
+    assert!(!is_valid_bucket_name("-photos"));
+}
+
+#[test]
+fn keys_are_decoded_before_filtering() {
+    assert_eq!(
+        decode_key("raw/summer+trip%2C+day+1.png"),
+        "raw/summer trip, day 1.png"
+    );
+
+    let filter = S3KeyFilter {
+        prefix: Some("raw/".into()),
+        suffix: Some(".png".into()),
+    };
+    assert!(key_matches(Some(&filter), "raw/a.png"));
+    assert!(!key_matches(Some(&filter), "thumbs/a.png"));
+    assert!(!key_matches(Some(&filter), "raw/a.jpg"));
+    assert!(key_matches(None, "anything"));
+}
+
+#[test]
+fn minio_records_become_lambda_s3_records() {
+    let notices = parse_notification(
+        &IdentityConfig::default(),
+        &minio_notification("photos", "raw/cat+1.png"),
+    )
+    .unwrap();
+    assert_eq!(notices.len(), 1);
+    let notice = &notices[0];
+    assert_eq!(notice.bucket, "photos");
+    assert_eq!(notice.key, "raw/cat 1.png");
+    assert_eq!(notice.record["eventSource"], "aws:s3");
+    assert_eq!(notice.record["eventName"], "ObjectCreated:Put");
+    assert_eq!(notice.record["awsRegion"], "local");
+    // The key stays encoded in the record, as S3 delivers it
+    assert_eq!(notice.record["s3"]["object"]["key"], "raw/cat+1.png");
+
+    assert_eq!(
+        parse_notification(&IdentityConfig::default(), &json!({ "EventName": "x" }))
+            .unwrap_err()
+            .http_status(),
+        400
+    );
+}
+
+#[tokio::test]
+async fn bucket_mappings_filter_notifications_by_key() {
+    let config = test_config();
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    let cp = Arc::new(ControlPlane::new(pool, invoker, config).await.unwrap());
+    cp.create_function(create_request("thumbnailer"))
+        .await
+        .unwrap();
+
+    let request = |arn: String, filter: Option<S3KeyFilter>| CreateEventSourceMappingRequest {
+        event_source_arn: arn,
+        function_name: "thumbnailer".into(),
+        batch_size: None,
+        enabled: None,
+        function_response_types: vec![],
+        s3_key_filter: filter,
+    };
+    let png_filter = S3KeyFilter {
+        prefix: Some("raw/".into()),
+        suffix: Some(".png".into()),
+    };
+
+    let mapping = cp
+        .create_event_source_mapping(request(bucket_arn("photos"), Some(png_filter.clone())))
+        .await
+        .unwrap();
+    assert_eq!(mapping.event_source_arn, "arn:aws:s3:::photos");
+    assert_eq!(mapping.batch_size, 1);
+    let fetched = cp.get_event_source_mapping(mapping.uuid).await.unwrap();
+    assert_eq!(fetched.s3_key_filter, Some(png_filter));
+
+    // Bucket sources take no batching options, queue sources no key filter
+    let mut batched = request(bucket_arn("photos"), None);
+    batched.batch_size = Some(5);
+    assert_eq!(
+        cp.create_event_source_mapping(batched)
+            .await
+            .unwrap_err()
+            .http_status(),
+        400
+    );
+    let mut reporting = request(bucket_arn("photos"), None);
+    reporting.function_response_types = vec![FunctionResponseType::ReportBatchItemFailures];
+    assert!(cp.create_event_source_mapping(reporting).await.is_err());
+    cp.sqs()
+        .create_queue("uploads", CreateQueueOptions::default())
+        .await
+        .unwrap();
+    assert!(cp
+        .create_event_source_mapping(request(
+            IdentityConfig::default().queue_arn("uploads"),
+            Some(S3KeyFilter::default())
+        ))
+        .await
+        .is_err());
+
+    assert_eq!(
+        dispatch_notification(&cp, &minio_notification("photos", "raw/cat.png"))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        dispatch_notification(&cp, &minio_notification("photos", "raw/cat.jpg"))
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        dispatch_notification(&cp, &minio_notification("videos", "raw/cat.png"))
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Clearing the filter makes every key match; disabling stops delivery
+    cp.update_event_source_mapping(
+        mapping.uuid,
+        UpdateEventSourceMappingRequest {
+            s3_key_filter: Some(S3KeyFilter::default()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        dispatch_notification(&cp, &minio_notification("photos", "docs/a.pdf"))
+            .await
+            .unwrap(),
+        1
+    );
+    cp.update_event_source_mapping(
+        mapping.uuid,
+        UpdateEventSourceMappingRequest {
+            enabled: Some(false),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        dispatch_notification(&cp, &minio_notification("photos", "docs/a.pdf"))
+            .await
+            .unwrap(),
+        0
+    );
+}
@@ -0,0 +1,37 @@
+use crate::common::{control_plane_with, create_function, test_config};
+use lambda_models::FunctionSandbox;
+
+#[tokio::test]
+async fn functions_run_under_the_default_profile_until_they_choose() {
+    let mut config = test_config();
+    config.sandbox.default_profile = "strict".into();
+    let cp = control_plane_with(config).await;
+    create_function(&cp, "orders").await;
+
+    assert_eq!(cp.get_sandbox("orders").await.unwrap().profile, "strict");
+    let err = cp
+        .put_sandbox(
+            "orders",
+            FunctionSandbox {
+                profile: "paranoid".into(),
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    assert!(err.to_string().contains("default, strict"));
+    assert_eq!(cp.get_sandbox("orders").await.unwrap().profile, "strict");
+    cp.delete_sandbox("orders").await.unwrap();
+
+    assert_eq!(
+        cp.get_sandbox("missing").await.unwrap_err().http_status(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn unknown_default_profiles_fail_startup() {
+    let mut config = test_config();
+    config.sandbox.default_profile = "paranoid".into();
+    assert!(lambda_invoker::Invoker::new(config).await.is_err());
+}
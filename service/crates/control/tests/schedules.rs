@@ -0,0 +1,253 @@
+use crate::common::{create_function, temp_dir, test_config};
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use lambda_control::registry::ControlPlane;
+use lambda_control::schedules::ScheduleExpression;
+use lambda_models::ScheduleRequest;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+async fn control_plane_on(pool: SqlitePool) -> ControlPlane {
+    let config = test_config();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    ControlPlane::new(pool, invoker, config).await.unwrap()
+}
+
+async fn control_plane() -> ControlPlane {
+    control_plane_on(SqlitePool::connect(":memory:").await.unwrap()).await
+}
+
+fn schedule(expression: &str) -> ScheduleRequest {
+    ScheduleRequest {
+        schedule_expression: expression.into(),
+        payload: serde_json::json!({ "task": "report" }),
+        qualifier: None,
+        description: None,
+        enabled: true,
+    }
+}
+
+fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+        .unwrap()
+}
+
+fn next(expression: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    ScheduleExpression::parse(expression)
+        .unwrap()
+        .next_run(after, after)
+}
+
+#[test]
+fn cron_expressions_match_like_eventbridge() {
+    // 2026-10-16 is a Friday
+    let friday_evening = Utc.with_ymd_and_hms(2026, 10, 16, 18, 30, 15).unwrap();
+    assert_eq!(
+        next("cron(0 9 ? * MON-FRI *)", friday_evening),
+        Some(at(2026, 10, 19, 9, 0))
+    );
+    assert_eq!(
+        next("cron(*/15 * * * ? *)", friday_evening),
+        Some(at(2026, 10, 16, 18, 45))
+    );
+    assert_eq!(
+        next("cron(0 12 1 JAN,jul ? *)", friday_evening),
+        Some(at(2027, 1, 1, 12, 0))
+    );
+    // Day 31 skips the months without one
+    assert_eq!(
+        next("cron(0 0 31 * ? *)", friday_evening),
+        Some(at(2026, 10, 31, 0, 0))
+    );
+    assert_eq!(
+        next("cron(0 0 31 * ? *)", at(2026, 10, 31, 0, 0)),
+        Some(at(2026, 12, 31, 0, 0))
+    );
+    // 1 is Sunday
+    assert_eq!(
+        next("cron(5/20 8-9 ? * 1 2027)", friday_evening),
+        Some(at(2027, 1, 3, 8, 5))
+    );
+    assert_eq!(next("cron(0 0 1 1 ? 2020-2025)", friday_evening), None);
+}
+
+#[test]
+fn rates_run_every_interval_and_skip_missed_runs() {
+    let created = at(2026, 10, 16, 12, 0);
+    let rate = ScheduleExpression::parse("rate(5 minutes)").unwrap();
+    assert_eq!(
+        rate.next_run(created, created),
+        Some(at(2026, 10, 16, 12, 5))
+    );
+    // Down for an hour: the next run keeps the cadence instead of catching up
+    assert_eq!(
+        rate.next_run(created, at(2026, 10, 16, 13, 2)),
+        Some(at(2026, 10, 16, 13, 5))
+    );
+    assert_eq!(
+        ScheduleExpression::parse("rate(1 day)").unwrap(),
+        ScheduleExpression::Rate(ChronoDuration::days(1))
+    );
+}
+
+#[test]
+fn malformed_expressions_are_rejected() {
+    for (expression, expected) in [
+        ("every 5 minutes", "must be cron(...) or rate(...)"),
+        ("rate(0 minutes)", "positive value"),
+        ("rate(5 seconds)", "minutes, hours or days"),
+        ("rate(400 days)", "at most a year"),
+        ("cron(0 9 * * *)", "six fields"),
+        (
+            "cron(0 9 * * MON *)",
+            "exactly one of day-of-month and day-of-week",
+        ),
+        (
+            "cron(0 9 ? * ? *)",
+            "exactly one of day-of-month and day-of-week",
+        ),
+        ("cron(60 9 * * ? *)", "60 is not between 0 and 59"),
+        ("cron(0 9 L * ? *)", "L, W and # are not supported"),
+        ("cron(0 9 ? * 6#3 *)", "L, W and # are not supported"),
+        ("cron(0 9 ? * FRI-MON *)", "runs backwards"),
+        ("cron(0/0 9 * * ? *)", "must be a positive number"),
+    ] {
+        let err = ScheduleExpression::parse(expression).unwrap_err();
+        assert_eq!(err.http_status(), 400);
+        assert!(err.to_string().contains(expected), "{expression}: {err}");
+    }
+}
+
+#[tokio::test]
+async fn schedules_are_managed_per_function() {
+    let cp = control_plane().await;
+    create_function(&cp, "reports").await;
+
+    let err = cp
+        .create_schedule("missing", schedule("rate(1 hour)"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+    let err = cp
+        .create_schedule("reports", schedule("rate(1 week)"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+
+    let hourly = cp
+        .create_schedule("reports", schedule("rate(1 hour)"))
+        .await
+        .unwrap();
+    assert_eq!(hourly.function_name, "reports");
+    assert_eq!(
+        hourly.next_run_at,
+        Some(hourly.created_at + ChronoDuration::hours(1))
+    );
+    let nightly = cp
+        .create_schedule("reports", schedule("cron(0 2 * * ? *)"))
+        .await
+        .unwrap();
+    assert_eq!(cp.list_schedules("reports").await.unwrap().len(), 2);
+
+    let disabled = cp
+        .update_schedule(
+            "reports",
+            &nightly.schedule_id,
+            ScheduleRequest {
+                enabled: false,
+                ..schedule("cron(0 3 * * ? *)")
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(disabled.schedule_expression, "cron(0 3 * * ? *)");
+    assert_eq!(disabled.next_run_at, None);
+    assert_eq!(
+        cp.get_schedule("reports", &nightly.schedule_id)
+            .await
+            .unwrap(),
+        disabled
+    );
+
+    cp.delete_schedule("reports", &hourly.schedule_id)
+        .await
+        .unwrap();
+    let err = cp
+        .get_schedule("reports", &hourly.schedule_id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    // The remaining schedule goes with its function
+    cp.delete_function("reports").await.unwrap();
+    create_function(&cp, "reports").await;
+    assert!(cp.list_schedules("reports").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn due_schedules_queue_one_event_each() {
+    let cp = control_plane().await;
+    create_function(&cp, "reports").await;
+    let every_minute = cp
+        .create_schedule("reports", schedule("rate(1 minute)"))
+        .await
+        .unwrap();
+    cp.create_schedule(
+        "reports",
+        ScheduleRequest {
+            enabled: false,
+            ..schedule("rate(1 minute)")
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(cp
+        .run_due_schedules(every_minute.created_at)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Ten minutes late, as after a restart: one run, then back on the cadence
+    let late = every_minute.created_at + ChronoDuration::seconds(630);
+    let fired = cp.run_due_schedules(late).await.unwrap();
+    assert_eq!(fired.len(), 1);
+    let ran = &fired[0];
+    assert_eq!(ran.schedule_id, every_minute.schedule_id);
+    assert_eq!(ran.last_run_at, Some(late));
+    assert_eq!(
+        ran.next_run_at,
+        Some(every_minute.created_at + ChronoDuration::minutes(11))
+    );
+    let job = cp
+        .get_job(ran.last_job_id.as_deref().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(job.function_name, "reports");
+
+    assert!(cp.run_due_schedules(late).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn schedules_survive_a_restart() {
+    let dir = temp_dir();
+    let url = format!(
+        "sqlite://{}?mode=rwc",
+        dir.path().join("lambda.db").display()
+    );
+    let created = {
+        let cp = control_plane_on(SqlitePool::connect(&url).await.unwrap()).await;
+        create_function(&cp, "reports").await;
+        cp.create_schedule("reports", schedule("cron(30 6 ? * MON *)"))
+            .await
+            .unwrap()
+    };
+
+    let cp = control_plane_on(SqlitePool::connect(&url).await.unwrap()).await;
+    let reloaded = cp.list_schedules("reports").await.unwrap();
+    assert_eq!(reloaded, vec![created.clone()]);
+    let fired = cp
+        .run_due_schedules(created.next_run_at.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(fired.len(), 1);
+}
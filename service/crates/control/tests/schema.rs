@@ -0,0 +1,76 @@
+use crate::common::{control_plane, test_config};
+use lambda_control::migrations::{run_migrations, schema_status, MIGRATIONS, SCHEMA_VERSION};
+use lambda_control::registry::ControlPlane;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[test]
+fn migrations_are_numbered_in_order() {
+    for (i, (version, _)) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(*version as usize, i + 1);
+    }
+    assert_eq!(SCHEMA_VERSION as usize, MIGRATIONS.len());
+}
+
+#[tokio::test]
+async fn migrations_record_the_schema_version() {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let status = schema_status(&pool).await.unwrap();
+    assert_eq!(status.current_version, 0);
+    assert_eq!(status.latest_version, SCHEMA_VERSION);
+    assert_eq!(status.pending.len(), MIGRATIONS.len());
+    assert_eq!(status.pending[0].version, 1);
+    assert_eq!(status.pending[0].name, "Initial schema");
+
+    run_migrations(&pool).await.unwrap();
+    let status = schema_status(&pool).await.unwrap();
+    assert_eq!(status.current_version, SCHEMA_VERSION);
+    assert!(status.pending.is_empty());
+
+    // Running them again changes nothing
+    run_migrations(&pool).await.unwrap();
+    assert_eq!(
+        schema_status(&pool).await.unwrap().current_version,
+        SCHEMA_VERSION
+    );
+
+    // An older schema lists what is left to run
+    sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION - 2))
+        .execute(&pool)
+        .await
+        .unwrap();
+    let pending: Vec<u32> = schema_status(&pool)
+        .await
+        .unwrap()
+        .pending
+        .iter()
+        .map(|m| m.version)
+        .collect();
+    assert_eq!(pending, [SCHEMA_VERSION - 1, SCHEMA_VERSION]);
+}
+
+#[tokio::test]
+async fn newer_schemas_are_refused() {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = run_migrations(&pool).await.unwrap_err();
+    assert!(err.to_string().contains("newer than version"), "{err}");
+    assert!(schema_status(&pool).await.unwrap().pending.is_empty());
+
+    let config = test_config();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    assert!(ControlPlane::new(pool, invoker, config).await.is_err());
+}
+
+#[tokio::test]
+async fn control_plane_reports_the_schema() {
+    let cp = control_plane().await;
+    let status = cp.schema_status().await.unwrap();
+    assert_eq!(status.current_version, SCHEMA_VERSION);
+    assert!(status.pending.is_empty());
+}
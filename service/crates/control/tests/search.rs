@@ -0,0 +1,128 @@
+use crate::common::{control_plane_and_pool, create_request};
+use chrono::{Duration as ChronoDuration, Utc};
+use lambda_control::search::{like_pattern, match_rank, rank_hits};
+use lambda_models::{Config, CreateApiRouteRequest, CreateFunctionRequest, SearchHit};
+
+fn secret(name: &str) -> SearchHit {
+    SearchHit::Secret {
+        name: name.to_string(),
+    }
+}
+
+fn function(name: &str, description: Option<&str>) -> SearchHit {
+    SearchHit::Function {
+        function_name: name.to_string(),
+        runtime: "nodejs22.x".to_string(),
+        description: description.map(str::to_string),
+    }
+}
+
+#[test]
+fn like_wildcards_in_queries_are_literal() {
+    assert_eq!(like_pattern("orders"), "%orders%");
+    assert_eq!(like_pattern("50%_off\\"), "%50\\%\\_off\\\\%");
+}
+
+#[test]
+fn ranks_exact_then_prefix_then_substring() {
+    assert_eq!(match_rank("Orders", "orders"), Some(0));
+    assert_eq!(match_rank("orders-api", "orders"), Some(1));
+    assert_eq!(match_rank("get-orders", "orders"), Some(2));
+    assert_eq!(match_rank("users", "orders"), None);
+
+    let (hits, truncated) = rank_hits(
+        vec![
+            function("get-orders", None),
+            function("billing", Some("Charges orders")),
+            secret("ORDERS_KEY"),
+            function("orders", None),
+        ],
+        "orders",
+        3,
+    );
+    assert!(truncated);
+    assert_eq!(
+        hits,
+        vec![
+            function("orders", None),
+            secret("ORDERS_KEY"),
+            function("get-orders", None)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn searches_functions_routes_secrets_and_recent_executions() {
+    let config = Config::default();
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    let mut created = Vec::new();
+    for (name, description) in [("orders", None), ("billing", Some("Bills orders nightly"))] {
+        created.push(
+            cp.create_function(CreateFunctionRequest {
+                description: description.map(str::to_string),
+                ..create_request(name)
+            })
+            .await
+            .unwrap(),
+        );
+    }
+    cp.create_api_route(CreateApiRouteRequest {
+        path: "/v1/orders".into(),
+        method: Some("GET".into()),
+        function_name: "orders".into(),
+        canary: None,
+    })
+    .await
+    .unwrap();
+    cp.create_secret("ORDERS_DB_PASSWORD", "hunter2")
+        .await
+        .unwrap();
+
+    for (id, days_ago) in [("orders-req-1", 0), ("orders-req-old", 30)] {
+        sqlx::query(
+            "INSERT INTO executions (execution_id, function_id, function_version, aws_request_id, start_time, status)
+             VALUES (?, ?, '1', ?, ?, 'Success')",
+        )
+        .bind(id)
+        .bind(created[0].function_id)
+        .bind(id)
+        .bind(Utc::now() - ChronoDuration::days(days_ago))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let response = cp.search("orders", 20).await.unwrap();
+    assert!(!response.truncated);
+    assert_eq!(response.results.len(), 5);
+    assert!(matches!(
+        &response.results[0],
+        SearchHit::Function { function_name, .. } if function_name == "orders"
+    ));
+    assert!(response
+        .results
+        .iter()
+        .any(|hit| matches!(hit, SearchHit::Route { path, .. } if path == "/v1/orders")));
+    assert!(response
+        .results
+        .iter()
+        .any(|hit| matches!(hit, SearchHit::Secret { name } if name == "ORDERS_DB_PASSWORD")));
+    assert!(response.results.iter().any(|hit| matches!(
+        hit,
+        SearchHit::Execution { request_id, function_name, .. }
+            if request_id == "orders-req-1" && function_name == "orders"
+    )));
+    assert!(matches!(
+        response.results.last(),
+        Some(SearchHit::Function { function_name, .. }) if function_name == "billing"
+    ));
+    // Secret values are never searched
+    assert!(cp.search("hunter2", 20).await.unwrap().results.is_empty());
+
+    let response = cp.search("orders", 2).await.unwrap();
+    assert!(response.truncated);
+    assert_eq!(response.results.len(), 2);
+
+    assert_eq!(cp.search("  ", 20).await.unwrap_err().http_status(), 400);
+}
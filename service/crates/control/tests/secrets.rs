@@ -36,6 +36,13 @@ async fn resolve_env_vars_resolves_secrets() {
         state: lambda_models::FunctionState::Active,
         state_reason: None,
         state_reason_code: None,
+        last_update_status: lambda_models::LastUpdateStatus::Successful,
+        last_update_status_reason: None,
+        last_update_status_reason_code: None,
+        metadata: None,
+        function_arn: None,
+        package_type: lambda_models::PackageType::Zip,
+        image_uri: None,
     };
 
     let resolved = cp.resolve_env_vars(&f).await.unwrap();
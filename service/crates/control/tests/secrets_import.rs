@@ -0,0 +1,178 @@
+use crate::common::control_plane;
+use lambda_control::secrets::{parse_dotenv, parse_secrets_file};
+use lambda_models::{
+    ExportSecretsRequest, ImportSecretsRequest, SecretConflictMode, SecretsFileFormat,
+    EXPORT_VALUES_CONFIRMATION,
+};
+use std::collections::BTreeMap;
+
+fn import(content: &str, on_conflict: SecretConflictMode) -> ImportSecretsRequest {
+    ImportSecretsRequest {
+        content: content.into(),
+        format: None,
+        on_conflict,
+    }
+}
+
+fn secrets(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn dotenv_files_are_read_like_dotenv_does() {
+    let content = r#"
+# Database
+export DB_HOST=db.internal
+DB_PASS="p@ss \"quoted\"\nsecond line"   # trailing comment
+API_KEY = abc123 # unquoted comments end the value
+RAW='no $expansion or \n escapes'
+CERT="-----BEGIN-----
+MIIB
+-----END-----"
+EMPTY=
+"#;
+    assert_eq!(
+        parse_dotenv(content).unwrap(),
+        secrets(&[
+            ("API_KEY", "abc123"),
+            ("CERT", "-----BEGIN-----\nMIIB\n-----END-----"),
+            ("DB_HOST", "db.internal"),
+            ("DB_PASS", "p@ss \"quoted\"\nsecond line"),
+            ("EMPTY", ""),
+            ("RAW", "no $expansion or \\n escapes"),
+        ])
+    );
+}
+
+#[test]
+fn malformed_dotenv_lines_are_reported_by_number() {
+    for (content, expected) in [
+        ("A=1\nnot a pair\n", "Line 2: expected NAME=value"),
+        ("A=1\nA=2\n", "A is set twice, on lines 1 and 2"),
+        (
+            "A=\"open\nB=2\n",
+            "Line 1: the value of A has no closing quote",
+        ),
+        ("A='x' y\n", "Line 1: unexpected y after the value of A"),
+        ("1ST=x\n", "Secret name '1ST'"),
+    ] {
+        let err = parse_secrets_file(content, None).unwrap_err();
+        assert_eq!(err.http_status(), 400);
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn json_files_are_detected_or_chosen() {
+    assert_eq!(
+        parse_secrets_file(r#" { "TOKEN": "t0k", "REGION": "eu" }"#, None).unwrap(),
+        secrets(&[("REGION", "eu"), ("TOKEN", "t0k")])
+    );
+    let err = parse_secrets_file(r#"{ "PORT": 5432 }"#, None).unwrap_err();
+    assert!(err.to_string().contains("PORT must be a string"), "{err}");
+    // A dotenv value may start with a brace when the format says so
+    assert_eq!(
+        parse_secrets_file("{A}=1", Some(SecretsFileFormat::Dotenv))
+            .unwrap_err()
+            .http_status(),
+        400
+    );
+}
+
+#[tokio::test]
+async fn imports_resolve_conflicts_as_asked() {
+    let cp = control_plane().await;
+    cp.create_secret("DB_PASS", "old").await.unwrap();
+    assert_eq!(
+        cp.get_secret_value("DB_PASS").await.unwrap().unwrap(),
+        "old"
+    );
+
+    let file = "DB_PASS=new\nAPI_KEY=k1\n";
+    let err = cp
+        .import_secrets(import(file, SecretConflictMode::Fail))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 409);
+    assert!(err.to_string().contains("DB_PASS"), "{err}");
+    assert_eq!(cp.get_secret_value("API_KEY").await.unwrap(), None);
+
+    let skipped = cp
+        .import_secrets(import(file, SecretConflictMode::Skip))
+        .await
+        .unwrap();
+    assert_eq!(skipped.created, vec!["API_KEY"]);
+    assert_eq!(skipped.skipped, vec!["DB_PASS"]);
+    assert_eq!(
+        cp.get_secret_value("DB_PASS").await.unwrap().unwrap(),
+        "old"
+    );
+
+    let overwritten = cp
+        .import_secrets(import(file, SecretConflictMode::Overwrite))
+        .await
+        .unwrap();
+    assert!(overwritten.created.is_empty());
+    assert_eq!(overwritten.overwritten, vec!["API_KEY", "DB_PASS"]);
+    assert_eq!(
+        cp.get_secret_value("DB_PASS").await.unwrap().unwrap(),
+        "new"
+    );
+}
+
+#[tokio::test]
+async fn exporting_values_needs_confirmation_and_is_audited() {
+    let cp = control_plane().await;
+    cp.import_secrets(import(
+        r#"{ "DB_PASS": "line1\nline2 \"q\"", "API_KEY": "k1" }"#,
+        SecretConflictMode::Fail,
+    ))
+    .await
+    .unwrap();
+
+    let names = cp
+        .export_secrets(ExportSecretsRequest::default(), None)
+        .await
+        .unwrap();
+    assert!(names.secrets.iter().all(|s| s.value.is_none()));
+    assert_eq!(names.to_dotenv(), "API_KEY=\nDB_PASS=\n");
+
+    for confirm in [None, Some("yes".to_string())] {
+        let err = cp
+            .export_secrets(
+                ExportSecretsRequest {
+                    include_values: true,
+                    confirm,
+                },
+                Some("alice"),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 400);
+    }
+    assert!(cp.list_secret_exports(10).await.unwrap().is_empty());
+
+    let exported = cp
+        .export_secrets(
+            ExportSecretsRequest {
+                include_values: true,
+                confirm: Some(EXPORT_VALUES_CONFIRMATION.into()),
+            },
+            Some("alice"),
+        )
+        .await
+        .unwrap();
+    // The dotenv export reads back as the same secrets
+    assert_eq!(
+        parse_dotenv(&exported.to_dotenv()).unwrap(),
+        secrets(&[("API_KEY", "k1"), ("DB_PASS", "line1\nline2 \"q\"")])
+    );
+
+    let audit = cp.list_secret_exports(10).await.unwrap();
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0].actor.as_deref(), Some("alice"));
+    assert_eq!(audit[0].names, vec!["API_KEY", "DB_PASS"]);
+}
@@ -0,0 +1,366 @@
+use crate::common::{control_plane, create_request};
+use lambda_control::event_sources::{batch_outcome, reported_failures, sqs_event};
+use lambda_control::sqs::{md5_hex, QueueService};
+use lambda_models::{
+    queue_name_from_arn, CreateEventSourceMappingRequest, CreateQueueOptions, EventSourceMapping,
+    FunctionError, FunctionResponseType, IdentityConfig, InvokeResponse, LambdaError,
+    ReceivedMessage, RedrivePolicy, UpdateEventSourceMappingRequest,
+};
+use serde_json::json;
+use std::time::Duration;
+
+fn queue_arn(queue_name: &str) -> String {
+    IdentityConfig::default().queue_arn(queue_name)
+}
+
+#[test]
+fn queue_arns_round_trip() {
+    let arn = queue_arn("orders");
+    assert_eq!(arn, "arn:aws:sqs:local:000000000000:orders");
+    assert_eq!(queue_name_from_arn(&arn), Some("orders"));
+    assert_eq!(
+        queue_name_from_arn("arn:aws:sqs:us-east-1:123:jobs"),
+        Some("jobs")
+    );
+    assert_eq!(
+        queue_name_from_arn("arn:aws-cn:sqs:cn-north-1:123456789012:jobs"),
+        Some("jobs")
+    );
+    assert_eq!(
+        queue_name_from_arn("arn:aws:sns:local:000000000000:t"),
+        None
+    );
+    assert_eq!(md5_hex("hello"), "5d41402abc4b2a76b9719d911017c592");
+}
+
+#[tokio::test]
+async fn messages_stay_hidden_until_deleted_or_timed_out() {
+    let cp = control_plane().await;
+    let sqs = cp.sqs();
+    sqs.create_queue("jobs", CreateQueueOptions::default())
+        .await
+        .unwrap();
+
+    let (id, md5) = sqs.send_message("jobs", "first", None).await.unwrap();
+    assert_eq!(md5, md5_hex("first"));
+    sqs.send_message("jobs", "second", None).await.unwrap();
+
+    let received = sqs
+        .receive_messages("jobs", 1, Some(0), Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].message_id, id);
+    assert_eq!(received[0].receive_count, 1);
+
+    // A zero visibility timeout makes the message visible again straight away
+    let again = sqs
+        .receive_messages("jobs", 10, Some(30), Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(again.len(), 2);
+    assert_eq!(again[0].body, "first");
+    assert_eq!(again[0].receive_count, 2);
+    assert!(sqs
+        .receive_messages("jobs", 10, None, Duration::ZERO)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let counts = sqs.queue_counts("jobs").await.unwrap();
+    assert_eq!((counts.visible, counts.in_flight), (0, 2));
+
+    // The handle of the earlier receive no longer deletes the message
+    sqs.delete_message("jobs", &received[0].receipt_handle)
+        .await
+        .unwrap();
+    assert_eq!(sqs.queue_counts("jobs").await.unwrap().in_flight, 2);
+
+    sqs.delete_message("jobs", &again[0].receipt_handle)
+        .await
+        .unwrap();
+    sqs.change_message_visibility("jobs", &again[1].receipt_handle, 0)
+        .await
+        .unwrap();
+    let last = sqs
+        .receive_messages("jobs", 10, None, Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(last.len(), 1);
+    assert_eq!(last[0].body, "second");
+
+    let err = sqs.send_message("missing", "x", None).await.unwrap_err();
+    assert!(matches!(err, LambdaError::QueueNotFound { .. }));
+}
+
+#[tokio::test]
+async fn long_poll_wakes_on_send() {
+    let cp = control_plane().await;
+    let sqs = cp.sqs();
+    sqs.create_queue("events", CreateQueueOptions::default())
+        .await
+        .unwrap();
+
+    let receiver: QueueService = sqs.clone();
+    let waiting = tokio::spawn(async move {
+        receiver
+            .receive_messages("events", 1, None, Duration::from_secs(10))
+            .await
+            .unwrap()
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    sqs.send_message("events", "ping", None).await.unwrap();
+
+    let received = tokio::time::timeout(Duration::from_secs(2), waiting)
+        .await
+        .expect("long poll should return once a message arrives")
+        .unwrap();
+    assert_eq!(received[0].body, "ping");
+}
+
+#[tokio::test]
+async fn unprocessed_messages_move_to_the_dead_letter_queue() {
+    let cp = control_plane().await;
+    let sqs = cp.sqs();
+    sqs.create_queue("work-dlq", CreateQueueOptions::default())
+        .await
+        .unwrap();
+    sqs.create_queue(
+        "work",
+        CreateQueueOptions {
+            redrive_policy: Some(RedrivePolicy {
+                dead_letter_target_arn: queue_arn("work-dlq"),
+                max_receive_count: 2,
+            }),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    sqs.send_message("work", "poison", None).await.unwrap();
+
+    for _ in 0..2 {
+        let received = sqs
+            .receive_messages("work", 1, Some(0), Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(received.len(), 1);
+    }
+    assert!(sqs
+        .receive_messages("work", 1, Some(0), Duration::ZERO)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let dead = sqs
+        .receive_messages("work-dlq", 1, None, Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(dead[0].body, "poison");
+    assert_eq!(dead[0].receive_count, 1);
+
+    // A queue cannot dead-letter into a queue that does not exist
+    let err = sqs
+        .create_queue(
+            "other",
+            CreateQueueOptions {
+                redrive_policy: Some(RedrivePolicy {
+                    dead_letter_target_arn: queue_arn("nowhere"),
+                    max_receive_count: 3,
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+}
+
+#[test]
+fn redrive_policy_accepts_string_counts() {
+    let policy: RedrivePolicy = serde_json::from_str(
+        r#"{"deadLetterTargetArn":"arn:aws:sqs:local:000000000000:dlq","maxReceiveCount":"5"}"#,
+    )
+    .unwrap();
+    assert_eq!(policy.max_receive_count, 5);
+}
+
+fn message(id: &str) -> ReceivedMessage {
+    ReceivedMessage {
+        message_id: id.to_string(),
+        receipt_handle: format!("handle-{id}"),
+        body: format!("body {id}"),
+        md5_of_body: md5_hex(&format!("body {id}")),
+        sent_at: chrono::Utc::now(),
+        receive_count: 1,
+        first_received_at: chrono::Utc::now(),
+    }
+}
+
+fn mapping(report_failures: bool) -> EventSourceMapping {
+    EventSourceMapping {
+        uuid: uuid::Uuid::new_v4(),
+        event_source_arn: queue_arn("jobs"),
+        function_name: "worker".into(),
+        batch_size: 10,
+        enabled: true,
+        function_response_types: if report_failures {
+            vec![FunctionResponseType::ReportBatchItemFailures]
+        } else {
+            vec![]
+        },
+        last_modified: chrono::Utc::now(),
+        last_processing_result: None,
+        s3_key_filter: None,
+    }
+}
+
+fn ok(payload: serde_json::Value) -> Result<InvokeResponse, LambdaError> {
+    Ok(InvokeResponse {
+        status_code: 200,
+        payload: Some(payload),
+        executed_version: None,
+        function_error: None,
+        log_result: None,
+        headers: Default::default(),
+        duration_ms: None,
+    })
+}
+
+#[test]
+fn events_are_shaped_like_lambda_sqs_events() {
+    let event = serde_json::to_value(sqs_event(
+        &IdentityConfig::default(),
+        "jobs",
+        &[message("a")],
+    ))
+    .unwrap();
+    let record = &event["Records"][0];
+    assert_eq!(record["messageId"], "a");
+    assert_eq!(record["receiptHandle"], "handle-a");
+    assert_eq!(record["eventSource"], "aws:sqs");
+    assert_eq!(record["eventSourceARN"], queue_arn("jobs"));
+    assert_eq!(record["attributes"]["ApproximateReceiveCount"], "1");
+}
+
+#[test]
+fn partial_batch_failures_keep_only_the_failed_messages() {
+    let messages = [message("a"), message("b"), message("c")];
+    let ids: Vec<String> = messages.iter().map(|m| m.message_id.clone()).collect();
+
+    let reply = json!({ "batchItemFailures": [{ "itemIdentifier": "b" }] });
+    assert_eq!(reported_failures(Some(&reply), &ids).unwrap().len(), 1);
+    assert!(
+        reported_failures(Some(&json!({ "batchItemFailures": [] })), &ids)
+            .unwrap()
+            .is_empty()
+    );
+    // Unknown identifiers make the whole response invalid
+    let unknown = json!({ "batchItemFailures": [{ "itemIdentifier": "z" }] });
+    assert!(reported_failures(Some(&unknown), &ids).is_none());
+
+    let outcome = batch_outcome(&mapping(true), &messages, &ok(reply.clone()));
+    assert_eq!(outcome.delete, vec!["handle-a", "handle-c"]);
+    assert!(!outcome.back_off);
+
+    // Without ReportBatchItemFailures a successful invoke deletes the whole batch
+    let outcome = batch_outcome(&mapping(false), &messages, &ok(reply));
+    assert_eq!(outcome.delete.len(), 3);
+    assert_eq!(outcome.result, "OK");
+
+    let outcome = batch_outcome(&mapping(true), &messages, &ok(unknown));
+    assert!(outcome.delete.is_empty());
+    assert_eq!(outcome.result, "InvalidBatchItemFailures");
+
+    let mut failed = ok(json!({ "errorType": "TypeError" })).unwrap();
+    failed.function_error = Some(FunctionError::Unhandled);
+    let outcome = batch_outcome(&mapping(true), &messages, &Ok(failed));
+    assert!(outcome.delete.is_empty());
+    assert!(outcome.back_off);
+    assert_eq!(outcome.result, "FunctionError: TypeError");
+}
+
+#[tokio::test]
+async fn mappings_link_existing_queues_and_functions() {
+    let cp = control_plane().await;
+    cp.sqs()
+        .create_queue("orders", CreateQueueOptions::default())
+        .await
+        .unwrap();
+    cp.create_function(create_request("order-worker"))
+        .await
+        .unwrap();
+
+    let request = |queue: &str, batch_size| CreateEventSourceMappingRequest {
+        event_source_arn: queue_arn(queue),
+        function_name: "order-worker".into(),
+        batch_size,
+        // Disabled so the poller leaves the queue alone
+        enabled: Some(false),
+        function_response_types: vec![FunctionResponseType::ReportBatchItemFailures],
+        s3_key_filter: None,
+    };
+    assert_eq!(
+        cp.create_event_source_mapping(request("missing", None))
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+    assert_eq!(
+        cp.create_event_source_mapping(request("orders", Some(11)))
+            .await
+            .unwrap_err()
+            .http_status(),
+        400
+    );
+
+    let created = cp
+        .create_event_source_mapping(request("orders", None))
+        .await
+        .unwrap();
+    assert_eq!(created.batch_size, 10);
+    assert!(!created.enabled);
+
+    let listed = cp
+        .list_event_source_mappings(Some("order-worker"))
+        .await
+        .unwrap();
+    assert_eq!(listed.event_source_mappings.len(), 1);
+    assert_eq!(
+        listed.event_source_mappings[0].function_response_types,
+        vec![FunctionResponseType::ReportBatchItemFailures]
+    );
+    assert!(cp
+        .list_event_source_mappings(Some("other"))
+        .await
+        .unwrap()
+        .event_source_mappings
+        .is_empty());
+
+    let updated = cp
+        .update_event_source_mapping(
+            created.uuid,
+            UpdateEventSourceMappingRequest {
+                batch_size: Some(5),
+                function_response_types: Some(vec![]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.batch_size, 5);
+    let fetched = cp.get_event_source_mapping(created.uuid).await.unwrap();
+    assert_eq!(fetched.batch_size, 5);
+    assert!(fetched.function_response_types.is_empty());
+
+    // Deleting the queue removes its mappings
+    cp.sqs().delete_queue("orders").await.unwrap();
+    assert_eq!(
+        cp.get_event_source_mapping(created.uuid)
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+}
@@ -0,0 +1,123 @@
+use crate::common::{control_plane_with, temp_dir, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_control::static_functions::StaticFunctionOutcome;
+use lambda_models::StaticFunctionConfig;
+use std::collections::HashMap;
+use std::path::Path;
+
+async fn control_plane(data_dir: &Path) -> ControlPlane {
+    let mut config = test_config();
+    config.data.dir = data_dir.join("data").to_string_lossy().to_string();
+    control_plane_with(config).await
+}
+
+fn spec(code: &Path) -> StaticFunctionConfig {
+    StaticFunctionConfig {
+        name: "hello".to_string(),
+        runtime: "nodejs22.x".to_string(),
+        handler: "index.handler".to_string(),
+        code: code.to_string_lossy().to_string(),
+        workspace_package: None,
+        role: None,
+        description: None,
+        timeout: Some(10),
+        memory_size: None,
+        environment: Some(HashMap::from([("STAGE".to_string(), "dev".to_string())])),
+    }
+}
+
+#[tokio::test]
+async fn zipping_a_directory_is_reproducible() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(dir.path().join("lib")).unwrap();
+    std::fs::write(
+        dir.path().join("index.js"),
+        "exports.handler = async () => 1;",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("lib/util.js"), "module.exports = {};").unwrap();
+
+    let first = lambda_packaging::zip_directory(dir.path()).unwrap();
+    // Rewriting a file bumps its modification time but not the archive
+    std::fs::write(
+        dir.path().join("index.js"),
+        "exports.handler = async () => 1;",
+    )
+    .unwrap();
+    assert_eq!(first, lambda_packaging::zip_directory(dir.path()).unwrap());
+
+    let zip = lambda_packaging::ZipHandler::new(1024 * 1024)
+        .process_zip(&first)
+        .await
+        .unwrap();
+    assert!(zip.contains_file("index.js"));
+    assert_eq!(
+        zip.read_file("lib/util.js").unwrap(),
+        b"module.exports = {};"
+    );
+}
+
+#[tokio::test]
+async fn declared_functions_are_created_then_kept_in_line() {
+    let dir = temp_dir();
+    let code = dir.path().join("hello");
+    std::fs::create_dir_all(&code).unwrap();
+    std::fs::write(code.join("index.js"), "exports.handler = async () => 1;").unwrap();
+    let cp = control_plane(dir.path()).await;
+    let mut spec = spec(&code);
+
+    assert_eq!(
+        cp.apply_static_function(&spec).await.unwrap(),
+        StaticFunctionOutcome::Created
+    );
+    let function = cp.get_function("hello").await.unwrap();
+    assert_eq!(function.timeout, 10);
+    assert_eq!(function.environment["STAGE"], "dev");
+    assert_eq!(
+        cp.apply_static_function(&spec).await.unwrap(),
+        StaticFunctionOutcome::Unchanged
+    );
+
+    // Settings the config file leaves out are not reset
+    cp.update_function_configuration(
+        "hello",
+        lambda_models::UpdateFunctionConfigurationRequest {
+            role: None,
+            handler: None,
+            description: None,
+            timeout: None,
+            memory_size: Some(1024),
+            environment: None,
+        },
+    )
+    .await
+    .unwrap();
+    spec.timeout = Some(20);
+    assert_eq!(
+        cp.apply_static_function(&spec).await.unwrap(),
+        StaticFunctionOutcome::Updated
+    );
+    let function = cp.get_function("hello").await.unwrap();
+    assert_eq!((function.timeout, function.memory_size), (20, 1024));
+
+    std::fs::write(code.join("index.js"), "exports.handler = async () => 2;").unwrap();
+    assert_eq!(
+        cp.apply_static_function(&spec).await.unwrap(),
+        StaticFunctionOutcome::Recreated
+    );
+    let recreated = cp.get_function("hello").await.unwrap();
+    assert_ne!(recreated.code_sha256, function.code_sha256);
+    assert_eq!(recreated.timeout, 20);
+}
+
+#[tokio::test]
+async fn missing_code_is_reported() {
+    let dir = temp_dir();
+    let cp = control_plane(dir.path()).await;
+    let err = cp
+        .apply_static_function(&spec(&dir.path().join("missing")))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("missing"), "{err}");
+    assert!(cp.get_function("hello").await.is_err());
+}
@@ -0,0 +1,112 @@
+use crate::common::{control_plane_with, create_function, test_config};
+use lambda_control::queues::FnKey;
+use lambda_control::registry::ControlPlane;
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_models::StopGraceConfig;
+use std::time::Instant;
+
+async fn control_plane() -> ControlPlane {
+    let mut config = test_config();
+    config.defaults.stop_grace_seconds = 15;
+    control_plane_with(config).await
+}
+
+#[tokio::test]
+async fn stop_grace_defaults_to_config_until_set() {
+    let cp = control_plane().await;
+    create_function(&cp, "jobs").await;
+    assert_eq!(
+        cp.get_stop_grace("jobs")
+            .await
+            .unwrap()
+            .grace_period_seconds,
+        15
+    );
+
+    let config = StopGraceConfig {
+        grace_period_seconds: 120,
+    };
+    assert_eq!(
+        cp.put_stop_grace("jobs", config.clone()).await.unwrap(),
+        config
+    );
+    assert_eq!(cp.get_stop_grace("jobs").await.unwrap(), config);
+
+    cp.delete_stop_grace("jobs").await.unwrap();
+    assert_eq!(
+        cp.get_stop_grace("jobs")
+            .await
+            .unwrap()
+            .grace_period_seconds,
+        15
+    );
+}
+
+#[tokio::test]
+async fn stop_grace_is_validated() {
+    let cp = control_plane().await;
+    create_function(&cp, "jobs").await;
+    let too_long = cp
+        .put_stop_grace(
+            "jobs",
+            StopGraceConfig {
+                grace_period_seconds: 901,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(too_long.http_status(), 400);
+
+    let missing = cp
+        .put_stop_grace(
+            "nope",
+            StopGraceConfig {
+                grace_period_seconds: 5,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(missing.http_status(), 404);
+}
+
+#[tokio::test]
+async fn unregistered_instances_stop_counting_as_idle() {
+    let cp = control_plane().await;
+    let key = FnKey {
+        function_name: "jobs".to_string(),
+        runtime: "nodejs22.x".to_string(),
+        version: "LATEST".to_string(),
+        env_hash: "abc".to_string(),
+    };
+    cp.warm_pool()
+        .add_warm_container(
+            key.clone(),
+            WarmContainer {
+                container_id: "c1".to_string(),
+                instance_id: "inst-1".to_string(),
+                function_id: uuid::Uuid::new_v4(),
+                image_ref: "lambda-home/jobs:sha".to_string(),
+                memory_mb: 128,
+                created_at: Instant::now(),
+                last_used: Instant::now(),
+                state: InstanceState::WarmIdle,
+            },
+        )
+        .await;
+
+    assert_eq!(
+        cp.unregister_instance("inst-1").await,
+        Some((key.clone(), "c1".to_string()))
+    );
+    assert_eq!(
+        cp.warm_pool().find_container("c1").await,
+        Some((key.clone(), InstanceState::Stopping))
+    );
+    assert_eq!(
+        cp.warm_pool()
+            .count_state(&key, InstanceState::WarmIdle)
+            .await,
+        0
+    );
+    assert_eq!(cp.unregister_instance("unknown").await, None);
+}
@@ -0,0 +1,169 @@
+use crate::common::test_config;
+use lambda_control::jobs::JobStore;
+use lambda_control::migrations::run_migrations;
+use lambda_control::registry::ControlPlane;
+use lambda_control::sync_invoke::{run_bounded, BoundedInvoke};
+use lambda_models::{
+    InvocationType, InvokeRequest, InvokeResponse, JobStatus, JobsConfig, LambdaError,
+    SlowInvokeMode, SyncInvokeConfig,
+};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+async fn job_store() -> JobStore {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    JobStore::new(pool, JobsConfig::default())
+}
+
+fn response(payload: serde_json::Value) -> InvokeResponse {
+    InvokeResponse {
+        status_code: 200,
+        payload: Some(payload),
+        executed_version: Some("$LATEST".into()),
+        function_error: None,
+        log_result: None,
+        headers: HashMap::new(),
+        duration_ms: Some(42),
+    }
+}
+
+/// An invocation that finishes when `finish` is sent its payload
+fn held_invocation() -> (
+    oneshot::Sender<serde_json::Value>,
+    impl std::future::Future<Output = Result<InvokeResponse, LambdaError>> + Send + 'static,
+) {
+    let (finish, finished) = oneshot::channel();
+    (finish, async move { Ok(response(finished.await.unwrap())) })
+}
+
+#[tokio::test]
+async fn quick_invocations_finish_within_the_wait() {
+    let jobs = job_store().await;
+    let outcome = run_bounded(
+        jobs.clone(),
+        "req-1".into(),
+        "orders".into(),
+        Duration::from_secs(5),
+        async { Ok(response(serde_json::json!({ "ok": true }))) },
+    )
+    .await;
+    let BoundedInvoke::Finished(result) = outcome else {
+        panic!("invocation should have finished");
+    };
+    assert_eq!(result.unwrap().payload.unwrap()["ok"], true);
+    // Nothing is stored for a caller that got its result
+    assert!(jobs.get("req-1").await.is_err());
+}
+
+#[tokio::test]
+async fn detached_invocations_store_their_result() {
+    let jobs = job_store().await;
+    let (finish, invocation) = held_invocation();
+    let outcome = run_bounded(
+        jobs.clone(),
+        "req-2".into(),
+        "orders".into(),
+        Duration::from_millis(20),
+        invocation,
+    )
+    .await;
+    let BoundedInvoke::Pending(pending) = outcome else {
+        panic!("invocation should still run");
+    };
+    assert_eq!(pending.request_id, "req-2");
+    assert!(pending.detach(&jobs).await.unwrap().is_none());
+    let job = jobs.get("req-2").await.unwrap();
+    assert_eq!(job.status, JobStatus::Running);
+    assert_eq!(job.function_name, "orders");
+
+    finish.send(serde_json::json!({ "late": true })).unwrap();
+    let mut job = jobs.get("req-2").await.unwrap();
+    for _ in 0..50 {
+        if job.status != JobStatus::Running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        job = jobs.get("req-2").await.unwrap();
+    }
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.payload.unwrap()["late"], true);
+}
+
+#[tokio::test]
+async fn pending_invocations_can_still_be_awaited() {
+    let jobs = job_store().await;
+    let (finish, invocation) = held_invocation();
+    let outcome = run_bounded(
+        jobs.clone(),
+        "req-3".into(),
+        "orders".into(),
+        Duration::from_millis(20),
+        invocation,
+    )
+    .await;
+    let BoundedInvoke::Pending(mut pending) = outcome else {
+        panic!("invocation should still run");
+    };
+    // A heartbeat interval passing without the result loses nothing
+    assert!(
+        tokio::time::timeout(Duration::from_millis(20), pending.result())
+            .await
+            .is_err()
+    );
+    finish.send(serde_json::json!("done")).unwrap();
+    let result = pending.result().await.unwrap();
+    assert_eq!(result.payload.unwrap(), "done");
+    assert!(jobs.get("req-3").await.is_err());
+}
+
+#[test]
+fn sync_invoke_config_is_validated() {
+    assert!(SyncInvokeConfig::default().validate().is_ok());
+    let config = SyncInvokeConfig {
+        max_wait_secs: Some(25),
+        invoke: SlowInvokeMode::KeepAlive,
+        ..SyncInvokeConfig::default()
+    };
+    assert!(config.validate().is_err());
+    let config = SyncInvokeConfig {
+        max_wait_secs: Some(0),
+        ..SyncInvokeConfig::default()
+    };
+    assert!(config.validate().is_err());
+
+    let e = LambdaError::ResultPending {
+        request_id: "req-4".into(),
+        waited_secs: 25,
+    };
+    assert_eq!(e.http_status(), 504);
+    let shape = e.to_error_shape();
+    assert_eq!(shape.error_type, "ResultPendingException");
+    assert!(shape.error_message.contains("/api/executions/req-4/result"));
+}
+
+#[tokio::test]
+async fn a_refused_streaming_invoke_closes_its_stream() {
+    let config = test_config();
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+    let cp = Arc::new(ControlPlane::new(pool, invoker, config).await.unwrap());
+
+    let mut stream = cp.invoke_with_response_stream(InvokeRequest {
+        function_name: "missing".into(),
+        invocation_type: InvocationType::RequestResponse,
+        log_type: None,
+        client_context: None,
+        payload: None,
+        qualifier: None,
+        idempotent: false,
+        trace: false,
+    });
+    let err = (&mut stream.result).await.unwrap().unwrap_err();
+    assert_eq!(err.http_status(), 404);
+    assert_eq!(stream.parts.recv().await, None);
+    assert!(!cp.pending().is_streaming(&stream.request_id));
+}
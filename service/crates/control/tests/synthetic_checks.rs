@@ -0,0 +1,188 @@
+use crate::common::{control_plane, create_request};
+use chrono::Utc;
+use lambda_control::synthetics::{
+    check_due, evaluate_run, matches_expectation, summarize_runs, validate_synthetic_check,
+    MAX_CHECK_INTERVAL_SECS,
+};
+use lambda_models::{
+    FunctionError, InvokeResponse, LambdaError, SyntheticCheck, SyntheticCheckRun,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn check(expect: Option<serde_json::Value>) -> SyntheticCheck {
+    serde_json::from_value(json!({ "interval_secs": 60, "expect": expect })).unwrap()
+}
+
+fn response(payload: serde_json::Value, function_error: Option<FunctionError>) -> InvokeResponse {
+    InvokeResponse {
+        status_code: 200,
+        payload: Some(payload),
+        executed_version: None,
+        function_error,
+        log_result: None,
+        headers: HashMap::new(),
+        duration_ms: None,
+    }
+}
+
+fn run(success: bool, latency_ms: u64) -> SyntheticCheckRun {
+    SyntheticCheckRun {
+        ran_at: Utc::now(),
+        success,
+        latency_ms,
+        failure: (!success).then(|| "failed".to_string()),
+        request_id: None,
+    }
+}
+
+#[test]
+fn expectations_match_subsets_of_objects() {
+    let actual = json!({ "statusCode": 200, "body": { "ok": true, "count": 3 } });
+    assert!(matches_expectation(&json!({ "statusCode": 200 }), &actual));
+    assert!(matches_expectation(
+        &json!({ "body": { "ok": true } }),
+        &actual
+    ));
+    assert!(!matches_expectation(&json!({ "statusCode": 500 }), &actual));
+    assert!(!matches_expectation(&json!({ "missing": 1 }), &actual));
+    assert!(matches_expectation(&json!([1, 2]), &json!([1, 2])));
+    assert!(!matches_expectation(&json!([1]), &json!([1, 2])));
+}
+
+#[test]
+fn runs_fail_on_errors_mismatches_and_slowness() {
+    let mut check = check(Some(json!({ "ok": true })));
+    assert_eq!(
+        evaluate_run(&check, Ok(&response(json!({ "ok": true }), None)), 10),
+        None
+    );
+    let failure = evaluate_run(&check, Ok(&response(json!({ "ok": false }), None)), 10).unwrap();
+    assert!(failure.contains("does not match"), "{failure}");
+
+    let error = response(
+        json!({ "errorMessage": "boom" }),
+        Some(FunctionError::Unhandled),
+    );
+    assert_eq!(
+        evaluate_run(&check, Ok(&error), 10).as_deref(),
+        Some("Function error: boom")
+    );
+
+    let err = LambdaError::InternalError {
+        reason: "no container".to_string(),
+    };
+    assert!(evaluate_run(&check, Err(&err), 10).is_some());
+
+    check.max_latency_ms = Some(100);
+    let failure = evaluate_run(&check, Ok(&response(json!({ "ok": true }), None)), 150).unwrap();
+    assert!(failure.contains("150 ms"), "{failure}");
+}
+
+#[test]
+fn checks_are_validated() {
+    assert!(validate_synthetic_check(&check(None)).is_ok());
+    let mut too_often = check(None);
+    too_often.interval_secs = 1;
+    assert_eq!(
+        validate_synthetic_check(&too_often)
+            .unwrap_err()
+            .http_status(),
+        400
+    );
+    // Too rarely, including intervals that would wrap negative as an i64
+    for interval_secs in [
+        MAX_CHECK_INTERVAL_SECS + 1,
+        10_000_000_000_000_000,
+        u64::MAX,
+    ] {
+        let mut too_rarely = check(None);
+        too_rarely.interval_secs = interval_secs;
+        assert!(validate_synthetic_check(&too_rarely).is_err());
+    }
+    let mut bad_slo = check(None);
+    bad_slo.slo_target = 120.0;
+    assert!(validate_synthetic_check(&bad_slo).is_err());
+}
+
+#[test]
+fn checks_come_due_after_their_interval() {
+    let now = Utc::now();
+    let check = check(None);
+    assert!(check_due(&check, None, now));
+    assert!(!check_due(
+        &check,
+        Some(now - chrono::Duration::seconds(30)),
+        now
+    ));
+    assert!(check_due(
+        &check,
+        Some(now - chrono::Duration::seconds(60)),
+        now
+    ));
+
+    // Intervals stored before they were bounded never come due instead of overflowing
+    let mut unbounded = check.clone();
+    for interval_secs in [10_000_000_000_000_000, u64::MAX] {
+        unbounded.interval_secs = interval_secs;
+        assert!(!check_due(&unbounded, Some(now), now));
+    }
+}
+
+#[test]
+fn stats_report_uptime_error_budget_and_latency() {
+    let mut runs: Vec<_> = (1..=9).map(|i| run(true, i * 10)).collect();
+    runs.insert(0, run(false, 1000));
+    let stats = summarize_runs("hook", Duration::from_secs(3600), 95.0, runs);
+    assert_eq!(stats.runs, 10);
+    assert_eq!(stats.successes, 9);
+    assert_eq!(stats.uptime_percent, Some(90.0));
+    assert_eq!(stats.slo_met, Some(false));
+    assert!((stats.error_budget_remaining.unwrap() + 0.5).abs() < 1e-9);
+    assert_eq!(stats.p50_latency_ms, Some(50));
+    assert_eq!(stats.p95_latency_ms, Some(1000));
+    assert!(!stats.recent_runs[0].success);
+
+    let empty = summarize_runs("hook", Duration::from_secs(3600), 95.0, Vec::new());
+    assert_eq!(empty.uptime_percent, None);
+    assert_eq!(empty.slo_met, None);
+    assert_eq!(empty.p50_latency_ms, None);
+}
+
+#[tokio::test]
+async fn failed_runs_are_recorded_and_raise_a_dashboard_alert() {
+    let cp = control_plane().await;
+    // Without code the function never becomes invocable, so every run fails
+    cp.create_function(create_request("hook")).await.unwrap();
+
+    let err = cp.get_synthetic_check("hook").await.unwrap_err();
+    assert_eq!(err.http_status(), 404, "{err}");
+    cp.put_synthetic_check("hook", check(None)).await.unwrap();
+    assert_eq!(cp.get_synthetic_check("hook").await.unwrap(), check(None));
+
+    let run = cp.run_synthetic_check("hook").await.unwrap();
+    assert!(!run.success);
+    assert!(run.failure.is_some());
+
+    let stats = cp
+        .synthetic_check_stats("hook", Duration::from_secs(3600))
+        .await
+        .unwrap();
+    assert_eq!(stats.runs, 1);
+    assert_eq!(stats.uptime_percent, Some(0.0));
+    // Windows reach back no further than runs are kept
+    assert!(cp
+        .synthetic_check_stats("hook", Duration::from_secs(100_000_000 * 86400))
+        .await
+        .is_err());
+    let alerts = cp.dashboard().await.unwrap().alerts;
+    assert!(alerts
+        .iter()
+        .any(|a| a.code == "SyntheticCheckFailing" && a.function_name.as_deref() == Some("hook")));
+
+    cp.delete_synthetic_check("hook").await.unwrap();
+    assert!(cp.get_synthetic_check("hook").await.is_err());
+    let alerts = cp.dashboard().await.unwrap().alerts;
+    assert!(alerts.iter().all(|a| a.code != "SyntheticCheckFailing"));
+}
@@ -0,0 +1,59 @@
+use crate::common::{control_plane_with, test_config};
+use lambda_control::features::FeatureFlags;
+use lambda_control::migrations::SCHEMA_VERSION;
+use lambda_control::system_info::enabled_features;
+
+#[test]
+fn features_follow_the_configuration() {
+    let mut config = test_config();
+    config.archive.idle_days = Some(30);
+    let features = enabled_features(&config, &FeatureFlags::new(&config.features));
+    assert_eq!(features.get("warmup"), Some(&false));
+    assert_eq!(features.get("archive"), Some(&true));
+    assert_eq!(
+        features.get("replication"),
+        Some(&config.replication.enabled)
+    );
+    assert_eq!(features.get("synthetic_checks"), Some(&true));
+}
+
+#[tokio::test]
+async fn system_info_describes_the_server() {
+    let mut config = test_config();
+    config.quotas.max_functions = Some(10);
+    let cp = control_plane_with(config.clone()).await;
+
+    let info = cp.system_info().await;
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.schema_version, SCHEMA_VERSION);
+    assert_eq!(info.features.get("warmup"), Some(&false));
+
+    let runtimes: Vec<&str> = info.runtimes.iter().map(|r| r.runtime.as_str()).collect();
+    assert_eq!(
+        runtimes,
+        [
+            "nodejs18.x",
+            "nodejs22.x",
+            "nodejs24.x",
+            "python3.11",
+            "rust"
+        ]
+    );
+    assert_eq!(info.runtimes[1].base_image, "node:22-alpine");
+
+    assert_eq!(
+        info.limits.max_global_concurrency,
+        config.limits.max_global_concurrency
+    );
+    assert_eq!(info.limits.max_timeout_secs, 900);
+    assert_eq!(info.limits.quotas.max_functions, Some(10));
+
+    assert_eq!(info.backend.kind, "docker");
+    assert_eq!(info.backend.host, config.docker.host);
+    // Either the daemon answered or the reason it did not is given
+    assert_ne!(info.backend.docker.is_some(), info.backend.error.is_some());
+    assert!(info
+        .backend
+        .sandbox_profiles
+        .contains(&info.backend.default_sandbox_profile));
+}
@@ -0,0 +1,172 @@
+use crate::common::{control_plane, create_request};
+use lambda_control::registry::ControlPlane;
+use lambda_models::{
+    function_name_from_arn, BatchFunctionsRequest, BatchOperation, Config, CreateFunctionRequest,
+    FunctionSelector, LambdaError, ListFunctionsQuery,
+};
+use std::collections::BTreeMap;
+
+async fn create_function(
+    cp: &ControlPlane,
+    name: &str,
+    tags: &[(&str, &str)],
+) -> Result<(), LambdaError> {
+    cp.create_function(CreateFunctionRequest {
+        tags: Some(
+            tags.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ),
+        ..create_request(name)
+    })
+    .await
+    .map(|_| ())
+}
+
+fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn arn(name: &str) -> String {
+    Config::default().identity.function_arn(name)
+}
+
+#[test]
+fn function_names_are_read_from_arns() {
+    assert_eq!(function_name_from_arn(&arn("orders")), Some("orders"));
+    assert_eq!(
+        function_name_from_arn(&format!("{}:live", arn("orders"))),
+        Some("orders")
+    );
+    assert_eq!(
+        function_name_from_arn("arn:aws:sqs:us-east-1:000000000000:orders"),
+        None
+    );
+    assert_eq!(function_name_from_arn("orders"), None);
+}
+
+#[tokio::test]
+async fn tags_are_set_at_creation_and_changed_by_arn() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders", &[("team", "payments")])
+        .await
+        .unwrap();
+
+    cp.tag_resource(
+        &arn("orders"),
+        tags(&[("env", "prod"), ("team", "checkout")]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        cp.list_tags(&arn("orders")).await.unwrap().tags,
+        tags(&[("env", "prod"), ("team", "checkout")])
+    );
+
+    cp.untag_resource(&arn("orders"), &["team".to_string(), "unknown".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(
+        cp.list_tags(&arn("orders")).await.unwrap().tags,
+        tags(&[("env", "prod")])
+    );
+    let response = serde_json::to_value(cp.get_function_response("orders").await.unwrap()).unwrap();
+    assert_eq!(response["Tags"], serde_json::json!({ "env": "prod" }));
+
+    // Tags go with their function
+    cp.delete_function("orders").await.unwrap();
+    create_function(&cp, "orders", &[]).await.unwrap();
+    assert!(cp.list_tags(&arn("orders")).await.unwrap().tags.is_empty());
+}
+
+#[tokio::test]
+async fn invalid_tags_are_refused() {
+    let cp = control_plane().await;
+    let reserved = create_function(&cp, "orders", &[("aws:owner", "me")]).await;
+    assert!(matches!(reserved, Err(LambdaError::InvalidRequest { .. })));
+    assert!(cp.get_function("orders").await.is_err());
+
+    create_function(&cp, "orders", &[]).await.unwrap();
+    let too_many: BTreeMap<String, String> = (0..51)
+        .map(|i| (format!("k{i}"), "v".to_string()))
+        .collect();
+    let error = cp.tag_resource(&arn("orders"), too_many).await.unwrap_err();
+    assert!(matches!(error, LambdaError::InvalidRequest { .. }));
+
+    let error = cp.list_tags("orders").await.unwrap_err();
+    assert!(matches!(error, LambdaError::InvalidRequest { .. }));
+    let error = cp.list_tags(&arn("missing")).await.unwrap_err();
+    assert_eq!(error.http_status(), 404);
+}
+
+async fn listed(cp: &ControlPlane, filter: &[(&str, &str)]) -> Vec<String> {
+    let query = ListFunctionsQuery {
+        tags: tags(filter),
+        ..Default::default()
+    };
+    let response = cp.list_functions(&query).await.unwrap();
+    response
+        .functions
+        .into_iter()
+        .map(|f| f.function_name)
+        .collect()
+}
+
+#[tokio::test]
+async fn functions_are_listed_and_batched_by_tag() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders", &[("team", "payments"), ("env", "prod")])
+        .await
+        .unwrap();
+    create_function(&cp, "refunds", &[("team", "payments"), ("env", "dev")])
+        .await
+        .unwrap();
+    create_function(&cp, "search", &[("team", "discovery")])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        listed(&cp, &[("team", "payments")]).await,
+        ["orders", "refunds"]
+    );
+    assert_eq!(
+        listed(&cp, &[("team", "payments"), ("env", "prod")]).await,
+        ["orders"]
+    );
+    assert!(listed(&cp, &[("team", "nobody")]).await.is_empty());
+    assert_eq!(listed(&cp, &[]).await.len(), 3);
+
+    let response = cp
+        .batch_functions(
+            BatchFunctionsRequest {
+                selector: FunctionSelector {
+                    tags: tags(&[("team", "payments")]),
+                    ..Default::default()
+                },
+                operation: BatchOperation::Tag {
+                    tags: tags(&[("cost-center", "42")]),
+                },
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    let tagged: Vec<&str> = response
+        .results
+        .iter()
+        .map(|r| r.function_name.as_str())
+        .collect();
+    assert_eq!(tagged, ["orders", "refunds"]);
+    assert_eq!(
+        cp.list_function_tags("refunds").await.unwrap()["cost-center"],
+        "42"
+    );
+    assert!(!cp
+        .list_function_tags("search")
+        .await
+        .unwrap()
+        .contains_key("cost-center"));
+}
@@ -0,0 +1,130 @@
+use crate::common::{control_plane, create_function};
+use lambda_control::transforms::{render, request_context, response_context, validate_template};
+use lambda_models::{CreateApiRouteRequest, RouteTransform};
+use serde_json::json;
+
+fn proxy_event(body: &str) -> serde_json::Value {
+    json!({
+        "path": "/legacy/orders",
+        "httpMethod": "POST",
+        "headers": { "x-user-id": "u-1" },
+        "queryStringParameters": { "dry": "1" },
+        "body": body,
+    })
+}
+
+#[test]
+fn request_templates_reshape_the_proxy_event() {
+    let template = json!({
+        "orderId": "{{ body.ORDER.ID }}",
+        "items": "{{ body.ORDER.LINES }}",
+        "firstSku": "{{ body.ORDER.LINES.0.SKU }}",
+        "user": "{{ headers.x-user-id }}",
+        "source": "{{ method }} {{ path }}?dry={{ query.dry }}",
+        "missing": "{{ body.NOPE }}",
+        "raw": "{{ rawBody }}",
+        "fixed": 3,
+    });
+    let body = r#"{"ORDER":{"ID":42,"LINES":[{"SKU":"a"},{"SKU":"b"}]}}"#;
+    let event = render(&template, &request_context(&proxy_event(body)));
+    assert_eq!(
+        event,
+        json!({
+            "orderId": 42,
+            "items": [{ "SKU": "a" }, { "SKU": "b" }],
+            "firstSku": "a",
+            "user": "u-1",
+            "source": "POST /legacy/orders?dry=1",
+            "missing": null,
+            "raw": body,
+            "fixed": 3,
+        })
+    );
+
+    // Bodies that are not JSON are text
+    let text = render(&json!("{{ body }}"), &request_context(&proxy_event("a=1")));
+    assert_eq!(text, json!("a=1"));
+}
+
+#[test]
+fn filters_convert_to_and_from_json_text() {
+    let context = response_context(Some(
+        &json!({ "result": { "ok": true }, "encoded": "[1,2]" }),
+    ));
+    assert_eq!(
+        render(&json!("{{ payload.result | json }}"), &context),
+        json!(r#"{"ok":true}"#)
+    );
+    assert_eq!(
+        render(&json!("{{ payload.encoded | parse }}"), &context),
+        json!([1, 2])
+    );
+    assert_eq!(
+        render(
+            &json!({ "statusCode": 201, "body": "{{ payload.result | json }}" }),
+            &context
+        ),
+        json!({ "statusCode": 201, "body": r#"{"ok":true}"# })
+    );
+}
+
+#[test]
+fn invalid_templates_are_refused() {
+    for template in [
+        json!("{{ body"),
+        json!({ "a": ["{{ }}"] }),
+        json!("{{ body..id }}"),
+        json!("{{ body | upper }}"),
+    ] {
+        assert!(validate_template(&template).is_err(), "{template}");
+    }
+    assert!(validate_template(&json!({ "a": "plain", "b": [1, "{{ path }}"] })).is_ok());
+}
+
+#[tokio::test]
+async fn transforms_are_stored_on_routes() {
+    let cp = control_plane().await;
+    create_function(&cp, "orders").await;
+    let route = cp
+        .create_api_route(CreateApiRouteRequest {
+            path: "/legacy/orders".into(),
+            method: Some("POST".into()),
+            function_name: "orders".into(),
+            canary: None,
+        })
+        .await
+        .unwrap();
+    assert!(route.transform.is_none());
+
+    let transform = RouteTransform {
+        request: Some(json!({ "orderId": "{{ body.ORDER.ID }}" })),
+        response: Some(json!({ "STATUS": "{{ payload.status }}" })),
+    };
+    let updated = cp
+        .put_api_route_transform(route.route_id, transform.clone())
+        .await
+        .unwrap();
+    assert_eq!(updated.transform, Some(transform));
+
+    for invalid in [
+        RouteTransform::default(),
+        RouteTransform {
+            request: Some(json!("{{ body")),
+            response: None,
+        },
+    ] {
+        let err = cp
+            .put_api_route_transform(route.route_id, invalid)
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 400);
+    }
+
+    cp.delete_api_route_transform(route.route_id).await.unwrap();
+    assert!(cp
+        .get_api_route(route.route_id)
+        .await
+        .unwrap()
+        .transform
+        .is_none());
+}
@@ -0,0 +1,76 @@
+use lambda_control::trusted_headers::authorizer;
+use lambda_models::TrustedHeadersConfig;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn enabled() -> TrustedHeadersConfig {
+    TrustedHeadersConfig {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn proxy_headers_become_the_authorizer() {
+    let request = headers(&[
+        ("x-forwarded-user", "alice"),
+        ("x-forwarded-groups", "admins, dev,"),
+        ("x-forwarded-email", "alice@example.com"),
+    ]);
+    assert_eq!(
+        authorizer(&enabled(), &request, None),
+        Some(json!({
+            "principalId": "alice",
+            "user": "alice",
+            "groups": ["admins", "dev"],
+            "email": "alice@example.com",
+        }))
+    );
+
+    // Nothing is asserted without a user, or with the headers off
+    let anonymous = headers(&[("x-forwarded-groups", "admins")]);
+    assert_eq!(authorizer(&enabled(), &anonymous, None), None);
+    assert_eq!(
+        authorizer(&TrustedHeadersConfig::default(), &request, None),
+        None
+    );
+}
+
+#[test]
+fn only_trusted_proxies_may_assert_an_identity() {
+    let proxy: IpAddr = "172.17.0.1".parse().unwrap();
+    let config = TrustedHeadersConfig {
+        trusted_proxies: vec![proxy],
+        groups_header: None,
+        ..enabled()
+    };
+    let request = headers(&[("x-forwarded-user", "bob")]);
+
+    let from_proxy = authorizer(&config, &request, Some(proxy)).unwrap();
+    assert_eq!(from_proxy["principalId"], "bob");
+    assert_eq!(from_proxy["groups"], json!([]));
+    let mapped: IpAddr = "::ffff:172.17.0.1".parse().unwrap();
+    assert!(authorizer(&config, &request, Some(mapped)).is_some());
+
+    let other: IpAddr = "10.0.0.5".parse().unwrap();
+    assert_eq!(authorizer(&config, &request, Some(other)), None);
+    assert_eq!(authorizer(&config, &request, None), None);
+}
+
+#[test]
+fn invalid_header_names_are_refused() {
+    let config = TrustedHeadersConfig {
+        user_header: "X Forwarded User".into(),
+        ..enabled()
+    };
+    assert!(config.validate().is_err());
+    assert!(enabled().validate().is_ok());
+}
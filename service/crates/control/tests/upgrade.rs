@@ -0,0 +1,132 @@
+use crate::common::temp_dir;
+use lambda_control::upgrade::{
+    install_binary, is_newer, migrate_with_backup, parse_checksum, parse_version,
+    previous_binary_path, release_assets, restore_binary, sqlite_path, verify_checksum, Release,
+};
+use serde_json::json;
+use sqlx::{Connection, SqliteConnection};
+use std::path::PathBuf;
+
+async fn tables(db_url: &str) -> Vec<String> {
+    let mut conn = SqliteConnection::connect(db_url).await.unwrap();
+    sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+        .fetch_all(&mut conn)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn release_versions_are_compared_numerically() {
+    assert_eq!(parse_version("v0.10.2"), Some((0, 10, 2)));
+    assert_eq!(parse_version("1.2.3-rc.1"), Some((1, 2, 3)));
+    assert_eq!(parse_version("1.2"), None);
+    assert_eq!(parse_version("latest"), None);
+    assert!(is_newer("0.3.0", "v0.10.0"));
+    assert!(!is_newer("0.3.0", "v0.3.0"));
+    assert!(!is_newer("0.3.0", "v0.2.9"));
+    assert!(!is_newer("0.3.0", "nightly"));
+}
+
+#[test]
+fn the_binary_and_its_checksum_are_picked_from_the_release() {
+    let release: Release = serde_json::from_value(json!({
+        "tag_name": "v0.4.0",
+        "assets": [
+            { "name": "lambda-at-home-server-v0.4.0-linux-x86_64", "browser_download_url": "https://example.com/bin" },
+            { "name": "lambda-at-home-server-v0.4.0-linux-x86_64.sha256", "browser_download_url": "https://example.com/bin.sha256" },
+            { "name": "lambda-at-home-server-v0.4.0-macos-arm64", "browser_download_url": "https://example.com/mac" },
+        ]
+    }))
+    .unwrap();
+    let (binary, checksum) = release_assets(&release, "linux-x86_64").unwrap();
+    assert_eq!(binary.browser_download_url, "https://example.com/bin");
+    assert_eq!(
+        checksum.browser_download_url,
+        "https://example.com/bin.sha256"
+    );
+    // A binary without a published checksum is never installed
+    assert!(release_assets(&release, "macos-arm64").is_err());
+
+    let digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    assert_eq!(
+        parse_checksum(&format!(
+            "{}  lambda-at-home-server\n",
+            digest.to_uppercase()
+        ))
+        .as_deref(),
+        Some(digest)
+    );
+    assert_eq!(parse_checksum("not a digest"), None);
+    assert!(verify_checksum(b"hello", digest).is_ok());
+    let err = verify_checksum(b"tampered", digest).unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "{err}");
+}
+
+#[test]
+fn installed_binaries_can_be_rolled_back() {
+    let dir = temp_dir();
+    let exe = dir.path().join("lambda-at-home-server");
+    std::fs::write(&exe, b"old").unwrap();
+
+    let previous = install_binary(&exe, b"new").unwrap();
+    assert_eq!(previous, previous_binary_path(&exe));
+    assert_eq!(std::fs::read(&exe).unwrap(), b"new");
+    assert_eq!(std::fs::read(&previous).unwrap(), b"old");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&exe).unwrap().permissions().mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+
+    restore_binary(&exe).unwrap();
+    assert_eq!(std::fs::read(&exe).unwrap(), b"old");
+    assert!(!previous.exists());
+}
+
+#[test]
+fn only_file_databases_have_a_path() {
+    assert_eq!(
+        sqlite_path("sqlite://data/lambda.db?mode=rwc"),
+        Some(PathBuf::from("data/lambda.db"))
+    );
+    assert_eq!(
+        sqlite_path("sqlite:lambda.db"),
+        Some(PathBuf::from("lambda.db"))
+    );
+    assert_eq!(sqlite_path("sqlite::memory:"), None);
+    assert_eq!(sqlite_path("postgres://db"), None);
+}
+
+#[tokio::test]
+async fn migrations_run_after_a_backup() {
+    let dir = temp_dir();
+    let db_url = format!(
+        "sqlite://{}?mode=rwc",
+        dir.path().join("lambda.db").display()
+    );
+
+    let backup = migrate_with_backup(&db_url).await.unwrap().unwrap();
+    assert!(backup.exists());
+    assert!(tables(&db_url).await.contains(&"functions".to_string()));
+}
+
+#[tokio::test]
+async fn a_failed_migration_restores_the_backup() {
+    let dir = temp_dir();
+    let db_url = format!(
+        "sqlite://{}?mode=rwc",
+        dir.path().join("lambda.db").display()
+    );
+    // An executions table without the columns the initial migration indexes
+    let mut conn = SqliteConnection::connect(&db_url).await.unwrap();
+    sqlx::query("CREATE TABLE executions (note TEXT)")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    conn.close().await.unwrap();
+
+    let err = migrate_with_backup(&db_url).await.unwrap_err();
+    assert!(err.to_string().contains("restored"), "{err}");
+    assert_eq!(tables(&db_url).await, vec!["executions".to_string()]);
+}
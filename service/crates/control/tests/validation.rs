@@ -0,0 +1,146 @@
+use crate::common::{control_plane_with, temp_dir};
+use lambda_control::validation::validate_create_function_request;
+use lambda_models::{Config, CreateFunctionRequest, FunctionCode};
+use lambda_testsupport::{b64, zip_dir};
+use std::collections::HashMap;
+
+fn request(
+    name: &str,
+    runtime: &str,
+    handler: &str,
+    zip_file: Option<String>,
+) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        function_name: name.into(),
+        runtime: runtime.into(),
+        handler: handler.into(),
+        code: FunctionCode {
+            zip_file,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn bundle(files: &[(&str, &str)]) -> String {
+    let dir = temp_dir();
+    for (name, content) in files {
+        std::fs::create_dir_all(dir.path().join(name).parent().unwrap()).unwrap();
+        std::fs::write(dir.path().join(name), content).unwrap();
+    }
+    let zip = zip_dir(dir.path()).unwrap();
+    b64(zip)
+}
+
+fn codes(problems: &[lambda_models::ValidationProblem]) -> Vec<&str> {
+    problems.iter().map(|p| p.code.as_str()).collect()
+}
+
+#[tokio::test]
+async fn valid_request_has_no_problems() {
+    let zip = bundle(&[("index.js", "exports.handler = async () => 'ok';")]);
+    let req = request("hello", "nodejs22.x", "index.handler", Some(zip));
+    let (problems, zip_info) = validate_create_function_request(&req).await;
+    assert!(problems.is_empty(), "unexpected problems: {problems:?}");
+    assert!(zip_info.is_some());
+}
+
+#[tokio::test]
+async fn reports_every_problem_at_once() {
+    let mut req = request("bad name!", "cobol", "", None);
+    req.timeout = Some(0);
+    req.memory_size = Some(64);
+    let mut env = HashMap::new();
+    env.insert("1BAD".to_string(), "x".to_string());
+    env.insert("AWS_REGION".to_string(), "eu-west-1".to_string());
+    env.insert("BIG".to_string(), "x".repeat(5000));
+    req.environment = Some(env);
+
+    let (problems, _) = validate_create_function_request(&req).await;
+    let codes = codes(&problems);
+    for expected in [
+        "InvalidFunctionName",
+        "InvalidRuntime",
+        "InvalidHandler",
+        "InvalidTimeout",
+        "InvalidMemorySize",
+        "InvalidEnvironmentVariableName",
+        "ReservedEnvironmentVariable",
+        "EnvironmentTooLarge",
+    ] {
+        assert!(codes.contains(&expected), "missing {expected} in {codes:?}");
+    }
+}
+
+#[tokio::test]
+async fn detects_missing_handler_file_and_bad_zip() {
+    let zip = bundle(&[("main.py", "def handler(e, c): return e")]);
+    let req = request("py", "python3.11", "lambda_function.handler", Some(zip));
+    let (problems, _) = validate_create_function_request(&req).await;
+    assert_eq!(codes(&problems), vec!["HandlerNotFound"]);
+    assert_eq!(problems[0].field, "handler");
+
+    let req = request(
+        "py",
+        "python3.11",
+        "lambda_function.handler",
+        Some(b64(b"not a zip")),
+    );
+    let (problems, zip_info) = validate_create_function_request(&req).await;
+    assert_eq!(codes(&problems), vec!["InvalidZipFile"]);
+    assert!(zip_info.is_none());
+}
+
+#[tokio::test]
+async fn resolves_the_handler_inside_a_workspace_package() {
+    let zip = bundle(&[
+        ("package.json", r#"{ "workspaces": ["services/*"] }"#),
+        ("services/api/package.json", r#"{ "name": "api" }"#),
+        (
+            "services/api/index.js",
+            "exports.handler = async () => 'ok';",
+        ),
+    ]);
+    let mut req = request("api", "nodejs22.x", "index.handler", Some(zip));
+    let (problems, _) = validate_create_function_request(&req).await;
+    assert_eq!(codes(&problems), vec!["HandlerNotFound"]);
+
+    req.code.workspace_package = Some("services/api".into());
+    let (problems, zip_info) = validate_create_function_request(&req).await;
+    assert!(problems.is_empty(), "unexpected problems: {problems:?}");
+    assert!(zip_info.unwrap().contains_file("index.js"));
+
+    req.code.workspace_package = Some("services/web".into());
+    let (problems, _) = validate_create_function_request(&req).await;
+    assert_eq!(codes(&problems), vec!["InvalidWorkspacePackage"]);
+    assert_eq!(problems[0].field, "code.workspace_package");
+}
+
+#[tokio::test]
+async fn dry_run_does_not_create_and_flags_existing_names() {
+    let config = Config::default();
+    let cp = control_plane_with(config.clone()).await;
+
+    let req = request("dry-run", "nodejs22.x", "index.handler", None);
+    assert!(cp.validate_function(&req).await.unwrap().is_empty());
+    assert!(cp.get_function("dry-run").await.is_err());
+
+    cp.create_function(req.clone()).await.unwrap();
+    let problems = cp.validate_function(&req).await.unwrap();
+    assert_eq!(codes(&problems), vec!["FunctionAlreadyExists"]);
+}
+
+#[tokio::test]
+async fn create_rejects_unresolvable_handler() {
+    let config = Config::default();
+    let cp = control_plane_with(config.clone()).await;
+
+    let zip = bundle(&[("index.js", "exports.main = async () => 'ok';")]);
+    let req = request("unresolved", "nodejs22.x", "index.handler", Some(zip));
+    let err = cp.create_function(req).await.unwrap_err();
+    assert!(matches!(
+        err,
+        lambda_models::LambdaError::HandlerNotFound { .. }
+    ));
+    assert!(cp.get_function("unresolved").await.is_err());
+}
@@ -0,0 +1,327 @@
+use crate::common::control_plane;
+use lambda_control::queues::FnKey;
+use lambda_control::versions::{alias_version, Qualifier};
+use lambda_models::{
+    CreateAliasRequest, CreateFunctionRequest, PublishVersionRequest, RoutingConfig,
+    UpdateAliasRequest, UpdateFunctionConfigurationRequest, LATEST_VERSION,
+};
+
+fn create_request(name: &str, publish: Option<bool>) -> CreateFunctionRequest {
+    CreateFunctionRequest {
+        description: Some("first".into()),
+        publish,
+        ..crate::common::create_request(name)
+    }
+}
+
+fn version_numbers(versions: &lambda_models::ListVersionsResponse) -> Vec<&str> {
+    versions
+        .versions
+        .iter()
+        .map(|v| v.version.as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn create_with_publish_publishes_version_one() {
+    let cp = control_plane().await;
+    let function = cp
+        .create_function(create_request("published", Some(true)))
+        .await
+        .unwrap();
+    assert_eq!(function.version, LATEST_VERSION);
+
+    let versions = cp.list_versions("published", None, None).await.unwrap();
+    assert_eq!(version_numbers(&versions), ["1"]);
+    assert_eq!(versions.versions[0].description.as_deref(), Some("first"));
+    assert_eq!(versions.versions[0].code_sha256, function.code_sha256);
+}
+
+#[tokio::test]
+async fn versions_are_numbered_in_publishing_order() {
+    let cp = control_plane().await;
+    for publish in [None, Some(false)] {
+        cp.create_function(create_request("draft", publish))
+            .await
+            .unwrap();
+        let versions = cp.list_versions("draft", None, None).await.unwrap();
+        assert!(versions.versions.is_empty());
+        cp.delete_function("draft").await.unwrap();
+    }
+
+    cp.create_function(create_request("draft", None))
+        .await
+        .unwrap();
+    for expected in 1..=11 {
+        let version = cp
+            .publish_version(
+                "draft",
+                PublishVersionRequest {
+                    description: None,
+                    revision_id: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(version.version, expected.to_string());
+    }
+    let versions = cp.list_versions("draft", None, Some(3)).await.unwrap();
+    assert_eq!(version_numbers(&versions), ["1", "2", "3"]);
+    let marker = versions.next_marker.unwrap();
+    let versions = cp
+        .list_versions("draft", Some(&marker), Some(20))
+        .await
+        .unwrap();
+    assert_eq!(versions.versions.last().unwrap().version, "11");
+}
+
+#[tokio::test]
+async fn versions_record_the_image_their_code_built_to() {
+    let cp = control_plane().await;
+    let function = cp
+        .create_function(create_request("imaged", Some(true)))
+        .await
+        .unwrap();
+    let publish = || PublishVersionRequest {
+        description: None,
+        revision_id: None,
+    };
+    let digest = |c: &str| format!("sha256:{}", c.repeat(64));
+
+    // Published before the build: filled in once the build is recorded
+    let versions = cp.list_versions("imaged", None, None).await.unwrap();
+    assert_eq!(versions.versions[0].image_digest, None);
+    cp.record_image_digest(&function, &digest("a"))
+        .await
+        .unwrap();
+    let second = cp.publish_version("imaged", publish()).await.unwrap();
+    assert_eq!(second.image_digest, Some(digest("a")));
+
+    // A rebuild that differs does not rewrite what versions recorded
+    cp.record_image_digest(&function, &digest("b"))
+        .await
+        .unwrap();
+    let third = cp.publish_version("imaged", publish()).await.unwrap();
+    assert_eq!(third.image_digest, Some(digest("b")));
+    let versions = cp.list_versions("imaged", None, None).await.unwrap();
+    let digests: Vec<_> = versions
+        .versions
+        .iter()
+        .map(|v| v.image_digest.clone())
+        .collect();
+    assert_eq!(
+        digests,
+        [Some(digest("a")), Some(digest("a")), Some(digest("b"))]
+    );
+}
+
+fn publish() -> PublishVersionRequest {
+    PublishVersionRequest {
+        description: None,
+        revision_id: None,
+    }
+}
+
+fn set_timeout(timeout: u64) -> UpdateFunctionConfigurationRequest {
+    UpdateFunctionConfigurationRequest {
+        role: None,
+        handler: None,
+        description: None,
+        timeout: Some(timeout),
+        memory_size: None,
+        environment: Some([("STAGE".to_string(), timeout.to_string())].into()),
+    }
+}
+
+#[test]
+fn qualifiers_name_versions_or_aliases() {
+    assert_eq!(Qualifier::parse(None), Qualifier::Latest);
+    assert_eq!(Qualifier::parse(Some("$LATEST")), Qualifier::Latest);
+    assert_eq!(Qualifier::parse(Some("")), Qualifier::Latest);
+    assert_eq!(Qualifier::parse(Some("12")), Qualifier::Version("12"));
+    assert_eq!(Qualifier::parse(Some("live")), Qualifier::Alias("live"));
+}
+
+#[tokio::test]
+async fn versions_snapshot_the_configuration_they_were_published_with() {
+    let cp = control_plane().await;
+    cp.create_function(create_request("orders", None))
+        .await
+        .unwrap();
+    cp.update_function_configuration("orders", set_timeout(10))
+        .await
+        .unwrap();
+    let first = cp.publish_version("orders", publish()).await.unwrap();
+    assert_eq!(first.config.as_ref().unwrap().timeout, 10);
+    cp.update_function_configuration("orders", set_timeout(20))
+        .await
+        .unwrap();
+
+    let latest = cp.get_function("orders").await.unwrap();
+    let resolved = cp.resolve_qualifier(latest.clone(), None).await.unwrap();
+    assert_eq!(resolved.version, LATEST_VERSION);
+    assert_eq!(resolved.timeout, 20);
+
+    let v1 = cp
+        .resolve_qualifier(latest.clone(), Some("1"))
+        .await
+        .unwrap();
+    assert_eq!(v1.version, "1");
+    assert_eq!(v1.timeout, 10);
+    assert_eq!(v1.environment["STAGE"], "10");
+    assert_eq!(v1.code_sha256, first.code_sha256);
+
+    // Each version runs in containers of its own
+    let latest_key = FnKey::for_function(&resolved);
+    let v1_key = FnKey::for_function(&v1);
+    assert_eq!(latest_key.version, LATEST_VERSION);
+    assert_eq!(v1_key.version, "1");
+    assert_ne!(latest_key, v1_key);
+    assert_eq!(cp.function_for_key(&v1_key).await.unwrap().timeout, 10);
+
+    let err = cp.resolve_qualifier(latest, Some("7")).await.unwrap_err();
+    assert_eq!(err.http_status(), 404);
+    assert!(err.to_string().contains("orders:7"), "{err}");
+}
+
+#[tokio::test]
+async fn aliases_resolve_to_the_version_they_route_to() {
+    let cp = control_plane().await;
+    cp.create_function(create_request("orders", None))
+        .await
+        .unwrap();
+    for timeout in [10, 20] {
+        cp.update_function_configuration("orders", set_timeout(timeout))
+            .await
+            .unwrap();
+        cp.publish_version("orders", publish()).await.unwrap();
+    }
+    let alias = cp
+        .create_alias(
+            "orders",
+            CreateAliasRequest {
+                name: "live".into(),
+                function_version: "1".into(),
+                description: None,
+                routing_config: None,
+            },
+        )
+        .await
+        .unwrap();
+    let latest = cp.get_function("orders").await.unwrap();
+    let live = cp
+        .resolve_qualifier(latest.clone(), Some("live"))
+        .await
+        .unwrap();
+    assert_eq!(live.version, "1");
+    assert_eq!(live.timeout, 10);
+
+    let err = cp
+        .resolve_qualifier(latest, Some("staging"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    // A tenth of the traffic goes to version 2
+    let mut weighted = alias;
+    weighted.routing_config = Some(RoutingConfig {
+        additional_version_weights: [("2".to_string(), 0.1)].into(),
+    });
+    assert_eq!(alias_version(&weighted, 0.05), "2");
+    assert_eq!(alias_version(&weighted, 0.5), "1");
+}
+
+fn routing(weights: &[(&str, f64)]) -> Option<RoutingConfig> {
+    Some(RoutingConfig {
+        additional_version_weights: weights
+            .iter()
+            .map(|(version, weight)| (version.to_string(), *weight))
+            .collect(),
+    })
+}
+
+fn alias_request(
+    function_version: &str,
+    routing_config: Option<RoutingConfig>,
+) -> CreateAliasRequest {
+    CreateAliasRequest {
+        name: "live".into(),
+        function_version: function_version.into(),
+        description: None,
+        routing_config,
+    }
+}
+
+fn shift_to(weights: &[(&str, f64)]) -> UpdateAliasRequest {
+    UpdateAliasRequest {
+        function_version: None,
+        description: None,
+        routing_config: routing(weights),
+        revision_id: None,
+    }
+}
+
+#[tokio::test]
+async fn aliases_only_shift_traffic_between_published_versions() {
+    let cp = control_plane().await;
+    cp.create_function(create_request("orders", None))
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        cp.publish_version("orders", publish()).await.unwrap();
+    }
+
+    let err = cp
+        .create_alias("orders", alias_request("7", None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+    for (function_version, weights) in [
+        ("1", vec![("1", 0.5)]),
+        ("1", vec![("2", 1.5)]),
+        ("1", vec![("2", f64::NAN)]),
+        ("1", vec![("2", 0.1), ("3", 0.1)]),
+        (LATEST_VERSION, vec![("2", 0.1)]),
+        ("1", vec![(LATEST_VERSION, 0.1)]),
+    ] {
+        let err = cp
+            .create_alias("orders", alias_request(function_version, routing(&weights)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.http_status(), 400, "{function_version} {weights:?}");
+    }
+    let err = cp
+        .create_alias("orders", alias_request("1", routing(&[("9", 0.1)])))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 404);
+
+    cp.create_alias("orders", alias_request("1", None))
+        .await
+        .unwrap();
+    let err = cp
+        .update_alias("orders", "live", shift_to(&[("1", 0.2)]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    let alias = cp
+        .update_alias("orders", "live", shift_to(&[("2", 0.5)]))
+        .await
+        .unwrap();
+    assert_eq!(
+        alias.routing_config.unwrap().additional_version_weights["2"],
+        0.5
+    );
+
+    // Invocations through the alias land on both versions
+    let latest = cp.get_function("orders").await.unwrap();
+    let mut executed = std::collections::BTreeSet::new();
+    for _ in 0..200 {
+        let function = cp
+            .resolve_qualifier(latest.clone(), Some("live"))
+            .await
+            .unwrap();
+        executed.insert(function.version);
+    }
+    assert_eq!(executed, ["1".to_string(), "2".to_string()].into());
+}
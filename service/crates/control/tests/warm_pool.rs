@@ -19,6 +19,7 @@ async fn warm_pool_basic_operations() {
         instance_id: "inst-1".to_string(),
         function_id: Uuid::new_v4(),
         image_ref: "test-image".to_string(),
+        memory_mb: 128,
         created_at: Instant::now(),
         last_used: Instant::now(),
         state: lambda_control::warm_pool::InstanceState::WarmIdle,
@@ -62,6 +63,7 @@ async fn drain_by_function_id_removes_all() {
             instance_id: format!("inst-{}", i + 1),
             function_id: fid,
             image_ref: "img".into(),
+            memory_mb: 128,
             created_at: Instant::now(),
             last_used: Instant::now(),
             state: lambda_control::warm_pool::InstanceState::WarmIdle,
@@ -74,3 +76,150 @@ async fn drain_by_function_id_removes_all() {
     assert_eq!(pool.container_count(&key_a).await, 0);
     assert_eq!(pool.container_count(&key_b).await, 0);
 }
+
+#[tokio::test]
+async fn prewarming_instance_stays_initializing() {
+    use lambda_control::warm_pool::InstanceState;
+
+    let pool = WarmPool::new();
+    let key = FnKey {
+        function_name: "fn-a".into(),
+        runtime: "nodejs18.x".into(),
+        version: "LATEST".into(),
+        env_hash: "h1".into(),
+    };
+    pool.add_warm_container(
+        key.clone(),
+        WarmContainer {
+            container_id: "c1".into(),
+            instance_id: "i1".into(),
+            function_id: Uuid::new_v4(),
+            image_ref: "img".into(),
+            memory_mb: 128,
+            created_at: Instant::now(),
+            last_used: Instant::now(),
+            state: InstanceState::Initializing,
+        },
+    )
+    .await;
+
+    // The prewarm invocation still gets assigned to the container
+    assert_eq!(
+        pool.mark_active_by_instance("i1").await,
+        Some((key.clone(), "c1".to_string()))
+    );
+    assert!(pool.mark_idle_by_instance("i1").await.is_some());
+    assert_eq!(pool.count_state(&key, InstanceState::Initializing).await, 1);
+    assert_eq!(pool.count_state(&key, InstanceState::WarmIdle).await, 0);
+}
+
+#[tokio::test]
+async fn provisioned_containers_survive_idle_cleanup() {
+    use lambda_control::warm_pool::InstanceState;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    let pool = WarmPool::new();
+    let key = FnKey {
+        function_name: "fn-a".into(),
+        runtime: "nodejs18.x".into(),
+        version: "1".into(),
+        env_hash: "h1".into(),
+    };
+    for (i, idle_secs) in [30, 10, 20].into_iter().enumerate() {
+        pool.add_warm_container(
+            key.clone(),
+            WarmContainer {
+                container_id: format!("c{i}"),
+                instance_id: format!("i{i}"),
+                function_id: Uuid::new_v4(),
+                image_ref: "img".into(),
+                memory_mb: 128,
+                created_at: Instant::now(),
+                last_used: Instant::now() - Duration::from_secs(idle_secs),
+                state: InstanceState::WarmIdle,
+            },
+        )
+        .await;
+    }
+    pool.set_provisioned(HashMap::from([(key.clone(), 2)]));
+
+    // The most recently used containers are the provisioned ones
+    assert_eq!(
+        pool.list_soft_idle_containers(Duration::from_secs(1)).await,
+        ["c0"]
+    );
+    assert_eq!(
+        pool.cleanup_idle_containers(Duration::from_secs(1), Duration::from_secs(1))
+            .await,
+        ["c0"]
+    );
+    assert_eq!(pool.count_running(&key).await, 2);
+
+    pool.set_provisioned(HashMap::new());
+    assert_eq!(
+        pool.cleanup_idle_containers(Duration::from_secs(1), Duration::from_secs(1))
+            .await
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn function_lifecycle_overrides_idle_timeout_and_caps_warm_containers() {
+    use lambda_control::warm_pool::InstanceState;
+    use lambda_models::FunctionLifecycle;
+    use std::time::Duration;
+
+    let pool = WarmPool::new();
+    let key = FnKey {
+        function_name: "fn-a".into(),
+        runtime: "nodejs18.x".into(),
+        version: "LATEST".into(),
+        env_hash: "h1".into(),
+    };
+    let function_id = Uuid::new_v4();
+    for (i, idle_secs) in [30, 10, 20].into_iter().enumerate() {
+        pool.add_warm_container(
+            key.clone(),
+            WarmContainer {
+                container_id: format!("c{i}"),
+                instance_id: format!("i{i}"),
+                function_id,
+                image_ref: "img".into(),
+                memory_mb: 128,
+                created_at: Instant::now(),
+                last_used: Instant::now() - Duration::from_secs(idle_secs),
+                state: InstanceState::WarmIdle,
+            },
+        )
+        .await;
+    }
+    let soft_idle = Duration::from_secs(60);
+
+    // Nothing is idle past the global timeout
+    assert!(pool.list_soft_idle_containers(soft_idle).await.is_empty());
+
+    // Its own timeout stops the containers idle for longer
+    pool.set_lifecycle(
+        function_id,
+        Some(FunctionLifecycle {
+            idle_timeout_secs: Some(15),
+            ..Default::default()
+        }),
+    );
+    let mut idle = pool.list_soft_idle_containers(soft_idle).await;
+    idle.sort();
+    assert_eq!(idle, ["c0", "c2"]);
+
+    // Past the maximum, the least recently used idle containers are stopped
+    pool.set_lifecycle(
+        function_id,
+        Some(FunctionLifecycle {
+            max_warm_containers: Some(2),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(pool.list_soft_idle_containers(soft_idle).await, ["c0"]);
+    assert_eq!(pool.warm_room(function_id).await, Some(0));
+}
@@ -0,0 +1,142 @@
+use lambda_control::migrations::run_migrations;
+use lambda_control::queues::FnKey;
+use lambda_control::warm_pool::{InstanceState, WarmContainer, WarmPool};
+use lambda_control::warm_pool_snapshot::{
+    restore_action, RestoreAction, SnapshotEntry, WarmPoolSnapshot,
+};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+fn key(function_name: &str) -> FnKey {
+    FnKey {
+        function_name: function_name.to_string(),
+        runtime: "nodejs22.x".to_string(),
+        version: "LATEST".to_string(),
+        env_hash: "abc".to_string(),
+    }
+}
+
+fn container(container_id: &str, state: InstanceState) -> WarmContainer {
+    WarmContainer {
+        container_id: container_id.to_string(),
+        instance_id: format!("inst-{container_id}"),
+        function_id: Uuid::new_v4(),
+        image_ref: "lambda-home/orders:sha".to_string(),
+        memory_mb: 256,
+        created_at: Instant::now(),
+        last_used: Instant::now(),
+        state,
+    }
+}
+
+async fn snapshot() -> WarmPoolSnapshot {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    WarmPoolSnapshot::new(pool)
+}
+
+#[tokio::test]
+async fn saves_and_loads_membership() {
+    let snapshot = snapshot().await;
+    let pool = WarmPool::new();
+    let idle = container("c1", InstanceState::WarmIdle);
+    pool.add_warm_container(key("orders"), idle.clone()).await;
+    pool.add_warm_container(key("orders"), container("c2", InstanceState::Stopped))
+        .await;
+    assert_eq!(snapshot.save(&pool).await.unwrap(), 2);
+
+    let mut entries = snapshot.load().await.unwrap();
+    entries.sort_by(|a, b| a.container_id.cmp(&b.container_id));
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].key, key("orders"));
+    assert_eq!(entries[0].instance_id, "inst-c1");
+    assert_eq!(entries[0].function_id, idle.function_id);
+    assert_eq!(entries[0].memory_mb, 256);
+    assert_eq!(entries[0].state, InstanceState::WarmIdle);
+    assert_eq!(entries[1].state, InstanceState::Stopped);
+
+    // Each save replaces the previous snapshot
+    pool.remove_container_by_id("c1").await.unwrap();
+    snapshot.save(&pool).await.unwrap();
+    assert_eq!(snapshot.load().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn writer_saves_after_changes() {
+    let snapshot = snapshot().await;
+    let pool = Arc::new(WarmPool::new());
+    tokio::spawn(snapshot.clone().run(pool.clone()));
+
+    pool.add_warm_container(key("orders"), container("c1", InstanceState::WarmIdle))
+        .await;
+    pool.mark_active_by_instance("inst-c1").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(600)).await;
+
+    let entries = snapshot.load().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].state, InstanceState::Active);
+}
+
+#[test]
+fn restore_reconciles_with_docker_and_the_function() {
+    let c = container("c1", InstanceState::Active);
+    let entry = SnapshotEntry {
+        key: key("orders"),
+        container_id: c.container_id,
+        instance_id: c.instance_id,
+        function_id: c.function_id,
+        image_ref: c.image_ref,
+        memory_mb: c.memory_mb,
+        state: c.state,
+    };
+    let current = Some(("lambda-home/orders:sha", "abc"));
+
+    // In-flight work died with the previous run
+    assert_eq!(
+        restore_action(&entry, Some(true), current),
+        RestoreAction::Adopt(InstanceState::WarmIdle)
+    );
+    assert_eq!(
+        restore_action(&entry, Some(false), current),
+        RestoreAction::Adopt(InstanceState::Stopped)
+    );
+    assert_eq!(restore_action(&entry, None, current), RestoreAction::Forget);
+
+    // Deleted function, new code or new environment
+    assert_eq!(
+        restore_action(&entry, Some(true), None),
+        RestoreAction::Remove
+    );
+    assert_eq!(
+        restore_action(&entry, Some(true), Some(("lambda-home/orders:new", "abc"))),
+        RestoreAction::Remove
+    );
+    assert_eq!(
+        restore_action(&entry, Some(true), Some(("lambda-home/orders:sha", "def"))),
+        RestoreAction::Remove
+    );
+
+    let failed = SnapshotEntry {
+        state: InstanceState::Failed,
+        ..entry
+    };
+    assert_eq!(
+        restore_action(&failed, Some(true), current),
+        RestoreAction::Remove
+    );
+}
+
+#[test]
+fn instance_states_round_trip() {
+    for state in [
+        InstanceState::WarmIdle,
+        InstanceState::Active,
+        InstanceState::Stopped,
+        InstanceState::Terminated,
+    ] {
+        assert_eq!(state.as_str().parse::<InstanceState>().unwrap(), state);
+    }
+    assert!("Sleeping".parse::<InstanceState>().is_err());
+}
@@ -0,0 +1,38 @@
+use lambda_control::warmup_stats::{StartKind, WarmupTracker};
+
+#[test]
+fn tracks_per_function_and_global_hit_rate() {
+    let tracker = WarmupTracker::new();
+    tracker.record("a", StartKind::Cold);
+    tracker.record("a", StartKind::Warm);
+    tracker.record("a", StartKind::Warm);
+    tracker.record("a", StartKind::Warm);
+    tracker.record("b", StartKind::Restarted);
+
+    let stats = tracker.snapshot();
+    assert_eq!(stats.global.total(), 5);
+    assert_eq!(stats.global.warm_starts, 3);
+    assert!((stats.global.warm_hit_rate - 0.6).abs() < f64::EPSILON);
+
+    let names: Vec<_> = stats
+        .functions
+        .iter()
+        .map(|f| f.function_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["a", "b"]);
+    assert_eq!(stats.functions[0].counts.cold_starts, 1);
+    assert!((stats.functions[0].counts.warm_hit_rate - 0.75).abs() < f64::EPSILON);
+    assert_eq!(stats.functions[1].counts.restarted_starts, 1);
+    assert_eq!(stats.functions[1].counts.warm_hit_rate, 0.0);
+}
+
+#[test]
+fn removing_function_keeps_global_totals() {
+    let tracker = WarmupTracker::new();
+    tracker.record("gone", StartKind::Warm);
+    tracker.remove_function("gone");
+
+    let stats = tracker.snapshot();
+    assert!(stats.functions.is_empty());
+    assert_eq!(stats.global.warm_starts, 1);
+}
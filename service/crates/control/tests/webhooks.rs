@@ -0,0 +1,250 @@
+use crate::common::{control_plane_and_pool, control_plane_with, create_request, test_config};
+use lambda_control::registry::ControlPlane;
+use lambda_control::webhooks::{
+    is_retryable_status, retry_backoff, sign_payload, subscribes_to, SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+};
+use lambda_models::{
+    Config, CreateWebhookRequest, DeliveryStatus, WebhookDelivery, WebhookEventType, WebhooksConfig,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+#[test]
+fn signs_timestamp_and_body() {
+    assert_eq!(
+        sign_payload("whsec", 1_700_000_000, br#"{"ok":true}"#),
+        "sha256=8fff954f80a855c2adb24ba36c6f6d47d926481b848e42bd0cda22c562a7f436"
+    );
+}
+
+#[test]
+fn retries_back_off_exponentially_up_to_a_cap() {
+    let config = WebhooksConfig {
+        max_attempts: 10,
+        initial_backoff_ms: 1000,
+        timeout_ms: 1000,
+    };
+    assert_eq!(retry_backoff(&config, 1), Duration::from_secs(1));
+    assert_eq!(retry_backoff(&config, 3), Duration::from_secs(4));
+    assert_eq!(retry_backoff(&config, 9), Duration::from_secs(60));
+
+    assert!(is_retryable_status(503));
+    assert!(is_retryable_status(429));
+    assert!(!is_retryable_status(404));
+}
+
+#[test]
+fn empty_subscription_receives_every_event() {
+    assert!(subscribes_to(&[], WebhookEventType::AlertFired));
+    assert!(subscribes_to(
+        &[WebhookEventType::FunctionDeleted],
+        WebhookEventType::FunctionDeleted
+    ));
+    assert!(!subscribes_to(
+        &[WebhookEventType::FunctionDeleted],
+        WebhookEventType::FunctionCreated
+    ));
+    assert_eq!(
+        serde_json::to_string(&WebhookEventType::BuildFailed).unwrap(),
+        r#""build.failed""#
+    );
+    assert_eq!(
+        "alias.updated".parse::<WebhookEventType>().unwrap(),
+        WebhookEventType::AliasUpdated
+    );
+}
+
+/// Requests received by a local target that answers with `statuses` in turn, then 200
+async fn spawn_target(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    tokio::spawn(async move {
+        let mut statuses = statuses.into_iter();
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read headers, then as much body as Content-Length announces
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length: ")
+                                .map(str::to_string)
+                        })
+                        .and_then(|l| l.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            log.lock()
+                .unwrap()
+                .push(String::from_utf8_lossy(&request).to_string());
+            let status = statuses.next().unwrap_or(200);
+            let response = format!(
+                "HTTP/1.1 {status} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (url, received)
+}
+
+async fn settled_deliveries(cp: &ControlPlane, webhook_id: Uuid) -> Vec<WebhookDelivery> {
+    for _ in 0..200 {
+        let deliveries = cp
+            .list_webhook_deliveries(webhook_id, 50)
+            .await
+            .unwrap()
+            .deliveries;
+        if !deliveries.is_empty()
+            && deliveries
+                .iter()
+                .all(|d| d.status != DeliveryStatus::Pending)
+        {
+            return deliveries;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("webhook deliveries did not settle");
+}
+
+#[tokio::test]
+async fn delivers_signed_events_with_retries_and_logs_them() {
+    let mut config = test_config();
+    config.webhooks = WebhooksConfig {
+        max_attempts: 3,
+        initial_backoff_ms: 10,
+        timeout_ms: 2000,
+    };
+    let (cp, pool) = control_plane_and_pool(config.clone()).await;
+
+    let (flaky_url, flaky_requests) = spawn_target(vec![503]).await;
+    let flaky = cp
+        .create_webhook(CreateWebhookRequest {
+            url: flaky_url,
+            events: vec![WebhookEventType::FunctionCreated],
+            secret: Some("whsec".into()),
+            description: None,
+            enabled: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(flaky.secret, "whsec");
+
+    let (gone_url, _) = spawn_target(vec![410]).await;
+    let gone = cp
+        .create_webhook(CreateWebhookRequest {
+            url: gone_url,
+            events: vec![],
+            secret: None,
+            description: Some("catch-all".into()),
+            enabled: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(gone.secret.len(), 64);
+
+    let (deletes_url, deletes_requests) = spawn_target(vec![]).await;
+    cp.create_webhook(CreateWebhookRequest {
+        url: deletes_url,
+        events: vec![WebhookEventType::FunctionDeleted],
+        secret: None,
+        description: None,
+        enabled: None,
+    })
+    .await
+    .unwrap();
+    assert_eq!(cp.list_webhooks().await.unwrap().webhooks.len(), 3);
+
+    cp.create_function(create_request("hooked")).await.unwrap();
+
+    // A 503 is retried until the target accepts
+    let deliveries = settled_deliveries(&cp, flaky.webhook.webhook_id).await;
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].status, DeliveryStatus::Succeeded);
+    assert_eq!(deliveries[0].attempts, 2);
+    assert_eq!(deliveries[0].event_type, WebhookEventType::FunctionCreated);
+
+    let requests = flaky_requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    let request = &requests[1];
+    let header = |name: &str| {
+        request
+            .lines()
+            .find_map(|l| {
+                l.to_ascii_lowercase()
+                    .starts_with(&format!("{}:", name.to_ascii_lowercase()))
+                    .then(|| l.split_once(':').unwrap().1.trim().to_string())
+            })
+            .unwrap()
+    };
+    let body = request.split_once("\r\n\r\n").unwrap().1;
+    let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+    assert_eq!(
+        header(SIGNATURE_HEADER),
+        sign_payload("whsec", timestamp, body.as_bytes())
+    );
+    let event: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(event["event_type"], "function.created");
+    assert_eq!(event["function_name"], "hooked");
+
+    // A 4xx other than 408/429 is not retried
+    let deliveries = settled_deliveries(&cp, gone.webhook.webhook_id).await;
+    assert_eq!(deliveries[0].status, DeliveryStatus::Failed);
+    assert_eq!(deliveries[0].attempts, 1);
+    assert_eq!(deliveries[0].response_status, Some(410));
+
+    // Unsubscribed events are never sent
+    assert!(deletes_requests.lock().unwrap().is_empty());
+
+    cp.delete_webhook(gone.webhook.webhook_id).await.unwrap();
+    assert_eq!(
+        cp.get_webhook(gone.webhook.webhook_id)
+            .await
+            .unwrap_err()
+            .http_status(),
+        404
+    );
+    let remaining: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM webhook_deliveries WHERE webhook_id = ?")
+            .bind(gone.webhook.webhook_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn rejects_non_http_targets() {
+    let config = Config::default();
+    let cp = control_plane_with(config).await;
+
+    let err = cp
+        .create_webhook(CreateWebhookRequest {
+            url: "ftp://example.com/hook".into(),
+            events: vec![],
+            secret: None,
+            description: None,
+            enabled: None,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.http_status(), 400);
+}
@@ -42,7 +42,7 @@ impl ContainerManager {
         image_ref: &str,
         env_vars: HashMap<String, String>,
     ) -> Result<String, LambdaError> {
-        let container_id = self.invoker.create_container(function, image_ref, env_vars).await?;
+        let container_id = self.invoker.create_container(function, image_ref, env_vars, None).await?;
         
         let container_info = ContainerInfo {
             container_id: container_id.clone(),
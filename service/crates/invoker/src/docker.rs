@@ -2,20 +2,31 @@ use bollard::container::{
     Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions,
     StartContainerOptions, StopContainerOptions,
 };
-use bollard::image::{ListImagesOptions, RemoveImageOptions};
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecResults};
+use bollard::image::{
+    CreateImageOptions, ListImagesOptions, PruneImagesOptions, RemoveImageOptions,
+};
 use bollard::models::EventMessage;
+use bollard::network::InspectNetworkOptions;
 use bollard::Docker;
 // Unused imports removed - these types are re-exported by bollard::models
 
+use crate::host::{self, ContainerNetwork, DockerEndpoint, RuntimeApiAddress};
+use crate::retry::{DockerCallError, DockerRetry};
+use crate::sandbox::{SandboxProfiles, SandboxSupport};
 use async_trait::async_trait;
 use bollard::models::{ContainerCreateResponse, HostConfig, RestartPolicy, RestartPolicyNameEnum};
-use futures_util::StreamExt;
+use futures_util::{Stream, StreamExt};
 use lambda_models::{
-    Config as AppConfig, DockerDiskUsage, DockerStats, DockerSystemInfo,
-    DockerVersion as LambdaDockerVersion, Function, LambdaError,
+    Config as AppConfig, DockerApiStatus, DockerDiskUsage, DockerStats, DockerSystemInfo,
+    DockerVersion as LambdaDockerVersion, Function, FunctionUser, LambdaError, PackageType,
+    RuntimeApiSettings,
 };
 use std::collections::HashMap;
-use tokio::sync::mpsc;
+use std::pin::Pin;
+use std::sync::RwLock;
+use tokio::io::AsyncWrite;
+use tokio::sync::{mpsc, OnceCell};
 use tracing::{error, info, instrument, warn};
 
 #[derive(Clone, Debug)]
@@ -43,6 +54,19 @@ pub enum ContainerEvent {
 
 pub type ContainerEventSender = mpsc::UnboundedSender<ContainerEvent>;
 
+/// Output and input of an attached exec
+pub struct ExecAttachment {
+    pub exec_id: String,
+    pub output: Pin<Box<dyn Stream<Item = Result<bytes::Bytes, LambdaError>> + Send>>,
+    pub input: Pin<Box<dyn AsyncWrite + Send>>,
+}
+
+fn exec_error(e: bollard::errors::Error) -> LambdaError {
+    LambdaError::DockerError {
+        message: e.to_string(),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CreateSpec {
     pub image: String,
@@ -94,37 +118,155 @@ pub struct Invoker {
     docker: Docker,
     config: AppConfig,
     event_sender: Option<ContainerEventSender>,
+    sandbox: SandboxProfiles,
+    /// Whether the daemon is Docker Desktop, asked on the first container created
+    docker_desktop: OnceCell<bool>,
+    /// Runtime API settings set through the admin API; None uses `[docker]`
+    runtime_api: RwLock<Option<RuntimeApiSettings>>,
+    /// Retries and circuit breaker of container calls
+    retry: DockerRetry,
 }
 
 impl Invoker {
     pub async fn new(config: AppConfig) -> Result<Self, LambdaError> {
-        let docker = if let Ok(docker_host) = std::env::var("DOCKER_HOST") {
-            // Use DOCKER_HOST environment variable if available (for CI/Docker-in-Docker)
-            if docker_host.starts_with("tcp://") {
-                Docker::connect_with_http(&docker_host, 120, bollard::API_DEFAULT_VERSION)
-                    .map_err(|e| LambdaError::DockerError {
-                        message: format!("Failed to connect to Docker at {docker_host}: {e}"),
-                    })?
-            } else {
-                // Fallback to socket connection
-                Docker::connect_with_socket_defaults().map_err(|e| LambdaError::DockerError {
-                    message: e.to_string(),
-                })?
-            }
-        } else {
-            // Default to Unix socket connection
-            Docker::connect_with_socket_defaults().map_err(|e| LambdaError::DockerError {
-                message: e.to_string(),
-            })?
-        };
+        let sandbox = SandboxProfiles::load(&config.sandbox)?;
+        let docker_host = std::env::var("DOCKER_HOST").ok();
+        let docker =
+            DockerEndpoint::resolve(&config.docker.host, docker_host.as_deref())?.connect()?;
+        let retry = DockerRetry::new(config.docker.retry.clone());
 
         Ok(Self {
             docker,
             config,
             event_sender: None,
+            sandbox,
+            docker_desktop: OnceCell::new(),
+            runtime_api: RwLock::new(None),
+            retry,
         })
     }
 
+    /// Retries of container calls and whether they are suspended
+    pub fn docker_api_status(&self) -> DockerApiStatus {
+        self.retry.status()
+    }
+
+    async fn is_docker_desktop(&self) -> bool {
+        *self
+            .docker_desktop
+            .get_or_init(|| async {
+                match self.docker.info().await {
+                    Ok(info) => host::is_docker_desktop(&info.operating_system.unwrap_or_default()),
+                    Err(e) => {
+                        warn!(
+                            "Failed to get Docker system info, mapping host-gateway: {}",
+                            e
+                        );
+                        false
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Runtime API settings in effect, and whether they were set through
+    /// [`Self::set_runtime_api`] rather than read from the config file
+    pub fn runtime_api_settings(&self) -> (RuntimeApiSettings, bool) {
+        match &*self.runtime_api.read().unwrap_or_else(|e| e.into_inner()) {
+            Some(settings) => (settings.clone(), true),
+            None => (self.config.docker.runtime_api_settings(), false),
+        }
+    }
+
+    /// Give containers created from now on another way to the runtime API, or the config
+    /// file's again with None. Containers already running keep theirs. Fails, keeping the
+    /// current settings, when the network cannot be found.
+    #[instrument(skip(self))]
+    pub async fn set_runtime_api(
+        &self,
+        settings: Option<RuntimeApiSettings>,
+    ) -> Result<RuntimeApiAddress, LambdaError> {
+        let effective = match &settings {
+            Some(settings) => {
+                settings.validate()?;
+                settings.clone()
+            }
+            None => self.config.docker.runtime_api_settings(),
+        };
+        let address = self.resolve_runtime_api(&effective).await?;
+        *self.runtime_api.write().unwrap_or_else(|e| e.into_inner()) = settings;
+        info!(
+            "Runtime API advertised to new containers as {}",
+            address.advertised
+        );
+        Ok(address)
+    }
+
+    /// How a container created now reaches the runtime API. A custom network is inspected
+    /// each time, as it may have been re-created with another subnet.
+    pub async fn runtime_api_address(&self) -> Result<RuntimeApiAddress, LambdaError> {
+        let (settings, _) = self.runtime_api_settings();
+        self.resolve_runtime_api(&settings).await
+    }
+
+    async fn resolve_runtime_api(
+        &self,
+        settings: &RuntimeApiSettings,
+    ) -> Result<RuntimeApiAddress, LambdaError> {
+        let network = match settings.network.as_deref().map(str::trim) {
+            None | Some("" | "bridge" | "default") => ContainerNetwork::Bridge,
+            Some("host") => ContainerNetwork::Host,
+            Some(name) => ContainerNetwork::Custom {
+                name: name.to_string(),
+                gateway: self.network_gateway(name).await?,
+            },
+        };
+        Ok(host::runtime_api_address(
+            settings,
+            self.config.server.port_runtime_api,
+            &network,
+            self.is_docker_desktop().await,
+        ))
+    }
+
+    /// IPv4 gateway of a user-defined network: the host's address on it
+    async fn network_gateway(&self, name: &str) -> Result<Option<String>, LambdaError> {
+        let network = self
+            .docker
+            .inspect_network(name, None::<InspectNetworkOptions<String>>)
+            .await
+            .map_err(|e| LambdaError::InvalidRequest {
+                reason: format!("Cannot use Docker network {name}: {e}"),
+            })?;
+        Ok(network
+            .ipam
+            .and_then(|ipam| ipam.config)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|config| config.gateway)
+            .find(|gateway| gateway.parse::<std::net::Ipv4Addr>().is_ok()))
+    }
+
+    /// Sandbox profiles containers can be created under
+    pub fn sandbox_profiles(&self) -> &SandboxProfiles {
+        &self.sandbox
+    }
+
+    /// Whether the Docker host can apply seccomp and AppArmor profiles
+    #[instrument(skip(self))]
+    pub async fn sandbox_support(&self) -> Result<SandboxSupport, LambdaError> {
+        let info = self
+            .docker
+            .info()
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: format!("Failed to get Docker system info: {e}"),
+            })?;
+        Ok(SandboxSupport::from_security_options(
+            &info.security_options.unwrap_or_default(),
+        ))
+    }
+
     pub fn with_event_sender(mut self, sender: ContainerEventSender) -> Self {
         self.event_sender = Some(sender);
         self
@@ -223,23 +365,44 @@ impl Invoker {
         function: &Function,
         image_ref: &str,
         env_vars: HashMap<String, String>,
+        stop_grace_seconds: Option<u64>,
+        sandbox_profile: &str,
+        user: &FunctionUser,
     ) -> Result<String, LambdaError> {
+        let sandbox =
+            self.sandbox
+                .get(sandbox_profile)
+                .ok_or_else(|| LambdaError::InvalidRequest {
+                    reason: format!("Unknown sandbox profile: {sandbox_profile}"),
+                })?;
         let container_name = format!("lambda-{}-{}", function.function_name, uuid::Uuid::new_v4());
 
         // Build environment variables
-        let runtime_api = format!("host.docker.internal:{}", self.config.server.port_runtime_api);
+        let address = self.runtime_api_address().await?;
+        let runtime_api = address.advertised;
         let mut env = vec![
             format!("AWS_LAMBDA_RUNTIME_API={}", runtime_api),
             "AWS_LAMBDA_FUNCTION_NAME=".to_string() + &function.function_name,
             "AWS_LAMBDA_FUNCTION_VERSION=".to_string() + &function.version,
             "AWS_LAMBDA_FUNCTION_MEMORY_SIZE=".to_string() + &function.memory_size.to_string(),
+            // Read by the bootstraps to find the module and function to load
+            "AWS_LAMBDA_FUNCTION_HANDLER=".to_string() + &function.handler,
             "AWS_LAMBDA_LOG_GROUP_NAME=/aws/lambda/".to_string() + &function.function_name,
             "AWS_LAMBDA_LOG_STREAM_NAME=".to_string() + &uuid::Uuid::new_v4().to_string(),
             "AWS_LAMBDA_RUNTIME_DIR=/var/runtime".to_string(),
             "LAMBDA_TASK_ROOT=/var/task".to_string(),
             "LAMBDA_RUNTIME_DIR=/var/runtime".to_string(),
-            "TZ=UTC".to_string(),
+            // Endpoint of the embedded SQS-compatible queues
+            format!("LAMBDAH_SQS_ENDPOINT=http://{}", runtime_api),
         ];
+        // UTC unless the function has its own timezone
+        if !env_vars.contains_key("TZ") {
+            env.push("TZ=UTC".to_string());
+        }
+        // Other users have no home in the image, and only /tmp is writable
+        if *user != FunctionUser::default() && !env_vars.contains_key("HOME") {
+            env.push("HOME=/tmp".to_string());
+        }
 
         // Add custom environment variables
         for (key, value) in env_vars {
@@ -264,9 +427,10 @@ impl Invoker {
             }),
             cap_drop: Some(vec!["ALL".to_string()]),
             cap_add: None,
-            security_opt: Some(vec!["no-new-privileges:true".to_string()]),
+            security_opt: Some(sandbox.security_opt()),
             // Add host mapping for Runtime API connectivity
-            extra_hosts: Some(vec!["host.docker.internal:host-gateway".to_string()]),
+            extra_hosts: address.extra_hosts,
+            network_mode: address.network_mode,
             ..Default::default()
         };
 
@@ -274,8 +438,14 @@ impl Invoker {
             image: Some(image_ref.to_string()),
             env: Some(env),
             host_config: Some(host_config),
-            working_dir: Some("/var/task".to_string()),
-            user: Some("1000:1000".to_string()), // Non-root user
+            // Pre-built images run from their own working directory
+            working_dir: (function.package_type == PackageType::Zip)
+                .then(|| "/var/task".to_string()),
+            user: Some(user.docker_user()),
+            // Time between SIGTERM and SIGKILL on `docker stop`
+            stop_timeout: Some(
+                stop_grace_seconds.unwrap_or(self.config.defaults.stop_grace_seconds) as i64,
+            ),
             ..Default::default()
         };
 
@@ -284,13 +454,15 @@ impl Invoker {
             ..Default::default()
         };
 
+        // Not retried: a create that failed in transit may still have made the container,
+        // and creating it again would clash with its name
         let response: ContainerCreateResponse = self
-            .docker
-            .create_container(Some(options), container_config)
-            .await
-            .map_err(|e| LambdaError::DockerError {
-                message: e.to_string(),
-            })?;
+            .retry
+            .call_once("create", || {
+                self.docker
+                    .create_container(Some(options.clone()), container_config.clone())
+            })
+            .await?;
 
         info!(
             "Created container: {} with ID: {}",
@@ -305,12 +477,12 @@ impl Invoker {
             ..Default::default()
         };
 
-        self.docker
-            .start_container(container_id, Some(options))
-            .await
-            .map_err(|e| LambdaError::DockerError {
-                message: e.to_string(),
-            })?;
+        self.retry
+            .call("start", || {
+                self.docker
+                    .start_container(container_id, Some(options.clone()))
+            })
+            .await?;
 
         info!("Started container: {}", container_id);
         Ok(())
@@ -318,16 +490,13 @@ impl Invoker {
 
     #[instrument(skip(self))]
     pub async fn stop_container(&self, container_id: &str) -> Result<(), LambdaError> {
-        let options = StopContainerOptions {
-            t: 10, // 10 second grace period
-        };
-
-        self.docker
-            .stop_container(container_id, Some(options))
-            .await
-            .map_err(|e| LambdaError::DockerError {
-                message: e.to_string(),
-            })?;
+        // No explicit timeout, so Docker waits the stop grace period set at creation
+        self.retry
+            .call("stop", || {
+                self.docker
+                    .stop_container(container_id, None::<StopContainerOptions>)
+            })
+            .await?;
 
         info!("Stopped container: {}", container_id);
         Ok(())
@@ -340,12 +509,11 @@ impl Invoker {
             ..Default::default()
         };
 
-        self.docker
-            .remove_container(container_id, Some(options))
-            .await
-            .map_err(|e| LambdaError::DockerError {
-                message: e.to_string(),
-            })?;
+        self.retry
+            .call("remove", || {
+                self.docker.remove_container(container_id, Some(options))
+            })
+            .await?;
 
         info!("Removed container: {}", container_id);
         Ok(())
@@ -426,6 +594,89 @@ impl Invoker {
         }
     }
 
+    /// Whether a container is running, or `None` when Docker no longer knows it
+    #[instrument(skip(self))]
+    pub async fn container_running(&self, container_id: &str) -> Result<Option<bool>, LambdaError> {
+        match self
+            .retry
+            .call("inspect", || {
+                self.docker.inspect_container(container_id, None)
+            })
+            .await
+        {
+            Ok(container) => Ok(Some(
+                container
+                    .state
+                    .is_some_and(|state| state.running.unwrap_or(false)),
+            )),
+            Err(DockerCallError::Failed(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404,
+                ..
+            })) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Start `command` in a running container with a TTY attached. The command is wrapped in
+    /// `timeout -s KILL`, so it is killed after `max_secs` even if nobody detaches.
+    #[instrument(skip(self))]
+    pub async fn exec_attached(
+        &self,
+        container_id: &str,
+        command: &str,
+        max_secs: u64,
+    ) -> Result<ExecAttachment, LambdaError> {
+        let max_secs = max_secs.max(1).to_string();
+        let exec = self
+            .docker
+            .create_exec(
+                container_id,
+                CreateExecOptions {
+                    attach_stdin: Some(true),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    tty: Some(true),
+                    cmd: Some(vec!["timeout", "-s", "KILL", max_secs.as_str(), command]),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(exec_error)?;
+        match self
+            .docker
+            .start_exec(&exec.id, None)
+            .await
+            .map_err(exec_error)?
+        {
+            StartExecResults::Attached { output, input } => Ok(ExecAttachment {
+                exec_id: exec.id,
+                output: Box::pin(
+                    output.map(|chunk| chunk.map(LogOutput::into_bytes).map_err(exec_error)),
+                ),
+                input,
+            }),
+            StartExecResults::Detached => Err(LambdaError::DockerError {
+                message: format!(
+                    "Exec {} in container {} did not attach",
+                    exec.id, container_id
+                ),
+            }),
+        }
+    }
+
+    /// Resize the TTY of an exec started by [`Self::exec_attached`]
+    pub async fn resize_exec(
+        &self,
+        exec_id: &str,
+        width: u16,
+        height: u16,
+    ) -> Result<(), LambdaError> {
+        self.docker
+            .resize_exec(exec_id, ResizeExecOptions { width, height })
+            .await
+            .map_err(exec_error)
+    }
+
     #[instrument(skip(self))]
     pub async fn get_docker_stats(&self) -> anyhow::Result<DockerStats> {
         // Get system info
@@ -553,27 +804,91 @@ impl Invoker {
                 .collect(),
         };
 
-        let docker_version = LambdaDockerVersion {
-            version: version.version.unwrap_or_default(),
-            api_version: version.api_version.unwrap_or_default(),
-            min_api_version: version.min_api_version.unwrap_or_default(),
-            git_commit: version.git_commit.unwrap_or_default(),
-            go_version: version.go_version.unwrap_or_default(),
-            os: version.os.unwrap_or_default(),
-            arch: version.arch.unwrap_or_default(),
-            kernel_version: version.kernel_version.unwrap_or_default(),
-            experimental: version.experimental.map(|e| e == "true").unwrap_or(false),
-            build_time: version.build_time.unwrap_or_default(),
-        };
-
         Ok(DockerStats {
             system_info: docker_system_info,
             disk_usage: docker_disk_usage,
-            version: docker_version,
+            version: to_docker_version(version),
             cache_stats: None, // Will be filled by the caller
         })
     }
 
+    #[instrument(skip(self))]
+    pub async fn docker_version(&self) -> Result<LambdaDockerVersion, LambdaError> {
+        let version = self
+            .docker
+            .version()
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: format!("Failed to get Docker version: {e}"),
+            })?;
+        Ok(to_docker_version(version))
+    }
+
+    /// Pull `image` unless it is already present
+    #[instrument(skip(self))]
+    pub async fn ensure_image(&self, image: &str) -> Result<(), LambdaError> {
+        if self.docker.inspect_image(image).await.is_ok() {
+            return Ok(());
+        }
+        let options = CreateImageOptions {
+            from_image: image,
+            ..Default::default()
+        };
+        let mut pull = self.docker.create_image(Some(options), None, None);
+        while let Some(progress) = pull.next().await {
+            progress.map_err(|e| LambdaError::DockerError {
+                message: format!("Failed to pull {image}: {e}"),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Run `cmd` in a throwaway container with the same host mapping function containers get,
+    /// returning its exit code and output. The container is removed afterwards.
+    #[instrument(skip(self))]
+    pub async fn run_probe(
+        &self,
+        image: &str,
+        cmd: Vec<String>,
+        timeout_ms: u64,
+    ) -> Result<(i64, String), LambdaError> {
+        let address = self.runtime_api_address().await?;
+        let container_config = Config {
+            image: Some(image.to_string()),
+            cmd: Some(cmd),
+            host_config: Some(HostConfig {
+                extra_hosts: address.extra_hosts,
+                network_mode: address.network_mode,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let options = CreateContainerOptions {
+            name: format!("lambdah-probe-{}", uuid::Uuid::new_v4()),
+            ..Default::default()
+        };
+        let container_id = self
+            .docker
+            .create_container(Some(options), container_config)
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: e.to_string(),
+            })?
+            .id;
+
+        let outcome = match self.start_container(&container_id).await {
+            Ok(()) => match self.wait_for_container(&container_id, timeout_ms).await {
+                Ok(exit_code) => Ok((exit_code, self.get_container_logs(&container_id).await?)),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = self.remove_container(&container_id).await {
+            warn!("Failed to remove probe container {}: {}", container_id, e);
+        }
+        outcome
+    }
+
     #[instrument(skip(self))]
     pub async fn remove_image(&self, image_ref: &str, force: bool) -> Result<(), LambdaError> {
         let options = RemoveImageOptions {
@@ -592,6 +907,34 @@ impl Invoker {
         Ok(())
     }
 
+    /// Remove dangling (untagged) images left behind by rebuilds; returns bytes reclaimed
+    #[instrument(skip(self))]
+    pub async fn prune_dangling_images(&self) -> Result<u64, LambdaError> {
+        let mut filters = HashMap::new();
+        filters.insert("dangling", vec!["true"]);
+        let response = self
+            .docker
+            .prune_images(Some(PruneImagesOptions { filters }))
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: format!("Failed to prune images: {e}"),
+            })?;
+        Ok(response.space_reclaimed.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Where the Docker daemon keeps images and containers
+    #[instrument(skip(self))]
+    pub async fn docker_root_dir(&self) -> Result<Option<String>, LambdaError> {
+        let info = self
+            .docker
+            .info()
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: format!("Failed to get Docker system info: {e}"),
+            })?;
+        Ok(info.docker_root_dir)
+    }
+
     #[instrument(skip(self))]
     pub async fn list_lambda_images(&self) -> Result<Vec<String>, LambdaError> {
         let options = ListImagesOptions::<String> {
@@ -623,6 +966,21 @@ impl Invoker {
     }
 }
 
+fn to_docker_version(version: bollard::system::Version) -> LambdaDockerVersion {
+    LambdaDockerVersion {
+        version: version.version.unwrap_or_default(),
+        api_version: version.api_version.unwrap_or_default(),
+        min_api_version: version.min_api_version.unwrap_or_default(),
+        git_commit: version.git_commit.unwrap_or_default(),
+        go_version: version.go_version.unwrap_or_default(),
+        os: version.os.unwrap_or_default(),
+        arch: version.arch.unwrap_or_default(),
+        kernel_version: version.kernel_version.unwrap_or_default(),
+        experimental: version.experimental.map(|e| e == "true").unwrap_or(false),
+        build_time: version.build_time.unwrap_or_default(),
+    }
+}
+
 #[async_trait]
 impl DockerLike for Invoker {
     async fn create(&self, spec: CreateSpec) -> anyhow::Result<String> {
@@ -657,18 +1015,28 @@ impl DockerLike for Invoker {
             ..Default::default()
         };
 
+        // Not retried, as in `create_container`: the name would clash with a container a
+        // failed attempt still made
         let response = self
-            .docker
-            .create_container(Some(create_options), config)
-            .await?;
+            .retry
+            .call_once("create", || {
+                self.docker
+                    .create_container(Some(create_options.clone()), config.clone())
+            })
+            .await
+            .map_err(LambdaError::from)?;
 
         Ok(response.id)
     }
 
     async fn start(&self, container_id: &str) -> anyhow::Result<()> {
-        self.docker
-            .start_container(container_id, None::<StartContainerOptions<String>>)
-            .await?;
+        self.retry
+            .call("start", || {
+                self.docker
+                    .start_container(container_id, None::<StartContainerOptions<String>>)
+            })
+            .await
+            .map_err(LambdaError::from)?;
         Ok(())
     }
 
@@ -676,9 +1044,12 @@ impl DockerLike for Invoker {
         let options = StopContainerOptions {
             t: timeout_secs as i64,
         };
-        self.docker
-            .stop_container(container_id, Some(options))
-            .await?;
+        self.retry
+            .call("stop", || {
+                self.docker.stop_container(container_id, Some(options))
+            })
+            .await
+            .map_err(LambdaError::from)?;
         Ok(())
     }
 
@@ -687,14 +1058,23 @@ impl DockerLike for Invoker {
             force,
             ..Default::default()
         };
-        self.docker
-            .remove_container(container_id, Some(options))
-            .await?;
+        self.retry
+            .call("remove", || {
+                self.docker.remove_container(container_id, Some(options))
+            })
+            .await
+            .map_err(LambdaError::from)?;
         Ok(())
     }
 
     async fn inspect_running(&self, container_id: &str) -> anyhow::Result<bool> {
-        let container = self.docker.inspect_container(container_id, None).await?;
+        let container = self
+            .retry
+            .call("inspect", || {
+                self.docker.inspect_container(container_id, None)
+            })
+            .await
+            .map_err(LambdaError::from)?;
         Ok(container
             .state
             .is_some_and(|state| state.running.unwrap_or(false)))
@@ -0,0 +1,172 @@
+use bollard::Docker;
+use lambda_models::{LambdaError, RuntimeApiSettings};
+
+/// Seconds a request to the Docker daemon may take
+const DOCKER_TIMEOUT_SECS: u64 = 120;
+
+/// Where Docker listens unless told otherwise
+#[cfg(unix)]
+pub const DEFAULT_DOCKER_HOST: &str = "unix:///var/run/docker.sock";
+#[cfg(windows)]
+pub const DEFAULT_DOCKER_HOST: &str = "npipe:////./pipe/docker_engine";
+
+/// Name function containers reach the runtime API on the host by
+pub const HOST_GATEWAY_NAME: &str = "host.docker.internal";
+
+/// Where the Docker daemon listens
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DockerEndpoint {
+    /// A Unix socket, or a named pipe on Windows, e.g. `npipe:////./pipe/docker_engine`
+    Local(String),
+    /// A daemon reached over plain TCP, as `tcp://` or `http://`
+    Http(String),
+}
+
+impl DockerEndpoint {
+    /// Endpoint of `[docker] host` when set, else of `DOCKER_HOST` (`env`), else the
+    /// platform's default: `/var/run/docker.sock`, or the `docker_engine` pipe on Windows.
+    /// Fails on a scheme the platform cannot connect with.
+    pub fn resolve(configured: &str, env: Option<&str>) -> Result<Self, LambdaError> {
+        let host = [Some(configured), env]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .find(|host| !host.is_empty())
+            .unwrap_or(DEFAULT_DOCKER_HOST);
+        let unsupported = |reason: &str| {
+            Err(LambdaError::DockerError {
+                message: format!("Cannot connect to Docker at {host}: {reason}"),
+            })
+        };
+        match host.split_once("://").map(|(scheme, _)| scheme) {
+            Some("tcp" | "http") => Ok(Self::Http(host.to_string())),
+            Some("unix") if cfg!(windows) => {
+                unsupported("Unix sockets are not supported on Windows, use npipe://")
+            }
+            Some("npipe") if cfg!(unix) => {
+                unsupported("named pipes are only supported on Windows, use unix://")
+            }
+            Some("unix" | "npipe") => Ok(Self::Local(host.to_string())),
+            Some(scheme) => unsupported(&format!("the {scheme}:// scheme is not supported")),
+            None => unsupported("expected a unix://, npipe://, tcp:// or http:// address"),
+        }
+    }
+
+    pub fn connect(&self) -> Result<Docker, LambdaError> {
+        let (address, docker) = match self {
+            Self::Local(address) => (
+                address,
+                Docker::connect_with_socket(
+                    address,
+                    DOCKER_TIMEOUT_SECS,
+                    bollard::API_DEFAULT_VERSION,
+                ),
+            ),
+            Self::Http(address) => (
+                address,
+                Docker::connect_with_http(
+                    address,
+                    DOCKER_TIMEOUT_SECS,
+                    bollard::API_DEFAULT_VERSION,
+                ),
+            ),
+        };
+        docker.map_err(|e| LambdaError::DockerError {
+            message: format!("Failed to connect to Docker at {address}: {e}"),
+        })
+    }
+}
+
+/// Whether `docker info` reports Docker Desktop, as on Windows and macOS hosts
+pub fn is_docker_desktop(operating_system: &str) -> bool {
+    operating_system.contains("Docker Desktop")
+}
+
+/// `extra_hosts` that let containers resolve [`HOST_GATEWAY_NAME`]: the configured address,
+/// else Docker's `host-gateway`. Docker Desktop resolves the name to the host by itself, also
+/// for servers bound to loopback, so nothing is added there.
+pub fn host_gateway_extra_hosts(
+    configured: Option<&str>,
+    docker_desktop: bool,
+) -> Option<Vec<String>> {
+    let address = match configured.map(str::trim).filter(|a| !a.is_empty()) {
+        Some(address) => address,
+        None if docker_desktop => return None,
+        None => "host-gateway",
+    };
+    Some(vec![format!("{HOST_GATEWAY_NAME}:{address}")])
+}
+
+/// Network function containers join, as far as reaching the host is concerned
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContainerNetwork {
+    /// Docker's default bridge
+    Bridge,
+    /// The host's own network stack, where the runtime API is on loopback
+    Host,
+    /// A user-defined network and its gateway, which is the host's address on it
+    Custom {
+        name: String,
+        gateway: Option<String>,
+    },
+}
+
+impl ContainerNetwork {
+    /// `network_mode` of containers on this network; None for the default bridge
+    pub fn mode(&self) -> Option<String> {
+        match self {
+            Self::Bridge => None,
+            Self::Host => Some("host".to_string()),
+            Self::Custom { name, .. } => Some(name.clone()),
+        }
+    }
+}
+
+/// How a function container reaches the runtime API
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeApiAddress {
+    /// `host:port` advertised in `AWS_LAMBDA_RUNTIME_API`
+    pub advertised: String,
+    pub extra_hosts: Option<Vec<String>>,
+    pub network_mode: Option<String>,
+}
+
+/// Where containers on `network` find the runtime API. An explicit host and port win; else
+/// containers on the host network use loopback, and the others [`HOST_GATEWAY_NAME`], mapped
+/// to `host_gateway` when set, else to the gateway of their custom network, else to Docker's
+/// `host-gateway`.
+pub fn runtime_api_address(
+    settings: &RuntimeApiSettings,
+    default_port: u16,
+    network: &ContainerNetwork,
+    docker_desktop: bool,
+) -> RuntimeApiAddress {
+    let port = settings.port.unwrap_or(default_port);
+    let configured_host = settings
+        .host
+        .as_deref()
+        .map(str::trim)
+        .filter(|h| !h.is_empty());
+    let (default_host, extra_hosts) = match network {
+        ContainerNetwork::Host => ("127.0.0.1", None),
+        ContainerNetwork::Bridge => (
+            HOST_GATEWAY_NAME,
+            host_gateway_extra_hosts(settings.host_gateway.as_deref(), docker_desktop),
+        ),
+        ContainerNetwork::Custom { gateway, .. } => (
+            HOST_GATEWAY_NAME,
+            host_gateway_extra_hosts(
+                settings
+                    .host_gateway
+                    .as_deref()
+                    .or(gateway.as_deref().filter(|_| !docker_desktop)),
+                docker_desktop,
+            ),
+        ),
+    };
+    RuntimeApiAddress {
+        advertised: format!("{}:{port}", configured_host.unwrap_or(default_host)),
+        extra_hosts,
+        network_mode: network.mode(),
+    }
+}
@@ -1,3 +1,9 @@
 pub mod docker;
+pub mod host;
+pub mod retry;
+pub mod sandbox;
 
 pub use docker::*;
+pub use host::*;
+pub use retry::*;
+pub use sandbox::*;
@@ -0,0 +1,234 @@
+use bollard::errors::Error as DockerApiError;
+use chrono::{DateTime, Utc};
+use lambda_models::{DockerApiStatus, DockerBreakerState, DockerRetryConfig, LambdaError};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// Whether a failed Docker call may succeed when made again: the daemon answered with a
+/// server error, or the connection broke or timed out
+pub fn is_transient(error: &DockerApiError) -> bool {
+    match error {
+        DockerApiError::DockerResponseServerError { status_code, .. } => *status_code >= 500,
+        DockerApiError::RequestTimeoutError
+        | DockerApiError::IOError { .. }
+        | DockerApiError::HyperResponseError { .. }
+        | DockerApiError::HyperLegacyError { .. } => true,
+        _ => false,
+    }
+}
+
+/// Delay before retry `retry`, counted from 1
+pub fn backoff(config: &DockerRetryConfig, retry: u32) -> Duration {
+    let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+    Duration::from_millis(
+        config
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(config.max_backoff_ms),
+    )
+}
+
+/// A Docker call that failed after its retries, or was not made because the breaker is open
+#[derive(Debug)]
+pub enum DockerCallError {
+    Failed(DockerApiError),
+    CircuitOpen { retry_at: DateTime<Utc> },
+}
+
+impl From<DockerCallError> for LambdaError {
+    fn from(e: DockerCallError) -> Self {
+        let message = match e {
+            DockerCallError::Failed(e) => e.to_string(),
+            DockerCallError::CircuitOpen { retry_at } => format!(
+                "Docker calls are suspended after repeated failures until {}",
+                retry_at.to_rfc3339()
+            ),
+        };
+        LambdaError::DockerError { message }
+    }
+}
+
+#[derive(Default)]
+struct Breaker {
+    state: DockerBreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<DateTime<Utc>>,
+    retry_at: Option<DateTime<Utc>>,
+}
+
+/// Retries Docker calls that failed transiently with exponential backoff, and stops making
+/// calls for a while once they keep failing anyway
+pub struct DockerRetry {
+    config: DockerRetryConfig,
+    breaker: Mutex<Breaker>,
+    retries: Mutex<BTreeMap<String, u64>>,
+    short_circuited: AtomicU64,
+}
+
+impl DockerRetry {
+    pub fn new(config: DockerRetryConfig) -> Self {
+        Self {
+            config,
+            breaker: Mutex::new(Breaker::default()),
+            retries: Mutex::new(BTreeMap::new()),
+            short_circuited: AtomicU64::new(0),
+        }
+    }
+
+    /// Make `call`, named `operation` in logs and metrics, until it succeeds, fails for good
+    /// or runs out of attempts
+    pub async fn call<T, F, Fut>(&self, operation: &str, call: F) -> Result<T, DockerCallError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, DockerApiError>>,
+    {
+        self.call_with_attempts(operation, self.config.max_attempts, call)
+            .await
+    }
+
+    /// Make `call` once, through the breaker but without retries, for calls whose failure
+    /// may still have taken effect, like creating a container under a fixed name
+    pub async fn call_once<T, F, Fut>(&self, operation: &str, call: F) -> Result<T, DockerCallError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, DockerApiError>>,
+    {
+        self.call_with_attempts(operation, 1, call).await
+    }
+
+    async fn call_with_attempts<T, F, Fut>(
+        &self,
+        operation: &str,
+        max_attempts: u32,
+        mut call: F,
+    ) -> Result<T, DockerCallError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, DockerApiError>>,
+    {
+        let _probe = self.admit()?;
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(value) => {
+                    self.record(true);
+                    return Ok(value);
+                }
+                Err(e) if is_transient(&e) && attempt < max_attempts => {
+                    let delay = backoff(&self.config, attempt);
+                    warn!(
+                        "Docker {} failed, retrying in {:?} ({}/{}): {}",
+                        operation,
+                        delay,
+                        attempt,
+                        self.config.max_attempts - 1,
+                        e
+                    );
+                    *self
+                        .retries
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .entry(operation.to_string())
+                        .or_default() += 1;
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    // The daemon answered a client error, so it is up
+                    self.record(!is_transient(&e));
+                    return Err(DockerCallError::Failed(e));
+                }
+            }
+        }
+    }
+
+    /// Let a call through unless the breaker is open. Once the open period is over, one call
+    /// goes through as the probe, holding the returned guard, while the others keep failing
+    /// until it settled whether the breaker closes
+    fn admit(&self) -> Result<Option<Probe<'_>>, DockerCallError> {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        let retry_at = match breaker.state {
+            DockerBreakerState::Closed => return Ok(None),
+            DockerBreakerState::Open => match breaker.retry_at {
+                Some(retry_at) if Utc::now() < retry_at => retry_at,
+                _ => {
+                    breaker.state = DockerBreakerState::HalfOpen;
+                    return Ok(Some(Probe { retry: self }));
+                }
+            },
+            DockerBreakerState::HalfOpen => breaker.retry_at.unwrap_or_else(Utc::now),
+        };
+        self.short_circuited.fetch_add(1, Ordering::Relaxed);
+        Err(DockerCallError::CircuitOpen { retry_at })
+    }
+
+    fn record(&self, success: bool) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        if success {
+            *breaker = Breaker::default();
+            return;
+        }
+        breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+        let threshold = self.config.breaker_threshold;
+        if threshold == 0 {
+            return;
+        }
+        if breaker.state == DockerBreakerState::HalfOpen
+            || (breaker.state == DockerBreakerState::Closed
+                && breaker.consecutive_failures >= threshold)
+        {
+            let now = Utc::now();
+            let open_for = i64::try_from(self.config.breaker_open_secs)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .unwrap_or(chrono::Duration::MAX);
+            let retry_at = now
+                .checked_add_signed(open_for)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            warn!(
+                "Docker calls failed {} times in a row, suspending them until {}",
+                breaker.consecutive_failures,
+                retry_at.to_rfc3339()
+            );
+            breaker.state = DockerBreakerState::Open;
+            breaker.opened_at = Some(now);
+            breaker.retry_at = Some(retry_at);
+        }
+    }
+
+    pub fn status(&self) -> DockerApiStatus {
+        let breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        DockerApiStatus {
+            state: breaker.state,
+            consecutive_failures: breaker.consecutive_failures,
+            opened_at: breaker.opened_at,
+            retry_at: breaker.retry_at,
+            retries: self
+                .retries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+            short_circuited_total: self.short_circuited.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The one call let through a half-open breaker. A probe dropped before its outcome was
+/// recorded, e.g. because its caller gave up, opens the breaker again for the next call to
+/// probe, rather than leaving it half-open for good
+struct Probe<'a> {
+    retry: &'a DockerRetry,
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        let mut breaker = self.retry.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        if breaker.state == DockerBreakerState::HalfOpen {
+            breaker.state = DockerBreakerState::Open;
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use lambda_models::{
+    LambdaError, SandboxConfig, SandboxProfile, DEFAULT_SANDBOX_PROFILE, STRICT_SANDBOX_PROFILE,
+};
+use std::collections::HashMap;
+
+/// Seccomp profile of the built-in `strict` sandbox: Docker's default denials, plus io_uring,
+/// new mount APIs, `chroot` and `syslog`
+const STRICT_SECCOMP: &str = include_str!("seccomp_strict.json");
+
+/// A sandbox profile with its seccomp JSON read, as Docker takes it inline
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedSandbox {
+    pub seccomp: Option<String>,
+    pub apparmor: Option<String>,
+}
+
+impl ResolvedSandbox {
+    /// `security_opt` of a container confined by this profile
+    pub fn security_opt(&self) -> Vec<String> {
+        let mut opts = vec!["no-new-privileges:true".to_string()];
+        if let Some(seccomp) = &self.seccomp {
+            opts.push(format!("seccomp={seccomp}"));
+        }
+        if let Some(apparmor) = &self.apparmor {
+            opts.push(format!("apparmor={apparmor}"));
+        }
+        opts
+    }
+}
+
+/// The built-in and configured sandbox profiles, read once at startup
+#[derive(Clone, Debug)]
+pub struct SandboxProfiles {
+    profiles: HashMap<String, ResolvedSandbox>,
+    default_profile: String,
+}
+
+impl SandboxProfiles {
+    /// Resolve `config`'s profiles, failing on a seccomp file that is missing or not JSON
+    pub fn load(config: &SandboxConfig) -> Result<Self, LambdaError> {
+        config.validate()?;
+        let mut profiles = HashMap::from([
+            (
+                DEFAULT_SANDBOX_PROFILE.to_string(),
+                ResolvedSandbox {
+                    seccomp: None,
+                    apparmor: None,
+                },
+            ),
+            (
+                STRICT_SANDBOX_PROFILE.to_string(),
+                ResolvedSandbox {
+                    seccomp: Some(compact_json(STRICT_SECCOMP, STRICT_SANDBOX_PROFILE)?),
+                    apparmor: None,
+                },
+            ),
+        ]);
+        for (name, profile) in &config.profiles {
+            profiles.insert(name.clone(), resolve(name, profile)?);
+        }
+        Ok(Self {
+            profiles,
+            default_profile: config.default_profile.clone(),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ResolvedSandbox> {
+        self.profiles.get(name)
+    }
+
+    /// Profile of functions that did not choose one
+    pub fn default_profile(&self) -> &str {
+        &self.default_profile
+    }
+
+    /// Profile names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+fn resolve(name: &str, profile: &SandboxProfile) -> Result<ResolvedSandbox, LambdaError> {
+    let seccomp = match &profile.seccomp {
+        Some(path) => {
+            let json = std::fs::read_to_string(path).map_err(|e| LambdaError::ConfigError {
+                reason: format!("sandbox profile {name}: cannot read seccomp profile {path}: {e}"),
+            })?;
+            Some(compact_json(&json, name)?)
+        }
+        None => None,
+    };
+    Ok(ResolvedSandbox {
+        seccomp,
+        apparmor: profile.apparmor.clone(),
+    })
+}
+
+/// `json` on one line, after checking it is a seccomp profile at all
+fn compact_json(json: &str, name: &str) -> Result<String, LambdaError> {
+    let invalid = |reason: String| LambdaError::ConfigError {
+        reason: format!("sandbox profile {name}: {reason}"),
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| invalid(format!("invalid seccomp JSON: {e}")))?;
+    if value
+        .get("defaultAction")
+        .and_then(|a| a.as_str())
+        .is_none()
+    {
+        return Err(invalid("seccomp profile has no defaultAction".to_string()));
+    }
+    Ok(value.to_string())
+}
+
+/// Confinement the Docker host offers, from the security options of `docker info`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SandboxSupport {
+    pub seccomp: bool,
+    pub apparmor: bool,
+    /// The daemon runs without root, so root in a container is the daemon's own user
+    pub rootless: bool,
+}
+
+impl SandboxSupport {
+    /// Read options such as `name=seccomp,profile=builtin` and `name=apparmor`
+    pub fn from_security_options(options: &[String]) -> Self {
+        let has = |feature: &str| {
+            options.iter().any(|option| {
+                option
+                    .split(',')
+                    .any(|part| part.strip_prefix("name=") == Some(feature))
+            })
+        };
+        Self {
+            seccomp: has("seccomp"),
+            apparmor: has("apparmor"),
+            rootless: has("rootless"),
+        }
+    }
+
+    /// Why the host cannot run containers under `profile`, if it cannot
+    pub fn unsupported(&self, profile: &ResolvedSandbox) -> Option<String> {
+        if profile.seccomp.is_some() && !self.seccomp {
+            return Some("the Docker host does not support seccomp".to_string());
+        }
+        if profile.apparmor.is_some() && !self.apparmor {
+            return Some("the Docker host does not support AppArmor".to_string());
+        }
+        None
+    }
+}
@@ -0,0 +1,129 @@
+use lambda_invoker::{
+    host_gateway_extra_hosts, is_docker_desktop, runtime_api_address, ContainerNetwork,
+    DockerEndpoint, DEFAULT_DOCKER_HOST,
+};
+use lambda_models::RuntimeApiSettings;
+
+#[test]
+fn the_configured_host_wins_over_docker_host() {
+    assert_eq!(
+        DockerEndpoint::resolve("tcp://10.0.0.2:2375", Some("tcp://127.0.0.1:2375")).unwrap(),
+        DockerEndpoint::Http("tcp://10.0.0.2:2375".into())
+    );
+    assert_eq!(
+        DockerEndpoint::resolve("  ", Some("http://127.0.0.1:2375")).unwrap(),
+        DockerEndpoint::Http("http://127.0.0.1:2375".into())
+    );
+    assert_eq!(
+        DockerEndpoint::resolve("", Some("")).unwrap(),
+        DockerEndpoint::Local(DEFAULT_DOCKER_HOST.into())
+    );
+}
+
+#[test]
+fn unknown_schemes_are_rejected() {
+    for host in [
+        "ssh://me@builder",
+        "https://docker:2376",
+        "/var/run/docker.sock",
+    ] {
+        let err = DockerEndpoint::resolve(host, None).unwrap_err();
+        assert!(err.to_string().contains(host), "{err}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_hosts_connect_through_sockets() {
+    assert_eq!(DEFAULT_DOCKER_HOST, "unix:///var/run/docker.sock");
+    let rootless = "unix:///run/user/1000/docker.sock";
+    assert_eq!(
+        DockerEndpoint::resolve("", Some(rootless)).unwrap(),
+        DockerEndpoint::Local(rootless.into())
+    );
+    let err = DockerEndpoint::resolve("npipe:////./pipe/docker_engine", None).unwrap_err();
+    assert!(
+        err.to_string().contains("only supported on Windows"),
+        "{err}"
+    );
+    // Connecting is lazy, so a socket that does not exist yet is fine
+    assert!(DockerEndpoint::Local(rootless.into()).connect().is_ok());
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_hosts_connect_through_named_pipes() {
+    assert_eq!(DEFAULT_DOCKER_HOST, "npipe:////./pipe/docker_engine");
+    let desktop = "npipe:////./pipe/dockerDesktopLinuxEngine";
+    assert_eq!(
+        DockerEndpoint::resolve(desktop, None).unwrap(),
+        DockerEndpoint::Local(desktop.into())
+    );
+    let err = DockerEndpoint::resolve("unix:///var/run/docker.sock", None).unwrap_err();
+    assert!(err.to_string().contains("use npipe://"), "{err}");
+}
+
+#[test]
+fn host_docker_internal_is_mapped_unless_docker_desktop_resolves_it() {
+    assert!(is_docker_desktop("Docker Desktop"));
+    assert!(!is_docker_desktop("Ubuntu 24.04.1 LTS"));
+
+    assert_eq!(
+        host_gateway_extra_hosts(None, false),
+        Some(vec!["host.docker.internal:host-gateway".to_string()])
+    );
+    assert_eq!(host_gateway_extra_hosts(None, true), None);
+    assert_eq!(host_gateway_extra_hosts(Some(" "), true), None);
+    assert_eq!(
+        host_gateway_extra_hosts(Some("192.168.65.254"), true),
+        Some(vec!["host.docker.internal:192.168.65.254".to_string()])
+    );
+}
+
+#[test]
+fn the_runtime_api_address_follows_the_network() {
+    let detected = RuntimeApiSettings::default();
+    let bridge = runtime_api_address(&detected, 9001, &ContainerNetwork::Bridge, false);
+    assert_eq!(bridge.advertised, "host.docker.internal:9001");
+    assert_eq!(
+        bridge.extra_hosts,
+        Some(vec!["host.docker.internal:host-gateway".to_string()])
+    );
+    assert_eq!(bridge.network_mode, None);
+
+    // On the host network the runtime API is on loopback
+    let host = runtime_api_address(&detected, 9001, &ContainerNetwork::Host, false);
+    assert_eq!(host.advertised, "127.0.0.1:9001");
+    assert_eq!(host.extra_hosts, None);
+    assert_eq!(host.network_mode.as_deref(), Some("host"));
+
+    // A custom network reaches the host through its own gateway
+    let custom = ContainerNetwork::Custom {
+        name: "functions".into(),
+        gateway: Some("172.18.0.1".into()),
+    };
+    let on_custom = runtime_api_address(&detected, 9001, &custom, false);
+    assert_eq!(
+        on_custom.extra_hosts,
+        Some(vec!["host.docker.internal:172.18.0.1".to_string()])
+    );
+    assert_eq!(on_custom.network_mode.as_deref(), Some("functions"));
+    assert_eq!(
+        runtime_api_address(&detected, 9001, &custom, true).extra_hosts,
+        None
+    );
+
+    // What is configured wins over what is detected
+    let configured = RuntimeApiSettings {
+        host: Some("10.0.0.5".into()),
+        port: Some(19001),
+        host_gateway: Some("10.0.0.5".into()),
+        network: Some("functions".into()),
+    };
+    let address = runtime_api_address(&configured, 9001, &custom, false);
+    assert_eq!(address.advertised, "10.0.0.5:19001");
+    assert_eq!(
+        address.extra_hosts,
+        Some(vec!["host.docker.internal:10.0.0.5".to_string()])
+    );
+}
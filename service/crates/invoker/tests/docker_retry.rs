@@ -0,0 +1,224 @@
+use bollard::errors::Error;
+use lambda_invoker::{backoff, is_transient, DockerCallError, DockerRetry};
+use lambda_models::{DockerBreakerState, DockerRetryConfig, LambdaError};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+fn server_error(status_code: u16) -> Error {
+    Error::DockerResponseServerError {
+        status_code,
+        message: "boom".to_string(),
+    }
+}
+
+fn config(max_attempts: u32, breaker_threshold: u32) -> DockerRetryConfig {
+    DockerRetryConfig {
+        max_attempts,
+        initial_backoff_ms: 1,
+        max_backoff_ms: 4,
+        breaker_threshold,
+        breaker_open_secs: 60,
+    }
+}
+
+#[test]
+fn server_and_connection_errors_are_transient() {
+    assert!(is_transient(&server_error(500)));
+    assert!(is_transient(&server_error(503)));
+    assert!(is_transient(&Error::RequestTimeoutError));
+    assert!(is_transient(&Error::IOError {
+        err: std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "eof"),
+    }));
+    assert!(!is_transient(&server_error(404)));
+    assert!(!is_transient(&server_error(409)));
+}
+
+#[test]
+fn backoff_doubles_up_to_the_maximum() {
+    let config = DockerRetryConfig {
+        initial_backoff_ms: 100,
+        max_backoff_ms: 350,
+        ..Default::default()
+    };
+    let delays: Vec<_> = (1..=4).map(|retry| backoff(&config, retry)).collect();
+    assert_eq!(
+        delays,
+        [100, 200, 350, 350].map(Duration::from_millis).to_vec()
+    );
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_until_the_call_succeeds() {
+    let retry = DockerRetry::new(config(3, 5));
+    let calls = AtomicU32::new(0);
+    let result = retry
+        .call("create", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(server_error(500)),
+                _ => Ok("abc"),
+            }
+        })
+        .await;
+    assert_eq!(result.unwrap(), "abc");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let status = retry.status();
+    assert_eq!(status.retries.get("create"), Some(&2));
+    assert_eq!(status.state, DockerBreakerState::Closed);
+    assert_eq!(status.consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried_and_do_not_trip_the_breaker() {
+    let retry = DockerRetry::new(config(3, 1));
+    let calls = AtomicU32::new(0);
+    let result: Result<(), _> = retry
+        .call("inspect", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(server_error(404))
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(DockerCallError::Failed(Error::DockerResponseServerError {
+            status_code: 404,
+            ..
+        }))
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(retry.status().state, DockerBreakerState::Closed);
+}
+
+#[tokio::test]
+async fn repeated_failures_open_the_breaker() {
+    let retry = DockerRetry::new(config(2, 2));
+    let calls = AtomicU32::new(0);
+    for _ in 0..2 {
+        let result: Result<(), _> = retry
+            .call("start", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(server_error(500))
+            })
+            .await;
+        assert!(matches!(result, Err(DockerCallError::Failed(_))));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    let status = retry.status();
+    assert_eq!(status.state, DockerBreakerState::Open);
+    assert_eq!(status.consecutive_failures, 2);
+    assert!(status.retry_at.is_some());
+
+    // Calls fail without reaching Docker until the open period is over
+    let result: Result<(), _> = retry
+        .call("start", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .await;
+    let err = result.unwrap_err();
+    assert!(matches!(err, DockerCallError::CircuitOpen { .. }));
+    assert!(matches!(
+        LambdaError::from(err),
+        LambdaError::DockerError { .. }
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    assert_eq!(retry.status().short_circuited_total, 1);
+}
+
+#[tokio::test]
+async fn a_trial_call_after_the_open_period_closes_the_breaker() {
+    let retry = DockerRetry::new(DockerRetryConfig {
+        breaker_open_secs: 0,
+        ..config(1, 1)
+    });
+    let failed: Result<(), _> = retry
+        .call("stop", || async { Err(server_error(502)) })
+        .await;
+    assert!(failed.is_err());
+    assert_eq!(retry.status().state, DockerBreakerState::Open);
+
+    retry.call("stop", || async { Ok(()) }).await.unwrap();
+    let status = retry.status();
+    assert_eq!(status.state, DockerBreakerState::Closed);
+    assert_eq!(status.retry_at, None);
+}
+
+#[tokio::test]
+async fn creates_are_not_retried() {
+    let retry = DockerRetry::new(config(3, 5));
+    let calls = AtomicU32::new(0);
+    let result: Result<(), _> = retry
+        .call_once("create", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(server_error(500))
+        })
+        .await;
+    assert!(matches!(result, Err(DockerCallError::Failed(_))));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let status = retry.status();
+    assert_eq!(status.retries.get("create"), None);
+    assert_eq!(status.consecutive_failures, 1);
+}
+
+#[tokio::test]
+async fn a_half_open_breaker_lets_one_trial_call_through() {
+    let retry = DockerRetry::new(DockerRetryConfig {
+        breaker_open_secs: 0,
+        ..config(1, 1)
+    });
+    let failed: Result<(), _> = retry
+        .call("stop", || async { Err(server_error(502)) })
+        .await;
+    assert!(failed.is_err());
+
+    let release = tokio::sync::Notify::new();
+    let trial = retry.call("stop", || async {
+        release.notified().await;
+        Ok(())
+    });
+    tokio::pin!(trial);
+    // Polling once lets the trial call through; it then waits for the release
+    assert!(futures::poll!(trial.as_mut()).is_pending());
+    assert_eq!(retry.status().state, DockerBreakerState::HalfOpen);
+    let others: Result<(), _> = retry.call("stop", || async { Ok(()) }).await;
+    assert!(matches!(others, Err(DockerCallError::CircuitOpen { .. })));
+
+    release.notify_one();
+    trial.await.unwrap();
+    assert_eq!(retry.status().state, DockerBreakerState::Closed);
+}
+
+#[tokio::test]
+async fn an_abandoned_trial_call_leaves_the_next_call_to_try() {
+    let retry = DockerRetry::new(DockerRetryConfig {
+        breaker_open_secs: 0,
+        ..config(1, 1)
+    });
+    let failed: Result<(), _> = retry
+        .call("stop", || async { Err(server_error(502)) })
+        .await;
+    assert!(failed.is_err());
+
+    let abandoned = tokio::time::timeout(
+        Duration::from_millis(10),
+        retry.call("stop", std::future::pending::<Result<(), Error>>),
+    )
+    .await;
+    assert!(abandoned.is_err());
+    assert_eq!(retry.status().state, DockerBreakerState::Open);
+
+    retry.call("stop", || async { Ok(()) }).await.unwrap();
+    assert_eq!(retry.status().state, DockerBreakerState::Closed);
+}
+
+#[test]
+fn retry_settings_are_validated() {
+    assert!(DockerRetryConfig::default().validate().is_ok());
+    assert!(config(0, 5).validate().is_err());
+    let backwards = DockerRetryConfig {
+        initial_backoff_ms: 500,
+        max_backoff_ms: 100,
+        ..Default::default()
+    };
+    assert!(backwards.validate().is_err());
+}
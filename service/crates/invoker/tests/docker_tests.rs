@@ -1,4 +1,4 @@
-use lambda_models::{Function, FunctionState};
+use lambda_models::{Function, FunctionState, LastUpdateStatus, PackageType};
 
 fn create_test_function() -> Function {
     Function {
@@ -18,6 +18,13 @@ fn create_test_function() -> Function {
         state: FunctionState::Active,
         state_reason: None,
         state_reason_code: None,
+        last_update_status: LastUpdateStatus::Successful,
+        last_update_status_reason: None,
+        last_update_status_reason_code: None,
+        metadata: None,
+        function_arn: None,
+        package_type: PackageType::Zip,
+        image_uri: None,
     }
 }
 
@@ -1,4 +1,5 @@
 mod fs_policy;
 mod lifecycle;
 mod network;
+mod sandbox;
 mod security_env;
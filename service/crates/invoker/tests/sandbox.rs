@@ -0,0 +1,125 @@
+use lambda_invoker::sandbox::{SandboxProfiles, SandboxSupport};
+use lambda_models::{SandboxConfig, SandboxProfile};
+use std::collections::HashMap;
+
+fn config_with(name: &str, profile: SandboxProfile) -> SandboxConfig {
+    SandboxConfig {
+        default_profile: "default".into(),
+        profiles: HashMap::from([(name.to_string(), profile)]),
+    }
+}
+
+#[test]
+fn built_in_profiles_set_security_options() {
+    let profiles = SandboxProfiles::load(&SandboxConfig::default()).unwrap();
+    assert_eq!(profiles.names(), vec!["default", "strict"]);
+    assert_eq!(profiles.default_profile(), "default");
+
+    let default = profiles.get("default").unwrap().security_opt();
+    assert_eq!(default, vec!["no-new-privileges:true"]);
+
+    let strict = profiles.get("strict").unwrap().security_opt();
+    assert_eq!(strict.len(), 2);
+    let seccomp = strict[1].strip_prefix("seccomp=").unwrap();
+    // Docker takes the profile inline, on one line
+    assert!(!seccomp.contains('\n'));
+    let seccomp: serde_json::Value = serde_json::from_str(seccomp).unwrap();
+    let denied = seccomp["syscalls"][0]["names"].as_array().unwrap();
+    assert!(denied.iter().any(|name| name == "ptrace"));
+    assert!(denied.iter().any(|name| name == "io_uring_setup"));
+}
+
+#[test]
+fn custom_profiles_read_their_seccomp_file() {
+    let path = std::env::temp_dir().join(format!("seccomp-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &path,
+        "{\n  \"defaultAction\": \"SCMP_ACT_ERRNO\",\n  \"syscalls\": []\n}\n",
+    )
+    .unwrap();
+    let profiles = SandboxProfiles::load(&config_with(
+        "locked",
+        SandboxProfile {
+            seccomp: Some(path.to_string_lossy().into_owned()),
+            apparmor: Some("lambda-locked".into()),
+        },
+    ))
+    .unwrap();
+    let opts = profiles.get("locked").unwrap().security_opt();
+    assert_eq!(
+        opts,
+        vec![
+            "no-new-privileges:true".to_string(),
+            r#"seccomp={"defaultAction":"SCMP_ACT_ERRNO","syscalls":[]}"#.to_string(),
+            "apparmor=lambda-locked".to_string(),
+        ]
+    );
+
+    std::fs::write(&path, "{\"syscalls\": []}").unwrap();
+    let seccomp = Some(path.to_string_lossy().into_owned());
+    let err = SandboxProfiles::load(&config_with(
+        "locked",
+        SandboxProfile {
+            seccomp: seccomp.clone(),
+            apparmor: None,
+        },
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("defaultAction"));
+    std::fs::remove_file(&path).unwrap();
+    assert!(SandboxProfiles::load(&config_with(
+        "locked",
+        SandboxProfile {
+            seccomp,
+            apparmor: None
+        },
+    ))
+    .is_err());
+}
+
+#[test]
+fn invalid_sandbox_configs_are_refused() {
+    // Built-in profiles cannot be redefined
+    assert!(SandboxProfiles::load(&config_with("strict", SandboxProfile::default())).is_err());
+    let config = SandboxConfig {
+        default_profile: "missing".into(),
+        ..SandboxConfig::default()
+    };
+    assert!(SandboxProfiles::load(&config).is_err());
+    let config = SandboxConfig {
+        default_profile: "strict".into(),
+        ..SandboxConfig::default()
+    };
+    assert!(SandboxProfiles::load(&config).is_ok());
+}
+
+#[test]
+fn host_support_comes_from_docker_security_options() {
+    let support = SandboxSupport::from_security_options(&[
+        "name=seccomp,profile=builtin".to_string(),
+        "name=cgroupns".to_string(),
+    ]);
+    assert!(support.seccomp);
+    assert!(!support.apparmor);
+    assert!(!support.rootless);
+    assert!(SandboxSupport::from_security_options(&["name=rootless".to_string()]).rootless);
+
+    let profiles = SandboxProfiles::load(&config_with(
+        "confined",
+        SandboxProfile {
+            seccomp: None,
+            apparmor: Some("lambda-confined".into()),
+        },
+    ))
+    .unwrap();
+    assert!(support
+        .unsupported(profiles.get("strict").unwrap())
+        .is_none());
+    assert!(support
+        .unsupported(profiles.get("confined").unwrap())
+        .unwrap()
+        .contains("AppArmor"));
+    assert!(SandboxSupport::default()
+        .unsupported(profiles.get("default").unwrap())
+        .is_none());
+}
@@ -1,5 +1,7 @@
+pub mod log_level;
 pub mod prometheus;
 pub mod service;
 pub mod tracing;
 
+pub use log_level::*;
 pub use service::*;
@@ -0,0 +1,119 @@
+use lambda_models::LambdaError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Filter used when neither RUST_LOG nor an explicit default is provided
+pub const DEFAULT_LOG_FILTER: &str = "info";
+
+static CONTROLLER: OnceLock<LogLevelController> = OnceLock::new();
+
+/// Runtime control over the tracing filter (per-target directives such as
+/// `lambda_control::scheduler=trace`), backed by a tracing-subscriber reload handle.
+pub struct LogLevelController {
+    handle: reload::Handle<EnvFilter, Registry>,
+    default_filter: String,
+    current_filter: Mutex<String>,
+    // Bumped on every change so a scheduled revert only fires if nothing changed since
+    generation: AtomicU64,
+}
+
+impl LogLevelController {
+    /// Install the global subscriber with a reloadable filter.
+    /// The initial filter comes from RUST_LOG when set, otherwise `default_filter`.
+    pub fn init(default_filter: &str) -> Result<&'static LogLevelController, LambdaError> {
+        let initial = std::env::var("RUST_LOG")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| default_filter.to_string());
+        let filter = parse_filter(&initial)?;
+        let (filter_layer, handle) = reload::Layer::new(filter);
+
+        tracing_subscriber::registry()
+            .with(filter_layer)
+            .with(tracing_subscriber::fmt::layer())
+            .try_init()
+            .map_err(|e| LambdaError::ConfigError {
+                reason: format!("Failed to install tracing subscriber: {e}"),
+            })?;
+
+        let controller = LogLevelController {
+            handle,
+            default_filter: initial.clone(),
+            current_filter: Mutex::new(initial),
+            generation: AtomicU64::new(0),
+        };
+        Ok(CONTROLLER.get_or_init(|| controller))
+    }
+
+    /// The controller installed by `init`, if any
+    pub fn global() -> Option<&'static LogLevelController> {
+        CONTROLLER.get()
+    }
+
+    pub fn current_filter(&self) -> String {
+        self.current_filter
+            .lock()
+            .map(|f| f.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn default_filter(&self) -> &str {
+        &self.default_filter
+    }
+
+    /// Replace the active filter. Invalid directives are rejected without touching the current filter.
+    pub fn set_filter(&self, directives: &str) -> Result<(), LambdaError> {
+        let filter = parse_filter(directives)?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| LambdaError::InternalError {
+                reason: format!("Failed to reload log filter: {e}"),
+            })?;
+        if let Ok(mut current) = self.current_filter.lock() {
+            *current = directives.to_string();
+        }
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        info!("Log filter set to: {}", directives);
+        Ok(())
+    }
+
+    /// Apply a filter temporarily; the previous filter is restored after `ttl`
+    /// unless another change happened in the meantime.
+    pub fn set_filter_for(
+        &'static self,
+        directives: &str,
+        ttl: Duration,
+    ) -> Result<(), LambdaError> {
+        let previous = self.current_filter();
+        self.set_filter(directives)?;
+        let generation = self.generation.load(Ordering::SeqCst);
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            if self.generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if let Err(e) = self.set_filter(&previous) {
+                warn!("Failed to restore log filter {}: {}", previous, e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Restore the filter the process started with
+    pub fn reset(&self) -> Result<(), LambdaError> {
+        let default_filter = self.default_filter.clone();
+        self.set_filter(&default_filter)
+    }
+}
+
+/// Validate a filter string (e.g. `info,lambda_control::scheduler=trace,bollard=warn`)
+pub fn parse_filter(directives: &str) -> Result<EnvFilter, LambdaError> {
+    EnvFilter::try_new(directives).map_err(|e| LambdaError::InvalidRequest {
+        reason: format!("Invalid log filter '{directives}': {e}"),
+    })
+}
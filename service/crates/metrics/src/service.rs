@@ -1,5 +1,11 @@
-use lambda_models::LambdaError;
-use prometheus::{Counter, Encoder, Histogram, HistogramOpts, Registry, TextEncoder};
+use lambda_models::{
+    ConcurrencyOverview, DiskPressure, DiskStatus, DockerApiStatus, DockerBreakerState,
+    LambdaError, ReplicationStatus, WarmupStats,
+};
+use prometheus::{
+    Counter, Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
 use tracing::{info, instrument};
 
 pub struct MetricsService {
@@ -174,4 +180,328 @@ impl MetricsService {
             reason: e.to_string(),
         })
     }
+
+    /// Render execution environment reuse stats tracked by the control plane.
+    /// Counters are rebuilt from the snapshot on every scrape, so they stay monotonic
+    /// for as long as the control plane keeps counting.
+    pub fn render_warmup_metrics(&self, stats: &WarmupStats) -> Result<String, LambdaError> {
+        let to_err = |e: prometheus::Error| LambdaError::InternalError {
+            reason: e.to_string(),
+        };
+        let registry = Registry::new();
+
+        let starts = IntCounterVec::new(
+            Opts::new(
+                "lambda_function_starts_total",
+                "Invocations by function and how their execution environment was obtained",
+            ),
+            &["function_name", "start_type"],
+        )
+        .map_err(to_err)?;
+        let hit_rate = GaugeVec::new(
+            Opts::new(
+                "lambda_warm_hit_rate",
+                "Fraction of invocations served by an already-warm container",
+            ),
+            &["function_name"],
+        )
+        .map_err(to_err)?;
+        let global_hit_rate = Gauge::new(
+            "lambda_warm_hit_rate_global",
+            "Fraction of all invocations served by an already-warm container",
+        )
+        .map_err(to_err)?;
+
+        registry
+            .register(Box::new(starts.clone()))
+            .map_err(to_err)?;
+        registry
+            .register(Box::new(hit_rate.clone()))
+            .map_err(to_err)?;
+        registry
+            .register(Box::new(global_hit_rate.clone()))
+            .map_err(to_err)?;
+
+        for function in &stats.functions {
+            let name = function.function_name.as_str();
+            let counts = &function.counts;
+            starts
+                .with_label_values(&[name, "warm"])
+                .inc_by(counts.warm_starts);
+            starts
+                .with_label_values(&[name, "restarted"])
+                .inc_by(counts.restarted_starts);
+            starts
+                .with_label_values(&[name, "cold"])
+                .inc_by(counts.cold_starts);
+            hit_rate
+                .with_label_values(&[name])
+                .set(counts.warm_hit_rate);
+        }
+        global_hit_rate.set(stats.global.warm_hit_rate);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.gather(), &mut buffer)
+            .map_err(to_err)?;
+        String::from_utf8(buffer).map_err(|e| LambdaError::InternalError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Render free disk for the data dir and Docker root plus the overall pressure level
+    /// (0 = ok, 1 = low, 2 = critical).
+    pub fn render_disk_metrics(&self, status: &DiskStatus) -> Result<String, LambdaError> {
+        let to_err = |e: prometheus::Error| LambdaError::InternalError {
+            reason: e.to_string(),
+        };
+        let registry = Registry::new();
+
+        let free = GaugeVec::new(
+            Opts::new("lambda_disk_free_bytes", "Free bytes on the filesystem"),
+            &["location", "path"],
+        )
+        .map_err(to_err)?;
+        let total = GaugeVec::new(
+            Opts::new("lambda_disk_total_bytes", "Size of the filesystem in bytes"),
+            &["location", "path"],
+        )
+        .map_err(to_err)?;
+        let pressure = Gauge::new(
+            "lambda_disk_pressure",
+            "Disk pressure level: 0 = ok, 1 = low (GC running), 2 = critical (builds refused)",
+        )
+        .map_err(to_err)?;
+
+        registry.register(Box::new(free.clone())).map_err(to_err)?;
+        registry.register(Box::new(total.clone())).map_err(to_err)?;
+        registry
+            .register(Box::new(pressure.clone()))
+            .map_err(to_err)?;
+
+        for (location, status) in [
+            ("data_dir", &status.data_dir),
+            ("docker_root", &status.docker_root),
+        ] {
+            if let Some(status) = status {
+                free.with_label_values(&[location, &status.path])
+                    .set(status.free_bytes as f64);
+                total
+                    .with_label_values(&[location, &status.path])
+                    .set(status.total_bytes as f64);
+            }
+        }
+        pressure.set(match status.pressure {
+            DiskPressure::Ok => 0.0,
+            DiskPressure::Low => 1.0,
+            DiskPressure::Critical => 2.0,
+        });
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.gather(), &mut buffer)
+            .map_err(to_err)?;
+        String::from_utf8(buffer).map_err(|e| LambdaError::InternalError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Health of this instance and its replication peer, and invokes forwarded to the peer
+    pub fn render_replication_metrics(
+        &self,
+        status: &ReplicationStatus,
+    ) -> Result<String, LambdaError> {
+        let to_err = |e: prometheus::Error| LambdaError::InternalError {
+            reason: e.to_string(),
+        };
+        let registry = Registry::new();
+
+        let forwarded = IntCounterVec::new(
+            Opts::new(
+                "lambda_forwarded_invocations_total",
+                "Invokes sent to the replication peer while this instance was unhealthy, by \
+                 outcome: forwarded, or failed when the peer could not be reached",
+            ),
+            &["function_name", "outcome"],
+        )
+        .map_err(to_err)?;
+        registry
+            .register(Box::new(forwarded.clone()))
+            .map_err(to_err)?;
+        for function in &status.forwarded {
+            let name = function.function_name.as_str();
+            forwarded
+                .with_label_values(&[name, "forwarded"])
+                .inc_by(function.forwarded);
+            forwarded
+                .with_label_values(&[name, "failed"])
+                .inc_by(function.failed);
+        }
+
+        // Only reported once a health check has run
+        for (name, help, health) in [
+            (
+                "lambda_instance_healthy",
+                "Whether this instance can run invocations (1) or not (0)",
+                &status.local_health,
+            ),
+            (
+                "lambda_replication_peer_healthy",
+                "Whether the replication peer last reported itself healthy (1) or not (0)",
+                &status.peer_health,
+            ),
+        ] {
+            if let Some(health) = health {
+                let gauge = Gauge::new(name, help).map_err(to_err)?;
+                registry.register(Box::new(gauge.clone())).map_err(to_err)?;
+                gauge.set(if health.healthy { 1.0 } else { 0.0 });
+            }
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.gather(), &mut buffer)
+            .map_err(to_err)?;
+        String::from_utf8(buffer).map_err(|e| LambdaError::InternalError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Render retries of Docker calls by operation, and the circuit breaker around them
+    /// (0 = closed, 1 = half open, 2 = open).
+    pub fn render_docker_metrics(&self, status: &DockerApiStatus) -> Result<String, LambdaError> {
+        let to_err = |e: prometheus::Error| LambdaError::InternalError {
+            reason: e.to_string(),
+        };
+        let registry = Registry::new();
+
+        let retries = IntCounterVec::new(
+            Opts::new(
+                "lambda_docker_retries_total",
+                "Docker calls made again after failing transiently, by operation",
+            ),
+            &["operation"],
+        )
+        .map_err(to_err)?;
+        registry
+            .register(Box::new(retries.clone()))
+            .map_err(to_err)?;
+        for (operation, count) in &status.retries {
+            retries.with_label_values(&[operation]).inc_by(*count);
+        }
+
+        let short_circuited = Counter::new(
+            "lambda_docker_short_circuited_total",
+            "Docker calls failed without being made because the circuit breaker was open",
+        )
+        .map_err(to_err)?;
+        registry
+            .register(Box::new(short_circuited.clone()))
+            .map_err(to_err)?;
+        short_circuited.inc_by(status.short_circuited_total as f64);
+
+        let breaker = Gauge::new(
+            "lambda_docker_breaker_state",
+            "Circuit breaker of Docker calls (0 = closed, 1 = half open, 2 = open)",
+        )
+        .map_err(to_err)?;
+        registry
+            .register(Box::new(breaker.clone()))
+            .map_err(to_err)?;
+        breaker.set(match status.state {
+            DockerBreakerState::Closed => 0.0,
+            DockerBreakerState::HalfOpen => 1.0,
+            DockerBreakerState::Open => 2.0,
+        });
+        let failures = Gauge::new(
+            "lambda_docker_consecutive_failures",
+            "Docker calls that failed in a row after their retries",
+        )
+        .map_err(to_err)?;
+        registry
+            .register(Box::new(failures.clone()))
+            .map_err(to_err)?;
+        failures.set(status.consecutive_failures as f64);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.gather(), &mut buffer)
+            .map_err(to_err)?;
+        String::from_utf8(buffer).map_err(|e| LambdaError::InternalError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Render invocations running and waiting per function, and the share of their
+    /// concurrency limit in use, for functions running now or with reserved concurrency.
+    pub fn render_concurrency_metrics(
+        &self,
+        overview: &ConcurrencyOverview,
+    ) -> Result<String, LambdaError> {
+        let to_err = |e: prometheus::Error| LambdaError::InternalError {
+            reason: e.to_string(),
+        };
+        let registry = Registry::new();
+
+        let function_gauge = |name: &str, help: &str| -> Result<GaugeVec, LambdaError> {
+            let gauge = GaugeVec::new(Opts::new(name, help), &["function_name"]).map_err(to_err)?;
+            registry.register(Box::new(gauge.clone())).map_err(to_err)?;
+            Ok(gauge)
+        };
+        let running = function_gauge(
+            "lambda_concurrent_executions",
+            "Invocations of the function running now",
+        )?;
+        let waiting = function_gauge(
+            "lambda_concurrency_waiting",
+            "Invocations of the function waiting for a concurrency token",
+        )?;
+        let reserved = function_gauge(
+            "lambda_reserved_concurrency",
+            "Reserved concurrency of the function",
+        )?;
+        let utilization = function_gauge(
+            "lambda_concurrency_utilization",
+            "Share of the concurrency limit the function counts toward in use: its reserved \
+             concurrency, or the unreserved pool (1 = invocations wait)",
+        )?;
+        for usage in &overview.functions {
+            let labels = [usage.function_name.as_str()];
+            running
+                .with_label_values(&labels)
+                .set(usage.in_flight as f64);
+            waiting.with_label_values(&labels).set(usage.waiting as f64);
+            if let Some(limit) = usage.reserved_concurrent_executions {
+                reserved.with_label_values(&labels).set(limit as f64);
+            }
+            utilization
+                .with_label_values(&labels)
+                .set(usage.utilization);
+        }
+
+        for (name, help, value) in [
+            (
+                "lambda_unreserved_concurrency_limit",
+                "Concurrency shared by functions without reserved concurrency",
+                overview.unreserved_concurrency_limit,
+            ),
+            (
+                "lambda_unreserved_concurrent_executions",
+                "Invocations of functions without reserved concurrency running now",
+                overview.unreserved_in_flight,
+            ),
+        ] {
+            let gauge = Gauge::new(name, help).map_err(to_err)?;
+            registry.register(Box::new(gauge.clone())).map_err(to_err)?;
+            gauge.set(value as f64);
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.gather(), &mut buffer)
+            .map_err(to_err)?;
+        String::from_utf8(buffer).map_err(|e| LambdaError::InternalError {
+            reason: e.to_string(),
+        })
+    }
 }
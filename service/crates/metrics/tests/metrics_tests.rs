@@ -91,3 +91,144 @@ fn test_metrics_encoding() {
     assert!(output.contains("test_counter"));
     assert!(output.contains("1"));
 }
+
+#[test]
+fn test_log_filter_parsing() {
+    assert!(parse_filter("info").is_ok());
+    assert!(parse_filter("info,lambda_control::scheduler=trace,bollard=warn").is_ok());
+
+    let err = parse_filter("lambda_control=notalevel").unwrap_err();
+    assert_eq!(err.http_status(), 400);
+}
+
+#[test]
+fn test_warmup_metrics_rendering() {
+    let service = MetricsService::new().unwrap();
+    let stats = lambda_models::WarmupStats {
+        global: lambda_models::WarmupCounts::new(3, 0, 1),
+        functions: vec![lambda_models::FunctionWarmupStats {
+            function_name: "hello".to_string(),
+            counts: lambda_models::WarmupCounts::new(3, 0, 1),
+        }],
+    };
+
+    let text = service.render_warmup_metrics(&stats).unwrap();
+    assert!(text
+        .contains("lambda_function_starts_total{function_name=\"hello\",start_type=\"warm\"} 3"));
+    assert!(text
+        .contains("lambda_function_starts_total{function_name=\"hello\",start_type=\"cold\"} 1"));
+    assert!(text.contains("lambda_warm_hit_rate{function_name=\"hello\"} 0.75"));
+    assert!(text.contains("lambda_warm_hit_rate_global 0.75"));
+}
+
+#[test]
+fn test_disk_metrics_rendering() {
+    let service = MetricsService::new().unwrap();
+    let status = lambda_models::DiskStatus {
+        pressure: lambda_models::DiskPressure::Low,
+        data_dir: Some(lambda_models::DiskLocationStatus {
+            path: "/data".to_string(),
+            free_bytes: 2048,
+            total_bytes: 4096,
+            pressure: lambda_models::DiskPressure::Low,
+        }),
+        docker_root: None,
+        gc_below_free_mb: 5120,
+        min_free_mb: 1024,
+        checked_at: chrono::Utc::now(),
+        last_gc_at: None,
+    };
+
+    let text = service.render_disk_metrics(&status).unwrap();
+    assert!(text.contains("lambda_disk_free_bytes{location=\"data_dir\",path=\"/data\"} 2048"));
+    assert!(text.contains("lambda_disk_total_bytes{location=\"data_dir\",path=\"/data\"} 4096"));
+    assert!(!text.contains("docker_root"));
+    assert!(text.contains("lambda_disk_pressure 1"));
+}
+
+#[test]
+fn test_replication_metrics_rendering() {
+    let service = MetricsService::new().unwrap();
+    let mut status = lambda_models::ReplicationStatus {
+        forwarded: vec![lambda_models::ForwardedInvocations {
+            function_name: "hello".to_string(),
+            forwarded: 3,
+            failed: 1,
+        }],
+        ..Default::default()
+    };
+
+    let text = service.render_replication_metrics(&status).unwrap();
+    assert!(text.contains(
+        "lambda_forwarded_invocations_total{function_name=\"hello\",outcome=\"forwarded\"} 3"
+    ));
+    assert!(text.contains(
+        "lambda_forwarded_invocations_total{function_name=\"hello\",outcome=\"failed\"} 1"
+    ));
+    assert!(!text.contains("lambda_instance_healthy"));
+
+    status.local_health = Some(lambda_models::InstanceHealth {
+        healthy: false,
+        docker: false,
+        docker_breaker: lambda_models::DockerBreakerState::Closed,
+        disk_pressure: lambda_models::DiskPressure::Ok,
+        reason: Some("Docker is unreachable".to_string()),
+        checked_at: chrono::Utc::now(),
+    });
+    let text = service.render_replication_metrics(&status).unwrap();
+    assert!(text.contains("lambda_instance_healthy 0"));
+    assert!(!text.contains("lambda_replication_peer_healthy"));
+}
+
+#[test]
+fn test_docker_metrics_rendering() {
+    let service = MetricsService::new().unwrap();
+    let status = lambda_models::DockerApiStatus {
+        state: lambda_models::DockerBreakerState::Open,
+        consecutive_failures: 5,
+        retries: [("create".to_string(), 4), ("start".to_string(), 1)].into(),
+        short_circuited_total: 2,
+        ..Default::default()
+    };
+
+    let text = service.render_docker_metrics(&status).unwrap();
+    assert!(text.contains("lambda_docker_retries_total{operation=\"create\"} 4"));
+    assert!(text.contains("lambda_docker_retries_total{operation=\"start\"} 1"));
+    assert!(text.contains("lambda_docker_short_circuited_total 2"));
+    assert!(text.contains("lambda_docker_breaker_state 2"));
+    assert!(text.contains("lambda_docker_consecutive_failures 5"));
+}
+
+#[test]
+fn test_concurrency_metrics_rendering() {
+    let service = MetricsService::new().unwrap();
+    let overview = lambda_models::ConcurrencyOverview {
+        unreserved_concurrency_limit: 256,
+        unreserved_in_flight: 3,
+        functions: vec![
+            lambda_models::ConcurrencyUsage {
+                function_name: "orders".to_string(),
+                in_flight: 2,
+                waiting: 1,
+                reserved_concurrent_executions: Some(2),
+                utilization: 1.0,
+                ..Default::default()
+            },
+            lambda_models::ConcurrencyUsage {
+                function_name: "users".to_string(),
+                in_flight: 3,
+                utilization: 3.0 / 256.0,
+                ..Default::default()
+            },
+        ],
+    };
+
+    let text = service.render_concurrency_metrics(&overview).unwrap();
+    assert!(text.contains("lambda_concurrent_executions{function_name=\"orders\"} 2"));
+    assert!(text.contains("lambda_concurrency_waiting{function_name=\"orders\"} 1"));
+    assert!(text.contains("lambda_reserved_concurrency{function_name=\"orders\"} 2"));
+    assert!(!text.contains("lambda_reserved_concurrency{function_name=\"users\"}"));
+    assert!(text.contains("lambda_concurrency_utilization{function_name=\"orders\"} 1"));
+    assert!(text.contains("lambda_unreserved_concurrency_limit 256"));
+    assert!(text.contains("lambda_unreserved_concurrent_executions 3"));
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Order in which queued image builds start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BuildPriority {
+    /// Deploys, warm-up and autoscaling
+    Normal,
+    /// A synchronous invocation is waiting for the image
+    Urgent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildStatus {
+    Queued,
+    Running,
+}
+
+/// Why `docker build` failed, judged from its output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildFailureKind {
+    /// The runtime's base image could not be pulled
+    BaseImagePull,
+    /// A step of the Dockerfile, e.g. installing dependencies, exited with an error
+    StepFailed,
+    /// The Docker host ran out of disk
+    NoSpace,
+    /// The Docker daemon could not be reached
+    DaemonUnavailable,
+    Other,
+}
+
+impl BuildFailureKind {
+    /// Whether building again may succeed without the function changing
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            BuildFailureKind::NoSpace | BuildFailureKind::DaemonUnavailable
+        )
+    }
+}
+
+impl std::fmt::Display for BuildFailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BuildFailureKind::BaseImagePull => "base image pull failed",
+            BuildFailureKind::StepFailed => "build step failed",
+            BuildFailureKind::NoSpace => "no space left on device",
+            BuildFailureKind::DaemonUnavailable => "Docker daemon unavailable",
+            BuildFailureKind::Other => "build failed",
+        })
+    }
+}
+
+/// An image build waiting in the build queue or running
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Build {
+    pub build_id: String,
+    pub function_name: String,
+    pub image_ref: String,
+    pub priority: BuildPriority,
+    pub status: BuildStatus,
+    pub queued_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// Running builds, then queued ones in the order they will start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListBuildsResponse {
+    pub builds: Vec<Build>,
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Control-plane tables recorded in the change feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeEntity {
+    Function,
+    Route,
+    Execution,
+}
+
+impl ChangeEntity {
+    pub const ALL: [ChangeEntity; 3] = [Self::Function, Self::Route, Self::Execution];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Function => "function",
+            Self::Route => "route",
+            Self::Execution => "execution",
+        }
+    }
+}
+
+impl FromStr for ChangeEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|entity| entity.as_str() == s)
+            .ok_or_else(|| format!("unknown change entity: {s}"))
+    }
+}
+
+/// What happened to the row, named as in DynamoDB Streams
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeEventName {
+    Insert,
+    Modify,
+    Remove,
+}
+
+impl FromStr for ChangeEventName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "INSERT" => Ok(Self::Insert),
+            "MODIFY" => Ok(Self::Modify),
+            "REMOVE" => Ok(Self::Remove),
+            _ => Err(format!("unknown change event name: {s}")),
+        }
+    }
+}
+
+/// One row change, with the row as it was and as it became
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    /// Strictly increasing across the whole feed
+    pub sequence: i64,
+    pub entity: ChangeEntity,
+    /// Function name, route id or execution request id
+    pub entity_id: String,
+    pub event_name: ChangeEventName,
+    pub occurred_at: DateTime<Utc>,
+    /// Absent for `REMOVE`
+    pub new_image: Option<serde_json::Value>,
+    /// Absent for `INSERT`
+    pub old_image: Option<serde_json::Value>,
+}
+
+/// A page of `GET /api/changes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeFeedResponse {
+    pub events: Vec<ChangeEvent>,
+    /// Pass back as `cursor` to continue after the last event; unchanged when there were none
+    pub next_cursor: String,
+    /// More events are already available after `next_cursor`
+    pub has_more: bool,
+}
@@ -1,4 +1,7 @@
+use crate::{AwsEmulation, FeatureFlag, LambdaError, RuntimeApiSettings};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
@@ -10,6 +13,62 @@ pub struct Config {
     pub idle: IdleConfig,
     pub limits: LimitsConfig,
     pub warmup: WarmupConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub disk: DiskConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub s3_notifications: S3NotificationsConfig,
+    #[serde(default)]
+    pub change_feed: ChangeFeedConfig,
+    #[serde(default)]
+    pub warm_pool: WarmPoolConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub async_invoke: AsyncInvokeConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
+    #[serde(default)]
+    pub replication: ReplicationConfig,
+    #[serde(default)]
+    pub builds: BuildsConfig,
+    #[serde(default)]
+    pub invoke_response: InvokeResponseConfig,
+    #[serde(default)]
+    pub exec: ExecConfig,
+    #[serde(default)]
+    pub quotas: QuotasConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    #[serde(default)]
+    pub upgrade: UpgradeConfig,
+    #[serde(default)]
+    pub integrity: IntegrityConfig,
+    #[serde(default)]
+    pub rollout: RolloutConfig,
+    #[serde(default)]
+    pub sync_invoke: SyncInvokeConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub aws_emulation: AwsEmulationConfig,
+    #[serde(default)]
+    pub aws_pricing: AwsPricingConfig,
+    #[serde(default)]
+    pub trusted_headers: TrustedHeadersConfig,
+    /// Feature flags of optional subsystems, by name; unnamed flags keep their default
+    #[serde(default)]
+    pub features: BTreeMap<FeatureFlag, bool>,
+    /// Functions created or updated to match at startup, as `[[functions]]` entries
+    #[serde(default)]
+    pub functions: Vec<StaticFunctionConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,6 +78,11 @@ pub struct ServerConfig {
     pub port_user_api: u16,
     pub port_runtime_api: u16,
     pub max_request_body_size_mb: u64,
+    /// URL clients reach the user API at, e.g. `https://lambda.example.com` behind a reverse
+    /// proxy, used in the function URLs and code links it hands out; unset builds it from
+    /// `bind` and `port_user_api`
+    #[serde(default)]
+    pub public_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,7 +95,84 @@ pub struct DataConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct DockerConfig {
+    /// Docker daemon address, e.g. `unix:///var/run/docker.sock` or, on Windows,
+    /// `npipe:////./pipe/docker_engine`; empty uses `DOCKER_HOST` or the platform default
     pub host: String,
+    /// Address `host.docker.internal` maps to in function containers. Unset maps it to the
+    /// gateway of `network`, or to Docker's `host-gateway` on the default bridge, except on
+    /// Docker Desktop, which resolves the name itself
+    #[serde(default)]
+    pub host_gateway: Option<String>,
+    /// Host advertised to function containers in `AWS_LAMBDA_RUNTIME_API`; unset uses
+    /// `host.docker.internal`, or `127.0.0.1` on the `host` network
+    #[serde(default)]
+    pub runtime_api_host: Option<String>,
+    /// Port advertised with it; unset uses `server.port_runtime_api`
+    #[serde(default)]
+    pub runtime_api_port: Option<u16>,
+    /// Network function containers join: `host` or a user-defined network; unset uses the
+    /// default bridge
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub retry: DockerRetryConfig,
+}
+
+/// Retries of Docker API calls that failed transiently, and the circuit breaker that stops
+/// calling the daemon while it keeps failing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct DockerRetryConfig {
+    /// Attempts of each call, the first included; 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled before each next one
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Calls failing in a row, after their retries, that open the breaker; 0 disables it
+    pub breaker_threshold: u32,
+    /// How long an open breaker fails calls without making them before letting one through
+    pub breaker_open_secs: u64,
+}
+
+impl Default for DockerRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 2000,
+            breaker_threshold: 5,
+            breaker_open_secs: 30,
+        }
+    }
+}
+
+impl DockerRetryConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        if self.max_attempts == 0 {
+            return Err(LambdaError::InvalidRequest {
+                reason: "docker.retry: max_attempts must be at least 1".to_string(),
+            });
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err(LambdaError::InvalidRequest {
+                reason: "docker.retry: initial_backoff_ms must not exceed max_backoff_ms"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl DockerConfig {
+    /// How containers reach the runtime API until changed through the admin API
+    pub fn runtime_api_settings(&self) -> RuntimeApiSettings {
+        RuntimeApiSettings {
+            host: self.runtime_api_host.clone(),
+            port: self.runtime_api_port,
+            host_gateway: self.host_gateway.clone(),
+            network: self.network.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -40,6 +181,38 @@ pub struct DefaultsConfig {
     pub memory_mb: u64,
     pub timeout_ms: u64,
     pub tmp_mb: u64,
+    /// Seconds a container gets to finish its invocation after SIGTERM before it is killed
+    #[serde(default = "default_stop_grace_seconds")]
+    pub stop_grace_seconds: u64,
+    /// Time a synchronous invoke waits beyond the function's timeout, for a container to
+    /// start and pick the invocation up; the timeout itself runs from that pickup
+    #[serde(default = "default_init_buffer_ms")]
+    pub init_buffer_ms: u64,
+    /// What happens to the container of an invocation that ran past its timeout
+    #[serde(default)]
+    pub on_timeout: TimeoutAction,
+}
+
+/// What becomes of the container running an invocation that timed out. Whatever it posts
+/// for that invocation afterwards is discarded either way
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutAction {
+    /// Kill it; the next invocation gets a fresh container
+    #[default]
+    Kill,
+    /// Kill it and start a replacement right away, keeping the function's warm capacity
+    Restart,
+    /// Leave it running; it takes work again once the handler returns
+    Keep,
+}
+
+fn default_stop_grace_seconds() -> u64 {
+    10
+}
+
+fn default_init_buffer_ms() -> u64 {
+    10000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -53,6 +226,9 @@ pub struct IdleConfig {
 #[serde(deny_unknown_fields)]
 pub struct LimitsConfig {
     pub max_global_concurrency: u32,
+    /// Memory (MB) that running containers may commit in total; unlimited when unset
+    #[serde(default)]
+    pub host_memory_budget_mb: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -62,6 +238,763 @@ pub struct WarmupConfig {
     pub timeout_ms: u64,
 }
 
+/// Retries for synchronous invokes lost to container failures (never function errors).
+/// Only applied to requests flagged as idempotent by the caller.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self { max_retries: 2 }
+    }
+}
+
+/// Free-space thresholds for the data dir and Docker root
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DiskConfig {
+    /// Collect unused images and build artifacts below this much free space
+    pub gc_below_free_mb: u64,
+    /// Refuse new builds below this much free space
+    pub min_free_mb: u64,
+    pub check_interval_ms: u64,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self {
+            gc_below_free_mb: 5120,
+            min_free_mb: 1024,
+            check_interval_ms: 60000,
+        }
+    }
+}
+
+/// Delivery of lifecycle events to webhook targets
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WebhooksConfig {
+    /// Attempts per delivery, including the first
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles on each further retry
+    pub initial_backoff_ms: u64,
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            timeout_ms: 10000,
+        }
+    }
+}
+
+/// Bucket notifications from an external S3-compatible store such as MinIO, received on
+/// `POST /api/s3/notifications`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct S3NotificationsConfig {
+    pub enabled: bool,
+    /// When set, notifications must carry `Authorization: Bearer <token>`, as MinIO sends
+    /// for a webhook target's `auth_token`
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+/// Retention of the change feed served on `GET /api/changes`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ChangeFeedConfig {
+    /// Events older than this are trimmed; cursors pointing before them expire
+    pub retention_hours: u64,
+}
+
+impl Default for ChangeFeedConfig {
+    fn default() -> Self {
+        Self {
+            retention_hours: 24,
+        }
+    }
+}
+
+/// Keeping warm containers across planned restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WarmPoolConfig {
+    /// Snapshot warm pool membership to the database and leave containers running on
+    /// shutdown, so the next start adopts them instead of cold-starting. Needs a
+    /// file-backed `data.db_url`
+    pub persist: bool,
+}
+
+/// Results of job-mode and Event invocations, kept for callers that poll for them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct JobsConfig {
+    /// How long a finished job's result stays available for polling
+    pub result_ttl_secs: u64,
+    /// Larger result payloads are dropped; the job is kept with `payload_truncated`
+    pub max_result_bytes: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            result_ttl_secs: 3600,
+            max_result_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// Event invocations, queued and retried the way AWS handles asynchronous invocation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct AsyncInvokeConfig {
+    /// Retries after an attempt fails with a function error or a server-side failure, 0-2.
+    /// Throttled attempts are retried until the event is too old and do not count
+    pub max_retry_attempts: u32,
+    /// Events not processed within this long are dropped, 60-21600
+    pub max_event_age_secs: u64,
+    /// Wait before the first retry; doubles on each further retry
+    pub retry_delay_ms: u64,
+    /// Events attempted at the same time
+    pub max_concurrent: usize,
+}
+
+impl Default for AsyncInvokeConfig {
+    fn default() -> Self {
+        Self {
+            max_retry_attempts: 2,
+            max_event_age_secs: 21600,
+            retry_delay_ms: 60000,
+            max_concurrent: 10,
+        }
+    }
+}
+
+impl AsyncInvokeConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let invalid = |reason: &str| {
+            Err(LambdaError::InvalidRequest {
+                reason: format!("async_invoke: {reason}"),
+            })
+        };
+        if self.max_retry_attempts > 2 {
+            return invalid("max_retry_attempts must be between 0 and 2");
+        }
+        if !(60..=21600).contains(&self.max_event_age_secs) {
+            return invalid("max_event_age_secs must be between 60 and 21600");
+        }
+        if self.max_concurrent == 0 {
+            return invalid("max_concurrent must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Keeping the SQLite database from growing without bound
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct MaintenanceConfig {
+    /// Executions that started longer ago are deleted; 0 keeps them whatever their age
+    pub execution_retention_days: u64,
+    /// Only the newest this many executions are kept; 0 keeps any number
+    pub max_executions: u64,
+    /// How often executions are pruned
+    pub prune_interval_secs: u64,
+    /// How often the database file is compacted with VACUUM and its query planner
+    /// statistics refreshed with ANALYZE; 0 turns it off
+    pub vacuum_interval_hours: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            execution_retention_days: 30,
+            max_executions: 0,
+            prune_interval_secs: 3600,
+            vacuum_interval_hours: 24,
+        }
+    }
+}
+
+/// HTTP access log of gateway and invoke requests
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    /// JSON lines file to write to; without one, entries go to the server log with target
+    /// `access_log`
+    pub file: Option<String>,
+    /// Rotate the file once it grows past this size
+    pub max_file_mb: u64,
+    /// Rotated files to keep (`<file>.1` is the newest)
+    pub max_files: u32,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: None,
+            max_file_mb: 100,
+            max_files: 5,
+        }
+    }
+}
+
+/// Image builds, queued with invocations waiting on them first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct BuildsConfig {
+    /// Builds run at once; the rest wait in the queue
+    pub max_concurrent: usize,
+    /// Largest build context, i.e. unzipped package minus its `.lambdaignore` entries
+    pub max_context_mb: u64,
+    /// A build that failed in a way another attempt will not fix is not retried for this
+    /// long unless the function changes; invokes get its failure right away. 0 retries on
+    /// every invoke.
+    pub failed_build_backoff_secs: u64,
+}
+
+impl Default for BuildsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            max_context_mb: 250,
+            failed_build_backoff_secs: 300,
+        }
+    }
+}
+
+/// Headers added to successful direct invoke responses of every function
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct InvokeResponseConfig {
+    /// Sent unless the function's own response headers set the same header
+    pub default_headers: HashMap<String, String>,
+}
+
+/// Debug shells into warm containers through `POST /api/containers/{id}/exec`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct ExecConfig {
+    pub enabled: bool,
+    /// Opening a session needs `Authorization: Bearer <admin_token>`; without a token no
+    /// session can be opened
+    pub admin_token: Option<String>,
+    /// Sessions are closed and their shell killed this long after they were opened
+    pub max_session_secs: u64,
+    /// Sessions are closed after this long without input
+    pub idle_timeout_secs: u64,
+    /// Command run in the container
+    pub shell: String,
+    /// JSON lines file for the audit log of sessions and their input; without one, entries
+    /// go to the server log with target `exec_audit`
+    pub audit_file: Option<String>,
+}
+
+impl Default for ExecConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            admin_token: None,
+            max_session_secs: 600,
+            idle_timeout_secs: 120,
+            shell: "/bin/sh".to_string(),
+            audit_file: None,
+        }
+    }
+}
+
+/// Limits for the functions of a namespace; unset ones are unlimited. Every function is in
+/// the `default` namespace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct QuotasConfig {
+    pub max_functions: Option<u64>,
+    /// Memory (MB) of all warm containers together
+    pub max_warm_memory_mb: Option<u64>,
+    /// Invocations per UTC day
+    pub max_daily_invocations: Option<u64>,
+    /// Size of the stored deployment packages
+    pub max_storage_bytes: Option<u64>,
+}
+
+/// Cold storage of functions nobody invokes: their images and containers are removed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct ArchiveConfig {
+    /// Archive functions without an invocation or change for this many days; off when unset
+    pub idle_days: Option<u64>,
+    /// How often to look for idle functions
+    pub check_interval_secs: u64,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            idle_days: None,
+            check_interval_secs: 3600,
+        }
+    }
+}
+
+/// Partition, region and account used in the ARNs, queue URLs and event payloads handed
+/// out, for SDK code that parses them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct IdentityConfig {
+    /// `aws`, `aws-cn` or `aws-us-gov`
+    pub partition: String,
+    pub region: String,
+    /// Twelve digits
+    pub account_id: String,
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        Self {
+            partition: "aws".to_string(),
+            region: "local".to_string(),
+            account_id: "000000000000".to_string(),
+        }
+    }
+}
+
+impl IdentityConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let invalid = |reason: &str| {
+            Err(LambdaError::InvalidRequest {
+                reason: format!("identity: {reason}"),
+            })
+        };
+        if self.account_id.len() != 12 || !self.account_id.bytes().all(|b| b.is_ascii_digit()) {
+            return invalid("account_id must be twelve digits");
+        }
+        for (field, value) in [("partition", &self.partition), ("region", &self.region)] {
+            if value.is_empty()
+                || !value
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+            {
+                return invalid(&format!(
+                    "{field} must be lowercase letters, digits and dashes"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `arn:<partition>:<service>:<region>:<account>:<resource>`
+    pub fn arn(&self, service: &str, resource: &str) -> String {
+        format!(
+            "arn:{}:{service}:{}:{}:{resource}",
+            self.partition, self.region, self.account_id
+        )
+    }
+
+    pub fn function_arn(&self, function_name: &str) -> String {
+        self.arn("lambda", &format!("function:{function_name}"))
+    }
+
+    pub fn queue_arn(&self, queue_name: &str) -> String {
+        self.arn("sqs", queue_name)
+    }
+
+    /// Bucket ARNs carry neither region nor account
+    pub fn bucket_arn(&self, bucket: &str) -> String {
+        format!("arn:{}:s3:::{bucket}", self.partition)
+    }
+}
+
+/// What happens to the routes and event source mappings of a deleted function
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct IntegrityConfig {
+    /// Delete a function's routes and mappings with it, and drop canaries pointing at it,
+    /// instead of refusing the delete
+    pub cascade_function_deletes: bool,
+    /// How often to look for routes and mappings naming missing functions; 0 turns it off
+    pub check_interval_secs: u64,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self {
+            cascade_function_deletes: false,
+            check_interval_secs: 3600,
+        }
+    }
+}
+
+/// Replacing warm containers after a configuration change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct RolloutConfig {
+    /// Containers replaced at a time
+    pub batch_size: usize,
+}
+
+impl Default for RolloutConfig {
+    fn default() -> Self {
+        Self { batch_size: 1 }
+    }
+}
+
+/// What the caller of a synchronous invoke gets once it waited `max_wait_secs`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowInvokeMode {
+    /// Keep waiting, up to the function's timeout
+    Wait,
+    /// 504 with the request id; the result is kept for `/api/executions/{id}/result`
+    GatewayTimeout,
+    /// 200 right away, then whitespace every `heartbeat_secs` until the body follows
+    KeepAlive,
+}
+
+/// Synchronous invokes that outlast the clients or proxies waiting for them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct SyncInvokeConfig {
+    /// How long a synchronous invoke holds its request open; unset waits for the function
+    pub max_wait_secs: Option<u64>,
+    /// Mode of the invoke API, `wait` or `gateway_timeout`: its status and headers depend on
+    /// the result, so it cannot answer before it
+    pub invoke: SlowInvokeMode,
+    /// Mode of API gateway routes
+    pub gateway: SlowInvokeMode,
+    pub heartbeat_secs: u64,
+}
+
+impl Default for SyncInvokeConfig {
+    fn default() -> Self {
+        Self {
+            max_wait_secs: None,
+            invoke: SlowInvokeMode::GatewayTimeout,
+            gateway: SlowInvokeMode::KeepAlive,
+            heartbeat_secs: 10,
+        }
+    }
+}
+
+impl SyncInvokeConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let invalid = |reason: &str| {
+            Err(LambdaError::InvalidRequest {
+                reason: format!("sync_invoke: {reason}"),
+            })
+        };
+        if self.max_wait_secs == Some(0) {
+            return invalid("max_wait_secs must be positive");
+        }
+        if self.invoke == SlowInvokeMode::KeepAlive {
+            return invalid("invoke must be wait or gateway_timeout");
+        }
+        if self.heartbeat_secs == 0 {
+            return invalid("heartbeat_secs must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Built-in sandbox profile: Docker's own seccomp and AppArmor defaults
+pub const DEFAULT_SANDBOX_PROFILE: &str = "default";
+/// Built-in sandbox profile: a seccomp profile denying what Docker's does plus io_uring, the
+/// new mount API, `chroot` and `syslog`
+pub const STRICT_SANDBOX_PROFILE: &str = "strict";
+
+/// Seccomp and AppArmor confinement of function containers, chosen per function by name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct SandboxConfig {
+    /// Profile of functions that did not choose one
+    pub default_profile: String,
+    /// Profiles besides the built-in `default` and `strict`, by name
+    pub profiles: HashMap<String, SandboxProfile>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            default_profile: DEFAULT_SANDBOX_PROFILE.to_string(),
+            profiles: HashMap::new(),
+        }
+    }
+}
+
+/// A named sandbox profile; what it leaves unset stays at Docker's default
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SandboxProfile {
+    /// Path of a seccomp profile in Docker's JSON format
+    #[serde(default)]
+    pub seccomp: Option<String>,
+    /// Name of an AppArmor profile loaded on the host
+    #[serde(default)]
+    pub apparmor: Option<String>,
+}
+
+impl SandboxConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let invalid = |reason: String| {
+            Err(LambdaError::InvalidRequest {
+                reason: format!("sandbox: {reason}"),
+            })
+        };
+        for (name, profile) in &self.profiles {
+            if name == DEFAULT_SANDBOX_PROFILE || name == STRICT_SANDBOX_PROFILE {
+                return invalid(format!("{name} is a built-in profile"));
+            }
+            if profile.seccomp.as_deref().is_some_and(str::is_empty)
+                || profile.apparmor.as_deref().is_some_and(str::is_empty)
+            {
+                return invalid(format!("profile {name} has an empty setting"));
+            }
+        }
+        if !self.has_profile(&self.default_profile) {
+            return invalid(format!(
+                "default_profile {} is not a profile",
+                self.default_profile
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether `name` is a built-in or configured profile
+    pub fn has_profile(&self, name: &str) -> bool {
+        name == DEFAULT_SANDBOX_PROFILE
+            || name == STRICT_SANDBOX_PROFILE
+            || self.profiles.contains_key(name)
+    }
+}
+
+/// Fake AWS credentials and emulator endpoints given to functions without settings of their
+/// own; see [`AwsEmulation`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct AwsEmulationConfig {
+    pub enabled: bool,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub service_endpoints: BTreeMap<String, String>,
+}
+
+impl Default for AwsEmulationConfig {
+    fn default() -> Self {
+        let settings = AwsEmulation::default();
+        Self {
+            enabled: false,
+            access_key_id: settings.access_key_id,
+            secret_access_key: settings.secret_access_key,
+            session_token: None,
+            endpoint_url: None,
+            service_endpoints: BTreeMap::new(),
+        }
+    }
+}
+
+impl AwsEmulationConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        self.settings().validate()
+    }
+
+    pub fn settings(&self) -> AwsEmulation {
+        AwsEmulation {
+            enabled: self.enabled,
+            access_key_id: self.access_key_id.clone(),
+            secret_access_key: self.secret_access_key.clone(),
+            session_token: self.session_token.clone(),
+            endpoint_url: self.endpoint_url.clone(),
+            service_endpoints: self.service_endpoints.clone(),
+        }
+    }
+}
+
+/// AWS Lambda prices cost estimates use, in USD; the defaults are us-east-1 list prices
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct AwsPricingConfig {
+    pub request_price_per_million: f64,
+    pub x86_64_gb_second_price: f64,
+    pub arm64_gb_second_price: f64,
+    /// Monthly free tier of the account, shared by all functions
+    pub free_requests_per_month: u64,
+    pub free_gb_seconds_per_month: f64,
+}
+
+impl Default for AwsPricingConfig {
+    fn default() -> Self {
+        Self {
+            request_price_per_million: 0.20,
+            x86_64_gb_second_price: 0.0000166667,
+            arm64_gb_second_price: 0.0000133334,
+            free_requests_per_month: 1_000_000,
+            free_gb_seconds_per_month: 400_000.0,
+        }
+    }
+}
+
+impl AwsPricingConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let prices = [
+            self.request_price_per_million,
+            self.x86_64_gb_second_price,
+            self.arm64_gb_second_price,
+            self.free_gb_seconds_per_month,
+        ];
+        if prices
+            .iter()
+            .any(|price| !price.is_finite() || *price < 0.0)
+        {
+            return Err(LambdaError::InvalidRequest {
+                reason: "aws_pricing: prices and free tier must be zero or more".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Caller identity asserted by an authenticating reverse proxy (Authelia, oauth2-proxy),
+/// passed to functions behind API gateway routes as `requestContext.authorizer`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct TrustedHeadersConfig {
+    pub enabled: bool,
+    /// Header naming the authenticated user; requests without it get no authorizer
+    pub user_header: String,
+    /// Header listing the user's groups, separated by commas
+    pub groups_header: Option<String>,
+    pub email_header: Option<String>,
+    /// Addresses the proxy connects from; the headers of other peers are not trusted. Empty
+    /// trusts every peer, for a server only the proxy can reach.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for TrustedHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            user_header: "X-Forwarded-User".to_string(),
+            groups_header: Some("X-Forwarded-Groups".to_string()),
+            email_header: Some("X-Forwarded-Email".to_string()),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl TrustedHeadersConfig {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let headers = std::iter::once(self.user_header.as_str())
+            .chain(self.groups_header.as_deref())
+            .chain(self.email_header.as_deref());
+        for name in headers {
+            if name.is_empty() || http_header_name_invalid(name) {
+                return Err(LambdaError::InvalidRequest {
+                    reason: format!("trusted_headers: invalid header name {name:?}"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the headers of a request from `peer` are trusted
+    pub fn trusts(&self, peer: Option<IpAddr>) -> bool {
+        self.trusted_proxies.is_empty()
+            || peer.is_some_and(|peer| self.trusted_proxies.contains(&peer.to_canonical()))
+    }
+}
+
+/// Header names are tokens: visible ASCII without separators
+fn http_header_name_invalid(name: &str) -> bool {
+    name.bytes()
+        .any(|b| !b.is_ascii_graphic() || b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+/// Where `lambda-at-home-server upgrade` looks for new releases
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct UpgradeConfig {
+    /// Latest release in the shape of GitHub's releases API: `tag_name` and `assets`, each
+    /// with `name` and `browser_download_url`
+    pub feed_url: String,
+    /// Limit on fetching the feed and downloading the binary
+    pub timeout_secs: u64,
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            feed_url: "https://api.github.com/repos/fearlessfara/lambda-at-home/releases/latest"
+                .to_string(),
+            timeout_secs: 300,
+        }
+    }
+}
+
+/// Mirroring functions, their code and routes to a second lambda-at-home instance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct ReplicationConfig {
+    pub enabled: bool,
+    /// Management API of the peer, e.g. `http://vps.example.com:8000`
+    pub peer_url: Option<String>,
+    /// Sent as `Authorization: Bearer <token>`, for a peer behind an authenticating proxy
+    pub auth_token: Option<String>,
+    /// Time between sync rounds
+    pub interval_secs: u64,
+    pub timeout_ms: u64,
+    /// Replicate API gateway routes as well as functions
+    pub routes: bool,
+    /// Forward synchronous invokes to the peer while this instance is unhealthy (Docker
+    /// unreachable or disk critically low) and the peer reports itself healthy
+    pub failover: bool,
+    /// Time between health reports exchanged with the peer
+    pub health_interval_secs: u64,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            peer_url: None,
+            auth_token: None,
+            interval_secs: 60,
+            timeout_ms: 30000,
+            routes: true,
+            failover: false,
+            health_interval_secs: 10,
+        }
+    }
+}
+
+/// Function declared in the config file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StaticFunctionConfig {
+    pub name: String,
+    pub runtime: String,
+    pub handler: String,
+    /// Directory holding the function's code, or a `.zip` file, relative to the working
+    /// directory of the server
+    pub code: String,
+    /// Path of the function's package when `code` is an npm or pnpm workspace
+    pub workspace_package: Option<String>,
+    pub role: Option<String>,
+    pub description: Option<String>,
+    pub timeout: Option<u64>,
+    pub memory_size: Option<u64>,
+    pub environment: Option<HashMap<String, String>>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -70,6 +1003,7 @@ impl Default for Config {
                 port_user_api: 8000,
                 port_runtime_api: 8001,
                 max_request_body_size_mb: 50, // 50MB default limit
+                public_url: None,
             },
             data: DataConfig {
                 dir: "data".to_string(),
@@ -77,11 +1011,19 @@ impl Default for Config {
             },
             docker: DockerConfig {
                 host: "".to_string(),
+                host_gateway: None,
+                runtime_api_host: None,
+                runtime_api_port: None,
+                network: None,
+                retry: DockerRetryConfig::default(),
             },
             defaults: DefaultsConfig {
                 memory_mb: 512,
                 timeout_ms: 3000,
                 tmp_mb: 512,
+                stop_grace_seconds: default_stop_grace_seconds(),
+                init_buffer_ms: default_init_buffer_ms(),
+                on_timeout: TimeoutAction::default(),
             },
             idle: IdleConfig {
                 soft_ms: 45000,
@@ -89,11 +1031,39 @@ impl Default for Config {
             },
             limits: LimitsConfig {
                 max_global_concurrency: 256,
+                host_memory_budget_mb: None,
             },
             warmup: WarmupConfig {
                 enabled: true,
                 timeout_ms: 30000, // 30 seconds timeout for warm-up
             },
+            retry: RetryConfig::default(),
+            disk: DiskConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            s3_notifications: S3NotificationsConfig::default(),
+            change_feed: ChangeFeedConfig::default(),
+            warm_pool: WarmPoolConfig::default(),
+            jobs: JobsConfig::default(),
+            async_invoke: AsyncInvokeConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            access_log: AccessLogConfig::default(),
+            replication: ReplicationConfig::default(),
+            builds: BuildsConfig::default(),
+            invoke_response: InvokeResponseConfig::default(),
+            exec: ExecConfig::default(),
+            quotas: QuotasConfig::default(),
+            archive: ArchiveConfig::default(),
+            identity: IdentityConfig::default(),
+            upgrade: UpgradeConfig::default(),
+            integrity: IntegrityConfig::default(),
+            rollout: RolloutConfig::default(),
+            sync_invoke: SyncInvokeConfig::default(),
+            sandbox: SandboxConfig::default(),
+            aws_emulation: AwsEmulationConfig::default(),
+            aws_pricing: AwsPricingConfig::default(),
+            trusted_headers: TrustedHeadersConfig::default(),
+            features: BTreeMap::new(),
+            functions: Vec::new(),
         }
     }
 }
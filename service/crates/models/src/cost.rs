@@ -0,0 +1,75 @@
+use crate::AwsPricingConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Instruction set a function would run on in AWS; arm64 GB-seconds are cheaper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Architecture {
+    #[default]
+    X86_64,
+    Arm64,
+}
+
+/// `POST /api/cost-estimate`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostEstimateRequest {
+    /// Only this function; every function when unset
+    #[serde(default)]
+    pub function_name: Option<String>,
+    /// Recorded executions to price, e.g. `24h` or `30d`; defaults to `30d`
+    #[serde(default)]
+    pub window: Option<String>,
+    #[serde(default)]
+    pub architecture: Architecture,
+    /// Prices to use instead of the server's `[aws_pricing]`
+    #[serde(default)]
+    pub pricing: Option<AwsPricingConfig>,
+}
+
+/// Durations of the executions a function finished within the window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationDistribution {
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+    pub mean_ms: f64,
+}
+
+/// What one function's recorded executions would have cost on AWS Lambda
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCostEstimate {
+    pub function_name: String,
+    /// Configured memory, which AWS bills for whatever the function uses
+    pub memory_size_mb: u64,
+    pub invocations: u64,
+    /// None without finished executions
+    pub duration: Option<DurationDistribution>,
+    /// Highest memory use recorded, to see whether less memory would do
+    pub max_memory_used_mb: Option<u64>,
+    pub gb_seconds: f64,
+    /// USD for the window: requests, compute and both
+    pub request_cost: f64,
+    pub compute_cost: f64,
+    pub cost: f64,
+    /// `cost` scaled to a 730-hour month, before the free tier
+    pub monthly_cost: f64,
+}
+
+/// AWS Lambda cost of the workload recorded within a window, in USD. Per-function costs are
+/// before the free tier, which the account totals apply once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub generated_at: DateTime<Utc>,
+    pub window_seconds: u64,
+    pub architecture: Architecture,
+    pub pricing: AwsPricingConfig,
+    /// Most expensive first
+    pub functions: Vec<FunctionCostEstimate>,
+    pub invocations: u64,
+    pub gb_seconds: f64,
+    pub cost: f64,
+    pub monthly_cost_before_free_tier: f64,
+    pub monthly_cost: f64,
+}
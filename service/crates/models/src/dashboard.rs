@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Everything the console landing page shows, computed server-side in one request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub generated_at: DateTime<Utc>,
+    pub functions: FunctionStateCounts,
+    pub invocations: InvocationTotals,
+    pub warm_pool: WarmPoolOccupancy,
+    /// Most recent code/configuration updates and published versions, newest first
+    pub recent_deployments: Vec<Deployment>,
+    /// Critical alerts first
+    pub alerts: Vec<DashboardAlert>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FunctionStateCounts {
+    pub total: u64,
+    pub pending: u64,
+    pub active: u64,
+    pub inactive: u64,
+    #[serde(default)]
+    pub archived: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InvocationTotals {
+    pub window_seconds: u64,
+    pub invocations: u64,
+    pub errors: u64,
+    /// errors / invocations, 0.0 when there were no invocations
+    pub error_rate: f64,
+    pub avg_duration_ms: Option<f64>,
+    /// Only functions invoked inside the window, busiest first
+    pub by_function: Vec<FunctionInvocationTotals>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionInvocationTotals {
+    pub function_name: String,
+    pub invocations: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WarmPoolOccupancy {
+    pub total: u64,
+    pub warm_idle: u64,
+    pub active: u64,
+    pub stopped: u64,
+    /// Memory of containers that are currently running
+    pub committed_memory_mb: u64,
+    pub host_memory_budget_mb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeploymentKind {
+    /// $LATEST code or configuration changed
+    Updated,
+    /// A numbered version was published
+    Published,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deployment {
+    pub function_name: String,
+    pub version: String,
+    pub code_sha256: String,
+    pub kind: DeploymentKind,
+    pub deployed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Critical,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DashboardAlert {
+    pub severity: AlertSeverity,
+    /// Stable identifier such as `FunctionFailed` or `HighErrorRate`
+    pub code: String,
+    pub message: String,
+    pub function_name: Option<String>,
+}
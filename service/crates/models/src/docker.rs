@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Docker system information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -158,3 +160,31 @@ pub struct LambdaServiceStats {
     pub max_duration_ms: f64,
     pub min_duration_ms: f64,
 }
+
+/// State of the circuit breaker around Docker API calls
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DockerBreakerState {
+    /// Calls are made
+    #[default]
+    Closed,
+    /// Calls fail without being made
+    Open,
+    /// The open period is over; the next call decides whether the breaker closes again
+    HalfOpen,
+}
+
+/// How Docker API calls have been faring: the breaker and the retries made
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DockerApiStatus {
+    pub state: DockerBreakerState,
+    /// Calls that failed in a row after their retries
+    pub consecutive_failures: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// When an open breaker lets a call through again
+    pub retry_at: Option<DateTime<Utc>>,
+    /// Retries made since startup, by operation
+    pub retries: BTreeMap<String, u64>,
+    /// Calls failed without being made because the breaker was open
+    pub short_circuited_total: u64,
+}
@@ -1,3 +1,4 @@
+use crate::{BuildFailureKind, FunctionReference};
 use serde::{Deserialize, Serialize};
 use sqlx;
 use thiserror::Error;
@@ -8,6 +9,13 @@ pub struct ErrorShape {
     pub error_message: String,
     pub error_type: String,
     pub stack_trace: Option<Vec<String>>,
+    /// Tail of the `docker build` output when the function's image failed to build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_log: Option<String>,
+    /// Routes and mappings that keep a function from being deleted. Boxed so the shape stays
+    /// small enough to return by value from handlers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Box<[FunctionReference]>>,
 }
 
 #[derive(Error, Debug)]
@@ -15,9 +23,145 @@ pub enum LambdaError {
     #[error("Function not found: {function_name}")]
     FunctionNotFound { function_name: String },
 
+    #[error("Execution not found: {request_id}")]
+    ExecutionNotFound { request_id: String },
+
+    #[error("No trace recorded for execution: {request_id}")]
+    TraceNotFound { request_id: String },
+
+    #[error("Webhook not found: {webhook_id}")]
+    WebhookNotFound { webhook_id: String },
+
+    #[error("Queue does not exist: {queue_name}")]
+    QueueNotFound { queue_name: String },
+
+    #[error("Event source mapping not found: {uuid}")]
+    EventSourceMappingNotFound { uuid: String },
+
+    #[error("Function {function_name} has no configuration revision {revision}")]
+    ConfigRevisionNotFound {
+        function_name: String,
+        revision: u64,
+    },
+
+    #[error("Job not found: {job_id}")]
+    JobNotFound { job_id: String },
+
+    #[error("Test event not found: {event_id}")]
+    TestEventNotFound { event_id: String },
+
+    #[error("Function {function_name} has no synthetic check")]
+    SyntheticCheckNotFound { function_name: String },
+
+    #[error("Schedule {schedule_id} not found for function {function_name}")]
+    ScheduleNotFound {
+        function_name: String,
+        schedule_id: String,
+    },
+
+    #[error("Function {function_name} has no function URL")]
+    FunctionUrlConfigNotFound { function_name: String },
+
+    #[error("Function {function_name}:{qualifier} has no provisioned concurrency config")]
+    ProvisionedConcurrencyConfigNotFound {
+        function_name: String,
+        qualifier: String,
+    },
+
+    #[error("Function URL {url_id} not found")]
+    FunctionUrlNotFound { url_id: String },
+
+    #[error("Function {function_name} has not had a rollout")]
+    RolloutNotFound { function_name: String },
+
+    #[error("Function group not found: {group}")]
+    GroupNotFound { group: String },
+
+    #[error("Build not found: {build_id}")]
+    BuildNotFound { build_id: String },
+
+    #[error("Container not found: {container_id}")]
+    ContainerNotFound { container_id: String },
+
+    #[error("Exec session not found or already connected: {session_id}")]
+    ExecSessionNotFound { session_id: String },
+
+    #[error("Namespace not found: {namespace}")]
+    NamespaceNotFound { namespace: String },
+
+    #[error("Function {function_name} has no policy statement {statement_id}")]
+    PermissionNotFound {
+        function_name: String,
+        statement_id: String,
+    },
+
+    #[error("Unknown feature flag: {name}")]
+    FeatureFlagNotFound { name: String },
+
+    #[error("Feature {name} is disabled")]
+    FeatureDisabled { name: String },
+
+    #[error("Change feed cursor {cursor} points at events that have been trimmed")]
+    ExpiredCursor { cursor: String },
+
+    #[error("Event was not processed within {max_event_age_secs} seconds and was dropped")]
+    EventAgeExceeded { max_event_age_secs: u64 },
+
+    #[error("Secrets already exist: {}", names.join(", "))]
+    SecretsAlreadyExist { names: Vec<String> },
+
+    #[error("Feature flag {name} only changes on restart; set it under [features]")]
+    FeatureFlagRequiresRestart { name: String },
+
     #[error("Function already exists: {function_name}")]
     FunctionAlreadyExists { function_name: String },
 
+    #[error("Function group already exists: {group}")]
+    GroupAlreadyExists { group: String },
+
+    #[error("Function {function_name} is disabled with its group {group}")]
+    FunctionDisabled {
+        function_name: String,
+        group: String,
+    },
+
+    #[error("Invocation of {function_name} was rejected by its pre-invoke hook: {reason}")]
+    InvocationRejected {
+        function_name: String,
+        reason: String,
+    },
+
+    #[error("The {hook} hook of {function_name} failed: {reason}")]
+    HookFailed {
+        function_name: String,
+        hook: String,
+        reason: String,
+    },
+
+    #[error("Function {function_name} already has a policy statement {statement_id}")]
+    PermissionAlreadyExists {
+        function_name: String,
+        statement_id: String,
+    },
+
+    #[error("Function {function_name} already has a function URL")]
+    FunctionUrlConfigAlreadyExists { function_name: String },
+
+    #[error("The function URL response is malformed: {reason}")]
+    InvalidFunctionUrlResponse { reason: String },
+
+    #[error("Access denied: {reason}")]
+    AccessDenied { reason: String },
+
+    #[error(
+        "Function {function_name} is still referenced by {}",
+        references.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    FunctionInUse {
+        function_name: String,
+        references: Vec<FunctionReference>,
+    },
+
     #[error("Invalid function name: {function_name}")]
     InvalidFunctionName { function_name: String },
 
@@ -27,15 +171,34 @@ pub enum LambdaError {
     #[error("Invalid handler: {handler}")]
     InvalidHandler { handler: String },
 
+    #[error("Handler {handler} not found in deployment package: {reason}")]
+    HandlerNotFound { handler: String, reason: String },
+
     #[error("Code too large: {size} bytes (max: {max_size})")]
     CodeTooLarge { size: u64, max_size: u64 },
 
+    #[error("Build context too large: {size} bytes (max: {max_size}); trim it with .lambdaignore")]
+    BuildContextTooLarge { size: u64, max_size: u64 },
+
     #[error("Invalid ZIP file: {reason}")]
     InvalidZipFile { reason: String },
 
+    #[error("Image build of function {function_name} failed ({kind}): {summary}")]
+    ImageBuildFailed {
+        function_name: String,
+        kind: BuildFailureKind,
+        /// Line of the build output that best explains the failure
+        summary: String,
+        /// Truncated tail of the build output
+        build_log: String,
+    },
+
     #[error("Docker error: {message}")]
     DockerError { message: String },
 
+    #[error("Image build {build_id} was cancelled")]
+    BuildCancelled { build_id: String },
+
     #[error("Container timeout after {timeout_ms}ms")]
     ContainerTimeout { timeout_ms: u64 },
 
@@ -54,6 +217,28 @@ pub enum LambdaError {
     #[error("Global concurrency limit exceeded")]
     GlobalConcurrencyLimitExceeded,
 
+    #[error("Host memory budget exhausted: need {requested_mb} MB, {available_mb} MB available")]
+    HostMemoryExhausted {
+        requested_mb: u64,
+        available_mb: u64,
+    },
+
+    #[error("Quota {quota} of namespace {namespace} exceeded: limit is {limit}")]
+    QuotaExceeded {
+        namespace: String,
+        quota: String,
+        limit: u64,
+    },
+
+    #[error("Route {path} is saturated, retry in {retry_after_secs} seconds")]
+    RouteSaturated { path: String, retry_after_secs: u32 },
+
+    #[error("Stopped waiting after {waited_secs} seconds; invocation {request_id} is still running, its result will be at /api/executions/{request_id}/result")]
+    ResultPending {
+        request_id: String,
+        waited_secs: u64,
+    },
+
     #[error("Invalid request: {reason}")]
     InvalidRequest { reason: String },
 
@@ -68,6 +253,19 @@ pub enum LambdaError {
 
     #[error("Configuration error: {reason}")]
     ConfigError { reason: String },
+
+    #[error("Replication peer error: {reason}")]
+    PeerError { reason: String },
+
+    #[error("Upgrade failed: {reason}")]
+    UpgradeFailed { reason: String },
+
+    #[error("Not enough disk space at {path}: {free_mb} MB free, {required_mb} MB required")]
+    InsufficientDiskSpace {
+        path: String,
+        free_mb: u64,
+        required_mb: u64,
+    },
 }
 
 impl LambdaError {
@@ -76,54 +274,156 @@ impl LambdaError {
             error_message: self.to_string(),
             error_type: self.error_type().to_string(),
             stack_trace: None,
+            build_log: match self {
+                LambdaError::ImageBuildFailed { build_log, .. } => Some(build_log.clone()),
+                _ => None,
+            },
+            references: match self {
+                LambdaError::FunctionInUse { references, .. } => Some(references.as_slice().into()),
+                _ => None,
+            },
         }
     }
 
     pub fn error_type(&self) -> &'static str {
         match self {
             LambdaError::FunctionNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::ExecutionNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::TraceNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::WebhookNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::QueueNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::EventSourceMappingNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::ConfigRevisionNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::JobNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::TestEventNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::SyntheticCheckNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::ScheduleNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::FunctionUrlConfigNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::ProvisionedConcurrencyConfigNotFound { .. } => {
+                "ProvisionedConcurrencyConfigNotFoundException"
+            }
+            LambdaError::FunctionUrlNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::RolloutNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::GroupNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::BuildNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::ContainerNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::ExecSessionNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::NamespaceNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::PermissionNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::FeatureFlagNotFound { .. } => "ResourceNotFoundException",
+            LambdaError::FeatureDisabled { .. } => "ResourceNotFoundException",
+            LambdaError::ExpiredCursor { .. } => "ExpiredIteratorException",
+            LambdaError::EventAgeExceeded { .. } => "EventAgeExceededException",
+            LambdaError::SecretsAlreadyExist { .. } => "ResourceConflictException",
+            LambdaError::FeatureFlagRequiresRestart { .. } => "ResourceConflictException",
             LambdaError::FunctionAlreadyExists { .. } => "ResourceConflictException",
+            LambdaError::GroupAlreadyExists { .. } => "ResourceConflictException",
+            LambdaError::FunctionDisabled { .. } => "ResourceConflictException",
+            LambdaError::PermissionAlreadyExists { .. } => "ResourceConflictException",
+            LambdaError::FunctionUrlConfigAlreadyExists { .. } => "ResourceConflictException",
+            LambdaError::InvalidFunctionUrlResponse { .. } => "InvalidResponseException",
+            LambdaError::AccessDenied { .. } => "AccessDeniedException",
+            LambdaError::InvocationRejected { .. } => "InvocationRejectedException",
+            LambdaError::HookFailed { .. } => "HookFailedException",
+            LambdaError::FunctionInUse { .. } => "ResourceConflictException",
             LambdaError::InvalidFunctionName { .. } => "InvalidParameterValueException",
             LambdaError::InvalidRuntime { .. } => "InvalidParameterValueException",
             LambdaError::InvalidHandler { .. } => "InvalidParameterValueException",
+            LambdaError::HandlerNotFound { .. } => "InvalidParameterValueException",
             LambdaError::CodeTooLarge { .. } => "InvalidParameterValueException",
+            LambdaError::BuildContextTooLarge { .. } => "InvalidParameterValueException",
             LambdaError::InvalidZipFile { .. } => "InvalidParameterValueException",
+            LambdaError::ImageBuildFailed { .. } => "ImageBuildFailedException",
             LambdaError::DockerError { .. } => "ServiceException",
+            LambdaError::BuildCancelled { .. } => "ResourceConflictException",
             LambdaError::ContainerTimeout { .. } => "TaskTimedOutException",
             LambdaError::ContainerOOM => "OutOfMemoryError",
             LambdaError::ContainerInitError { .. } => "InitError",
             LambdaError::FunctionExecutionError { .. } => "Unhandled",
             LambdaError::ConcurrencyLimitExceeded { .. } => "TooManyRequestsException",
             LambdaError::GlobalConcurrencyLimitExceeded => "TooManyRequestsException",
+            LambdaError::HostMemoryExhausted { .. } => "TooManyRequestsException",
+            LambdaError::QuotaExceeded { .. } => "QuotaExceededException",
+            LambdaError::RouteSaturated { .. } => "ServiceUnavailableException",
+            LambdaError::ResultPending { .. } => "ResultPendingException",
             LambdaError::InvalidRequest { .. } => "InvalidParameterValueException",
             LambdaError::InternalError { .. } => "ServiceException",
             LambdaError::DatabaseError { .. } => "ServiceException",
             LambdaError::SqlxError(_) => "ServiceException",
             LambdaError::ConfigError { .. } => "ServiceException",
+            LambdaError::PeerError { .. } => "ServiceException",
+            LambdaError::UpgradeFailed { .. } => "ServiceException",
+            LambdaError::InsufficientDiskSpace { .. } => "ServiceException",
         }
     }
 
     pub fn http_status(&self) -> u16 {
         match self {
             LambdaError::FunctionNotFound { .. } => 404,
+            LambdaError::ExecutionNotFound { .. } => 404,
+            LambdaError::TraceNotFound { .. } => 404,
+            LambdaError::WebhookNotFound { .. } => 404,
+            LambdaError::QueueNotFound { .. } => 404,
+            LambdaError::EventSourceMappingNotFound { .. } => 404,
+            LambdaError::ConfigRevisionNotFound { .. } => 404,
+            LambdaError::JobNotFound { .. } => 404,
+            LambdaError::TestEventNotFound { .. } => 404,
+            LambdaError::SyntheticCheckNotFound { .. } => 404,
+            LambdaError::ScheduleNotFound { .. } => 404,
+            LambdaError::FunctionUrlConfigNotFound { .. } => 404,
+            LambdaError::ProvisionedConcurrencyConfigNotFound { .. } => 404,
+            LambdaError::FunctionUrlNotFound { .. } => 404,
+            LambdaError::RolloutNotFound { .. } => 404,
+            LambdaError::GroupNotFound { .. } => 404,
+            LambdaError::BuildNotFound { .. } => 404,
+            LambdaError::ContainerNotFound { .. } => 404,
+            LambdaError::ExecSessionNotFound { .. } => 404,
+            LambdaError::NamespaceNotFound { .. } => 404,
+            LambdaError::PermissionNotFound { .. } => 404,
+            LambdaError::FeatureFlagNotFound { .. } => 404,
+            LambdaError::FeatureDisabled { .. } => 404,
+            LambdaError::ExpiredCursor { .. } => 410,
+            LambdaError::EventAgeExceeded { .. } => 410,
+            LambdaError::SecretsAlreadyExist { .. } => 409,
+            LambdaError::FeatureFlagRequiresRestart { .. } => 409,
             LambdaError::FunctionAlreadyExists { .. } => 409,
+            LambdaError::GroupAlreadyExists { .. } => 409,
+            LambdaError::FunctionDisabled { .. } => 409,
+            LambdaError::PermissionAlreadyExists { .. } => 409,
+            LambdaError::FunctionUrlConfigAlreadyExists { .. } => 409,
+            LambdaError::InvalidFunctionUrlResponse { .. } => 502,
+            LambdaError::AccessDenied { .. } => 403,
+            LambdaError::InvocationRejected { .. } => 403,
+            LambdaError::HookFailed { .. } => 502,
+            LambdaError::FunctionInUse { .. } => 409,
             LambdaError::InvalidFunctionName { .. } => 400,
             LambdaError::InvalidRuntime { .. } => 400,
             LambdaError::InvalidHandler { .. } => 400,
+            LambdaError::HandlerNotFound { .. } => 400,
             LambdaError::CodeTooLarge { .. } => 400,
+            LambdaError::BuildContextTooLarge { .. } => 400,
             LambdaError::InvalidZipFile { .. } => 400,
+            LambdaError::ImageBuildFailed { .. } => 502,
             LambdaError::DockerError { .. } => 500,
+            LambdaError::BuildCancelled { .. } => 409,
             LambdaError::ContainerTimeout { .. } => 200, // Lambda returns 200 with error header
             LambdaError::ContainerOOM => 200,            // Lambda returns 200 with error header
             LambdaError::ContainerInitError { .. } => 200, // Lambda returns 200 with error header
             LambdaError::FunctionExecutionError { .. } => 200, // Lambda returns 200 with error header
             LambdaError::ConcurrencyLimitExceeded { .. } => 429,
             LambdaError::GlobalConcurrencyLimitExceeded => 429,
+            LambdaError::HostMemoryExhausted { .. } => 429,
+            LambdaError::QuotaExceeded { .. } => 429,
+            LambdaError::RouteSaturated { .. } => 503,
+            LambdaError::ResultPending { .. } => 504,
             LambdaError::InvalidRequest { .. } => 400,
             LambdaError::InternalError { .. } => 500,
             LambdaError::DatabaseError { .. } => 500,
             LambdaError::SqlxError(_) => 500,
             LambdaError::ConfigError { .. } => 500,
+            LambdaError::PeerError { .. } => 502,
+            LambdaError::UpgradeFailed { .. } => 500,
+            LambdaError::InsufficientDiskSpace { .. } => 507,
         }
     }
 }
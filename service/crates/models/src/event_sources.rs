@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Bucket name from `arn:<partition>:s3:::<bucket>`, the ARN of a bucket in an external
+/// S3-compatible store
+pub fn bucket_name_from_arn(arn: &str) -> Option<&str> {
+    let (_partition, rest) = arn.strip_prefix("arn:")?.split_once(':')?;
+    rest.strip_prefix("s3:::")
+        .filter(|name| !name.is_empty() && !name.contains(['/', ':']))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FunctionResponseType {
+    /// The function returns `batchItemFailures` so only those messages are retried
+    ReportBatchItemFailures,
+}
+
+/// Object keys an S3 mapping reacts to; both must match when set
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3KeyFilter {
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub suffix: Option<String>,
+}
+
+/// Delivers messages from a local queue to a function in batches, or bucket notifications
+/// from an S3-compatible store one record at a time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSourceMapping {
+    pub uuid: Uuid,
+    pub event_source_arn: String,
+    pub function_name: String,
+    pub batch_size: u32,
+    pub enabled: bool,
+    pub function_response_types: Vec<FunctionResponseType>,
+    pub last_modified: DateTime<Utc>,
+    /// Outcome of the latest batch, e.g. `OK` or the function error
+    pub last_processing_result: Option<String>,
+    /// Only for S3 sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub s3_key_filter: Option<S3KeyFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateEventSourceMappingRequest {
+    /// ARN of a local queue, `arn:aws:sqs:<region>:<account>:<queue>`, or of a bucket,
+    /// `arn:aws:s3:::<bucket>`
+    pub event_source_arn: String,
+    pub function_name: String,
+    #[serde(default)]
+    pub batch_size: Option<u32>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub function_response_types: Vec<FunctionResponseType>,
+    #[serde(default)]
+    pub s3_key_filter: Option<S3KeyFilter>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateEventSourceMappingRequest {
+    #[serde(default)]
+    pub batch_size: Option<u32>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub function_response_types: Option<Vec<FunctionResponseType>>,
+    #[serde(default)]
+    pub s3_key_filter: Option<S3KeyFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListEventSourceMappingsResponse {
+    pub event_source_mappings: Vec<EventSourceMapping>,
+}
+
+/// Payload a mapped function receives, shaped like Lambda's SQS event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SqsEvent {
+    pub records: Vec<SqsEventRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsEventRecord {
+    pub message_id: String,
+    pub receipt_handle: String,
+    pub body: String,
+    /// `ApproximateReceiveCount`, `SentTimestamp`, `SenderId`, `ApproximateFirstReceiveTimestamp`
+    pub attributes: HashMap<String, String>,
+    pub message_attributes: HashMap<String, serde_json::Value>,
+    pub md5_of_body: String,
+    pub event_source: String,
+    #[serde(rename = "eventSourceARN")]
+    pub event_source_arn: String,
+    pub aws_region: String,
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Debug shell into a warm container, opened with `POST /api/containers/{id}/exec`. The
+/// shell runs once a client connects to `websocket_path` and ends at `expires_at` at the latest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ExecSession {
+    pub session_id: String,
+    pub container_id: String,
+    pub function_name: String,
+    /// Who opened the session (`X-Lambdah-Actor`)
+    pub opened_by: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// WebSocket to connect to, once; the session id is the credential
+    pub websocket_path: String,
+}
+
+/// What happened in an exec session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ExecAuditEvent {
+    Opened,
+    Connected,
+    /// Bytes the client sent to the shell, lossily decoded as UTF-8
+    Input {
+        data: String,
+    },
+    Closed {
+        reason: String,
+    },
+}
+
+/// One line of the exec audit log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExecAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    pub container_id: String,
+    pub function_name: String,
+    pub actor: Option<String>,
+    #[serde(flatten)]
+    pub event: ExecAuditEvent,
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A single invocation as recorded in the executions table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    pub execution_id: String,
+    pub aws_request_id: String,
+    pub function_name: Option<String>,
+    pub function_version: String,
+    pub container_id: Option<String>,
+    pub status: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub error: Option<ExecutionErrorDetail>,
+    /// A runtime API trace was recorded, at `GET /api/executions/{id}/trace`
+    #[serde(default)]
+    pub traced: bool,
+}
+
+/// Error reported for a failed execution, as posted by the runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionErrorDetail {
+    pub error_type: String,
+    pub error_message: Option<String>,
+    pub stack_trace: Option<Vec<String>>,
+    /// Raw error document (errorMessage, errorType, stackTrace, ...)
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Runtime API exchange of one invocation run with `X-Lambdah-Trace: true`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InvocationTrace {
+    pub request_id: String,
+    pub function_name: String,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub events: Vec<RuntimeTraceEvent>,
+}
+
+/// One call of the runtime API made for a traced invocation. Bodies longer than the trace
+/// limit are cut, with `body_truncated` set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeTraceEvent {
+    pub at: DateTime<Utc>,
+    pub instance_id: Option<String>,
+    #[serde(flatten)]
+    pub call: RuntimeTraceCall,
+    /// Headers sent to the runtime for `next`, received from it for `response` and `error`
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+    #[serde(default)]
+    pub body_truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "call", rename_all = "snake_case")]
+pub enum RuntimeTraceCall {
+    /// `GET /runtime/invocation/next` that delivered the invocation
+    Next {
+        polled_at: DateTime<Utc>,
+        waited_ms: u64,
+    },
+    /// `POST /runtime/invocation/{id}/response`
+    Response,
+    /// `POST /runtime/invocation/{id}/error`
+    Error,
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+/// An optional subsystem that can ship dark and be turned on under `[features]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureFlag {
+    /// Scheduled synthetic invocations: the checker task and the synthetic-check routes
+    SyntheticChecks,
+    /// `GET /api/functions/{name}/insights`
+    FunctionInsights,
+    /// `GET /api/gateway/openapi.json`
+    GatewayOpenapi,
+}
+
+impl FeatureFlag {
+    pub const ALL: &'static [FeatureFlag] = &[
+        FeatureFlag::SyntheticChecks,
+        FeatureFlag::FunctionInsights,
+        FeatureFlag::GatewayOpenapi,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FeatureFlag::SyntheticChecks => "synthetic_checks",
+            FeatureFlag::FunctionInsights => "function_insights",
+            FeatureFlag::GatewayOpenapi => "gateway_openapi",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            FeatureFlag::SyntheticChecks => {
+                "Scheduled synthetic invocations and the synthetic-check API"
+            }
+            FeatureFlag::FunctionInsights => "Per-function health insights endpoint",
+            FeatureFlag::GatewayOpenapi => "Generated OpenAPI document of the gateway's routes",
+        }
+    }
+
+    /// Whether the flag is on when `[features]` does not name it
+    pub fn default_enabled(self) -> bool {
+        match self {
+            FeatureFlag::SyntheticChecks
+            | FeatureFlag::FunctionInsights
+            | FeatureFlag::GatewayOpenapi => true,
+        }
+    }
+
+    /// Whether the flag can be toggled on a running server. Flags that gate route
+    /// registration or background tasks are read once at startup.
+    pub fn runtime_safe(self) -> bool {
+        match self {
+            FeatureFlag::SyntheticChecks => false,
+            FeatureFlag::FunctionInsights | FeatureFlag::GatewayOpenapi => true,
+        }
+    }
+}
+
+impl std::str::FromStr for FeatureFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FeatureFlag::ALL
+            .iter()
+            .copied()
+            .find(|flag| flag.name() == s)
+            .ok_or_else(|| format!("Unknown feature flag: {s}"))
+    }
+}
+
+/// A feature flag and where its current value comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagStatus {
+    pub name: FeatureFlag,
+    pub description: String,
+    pub enabled: bool,
+    /// Value under `[features]`, or the flag's default
+    pub configured: bool,
+    /// Whether the admin API set `enabled` apart from `configured`; overrides last until
+    /// reset or restart
+    pub overridden: bool,
+    pub runtime_safe: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListFeatureFlagsResponse {
+    pub flags: Vec<FeatureFlagStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetFeatureFlagRequest {
+    pub enabled: bool,
+}
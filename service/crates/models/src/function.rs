@@ -1,6 +1,7 @@
+use crate::{ErrorShape, IdentityConfig, LambdaError};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +23,71 @@ pub struct Function {
     pub state: FunctionState,
     pub state_reason: Option<String>,
     pub state_reason_code: Option<String>,
+    /// Outcome of the last create or image build, polled by `aws lambda wait
+    /// function-updated-v2`
+    #[serde(default)]
+    pub last_update_status: LastUpdateStatus,
+    #[serde(default)]
+    pub last_update_status_reason: Option<String>,
+    #[serde(default)]
+    pub last_update_status_reason_code: Option<String>,
+    /// Operational context; only populated by GetFunction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<FunctionMetadata>,
+    /// `arn:<partition>:lambda:<region>:<account>:function:<name>` under the configured
+    /// identity; only populated by GetFunction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_arn: Option<String>,
+    #[serde(default)]
+    pub package_type: PackageType,
+    /// Image the function runs with the `Image` package type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_uri: Option<String>,
+}
+
+impl Function {
+    /// Image the function's containers run: its own with the `Image` package type,
+    /// otherwise the one built from its code
+    pub fn image_ref(&self) -> String {
+        match (&self.package_type, &self.image_uri) {
+            (PackageType::Image, Some(image_uri)) => image_uri.clone(),
+            _ => format!("lambda-home/{}:{}", self.function_name, self.code_sha256),
+        }
+    }
+
+    /// The function as `version` published it: that version's code and configuration
+    pub fn at_version(&self, version: &Version) -> Function {
+        let mut function = self.clone();
+        function.version = version.version.clone();
+        function.code_sha256 = version.code_sha256.clone();
+        function.code_size = version.code_size;
+        if version.image_uri.is_some() {
+            function.image_uri = version.image_uri.clone();
+        }
+        if let Some(config) = &version.config {
+            function.role = config.role.clone();
+            function.handler = config.handler.clone();
+            function.description = config.description.clone();
+            function.timeout = config.timeout;
+            function.memory_size = config.memory_size;
+            function.environment = config.environment.clone();
+        }
+        function
+    }
+}
+
+/// Version of a function that is not published: its code and configuration as they are now
+pub const LATEST_VERSION: &str = "$LATEST";
+
+/// How a function's code is deployed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub enum PackageType {
+    /// A ZIP the server builds an image from
+    #[default]
+    Zip,
+    /// A pre-built container image
+    Image,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,6 +97,33 @@ pub enum FunctionState {
     Active,
     Inactive,
     Failed,
+    /// Idle long enough to have its image and containers removed; the code is kept and the
+    /// next invoke or unarchive rebuilds it
+    Archived,
+}
+
+impl std::str::FromStr for FunctionState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Pending" => Ok(FunctionState::Pending),
+            "Active" => Ok(FunctionState::Active),
+            "Inactive" => Ok(FunctionState::Inactive),
+            "Failed" => Ok(FunctionState::Failed),
+            "Archived" => Ok(FunctionState::Archived),
+            _ => Err(format!("Invalid function state: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub enum LastUpdateStatus {
+    #[default]
+    Successful,
+    InProgress,
+    Failed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +136,16 @@ pub struct Version {
     pub code_sha256: String,
     pub last_modified: DateTime<Utc>,
     pub code_size: u64,
+    /// Image ID built from this code, once it has been built
+    #[serde(default)]
+    pub image_digest: Option<String>,
+    /// Configuration the version runs with; versions published before it was recorded run
+    /// with the function's current configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<FunctionConfigSnapshot>,
+    /// Image the version runs with the `Image` package type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_uri: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,9 +173,534 @@ pub struct ConcurrencyConfig {
     pub reserved_concurrent_executions: Option<u32>,
 }
 
-// Request/Response types for API
+/// PutProvisionedConcurrencyConfig body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct PutProvisionedConcurrencyConfigRequest {
+    pub provisioned_concurrent_executions: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProvisionedConcurrencyStatus {
+    /// The image is building or containers are still starting
+    InProgress,
+    Ready,
+    /// The image build failed; see `StatusReason`
+    Failed,
+}
+
+/// Containers kept warm for a version or alias, in AWS's shape
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProvisionedConcurrencyConfig {
+    pub requested_provisioned_concurrent_executions: u32,
+    /// Containers started, including those still initializing
+    pub allocated_provisioned_concurrent_executions: u32,
+    /// Containers ready to take invocations
+    pub available_provisioned_concurrent_executions: u32,
+    pub status: ProvisionedConcurrencyStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>,
+    pub last_modified: DateTime<Utc>,
+}
+
+/// Invocations of a function running and waiting for a concurrency token now, against the
+/// limit they count toward
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConcurrencyUsage {
+    pub function_name: String,
+    pub in_flight: u32,
+    /// Invocations waiting because the limit is reached; they run once others finish
+    pub waiting: u32,
+    pub reserved_concurrent_executions: Option<u32>,
+    /// Limit of the concurrency shared by functions without reserved concurrency
+    pub unreserved_concurrency_limit: u32,
+    /// Invocations of all those functions running now
+    pub unreserved_in_flight: u32,
+    /// Share of the limit in use: `in_flight` of the reserved concurrency, or
+    /// `unreserved_in_flight` of the shared limit; 1.0 means new invocations wait
+    pub utilization: f64,
+}
+
+/// Concurrency in use across functions, as exported to Prometheus
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConcurrencyOverview {
+    pub unreserved_concurrency_limit: u32,
+    pub unreserved_in_flight: u32,
+    /// Functions with invocations running or waiting, or with reserved concurrency
+    pub functions: Vec<ConcurrencyUsage>,
+}
+
+/// GetFunction response in the shape AWS SDKs and Terraform parse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetFunctionResponse {
+    pub configuration: FunctionConfiguration,
+    pub code: FunctionCodeLocation,
+    pub tags: BTreeMap<String, String>,
+    /// Only present with reserved concurrency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<FunctionConcurrency>,
+}
+
+/// A function's configuration as AWS returns it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FunctionConfiguration {
+    pub function_name: String,
+    pub function_arn: String,
+    pub runtime: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub handler: String,
+    pub code_size: u64,
+    pub description: String,
+    /// Seconds
+    pub timeout: u64,
+    pub memory_size: u64,
+    /// e.g. `2024-05-01T12:00:00.000+0000`
+    pub last_modified: String,
+    pub code_sha256: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<EnvironmentResponse>,
+    pub state: FunctionState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_reason_code: Option<String>,
+    pub last_update_status: LastUpdateStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update_status_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update_status_reason_code: Option<String>,
+    pub package_type: PackageType,
+}
+
+impl FunctionConfiguration {
+    pub fn new(function: &Function, function_arn: String) -> Self {
+        Self {
+            function_name: function.function_name.clone(),
+            function_arn,
+            runtime: function.runtime.clone(),
+            role: function.role.clone(),
+            handler: function.handler.clone(),
+            code_size: function.code_size,
+            description: function.description.clone().unwrap_or_default(),
+            timeout: function.timeout,
+            memory_size: function.memory_size,
+            last_modified: function
+                .last_modified
+                .format("%Y-%m-%dT%H:%M:%S%.3f%z")
+                .to_string(),
+            code_sha256: function.code_sha256.clone(),
+            version: function.version.clone(),
+            environment: (!function.environment.is_empty()).then(|| EnvironmentResponse {
+                variables: function.environment.clone(),
+            }),
+            state: function.state.clone(),
+            state_reason: function.state_reason.clone(),
+            state_reason_code: function.state_reason_code.clone(),
+            last_update_status: function.last_update_status.clone(),
+            last_update_status_reason: function.last_update_status_reason.clone(),
+            last_update_status_reason_code: function.last_update_status_reason_code.clone(),
+            package_type: function.package_type,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnvironmentResponse {
+    pub variables: HashMap<String, String>,
+}
+
+/// Where to get a function's code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FunctionCodeLocation {
+    /// `S3` for a ZIP, `ECR` for a container image
+    pub repository_type: String,
+    /// URL the ZIP is downloaded from; absent for images and functions without code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_uri: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FunctionConcurrency {
+    pub reserved_concurrent_executions: u32,
+}
+
+/// Who owns a function and where to find its code and docs
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionMetadata {
+    pub owner: Option<String>,
+    pub repo_url: Option<String>,
+    pub docs_url: Option<String>,
+    /// Freeform notes, rendered as Markdown by the console
+    pub notes: Option<String>,
+}
+
+/// Opt-in hedged invokes for idempotent, read-style handlers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HedgingConfig {
+    pub enabled: bool,
+    /// Percentile of recent successful durations used as the hedge delay (default 99)
+    pub percentile: Option<f64>,
+    /// Never hedge sooner than this, in milliseconds
+    pub min_delay_ms: Option<u64>,
+}
+
+/// How long a function's containers get to finish after SIGTERM before Docker kills them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StopGraceConfig {
+    pub grace_period_seconds: u64,
+}
+
+/// How long a function's containers stay warm and how many it keeps. Unset fields follow
+/// `[idle]` and the autoscaler's defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionLifecycle {
+    /// Idle time after which a container is stopped, instead of `idle.soft_ms`. Stopped
+    /// containers are removed at `idle.hard_ms`, or at this timeout when it is later.
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+    /// Containers the function may run at once, across its versions; invocations past
+    /// them wait for a busy one
+    #[serde(default)]
+    pub max_warm_containers: Option<u32>,
+    /// Containers of `$LATEST` kept running even when idle
+    #[serde(default)]
+    pub min_warm_containers: Option<u32>,
+}
+
+/// Sandbox profile a function's containers run under, one of `[sandbox]`'s
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionSandbox {
+    pub profile: String,
+}
+
+/// User and group a function's containers run as. The bundled runtime images run as
+/// `1000:1000`; ids rather than names, since the read-only root filesystem leaves no way to
+/// add users to the image
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionUser {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl Default for FunctionUser {
+    fn default() -> Self {
+        Self {
+            uid: 1000,
+            gid: 1000,
+        }
+    }
+}
+
+impl FunctionUser {
+    /// `uid:gid`, as Docker takes it
+    pub fn docker_user(&self) -> String {
+        format!("{}:{}", self.uid, self.gid)
+    }
+
+    /// Root, or in the root group
+    pub fn is_root(&self) -> bool {
+        self.uid == 0 || self.gid == 0
+    }
+}
+
+/// Timezone and locale a function's containers run with, injected as `TZ`, `LANG` and
+/// `LC_ALL`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LocaleConfig {
+    /// tz database name such as `Europe/Rome`; UTC when unset
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// POSIX locale such as `de_DE.UTF-8`; the image's default when unset
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Fake credentials and local service endpoints given to a function's containers, so AWS
+/// SDKs in its handler work against emulators such as MinIO or LocalStack. Values may use
+/// `{function_name}`, `{region}` and `{account_id}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AwsEmulation {
+    /// Off leaves the function without them even when `[aws_emulation]` is enabled
+    #[serde(default = "default_aws_emulation_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_fake_credential")]
+    pub access_key_id: String,
+    #[serde(default = "default_fake_credential")]
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub session_token: Option<String>,
+    /// Endpoint of every service without its own, as `AWS_ENDPOINT_URL`
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+    /// Endpoints by service id such as `s3` or `dynamodb`, as `AWS_ENDPOINT_URL_<SERVICE>`
+    #[serde(default)]
+    pub service_endpoints: BTreeMap<String, String>,
+}
+
+fn default_aws_emulation_enabled() -> bool {
+    true
+}
+
+fn default_fake_credential() -> String {
+    "test".to_string()
+}
+
+impl Default for AwsEmulation {
+    fn default() -> Self {
+        Self {
+            enabled: default_aws_emulation_enabled(),
+            access_key_id: default_fake_credential(),
+            secret_access_key: default_fake_credential(),
+            session_token: None,
+            endpoint_url: None,
+            service_endpoints: BTreeMap::new(),
+        }
+    }
+}
+
+impl AwsEmulation {
+    /// Placeholders values may use
+    pub const PLACEHOLDERS: &'static [&'static str] = &["function_name", "region", "account_id"];
+
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let invalid = |reason: String| {
+            Err(LambdaError::InvalidRequest {
+                reason: format!("aws_emulation: {reason}"),
+            })
+        };
+        if self.access_key_id.is_empty() || self.secret_access_key.is_empty() {
+            return invalid("access_key_id and secret_access_key must not be empty".to_string());
+        }
+        let mut values = vec![
+            ("access_key_id", &self.access_key_id),
+            ("secret_access_key", &self.secret_access_key),
+        ];
+        values.extend(self.session_token.iter().map(|v| ("session_token", v)));
+        for (field, value) in values {
+            if let Err(reason) = render(value, |_| Some("")) {
+                return invalid(format!("{field}: {reason}"));
+            }
+        }
+        let endpoints = self
+            .endpoint_url
+            .iter()
+            .map(|url| ("endpoint_url".to_string(), url))
+            .chain(
+                self.service_endpoints
+                    .iter()
+                    .map(|(service, url)| (format!("service_endpoints.{service}"), url)),
+            );
+        for (field, url) in endpoints {
+            let url = match render(url, |_| Some("x")) {
+                Ok(url) => url,
+                Err(reason) => return invalid(format!("{field}: {reason}")),
+            };
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return invalid(format!("{field} must be an http:// or https:// URL"));
+            }
+        }
+        for service in self.service_endpoints.keys() {
+            let valid = !service.is_empty()
+                && service
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+            if !valid {
+                return invalid(format!(
+                    "service {service:?} must be a lowercase service id such as s3 or dynamodb"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Environment variables the settings give a container of `function_name`, with
+    /// placeholders filled in; none when disabled
+    pub fn environment(
+        &self,
+        function_name: &str,
+        identity: &IdentityConfig,
+    ) -> BTreeMap<String, String> {
+        let mut env = BTreeMap::new();
+        if !self.enabled {
+            return env;
+        }
+        let fill = |value: &str| {
+            render(value, |name| match name {
+                "function_name" => Some(function_name),
+                "region" => Some(identity.region.as_str()),
+                "account_id" => Some(identity.account_id.as_str()),
+                _ => None,
+            })
+            .unwrap_or_else(|_| value.to_string())
+        };
+        env.insert("AWS_ACCESS_KEY_ID".to_string(), fill(&self.access_key_id));
+        env.insert(
+            "AWS_SECRET_ACCESS_KEY".to_string(),
+            fill(&self.secret_access_key),
+        );
+        if let Some(token) = &self.session_token {
+            env.insert("AWS_SESSION_TOKEN".to_string(), fill(token));
+        }
+        env.insert("AWS_REGION".to_string(), identity.region.clone());
+        env.insert("AWS_DEFAULT_REGION".to_string(), identity.region.clone());
+        if let Some(url) = &self.endpoint_url {
+            env.insert("AWS_ENDPOINT_URL".to_string(), fill(url));
+        }
+        for (service, url) in &self.service_endpoints {
+            env.insert(
+                format!(
+                    "AWS_ENDPOINT_URL_{}",
+                    service.replace('-', "_").to_uppercase()
+                ),
+                fill(url),
+            );
+        }
+        env
+    }
+}
+
+/// Fill `{name}` placeholders of `template`; an unknown or unclosed one is an error
+fn render<'a>(template: &str, value: impl Fn(&str) -> Option<&'a str>) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("unclosed placeholder in {template:?}"))?;
+        let name = &rest[start + 1..start + end];
+        if !AwsEmulation::PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "unknown placeholder {{{name}}}, expected one of {{{}}}",
+                AwsEmulation::PLACEHOLDERS.join("}, {")
+            ));
+        }
+        out.push_str(value(name).unwrap_or_default());
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// A function's AWS emulation: its own settings, else those of `[aws_emulation]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionAwsEmulation {
+    /// Whether the function has settings of its own
+    pub overridden: bool,
+    pub settings: AwsEmulation,
+    /// What its containers get
+    pub environment: BTreeMap<String, String>,
+}
+
+/// Prewarm invocation sent to containers the autoscaler starts, so handlers can load
+/// models or open connections before real traffic reaches them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PrewarmConfig {
+    pub enabled: bool,
+    /// How long the handler gets for the prewarm event; the function timeout when unset
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RolloutStatus {
+    InProgress,
+    Completed,
+    Failed,
+}
+
+/// Replacement of a function's warm containers after a configuration change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rollout {
+    pub function_name: String,
+    pub status: RolloutStatus,
+    /// Settings whose change started the rollout, `memory_size` and `environment`
+    pub changed: Vec<String>,
+    /// Running containers with the previous settings when the rollout started
+    pub total: usize,
+    /// Of those, the ones drained and replaced so far
+    pub replaced: usize,
+    /// Stopped containers with the previous settings, removed without a replacement
+    pub removed_stopped: usize,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Why the rollout failed
+    pub error: Option<String>,
+}
+
+/// Headers for a function's successful direct invoke responses
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseHeadersConfig {
+    /// Sent with every successful response, e.g. `Content-Type` or `Cache-Control`; names
+    /// are stored lowercase
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Treat results shaped `{ "headers": {...}, "body": ... }` as an envelope: the headers
+    /// are sent as response headers and `body` as the response
+    #[serde(default)]
+    pub envelope: bool,
+}
+
+/// When a function's builds move to newer images of its runtime's base image
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub enum UpdateRuntimeOn {
+    /// Build on whatever the base image tag currently points to
+    #[default]
+    Auto,
+    /// Build on the pinned `runtime_version` digest only
+    Manual,
+}
+
+/// Runtime version pinning for a function, like Lambda's PutRuntimeManagementConfig
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeManagementConfig {
+    pub update_runtime_on: UpdateRuntimeOn,
+    /// Base image digest (`sha256:...`) to pin with `Manual`; the digest of the last build
+    /// when omitted
+    #[serde(default)]
+    pub runtime_version: Option<String>,
+}
+
+/// Runtime management settings of a function and the base image its last build used
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeManagement {
+    pub update_runtime_on: UpdateRuntimeOn,
+    /// Pinned digest with `Manual`
+    pub runtime_version: Option<String>,
+    /// Base image of the function's runtime, e.g. `node:22-alpine`
+    pub base_image: String,
+    /// Digest of the base image the last build used, when Docker reported one
+    pub last_build_runtime_version: Option<String>,
+    pub last_build_at: Option<DateTime<Utc>>,
+}
+
+// Request/Response types for API
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct CreateFunctionRequest {
     pub function_name: String,
@@ -84,16 +712,29 @@ pub struct CreateFunctionRequest {
     pub timeout: Option<u64>,
     pub memory_size: Option<u64>,
     pub environment: Option<HashMap<String, String>>,
+    /// Publish version 1 together with the function
     pub publish: Option<bool>,
+    /// `Image` deploys `code.image_uri` as it is instead of building an image from a ZIP;
+    /// `Zip` when omitted
+    pub package_type: Option<PackageType>,
+    pub tags: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct FunctionCode {
     pub zip_file: Option<String>, // base64 encoded
     pub s3_bucket: Option<String>,
     pub s3_key: Option<String>,
     pub s3_object_version: Option<String>,
+    /// Path of the function's package inside a zipped npm or pnpm workspace; only that
+    /// package and the workspace packages it depends on are deployed
+    #[serde(default)]
+    pub workspace_package: Option<String>,
+    /// Pre-built image to run, e.g. `registry.example.com/orders:1.4`; only with the `Image`
+    /// package type. The image brings its own runtime interface client.
+    #[serde(default)]
+    pub image_uri: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +758,111 @@ pub struct UpdateFunctionConfigurationRequest {
     pub environment: Option<HashMap<String, String>>,
 }
 
+/// Prefix of environment values that stand for a secret, resolved when containers start
+pub const SECRET_REF_PREFIX: &str = "SECRET_REF:";
+
+/// An environment variable as the console's editor shows it. Secret references carry the
+/// name of the secret, never its value.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentVariable {
+    pub name: String,
+    /// Plain value; `None` for secret references
+    pub value: Option<String>,
+    /// Name of the referenced secret
+    pub secret: Option<String>,
+    /// Whether the referenced secret exists; a missing one reaches the function unresolved
+    pub secret_exists: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionEnvironment {
+    /// Sorted by name
+    pub variables: Vec<EnvironmentVariable>,
+}
+
+/// New value of a variable: a plain string, or `{ "secret": "<name>" }`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EnvironmentValue {
+    Plain(String),
+    Secret { secret: String },
+}
+
+/// Partial environment update; variables left out keep their values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchEnvironmentRequest {
+    /// Variables to add or change; `null` removes one
+    pub variables: HashMap<String, Option<EnvironmentValue>>,
+}
+
+/// `POST /api/functions/batch`: one operation applied to every selected function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchFunctionsRequest {
+    pub selector: FunctionSelector,
+    pub operation: BatchOperation,
+}
+
+/// Functions a batch applies to: those named, plus those starting with `name_prefix`,
+/// carrying all of `tags` and in `group`. At least one must be given, so a batch never
+/// reaches every function by accident.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionSelector {
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub name_prefix: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BatchOperation {
+    /// DeleteFunction, refused per function while routes or mappings name it
+    Delete,
+    /// Add, change or remove environment variables as `PATCH .../environment` does
+    PatchEnvironment {
+        variables: HashMap<String, Option<EnvironmentValue>>,
+    },
+    /// Set memory and/or timeout, leaving the rest of each configuration alone
+    UpdateConfiguration {
+        #[serde(default)]
+        memory_size: Option<u64>,
+        #[serde(default)]
+        timeout: Option<u64>,
+    },
+    /// Add or overwrite tags as TagResource does
+    Tag { tags: BTreeMap<String, String> },
+    /// Stop and remove the function's containers; the next invocation starts cold
+    Drain,
+}
+
+/// Outcome of a batch for one function. Each function is changed on its own, so one
+/// failing leaves the others done.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub function_name: String,
+    /// HTTP status the single-function call would have answered
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorShape>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchFunctionsResponse {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Sorted by function name
+    pub results: Vec<BatchItemResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct PublishVersionRequest {
@@ -148,6 +894,107 @@ pub struct ListFunctionsResponse {
     pub functions: Vec<Function>,
     pub next_marker: Option<String>,
     pub total_count: Option<u32>,
+    /// Warm state of each listed function by name, when the query asked for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_state: Option<BTreeMap<String, FunctionWarmState>>,
+}
+
+/// Containers a function has running and invocations waiting for one, across its versions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionWarmState {
+    pub warm_idle: u32,
+    pub active: u32,
+    pub queued: u32,
+}
+
+/// Column ListFunctions orders by; ties are broken by function name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionSortKey {
+    #[default]
+    Name,
+    LastModified,
+    /// Executions recorded for the function
+    InvocationCount,
+}
+
+impl std::str::FromStr for FunctionSortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(FunctionSortKey::Name),
+            "last_modified" => Ok(FunctionSortKey::LastModified),
+            "invocation_count" => Ok(FunctionSortKey::InvocationCount),
+            _ => Err(format!(
+                "Invalid sort key: {s} (expected name, last_modified or invocation_count)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Invalid sort order: {s} (expected asc or desc)")),
+        }
+    }
+}
+
+/// TagResource body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct TagResourceRequest {
+    pub tags: BTreeMap<String, String>,
+}
+
+/// ListTags answer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListTagsResponse {
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Function name from `arn:<partition>:lambda:<region>:<account>:function:<name>`, with or
+/// without a trailing `:<qualifier>`
+pub fn function_name_from_arn(arn: &str) -> Option<&str> {
+    let mut parts = arn.strip_prefix("arn:")?.splitn(5, ':');
+    let (_partition, service, _region, _account) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    let rest = parts.next().filter(|_| service == "lambda")?;
+    let name = rest.strip_prefix("function:")?;
+    Some(name.split(':').next().unwrap_or(name)).filter(|name| !name.is_empty())
+}
+
+/// Filters, ordering and page position for ListFunctions
+#[derive(Debug, Clone, Default)]
+pub struct ListFunctionsQuery {
+    /// Opaque marker from a previous page's `next_marker`
+    pub marker: Option<String>,
+    pub max_items: Option<u32>,
+    pub name_prefix: Option<String>,
+    pub runtime: Option<String>,
+    pub state: Option<FunctionState>,
+    /// Only functions carrying all of these tags
+    pub tags: BTreeMap<String, String>,
+    /// Only functions in this group
+    pub group: Option<String>,
+    pub sort_by: FunctionSortKey,
+    pub order: SortOrder,
+    /// Also report the warm state of each listed function
+    pub include_warm_state: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,3 +1010,89 @@ pub struct ListAliasesResponse {
     pub aliases: Vec<Alias>,
     pub next_marker: Option<String>,
 }
+
+/// The settings UpdateFunctionConfiguration changes, as recorded in each config revision
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionConfigSnapshot {
+    pub role: Option<String>,
+    pub handler: String,
+    pub description: Option<String>,
+    pub timeout: u64,
+    pub memory_size: u64,
+    pub environment: HashMap<String, String>,
+}
+
+impl From<&Function> for FunctionConfigSnapshot {
+    fn from(function: &Function) -> Self {
+        Self {
+            role: function.role.clone(),
+            handler: function.handler.clone(),
+            description: function.description.clone(),
+            timeout: function.timeout,
+            memory_size: function.memory_size,
+            environment: function.environment.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChangeSource {
+    Create,
+    Update,
+    Revert,
+}
+
+impl ConfigChangeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Revert => "revert",
+        }
+    }
+}
+
+impl std::str::FromStr for ConfigChangeSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(Self::Create),
+            "update" => Ok(Self::Update),
+            "revert" => Ok(Self::Revert),
+            _ => Err(format!("unknown config change source: {s}")),
+        }
+    }
+}
+
+/// One changed setting; environment variables are listed as `environment.<NAME>`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfigChange {
+    pub field: String,
+    /// Absent when the setting or variable was added
+    pub old: Option<serde_json::Value>,
+    /// Absent when the setting or variable was removed
+    pub new: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigRevision {
+    /// Numbered from 1 per function
+    pub revision: u64,
+    pub changed_at: DateTime<Utc>,
+    /// `X-Lambdah-Actor` of the request that made the change, when given
+    pub changed_by: Option<String>,
+    pub source: ConfigChangeSource,
+    /// For reverts, the revision whose configuration was restored
+    pub reverted_from: Option<u64>,
+    pub changes: Vec<ConfigChange>,
+    /// Configuration after the change
+    pub configuration: FunctionConfigSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigHistoryResponse {
+    /// Newest first
+    pub revisions: Vec<ConfigRevision>,
+}
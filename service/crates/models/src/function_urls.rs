@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Dedicated HTTP endpoint of a function, invoked with the HTTP API payload format 2.0.
+/// `POST /2021-10-31/functions/{name}/url` creates it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionUrlConfig {
+    /// e.g. `http://127.0.0.1:8000/lambda-url/4c2f.../`; requests to it and to any path below
+    /// it invoke the function
+    pub function_url: String,
+    /// Part of the URL that stays the same for as long as the config exists
+    pub url_id: String,
+    pub function_name: String,
+    pub function_arn: String,
+    /// Version or alias the URL invokes; `$LATEST` when unset
+    pub qualifier: Option<String>,
+    pub creation_time: DateTime<Utc>,
+}
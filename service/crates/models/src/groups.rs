@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A folder of functions. A function is in at most one group; deleting the group leaves its
+/// functions ungrouped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionGroup {
+    pub name: String,
+    pub description: Option<String>,
+    /// Invocations of the group's functions are refused while set
+    pub disabled: bool,
+    /// Names of the group's functions, sorted
+    pub functions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateFunctionGroupRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateFunctionGroupRequest {
+    /// Replaces the description; empty or omitted clears it
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// `GET /api/groups`: the folder tree
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListFunctionGroupsResponse {
+    /// Sorted by name
+    pub groups: Vec<FunctionGroup>,
+    /// Functions in no group, sorted
+    pub ungrouped: Vec<String>,
+}
+
+/// `PUT /api/functions/{name}/group`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionGroupMembership {
+    pub group: String,
+}
+
+/// Executions of one function of a group in the metrics window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupFunctionMetrics {
+    pub function_name: String,
+    pub invocations: u64,
+    pub errors: u64,
+    pub avg_duration_ms: Option<f64>,
+}
+
+/// `GET /api/groups/{group}/metrics`: executions of the group's functions since `since`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupMetrics {
+    pub group: String,
+    pub since: DateTime<Utc>,
+    pub invocations: u64,
+    pub errors: u64,
+    /// errors / invocations, 0.0 without invocations
+    pub error_rate: f64,
+    pub avg_duration_ms: Option<f64>,
+    /// Every function of the group, busiest first
+    pub functions: Vec<GroupFunctionMetrics>,
+}
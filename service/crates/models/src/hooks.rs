@@ -0,0 +1,107 @@
+use crate::FunctionError;
+use serde::{Deserialize, Serialize};
+
+/// Longest a hook may run, in milliseconds
+pub const MAX_HOOK_TIMEOUT_MS: u64 = 60_000;
+
+fn default_hook_timeout_ms() -> u64 {
+    3_000
+}
+
+/// Hooks run by the control plane around every invocation of a function
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionHooks {
+    /// Runs before the function and may replace its payload or reject the invocation
+    #[serde(default)]
+    pub pre_invoke: Option<InvocationHook>,
+    /// Runs once the function answered, e.g. to record results; its answer is ignored
+    #[serde(default)]
+    pub post_invoke: Option<InvocationHook>,
+}
+
+impl FunctionHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_invoke.is_none() && self.post_invoke.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct InvocationHook {
+    pub target: HookTarget,
+    #[serde(default = "default_hook_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub on_failure: HookFailurePolicy,
+}
+
+/// What a hook calls with its [`HookEvent`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum HookTarget {
+    /// Invoke another function of this server; its own hooks are not run
+    Function { function_name: String },
+    /// POST the event as JSON
+    Webhook { url: String },
+}
+
+/// What happens to the invocation when a hook errors, times out or answers with something
+/// other than a JSON object
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookFailurePolicy {
+    /// Fail the invocation with `HookFailedException`
+    #[default]
+    Fail,
+    /// Log the failure and carry on as if there were no hook
+    Continue,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPhase {
+    PreInvoke,
+    PostInvoke,
+}
+
+impl HookPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookPhase::PreInvoke => "pre_invoke",
+            HookPhase::PostInvoke => "post_invoke",
+        }
+    }
+}
+
+/// Event a hook receives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookEvent {
+    pub hook: HookPhase,
+    pub function_name: String,
+    pub request_id: String,
+    /// Payload of the invocation, after the pre-invoke hook for the post-invoke one
+    pub payload: Option<serde_json::Value>,
+    /// What the function answered; post-invoke only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<HookInvocationResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookInvocationResult {
+    pub payload: Option<serde_json::Value>,
+    pub function_error: Option<FunctionError>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Answer of a pre-invoke hook. Both fields are optional; an empty object lets the
+/// invocation through unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreInvokeHookResult {
+    /// Replaces the invocation's payload
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    /// Refuses the invocation with this reason
+    #[serde(default)]
+    pub reject: Option<String>,
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    ApiRoute,
+    /// The secondary target of a route's canary
+    RouteCanary,
+    EventSourceMapping,
+}
+
+/// A route or mapping that names a function
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionReference {
+    pub kind: ReferenceKind,
+    /// Route id or mapping uuid
+    pub id: String,
+    /// Route path or mapping event source ARN
+    pub resource: String,
+    pub function_name: String,
+}
+
+impl std::fmt::Display for FunctionReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ReferenceKind::ApiRoute => "route",
+            ReferenceKind::RouteCanary => "canary of route",
+            ReferenceKind::EventSourceMapping => "event source mapping of",
+        };
+        write!(f, "{kind} {}", self.resource)
+    }
+}
+
+/// `GET /admin/consistency`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsistencyReport {
+    pub checked_at: DateTime<Utc>,
+    /// References to functions that do not exist
+    pub dangling: Vec<FunctionReference>,
+}
@@ -12,6 +12,12 @@ pub struct InvokeRequest {
     pub client_context: Option<String>, // base64 encoded
     pub payload: Option<serde_json::Value>,
     pub qualifier: Option<String>,
+    /// Caller marked the request safe to re-run after a container failure
+    #[serde(default)]
+    pub idempotent: bool,
+    /// Record the runtime API exchange of this invocation
+    #[serde(default)]
+    pub trace: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -0,0 +1,59 @@
+use crate::FunctionError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Lifecycle of an invocation started in job mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "Running",
+            Self::Succeeded => "Succeeded",
+            Self::Failed => "Failed",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Running" => Ok(Self::Running),
+            "Succeeded" => Ok(Self::Succeeded),
+            "Failed" => Ok(Self::Failed),
+            _ => Err(format!("unknown job status: {s}")),
+        }
+    }
+}
+
+/// An invocation whose result is polled instead of held on the invoking connection: a
+/// job-mode invoke read from `GET /api/jobs/{id}`, or an Event invoke read from
+/// `GET /api/executions/{id}/result`. The id is the invocation's request id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub job_id: String,
+    pub function_name: String,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The job and its result are discarded after this; unset while running
+    pub expires_at: Option<DateTime<Utc>>,
+    pub executed_version: Option<String>,
+    /// Set when the handler returned an error payload
+    pub function_error: Option<FunctionError>,
+    pub payload: Option<serde_json::Value>,
+    /// The payload was larger than `jobs.max_result_bytes` and was not kept
+    #[serde(default)]
+    pub payload_truncated: bool,
+    /// Set when the invocation itself failed, e.g. timed out or could not be placed
+    pub error_message: Option<String>,
+    pub duration_ms: Option<u64>,
+}
@@ -1,15 +1,71 @@
+pub mod builds;
+pub mod changes;
 pub mod config;
+pub mod cost;
+pub mod dashboard;
 pub mod docker;
+pub mod doctor;
 pub mod error;
+pub mod event_sources;
+pub mod exec;
+pub mod execution;
+pub mod features;
 pub mod function;
+pub mod function_urls;
+pub mod groups;
+pub mod hooks;
+pub mod integrity;
 pub mod invoke;
+pub mod jobs;
+pub mod logging;
+pub mod permissions;
+pub mod quotas;
+pub mod replication;
 pub mod routes;
+pub mod runtime_api;
+pub mod schedules;
+pub mod search;
 pub mod secrets;
+pub mod sqs;
+pub mod stats;
+pub mod synthetics;
+pub mod system;
+pub mod test_events;
+pub mod validation;
+pub mod webhooks;
 
+pub use builds::*;
+pub use changes::*;
 pub use config::*;
+pub use cost::*;
+pub use dashboard::*;
 pub use docker::*;
+pub use doctor::*;
 pub use error::*;
+pub use event_sources::*;
+pub use exec::*;
+pub use execution::*;
+pub use features::*;
 pub use function::*;
+pub use function_urls::*;
+pub use groups::*;
+pub use hooks::*;
+pub use integrity::*;
 pub use invoke::*;
+pub use jobs::*;
+pub use logging::*;
+pub use permissions::*;
+pub use quotas::*;
+pub use replication::*;
 pub use routes::*;
+pub use runtime_api::*;
+pub use schedules::*;
+pub use search::*;
 pub use secrets::*;
+pub use sqs::*;
+pub use stats::*;
+pub use synthetics::*;
+pub use system::*;
+pub use test_events::*;
+pub use validation::*;
+pub use webhooks::*;
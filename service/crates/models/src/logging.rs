@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogLevelResponse {
+    /// Active tracing filter, e.g. `info,lambda_control::scheduler=trace`
+    pub filter: String,
+    /// Filter the server started with
+    pub default_filter: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetLogLevelRequest {
+    pub filter: String,
+    /// Restore the previous filter after this many seconds
+    #[serde(default)]
+    pub revert_after_secs: Option<u64>,
+}
+
+/// One gateway or invoke request in the access log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccessLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    /// Function the request was routed to
+    pub function_name: String,
+    /// Gateway route that matched, if any
+    pub route_id: Option<Uuid>,
+    pub status: u16,
+    pub latency_ms: u64,
+    pub source_ip: Option<String>,
+    pub request_id: Option<String>,
+}
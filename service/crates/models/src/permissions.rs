@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The one action a function's policy grants
+pub const INVOKE_FUNCTION_ACTION: &str = "lambda:InvokeFunction";
+
+/// Principal that lets every function invoke
+pub const ANY_FUNCTION_PRINCIPAL: &str = "*";
+
+/// AddPermission: let another function invoke this one through the runtime API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddPermissionRequest {
+    /// Unique within the function's policy, and how the statement is removed again
+    pub statement_id: String,
+    #[serde(default = "default_permission_action")]
+    pub action: String,
+    /// Calling function by name or ARN, or `*` for every function
+    pub principal: String,
+}
+
+fn default_permission_action() -> String {
+    INVOKE_FUNCTION_ACTION.to_string()
+}
+
+/// A statement of a function's policy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PermissionStatement {
+    pub statement_id: String,
+    pub action: String,
+    /// Calling function by name, or `*`
+    pub principal: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// GetPolicy: the functions allowed to invoke a function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionPolicy {
+    pub function_name: String,
+    pub statements: Vec<PermissionStatement>,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Namespace every function belongs to
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// What a namespace uses of one quota, and its limit when one is set
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuotaUsage {
+    pub used: u64,
+    pub limit: Option<u64>,
+}
+
+/// `GET /api/namespaces/{ns}/usage`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NamespaceUsage {
+    pub namespace: String,
+    pub functions: QuotaUsage,
+    pub warm_memory_mb: QuotaUsage,
+    /// Invocations since `day_started_at`
+    pub daily_invocations: QuotaUsage,
+    pub storage_bytes: QuotaUsage,
+    /// Start of the current UTC day
+    pub day_started_at: DateTime<Utc>,
+}
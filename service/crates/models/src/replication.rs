@@ -0,0 +1,83 @@
+use crate::{DiskPressure, DockerBreakerState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a replicated item is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplicatedKind {
+    Function,
+    Route,
+}
+
+impl ReplicatedKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Function => "function",
+            Self::Route => "route",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicationState {
+    /// The peer has the local definition
+    InSync,
+    /// The peer's copy changed outside replication; left alone until a forced sync
+    Conflict,
+    /// Reaching or updating the peer failed; retried next round
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationItem {
+    pub kind: ReplicatedKind,
+    /// Function name, or `METHOD path` of a route
+    pub id: String,
+    pub state: ReplicationState,
+    pub message: Option<String>,
+    /// Last time the peer was brought in sync with the item
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of the latest sync round with the replication peer
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplicationStatus {
+    pub enabled: bool,
+    pub peer_url: Option<String>,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    /// Why the last round stopped early, e.g. an unreachable peer
+    pub last_error: Option<String>,
+    pub items: Vec<ReplicationItem>,
+    /// Whether invokes are forwarded to the peer while this instance is unhealthy
+    pub failover: bool,
+    pub local_health: Option<InstanceHealth>,
+    /// Latest health the peer reported, with `checked_at` set to when it arrived
+    pub peer_health: Option<InstanceHealth>,
+    pub forwarded: Vec<ForwardedInvocations>,
+}
+
+/// Whether an instance can run invocations, exchanged between replication peers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstanceHealth {
+    pub healthy: bool,
+    /// The Docker daemon answered
+    pub docker: bool,
+    /// Circuit breaker of container calls; open while Docker keeps failing
+    #[serde(default)]
+    pub docker_breaker: DockerBreakerState,
+    pub disk_pressure: DiskPressure,
+    /// Why the instance is unhealthy
+    pub reason: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Invocations of a function sent to the peer since server start
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ForwardedInvocations {
+    pub function_name: String,
+    pub forwarded: u64,
+    /// The peer could not be reached, so the invocation ran locally
+    pub failed: u64,
+}
@@ -1,5 +1,7 @@
+use crate::InvocationType;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +12,27 @@ pub struct ApiRoute {
     pub method: Option<String>, // e.g. "GET" | "POST" | None for any
     pub function_name: String,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub canary: Option<RouteCanary>,
+    /// Whether requests through this route are written to the access log
+    #[serde(default = "default_access_log")]
+    pub access_log: bool,
+    #[serde(default)]
+    pub mock: Option<RouteMock>,
+    #[serde(default)]
+    pub recording: Option<RouteRecording>,
+    #[serde(default)]
+    pub limits: Option<RouteLimits>,
+    #[serde(default)]
+    pub transform: Option<RouteTransform>,
+    #[serde(default)]
+    pub invocation: Option<RouteInvocation>,
+    #[serde(default)]
+    pub jwt_authorizer: Option<RouteJwtAuthorizer>,
+}
+
+fn default_access_log() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +41,228 @@ pub struct CreateApiRouteRequest {
     pub path: String,
     pub method: Option<String>,
     pub function_name: String,
+    #[serde(default)]
+    pub canary: Option<RouteCanary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteAccessLog {
+    pub enabled: bool,
+}
+
+/// Mapping templates the gateway applies around a route's invocations; see
+/// `PUT /admin/api-gateway/routes/{id}/transform` for the template syntax
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteTransform {
+    /// Builds the function's event from the request instead of the proxy event
+    #[serde(default)]
+    pub request: Option<serde_json::Value>,
+    /// Builds the result the gateway turns into its HTTP response from the function's
+    #[serde(default)]
+    pub response: Option<serde_json::Value>,
+}
+
+/// Static response a route returns instead of invoking its function while `enabled`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteMock {
+    #[serde(default = "default_mock_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_mock_status")]
+    pub status: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Strings are sent as-is, anything else as JSON
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+    /// Delay before answering, to mimic the real function
+    #[serde(default)]
+    pub latency_ms: u64,
+}
+
+fn default_mock_enabled() -> bool {
+    true
+}
+
+fn default_mock_status() -> u16 {
+    200
+}
+
+/// Samples a route's requests into saved test events of its function while `enabled`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteRecording {
+    #[serde(default = "default_recording_enabled")]
+    pub enabled: bool,
+    /// Requests recorded per minute at most
+    #[serde(default = "default_recording_per_minute")]
+    pub per_minute: u32,
+    /// Headers (case-insensitive) whose values are replaced with `[REDACTED]`
+    #[serde(default = "default_redact_headers")]
+    pub redact_headers: Vec<String>,
+    /// Query string parameters whose values are replaced
+    #[serde(default)]
+    pub redact_query: Vec<String>,
+    /// Fields of a JSON body, at any depth, whose values are replaced
+    #[serde(default)]
+    pub redact_body_fields: Vec<String>,
+    /// Recorded events kept for the function; older ones are dropped
+    #[serde(default = "default_recording_max_events")]
+    pub max_events: u32,
+}
+
+fn default_recording_enabled() -> bool {
+    true
+}
+
+fn default_recording_per_minute() -> u32 {
+    10
+}
+
+fn default_redact_headers() -> Vec<String> {
+    vec!["authorization".to_string(), "cookie".to_string()]
+}
+
+fn default_recording_max_events() -> u32 {
+    100
+}
+
+/// Admission limits of a route, independent of its functions' concurrency settings.
+/// Requests over them are shed with 503 and `Retry-After`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteLimits {
+    /// Requests the route forwards at once
+    pub max_in_flight: u32,
+    /// Requests waiting for one of the `max_in_flight` slots; more are shed right away
+    #[serde(default)]
+    pub max_queued: u32,
+    /// How long a queued request waits for a slot before it is shed
+    #[serde(default = "default_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+    /// Seconds sent in `Retry-After` with shed requests
+    #[serde(default = "default_retry_after_secs")]
+    pub retry_after_secs: u32,
+}
+
+fn default_queue_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_retry_after_secs() -> u32 {
+    1
+}
+
+/// How the gateway invokes a route's function. Without one, requests wait for the function.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteInvocation {
+    /// `RequestResponse` waits for the function; `Event` answers 202 with the request id
+    /// right away and runs it in the background
+    pub invocation_type: InvocationType,
+    /// Whether a request's `X-Amz-Invocation-Type` header may pick the other type
+    #[serde(default)]
+    pub allow_override: bool,
+}
+
+impl RouteInvocation {
+    /// The type a request sending `header` as its `X-Amz-Invocation-Type` is invoked with.
+    /// Headers the route does not allow, or naming neither type, are ignored.
+    pub fn invocation_type_for(&self, header: Option<&str>) -> InvocationType {
+        match header.and_then(|h| h.parse().ok()) {
+            Some(requested @ (InvocationType::RequestResponse | InvocationType::Event))
+                if self.allow_override =>
+            {
+                requested
+            }
+            _ => self.invocation_type.clone(),
+        }
+    }
+}
+
+/// Bearer tokens a route requires, checked like an HTTP API JWT authorizer: requests
+/// without a valid token are refused with 401, and the claims of valid ones are passed to
+/// the function as `requestContext.authorizer.jwt`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteJwtAuthorizer {
+    /// `iss` the tokens must carry, e.g. `https://auth.example.com/realms/main`
+    pub issuer: String,
+    /// Accepted `aud` values; a token without `aud` may match with its `client_id`
+    pub audience: Vec<String>,
+    /// JSON Web Key Set the issuer publishes its signing keys at
+    pub jwks_url: String,
+}
+
+/// Secondary target of a route, serving the requests that match `condition`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteCanary {
+    pub function_name: String,
+    #[serde(default)]
+    pub qualifier: Option<String>,
+    #[serde(rename = "match")]
+    pub condition: CanaryMatch,
+}
+
+/// Which requests go to a route's canary
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CanaryMatch {
+    /// Requests with header `name` set to `value` (names are case-insensitive)
+    Header { name: String, value: String },
+    /// Requests sending cookie `name` with `value`
+    Cookie { name: String, value: String },
+    /// A random share of requests, from 0 to 100
+    Percentage { percent: f64 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteTarget {
+    Primary,
+    Canary,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RouteTargetStats {
+    pub requests: u64,
+    /// Invoke failures and function errors
+    pub errors: u64,
+    /// errors / requests, 0.0 when there were no requests
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: u64,
+}
+
+/// Admission of a route's requests under its limits, since server start or the last
+/// change of the limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RouteSaturation {
+    /// Requests being forwarded right now
+    pub in_flight: u32,
+    /// Requests waiting for a slot right now
+    pub queued: u32,
+    pub max_in_flight: Option<u32>,
+    pub max_queued: Option<u32>,
+    /// Requests forwarded
+    pub admitted: u64,
+    /// Requests that had to wait for a slot, whether they got one or not
+    pub queued_total: u64,
+    /// Requests answered with 503
+    pub shed: u64,
+}
+
+/// Requests served by each target of a route since server start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteMetrics {
+    pub route_id: Uuid,
+    pub primary: RouteTargetStats,
+    pub canary: RouteTargetStats,
+    #[serde(default)]
+    pub saturation: RouteSaturation,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
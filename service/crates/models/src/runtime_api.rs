@@ -0,0 +1,66 @@
+use crate::LambdaError;
+use serde::{Deserialize, Serialize};
+
+/// How function containers reach the runtime API. What is left unset is detected from the
+/// network containers join; `[docker]` in the config file holds the startup values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeApiSettings {
+    /// Host advertised in `AWS_LAMBDA_RUNTIME_API`; `host.docker.internal`, or `127.0.0.1`
+    /// on the `host` network, when unset
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Port advertised with it, when the runtime API is published on another port than
+    /// `server.port_runtime_api`, e.g. behind a port forward
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Address `host.docker.internal` maps to; unset uses the gateway of the network
+    #[serde(default)]
+    pub host_gateway: Option<String>,
+    /// Docker network function containers join: `host`, or the name of a network created
+    /// with `docker network create`. Unset uses Docker's default bridge
+    #[serde(default)]
+    pub network: Option<String>,
+}
+
+impl RuntimeApiSettings {
+    pub fn validate(&self) -> Result<(), LambdaError> {
+        let invalid = |reason: &str| {
+            Err(LambdaError::InvalidRequest {
+                reason: format!("runtime API: {reason}"),
+            })
+        };
+        let blank_or_spaced = |value: &Option<String>| {
+            value
+                .as_deref()
+                .is_some_and(|v| v.trim().is_empty() || v.contains(char::is_whitespace))
+        };
+        if blank_or_spaced(&self.host)
+            || self.host.as_deref().is_some_and(|h| h.contains(['/', ':']))
+        {
+            return invalid("host must be a hostname or IPv4 address, without a port");
+        }
+        if self.port == Some(0) {
+            return invalid("port must be positive");
+        }
+        if blank_or_spaced(&self.host_gateway) {
+            return invalid("host_gateway must be an address");
+        }
+        if blank_or_spaced(&self.network) {
+            return invalid("network must be a network name");
+        }
+        Ok(())
+    }
+}
+
+/// `GET /api/admin/runtime-api`: what containers created now are given
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeApiStatus {
+    pub settings: RuntimeApiSettings,
+    /// Whether `settings` were set through the API rather than read from the config file
+    pub overridden: bool,
+    /// `AWS_LAMBDA_RUNTIME_API` of new containers
+    pub advertised: String,
+    /// `extra_hosts` entries of new containers, e.g. `host.docker.internal:172.18.0.1`
+    pub extra_hosts: Vec<String>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// `POST /2015-03-31/functions/{name}/schedules` and `PUT .../schedules/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleRequest {
+    /// `cron(minutes hours day-of-month month day-of-week year)` or `rate(value unit)`, as in
+    /// EventBridge, e.g. `cron(0 9 ? * MON-FRI *)` or `rate(5 minutes)`. Times are UTC.
+    pub schedule_expression: String,
+    /// Event the function is invoked with
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    /// Version or alias to invoke; `$LATEST` when unset
+    #[serde(default)]
+    pub qualifier: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Rule invoking a function asynchronously whenever its expression comes due
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Schedule {
+    pub schedule_id: String,
+    pub function_name: String,
+    pub schedule_expression: String,
+    pub payload: serde_json::Value,
+    pub qualifier: Option<String>,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// Job of the latest invocation, for `GET /api/jobs/{id}`
+    pub last_job_id: Option<String>,
+    /// `None` while disabled or once a cron expression has no later match
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSchedulesResponse {
+    pub schedules: Vec<Schedule>,
+}
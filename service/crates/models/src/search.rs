@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One match from `GET /api/search`, tagged by `kind`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchHit {
+    Function {
+        function_name: String,
+        runtime: String,
+        description: Option<String>,
+    },
+    Route {
+        route_id: Uuid,
+        path: String,
+        method: Option<String>,
+        function_name: String,
+    },
+    /// Secret names only; values never leave the secrets store
+    Secret { name: String },
+    Execution {
+        request_id: String,
+        function_name: String,
+        status: String,
+        start_time: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub query: String,
+    /// Best matches first: exact, then prefix, then substring
+    pub results: Vec<SearchHit>,
+    /// More matches exist than were returned
+    pub truncated: bool,
+}
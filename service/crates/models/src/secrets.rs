@@ -20,3 +20,111 @@ pub struct CreateSecretRequest {
     pub name: String,
     pub value: String,
 }
+
+/// `confirm` an export has to carry to include secret values
+pub const EXPORT_VALUES_CONFIRMATION: &str = "export-secret-values";
+
+/// Format of a secrets import file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsFileFormat {
+    /// `NAME=value` lines
+    Dotenv,
+    /// An object of names to string values
+    Json,
+}
+
+/// What an import does with secrets that already exist
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretConflictMode {
+    Skip,
+    Overwrite,
+    /// Import nothing when any of the secrets exists
+    #[default]
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportSecretsRequest {
+    /// Contents of the dotenv or JSON file
+    pub content: String,
+    /// Detected when unset: JSON when the content starts with `{`, else dotenv
+    #[serde(default)]
+    pub format: Option<SecretsFileFormat>,
+    #[serde(default)]
+    pub on_conflict: SecretConflictMode,
+}
+
+/// Names of the imported secrets by outcome, each sorted
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ImportSecretsResponse {
+    pub created: Vec<String>,
+    pub overwritten: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportSecretsRequest {
+    #[serde(default)]
+    pub include_values: bool,
+    /// Must be [`EXPORT_VALUES_CONFIRMATION`] when `include_values` is set
+    #[serde(default)]
+    pub confirm: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExportedSecret {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportSecretsResponse {
+    pub secrets: Vec<ExportedSecret>,
+}
+
+impl ExportSecretsResponse {
+    /// The secrets as a dotenv file, with double-quoted values that an import reads back the
+    /// same. Secrets exported without values come out as `NAME=`.
+    pub fn to_dotenv(&self) -> String {
+        let mut out = String::new();
+        for secret in &self.secrets {
+            match &secret.value {
+                Some(value) => {
+                    let escaped = value
+                        .replace('\\', "\\\\")
+                        .replace('"', "\\\"")
+                        .replace('\n', "\\n")
+                        .replace('\r', "\\r");
+                    out.push_str(&format!("{}=\"{escaped}\"\n", secret.name));
+                }
+                None => out.push_str(&format!("{}=\n", secret.name)),
+            }
+        }
+        out
+    }
+}
+
+/// An export that included secret values
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SecretExportAuditEntry {
+    pub exported_at: DateTime<Utc>,
+    /// `X-Lambdah-Actor` of the request
+    pub actor: Option<String>,
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListSecretExportsResponse {
+    pub exports: Vec<SecretExportAuditEntry>,
+}
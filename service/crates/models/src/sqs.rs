@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Queue name from `arn:<partition>:sqs:<region>:<account>:<name>`
+pub fn queue_name_from_arn(arn: &str) -> Option<&str> {
+    let mut parts = arn.strip_prefix("arn:")?.splitn(5, ':');
+    let (_partition, service, _region, _account, name) = (
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+    );
+    (service == "sqs" && !name.is_empty() && !name.contains(':')).then_some(name)
+}
+
+/// Where messages go after `max_receive_count` receives without being deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedrivePolicy {
+    #[serde(rename = "deadLetterTargetArn")]
+    pub dead_letter_target_arn: String,
+    /// SQS accepts this as a number or a numeric string
+    #[serde(rename = "maxReceiveCount", deserialize_with = "number_or_string")]
+    pub max_receive_count: u32,
+}
+
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u32),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Queue {
+    pub name: String,
+    pub visibility_timeout_secs: u32,
+    pub delay_seconds: u32,
+    pub redrive_policy: Option<RedrivePolicy>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreateQueueOptions {
+    pub visibility_timeout_secs: Option<u32>,
+    pub delay_seconds: Option<u32>,
+    pub redrive_policy: Option<RedrivePolicy>,
+}
+
+/// Message counts, approximate in the same way as SQS: they may change as soon as read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueCounts {
+    /// Visible and ready to receive
+    pub visible: u64,
+    /// Received and not yet deleted or returned
+    pub in_flight: u64,
+    /// Sent with a delay that has not yet elapsed
+    pub delayed: u64,
+}
+
+/// A message handed to a consumer; deleting it needs the receipt handle of this receive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceivedMessage {
+    pub message_id: String,
+    pub receipt_handle: String,
+    pub body: String,
+    pub md5_of_body: String,
+    pub sent_at: DateTime<Utc>,
+    pub receive_count: u32,
+    pub first_received_at: DateTime<Utc>,
+}
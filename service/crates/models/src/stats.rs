@@ -0,0 +1,171 @@
+use crate::WarmPoolOccupancy;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How invocations were placed onto execution environments
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WarmupCounts {
+    /// Served by an idle warm container
+    pub warm_starts: u64,
+    /// Served by restarting a stopped container
+    pub restarted_starts: u64,
+    /// Required creating a new container
+    pub cold_starts: u64,
+    /// warm_starts / total, 0.0 when there were no invocations
+    pub warm_hit_rate: f64,
+}
+
+impl WarmupCounts {
+    pub fn new(warm_starts: u64, restarted_starts: u64, cold_starts: u64) -> Self {
+        let total = warm_starts + restarted_starts + cold_starts;
+        let warm_hit_rate = if total == 0 {
+            0.0
+        } else {
+            warm_starts as f64 / total as f64
+        };
+        Self {
+            warm_starts,
+            restarted_starts,
+            cold_starts,
+            warm_hit_rate,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.warm_starts + self.restarted_starts + self.cold_starts
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionWarmupStats {
+    pub function_name: String,
+    #[serde(flatten)]
+    pub counts: WarmupCounts,
+}
+
+/// Execution environment reuse statistics since server start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupStats {
+    pub global: WarmupCounts,
+    pub functions: Vec<FunctionWarmupStats>,
+}
+
+/// Failed executions sharing an error type and normalized message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorGroup {
+    pub fingerprint: String,
+    pub error_type: String,
+    /// Message of the most recent occurrence
+    pub error_message: Option<String>,
+    pub count: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Most recent request ids in this group
+    pub sample_request_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorSummary {
+    pub function_name: String,
+    pub window_seconds: u64,
+    pub total_errors: u64,
+    /// Sorted by count, most frequent first
+    pub groups: Vec<ErrorGroup>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendDirection {
+    Up,
+    Down,
+    /// Within 10% of the previous window
+    Flat,
+    /// One of the windows had nothing to measure
+    Unknown,
+}
+
+/// A figure of the window compared with the same figure over the window before it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricTrend {
+    pub current: Option<f64>,
+    pub previous: Option<f64>,
+    pub direction: TrendDirection,
+}
+
+/// Health of one function over a window, for deciding whether it is getting worse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionInsights {
+    pub function_name: String,
+    pub generated_at: DateTime<Utc>,
+    pub window_seconds: u64,
+    pub invocations: u64,
+    pub errors: u64,
+    /// Failed share of invocations
+    pub error_rate: MetricTrend,
+    pub p95_duration_ms: MetricTrend,
+    /// Where invocations were placed since server start
+    pub starts: WarmupCounts,
+    /// cold_starts / total starts, None before the first invocation
+    pub cold_start_ratio: Option<f64>,
+    /// Invocations refused for lack of concurrency or memory within the window
+    pub throttles: u64,
+    /// Configuration revisions recorded within the window
+    pub config_changes: u64,
+    pub last_config_change: Option<DateTime<Utc>>,
+    /// Containers of this function right now
+    pub warm_pool: WarmPoolOccupancy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskPressure {
+    /// Free space above the GC threshold
+    Ok,
+    /// Below `gc_below_free_mb`: build garbage is collected automatically
+    Low,
+    /// Below `min_free_mb`: new builds are refused
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskLocationStatus {
+    pub path: String,
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub pressure: DiskPressure,
+}
+
+/// Free space where build artifacts (data dir) and images (Docker root) live
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskStatus {
+    /// Worst pressure across the measured locations
+    pub pressure: DiskPressure,
+    pub data_dir: Option<DiskLocationStatus>,
+    /// Only measured when the Docker root is on this host
+    pub docker_root: Option<DiskLocationStatus>,
+    pub gc_below_free_mb: u64,
+    pub min_free_mb: u64,
+    pub checked_at: DateTime<Utc>,
+    pub last_gc_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableStatus {
+    pub name: String,
+    pub rows: u64,
+}
+
+/// Size of the SQLite database and what maintenance last did to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStatus {
+    /// Pages in use and free, i.e. the size of the main database file
+    pub size_bytes: u64,
+    /// Free pages, given back to the file system by the next VACUUM
+    pub free_bytes: u64,
+    /// Largest first
+    pub tables: Vec<TableStatus>,
+    pub last_pruned_at: Option<DateTime<Utc>>,
+    /// Executions deleted by the last pruning
+    pub last_pruned_executions: u64,
+    pub last_vacuum_at: Option<DateTime<Utc>>,
+}
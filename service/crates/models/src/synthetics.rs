@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Scheduled invocation of a function whose result is checked, so a broken function is
+/// noticed before real traffic hits it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SyntheticCheck {
+    /// Seconds between runs
+    pub interval_secs: u64,
+    /// Event the function is invoked with
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    /// The result must contain this JSON: objects match when every key of `expect` matches,
+    /// anything else must be equal. Any result passes when unset; function errors never do.
+    #[serde(default)]
+    pub expect: Option<serde_json::Value>,
+    /// Runs slower than this fail
+    #[serde(default)]
+    pub max_latency_ms: Option<u64>,
+    /// Percentage of runs that must pass, e.g. `99.9`
+    #[serde(default = "default_slo_target")]
+    pub slo_target: f64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_slo_target() -> f64 {
+    99.0
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// One run of a function's synthetic check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyntheticCheckRun {
+    pub ran_at: DateTime<Utc>,
+    pub success: bool,
+    pub latency_ms: u64,
+    /// Why the run failed
+    pub failure: Option<String>,
+    pub request_id: Option<String>,
+}
+
+/// `GET /api/functions/{name}/synthetic-check/stats`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyntheticCheckStats {
+    pub function_name: String,
+    pub window_seconds: u64,
+    pub runs: u64,
+    pub successes: u64,
+    /// Percentage of runs in the window that passed; `None` without runs
+    pub uptime_percent: Option<f64>,
+    pub slo_target: f64,
+    pub slo_met: Option<bool>,
+    /// Failures the SLO still allows in the window, given the runs so far; negative once
+    /// it is breached
+    pub error_budget_remaining: Option<f64>,
+    pub p50_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    /// Newest first
+    pub recent_runs: Vec<SyntheticCheckRun>,
+}
@@ -0,0 +1,76 @@
+use crate::{DockerVersion, QuotasConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A migration embedded in the server binary
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SchemaMigration {
+    pub version: u32,
+    pub name: String,
+}
+
+/// Schema version of the database against the migrations this binary carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaStatus {
+    /// Version recorded in the database; 0 for a new database, or one created before
+    /// schema versions were recorded
+    pub current_version: u32,
+    /// Latest version this binary migrates to, and the newest it runs against
+    pub latest_version: u32,
+    /// Migrations the next start runs, oldest first
+    pub pending: Vec<SchemaMigration>,
+}
+
+/// What a server is and runs, for compatibility checks and the console's about page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemInfo {
+    /// Version of the server
+    pub version: String,
+    /// Commit the server was built from, when the build recorded one
+    pub commit: Option<String>,
+    /// Database schema version this server migrates to
+    pub schema_version: u32,
+    /// Optional subsystems by name, and whether they are on
+    pub features: BTreeMap<String, bool>,
+    pub runtimes: Vec<RuntimeInfo>,
+    pub limits: SystemLimits,
+    pub backend: BackendInfo,
+}
+
+/// A supported runtime and the base image its functions build on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeInfo {
+    pub runtime: String,
+    pub base_image: String,
+    /// Digest (`sha256:...`) of the base image as pulled on this host; `None` until a
+    /// build pulls it
+    pub base_image_digest: Option<String>,
+}
+
+/// Limits that requests are checked against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemLimits {
+    pub max_global_concurrency: u32,
+    pub host_memory_budget_mb: Option<u64>,
+    pub max_request_body_size_mb: u64,
+    pub max_code_size_bytes: u64,
+    pub max_build_context_bytes: u64,
+    pub min_timeout_secs: u64,
+    pub max_timeout_secs: u64,
+    pub min_memory_mb: u64,
+    pub max_memory_mb: u64,
+    pub quotas: QuotasConfig,
+}
+
+/// The container backend functions run on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendInfo {
+    /// Always `docker` for now
+    pub kind: String,
+    pub host: String,
+    /// `None` when the daemon could not be reached, with the reason in `error`
+    pub docker: Option<DockerVersion>,
+    pub error: Option<String>,
+    pub sandbox_profiles: Vec<String>,
+    pub default_sandbox_profile: String,
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How a saved test event was created
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestEventSource {
+    /// Created or imported through the API
+    Manual,
+    /// Sampled from gateway traffic of a recording route
+    Recorded,
+}
+
+impl TestEventSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Recorded => "recorded",
+        }
+    }
+}
+
+/// Saved payload to invoke a function with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestEvent {
+    pub event_id: Uuid,
+    pub function_name: String,
+    pub name: String,
+    pub payload: serde_json::Value,
+    pub source: TestEventSource,
+    /// Route the event was recorded from
+    pub route_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Save or import a test event; an exported event's `name` and `payload` can be sent as-is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTestEventRequest {
+    pub name: String,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTestEventsResponse {
+    pub test_events: Vec<TestEvent>,
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationProblem {
+    /// Request field the problem refers to, e.g. `handler` or `environment.FOO`
+    pub field: String,
+    /// Machine-readable problem code, e.g. `InvalidHandler`
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidateFunctionResponse {
+    pub valid: bool,
+    pub problems: Vec<ValidationProblem>,
+}
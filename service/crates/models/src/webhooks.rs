@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Control-plane lifecycle changes a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "function.created")]
+    FunctionCreated,
+    /// $LATEST code or configuration changed
+    #[serde(rename = "function.updated")]
+    FunctionUpdated,
+    #[serde(rename = "function.deleted")]
+    FunctionDeleted,
+    #[serde(rename = "version.published")]
+    VersionPublished,
+    /// An alias was created or now points elsewhere
+    #[serde(rename = "alias.updated")]
+    AliasUpdated,
+    #[serde(rename = "build.failed")]
+    BuildFailed,
+    #[serde(rename = "alert.fired")]
+    AlertFired,
+}
+
+impl WebhookEventType {
+    pub const ALL: [WebhookEventType; 7] = [
+        Self::FunctionCreated,
+        Self::FunctionUpdated,
+        Self::FunctionDeleted,
+        Self::VersionPublished,
+        Self::AliasUpdated,
+        Self::BuildFailed,
+        Self::AlertFired,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FunctionCreated => "function.created",
+            Self::FunctionUpdated => "function.updated",
+            Self::FunctionDeleted => "function.deleted",
+            Self::VersionPublished => "version.published",
+            Self::AliasUpdated => "alias.updated",
+            Self::BuildFailed => "build.failed",
+            Self::AlertFired => "alert.fired",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WebhookEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|event| event.as_str() == s)
+            .ok_or_else(|| format!("unknown webhook event type: {s}"))
+    }
+}
+
+/// Body POSTed to every subscribed webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub function_name: Option<String>,
+    /// Event-specific fields, e.g. the alias version or the build error
+    pub detail: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub webhook_id: Uuid,
+    pub url: String,
+    /// Subscribed event types; empty means every event
+    pub events: Vec<WebhookEventType>,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEventType>,
+    /// HMAC signing secret; generated when omitted
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// Returned once on creation; the secret cannot be read back afterwards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListWebhooksResponse {
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Not yet delivered; attempts remain
+    Pending,
+    Succeeded,
+    /// Gave up after a non-retryable response or the last attempt
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "succeeded" => Ok(Self::Succeeded),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("unknown delivery status: {s}")),
+        }
+    }
+}
+
+/// One event sent to one webhook, across all of its attempts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub delivery_id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the target answered
+    pub response_status: Option<u16>,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListWebhookDeliveriesResponse {
+    /// Newest first
+    pub deliveries: Vec<WebhookDelivery>,
+}
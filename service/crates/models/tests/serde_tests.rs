@@ -19,6 +19,13 @@ fn test_function_serde_roundtrip() {
         state: FunctionState::Active,
         state_reason: None,
         state_reason_code: None,
+        last_update_status: LastUpdateStatus::Successful,
+        last_update_status_reason: None,
+        last_update_status_reason_code: None,
+        metadata: None,
+        function_arn: None,
+        package_type: PackageType::Zip,
+        image_uri: None,
     };
 
     let json = serde_json::to_string(&function).unwrap();
@@ -28,6 +35,43 @@ fn test_function_serde_roundtrip() {
     assert_eq!(function.handler, deserialized.handler);
 }
 
+#[test]
+fn test_function_metadata_is_optional_on_the_wire() {
+    let mut function: Function = serde_json::from_value(serde_json::json!({
+        "function_id": uuid::Uuid::new_v4(),
+        "function_name": "plain",
+        "runtime": "nodejs22.x",
+        "role": null,
+        "handler": "index.handler",
+        "code_sha256": "abcd",
+        "description": null,
+        "timeout": 3,
+        "memory_size": 128,
+        "environment": {},
+        "last_modified": chrono::Utc::now(),
+        "code_size": 0,
+        "version": "$LATEST",
+        "state": "Active",
+        "state_reason": null,
+        "state_reason_code": null
+    }))
+    .unwrap();
+    assert!(function.metadata.is_none());
+    assert_eq!(function.last_update_status, LastUpdateStatus::Successful);
+    assert!(!serde_json::to_string(&function)
+        .unwrap()
+        .contains("metadata"));
+
+    function.metadata = Some(FunctionMetadata {
+        owner: Some("platform".to_string()),
+        ..Default::default()
+    });
+    let json = serde_json::to_value(&function).unwrap();
+    assert_eq!(json["metadata"]["owner"], "platform");
+    let deserialized: Function = serde_json::from_value(json).unwrap();
+    assert_eq!(deserialized.metadata, function.metadata);
+}
+
 #[test]
 fn test_create_function_request_deny_unknown_fields() {
     let json = r#"{
@@ -52,6 +96,8 @@ fn test_invoke_request_serde() {
         client_context: None,
         payload: Some(serde_json::json!({"test": "data"})),
         qualifier: None,
+        idempotent: false,
+        trace: false,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -90,6 +136,8 @@ fn test_error_shape_serde() {
         error_message: "Test error".to_string(),
         error_type: "TestError".to_string(),
         stack_trace: Some(vec!["line1".to_string(), "line2".to_string()]),
+        build_log: None,
+        references: None,
     };
 
     let json = serde_json::to_string(&error).unwrap();
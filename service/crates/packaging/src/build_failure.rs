@@ -0,0 +1,73 @@
+use lambda_models::{BuildFailureKind, LambdaError};
+
+/// Lines of build output kept in a build failure
+pub const BUILD_LOG_MAX_LINES: usize = 40;
+/// Bytes of build output kept in a build failure
+pub const BUILD_LOG_MAX_BYTES: usize = 4096;
+/// Longest summary line of a build failure
+const SUMMARY_MAX_CHARS: usize = 300;
+
+/// Judge why a build failed from its output, checking the causes that say most first
+pub fn classify_build_failure(output: &str) -> BuildFailureKind {
+    let output = output.to_lowercase();
+    let any = |needles: &[&str]| needles.iter().any(|n| output.contains(n));
+    if any(&["no space left on device"]) {
+        BuildFailureKind::NoSpace
+    } else if any(&[
+        "cannot connect to the docker daemon",
+        "error during connect",
+    ]) {
+        BuildFailureKind::DaemonUnavailable
+    } else if any(&[
+        "pull access denied",
+        "manifest unknown",
+        "failed to resolve source metadata",
+        "error pulling image",
+        "toomanyrequests",
+    ]) {
+        BuildFailureKind::BaseImagePull
+    } else if any(&[
+        "did not complete successfully",
+        "returned a non-zero code",
+        "npm err!",
+    ]) {
+        BuildFailureKind::StepFailed
+    } else {
+        BuildFailureKind::Other
+    }
+}
+
+/// Last lines of the build output, at most [`BUILD_LOG_MAX_LINES`] and
+/// [`BUILD_LOG_MAX_BYTES`]
+pub fn build_log_excerpt(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    let tail = lines[lines.len().saturating_sub(BUILD_LOG_MAX_LINES)..].join("\n");
+    if tail.len() <= BUILD_LOG_MAX_BYTES {
+        return tail;
+    }
+    let mut start = tail.len() - BUILD_LOG_MAX_BYTES;
+    while !tail.is_char_boundary(start) {
+        start += 1;
+    }
+    tail[start..].to_string()
+}
+
+/// The last line of the build output that reports an error, or its last line
+pub fn build_failure_summary(output: &str) -> String {
+    let lines = || output.lines().map(str::trim).filter(|l| !l.is_empty());
+    let line = lines()
+        .rfind(|l| l.to_lowercase().contains("error"))
+        .or_else(|| lines().next_back())
+        .unwrap_or("docker build exited with an error");
+    line.chars().take(SUMMARY_MAX_CHARS).collect()
+}
+
+/// The error of a failed `docker build` of `function_name` with `output`
+pub fn build_failure(function_name: &str, output: &str) -> LambdaError {
+    LambdaError::ImageBuildFailed {
+        function_name: function_name.to_string(),
+        kind: classify_build_failure(output),
+        summary: build_failure_summary(output),
+        build_log: build_log_excerpt(output),
+    }
+}
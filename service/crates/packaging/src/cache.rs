@@ -1,6 +1,6 @@
 use crate::zip_handler::ZipInfo;
 use lambda_models::{Function, LambdaError};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use tracing::{info, instrument};
@@ -145,6 +145,45 @@ impl PackagingCache {
         Ok(removed_count)
     }
 
+    /// Delete stored ZIPs whose SHA256 is not in `keep`; returns (files removed, bytes freed)
+    #[instrument(skip(self, keep))]
+    pub fn remove_unreferenced_zips(
+        &mut self,
+        keep: &HashSet<String>,
+    ) -> Result<(usize, u64), LambdaError> {
+        let zip_dir = self.cache_dir.join("zips");
+        if !zip_dir.exists() {
+            return Ok((0, 0));
+        }
+
+        let mut removed = 0;
+        let mut freed_bytes = 0;
+        for entry in fs::read_dir(&zip_dir).map_err(|e| LambdaError::InternalError {
+            reason: e.to_string(),
+        })? {
+            let entry = entry.map_err(|e| LambdaError::InternalError {
+                reason: e.to_string(),
+            })?;
+            let sha256 = entry.file_name().to_string_lossy().to_string();
+            if keep.contains(&sha256) {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(entry.path()).map_err(|e| LambdaError::InternalError {
+                reason: e.to_string(),
+            })?;
+            self.zip_cache.remove(&sha256);
+            removed += 1;
+            freed_bytes += size;
+        }
+
+        info!(
+            "Removed {} unreferenced ZIP artifacts ({} bytes)",
+            removed, freed_bytes
+        );
+        Ok((removed, freed_bytes))
+    }
+
     fn load_cache(&mut self) -> Result<(), LambdaError> {
         // Load image cache from disk
         let image_cache_file = self.cache_dir.join("image_cache.json");
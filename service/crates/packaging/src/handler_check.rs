@@ -0,0 +1,108 @@
+use crate::{handler_entry_files, ZipInfo};
+use lambda_models::LambdaError;
+use tracing::debug;
+
+/// Check that `handler` resolves inside the deployment package: the entry file
+/// exists and (for interpreted runtimes) appears to define the handler function.
+/// This is a static check on the source text, so it errs on the side of accepting.
+pub fn verify_handler(runtime: &str, handler: &str, zip_info: &ZipInfo) -> Result<(), LambdaError> {
+    let candidates = handler_entry_files(runtime, handler);
+    if candidates.is_empty() {
+        return Ok(());
+    }
+
+    let Some(entry) = candidates.iter().find(|c| zip_info.contains_file(c)) else {
+        return Err(LambdaError::HandlerNotFound {
+            handler: handler.to_string(),
+            reason: format!("expected file {}", candidates.join(" or ")),
+        });
+    };
+
+    // Split the handler the way each bootstrap does
+    let function_name = match runtime {
+        "nodejs18.x" | "nodejs22.x" | "nodejs24.x" => handler
+            .split('.')
+            .nth(1)
+            .filter(|name| !name.is_empty())
+            .unwrap_or("handler"),
+        "python3.11" => match handler.rsplit_once('.') {
+            Some((_, function_name)) => function_name,
+            None => return Ok(()),
+        },
+        // Compiled runtimes only need the entry file to be present
+        _ => return Ok(()),
+    };
+
+    let source = String::from_utf8_lossy(&zip_info.read_file(entry)?).into_owned();
+    let defined = if runtime == "python3.11" {
+        python_defines(&source, function_name)
+    } else {
+        node_exports(&source, function_name)
+    };
+
+    if !defined {
+        return Err(LambdaError::HandlerNotFound {
+            handler: handler.to_string(),
+            reason: format!("{entry} does not define '{function_name}'"),
+        });
+    }
+
+    debug!("Handler {} resolved to {}", handler, entry);
+    Ok(())
+}
+
+fn node_exports(source: &str, name: &str) -> bool {
+    let direct = [
+        format!("exports.{name}"),
+        format!("exports[\"{name}\"]"),
+        format!("exports['{name}']"),
+        format!("export const {name}"),
+        format!("export let {name}"),
+        format!("export var {name}"),
+        format!("export function {name}"),
+        format!("export async function {name}"),
+    ];
+    if direct.iter().any(|p| source.contains(p.as_str())) {
+        return true;
+    }
+    // `module.exports = { handler }` / `export { handler }` style: accept if the name appears at all
+    (source.contains("module.exports") || source.contains("export {"))
+        && contains_identifier(source, name)
+}
+
+fn python_defines(source: &str, name: &str) -> bool {
+    let mut in_import = false;
+    source.lines().any(|line| {
+        let line = line.trim_end();
+        // `from x import name`, possibly aliased or inside a parenthesised list
+        let imported = if in_import {
+            Some(line)
+        } else {
+            line.strip_prefix("from ")
+                .and_then(|rest| rest.split_once(" import "))
+                .map(|(_, names)| names)
+        };
+        if let Some(names) = imported {
+            in_import = (in_import || names.contains('(')) && !names.contains(')');
+            let names = names.split('#').next().unwrap_or_default();
+            return names
+                .split(',')
+                .map(|item| item.trim_matches(|c: char| c.is_whitespace() || c == '(' || c == ')'))
+                .any(|item| item.rsplit(" as ").next() == Some(name));
+        }
+        line.starts_with(&format!("def {name}("))
+            || line.starts_with(&format!("async def {name}("))
+            || line
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+fn contains_identifier(source: &str, name: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    source.match_indices(name).any(|(idx, _)| {
+        let before = source[..idx].chars().next_back();
+        let after = source[idx + name.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
@@ -0,0 +1,123 @@
+use crate::zip_handler::ZipInfo;
+use lambda_models::LambdaError;
+
+/// File in the deployment package listing paths to leave out of the build context
+pub const IGNORE_FILE: &str = ".lambdaignore";
+
+/// One `.lambdaignore` line
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: String,
+    /// `!pattern`: paths it matches are kept after all
+    negated: bool,
+    /// `pattern/`: only matches directories
+    dir_only: bool,
+    /// Contains a `/`: matched against the whole path rather than any file or directory name
+    anchored: bool,
+}
+
+/// `.lambdaignore` rules, a subset of `.gitignore` syntax: `#` comments, `*`, `?`, `**`,
+/// `!` to re-include, a trailing `/` for directories and a `/` anywhere else to match from
+/// the package root. The last matching rule wins, and everything under an ignored
+/// directory is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct LambdaIgnore {
+    rules: Vec<Rule>,
+}
+
+impl LambdaIgnore {
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let pattern = line.trim_start_matches('/').to_string();
+                (!pattern.is_empty()).then_some(Rule {
+                    pattern,
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path`, relative to the package root with `/` separators, is left out
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        path.match_indices('/')
+            .any(|(i, _)| self.matches(&path[..i], true))
+            || self.matches(path, is_dir)
+    }
+
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && glob_match(
+                        rule.pattern.as_bytes(),
+                        if rule.anchored { path } else { name }.as_bytes(),
+                    )
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+/// `*` and `?` stay within one path segment; `**` crosses segments, and `**/` also matches
+/// no directory at all
+pub(crate) fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            glob_match(rest, text)
+                || text
+                    .iter()
+                    .enumerate()
+                    .any(|(i, &c)| c == b'/' && glob_match(rest, &text[i + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        [b'?', rest @ ..] => {
+            matches!(text.first(), Some(&c) if c != b'/') && glob_match(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Fail when the build context of `zip_info`, after its `.lambdaignore`, is over `max_size`
+/// bytes; otherwise return its size
+pub fn check_build_context(zip_info: &ZipInfo, max_size: u64) -> Result<u64, LambdaError> {
+    let size = zip_info.build_context_size(&zip_info.lambda_ignore()?);
+    if size > max_size {
+        return Err(LambdaError::BuildContextTooLarge { size, max_size });
+    }
+    Ok(size)
+}
@@ -11,7 +11,55 @@ use tracing::{error, info, instrument};
 struct RuntimeAssets;
 
 pub struct ImageBuilder {
-    _docker_host: String,
+    docker_host: String,
+    max_context_bytes: u64,
+}
+
+/// Timestamp given to every file in the build context and, through BuildKit, to the image,
+/// so that the same code and base image build the same image digest
+const SOURCE_DATE_EPOCH: u64 = 0;
+
+/// Label put on images of one `docker build`, to find what an interrupted build left
+const BUILD_LABEL: &str = "lambdah.build";
+
+/// Prunes the untagged images of a build that failed or was dropped before it finished,
+/// i.e. was cancelled; `docker build` itself is killed as its `Command` is dropped
+struct InterruptedBuildCleanup {
+    docker_host: String,
+    build_id: String,
+    finished: bool,
+}
+
+impl Drop for InterruptedBuildCleanup {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let filter = format!("label={BUILD_LABEL}={}", self.build_id);
+        let mut prune = docker_command(&self.docker_host);
+        runtime.spawn(async move {
+            let pruned = prune
+                .args(["image", "prune", "--force", "--filter", &filter])
+                .output()
+                .await;
+            if let Err(e) = pruned {
+                error!("Failed to prune images of build ({}): {}", filter, e);
+            }
+        });
+    }
+}
+
+/// `docker` CLI talking to `docker_host`, the `[docker] host` setting; when that is empty the
+/// CLI picks the daemon itself, from `DOCKER_HOST` or its context
+fn docker_command(docker_host: &str) -> Command {
+    let mut command = Command::new("docker");
+    if !docker_host.trim().is_empty() {
+        command.env("DOCKER_HOST", docker_host.trim());
+    }
+    command
 }
 
 /// Get embedded bootstrap file content for a given runtime
@@ -57,9 +105,10 @@ fn get_embedded_websocket_bootstrap(function: &Function) -> Result<Vec<u8>, Lamb
 }
 
 impl ImageBuilder {
-    pub fn new(docker_host: String) -> Self {
+    pub fn new(docker_host: String, max_context_bytes: u64) -> Self {
         Self {
-            _docker_host: docker_host,
+            docker_host,
+            max_context_bytes,
         }
     }
 
@@ -70,8 +119,13 @@ impl ImageBuilder {
         zip_info: &ZipInfo,
         image_ref: &str,
         runtime_api_port: u16,
+        base_image: &str,
     ) -> Result<(), LambdaError> {
-        // Create temporary directory for build context
+        // Leave out what the package's .lambdaignore lists
+        crate::ignore::check_build_context(zip_info, self.max_context_bytes)?;
+        let ignore = zip_info.lambda_ignore()?;
+
+        // Create temporary directory for build context; removed when dropped
         let temp_dir = tempfile::tempdir().map_err(|e| LambdaError::InternalError {
             reason: e.to_string(),
         })?;
@@ -81,7 +135,7 @@ impl ImageBuilder {
         // Extract ZIP to build context
         let zip_handler = crate::zip_handler::ZipHandler::new(50 * 1024 * 1024); // 50MB limit
         zip_handler
-            .extract_to_directory(&zip_info.zip_data, build_context)
+            .extract_build_context(&zip_info.zip_data, build_context, &ignore)
             .await?;
 
         // Copy embedded bootstrap scripts to build context
@@ -105,15 +159,15 @@ impl ImageBuilder {
                 _ => "bootstrap-websocket.js",
             };
             let websocket_bootstrap_dest = build_context.join(websocket_bootstrap_filename);
-            std::fs::write(&websocket_bootstrap_dest, websocket_bootstrap_content).map_err(|e| {
-                LambdaError::InternalError {
+            std::fs::write(&websocket_bootstrap_dest, websocket_bootstrap_content).map_err(
+                |e| LambdaError::InternalError {
                     reason: e.to_string(),
-                }
-            })?;
+                },
+            )?;
         }
 
         // Create Dockerfile based on runtime
-        let dockerfile_content = runtimes::dockerfile_for(function, runtime_api_port);
+        let dockerfile_content = runtimes::dockerfile_for(function, runtime_api_port, base_image);
         let dockerfile_path = build_context.join("Dockerfile");
         std::fs::write(&dockerfile_path, dockerfile_content).map_err(|e| {
             LambdaError::InternalError {
@@ -123,13 +177,25 @@ impl ImageBuilder {
 
         // Bootstrap files are already copied above using embedded assets
 
+        crate::zip_handler::pin_timestamps(build_context, SOURCE_DATE_EPOCH)?;
+
         // Build Docker image
         info!("Building Docker image: {}", image_ref);
         info!("Build context: {:?}", build_context);
         info!("Dockerfile path: {:?}", dockerfile_path);
 
-        let build_result = Command::new("docker")
+        let mut cleanup = InterruptedBuildCleanup {
+            docker_host: self.docker_host.clone(),
+            build_id: uuid::Uuid::new_v4().to_string(),
+            finished: false,
+        };
+        let build_result = docker_command(&self.docker_host)
             .arg("build")
+            .arg("--force-rm")
+            .arg("--build-arg")
+            .arg(format!("SOURCE_DATE_EPOCH={SOURCE_DATE_EPOCH}"))
+            .arg("--label")
+            .arg(format!("{BUILD_LABEL}={}", cleanup.build_id))
             .arg("-t")
             .arg(image_ref)
             .arg("-f")
@@ -137,23 +203,76 @@ impl ImageBuilder {
             .arg(build_context)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .output()
             .await
             .map_err(|e| LambdaError::DockerError {
                 message: e.to_string(),
             })?;
+        // Failed builds leave untagged images as well
+        cleanup.finished = build_result.status.success();
 
         if !build_result.status.success() {
             let stdout = String::from_utf8_lossy(&build_result.stdout);
             let stderr = String::from_utf8_lossy(&build_result.stderr);
             error!("Docker build failed - stdout: {}", stdout);
             error!("Docker build failed - stderr: {}", stderr);
-            return Err(LambdaError::DockerError {
-                message: format!("Docker build failed: {stderr}"),
-            });
+            // BuildKit writes its progress and errors to stderr, the classic builder to stdout
+            return Err(crate::build_failure::build_failure(
+                &function.function_name,
+                &format!("{stdout}\n{stderr}"),
+            ));
         }
 
         info!("Built Docker image: {}", image_ref);
         Ok(())
     }
+
+    /// Image ID (`sha256:...`) of `image_ref`, the digest of its config and layers
+    pub async fn image_digest(&self, image_ref: &str) -> Result<String, LambdaError> {
+        let output = docker_command(&self.docker_host)
+            .args(["image", "inspect", "--format", "{{.Id}}", image_ref])
+            .output()
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: e.to_string(),
+            })?;
+        if !output.status.success() {
+            return Err(LambdaError::DockerError {
+                message: format!(
+                    "Failed to inspect image {image_ref}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Digest (`sha256:...`) of the local copy of `base_image`, or `None` when Docker has
+    /// none for it, e.g. for an image that was built rather than pulled
+    pub async fn base_image_digest(&self, base_image: &str) -> Result<Option<String>, LambdaError> {
+        let output = docker_command(&self.docker_host)
+            .args([
+                "image",
+                "inspect",
+                "--format",
+                "{{json .RepoDigests}}",
+                base_image,
+            ])
+            .output()
+            .await
+            .map_err(|e| LambdaError::DockerError {
+                message: e.to_string(),
+            })?;
+        if !output.status.success() {
+            return Err(LambdaError::DockerError {
+                message: format!(
+                    "Failed to inspect image {base_image}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        let repo_digests: Vec<String> = serde_json::from_slice(&output.stdout).unwrap_or_default();
+        Ok(runtimes::repo_digest(base_image, &repo_digests))
+    }
 }
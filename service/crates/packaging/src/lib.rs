@@ -1,11 +1,19 @@
+pub mod build_failure;
 pub mod cache;
+pub mod handler_check;
+pub mod ignore;
 pub mod image_builder;
 pub mod runtimes;
 pub mod service;
+pub mod workspace;
 pub mod zip_handler;
 
+pub use build_failure::*;
 pub use cache::*;
+pub use handler_check::*;
+pub use ignore::*;
 pub use image_builder::*;
 pub use runtimes::*;
 pub use service::*;
+pub use workspace::*;
 pub use zip_handler::*;
@@ -5,15 +5,64 @@ mod node;
 mod python;
 mod rust_rt;
 
-pub fn dockerfile_for(function: &Function, runtime_api_port: u16) -> String {
+/// Dockerfile for `function`, with `base_image` as the image its code runs on
+pub fn dockerfile_for(function: &Function, runtime_api_port: u16, base_image: &str) -> String {
     match function.runtime.as_str() {
-        "nodejs18.x" | "nodejs22.x" | "nodejs24.x" => node::dockerfile(function, runtime_api_port),
-        "python3.11" => python::dockerfile(function, runtime_api_port),
-        "rust" => rust_rt::dockerfile(function, runtime_api_port),
+        "nodejs18.x" | "nodejs22.x" | "nodejs24.x" => {
+            node::dockerfile(function, runtime_api_port, base_image)
+        }
+        "python3.11" => python::dockerfile(function, runtime_api_port, base_image),
+        "rust" => rust_rt::dockerfile(function, runtime_api_port, base_image),
         _ => unreachable!("unsupported runtime checked earlier"),
     }
 }
 
+/// Runtimes functions can be created with
+pub const SUPPORTED_RUNTIMES: &[&str] = &[
+    "nodejs18.x",
+    "nodejs22.x",
+    "nodejs24.x",
+    "python3.11",
+    "rust",
+];
+
+/// Image a runtime's functions run on, as referenced by its Dockerfile
+pub fn base_image(runtime: &str) -> Option<&'static str> {
+    match runtime {
+        "nodejs18.x" => Some("node:18-alpine"),
+        "nodejs22.x" => Some("node:22-alpine"),
+        "nodejs24.x" => Some("node:24-alpine"),
+        "python3.11" => Some("python:3.11-alpine"),
+        // The builder stage is not pinned; only the stage the binary runs in
+        "rust" => Some("alpine:latest"),
+        _ => None,
+    }
+}
+
+/// `base_image` pinned to `digest` (`sha256:...`) so later pulls of its tag are not picked up
+pub fn pinned_base_image(base_image: &str, digest: Option<&str>) -> String {
+    match digest {
+        Some(digest) => format!("{base_image}@{digest}"),
+        None => base_image.to_string(),
+    }
+}
+
+/// Digest of `base_image` among the `RepoDigests` Docker reports for it
+/// (`["node@sha256:..."]`)
+pub fn repo_digest(base_image: &str, repo_digests: &[String]) -> Option<String> {
+    let repository = base_image
+        .split_once('@')
+        .map_or(base_image, |(image, _)| image);
+    let repository = match repository.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => name,
+        _ => repository,
+    };
+    repo_digests.iter().find_map(|entry| {
+        let (name, digest) = entry.split_once('@')?;
+        (name == repository).then(|| digest.to_string())
+    })
+}
+
 /// Optional bootstrap source path relative to repo root that should be copied into build context.
 /// Returns (relative_path_in_repo, dest_filename) where dest is placed in /var/runtime in the image.
 pub fn bootstrap_source(function: &Function) -> Option<(PathBuf, &'static str)> {
@@ -37,3 +86,21 @@ pub fn bootstrap_source(function: &Function) -> Option<(PathBuf, &'static str)>
         _ => None,
     }
 }
+
+/// Paths inside the function bundle that the runtime loads for `handler`.
+/// Any one of them being present is enough; an empty list means the runtime has no handler file.
+pub fn handler_entry_files(runtime: &str, handler: &str) -> Vec<String> {
+    match runtime {
+        // The node bootstrap loads `<first segment>.js` from the task root
+        "nodejs18.x" | "nodejs22.x" | "nodejs24.x" => {
+            let module = handler.split('.').next().unwrap_or(handler);
+            vec![format!("{module}.js")]
+        }
+        "python3.11" => {
+            let module = handler.rsplit_once('.').map_or(handler, |(m, _)| m);
+            vec![format!("{}.py", module.replace('.', "/"))]
+        }
+        "rust" => vec!["Cargo.toml".to_string()],
+        _ => Vec::new(),
+    }
+}
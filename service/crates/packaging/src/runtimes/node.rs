@@ -1,14 +1,9 @@
 use lambda_models::Function;
 
-pub fn dockerfile(function: &Function, runtime_api_port: u16) -> String {
-    let tag = match function.runtime.as_str() {
-        "nodejs24.x" => "24",
-        "nodejs22.x" => "22",
-        _ => "18",
-    };
+pub fn dockerfile(_function: &Function, runtime_api_port: u16, base_image: &str) -> String {
     format!(
         r#"
-FROM node:{tag}-alpine
+FROM {base_image}
 ENV NODE_ENV=production
 
 # Install runtime interface client and WebSocket dependencies
@@ -54,8 +49,8 @@ export LAMBDA_TASK_ROOT=/var/task\n\
 export LAMBDA_RUNTIME_DIR=/var/runtime\n\
 export NODE_PATH="/var/task/node_modules:/opt/nodejs/node_modules:/opt/node_modules:$NODE_PATH"\n\
 \n\
-# Start the runtime\n\
-node /var/runtime/bootstrap-websocket.js\n' > /var/runtime/bootstrap.sh && chmod +x /var/runtime/bootstrap.sh
+# Start the runtime as PID 1 so it receives SIGTERM from docker stop\n\
+exec node /var/runtime/bootstrap-websocket.js\n' > /var/runtime/bootstrap.sh && chmod +x /var/runtime/bootstrap.sh
 
 # Set entrypoint
 ENTRYPOINT ["/var/runtime/bootstrap.sh"]
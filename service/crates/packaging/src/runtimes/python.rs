@@ -1,9 +1,9 @@
 use lambda_models::Function;
 
-pub fn dockerfile(_function: &Function, runtime_api_port: u16) -> String {
+pub fn dockerfile(_function: &Function, runtime_api_port: u16, base_image: &str) -> String {
     format!(
         r#"
-FROM python:3.11-alpine
+FROM {base_image}
 
 # Install runtime interface client and WebSocket dependencies
 RUN apk add --no-cache curl
@@ -49,8 +49,8 @@ export LAMBDA_TASK_ROOT=/var/task\n\
 export LAMBDA_RUNTIME_DIR=/var/runtime\n\
 export PYTHONPATH="/var/task:/var/task/python:/opt/python:$PYTHONPATH"\n\
 \n\
-# Start the runtime\n\
-python /var/runtime/bootstrap-websocket.py\n' > /var/runtime/bootstrap.sh && chmod +x /var/runtime/bootstrap.sh
+# Start the runtime as PID 1 so it receives SIGTERM from docker stop\n\
+exec python /var/runtime/bootstrap-websocket.py\n' > /var/runtime/bootstrap.sh && chmod +x /var/runtime/bootstrap.sh
 
 ENTRYPOINT ["/var/runtime/bootstrap.sh"]
 USER 1000:1000
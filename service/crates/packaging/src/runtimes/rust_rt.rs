@@ -1,6 +1,6 @@
 use lambda_models::Function;
 
-pub fn dockerfile(function: &Function, runtime_api_port: u16) -> String {
+pub fn dockerfile(function: &Function, runtime_api_port: u16, base_image: &str) -> String {
     format!(
         r#"
 FROM rust:1.75-alpine as builder
@@ -21,7 +21,7 @@ WORKDIR /var/task
 RUN cargo build --release
 
 # Runtime stage
-FROM alpine:latest
+FROM {base_image}
 
 # Install runtime dependencies
 RUN apk add --no-cache libgcc
@@ -44,8 +44,8 @@ export AWS_LAMBDA_LOG_STREAM_NAME=${{AWS_LAMBDA_LOG_STREAM_NAME}}
 export LAMBDA_TASK_ROOT=/var/task
 export LAMBDA_RUNTIME_DIR=/var/runtime
 
-# Start the runtime
-/var/task/{bin}
+# Start the runtime as PID 1 so it receives SIGTERM from docker stop
+exec /var/task/{bin}
 ' > /var/runtime/bootstrap.sh && chmod +x /var/runtime/bootstrap.sh
 
 ENTRYPOINT ["/var/runtime/bootstrap.sh"]
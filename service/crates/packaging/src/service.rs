@@ -11,7 +11,10 @@ pub struct PackagingService {
 impl PackagingService {
     pub fn new(config: Config) -> Self {
         let zip_handler = ZipHandler::new(50 * 1024 * 1024); // 50MB limit
-        let image_builder = ImageBuilder::new(config.docker.host.clone());
+        let image_builder = ImageBuilder::new(
+            config.docker.host.clone(),
+            config.builds.max_context_mb * 1024 * 1024,
+        );
         let cache = PackagingCache::new(config.data.dir.clone().into()).unwrap_or_else(|_| {
             // Create a default cache if the directory doesn't exist
             PackagingCache::new(PathBuf::from("./data")).unwrap()
@@ -28,11 +31,14 @@ impl PackagingService {
         self.zip_handler.process_zip(zip_data).await
     }
 
+    /// Build the image of `function` on `base_image`, e.g. a digest-pinned
+    /// [`pinned_base_image`](crate::pinned_base_image)
     pub async fn build_image(
         &mut self,
         function: &Function,
         image_ref: &str,
         runtime_api_port: u16,
+        base_image: &str,
     ) -> Result<(), LambdaError> {
         // Get the ZIP data for this function
         let zip_data = self.cache.load_zip_file(&function.code_sha256)?;
@@ -45,7 +51,7 @@ impl PackagingService {
 
         // Build new image
         self.image_builder
-            .build_image(function, &zip_info, image_ref, runtime_api_port)
+            .build_image(function, &zip_info, image_ref, runtime_api_port, base_image)
             .await?;
 
         // Cache the result
@@ -55,6 +61,14 @@ impl PackagingService {
         Ok(())
     }
 
+    pub async fn base_image_digest(&self, base_image: &str) -> Result<Option<String>, LambdaError> {
+        self.image_builder.base_image_digest(base_image).await
+    }
+
+    pub async fn image_digest(&self, image_ref: &str) -> Result<String, LambdaError> {
+        self.image_builder.image_digest(image_ref).await
+    }
+
     pub fn store_zip(&self, zip_info: &crate::ZipInfo) -> Result<PathBuf, LambdaError> {
         self.cache.store_zip_file(zip_info)
     }
@@ -63,6 +77,16 @@ impl PackagingService {
         self.cache.load_zip_file(sha256)
     }
 
+    /// Delete stored ZIPs not referenced by any function or version
+    pub fn remove_unreferenced_zips(
+        &mut self,
+        keep: &std::collections::HashSet<String>,
+    ) -> Result<(usize, u64), LambdaError> {
+        let removed = self.cache.remove_unreferenced_zips(keep)?;
+        self.cache.save_cache()?;
+        Ok(removed)
+    }
+
     pub fn save_cache(&self) -> Result<(), LambdaError> {
         self.cache.save_cache()
     }
@@ -0,0 +1,268 @@
+use crate::ignore::glob_match;
+use lambda_models::LambdaError;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Directory of the bundle that workspace dependencies are copied into
+pub const WORKSPACE_DEPS_DIR: &str = ".workspace";
+
+/// Dependency sections whose workspace entries are deployed with the package
+const RUNTIME_DEPENDENCIES: [&str; 2] = ["dependencies", "optionalDependencies"];
+
+struct Entry {
+    data: Vec<u8>,
+    mode: Option<u32>,
+}
+
+/// Deployment package for the package at `package_path` of a zipped npm or pnpm workspace.
+/// The package's files go at the root of the bundle, and the workspace packages it depends
+/// on at runtime, directly or not, under `.workspace/<name>/`. Their `package.json` files
+/// point at each other with `file:` paths instead of `workspace:` ranges, so `npm install`
+/// in the image links them and installs their registry dependencies.
+pub fn bundle_workspace(zip_data: &[u8], package_path: &str) -> Result<Vec<u8>, LambdaError> {
+    let entries = read_entries(zip_data)?;
+    let packages = workspace_packages(&entries)?;
+    let package_path = package_path.trim_start_matches("./").trim_matches('/');
+    let (root_name, _) = packages
+        .iter()
+        .find(|(_, dir)| dir.as_str() == package_path)
+        .ok_or_else(|| LambdaError::InvalidRequest {
+            reason: format!("{package_path} is not a package of the workspace"),
+        })?;
+
+    // Workspace packages needed at runtime, by name
+    let mut needed = BTreeSet::new();
+    let mut pending = VecDeque::from([root_name.clone()]);
+    while let Some(name) = pending.pop_front() {
+        let manifest = read_manifest(&entries, &packages[&name])?;
+        for dep in dependency_names(&manifest, &RUNTIME_DEPENDENCIES) {
+            if packages.contains_key(&dep) && dep != *root_name && needed.insert(dep.clone()) {
+                pending.push_back(dep);
+            }
+        }
+    }
+
+    let mut bundle = BTreeMap::new();
+    let mut add_package = |name: &str, target: String| -> Result<(), LambdaError> {
+        let dir = &packages[name];
+        // From `target` back to the bundle root, for `file:` paths
+        let to_root = "../".repeat(target.split('/').filter(|s| !s.is_empty()).count());
+        for (path, entry) in &entries {
+            let Some(relative) = path.strip_prefix(&format!("{dir}/")) else {
+                continue;
+            };
+            if relative.starts_with("node_modules/") {
+                continue;
+            }
+            let data = if relative == "package.json" {
+                let manifest = read_manifest(&entries, dir)?;
+                let manifest = link_workspace_dependencies(manifest, &packages, &needed, &to_root);
+                serde_json::to_vec_pretty(&manifest).map_err(|e| LambdaError::InvalidRequest {
+                    reason: format!("{path}: {e}"),
+                })?
+            } else {
+                entry.data.clone()
+            };
+            bundle.insert(format!("{target}{relative}"), (data, entry.mode));
+        }
+        Ok(())
+    };
+    add_package(root_name, String::new())?;
+    for name in &needed {
+        add_package(name, format!("{WORKSPACE_DEPS_DIR}/{name}/"))?;
+    }
+
+    let mut zip_data = Vec::new();
+    let mut zip = ZipWriter::new(std::io::Cursor::new(&mut zip_data));
+    let to_err = |e: &dyn std::fmt::Display| LambdaError::InternalError {
+        reason: e.to_string(),
+    };
+    for (name, (data, mode)) in bundle {
+        let options = FileOptions::default().last_modified_time(zip::DateTime::default());
+        let options = match mode {
+            Some(mode) => options.unix_permissions(mode & 0o777),
+            None => options,
+        };
+        zip.start_file(name, options).map_err(|e| to_err(&e))?;
+        zip.write_all(&data).map_err(|e| to_err(&e))?;
+    }
+    zip.finish().map_err(|e| to_err(&e))?;
+    drop(zip);
+    Ok(zip_data)
+}
+
+/// [`bundle_workspace`] for a workspace checked out at `dir`. `node_modules` directories are
+/// skipped; dependencies are installed in the image.
+pub fn bundle_workspace_directory(dir: &Path, package_path: &str) -> Result<Vec<u8>, LambdaError> {
+    let zip_data =
+        crate::zip_handler::zip_directory_where(dir, |path| !path.ends_with("node_modules"))?;
+    bundle_workspace(&zip_data, package_path)
+}
+
+fn read_entries(zip_data: &[u8]) -> Result<BTreeMap<String, Entry>, LambdaError> {
+    let invalid = |e: &dyn std::fmt::Display| LambdaError::InvalidZipFile {
+        reason: e.to_string(),
+    };
+    let mut archive = ZipArchive::new(std::io::Cursor::new(zip_data)).map_err(|e| invalid(&e))?;
+    let mut entries = BTreeMap::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| invalid(&e))?;
+        if file.is_dir() {
+            continue;
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(|e| invalid(&e))?;
+        let name = file.name().trim_start_matches("./").to_string();
+        entries.insert(
+            name,
+            Entry {
+                data,
+                mode: file.unix_mode(),
+            },
+        );
+    }
+    Ok(entries)
+}
+
+/// Directory of each workspace package, by package name
+fn workspace_packages(
+    entries: &BTreeMap<String, Entry>,
+) -> Result<BTreeMap<String, String>, LambdaError> {
+    let patterns = workspace_patterns(entries)?;
+    let included = |dir: &str| {
+        let matching = |negated: bool| {
+            patterns
+                .iter()
+                .filter(|p| p.starts_with('!') == negated)
+                .any(|p| glob_match(p.trim_start_matches('!').as_bytes(), dir.as_bytes()))
+        };
+        matching(false) && !matching(true)
+    };
+
+    let mut packages = BTreeMap::new();
+    for path in entries.keys() {
+        let Some(dir) = path.strip_suffix("/package.json") else {
+            continue;
+        };
+        if dir.split('/').any(|s| s == "node_modules") || !included(dir) {
+            continue;
+        }
+        if let Some(name) = read_manifest(entries, dir)?["name"].as_str() {
+            packages.insert(name.to_string(), dir.to_string());
+        }
+    }
+    Ok(packages)
+}
+
+/// `workspaces` of the root `package.json`, or `packages` of `pnpm-workspace.yaml`
+fn workspace_patterns(entries: &BTreeMap<String, Entry>) -> Result<Vec<String>, LambdaError> {
+    let normalize = |p: &str| {
+        let (negated, p) = match p.strip_prefix('!') {
+            Some(rest) => ("!", rest),
+            None => ("", p),
+        };
+        format!(
+            "{negated}{}",
+            p.trim_start_matches("./").trim_end_matches('/')
+        )
+    };
+    if entries.contains_key("package.json") {
+        let manifest = read_manifest(entries, "")?;
+        let workspaces = match &manifest["workspaces"] {
+            Value::Object(yarn_style) => yarn_style.get("packages").cloned(),
+            other => Some(other.clone()),
+        };
+        if let Some(Value::Array(patterns)) = workspaces {
+            return Ok(patterns
+                .iter()
+                .filter_map(Value::as_str)
+                .map(normalize)
+                .collect());
+        }
+    }
+    if let Some(entry) = entries.get("pnpm-workspace.yaml") {
+        return Ok(pnpm_packages(&String::from_utf8_lossy(&entry.data))
+            .iter()
+            .map(|p| normalize(p))
+            .collect());
+    }
+    Err(LambdaError::InvalidRequest {
+        reason: "Not an npm or pnpm workspace: the package has no workspaces in package.json \
+                 or pnpm-workspace.yaml at its root"
+            .to_string(),
+    })
+}
+
+/// Items of the top-level `packages:` list of a `pnpm-workspace.yaml`
+fn pnpm_packages(yaml: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in yaml.lines() {
+        let line = line.split(" #").next().unwrap_or(line).trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '-']) {
+            in_packages = line.trim() == "packages:";
+            continue;
+        }
+        if let Some(item) = line.trim().strip_prefix('-').filter(|_| in_packages) {
+            packages.push(item.trim().trim_matches(['\'', '"']).to_string());
+        }
+    }
+    packages
+}
+
+fn read_manifest(entries: &BTreeMap<String, Entry>, dir: &str) -> Result<Value, LambdaError> {
+    let path = match dir {
+        "" => "package.json".to_string(),
+        dir => format!("{dir}/package.json"),
+    };
+    let entry = entries
+        .get(&path)
+        .ok_or_else(|| LambdaError::InvalidRequest {
+            reason: format!("{path} not found"),
+        })?;
+    serde_json::from_slice(&entry.data).map_err(|e| LambdaError::InvalidRequest {
+        reason: format!("{path}: {e}"),
+    })
+}
+
+fn dependency_names(manifest: &Value, sections: &[&str]) -> Vec<String> {
+    sections
+        .iter()
+        .filter_map(|section| manifest[*section].as_object())
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Point runtime dependencies on bundled workspace packages at their copies, `to_root` being
+/// the path from the package back to the bundle root, and drop dev dependencies on workspace
+/// packages, which `npm` could not resolve outside the workspace
+fn link_workspace_dependencies(
+    mut manifest: Value,
+    packages: &BTreeMap<String, String>,
+    bundled: &BTreeSet<String>,
+    to_root: &str,
+) -> Value {
+    for section in RUNTIME_DEPENDENCIES {
+        if let Some(deps) = manifest[section].as_object_mut() {
+            for (name, range) in deps.iter_mut() {
+                if bundled.contains(name) {
+                    *range = Value::String(format!("file:{to_root}{WORKSPACE_DEPS_DIR}/{name}"));
+                }
+            }
+        }
+    }
+    if let Some(deps) = manifest["devDependencies"].as_object_mut() {
+        deps.retain(|name, range| {
+            !packages.contains_key(name)
+                && !range.as_str().is_some_and(|r| r.starts_with("workspace:"))
+        });
+    }
+    manifest
+}
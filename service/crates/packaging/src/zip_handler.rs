@@ -1,8 +1,11 @@
+use crate::ignore::{LambdaIgnore, IGNORE_FILE};
 use lambda_models::LambdaError;
 use sha2::{Digest, Sha256};
-use std::io::Read;
+use std::io::{Read, Write};
+use std::path::Path;
 use tracing::{info, instrument};
-use zip::ZipArchive;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
 
 pub struct ZipHandler {
     max_zip_size: u64,
@@ -76,6 +79,34 @@ impl ZipHandler {
         &self,
         zip_data: &[u8],
         target_dir: &std::path::Path,
+    ) -> Result<(), LambdaError> {
+        self.extract(zip_data, target_dir, |_, _| true)?;
+        info!("Extracted ZIP to directory: {}", target_dir.display());
+        Ok(())
+    }
+
+    /// Extract what the image needs: everything except paths matched by `ignore`, and the
+    /// `.lambdaignore` file itself
+    #[instrument(skip(self, zip_data, ignore))]
+    pub async fn extract_build_context(
+        &self,
+        zip_data: &[u8],
+        target_dir: &std::path::Path,
+        ignore: &LambdaIgnore,
+    ) -> Result<(), LambdaError> {
+        self.extract(zip_data, target_dir, |name, is_dir| {
+            !is_ignore_file(name) && !ignore.is_ignored(name, is_dir)
+        })?;
+        info!("Extracted build context to: {}", target_dir.display());
+        Ok(())
+    }
+
+    /// Extract the entries `keep` accepts, given their name and whether they are a directory
+    fn extract(
+        &self,
+        zip_data: &[u8],
+        target_dir: &std::path::Path,
+        keep: impl Fn(&str, bool) -> bool,
     ) -> Result<(), LambdaError> {
         let mut archive = ZipArchive::new(std::io::Cursor::new(zip_data)).map_err(|e| {
             LambdaError::InvalidZipFile {
@@ -83,14 +114,22 @@ impl ZipHandler {
             }
         })?;
 
-        for i in 0..archive.len() {
+        // In name order, whatever order the archive lists entries in
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        for name in names {
             let mut file = archive
-                .by_index(i)
+                .by_name(&name)
                 .map_err(|e| LambdaError::InvalidZipFile {
                     reason: e.to_string(),
                 })?;
 
-            let file_path = target_dir.join(file.name());
+            let is_dir = file.name().ends_with('/') || file.name().ends_with('\\');
+            let entry_name = entry_name(file.name())?;
+            if entry_name.is_empty() || !keep(&entry_name, is_dir) {
+                continue;
+            }
+            let file_path = target_dir.join(&entry_name);
 
             // Create parent directories if they don't exist
             if let Some(parent) = file_path.parent() {
@@ -100,7 +139,7 @@ impl ZipHandler {
             }
 
             // Skip directories
-            if file.name().ends_with('/') {
+            if is_dir {
                 std::fs::create_dir_all(&file_path).map_err(|e| LambdaError::InvalidZipFile {
                     reason: e.to_string(),
                 })?;
@@ -133,7 +172,6 @@ impl ZipHandler {
             }
         }
 
-        info!("Extracted ZIP to directory: {}", target_dir.display());
         Ok(())
     }
 }
@@ -152,3 +190,174 @@ pub struct ZipFileInfo {
     pub size: u64,
     pub is_executable: bool,
 }
+
+impl ZipInfo {
+    /// Whether the archive contains a file at `path` (leading `./` is ignored)
+    pub fn contains_file(&self, path: &str) -> bool {
+        self.files
+            .iter()
+            .any(|f| f.name.trim_start_matches("./") == path)
+    }
+
+    /// Rules of the package's `.lambdaignore`; empty when it has none
+    pub fn lambda_ignore(&self) -> Result<LambdaIgnore, LambdaError> {
+        if !self.contains_file(IGNORE_FILE) {
+            return Ok(LambdaIgnore::default());
+        }
+        let content = self.read_file(IGNORE_FILE)?;
+        Ok(LambdaIgnore::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    /// Uncompressed size of the files that go into the build context
+    pub fn build_context_size(&self, ignore: &LambdaIgnore) -> u64 {
+        self.files
+            .iter()
+            .filter(|f| !f.name.ends_with('/') && !is_ignore_file(&f.name))
+            .filter(|f| !ignore.is_ignored(&f.name, false))
+            .map(|f| f.size)
+            .sum()
+    }
+
+    /// Read a single file out of the archive
+    pub fn read_file(&self, path: &str) -> Result<Vec<u8>, LambdaError> {
+        let mut archive = ZipArchive::new(std::io::Cursor::new(&self.zip_data)).map_err(|e| {
+            LambdaError::InvalidZipFile {
+                reason: e.to_string(),
+            }
+        })?;
+        let name = self
+            .files
+            .iter()
+            .map(|f| f.name.as_str())
+            .find(|name| name.trim_start_matches("./") == path)
+            .unwrap_or(path)
+            .to_string();
+        let mut file = archive
+            .by_name(&name)
+            .map_err(|e| LambdaError::InvalidZipFile {
+                reason: format!("{path}: {e}"),
+            })?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|e| LambdaError::InvalidZipFile {
+                reason: format!("{path}: {e}"),
+            })?;
+        Ok(data)
+    }
+}
+
+fn is_ignore_file(name: &str) -> bool {
+    name.trim_start_matches("./") == IGNORE_FILE
+}
+
+/// Path of the entry `name` relative to the extraction directory, with `/` separators.
+/// Archives made on Windows may separate directories with `\` instead. Names that would
+/// leave the directory, through `..` or a root, are rejected, and on Windows so are drives
+/// and alternate data streams (`:`).
+pub fn entry_name(name: &str) -> Result<String, LambdaError> {
+    let escapes = || LambdaError::InvalidZipFile {
+        reason: format!("Entry {name} points outside the archive"),
+    };
+    if name.starts_with(['/', '\\']) {
+        return Err(escapes());
+    }
+    let mut parts = Vec::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => return Err(escapes()),
+            _ if cfg!(windows) && part.contains(':') => return Err(escapes()),
+            _ => parts.push(part),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Set the modification time of `dir` and everything under it to `SOURCE_DATE_EPOCH`
+/// seconds, so the same files always give the same `COPY` layers
+pub fn pin_timestamps(dir: &Path, source_date_epoch: u64) -> Result<(), LambdaError> {
+    let to_err = |path: &Path, e: &dyn std::fmt::Display| LambdaError::InternalError {
+        reason: format!("{}: {e}", path.display()),
+    };
+    let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(source_date_epoch);
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current).map_err(|e| to_err(&current, &e))? {
+            let path = entry.map_err(|e| to_err(&current, &e))?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                std::fs::File::open(&path)
+                    .and_then(|file| file.set_modified(mtime))
+                    .map_err(|e| to_err(&path, &e))?;
+            }
+        }
+        // Directories can only be opened like this on unix
+        #[cfg(unix)]
+        std::fs::File::open(&current)
+            .and_then(|file| file.set_modified(mtime))
+            .map_err(|e| to_err(&current, &e))?;
+    }
+    Ok(())
+}
+
+/// Zip the files under `dir`, keeping their unix permissions. Entries are sorted and carry no
+/// timestamps, so the same files always give the same archive and SHA256.
+pub fn zip_directory(dir: &Path) -> Result<Vec<u8>, LambdaError> {
+    zip_directory_where(dir, |_| true)
+}
+
+/// [`zip_directory`], leaving out the directories `descend` rejects
+pub fn zip_directory_where(
+    dir: &Path,
+    descend: impl Fn(&Path) -> bool,
+) -> Result<Vec<u8>, LambdaError> {
+    let to_err = |path: &Path, e: &dyn std::fmt::Display| LambdaError::InvalidRequest {
+        reason: format!("{}: {e}", path.display()),
+    };
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current).map_err(|e| to_err(&current, &e))? {
+            let path = entry.map_err(|e| to_err(&current, &e))?.path();
+            let metadata = std::fs::metadata(&path).map_err(|e| to_err(&path, &e))?;
+            if metadata.is_dir() {
+                if descend(&path) {
+                    pending.push(path);
+                }
+            } else if metadata.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    let mut zip_data = Vec::new();
+    let mut zip = ZipWriter::new(std::io::Cursor::new(&mut zip_data));
+    for path in files {
+        let name = path
+            .strip_prefix(dir)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let options = FileOptions::default().last_modified_time(zip::DateTime::default());
+        #[cfg(unix)]
+        let options = {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path)
+                .map_err(|e| to_err(&path, &e))?
+                .permissions()
+                .mode();
+            options.unix_permissions(mode & 0o777)
+        };
+        let contents = std::fs::read(&path).map_err(|e| to_err(&path, &e))?;
+        zip.start_file(name, options)
+            .map_err(|e| to_err(&path, &e))?;
+        zip.write_all(&contents).map_err(|e| to_err(&path, &e))?;
+    }
+    zip.finish().map_err(|e| to_err(dir, &e))?;
+    drop(zip);
+    Ok(zip_data)
+}
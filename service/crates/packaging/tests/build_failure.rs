@@ -0,0 +1,76 @@
+use lambda_models::{BuildFailureKind, LambdaError};
+use lambda_packaging::{
+    build_failure, build_log_excerpt, classify_build_failure, BUILD_LOG_MAX_BYTES,
+    BUILD_LOG_MAX_LINES,
+};
+
+const NPM_FAILURE: &str = r#"#7 [4/5] RUN npm ci --omit=dev
+#7 2.113 npm ERR! code E404
+#7 2.114 npm ERR! 404 Not Found - GET https://registry.npmjs.org/left-padd
+#7 ERROR: process "/bin/sh -c npm ci --omit=dev" did not complete successfully: exit code: 1
+------
+ > [4/5] RUN npm ci --omit=dev:
+------
+"#;
+
+#[test]
+fn failures_are_classified_by_their_output() {
+    assert_eq!(
+        classify_build_failure(NPM_FAILURE),
+        BuildFailureKind::StepFailed
+    );
+    assert_eq!(
+        classify_build_failure(
+            "ERROR: failed to solve: node:99-alpine: failed to resolve source metadata for docker.io/library/node:99-alpine: not found"
+        ),
+        BuildFailureKind::BaseImagePull
+    );
+    assert_eq!(
+        classify_build_failure("write /var/lib/docker/tmp/x: no space left on device"),
+        BuildFailureKind::NoSpace
+    );
+    assert_eq!(
+        classify_build_failure(
+            "Cannot connect to the Docker daemon at unix:///var/run/docker.sock. Is the docker daemon running?"
+        ),
+        BuildFailureKind::DaemonUnavailable
+    );
+    assert_eq!(classify_build_failure("killed"), BuildFailureKind::Other);
+}
+
+#[test]
+fn failures_carry_the_error_line_and_a_log_excerpt() {
+    let err = build_failure("orders", NPM_FAILURE);
+    let LambdaError::ImageBuildFailed {
+        kind,
+        summary,
+        build_log,
+        ..
+    } = &err
+    else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(*kind, BuildFailureKind::StepFailed);
+    assert!(
+        summary.contains("did not complete successfully"),
+        "{summary}"
+    );
+    assert!(build_log.contains("npm ERR! 404 Not Found"));
+    assert_eq!(err.error_type(), "ImageBuildFailedException");
+    assert!(err
+        .to_string()
+        .starts_with("Image build of function orders failed (build step failed): "));
+}
+
+#[test]
+fn log_excerpts_keep_the_tail() {
+    let output: String = (0..100).map(|i| format!("line {i}\n")).collect();
+    let excerpt = build_log_excerpt(&output);
+    assert_eq!(excerpt.lines().count(), BUILD_LOG_MAX_LINES);
+    assert!(excerpt.ends_with("line 99"));
+
+    let long = "é".repeat(BUILD_LOG_MAX_BYTES);
+    let excerpt = build_log_excerpt(&long);
+    assert!(excerpt.len() <= BUILD_LOG_MAX_BYTES);
+    assert!(excerpt.chars().all(|c| c == 'é'));
+}
@@ -43,6 +43,13 @@ fn test_image_tag_computation() {
         state: lambda_models::FunctionState::Active,
         state_reason: None,
         state_reason_code: None,
+        last_update_status: lambda_models::LastUpdateStatus::Successful,
+        last_update_status_reason: None,
+        last_update_status_reason_code: None,
+        metadata: None,
+        function_arn: None,
+        package_type: lambda_models::PackageType::Zip,
+        image_uri: None,
     };
 
     // Test image tag generation logic without actually building
@@ -153,3 +160,293 @@ fn test_zip_extraction_with_node_modules() {
     let axios_index = temp_dir.path().join("node_modules/axios/index.js");
     assert!(axios_index.exists());
 }
+
+fn zip_of(files: &[(&str, &str)]) -> ZipInfo {
+    let mut zip_data = Vec::new();
+    {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut zip_data));
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    futures::executor::block_on(ZipHandler::new(1024 * 1024).process_zip(&zip_data)).unwrap()
+}
+
+#[test]
+fn test_verify_handler_node() {
+    let zip = zip_of(&[("index.js", "exports.handler = async (event) => event;")]);
+    assert!(verify_handler("nodejs22.x", "index.handler", &zip).is_ok());
+    assert!(verify_handler("nodejs22.x", "index.other", &zip).is_err());
+    assert!(verify_handler("nodejs22.x", "app.handler", &zip).is_err());
+
+    let zip = zip_of(&[(
+        "src/app.js",
+        "const handler = async () => 1;\nmodule.exports = { handler };",
+    )]);
+    assert!(verify_handler("nodejs18.x", "src/app.handler", &zip).is_ok());
+
+    // The bootstrap loads the second segment and ignores the rest
+    let zip = zip_of(&[("index.js", "exports.handler = async (event) => event;")]);
+    assert!(verify_handler("nodejs24.x", "index.handler.v2", &zip).is_ok());
+    assert!(verify_handler("nodejs24.x", "index.main.handler", &zip).is_err());
+}
+
+#[test]
+fn test_verify_handler_python() {
+    let zip = zip_of(&[(
+        "app.py",
+        "import json\n\ndef lambda_handler(event, context):\n    return event\n",
+    )]);
+    assert!(verify_handler("python3.11", "app.lambda_handler", &zip).is_ok());
+
+    let err = verify_handler("python3.11", "app.handler", &zip).unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    assert!(err.to_string().contains("app.py does not define 'handler'"));
+}
+
+#[test]
+fn test_verify_handler_python_reexports() {
+    let zip = zip_of(&[
+        ("app.py", "from handlers.orders import handler\n"),
+        (
+            "aliased.py",
+            "from handlers.orders import process as handler, other\n",
+        ),
+        (
+            "grouped.py",
+            "from handlers.orders import (\n    other,\n    handler,  # entry point\n)\n",
+        ),
+        ("pkg/api.py", "from .orders import other, handler\n"),
+    ]);
+    assert!(verify_handler("python3.11", "app.handler", &zip).is_ok());
+    assert!(verify_handler("python3.11", "aliased.handler", &zip).is_ok());
+    assert!(verify_handler("python3.11", "aliased.process", &zip).is_err());
+    assert!(verify_handler("python3.11", "grouped.handler", &zip).is_ok());
+    assert!(verify_handler("python3.11", "pkg.api.handler", &zip).is_ok());
+    assert!(verify_handler("python3.11", "pkg.api.orders", &zip).is_err());
+}
+
+#[test]
+fn test_remove_unreferenced_zips() {
+    let dir = tempdir().unwrap();
+    let mut cache = PackagingCache::new(dir.path().to_path_buf()).unwrap();
+    for (sha256, data) in [("keep", b"kept".to_vec()), ("drop", b"dropped".to_vec())] {
+        cache
+            .store_zip_file(&ZipInfo {
+                sha256: sha256.to_string(),
+                files: Vec::new(),
+                total_size: data.len() as u64,
+                zip_data: data,
+            })
+            .unwrap();
+    }
+
+    let keep = std::collections::HashSet::from(["keep".to_string()]);
+    let (removed, freed_bytes) = cache.remove_unreferenced_zips(&keep).unwrap();
+    assert_eq!(removed, 1);
+    assert_eq!(freed_bytes, 7);
+    assert!(cache.load_zip_file("keep").is_ok());
+    assert!(cache.load_zip_file("drop").is_err());
+}
+
+#[test]
+fn test_base_image_pinning() {
+    let digest = format!("sha256:{}", "ab".repeat(32));
+    assert_eq!(base_image("nodejs22.x"), Some("node:22-alpine"));
+    assert_eq!(base_image("java21"), None);
+    assert_eq!(
+        pinned_base_image("node:22-alpine", Some(&digest)),
+        format!("node:22-alpine@{digest}")
+    );
+    assert_eq!(pinned_base_image("node:22-alpine", None), "node:22-alpine");
+
+    let repo_digests = vec![
+        format!("registry.local:5000/node@sha256:{}", "cd".repeat(32)),
+        format!("node@{digest}"),
+    ];
+    assert_eq!(repo_digest("node:22-alpine", &repo_digests), Some(digest));
+    assert_eq!(
+        repo_digest("registry.local:5000/node:22", &repo_digests),
+        Some(format!("sha256:{}", "cd".repeat(32)))
+    );
+    assert_eq!(repo_digest("python:3.11-alpine", &repo_digests), None);
+}
+
+#[test]
+fn test_lambdaignore_rules() {
+    let ignore = LambdaIgnore::parse(
+        "# local only\nnode_modules/\n*.test.js\n/fixtures\n**/__snapshots__/**\n!keep.test.js\n",
+    );
+    assert!(ignore.is_ignored("node_modules/axios/index.js", false));
+    assert!(ignore.is_ignored("lib/node_modules/x.js", false));
+    assert!(ignore.is_ignored("src/app.test.js", false));
+    assert!(!ignore.is_ignored("src/keep.test.js", false));
+    assert!(ignore.is_ignored("fixtures/big.json", false));
+    assert!(!ignore.is_ignored("src/fixtures/small.json", false));
+    assert!(ignore.is_ignored("src/__snapshots__/app.snap", false));
+    assert!(!ignore.is_ignored("index.js", false));
+    assert!(LambdaIgnore::parse("# nothing\n\n").is_empty());
+}
+
+#[test]
+fn test_build_context_respects_lambdaignore() {
+    let zip = zip_of(&[
+        (".lambdaignore", "tests/\n*.md\n"),
+        ("index.js", "exports.handler = async () => 1;"),
+        ("README.md", "# docs"),
+        ("tests/index.test.js", "test('x', () => {});"),
+    ]);
+    let ignore = zip.lambda_ignore().unwrap();
+    let index_size = "exports.handler = async () => 1;".len() as u64;
+    assert_eq!(zip.build_context_size(&ignore), index_size);
+    assert_eq!(check_build_context(&zip, index_size).unwrap(), index_size);
+    let err = check_build_context(&zip, index_size - 1).unwrap_err();
+    assert_eq!(err.http_status(), 400);
+    assert!(err.to_string().contains(".lambdaignore"));
+
+    let temp_dir = tempdir().unwrap();
+    futures::executor::block_on(ZipHandler::new(1024 * 1024).extract_build_context(
+        &zip.zip_data,
+        temp_dir.path(),
+        &ignore,
+    ))
+    .unwrap();
+    assert!(temp_dir.path().join("index.js").exists());
+    assert!(!temp_dir.path().join("README.md").exists());
+    assert!(!temp_dir.path().join("tests").exists());
+    assert!(!temp_dir.path().join(".lambdaignore").exists());
+
+    let zip = zip_of(&[("index.js", "exports.handler = async () => 1;")]);
+    assert!(zip.lambda_ignore().unwrap().is_empty());
+}
+
+#[test]
+fn test_pin_timestamps() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("lib/nested")).unwrap();
+    std::fs::write(dir.path().join("index.js"), "1").unwrap();
+    std::fs::write(dir.path().join("lib/nested/util.js"), "2").unwrap();
+
+    pin_timestamps(dir.path(), 315532800).unwrap();
+    let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(315532800);
+    for path in ["index.js", "lib", "lib/nested", "lib/nested/util.js"] {
+        let modified = std::fs::metadata(dir.path().join(path))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(modified, expected, "{path}");
+    }
+}
+
+fn zip_entries(zip_data: &[u8]) -> std::collections::BTreeMap<String, String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(zip_data)).unwrap();
+    (0..archive.len())
+        .map(|i| {
+            let mut file = archive.by_index(i).unwrap();
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut file, &mut content).unwrap();
+            (file.name().to_string(), content)
+        })
+        .collect()
+}
+
+#[test]
+fn test_bundle_npm_workspace_package() {
+    let zip = zip_of(&[
+        (
+            "package.json",
+            r#"{ "name": "monorepo", "private": true, "workspaces": ["packages/*", "services/*"] }"#,
+        ),
+        (
+            "services/api/package.json",
+            r#"{ "name": "api", "dependencies": { "@acme/db": "workspace:*", "express": "^4.0.0" },
+                 "devDependencies": { "@acme/testkit": "^1.0.0", "jest": "^29.0.0" } }"#,
+        ),
+        ("services/api/index.js", "exports.handler = async () => 1;"),
+        ("services/api/node_modules/express/index.js", "stale"),
+        (
+            "packages/db/package.json",
+            r#"{ "name": "@acme/db", "dependencies": { "@acme/log": "1.0.0", "pg": "^8.0.0" } }"#,
+        ),
+        ("packages/db/index.js", "module.exports = {};"),
+        ("packages/log/package.json", r#"{ "name": "@acme/log" }"#),
+        ("packages/log/index.js", "module.exports = console;"),
+        (
+            "packages/testkit/package.json",
+            r#"{ "name": "@acme/testkit" }"#,
+        ),
+        ("services/web/package.json", r#"{ "name": "web" }"#),
+    ]);
+
+    let bundle = bundle_workspace(&zip.zip_data, "./services/api/").unwrap();
+    let entries = zip_entries(&bundle);
+    assert_eq!(
+        entries.keys().collect::<Vec<_>>(),
+        [
+            ".workspace/@acme/db/index.js",
+            ".workspace/@acme/db/package.json",
+            ".workspace/@acme/log/index.js",
+            ".workspace/@acme/log/package.json",
+            "index.js",
+            "package.json",
+        ]
+    );
+
+    let manifest: serde_json::Value = serde_json::from_str(&entries["package.json"]).unwrap();
+    assert_eq!(
+        manifest["dependencies"],
+        serde_json::json!({ "@acme/db": "file:.workspace/@acme/db", "express": "^4.0.0" })
+    );
+    assert_eq!(
+        manifest["devDependencies"],
+        serde_json::json!({ "jest": "^29.0.0" })
+    );
+    let db: serde_json::Value =
+        serde_json::from_str(&entries[".workspace/@acme/db/package.json"]).unwrap();
+    assert_eq!(
+        db["dependencies"]["@acme/log"],
+        "file:../../../.workspace/@acme/log"
+    );
+
+    // Same input, same bundle
+    assert_eq!(
+        bundle,
+        bundle_workspace(&zip.zip_data, "services/api").unwrap()
+    );
+    let err = bundle_workspace(&zip.zip_data, "services/missing").unwrap_err();
+    assert!(err.to_string().contains("not a package of the workspace"));
+}
+
+#[test]
+fn test_bundle_pnpm_workspace_package() {
+    let zip = zip_of(&[
+        ("package.json", r#"{ "name": "monorepo", "private": true }"#),
+        (
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'apps/*'\n  - \"libs/**\" # shared code\n  - '!**/fixtures/**'\n",
+        ),
+        (
+            "apps/worker/package.json",
+            r#"{ "name": "worker", "dependencies": { "shared": "workspace:^" } }"#,
+        ),
+        ("apps/worker/handler.js", "exports.handler = async () => 1;"),
+        ("libs/shared/package.json", r#"{ "name": "shared" }"#),
+        (
+            "libs/shared/fixtures/package.json",
+            r#"{ "name": "fixture" }"#,
+        ),
+    ]);
+
+    let entries = zip_entries(&bundle_workspace(&zip.zip_data, "apps/worker").unwrap());
+    assert!(entries.contains_key("handler.js"));
+    assert!(entries.contains_key(".workspace/shared/package.json"));
+    let manifest: serde_json::Value = serde_json::from_str(&entries["package.json"]).unwrap();
+    assert_eq!(manifest["dependencies"]["shared"], "file:.workspace/shared");
+
+    let plain = zip_of(&[("index.js", "exports.handler = async () => 1;")]);
+    let err = bundle_workspace(&plain.zip_data, "apps/worker").unwrap_err();
+    assert_eq!(err.http_status(), 400);
+}
@@ -0,0 +1,85 @@
+use lambda_packaging::{entry_name, ZipHandler};
+use std::io::Write;
+use tempfile::tempdir;
+
+fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip_data = Vec::new();
+    {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut zip_data));
+        for (name, contents) in entries {
+            zip.start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    zip_data
+}
+
+#[test]
+fn entry_names_use_forward_slashes() {
+    assert_eq!(entry_name("index.js").unwrap(), "index.js");
+    assert_eq!(entry_name("./lib/util.js").unwrap(), "lib/util.js");
+    assert_eq!(
+        entry_name("lib\\handlers\\orders.js").unwrap(),
+        "lib/handlers/orders.js"
+    );
+    assert_eq!(entry_name("node_modules/").unwrap(), "node_modules");
+}
+
+#[test]
+fn entries_outside_the_archive_are_rejected() {
+    for name in [
+        "../evil.sh",
+        "lib/../../evil.sh",
+        "..\\evil.bat",
+        "/etc/passwd",
+        "\\evil",
+    ] {
+        let err = entry_name(name).unwrap_err();
+        assert!(
+            err.to_string().contains("outside the archive"),
+            "{name}: {err}"
+        );
+    }
+    #[cfg(windows)]
+    assert!(entry_name("C:/Windows/evil.dll").is_err());
+    #[cfg(unix)]
+    assert_eq!(entry_name("notes:v2.txt").unwrap(), "notes:v2.txt");
+}
+
+#[tokio::test]
+async fn archives_from_windows_extract_into_directories() {
+    let handler = ZipHandler::new(1024 * 1024);
+    let dir = tempdir().unwrap();
+    let zip_data = zip_of(&[
+        (
+            "index.js",
+            b"exports.handler = require('./lib/app').handler;",
+        ),
+        ("lib\\app.js", b"exports.handler = async () => 'ok';"),
+    ]);
+    handler
+        .extract_to_directory(&zip_data, dir.path())
+        .await
+        .unwrap();
+    let app = dir.path().join("lib").join("app.js");
+    assert_eq!(
+        std::fs::read_to_string(app).unwrap(),
+        "exports.handler = async () => 'ok';"
+    );
+}
+
+#[tokio::test]
+async fn zip_slip_entries_fail_the_extraction() {
+    let handler = ZipHandler::new(1024 * 1024);
+    let root = tempdir().unwrap();
+    let target = root.path().join("code");
+    let zip_data = zip_of(&[("index.js", b""), ("../escaped.txt", b"nope")]);
+    let err = handler
+        .extract_to_directory(&zip_data, &target)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("outside the archive"), "{err}");
+    assert!(!root.path().join("escaped.txt").exists());
+}
@@ -7,14 +7,23 @@ use axum::{
 use serde::Deserialize;
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use tracing::{error, instrument};
+use std::collections::BTreeMap;
+use tracing::{error, instrument, warn};
 
 use crate::state::RtState;
-use lambda_control::pending::InvocationResult;
+use base64::Engine;
+use http_body_util::BodyExt;
+use lambda_control::pending::{InvocationResult, StreamPart};
 use lambda_control::queues::FnKey;
-use lambda_models::{RuntimeError, RuntimeResponse};
+use lambda_models::{IdentityConfig, InitError, RuntimeError, RuntimeResponse, RuntimeTraceCall};
 use uuid::Uuid;
 
+/// Header a runtime sets to `streaming` on `/response` to stream it, as on AWS
+pub const RESPONSE_MODE_HEADER: &str = "Lambda-Runtime-Function-Response-Mode";
+
+/// Largest response a runtime may post whole
+const MAX_RESPONSE_BYTES: usize = 6 * 1024 * 1024;
+
 #[derive(Deserialize, Debug)]
 pub struct NextQuery {
     /// function name (required)
@@ -31,6 +40,19 @@ pub struct NextQuery {
     pub env_hash: Option<String>,
 }
 
+/// Header names and values as recorded in invocation traces
+fn trace_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
 fn json_response<T: serde::Serialize>(status: StatusCode, v: &T) -> Response {
     let mut res = Response::new(Body::from(serde_json::to_vec(v).unwrap_or_default()));
     *res.status_mut() = status;
@@ -41,6 +63,23 @@ fn json_response<T: serde::Serialize>(status: StatusCode, v: &T) -> Response {
     res
 }
 
+/// Client context and Cognito identity of the invocation, which runtimes expose on their
+/// context object
+fn insert_context_headers(
+    headers: &mut HeaderMap,
+    client_context: Option<&str>,
+    cognito_identity: Option<&str>,
+) {
+    for (name, value) in [
+        ("lambda-runtime-client-context", client_context),
+        ("lambda-runtime-cognito-identity", cognito_identity),
+    ] {
+        if let Some(hv) = value.and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(HeaderName::from_static(name), hv);
+        }
+    }
+}
+
 #[instrument(skip(state), fields(func = %q.function_name))]
 pub async fn runtime_next(
     State(state): State<RtState>,
@@ -72,18 +111,31 @@ pub async fn runtime_next(
                 q.env_hash.clone(),
             ),
         };
+        let instance_id = headers_in
+            .get("x-lambdah-instance-id")
+            .and_then(|v| v.to_str().ok());
         // Long-lived GET: block until a work item is available.
+        let polled_at = chrono::Utc::now();
         match control
-            .get_next_invocation(function_name, &rt, ver.as_deref(), eh.as_deref())
+            .get_next_invocation_for_instance(
+                instance_id,
+                function_name,
+                &rt,
+                ver.as_deref(),
+                eh.as_deref(),
+            )
             .await
         {
             Ok(inv) => {
                 // Mark instance active using container-provided instance ID
-                if let Some(inst_id) = headers_in
-                    .get("x-lambdah-instance-id")
-                    .and_then(|v| v.to_str().ok())
-                {
-                    let _ = control.mark_instance_active_by_id(inst_id).await;
+                if let Some(inst_id) = instance_id {
+                    if let Some((_, container_id)) =
+                        control.mark_instance_active_by_id(inst_id).await
+                    {
+                        control
+                            .pending()
+                            .assign(&inv.aws_request_id.to_string(), container_id);
+                    }
                 }
                 // Build AWS Lambda style headers and body as the event JSON
                 let mut res = Response::new(Body::from(
@@ -112,6 +164,25 @@ pub async fn runtime_next(
                         headers.insert(HeaderName::from_static("lambda-runtime-trace-id"), hv);
                     }
                 }
+                insert_context_headers(
+                    headers,
+                    inv.client_context.as_deref(),
+                    inv.cognito_identity.as_deref(),
+                );
+                let request_id = inv.aws_request_id.to_string();
+                if control.invocation_traces().is_tracing(&request_id) {
+                    let waited_ms = (chrono::Utc::now() - polled_at).num_milliseconds().max(0);
+                    control.invocation_traces().record(
+                        &request_id,
+                        instance_id,
+                        RuntimeTraceCall::Next {
+                            polled_at,
+                            waited_ms: waited_ms as u64,
+                        },
+                        trace_headers(res.headers()),
+                        &serde_json::to_vec(&inv.payload).unwrap_or_default(),
+                    );
+                }
                 return res;
             }
             Err(e) => {
@@ -149,12 +220,16 @@ pub async fn runtime_next(
                 );
                 headers.insert(
                     HeaderName::from_static("lambda-runtime-invoked-function-arn"),
-                    HeaderValue::from_str(&format!(
-                        "arn:aws:lambda:local:000000000000:function:{}",
-                        key.function_name
-                    ))
+                    HeaderValue::from_str(
+                        &IdentityConfig::default().function_arn(&key.function_name),
+                    )
                     .unwrap(),
                 );
+                insert_context_headers(
+                    headers,
+                    work_item.client_context.as_deref(),
+                    work_item.cognito_identity.as_deref(),
+                );
                 return res;
             }
             Err(e) => {
@@ -173,14 +248,129 @@ pub async fn runtime_response(
     Path(request_id): Path<String>,
     State(state): State<RtState>,
     headers: HeaderMap,
-    body: bytes::Bytes,
-) -> impl IntoResponse {
+    body: Body,
+) -> StatusCode {
+    // Runtimes streaming their response say so in a header, as AWS's do
+    let streaming = headers
+        .get(RESPONSE_MODE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|mode| mode.eq_ignore_ascii_case("streaming"));
+    if streaming {
+        return stream_response(&state, &request_id, &headers, body).await;
+    }
+    match axum::body::to_bytes(body, MAX_RESPONSE_BYTES).await {
+        Ok(body) => deliver_response(&state, &request_id, &headers, &body).await,
+        Err(_) => StatusCode::PAYLOAD_TOO_LARGE,
+    }
+}
+
+/// `POST /2018-06-01/runtime/invocation/{id}/response-stream`: the response in chunks, sent
+/// on to the caller as they arrive
+#[instrument(skip(state, body, headers), fields(req_id = %request_id))]
+pub async fn runtime_response_stream(
+    Path(request_id): Path<String>,
+    State(state): State<RtState>,
+    headers: HeaderMap,
+    body: Body,
+) -> StatusCode {
+    stream_response(&state, &request_id, &headers, body).await
+}
+
+/// Send the chunks of a streamed response to the caller, then complete the invocation. An
+/// error the runtime reports in the `Lambda-Runtime-Function-Error-Type` and
+/// `Lambda-Runtime-Function-Error-Body` trailers fails it instead
+async fn stream_response(
+    state: &RtState,
+    request_id: &str,
+    headers: &HeaderMap,
+    mut body: Body,
+) -> StatusCode {
+    let pending = state
+        .control
+        .as_ref()
+        .map_or_else(|| state.pending.clone(), |control| control.pending());
+    let Some(stream) = pending.stream(request_id) else {
+        // The caller waits for the whole response, as a plain invoke of a streaming function does
+        return match axum::body::to_bytes(body, MAX_RESPONSE_BYTES).await {
+            Ok(body) => deliver_response(state, request_id, headers, &body).await,
+            Err(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        };
+    };
+    let content_type = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    // Sends fail only once the caller went away; the rest of the body is read all the same
+    let _ = stream.send(StreamPart::Start { content_type }).await;
+    let mut trailers = None;
+    while let Some(frame) = body.frame().await {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(e) => {
+                // The runtime's /error post or the timeout completes the invocation
+                warn!(error=%e, "Runtime stopped streaming its response");
+                return StatusCode::BAD_REQUEST;
+            }
+        };
+        match frame.into_data() {
+            Ok(chunk) => {
+                let _ = stream.send(StreamPart::Chunk(chunk.to_vec())).await;
+            }
+            Err(frame) => trailers = frame.into_trailers().ok(),
+        }
+    }
+    drop(stream);
+
+    let error_type = trailers
+        .as_ref()
+        .and_then(|t| t.get("Lambda-Runtime-Function-Error-Type"))
+        .and_then(|v| v.to_str().ok());
+    let Some(error_type) = error_type else {
+        return deliver_response(state, request_id, headers, &[]).await;
+    };
+    let error_body = trailers
+        .as_ref()
+        .and_then(|t| t.get("Lambda-Runtime-Function-Error-Body"))
+        .and_then(|v| {
+            base64::engine::general_purpose::STANDARD
+                .decode(v.as_bytes())
+                .ok()
+        })
+        .unwrap_or_else(|| {
+            serde_json::to_vec(&json!({"errorType": error_type, "errorMessage": ""}))
+                .unwrap_or_default()
+        });
+    let mut headers = headers.clone();
+    headers.insert(
+        "X-Amz-Function-Error",
+        HeaderValue::from_static("Unhandled"),
+    );
+    deliver_error(state, request_id, &headers, &error_body).await
+}
+
+/// Complete the invocation with the response a runtime posted
+async fn deliver_response(
+    state: &RtState,
+    request_id: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> StatusCode {
     if let Some(control) = state.control.clone() {
+        // Traces are stored once the invocation completes, so record the call before that
+        control.invocation_traces().record(
+            request_id,
+            headers
+                .get("x-lambdah-instance-id")
+                .and_then(|v| v.to_str().ok()),
+            RuntimeTraceCall::Response,
+            trace_headers(headers),
+            body,
+        );
         // Route through control plane so pending waiter is shared
         let payload_json =
-            serde_json::from_slice::<serde_json::Value>(&body).unwrap_or(serde_json::Value::Null);
+            serde_json::from_slice::<serde_json::Value>(body).unwrap_or(serde_json::Value::Null);
         let rr = RuntimeResponse {
-            aws_request_id: Uuid::try_parse(&request_id).unwrap_or_else(|_| Uuid::nil()),
+            aws_request_id: Uuid::try_parse(request_id).unwrap_or_else(|_| Uuid::nil()),
             payload: payload_json,
         };
         let hdrs = {
@@ -230,7 +420,7 @@ pub async fn runtime_response(
         {
             res.log_tail_b64 = Some(v.to_string());
         }
-        if state.pending.complete(&request_id, res) {
+        if state.pending.complete(request_id, res) {
             StatusCode::ACCEPTED
         } else {
             StatusCode::NOT_FOUND
@@ -244,20 +434,60 @@ pub async fn runtime_error(
     State(state): State<RtState>,
     headers: HeaderMap,
     body: bytes::Bytes,
-) -> impl IntoResponse {
+) -> StatusCode {
+    deliver_error(&state, &request_id, &headers, &body).await
+}
+
+/// Fail the invocation with the error a runtime reported
+async fn deliver_error(
+    state: &RtState,
+    request_id: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> StatusCode {
     if let Some(control) = state.control.clone() {
+        control.invocation_traces().record(
+            request_id,
+            headers
+                .get("x-lambdah-instance-id")
+                .and_then(|v| v.to_str().ok()),
+            RuntimeTraceCall::Error,
+            trace_headers(headers),
+            body,
+        );
         let err_kind = headers
             .get("X-Amz-Function-Error")
             .and_then(|h| h.to_str().ok())
             .unwrap_or("Unhandled")
             .to_string();
         let payload_json =
-            serde_json::from_slice::<serde_json::Value>(&body).unwrap_or(serde_json::Value::Null);
+            serde_json::from_slice::<serde_json::Value>(body).unwrap_or(serde_json::Value::Null);
+        // Runtimes post {errorMessage, errorType, stackTrace}; keep the raw body if it isn't shaped that way
+        let error_message = payload_json
+            .get("errorMessage")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| payload_json.to_string());
+        let error_type = payload_json
+            .get("errorType")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| err_kind.clone());
+        let stack_trace = payload_json.get("stackTrace").and_then(|v| match v {
+            serde_json::Value::Array(lines) => Some(
+                lines
+                    .iter()
+                    .map(|l| l.as_str().map_or_else(|| l.to_string(), str::to_string))
+                    .collect(),
+            ),
+            serde_json::Value::String(trace) => Some(trace.lines().map(str::to_string).collect()),
+            _ => None,
+        });
         let re = RuntimeError {
-            aws_request_id: Uuid::try_parse(&request_id).unwrap_or_else(|_| Uuid::nil()),
-            error_message: payload_json.to_string(),
-            error_type: err_kind.clone(),
-            stack_trace: None,
+            aws_request_id: Uuid::try_parse(request_id).unwrap_or_else(|_| Uuid::nil()),
+            error_message,
+            error_type,
+            stack_trace,
         };
         let hdrs = {
             let mut map = std::collections::HashMap::new();
@@ -294,7 +524,7 @@ pub async fn runtime_error(
         {
             res.log_tail_b64 = Some(v.to_string());
         }
-        if state.pending.complete(&request_id, res) {
+        if state.pending.complete(request_id, res) {
             StatusCode::ACCEPTED
         } else {
             StatusCode::NOT_FOUND
@@ -302,6 +532,70 @@ pub async fn runtime_error(
     }
 }
 
+/// Sent by a runtime whose handler failed to load, before it exits
+#[instrument(skip(state, body, headers))]
+pub async fn runtime_init_error(
+    State(state): State<RtState>,
+    headers: HeaderMap,
+    body: bytes::Bytes,
+) -> StatusCode {
+    let Some(control) = state.control.as_ref() else {
+        return StatusCode::ACCEPTED;
+    };
+    let payload_json =
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap_or(serde_json::Value::Null);
+    let field = |name: &str| {
+        payload_json
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let error = InitError {
+        error_type: field("errorType")
+            .or_else(|| {
+                headers
+                    .get("Lambda-Runtime-Function-Error-Type")
+                    .and_then(|h| h.to_str().ok())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| "Runtime.Unknown".to_string()),
+        error_message: field("errorMessage").unwrap_or_else(|| payload_json.to_string()),
+        stack_trace: payload_json
+            .get("stackTrace")
+            .and_then(|v| v.as_array())
+            .map(|lines| {
+                lines
+                    .iter()
+                    .filter_map(|l| l.as_str().map(str::to_string))
+                    .collect()
+            }),
+    };
+    let instance_id = headers
+        .get("x-lambdah-instance-id")
+        .and_then(|v| v.to_str().ok());
+    match control.post_init_error(instance_id, error).await {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(e) => {
+            error!(error=?e, "Error recording init error");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
 pub async fn runtime_healthz() -> &'static str {
     "ok"
 }
+
+/// Sent by a runtime that received SIGTERM once its current invocation is done, so the
+/// instance is no longer counted as idle capacity while it shuts down
+pub async fn runtime_unregister(State(state): State<RtState>, headers: HeaderMap) -> StatusCode {
+    if let (Some(control), Some(instance_id)) = (
+        state.control.as_ref(),
+        headers
+            .get("x-lambdah-instance-id")
+            .and_then(|v| v.to_str().ok()),
+    ) {
+        control.unregister_instance(instance_id).await;
+    }
+    StatusCode::ACCEPTED
+}
@@ -0,0 +1,140 @@
+//! Invoke API for functions calling other functions from their containers. The caller is
+//! identified by the `X-LambdaH-Instance-Id` header runtimes already send, together with
+//! `X-LambdaH-Instance-Token` from the container's `LAMBDAH_INSTANCE_TOKEN`, and the
+//! target's policy must grant it; see `POST /api/functions/{name}/policy`.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::Response,
+};
+use lambda_models::{FunctionError, InvocationType, InvokeRequest, LambdaError};
+use tracing::{info, instrument};
+
+use crate::state::RtState;
+
+fn json_response(status: StatusCode, headers: HeaderMap, body: &serde_json::Value) -> Response {
+    let mut res = Response::new(Body::from(serde_json::to_vec(body).unwrap_or_default()));
+    *res.status_mut() = status;
+    *res.headers_mut() = headers;
+    res.headers_mut().insert(
+        axum::http::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+fn error_response(e: &LambdaError) -> Response {
+    let body = serde_json::to_value(e.to_error_shape()).unwrap_or_default();
+    json_response(
+        StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        HeaderMap::new(),
+        &body,
+    )
+}
+
+/// `POST /2015-03-31/functions/{name}/invocations` from a function container, answered like
+/// the user API's invoke: `RequestResponse` waits for the result, `Event` returns 202 at once
+#[instrument(skip(state, headers, body))]
+pub async fn function_invoke(
+    State(state): State<RtState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(control) = state.control.clone() else {
+        return error_response(&LambdaError::InternalError {
+            reason: "Function invokes are not available".to_string(),
+        });
+    };
+    let instance_id = headers
+        .get("x-lambdah-instance-id")
+        .and_then(|v| v.to_str().ok());
+    let instance_token = headers
+        .get("x-lambdah-instance-token")
+        .and_then(|v| v.to_str().ok());
+    let caller = match control
+        .authorize_function_invoke(instance_id, instance_token, &name)
+        .await
+    {
+        Ok(caller) => caller,
+        Err(e) => return error_response(&e),
+    };
+    info!("Function {} invokes {}", caller, name);
+
+    let invocation_type = headers
+        .get("X-Amz-Invocation-Type")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(InvocationType::RequestResponse);
+    let payload = if body.is_empty() {
+        None
+    } else {
+        Some(serde_json::from_slice(&body).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(&body).to_string())
+        }))
+    };
+    let request = InvokeRequest {
+        function_name: name,
+        invocation_type: invocation_type.clone(),
+        log_type: None,
+        client_context: headers
+            .get("X-Amz-Client-Context")
+            .and_then(|h| h.to_str().ok())
+            .map(str::to_string),
+        payload,
+        qualifier: None,
+        idempotent: false,
+        trace: false,
+    };
+
+    if invocation_type == InvocationType::Event {
+        return match control.enqueue_event(request).await {
+            Ok(job) => {
+                let mut response_headers = HeaderMap::new();
+                if let Ok(value) = HeaderValue::from_str(&job.job_id) {
+                    response_headers.insert("X-Amz-Request-Id", value);
+                }
+                json_response(
+                    StatusCode::ACCEPTED,
+                    response_headers,
+                    &serde_json::Value::Null,
+                )
+            }
+            Err(e) => error_response(&e),
+        };
+    }
+
+    match control.invoke_function(request).await {
+        Ok(response) => {
+            let mut response_headers = HeaderMap::new();
+            for (key, value) in &response.headers {
+                if let (Ok(k), Ok(v)) = (
+                    HeaderName::from_bytes(key.as_bytes()),
+                    HeaderValue::from_str(value),
+                ) {
+                    response_headers.insert(k, v);
+                }
+            }
+            if let Some(function_error) = &response.function_error {
+                let error = match function_error {
+                    FunctionError::Handled => "Handled",
+                    FunctionError::Unhandled => "Unhandled",
+                };
+                response_headers.insert("X-Amz-Function-Error", HeaderValue::from_static(error));
+            }
+            if let Some(version) = &response.executed_version {
+                if let Ok(value) = HeaderValue::from_str(version) {
+                    response_headers.insert("X-Amz-Executed-Version", value);
+                }
+            }
+            json_response(
+                StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::OK),
+                response_headers,
+                &response.payload.unwrap_or(serde_json::Value::Null),
+            )
+        }
+        Err(e) => error_response(&e),
+    }
+}
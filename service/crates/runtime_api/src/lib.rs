@@ -1,5 +1,7 @@
 pub mod handlers;
+pub mod invoke;
 pub mod routes;
+pub mod sqs;
 pub mod state;
 pub mod websocket;
 
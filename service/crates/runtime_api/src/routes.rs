@@ -1,4 +1,6 @@
 use crate::handlers::*;
+use crate::invoke::function_invoke;
+use crate::sqs::sqs_dispatch;
 use crate::websocket::websocket_handler;
 use axum::{
     routing::{get, post},
@@ -8,15 +10,28 @@ use axum::{
 pub fn build_router(state: crate::state::RtState) -> Router {
     Router::new()
         .route("/runtime/healthz", get(runtime_healthz))
+        .route("/runtime/unregister", post(runtime_unregister))
         .route("/2018-06-01/runtime/invocation/next", get(runtime_next))
         .route(
             "/2018-06-01/runtime/invocation/:request_id/response",
             post(runtime_response),
         )
+        .route(
+            "/2018-06-01/runtime/invocation/:request_id/response-stream",
+            post(runtime_response_stream),
+        )
         .route(
             "/2018-06-01/runtime/invocation/:request_id/error",
             post(runtime_error),
         )
+        .route("/2018-06-01/runtime/init/error", post(runtime_init_error))
         .route("/2018-06-01/runtime/websocket", get(websocket_handler))
+        // Functions invoking other functions, as their policies allow
+        .route(
+            "/2015-03-31/functions/:name/invocations",
+            post(function_invoke),
+        )
+        // SQS JSON protocol for the embedded queues
+        .route("/", post(sqs_dispatch))
         .with_state(state)
 }
@@ -0,0 +1,330 @@
+//! SQS JSON protocol (`X-Amz-Target: AmazonSQS.<Action>`) over the embedded queues, so
+//! functions can use the AWS SDK with its endpoint set to `LAMBDAH_SQS_ENDPOINT`.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+use lambda_control::sqs::QueueService;
+use lambda_models::{CreateQueueOptions, IdentityConfig, LambdaError, RedrivePolicy};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::instrument;
+
+use crate::state::RtState;
+
+const TARGET_PREFIX: &str = "AmazonSQS.";
+const CONTENT_TYPE: &str = "application/x-amz-json-1.0";
+
+fn sqs_response(status: StatusCode, body: &Value) -> Response {
+    let mut res = Response::new(Body::from(serde_json::to_vec(body).unwrap_or_default()));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        axum::http::header::CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE),
+    );
+    res
+}
+
+/// An SQS protocol error, rendered as `{"__type": "com.amazonaws.sqs#<code>", "message"}`
+struct SqsError {
+    code: &'static str,
+    message: String,
+}
+
+impl SqsError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn into_response(self) -> Response {
+        let status = if self.code == "InternalError" {
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        sqs_response(
+            status,
+            &json!({ "__type": format!("com.amazonaws.sqs#{}", self.code), "message": self.message }),
+        )
+    }
+}
+
+impl From<LambdaError> for SqsError {
+    fn from(e: LambdaError) -> Self {
+        match e {
+            LambdaError::QueueNotFound { .. } => {
+                Self::new("QueueDoesNotExist", "The specified queue does not exist.")
+            }
+            LambdaError::InvalidRequest { reason } => Self::new("InvalidParameterValue", reason),
+            other => Self::new("InternalError", other.to_string()),
+        }
+    }
+}
+
+/// Queue URLs point back at whichever host the caller used to reach us
+fn queue_url(identity: &IdentityConfig, headers: &HeaderMap, queue_name: &str) -> String {
+    let host = headers
+        .get(axum::http::header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost");
+    format!("http://{host}/{}/{queue_name}", identity.account_id)
+}
+
+/// The queue name is the last path segment of a queue URL
+fn queue_name(queue_url: &str) -> &str {
+    queue_url
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// SQS encodes numeric attributes as strings
+fn numeric_attribute(
+    attributes: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<u32>, SqsError> {
+    attributes
+        .get(name)
+        .map(|v| {
+            v.parse().map_err(|_| {
+                SqsError::new("InvalidAttributeValue", format!("{name} must be a number"))
+            })
+        })
+        .transpose()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CreateQueueInput {
+    queue_name: String,
+    #[serde(default)]
+    attributes: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct QueueNameInput {
+    queue_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListQueuesInput {
+    queue_name_prefix: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct QueueUrlInput {
+    queue_url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendMessageInput {
+    queue_url: String,
+    message_body: String,
+    delay_seconds: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReceiveMessageInput {
+    queue_url: String,
+    max_number_of_messages: Option<u32>,
+    visibility_timeout: Option<u32>,
+    wait_time_seconds: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DeleteMessageInput {
+    queue_url: String,
+    receipt_handle: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ChangeMessageVisibilityInput {
+    queue_url: String,
+    receipt_handle: String,
+    visibility_timeout: u32,
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: &Bytes) -> Result<T, SqsError> {
+    // SDKs send `{}` for actions without input; treat an empty body the same way
+    let body: &[u8] = if body.is_empty() { b"{}" } else { body };
+    serde_json::from_slice(body).map_err(|e| SqsError::new("InvalidParameterValue", e.to_string()))
+}
+
+#[instrument(skip(state, headers, body))]
+pub async fn sqs_dispatch(
+    State(state): State<RtState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(control) = state.control.clone() else {
+        return sqs_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &json!({ "__type": "com.amazonaws.sqs#ServiceUnavailable", "message": "Queues are not available" }),
+        );
+    };
+    let Some(action) = headers
+        .get("x-amz-target")
+        .and_then(|t| t.to_str().ok())
+        .and_then(|t| t.strip_prefix(TARGET_PREFIX))
+    else {
+        return SqsError::new(
+            "MissingAction",
+            "X-Amz-Target must name an AmazonSQS action",
+        )
+        .into_response();
+    };
+    let sqs = control.sqs();
+    match handle(&sqs, control.identity(), action, &headers, &body).await {
+        Ok(value) => sqs_response(StatusCode::OK, &value),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn handle(
+    sqs: &QueueService,
+    identity: &IdentityConfig,
+    action: &str,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<Value, SqsError> {
+    match action {
+        "CreateQueue" => {
+            let input: CreateQueueInput = parse(body)?;
+            let redrive_policy = input
+                .attributes
+                .get("RedrivePolicy")
+                .map(|p| serde_json::from_str::<RedrivePolicy>(p))
+                .transpose()
+                .map_err(|e| {
+                    SqsError::new("InvalidAttributeValue", format!("RedrivePolicy: {e}"))
+                })?;
+            let options = CreateQueueOptions {
+                visibility_timeout_secs: numeric_attribute(&input.attributes, "VisibilityTimeout")?,
+                delay_seconds: numeric_attribute(&input.attributes, "DelaySeconds")?,
+                redrive_policy,
+            };
+            let queue = sqs.create_queue(&input.queue_name, options).await?;
+            Ok(json!({ "QueueUrl": queue_url(identity, headers, &queue.name) }))
+        }
+        "GetQueueUrl" => {
+            let input: QueueNameInput = parse(body)?;
+            let queue = sqs.get_queue(&input.queue_name).await?;
+            Ok(json!({ "QueueUrl": queue_url(identity, headers, &queue.name) }))
+        }
+        "ListQueues" => {
+            let input: ListQueuesInput = parse(body)?;
+            let queues = sqs.list_queues(input.queue_name_prefix.as_deref()).await?;
+            let urls: Vec<String> = queues
+                .iter()
+                .map(|q| queue_url(identity, headers, &q.name))
+                .collect();
+            Ok(json!({ "QueueUrls": urls }))
+        }
+        "DeleteQueue" => {
+            let input: QueueUrlInput = parse(body)?;
+            sqs.delete_queue(queue_name(&input.queue_url)).await?;
+            Ok(json!({}))
+        }
+        "PurgeQueue" => {
+            let input: QueueUrlInput = parse(body)?;
+            sqs.purge_queue(queue_name(&input.queue_url)).await?;
+            Ok(json!({}))
+        }
+        "GetQueueAttributes" => {
+            let input: QueueUrlInput = parse(body)?;
+            let name = queue_name(&input.queue_url);
+            let queue = sqs.get_queue(name).await?;
+            let counts = sqs.queue_counts(name).await?;
+            let mut attributes = json!({
+                "QueueArn": identity.queue_arn(&queue.name),
+                "VisibilityTimeout": queue.visibility_timeout_secs.to_string(),
+                "DelaySeconds": queue.delay_seconds.to_string(),
+                "CreatedTimestamp": queue.created_at.timestamp().to_string(),
+                "ApproximateNumberOfMessages": counts.visible.to_string(),
+                "ApproximateNumberOfMessagesNotVisible": counts.in_flight.to_string(),
+                "ApproximateNumberOfMessagesDelayed": counts.delayed.to_string(),
+            });
+            if let Some(policy) = &queue.redrive_policy {
+                attributes["RedrivePolicy"] =
+                    Value::String(serde_json::to_string(policy).unwrap_or_default());
+            }
+            Ok(json!({ "Attributes": attributes }))
+        }
+        "SendMessage" => {
+            let input: SendMessageInput = parse(body)?;
+            let (message_id, md5) = sqs
+                .send_message(
+                    queue_name(&input.queue_url),
+                    &input.message_body,
+                    input.delay_seconds,
+                )
+                .await?;
+            Ok(json!({ "MessageId": message_id, "MD5OfMessageBody": md5 }))
+        }
+        "ReceiveMessage" => {
+            let input: ReceiveMessageInput = parse(body)?;
+            let messages = sqs
+                .receive_messages(
+                    queue_name(&input.queue_url),
+                    input.max_number_of_messages.unwrap_or(1),
+                    input.visibility_timeout,
+                    Duration::from_secs(input.wait_time_seconds.unwrap_or(0) as u64),
+                )
+                .await?;
+            let messages: Vec<Value> = messages
+                .iter()
+                .map(|m| {
+                    json!({
+                        "MessageId": m.message_id,
+                        "ReceiptHandle": m.receipt_handle,
+                        "Body": m.body,
+                        "MD5OfBody": m.md5_of_body,
+                        "Attributes": {
+                            "ApproximateReceiveCount": m.receive_count.to_string(),
+                            "SentTimestamp": m.sent_at.timestamp_millis().to_string(),
+                            "ApproximateFirstReceiveTimestamp":
+                                m.first_received_at.timestamp_millis().to_string(),
+                        },
+                    })
+                })
+                .collect();
+            Ok(json!({ "Messages": messages }))
+        }
+        "DeleteMessage" => {
+            let input: DeleteMessageInput = parse(body)?;
+            sqs.delete_message(queue_name(&input.queue_url), &input.receipt_handle)
+                .await?;
+            Ok(json!({}))
+        }
+        "ChangeMessageVisibility" => {
+            let input: ChangeMessageVisibilityInput = parse(body)?;
+            sqs.change_message_visibility(
+                queue_name(&input.queue_url),
+                &input.receipt_handle,
+                input.visibility_timeout,
+            )
+            .await?;
+            Ok(json!({}))
+        }
+        other => Err(SqsError::new(
+            "InvalidAction",
+            format!("The action {other} is not supported by the local queue service"),
+        )),
+    }
+}
@@ -3,12 +3,14 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         Query, State,
     },
+    http::HeaderMap,
     response::Response,
 };
 use futures_util::{SinkExt, StreamExt};
-use lambda_models::{RuntimeError, RuntimeResponse};
+use lambda_models::{IdentityConfig, RuntimeError, RuntimeResponse};
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
 use tracing::{error, info, instrument, warn};
 use uuid::Uuid;
 use sha2::Digest;
@@ -29,6 +31,9 @@ pub struct WebSocketQuery {
     /// environment hash (optional)
     #[serde(rename = "eh")]
     pub env_hash: Option<String>,
+    /// container instance ID, for runtimes that cannot set `X-LambdaH-Instance-Id` (optional)
+    #[serde(rename = "iid")]
+    pub instance_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -64,6 +69,12 @@ pub enum WebSocketMessage {
         stack_trace: Option<Vec<String>>,
         headers: Option<std::collections::HashMap<String, String>>,
     },
+    /// The runtime is shutting down and takes no more invocations
+    #[serde(rename = "unregister")]
+    Unregister { instance_id: Option<String> },
+    /// Sent after the last invocation for an unregistered runtime
+    #[serde(rename = "unregistered")]
+    Unregistered,
     #[serde(rename = "ping")]
     Ping,
     #[serde(rename = "pong")]
@@ -77,9 +88,16 @@ pub enum WebSocketMessage {
 
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
-    Query(query): Query<WebSocketQuery>,
+    Query(mut query): Query<WebSocketQuery>,
     State(state): State<RtState>,
+    headers: HeaderMap,
 ) -> Response {
+    if let Some(instance_id) = headers
+        .get("x-lambdah-instance-id")
+        .and_then(|v| v.to_str().ok())
+    {
+        query.instance_id = Some(instance_id.to_string());
+    }
     info!(
         "WebSocket connection request for function: {}",
         query.function_name
@@ -96,11 +114,13 @@ async fn websocket_connection(
 ) {
     let (mut sender, mut receiver) = socket.split();
     let (tx, mut rx) = mpsc::unbounded_channel::<WebSocketMessage>();
+    let unregistered = Arc::new(Notify::new());
 
     // Spawn task to handle incoming WebSocket messages
     let state_clone = state.clone();
     let query_clone = query.clone();
     let tx_clone = tx.clone();
+    let unregistered_clone = unregistered.clone();
     tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
             match msg {
@@ -110,6 +130,7 @@ async fn websocket_connection(
                         &query_clone,
                         &state_clone,
                         &tx_clone,
+                        &unregistered_clone,
                     ).await {
                         error!("Error handling WebSocket message: {}", e);
                         let _ = tx_clone.send(WebSocketMessage::ErrorResponse {
@@ -158,7 +179,7 @@ async fn websocket_connection(
     });
 
     // Start the invocation loop
-    if let Err(e) = start_invocation_loop(query, state, tx).await {
+    if let Err(e) = start_invocation_loop(query, state, tx, unregistered).await {
         error!("Invocation loop error: {}", e);
     }
 }
@@ -168,6 +189,7 @@ async fn handle_websocket_message(
     _query: &WebSocketQuery,
     state: &RtState,
     tx: &mpsc::UnboundedSender<WebSocketMessage>,
+    unregistered: &Notify,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let msg: WebSocketMessage = serde_json::from_str(text)?;
 
@@ -199,6 +221,15 @@ async fn handle_websocket_message(
                 state,
             ).await?;
         }
+        WebSocketMessage::Unregister { instance_id } => {
+            info!("Container unregistered via WebSocket");
+            if let (Some(control), Some(instance_id)) = (&state.control, instance_id) {
+                control.unregister_instance(&instance_id).await;
+            }
+            // Stops the invocation loop, so nothing more is dequeued for this connection;
+            // the loop acknowledges with `unregistered` after any invocation already sent
+            unregistered.notify_one();
+        }
         WebSocketMessage::Ping => {
             let _ = tx.send(WebSocketMessage::Pong);
         }
@@ -306,11 +337,12 @@ async fn handle_error(
     Ok(())
 }
 
-#[instrument(skip(query, state, tx))]
+#[instrument(skip(query, state, tx, unregistered))]
 async fn start_invocation_loop(
     query: WebSocketQuery,
     state: RtState,
     tx: mpsc::UnboundedSender<WebSocketMessage>,
+    unregistered: Arc<Notify>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let function_name = &query.function_name;
 
@@ -338,11 +370,22 @@ async fn start_invocation_loop(
         };
 
         loop {
-            // Get next invocation
-            match control
-                .get_next_invocation(function_name, &rt, ver.as_deref(), eh.as_deref())
-                .await
-            {
+            // Get next invocation, unless the runtime unregisters first
+            let next = tokio::select! {
+                biased;
+                _ = unregistered.notified() => {
+                    let _ = tx.send(WebSocketMessage::Unregistered);
+                    break;
+                }
+                next = control.get_next_invocation_for_instance(
+                    query.instance_id.as_deref(),
+                    function_name,
+                    &rt,
+                    ver.as_deref(),
+                    eh.as_deref(),
+                ) => next,
+            };
+            match next {
                 Ok(inv) => {
                     let msg = WebSocketMessage::Invocation {
                         request_id: inv.aws_request_id.to_string(),
@@ -377,17 +420,23 @@ async fn start_invocation_loop(
         };
 
         loop {
-            match state.queues.pop_or_wait(&key).await {
+            let next = tokio::select! {
+                biased;
+                _ = unregistered.notified() => {
+                    let _ = tx.send(WebSocketMessage::Unregistered);
+                    break;
+                }
+                next = state.queues.pop_or_wait(&key) => next,
+            };
+            match next {
                 Ok(work_item) => {
                     let msg = WebSocketMessage::Invocation {
                         request_id: work_item.request_id,
                         payload: serde_json::from_slice(&work_item.payload)
                             .unwrap_or(serde_json::Value::Null),
                         deadline_ms: work_item.deadline_ms as u64,
-                        invoked_function_arn: format!(
-                            "arn:aws:lambda:local:000000000000:function:{}",
-                            key.function_name
-                        ),
+                        invoked_function_arn: IdentityConfig::default()
+                            .function_arn(&key.function_name),
                         trace_id: None,
                     };
 
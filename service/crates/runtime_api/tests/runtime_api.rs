@@ -1,11 +1,14 @@
+use std::convert::Infallible;
 use std::time::Duration;
 
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{HeaderMap, HeaderValue, Request, StatusCode},
     Router,
 };
+use base64::Engine;
 use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
 use lambda_runtime_api::build_router;
 use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
@@ -14,7 +17,7 @@ use tokio::time::timeout;
 use tower::util::ServiceExt;
 
 // Bring control-plane types
-use lambda_control::pending::Pending;
+use lambda_control::pending::{Pending, StreamPart};
 use lambda_control::queues::{FnKey, Queues};
 use lambda_control::work_item::{FunctionMeta, WorkItem};
 use lambda_runtime_api::state::RtState;
@@ -260,3 +263,122 @@ async fn runtime_response_404_if_no_waiter() {
     let res = app.oneshot(req).await.unwrap();
     assert_eq!(res.status(), StatusCode::NOT_FOUND);
 }
+
+#[tokio::test]
+async fn unregister_returns_202() {
+    let app = test_router();
+    let res = app
+        .oneshot(
+            Request::post("/runtime/unregister")
+                .header("X-LambdaH-Instance-Id", "inst-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn streamed_response_chunks_reach_a_streaming_caller() {
+    let pending = Pending::new();
+    let app = build_router(RtState {
+        control: None,
+        queues: Queues::new(),
+        pending: pending.clone(),
+    });
+    let rx = pending.register("req-6".to_string());
+    let mut parts = pending.register_stream("req-6".to_string());
+
+    let chunks = futures::stream::iter([
+        Ok::<_, std::io::Error>(Bytes::from_static(b"hello ")),
+        Ok(Bytes::from_static(b"world")),
+    ]);
+    let req = Request::post("/2018-06-01/runtime/invocation/req-6/response")
+        .header("Lambda-Runtime-Function-Response-Mode", "streaming")
+        .header("Content-Type", "text/plain")
+        .body(Body::from_stream(chunks))
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+
+    let mut received = Vec::new();
+    while let Some(part) = parts.recv().await {
+        received.push(part);
+    }
+    assert_eq!(
+        received[0],
+        StreamPart::Start {
+            content_type: Some("text/plain".to_string())
+        }
+    );
+    let body: Vec<u8> = received[1..]
+        .iter()
+        .flat_map(|part| match part {
+            StreamPart::Chunk(chunk) => chunk.clone(),
+            StreamPart::Start { .. } => panic!("started twice"),
+        })
+        .collect();
+    assert_eq!(body, b"hello world");
+    let delivered = timeout(Duration::from_secs(1), rx).await.unwrap().unwrap();
+    assert!(delivered.ok);
+}
+
+#[tokio::test]
+async fn error_trailers_fail_a_streamed_response() {
+    let pending = Pending::new();
+    let app = build_router(RtState {
+        control: None,
+        queues: Queues::new(),
+        pending: pending.clone(),
+    });
+    let rx = pending.register("req-7".to_string());
+    let _parts = pending.register_stream("req-7".to_string());
+
+    let error = br#"{"errorMessage":"boom","errorType":"Error"}"#;
+    let mut trailers = HeaderMap::new();
+    trailers.insert(
+        "Lambda-Runtime-Function-Error-Type",
+        HeaderValue::from_static("Error"),
+    );
+    trailers.insert(
+        "Lambda-Runtime-Function-Error-Body",
+        HeaderValue::from_str(&base64::engine::general_purpose::STANDARD.encode(error)).unwrap(),
+    );
+    let body = Full::new(Bytes::from_static(b"partial"))
+        .with_trailers(async move { Some(Ok::<_, Infallible>(trailers)) });
+    let req = Request::post("/2018-06-01/runtime/invocation/req-7/response-stream")
+        .body(Body::new(body))
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+
+    let delivered = timeout(Duration::from_secs(1), rx).await.unwrap().unwrap();
+    assert!(!delivered.ok);
+    assert_eq!(delivered.function_error.as_deref(), Some("Unhandled"));
+    assert_eq!(delivered.payload, error.to_vec());
+}
+
+#[tokio::test]
+async fn streamed_response_is_buffered_for_a_plain_invoke() {
+    let pending = Pending::new();
+    let app = build_router(RtState {
+        control: None,
+        queues: Queues::new(),
+        pending: pending.clone(),
+    });
+    let rx = pending.register("req-8".to_string());
+
+    let req = Request::post("/2018-06-01/runtime/invocation/req-8/response-stream")
+        .body(Body::from_stream(futures::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(br#"{"a":"#)),
+            Ok(Bytes::from_static(b"1}")),
+        ])))
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+
+    let delivered = timeout(Duration::from_secs(1), rx).await.unwrap().unwrap();
+    assert!(delivered.ok);
+    assert_eq!(delivered.payload, br#"{"a":1}"#.to_vec());
+}
@@ -115,7 +115,7 @@ async fn test_websocket_query_parameters() {
 
     for query in test_cases {
         let request = Request::builder()
-            .uri(&format!("/2018-06-01/runtime/websocket?{}", query))
+            .uri(format!("/2018-06-01/runtime/websocket?{query}"))
             .header("upgrade", "websocket")
             .header("connection", "upgrade")
             .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
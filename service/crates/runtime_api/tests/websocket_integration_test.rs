@@ -134,7 +134,7 @@ async fn test_websocket_message_roundtrip() {
 
     // Serialize to JSON
     let json = serde_json::to_string(&original).unwrap();
-    println!("Serialized message: {}", json);
+    println!("Serialized message: {json}");
 
     // Deserialize back
     let deserialized: WebSocketMessage = serde_json::from_str(&json).unwrap();
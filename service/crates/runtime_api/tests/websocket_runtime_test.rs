@@ -13,12 +13,12 @@ async fn test_nodejs_websocket_bootstrap_syntax() {
         Ok(result) => {
             if !result.status.success() {
                 let stderr = String::from_utf8_lossy(&result.stderr);
-                panic!("Node.js WebSocket bootstrap syntax error: {}", stderr);
+                panic!("Node.js WebSocket bootstrap syntax error: {stderr}");
             }
         }
         Err(e) => {
             // Node.js might not be available in the test environment
-            println!("Node.js not available for syntax check: {}", e);
+            println!("Node.js not available for syntax check: {e}");
         }
     }
 }
@@ -52,15 +52,15 @@ async fn test_nodejs_bootstrap_websocket_detection() {
     match output {
         Ok(result) => {
             let stdout = String::from_utf8_lossy(&result.stdout);
-            println!("Node.js WebSocket detection output: {}", stdout);
+            println!("Node.js WebSocket detection output: {stdout}");
             
             if !result.status.success() {
                 let stderr = String::from_utf8_lossy(&result.stderr);
-                panic!("Node.js WebSocket detection error: {}", stderr);
+                panic!("Node.js WebSocket detection error: {stderr}");
             }
         }
         Err(e) => {
-            println!("Node.js not available for WebSocket detection test: {}", e);
+            println!("Node.js not available for WebSocket detection test: {e}");
         }
     }
 }
@@ -79,12 +79,12 @@ async fn test_python_websocket_bootstrap_syntax() {
         Ok(result) => {
             if !result.status.success() {
                 let stderr = String::from_utf8_lossy(&result.stderr);
-                panic!("Python WebSocket bootstrap syntax error: {}", stderr);
+                panic!("Python WebSocket bootstrap syntax error: {stderr}");
             }
         }
         Err(e) => {
             // Python might not be available in the test environment
-            println!("Python not available for syntax check: {}", e);
+            println!("Python not available for syntax check: {e}");
         }
     }
 }
@@ -117,15 +117,15 @@ else:
     match output {
         Ok(result) => {
             let stdout = String::from_utf8_lossy(&result.stdout);
-            println!("Python WebSocket detection output: {}", stdout);
+            println!("Python WebSocket detection output: {stdout}");
             
             if !result.status.success() {
                 let stderr = String::from_utf8_lossy(&result.stderr);
-                panic!("Python WebSocket detection error: {}", stderr);
+                panic!("Python WebSocket detection error: {stderr}");
             }
         }
         Err(e) => {
-            println!("Python not available for WebSocket detection test: {}", e);
+            println!("Python not available for WebSocket detection test: {e}");
         }
     }
 }
@@ -145,11 +145,11 @@ async fn test_example_function_syntax() {
         Ok(result) => {
             if !result.status.success() {
                 let stderr = String::from_utf8_lossy(&result.stderr);
-                panic!("Node.js example function syntax error: {}", stderr);
+                panic!("Node.js example function syntax error: {stderr}");
             }
         }
         Err(e) => {
-            println!("Node.js not available for example syntax check: {}", e);
+            println!("Node.js not available for example syntax check: {e}");
         }
     }
 
@@ -165,11 +165,11 @@ async fn test_example_function_syntax() {
         Ok(result) => {
             if !result.status.success() {
                 let stderr = String::from_utf8_lossy(&result.stderr);
-                panic!("Python example function syntax error: {}", stderr);
+                panic!("Python example function syntax error: {stderr}");
             }
         }
         Err(e) => {
-            println!("Python not available for example syntax check: {}", e);
+            println!("Python not available for example syntax check: {e}");
         }
     }
 }
@@ -188,10 +188,10 @@ async fn test_websocket_dependencies() {
     match node_output {
         Ok(result) => {
             let stdout = String::from_utf8_lossy(&result.stdout);
-            println!("Node.js ws package test: {}", stdout);
+            println!("Node.js ws package test: {stdout}");
         }
         Err(e) => {
-            println!("Node.js not available for dependency test: {}", e);
+            println!("Node.js not available for dependency test: {e}");
         }
     }
 
@@ -205,10 +205,10 @@ async fn test_websocket_dependencies() {
     match python_output {
         Ok(result) => {
             let stdout = String::from_utf8_lossy(&result.stdout);
-            println!("Python websockets package test: {}", stdout);
+            println!("Python websockets package test: {stdout}");
         }
         Err(e) => {
-            println!("Python not available for dependency test: {}", e);
+            println!("Python not available for dependency test: {e}");
         }
     }
 }
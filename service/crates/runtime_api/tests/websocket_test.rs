@@ -172,3 +172,21 @@ async fn test_websocket_error_response() {
         _ => panic!("Expected ErrorResponse message"),
     }
 }
+
+#[tokio::test]
+async fn test_websocket_unregister_messages() {
+    use lambda_runtime_api::websocket::WebSocketMessage;
+
+    // Wire format sent by the bootstraps on SIGTERM
+    let msg: WebSocketMessage =
+        serde_json::from_value(json!({"type": "unregister", "instance_id": "inst-1"})).unwrap();
+    match msg {
+        WebSocketMessage::Unregister { instance_id } => {
+            assert_eq!(instance_id.as_deref(), Some("inst-1"));
+        }
+        _ => panic!("Expected Unregister message"),
+    }
+
+    let ack = serde_json::to_value(WebSocketMessage::Unregistered).unwrap();
+    assert_eq!(ack, json!({"type": "unregistered"}));
+}
@@ -0,0 +1,293 @@
+//! Runtime API conformance harness: an in-process runtime API server backed by a real
+//! control plane, and a runtime client that speaks the protocol the way the AWS runtime
+//! interface clients do. Functions get a registered stand-in container, so invocations take
+//! the scheduler's warm path without Docker.
+
+use lambda_control::pending::Pending;
+use lambda_control::queues::{FnKey, Queues};
+use lambda_control::warm_pool::{InstanceState, WarmContainer};
+use lambda_control::ControlPlane;
+use lambda_models::{
+    Config, CreateFunctionRequest, Function, InvocationType, InvokeRequest, InvokeResponse,
+    LambdaError,
+};
+use lambda_runtime_api::{build_router, RtState};
+use reqwest::StatusCode;
+use sqlx::SqlitePool;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Base path of the runtime API, as the runtime interface clients build it
+pub const RUNTIME_API_PREFIX: &str = "/2018-06-01/runtime";
+
+/// A runtime API server listening on a local port
+pub struct RuntimeApiServer {
+    pub control: Arc<ControlPlane>,
+    addr: SocketAddr,
+    server: JoinHandle<()>,
+}
+
+impl RuntimeApiServer {
+    pub async fn start() -> Self {
+        let mut config = Config::default();
+        config.warmup.enabled = false;
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        let invoker = Arc::new(lambda_invoker::Invoker::new(config.clone()).await.unwrap());
+        let control = Arc::new(ControlPlane::new(pool, invoker, config).await.unwrap());
+
+        let app = build_router(RtState {
+            control: Some(control.clone()),
+            queues: Queues::new(),
+            pending: Pending::new(),
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        Self {
+            control,
+            addr,
+            server,
+        }
+    }
+
+    /// `host:port`, the form containers get in `AWS_LAMBDA_RUNTIME_API`
+    pub fn endpoint(&self) -> String {
+        self.addr.to_string()
+    }
+
+    /// Create `function_name` with a running stand-in container, and the runtime that serves
+    /// it from that container
+    pub async fn deploy(&self, function_name: &str, timeout_secs: u64) -> RuntimeClient {
+        let function = self
+            .control
+            .create_function(CreateFunctionRequest {
+                function_name: function_name.into(),
+                runtime: "nodejs22.x".into(),
+                handler: "index.handler".into(),
+                timeout: Some(timeout_secs),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        self.add_runtime(&function).await
+    }
+
+    /// Another stand-in container for a deployed function, and the runtime serving from it
+    pub async fn add_runtime(&self, function: &Function) -> RuntimeClient {
+        let function_name = &function.function_name;
+        let instance_id = format!("inst-{}", uuid::Uuid::new_v4());
+        self.control
+            .warm_pool()
+            .add_warm_container(
+                FnKey::for_function(function),
+                stand_in_container(function, &instance_id),
+            )
+            .await;
+        RuntimeClient {
+            http: reqwest::Client::new(),
+            base: format!("http://{}{}", self.endpoint(), RUNTIME_API_PREFIX),
+            function_name: function_name.into(),
+            instance_token: self.control.instance_token(&instance_id),
+            instance_id,
+        }
+    }
+
+    /// Invoke `function_name` synchronously in the background, as the invoke API does
+    pub fn invoke(
+        &self,
+        function_name: &str,
+        payload: serde_json::Value,
+        client_context: Option<&str>,
+    ) -> JoinHandle<Result<InvokeResponse, LambdaError>> {
+        let control = self.control.clone();
+        let request = InvokeRequest {
+            function_name: function_name.into(),
+            invocation_type: InvocationType::RequestResponse,
+            log_type: None,
+            client_context: client_context.map(str::to_string),
+            payload: Some(payload),
+            qualifier: None,
+            idempotent: false,
+            trace: false,
+        };
+        tokio::spawn(async move { control.invoke_function(request).await })
+    }
+}
+
+impl Drop for RuntimeApiServer {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn stand_in_container(function: &Function, instance_id: &str) -> WarmContainer {
+    WarmContainer {
+        container_id: format!("container-{instance_id}"),
+        instance_id: instance_id.into(),
+        function_id: function.function_id,
+        image_ref: format!("lambda-home/{}:conformance", function.function_name),
+        memory_mb: function.memory_size,
+        created_at: Instant::now(),
+        last_used: Instant::now(),
+        state: InstanceState::WarmIdle,
+    }
+}
+
+/// An invocation as a runtime reads it from `GET /invocation/next`
+#[derive(Clone, Debug)]
+pub struct Invocation {
+    pub request_id: String,
+    pub deadline_ms: i64,
+    pub invoked_function_arn: String,
+    pub trace_id: Option<String>,
+    pub client_context: Option<String>,
+    pub cognito_identity: Option<String>,
+    pub content_type: Option<String>,
+    pub event: serde_json::Value,
+}
+
+/// A function runtime polling the runtime API from its container
+pub struct RuntimeClient {
+    http: reqwest::Client,
+    base: String,
+    function_name: String,
+    instance_id: String,
+    /// What the container finds in `LAMBDAH_INSTANCE_TOKEN`
+    instance_token: String,
+}
+
+impl RuntimeClient {
+    /// Instance id of the stand-in container, as traces of its invocations show it
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Long-poll for the next invocation until one arrives
+    pub async fn next(&self) -> Result<Invocation, reqwest::Error> {
+        let response = self
+            .http
+            .get(format!("{}/invocation/next", self.base))
+            .query(&[("fn", &self.function_name)])
+            .header("X-LambdaH-Instance-Id", &self.instance_id)
+            .send()
+            .await?
+            .error_for_status()?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let request_id = header("Lambda-Runtime-Aws-Request-Id").unwrap_or_default();
+        let deadline_ms = header("Lambda-Runtime-Deadline-Ms")
+            .and_then(|d| d.parse().ok())
+            .unwrap_or_default();
+        let invoked_function_arn =
+            header("Lambda-Runtime-Invoked-Function-Arn").unwrap_or_default();
+        let trace_id = header("Lambda-Runtime-Trace-Id");
+        let client_context = header("Lambda-Runtime-Client-Context");
+        let cognito_identity = header("Lambda-Runtime-Cognito-Identity");
+        let content_type = header("Content-Type");
+        let body = response.bytes().await?;
+        Ok(Invocation {
+            request_id,
+            deadline_ms,
+            invoked_function_arn,
+            trace_id,
+            client_context,
+            cognito_identity,
+            content_type,
+            event: serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        })
+    }
+
+    /// Long-poll for the next invocation, giving up after `wait` like a client-side timeout
+    pub async fn next_within(&self, wait: Duration) -> Option<Invocation> {
+        tokio::time::timeout(wait, self.next())
+            .await
+            .ok()
+            .map(|next| next.unwrap())
+    }
+
+    /// `POST /invocation/{id}/response` with the handler's result
+    pub async fn respond(&self, request_id: &str, result: &serde_json::Value) -> StatusCode {
+        self.post(&format!("invocation/{request_id}/response"), result, None)
+            .await
+    }
+
+    /// `POST /invocation/{id}/error` as the runtime interface clients report a thrown error
+    pub async fn fail(
+        &self,
+        request_id: &str,
+        error_type: &str,
+        error_message: &str,
+        stack_trace: &[&str],
+    ) -> StatusCode {
+        self.post(
+            &format!("invocation/{request_id}/error"),
+            &error_body(error_type, error_message, stack_trace),
+            Some(error_type),
+        )
+        .await
+    }
+
+    /// `POST /init/error`, sent by a runtime whose handler could not be loaded
+    pub async fn init_error(&self, error_type: &str, error_message: &str) -> StatusCode {
+        self.post(
+            "init/error",
+            &error_body(error_type, error_message, &[]),
+            Some(error_type),
+        )
+        .await
+    }
+
+    /// Invoke another function from this container, as the policy of `function_name` allows
+    pub async fn invoke_function(
+        &self,
+        function_name: &str,
+        payload: &serde_json::Value,
+    ) -> reqwest::Response {
+        let root = self.base.trim_end_matches(RUNTIME_API_PREFIX);
+        self.http
+            .post(format!(
+                "{root}/2015-03-31/functions/{function_name}/invocations"
+            ))
+            .header("X-LambdaH-Instance-Id", &self.instance_id)
+            .header("X-LambdaH-Instance-Token", &self.instance_token)
+            .json(payload)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        error_type: Option<&str>,
+    ) -> StatusCode {
+        let mut request = self
+            .http
+            .post(format!("{}/{path}", self.base))
+            .header("X-LambdaH-Instance-Id", &self.instance_id)
+            .json(body);
+        if let Some(error_type) = error_type {
+            request = request.header("Lambda-Runtime-Function-Error-Type", error_type);
+        }
+        request.send().await.unwrap().status()
+    }
+}
+
+/// The error document the runtime interface clients post
+fn error_body(error_type: &str, error_message: &str, stack_trace: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "errorMessage": error_message,
+        "errorType": error_type,
+        "stackTrace": stack_trace,
+    })
+}
@@ -0,0 +1,71 @@
+use lambda_control::queues::FnKey;
+use lambda_control::warm_pool::InstanceState;
+use lambda_models::FunctionError;
+use lambda_runtime_conformance::RuntimeApiServer;
+
+#[tokio::test]
+async fn errors_reach_the_caller_as_unhandled_function_errors() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    let invoke = server.invoke("orders", serde_json::json!({}), None);
+    let invocation = runtime.next().await.unwrap();
+    assert_eq!(
+        runtime
+            .fail(
+                &invocation.request_id,
+                "TypeError",
+                "Cannot read properties of undefined",
+                &["at handler (/var/task/index.js:3:9)", "at process"],
+            )
+            .await,
+        202
+    );
+
+    let response = invoke.await.unwrap().unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.function_error, Some(FunctionError::Unhandled));
+    let payload = response.payload.unwrap();
+    assert_eq!(payload["errorType"], "TypeError");
+    assert_eq!(
+        payload["errorMessage"],
+        "Cannot read properties of undefined"
+    );
+    assert_eq!(
+        payload["stackTrace"],
+        serde_json::json!(["at handler (/var/task/index.js:3:9)", "at process"])
+    );
+}
+
+#[tokio::test]
+async fn duplicate_errors_are_rejected() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    let invoke = server.invoke("orders", serde_json::json!({}), None);
+    let invocation = runtime.next().await.unwrap();
+    let id = &invocation.request_id;
+    assert_eq!(runtime.fail(id, "Error", "first", &[]).await, 202);
+    assert_eq!(runtime.fail(id, "Error", "second", &[]).await, 404);
+    assert_eq!(runtime.respond(id, &serde_json::json!("late")).await, 404);
+
+    let payload = invoke.await.unwrap().unwrap().payload.unwrap();
+    assert_eq!(payload["errorMessage"], "first");
+}
+
+#[tokio::test]
+async fn init_errors_take_the_container_out_of_rotation() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+    let key = FnKey::for_function(&server.control.get_function("orders").await.unwrap());
+
+    assert_eq!(
+        runtime
+            .init_error("Runtime.ImportModuleError", "Cannot find module 'index'")
+            .await,
+        202
+    );
+    let pool = server.control.warm_pool();
+    assert!(!pool.has_available(&key).await);
+    assert_eq!(pool.count_state(&key, InstanceState::Stopping).await, 1);
+}
@@ -0,0 +1,105 @@
+use lambda_models::AddPermissionRequest;
+use lambda_runtime_conformance::RuntimeApiServer;
+
+fn grant(statement_id: &str, principal: &str) -> AddPermissionRequest {
+    AddPermissionRequest {
+        statement_id: statement_id.into(),
+        action: "lambda:InvokeFunction".into(),
+        principal: principal.into(),
+    }
+}
+
+#[tokio::test]
+async fn functions_invoke_only_what_their_target_allows() {
+    let server = RuntimeApiServer::start().await;
+    let checkout = server.deploy("checkout", 3).await;
+    let payments = server.deploy("payments", 3).await;
+
+    let denied = checkout
+        .invoke_function("payments", &serde_json::json!({ "amount": 10 }))
+        .await;
+    assert_eq!(denied.status(), 403);
+    let error: serde_json::Value = denied.json().await.unwrap();
+    assert_eq!(error["error_type"], "AccessDeniedException");
+
+    server
+        .control
+        .add_permission("payments", grant("checkout-pays", "checkout"))
+        .await
+        .unwrap();
+    let event = serde_json::json!({ "amount": 10 });
+    let (response, ()) = tokio::join!(checkout.invoke_function("payments", &event), async {
+        let invocation = payments.next().await.unwrap();
+        assert_eq!(invocation.event["amount"], 10);
+        payments
+            .respond(&invocation.request_id, &serde_json::json!({ "paid": true }))
+            .await;
+    });
+    assert_eq!(response.status(), 200);
+    let result: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(result["paid"], true);
+
+    // The grant goes one way only
+    let reverse = payments
+        .invoke_function("checkout", &serde_json::json!({}))
+        .await;
+    assert_eq!(reverse.status(), 403);
+
+    server
+        .control
+        .remove_permission("payments", "checkout-pays")
+        .await
+        .unwrap();
+    let revoked = checkout
+        .invoke_function("payments", &serde_json::json!({}))
+        .await;
+    assert_eq!(revoked.status(), 403);
+}
+
+#[tokio::test]
+async fn callers_must_be_known_containers() {
+    let server = RuntimeApiServer::start().await;
+    let payments = server.deploy("payments", 3).await;
+    server
+        .control
+        .add_permission("payments", grant("everyone", "*"))
+        .await
+        .unwrap();
+
+    let url = format!(
+        "http://{}/2015-03-31/functions/payments/invocations",
+        server.endpoint()
+    );
+    let http = reqwest::Client::new();
+    let anonymous = http.post(&url).json(&serde_json::json!({})).send().await;
+    assert_eq!(anonymous.unwrap().status(), 403);
+    let forged = http
+        .post(&url)
+        .header("X-LambdaH-Instance-Id", "inst-made-up")
+        .json(&serde_json::json!({}))
+        .send()
+        .await;
+    assert_eq!(forged.unwrap().status(), 403);
+
+    // The instance id of a running container, as traces show it, is not enough on its own
+    for token in [None, Some("made-up")] {
+        let mut request = http
+            .post(&url)
+            .header("X-LambdaH-Instance-Id", payments.instance_id());
+        if let Some(token) = token {
+            request = request.header("X-LambdaH-Instance-Token", token);
+        }
+        let impersonated = request.json(&serde_json::json!({})).send().await;
+        assert_eq!(impersonated.unwrap().status(), 403);
+    }
+}
+
+#[tokio::test]
+async fn unknown_targets_are_not_found() {
+    let server = RuntimeApiServer::start().await;
+    let checkout = server.deploy("checkout", 3).await;
+    let response = checkout
+        .invoke_function("missing", &serde_json::json!({}))
+        .await;
+    assert_eq!(response.status(), 404);
+}
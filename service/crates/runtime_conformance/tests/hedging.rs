@@ -0,0 +1,77 @@
+use lambda_models::HedgingConfig;
+use lambda_runtime_conformance::RuntimeApiServer;
+use std::time::Duration;
+
+#[tokio::test]
+async fn a_winning_hedge_is_recorded_under_the_callers_request_id() {
+    let server = RuntimeApiServer::start().await;
+    let first = server.deploy("catalog", 5).await;
+    let function = server.control.get_function("catalog").await.unwrap();
+
+    // Hedging waits for enough recent durations to take a percentile from
+    for _ in 0..20 {
+        let invoke = server.invoke("catalog", serde_json::json!({}), None);
+        let invocation = first.next().await.unwrap();
+        first
+            .respond(&invocation.request_id, &serde_json::json!("fast"))
+            .await;
+        invoke.await.unwrap().unwrap();
+    }
+    server
+        .control
+        .put_hedging(
+            "catalog",
+            HedgingConfig {
+                enabled: true,
+                percentile: None,
+                min_delay_ms: Some(200),
+            },
+        )
+        .await
+        .unwrap();
+    let second = server.add_runtime(&function).await;
+
+    // The first container sits on the request, so the idle second one gets a copy
+    let invoke = server.invoke("catalog", serde_json::json!({}), None);
+    let original = first.next().await.unwrap();
+    let copy = second.next().await.unwrap();
+    assert_ne!(copy.request_id, original.request_id);
+    assert_eq!(
+        second
+            .respond(&copy.request_id, &serde_json::json!("hedged"))
+            .await,
+        202
+    );
+    let response = invoke.await.unwrap().unwrap();
+    assert_eq!(response.payload, Some(serde_json::json!("hedged")));
+    assert_eq!(response.headers["X-Amz-Request-Id"], original.request_id);
+
+    // The losing copy's result goes nowhere
+    assert_eq!(
+        first
+            .respond(&original.request_id, &serde_json::json!("slow"))
+            .await,
+        404
+    );
+
+    // Executions are recorded in the background
+    let mut execution = None;
+    for _ in 0..50 {
+        let record = server
+            .control
+            .get_execution(&original.request_id)
+            .await
+            .unwrap();
+        if record.status != "Running" {
+            execution = Some(record);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(execution.unwrap().status, "Success");
+    assert!(server
+        .control
+        .get_execution(&copy.request_id)
+        .await
+        .is_err());
+}
@@ -0,0 +1,194 @@
+use axum::{routing::post, Json, Router};
+use lambda_models::{FunctionHooks, HookFailurePolicy, HookTarget, InvocationHook};
+use lambda_runtime_conformance::RuntimeApiServer;
+use serde_json::json;
+use std::time::Duration;
+
+fn function_hook(function_name: &str, on_failure: HookFailurePolicy) -> InvocationHook {
+    InvocationHook {
+        target: HookTarget::Function {
+            function_name: function_name.into(),
+        },
+        timeout_ms: 2_000,
+        on_failure,
+    }
+}
+
+fn webhook_hook(url: String, on_failure: HookFailurePolicy) -> InvocationHook {
+    InvocationHook {
+        target: HookTarget::Webhook { url },
+        timeout_ms: 2_000,
+        on_failure,
+    }
+}
+
+/// A webhook hook target answering every event with `answer`
+async fn webhook_target(answer: serde_json::Value) -> String {
+    let app = Router::new().route(
+        "/hook",
+        post(move |Json(event): Json<serde_json::Value>| async move {
+            assert_eq!(event["hook"], "pre_invoke");
+            Json(answer)
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{addr}/hook")
+}
+
+#[tokio::test]
+async fn function_hooks_enrich_payloads_and_see_results() {
+    let server = RuntimeApiServer::start().await;
+    let orders = server.deploy("orders", 3).await;
+    let auth = server.deploy("auth", 3).await;
+    let audit = server.deploy("audit", 3).await;
+    server
+        .control
+        .put_hooks(
+            "orders",
+            FunctionHooks {
+                pre_invoke: Some(function_hook("auth", HookFailurePolicy::Fail)),
+                post_invoke: Some(function_hook("audit", HookFailurePolicy::Fail)),
+            },
+        )
+        .await
+        .unwrap();
+
+    let invoke = server.invoke("orders", json!({ "token": "t-1" }), None);
+
+    let pre = auth.next().await.unwrap();
+    assert_eq!(pre.event["hook"], "pre_invoke");
+    assert_eq!(pre.event["function_name"], "orders");
+    assert_eq!(pre.event["payload"]["token"], "t-1");
+    auth.respond(
+        &pre.request_id,
+        &json!({ "payload": { "token": "t-1", "user": "ada" } }),
+    )
+    .await;
+
+    let invocation = orders.next().await.unwrap();
+    assert_eq!(invocation.event["user"], "ada");
+    orders
+        .respond(&invocation.request_id, &json!({ "order": 7 }))
+        .await;
+
+    let post = audit.next().await.unwrap();
+    assert_eq!(post.event["hook"], "post_invoke");
+    assert_eq!(post.event["request_id"], pre.event["request_id"]);
+    assert_eq!(post.event["payload"]["user"], "ada");
+    assert_eq!(post.event["response"]["payload"]["order"], 7);
+    // What post-invoke hooks answer is ignored
+    audit
+        .respond(&post.request_id, &json!({ "payload": "ignored" }))
+        .await;
+
+    let response = invoke.await.unwrap().unwrap();
+    assert_eq!(response.payload, Some(json!({ "order": 7 })));
+}
+
+#[tokio::test]
+async fn rejected_invocations_never_reach_the_function() {
+    let server = RuntimeApiServer::start().await;
+    let orders = server.deploy("orders", 3).await;
+    let auth = server.deploy("auth", 3).await;
+    server
+        .control
+        .put_hooks(
+            "orders",
+            FunctionHooks {
+                pre_invoke: Some(function_hook("auth", HookFailurePolicy::Continue)),
+                post_invoke: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let invoke = server.invoke("orders", json!({}), None);
+    let pre = auth.next().await.unwrap();
+    auth.respond(&pre.request_id, &json!({ "reject": "no token" }))
+        .await;
+
+    let err = invoke.await.unwrap().unwrap_err();
+    assert_eq!(err.http_status(), 403);
+    assert_eq!(err.error_type(), "InvocationRejectedException");
+    assert!(err.to_string().contains("no token"));
+    assert!(orders
+        .next_within(Duration::from_millis(200))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn failure_policies_decide_whether_invocations_go_on() {
+    let server = RuntimeApiServer::start().await;
+    let orders = server.deploy("orders", 3).await;
+    let auth = server.deploy("auth", 3).await;
+
+    // A hook function that never answers times out
+    let mut hook = function_hook("auth", HookFailurePolicy::Fail);
+    hook.timeout_ms = 200;
+    server
+        .control
+        .put_hooks(
+            "orders",
+            FunctionHooks {
+                pre_invoke: Some(hook),
+                post_invoke: None,
+            },
+        )
+        .await
+        .unwrap();
+    let invoke = server.invoke("orders", json!({}), None);
+    auth.next().await.unwrap();
+    let err = invoke.await.unwrap().unwrap_err();
+    assert_eq!(err.http_status(), 502);
+    assert_eq!(err.error_type(), "HookFailedException");
+
+    // An unreachable webhook is skipped when the policy says so
+    server
+        .control
+        .put_hooks(
+            "orders",
+            FunctionHooks {
+                pre_invoke: Some(webhook_hook(
+                    "http://127.0.0.1:1/hook".into(),
+                    HookFailurePolicy::Continue,
+                )),
+                post_invoke: None,
+            },
+        )
+        .await
+        .unwrap();
+    let invoke = server.invoke("orders", json!({ "n": 1 }), None);
+    let invocation = orders.next().await.unwrap();
+    assert_eq!(invocation.event["n"], 1);
+    orders.respond(&invocation.request_id, &json!("done")).await;
+    assert_eq!(invoke.await.unwrap().unwrap().payload, Some(json!("done")));
+}
+
+#[tokio::test]
+async fn webhook_hooks_replace_payloads() {
+    let server = RuntimeApiServer::start().await;
+    let orders = server.deploy("orders", 3).await;
+    let url = webhook_target(json!({ "payload": { "tenant": "acme" } })).await;
+    server
+        .control
+        .put_hooks(
+            "orders",
+            FunctionHooks {
+                pre_invoke: Some(webhook_hook(url, HookFailurePolicy::Fail)),
+                post_invoke: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let invoke = server.invoke("orders", json!({}), None);
+    let invocation = orders.next().await.unwrap();
+    assert_eq!(invocation.event, json!({ "tenant": "acme" }));
+    orders.respond(&invocation.request_id, &json!(null)).await;
+    invoke.await.unwrap().unwrap();
+}
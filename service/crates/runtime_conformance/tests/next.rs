@@ -0,0 +1,135 @@
+use lambda_models::IdentityConfig;
+use lambda_runtime_conformance::RuntimeApiServer;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[tokio::test]
+async fn next_carries_the_invocation_and_its_context() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    let invoked_at = now_ms();
+    let invoke = server.invoke(
+        "orders",
+        serde_json::json!({ "order": 42 }),
+        Some("eyJjdXN0b20iOnsiYXBwIjoic2hvcCJ9fQ=="),
+    );
+    let invocation = runtime.next().await.unwrap();
+
+    assert!(uuid::Uuid::parse_str(&invocation.request_id).is_ok());
+    // The deadline is wall-clock milliseconds, the function's timeout away
+    assert!(invocation.deadline_ms > invoked_at);
+    assert!(invocation.deadline_ms <= now_ms() + 3000);
+    assert_eq!(
+        invocation.invoked_function_arn,
+        IdentityConfig::default().function_arn("orders")
+    );
+    assert_eq!(
+        invocation.client_context.as_deref(),
+        Some("eyJjdXN0b20iOnsiYXBwIjoic2hvcCJ9fQ==")
+    );
+    assert_eq!(invocation.cognito_identity, None);
+    assert_eq!(invocation.content_type.as_deref(), Some("application/json"));
+    assert_eq!(invocation.event, serde_json::json!({ "order": 42 }));
+
+    assert_eq!(
+        runtime
+            .respond(&invocation.request_id, &serde_json::json!({ "ok": true }))
+            .await,
+        202
+    );
+    let response = invoke.await.unwrap().unwrap();
+    assert_eq!(response.payload.unwrap(), serde_json::json!({ "ok": true }));
+    assert_eq!(response.function_error, None);
+    assert_eq!(response.headers["X-Amz-Request-Id"], invocation.request_id);
+}
+
+#[tokio::test]
+async fn next_long_polls_until_work_arrives() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    // Nothing is queued, so the poll is held open rather than answered empty
+    assert!(runtime
+        .next_within(Duration::from_millis(300))
+        .await
+        .is_none());
+
+    let poll = runtime.next();
+    tokio::pin!(poll);
+    assert!(tokio::time::timeout(Duration::from_millis(100), &mut poll)
+        .await
+        .is_err());
+    let response = server.invoke("orders", serde_json::json!("late"), None);
+    let invocation = poll.await.unwrap();
+    assert_eq!(invocation.event, "late");
+    runtime
+        .respond(&invocation.request_id, &serde_json::json!("done"))
+        .await;
+    assert_eq!(response.await.unwrap().unwrap().payload.unwrap(), "done");
+}
+
+#[tokio::test]
+async fn abandoned_polls_do_not_swallow_invocations() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    // Clients give up on a poll and reconnect, as runtimes do behind an HTTP timeout
+    for _ in 0..3 {
+        assert!(runtime
+            .next_within(Duration::from_millis(100))
+            .await
+            .is_none());
+    }
+    // Polls are only dropped once the server has seen their connection close
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let response = server.invoke("orders", serde_json::json!({ "n": 1 }), None);
+    let invocation = runtime
+        .next_within(Duration::from_secs(2))
+        .await
+        .expect("the invocation should go to the poll still open");
+    assert_eq!(invocation.event["n"], 1);
+    runtime
+        .respond(&invocation.request_id, &invocation.event)
+        .await;
+    assert_eq!(response.await.unwrap().unwrap().payload.unwrap()["n"], 1);
+}
+
+#[tokio::test]
+async fn queued_invocations_are_each_delivered_once() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    let invokes: Vec<_> = (0..3)
+        .map(|n| server.invoke("orders", serde_json::json!({ "n": n }), None))
+        .collect();
+    // Let every invocation queue before the runtime picks the first one up
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let mut request_ids = HashSet::new();
+    for _ in 0..3 {
+        let invocation = runtime.next().await.unwrap();
+        assert!(request_ids.insert(invocation.request_id.clone()));
+        // Echo the event, so each caller can tell its own result
+        assert_eq!(
+            runtime
+                .respond(&invocation.request_id, &invocation.event)
+                .await,
+            202
+        );
+    }
+    assert!(runtime
+        .next_within(Duration::from_millis(100))
+        .await
+        .is_none());
+    for (n, invoke) in invokes.into_iter().enumerate() {
+        assert_eq!(invoke.await.unwrap().unwrap().payload.unwrap()["n"], n);
+    }
+}
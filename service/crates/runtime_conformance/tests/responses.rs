@@ -0,0 +1,74 @@
+use lambda_models::FunctionError;
+use lambda_runtime_conformance::RuntimeApiServer;
+
+#[tokio::test]
+async fn any_json_result_reaches_the_caller_unchanged() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    for result in [
+        serde_json::json!({ "statusCode": 200, "body": "{\"ok\":true}" }),
+        serde_json::json!("plain string"),
+        serde_json::json!([1, 2, 3]),
+        serde_json::json!(7.5),
+        serde_json::Value::Null,
+    ] {
+        let invoke = server.invoke("orders", serde_json::json!({}), None);
+        let invocation = runtime.next().await.unwrap();
+        assert_eq!(runtime.respond(&invocation.request_id, &result).await, 202);
+        assert_eq!(invoke.await.unwrap().unwrap().payload.unwrap(), result);
+    }
+}
+
+#[tokio::test]
+async fn duplicate_results_are_rejected() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    let invoke = server.invoke("orders", serde_json::json!({}), None);
+    let invocation = runtime.next().await.unwrap();
+    let id = &invocation.request_id;
+    assert_eq!(runtime.respond(id, &serde_json::json!("first")).await, 202);
+    assert_eq!(runtime.respond(id, &serde_json::json!("second")).await, 404);
+    assert_eq!(runtime.fail(id, "Error", "too late", &[]).await, 404);
+
+    // The first result stands
+    let response = invoke.await.unwrap().unwrap();
+    assert_eq!(response.payload.unwrap(), "first");
+    assert_eq!(response.function_error, None);
+}
+
+#[tokio::test]
+async fn results_for_unknown_requests_are_rejected() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 3).await;
+
+    let unknown = uuid::Uuid::new_v4().to_string();
+    assert_eq!(runtime.respond(&unknown, &serde_json::json!({})).await, 404);
+    assert_eq!(runtime.fail(&unknown, "Error", "boom", &[]).await, 404);
+    assert_eq!(
+        runtime
+            .respond("not-a-request-id", &serde_json::json!({}))
+            .await,
+        404
+    );
+}
+
+#[tokio::test]
+async fn results_after_the_timeout_are_rejected() {
+    let server = RuntimeApiServer::start().await;
+    let runtime = server.deploy("orders", 1).await;
+
+    let invoke = server.invoke("orders", serde_json::json!({}), None);
+    let invocation = runtime.next().await.unwrap();
+    let response = invoke.await.unwrap().unwrap();
+    assert_eq!(response.function_error, Some(FunctionError::Unhandled));
+    assert_eq!(response.payload.unwrap()["errorType"], "TaskTimedOut");
+
+    assert_eq!(
+        runtime
+            .respond(&invocation.request_id, &serde_json::json!("late"))
+            .await,
+        404
+    );
+}
@@ -0,0 +1,185 @@
+//! Hammer a running server's invoke path at high concurrency, then check every invocation got
+//! a result of its own. Start the server and create the function first, e.g. from
+//! `examples/echo-node`, which returns its event and so works with `--check-echo`.
+
+use anyhow::Result;
+use clap::Parser;
+use futures::stream::{self, StreamExt};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+#[derive(Parser)]
+#[command(name = "lambda-stress")]
+#[command(about = "Stress the invoke path of a running Lambda@Home server")]
+struct Args {
+    /// User API of the server under test
+    #[arg(long, default_value = "http://localhost:8000")]
+    endpoint: String,
+
+    /// Function to invoke
+    #[arg(long)]
+    function: String,
+
+    /// Invocations in flight at once
+    #[arg(long, default_value_t = 64)]
+    concurrency: usize,
+
+    /// Invocations in total
+    #[arg(long, default_value_t = 1000)]
+    requests: usize,
+
+    /// Fail unless each result contains the event it was invoked with
+    #[arg(long)]
+    check_echo: bool,
+}
+
+struct Outcome {
+    seq: usize,
+    status: u16,
+    latency: Duration,
+    request_id: Option<String>,
+    function_error: Option<String>,
+    body: Option<Value>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/2015-03-31/functions/{}/invocations",
+        args.endpoint.trim_end_matches('/'),
+        args.function
+    );
+
+    let started = Instant::now();
+    let outcomes: Vec<Result<Outcome>> = stream::iter(0..args.requests)
+        .map(|seq| {
+            let client = client.clone();
+            let url = url.clone();
+            async move {
+                let sent = Instant::now();
+                let response = client
+                    .post(&url)
+                    .json(&json!({ "stress_seq": seq }))
+                    .send()
+                    .await?;
+                let header = |name: &str| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                let request_id = header("X-Amz-Request-Id");
+                let function_error = header("X-Amz-Function-Error");
+                let status = response.status().as_u16();
+                let body = response.json::<Value>().await.ok();
+                Ok(Outcome {
+                    seq,
+                    status,
+                    latency: sent.elapsed(),
+                    request_id,
+                    function_error,
+                    body,
+                })
+            }
+        })
+        .buffer_unordered(args.concurrency.max(1))
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    let mut transport_errors = 0;
+    let mut statuses: BTreeMap<u16, usize> = BTreeMap::new();
+    let mut function_errors = 0;
+    let mut request_ids = HashSet::new();
+    let mut duplicate_ids = 0;
+    let mut echo_mismatches = 0;
+    let mut latencies = Vec::new();
+    for outcome in outcomes {
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("request failed: {e}");
+                transport_errors += 1;
+                continue;
+            }
+        };
+        *statuses.entry(outcome.status).or_default() += 1;
+        latencies.push(outcome.latency);
+        if outcome.function_error.is_some() {
+            function_errors += 1;
+        }
+        if let Some(request_id) = outcome.request_id {
+            if !request_ids.insert(request_id) {
+                duplicate_ids += 1;
+            }
+        }
+        if args.check_echo
+            && outcome.status == 200
+            && outcome.function_error.is_none()
+            && !outcome
+                .body
+                .as_ref()
+                .is_some_and(|body| contains_seq(body, outcome.seq))
+        {
+            echo_mismatches += 1;
+        }
+    }
+    latencies.sort();
+
+    println!(
+        "{} invocations in {:.2}s ({:.1}/s) at concurrency {}",
+        args.requests,
+        elapsed.as_secs_f64(),
+        args.requests as f64 / elapsed.as_secs_f64(),
+        args.concurrency
+    );
+    println!(
+        "latency p50 {:?}  p90 {:?}  p99 {:?}  max {:?}",
+        percentile(&latencies, 50.0),
+        percentile(&latencies, 90.0),
+        percentile(&latencies, 99.0),
+        latencies.last().copied().unwrap_or_default()
+    );
+    for (status, count) in &statuses {
+        println!("HTTP {status}: {count}");
+    }
+    println!("function errors: {function_errors}");
+    println!("transport errors: {transport_errors}");
+    println!("duplicate request ids: {duplicate_ids}");
+    if args.check_echo {
+        println!("results not matching their event: {echo_mismatches}");
+    }
+
+    let failed = transport_errors > 0
+        || statuses.keys().any(|status| *status != 200)
+        || duplicate_ids > 0
+        || echo_mismatches > 0;
+    if failed {
+        anyhow::bail!("stress run found failed or crossed invocations");
+    }
+    Ok(())
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
+
+/// Whether `value` holds `"stress_seq": seq` at any depth, wherever the function put its event
+fn contains_seq(value: &Value, seq: usize) -> bool {
+    match value {
+        Value::Object(map) => {
+            map.get("stress_seq").and_then(Value::as_u64) == Some(seq as u64)
+                || map.values().any(|v| contains_seq(v, seq))
+        }
+        Value::Array(items) => items.iter().any(|v| contains_seq(v, seq)),
+        _ => false,
+    }
+}
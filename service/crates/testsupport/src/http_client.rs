@@ -39,7 +39,7 @@ impl LambdaClient {
     pub async fn get_function(&self, name: &str) -> Result<Function> {
         let response = self
             .client
-            .get(format!("{}/2015-03-31/functions/{}", self.base_url, name))
+            .get(format!("{}/api/functions/{}", self.base_url, name))
             .send()
             .await?;
 
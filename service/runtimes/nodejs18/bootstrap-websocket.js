@@ -1,6 +1,7 @@
 #!/usr/bin/env node
 
 const WebSocket = require('ws');
+const http = require('http');
 const fs = require('fs');
 const path = require('path');
 
@@ -31,6 +32,15 @@ console.log('Function:', FUNCTION_NAME);
 console.log('Handler:', HANDLER);
 console.log('Task Root:', TASK_ROOT);
 
+// awslambda.streamifyResponse marks handlers that stream their response, as on AWS
+const STREAMING = Symbol.for('aws.lambda.runtime.handler.streaming');
+globalThis.awslambda = {
+    streamifyResponse(handler) {
+        handler[STREAMING] = 'response';
+        return handler;
+    },
+};
+
 // Load the user function
 let userHandler;
 try {
@@ -61,9 +71,56 @@ const MessageType = {
     ERROR: 'error',
     PING: 'ping',
     PONG: 'pong',
-    ERROR_RESPONSE: 'error_response'
+    ERROR_RESPONSE: 'error_response',
+    UNREGISTER: 'unregister',
+    UNREGISTERED: 'unregistered'
 };
 
+// Run a streaming handler, posting what it writes to its response stream to the runtime
+// API over HTTP as it goes. An error after the stream started is reported in trailers, the
+// way AWS runtimes do. The WebSocket connection tracks the instance, so the post names none
+function streamResponse(awsRequestId, event) {
+    return new Promise((resolve, reject) => {
+        const req = http.request({
+            hostname: RUNTIME_API.hostname,
+            port: RUNTIME_API.port,
+            path: `/2018-06-01/runtime/invocation/${awsRequestId}/response`,
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/octet-stream',
+                'Lambda-Runtime-Function-Response-Mode': 'streaming',
+                'Transfer-Encoding': 'chunked',
+                Trailer: 'Lambda-Runtime-Function-Error-Type, Lambda-Runtime-Function-Error-Body',
+            },
+        }, (res) => {
+            res.resume();
+            res.on('end', resolve);
+        });
+        req.on('error', reject);
+        // Only before the first write, as on AWS
+        req.setContentType = (type) => req.setHeader('Content-Type', type);
+        Promise.resolve()
+            .then(() => userHandler(event, req, {}))
+            .catch((error) => {
+                console.error('Function error:', error);
+                // Too late to report once the handler ended the stream
+                if (req.writableEnded) return;
+                const body = JSON.stringify({
+                    errorMessage: error.message,
+                    errorType: error.name || 'Error',
+                    stackTrace: String(error.stack || '').split('\n'),
+                });
+                req.addTrailers({
+                    'Lambda-Runtime-Function-Error-Type': error.name || 'Error',
+                    'Lambda-Runtime-Function-Error-Body': Buffer.from(body).toString('base64'),
+                });
+            })
+            .finally(() => {
+                if (!req.writableEnded) req.end();
+            });
+    });
+}
+
 class WebSocketRuntime {
     constructor() {
         this.ws = null;
@@ -71,13 +128,19 @@ class WebSocketRuntime {
         this.maxReconnectAttempts = 10;
         this.reconnectDelay = 1000;
         this.isConnected = false;
+        // SIGTERM handling: stop taking work, finish what is running, then exit
+        this.inFlight = 0;
+        this.shuttingDown = false;
+        this.unregistered = false;
     }
 
     connect() {
         const wsUrl = `ws://${RUNTIME_API.hostname}:${RUNTIME_API.port}/2018-06-01/runtime/websocket?fn=${encodeURIComponent(FUNCTION_NAME)}`;
         console.log('Connecting to WebSocket:', wsUrl);
 
-        this.ws = new WebSocket(wsUrl);
+        this.ws = new WebSocket(wsUrl, {
+            headers: INSTANCE_ID ? { 'X-LambdaH-Instance-Id': INSTANCE_ID } : {}
+        });
 
         this.ws.on('open', () => {
             console.log('WebSocket connected');
@@ -98,6 +161,9 @@ class WebSocketRuntime {
         this.ws.on('close', (code, reason) => {
             console.log(`WebSocket closed: ${code} ${reason}`);
             this.isConnected = false;
+            if (this.shuttingDown) {
+                process.exit(0);
+            }
             this.handleReconnect();
         });
 
@@ -123,6 +189,11 @@ class WebSocketRuntime {
             case MessageType.INVOCATION:
                 this.handleInvocation(message);
                 break;
+            case MessageType.UNREGISTERED:
+                // No invocations follow this message
+                this.unregistered = true;
+                this.exitWhenDrained();
+                break;
             case MessageType.PING:
                 this.send({ type: MessageType.PONG });
                 break;
@@ -137,6 +208,20 @@ class WebSocketRuntime {
     async handleInvocation(message) {
         const { request_id, payload, deadline_ms, invoked_function_arn, trace_id } = message;
         console.log('Got invocation:', request_id);
+        this.inFlight++;
+
+        if (userHandler[STREAMING] === 'response') {
+            try {
+                await streamResponse(request_id, payload);
+                console.log('Streamed response for:', request_id);
+            } catch (error) {
+                console.error('Failed to stream response:', error);
+            } finally {
+                this.inFlight--;
+                this.exitWhenDrained();
+            }
+            return;
+        }
 
         try {
             // Execute the user function
@@ -168,6 +253,37 @@ class WebSocketRuntime {
                     'X-Amz-Function-Error': 'Unhandled'
                 }
             });
+        } finally {
+            this.inFlight--;
+            this.exitWhenDrained();
+        }
+    }
+
+    // Unregister so the runtime API stops sending work, let in-flight invocations
+    // finish and post their results, then exit within the stop grace period
+    shutdown(signal) {
+        if (this.shuttingDown) {
+            return;
+        }
+        this.shuttingDown = true;
+        console.log(`Received ${signal}, finishing ${this.inFlight} in-flight invocation(s) before exit`);
+        if (this.ws && this.isConnected) {
+            this.send({ type: MessageType.UNREGISTER, instance_id: INSTANCE_ID || null });
+        } else {
+            this.unregistered = true;
+        }
+        this.exitWhenDrained();
+    }
+
+    exitWhenDrained() {
+        if (!this.shuttingDown || this.inFlight > 0 || (this.isConnected && !this.unregistered)) {
+            return;
+        }
+        if (this.ws && this.isConnected) {
+            // Closing flushes queued responses; the close handler exits
+            this.ws.close();
+        } else {
+            process.exit(0);
         }
     }
 
@@ -213,18 +329,5 @@ const runtime = new WebSocketRuntime();
 runtime.connect();
 
 // Handle graceful shutdown
-process.on('SIGTERM', () => {
-    console.log('Received SIGTERM, closing WebSocket connection');
-    if (runtime.ws) {
-        runtime.ws.close();
-    }
-    process.exit(0);
-});
-
-process.on('SIGINT', () => {
-    console.log('Received SIGINT, closing WebSocket connection');
-    if (runtime.ws) {
-        runtime.ws.close();
-    }
-    process.exit(0);
-});
+process.on('SIGTERM', () => runtime.shutdown('SIGTERM'));
+process.on('SIGINT', () => runtime.shutdown('SIGINT'));
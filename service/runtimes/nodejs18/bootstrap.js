@@ -42,6 +42,15 @@ console.log('Function:', FUNCTION_NAME);
 console.log('Handler:', HANDLER);
 console.log('Task Root:', TASK_ROOT);
 
+// awslambda.streamifyResponse marks handlers that stream their response, as on AWS
+const STREAMING = Symbol.for('aws.lambda.runtime.handler.streaming');
+globalThis.awslambda = {
+    streamifyResponse(handler) {
+        handler[STREAMING] = 'response';
+        return handler;
+    },
+};
+
 // Load the user function
 let userHandler;
 try {
@@ -112,9 +121,83 @@ function makeRequest(method, path, data = null, extraHeaders = {}) {
     });
 }
 
+// Run a streaming handler, posting what it writes to its response stream as it goes. An
+// error after the stream started is reported in trailers, the way AWS runtimes do
+function streamResponse(awsRequestId, event) {
+    return new Promise((resolve, reject) => {
+        const req = http.request({
+            hostname: RUNTIME_API.hostname,
+            port: RUNTIME_API.port,
+            path: `/2018-06-01/runtime/invocation/${awsRequestId}/response`,
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/octet-stream',
+                'Lambda-Runtime-Function-Response-Mode': 'streaming',
+                'Transfer-Encoding': 'chunked',
+                Trailer: 'Lambda-Runtime-Function-Error-Type, Lambda-Runtime-Function-Error-Body',
+                ...(INSTANCE_ID ? { 'X-LambdaH-Instance-Id': INSTANCE_ID } : {}),
+            },
+        }, (res) => {
+            res.resume();
+            res.on('end', resolve);
+        });
+        req.on('error', reject);
+        // Only before the first write, as on AWS
+        req.setContentType = (type) => req.setHeader('Content-Type', type);
+        Promise.resolve()
+            .then(() => userHandler(event, req, {}))
+            .catch((error) => {
+                console.error('Function error:', error);
+                // Too late to report once the handler ended the stream
+                if (req.writableEnded) return;
+                const body = JSON.stringify({
+                    errorMessage: error.message,
+                    errorType: error.name || 'Error',
+                    stackTrace: String(error.stack || '').split('\n'),
+                });
+                req.addTrailers({
+                    'Lambda-Runtime-Function-Error-Type': error.name || 'Error',
+                    'Lambda-Runtime-Function-Error-Body': Buffer.from(body).toString('base64'),
+                });
+            })
+            .finally(() => {
+                if (!req.writableEnded) req.end();
+            });
+    });
+}
+
+// SIGTERM handling: finish the current invocation, unregister, then exit
+let shuttingDown = false;
+let busy = false;
+
+async function unregisterAndExit() {
+    try {
+        await makeRequest('POST', '/runtime/unregister', null, INSTANCE_ID ? { 'X-LambdaH-Instance-Id': INSTANCE_ID } : {});
+    } catch (error) {
+        console.error('Failed to unregister:', error);
+    }
+    // An invocation that arrived meanwhile finishes first; the loop exits after it
+    if (!busy) {
+        process.exit(0);
+    }
+}
+
+function shutdown(signal) {
+    if (shuttingDown) {
+        return;
+    }
+    shuttingDown = true;
+    console.log(`Received ${signal}, exiting after the current invocation`);
+    if (!busy) {
+        unregisterAndExit();
+    }
+}
+
 // Main runtime loop
 async function runtimeLoop() {
     console.log('Starting runtime loop...');
+    process.on('SIGTERM', () => shutdown('SIGTERM'));
+    process.on('SIGINT', () => shutdown('SIGINT'));
     
     // Build query parameters for runtime API (simplified to just function name)
     const queryParams = new URLSearchParams({
@@ -122,6 +205,11 @@ async function runtimeLoop() {
     });
     
     while (true) {
+        busy = false;
+        if (shuttingDown) {
+            await unregisterAndExit();
+            return;
+        }
         try {
             // Long-lived GET: this call blocks until work is available
             const url = `/2018-06-01/runtime/invocation/next?${queryParams.toString()}`;
@@ -137,6 +225,7 @@ async function runtimeLoop() {
                 continue;
             }
 
+            busy = true;
             // Parse AWS-style response: headers carry metadata, body is event JSON
             const awsRequestId = response.headers['lambda-runtime-aws-request-id'];
             const deadlineHeader = response.headers['lambda-runtime-deadline-ms'];
@@ -151,6 +240,12 @@ async function runtimeLoop() {
             
             console.log('Got invocation:', awsRequestId);
 
+            if (userHandler[STREAMING] === 'response') {
+                await streamResponse(awsRequestId, payload);
+                console.log('Streamed response for:', awsRequestId);
+                continue;
+            }
+
             // Execute the user function
             let result;
             try {
@@ -51,6 +51,15 @@ console.log('Function:', FUNCTION_NAME);
 console.log('Handler:', HANDLER);
 console.log('Task Root:', TASK_ROOT);
 
+// awslambda.streamifyResponse marks handlers that stream their response, as on AWS
+const STREAMING = Symbol.for('aws.lambda.runtime.handler.streaming');
+globalThis.awslambda = {
+  streamifyResponse(handler) {
+    handler[STREAMING] = 'response';
+    return handler;
+  },
+};
+
 // Load the user function
 let userHandler;
 try {
@@ -91,9 +100,89 @@ function makeRequest(method, path, data = null, extraHeaders = {}) {
   });
 }
 
+// Run a streaming handler, posting what it writes to its response stream as it goes. An
+// error after the stream started is reported in trailers, the way AWS runtimes do
+function streamResponse(awsRequestId, event) {
+  return new Promise((resolve, reject) => {
+    const req = http.request({
+      hostname: RUNTIME_API.hostname,
+      port: RUNTIME_API.port,
+      path: `/2018-06-01/runtime/invocation/${awsRequestId}/response`,
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/octet-stream',
+        'Lambda-Runtime-Function-Response-Mode': 'streaming',
+        'Transfer-Encoding': 'chunked',
+        Trailer: 'Lambda-Runtime-Function-Error-Type, Lambda-Runtime-Function-Error-Body',
+        ...(INSTANCE_ID ? { 'X-LambdaH-Instance-Id': INSTANCE_ID } : {}),
+      },
+    }, (res) => {
+      res.resume();
+      res.on('end', resolve);
+    });
+    req.on('error', reject);
+    // Only before the first write, as on AWS
+    req.setContentType = (type) => req.setHeader('Content-Type', type);
+    Promise.resolve()
+      .then(() => userHandler(event, req, {}))
+      .catch((error) => {
+        console.error('Function error:', error);
+        // Too late to report once the handler ended the stream
+        if (req.writableEnded) return;
+        const body = JSON.stringify({
+          errorMessage: error.message,
+          errorType: error.name || 'Error',
+          stackTrace: String(error.stack || '').split('\n'),
+        });
+        req.addTrailers({
+          'Lambda-Runtime-Function-Error-Type': error.name || 'Error',
+          'Lambda-Runtime-Function-Error-Body': Buffer.from(body).toString('base64'),
+        });
+      })
+      .finally(() => {
+        if (!req.writableEnded) req.end();
+      });
+  });
+}
+
+// SIGTERM handling: finish the current invocation, unregister, then exit
+let shuttingDown = false;
+let busy = false;
+
+async function unregisterAndExit() {
+  try {
+    await makeRequest('POST', '/runtime/unregister', null, INSTANCE_ID ? { 'X-LambdaH-Instance-Id': INSTANCE_ID } : {});
+  } catch (err) {
+    console.error('Failed to unregister:', err);
+  }
+  // An invocation that arrived meanwhile finishes first; the loop exits after it
+  if (!busy) {
+    process.exit(0);
+  }
+}
+
+function shutdown(signal) {
+  if (shuttingDown) {
+    return;
+  }
+  shuttingDown = true;
+  console.log(`Received ${signal}, exiting after the current invocation`);
+  if (!busy) {
+    unregisterAndExit();
+  }
+}
+
+process.on('SIGTERM', () => shutdown('SIGTERM'));
+process.on('SIGINT', () => shutdown('SIGINT'));
+
 async function runtimeLoop() {
   const query = new URLSearchParams({ fn: FUNCTION_NAME });
   while (true) {
+    busy = false;
+    if (shuttingDown) {
+      await unregisterAndExit();
+      return;
+    }
     try {
       const url = `/2018-06-01/runtime/invocation/next?${query.toString()}`;
       console.log('Waiting for next invocation at', url);
@@ -104,12 +193,19 @@ async function runtimeLoop() {
         await new Promise(r => setTimeout(r, 1000));
         continue;
       }
+      busy = true;
       const awsRequestId = response.headers['lambda-runtime-aws-request-id'];
       const deadlineMs = response.headers['lambda-runtime-deadline-ms'];
       let event;
       try { event = response.body ? JSON.parse(response.body) : undefined; } catch (e) { event = undefined; }
       console.log('Got invocation:', awsRequestId);
 
+      if (userHandler[STREAMING] === 'response') {
+        await streamResponse(awsRequestId, event);
+        console.log('Streamed response for:', awsRequestId);
+        continue;
+      }
+
       let result;
       try {
         result = await userHandler(event);
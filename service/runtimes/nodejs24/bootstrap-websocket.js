@@ -1,6 +1,7 @@
 #!/usr/bin/env node
 
 const WebSocket = require('ws');
+const http = require('http');
 const fs = require('fs');
 const path = require('path');
 
@@ -29,6 +30,15 @@ console.log('Function:', FUNCTION_NAME);
 console.log('Handler:', HANDLER);
 console.log('Task Root:', TASK_ROOT);
 
+// awslambda.streamifyResponse marks handlers that stream their response, as on AWS
+const STREAMING = Symbol.for('aws.lambda.runtime.handler.streaming');
+globalThis.awslambda = {
+  streamifyResponse(handler) {
+    handler[STREAMING] = 'response';
+    return handler;
+  },
+};
+
 // Load the user function
 let userHandler;
 try {
@@ -45,12 +55,62 @@ try {
   process.exit(1);
 }
 
+// Run a streaming handler, posting what it writes to its response stream to the runtime
+// API over HTTP as it goes. An error after the stream started is reported in trailers, the
+// way AWS runtimes do. The WebSocket connection tracks the instance, so the post names none
+function streamResponse(awsRequestId, event) {
+  return new Promise((resolve, reject) => {
+    const req = http.request({
+      hostname: RUNTIME_API.hostname,
+      port: RUNTIME_API.port,
+      path: `/2018-06-01/runtime/invocation/${awsRequestId}/response`,
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/octet-stream',
+        'Lambda-Runtime-Function-Response-Mode': 'streaming',
+        'Transfer-Encoding': 'chunked',
+        Trailer: 'Lambda-Runtime-Function-Error-Type, Lambda-Runtime-Function-Error-Body',
+      },
+    }, (res) => {
+      res.resume();
+      res.on('end', resolve);
+    });
+    req.on('error', reject);
+    // Only before the first write, as on AWS
+    req.setContentType = (type) => req.setHeader('Content-Type', type);
+    Promise.resolve()
+      .then(() => userHandler(event, req, {}))
+      .catch((error) => {
+        console.error('Function error:', error);
+        // Too late to report once the handler ended the stream
+        if (req.writableEnded) return;
+        const body = JSON.stringify({
+          errorMessage: error.message,
+          errorType: error.name || 'Error',
+          stackTrace: String(error.stack || '').split('\n'),
+        });
+        req.addTrailers({
+          'Lambda-Runtime-Function-Error-Type': error.name || 'Error',
+          'Lambda-Runtime-Function-Error-Body': Buffer.from(body).toString('base64'),
+        });
+      })
+      .finally(() => {
+        if (!req.writableEnded) req.end();
+      });
+  });
+}
+
 let ws = null;
 let reconnectTimeout = null;
 const RECONNECT_DELAY = 1000;
 const MAX_RECONNECT_DELAY = 30000;
 let currentReconnectDelay = RECONNECT_DELAY;
 
+// SIGTERM handling: stop taking work, finish what is running, then exit
+let inFlight = 0;
+let shuttingDown = false;
+let unregistered = false;
+
 function connectWebSocket() {
   return new Promise((resolve, reject) => {
     const wsUrl = `ws://${RUNTIME_API.hostname}:${RUNTIME_API.port}/2018-06-01/runtime/websocket?fn=${encodeURIComponent(FUNCTION_NAME)}&ver=${encodeURIComponent(FUNCTION_VERSION)}`;
@@ -74,6 +134,20 @@ function connectWebSocket() {
         if (message.type === 'invocation') {
           const { request_id, payload, deadline_ms } = message;
           console.log('Got invocation:', request_id);
+          inFlight++;
+
+          if (userHandler[STREAMING] === 'response') {
+            try {
+              await streamResponse(request_id, payload);
+              console.log('Streamed response for:', request_id);
+            } catch (error) {
+              console.error('Failed to stream response:', error);
+            } finally {
+              inFlight--;
+              exitWhenDrained();
+            }
+            return;
+          }
 
           let result;
           try {
@@ -103,7 +177,14 @@ function connectWebSocket() {
               }
             };
             ws.send(JSON.stringify(errorResponse));
+          } finally {
+            inFlight--;
+            exitWhenDrained();
           }
+        } else if (message.type === 'unregistered') {
+          // No invocations follow this message
+          unregistered = true;
+          exitWhenDrained();
         } else if (message.type === 'ping') {
           // Respond to ping with pong
           ws.send(JSON.stringify({ type: 'pong' }));
@@ -116,6 +197,9 @@ function connectWebSocket() {
     ws.on('close', (code, reason) => {
       console.log(`WebSocket closed: ${code} ${reason}`);
       ws = null;
+      if (shuttingDown) {
+        process.exit(0);
+      }
       scheduleReconnect();
     });
 
@@ -150,25 +234,36 @@ connectWebSocket().catch((error) => {
   process.exit(1);
 });
 
-// Graceful shutdown
-process.on('SIGTERM', () => {
-  console.log('Received SIGTERM, closing WebSocket...');
+// Graceful shutdown: unregister so the runtime API stops sending work, let in-flight
+// invocations finish and post their results, then exit within the stop grace period
+function exitWhenDrained() {
+  if (!shuttingDown || inFlight > 0 || (ws && !unregistered)) {
+    return;
+  }
   if (ws) {
+    // Closing flushes queued responses; the close handler exits
     ws.close();
+  } else {
+    process.exit(0);
   }
-  if (reconnectTimeout) {
-    clearTimeout(reconnectTimeout);
-  }
-  process.exit(0);
-});
+}
 
-process.on('SIGINT', () => {
-  console.log('Received SIGINT, closing WebSocket...');
-  if (ws) {
-    ws.close();
+function shutdown(signal) {
+  if (shuttingDown) {
+    return;
   }
+  shuttingDown = true;
+  console.log(`Received ${signal}, finishing ${inFlight} in-flight invocation(s) before exit`);
   if (reconnectTimeout) {
     clearTimeout(reconnectTimeout);
   }
-  process.exit(0);
-});
+  if (ws && ws.readyState === WebSocket.OPEN) {
+    ws.send(JSON.stringify({ type: 'unregister', instance_id: INSTANCE_ID || null }));
+  } else {
+    unregistered = true;
+  }
+  exitWhenDrained();
+}
+
+process.on('SIGTERM', () => shutdown('SIGTERM'));
+process.on('SIGINT', () => shutdown('SIGINT'));
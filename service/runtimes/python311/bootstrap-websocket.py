@@ -1,5 +1,6 @@
 #!/usr/bin/env python3
 
+import importlib
 import json
 import os
 import sys
@@ -18,12 +19,13 @@ LOG_GROUP_NAME = os.environ.get('AWS_LAMBDA_LOG_GROUP_NAME')
 LOG_STREAM_NAME = os.environ.get('AWS_LAMBDA_LOG_STREAM_NAME')
 INSTANCE_ID = os.environ.get('LAMBDAH_INSTANCE_ID')
 
-# Load the user's handler
+# Load the user's handler: `package.module.function`, as configured on the function
+HANDLER = os.environ.get('AWS_LAMBDA_FUNCTION_HANDLER', 'lambda_function.handler')
 try:
-    import lambda_function
-    handler = lambda_function.handler
+    module_name, _, function_name = HANDLER.rpartition('.')
+    handler = getattr(importlib.import_module(module_name), function_name)
     if not callable(handler):
-        raise Exception('Handler function not found in lambda_function.py')
+        raise Exception(f'{function_name} in {module_name} is not callable')
 except Exception as e:
     print(f'Failed to load handler: {e}', file=sys.stderr)
     sys.exit(1)
@@ -36,10 +38,13 @@ class WebSocketRuntime:
         self.reconnect_delay = 1.0
         self.is_connected = False
         self.should_stop = False
+        self.main_task: Optional[asyncio.Task] = None
 
     async def connect(self):
         """Connect to the WebSocket runtime API"""
         ws_url = f'ws://{RUNTIME_API}/2018-06-01/runtime/websocket?fn={FUNCTION_NAME}'
+        if INSTANCE_ID:
+            ws_url += f'&iid={INSTANCE_ID}'
         print(f'Connecting to WebSocket: {ws_url}')
 
         try:
@@ -91,6 +96,10 @@ class WebSocketRuntime:
         
         if message_type == 'invocation':
             await self.handle_invocation(message)
+        elif message_type == 'unregistered':
+            # No invocations follow this message; closing ends the message loop
+            print('Unregistered, closing WebSocket')
+            await self.websocket.close()
         elif message_type == 'ping':
             await self.send({'type': 'pong'})
         elif message_type == 'error_response':
@@ -163,6 +172,8 @@ class WebSocketRuntime:
 
     async def handle_reconnect(self):
         """Handle reconnection logic"""
+        if self.should_stop:
+            return
         if self.reconnect_attempts < self.max_reconnect_attempts and not self.should_stop:
             self.reconnect_attempts += 1
             print(f'Attempting to reconnect ({self.reconnect_attempts}/{self.max_reconnect_attempts}) in {self.reconnect_delay}s')
@@ -183,6 +194,18 @@ class WebSocketRuntime:
         import bootstrap
         # The HTTP runtime will start automatically when imported
 
+    def begin_shutdown(self, signame: str):
+        """Unregister so the runtime API stops sending work. Invocations run one at a
+        time, so the current one posts its result before the acknowledgement is handled."""
+        if self.should_stop:
+            return
+        self.should_stop = True
+        print(f'Received {signame}, exiting after the current invocation')
+        if self.websocket and self.is_connected:
+            asyncio.ensure_future(self.send({'type': 'unregister', 'instance_id': INSTANCE_ID}))
+        elif self.main_task:
+            self.main_task.cancel()
+
     async def shutdown(self):
         """Graceful shutdown"""
         self.should_stop = True
@@ -195,17 +218,17 @@ async def main():
     print('Lambda runtime started with WebSocket support')
     
     runtime = WebSocketRuntime()
+    runtime.main_task = asyncio.current_task()
     
     # Set up signal handlers for graceful shutdown
-    def signal_handler(signum, frame):
-        print(f'Received signal {signum}, shutting down...')
-        asyncio.create_task(runtime.shutdown())
-    
-    signal.signal(signal.SIGTERM, signal_handler)
-    signal.signal(signal.SIGINT, signal_handler)
+    loop = asyncio.get_running_loop()
+    loop.add_signal_handler(signal.SIGTERM, runtime.begin_shutdown, 'SIGTERM')
+    loop.add_signal_handler(signal.SIGINT, runtime.begin_shutdown, 'SIGINT')
     
     try:
         await runtime.connect()
+    except asyncio.CancelledError:
+        print('Shut down while disconnected')
     except KeyboardInterrupt:
         print('Received keyboard interrupt, shutting down...')
         await runtime.shutdown()
@@ -1,7 +1,9 @@
 #!/usr/bin/env python3
 
+import importlib
 import json
 import os
+import signal
 import sys
 import urllib.request
 import urllib.error
@@ -26,12 +28,13 @@ LOG_GROUP_NAME = os.environ.get('AWS_LAMBDA_LOG_GROUP_NAME')
 LOG_STREAM_NAME = os.environ.get('AWS_LAMBDA_LOG_STREAM_NAME')
 INSTANCE_ID = os.environ.get('LAMBDAH_INSTANCE_ID')
 
-# Load the user's handler
+# Load the user's handler: `package.module.function`, as configured on the function
+HANDLER = os.environ.get('AWS_LAMBDA_FUNCTION_HANDLER', 'lambda_function.handler')
 try:
-    import lambda_function
-    handler = lambda_function.handler
+    module_name, _, function_name = HANDLER.rpartition('.')
+    handler = getattr(importlib.import_module(module_name), function_name)
     if not callable(handler):
-        raise Exception('Handler function not found in lambda_function.py')
+        raise Exception(f'{function_name} in {module_name} is not callable')
 except Exception as e:
     print(f'Failed to load handler: {e}', file=sys.stderr)
     sys.exit(1)
@@ -98,12 +101,44 @@ def post_error(request_id, error):
     except Exception as e:
         print(f'Failed to post error: {e}', file=sys.stderr)
 
+# SIGTERM handling: finish the current invocation, unregister, then exit
+shutting_down = False
+busy = False
+
+def unregister_and_exit():
+    """Tell the runtime API this instance takes no more work, then exit"""
+    url = f'http://{RUNTIME_API}/runtime/unregister'
+    try:
+        req = urllib.request.Request(url, data=b'', method='POST')
+        if INSTANCE_ID:
+            req.add_header('X-LambdaH-Instance-Id', INSTANCE_ID)
+        with urllib.request.urlopen(req) as response:
+            pass
+    except Exception as e:
+        print(f'Failed to unregister: {e}', file=sys.stderr)
+    sys.exit(0)
+
+def handle_shutdown_signal(signum, frame):
+    global shutting_down
+    shutting_down = True
+    print(f'Received signal {signum}, exiting after the current invocation')
+    if not busy:
+        # Interrupts the long poll for the next invocation
+        unregister_and_exit()
+
 def main():
+    global busy
     print('Lambda runtime started')
+    signal.signal(signal.SIGTERM, handle_shutdown_signal)
+    signal.signal(signal.SIGINT, handle_shutdown_signal)
     
     while True:
+        busy = False
+        if shutting_down:
+            unregister_and_exit()
         try:
             invocation = get_next_invocation()
+            busy = True
             print(f'Received invocation: {invocation["requestId"]}')
             
             try:
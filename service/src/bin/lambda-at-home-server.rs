@@ -1,9 +1,11 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand};
+use lambda_control::migrations;
+use lambda_control::upgrade::{self, ReleaseFeed};
 use lambda_control::ControlPlane;
 use lambda_control::IdleWatchdog;
 use lambda_invoker::Invoker;
-use lambda_metrics::MetricsService;
+use lambda_metrics::{LogLevelController, MetricsService, DEFAULT_LOG_FILTER};
 use lambda_models::Config;
 use sqlx::SqlitePool;
 use std::fs;
@@ -40,6 +42,27 @@ struct Args {
     /// Maximum request body size in MB
     #[arg(long, default_value = "50")]
     max_body_size_mb: u64,
+
+    /// Print the database's schema version and the migrations starting would run, then
+    /// exit without changing anything
+    #[arg(long)]
+    migrate_dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Install the latest release from the release feed and migrate the database with it,
+    /// rolling back when the migration fails
+    Upgrade {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
+    /// Back up the database and run the migrations, restoring the backup when they fail
+    Migrate,
 }
 
 fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
@@ -63,10 +86,104 @@ fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
     Err("No config file found".into())
 }
 
+/// Replace this binary with the latest release, then have the new binary migrate the
+/// database. A failed migration restores the database and puts this binary back.
+async fn upgrade(config: &Config, db_url_arg: &str, check: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let feed = ReleaseFeed::new(&config.upgrade)?;
+    let release = feed.latest_release().await?;
+    if !upgrade::is_newer(current, &release.tag_name) {
+        println!(
+            "Lambda@Home {current} is up to date (latest release {})",
+            release.tag_name
+        );
+        return Ok(());
+    }
+    if check {
+        println!(
+            "Lambda@Home {} is available (running {current})",
+            release.tag_name
+        );
+        return Ok(());
+    }
+
+    let platform = upgrade::host_platform().ok_or_else(|| {
+        anyhow!(
+            "no release binaries are published for {}-{}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    })?;
+    let bytes = feed.download(&release, &platform).await?;
+    let exe = std::env::current_exe()?;
+    let previous = upgrade::install_binary(&exe, &bytes)?;
+    info!("Installed {} at {}", release.tag_name, exe.display());
+
+    let migrated = std::process::Command::new(&exe)
+        .args(["--db-url", db_url_arg, "migrate"])
+        .status();
+    if !migrated.as_ref().is_ok_and(|status| status.success()) {
+        upgrade::restore_binary(&exe)?;
+        bail!(
+            "{} could not migrate the database ({:?}); restored {current}",
+            release.tag_name,
+            migrated
+        );
+    }
+    println!(
+        "Upgraded Lambda@Home {current} to {}; restart the server to run it. The previous binary is kept at {}",
+        release.tag_name,
+        previous.display()
+    );
+    Ok(())
+}
+
+/// Report what starting against `db_url` would migrate, leaving the database untouched
+async fn migrate_dry_run(db_url: &str) -> Result<()> {
+    if let Some(path) = upgrade::sqlite_path(db_url).filter(|path| !path.exists()) {
+        println!(
+            "{} does not exist; starting would create it at schema version {}",
+            path.display(),
+            migrations::SCHEMA_VERSION
+        );
+        return Ok(());
+    }
+    let pool = SqlitePool::connect(db_url).await?;
+    let status = migrations::schema_status(&pool).await?;
+    pool.close().await;
+    if status.current_version > status.latest_version {
+        bail!(
+            "the database schema is at version {}, newer than version {} this binary understands; starting would be refused",
+            status.current_version,
+            status.latest_version
+        );
+    }
+    if status.pending.is_empty() {
+        println!(
+            "The database is at schema version {}; nothing to migrate",
+            status.current_version
+        );
+        return Ok(());
+    }
+    println!(
+        "The database is at schema version {}; starting would migrate it to {}:",
+        status.current_version, status.latest_version
+    );
+    for migration in &status.pending {
+        println!("  {:03} {}", migration.version, migration.name);
+    }
+    if status.current_version == 0 {
+        println!(
+            "A database from before schema versions were recorded runs every migration; each skips what is already in place"
+        );
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt().init();
+    // Initialize tracing with a reloadable filter so log levels can be changed at runtime
+    LogLevelController::init(DEFAULT_LOG_FILTER)?;
 
     // Change to the root directory if we're running from service/
     if std::env::current_dir()?.ends_with("service") {
@@ -74,7 +191,7 @@ async fn main() -> Result<()> {
     }
 
     // Parse command-line arguments
-    let args = Args::parse();
+    let mut args = Args::parse();
     info!("Starting Lambda@Home server with args: {:?}", args);
 
     // Load configuration from file or use defaults
@@ -88,7 +205,7 @@ async fn main() -> Result<()> {
     config.server.port_user_api = args.api_port;
     config.server.port_runtime_api = args.ric_port;
     config.server.max_request_body_size_mb = args.max_body_size_mb;
-    config.data.db_url = args.db_url;
+    config.data.db_url = args.db_url.clone();
 
     info!("Configuration loaded: {:?}", config);
 
@@ -112,6 +229,10 @@ async fn main() -> Result<()> {
         config.data.db_url.clone()
     };
 
+    if args.migrate_dry_run {
+        return migrate_dry_run(&db_url).await;
+    }
+
     // Handle both sqlite:// and sqlite: formats
     let db_path = if let Some(path) = db_url.strip_prefix("sqlite://") {
         Some(path)
@@ -135,6 +256,18 @@ async fn main() -> Result<()> {
         }
     }
 
+    match args.command.take() {
+        Some(Command::Upgrade { check }) => return upgrade(&config, &args.db_url, check).await,
+        Some(Command::Migrate) => {
+            match upgrade::migrate_with_backup(&db_url).await? {
+                Some(backup) => info!("Migrated {} (backup at {})", db_url, backup.display()),
+                None => info!("Migrated {}", db_url),
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+
     // Initialize database pool
     let pool = SqlitePool::connect(&db_url).await?;
     info!("Database connected");
@@ -244,8 +377,17 @@ async fn main() -> Result<()> {
     runtime_api_handle.abort();
     watchdog_handle.abort();
 
-    // Best-effort: remove any remaining containers
-    {
+    if config.warm_pool.persist {
+        // Leave containers running for the next start to adopt
+        match control_plane.save_warm_pool_snapshot().await {
+            Ok(count) => info!(
+                "Left {} warm container(s) running for the next start",
+                count
+            ),
+            Err(e) => warn!("Failed to save warm pool snapshot: {}", e),
+        }
+    } else {
+        // Best-effort: remove any remaining containers
         let inv = control_plane.invoker();
         let pool = control_plane.warm_pool();
         let ids = pool.drain_all().await;